@@ -0,0 +1,286 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::{Arc, OnceLock};
+
+use async_trait::async_trait;
+
+use http::StatusCode;
+
+use common::{
+    context::RequestContext,
+    env_const::{get_admin_http_path, get_admin_role},
+    http::{Headers, RequestHead, RequestPayload, ResponseBody, ResponsePayload},
+    log_filter,
+    maintenance::MaintenanceMode,
+    router::Router,
+    value::Val,
+};
+use core_resolver::system_rest_resolver::SystemRestResolver;
+use exo_env::Environment;
+use serde_json::{Value, json};
+
+/// Routes requests to the admin API (schema metadata and raw table browsing/editing).
+///
+/// Every request must carry a JWT whose `role` claim matches the configured admin role.
+pub struct AdminRouter {
+    system_resolver: SystemRestResolver,
+    api_path_prefix: String,
+    admin_role: String,
+    maintenance_mode: Arc<MaintenanceMode>,
+    /// Set once the system has loaded (see `system_router::startup_report`)
+    startup_report: Arc<OnceLock<Value>>,
+    env: Arc<dyn Environment>,
+}
+
+impl AdminRouter {
+    pub fn new(
+        system_resolver: SystemRestResolver,
+        env: Arc<dyn Environment>,
+        maintenance_mode: Arc<MaintenanceMode>,
+        startup_report: Arc<OnceLock<Value>>,
+    ) -> Self {
+        // Set the prefix to "/admin/api" + "/" to avoid matching with routes such as "/admin/apis" etc.
+        let api_path_prefix = format!("{}/", get_admin_http_path(env.as_ref()));
+        Self {
+            system_resolver,
+            api_path_prefix,
+            admin_role: get_admin_role(env.as_ref()),
+            maintenance_mode,
+            startup_report,
+            env,
+        }
+    }
+
+    fn suitable(&self, request_head: &(dyn RequestHead + Sync)) -> bool {
+        request_head.get_path().starts_with(&self.api_path_prefix)
+    }
+
+    /// Get (`GET`) or set (`PUT` with `{"enabled": true}`) the maintenance mode
+    fn route_maintenance(&self, request_context: &RequestContext<'_>) -> ResponsePayload {
+        let method = request_context.get_head().get_method();
+
+        if method == http::Method::PUT {
+            match request_context.take_body().get("enabled") {
+                Some(Value::Bool(enabled)) => {
+                    tracing::info!(
+                        "Maintenance mode {} through the admin API",
+                        if *enabled { "enabled" } else { "disabled" }
+                    );
+                    self.maintenance_mode.set_enabled(*enabled)
+                }
+                _ => {
+                    return ResponsePayload {
+                        body: ResponseBody::None,
+                        headers: Headers::new(),
+                        status_code: StatusCode::BAD_REQUEST,
+                    };
+                }
+            }
+        } else if method != http::Method::GET {
+            return ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::METHOD_NOT_ALLOWED,
+            };
+        }
+
+        let body = json!({
+            "enabled": self.maintenance_mode.is_enabled(),
+            "retryAfter": self.maintenance_mode.retry_after_secs(),
+        });
+
+        json_response(body, StatusCode::OK)
+    }
+
+    /// Get (`GET`) or set (`PUT` with `{"filter": "info,exo_sql=debug"}`) the log filter
+    fn route_log_filter(&self, request_context: &RequestContext<'_>) -> ResponsePayload {
+        let method = request_context.get_head().get_method();
+
+        if method == http::Method::PUT {
+            let result = match request_context.take_body().get("filter") {
+                Some(Value::String(filter)) => log_filter::set(filter),
+                _ => {
+                    return ResponsePayload {
+                        body: ResponseBody::None,
+                        headers: Headers::new(),
+                        status_code: StatusCode::BAD_REQUEST,
+                    };
+                }
+            };
+
+            match result {
+                Ok(()) => tracing::info!("Log filter changed through the admin API"),
+                Err(e) => {
+                    let status_code = match e {
+                        log_filter::LogFilterError::NotInstalled => StatusCode::NOT_IMPLEMENTED,
+                        log_filter::LogFilterError::Invalid(..) => StatusCode::BAD_REQUEST,
+                    };
+                    return json_response(json!({ "error": e.to_string() }), status_code);
+                }
+            }
+        } else if method != http::Method::GET {
+            return ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::METHOD_NOT_ALLOWED,
+            };
+        }
+
+        json_response(json!({ "filter": log_filter::current() }), StatusCode::OK)
+    }
+
+    /// Get (`GET`) the report produced when the system was loaded
+    fn route_startup_report(&self, request_context: &RequestContext<'_>) -> ResponsePayload {
+        if request_context.get_head().get_method() != http::Method::GET {
+            return ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::METHOD_NOT_ALLOWED,
+            };
+        }
+
+        match self.startup_report.get() {
+            Some(report) => json_response(report.clone(), StatusCode::OK),
+            None => ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::SERVICE_UNAVAILABLE,
+            },
+        }
+    }
+
+    /// Reload (`POST`) the JWT configuration from the environment (re-reading the env files), for
+    /// example, after rotating a secret. Responds with the resulting validation setup.
+    async fn route_jwt_reload(&self, request_context: &RequestContext<'_>) -> ResponsePayload {
+        if request_context.get_head().get_method() != http::Method::POST {
+            return ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::METHOD_NOT_ALLOWED,
+            };
+        }
+
+        let Some(authenticator) = request_context.jwt_authenticator.as_ref() else {
+            return ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::NOT_FOUND,
+            };
+        };
+
+        self.env.reload();
+
+        match authenticator.reload_from_env(self.env.as_ref()).await {
+            Ok(()) => {
+                tracing::info!("JWT configuration reloaded through the admin API");
+                json_response(authenticator.status(), StatusCode::OK)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to reload the JWT configuration: {}", e);
+                json_response(json!({ "error": e.to_string() }), StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+
+    /// Check that the request is made by an admin, returning the status code to respond with
+    /// otherwise.
+    async fn authorize(&self, request_context: &RequestContext<'_>) -> Result<(), StatusCode> {
+        let role = request_context
+            .extract_context_field("AdminContext", "jwt", &Some("role"), "role", &Ok)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Failed to extract the role for an admin request: {}", e);
+                StatusCode::UNAUTHORIZED
+            })?;
+
+        match role {
+            Some(Val::String(role)) if role == &self.admin_role => Ok(()),
+            Some(_) => Err(StatusCode::FORBIDDEN),
+            None => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> Router<RequestContext<'a>> for AdminRouter {
+    async fn route(&self, request_context: &RequestContext<'a>) -> Option<ResponsePayload> {
+        if !self.suitable(request_context.get_head()) {
+            return None;
+        }
+
+        if let Err(status_code) = self.authorize(request_context).await {
+            return Some(ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code,
+            });
+        }
+
+        if request_context.get_head().get_path() == format!("{}maintenance", self.api_path_prefix) {
+            return Some(self.route_maintenance(request_context));
+        }
+
+        if request_context.get_head().get_path() == format!("{}log-filter", self.api_path_prefix) {
+            return Some(self.route_log_filter(request_context));
+        }
+
+        if request_context.get_head().get_path()
+            == format!("{}startup-report", self.api_path_prefix)
+        {
+            return Some(self.route_startup_report(request_context));
+        }
+
+        if request_context.get_head().get_path() == format!("{}jwt/reload", self.api_path_prefix) {
+            return Some(self.route_jwt_reload(request_context).await);
+        }
+
+        let response = self.system_resolver.resolve(request_context).await;
+
+        // Roll back unless the request succeeded (for example, an import with invalid rows)
+        let commit = matches!(&response, Ok(Some(response)) if response.status_code.is_success());
+
+        if let Err(e) = request_context.finalize_transaction(commit).await {
+            tracing::error!("Error finalizing the admin transaction: {}", e);
+            return Some(ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            });
+        }
+
+        match response {
+            Ok(Some(response)) => Some(response),
+            Err(e) => {
+                tracing::error!("Error resolving admin request: {}", e);
+                Some(ResponsePayload {
+                    body: ResponseBody::None,
+                    headers: Headers::new(),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                })
+            }
+            Ok(None) => Some(ResponsePayload {
+                body: ResponseBody::None,
+                headers: Headers::new(),
+                status_code: StatusCode::NOT_FOUND,
+            }),
+        }
+    }
+}
+
+fn json_response(body: Value, status_code: StatusCode) -> ResponsePayload {
+    ResponsePayload {
+        body: ResponseBody::Bytes(body.to_string().into_bytes()),
+        headers: Headers::from_vec(vec![(
+            "content-type".to_string(),
+            "application/json".to_string(),
+        )]),
+        status_code,
+    }
+}
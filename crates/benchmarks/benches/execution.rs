@@ -0,0 +1,65 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! End-to-end execution of GraphQL operations (validation, access control, SQL building, and
+//! execution) against an ephemeral database.
+//!
+//! The database is launched as for `exo test` (see `EXO_SQL_EPHEMERAL_DATABASE_LAUNCH_PREFERENCE`).
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use serde_json::json;
+
+use benchmarks::setup::{
+    BenchDatabase, CREATE_MUTATION, NESTED_QUERY, SIMPLE_QUERY, build_system,
+    create_mutation_variables, create_router, execute_operation, nested_query_variables,
+    postgres_database, seed,
+};
+
+fn execution(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let (_database, router) = runtime
+        .block_on(async {
+            let system = build_system().await?;
+            let database = BenchDatabase::launch(&postgres_database(&system)?).await?;
+            let router = create_router(system, &database.url()).await?;
+            seed(&router, 100, 10, 10).await?;
+            Ok::<_, anyhow::Error>((database, router))
+        })
+        .unwrap();
+
+    let mut group = c.benchmark_group("execution");
+
+    for (name, query, variables) in [
+        ("simple_query", SIMPLE_QUERY, json!({})),
+        ("nested_query", NESTED_QUERY, nested_query_variables()),
+        (
+            "create_mutation",
+            CREATE_MUTATION,
+            create_mutation_variables(),
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                black_box(
+                    execute_operation(&router, query, variables.clone())
+                        .await
+                        .unwrap(),
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, execution);
+criterion_main!(benches);
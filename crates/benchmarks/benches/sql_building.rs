@@ -0,0 +1,259 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Transforming abstract operations into SQL (without executing it).
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use benchmarks::setup::{build_system, postgres_database};
+use exo_sql::{
+    AbstractInsert, AbstractOperation, AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate,
+    AbstractSelect, AliasedSelectionElement, Column, ColumnId, ColumnPath, ColumnValuePair,
+    Database, DatabaseExecutor, InsertionElement, InsertionRow, Limit, NestedInsertion, Ordering,
+    PhysicalColumnPath, RelationId, SQLParamContainer, SchemaObjectName, Selection,
+    SelectionCardinality, SelectionElement, TableId, get_otm_relation_for_columns,
+};
+
+struct Tables {
+    venues: TableId,
+    concerts: TableId,
+}
+
+impl Tables {
+    fn new(database: &Database) -> Self {
+        let table_id = |name: &str| {
+            database
+                .get_table_id(&SchemaObjectName::new(name, None))
+                .unwrap()
+        };
+
+        Self {
+            venues: table_id("venues"),
+            concerts: table_id("concerts"),
+        }
+    }
+}
+
+fn column(database: &Database, table_id: TableId, name: &str) -> ColumnId {
+    database.get_column_id(table_id, name).unwrap()
+}
+
+fn select(table_id: TableId, elements: Vec<(&str, SelectionElement)>) -> AbstractSelect {
+    AbstractSelect {
+        table_id,
+        selection: Selection::Json(
+            elements
+                .into_iter()
+                .map(|(alias, element)| AliasedSelectionElement::new(alias.to_string(), element))
+                .collect(),
+            SelectionCardinality::Many,
+        ),
+        predicate: AbstractPredicate::True,
+        order_by: None,
+        offset: None,
+        limit: None,
+        lock: None,
+    }
+}
+
+/// The equivalent of `venues { id name }`
+fn simple_select(database: &Database, tables: &Tables) -> AbstractOperation {
+    AbstractOperation::Select(select(
+        tables.venues,
+        vec![
+            (
+                "id",
+                SelectionElement::Physical(column(database, tables.venues, "id")),
+            ),
+            (
+                "name",
+                SelectionElement::Physical(column(database, tables.venues, "name")),
+            ),
+        ],
+    ))
+}
+
+/// The equivalent of the `NESTED_QUERY` operation
+fn nested_select(database: &Database, tables: &Tables) -> AbstractOperation {
+    let concerts_venue_id = column(database, tables.concerts, "venue_id");
+    let concerts_relation =
+        get_otm_relation_for_columns(&[concerts_venue_id], database, None).unwrap();
+
+    let concerts = AbstractSelect {
+        predicate: AbstractPredicate::Eq(
+            ColumnPath::Physical(PhysicalColumnPath::leaf(column(
+                database,
+                tables.concerts,
+                "published",
+            ))),
+            ColumnPath::Param(SQLParamContainer::bool(true)),
+        ),
+        order_by: Some(AbstractOrderBy(vec![(
+            AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(column(
+                database,
+                tables.concerts,
+                "price",
+            ))),
+            Ordering::Desc,
+        )])),
+        limit: Some(Limit(5)),
+        ..select(
+            tables.concerts,
+            vec![
+                (
+                    "id",
+                    SelectionElement::Physical(column(database, tables.concerts, "id")),
+                ),
+                (
+                    "title",
+                    SelectionElement::Physical(column(database, tables.concerts, "title")),
+                ),
+                (
+                    "price",
+                    SelectionElement::Physical(column(database, tables.concerts, "price")),
+                ),
+            ],
+        )
+    };
+
+    AbstractOperation::Select(AbstractSelect {
+        predicate: AbstractPredicate::Eq(
+            ColumnPath::Physical(PhysicalColumnPath::leaf(column(
+                database,
+                tables.venues,
+                "city",
+            ))),
+            ColumnPath::Param(SQLParamContainer::string("City 0".to_string())),
+        ),
+        order_by: Some(AbstractOrderBy(vec![(
+            AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(column(
+                database,
+                tables.venues,
+                "name",
+            ))),
+            Ordering::Asc,
+        )])),
+        ..select(
+            tables.venues,
+            vec![
+                (
+                    "id",
+                    SelectionElement::Physical(column(database, tables.venues, "id")),
+                ),
+                (
+                    "name",
+                    SelectionElement::Physical(column(database, tables.venues, "name")),
+                ),
+                (
+                    "concerts",
+                    SelectionElement::SubSelect(
+                        RelationId::OneToMany(concerts_relation),
+                        Box::new(concerts),
+                    ),
+                ),
+            ],
+        )
+    })
+}
+
+/// The equivalent of the `CREATE_MUTATION` operation
+fn nested_insert(database: &Database, tables: &Tables) -> AbstractOperation {
+    let concerts_venue_id = column(database, tables.concerts, "venue_id");
+    let concerts_relation =
+        get_otm_relation_for_columns(&[concerts_venue_id], database, None).unwrap();
+
+    let value = |table_id: TableId, name: &str, param: SQLParamContainer| {
+        InsertionElement::SelfInsert(ColumnValuePair::new(
+            column(database, table_id, name),
+            Column::Param(param),
+        ))
+    };
+
+    let concert = |title: &str, published: bool, price: f64| InsertionRow {
+        elems: vec![
+            value(
+                tables.concerts,
+                "title",
+                SQLParamContainer::string(title.to_string()),
+            ),
+            value(
+                tables.concerts,
+                "published",
+                SQLParamContainer::bool(published),
+            ),
+            value(tables.concerts, "price", SQLParamContainer::f64(price)),
+        ],
+    };
+
+    AbstractOperation::Insert(AbstractInsert {
+        table_id: tables.venues,
+        rows: vec![InsertionRow {
+            elems: vec![
+                value(
+                    tables.venues,
+                    "name",
+                    SQLParamContainer::string("New venue".to_string()),
+                ),
+                value(
+                    tables.venues,
+                    "city",
+                    SQLParamContainer::string("City 0".to_string()),
+                ),
+                InsertionElement::NestedInsert(NestedInsertion {
+                    relation_id: concerts_relation,
+                    insertions: vec![
+                        concert("Opening", true, 20.0),
+                        concert("Closing", false, 30.0),
+                    ],
+                    precheck_predicates: vec![],
+                }),
+            ],
+        }],
+        selection: select(
+            tables.venues,
+            vec![(
+                "id",
+                SelectionElement::Physical(column(database, tables.venues, "id")),
+            )],
+        ),
+        precheck_predicates: vec![],
+    })
+}
+
+fn sql_building(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let system = runtime.block_on(build_system()).unwrap();
+    let database = postgres_database(&system).unwrap();
+    let tables = Tables::new(&database);
+
+    let mut group = c.benchmark_group("sql_building");
+
+    let operations: [(&str, fn(&Database, &Tables) -> AbstractOperation); 3] = [
+        ("simple_select", simple_select),
+        ("nested_select", nested_select),
+        ("nested_insert", nested_insert),
+    ];
+
+    for (name, operation) in operations {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || operation(&database, &tables),
+                |operation| black_box(DatabaseExecutor::to_sql_statements(operation, &database)),
+                criterion::BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, sql_building);
+criterion_main!(benches);
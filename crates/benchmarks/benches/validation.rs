@@ -0,0 +1,55 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Parsing and validating GraphQL documents against the schema of the benchmark model.
+
+use std::hint::black_box;
+
+use async_graphql_parser::parse_query;
+use criterion::{Criterion, criterion_group, criterion_main};
+use serde_json::json;
+
+use benchmarks::setup::{
+    CREATE_MUTATION, NESTED_QUERY, SIMPLE_QUERY, build_system, create_mutation_variables,
+    create_schema, nested_query_variables,
+};
+use core_resolver::validation::document_validator::DocumentValidator;
+
+fn validation(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let schema = runtime
+        .block_on(async { create_schema(build_system().await?).await })
+        .unwrap();
+
+    let mut group = c.benchmark_group("validation");
+
+    for (name, query, variables) in [
+        ("simple_query", SIMPLE_QUERY, json!({})),
+        ("nested_query", NESTED_QUERY, nested_query_variables()),
+        (
+            "create_mutation",
+            CREATE_MUTATION,
+            create_mutation_variables(),
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let document = parse_query(black_box(query)).unwrap();
+                let validator =
+                    DocumentValidator::new(&schema, None, variables.as_object().cloned(), 10, 10);
+                black_box(validator.validate(document).unwrap())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, validation);
+criterion_main!(benches);
@@ -0,0 +1,209 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Baselines for detecting performance regressions.
+//!
+//! A baseline is a JSON file mapping each benchmark id to a measurement in nanoseconds (lower is
+//! better):
+//!
+//! ```json
+//! {
+//!   "measurements": {
+//!     "validation/nested_query": 48210.5,
+//!     "load/nested_query/p99": 2810000.0
+//!   }
+//! }
+//! ```
+//!
+//! Baselines are recorded from the output of a criterion run (`exo-bench collect`) or of the load
+//! mode (`exo-bench load --output`), and compared with `exo-bench compare`.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Baseline {
+    pub measurements: BTreeMap<String, f64>,
+}
+
+/// A measurement that is slower than its baseline by more than the allowed threshold
+#[derive(Debug, PartialEq)]
+pub struct Regression {
+    pub id: String,
+    pub baseline_ns: f64,
+    pub current_ns: f64,
+}
+
+impl Regression {
+    /// The relative change from the baseline (0.25 means 25% slower)
+    pub fn change(&self) -> f64 {
+        (self.current_ns - self.baseline_ns) / self.baseline_ns
+    }
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content + "\n")
+            .with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Record the mean time of each benchmark in a criterion output directory (typically
+    /// `target/criterion`), using the results of the latest run
+    pub fn from_criterion_dir(criterion_dir: &Path) -> Result<Self> {
+        let mut baseline = Baseline::default();
+
+        for dir in benchmark_dirs(criterion_dir)? {
+            let benchmark: Value = read_json(&dir.join("new").join("benchmark.json"))?;
+            let estimates: Value = read_json(&dir.join("new").join("estimates.json"))?;
+
+            let id = benchmark["full_id"].as_str();
+            let mean = estimates["mean"]["point_estimate"].as_f64();
+
+            if let (Some(id), Some(mean)) = (id, mean) {
+                baseline.measurements.insert(id.to_string(), mean);
+            }
+        }
+
+        Ok(baseline)
+    }
+
+    /// Add (or replace) measurements from another baseline
+    pub fn merge(&mut self, other: Baseline) {
+        self.measurements.extend(other.measurements);
+    }
+
+    /// Measurements in `current` that are slower than this baseline by more than `threshold` (0.1
+    /// means 10%). Measurements that are only in one of the baselines are ignored, so comparing
+    /// the result of a filtered run with a full baseline works as expected.
+    pub fn regressions(&self, current: &Baseline, threshold: f64) -> Vec<Regression> {
+        self.measurements
+            .iter()
+            .filter_map(|(id, baseline_ns)| {
+                let current_ns = *current.measurements.get(id)?;
+
+                (current_ns > baseline_ns * (1.0 + threshold)).then(|| Regression {
+                    id: id.clone(),
+                    baseline_ns: *baseline_ns,
+                    current_ns,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Directories with the results of a benchmark (the ones with a `new/benchmark.json` file)
+fn benchmark_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = vec![];
+
+    if dir.join("new").join("benchmark.json").exists() {
+        dirs.push(dir.to_path_buf());
+    }
+
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read criterion output {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        // Skip criterion's own directories (`new`, `base`, `change`, `report`) and any saved baselines
+        if path.is_dir() && !path.join("benchmark.json").exists() && !path.ends_with("report") {
+            dirs.extend(benchmark_dirs(&path)?);
+        }
+    }
+
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(measurements: &[(&str, f64)]) -> Baseline {
+        Baseline {
+            measurements: measurements
+                .iter()
+                .map(|(id, value)| (id.to_string(), *value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn reports_regressions_over_threshold() {
+        let previous = baseline(&[("a", 100.0), ("b", 100.0), ("c", 100.0), ("d", 100.0)]);
+        let current = baseline(&[("a", 105.0), ("b", 130.0), ("c", 80.0), ("e", 500.0)]);
+
+        let regressions = previous.regressions(&current, 0.1);
+
+        assert_eq!(
+            regressions,
+            vec![Regression {
+                id: "b".to_string(),
+                baseline_ns: 100.0,
+                current_ns: 130.0,
+            }]
+        );
+        assert!((regressions[0].change() - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reads_criterion_output() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let write_benchmark = |path: &str, full_id: &str, mean: f64| {
+            let new_dir = dir.path().join(path).join("new");
+            fs::create_dir_all(&new_dir).unwrap();
+            fs::write(
+                new_dir.join("benchmark.json"),
+                serde_json::json!({ "full_id": full_id }).to_string(),
+            )
+            .unwrap();
+            fs::write(
+                new_dir.join("estimates.json"),
+                serde_json::json!({ "mean": { "point_estimate": mean } }).to_string(),
+            )
+            .unwrap();
+        };
+
+        write_benchmark("validation/simple_query", "validation/simple_query", 10.0);
+        write_benchmark("validation/nested_query", "validation/nested_query", 20.0);
+        fs::create_dir_all(dir.path().join("report")).unwrap();
+
+        assert_eq!(
+            Baseline::from_criterion_dir(dir.path()).unwrap(),
+            baseline(&[
+                ("validation/nested_query", 20.0),
+                ("validation/simple_query", 10.0)
+            ])
+        );
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Load testing and baseline management for the benchmarks.
+//!
+//! - `exo-bench load`: send requests to a running server and report throughput and latencies
+//! - `exo-bench collect`: record the results of the last criterion run as a baseline
+//! - `exo-bench compare`: report measurements that regressed compared to a baseline (exits with a
+//!   non-zero status if there are any)
+
+use std::{path::PathBuf, process::ExitCode, time::Duration};
+
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use serde_json::{Value, json};
+
+use benchmarks::{
+    baseline::Baseline,
+    load::{LoadConfig, run_load},
+    setup::{NESTED_QUERY, nested_query_variables},
+};
+
+fn command() -> Command {
+    Command::new("exo-bench")
+        .about("Load testing and benchmark baselines for Exograph")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("load")
+                .about("Send GraphQL requests to a running server and report latencies")
+                .arg(
+                    Arg::new("url")
+                        .long("url")
+                        .help("The GraphQL endpoint")
+                        .default_value("http://localhost:9876/graphql"),
+                )
+                .arg(
+                    Arg::new("query-file")
+                        .long("query-file")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("File with the GraphQL operation. Default: the nested query of the benchmark model"),
+                )
+                .arg(
+                    Arg::new("variables")
+                        .long("variables")
+                        .help("Variables for the operation (as JSON)"),
+                )
+                .arg(
+                    Arg::new("header")
+                        .long("header")
+                        .short('H')
+                        .action(ArgAction::Append)
+                        .help("Additional header in the form 'name: value'"),
+                )
+                .arg(
+                    Arg::new("concurrency")
+                        .long("concurrency")
+                        .short('c')
+                        .value_parser(value_parser!(usize))
+                        .default_value("16"),
+                )
+                .arg(
+                    Arg::new("duration")
+                        .long("duration")
+                        .short('d')
+                        .value_parser(value_parser!(u64))
+                        .help("Seconds to measure for")
+                        .default_value("10"),
+                )
+                .arg(
+                    Arg::new("warmup")
+                        .long("warmup")
+                        .value_parser(value_parser!(u64))
+                        .help("Seconds to send requests before measuring")
+                        .default_value("2"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .help("Name for the measurements in the baseline")
+                        .default_value("nested_query"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Write the latencies as a baseline file"),
+                ),
+        )
+        .subcommand(
+            Command::new("collect")
+                .about("Record the results of the last criterion run as a baseline")
+                .arg(
+                    Arg::new("criterion-dir")
+                        .long("criterion-dir")
+                        .value_parser(value_parser!(PathBuf))
+                        .default_value("target/criterion"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("compare")
+                .about("Compare measurements with a baseline")
+                .arg(
+                    Arg::new("baseline")
+                        .long("baseline")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("current")
+                        .long("current")
+                        .value_parser(value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .value_parser(value_parser!(f64))
+                        .help("Allowed slowdown before reporting a regression (0.1 means 10%)")
+                        .default_value("0.1"),
+                ),
+        )
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    let matches = command().get_matches();
+
+    match matches.subcommand() {
+        Some(("load", matches)) => load(matches).await,
+        Some(("collect", matches)) => collect(matches),
+        Some(("compare", matches)) => compare(matches),
+        _ => unreachable!("subcommand_required is set"),
+    }
+}
+
+async fn load(matches: &ArgMatches) -> Result<ExitCode> {
+    let (query, variables) = match matches.get_one::<PathBuf>("query-file") {
+        Some(query_file) => {
+            let query = std::fs::read_to_string(query_file)
+                .with_context(|| format!("Failed to read {}", query_file.display()))?;
+            (query, json!({}))
+        }
+        None => (NESTED_QUERY.to_string(), nested_query_variables()),
+    };
+
+    let variables: Value = match matches.get_one::<String>("variables") {
+        Some(variables) => serde_json::from_str(variables).context("Invalid variables")?,
+        None => variables,
+    };
+
+    let headers = matches
+        .get_many::<String>("header")
+        .unwrap_or_default()
+        .map(|header| {
+            header
+                .split_once(':')
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                .ok_or_else(|| anyhow!("Invalid header '{header}' (expected 'name: value')"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let config = LoadConfig {
+        url: matches.get_one::<String>("url").unwrap().clone(),
+        body: json!({ "query": query, "variables": variables }),
+        headers,
+        concurrency: *matches.get_one::<usize>("concurrency").unwrap(),
+        duration: Duration::from_secs(*matches.get_one::<u64>("duration").unwrap()),
+        warmup: Duration::from_secs(*matches.get_one::<u64>("warmup").unwrap()),
+    };
+
+    println!(
+        "Running {}s load test with {} connections against {}",
+        config.duration.as_secs(),
+        config.concurrency,
+        config.url
+    );
+
+    let report = run_load(&config).await?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if let Some(output) = matches.get_one::<PathBuf>("output") {
+        let name = matches.get_one::<String>("name").unwrap();
+        report.to_baseline(name).save(output)?;
+    }
+
+    if report.errors > 0 {
+        eprintln!("{} of {} requests failed", report.errors, report.requests);
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn collect(matches: &ArgMatches) -> Result<ExitCode> {
+    let criterion_dir = matches.get_one::<PathBuf>("criterion-dir").unwrap();
+    let output = matches.get_one::<PathBuf>("output").unwrap();
+
+    let baseline = Baseline::from_criterion_dir(criterion_dir)?;
+    baseline.save(output)?;
+
+    println!(
+        "Recorded {} measurements in {}",
+        baseline.measurements.len(),
+        output.display()
+    );
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn compare(matches: &ArgMatches) -> Result<ExitCode> {
+    let baseline = Baseline::load(matches.get_one::<PathBuf>("baseline").unwrap())?;
+    let current = Baseline::load(matches.get_one::<PathBuf>("current").unwrap())?;
+    let threshold = *matches.get_one::<f64>("threshold").unwrap();
+
+    let regressions = baseline.regressions(&current, threshold);
+
+    if regressions.is_empty() {
+        println!("No regressions over {:.0}%", threshold * 100.0);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!("Regressions over {:.0}%:", threshold * 100.0);
+    for regression in regressions.iter() {
+        println!(
+            "  {}: {:.0}ns -> {:.0}ns (+{:.1}%)",
+            regression.id,
+            regression.baseline_ns,
+            regression.current_ns,
+            regression.change() * 100.0
+        );
+    }
+
+    Ok(ExitCode::FAILURE)
+}
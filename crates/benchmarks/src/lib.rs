@@ -0,0 +1,22 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Benchmarks for the resolver pipeline.
+//!
+//! The criterion benchmarks (in `benches/`) cover three stages:
+//! - `validation`: parsing and validating GraphQL documents against the schema
+//! - `sql_building`: transforming abstract operations into SQL
+//! - `execution`: end-to-end execution of GraphQL operations against an ephemeral database
+//!
+//! The `exo-bench` binary complements them with an HTTP load mode (against a running server) and
+//! with commands to record and compare baselines (see [`baseline`]).
+
+pub mod baseline;
+pub mod load;
+pub mod setup;
@@ -0,0 +1,203 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A wrk-style HTTP load generator: a fixed number of connections send the same request in a loop
+//! for a fixed duration, and the latencies of successful requests are summarized.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::baseline::Baseline;
+
+pub struct LoadConfig {
+    /// The endpoint URL (such as `http://localhost:9876/graphql`)
+    pub url: String,
+    /// The request body (such as `{"query": "...", "variables": {...}}`)
+    pub body: Value,
+    pub headers: Vec<(String, String)>,
+    /// The number of concurrent connections
+    pub concurrency: usize,
+    /// How long to measure for
+    pub duration: Duration,
+    /// How long to send requests before measuring (to warm up connections and caches)
+    pub warmup: Duration,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LoadReport {
+    pub requests: usize,
+    /// Requests that failed to send or returned a non-success status
+    pub errors: usize,
+    pub duration_secs: f64,
+    pub requests_per_sec: f64,
+    pub latency_ns: LatencySummary,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LatencySummary {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    fn from_latencies(mut latencies: Vec<Duration>) -> Self {
+        if latencies.is_empty() {
+            return Self {
+                mean: 0.0,
+                p50: 0.0,
+                p90: 0.0,
+                p99: 0.0,
+                max: 0.0,
+            };
+        }
+
+        latencies.sort();
+
+        let nanos = |latency: &Duration| latency.as_nanos() as f64;
+        let percentile = |p: f64| {
+            let index = ((latencies.len() as f64 * p).ceil() as usize).clamp(1, latencies.len());
+            nanos(&latencies[index - 1])
+        };
+
+        Self {
+            mean: latencies.iter().map(nanos).sum::<f64>() / latencies.len() as f64,
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            max: nanos(latencies.last().unwrap()),
+        }
+    }
+}
+
+impl LoadReport {
+    /// The latencies as baseline measurements (with ids such as `load/<name>/p99`)
+    pub fn to_baseline(&self, name: &str) -> Baseline {
+        let mut baseline = Baseline::default();
+        for (stat, value) in [
+            ("mean", self.latency_ns.mean),
+            ("p50", self.latency_ns.p50),
+            ("p90", self.latency_ns.p90),
+            ("p99", self.latency_ns.p99),
+        ] {
+            baseline
+                .measurements
+                .insert(format!("load/{name}/{stat}"), value);
+        }
+        baseline
+    }
+}
+
+pub async fn run_load(config: &LoadConfig) -> Result<LoadReport> {
+    let client = reqwest::Client::builder()
+        .pool_max_idle_per_host(config.concurrency)
+        .build()?;
+    let body = serde_json::to_string(&config.body)?;
+
+    let measure_start = Instant::now() + config.warmup;
+    let end = measure_start + config.duration;
+
+    let workers: Vec<_> = (0..config.concurrency.max(1))
+        .map(|_| {
+            let client = client.clone();
+            let url = config.url.clone();
+            let headers = config.headers.clone();
+            let body = body.clone();
+
+            tokio::spawn(async move {
+                let mut latencies = vec![];
+                let mut errors = 0;
+
+                loop {
+                    let start = Instant::now();
+                    if start >= end {
+                        break;
+                    }
+
+                    let success = send_request(&client, &url, &headers, body.clone()).await;
+
+                    if start >= measure_start {
+                        match success {
+                            Ok(true) => latencies.push(start.elapsed()),
+                            _ => errors += 1,
+                        }
+                    }
+                }
+
+                (latencies, errors)
+            })
+        })
+        .collect();
+
+    let mut latencies = vec![];
+    let mut errors = 0;
+    for worker in workers {
+        let (worker_latencies, worker_errors) = worker.await?;
+        latencies.extend(worker_latencies);
+        errors += worker_errors;
+    }
+
+    let duration_secs = config.duration.as_secs_f64();
+    let requests = latencies.len() + errors;
+
+    Ok(LoadReport {
+        requests,
+        errors,
+        duration_secs,
+        requests_per_sec: latencies.len() as f64 / duration_secs,
+        latency_ns: LatencySummary::from_latencies(latencies),
+    })
+}
+
+async fn send_request(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &[(String, String)],
+    body: String,
+) -> Result<bool> {
+    let mut request = client
+        .post(url)
+        .header(http::header::CONTENT_TYPE, "application/json");
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = request.body(body).send().await?;
+    let success = response.status().is_success();
+    // Read the whole body, so that the latency includes streaming the response
+    response.bytes().await?;
+
+    Ok(success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_latencies() {
+        let latencies = (1..=100).rev().map(Duration::from_nanos).collect();
+
+        assert_eq!(
+            LatencySummary::from_latencies(latencies),
+            LatencySummary {
+                mean: 50.5,
+                p50: 50.0,
+                p90: 90.0,
+                p99: 99.0,
+                max: 100.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Shared setup for the benchmarks: the model, representative operations, and helpers to build
+//! the schema, the database, and the router.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+use common::{
+    env_const::{EXO_CHECK_CONNECTION_ON_STARTUP, EXO_POSTGRES_READ_WRITE, EXO_POSTGRES_URL},
+    http::{MemoryRequestHead, MemoryRequestPayload},
+    operation_payload::OperationsPayload,
+    router::{PlainRequestPayload, Router},
+};
+use core_model_builder::plugin::BuildMode;
+use core_plugin_interface::interface::SubsystemLoader;
+use core_plugin_shared::{
+    profile::SchemaProfile, serializable_system::SerializableSystem,
+    system_serializer::SystemSerializer,
+};
+use core_resolver::introspection::definition::schema::Schema;
+use exo_env::MapEnvironment;
+use exo_sql::{
+    Database, DatabaseClientManager, TransactionMode,
+    schema::{database_spec::DatabaseSpec, migration::Migration, spec::MigrationScope},
+    testing::db::{EphemeralDatabase, EphemeralDatabaseLauncher, EphemeralDatabaseServer},
+};
+use postgres_core_model::subsystem::PostgresCoreSubsystem;
+use postgres_resolver::PostgresSubsystemLoader;
+use system_router::{SystemRouter, create_system_router_from_system};
+
+pub const MODEL: &str = include_str!("../models/concerts.exo");
+
+/// A query with a single table and no arguments
+pub const SIMPLE_QUERY: &str = r#"
+query {
+    venues {
+        id
+        name
+    }
+}
+"#;
+
+/// A query with variables, predicates, ordering, and a nested collection
+pub const NESTED_QUERY: &str = r#"
+query($city: String!) {
+    venues(where: {city: {eq: $city}}, orderBy: {name: ASC}) {
+        id
+        name
+        concerts(where: {published: {eq: true}}, orderBy: {price: DESC}, limit: 5) {
+            id
+            title
+            price
+        }
+    }
+}
+"#;
+
+/// A mutation that creates a venue with nested concerts
+pub const CREATE_MUTATION: &str = r#"
+mutation($name: String!, $city: String!) {
+    createVenue(data: {name: $name, city: $city, concerts: [
+        {title: "Opening", published: true, price: 20.0},
+        {title: "Closing", published: false, price: 30.0}
+    ]}) {
+        id
+    }
+}
+"#;
+
+pub fn nested_query_variables() -> Value {
+    json!({ "city": "City 0" })
+}
+
+pub fn create_mutation_variables() -> Value {
+    json!({ "name": "New venue", "city": "City 0" })
+}
+
+/// Build the system for the benchmark model
+pub async fn build_system() -> Result<SerializableSystem> {
+    Ok(builder::build_system_from_str(
+        MODEL,
+        "concerts.exo".to_string(),
+        vec![Box::new(
+            postgres_builder::PostgresSubsystemBuilder::default(),
+        )],
+        BuildMode::Build,
+    )
+    .await?)
+}
+
+/// The database (tables, columns, and relations) of the Postgres subsystem
+pub fn postgres_database(system: &SerializableSystem) -> Result<Database> {
+    let subsystem = system
+        .subsystems
+        .iter()
+        .find(|subsystem| subsystem.id == "postgres")
+        .ok_or_else(|| anyhow!("The model has no Postgres subsystem"))?;
+
+    let core_subsystem = PostgresCoreSubsystem::deserialize(subsystem.core.0.clone())?;
+
+    Ok(core_subsystem.database)
+}
+
+/// Create the GraphQL schema (to validate documents against) without connecting to a database
+pub async fn create_schema(system: SerializableSystem) -> Result<Schema> {
+    let subsystem = system
+        .subsystems
+        .into_iter()
+        .find(|subsystem| subsystem.id == "postgres")
+        .ok_or_else(|| anyhow!("The model has no Postgres subsystem"))?;
+
+    // The client connects lazily, and validation never gets that far
+    let client = DatabaseClientManager::from_url(
+        "postgres://localhost/exo_bench",
+        false,
+        None,
+        TransactionMode::ReadWrite,
+    )
+    .await?;
+
+    let resolver = PostgresSubsystemLoader {
+        existing_client: Some(client),
+    }
+    .init(subsystem, Arc::new(MapEnvironment::default()))
+    .await?;
+
+    let graphql_resolver = resolver
+        .graphql
+        .ok_or_else(|| anyhow!("The Postgres subsystem has no GraphQL resolver"))?;
+
+    Ok(Schema::new_from_resolvers(
+        &[graphql_resolver],
+        &SchemaProfile::all(),
+        Arc::new(None),
+    ))
+}
+
+/// An ephemeral database with the schema for the benchmark model
+pub struct BenchDatabase {
+    // Declared before the server so that it is dropped first
+    database: Box<dyn EphemeralDatabase + Send + Sync>,
+    _server: Box<dyn EphemeralDatabaseServer + Send + Sync>,
+}
+
+impl BenchDatabase {
+    pub async fn launch(database: &Database) -> Result<Self> {
+        let server = EphemeralDatabaseLauncher::from_env().create_server()?;
+        let ephemeral_database = server.create_database("exo_bench")?;
+
+        let mut client = DatabaseClientManager::from_url(
+            &ephemeral_database.url(),
+            true,
+            None,
+            TransactionMode::ReadWrite,
+        )
+        .await?
+        .get_client()
+        .await?;
+
+        // Creating the schema is the same as migrating from an empty database
+        Migration::from_schemas(
+            &DatabaseSpec::new(vec![], vec![], vec![]),
+            &DatabaseSpec::from_database(database),
+            &MigrationScope::FromNewSpec,
+        )
+        .apply(&mut client, false)
+        .await?;
+
+        Ok(Self {
+            database: ephemeral_database,
+            _server: server,
+        })
+    }
+
+    pub fn url(&self) -> String {
+        self.database.url()
+    }
+}
+
+/// Create a router for the system that uses the database at the given URL
+pub async fn create_router(system: SerializableSystem, database_url: &str) -> Result<SystemRouter> {
+    let env = MapEnvironment::from(HashMap::from([
+        (EXO_POSTGRES_URL.to_string(), database_url.to_string()),
+        (
+            EXO_CHECK_CONNECTION_ON_STARTUP.to_string(),
+            "false".to_string(),
+        ),
+        (EXO_POSTGRES_READ_WRITE.to_string(), "true".to_string()),
+    ]));
+
+    Ok(create_system_router_from_system(
+        system,
+        server_common::create_static_loaders(),
+        Arc::new(env),
+    )
+    .await?)
+}
+
+/// Execute a GraphQL operation through the router (the same path as an HTTP request, minus the
+/// network)
+pub async fn execute_operation(
+    router: &SystemRouter,
+    query: &str,
+    variables: Value,
+) -> Result<Value> {
+    let mut request_head = MemoryRequestHead::new(
+        HashMap::new(),
+        HashMap::new(),
+        http::Method::POST,
+        "/graphql".to_string(),
+        Value::default(),
+        None,
+    );
+    request_head.add_header("Content-Type", "application/json");
+
+    let operations_payload = OperationsPayload {
+        operation_name: None,
+        query: Some(query.to_string()),
+        variables: variables.as_object().cloned(),
+        query_hash: None,
+    };
+
+    let request = MemoryRequestPayload::new(operations_payload.to_json()?, request_head);
+
+    let response = router
+        .route(&PlainRequestPayload::external(Box::new(request)))
+        .await
+        .ok_or_else(|| anyhow!("No response for the operation"))?;
+
+    let body = response.body.to_json().await?;
+
+    if let Some(errors) = body.get("errors") {
+        return Err(anyhow!("Operation failed: {errors}"));
+    }
+
+    Ok(body)
+}
+
+/// Seed the database with venues in `city_count` cities, each with `concerts_per_venue` concerts
+pub async fn seed(
+    router: &SystemRouter,
+    venue_count: usize,
+    city_count: usize,
+    concerts_per_venue: usize,
+) -> Result<()> {
+    for venue_index in 0..venue_count {
+        let concerts: Vec<Value> = (0..concerts_per_venue)
+            .map(|concert_index| {
+                json!({
+                    "title": format!("Concert {venue_index}-{concert_index}"),
+                    "published": concert_index % 2 == 0,
+                    "price": (concert_index * 10) as f64,
+                })
+            })
+            .collect();
+
+        execute_operation(
+            router,
+            "mutation($data: VenueCreationInput!) { createVenue(data: $data) { id } }",
+            json!({
+                "data": {
+                    "name": format!("Venue {venue_index}"),
+                    "city": format!("City {}", venue_index % city_count.max(1)),
+                    "concerts": concerts,
+                }
+            }),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+use core_model::{
+    context_type::{ContextFieldType, ContextType},
+    mapped_arena::MappedArena,
+    primitive_type::PrimitiveType,
+};
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr},
+    error::ModelBuildingError,
+    typechecker::typ::{Module, TypecheckedSystem},
+};
+use core_plugin_shared::env_requirement::{EnvRequirement, EnvVarType};
+
+/// Collect the env vars that modules require through `@requireEnv("SomeContext")`.
+///
+/// Each `@env` field of the named contexts becomes a requirement (with the type of the field).
+/// A var required by several modules is reported once (with all the modules that require it).
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+    contexts: &MappedArena<ContextType>,
+) -> Result<Vec<EnvRequirement>, ModelBuildingError> {
+    let mut requirements: Vec<EnvRequirement> = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let Some(annotation) = module.annotations.annotations.get("requireEnv") else {
+            continue;
+        };
+
+        let context_names = match &annotation.params {
+            AstAnnotationParams::Single(AstExpr::StringLiteral(name, _), _) => vec![name.clone()],
+            AstAnnotationParams::Single(AstExpr::StringList(names, _), _) => names.clone(),
+            _ => {
+                errors.push(error(
+                    "@requireEnv expects the names of contexts (such as @requireEnv(\"StripeEnv\"))"
+                        .to_string(),
+                    annotation.span,
+                ));
+                continue;
+            }
+        };
+
+        for context_name in context_names {
+            let Some(context) = contexts.get_by_key(&context_name) else {
+                errors.push(error(
+                    format!(
+                        "Context `{context_name}` required by module `{}` is not defined",
+                        module.name
+                    ),
+                    annotation.span,
+                ));
+                continue;
+            };
+
+            for field in context.fields.iter() {
+                let requirement = (field.source.annotation_name == "env")
+                    .then_some(field.source.value.as_ref())
+                    .flatten()
+                    .and_then(|key| {
+                        env_var_type(&field.typ).map(|(typ, optional)| (key.clone(), typ, optional))
+                    });
+
+                let Some((key, typ, optional)) = requirement else {
+                    errors.push(error(
+                        format!(
+                            "Field `{}.{}` required by module `{}` must be an @env field of type String, Int, Float, Decimal, or Boolean (optionally, with `?`)",
+                            context.name, field.name, module.name
+                        ),
+                        annotation.span,
+                    ));
+                    continue;
+                };
+
+                match requirements.iter_mut().find(|existing| existing.key == key) {
+                    Some(existing) if existing.typ != typ => {
+                        errors.push(error(
+                            format!(
+                                "Env var {key} is required as {} and {} (by modules {} and {})",
+                                existing.typ,
+                                typ,
+                                existing.required_by.join(", "),
+                                module.name
+                            ),
+                            annotation.span,
+                        ));
+                    }
+                    Some(existing) => {
+                        existing.optional &= optional;
+                        if !existing.required_by.contains(&module.name) {
+                            existing.required_by.push(module.name.clone());
+                        }
+                    }
+                    None => requirements.push(EnvRequirement {
+                        key,
+                        typ,
+                        optional,
+                        required_by: vec![module.name.clone()],
+                    }),
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        requirements.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(requirements)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+/// The type of the env var and whether it is optional
+fn env_var_type(typ: &ContextFieldType) -> Option<(EnvVarType, bool)> {
+    match typ {
+        ContextFieldType::Plain(PrimitiveType::Plain(pt)) => {
+            EnvVarType::from_type_name(pt.name()).map(|typ| (typ, false))
+        }
+        ContextFieldType::Optional(underlying) => {
+            env_var_type(underlying).map(|(typ, _)| (typ, true))
+        }
+        _ => None,
+    }
+}
+
+fn error(message: String, span: codemap::Span) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message,
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
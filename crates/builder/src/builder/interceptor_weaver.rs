@@ -193,7 +193,8 @@ fn ordered(interceptions: Vec<(usize, &Interception)>) -> InterceptionTree {
             .into_iter()
             .for_each(|(subsystem_index, interception)| {
                 let interceptor =
-                    InterceptorIndexWithSubsystemIndex::new(subsystem_index, interception.index);
+                    InterceptorIndexWithSubsystemIndex::new(subsystem_index, interception.index)
+                        .with_error_policy(interception.error_policy);
 
                 match interception.kind {
                     InterceptorKind::Before => before.push(interceptor),
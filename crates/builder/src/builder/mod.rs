@@ -7,7 +7,11 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+mod env_requirements;
 mod interceptor_weaver;
+mod operation_limits;
+mod response_headers;
+mod routes;
 mod system_builder;
 
 pub(crate) use system_builder::build;
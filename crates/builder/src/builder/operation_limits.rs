@@ -0,0 +1,82 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr},
+    error::ModelBuildingError,
+    typechecker::{
+        Typed,
+        typ::{Module, TypecheckedSystem},
+    },
+};
+use core_plugin_shared::operation_limit::OperationLimit;
+
+/// Collect the concurrency limits declared on module methods through `@concurrencyLimit(2)` or
+/// `@concurrencyLimit(max=2, queue=10)`.
+///
+/// Without a `queue`, executions beyond the limit are rejected right away.
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Vec<OperationLimit>, ModelBuildingError> {
+    let mut limits = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        for method in module.methods.iter() {
+            let Some(annotation) = method.annotations.annotations.get("concurrencyLimit") else {
+                continue;
+            };
+
+            let (max_concurrent, max_queued) = match &annotation.params {
+                AstAnnotationParams::Single(expr, _) => (count(expr, 1), Some(0)),
+                AstAnnotationParams::Map(params, _) => (
+                    params.get("max").and_then(|expr| count(expr, 1)),
+                    params.get("queue").map_or(Some(0), |expr| count(expr, 0)),
+                ),
+                AstAnnotationParams::None => (None, None),
+            };
+
+            match (max_concurrent, max_queued) {
+                (Some(max_concurrent), Some(max_queued)) => limits.push(OperationLimit {
+                    operation: method.name.clone(),
+                    max_concurrent,
+                    max_queued,
+                }),
+                _ => errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!(
+                        "@concurrencyLimit on `{}.{}` expects a positive limit and an optional queue size (such as @concurrencyLimit(max=2, queue=10))",
+                        module.name, method.name
+                    ),
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: annotation.span,
+                        style: SpanStyle::Primary,
+                        label: None,
+                    }],
+                }),
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        limits.sort_by(|a, b| a.operation.cmp(&b.operation));
+        Ok(limits)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+fn count(expr: &AstExpr<Typed>, min: usize) -> Option<usize> {
+    match expr {
+        AstExpr::NumberLiteral(value, _) => value.parse::<usize>().ok().filter(|n| *n >= min),
+        _ => None,
+    }
+}
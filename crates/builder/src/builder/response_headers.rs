@@ -0,0 +1,94 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr},
+    error::ModelBuildingError,
+    typechecker::typ::{Module, TypecheckedSystem},
+};
+use core_plugin_shared::response_headers::OperationResponseHeaders;
+
+/// Collect the response headers declared on module methods through
+/// `@responseHeaders({"Cache-Control": "public, max-age=60"})`.
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Vec<OperationResponseHeaders>, ModelBuildingError> {
+    let mut declared = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        for method in module.methods.iter() {
+            let Some(annotation) = method.annotations.annotations.get("responseHeaders") else {
+                continue;
+            };
+
+            let error = |message: String| Diagnostic {
+                level: Level::Error,
+                message,
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: annotation.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            };
+
+            let AstAnnotationParams::Single(AstExpr::ObjectLiteral(params, _), _) =
+                &annotation.params
+            else {
+                errors.push(error(format!(
+                    "@responseHeaders on `{}.{}` expects the headers as an object (such as @responseHeaders({{\"Cache-Control\": \"public, max-age=60\"}}))",
+                    module.name, method.name
+                )));
+                continue;
+            };
+
+            let mut headers = vec![];
+            for (name, value) in params {
+                match value {
+                    AstExpr::StringLiteral(value, _)
+                        if is_header_name(name) && is_header_value(value) =>
+                    {
+                        headers.push((name.clone(), value.clone()))
+                    }
+                    _ => errors.push(error(format!(
+                        "Invalid response header `{name}` on `{}.{}` (the name must be a valid header name and the value a string without control characters)",
+                        module.name, method.name
+                    ))),
+                }
+            }
+            headers.sort();
+
+            declared.push(OperationResponseHeaders {
+                operation: method.name.clone(),
+                headers,
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        declared.sort_by(|a, b| a.operation.cmp(&b.operation));
+        Ok(declared)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+/// Is the name a token (as HTTP requires of header names)?
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+fn is_header_value(value: &str) -> bool {
+    value.chars().all(|c| c == '\t' || !c.is_control())
+}
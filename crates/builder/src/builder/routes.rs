@@ -0,0 +1,187 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use codemap::Span;
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr, AstFieldType, AstMethodType},
+    error::ModelBuildingError,
+    typechecker::typ::{Module, TypecheckedSystem},
+};
+use core_plugin_shared::routes::{DeclaredRoute, RouteArgument, RouteOperation, RouteTarget};
+
+/// Collect the routes declared through `@redirects({"/docs": "https://docs.example.com"})` on
+/// modules and `@route("/robots.txt")` on module methods.
+///
+/// A method that serves a route may only take `String` arguments (besides the injected ones),
+/// which are taken from the query parameters, and must return a `String` or `Json`.
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Vec<DeclaredRoute>, ModelBuildingError> {
+    let mut routes = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        if let Some(annotation) = module.annotations.annotations.get("redirects") {
+            match &annotation.params {
+                AstAnnotationParams::Single(AstExpr::ObjectLiteral(params, _), _) => {
+                    for (path, location) in params {
+                        match location {
+                            AstExpr::StringLiteral(location, _)
+                                if is_route_path(path) && is_location(location) =>
+                            {
+                                routes.push((
+                                    DeclaredRoute {
+                                        path: path.clone(),
+                                        target: RouteTarget::Redirect {
+                                            location: location.clone(),
+                                        },
+                                    },
+                                    annotation.span,
+                                ))
+                            }
+                            _ => errors.push(error(
+                                format!(
+                                    "Invalid redirect `{path}` on `{}` (the path must start with a slash and the location must be a non-empty string)",
+                                    module.name
+                                ),
+                                annotation.span,
+                            )),
+                        }
+                    }
+                }
+                _ => errors.push(error(
+                    format!(
+                        "@redirects on `{}` expects the redirects as an object (such as @redirects({{\"/docs\": \"https://docs.example.com\"}}))",
+                        module.name
+                    ),
+                    annotation.span,
+                )),
+            }
+        }
+
+        for method in module.methods.iter() {
+            let Some(annotation) = method.annotations.annotations.get("route") else {
+                continue;
+            };
+
+            let path = match &annotation.params {
+                AstAnnotationParams::Single(AstExpr::StringLiteral(path, _), _)
+                    if is_route_path(path) =>
+                {
+                    path.clone()
+                }
+                _ => {
+                    errors.push(error(
+                        format!(
+                            "@route on `{}.{}` expects a path starting with a slash (such as @route(\"/robots.txt\"))",
+                            module.name, method.name
+                        ),
+                        annotation.span,
+                    ));
+                    continue;
+                }
+            };
+
+            if !matches!(method.return_type.name().as_str(), "String" | "Json") {
+                errors.push(error(
+                    format!(
+                        "`{}.{}` serves a route, so it must return a `String` or `Json`",
+                        module.name, method.name
+                    ),
+                    annotation.span,
+                ));
+                continue;
+            }
+
+            let mut arguments = vec![];
+            for argument in method.arguments.iter() {
+                if argument.annotations.annotations.contains_key("inject") {
+                    continue;
+                }
+
+                if argument.typ.name() != "String" {
+                    errors.push(error(
+                        format!(
+                            "`{}.{}` serves a route, so its argument `{}` must be a `String` (taken from the query parameter of the same name)",
+                            module.name, method.name, argument.name
+                        ),
+                        annotation.span,
+                    ));
+                    continue;
+                }
+
+                arguments.push(RouteArgument {
+                    name: argument.name.clone(),
+                    optional: matches!(argument.typ, AstFieldType::Optional(_)),
+                });
+            }
+
+            routes.push((
+                DeclaredRoute {
+                    path,
+                    target: RouteTarget::Operation(RouteOperation {
+                        name: method.name.clone(),
+                        mutation: method.typ == AstMethodType::Mutation,
+                        arguments,
+                    }),
+                },
+                annotation.span,
+            ));
+        }
+    }
+
+    let mut paths: HashMap<&str, Span> = HashMap::new();
+    for (route, span) in routes.iter() {
+        if paths.insert(&route.path, *span).is_some() {
+            errors.push(error(
+                format!("More than one route for the path `{}`", route.path),
+                *span,
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        let mut routes: Vec<_> = routes.into_iter().map(|(route, _)| route).collect();
+        routes.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(routes)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+fn error(message: String, span: Span) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message,
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
+
+/// Is the path an absolute path (without a query or fragment)?
+fn is_route_path(path: &str) -> bool {
+    path.starts_with('/')
+        && !path
+            .chars()
+            .any(|c| c == '?' || c == '#' || c.is_whitespace() || c.is_control())
+}
+
+fn is_location(location: &str) -> bool {
+    !location.is_empty()
+        && !location
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control())
+}
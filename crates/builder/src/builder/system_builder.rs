@@ -7,7 +7,12 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
+
 use super::interceptor_weaver::{self, OperationKind};
+use super::{env_requirements, operation_limits, response_headers, routes};
+use core_model::context_type::ContextType;
+use core_model::mapped_arena::MappedArena;
 use core_model_builder::error::ModelBuildingError;
 use core_model_builder::plugin::BuildMode;
 use core_model_builder::typechecker::typ::TypecheckedSystem;
@@ -44,6 +49,11 @@ pub async fn build(
     build_mode: BuildMode,
 ) -> Result<SerializableSystem, ModelBuildingError> {
     let base_system = core_model_builder::builder::system_builder::build(&typechecked_system)?;
+    let env_defaults = env_defaults(&base_system.contexts)?;
+    let env_requirements = env_requirements::build(&typechecked_system, &base_system.contexts)?;
+    let operation_limits = operation_limits::build(&typechecked_system)?;
+    let operation_response_headers = response_headers::build(&typechecked_system)?;
+    let routes = routes::build(&typechecked_system)?;
 
     let mut subsystem_interceptions = vec![];
     let mut query_names = vec![];
@@ -122,5 +132,36 @@ pub async fn build(
         trusted_documents,
         declaration_doc_comments: typechecked_system.declaration_doc_comments,
         schema_profiles,
+        env_defaults,
+        env_requirements,
+        operation_limits,
+        operation_response_headers,
+        routes,
     })
 }
+
+/// Collect defaults declared for `@env` context fields (keyed by the env var name)
+fn env_defaults(
+    contexts: &MappedArena<ContextType>,
+) -> Result<HashMap<String, String>, ModelBuildingError> {
+    let mut env_defaults: HashMap<String, String> = HashMap::new();
+
+    for (_, context) in contexts.iter() {
+        for field in context.fields.iter() {
+            if let (Some(key), Some(default_value)) = (&field.source.value, &field.default_value) {
+                match env_defaults.get(key) {
+                    Some(existing) if existing != default_value => {
+                        return Err(ModelBuildingError::Generic(format!(
+                            "Conflicting defaults for env var {key}: '{existing}' and '{default_value}'"
+                        )));
+                    }
+                    _ => {
+                        env_defaults.insert(key.clone(), default_value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(env_defaults)
+}
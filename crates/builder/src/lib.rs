@@ -33,6 +33,7 @@ use colored::Colorize;
 mod builder;
 pub mod error;
 pub mod parser;
+pub mod report;
 pub mod typechecker;
 mod util;
 
@@ -148,6 +149,33 @@ pub async fn build_system_from_str_with_reporting(
     }
 }
 
+/// Check a model without building it, returning all diagnostics as structured data.
+///
+/// Returns an empty list if the model has no errors. Unlike the `build_system*` functions, this
+/// doesn't print anything, so tools (the LSP, web editors, snapshot tests) can render the
+/// diagnostics themselves.
+pub async fn check_system_from_str(
+    model_str: &str,
+    file_name: String,
+    static_builders: Vec<Box<dyn SubsystemBuilder + Send + Sync>>,
+) -> Vec<report::Diagnostic> {
+    let mut codemap = CodeMap::new();
+    codemap.add_file(file_name.clone(), model_str.to_string());
+
+    match build_from_ast_system(
+        parser::parse_str(model_str, &mut codemap, &file_name),
+        TrustedDocuments::all(),
+        None,
+        static_builders,
+        BuildMode::CheckOnly,
+    )
+    .await
+    {
+        Ok(_) => vec![],
+        Err(err) => report::diagnostics(&err, &codemap),
+    }
+}
+
 pub fn load_subsystem_builders(
     static_builders: Vec<Box<dyn SubsystemBuilder + Send + Sync>>,
 ) -> Result<Vec<Box<dyn SubsystemBuilder + Send + Sync>>, LibraryLoadingError> {
@@ -356,7 +384,7 @@ struct StderrReporter;
 impl ErrorReporter for StderrReporter {
     fn emit(&mut self, codemap: &CodeMap, err: &ParserError) {
         let mut emitter = Emitter::stderr(ColorConfig::Auto, Some(codemap));
-        emit_diagnostics(err, &mut emitter, codemap, None);
+        emit_diagnostics(err, &mut emitter);
     }
 }
 
@@ -373,86 +401,17 @@ impl ErrorReporter for ExternalReporter {
         let mut buffer: Vec<u8> = vec![];
         {
             let mut emitter = Emitter::vec(&mut buffer, Some(codemap));
-            emit_diagnostics(err, &mut emitter, codemap, Some(&mut self.diagnostics));
+            emit_diagnostics(err, &mut emitter);
         }
+        self.diagnostics = report::diagnostics(err, codemap);
         self.display_string = String::from_utf8(buffer).unwrap();
     }
 }
 
-pub mod report {
-    use serde::{Deserialize, Serialize};
-
-    #[derive(Serialize, Deserialize, Debug)]
-    pub struct Diagnostic {
-        pub spans: Vec<Span>,
-        pub message: String,
-        #[serde(rename = "isError")]
-        pub is_error: bool,
-    }
-
-    #[derive(Serialize, Deserialize, Debug)]
-    pub struct Span {
-        #[serde(rename = "fileName")]
-        pub file_name: String,
-        pub start: Position,
-        pub end: Position,
-    }
-
-    #[derive(Serialize, Deserialize, Debug)]
-    pub struct Position {
-        pub line: usize,
-        pub column: usize,
-    }
-}
-
-fn emit_diagnostics(
-    err: &ParserError,
-    emitter: &mut Emitter,
-    codemap: &CodeMap,
-    report_collector: Option<&mut Vec<report::Diagnostic>>,
-) {
-    fn collect_diagnostics(
-        diagnostics: &[Diagnostic],
-        codemap: &CodeMap,
-        report_collector: Option<&mut Vec<report::Diagnostic>>,
-    ) {
-        if let Some(report_collector) = report_collector {
-            diagnostics.iter().for_each(|diagnostic| {
-                let report_spans = diagnostic
-                    .spans
-                    .iter()
-                    .map(|span_label| {
-                        let span_loc = codemap.look_up_span(span_label.span);
-                        report::Span {
-                            file_name: span_loc.file.name().to_string(),
-                            start: report::Position {
-                                line: span_loc.begin.line,
-                                column: span_loc.begin.column,
-                            },
-                            end: report::Position {
-                                line: span_loc.end.line,
-                                column: span_loc.end.column,
-                            },
-                        }
-                    })
-                    .collect();
-
-                report_collector.push(report::Diagnostic {
-                    spans: report_spans,
-                    message: diagnostic.message.clone(),
-                    is_error: diagnostic.level == Level::Error,
-                });
-            });
-        }
-    }
-
+fn emit_diagnostics(err: &ParserError, emitter: &mut Emitter) {
     match err {
-        ParserError::Diagnosis(diagnostics) => {
-            collect_diagnostics(diagnostics, codemap, report_collector);
-            emitter.emit(diagnostics);
-        }
-        ParserError::ModelBuildingError(ModelBuildingError::Diagnosis(diagnostics)) => {
-            collect_diagnostics(diagnostics, codemap, report_collector);
+        ParserError::Diagnosis(diagnostics)
+        | ParserError::ModelBuildingError(ModelBuildingError::Diagnosis(diagnostics)) => {
             emitter.emit(diagnostics);
         }
         ParserError::ModelBuildingError(ModelBuildingError::TSJSParsingError(error)) => {
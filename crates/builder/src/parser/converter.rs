@@ -259,6 +259,10 @@ fn convert_enum(node: Node, source: &[u8], source_span: Span) -> AstEnum<Untyped
     AstEnum {
         name: text_child(node, source, "name"),
         fields,
+        annotations: node
+            .children_by_field_name("annotation", &mut cursor)
+            .map(|c| convert_annotation(c, source, source_span))
+            .collect(),
         doc_comments: convert_doc_comments(node, source, source_span),
         span: span_from_node(source_span, node),
     }
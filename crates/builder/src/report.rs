@@ -0,0 +1,265 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Diagnostics from building an exo model as structured data.
+//!
+//! Unlike the human-formatted output (see [`crate::ErrorReporter`]), these are meant for tools
+//! such as the LSP, web editors, and snapshot tests. The order is deterministic: diagnostics are
+//! sorted by the position of their primary span (diagnostics without a span come first).
+//!
+//! Lines and columns are 0-based.
+
+use codemap::CodeMap;
+use codemap_diagnostic::SpanStyle;
+use core_model_builder::error::ModelBuildingError;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ParserError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// A code that identifies the kind of problem (such as `C000`), if the diagnostic has one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub code: Option<String>,
+    pub message: String,
+    pub spans: Vec<Span>,
+    #[serde(rename = "isError")]
+    pub is_error: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Bug,
+    Fatal,
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Span {
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub start: Position,
+    pub end: Position,
+    /// Text to show at the span (such as "unknown type")
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub label: Option<String>,
+    /// Whether this is the span the diagnostic is about (other spans provide context, such as
+    /// the first definition for a duplicate definition)
+    #[serde(rename = "isPrimary", default)]
+    pub is_primary: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Diagnostic {
+    /// The span the diagnostic is about (the first primary span, or the first span if none is
+    /// marked primary)
+    pub fn primary_span(&self) -> Option<&Span> {
+        self.spans
+            .iter()
+            .find(|span| span.is_primary)
+            .or_else(|| self.spans.first())
+    }
+
+    fn spanless(message: String) -> Self {
+        Self {
+            level: Level::Error,
+            code: None,
+            message,
+            spans: vec![],
+            is_error: true,
+        }
+    }
+}
+
+impl From<codemap_diagnostic::Level> for Level {
+    fn from(level: codemap_diagnostic::Level) -> Self {
+        match level {
+            codemap_diagnostic::Level::Bug => Level::Bug,
+            codemap_diagnostic::Level::Fatal => Level::Fatal,
+            codemap_diagnostic::Level::Error => Level::Error,
+            codemap_diagnostic::Level::Warning => Level::Warning,
+            codemap_diagnostic::Level::Note => Level::Note,
+            codemap_diagnostic::Level::Help => Level::Help,
+        }
+    }
+}
+
+/// All diagnostics for a build error. Errors that don't come with diagnostics (such as a missing
+/// file) are reported as a diagnostic without spans.
+pub fn diagnostics(err: &ParserError, codemap: &CodeMap) -> Vec<Diagnostic> {
+    let mut diagnostics = match err {
+        ParserError::Diagnosis(diagnostics)
+        | ParserError::ModelBuildingError(ModelBuildingError::Diagnosis(diagnostics)) => {
+            diagnostics
+                .iter()
+                .map(|diagnostic| convert(diagnostic, codemap))
+                .collect()
+        }
+        ParserError::ModelBuildingError(ModelBuildingError::TSJSParsingError(error)) => {
+            vec![Diagnostic::spanless(error.to_string())]
+        }
+        _ => vec![Diagnostic::spanless(err.to_string())],
+    };
+
+    diagnostics.sort_by(|a, b| {
+        let key = |diagnostic: &Diagnostic| {
+            diagnostic
+                .primary_span()
+                .map(|span| (span.file_name.clone(), span.start))
+        };
+        key(a).cmp(&key(b))
+    });
+
+    diagnostics
+}
+
+pub(crate) fn convert(
+    diagnostic: &codemap_diagnostic::Diagnostic,
+    codemap: &CodeMap,
+) -> Diagnostic {
+    let spans = diagnostic
+        .spans
+        .iter()
+        .map(|span_label| {
+            let span_loc = codemap.look_up_span(span_label.span);
+            Span {
+                file_name: span_loc.file.name().to_string(),
+                start: Position {
+                    line: span_loc.begin.line,
+                    column: span_loc.begin.column,
+                },
+                end: Position {
+                    line: span_loc.end.line,
+                    column: span_loc.end.column,
+                },
+                label: span_label.label.clone(),
+                is_primary: span_label.style == SpanStyle::Primary,
+            }
+        })
+        .collect();
+
+    Diagnostic {
+        level: diagnostic.level.into(),
+        code: diagnostic.code.clone(),
+        message: diagnostic.message.clone(),
+        spans,
+        is_error: diagnostic.level == codemap_diagnostic::Level::Error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    fn check(src: &str) -> Vec<Diagnostic> {
+        futures::executor::block_on(crate::check_system_from_str(
+            src,
+            "index.exo".to_string(),
+            vec![Box::new(
+                postgres_builder::PostgresSubsystemBuilder::default(),
+            )],
+        ))
+    }
+
+    #[multiplatform_test]
+    fn duplicate_fields() {
+        let src = r#"@postgres
+module UserModule {
+  type User {
+    @pk id: Int = autoIncrement()
+    name: String
+    name: String
+    email: String
+    email: String
+  }
+}"#;
+
+        let diagnostics = check(src);
+
+        let summary: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.code.as_deref(),
+                    diagnostic.message.as_str(),
+                    diagnostic
+                        .spans
+                        .iter()
+                        .map(|span| {
+                            (
+                                span.start.line,
+                                span.start.column,
+                                span.label.as_deref(),
+                                span.is_primary,
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Some("C000"),
+                    "Duplicate field: name",
+                    vec![
+                        (4, 4, Some("first defined here"), true),
+                        (5, 4, Some("again defined here"), false)
+                    ]
+                ),
+                (
+                    Some("C000"),
+                    "Duplicate field: email",
+                    vec![
+                        (6, 4, Some("first defined here"), true),
+                        (7, 4, Some("again defined here"), false)
+                    ]
+                ),
+            ]
+        );
+        assert!(diagnostics.iter().all(|diagnostic| {
+            diagnostic.is_error
+                && diagnostic.level == Level::Error
+                && diagnostic
+                    .spans
+                    .iter()
+                    .all(|span| span.file_name == "index.exo")
+        }));
+
+        // The same source must always produce the same diagnostics
+        assert_eq!(diagnostics, check(src));
+    }
+
+    #[multiplatform_test]
+    fn valid_model() {
+        let src = r#"@postgres
+module UserModule {
+  type User {
+    @pk id: Int = autoIncrement()
+    name: String
+  }
+}"#;
+
+        assert_eq!(check(src), vec![]);
+    }
+}
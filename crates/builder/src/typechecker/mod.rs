@@ -119,6 +119,51 @@ fn populate_annotation_env(
                 ]),
             },
         ),
+        (
+            "concurrencyLimit",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Method],
+                no_params: false,
+                single_params: true,
+                mapped_params: Some(&[
+                    MappedAnnotationParamSpec {
+                        name: "max",
+                        optional: false,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "queue",
+                        optional: true,
+                    },
+                ]),
+            },
+        ),
+        (
+            "responseHeaders",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Method],
+                no_params: false,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
+        (
+            "route",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Method],
+                no_params: false,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
+        (
+            "redirects",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Module],
+                no_params: false,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
         (
             "cookie",
             AnnotationSpec {
@@ -137,6 +182,15 @@ fn populate_annotation_env(
                 mapped_params: None,
             },
         ),
+        (
+            "requireEnv",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Module],
+                no_params: false,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
         #[cfg(feature = "test-context")]
         (
             "test",
@@ -174,6 +228,33 @@ fn populate_annotation_env(
                 mapped_params: None,
             },
         ),
+        (
+            "onError",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Interceptor],
+                no_params: false,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
+        (
+            "deployment",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Field],
+                no_params: true,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
+        (
+            "flag",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Field],
+                no_params: true,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
         (
             "header",
             AnnotationSpec {
@@ -197,7 +278,16 @@ fn populate_annotation_env(
             AnnotationSpec {
                 targets: &[AnnotationTarget::Field],
                 no_params: true,
-                single_params: false,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
+        (
+            "clientCert",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Field],
+                no_params: true,
+                single_params: true,
                 mapped_params: None,
             },
         ),
@@ -219,6 +309,15 @@ fn populate_annotation_env(
                 mapped_params: None,
             },
         ),
+        (
+            "webhook",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Field],
+                no_params: true,
+                single_params: true,
+                mapped_params: None,
+            },
+        ),
     ];
 
     for builder in subsystem_builders.iter() {
@@ -102,6 +102,7 @@ impl TypecheckFrom<AstEnum<Untyped>> for AstEnum<Typed> {
         AstEnum {
             name: untyped.name.clone(),
             fields: untyped.fields.iter().map(AstEnumField::shallow).collect(),
+            annotations: AnnotationMap::new(&untyped.annotations),
             doc_comments: untyped.doc_comments.clone(),
             span: untyped.span,
         }
@@ -109,12 +110,18 @@ impl TypecheckFrom<AstEnum<Untyped>> for AstEnum<Typed> {
 
     fn pass(
         &mut self,
-        _type_env: &MappedArena<Type>,
-        _annotation_env: &HashMap<String, AnnotationSpec>,
-        _scope: &Scope,
-        _errors: &mut Vec<Diagnostic>,
+        type_env: &MappedArena<Type>,
+        annotation_env: &HashMap<String, AnnotationSpec>,
+        scope: &Scope,
+        errors: &mut Vec<Diagnostic>,
     ) -> bool {
-        false
+        self.annotations.pass(
+            AnnotationTarget::Enum,
+            type_env,
+            annotation_env,
+            scope,
+            errors,
+        )
     }
 }
 
@@ -23,6 +23,9 @@ use crate::ast::ast_types::{
 
 use super::{Scope, Type, TypecheckFrom, annotation_map::AnnotationMapImpl};
 
+/// Module annotations handled by the core (rather than a subsystem)
+const CORE_MODULE_ANNOTATIONS: &[&str] = &["requireEnv"];
+
 fn typed<U, T: TypecheckFrom<U>>(untyped: &[U]) -> Vec<T> {
     untyped.iter().map(|u| T::shallow(u)).collect()
 }
@@ -95,7 +98,15 @@ impl TypecheckFrom<AstModule<Untyped>> for AstModule<Typed> {
             errors,
         );
 
-        if self.annotations.annotations.is_empty() {
+        // Annotations such as `@requireEnv` may accompany the subsystem annotation
+        let subsystem_annotation_count = self
+            .annotations
+            .annotations
+            .keys()
+            .filter(|name| !CORE_MODULE_ANNOTATIONS.contains(&name.as_str()))
+            .count();
+
+        if subsystem_annotation_count == 0 {
             errors.push(Diagnostic {
                 level: Level::Error,
                 message: format!(
@@ -107,7 +118,7 @@ impl TypecheckFrom<AstModule<Untyped>> for AstModule<Typed> {
             })
         }
 
-        if self.annotations.annotations.len() > 1 {
+        if subsystem_annotation_count > 1 {
             errors.push(Diagnostic {
                 level: Level::Error,
                 message: format!(
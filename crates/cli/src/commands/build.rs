@@ -91,6 +91,10 @@ pub(crate) async fn build_system_with_static_builders(
         Box::new(postgres_builder::PostgresSubsystemBuilder::default()),
         Box::new(deno_builder::DenoSubsystemBuilder::default()),
         Box::new(wasm_builder::WasmSubsystemBuilder::default()),
+        Box::new(redis_builder::RedisSubsystemBuilder::default()),
+        Box::new(search_builder::SearchSubsystemBuilder::default()),
+        Box::new(messaging_builder::MessagingSubsystemBuilder::default()),
+        Box::new(llm_builder::LlmSubsystemBuilder::default()),
     ];
 
     builder::build_system(
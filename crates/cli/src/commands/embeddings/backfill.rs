@@ -0,0 +1,327 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use clap::{Arg, ArgAction, Command};
+use futures::future::join_all;
+use serde_json::{Map, Value};
+
+use common::{
+    context::RequestContext,
+    env_const::get_graphql_http_path,
+    http::{MemoryRequestHead, MemoryRequestPayload},
+    operation_payload::OperationsPayload,
+    router::{PlainRequestPayload, Router},
+};
+use core_plugin_shared::{
+    serializable_system::SerializableSystem, system_serializer::SystemSerializer,
+};
+use exo_env::Environment;
+use llm_model::{embedding::EmbeddingField, subsystem::LlmSubsystem};
+use system_router::{SystemRouter, create_system_router_from_system};
+
+use crate::commands::{
+    command::{CommandDefinition, default_model_file, get},
+    schema::util::create_system,
+    util::use_ir_arg,
+};
+use crate::config::Config;
+
+const DEFAULT_BATCH_SIZE: usize = 16;
+
+pub(super) struct BackfillCommandDefinition {}
+
+#[async_trait]
+impl CommandDefinition for BackfillCommandDefinition {
+    fn command(&self) -> Command {
+        Command::new("backfill")
+            .about("Compute the embeddings of existing entities for @embedding fields")
+            .arg(
+                Arg::new("field")
+                    .help("Only backfill the given field (such as `Document.contentVector`)")
+                    .long("field")
+                    .required(false)
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("batch-size")
+                    .help("Number of entities to process at a time (their embeddings are computed concurrently). Default: 16")
+                    .long("batch-size")
+                    .value_parser(clap::value_parser!(usize))
+                    .required(false)
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("context")
+                    .help("Context values to use for the update mutations, as JSON (such as `{\"AuthContext\": {\"role\": \"admin\"}}`)")
+                    .long("context")
+                    .required(false)
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("force")
+                    .help("Recompute embeddings even if already present")
+                    .long("force")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(use_ir_arg())
+    }
+
+    /// Compute missing embeddings by running the embedding query and the update mutation of each
+    /// entity, just like the server does when an entity is created or updated
+    async fn execute(
+        &self,
+        matches: &clap::ArgMatches,
+        _config: &Config,
+        env: Arc<dyn Environment>,
+    ) -> Result<()> {
+        let use_ir: bool = matches.get_flag("use-ir");
+        let force: bool = matches.get_flag("force");
+        let field_filter: Option<String> = get(matches, "field");
+        let batch_size: usize = get(matches, "batch-size").unwrap_or(DEFAULT_BATCH_SIZE);
+        let context_override: Value = match get::<String>(matches, "context") {
+            Some(context) => serde_json::from_str(&context)
+                .map_err(|e| anyhow!("Invalid JSON for --context: {e}"))?,
+            None => Value::Object(Map::new()),
+        };
+        if batch_size == 0 {
+            bail!("The batch size must be a positive integer");
+        }
+
+        let model_path: PathBuf = default_model_file();
+        let serialized_system = create_system(&model_path, None, use_ir).await?;
+
+        let embedding_fields: Vec<EmbeddingField> = embedding_fields(&serialized_system)?
+            .into_iter()
+            .filter(|field| match &field_filter {
+                Some(filter) => {
+                    *filter == format!("{}.{}", field.target.entity_name, field.target.vector_field)
+                }
+                None => true,
+            })
+            .collect();
+
+        if embedding_fields.is_empty() {
+            match field_filter {
+                Some(filter) => bail!("'{filter}' is not an @embedding field"),
+                None => bail!("The model has no @embedding fields"),
+            }
+        }
+
+        let router = create_system_router_from_system(
+            serialized_system,
+            server_common::create_static_loaders(),
+            env.clone(),
+        )
+        .await?;
+
+        for embedding_field in embedding_fields.iter() {
+            let backfilled = backfill(
+                embedding_field,
+                batch_size,
+                force,
+                &context_override,
+                &router,
+                env.as_ref(),
+            )
+            .await?;
+
+            println!(
+                "Computed {backfilled} embedding(s) for '{}.{}'",
+                embedding_field.target.entity_name, embedding_field.target.vector_field
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn embedding_fields(serialized_system: &SerializableSystem) -> Result<Vec<EmbeddingField>> {
+    let Some(graphql) = serialized_system
+        .subsystems
+        .iter()
+        .find(|subsystem| subsystem.id == "llm")
+        .and_then(|subsystem| subsystem.graphql.as_ref())
+    else {
+        return Ok(vec![]);
+    };
+
+    Ok(LlmSubsystem::deserialize(graphql.0.clone())?.embedding_fields)
+}
+
+/// Walk through all entities (ordered by the primary key) and compute the embedding of those
+/// without one (or all, if `force` is set). Returns the number of computed embeddings.
+async fn backfill(
+    embedding_field: &EmbeddingField,
+    batch_size: usize,
+    force: bool,
+    context_override: &Value,
+    router: &SystemRouter,
+    env: &dyn Environment,
+) -> Result<usize> {
+    let target = &embedding_field.target;
+    let page_query = format!(
+        "query($limit: Int!, $offset: Int!) {{ entities: {collection}(orderBy: {{{pk}: ASC}}, limit: $limit, offset: $offset) {{ {pk} {vector} {sources} }} }}",
+        collection = embedding_field.collection_query_name,
+        pk = target.pk_field,
+        vector = target.vector_field,
+        sources = embedding_field.source_fields.join(" "),
+    );
+
+    let mut backfilled = 0;
+    let mut offset = 0;
+
+    loop {
+        let variables = Map::from_iter([
+            ("limit".to_string(), Value::from(batch_size)),
+            ("offset".to_string(), Value::from(offset)),
+        ]);
+        let data = run_operation(&page_query, variables, context_override, router, env).await?;
+        let entities = match data.get("entities") {
+            Some(Value::Array(entities)) => entities,
+            _ => bail!(
+                "Unexpected response from '{}'",
+                embedding_field.collection_query_name
+            ),
+        };
+
+        let pending: Vec<(&Value, String)> = entities
+            .iter()
+            .filter_map(Value::as_object)
+            .filter(|entity| force || entity.get(&target.vector_field).is_none_or(Value::is_null))
+            .filter_map(|entity| {
+                let pk = entity.get(&target.pk_field)?;
+                Some((pk, embedding_field.source_text(entity)?))
+            })
+            .collect();
+
+        let results = join_all(pending.iter().map(|(pk, text)| {
+            embed_and_store(embedding_field, pk, text, context_override, router, env)
+        }))
+        .await;
+        for result in results {
+            result?;
+            backfilled += 1;
+        }
+
+        if entities.len() < batch_size {
+            break;
+        }
+        offset += batch_size;
+    }
+
+    Ok(backfilled)
+}
+
+async fn embed_and_store(
+    embedding_field: &EmbeddingField,
+    pk: &Value,
+    text: &str,
+    context_override: &Value,
+    router: &SystemRouter,
+    env: &dyn Environment,
+) -> Result<()> {
+    let target = &embedding_field.target;
+
+    let embed_query = format!(
+        "query($text: String!) {{ embedding: {}(text: $text) }}",
+        embedding_field.model_query_name
+    );
+    let data = run_operation(
+        &embed_query,
+        Map::from_iter([("text".to_string(), Value::String(text.to_string()))]),
+        context_override,
+        router,
+        env,
+    )
+    .await?;
+    let embedding = match data.get("embedding") {
+        Some(embedding @ Value::Array(_)) => embedding,
+        _ => bail!(
+            "'{}' did not return an embedding",
+            embedding_field.model_query_name
+        ),
+    };
+
+    // JSON numbers and strings (used by primary keys) are valid GraphQL literals, which avoids
+    // having to name the input types
+    let update_mutation = format!(
+        "mutation {{ {mutation}({pk}: {pk_value}, data: {{{vector}: {embedding}}}) {{ {pk} }} }}",
+        mutation = target.update_mutation_name,
+        pk = target.pk_field,
+        pk_value = pk,
+        vector = target.vector_field,
+    );
+    run_operation(&update_mutation, Map::new(), context_override, router, env).await?;
+
+    Ok(())
+}
+
+/// Run an operation as an internal request and return its data.
+///
+/// Internal requests bypass the access control of module methods (such as the model query), but
+/// not that of Postgres entities, which sees the context values in `context_override`.
+async fn run_operation(
+    query: &str,
+    variables: Map<String, Value>,
+    context_override: &Value,
+    router: &SystemRouter,
+    env: &dyn Environment,
+) -> Result<Value> {
+    let operations_payload = OperationsPayload {
+        operation_name: None,
+        query: Some(query.to_string()),
+        variables: Some(variables),
+        query_hash: None,
+    };
+    let body = operations_payload.to_json()?;
+
+    let request_head = || {
+        MemoryRequestHead::new(
+            HashMap::new(),
+            HashMap::new(),
+            http::Method::POST,
+            get_graphql_http_path(env),
+            Value::Null,
+            None,
+        )
+    };
+
+    // The base context owns the transaction, which must be finalized once the operation completes
+    let base_request = MemoryRequestPayload::new(Value::Null, request_head());
+    let request = MemoryRequestPayload::new(body, request_head());
+    let jwt_authenticator = None;
+    let base_context = RequestContext::new(&base_request, vec![], router, &jwt_authenticator, env);
+    let overridden_context = base_context.with_override(context_override.clone());
+    let request_context = overridden_context.with_request(&request);
+
+    let result = match router
+        .route(&PlainRequestPayload::internal(&request_context))
+        .await
+    {
+        Some(response) if response.status_code.is_success() => {
+            let mut body = response.body.to_json().await?;
+
+            match body.get("errors") {
+                Some(errors) => Err(anyhow!("{errors}")),
+                None => Ok(body["data"].take()),
+            }
+        }
+        Some(response) => Err(anyhow!("Responded with status {}", response.status_code)),
+        None => Err(anyhow!("No route for the operation")),
+    };
+
+    base_context.finalize_transaction(result.is_ok()).await?;
+
+    result
+}
@@ -0,0 +1,147 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::{Arg, ArgAction, Command};
+use colored::Colorize;
+use common::env_processing::EnvProcessing;
+use core_router::env_validation::validate_env;
+use exo_env::{EnvValue, Environment, LayeredEnvironment, Profile};
+
+use std::{path::PathBuf, sync::Arc};
+
+use crate::commands::{
+    command::{CommandDefinition, default_model_file, get},
+    schema::util::create_system,
+    util::use_ir_arg,
+};
+use crate::config::Config;
+
+pub struct EnvCommandDefinition {}
+
+#[async_trait]
+impl CommandDefinition for EnvCommandDefinition {
+    fn command(&self) -> clap::Command {
+        Command::new("env")
+            .about("Show the effective env values and where they come from")
+            .arg(
+                Arg::new("profile")
+                    .help("The profile (such as dev, test, or production) to pick .env.{profile}* files. Default: the value of EXO_ENV")
+                    .long("profile")
+                    .required(false)
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("show-values")
+                    .help("Show the values (by default, values are masked, since they may contain secrets)")
+                    .long("show-values")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(use_ir_arg())
+    }
+
+    /// The command loads env files itself (for the requested profile)
+    fn env_processing(&self, _env: &dyn Environment) -> EnvProcessing {
+        EnvProcessing::DoNotProcess
+    }
+
+    async fn execute(
+        &self,
+        matches: &clap::ArgMatches,
+        _config: &Config,
+        env: Arc<dyn Environment>,
+    ) -> Result<()> {
+        let profile = get::<String>(matches, "profile")
+            .map(Profile::new)
+            .or_else(|| Profile::from_env(env.as_ref()));
+        let show_values = matches.get_flag("show-values");
+        let use_ir: bool = matches.get_flag("use-ir");
+
+        let model_path: PathBuf = default_model_file();
+
+        // The model declares defaults and requirements, but we still want to show the rest of
+        // the values if it doesn't build (the builder will have reported the errors)
+        let (env_defaults, env_requirements) = match create_system(&model_path, None, use_ir).await
+        {
+            Ok(system) => (system.env_defaults, system.env_requirements),
+            Err(e) => {
+                eprintln!("{}", format!("Could not load the model: {e}").yellow());
+                Default::default()
+            }
+        };
+
+        let env = LayeredEnvironment::load(profile).with_defaults(env_defaults);
+
+        match env.profile() {
+            Some(profile) => println!("Profile: {}", profile.to_string().blue()),
+            None => println!(
+                "Profile: {}",
+                "<none> (set EXO_ENV or use --profile)".dimmed()
+            ),
+        }
+
+        let files: Vec<_> = env.files().map(|file| file.display().to_string()).collect();
+        if files.is_empty() {
+            println!("Env files: {}", "<none>".dimmed());
+        } else {
+            println!("Env files: {}", files.join(", "));
+        }
+
+        let values = env.effective_values();
+        if values.is_empty() {
+            println!("No values defined in env files or model defaults");
+        } else {
+            println!();
+            let key_width = values
+                .iter()
+                .map(|value| value.key.len())
+                .max()
+                .unwrap_or(0);
+            for value in values.iter() {
+                println!(
+                    "{:key_width$}  {}  {}",
+                    value.key.bold(),
+                    display_value(value, show_values),
+                    display_source(value).dimmed()
+                );
+            }
+        }
+
+        if let Err(report) = validate_env(&env_requirements, &env) {
+            println!();
+            println!("{}", "Required env vars with problems:".red());
+            print!("{report}");
+        }
+
+        Ok(())
+    }
+}
+
+fn display_value(value: &EnvValue, show_values: bool) -> String {
+    if show_values {
+        value.value.clone()
+    } else {
+        "****".to_string()
+    }
+}
+
+fn display_source(value: &EnvValue) -> String {
+    if value.overridden.is_empty() {
+        format!("({})", value.source)
+    } else {
+        let overridden: Vec<_> = value
+            .overridden
+            .iter()
+            .map(|source| source.to_string())
+            .collect();
+        format!("({}; overrides {})", value.source, overridden.join(", "))
+    }
+}
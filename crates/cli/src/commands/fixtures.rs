@@ -0,0 +1,73 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Command;
+use exo_env::Environment;
+
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::commands::{
+    command::{CommandDefinition, default_model_file, get, output_arg},
+    schema::util::create_system,
+    util::use_ir_arg,
+};
+use crate::config::Config;
+
+pub struct FixturesCommandDefinition {}
+
+#[async_trait]
+impl CommandDefinition for FixturesCommandDefinition {
+    fn command(&self) -> clap::Command {
+        Command::new("fixtures")
+            .about("Generate fixture factories for the entities in the model")
+            .arg(
+                output_arg().long_help(
+                    "Output file for the fixture factories. Default: generated/fixtures.ts",
+                ),
+            )
+            .arg(use_ir_arg())
+    }
+
+    async fn execute(
+        &self,
+        matches: &clap::ArgMatches,
+        _config: &Config,
+        _env: Arc<dyn Environment>,
+    ) -> Result<()> {
+        let use_ir: bool = matches.get_flag("use-ir");
+
+        let model_path: PathBuf = default_model_file();
+
+        let serialized_system = create_system(&model_path, None, use_ir).await?;
+
+        let fixtures = testing::generate_fixtures(&serialized_system)?;
+
+        let output: PathBuf = match get(matches, "output") {
+            Some(output) => output,
+            None => {
+                fs::create_dir_all("generated")?;
+
+                Path::new("generated/fixtures.ts").to_path_buf()
+            }
+        };
+
+        File::create(&output)?.write_all(fixtures.as_bytes())?;
+
+        println!("Fixture factories written to {}", output.display());
+
+        Ok(())
+    }
+}
@@ -13,7 +13,11 @@ pub(crate) mod build;
 pub(crate) mod command;
 pub(crate) mod deploy;
 pub(crate) mod dev;
+pub(crate) mod embeddings;
+pub(crate) mod env;
+pub(crate) mod fixtures;
 pub(crate) mod graphql;
+pub(crate) mod module_test;
 pub(crate) mod new;
 pub(crate) mod playground;
 pub(crate) mod schema;
@@ -0,0 +1,168 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use clap::{Arg, ArgMatches, Command};
+use deno_graphql_builder::system_builder::bundle_script;
+use deno_graphql_resolver::{MockExographClient, MockQuery, ModuleTestRunner};
+use exo_env::Environment;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::command::CommandDefinition;
+use crate::config::Config;
+
+/// Key marking an argument as an injected shim (for example, `{ "$shim": "Exograph" }`)
+const SHIM_ARG_KEY: &str = "$shim";
+
+pub struct ModuleTestCommandDefinition {}
+
+/// A file describing unit tests for functions exported by a module
+#[derive(Deserialize, Debug)]
+struct ModuleTestFile {
+    /// Path to the module, relative to the test file
+    module: PathBuf,
+    tests: Vec<ModuleTestCase>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModuleTestCase {
+    name: String,
+    function: String,
+    #[serde(default)]
+    args: Vec<Value>,
+    #[serde(default)]
+    mocks: Vec<MockQuery>,
+    #[serde(default)]
+    expect: Option<Value>,
+    #[serde(default)]
+    expect_error: Option<String>,
+}
+
+#[async_trait]
+impl CommandDefinition for ModuleTestCommandDefinition {
+    fn command(&self) -> Command {
+        Command::new("module-test")
+            .about("Unit test module functions without a running server")
+            .arg(
+                Arg::new("files")
+                    .help("Test files describing the functions to call, the mocked queries, and the expected results.")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .num_args(1..)
+                    .required(true)
+                    .index(1),
+            )
+    }
+
+    async fn execute(
+        &self,
+        matches: &ArgMatches,
+        _config: &Config,
+        env: Arc<dyn Environment>,
+    ) -> Result<()> {
+        let files: Vec<PathBuf> = matches
+            .get_many::<PathBuf>("files")
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+
+        let mut failures = 0;
+        let mut total = 0;
+
+        for file in files {
+            let (passed, count) = run_test_file(&file, env.clone()).await?;
+            failures += count - passed;
+            total += count;
+        }
+
+        println!("{} passed, {} failed", total - failures, failures);
+
+        if failures > 0 {
+            Err(anyhow!("{failures} module test(s) failed"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+async fn run_test_file(file: &Path, env: Arc<dyn Environment>) -> Result<(usize, usize)> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read test file {}", file.display()))?;
+    let test_file: ModuleTestFile = serde_saphyr::from_str(&content)
+        .map_err(|e| anyhow!("Invalid test file {}: {e}", file.display()))?;
+
+    let module_path = file
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(&test_file.module);
+    let (script_path, script) = bundle_script(&module_path).await?;
+
+    let runner = ModuleTestRunner::new(script_path, script, env);
+
+    let mut passed = 0;
+    for test in test_file.tests.iter() {
+        match run_test(&runner, test).await {
+            Ok(()) => {
+                println!("PASS {} ({})", test.name, file.display());
+                passed += 1;
+            }
+            Err(e) => println!("FAIL {} ({}): {e}", test.name, file.display()),
+        }
+    }
+
+    Ok((passed, test_file.tests.len()))
+}
+
+async fn run_test(runner: &ModuleTestRunner, test: &ModuleTestCase) -> Result<()> {
+    let args = test.args.iter().map(to_arg).collect();
+    let client = MockExographClient::new(test.mocks.clone());
+
+    let result = runner.call(&test.function, args, &client).await;
+
+    match (result, &test.expect_error) {
+        (Ok(value), None) => match &test.expect {
+            Some(expected) if expected != &value => {
+                Err(anyhow!("expected {expected}, but got {value}"))
+            }
+            _ => Ok(()),
+        },
+        (Ok(value), Some(expected_error)) => Err(anyhow!(
+            "expected error '{expected_error}', but got {value}"
+        )),
+        (Err(e), None) => Err(anyhow!("{e}")),
+        (Err(e), Some(expected_error)) => {
+            let message = e.to_string();
+            if message.contains(expected_error.as_str()) {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "expected error '{expected_error}', but got '{message}'"
+                ))
+            }
+        }
+    }
+}
+
+fn to_arg(value: &Value) -> exo_deno::Arg {
+    match value {
+        Value::Object(map) if map.len() == 1 => match map.get(SHIM_ARG_KEY) {
+            Some(Value::String(shim)) => exo_deno::Arg::Shim(shim.clone()),
+            _ => exo_deno::Arg::Serde(value.clone()),
+        },
+        _ => exo_deno::Arg::Serde(value.clone()),
+    }
+}
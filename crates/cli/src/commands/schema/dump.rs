@@ -0,0 +1,68 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Command;
+use exo_env::Environment;
+use exo_sql::snapshot::{SnapshotScope, dump_snapshot};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::commands::command::{
+    CommandDefinition, database_arg, database_value, default_model_file, get, migration_scope_arg,
+    migration_scope_value, output_arg,
+};
+use crate::commands::util::{compute_migration_scope, use_ir_arg};
+use crate::config::Config;
+
+use super::util;
+
+pub(super) struct DumpCommandDefinition {}
+
+#[async_trait]
+impl CommandDefinition for DumpCommandDefinition {
+    fn command(&self) -> clap::Command {
+        Command::new("dump")
+            .about("Create a snapshot of the database for a Exograph model (using pg_dump)")
+            .arg(database_arg())
+            .arg(output_arg())
+            .arg(use_ir_arg())
+            .arg(migration_scope_arg())
+    }
+
+    /// Dump the schemas used by the model (along with their data) in the pg_dump custom format
+    async fn execute(
+        &self,
+        matches: &clap::ArgMatches,
+        _config: &Config,
+        env: Arc<dyn Environment>,
+    ) -> Result<()> {
+        let model: PathBuf = default_model_file();
+        let database_url = util::database_url(database_value(matches), env.as_ref())?;
+        let output: Option<PathBuf> = get(matches, "output");
+        let use_ir: bool = matches.get_flag("use-ir");
+        let scope: Option<String> = migration_scope_value(matches);
+
+        let database = util::extract_postgres_database(&model, None, use_ir).await?;
+        let scope = SnapshotScope::from_database(&database, &compute_migration_scope(scope));
+
+        dump_snapshot(&database_url, &scope, output.as_deref())?;
+
+        if let Some(output) = output {
+            eprintln!(
+                "Created a snapshot of schemas {} in {}",
+                scope.schemas.join(", "),
+                output.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -4,8 +4,8 @@ use std::io::Write;
 use exo_sql::schema::column_spec::ColumnSpec;
 use exo_sql::schema::table_spec::TableSpec;
 use exo_sql::{
-    FloatBits, FloatColumnType, IntBits, IntColumnType, NumericColumnType, StringColumnType,
-    TimeColumnType, TimestampColumnType, VectorColumnType,
+    ColumnStorage, FloatBits, FloatColumnType, IntBits, IntColumnType, NumericColumnType,
+    StringColumnType, TimeColumnType, TimestampColumnType, VectorColumnType,
 };
 
 use super::{
@@ -67,6 +67,13 @@ impl ModelImporter<TableSpec, FieldImport> for ColumnSpec {
             all_annotations.push(col_annot);
         }
 
+        // `@storage` is allowed only on the types whose values may be large
+        if ["String", "Blob", "Json"].contains(&data_type.as_str())
+            && let Some(storage_annot) = storage_annotation(&self.storage)
+        {
+            all_annotations.push(storage_annot);
+        }
+
         let default_value = self.default_value.as_ref().and_then(|v| v.to_model());
 
         Ok(FieldImport {
@@ -130,6 +137,27 @@ fn type_annotation(physical_type: &dyn exo_sql::PhysicalColumnType) -> String {
     }
 }
 
+fn storage_annotation(storage: &ColumnStorage) -> Option<String> {
+    let strategy = storage
+        .strategy
+        .map(|strategy| strategy.sql().to_lowercase());
+
+    match (strategy, storage.compression) {
+        (None, None) => None,
+        (Some(strategy), None) => Some(format!("@storage(\"{strategy}\")")),
+        (strategy, compression) => {
+            let params = strategy
+                .map(|strategy| format!("strategy=\"{strategy}\""))
+                .into_iter()
+                .chain(
+                    compression.map(|compression| format!("compression=\"{}\"", compression.sql())),
+                )
+                .collect::<Vec<_>>();
+            Some(format!("@storage({})", params.join(", ")))
+        }
+    }
+}
+
 impl ImportWriter for FieldImport {
     fn write_to(self, writer: &mut (dyn Write + Send)) -> Result<()> {
         const INDENT: &str = "  ";
@@ -8,27 +8,32 @@
 // by the Apache License, Version 2.0.
 
 use self::{
-    create::CreateCommandDefinition, import::ImportCommandDefinition,
-    migrate::MigrateCommandDefinition, verify::VerifyCommandDefinition,
+    create::CreateCommandDefinition, dump::DumpCommandDefinition, import::ImportCommandDefinition,
+    migrate::MigrateCommandDefinition, restore::RestoreCommandDefinition,
+    verify::VerifyCommandDefinition,
 };
 
 use super::command::SubcommandDefinition;
 
 pub(crate) mod create;
+pub(crate) mod dump;
 pub(crate) mod import;
 pub(crate) mod migrate;
+pub(crate) mod restore;
 pub(crate) mod util;
 pub(crate) mod verify;
 
 pub fn command_definition() -> SubcommandDefinition {
     SubcommandDefinition::new(
         "schema",
-        "Create, migrate, verify, and import  database schema",
+        "Create, migrate, verify, import, dump, and restore database schema",
         vec![
             Box::new(CreateCommandDefinition {}),
             Box::new(MigrateCommandDefinition {}),
             Box::new(VerifyCommandDefinition {}),
             Box::new(ImportCommandDefinition {}),
+            Box::new(DumpCommandDefinition {}),
+            Box::new(RestoreCommandDefinition {}),
         ],
     )
 }
@@ -0,0 +1,63 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::{Arg, Command};
+use exo_env::Environment;
+use exo_sql::snapshot::restore_snapshot;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::commands::command::{CommandDefinition, database_arg, database_value, get_required};
+use crate::config::Config;
+
+use super::util;
+
+pub(super) struct RestoreCommandDefinition {}
+
+#[async_trait]
+impl CommandDefinition for RestoreCommandDefinition {
+    fn command(&self) -> clap::Command {
+        Command::new("restore")
+            .about("Restore a snapshot created by `exo schema dump` (using pg_restore)")
+            .arg(
+                Arg::new("snapshot")
+                    .help("The snapshot file to restore")
+                    .required(true)
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .index(1),
+            )
+            .arg(database_arg())
+            .arg(
+                Arg::new("clean")
+                    .help("Drop objects in the snapshot before recreating them (needed unless the database is empty)")
+                    .long("clean")
+                    .required(false)
+                    .num_args(0),
+            )
+    }
+
+    async fn execute(
+        &self,
+        matches: &clap::ArgMatches,
+        _config: &Config,
+        env: Arc<dyn Environment>,
+    ) -> Result<()> {
+        let snapshot: PathBuf = get_required(matches, "snapshot")?;
+        let database_url = util::database_url(database_value(matches), env.as_ref())?;
+        let clean: bool = matches.get_flag("clean");
+
+        restore_snapshot(&database_url, &snapshot, clean)?;
+
+        eprintln!("Restored the snapshot {}", snapshot.display());
+
+        Ok(())
+    }
+}
@@ -43,12 +43,7 @@ pub(crate) async fn database_manager_from_env(
     transaction_mode: TransactionMode,
     env: &dyn Environment,
 ) -> Result<DatabaseClientManager, DatabaseError> {
-    let url = env
-        .get(EXO_POSTGRES_URL)
-        .or(env.get(DATABASE_URL))
-        .ok_or(DatabaseError::Config(format!(
-            "{EXO_POSTGRES_URL} or {DATABASE_URL} not set"
-        )))?;
+    let url = database_url_from_env(env)?;
     let pool_size = env
         .get(EXO_CONNECTION_POOL_SIZE)
         .and_then(|s| s.parse().ok());
@@ -59,6 +54,24 @@ pub(crate) async fn database_manager_from_env(
     DatabaseClientManager::from_url(&url, check_connection, pool_size, transaction_mode).await
 }
 
+pub(crate) fn database_url(
+    url: Option<String>,
+    env: &dyn Environment,
+) -> Result<String, DatabaseError> {
+    match url {
+        Some(url) => Ok(url),
+        None => database_url_from_env(env),
+    }
+}
+
+fn database_url_from_env(env: &dyn Environment) -> Result<String, DatabaseError> {
+    env.get(EXO_POSTGRES_URL)
+        .or(env.get(DATABASE_URL))
+        .ok_or(DatabaseError::Config(format!(
+            "{EXO_POSTGRES_URL} or {DATABASE_URL} not set"
+        )))
+}
+
 pub(crate) async fn create_system(
     model_file: impl AsRef<Path>,
     trusted_documents_dir: Option<&Path>,
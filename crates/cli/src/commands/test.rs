@@ -42,6 +42,13 @@ impl CommandDefinition for TestCommandDefinition {
                     .required(false)
                     .index(2),
             )
+            .arg(
+                Arg::new("access-coverage")
+                    .help("Record which access rules the tests evaluate and report the rules that no test exercises")
+                    .long("access-coverage")
+                    .required(false)
+                    .action(clap::ArgAction::SetTrue),
+            )
     }
 
     async fn execute(
@@ -52,6 +59,7 @@ impl CommandDefinition for TestCommandDefinition {
     ) -> Result<()> {
         let dir: PathBuf = get_required(matches, "dir")?;
         let pattern: Option<String> = get(matches, "pattern"); // glob pattern indicating tests to be executed
+        let access_coverage = matches.get_flag("access-coverage");
 
         let run_introspection_tests: bool = match std::env::var(EXO_RUN_INTROSPECTION_TESTS) {
             Ok(e) => match e.to_lowercase().as_str() {
@@ -79,6 +87,6 @@ impl CommandDefinition for TestCommandDefinition {
             std::env::set_var(EXO_ENV, "test");
         }
 
-        testing::run(&dir, &pattern, run_introspection_tests)
+        testing::run(&dir, &pattern, run_introspection_tests, access_coverage)
     }
 }
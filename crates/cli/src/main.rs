@@ -27,7 +27,11 @@ use commands::{
     command::{CommandDefinition, SubcommandDefinition},
     deploy,
     dev::DevCommandDefinition,
+    embeddings,
+    env::EnvCommandDefinition,
+    fixtures::FixturesCommandDefinition,
     graphql,
+    module_test::ModuleTestCommandDefinition,
     new::NewCommandDefinition,
     playground::PlaygroundCommandDefinition,
     schema,
@@ -76,9 +80,13 @@ async fn main() -> Result<()> {
             Box::new(deploy::command_definition()),
             Box::new(schema::command_definition()),
             Box::new(graphql::command_definition()),
+            Box::new(embeddings::command_definition()),
+            Box::new(FixturesCommandDefinition {}),
+            Box::new(EnvCommandDefinition {}),
             Box::new(PlaygroundCommandDefinition {}),
             Box::new(UpdateCommandDefinition {}),
             Box::new(TestCommandDefinition {}),
+            Box::new(ModuleTestCommandDefinition {}),
         ],
     );
 
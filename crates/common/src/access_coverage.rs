@@ -0,0 +1,250 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Coverage of access rules: which access expressions (and their branches) were evaluated, to
+//! find authorization rules that no test exercises.
+//!
+//! Setting `EXO_ACCESS_COVERAGE=true` makes the system router record the outcome of every access
+//! expression it evaluates in an [AccessCoverage]. Subsystems then turn the recorded outcomes into
+//! an [AccessCoverageReport] by walking their access rules (so that rules that were never
+//! evaluated show up as well).
+
+use std::{collections::HashMap, fmt::Display, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessOutcome {
+    /// The expression evaluated to true
+    Allowed,
+    /// The expression evaluated to false
+    Denied,
+    /// The expression was reduced to a filter (to be evaluated by the data source)
+    Residual,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeCounts {
+    pub allowed: u64,
+    pub denied: u64,
+    pub residual: u64,
+}
+
+impl OutcomeCounts {
+    pub fn total(&self) -> u64 {
+        self.allowed + self.denied + self.residual
+    }
+
+    fn add(&mut self, outcome: AccessOutcome) {
+        match outcome {
+            AccessOutcome::Allowed => self.allowed += 1,
+            AccessOutcome::Denied => self.denied += 1,
+            AccessOutcome::Residual => self.residual += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &OutcomeCounts) {
+        self.allowed += other.allowed;
+        self.denied += other.denied;
+        self.residual += other.residual;
+    }
+}
+
+/// The recorded outcomes, keyed by the identity of the evaluated expression node (its address in
+/// the loaded model, which stays put for the lifetime of the system)
+#[derive(Debug, Default)]
+pub struct AccessCoverage {
+    outcomes: Mutex<HashMap<usize, OutcomeCounts>>,
+}
+
+impl AccessCoverage {
+    pub fn record<T>(&self, node: &T, outcome: AccessOutcome) {
+        self.outcomes
+            .lock()
+            .unwrap()
+            .entry(node as *const T as usize)
+            .or_default()
+            .add(outcome);
+    }
+
+    pub fn counts<T>(&self, node: &T) -> OutcomeCounts {
+        self.outcomes
+            .lock()
+            .unwrap()
+            .get(&(node as *const T as usize))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessCoverageReport {
+    pub rules: Vec<RuleCoverage>,
+}
+
+/// The coverage of an access rule (such as the query rule of an entity)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleCoverage {
+    /// For example, `Todo.query`
+    pub rule: String,
+    /// The nodes of the rule's expression in pre-order (the first one is the whole expression)
+    pub branches: Vec<BranchCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchCoverage {
+    /// The source-like form of the (sub)expression, for example `AuthContext.role == "admin"`
+    pub expression: String,
+    /// The nesting level of the branch within the rule (0 for the whole expression)
+    pub depth: usize,
+    #[serde(flatten)]
+    pub counts: OutcomeCounts,
+}
+
+impl RuleCoverage {
+    pub fn is_evaluated(&self) -> bool {
+        self.branches
+            .first()
+            .is_some_and(|branch| branch.counts.total() > 0)
+    }
+
+    pub fn evaluated_branches(&self) -> usize {
+        self.branches
+            .iter()
+            .filter(|branch| branch.counts.total() > 0)
+            .count()
+    }
+}
+
+impl AccessCoverageReport {
+    /// Combine with a report of another run (against the same model), adding up the counts
+    pub fn merge(&mut self, other: AccessCoverageReport) {
+        for other_rule in other.rules {
+            match self
+                .rules
+                .iter_mut()
+                .find(|rule| rule.rule == other_rule.rule)
+            {
+                Some(rule) => {
+                    for (branch, other_branch) in rule.branches.iter_mut().zip(&other_rule.branches)
+                    {
+                        branch.counts.merge(&other_branch.counts);
+                    }
+                }
+                None => self.rules.push(other_rule),
+            }
+        }
+    }
+
+    pub fn unevaluated_rules(&self) -> impl Iterator<Item = &RuleCoverage> {
+        self.rules.iter().filter(|rule| !rule.is_evaluated())
+    }
+}
+
+impl Display for AccessCoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total_branches: usize = self.rules.iter().map(|rule| rule.branches.len()).sum();
+        let evaluated_branches: usize = self
+            .rules
+            .iter()
+            .map(|rule| rule.evaluated_branches())
+            .sum();
+        let evaluated_rules = self.rules.iter().filter(|rule| rule.is_evaluated()).count();
+
+        writeln!(
+            f,
+            "Access rules evaluated: {evaluated_rules}/{}, branches evaluated: {evaluated_branches}/{total_branches}",
+            self.rules.len()
+        )?;
+
+        for rule in self.unevaluated_rules() {
+            let expression = rule
+                .branches
+                .first()
+                .map(|branch| branch.expression.as_str())
+                .unwrap_or_default();
+            writeln!(f, "  never evaluated: {} ({expression})", rule.rule)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, counts: &[(u64, u64, u64)]) -> RuleCoverage {
+        RuleCoverage {
+            rule: name.to_string(),
+            branches: counts
+                .iter()
+                .enumerate()
+                .map(|(depth, (allowed, denied, residual))| BranchCoverage {
+                    expression: format!("branch{depth}"),
+                    depth,
+                    counts: OutcomeCounts {
+                        allowed: *allowed,
+                        denied: *denied,
+                        residual: *residual,
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn records_by_node() {
+        let coverage = AccessCoverage::default();
+        let (first, second) = (1, 2);
+
+        coverage.record(&first, AccessOutcome::Allowed);
+        coverage.record(&first, AccessOutcome::Denied);
+        coverage.record(&first, AccessOutcome::Allowed);
+
+        assert_eq!(
+            coverage.counts(&first),
+            OutcomeCounts {
+                allowed: 2,
+                denied: 1,
+                residual: 0
+            }
+        );
+        assert_eq!(coverage.counts(&second), OutcomeCounts::default());
+    }
+
+    #[test]
+    fn merges_reports() {
+        let mut report = AccessCoverageReport {
+            rules: vec![rule("Todo.query", &[(1, 0, 0), (0, 0, 0)])],
+        };
+
+        report.merge(AccessCoverageReport {
+            rules: vec![
+                rule("Todo.query", &[(0, 1, 0), (0, 0, 1)]),
+                rule("Todo.delete", &[(0, 0, 0)]),
+            ],
+        });
+
+        assert_eq!(
+            report.rules,
+            vec![
+                rule("Todo.query", &[(1, 1, 0), (0, 0, 1)]),
+                rule("Todo.delete", &[(0, 0, 0)]),
+            ]
+        );
+        assert_eq!(
+            report
+                .unevaluated_rules()
+                .map(|rule| rule.rule.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Todo.delete"]
+        );
+    }
+}
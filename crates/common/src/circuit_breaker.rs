@@ -0,0 +1,54 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Configuration of circuit breakers (around database client acquisition and upstream
+//! forwarding) through `EXO_CIRCUIT_BREAKER_THRESHOLD` and `EXO_CIRCUIT_BREAKER_COOLDOWN`.
+
+use std::{sync::Arc, time::Duration};
+
+use exo_env::{EnvError, Environment};
+pub use exo_sql::{CircuitBreaker, CircuitBreakerStats, CircuitOpenError};
+
+use crate::env_const::{EXO_CIRCUIT_BREAKER_COOLDOWN, EXO_CIRCUIT_BREAKER_THRESHOLD};
+
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+/// A circuit breaker for the named dependency (or `None` if circuit breaking isn't enabled)
+pub fn circuit_breaker_from_env(
+    name: &str,
+    env: &dyn Environment,
+) -> Result<Option<Arc<CircuitBreaker>>, EnvError> {
+    let Some(threshold) = env.get(EXO_CIRCUIT_BREAKER_THRESHOLD) else {
+        return Ok(None);
+    };
+    let threshold = threshold
+        .parse::<u32>()
+        .ok()
+        .filter(|threshold| *threshold > 0)
+        .ok_or_else(|| EnvError::InvalidEnum {
+            env_key: EXO_CIRCUIT_BREAKER_THRESHOLD,
+            env_value: threshold.clone(),
+            message: "Must be a positive number".to_string(),
+        })?;
+
+    let cool_down_secs = match env.get(EXO_CIRCUIT_BREAKER_COOLDOWN) {
+        Some(value) => value.parse::<u64>().map_err(|_| EnvError::InvalidEnum {
+            env_key: EXO_CIRCUIT_BREAKER_COOLDOWN,
+            env_value: value.clone(),
+            message: "Must be a number of seconds".to_string(),
+        })?,
+        None => DEFAULT_COOLDOWN_SECS,
+    };
+
+    Ok(Some(CircuitBreaker::new(
+        name,
+        threshold,
+        Duration::from_secs(cool_down_secs),
+    )))
+}
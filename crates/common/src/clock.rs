@@ -0,0 +1,164 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The source of the current time for the values Exograph computes itself (such as `now()`
+//! defaults, the expiry of JWT tokens, and retention cutoffs).
+//!
+//! By default, the clock follows the system time. Setting `EXO_TEST_CLOCK` to an RFC3339 time
+//! (such as `2024-01-01T00:00:00Z`) freezes it at that time, so that tests can produce
+//! deterministic results (and move the time forward explicitly through [Clock::advance]). The
+//! expiry of JWTs is then checked against the frozen time, so a token that expired after that time
+//! remains valid for as long as the clock stays frozen (and time-based protections such as
+//! retention cutoffs and replay windows stop moving). Therefore, a frozen clock is refused in
+//! production.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicI64, Ordering},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use exo_env::Environment;
+use thiserror::Error;
+
+use crate::env_const::{EXO_ENV, EXO_TEST_CLOCK, is_production};
+
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    /// Milliseconds since the epoch (shared by all clones), if the clock is frozen
+    frozen_millis: Option<Arc<AtomicI64>>,
+}
+
+#[derive(Error, Debug)]
+pub enum ClockError {
+    #[error(
+        "Invalid value '{0}' for {EXO_TEST_CLOCK} (expected an RFC3339 time such as 2024-01-01T00:00:00Z)"
+    )]
+    InvalidTime(String),
+
+    #[error(
+        "The clock follows the system time and cannot be changed (set {EXO_TEST_CLOCK} to freeze it)"
+    )]
+    NotFrozen,
+
+    #[error("{EXO_TEST_CLOCK} cannot be set when {EXO_ENV} is 'production'")]
+    FrozenInProduction,
+}
+
+impl Clock {
+    /// A clock that follows the system time
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// A clock frozen at the given time
+    pub fn frozen_at(time: DateTime<Utc>) -> Self {
+        Self {
+            frozen_millis: Some(Arc::new(AtomicI64::new(time.timestamp_millis()))),
+        }
+    }
+
+    pub fn from_env(env: &dyn Environment) -> Result<Self, ClockError> {
+        match env.get(EXO_TEST_CLOCK) {
+            Some(_) if is_production(env) => Err(ClockError::FrozenInProduction),
+            Some(value) => {
+                let time = DateTime::parse_from_rfc3339(value.trim())
+                    .map_err(|_| ClockError::InvalidTime(value.clone()))?;
+                Ok(Self::frozen_at(time.with_timezone(&Utc)))
+            }
+            None => Ok(Self::system()),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match &self.frozen_millis {
+            Some(millis) => DateTime::from_timestamp_millis(millis.load(Ordering::Relaxed))
+                .expect("frozen clock out of range"),
+            None => Utc::now(),
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_millis.is_some()
+    }
+
+    /// Move a frozen clock to the given time (which may be in the past)
+    pub fn set(&self, time: DateTime<Utc>) -> Result<(), ClockError> {
+        let millis = self.frozen_millis.as_ref().ok_or(ClockError::NotFrozen)?;
+        millis.store(time.timestamp_millis(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Move a frozen clock forward by the given duration
+    pub fn advance(&self, by: Duration) -> Result<(), ClockError> {
+        let millis = self.frozen_millis.as_ref().ok_or(ClockError::NotFrozen)?;
+        millis.fetch_add(by.num_milliseconds(), Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    #[test]
+    fn frozen_from_env() {
+        let env = MapEnvironment::from([(EXO_TEST_CLOCK, "2024-01-01T10:00:00+02:00")]);
+        let clock = Clock::from_env(&env).unwrap();
+
+        assert!(clock.is_frozen());
+        assert_eq!(clock.now().to_rfc3339(), "2024-01-01T08:00:00+00:00");
+
+        // Clones share the time
+        let other = clock.clone();
+        other.advance(Duration::seconds(90)).unwrap();
+        assert_eq!(clock.now().to_rfc3339(), "2024-01-01T08:01:30+00:00");
+
+        clock
+            .set(
+                DateTime::parse_from_rfc3339("2023-06-01T00:00:00Z")
+                    .unwrap()
+                    .into(),
+            )
+            .unwrap();
+        assert_eq!(other.now().to_rfc3339(), "2023-06-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn system_clock() {
+        let clock = Clock::from_env(&MapEnvironment::new()).unwrap();
+
+        assert!(!clock.is_frozen());
+        assert!(matches!(
+            clock.advance(Duration::seconds(1)),
+            Err(ClockError::NotFrozen)
+        ));
+
+        let env = MapEnvironment::from([(EXO_TEST_CLOCK, "yesterday")]);
+        assert!(matches!(
+            Clock::from_env(&env),
+            Err(ClockError::InvalidTime(value)) if value == "yesterday"
+        ));
+    }
+
+    #[test]
+    fn frozen_in_production() {
+        let env = MapEnvironment::from([
+            (EXO_TEST_CLOCK, "2024-01-01T00:00:00Z"),
+            (EXO_ENV, "production"),
+        ]);
+
+        assert!(matches!(
+            Clock::from_env(&env),
+            Err(ClockError::FrozenInProduction)
+        ));
+    }
+}
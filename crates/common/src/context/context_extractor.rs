@@ -7,6 +7,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use serde_json::Value;
 
@@ -14,14 +16,28 @@ use super::{ContextExtractionError, RequestContext};
 
 /// Extractor for a particular context field
 ///
-/// This trait should be implemented on objects that represent a particular source of parsed context fields
+/// This trait should be implemented on objects that represent a particular source of parsed context fields.
+/// Besides the built-in sources (such as `@jwt` and `@header`), subsystem plugins may provide
+/// their own (for example, `@ldap` to look up the groups of the authenticated user) by returning
+/// them from their loader (see `SubsystemResolver::with_context_provider`).
+///
+/// For a context field such as `@ldap("groups") groups: Array<String>`, the extractor whose
+/// annotation name is `ldap` is asked for the `groups` key (or the field name, if the annotation
+/// has no parameter). Extraction is lazy: it happens only when an access expression, an
+/// injected argument, or another consumer needs the field. The value (including its absence) is
+/// cached for the rest of the request, so an extractor is asked for a key at most once per
+/// request. Caching across requests (for example, of a slow directory lookup) is up to the
+/// extractor.
 #[async_trait]
 pub trait ContextExtractor {
-    // what annotation does this extractor provide values for?
-    // e.g. "jwt", "header", etc.
+    /// The annotation this extractor provides values for (such as "jwt" or "header")
     fn annotation_name(&self) -> &str;
 
-    // extract a context field from this struct
+    /// Extract the value for the key from the request (`None` if the request doesn't have one).
+    ///
+    /// An error fails the request (as not authorized), so extractors should return `None` for
+    /// absent values and reserve errors for requests that can't be processed (such as a
+    /// malformed token) or failures of the underlying source.
     async fn extract_context_field(
         &self,
         key: &str,
@@ -30,6 +46,24 @@ pub trait ContextExtractor {
 }
 pub type BoxedContextExtractor<'a> = Box<dyn ContextExtractor + 'a + Send + Sync>;
 
+/// An extractor shared by all requests (such as one provided by a subsystem plugin)
+pub type SharedContextExtractor = Arc<dyn ContextExtractor + Send + Sync>;
+
+#[async_trait]
+impl<T: ContextExtractor + Send + Sync + ?Sized> ContextExtractor for Arc<T> {
+    fn annotation_name(&self) -> &str {
+        (**self).annotation_name()
+    }
+
+    async fn extract_context_field(
+        &self,
+        key: &str,
+        request_context: &RequestContext,
+    ) -> Result<Option<Value>, ContextExtractionError> {
+        (**self).extract_context_field(key, request_context).await
+    }
+}
+
 #[cfg(feature = "test-context")]
 pub struct TestRequestContext {
     pub test_values: Value,
@@ -50,3 +84,116 @@ impl ContextExtractor for TestRequestContext {
         Ok(self.test_values.get(key).cloned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use exo_env::MapEnvironment;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        context::JwtAuthenticator,
+        http::{MemoryRequestHead, MemoryRequestPayload, ResponsePayload},
+        router::{PlainRequestPayload, Router},
+        value::Val,
+    };
+
+    /// A provider such as a plugin may register (looking up the groups of a user in a directory)
+    struct GroupsExtractor {
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ContextExtractor for GroupsExtractor {
+        fn annotation_name(&self) -> &str {
+            "ldap"
+        }
+
+        async fn extract_context_field(
+            &self,
+            key: &str,
+            _request_context: &RequestContext,
+        ) -> Result<Option<Value>, ContextExtractionError> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+
+            match key {
+                "groups" => Ok(Some(json!(["admin", "billing"]))),
+                "manager" => Ok(None),
+                _ => Err(ContextExtractionError::Unauthorized),
+            }
+        }
+    }
+
+    struct TestRouter;
+
+    #[async_trait]
+    impl<'request> Router<PlainRequestPayload<'request>> for TestRouter {
+        async fn route(&self, _request: &PlainRequestPayload<'request>) -> Option<ResponsePayload> {
+            None
+        }
+    }
+
+    async fn extract<'a>(
+        request_context: &'a RequestContext<'a>,
+        key: &str,
+    ) -> Result<Option<&'a Val>, ContextExtractionError> {
+        request_context
+            .extract_context_field("AuthContext", "ldap", &Some(key), key, &|value| Ok(value))
+            .await
+    }
+
+    #[tokio::test]
+    async fn shared_extractor_values_are_cached_per_request() {
+        let provider = Arc::new(GroupsExtractor {
+            lookups: AtomicUsize::new(0),
+        });
+        let shared: SharedContextExtractor = provider.clone();
+
+        let request = MemoryRequestPayload::new(
+            Value::Null,
+            MemoryRequestHead::new(
+                HashMap::new(),
+                HashMap::new(),
+                http::Method::POST,
+                "/graphql".to_string(),
+                Value::Null,
+                None,
+            ),
+        );
+        let env = MapEnvironment::default();
+        let authenticator: Option<JwtAuthenticator> = None;
+        let request_context = RequestContext::new(
+            &request,
+            vec![Box::new(shared)],
+            &TestRouter,
+            &authenticator,
+            &env,
+        );
+
+        let groups = Some(Val::from(json!(["admin", "billing"])));
+        assert_eq!(
+            extract(&request_context, "groups").await.unwrap(),
+            groups.as_ref()
+        );
+        assert_eq!(
+            extract(&request_context, "groups").await.unwrap(),
+            groups.as_ref()
+        );
+        assert_eq!(provider.lookups.load(Ordering::Relaxed), 1);
+
+        // The absence of a value is cached as well
+        assert_eq!(extract(&request_context, "manager").await.unwrap(), None);
+        assert_eq!(extract(&request_context, "manager").await.unwrap(), None);
+        assert_eq!(provider.lookups.load(Ordering::Relaxed), 2);
+
+        assert!(matches!(
+            extract(&request_context, "unknown").await,
+            Err(ContextExtractionError::Unauthorized)
+        ));
+    }
+}
@@ -12,13 +12,17 @@ mod error;
 mod overridden_context;
 pub mod provider;
 mod request_context;
+mod request_stats;
 mod user_request_context;
 
+pub use provider::flag::FlagExtractor;
 pub use provider::jwt::JwtAuthenticator;
 
-pub use context_extractor::ContextExtractor;
+pub use context_extractor::{ContextExtractor, SharedContextExtractor};
 pub use error::ContextExtractionError;
+pub use provider::BUILT_IN_CONTEXT_ANNOTATIONS;
 pub use request_context::RequestContext;
+pub use request_stats::RequestStats;
 
 #[cfg(feature = "test-context")]
 pub use context_extractor::TestRequestContext;
@@ -0,0 +1,212 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::context::{ContextExtractionError, RequestContext, context_extractor::ContextExtractor};
+
+/// Extracts attributes of the client certificate for mutual TLS (such as
+/// `@clientCert("commonName") service: String?`).
+///
+/// The value is absent if the client didn't present a certificate (or the server doesn't
+/// terminate TLS).
+pub struct ClientCertExtractor;
+
+#[async_trait]
+impl ContextExtractor for ClientCertExtractor {
+    fn annotation_name(&self) -> &str {
+        "clientCert"
+    }
+
+    async fn extract_context_field(
+        &self,
+        key: &str,
+        request_context: &RequestContext,
+    ) -> Result<Option<Value>, ContextExtractionError> {
+        use crate::http::RequestPayload;
+
+        let Some(certificate) = request_context.get_head().get_client_certificate() else {
+            return Ok(None);
+        };
+
+        let value = match key {
+            "commonName" => certificate.common_name.clone().map(Value::String),
+            "subject" => Some(Value::String(certificate.subject.clone())),
+            "issuer" => Some(Value::String(certificate.issuer.clone())),
+            "sans" => Some(Value::Array(
+                certificate
+                    .sans
+                    .iter()
+                    .map(|san| Value::String(san.clone()))
+                    .collect(),
+            )),
+            "fingerprint" => Some(Value::String(certificate.fingerprint.clone())),
+            "serialNumber" => Some(Value::String(certificate.serial_number.clone())),
+            _ => {
+                return Err(ContextExtractionError::Generic(format!(
+                    "Unknown client certificate attribute '{key}' (expected commonName, subject, issuer, sans, fingerprint, or serialNumber)"
+                )));
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        http::{ClientCertificate, RequestHead, RequestPayload, ResponsePayload},
+        router::{PlainRequestPayload, Router},
+    };
+
+    struct TestRequest {
+        certificate: Option<ClientCertificate>,
+    }
+
+    impl RequestPayload for TestRequest {
+        fn get_head(&self) -> &(dyn RequestHead + Send + Sync) {
+            self
+        }
+
+        fn take_body(&self) -> Value {
+            Value::Null
+        }
+    }
+
+    impl RequestHead for TestRequest {
+        fn get_headers(&self, _key: &str) -> Vec<String> {
+            vec![]
+        }
+
+        fn get_ip(&self) -> Option<std::net::IpAddr> {
+            None
+        }
+
+        fn get_method(&self) -> http::Method {
+            http::Method::POST
+        }
+
+        fn get_path(&self) -> String {
+            "".to_string()
+        }
+
+        fn get_query(&self) -> Value {
+            Value::Null
+        }
+
+        fn get_client_certificate(&self) -> Option<&ClientCertificate> {
+            self.certificate.as_ref()
+        }
+    }
+
+    struct TestRouter;
+
+    #[async_trait]
+    impl<'request> Router<PlainRequestPayload<'request>> for TestRouter {
+        async fn route(&self, _request: &PlainRequestPayload<'request>) -> Option<ResponsePayload> {
+            None
+        }
+    }
+
+    fn certificate(common_name: Option<&str>) -> ClientCertificate {
+        ClientCertificate {
+            common_name: common_name.map(|name| name.to_string()),
+            subject: "O=Acme, CN=billing".to_string(),
+            issuer: "CN=Acme CA".to_string(),
+            sans: vec![
+                "billing.internal".to_string(),
+                "10.0.0.7".to_string(),
+                "spiffe://acme/billing".to_string(),
+            ],
+            fingerprint: "e951bc68".to_string(),
+            serial_number: "1234abcd".to_string(),
+        }
+    }
+
+    async fn extract(
+        certificate: Option<ClientCertificate>,
+        key: &str,
+    ) -> Result<Option<Value>, ContextExtractionError> {
+        let request = TestRequest { certificate };
+        let env = MapEnvironment::new();
+        let jwt_authenticator = None;
+        let request_context =
+            RequestContext::new(&request, vec![], &TestRouter, &jwt_authenticator, &env);
+
+        ClientCertExtractor
+            .extract_context_field(key, &request_context)
+            .await
+    }
+
+    #[tokio::test]
+    async fn attributes() {
+        let cert = || Some(certificate(Some("billing")));
+
+        assert_eq!(
+            extract(cert(), "commonName").await.unwrap(),
+            Some(json!("billing"))
+        );
+        assert_eq!(
+            extract(cert(), "subject").await.unwrap(),
+            Some(json!("O=Acme, CN=billing"))
+        );
+        assert_eq!(
+            extract(cert(), "issuer").await.unwrap(),
+            Some(json!("CN=Acme CA"))
+        );
+        assert_eq!(
+            extract(cert(), "sans").await.unwrap(),
+            Some(json!([
+                "billing.internal",
+                "10.0.0.7",
+                "spiffe://acme/billing"
+            ]))
+        );
+        assert_eq!(
+            extract(cert(), "fingerprint").await.unwrap(),
+            Some(json!("e951bc68"))
+        );
+        assert_eq!(
+            extract(cert(), "serialNumber").await.unwrap(),
+            Some(json!("1234abcd"))
+        );
+    }
+
+    #[tokio::test]
+    async fn certificate_without_common_name() {
+        assert_eq!(
+            extract(Some(certificate(None)), "commonName")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn no_certificate() {
+        assert_eq!(extract(None, "commonName").await.unwrap(), None);
+        assert_eq!(extract(None, "sans").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn unknown_attribute() {
+        let result = extract(Some(certificate(Some("billing"))), "email").await;
+
+        assert!(
+            matches!(result, Err(ContextExtractionError::Generic(ref message)) if message.contains("'email'")),
+            "Unexpected result {result:?}"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::context::{ContextExtractionError, RequestContext, context_extractor::ContextExtractor};
+use crate::deployment::DeploymentInfo;
+
+/// Extracts deployment metadata (such as `@deployment region: String`).
+///
+/// See [DeploymentInfo] for the supported keys.
+pub struct DeploymentExtractor;
+
+#[async_trait]
+impl ContextExtractor for DeploymentExtractor {
+    fn annotation_name(&self) -> &str {
+        "deployment"
+    }
+
+    async fn extract_context_field(
+        &self,
+        key: &str,
+        request_context: &RequestContext,
+    ) -> Result<Option<Value>, ContextExtractionError> {
+        Ok(DeploymentInfo::from_env(request_context.system_context.env).get(key))
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::context::{ContextExtractionError, RequestContext, context_extractor::ContextExtractor};
+use crate::feature_flags::FeatureFlags;
+
+/// Extracts feature flags (such as `@flag newCheckout: Boolean`).
+///
+/// The value is absent if no source sets the flag.
+pub struct FlagExtractor {
+    flags: Arc<FeatureFlags>,
+}
+
+impl FlagExtractor {
+    pub fn new(flags: Arc<FeatureFlags>) -> Self {
+        Self { flags }
+    }
+}
+
+#[async_trait]
+impl ContextExtractor for FlagExtractor {
+    fn annotation_name(&self) -> &str {
+        "flag"
+    }
+
+    async fn extract_context_field(
+        &self,
+        key: &str,
+        _request_context: &RequestContext,
+    ) -> Result<Option<Value>, ContextExtractionError> {
+        Ok(self.flags.get(key).await)
+    }
+}
@@ -11,7 +11,10 @@ use async_trait::async_trait;
 use serde_json::Value;
 
 use crate::context::{ContextExtractionError, RequestContext, context_extractor::ContextExtractor};
+use crate::ip_filter::is_internal_caller;
 
+/// Extracts the client IP (`@clientIp ip: String`) or whether the client is in one of the
+/// `EXO_INTERNAL_IP_RANGES` (`@clientIp("internal") isInternal: Boolean`).
 pub struct IpExtractor;
 
 #[async_trait]
@@ -22,11 +25,21 @@ impl ContextExtractor for IpExtractor {
 
     async fn extract_context_field(
         &self,
-        _key: &str,
+        key: &str,
         request_context: &RequestContext,
     ) -> Result<Option<Value>, ContextExtractionError> {
         use crate::http::RequestPayload;
 
+        if key == "internal" {
+            let is_internal = is_internal_caller(
+                request_context.system_context.env,
+                request_context.get_head(),
+            )
+            .map_err(|e| ContextExtractionError::Generic(e.to_string()))?;
+
+            return Ok(Some(Value::Bool(is_internal)));
+        }
+
         Ok(request_context
             .get_head()
             .get_ip()
@@ -7,28 +7,38 @@ use tracing::error;
 
 use exo_env::Environment;
 
+use crate::clock::Clock;
 use crate::context::error::ContextExtractionError;
 use crate::context::provider::cookie::CookieExtractor;
 use crate::env_const::{
-    EXO_JWKS_URLS, EXO_JWT_AUDIENCES, EXO_JWT_PROVIDER_CONFIG, EXO_JWT_PUBLIC_KEY_KID,
-    EXO_JWT_PUBLIC_KEY_PEM, EXO_JWT_PUBLIC_KEY_PEM_ENVS, EXO_JWT_SECRET, EXO_JWT_SOURCE_COOKIE,
-    EXO_JWT_SOURCE_HEADER, EXO_OIDC_URL, EXO_OIDC_URLS,
+    EXO_JWKS_URLS, EXO_JWT_AUDIENCES, EXO_JWT_CLAIM_MAPPINGS, EXO_JWT_PROVIDER_CONFIG,
+    EXO_JWT_PUBLIC_KEY_KID, EXO_JWT_PUBLIC_KEY_PEM, EXO_JWT_PUBLIC_KEY_PEM_ENVS, EXO_JWT_SECRET,
+    EXO_JWT_SOURCE_COOKIE, EXO_JWT_SOURCE_HEADER, EXO_OIDC_URL, EXO_OIDC_URLS,
 };
 use crate::http::RequestHead;
 
+use super::claim_mapping::ClaimMappings;
+use super::introspection::{DEFAULT_CACHE_TTL, DEFAULT_NEGATIVE_CACHE_TTL, TokenIntrospector};
 use super::jwks::{JwksValidator, JwtValidationError};
 use super::oidc::Oidc;
 use super::static_key::StaticKeyValidator;
 use base64::Engine;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use std::collections::HashSet;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
 
 const TOKEN_PREFIX: &str = "Bearer ";
 
 pub struct JwtAuthenticator {
-    style: JwtAuthenticatorStyle,
+    /// Replaced when the configuration is reloaded (see [`JwtAuthenticator::reload_from_env`]).
+    /// Requests in flight keep validating with the style they started with.
+    style: RwLock<Arc<JwtAuthenticatorStyle>>,
+    /// Applied to the claims of all tokens (after any provider-specific mappings). Replaced along
+    /// with the style.
+    claim_mappings: RwLock<Arc<ClaimMappings>>,
     authenticator_source: AuthenticatorSource,
+    clock: Clock,
 }
 
 #[derive(Debug)]
@@ -43,6 +53,7 @@ enum ProviderStrategy {
     Oidc,
     Jwks,
     Static,
+    Introspection,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,19 +69,44 @@ struct ProviderDescriptor {
     issuer_aliases: Vec<String>,
     #[serde(default)]
     audiences: Option<Vec<String>>,
+    /// Nest the claims of this provider's tokens under this key (for example, with "service", the
+    /// `sub` claim is available as `@jwt("service.sub")`), so that contexts can tell tokens from
+    /// different providers apart
+    #[serde(default)]
+    claims_namespace: Option<String>,
+    /// Rules to reshape the claims of this provider's tokens (see [`ClaimMappings`])
+    #[serde(default)]
+    claim_mappings: Option<Value>,
+    /// The client id to authenticate with the introspection endpoint
+    #[serde(default)]
+    client_id: Option<String>,
+    /// The environment variable holding the client secret to authenticate with the introspection
+    /// endpoint
+    #[serde(default)]
+    client_secret_env: Option<String>,
+    /// How long to cache the introspection result of an active token
+    #[serde(default)]
+    cache_ttl_seconds: Option<u64>,
+    /// How long to cache the introspection result of an inactive (or rejected) token
+    #[serde(default)]
+    negative_cache_ttl_seconds: Option<u64>,
 }
 
 /// Authenticator with information about how to validate JWT tokens
-/// It can be either a secret, OIDC url(s), JWKS url(s), static public key(s), or a mix of the above
+/// It can be either a secret, OIDC url(s), JWKS url(s), static public key(s), introspection
+/// endpoint(s) (for opaque tokens), or a mix of the above
 enum JwtAuthenticatorStyle {
     Secret(String),
     Oidc(Vec<Oidc>),
     Jwks(Vec<JwksValidator>),
     StaticKeys(Vec<StaticKeyValidator>),
+    Introspection(Vec<TokenIntrospector>),
     Mixed {
         oidc: Vec<Oidc>,
         jwks: Vec<JwksValidator>,
         static_keys: Vec<StaticKeyValidator>,
+        /// Used only for opaque (non-JWT) tokens
+        introspection: Vec<TokenIntrospector>,
     },
 }
 
@@ -141,6 +177,23 @@ fn decode_jwt_header_and_payload(token: &str) -> Option<(Value, Value)> {
     Some((header, payload))
 }
 
+/// The `iss` claim of the token (without verifying the token)
+fn token_issuer(token: &str) -> Option<String> {
+    let (_, payload) = decode_jwt_header_and_payload(token)?;
+    payload.get("iss")?.as_str().map(str::to_string)
+}
+
+/// Apply the mappings of the provider that validated the token and nest the result under its
+/// namespace (if any)
+fn provider_claims(namespace: Option<&str>, mappings: &ClaimMappings, claims: Value) -> Value {
+    let claims = mappings.apply(claims);
+
+    match namespace {
+        Some(namespace) => serde_json::json!({ namespace: claims }),
+        None => claims,
+    }
+}
+
 #[derive(Debug, Error)]
 pub(super) enum JwtAuthenticationError {
     #[error("Invalid token")]
@@ -440,6 +493,7 @@ impl JwtAuthenticator {
         let mut jwks_debug_snapshot: Vec<JwksDebugSnapshotEntry> = Vec::new();
         let mut static_key_validators = Vec::new();
         let mut static_debug_snapshot = Vec::new();
+        let mut introspectors = Vec::new();
 
         if let Some(descriptors) = provider_descriptors {
             for (idx, descriptor) in descriptors.into_iter().enumerate() {
@@ -461,6 +515,17 @@ impl JwtAuthenticator {
                 };
 
                 let issuer_aliases = sanitize_aliases(descriptor.issuer_aliases);
+                let claims_namespace = descriptor
+                    .claims_namespace
+                    .map(|namespace| namespace.trim().to_string())
+                    .filter(|namespace| !namespace.is_empty());
+                let claim_mappings = match descriptor.claim_mappings {
+                    Some(mappings) => ClaimMappings::from_value(
+                        mappings,
+                        &format!("provider '{provider_label}'"),
+                    )?,
+                    None => ClaimMappings::default(),
+                };
 
                 match descriptor.strategy {
                     ProviderStrategy::Oidc => {
@@ -489,7 +554,11 @@ impl JwtAuthenticator {
                                         provider_label, provider_audiences, issuer_aliases
                                     )
                                 });
-                                oidc_validators.push(validator);
+                                oidc_validators.push(
+                                    validator
+                                        .with_claims_namespace(claims_namespace)
+                                        .with_claim_mappings(claim_mappings),
+                                );
                             }
                             Err(e) => {
                                 return Err(JwtConfigurationError::Configuration {
@@ -539,7 +608,11 @@ impl JwtAuthenticator {
                                     debug_aud,
                                     debug_iss,
                                 ));
-                                jwks_validators.push(validator);
+                                jwks_validators.push(
+                                    validator
+                                        .with_claims_namespace(claims_namespace)
+                                        .with_claim_mappings(claim_mappings),
+                                );
                             }
                             Err(e) => {
                                 return Err(JwtConfigurationError::Configuration {
@@ -558,6 +631,62 @@ impl JwtAuthenticator {
                             provider_label, EXO_JWT_PUBLIC_KEY_PEM
                         )));
                     }
+                    ProviderStrategy::Introspection => {
+                        let url = descriptor.url.ok_or_else(|| {
+                            JwtConfigurationError::InvalidSetup(format!(
+                                "Introspection provider '{}' is missing 'url'",
+                                provider_label
+                            ))
+                        })?;
+
+                        let client_credentials = match (
+                            descriptor.client_id,
+                            descriptor.client_secret_env,
+                        ) {
+                            (Some(client_id), Some(secret_env)) => {
+                                let client_secret = env.get(&secret_env).ok_or_else(|| {
+                                        JwtConfigurationError::InvalidSetup(format!(
+                                            "Introspection provider '{}' refers to {}, which is not set",
+                                            provider_label, secret_env
+                                        ))
+                                    })?;
+                                Some((client_id, client_secret))
+                            }
+                            (None, None) => None,
+                            _ => {
+                                return Err(JwtConfigurationError::InvalidSetup(format!(
+                                    "Introspection provider '{}' must specify both 'client_id' and 'client_secret_env' (or neither)",
+                                    provider_label
+                                )));
+                            }
+                        };
+
+                        let cache_ttl = descriptor
+                            .cache_ttl_seconds
+                            .map_or(DEFAULT_CACHE_TTL, Duration::from_secs);
+                        let negative_cache_ttl = descriptor
+                            .negative_cache_ttl_seconds
+                            .map_or(DEFAULT_NEGATIVE_CACHE_TTL, Duration::from_secs);
+
+                        let introspector = TokenIntrospector::new(
+                            url.clone(),
+                            client_credentials,
+                            provider_audiences.clone(),
+                            cache_ttl,
+                            negative_cache_ttl,
+                        )?;
+
+                        tracing::info!(
+                            "Initialized introspection provider '{}': {}",
+                            provider_label,
+                            url
+                        );
+                        introspectors.push(
+                            introspector
+                                .with_claims_namespace(claims_namespace)
+                                .with_claim_mappings(claim_mappings),
+                        );
+                    }
                 }
             }
         } else {
@@ -758,19 +887,24 @@ impl JwtAuthenticator {
             let has_oidc = !oidc_validators.is_empty();
             let has_jwks = !jwks_validators.is_empty();
             let has_static = !static_key_validators.is_empty();
+            let has_introspection = !introspectors.is_empty();
 
-            if !has_oidc && !has_jwks && !has_static {
+            if !has_oidc && !has_jwks && !has_static && !has_introspection {
                 return Ok(None);
             }
 
-            match (has_oidc, has_jwks, has_static) {
-                (true, false, false) => JwtAuthenticatorStyle::Oidc(oidc_validators),
-                (false, true, false) => JwtAuthenticatorStyle::Jwks(jwks_validators),
-                (false, false, true) => JwtAuthenticatorStyle::StaticKeys(static_key_validators),
+            match (has_oidc, has_jwks, has_static, has_introspection) {
+                (true, false, false, false) => JwtAuthenticatorStyle::Oidc(oidc_validators),
+                (false, true, false, false) => JwtAuthenticatorStyle::Jwks(jwks_validators),
+                (false, false, true, false) => {
+                    JwtAuthenticatorStyle::StaticKeys(static_key_validators)
+                }
+                (false, false, false, true) => JwtAuthenticatorStyle::Introspection(introspectors),
                 _ => JwtAuthenticatorStyle::Mixed {
                     oidc: oidc_validators,
                     jwks: jwks_validators,
                     static_keys: static_key_validators,
+                    introspection: introspectors,
                 },
             }
         };
@@ -781,6 +915,7 @@ impl JwtAuthenticator {
                 JwtAuthenticatorStyle::Oidc(_) => "oidc",
                 JwtAuthenticatorStyle::Jwks(_) => "jwks",
                 JwtAuthenticatorStyle::StaticKeys(_) => "static_keys",
+                JwtAuthenticatorStyle::Introspection(_) => "introspection",
                 JwtAuthenticatorStyle::Mixed { .. } => "mixed",
             };
 
@@ -816,21 +951,33 @@ impl JwtAuthenticator {
             }
         }
 
+        let claim_mappings = match env.get(EXO_JWT_CLAIM_MAPPINGS) {
+            Some(raw) => ClaimMappings::parse(&raw, EXO_JWT_CLAIM_MAPPINGS)?,
+            None => ClaimMappings::default(),
+        };
+        let claim_mappings = RwLock::new(Arc::new(claim_mappings));
+
         let jwt_source_header = env.get(EXO_JWT_SOURCE_HEADER);
         let jwt_source_cookie = env.get(EXO_JWT_SOURCE_COOKIE);
 
         match (jwt_source_header, jwt_source_cookie) {
             (Some(header), None) => Ok(Some(JwtAuthenticator {
-                style,
+                style: RwLock::new(Arc::new(style)),
+                claim_mappings,
                 authenticator_source: AuthenticatorSource::Header(header),
+                clock: Clock::system(),
             })),
             (None, Some(cookie)) => Ok(Some(JwtAuthenticator {
-                style,
+                style: RwLock::new(Arc::new(style)),
+                claim_mappings,
                 authenticator_source: AuthenticatorSource::Cookie(cookie),
+                clock: Clock::system(),
             })),
             (None, None) => Ok(Some(JwtAuthenticator {
-                style,
+                style: RwLock::new(Arc::new(style)),
+                claim_mappings,
                 authenticator_source: AuthenticatorSource::Header("Authorization".to_string()),
+                clock: Clock::system(),
             })),
             (Some(_), Some(_)) => Err(JwtConfigurationError::InvalidSetup(format!(
                 "Both {EXO_JWT_SOURCE_HEADER} and {EXO_JWT_SOURCE_COOKIE} are set. Only one of them can be set at a time"
@@ -838,7 +985,7 @@ impl JwtAuthenticator {
         }
     }
 
-    async fn try_oidc(validators: &[Oidc], token: &str) -> ValidationAttempt {
+    async fn try_oidc(validators: &[Oidc], token: &str, issuer: Option<&str>) -> ValidationAttempt {
         let mut saw_expired = false;
         let mut had_non_kid_error = false;
 
@@ -850,12 +997,28 @@ impl JwtAuthenticator {
         });
 
         for (idx, validator) in validators.iter().enumerate() {
+            if let Some(issuer) = issuer
+                && !validator.handles_issuer(issuer)
+            {
+                jwt_debug_log(|| {
+                    format!(
+                        "OIDC provider {} skipped since it doesn't issue tokens for '{issuer}'",
+                        idx + 1
+                    )
+                });
+                continue;
+            }
+
             match validator.validate(token).await {
                 Ok(claims) => {
                     tracing::debug!("JWT validated successfully by OIDC provider {}", idx + 1);
                     jwt_debug_log(|| format!("OIDC provider {} accepted the token", idx + 1));
                     return ValidationAttempt {
-                        outcome: ValidationOutcome::Success(claims),
+                        outcome: ValidationOutcome::Success(provider_claims(
+                            validator.claims_namespace(),
+                            validator.claim_mappings(),
+                            claims,
+                        )),
                         had_non_kid_error: false,
                     };
                 }
@@ -906,7 +1069,11 @@ impl JwtAuthenticator {
         }
     }
 
-    async fn try_jwks(validators: &[JwksValidator], token: &str) -> ValidationAttempt {
+    async fn try_jwks(
+        validators: &[JwksValidator],
+        token: &str,
+        issuer: Option<&str>,
+    ) -> ValidationAttempt {
         let mut saw_expired = false;
         let mut had_non_kid_error = false;
 
@@ -918,6 +1085,19 @@ impl JwtAuthenticator {
         });
 
         for (idx, validator) in validators.iter().enumerate() {
+            if let Some(issuer) = issuer
+                && !validator.handles_issuer(issuer)
+            {
+                jwt_debug_log(|| {
+                    format!(
+                        "JWKS provider {} ('{}') skipped since it doesn't issue tokens for '{issuer}'",
+                        idx + 1,
+                        validator.debug_source()
+                    )
+                });
+                continue;
+            }
+
             match validator.validate(token).await {
                 Ok(claims) => {
                     tracing::debug!("JWT validated successfully by JWKS provider {}", idx + 1);
@@ -929,7 +1109,11 @@ impl JwtAuthenticator {
                         )
                     });
                     return ValidationAttempt {
-                        outcome: ValidationOutcome::Success(claims),
+                        outcome: ValidationOutcome::Success(provider_claims(
+                            validator.claims_namespace(),
+                            validator.claim_mappings(),
+                            claims,
+                        )),
                         had_non_kid_error: false,
                     };
                 }
@@ -979,6 +1163,42 @@ impl JwtAuthenticator {
         }
     }
 
+    /// Validate the token with the introspection endpoints in order (the first one that reports the
+    /// token as active wins)
+    async fn try_introspection(
+        introspectors: &[TokenIntrospector],
+        token: &str,
+    ) -> Result<Value, JwtAuthenticationError> {
+        let mut saw_expired = false;
+
+        for (idx, introspector) in introspectors.iter().enumerate() {
+            match introspector.validate(token).await {
+                Ok(claims) => {
+                    jwt_debug_log(|| {
+                        format!(
+                            "Introspection provider {} ('{}') accepted the token",
+                            idx + 1,
+                            introspector.debug_source()
+                        )
+                    });
+                    return Ok(provider_claims(
+                        introspector.claims_namespace(),
+                        introspector.claim_mappings(),
+                        claims,
+                    ));
+                }
+                Err(JwtValidationError::Expired) => saw_expired = true,
+                Err(_) => {}
+            }
+        }
+
+        if saw_expired {
+            Err(JwtAuthenticationError::Expired)
+        } else {
+            Err(JwtAuthenticationError::Invalid)
+        }
+    }
+
     fn try_static(validators: &[StaticKeyValidator], token: &str) -> ValidationAttempt {
         let mut saw_expired = false;
         let mut had_non_kid_error = false;
@@ -1052,6 +1272,48 @@ impl JwtAuthenticator {
         }
     }
 
+    /// Check the expiry of tokens signed with the secret against the given clock (instead of the
+    /// system time)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Rebuild the validation (secret, OIDC/JWKS URLs, public keys, audiences, claim mappings) from
+    /// the current environment, so a provider can be rotated without a restart.
+    ///
+    /// The new configuration is fully built (including fetching any OIDC discovery documents)
+    /// before replacing the current one, so on an error, the current configuration stays in place.
+    /// The token source (header or cookie) isn't reloaded.
+    pub async fn reload_from_env(
+        &self,
+        env: &dyn Environment,
+    ) -> Result<(), JwtConfigurationError> {
+        let reloaded = Self::new_from_env(env).await?.ok_or_else(|| {
+            JwtConfigurationError::InvalidSetup(
+                "No JWT configuration found (authentication can be disabled only with a restart)"
+                    .to_string(),
+            )
+        })?;
+
+        let mut style = self.style.write().unwrap();
+        *self.claim_mappings.write().unwrap() = reloaded.claim_mappings.into_inner().unwrap();
+        *style = reloaded.style.into_inner().unwrap();
+        drop(style);
+
+        tracing::info!("Reloaded the JWT configuration");
+
+        Ok(())
+    }
+
+    fn current_style(&self) -> Arc<JwtAuthenticatorStyle> {
+        self.style.read().unwrap().clone()
+    }
+
+    fn current_claim_mappings(&self) -> Arc<ClaimMappings> {
+        self.claim_mappings.read().unwrap().clone()
+    }
+
     async fn validate_jwt(&self, token: &str) -> Result<Value, JwtAuthenticationError> {
         if jwt_debug_enabled() {
             if let Some((header, payload)) = decode_jwt_header_and_payload(token) {
@@ -1085,28 +1347,54 @@ impl JwtAuthenticator {
             }
         }
 
+        let style = self.current_style();
+        // Select the providers by the (yet unverified) issuer of the token
+        let issuer = token_issuer(token);
+        let issuer = issuer.as_deref();
+
         jwt_debug_log(|| {
-            let style_desc = match &self.style {
+            let style_desc = match style.as_ref() {
                 JwtAuthenticatorStyle::Secret(_) => "secret",
                 JwtAuthenticatorStyle::Oidc(_) => "oidc",
                 JwtAuthenticatorStyle::Jwks(_) => "jwks",
                 JwtAuthenticatorStyle::StaticKeys(_) => "static_keys",
+                JwtAuthenticatorStyle::Introspection(_) => "introspection",
                 JwtAuthenticatorStyle::Mixed { .. } => "mixed",
             };
             format!("Beginning JWT validation with '{style_desc}' strategy")
         });
 
-        match &self.style {
-            JwtAuthenticatorStyle::Secret(secret) => Ok(decode::<Value>(
-                token,
-                &DecodingKey::from_secret(secret.as_ref()),
-                &Validation::default(),
-            )
-            .map_err(map_jwt_error)?
-            .claims),
+        match style.as_ref() {
+            JwtAuthenticatorStyle::Secret(secret) => {
+                let mut validation = Validation::default();
+                // A frozen clock needs checking the expiry ourselves (`decode` uses the system time)
+                validation.validate_exp = !self.clock.is_frozen();
+
+                let claims = decode::<Value>(
+                    token,
+                    &DecodingKey::from_secret(secret.as_ref()),
+                    &validation,
+                )
+                .map_err(map_jwt_error)?
+                .claims;
+
+                if self.clock.is_frozen() {
+                    let exp = claims
+                        .get("exp")
+                        .and_then(Value::as_i64)
+                        .ok_or(JwtAuthenticationError::Invalid)?;
+                    let leeway = i64::try_from(validation.leeway).unwrap_or(i64::MAX);
+
+                    if exp < self.clock.now().timestamp() - leeway {
+                        return Err(JwtAuthenticationError::Expired);
+                    }
+                }
+
+                Ok(claims)
+            }
 
             JwtAuthenticatorStyle::Oidc(validators) => {
-                let attempt = Self::try_oidc(validators, token).await;
+                let attempt = Self::try_oidc(validators, token, issuer).await;
                 match attempt.outcome {
                     ValidationOutcome::Success(claims) => Ok(claims),
                     ValidationOutcome::Expired => Err(JwtAuthenticationError::Expired),
@@ -1124,7 +1412,7 @@ impl JwtAuthenticator {
             }
 
             JwtAuthenticatorStyle::Jwks(validators) => {
-                let attempt = Self::try_jwks(validators, token).await;
+                let attempt = Self::try_jwks(validators, token, issuer).await;
                 match attempt.outcome {
                     ValidationOutcome::Success(claims) => Ok(claims),
                     ValidationOutcome::Expired => Err(JwtAuthenticationError::Expired),
@@ -1159,14 +1447,25 @@ impl JwtAuthenticator {
                 }
             }
 
+            JwtAuthenticatorStyle::Introspection(introspectors) => {
+                Self::try_introspection(introspectors, token).await
+            }
+
             JwtAuthenticatorStyle::Mixed {
                 oidc,
                 jwks,
                 static_keys,
+                introspection,
             } => {
+                // Opaque tokens can only be validated through introspection (and JWTs only by
+                // checking their signature)
+                if decode_jwt_header_and_payload(token).is_none() && !introspection.is_empty() {
+                    return Self::try_introspection(introspection, token).await;
+                }
+
                 let mut had_non_kid_error = false;
 
-                let oidc_attempt = Self::try_oidc(oidc, token).await;
+                let oidc_attempt = Self::try_oidc(oidc, token, issuer).await;
                 match oidc_attempt.outcome {
                     ValidationOutcome::Success(claims) => return Ok(claims),
                     ValidationOutcome::Expired => return Err(JwtAuthenticationError::Expired),
@@ -1184,7 +1483,7 @@ impl JwtAuthenticator {
                     }
                 }
 
-                let jwks_attempt = Self::try_jwks(jwks, token).await;
+                let jwks_attempt = Self::try_jwks(jwks, token, issuer).await;
                 match jwks_attempt.outcome {
                     ValidationOutcome::Success(claims) => return Ok(claims),
                     ValidationOutcome::Expired => return Err(JwtAuthenticationError::Expired),
@@ -1205,6 +1504,63 @@ impl JwtAuthenticator {
         }
     }
 
+    /// Describe how tokens are validated (for the startup report). Includes the JWKS URLs and the
+    /// ids of the keys fetched from them, but never the secret or keys themselves.
+    pub fn status(&self) -> Value {
+        let source = match &self.authenticator_source {
+            AuthenticatorSource::Header(header) => serde_json::json!({ "header": header }),
+            AuthenticatorSource::Cookie(cookie) => serde_json::json!({ "cookie": cookie }),
+        };
+
+        let jwks_status = |jwks: &[JwksValidator]| -> Vec<Value> {
+            jwks.iter()
+                .map(|validator| {
+                    serde_json::json!({
+                        "url": validator.debug_source(),
+                        "kids": validator.debug_known_kids(),
+                    })
+                })
+                .collect()
+        };
+
+        let introspection_status = |introspectors: &[TokenIntrospector]| -> Vec<Value> {
+            introspectors
+                .iter()
+                .map(|introspector| serde_json::json!({ "url": introspector.debug_source() }))
+                .collect()
+        };
+
+        let style = match self.current_style().as_ref() {
+            JwtAuthenticatorStyle::Secret(_) => serde_json::json!({ "kind": "secret" }),
+            JwtAuthenticatorStyle::Oidc(oidc) => {
+                serde_json::json!({ "kind": "oidc", "providers": oidc.len() })
+            }
+            JwtAuthenticatorStyle::Jwks(jwks) => {
+                serde_json::json!({ "kind": "jwks", "jwks": jwks_status(jwks) })
+            }
+            JwtAuthenticatorStyle::StaticKeys(static_keys) => {
+                serde_json::json!({ "kind": "static", "keys": static_keys.len() })
+            }
+            JwtAuthenticatorStyle::Introspection(introspectors) => {
+                serde_json::json!({ "kind": "introspection", "introspection": introspection_status(introspectors) })
+            }
+            JwtAuthenticatorStyle::Mixed {
+                oidc,
+                jwks,
+                static_keys,
+                introspection,
+            } => serde_json::json!({
+                "kind": "mixed",
+                "oidc_providers": oidc.len(),
+                "jwks": jwks_status(jwks),
+                "static_keys": static_keys.len(),
+                "introspection": introspection_status(introspection),
+            }),
+        };
+
+        serde_json::json!({ "source": source, "validation": style })
+    }
+
     /// Extract authentication form the source (header or cookie) with a bearer token
     pub fn extract_jwt_token(
         &self,
@@ -1253,6 +1609,7 @@ impl JwtAuthenticator {
                 jwt_debug_log(|| format!("JWT token extracted ({} characters)", jwt_token.len()));
                 self.validate_jwt(&jwt_token)
                     .await
+                    .map(|claims| self.current_claim_mappings().apply(claims))
                     .map_err(|err| {
                         jwt_debug_log(|| format!("JWT validation error: {:?}", err));
                         match &err {
@@ -1292,7 +1649,10 @@ impl JwtAuthenticator {
 mod tests {
     use std::{
         collections::HashMap,
-        sync::Arc,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
         time::{SystemTime, UNIX_EPOCH},
     };
 
@@ -1429,6 +1789,123 @@ GBIdO8TlPVil1Dnd9iNPpQ==
         assert_eq!(authentication.is_err(), true);
     }
 
+    #[tokio::test]
+    async fn expiry_checked_against_clock() {
+        let env = MapEnvironment::from([(EXO_JWT_SECRET, "secret")]);
+        let now = chrono::Utc::now();
+
+        let claims = json!({
+            "sub": "b@b.com",
+        });
+
+        // Not yet expired according to the system time, but expired according to the clock
+        let (token, _) = create_token(&claims, "secret", 100, TokenSource::Header);
+        let authenticator = JwtAuthenticator::new_from_env(&env)
+            .await
+            .unwrap()
+            .unwrap()
+            .with_clock(Clock::frozen_at(now + chrono::Duration::days(1)));
+        let request_head =
+            request_head_with_headers(HashMap::from([("Authorization".to_string(), vec![token])]));
+        assert!(matches!(
+            authenticator.extract_authentication(&request_head).await,
+            Err(ContextExtractionError::ExpiredAuthentication)
+        ));
+
+        // Expired according to the system time, but not according to the clock
+        let (token, claims) = create_token(&claims, "secret", -100, TokenSource::Header);
+        let authenticator = JwtAuthenticator::new_from_env(&env)
+            .await
+            .unwrap()
+            .unwrap()
+            .with_clock(Clock::frozen_at(now - chrono::Duration::days(1)));
+        let request_head =
+            request_head_with_headers(HashMap::from([("Authorization".to_string(), vec![token])]));
+        assert_eq!(
+            authenticator
+                .extract_authentication(&request_head)
+                .await
+                .unwrap(),
+            claims
+        );
+    }
+
+    #[tokio::test]
+    async fn reload_rotates_secret() {
+        let env = MapEnvironment::from([(EXO_JWT_SECRET, "old-secret")]);
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+
+        let claims = json!({
+            "sub": "b@b.com",
+        });
+        let (old_token, _) = create_token(&claims, "old-secret", 100, TokenSource::Header);
+        let (new_token, _) = create_token(&claims, "new-secret", 100, TokenSource::Header);
+
+        let authenticate = |token: String| {
+            let request_head = request_head_with_headers(HashMap::from([(
+                "Authorization".to_string(),
+                vec![token],
+            )]));
+            let authenticator = &authenticator;
+            async move { authenticator.extract_authentication(&request_head).await }
+        };
+
+        assert!(authenticate(old_token.clone()).await.is_ok());
+        assert!(authenticate(new_token.clone()).await.is_err());
+
+        let rotated_env = MapEnvironment::from([(EXO_JWT_SECRET, "new-secret")]);
+        authenticator.reload_from_env(&rotated_env).await.unwrap();
+
+        assert!(authenticate(old_token.clone()).await.is_err());
+        assert!(authenticate(new_token.clone()).await.is_ok());
+
+        // An invalid configuration keeps the current one
+        let invalid_env =
+            MapEnvironment::from([(EXO_JWT_SECRET, "secret"), (EXO_OIDC_URL, "oidc")]);
+        assert!(authenticator.reload_from_env(&invalid_env).await.is_err());
+        assert!(
+            authenticator
+                .reload_from_env(&MapEnvironment::new())
+                .await
+                .is_err()
+        );
+        assert!(authenticate(new_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn claim_mappings_reshape_claims() {
+        let env = MapEnvironment::from([
+            (EXO_JWT_SECRET, "secret"),
+            (
+                EXO_JWT_CLAIM_MAPPINGS,
+                r#"[{ "from": "app.role", "to": "role" }, { "from": "scope", "convert": "split" }]"#,
+            ),
+        ]);
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+
+        let claims = json!({
+            "sub": "b@b.com",
+            "app": { "role": "admin" },
+            "scope": "read write",
+        });
+        let (token, _) = create_token(&claims, "secret", 100, TokenSource::Header);
+        let request_head =
+            request_head_with_headers(HashMap::from([("Authorization".to_string(), vec![token])]));
+
+        let claims = authenticator
+            .extract_authentication(&request_head)
+            .await
+            .unwrap();
+        assert_eq!(claims["role"], json!("admin"));
+        assert_eq!(claims["scope"], json!(["read", "write"]));
+
+        let invalid_env = MapEnvironment::from([
+            (EXO_JWT_SECRET, "secret"),
+            (EXO_JWT_CLAIM_MAPPINGS, r#"[{ "to": "role" }]"#),
+        ]);
+        assert!(JwtAuthenticator::new_from_env(&invalid_env).await.is_err());
+    }
+
     #[tokio::test]
     async fn valid_token_default_header() {
         let env = MapEnvironment::from([(EXO_JWT_SECRET, "secret")]);
@@ -1642,6 +2119,121 @@ GBIdO8TlPVil1Dnd9iNPpQ==
         }
     }
 
+    #[tokio::test]
+    async fn provider_config_introspects_opaque_tokens() {
+        let jwks_url = spawn_jwks_server(STATIC_JWKS).await;
+        let (introspection_url, introspection_requests) = spawn_introspection_server().await;
+
+        let provider_config = format!(
+            r#"[
+                {{"name":"nhost","strategy":"jwks","jwks_url":"{jwks_url}","issuer_aliases":["hasura-auth"]}},
+                {{"name":"opaque","strategy":"introspection","url":"{introspection_url}","client_id":"exo","client_secret_env":"INTROSPECTION_SECRET","audiences":["concerts-api"]}}
+            ]"#
+        );
+
+        let env = MapEnvironment::from([
+            (EXO_JWT_PROVIDER_CONFIG, provider_config.as_str()),
+            ("INTROSPECTION_SECRET", "introspection-secret"),
+        ]);
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+
+        let authenticate = |token: String| {
+            let request_head = request_head_with_headers(HashMap::from([(
+                "Authorization".to_string(),
+                vec![token],
+            )]));
+            let authenticator = &authenticator;
+            async move { authenticator.extract_authentication(&request_head).await }
+        };
+
+        // Active tokens are cached
+        for _ in 0..2 {
+            let claims = authenticate(format!("{TOKEN_PREFIX}active-token"))
+                .await
+                .unwrap();
+            assert_eq!(claims["sub"], json!("service-account"));
+        }
+        assert_eq!(introspection_requests.load(Ordering::SeqCst), 1);
+
+        // So are inactive tokens and tokens for other audiences
+        for token in ["inactive-token", "other-audience-token"] {
+            for _ in 0..2 {
+                let result = authenticate(format!("{TOKEN_PREFIX}{token}")).await;
+                assert!(matches!(result, Err(ContextExtractionError::Unauthorized)));
+            }
+        }
+        assert_eq!(introspection_requests.load(Ordering::SeqCst), 3);
+
+        // JWTs are still validated by their signature (without introspection)
+        assert!(
+            authenticate(create_nhost_bearer_token("hasura-auth"))
+                .await
+                .is_ok()
+        );
+        assert_eq!(introspection_requests.load(Ordering::SeqCst), 3);
+
+        // The client secret must be available
+        let missing_secret_env =
+            MapEnvironment::from([(EXO_JWT_PROVIDER_CONFIG, provider_config.as_str())]);
+        assert!(
+            JwtAuthenticator::new_from_env(&missing_secret_env)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn provider_config_selects_provider_by_issuer() {
+        let jwks_url = spawn_jwks_server(STATIC_JWKS).await;
+
+        // Both providers accept tokens signed with the same key, so only the issuer tells them apart
+        let provider_config = format!(
+            r#"[
+                {{"name":"users","strategy":"jwks","jwks_url":"{jwks_url}","issuer_aliases":["hasura-auth"]}},
+                {{"name":"services","strategy":"jwks","jwks_url":"{jwks_url}","issuer_aliases":["internal"],"claims_namespace":"service"}}
+            ]"#
+        );
+
+        let mut env = MapEnvironment::new();
+        env.set(EXO_JWT_PROVIDER_CONFIG, &provider_config);
+
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+
+        let user_id = |claims: &Value| {
+            claims
+                .get("https://hasura.io/jwt/claims")
+                .and_then(|value| value.get("x-hasura-user-id"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+
+        let extract = |issuer: &str| {
+            let request = request_head_with_headers(HashMap::from([(
+                "Authorization".to_string(),
+                vec![create_nhost_bearer_token(issuer)],
+            )]));
+            let authenticator = &authenticator;
+            async move { authenticator.extract_authentication(&request).await }
+        };
+
+        let user_claims = extract("hasura-auth").await.unwrap();
+        assert_eq!(
+            user_id(&user_claims).as_deref(),
+            Some("15de885c-6cb0-480f-97ce-b8b8ece225d5")
+        );
+        assert!(user_claims.get("service").is_none());
+
+        let service_claims = extract("internal").await.unwrap();
+        assert_eq!(user_id(&service_claims), None);
+        assert_eq!(
+            service_claims.get("service").and_then(user_id).as_deref(),
+            Some("15de885c-6cb0-480f-97ce-b8b8ece225d5")
+        );
+
+        let result = extract("unexpected-issuer").await;
+        assert!(matches!(result, Err(ContextExtractionError::Unauthorized)));
+    }
+
     #[tokio::test]
     async fn provider_config_accepts_wrapped_json_string() {
         let jwks_url = spawn_jwks_server(STATIC_JWKS).await;
@@ -1702,6 +2294,44 @@ GBIdO8TlPVil1Dnd9iNPpQ==
         );
     }
 
+    #[tokio::test]
+    async fn status_describes_secret_without_revealing_it() {
+        let env = MapEnvironment::from([
+            (EXO_JWT_SECRET, "do-not-report"),
+            (EXO_JWT_SOURCE_COOKIE, "jwt-cookie"),
+        ]);
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+
+        let status = authenticator.status();
+        assert_eq!(
+            status,
+            json!({
+                "source": { "cookie": "jwt-cookie" },
+                "validation": { "kind": "secret" },
+            })
+        );
+        assert!(!status.to_string().contains("do-not-report"));
+    }
+
+    #[tokio::test]
+    async fn status_lists_fetched_jwks_keys() {
+        let jwks_url = spawn_jwks_server(STATIC_JWKS).await;
+
+        let env = MapEnvironment::from([(EXO_JWKS_URLS, jwks_url.as_str())]);
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+
+        assert_eq!(
+            authenticator.status(),
+            json!({
+                "source": { "header": "Authorization" },
+                "validation": {
+                    "kind": "jwks",
+                    "jwks": [{ "url": jwks_url, "kids": [STATIC_KEY_KID] }],
+                },
+            })
+        );
+    }
+
     fn request_head_with_headers(headers: HashMap<String, Vec<String>>) -> MemoryRequestHead {
         MemoryRequestHead::new(
             headers,
@@ -1829,6 +2459,78 @@ GBIdO8TlPVil1Dnd9iNPpQ==
         format!("{}{}", TOKEN_PREFIX, token)
     }
 
+    /// An introspection endpoint that reports `active-token` (and `other-audience-token`, but for
+    /// a different audience) as active. Also returns the number of requests made to it.
+    async fn spawn_introspection_server() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let server_requests = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let requests = server_requests.clone();
+                tokio::spawn(async move {
+                    // Read the whole request (the body may arrive after the headers)
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    loop {
+                        let read = socket.read(&mut buffer).await.unwrap_or(0);
+                        request.extend_from_slice(&buffer[..read]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        let complete = text.split_once("\r\n\r\n").is_some_and(|(head, body)| {
+                            let content_length = head
+                                .lines()
+                                .find_map(|line| {
+                                    line.to_ascii_lowercase()
+                                        .strip_prefix("content-length:")
+                                        .map(|value| value.trim().parse::<usize>().unwrap_or(0))
+                                })
+                                .unwrap_or(0);
+                            body.len() >= content_length
+                        });
+                        if read == 0 || complete {
+                            break;
+                        }
+                    }
+                    requests.fetch_add(1, Ordering::SeqCst);
+
+                    let request = String::from_utf8_lossy(&request);
+                    let authorized = request.lines().any(|line| {
+                        line.eq_ignore_ascii_case(&format!(
+                            "authorization: Basic {}",
+                            base64::engine::general_purpose::STANDARD
+                                .encode("exo:introspection-secret")
+                        ))
+                    });
+
+                    let body = if !authorized {
+                        json!({ "active": false })
+                    } else if request.ends_with("token=active-token&token_type_hint=access_token") {
+                        json!({ "active": true, "sub": "service-account", "aud": "concerts-api" })
+                    } else if request
+                        .ends_with("token=other-audience-token&token_type_hint=access_token")
+                    {
+                        json!({ "active": true, "sub": "service-account", "aud": "billing-api" })
+                    } else {
+                        json!({ "active": false })
+                    }
+                    .to_string();
+
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        (format!("http://{}/introspect", addr), requests)
+    }
+
     async fn spawn_jwks_server(body: &'static str) -> String {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
@@ -0,0 +1,321 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Declarative rules to reshape the claims of validated tokens before binding them to contexts.
+//!
+//! Each rule takes the claim at `from` and puts it (optionally converted) at `to`. For example,
+//! with the following rules, the Hasura-style user id becomes the `userId` claim, and the
+//! space-delimited `scope` claim becomes an array:
+//!
+//! ```json
+//! [
+//!   { "from": ["https://hasura.io/jwt/claims", "x-hasura-user-id"], "to": "userId" },
+//!   { "from": "scope", "convert": "split" }
+//! ]
+//! ```
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::authenticator::{JwtConfigurationError, jwt_debug_log};
+
+#[derive(Debug, Default, Clone)]
+pub(super) struct ClaimMappings(Vec<ClaimMapping>);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClaimMapping {
+    from: ClaimPath,
+    /// Where to put the claim (the same place as `from`, if not specified)
+    #[serde(default)]
+    to: Option<ClaimPath>,
+    #[serde(default)]
+    convert: Option<ClaimConversion>,
+    /// The separator for the `split` conversion (whitespace, if not specified)
+    #[serde(default)]
+    separator: Option<String>,
+}
+
+/// The path to a (possibly nested) claim. Either a dot-separated string (`"app.roles"`) or the
+/// list of keys (`["https://example.com/claims", "roles"]`) for keys that contain dots.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ClaimPath {
+    Dotted(String),
+    Keys(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ClaimConversion {
+    /// Split a delimited string (such as an OAuth `scope`) into an array of strings
+    Split,
+    Number,
+    Boolean,
+    String,
+}
+
+impl ClaimPath {
+    fn keys(&self) -> Vec<&str> {
+        match self {
+            ClaimPath::Dotted(path) => path.split('.').collect(),
+            ClaimPath::Keys(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl ClaimMappings {
+    /// Parse the rules from their JSON representation. The `source` (environment variable or
+    /// provider name) is used only to report errors.
+    pub(super) fn parse(raw: &str, source: &str) -> Result<Self, JwtConfigurationError> {
+        let mappings: Vec<ClaimMapping> = serde_json::from_str(raw).map_err(|err| {
+            JwtConfigurationError::InvalidSetup(format!(
+                "Failed to parse the claim mappings of {source}: {err}"
+            ))
+        })?;
+
+        ClaimMappings::new(mappings, source)
+    }
+
+    /// Parse the rules embedded in a provider descriptor
+    pub(super) fn from_value(value: Value, source: &str) -> Result<Self, JwtConfigurationError> {
+        let mappings: Vec<ClaimMapping> = serde_json::from_value(value).map_err(|err| {
+            JwtConfigurationError::InvalidSetup(format!(
+                "Failed to parse the claim mappings of {source}: {err}"
+            ))
+        })?;
+
+        ClaimMappings::new(mappings, source)
+    }
+
+    fn new(mappings: Vec<ClaimMapping>, source: &str) -> Result<Self, JwtConfigurationError> {
+        for mapping in &mappings {
+            let paths = std::iter::once(&mapping.from).chain(mapping.to.as_ref());
+            for path in paths {
+                if path.keys().iter().any(|key| key.is_empty()) {
+                    return Err(JwtConfigurationError::InvalidSetup(format!(
+                        "Invalid claim path {path:?} in the claim mappings of {source}"
+                    )));
+                }
+            }
+
+            if mapping.separator.is_some() && mapping.convert != Some(ClaimConversion::Split) {
+                return Err(JwtConfigurationError::InvalidSetup(format!(
+                    "A separator is allowed only with the 'split' conversion (claim {:?} in the claim mappings of {source})",
+                    mapping.from
+                )));
+            }
+        }
+
+        Ok(ClaimMappings(mappings))
+    }
+
+    /// Apply the rules in order. Rules whose source claim is absent are skipped, and values that
+    /// can't be converted are dropped (so access rules see the claim as missing, rather than with
+    /// an unexpected type).
+    pub(super) fn apply(&self, mut claims: Value) -> Value {
+        for mapping in &self.0 {
+            let from = mapping.from.keys();
+            let Some(value) = take_claim(&mut claims, &from) else {
+                continue;
+            };
+
+            let converted = match mapping.convert {
+                Some(conversion) => convert(value, conversion, mapping.separator.as_deref()),
+                None => Some(value),
+            };
+
+            let to = mapping.to.as_ref().map(ClaimPath::keys);
+            let to = to.as_deref().unwrap_or(&from);
+            match converted {
+                Some(value) => {
+                    if !put_claim(&mut claims, to, value) {
+                        jwt_debug_log(|| {
+                            format!("Claim mapping skipped: {to:?} isn't within an object")
+                        });
+                    }
+                }
+                None => jwt_debug_log(|| {
+                    format!("Claim mapping dropped {from:?}: the value can't be converted")
+                }),
+            }
+        }
+
+        claims
+    }
+}
+
+fn take_claim(claims: &mut Value, path: &[&str]) -> Option<Value> {
+    let (last, parents) = path.split_last()?;
+
+    let parent = parents
+        .iter()
+        .try_fold(claims, |value, key| value.get_mut(*key))?;
+
+    parent.as_object_mut()?.remove(*last)
+}
+
+/// Put the value at the path (creating the intermediate objects as needed). Returns false, if an
+/// intermediate claim exists, but isn't an object.
+fn put_claim(claims: &mut Value, path: &[&str], value: Value) -> bool {
+    let Some((last, parents)) = path.split_last() else {
+        return false;
+    };
+
+    let mut current = claims;
+    for key in parents {
+        let Some(object) = current.as_object_mut() else {
+            return false;
+        };
+        current = object
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    match current.as_object_mut() {
+        Some(object) => {
+            object.insert(last.to_string(), value);
+            true
+        }
+        None => false,
+    }
+}
+
+fn convert(value: Value, conversion: ClaimConversion, separator: Option<&str>) -> Option<Value> {
+    match (conversion, value) {
+        (ClaimConversion::Split, Value::String(value)) => {
+            let parts: Vec<&str> = match separator {
+                Some(separator) => value.split(separator).collect(),
+                None => value.split_whitespace().collect(),
+            };
+            Some(Value::Array(
+                parts
+                    .into_iter()
+                    .map(str::trim)
+                    .filter(|part| !part.is_empty())
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            ))
+        }
+        (ClaimConversion::Split, value @ Value::Array(_)) => Some(value),
+
+        (ClaimConversion::Number, value @ Value::Number(_)) => Some(value),
+        (ClaimConversion::Number, Value::String(value)) => {
+            let value = value.trim();
+            match value.parse::<i64>() {
+                Ok(number) => Some(Value::from(number)),
+                Err(_) => value
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number),
+            }
+        }
+
+        (ClaimConversion::Boolean, value @ Value::Bool(_)) => Some(value),
+        (ClaimConversion::Boolean, Value::String(value)) => {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            }
+        }
+
+        (ClaimConversion::String, value @ Value::String(_)) => Some(value),
+        (ClaimConversion::String, value @ (Value::Number(_) | Value::Bool(_))) => {
+            Some(Value::String(value.to_string()))
+        }
+
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn mappings(raw: &str) -> ClaimMappings {
+        ClaimMappings::parse(raw, "test").unwrap()
+    }
+
+    #[test]
+    fn rename_nested_claims() {
+        let mappings = mappings(
+            r#"[
+                { "from": ["https://hasura.io/jwt/claims", "x-hasura-user-id"], "to": "userId" },
+                { "from": "app.role", "to": "auth.role" }
+            ]"#,
+        );
+
+        let claims = mappings.apply(json!({
+            "sub": "42",
+            "https://hasura.io/jwt/claims": { "x-hasura-user-id": "u1", "x-hasura-default-role": "coach" },
+            "app": { "role": "admin" }
+        }));
+
+        assert_eq!(
+            claims,
+            json!({
+                "sub": "42",
+                "userId": "u1",
+                "https://hasura.io/jwt/claims": { "x-hasura-default-role": "coach" },
+                "app": {},
+                "auth": { "role": "admin" }
+            })
+        );
+    }
+
+    #[test]
+    fn convert_claims() {
+        let mappings = mappings(
+            r#"[
+                { "from": "scope", "convert": "split" },
+                { "from": "groups", "convert": "split", "separator": "," },
+                { "from": "age", "convert": "number" },
+                { "from": "ratio", "convert": "number" },
+                { "from": "verified", "convert": "boolean" },
+                { "from": "org", "convert": "string" },
+                { "from": "level", "convert": "number" },
+                { "from": "missing", "convert": "number" }
+            ]"#,
+        );
+
+        let claims = mappings.apply(json!({
+            "scope": "read:concerts  write:concerts",
+            "groups": "admins, editors",
+            "age": "42",
+            "ratio": "0.5",
+            "verified": "TRUE",
+            "org": 7,
+            "level": "high"
+        }));
+
+        assert_eq!(
+            claims,
+            json!({
+                "scope": ["read:concerts", "write:concerts"],
+                "groups": ["admins", "editors"],
+                "age": 42,
+                "ratio": 0.5,
+                "verified": true,
+                "org": "7"
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_mappings() {
+        assert!(ClaimMappings::parse(r#"[{ "from": "a..b" }]"#, "test").is_err());
+        assert!(ClaimMappings::parse(r#"[{ "from": "a", "convert": "date" }]"#, "test").is_err());
+        assert!(ClaimMappings::parse(r#"[{ "from": "a", "separator": "," }]"#, "test").is_err());
+        assert!(ClaimMappings::parse(r#"[{ "from": "a", "rename": "b" }]"#, "test").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Validation of opaque access tokens through an OAuth 2.0 token introspection endpoint
+//! ([RFC 7662](https://www.rfc-editor.org/rfc/rfc7662)).
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use super::authenticator::{JwtConfigurationError, jwt_debug_log};
+use super::claim_mapping::ClaimMappings;
+use super::jwks::JwtValidationError;
+
+pub(super) const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+pub(super) const DEFAULT_NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Beyond this many cached results, expired entries are evicted (and if that isn't enough, the
+/// whole cache), so a flood of distinct (invalid) tokens can't grow the cache unboundedly
+const MAX_CACHE_ENTRIES: usize = 10_000;
+
+pub(super) struct TokenIntrospector {
+    url: String,
+    /// Credentials to authenticate with the introspection endpoint (using HTTP basic auth)
+    client_credentials: Option<(String, String)>,
+    client: reqwest::Client,
+    allowed_audiences: Option<Vec<String>>,
+    cache_ttl: Duration,
+    negative_cache_ttl: Duration,
+    /// Keyed by the SHA-256 digest of the token (so the cache doesn't hold the tokens themselves)
+    cache: Mutex<HashMap<[u8; 32], CachedIntrospection>>,
+    /// Nest the claims of tokens from this provider under this key
+    claims_namespace: Option<String>,
+    /// Applied to the claims of tokens from this provider (before namespacing them)
+    claim_mappings: ClaimMappings,
+}
+
+struct CachedIntrospection {
+    /// The claims of an active token (`None` for an inactive or rejected token)
+    claims: Option<Value>,
+    expires_at: Instant,
+}
+
+impl TokenIntrospector {
+    pub(super) fn new(
+        url: String,
+        client_credentials: Option<(String, String)>,
+        allowed_audiences: Option<Vec<String>>,
+        cache_ttl: Duration,
+        negative_cache_ttl: Duration,
+    ) -> Result<Self, JwtConfigurationError> {
+        let client = reqwest::ClientBuilder::new().build().map_err(|e| {
+            JwtConfigurationError::Configuration {
+                message: "Unable to create HTTP client".to_owned(),
+                source: e.into(),
+            }
+        })?;
+
+        Ok(Self {
+            url,
+            client_credentials,
+            client,
+            allowed_audiences,
+            cache_ttl,
+            negative_cache_ttl,
+            cache: Mutex::new(HashMap::new()),
+            claims_namespace: None,
+            claim_mappings: ClaimMappings::default(),
+        })
+    }
+
+    pub(super) fn with_claims_namespace(mut self, claims_namespace: Option<String>) -> Self {
+        self.claims_namespace = claims_namespace;
+        self
+    }
+
+    pub(super) fn claims_namespace(&self) -> Option<&str> {
+        self.claims_namespace.as_deref()
+    }
+
+    pub(super) fn with_claim_mappings(mut self, claim_mappings: ClaimMappings) -> Self {
+        self.claim_mappings = claim_mappings;
+        self
+    }
+
+    pub(super) fn claim_mappings(&self) -> &ClaimMappings {
+        &self.claim_mappings
+    }
+
+    pub(super) fn debug_source(&self) -> &str {
+        &self.url
+    }
+
+    /// Validate the token by asking the introspection endpoint (or using a cached answer). The
+    /// claims are the members of the introspection response (`sub`, `scope`, `client_id`, etc.).
+    pub(super) async fn validate(&self, token: &str) -> Result<Value, JwtValidationError> {
+        let key: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+
+        if let Some(cached) = self.cached(&key) {
+            jwt_debug_log(|| format!("Using the cached introspection result from '{}'", self.url));
+            return cached.ok_or(JwtValidationError::Invalid);
+        }
+
+        let response = self.introspect(token).await?;
+
+        let (claims, ttl) = match self.check_response(response) {
+            Ok((claims, remaining)) => {
+                let ttl =
+                    remaining.map_or(self.cache_ttl, |remaining| remaining.min(self.cache_ttl));
+                (Some(claims), ttl)
+            }
+            Err(JwtValidationError::Expired) => return Err(JwtValidationError::Expired),
+            Err(_) => (None, self.negative_cache_ttl),
+        };
+
+        self.cache(key, claims.clone(), ttl);
+
+        claims.ok_or(JwtValidationError::Invalid)
+    }
+
+    async fn introspect(&self, token: &str) -> Result<Value, JwtValidationError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json")
+            .form(&[("token", token), ("token_type_hint", "access_token")]);
+
+        if let Some((client_id, client_secret)) = &self.client_credentials {
+            request = request.basic_auth(client_id, Some(client_secret));
+        }
+
+        // Failures to reach the endpoint are transient, so (unlike inactive tokens) not cached
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                error!("Failed to introspect token with '{}': {}", self.url, e);
+                JwtValidationError::Invalid
+            })?;
+
+        response.json::<Value>().await.map_err(|e| {
+            error!(
+                "Failed to parse the introspection response from '{}': {}",
+                self.url, e
+            );
+            JwtValidationError::Invalid
+        })
+    }
+
+    /// Check that the token is active, unexpired, and meant for us. Returns the claims along with
+    /// the remaining lifetime of the token (if the response specifies `exp`).
+    fn check_response(
+        &self,
+        response: Value,
+    ) -> Result<(Value, Option<Duration>), JwtValidationError> {
+        if response.get("active").and_then(Value::as_bool) != Some(true) {
+            jwt_debug_log(|| {
+                format!(
+                    "Introspection endpoint '{}' reported an inactive token",
+                    self.url
+                )
+            });
+            return Err(JwtValidationError::Invalid);
+        }
+
+        let remaining = match response.get("exp").and_then(Value::as_u64) {
+            Some(exp) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                if exp <= now {
+                    return Err(JwtValidationError::Expired);
+                }
+                Some(Duration::from_secs(exp - now))
+            }
+            None => None,
+        };
+
+        if let Some(allowed_audiences) = &self.allowed_audiences {
+            let audience_matches = |audience: &Value| {
+                audience
+                    .as_str()
+                    .is_some_and(|audience| allowed_audiences.iter().any(|a| a == audience))
+            };
+            let matches = match response.get("aud") {
+                Some(Value::Array(audiences)) => audiences.iter().any(audience_matches),
+                Some(audience) => audience_matches(audience),
+                None => false,
+            };
+
+            if !matches {
+                jwt_debug_log(|| {
+                    format!(
+                        "Introspection endpoint '{}' returned a token for another audience",
+                        self.url
+                    )
+                });
+                return Err(JwtValidationError::Invalid);
+            }
+        }
+
+        Ok((response, remaining))
+    }
+
+    fn cached(&self, key: &[u8; 32]) -> Option<Option<Value>> {
+        let cache = self.cache.lock().unwrap();
+        cache
+            .get(key)
+            .filter(|cached| cached.expires_at > Instant::now())
+            .map(|cached| cached.claims.clone())
+    }
+
+    fn cache(&self, key: [u8; 32], claims: Option<Value>, ttl: Duration) {
+        if ttl.is_zero() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut cache = self.cache.lock().unwrap();
+
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.retain(|_, cached| cached.expires_at > now);
+            if cache.len() >= MAX_CACHE_ENTRIES {
+                cache.clear();
+            }
+        }
+
+        cache.insert(
+            key,
+            CachedIntrospection {
+                claims,
+                expires_at: now + ttl,
+            },
+        );
+    }
+}
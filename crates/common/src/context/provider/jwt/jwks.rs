@@ -5,6 +5,7 @@ use std::collections::{HashMap, HashSet};
 use tracing::{error, warn};
 
 use super::authenticator::{JwtConfigurationError, jwt_debug_enabled, jwt_debug_log};
+use super::claim_mapping::ClaimMappings;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Jwks {
@@ -29,6 +30,10 @@ pub struct JwksValidator {
     client: reqwest::Client,
     allowed_audiences: Option<Vec<String>>,
     allowed_issuers: Option<Vec<String>>,
+    /// Nest the claims of tokens from this provider under this key
+    claims_namespace: Option<String>,
+    /// Applied to the claims of tokens from this provider (before namespacing them)
+    claim_mappings: ClaimMappings,
 }
 
 impl JwksValidator {
@@ -72,6 +77,8 @@ impl JwksValidator {
             client: client.clone(),
             allowed_audiences,
             allowed_issuers: normalized_issuers,
+            claims_namespace: None,
+            claim_mappings: ClaimMappings::default(),
         };
 
         // Fetch initial keys
@@ -142,6 +149,33 @@ impl JwksValidator {
         Ok(())
     }
 
+    pub(super) fn with_claims_namespace(mut self, claims_namespace: Option<String>) -> Self {
+        self.claims_namespace = claims_namespace;
+        self
+    }
+
+    pub(super) fn claims_namespace(&self) -> Option<&str> {
+        self.claims_namespace.as_deref()
+    }
+
+    pub(super) fn with_claim_mappings(mut self, claim_mappings: ClaimMappings) -> Self {
+        self.claim_mappings = claim_mappings;
+        self
+    }
+
+    pub(super) fn claim_mappings(&self) -> &ClaimMappings {
+        &self.claim_mappings
+    }
+
+    /// Could a token with the given `iss` claim come from this provider? Without configured
+    /// issuers, any issuer could.
+    pub(super) fn handles_issuer(&self, issuer: &str) -> bool {
+        let issuer = issuer.trim().trim_end_matches('/');
+        self.allowed_issuers
+            .as_ref()
+            .is_none_or(|issuers| issuers.iter().any(|allowed| allowed == issuer))
+    }
+
     pub async fn validate(&self, token: &str) -> Result<Value, JwtValidationError> {
         // Decode header to get kid
         let header = decode_header(token).map_err(|e| {
@@ -8,7 +8,9 @@
 // by the Apache License, Version 2.0.
 
 mod authenticator;
+mod claim_mapping;
 mod extractor;
+mod introspection;
 mod jwks;
 mod oidc;
 mod static_key;
@@ -3,9 +3,16 @@ use serde_json::Value;
 use std::collections::HashSet;
 
 use super::authenticator::JwtConfigurationError;
+use super::claim_mapping::ClaimMappings;
 
 pub struct Oidc {
     validator: Validator,
+    /// The accepted issuers (without a trailing slash)
+    issuers: Vec<String>,
+    /// Nest the claims of tokens from this provider under this key
+    claims_namespace: Option<String>,
+    /// Applied to the claims of tokens from this provider (before namespacing them)
+    claim_mappings: ClaimMappings,
 }
 
 impl Oidc {
@@ -58,7 +65,43 @@ impl Oidc {
                 source: e.into(),
             })?;
 
-        Ok(Self { validator })
+        let issuers = issuers
+            .into_iter()
+            .map(|issuer| issuer.trim_end_matches('/').to_string())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        Ok(Self {
+            validator,
+            issuers,
+            claims_namespace: None,
+            claim_mappings: ClaimMappings::default(),
+        })
+    }
+
+    pub(super) fn with_claims_namespace(mut self, claims_namespace: Option<String>) -> Self {
+        self.claims_namespace = claims_namespace;
+        self
+    }
+
+    pub(super) fn claims_namespace(&self) -> Option<&str> {
+        self.claims_namespace.as_deref()
+    }
+
+    pub(super) fn with_claim_mappings(mut self, claim_mappings: ClaimMappings) -> Self {
+        self.claim_mappings = claim_mappings;
+        self
+    }
+
+    pub(super) fn claim_mappings(&self) -> &ClaimMappings {
+        &self.claim_mappings
+    }
+
+    /// Could a token with the given `iss` claim come from this provider?
+    pub(super) fn handles_issuer(&self, issuer: &str) -> bool {
+        let issuer = issuer.trim().trim_end_matches('/');
+        self.issuers.iter().any(|candidate| candidate == issuer)
     }
 
     pub(super) async fn validate(&self, token: &str) -> Result<Value, ValidationError> {
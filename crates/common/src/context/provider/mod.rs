@@ -7,9 +7,27 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub(crate) mod client_cert;
 pub(crate) mod cookie;
+pub(crate) mod deployment;
 pub(crate) mod environment;
+pub mod flag;
 pub(crate) mod header;
 pub(crate) mod ip;
 pub(crate) mod jwt;
 pub(crate) mod query;
+pub(crate) mod webhook;
+
+/// The annotations of the built-in context sources (which plugins may not provide)
+pub const BUILT_IN_CONTEXT_ANNOTATIONS: &[&str] = &[
+    "env",
+    "query",
+    "header",
+    "clientIp",
+    "clientCert",
+    "deployment",
+    "webhook",
+    "cookie",
+    "jwt",
+    "flag",
+];
@@ -16,7 +16,7 @@ use tracing::{trace, warn};
 
 use crate::context::{ContextExtractionError, RequestContext, context_extractor::ContextExtractor};
 use crate::env_const::get_graphql_http_path;
-use crate::http::{RequestHead, RequestPayload};
+use crate::http::{ClientCertificate, RequestHead, RequestPayload};
 use crate::operation_payload::OperationsPayload;
 
 pub struct QueryExtractor;
@@ -155,4 +155,8 @@ impl<'original> RequestHead for OverriddenRequestHead<'original> {
     fn get_method(&self) -> http::Method {
         self.original_head.get_method()
     }
+
+    fn get_client_certificate(&self) -> Option<&ClientCertificate> {
+        self.original_head.get_client_certificate()
+    }
 }
@@ -0,0 +1,371 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use exo_env::Environment;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::context::{ContextExtractionError, RequestContext, context_extractor::ContextExtractor};
+
+const EXO_WEBHOOK_GITHUB_SECRET: &str = "EXO_WEBHOOK_GITHUB_SECRET";
+const EXO_WEBHOOK_STRIPE_SECRET: &str = "EXO_WEBHOOK_STRIPE_SECRET";
+const EXO_WEBHOOK_STRIPE_TOLERANCE: &str = "EXO_WEBHOOK_STRIPE_TOLERANCE";
+
+/// Maximum age (in seconds) of a Stripe signature timestamp, to limit replays
+const DEFAULT_STRIPE_TOLERANCE: u64 = 300;
+
+/// Verifies HMAC-signed webhook requests (such as `@webhook("verified") verified: Boolean`).
+///
+/// The provider is determined by the signature header (`X-Hub-Signature-256` for GitHub and
+/// `Stripe-Signature` for Stripe) and the signature is checked against the raw request body
+/// using the secrets in `EXO_WEBHOOK_GITHUB_SECRET` or `EXO_WEBHOOK_STRIPE_SECRET` (each a
+/// comma-separated list to allow rotating secrets). Empty secrets are ignored, since anyone could
+/// compute a signature with an empty key.
+///
+/// The `verified` attribute is always present. The other attributes (`provider`, `event`, and
+/// `deliveryId`) are present only for verified requests, so they can't be spoofed.
+pub struct WebhookExtractor;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WebhookProvider {
+    GitHub,
+    Stripe,
+}
+
+impl WebhookProvider {
+    fn name(&self) -> &'static str {
+        match self {
+            WebhookProvider::GitHub => "github",
+            WebhookProvider::Stripe => "stripe",
+        }
+    }
+}
+
+struct VerifiedWebhook {
+    provider: WebhookProvider,
+    event: Option<String>,
+    delivery_id: Option<String>,
+}
+
+#[async_trait]
+impl ContextExtractor for WebhookExtractor {
+    fn annotation_name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn extract_context_field(
+        &self,
+        key: &str,
+        request_context: &RequestContext,
+    ) -> Result<Option<Value>, ContextExtractionError> {
+        let webhook = verify(request_context)?;
+
+        let value = match key {
+            "verified" => Some(Value::Bool(webhook.is_some())),
+            "provider" => webhook.map(|webhook| Value::String(webhook.provider.name().into())),
+            "event" => webhook.and_then(|webhook| webhook.event.map(Value::String)),
+            "deliveryId" => webhook.and_then(|webhook| webhook.delivery_id.map(Value::String)),
+            _ => {
+                return Err(ContextExtractionError::Generic(format!(
+                    "Unknown webhook attribute '{key}' (expected verified, provider, event, or deliveryId)"
+                )));
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+fn verify(
+    request_context: &RequestContext,
+) -> Result<Option<VerifiedWebhook>, ContextExtractionError> {
+    use crate::http::RequestPayload;
+
+    // Use the original request (and not one made internally to evaluate a `@query` context)
+    let request = request_context.get_base_context().get_request();
+    let head = request.get_head();
+    let env = request_context.system_context.env;
+
+    let Some(body) = request.get_raw_body() else {
+        return Ok(None);
+    };
+
+    if let Some(signature) = head.get_header("x-hub-signature-256") {
+        let secrets = webhook_secrets(env, EXO_WEBHOOK_GITHUB_SECRET)?;
+
+        return Ok(
+            verify_github_signature(&signature, body, &secrets).then(|| VerifiedWebhook {
+                provider: WebhookProvider::GitHub,
+                event: head.get_header("x-github-event"),
+                delivery_id: head.get_header("x-github-delivery"),
+            }),
+        );
+    }
+
+    if let Some(signature) = head.get_header("stripe-signature") {
+        let secrets = webhook_secrets(env, EXO_WEBHOOK_STRIPE_SECRET)?;
+        let tolerance = match env.get(EXO_WEBHOOK_STRIPE_TOLERANCE) {
+            Some(tolerance) => tolerance.parse::<u64>().map_err(|_| {
+                ContextExtractionError::Generic(format!(
+                    "{EXO_WEBHOOK_STRIPE_TOLERANCE} must be a number of seconds"
+                ))
+            })?,
+            None => DEFAULT_STRIPE_TOLERANCE,
+        };
+
+        // Use the request's clock (which may be frozen in tests) to check the timestamp
+        let now = request_context
+            .system_context
+            .clock
+            .now()
+            .timestamp()
+            .max(0) as u64;
+
+        if !verify_stripe_signature(&signature, body, &secrets, now, tolerance) {
+            return Ok(None);
+        }
+
+        // Stripe sends the event type and id in the (now verified) body
+        let event: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+        let field = |name: &str| event.get(name).and_then(Value::as_str).map(String::from);
+
+        return Ok(Some(VerifiedWebhook {
+            provider: WebhookProvider::Stripe,
+            event: field("type"),
+            delivery_id: field("id"),
+        }));
+    }
+
+    Ok(None)
+}
+
+/// The non-empty secrets in the variable (none if it isn't set). A variable that is set but has no
+/// non-empty secrets (such as `EXO_WEBHOOK_GITHUB_SECRET=` or `EXO_WEBHOOK_GITHUB_SECRET=,`) is a
+/// configuration error rather than a way to skip verification.
+fn webhook_secrets(
+    env: &dyn Environment,
+    key: &str,
+) -> Result<Vec<String>, ContextExtractionError> {
+    let Some(value) = env.get(key) else {
+        return Ok(vec![]);
+    };
+
+    let secrets: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|secret| !secret.is_empty())
+        .map(String::from)
+        .collect();
+
+    if secrets.is_empty() {
+        return Err(ContextExtractionError::Generic(format!(
+            "{key} must contain at least one non-empty secret"
+        )));
+    }
+
+    Ok(secrets)
+}
+
+/// Verify a GitHub signature of the form `sha256=<hex-encoded HMAC of the body>`
+fn verify_github_signature(signature: &str, body: &[u8], secrets: &[String]) -> bool {
+    let Some(signature) = signature.strip_prefix("sha256=").and_then(decode_hex) else {
+        return false;
+    };
+
+    secrets
+        .iter()
+        .any(|secret| verify_hmac(secret, &[body], &signature))
+}
+
+/// Verify a Stripe signature of the form `t=<timestamp>,v1=<hex-encoded HMAC>[,v1=...]`,
+/// where the HMAC is computed over `<timestamp>.<body>`
+fn verify_stripe_signature(
+    signature: &str,
+    body: &[u8],
+    secrets: &[String],
+    now: u64,
+    tolerance: u64,
+) -> bool {
+    let mut timestamp = None;
+    let mut signatures = vec![];
+
+    for part in signature.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<u64>().ok().map(|t| (value, t)),
+            Some(("v1", value)) => signatures.extend(decode_hex(value)),
+            _ => {}
+        }
+    }
+
+    let Some((timestamp_str, timestamp)) = timestamp else {
+        return false;
+    };
+
+    if now.abs_diff(timestamp) > tolerance {
+        return false;
+    }
+
+    let signed_prefix = format!("{timestamp_str}.");
+
+    signatures.iter().any(|signature| {
+        secrets
+            .iter()
+            .any(|secret| verify_hmac(secret, &[signed_prefix.as_bytes(), body], signature))
+    })
+}
+
+fn verify_hmac(secret: &str, parts: &[&[u8]], signature: &[u8]) -> bool {
+    if secret.trim().is_empty() {
+        return false;
+    }
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+
+    for part in parts {
+        mac.update(part);
+    }
+
+    // Constant-time comparison
+    mac.verify_slice(signature).is_ok()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    fn sign(secret: &str, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(payload);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    #[test]
+    fn github_signature() {
+        let body = br#"{"action":"opened"}"#;
+        let secrets = vec!["old".to_string(), "current".to_string()];
+        let signature = format!("sha256={}", sign("current", body));
+
+        assert!(verify_github_signature(&signature, body, &secrets));
+        assert!(!verify_github_signature(
+            &signature,
+            br#"{"action":"closed"}"#,
+            &secrets
+        ));
+        assert!(!verify_github_signature(
+            &signature,
+            body,
+            &["other".to_string()]
+        ));
+        assert!(!verify_github_signature(
+            &sign("current", body),
+            body,
+            &secrets
+        ));
+    }
+
+    #[test]
+    fn stripe_signature() {
+        let body = br#"{"id":"evt_1","type":"invoice.paid"}"#;
+        let secrets = vec!["whsec_test".to_string()];
+        let payload = [b"1700000000.".as_slice(), body].concat();
+        let signature = format!(
+            "t=1700000000,v1={},v1={}",
+            sign("whsec_other", &payload),
+            sign("whsec_test", &payload)
+        );
+
+        assert!(verify_stripe_signature(
+            &signature, body, &secrets, 1700000100, 300
+        ));
+        // Too old
+        assert!(!verify_stripe_signature(
+            &signature, body, &secrets, 1700000400, 300
+        ));
+        // Timestamp not covered by the signature
+        assert!(!verify_stripe_signature(
+            &signature.replace("t=1700000000", "t=1700000001"),
+            body,
+            &secrets,
+            1700000100,
+            300
+        ));
+    }
+
+    #[test]
+    fn empty_secrets() {
+        let body = br#"{"action":"opened"}"#;
+        let signature = format!("sha256={}", sign("", body));
+
+        // A signature computed with an empty key must not verify, even if an empty secret slips
+        // through
+        assert!(!verify_github_signature(
+            &signature,
+            body,
+            &["".to_string()]
+        ));
+        assert!(!verify_github_signature(
+            &signature,
+            body,
+            &[" ".to_string()]
+        ));
+
+        let payload = [b"1700000000.".as_slice(), body].concat();
+        let signature = format!("t=1700000000,v1={}", sign("", &payload));
+        assert!(!verify_stripe_signature(
+            &signature,
+            body,
+            &["".to_string()],
+            1700000000,
+            300
+        ));
+    }
+
+    #[test]
+    fn secrets_from_env() {
+        let mut env = MapEnvironment::new();
+        assert!(
+            webhook_secrets(&env, EXO_WEBHOOK_GITHUB_SECRET)
+                .unwrap()
+                .is_empty()
+        );
+
+        // A trailing comma left while rotating secrets
+        env.set(EXO_WEBHOOK_GITHUB_SECRET, "new, ,");
+        assert_eq!(
+            webhook_secrets(&env, EXO_WEBHOOK_GITHUB_SECRET).unwrap(),
+            vec!["new".to_string()]
+        );
+
+        for value in ["", " ", ",", " , "] {
+            env.set(EXO_WEBHOOK_GITHUB_SECRET, value);
+            assert!(webhook_secrets(&env, EXO_WEBHOOK_GITHUB_SECRET).is_err());
+        }
+    }
+}
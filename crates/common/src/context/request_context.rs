@@ -13,12 +13,19 @@ use tokio::sync::Mutex;
 use async_recursion::async_recursion;
 use exo_env::Environment;
 
+use crate::access_coverage::AccessCoverage;
+use crate::clock::Clock;
+use crate::env_const::EXO_MAX_SQL_STATEMENTS;
 use crate::http::{RequestHead, RequestPayload, ResponsePayload};
 use crate::router::PlainRequestPayload;
 use crate::{router::Router, value::Val};
-use exo_sql::TransactionHolder;
+use exo_sql::{
+    PlanRecorder, READ_AFTER_LSN_HEADER, READ_CONSISTENCY_HEADER, ReadConsistency,
+    TransactionHolder,
+};
 
 use super::JwtAuthenticator;
+use super::RequestStats;
 use super::{
     context_extractor::BoxedContextExtractor, error::ContextExtractionError,
     overridden_context::OverriddenContext, user_request_context::UserRequestContext,
@@ -37,6 +44,37 @@ pub struct SystemRequestContext<'a> {
     pub jwt_authenticator: &'a Option<JwtAuthenticator>,
     pub system_router: &'a dyn for<'request> Router<PlainRequestPayload<'request>>,
     pub transaction_holder: Arc<Mutex<TransactionHolder>>,
+    pub stats: Arc<RequestStats>,
+    /// The plans of the database operations (recorded only once enabled)
+    pub plan_recorder: Arc<PlanRecorder>,
+    /// The source of the current time (for example, for `now()` defaults)
+    pub clock: Clock,
+    /// Where to record the evaluated access expressions (only when coverage is enabled)
+    pub access_coverage: Option<Arc<AccessCoverage>>,
+}
+
+/// The maximum number of SQL statements a request may execute (unlimited, if not set)
+fn statement_budget(env: &dyn Environment) -> Option<u64> {
+    let value = env.get(EXO_MAX_SQL_STATEMENTS)?;
+    let budget = value.parse::<u64>().ok().filter(|budget| *budget > 0);
+    if budget.is_none() {
+        tracing::warn!("Ignoring invalid {EXO_MAX_SQL_STATEMENTS} value '{value}'");
+    }
+    budget
+}
+
+/// The consistency of reads the request asks for (through the `Exo-Read-Consistency` and
+/// `Exo-Read-After-Lsn` headers). Invalid values fall back to reading from the primary, which is
+/// always consistent.
+fn read_consistency(request_head: &(dyn RequestHead + Send + Sync)) -> ReadConsistency {
+    ReadConsistency::from_headers(
+        request_head.get_header(READ_CONSISTENCY_HEADER).as_deref(),
+        request_head.get_header(READ_AFTER_LSN_HEADER).as_deref(),
+    )
+    .unwrap_or_else(|e| {
+        tracing::warn!("{e}; reading from the primary");
+        ReadConsistency::Primary
+    })
 }
 
 impl<'a> RequestContext<'a> {
@@ -47,17 +85,39 @@ impl<'a> RequestContext<'a> {
         jwt_authenticator: &'a Option<JwtAuthenticator>,
         env: &'a dyn Environment,
     ) -> RequestContext<'a> {
+        let stats = Arc::new(RequestStats::default());
+        let plan_recorder = Arc::new(PlanRecorder::default());
+
         Self {
             core: CoreRequestContext::new(request, parsed_contexts),
             system_context: SystemRequestContext {
                 env,
                 jwt_authenticator,
                 system_router,
-                transaction_holder: Arc::new(Mutex::new(TransactionHolder::new())),
+                transaction_holder: Arc::new(Mutex::new(
+                    TransactionHolder::with_stats(stats.sql.clone())
+                        .with_plan_recorder(plan_recorder.clone())
+                        .with_statement_budget(statement_budget(env))
+                        .with_read_consistency(read_consistency(request.get_head())),
+                )),
+                stats,
+                plan_recorder,
+                clock: Clock::system(),
+                access_coverage: None,
             },
         }
     }
 
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.system_context.clock = clock;
+        self
+    }
+
+    pub fn with_access_coverage(mut self, access_coverage: Option<Arc<AccessCoverage>>) -> Self {
+        self.system_context.access_coverage = access_coverage;
+        self
+    }
+
     pub fn is_internal(&self) -> bool {
         matches!(self.core, CoreRequestContext::InternalRequest(..))
     }
@@ -106,6 +166,17 @@ impl<'a> RequestContext<'a> {
             .await
     }
 
+    /// The LSN of the primary after committing the writes of the request (see
+    /// [`TransactionHolder::commit_lsn`])
+    pub async fn commit_lsn(&self) -> Option<String> {
+        self.system_context
+            .transaction_holder
+            .lock()
+            .await
+            .commit_lsn()
+            .map(str::to_string)
+    }
+
     pub fn with_override(&'a self, context_override: Value) -> RequestContext<'a> {
         Self {
             core: self.core.with_override(context_override),
@@ -157,6 +228,10 @@ impl<'a> RequestPayload for RequestContext<'a> {
     fn take_body(&self) -> Value {
         self.core.take_body()
     }
+
+    fn get_raw_body(&self) -> Option<&[u8]> {
+        self.core.get_raw_body()
+    }
 }
 
 pub(super) enum CoreRequestContext<'a> {
@@ -264,4 +339,11 @@ impl<'a> RequestPayload for CoreRequestContext<'a> {
             _ => self.get_base_context().get_request().take_body(),
         }
     }
+
+    fn get_raw_body(&self) -> Option<&[u8]> {
+        match self {
+            Self::InternalRequest(_, request) => request.get_raw_body(),
+            _ => self.get_base_context().get_request().get_raw_body(),
+        }
+    }
 }
@@ -0,0 +1,88 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use exo_sql::ExecutionStats;
+use serde_json::{Value, json};
+
+/// Execution statistics for a request (reported in the `extensions` of GraphQL responses when
+/// requested by an authorized client).
+///
+/// Shared by all the work done for a request, including internal requests (such as queries
+/// executed from Deno code).
+#[derive(Debug, Default)]
+pub struct RequestStats {
+    pub(super) sql: Arc<ExecutionStats>,
+    deno_calls: AtomicU64,
+    deno_duration_micros: AtomicU64,
+    cache_hits: AtomicU64,
+}
+
+impl RequestStats {
+    /// Record a call to a Deno function that took `elapsed` (including any queries it executed)
+    pub fn record_deno_call(&self, elapsed: Duration) {
+        self.deno_calls.fetch_add(1, Ordering::Relaxed);
+        self.deno_duration_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Record a context value served from the per-request cache
+    pub(super) fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "sqlStatements": self.sql.statements(),
+            "rowsReturned": self.sql.rows(),
+            "dbTimeMs": as_millis(self.sql.duration()),
+            "denoCalls": self.deno_calls.load(Ordering::Relaxed),
+            "denoTimeMs": as_millis(Duration::from_micros(
+                self.deno_duration_micros.load(Ordering::Relaxed)
+            )),
+            "cacheHits": self.cache_hits.load(Ordering::Relaxed),
+        })
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1000.0 * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_deno_calls_and_cache_hits() {
+        let stats = RequestStats::default();
+        stats.record_deno_call(Duration::from_micros(1500));
+        stats.record_deno_call(Duration::from_micros(2250));
+        stats.record_cache_hit();
+
+        assert_eq!(
+            stats.to_json(),
+            json!({
+                "sqlStatements": 0,
+                "rowsReturned": 0,
+                "dbTimeMs": 0.0,
+                "denoCalls": 2,
+                "denoTimeMs": 3.75,
+                "cacheHits": 1,
+            })
+        );
+    }
+}
@@ -18,8 +18,9 @@ use crate::value::Val;
 
 use super::provider::jwt::JwtExtractor;
 use super::provider::{
-    cookie::CookieExtractor, environment::EnvironmentContextExtractor, header::HeaderExtractor,
-    ip::IpExtractor, query::QueryExtractor,
+    client_cert::ClientCertExtractor, cookie::CookieExtractor, deployment::DeploymentExtractor,
+    environment::EnvironmentContextExtractor, header::HeaderExtractor, ip::IpExtractor,
+    query::QueryExtractor, webhook::WebhookExtractor,
 };
 use super::{
     RequestContext, context_extractor::BoxedContextExtractor, error::ContextExtractionError,
@@ -50,6 +51,9 @@ impl<'a> UserRequestContext<'a> {
             Box::new(QueryExtractor {}),
             Box::new(HeaderExtractor),
             Box::new(IpExtractor),
+            Box::new(ClientCertExtractor),
+            Box::new(DeploymentExtractor),
+            Box::new(WebhookExtractor),
             Box::new(CookieExtractor::new()),
             Box::new(JwtExtractor::new()),
         ];
@@ -83,7 +87,10 @@ impl<'a> UserRequestContext<'a> {
         let cached_value: Option<&Option<Val>> = self.context_cache.get(&cache_key);
 
         let value: &'a Option<Val> = match cached_value {
-            Some(value) => value,
+            Some(value) => {
+                request_context.system_context.stats.record_cache_hit();
+                value
+            }
             None => {
                 debug!(
                     "[ContextDebug] extracting field '{}' via annotation '{}'",
@@ -131,3 +138,46 @@ impl<'a> UserRequestContext<'a> {
         self.request.get_head()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        context::BUILT_IN_CONTEXT_ANNOTATIONS,
+        http::{MemoryRequestHead, MemoryRequestPayload},
+    };
+
+    #[test]
+    fn built_in_annotations() {
+        let request = MemoryRequestPayload::new(
+            Value::Null,
+            MemoryRequestHead::new(
+                HashMap::new(),
+                HashMap::new(),
+                http::Method::POST,
+                "/graphql".to_string(),
+                Value::Null,
+                None,
+            ),
+        );
+        let context = UserRequestContext::new(&request, vec![]);
+
+        // The flag extractor is passed in by the system router (since it needs the loaded flags)
+        let mut annotations: Vec<_> = context
+            .parsed_context_map
+            .keys()
+            .map(String::as_str)
+            .chain(["flag"])
+            .collect();
+        annotations.sort();
+
+        let mut expected = BUILT_IN_CONTEXT_ANNOTATIONS.to_vec();
+        expected.sort();
+
+        assert_eq!(annotations, expected);
+    }
+}
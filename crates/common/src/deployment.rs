@@ -0,0 +1,105 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Metadata about the deployment serving a request (region, instance id, and version).
+//!
+//! Exposed through the `@deployment` context annotation (so that access rules and resolvers can
+//! use it) and, if `EXO_DEPLOYMENT_EXTENSIONS` is enabled, in the `extensions` of GraphQL
+//! responses (so that clients and dashboards can attribute latency by region).
+//!
+//! The region and instance id fall back to the variables set by common hosting providers if
+//! `EXO_DEPLOYMENT_REGION` and `EXO_DEPLOYMENT_INSTANCE_ID` aren't set.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use exo_env::Environment;
+
+use crate::env_const::{EXO_DEPLOYMENT_INSTANCE_ID, EXO_DEPLOYMENT_REGION, EXO_DEPLOYMENT_VERSION};
+
+const REGION_FALLBACKS: &[&str] = &["FLY_REGION", "RAILWAY_REPLICA_REGION", "AWS_REGION"];
+const INSTANCE_ID_FALLBACKS: &[&str] = &["FLY_MACHINE_ID", "RAILWAY_REPLICA_ID", "HOSTNAME"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentInfo {
+    pub region: Option<String>,
+    pub instance_id: Option<String>,
+    /// The version of the deployed application (not to be confused with the server version)
+    pub version: Option<String>,
+    pub server_version: &'static str,
+}
+
+impl DeploymentInfo {
+    pub fn from_env(env: &dyn Environment) -> Self {
+        let first_of = |primary: &str, fallbacks: &[&str]| {
+            std::iter::once(primary)
+                .chain(fallbacks.iter().copied())
+                .find_map(|key| env.get(key))
+        };
+
+        Self {
+            region: first_of(EXO_DEPLOYMENT_REGION, REGION_FALLBACKS),
+            instance_id: first_of(EXO_DEPLOYMENT_INSTANCE_ID, INSTANCE_ID_FALLBACKS),
+            version: env.get(EXO_DEPLOYMENT_VERSION),
+            server_version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+
+    /// The value for a `@deployment` context field (absent for unknown keys and unset values)
+    pub fn get(&self, key: &str) -> Option<Value> {
+        match key {
+            "region" => self.region.clone().map(Value::String),
+            "instanceId" => self.instance_id.clone().map(Value::String),
+            "version" => self.version.clone().map(Value::String),
+            "serverVersion" => Some(Value::String(self.server_version.to_string())),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    fn env(values: &[(&str, &str)]) -> MapEnvironment {
+        MapEnvironment::from(
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn explicit_values_take_precedence() {
+        let info = DeploymentInfo::from_env(&env(&[
+            (EXO_DEPLOYMENT_REGION, "eu-west"),
+            ("FLY_REGION", "ams"),
+            (EXO_DEPLOYMENT_VERSION, "2024.06.1"),
+        ]));
+
+        assert_eq!(info.get("region"), Some(Value::from("eu-west")));
+        assert_eq!(info.get("version"), Some(Value::from("2024.06.1")));
+    }
+
+    #[test]
+    fn falls_back_to_provider_values() {
+        let info = DeploymentInfo::from_env(&env(&[("FLY_REGION", "ams"), ("HOSTNAME", "web-1")]));
+
+        assert_eq!(info.get("region"), Some(Value::from("ams")));
+        assert_eq!(info.get("instanceId"), Some(Value::from("web-1")));
+        assert_eq!(info.get("version"), None);
+        assert_eq!(info.get("unknown"), None);
+    }
+}
@@ -2,42 +2,123 @@ use exo_env::{EnvError, Environment};
 
 pub const EXO_INTROSPECTION: &str = "EXO_INTROSPECTION";
 pub const EXO_INTROSPECTION_LIVE_UPDATE: &str = "EXO_INTROSPECTION_LIVE_UPDATE";
+pub const EXO_INTROSPECTION_TOKEN: &str = "EXO_INTROSPECTION_TOKEN"; // Token required (in the `_exo_introspection_token` header) for introspection queries
+pub const EXO_MAX_SELECTION_DEPTH: &str = "EXO_MAX_SELECTION_DEPTH";
+pub const EXO_MAX_INTROSPECTION_SELECTION_DEPTH: &str = "EXO_MAX_INTROSPECTION_SELECTION_DEPTH";
 
 pub const EXO_CORS_DOMAINS: &str = "EXO_CORS_DOMAINS";
 
+// Comma-separated lists of IP addresses or CIDR ranges
+pub const EXO_GRAPHQL_IP_ALLOW: &str = "EXO_GRAPHQL_IP_ALLOW";
+pub const EXO_GRAPHQL_IP_DENY: &str = "EXO_GRAPHQL_IP_DENY";
+pub const EXO_PLAYGROUND_IP_ALLOW: &str = "EXO_PLAYGROUND_IP_ALLOW";
+pub const EXO_PLAYGROUND_IP_DENY: &str = "EXO_PLAYGROUND_IP_DENY";
+pub const EXO_HEALTHZ_IP_ALLOW: &str = "EXO_HEALTHZ_IP_ALLOW";
+pub const EXO_HEALTHZ_IP_DENY: &str = "EXO_HEALTHZ_IP_DENY";
+pub const EXO_INTERNAL_IP_RANGES: &str = "EXO_INTERNAL_IP_RANGES";
+pub const EXO_IP_FILTER_TRUST_FORWARDED: &str = "EXO_IP_FILTER_TRUST_FORWARDED"; // Use the IP from forwarding headers (only behind a trusted proxy)
+pub const EXO_GRAPHQL_RATE_LIMIT: &str = "EXO_GRAPHQL_RATE_LIMIT"; // Maximum GraphQL requests from each IP within a window (default: unlimited)
+pub const EXO_GRAPHQL_RATE_LIMIT_WINDOW: &str = "EXO_GRAPHQL_RATE_LIMIT_WINDOW"; // Seconds of the rate limiting window (default: 60)
+pub const EXO_GRAPHQL_RATE_LIMIT_STORE: &str = "EXO_GRAPHQL_RATE_LIMIT_STORE"; // Where to keep the counts: "memory" (default) or "redis" (shared by all servers, through EXO_REDIS_URL)
+
 pub const EXO_JWT_SECRET: &str = "EXO_JWT_SECRET";
 pub const EXO_OIDC_URL: &str = "EXO_OIDC_URL";
 pub const EXO_OIDC_URLS: &str = "EXO_OIDC_URLS"; // Comma-separated list of OIDC URLs
 pub const EXO_JWKS_URLS: &str = "EXO_JWKS_URLS"; // Comma-separated list of direct JWKS URLs (for providers without OIDC discovery)
 pub const EXO_JWT_AUDIENCES: &str = "EXO_JWT_AUDIENCES"; // Comma-separated list of allowed JWT audiences
 pub const EXO_JWT_PROVIDER_CONFIG: &str = "EXO_JWT_PROVIDER_CONFIG"; // JSON descriptor for multiple providers
+pub const EXO_JWT_CLAIM_MAPPINGS: &str = "EXO_JWT_CLAIM_MAPPINGS"; // JSON list of rules to rename and convert claims (applied to all tokens)
 pub const EXO_JWT_PUBLIC_KEY_PEM: &str = "EXO_JWT_PUBLIC_KEY_PEM"; // Optional PEM-encoded RSA public key for JWT validation
 pub const EXO_JWT_PUBLIC_KEY_PEM_ENVS: &str = "EXO_JWT_PUBLIC_KEY_PEM_ENVS"; // Comma-separated list of env vars containing PEM-encoded public keys
 pub const EXO_JWT_PUBLIC_KEY_KID: &str = "EXO_JWT_PUBLIC_KEY_KID"; // Optional kid for the direct PEM key
 pub const EXO_JWT_SOURCE_HEADER: &str = "EXO_JWT_SOURCE_HEADER";
 pub const EXO_JWT_SOURCE_COOKIE: &str = "EXO_JWT_SOURCE_COOKIE";
 
+pub const EXO_LOGIN_CLIENT_ID: &str = "EXO_LOGIN_CLIENT_ID"; // Enables the login endpoints (the client registered with the OIDC provider)
+pub const EXO_LOGIN_CLIENT_SECRET: &str = "EXO_LOGIN_CLIENT_SECRET"; // Optional (public clients rely on PKCE alone)
+pub const EXO_LOGIN_REDIRECT_URL: &str = "EXO_LOGIN_REDIRECT_URL"; // The full URL of the callback endpoint (as registered with the provider)
+pub const EXO_LOGIN_SCOPES: &str = "EXO_LOGIN_SCOPES"; // Space-separated scopes to request
+pub const EXO_LOGIN_SESSION_TOKEN: &str = "EXO_LOGIN_SESSION_TOKEN"; // The token to keep in the session cookie ("id_token" or "access_token")
+pub const EXO_LOGIN_POST_LOGOUT_REDIRECT_URL: &str = "EXO_LOGIN_POST_LOGOUT_REDIRECT_URL"; // Where the provider sends the user after logging out
+
 pub const EXO_POSTGRES_URL: &str = "EXO_POSTGRES_URL";
 pub const EXO_POSTGRES_READ_WRITE: &str = "EXO_POSTGRES_READ_WRITE";
+pub const EXO_POSTGRES_READ_REPLICA_URL: &str = "EXO_POSTGRES_READ_REPLICA_URL"; // URL of a read replica to send reads to (reads that follow a write in the same request still go to the primary)
+pub const EXO_POSTGRES_SESSION_SETTINGS: &str = "EXO_POSTGRES_SESSION_SETTINGS"; // Comma-separated "name=Context.field" pairs to set (with SET LOCAL) at the start of each transaction
+pub const EXO_OPAQUE_ID_KEY: &str = "EXO_OPAQUE_ID_KEY"; // Secret to derive the encoding of opaque ids (for types with @opaqueId) from
 pub const DATABASE_URL: &str = "DATABASE_URL";
 pub const EXO_CONNECTION_POOL_SIZE: &str = "EXO_CONNECTION_POOL_SIZE";
 pub const EXO_CHECK_CONNECTION_ON_STARTUP: &str = "EXO_CHECK_CONNECTION_ON_STARTUP";
+pub const EXO_NEQ_INCLUDES_NULL: &str = "EXO_NEQ_INCLUDES_NULL"; // Make `neq` filters also match null values (default: false, following SQL's three-valued logic)
 
 // Connection pool timeout settings (in seconds)
 pub const EXO_POOL_WAIT_TIMEOUT: &str = "EXO_POOL_WAIT_TIMEOUT"; // Timeout waiting for a connection from pool (default: 30s)
 pub const EXO_POOL_CREATE_TIMEOUT: &str = "EXO_POOL_CREATE_TIMEOUT"; // Timeout creating a new connection (default: 10s)
 pub const EXO_POOL_RECYCLE_TIMEOUT: &str = "EXO_POOL_RECYCLE_TIMEOUT"; // Timeout recycling/validating a connection (default: 5s)
 pub const EXO_POOL_MAX_LIFETIME: &str = "EXO_POOL_MAX_LIFETIME"; // Max lifetime of a connection in seconds (default: 1800 = 30min)
+pub const EXO_POOL_MIN_IDLE: &str = "EXO_POOL_MIN_IDLE"; // Connections to create at startup (and keep around when probing)
+pub const EXO_POOL_PROBE_INTERVAL: &str = "EXO_POOL_PROBE_INTERVAL"; // Seconds between validating idle connections (default: disabled)
+pub const EXO_POOL_DNS_REFRESH_INTERVAL: &str = "EXO_POOL_DNS_REFRESH_INTERVAL"; // Seconds between re-resolving the database host to detect failovers (default: 30; 0 disables)
+pub const EXO_POOL_ADAPTIVE_MIN_SIZE: &str = "EXO_POOL_ADAPTIVE_MIN_SIZE"; // Smallest size when resizing the pool based on load (default: 1)
+pub const EXO_POOL_ADAPTIVE_MAX_SIZE: &str = "EXO_POOL_ADAPTIVE_MAX_SIZE"; // Largest size when resizing the pool based on load (enables resizing)
+pub const EXO_POOL_ADAPTIVE_INTERVAL: &str = "EXO_POOL_ADAPTIVE_INTERVAL"; // Seconds between resizing decisions (default: 10)
+pub const EXO_POOL_KEEPALIVE_IDLE: &str = "EXO_POOL_KEEPALIVE_IDLE"; // Idle seconds before sending TCP keepalive probes on database connections (default: 7200)
+pub const EXO_POOL_KEEPALIVE_INTERVAL: &str = "EXO_POOL_KEEPALIVE_INTERVAL"; // Seconds between unanswered TCP keepalive probes (default: the OS setting)
+pub const EXO_POOL_KEEPALIVE_RETRIES: &str = "EXO_POOL_KEEPALIVE_RETRIES"; // Unanswered TCP keepalive probes before dropping a connection (default: the OS setting)
+pub const EXO_POSTGRES_APPLICATION_NAME: &str = "EXO_POSTGRES_APPLICATION_NAME"; // application_name reported to Postgres (overrides the one in the URL)
+pub const EXO_POSTGRES_OPTIONS: &str = "EXO_POSTGRES_OPTIONS"; // Command-line options sent to Postgres at connection startup (overrides the ones in the URL)
+pub const EXO_CIRCUIT_BREAKER_THRESHOLD: &str = "EXO_CIRCUIT_BREAKER_THRESHOLD"; // Consecutive failures (of the database or upstream endpoint) before failing fast (default: disabled)
+pub const EXO_CIRCUIT_BREAKER_COOLDOWN: &str = "EXO_CIRCUIT_BREAKER_COOLDOWN"; // Seconds to fail fast before trying again (default: 30)
 
 // Database retry settings (applies to SELECTs only)
 pub const EXO_DB_RETRY_MAX: &str = "EXO_DB_RETRY_MAX"; // Max retries for transient DB errors (default: 2)
 pub const EXO_DB_RETRY_BASE_MS: &str = "EXO_DB_RETRY_BASE_MS"; // Base backoff in ms (default: 50)
 pub const EXO_DB_RETRY_MAX_MS: &str = "EXO_DB_RETRY_MAX_MS"; // Max backoff in ms (default: 500)
+pub const EXO_DB_IN_LIST_CHUNK_SIZE: &str = "EXO_DB_IN_LIST_CHUNK_SIZE"; // Max keys per statement when filtering by keys from an earlier step (default: 10000)
+
+// Startup comparison of the model with the database schema
+pub const EXO_SCHEMA_DRIFT_CHECK: &str = "EXO_SCHEMA_DRIFT_CHECK"; // "off" (default), "warn" (log differences), or "strict" (refuse to start)
+pub const EXO_SCHEMA_DRIFT_REPORT: &str = "EXO_SCHEMA_DRIFT_REPORT"; // Path to write the JSON drift report to
+
+// Scheduled purging of expired rows (for types annotated with `@retention`)
+pub const EXO_RETENTION_INTERVAL: &str = "EXO_RETENTION_INTERVAL"; // Seconds between purges (default: 3600, 0 disables purging)
+pub const EXO_RETENTION_BATCH_SIZE: &str = "EXO_RETENTION_BATCH_SIZE"; // Max rows purged per statement (default: 1000)
+pub const EXO_RETENTION_BATCH_DELAY_MS: &str = "EXO_RETENTION_BATCH_DELAY_MS"; // Pause between batches in ms (default: 100)
+
+// Freezes the time used for `now()` defaults, JWT expiry, and retention (an RFC3339 time; for tests)
+pub const EXO_TEST_CLOCK: &str = "EXO_TEST_CLOCK";
+
+// Records which access rules are evaluated (for coverage reports; set by `exo test --access-coverage`)
+pub const EXO_ACCESS_COVERAGE: &str = "EXO_ACCESS_COVERAGE";
+
+// Creation of upcoming partitions (for types annotated with `@partitionBy(range: ...)`)
+pub const EXO_PARTITION_MAINTENANCE_INTERVAL: &str = "EXO_PARTITION_MAINTENANCE_INTERVAL"; // Seconds between runs (default: 21600, 0 disables)
+
+pub const EXO_REDIS_URL: &str = "EXO_REDIS_URL";
+
+pub const EXO_SEARCH_URL: &str = "EXO_SEARCH_URL";
+pub const EXO_SEARCH_API_KEY: &str = "EXO_SEARCH_API_KEY";
+
+pub const EXO_MESSAGING_URL: &str = "EXO_MESSAGING_URL";
+
+pub const EXO_DENO_EXECUTION_TIMEOUT: &str = "EXO_DENO_EXECUTION_TIMEOUT"; // Timeout in ms for calls into Deno modules without `@executionTimeout` (default: none)
+pub const EXO_SAGA_DIR: &str = "EXO_SAGA_DIR"; // Directory to persist running sagas in, to compensate them after a crash (must not be shared between servers; default: not persisted)
+
+// LLM provider settings (API keys are read from the variable named in `@llm`, e.g. OPENAI_API_KEY)
+pub const EXO_LLM_MAX_RETRIES: &str = "EXO_LLM_MAX_RETRIES"; // Max retries for rate-limited or failed requests (default: 3)
+pub const EXO_LLM_TIMEOUT_SECS: &str = "EXO_LLM_TIMEOUT_SECS"; // Timeout for each request in seconds (default: 120)
+pub const EXO_LLM_EMBEDDING_BATCH_SIZE: &str = "EXO_LLM_EMBEDDING_BATCH_SIZE"; // Max embeddings computed concurrently for `@embedding` fields (default: 16)
 
 pub const EXO_SERVER_PORT: &str = "EXO_SERVER_PORT";
 
 pub const EXO_ENABLE_OTEL: &str = "EXO_ENABLE_OTEL";
 pub const EXO_LOG: &str = "EXO_LOG";
+pub const EXO_LOG_FORMAT: &str = "EXO_LOG_FORMAT"; // "compact" (default) or "json"
+pub const EXO_LOG_SAMPLING: &str = "EXO_LOG_SAMPLING"; // Comma-separated "target=N" pairs to log one in N debug/trace events of the target
+pub const EXO_SQL_LOG: &str = "EXO_SQL_LOG"; // "on" (default in the dev mode), "pretty", or "off"
+pub const EXO_SQL_LOG_MAX_PARAM_LENGTH: &str = "EXO_SQL_LOG_MAX_PARAM_LENGTH"; // Truncate logged parameter values beyond this many characters (default: 100)
+pub const EXO_SQL_EXPLAIN_THRESHOLD: &str = "EXO_SQL_EXPLAIN_THRESHOLD"; // Log the plan of statements slower than this many milliseconds (requires statement logging)
+pub const EXO_MAX_SQL_STATEMENTS: &str = "EXO_MAX_SQL_STATEMENTS"; // Abort requests that would execute more SQL statements than this (default: unlimited)
 
 pub const EXO_ENV: &str = "EXO_ENV"; // "yolo", "dev", "test", "playground" or "production" for standard deployment modes or any other value for non-standard deployment modes
 pub const _EXO_ENFORCE_TRUSTED_DOCUMENTS: &str = "_EXO_ENFORCE_TRUSTED_DOCUMENTS";
@@ -49,12 +130,73 @@ pub const EXO_GRAPHQL_HTTP_PATH: &str = "EXO_GRAPHQL_HTTP_PATH";
 pub const EXO_REST_HTTP_PATH: &str = "EXO_REST_HTTP_PATH";
 pub const EXO_RPC_HTTP_PATH: &str = "EXO_RPC_HTTP_PATH";
 pub const EXO_MCP_HTTP_PATH: &str = "EXO_MCP_HTTP_PATH";
+pub const EXO_ADMIN_HTTP_PATH: &str = "EXO_ADMIN_HTTP_PATH";
+pub const EXO_LOGIN_HTTP_PATH: &str = "EXO_LOGIN_HTTP_PATH";
+pub const EXO_SCHEMA_VERSIONS: &str = "EXO_SCHEMA_VERSIONS"; // Comma-separated "<version>=<exo_ir file>" pairs of schema snapshots to serve under "/<version>"
+pub const HEALTHZ_HTTP_PATH: &str = "/healthz"; // Not configurable
 
 pub const EXO_GRAPHQL_ALLOW_MUTATIONS: &str = "EXO_GRAPHQL_ALLOW_MUTATIONS";
+pub const EXO_READ_ONLY: &str = "EXO_READ_ONLY"; // Reject all mutations (for example, when connected to a read replica; default: false)
+pub const EXO_PUBLIC_SANDBOX: &str = "EXO_PUBLIC_SANDBOX"; // Apply the settings for a public read-only endpoint (default: false)
 
 pub const EXO_UNSTABLE_ENABLE_REST_API: &str = "EXO_UNSTABLE_ENABLE_REST_API";
 pub const EXO_UNSTABLE_ENABLE_RPC_API: &str = "EXO_UNSTABLE_ENABLE_RPC_API";
 pub const EXO_ENABLE_MCP: &str = "EXO_ENABLE_MCP";
+pub const EXO_ENABLE_ADMIN_API: &str = "EXO_ENABLE_ADMIN_API";
+pub const EXO_ADMIN_ROLE: &str = "EXO_ADMIN_ROLE"; // The JWT `role` claim required to use the admin API (default: "admin")
+pub const EXO_ADMIN_TABLE_STATISTICS: &str = "EXO_ADMIN_TABLE_STATISTICS"; // Whether the admin API serves storage statistics of entity tables (default: false)
+
+pub const EXO_SUBSYSTEM_MANIFEST: &str = "EXO_SUBSYSTEM_MANIFEST"; // Path to a JSON manifest of subsystem libraries
+pub const EXO_SUBSYSTEM_LIBRARY_PATH: &str = "EXO_SUBSYSTEM_LIBRARY_PATH"; // Directories (separated like PATH) to search for subsystem libraries
+
+pub const EXO_MAINTENANCE_MODE: &str = "EXO_MAINTENANCE_MODE"; // Start in maintenance mode (default: false)
+pub const EXO_MAINTENANCE_MESSAGE: &str = "EXO_MAINTENANCE_MESSAGE";
+pub const EXO_MAINTENANCE_RETRY_AFTER: &str = "EXO_MAINTENANCE_RETRY_AFTER"; // Seconds for the `Retry-After` header (default: 300)
+pub const EXO_MAINTENANCE_ALLOWED_QUERIES: &str = "EXO_MAINTENANCE_ALLOWED_QUERIES"; // Comma-separated list of queries allowed in maintenance mode
+
+pub const EXO_OPERATION_CONCURRENCY: &str = "EXO_OPERATION_CONCURRENCY"; // Comma-separated concurrency limits such as "salesReport=2:10,orders=8" (limit and optional queue size)
+pub const EXO_OPERATION_QUEUE_TIMEOUT: &str = "EXO_OPERATION_QUEUE_TIMEOUT"; // Seconds a queued operation waits for a free slot (default: 30)
+pub const EXO_MAX_ALIASES: &str = "EXO_MAX_ALIASES"; // Maximum number of aliased fields in an operation (default: 200)
+pub const EXO_MAX_ROOT_FIELDS: &str = "EXO_MAX_ROOT_FIELDS"; // Maximum number of top-level fields in an operation (default: 100)
+
+pub const EXO_STRICT_TRUSTED_DOCUMENTS: &str = "EXO_STRICT_TRUSTED_DOCUMENTS"; // Fail startup if a trusted document doesn't validate against the schema (default: false)
+pub const EXO_TRUSTED_SERVICE_CERTS: &str = "EXO_TRUSTED_SERVICE_CERTS"; // Comma-separated client certificate common names of services exempt from trusted documents and operation limits
+pub const EXO_TRUSTED_SERVICE_KEYS: &str = "EXO_TRUSTED_SERVICE_KEYS"; // Comma-separated "<service>:<key>" pairs of services exempt from trusted documents and operation limits
+
+pub const EXO_REPLAY_WINDOW: &str = "EXO_REPLAY_WINDOW"; // Seconds within which a mutation's nonce may not be reused (enables replay protection)
+pub const EXO_REPLAY_NONCE_HEADER: &str = "EXO_REPLAY_NONCE_HEADER"; // Header with the nonce of a mutation request (default: "webhook-id")
+pub const EXO_REPLAY_TIMESTAMP_HEADER: &str = "EXO_REPLAY_TIMESTAMP_HEADER"; // Header with the Unix time a mutation request was sent (default: "webhook-timestamp")
+pub const EXO_REPLAY_CACHE_SIZE: &str = "EXO_REPLAY_CACHE_SIZE"; // Maximum number of nonces remembered (default: 100000)
+
+pub const EXO_RESPONSE_SIGNING_KEY: &str = "EXO_RESPONSE_SIGNING_KEY"; // PEM-encoded private key (or secret for HS* algorithms) to sign response bodies with
+pub const EXO_RESPONSE_SIGNING_ALGORITHM: &str = "EXO_RESPONSE_SIGNING_ALGORITHM"; // JWS algorithm to sign response bodies with (default: ES256)
+pub const EXO_RESPONSE_SIGNING_KEY_ID: &str = "EXO_RESPONSE_SIGNING_KEY_ID"; // The `kid` to include in response signatures
+pub const EXO_RESPONSE_SIGNING_PATHS: &str = "EXO_RESPONSE_SIGNING_PATHS"; // Comma-separated paths (or prefixes ending with "*") whose responses are signed
+
+pub const EXO_SERVICE_TOKEN_KEY: &str = "EXO_SERVICE_TOKEN_KEY"; // PEM-encoded private key (or secret for HS* algorithms) to sign tokens minted by Deno modules
+pub const EXO_SERVICE_TOKEN_ALGORITHM: &str = "EXO_SERVICE_TOKEN_ALGORITHM"; // JWS algorithm to sign minted tokens with (default: ES256)
+pub const EXO_SERVICE_TOKEN_KEY_ID: &str = "EXO_SERVICE_TOKEN_KEY_ID"; // The `kid` to include in minted tokens
+pub const EXO_SERVICE_TOKEN_ISSUER: &str = "EXO_SERVICE_TOKEN_ISSUER"; // The `iss` claim of minted tokens (default: "exograph")
+pub const EXO_SERVICE_TOKEN_MAX_TTL: &str = "EXO_SERVICE_TOKEN_MAX_TTL"; // Maximum lifetime (in seconds) of minted tokens (default: 900)
+
+pub const EXO_FLAGS: &str = "EXO_FLAGS"; // Comma-separated list of feature flags such as "newCheckout=true,maxItems=10"
+pub const EXO_FLAGS_FILE: &str = "EXO_FLAGS_FILE"; // Path to a JSON file with feature flags
+pub const EXO_FLAGS_TABLE: &str = "EXO_FLAGS_TABLE"; // Postgres table (with `name` and `value` columns) with feature flags
+pub const EXO_FLAGS_REFRESH_INTERVAL: &str = "EXO_FLAGS_REFRESH_INTERVAL"; // Seconds between refreshing feature flags (default: 30)
+
+pub const EXO_DEPLOYMENT_REGION: &str = "EXO_DEPLOYMENT_REGION"; // Region for `@deployment region` (default: the region set by Fly.io, Railway, or AWS)
+pub const EXO_DEPLOYMENT_INSTANCE_ID: &str = "EXO_DEPLOYMENT_INSTANCE_ID"; // Instance id for `@deployment instanceId` (default: the machine or replica id, or the host name)
+pub const EXO_DEPLOYMENT_VERSION: &str = "EXO_DEPLOYMENT_VERSION"; // Application version for `@deployment version`
+pub const EXO_DEPLOYMENT_EXTENSIONS: &str = "EXO_DEPLOYMENT_EXTENSIONS"; // Include deployment metadata in the `extensions` of GraphQL responses (default: false)
+
+pub const EXO_EXECUTION_STATS_ROLE: &str = "EXO_EXECUTION_STATS_ROLE"; // The JWT `role` claim required to receive execution stats (with the `x-exo-stats: true` header) in GraphQL responses
+
+pub const EXO_COMPACT_RESPONSES: &str = "EXO_COMPACT_RESPONSES"; // Omit null-valued fields and empty lists from GraphQL responses unless the client opts out with the `x-exo-compact: false` header (default: false)
+
+pub const EXO_ERROR_MESSAGES_DIR: &str = "EXO_ERROR_MESSAGES_DIR"; // Directory with a JSON file of translated error messages per locale (such as `fr.json`), selected through the `Accept-Language` header
+
+pub const EXO_RESPONSE_ANOMALY_FACTOR: &str = "EXO_RESPONSE_ANOMALY_FACTOR"; // Warn when a GraphQL response's field size or latency exceeds its baseline by this factor (enables anomaly detection)
+pub const EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL: &str = "EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL"; // Sample one in this many GraphQL responses for anomaly detection (default: 1)
 
 pub const EXO_WWW_AUTHENTICATE_HEADER: &str = "EXO_WWW_AUTHENTICATE_HEADER";
 
@@ -121,6 +263,16 @@ pub fn get_enforce_trusted_documents(env: &dyn Environment) -> bool {
         .unwrap_or(true)
 }
 
+/// Is the server in read-only mode (where all mutations are rejected)?
+pub fn is_read_only(env: &dyn Environment) -> Result<bool, EnvError> {
+    env.enabled(EXO_READ_ONLY, false)
+}
+
+/// Should a `neq` filter also match the rows where the field is null?
+pub fn neq_includes_null(env: &dyn Environment) -> Result<bool, EnvError> {
+    env.enabled(EXO_NEQ_INCLUDES_NULL, false)
+}
+
 pub fn get_playground_http_path(env: &dyn Environment) -> String {
     env.get(EXO_PLAYGROUND_HTTP_PATH)
         .unwrap_or_else(|| "/playground".to_string())
@@ -145,3 +297,18 @@ pub fn get_mcp_http_path(env: &dyn Environment) -> String {
     env.get(EXO_MCP_HTTP_PATH)
         .unwrap_or_else(|| "/mcp".to_string())
 }
+
+pub fn get_login_http_path(env: &dyn Environment) -> String {
+    env.get(EXO_LOGIN_HTTP_PATH)
+        .unwrap_or_else(|| "/auth".to_string())
+}
+
+pub fn get_admin_http_path(env: &dyn Environment) -> String {
+    env.get(EXO_ADMIN_HTTP_PATH)
+        .unwrap_or_else(|| "/admin/api".to_string())
+}
+
+pub fn get_admin_role(env: &dyn Environment) -> String {
+    env.get(EXO_ADMIN_ROLE)
+        .unwrap_or_else(|| "admin".to_string())
+}
@@ -1,7 +1,7 @@
-use std::{path::PathBuf, sync::Arc};
+use std::sync::Arc;
 
 use colored::Colorize;
-use exo_env::{CompositeEnvironment, DotEnvironment, Environment, SystemEnvironment};
+use exo_env::{EnvSource, LayeredEnvironment, Profile, SystemEnvironment};
 
 /// Describe the env processing for a command.
 ///
@@ -14,38 +14,21 @@ pub enum EnvProcessing {
 }
 
 impl EnvProcessing {
-    pub fn load_env(&self) -> impl Environment + 'static {
-        // Files in order of precedence
-        let mut env_files: Vec<PathBuf> = vec![];
-
-        let mut push_env_file = |file: &str| {
-            let file_path = PathBuf::from(file);
-            if file_path.exists() {
-                println!("Loading env file: {}", file.blue());
-                env_files.push(file_path);
+    pub fn load_env(&self) -> LayeredEnvironment {
+        let env = match self {
+            EnvProcessing::Process(exo_env) => {
+                LayeredEnvironment::load(exo_env.clone().map(Profile::new))
             }
+            EnvProcessing::DoNotProcess => LayeredEnvironment::new(
+                None,
+                vec![(EnvSource::Process, Arc::new(SystemEnvironment))],
+            ),
         };
 
-        if let EnvProcessing::Process(exo_env) = self {
-            if let Some(exo_env) = &exo_env {
-                push_env_file(&format!(".env.{}.local", exo_env));
-            }
-
-            push_env_file(".env.local");
-
-            if let Some(exo_env) = &exo_env {
-                push_env_file(&format!(".env.{}", exo_env));
-            }
-
-            push_env_file(".env");
-        }
-
-        let mut envs: Vec<Arc<dyn Environment>> = vec![Arc::new(SystemEnvironment)];
-
-        for env_file in env_files.iter() {
-            envs.push(Arc::new(DotEnvironment::new(env_file)));
+        for file in env.files() {
+            println!("Loading env file: {}", file.display().to_string().blue());
         }
 
-        CompositeEnvironment::new(envs)
+        env
     }
 }
@@ -0,0 +1,280 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Runtime feature flags, which access rules and resolvers can read through `@flag` context
+//! fields (such as `@flag newCheckout: Boolean`).
+//!
+//! Flags come from a list of sources (by default, `EXO_FLAGS` and the JSON file at
+//! `EXO_FLAGS_FILE`). Later sources take precedence. The values are refreshed when read after
+//! `EXO_FLAGS_REFRESH_INTERVAL` seconds, so changes to a file or a database table take effect
+//! without a redeploy.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use exo_env::{EnvError, Environment};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::env_const::{EXO_FLAGS, EXO_FLAGS_FILE, EXO_FLAGS_REFRESH_INTERVAL};
+
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 30;
+
+#[derive(Error, Debug)]
+pub enum FlagSourceError {
+    #[error("Failed to read flags from {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+
+    #[error("Invalid flags in {0}: {1}")]
+    Invalid(String, String),
+
+    #[error("{0}")]
+    Generic(String),
+}
+
+/// A source of flag values
+#[async_trait]
+pub trait FlagSource: Send + Sync {
+    /// Name to use in logs
+    fn name(&self) -> String;
+
+    async fn load(&self) -> Result<HashMap<String, Value>, FlagSourceError>;
+}
+
+pub struct FeatureFlags {
+    sources: RwLock<Vec<Arc<dyn FlagSource>>>,
+    values: RwLock<HashMap<String, Value>>,
+    refresh_interval: Duration,
+    refresh_state: tokio::sync::Mutex<RefreshState>,
+    loaded: AtomicBool,
+}
+
+#[derive(Default)]
+struct RefreshState {
+    // The last values loaded from each source (so that a failing source keeps its values)
+    source_values: Vec<HashMap<String, Value>>,
+    refreshed: bool,
+    #[cfg(not(target_family = "wasm"))]
+    refreshed_at: Option<std::time::Instant>,
+}
+
+impl RefreshState {
+    fn is_stale(&self, refresh_interval: Duration) -> bool {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            self.refreshed_at
+                .is_none_or(|refreshed_at| refreshed_at.elapsed() >= refresh_interval)
+        }
+
+        // No clock on WASM (and the server is short-lived), so load only once
+        #[cfg(target_family = "wasm")]
+        {
+            let _ = refresh_interval;
+            !self.refreshed
+        }
+    }
+
+    fn mark_refreshed(&mut self) {
+        self.refreshed = true;
+        #[cfg(not(target_family = "wasm"))]
+        {
+            self.refreshed_at = Some(std::time::Instant::now());
+        }
+    }
+}
+
+impl FeatureFlags {
+    pub fn new(sources: Vec<Arc<dyn FlagSource>>, refresh_interval: Duration) -> Self {
+        Self {
+            sources: RwLock::new(sources),
+            values: RwLock::new(HashMap::new()),
+            refresh_interval,
+            refresh_state: tokio::sync::Mutex::new(RefreshState::default()),
+            loaded: AtomicBool::new(false),
+        }
+    }
+
+    /// Flags from `EXO_FLAGS` and `EXO_FLAGS_FILE` (if set)
+    pub fn from_env(env: Arc<dyn Environment>) -> Result<Self, EnvError> {
+        let refresh_interval_secs = match env.get(EXO_FLAGS_REFRESH_INTERVAL) {
+            Some(value) => value.parse::<u64>().map_err(|_| EnvError::InvalidEnum {
+                env_key: EXO_FLAGS_REFRESH_INTERVAL,
+                env_value: value.clone(),
+                message: "Must be a number of seconds".to_string(),
+            })?,
+            None => DEFAULT_REFRESH_INTERVAL_SECS,
+        };
+
+        let mut sources: Vec<Arc<dyn FlagSource>> =
+            vec![Arc::new(EnvFlagSource { env: env.clone() })];
+
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(path) = env.get(EXO_FLAGS_FILE) {
+            sources.push(Arc::new(FileFlagSource { path: path.into() }));
+        }
+
+        Ok(Self::new(
+            sources,
+            Duration::from_secs(refresh_interval_secs),
+        ))
+    }
+
+    /// Add a source (taking precedence over the existing ones)
+    pub fn add_source(&self, source: Arc<dyn FlagSource>) {
+        self.sources.write().unwrap().push(source);
+    }
+
+    /// The value of a flag (refreshing the values first if they are stale)
+    pub async fn get(&self, name: &str) -> Option<Value> {
+        self.refresh_if_stale().await;
+        self.values.read().unwrap().get(name).cloned()
+    }
+
+    async fn refresh_if_stale(&self) {
+        let mut refresh_state = match self.refresh_state.try_lock() {
+            Ok(refresh_state) => refresh_state,
+            Err(_) => {
+                // Another request is refreshing, so use the current values (unless there are none
+                // yet, in which case wait for them)
+                if self.loaded.load(Ordering::Acquire) {
+                    return;
+                }
+                self.refresh_state.lock().await
+            }
+        };
+
+        if !refresh_state.is_stale(self.refresh_interval) {
+            return;
+        }
+
+        let sources = self.sources.read().unwrap().clone();
+        refresh_state
+            .source_values
+            .resize_with(sources.len(), HashMap::new);
+
+        for (source, source_values) in sources.iter().zip(refresh_state.source_values.iter_mut()) {
+            match source.load().await {
+                Ok(values) => *source_values = values,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to refresh flags from {} (keeping the previous values): {}",
+                        source.name(),
+                        e
+                    );
+                }
+            }
+        }
+
+        let mut values = HashMap::new();
+        for source_values in refresh_state.source_values.iter() {
+            values.extend(source_values.clone());
+        }
+        *self.values.write().unwrap() = values;
+
+        refresh_state.mark_refreshed();
+        self.loaded.store(true, Ordering::Release);
+    }
+}
+
+/// Flags from `EXO_FLAGS` such as `newCheckout=true,maxItems=10,theme=dark`
+struct EnvFlagSource {
+    env: Arc<dyn Environment>,
+}
+
+#[async_trait]
+impl FlagSource for EnvFlagSource {
+    fn name(&self) -> String {
+        EXO_FLAGS.to_string()
+    }
+
+    async fn load(&self) -> Result<HashMap<String, Value>, FlagSourceError> {
+        self.env
+            .get_list(EXO_FLAGS, vec![])
+            .into_iter()
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((name, value)) => {
+                    Ok((name.trim().to_string(), parse_flag_value(value.trim())))
+                }
+                None => Err(FlagSourceError::Invalid(
+                    EXO_FLAGS.to_string(),
+                    format!("expected <name>=<value>, got '{entry}'"),
+                )),
+            })
+            .collect()
+    }
+}
+
+/// Flags from a JSON file with an object such as `{"newCheckout": true, "maxItems": 10}`
+#[cfg(not(target_family = "wasm"))]
+struct FileFlagSource {
+    path: std::path::PathBuf,
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[async_trait]
+impl FlagSource for FileFlagSource {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    async fn load(&self) -> Result<HashMap<String, Value>, FlagSourceError> {
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| FlagSourceError::Io(self.name(), e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| FlagSourceError::Invalid(self.name(), e.to_string()))
+    }
+}
+
+/// Interpret a textual value as JSON (`true`, `10`, etc.) or, failing that, as a string
+pub fn parse_flag_value(value: &str) -> Value {
+    serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    struct FailingSource;
+
+    #[async_trait]
+    impl FlagSource for FailingSource {
+        fn name(&self) -> String {
+            "failing".to_string()
+        }
+
+        async fn load(&self) -> Result<HashMap<String, Value>, FlagSourceError> {
+            Err(FlagSourceError::Generic("unavailable".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn env_flags() {
+        let env =
+            MapEnvironment::from([("EXO_FLAGS", "newCheckout=true, maxItems=10, theme=dark")]);
+        let flags = FeatureFlags::from_env(Arc::new(env)).unwrap();
+        flags.add_source(Arc::new(FailingSource));
+
+        assert_eq!(flags.get("newCheckout").await, Some(Value::Bool(true)));
+        assert_eq!(flags.get("maxItems").await, Some(Value::from(10)));
+        assert_eq!(flags.get("theme").await, Some(Value::from("dark")));
+        assert_eq!(flags.get("unknown").await, None);
+    }
+}
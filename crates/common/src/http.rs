@@ -23,6 +23,14 @@ pub trait RequestPayload {
     ///
     /// Except for the first call, the return value will be `Value::Null`
     fn take_body(&self) -> Value;
+
+    /// The body exactly as received (before parsing), if the server retains it
+    ///
+    /// Unlike `take_body`, this doesn't consume the body. Needed to verify signatures computed
+    /// over the raw bytes (such as for webhooks).
+    fn get_raw_body(&self) -> Option<&[u8]> {
+        None
+    }
 }
 
 type PinnedStream<E> = Pin<Box<dyn Stream<Item = Result<Bytes, E>> + Send>>;
@@ -102,10 +110,39 @@ pub trait RequestHead {
     // return the IP address used to make the request
     fn get_ip(&self) -> Option<std::net::IpAddr>;
 
+    // return the IP address of the immediate peer (ignoring forwarding headers such as
+    // `X-Forwarded-For`, which any client can set)
+    fn get_peer_ip(&self) -> Option<std::net::IpAddr> {
+        self.get_ip()
+    }
+
     fn get_path(&self) -> String;
     fn get_query(&self) -> serde_json::Value;
 
     fn get_method(&self) -> http::Method;
+
+    // return the certificate the client presented (only when the server terminates TLS and
+    // verified the certificate)
+    fn get_client_certificate(&self) -> Option<&ClientCertificate> {
+        None
+    }
+}
+
+/// Attributes of a verified client certificate (for mutual TLS)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// Common name (CN) of the subject
+    pub common_name: Option<String>,
+    /// Distinguished name of the subject such as "CN=billing,O=Acme"
+    pub subject: String,
+    /// Distinguished name of the issuer
+    pub issuer: String,
+    /// Subject alternative names (DNS names, URIs, emails, and IP addresses)
+    pub sans: Vec<String>,
+    /// Hex-encoded SHA-256 fingerprint of the DER-encoded certificate
+    pub fingerprint: String,
+    /// Hex-encoded serial number
+    pub serial_number: String,
 }
 
 pub fn strip_leading_slash(path: &str) -> String {
@@ -1,6 +1,9 @@
 use exo_env::{EnvError, Environment};
 
-use crate::env_const::EXO_INTROSPECTION;
+use crate::env_const::{EXO_INTROSPECTION, EXO_INTROSPECTION_TOKEN};
+
+/// The header carrying the introspection token (sent by the bundled playground)
+pub const INTROSPECTION_TOKEN_HEADER: &str = "_exo_introspection_token";
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum IntrospectionMode {
@@ -32,3 +35,38 @@ pub fn allow_introspection(env: &dyn Environment) -> bool {
     let effective_mode = introspection_mode(env).unwrap_or(IntrospectionMode::Disabled);
     effective_mode == IntrospectionMode::Enabled || effective_mode == IntrospectionMode::Only
 }
+
+/// The token required to run introspection queries (if not set, introspection queries don't need
+/// a token)
+pub fn introspection_token(env: &dyn Environment) -> Option<String> {
+    env.get(EXO_INTROSPECTION_TOKEN)
+        .filter(|token| !token.is_empty())
+}
+
+/// Does the provided token match the expected one? Compares in constant time (for tokens of the
+/// same length) to not reveal the expected token through timing.
+pub fn matches_introspection_token(expected: &str, provided: Option<&str>) -> bool {
+    match provided {
+        Some(provided) if provided.len() == expected.len() => {
+            expected
+                .bytes()
+                .zip(provided.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_matching() {
+        assert!(matches_introspection_token("secret", Some("secret")));
+        assert!(!matches_introspection_token("secret", Some("secreT")));
+        assert!(!matches_introspection_token("secret", Some("secret1")));
+        assert!(!matches_introspection_token("secret", None));
+    }
+}
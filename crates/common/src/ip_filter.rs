@@ -0,0 +1,263 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! CIDR-based allow/deny lists for the GraphQL, playground, and health endpoints.
+//!
+//! Each endpoint has its own pair of lists (such as `EXO_GRAPHQL_IP_ALLOW` and
+//! `EXO_GRAPHQL_IP_DENY`). A request is rejected if its IP matches the deny list or if the allow
+//! list is non-empty and the IP doesn't match it.
+//!
+//! Independently, `EXO_INTERNAL_IP_RANGES` declares the ranges of "internal" callers, which
+//! access rules can check through `@clientIp("internal")`.
+
+use std::{net::IpAddr, str::FromStr};
+
+use exo_env::{EnvError, Environment};
+use thiserror::Error;
+
+use crate::{
+    env_const::{
+        EXO_GRAPHQL_IP_ALLOW, EXO_GRAPHQL_IP_DENY, EXO_HEALTHZ_IP_ALLOW, EXO_HEALTHZ_IP_DENY,
+        EXO_INTERNAL_IP_RANGES, EXO_IP_FILTER_TRUST_FORWARDED, EXO_PLAYGROUND_IP_ALLOW,
+        EXO_PLAYGROUND_IP_DENY,
+    },
+    http::RequestHead,
+};
+
+#[derive(Error, Debug)]
+pub enum IpFilterError {
+    #[error(
+        "Invalid IP range '{value}' in {env_key} (expected an IP address or a CIDR such as 10.0.0.0/8)"
+    )]
+    InvalidRange {
+        env_key: &'static str,
+        value: String,
+    },
+
+    #[error("{0}")]
+    Env(#[from] EnvError),
+}
+
+/// An IP address range in the CIDR notation (a plain address is a range of one)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // Treat IPv4-mapped IPv6 addresses (such as "::ffff:10.0.0.1") as IPv4
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                prefix_matches(u32::from(network), u32::from(ip), self.prefix_len, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                prefix_matches(u128::from(network), u128::from(ip), self.prefix_len, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches<T>(network: T, ip: T, prefix_len: u8, bits: u8) -> bool
+where
+    T: std::ops::BitXor<Output = T> + std::ops::Shr<u32, Output = T> + PartialEq + From<u8>,
+{
+    if prefix_len == 0 {
+        return true;
+    }
+
+    // Compare only the leading `prefix_len` bits
+    ((network ^ ip) >> u32::from(bits - prefix_len)) == T::from(0)
+}
+
+impl FromStr for IpNetwork {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match value.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (value, None),
+        };
+
+        let addr = IpAddr::from_str(addr.trim()).map_err(|_| ())?;
+        let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.trim().parse::<u8>().map_err(|_| ())?,
+            None => max_prefix_len,
+        };
+
+        if prefix_len > max_prefix_len {
+            return Err(());
+        }
+
+        Ok(Self {
+            addr: addr.to_canonical(),
+            prefix_len,
+        })
+    }
+}
+
+/// An allow list and a deny list (deny takes precedence)
+#[derive(Debug, Clone, Default)]
+pub struct IpRules {
+    allow: Vec<IpNetwork>,
+    deny: Vec<IpNetwork>,
+}
+
+impl IpRules {
+    fn from_env(
+        env: &dyn Environment,
+        allow_key: &'static str,
+        deny_key: &'static str,
+    ) -> Result<Self, IpFilterError> {
+        Ok(Self {
+            allow: parse_ranges(env, allow_key)?,
+            deny: parse_ranges(env, deny_key)?,
+        })
+    }
+
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        !self.deny.iter().any(|network| network.contains(ip))
+            && (self.allow.is_empty() || self.allow.iter().any(|network| network.contains(ip)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFilterEndpoint {
+    GraphQL,
+    Playground,
+    Health,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IpFilter {
+    graphql: IpRules,
+    playground: IpRules,
+    health: IpRules,
+    trust_forwarded: bool,
+}
+
+impl IpFilter {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, IpFilterError> {
+        Ok(Self {
+            graphql: IpRules::from_env(env, EXO_GRAPHQL_IP_ALLOW, EXO_GRAPHQL_IP_DENY)?,
+            playground: IpRules::from_env(env, EXO_PLAYGROUND_IP_ALLOW, EXO_PLAYGROUND_IP_DENY)?,
+            health: IpRules::from_env(env, EXO_HEALTHZ_IP_ALLOW, EXO_HEALTHZ_IP_DENY)?,
+            trust_forwarded: env.enabled(EXO_IP_FILTER_TRUST_FORWARDED, false)?,
+        })
+    }
+
+    pub fn allows(
+        &self,
+        endpoint: IpFilterEndpoint,
+        head: &(dyn RequestHead + Send + Sync),
+    ) -> bool {
+        let rules = match endpoint {
+            IpFilterEndpoint::GraphQL => &self.graphql,
+            IpFilterEndpoint::Playground => &self.playground,
+            IpFilterEndpoint::Health => &self.health,
+        };
+
+        match client_ip(head, self.trust_forwarded) {
+            Some(ip) => rules.allows(ip),
+            // Requests made in-process (such as the health check's GraphQL query) have no IP
+            None => true,
+        }
+    }
+}
+
+/// Is the request from a caller in one of the `EXO_INTERNAL_IP_RANGES`?
+pub fn is_internal_caller(
+    env: &dyn Environment,
+    head: &(dyn RequestHead + Send + Sync),
+) -> Result<bool, IpFilterError> {
+    let internal_ranges = parse_ranges(env, EXO_INTERNAL_IP_RANGES)?;
+    let trust_forwarded = env.enabled(EXO_IP_FILTER_TRUST_FORWARDED, false)?;
+
+    Ok(client_ip(head, trust_forwarded)
+        .is_some_and(|ip| internal_ranges.iter().any(|network| network.contains(ip))))
+}
+
+/// The IP to check against the lists. Forwarding headers (`Forwarded`, `X-Forwarded-For`) can be
+/// set by any client, so we use them only if told that a trusted proxy sets them.
+pub(crate) fn client_ip(
+    head: &(dyn RequestHead + Send + Sync),
+    trust_forwarded: bool,
+) -> Option<IpAddr> {
+    if trust_forwarded {
+        head.get_ip()
+    } else {
+        head.get_peer_ip()
+    }
+}
+
+fn parse_ranges(
+    env: &dyn Environment,
+    env_key: &'static str,
+) -> Result<Vec<IpNetwork>, IpFilterError> {
+    env.get_list(env_key, vec![])
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            IpNetwork::from_str(&value).map_err(|_| IpFilterError::InvalidRange { env_key, value })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(value: &str) -> IpNetwork {
+        value.parse().unwrap()
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn network_contains() {
+        assert!(network("10.0.0.0/8").contains(ip("10.1.2.3")));
+        assert!(!network("10.0.0.0/8").contains(ip("11.0.0.1")));
+        assert!(network("10.0.0.1").contains(ip("10.0.0.1")));
+        assert!(!network("10.0.0.1").contains(ip("10.0.0.2")));
+        assert!(network("0.0.0.0/0").contains(ip("192.168.1.1")));
+        assert!(network("10.0.0.0/8").contains(ip("::ffff:10.0.0.1")));
+        assert!(network("fd00::/8").contains(ip("fd12::1")));
+        assert!(!network("fd00::/8").contains(ip("fe80::1")));
+        assert!(!network("fd00::/8").contains(ip("10.0.0.1")));
+
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("10.0.0/8".parse::<IpNetwork>().is_err());
+    }
+
+    #[test]
+    fn rules() {
+        let rules = IpRules {
+            allow: vec![network("10.0.0.0/8")],
+            deny: vec![network("10.0.0.13")],
+        };
+
+        assert!(rules.allows(ip("10.0.0.1")));
+        assert!(!rules.allows(ip("10.0.0.13")));
+        assert!(!rules.allows(ip("192.168.1.1")));
+
+        let deny_only = IpRules {
+            allow: vec![],
+            deny: vec![network("192.168.0.0/16")],
+        };
+
+        assert!(deny_only.allows(ip("10.0.0.1")));
+        assert!(!deny_only.allows(ip("192.168.1.1")));
+    }
+}
@@ -7,15 +7,27 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod access_coverage;
+#[cfg(not(target_family = "wasm"))]
+pub mod circuit_breaker;
+pub mod clock;
 pub mod context;
 pub mod cors;
+pub mod deployment;
 pub mod download;
 pub mod env_const;
 pub mod env_processing;
+pub mod feature_flags;
 pub mod http;
 pub mod introspection;
+pub mod ip_filter;
+pub mod log_filter;
+pub mod maintenance;
 pub mod operation_payload;
+pub mod rate_limit;
+pub mod response_signing;
 pub mod router;
+pub mod service_token;
 pub mod test_support;
 pub mod value;
 
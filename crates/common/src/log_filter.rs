@@ -0,0 +1,62 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Runtime control of the log filter.
+//!
+//! The server installs a reloader when it initializes logging. After that, the filter (using the
+//! same syntax as `EXO_LOG`) can be changed without a restart, for example, through the admin API.
+
+use std::sync::{Mutex, OnceLock};
+
+use thiserror::Error;
+
+pub type LogFilterReloader = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+struct LogFilterControl {
+    reloader: LogFilterReloader,
+    current: Mutex<String>,
+}
+
+static LOG_FILTER_CONTROL: OnceLock<LogFilterControl> = OnceLock::new();
+
+#[derive(Error, Debug, PartialEq)]
+pub enum LogFilterError {
+    #[error("Log filter can't be changed at runtime")]
+    NotInstalled,
+
+    #[error("Invalid log filter '{0}': {1}")]
+    Invalid(String, String),
+}
+
+/// Install the reloader for the filter currently in effect. Only the first installation takes
+/// effect.
+pub fn install(current: String, reloader: LogFilterReloader) {
+    let _ = LOG_FILTER_CONTROL.set(LogFilterControl {
+        reloader,
+        current: Mutex::new(current),
+    });
+}
+
+pub fn current() -> Option<String> {
+    LOG_FILTER_CONTROL
+        .get()
+        .map(|control| control.current.lock().unwrap().clone())
+}
+
+pub fn set(filter: &str) -> Result<(), LogFilterError> {
+    let control = LOG_FILTER_CONTROL
+        .get()
+        .ok_or(LogFilterError::NotInstalled)?;
+
+    (control.reloader)(filter)
+        .map_err(|message| LogFilterError::Invalid(filter.to_string(), message))?;
+
+    *control.current.lock().unwrap() = filter.to_string();
+    Ok(())
+}
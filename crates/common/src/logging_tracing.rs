@@ -44,30 +44,35 @@ use opentelemetry_otlp::{SpanExporter, WithTonicConfig};
 use opentelemetry_sdk::{Resource, runtime, trace::TracerProvider};
 use std::str::FromStr;
 use tonic::transport::{ClientTlsConfig, Endpoint};
-use tracing_subscriber::{EnvFilter, filter::LevelFilter, prelude::*};
+use tracing_subscriber::{EnvFilter, Layer, filter::LevelFilter, prelude::*, registry::LookupSpan};
 
-use crate::env_const::{EXO_ENABLE_OTEL, EXO_LOG};
+use crate::env_const::{EXO_ENABLE_OTEL, EXO_ENV, EXO_LOG};
+
+/// The target under which `exo-sql` logs the executed statements
+const STATEMENT_LOG_TARGET: &str = "exo_sql::statements";
 
 /// Initialize the tracing subscriber.
 ///
 /// Creates a `tracing_subscriber::fmt` layer by default and adds a OpenTelemetry layer
 /// if any OpenTelemetry environment variables are set, exporting traces with `opentelemetry_otlp`.
 pub async fn init(env: &dyn Environment) -> Result<(), OtelError> {
-    let telemetry_layer = {
-        let oltp_trace_provider = create_oltp_trace_provider(env).await?;
-
-        use opentelemetry::trace::TracerProvider as _;
-        let oltp_tracer = oltp_trace_provider.map(|provider| provider.tracer("Exograph"));
-
-        oltp_tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer))
-    };
+    let telemetry_layer = telemetry_layer(env).await?;
 
     let fmt_layer = tracing_subscriber::fmt::layer().compact();
-    let filter = EnvFilter::builder()
+    let mut filter = EnvFilter::builder()
         .with_default_directive(LevelFilter::WARN.into())
         .with_env_var(EXO_LOG)
         .from_env_lossy();
 
+    // In the dev mode, show the executed statements (unless the log filter is set explicitly)
+    if env.get(EXO_LOG).is_none() && env.get(EXO_ENV).as_deref() == Some("dev") {
+        filter = filter.add_directive(
+            format!("{STATEMENT_LOG_TARGET}=info")
+                .parse()
+                .expect("valid directive"),
+        );
+    }
+
     tracing_subscriber::registry()
         .with(filter)
         .with(fmt_layer)
@@ -77,6 +82,19 @@ pub async fn init(env: &dyn Environment) -> Result<(), OtelError> {
     Ok(())
 }
 
+/// Create the OpenTelemetry layer if enabled (see [`EXO_ENABLE_OTEL`]).
+pub async fn telemetry_layer<S>(env: &dyn Environment) -> Result<Option<impl Layer<S>>, OtelError>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let oltp_trace_provider = create_oltp_trace_provider(env).await?;
+
+    use opentelemetry::trace::TracerProvider as _;
+    let oltp_tracer = oltp_trace_provider.map(|provider| provider.tracer("Exograph"));
+
+    Ok(oltp_tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
 async fn create_oltp_trace_provider(
     env: &dyn Environment,
 ) -> Result<Option<TracerProvider>, OtelError> {
@@ -0,0 +1,151 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Maintenance mode, during which the server rejects operations other than a few allowed
+//! read-only queries (with a 503 response and a `Retry-After` header).
+//!
+//! Set `EXO_MAINTENANCE_MODE` to start the server in maintenance mode. The mode can then be
+//! toggled at runtime (through the admin API or, for `exo-server`, by sending `SIGUSR2`).
+
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use exo_env::{EnvError, Environment};
+use thiserror::Error;
+
+use crate::env_const::{
+    EXO_MAINTENANCE_ALLOWED_QUERIES, EXO_MAINTENANCE_MESSAGE, EXO_MAINTENANCE_MODE,
+    EXO_MAINTENANCE_RETRY_AFTER,
+};
+
+const DEFAULT_MESSAGE: &str = "The service is temporarily unavailable for maintenance";
+const DEFAULT_RETRY_AFTER_SECS: u64 = 300;
+
+#[derive(Debug)]
+pub struct MaintenanceMode {
+    enabled: AtomicBool,
+    message: String,
+    retry_after_secs: u64,
+    allowed_queries: HashSet<String>,
+}
+
+/// The error for an operation rejected due to maintenance mode
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{message}")]
+pub struct MaintenanceError {
+    pub message: String,
+    pub retry_after_secs: u64,
+}
+
+impl MaintenanceMode {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, EnvError> {
+        let retry_after_secs = match env.get(EXO_MAINTENANCE_RETRY_AFTER) {
+            Some(value) => value.parse::<u64>().map_err(|_| EnvError::InvalidEnum {
+                env_key: EXO_MAINTENANCE_RETRY_AFTER,
+                env_value: value.clone(),
+                message: "Must be a number of seconds".to_string(),
+            })?,
+            None => DEFAULT_RETRY_AFTER_SECS,
+        };
+
+        Ok(Self {
+            enabled: AtomicBool::new(env.enabled(EXO_MAINTENANCE_MODE, false)?),
+            message: env.get_or_else(EXO_MAINTENANCE_MESSAGE, DEFAULT_MESSAGE),
+            retry_after_secs,
+            allowed_queries: env
+                .get_list(EXO_MAINTENANCE_ALLOWED_QUERIES, vec![])
+                .into_iter()
+                .filter(|query| !query.is_empty())
+                .collect(),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Toggle the mode, returning the new state
+    pub fn toggle(&self) -> bool {
+        !self.enabled.fetch_xor(true, Ordering::Relaxed)
+    }
+
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after_secs
+    }
+
+    /// Check if an operation with the given top-level fields may run.
+    ///
+    /// In maintenance mode, only queries whose fields are all allowed (or are introspection
+    /// fields) may run.
+    pub fn check<'a>(
+        &self,
+        is_query: bool,
+        mut field_names: impl Iterator<Item = &'a str>,
+    ) -> Result<(), MaintenanceError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let allowed = is_query
+            && field_names
+                .all(|name| name.starts_with("__") || self.allowed_queries.contains(name));
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(MaintenanceError {
+                message: self.message.clone(),
+                retry_after_secs: self.retry_after_secs,
+            })
+        }
+    }
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            message: DEFAULT_MESSAGE.to_string(),
+            retry_after_secs: DEFAULT_RETRY_AFTER_SECS,
+            allowed_queries: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_operations() {
+        let mode = MaintenanceMode {
+            allowed_queries: HashSet::from(["status".to_string()]),
+            ..Default::default()
+        };
+
+        assert!(mode.check(false, ["createOrder"].into_iter()).is_ok());
+
+        assert!(mode.toggle());
+        assert!(
+            mode.check(true, ["status", "__typename"].into_iter())
+                .is_ok()
+        );
+        assert!(mode.check(true, ["status", "orders"].into_iter()).is_err());
+        assert!(mode.check(false, ["status"].into_iter()).is_err());
+
+        assert!(!mode.toggle());
+        assert!(mode.check(true, ["orders"].into_iter()).is_ok());
+    }
+}
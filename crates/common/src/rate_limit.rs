@@ -0,0 +1,302 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Per-IP rate limiting of the GraphQL endpoint through `EXO_GRAPHQL_RATE_LIMIT` (the number of
+//! requests each IP may make within a window of `EXO_GRAPHQL_RATE_LIMIT_WINDOW` seconds).
+//!
+//! Each IP gets a fixed window (aligned to multiples of the window length). IPv6 clients are
+//! counted by their /64 prefix, since a single client typically has the whole prefix to pick
+//! addresses from.
+//!
+//! The counts are kept in memory, unless a [`RateLimitStore`] shared by the servers of a
+//! deployment is set (see [`RateLimiter::with_store`]), in which case the limit applies to the
+//! requests a client makes to all servers together.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use exo_env::{EnvError, Environment};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::{
+    env_const::{
+        EXO_GRAPHQL_RATE_LIMIT, EXO_GRAPHQL_RATE_LIMIT_WINDOW, EXO_IP_FILTER_TRUST_FORWARDED,
+    },
+    http::RequestHead,
+    ip_filter::client_ip,
+};
+
+const DEFAULT_WINDOW_SECS: u64 = 60;
+
+/// A store of request counts shared by the servers of a deployment (such as Redis)
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Increment the counter of the key and return its new value (a missing key counts as 0).
+    /// The counter must expire `ttl_secs` after it is created.
+    async fn increment(&self, key: &str, ttl_secs: u64) -> Result<u64, RateLimitStoreError>;
+}
+
+#[derive(Error, Debug)]
+pub enum RateLimitStoreError {
+    #[error("{0}")]
+    Generic(String),
+}
+
+pub struct RateLimiter {
+    limit: u32,
+    window_secs: u64,
+    trust_forwarded: bool,
+    state: Mutex<RateLimiterState>,
+    store: Option<Arc<dyn RateLimitStore>>,
+}
+
+#[derive(Debug, Default)]
+struct RateLimiterState {
+    window: u64,
+    /// The number of requests of each client in the current window
+    counts: HashMap<IpAddr, u32>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window_secs: u64, trust_forwarded: bool) -> Self {
+        Self {
+            limit,
+            window_secs,
+            trust_forwarded,
+            state: Mutex::new(RateLimiterState::default()),
+            store: None,
+        }
+    }
+
+    /// Count the requests in the given store instead of in memory. If the store fails, the
+    /// requests are counted in memory until it works again.
+    pub fn with_store(self, store: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            store: Some(store),
+            ..self
+        }
+    }
+
+    /// The rate limiter (or `None` if rate limiting isn't enabled)
+    pub fn from_env(env: &dyn Environment) -> Result<Option<Self>, EnvError> {
+        let Some(limit) = env.get(EXO_GRAPHQL_RATE_LIMIT) else {
+            return Ok(None);
+        };
+        let limit = limit
+            .parse::<u32>()
+            .ok()
+            .filter(|limit| *limit > 0)
+            .ok_or_else(|| EnvError::InvalidEnum {
+                env_key: EXO_GRAPHQL_RATE_LIMIT,
+                env_value: limit.clone(),
+                message: "Must be a positive number".to_string(),
+            })?;
+
+        let window_secs = match env.get(EXO_GRAPHQL_RATE_LIMIT_WINDOW) {
+            Some(value) => value
+                .parse::<u64>()
+                .ok()
+                .filter(|window| *window > 0)
+                .ok_or_else(|| EnvError::InvalidEnum {
+                    env_key: EXO_GRAPHQL_RATE_LIMIT_WINDOW,
+                    env_value: value.clone(),
+                    message: "Must be a positive number of seconds".to_string(),
+                })?,
+            None => DEFAULT_WINDOW_SECS,
+        };
+
+        Ok(Some(Self::new(
+            limit,
+            window_secs,
+            env.enabled(EXO_IP_FILTER_TRUST_FORWARDED, false)?,
+        )))
+    }
+
+    /// Count the request against its client's limit. Returns the seconds until the client may
+    /// make requests again, if the client has exceeded the limit.
+    pub async fn check(
+        &self,
+        head: &(dyn RequestHead + Send + Sync),
+        now: DateTime<Utc>,
+    ) -> Option<u64> {
+        // Requests made in-process (such as the health check's GraphQL query) have no IP
+        let ip = client_ip(head, self.trust_forwarded)?;
+        self.check_ip(ip, now).await
+    }
+
+    async fn check_ip(&self, ip: IpAddr, now: DateTime<Utc>) -> Option<u64> {
+        if let Some(store) = &self.store {
+            match self.check_ip_in_store(store.as_ref(), ip, now).await {
+                Ok(retry_after) => return retry_after,
+                Err(e) => warn!("Failed to count the request in the rate limit store: {e}"),
+            }
+        }
+
+        self.check_ip_locally(ip, now)
+    }
+
+    async fn check_ip_in_store(
+        &self,
+        store: &dyn RateLimitStore,
+        ip: IpAddr,
+        now: DateTime<Utc>,
+    ) -> Result<Option<u64>, RateLimitStoreError> {
+        let now_secs = now.timestamp().max(0) as u64;
+        let window = now_secs / self.window_secs;
+        let window_end = (window + 1) * self.window_secs;
+
+        // Keyed by the window, so that the count of each window starts afresh (and the previous
+        // one expires when its window ends)
+        let key = format!("{window}:{}", client_key(ip));
+        let count = store.increment(&key, window_end - now_secs).await?;
+
+        Ok((count > u64::from(self.limit)).then_some(window_end - now_secs))
+    }
+
+    fn check_ip_locally(&self, ip: IpAddr, now: DateTime<Utc>) -> Option<u64> {
+        let now_secs = now.timestamp().max(0) as u64;
+        let window = now_secs / self.window_secs;
+
+        let mut state = self.state.lock().unwrap();
+        if state.window != window {
+            // Forget the previous window (so that the map doesn't keep every client ever seen)
+            state.window = window;
+            state.counts.clear();
+        }
+
+        let count = state.counts.entry(client_key(ip)).or_insert(0);
+        if *count >= self.limit {
+            Some((window + 1) * self.window_secs - now_secs)
+        } else {
+            *count += 1;
+            None
+        }
+    }
+}
+
+fn client_key(ip: IpAddr) -> IpAddr {
+    match ip.to_canonical() {
+        IpAddr::V6(ip) => {
+            let prefix = u128::from(ip) & !(u128::MAX >> 64);
+            IpAddr::V6(Ipv6Addr::from(prefix))
+        }
+        ip => ip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn limits_each_client_per_window() {
+        let limiter = RateLimiter::new(2, 60, false);
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let other_client: IpAddr = "203.0.113.8".parse().unwrap();
+
+        assert_eq!(limiter.check_ip(client, at(600)).await, None);
+        assert_eq!(limiter.check_ip(client, at(610)).await, None);
+        assert_eq!(limiter.check_ip(client, at(615)).await, Some(45));
+        assert_eq!(limiter.check_ip(other_client, at(615)).await, None);
+
+        // A new window
+        assert_eq!(limiter.check_ip(client, at(660)).await, None);
+    }
+
+    #[tokio::test]
+    async fn counts_ipv6_clients_by_prefix() {
+        let limiter = RateLimiter::new(1, 60, false);
+
+        assert_eq!(
+            limiter
+                .check_ip("2001:db8:1:2::1".parse().unwrap(), at(0))
+                .await,
+            None
+        );
+        assert_eq!(
+            limiter
+                .check_ip("2001:db8:1:2::ffff".parse().unwrap(), at(1))
+                .await,
+            Some(59)
+        );
+        assert_eq!(
+            limiter
+                .check_ip("2001:db8:1:3::1".parse().unwrap(), at(1))
+                .await,
+            None
+        );
+    }
+
+    /// A store that keeps the counts in memory (recording the requested expiry of each key)
+    #[derive(Default)]
+    struct TestStore {
+        counts: Mutex<HashMap<String, (u64, u64)>>,
+        failing: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl RateLimitStore for TestStore {
+        async fn increment(&self, key: &str, ttl_secs: u64) -> Result<u64, RateLimitStoreError> {
+            if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(RateLimitStoreError::Generic("unavailable".to_string()));
+            }
+
+            let mut counts = self.counts.lock().unwrap();
+            let (count, _) = counts.entry(key.to_string()).or_insert((0, ttl_secs));
+            *count += 1;
+            Ok(*count)
+        }
+    }
+
+    #[tokio::test]
+    async fn shares_counts_through_store() {
+        let store = Arc::new(TestStore::default());
+        // Two servers of the same deployment
+        let limiter = RateLimiter::new(2, 60, false).with_store(store.clone());
+        let other_limiter = RateLimiter::new(2, 60, false).with_store(store.clone());
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+
+        assert_eq!(limiter.check_ip(client, at(600)).await, None);
+        assert_eq!(other_limiter.check_ip(client, at(610)).await, None);
+        assert_eq!(limiter.check_ip(client, at(615)).await, Some(45));
+        assert_eq!(other_limiter.check_ip(client, at(620)).await, Some(40));
+
+        // A new window
+        assert_eq!(other_limiter.check_ip(client, at(660)).await, None);
+
+        // Each window's count expires when the window ends
+        let counts = store.counts.lock().unwrap();
+        assert_eq!(counts.get("10:203.0.113.7"), Some(&(4, 60)));
+        assert_eq!(counts.get("11:203.0.113.7"), Some(&(1, 60)));
+    }
+
+    #[tokio::test]
+    async fn counts_locally_when_store_fails() {
+        let store = Arc::new(TestStore::default());
+        let limiter = RateLimiter::new(1, 60, false).with_store(store.clone());
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+
+        store
+            .failing
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+
+        assert_eq!(limiter.check_ip(client, at(600)).await, None);
+        assert_eq!(limiter.check_ip(client, at(610)).await, Some(50));
+    }
+}
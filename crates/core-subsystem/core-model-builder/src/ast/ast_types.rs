@@ -112,6 +112,7 @@ impl<T: NodeTypedness> Display for AstFragmentReference<T> {
 pub struct AstEnum<T: NodeTypedness> {
     pub name: String,
     pub fields: Vec<AstEnumField<T>>,
+    pub annotations: T::Annotations,
     pub doc_comments: Option<String>,
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -68,6 +68,7 @@ fn expand(context: &ResolvedContext, building: &mut SystemContextBuilding) {
                     value,
                 }
             },
+            default_value: field.default_value.clone(),
         })
         .collect::<Vec<_>>();
 
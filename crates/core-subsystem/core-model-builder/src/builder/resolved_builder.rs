@@ -13,7 +13,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     ast::ast_types::{
-        AstAnnotation, AstAnnotationParams, AstExpr, AstField, AstModel, AstModelKind,
+        AstAnnotation, AstAnnotationParams, AstExpr, AstField, AstFieldDefaultKind, AstModel,
+        AstModelKind,
     },
     error::ModelBuildingError,
     typechecker::{AnnotationMap, Type, Typed, typ::TypecheckedSystem},
@@ -61,6 +62,7 @@ pub struct ResolvedContextField {
     pub name: String,
     pub typ: ResolvedContextFieldType,
     pub source: ResolvedContextSource,
+    pub default_value: Option<String>,
     pub doc_comments: Option<String>,
 }
 
@@ -130,10 +132,14 @@ fn resolve_contexts(
                     };
 
                     typ.and_then(|typ| {
-                        extract_context_source(field, errors).map(|source| ResolvedContextField {
+                        let source = extract_context_source(field, errors)?;
+                        let default_value = extract_context_default(field, &source, errors);
+
+                        Some(ResolvedContextField {
                             name: field.name.clone(),
                             typ,
                             source,
+                            default_value,
                             doc_comments: field.doc_comments.clone(),
                         })
                     })
@@ -240,6 +246,53 @@ fn extract_context_source(
     }
 }
 
+fn extract_context_default(
+    field: &AstField<Typed>,
+    source: &ResolvedContextSource,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let default_value = field.default_value.as_ref()?;
+
+    let mut report = |message: &str, label: &str| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message: message.to_string(),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: default_value.span,
+                style: SpanStyle::Primary,
+                label: Some(label.to_string()),
+            }],
+        });
+    };
+
+    // Defaults become the lowest precedence layer of the environment, so only `@env` fields
+    // may have them
+    if source.annotation != "env" {
+        report(
+            &format!(
+                "Only @env context fields may have a default value (field `{}`)",
+                field.name
+            ),
+            "default value",
+        );
+        return None;
+    }
+
+    match &default_value.kind {
+        AstFieldDefaultKind::Value(AstExpr::StringLiteral(value, _))
+        | AstFieldDefaultKind::Value(AstExpr::NumberLiteral(value, _)) => Some(value.clone()),
+        AstFieldDefaultKind::Value(AstExpr::BooleanLiteral(value, _)) => Some(value.to_string()),
+        _ => {
+            report(
+                "The default value of an @env context field must be a string, number, or boolean literal",
+                "not a literal",
+            );
+            None
+        }
+    }
+}
+
 pub fn compute_fragment_fields<'a>(
     ct: &'a AstModel<Typed>,
     errors: &mut Vec<Diagnostic>,
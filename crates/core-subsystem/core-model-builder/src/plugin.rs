@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use crate::{ast::ast_types::AstExpr, typechecker::Typed};
-use core_plugin_shared::interception::{InterceptorIndex, InterceptorKind};
+use core_plugin_shared::interception::{InterceptorErrorPolicy, InterceptorIndex, InterceptorKind};
 use core_plugin_shared::serializable_system::{
     SerializableCoreBytes, SerializableGraphQLBytes, SerializableRestBytes, SerializableRpcBytes,
 };
@@ -26,6 +26,7 @@ pub struct Interception {
     pub expr: AstExpr<Typed>,
     pub kind: InterceptorKind,
     pub index: InterceptorIndex,
+    pub error_policy: InterceptorErrorPolicy,
 }
 
 pub struct RestSubsystemBuild {
@@ -23,6 +23,7 @@ pub struct AnnotationSpec {
 #[derive(Debug, PartialEq, Eq)]
 pub enum AnnotationTarget {
     Type,
+    Enum,
     Field,
     Argument,
     Module,
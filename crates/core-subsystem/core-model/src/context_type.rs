@@ -24,9 +24,12 @@
 //!
 //! context Env {
 //!   @env("DEVELOPMENT") isDev: Boolean
+//!   @env("LOG_LEVEL") logLevel: String = "info"
 //! }
 //! ```
 //! Annotations like `@jwt` or `@clientId` define how the context is populated from the request and environment.
+//! An `@env` field may declare a default, which applies to the whole system (as the lowest
+//! precedence layer of the environment).
 
 use serde::{Deserialize, Serialize};
 
@@ -56,6 +59,9 @@ pub struct ContextField {
     pub typ: ContextFieldType,
     /// Source of the information such as `@jwt` and `@clientId`
     pub source: ContextSource,
+    /// Default value such as `"info"` in `@env("LOG_LEVEL") logLevel: String = "info"` (only
+    /// `@env` fields may have one)
+    pub default_value: Option<String>,
 }
 
 /// The type of a context field such as `Int` and `Array<String>`
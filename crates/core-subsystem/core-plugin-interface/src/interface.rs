@@ -15,6 +15,9 @@ use std::{env::current_exe, path::Path};
 
 use async_trait::async_trait;
 
+use common::access_coverage::{AccessCoverage, RuleCoverage};
+use common::context::SharedContextExtractor;
+use common::router::{PlainRequestPayload, Router};
 use core_model_builder::plugin::{
     BuildMode, CoreSubsystemBuild, RestSubsystemBuild, RpcSubsystemBuild,
 };
@@ -30,6 +33,7 @@ use core_resolver::plugin::{SubsystemRestResolver, SubsystemRpcResolver};
 use thiserror::Error;
 
 use crate::build_info::SubsystemCheckError;
+use crate::manifest::SubsystemManifestError;
 use exo_env::Environment;
 
 pub struct SubsystemBuild {
@@ -123,6 +127,15 @@ pub struct SubsystemResolver {
     pub graphql: Option<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
     pub rest: Option<Box<dyn SubsystemRestResolver + Send + Sync>>,
     pub rpc: Option<Box<dyn SubsystemRpcResolver + Send + Sync>>,
+    /// Resolvers for the admin API (served under the admin path only when the admin API is enabled)
+    pub admin: Vec<Box<dyn SubsystemRestResolver + Send + Sync>>,
+    pub background_services: Vec<Arc<dyn SubsystemBackgroundService>>,
+    /// Status checks of the resources the subsystem depends on (included in the startup report)
+    pub diagnostics: Vec<Arc<dyn SubsystemDiagnostics>>,
+    /// The access rules to include in the access coverage report
+    pub access_rules: Option<Arc<dyn SubsystemAccessRules>>,
+    /// Sources of context fields (for annotations the subsystem's builder declares)
+    pub context_providers: Vec<SharedContextExtractor>,
 }
 
 impl SubsystemResolver {
@@ -131,8 +144,80 @@ impl SubsystemResolver {
         rest: Option<Box<dyn SubsystemRestResolver + Send + Sync>>,
         rpc: Option<Box<dyn SubsystemRpcResolver + Send + Sync>>,
     ) -> Self {
-        Self { graphql, rest, rpc }
+        Self {
+            graphql,
+            rest,
+            rpc,
+            admin: vec![],
+            background_services: vec![],
+            diagnostics: vec![],
+            access_rules: None,
+            context_providers: vec![],
+        }
     }
+
+    pub fn with_admin(mut self, admin: Box<dyn SubsystemRestResolver + Send + Sync>) -> Self {
+        self.admin.push(admin);
+        self
+    }
+
+    pub fn with_background_service(mut self, service: Arc<dyn SubsystemBackgroundService>) -> Self {
+        self.background_services.push(service);
+        self
+    }
+
+    pub fn with_diagnostics(mut self, diagnostics: Arc<dyn SubsystemDiagnostics>) -> Self {
+        self.diagnostics.push(diagnostics);
+        self
+    }
+
+    pub fn with_access_rules(mut self, access_rules: Arc<dyn SubsystemAccessRules>) -> Self {
+        self.access_rules = Some(access_rules);
+        self
+    }
+
+    /// Provide the values of context fields with the provider's annotation (such as
+    /// `@ldap("groups")`). The subsystem's builder must declare the annotation (see
+    /// [SubsystemBuilder::annotations]) for models to use it.
+    pub fn with_context_provider(mut self, provider: SharedContextExtractor) -> Self {
+        self.context_providers.push(provider);
+        self
+    }
+}
+
+pub type SystemRouterRef =
+    Arc<dyn for<'request> Router<PlainRequestPayload<'request>> + Send + Sync>;
+
+/// A long-running task provided by a subsystem (for example, a message consumer).
+///
+/// Background services are started only by long-running servers (and not, for example, by
+/// serverless deployments), once the system is fully loaded. They interact with the rest of the
+/// system by routing requests through the system router.
+#[async_trait]
+pub trait SubsystemBackgroundService: Send + Sync {
+    /// Run the service. Expected to run for the lifetime of the server.
+    async fn run(&self, router: SystemRouterRef, env: Arc<dyn Environment>);
+}
+
+/// A check of a resource a subsystem depends on (for example, the database connection).
+///
+/// Checks run once the system is loaded and their results are included in the startup report (to
+/// make it easy to tell why a deployment misbehaves).
+#[async_trait]
+pub trait SubsystemDiagnostics: Send + Sync {
+    /// The key to report the status under (for example, "database")
+    fn name(&self) -> &'static str;
+
+    /// Check the resource. Failures (such as an unreachable database) should be reported as part
+    /// of the returned status.
+    async fn diagnose(&self) -> serde_json::Value;
+}
+
+/// The access rules of a subsystem, to report which of them tests exercise (see
+/// [common::access_coverage]).
+pub trait SubsystemAccessRules: Send + Sync {
+    /// The coverage of each rule, given the outcomes recorded so far
+    fn coverage(&self, access_coverage: &AccessCoverage) -> Vec<RuleCoverage>;
 }
 
 #[async_trait]
@@ -179,6 +264,19 @@ pub enum LibraryLoadingError {
 
     #[error("Subsystem library check failed: {0}")]
     CheckFailed(#[from] SubsystemCheckError),
+
+    #[error("{0}")]
+    ManifestError(#[from] SubsystemManifestError),
+
+    #[error(
+        "Library {} provides subsystem '{actual}', but was registered for '{expected}'",
+        library.display()
+    )]
+    SubsystemIdMismatch {
+        library: PathBuf,
+        expected: String,
+        actual: String,
+    },
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -231,18 +329,47 @@ pub fn load_subsystem_builder(
 
 #[cfg(not(target_family = "wasm"))]
 /// Loads a subsystem loader from a dynamic library.
+///
+/// The library is searched for in each of `search_dirs` (in order) and then in the
+/// directory of the current executable.
 pub fn load_subsystem_loader(
     library_name: &str,
+    search_dirs: &[PathBuf],
 ) -> Result<Box<dyn SubsystemLoader + Send + Sync>, LibraryLoadingError> {
-    // search executable directory for library
-    // TODO: we should try to load from sources LD_LIBRARY_PATH first
-    let mut library_path = current_exe()?;
-    library_path.pop();
-    library_path.push(libloading::library_filename(library_name));
+    let library_filename = libloading::library_filename(library_name);
+
+    let mut exe_dir = current_exe()?;
+    exe_dir.pop();
+
+    let candidates = search_dirs
+        .iter()
+        .chain(std::iter::once(&exe_dir))
+        .map(|dir| dir.join(&library_filename));
+
+    let mut last_candidate = None;
+    for library_path in candidates {
+        if library_path.exists() {
+            return load_subsystem_loader_from_path(&library_path);
+        }
+        last_candidate = Some(library_path);
+    }
+
+    Err(LibraryLoadingError::LibraryNotFound(
+        last_candidate.unwrap_or(exe_dir),
+    ))
+}
 
+#[cfg(not(target_family = "wasm"))]
+/// Loads a subsystem loader from a dynamic library at an explicit path (typically, one
+/// declared in a [crate::manifest::SubsystemManifest]).
+pub fn load_subsystem_loader_from_path(
+    library_path: &Path,
+) -> Result<Box<dyn SubsystemLoader + Send + Sync>, LibraryLoadingError> {
     if !library_path.exists() {
-        return Err(LibraryLoadingError::LibraryNotFound(library_path));
+        return Err(LibraryLoadingError::LibraryNotFound(
+            library_path.to_path_buf(),
+        ));
     }
 
-    load_subsystem_library(&library_path, "__exograph_subsystem_loader")
+    load_subsystem_library(library_path, "__exograph_subsystem_loader")
 }
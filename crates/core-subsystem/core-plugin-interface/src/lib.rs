@@ -11,3 +11,4 @@ pub mod build_info;
 pub mod export_utils;
 
 pub mod interface;
+pub mod manifest;
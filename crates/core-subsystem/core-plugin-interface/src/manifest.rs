@@ -0,0 +1,139 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Manifest describing third-party subsystem loaders to load from dynamic libraries.
+//!
+//! The manifest is a JSON file such as:
+//!
+//! ```json
+//! {
+//!   "subsystems": [
+//!     { "id": "redis", "library": "plugins/libredis_resolver_dynamic.so" }
+//!   ]
+//! }
+//! ```
+//!
+//! Relative library paths are resolved against the directory containing the manifest.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct SubsystemManifest {
+    #[serde(default)]
+    pub subsystems: Vec<SubsystemManifestEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SubsystemManifestEntry {
+    /// Must match the id reported by the loader in the library (and the id of the subsystem in the exo_ir file)
+    pub id: String,
+    /// Path to the dynamic library that exports the subsystem loader
+    pub library: PathBuf,
+}
+
+#[derive(Error, Debug)]
+pub enum SubsystemManifestError {
+    #[error("Failed to read subsystem manifest {0}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid subsystem manifest {0}: {1}")]
+    Parse(PathBuf, #[source] serde_json::Error),
+
+    #[error("Subsystem '{0}' is declared more than once in the subsystem manifest")]
+    DuplicateSubsystem(String),
+}
+
+impl SubsystemManifest {
+    pub fn load(manifest_path: &Path) -> Result<Self, SubsystemManifestError> {
+        let content = std::fs::read_to_string(manifest_path)
+            .map_err(|e| SubsystemManifestError::Io(manifest_path.to_path_buf(), e))?;
+
+        Self::parse(&content, manifest_path)
+    }
+
+    fn parse(content: &str, manifest_path: &Path) -> Result<Self, SubsystemManifestError> {
+        let mut manifest: SubsystemManifest = serde_json::from_str(content)
+            .map_err(|e| SubsystemManifestError::Parse(manifest_path.to_path_buf(), e))?;
+
+        for (index, entry) in manifest.subsystems.iter().enumerate() {
+            if manifest.subsystems[..index]
+                .iter()
+                .any(|other| other.id == entry.id)
+            {
+                return Err(SubsystemManifestError::DuplicateSubsystem(entry.id.clone()));
+            }
+        }
+
+        let base_dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+        for entry in manifest.subsystems.iter_mut() {
+            if entry.library.is_relative() {
+                entry.library = base_dir.join(&entry.library);
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn library_for(&self, subsystem_id: &str) -> Option<&Path> {
+        self.subsystems
+            .iter()
+            .find(|entry| entry.id == subsystem_id)
+            .map(|entry| entry.library.as_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_paths() {
+        let manifest = SubsystemManifest::parse(
+            r#"{
+                "subsystems": [
+                    { "id": "redis", "library": "plugins/libredis.so" },
+                    { "id": "search", "library": "/opt/exo/libsearch.so" }
+                ]
+            }"#,
+            Path::new("/etc/exo/subsystems.json"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.library_for("redis"),
+            Some(Path::new("/etc/exo/plugins/libredis.so"))
+        );
+        assert_eq!(
+            manifest.library_for("search"),
+            Some(Path::new("/opt/exo/libsearch.so"))
+        );
+        assert_eq!(manifest.library_for("postgres"), None);
+    }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        let manifest = SubsystemManifest::parse(
+            r#"{
+                "subsystems": [
+                    { "id": "redis", "library": "a.so" },
+                    { "id": "redis", "library": "b.so" }
+                ]
+            }"#,
+            Path::new("subsystems.json"),
+        );
+
+        assert!(matches!(
+            manifest,
+            Err(SubsystemManifestError::DuplicateSubsystem(id)) if id == "redis"
+        ));
+    }
+}
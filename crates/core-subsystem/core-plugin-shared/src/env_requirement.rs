@@ -0,0 +1,125 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Env vars that modules declare they require (through `@requireEnv`), so the server can check
+//! them at startup instead of failing at first use.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnvRequirement {
+    /// Name of the env var such as `STRIPE_API_KEY`
+    pub key: String,
+    pub typ: EnvVarType,
+    /// Whether the var may be left unset (declared with an optional type)
+    pub optional: bool,
+    /// Names of the modules that require the var
+    pub required_by: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvVarType {
+    String,
+    Int,
+    Float,
+    Decimal,
+    Boolean,
+}
+
+impl EnvVarType {
+    /// The type for a primitive type name such as `Int` (or `None` if env vars can't have the type)
+    pub fn from_type_name(name: &str) -> Option<Self> {
+        match name {
+            "String" => Some(Self::String),
+            "Int" => Some(Self::Int),
+            "Float" => Some(Self::Float),
+            "Decimal" => Some(Self::Decimal),
+            "Boolean" => Some(Self::Boolean),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::String => "String",
+            Self::Int => "Int",
+            Self::Float => "Float",
+            Self::Decimal => "Decimal",
+            Self::Boolean => "Boolean",
+        }
+    }
+
+    /// Check that the value can be interpreted as this type
+    pub fn accepts(&self, value: &str) -> bool {
+        let value = value.trim();
+
+        match self {
+            Self::String => true,
+            Self::Int => value.parse::<i64>().is_ok(),
+            Self::Float => value.parse::<f64>().is_ok_and(f64::is_finite),
+            Self::Decimal => {
+                let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+                let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+                !whole.is_empty()
+                    && whole.chars().all(|c| c.is_ascii_digit())
+                    && fraction.chars().all(|c| c.is_ascii_digit())
+            }
+            // Same values as accepted by `Environment::enabled`
+            Self::Boolean => matches!(
+                value.to_lowercase().as_str(),
+                "true"
+                    | "1"
+                    | "yes"
+                    | "on"
+                    | "enabled"
+                    | "enable"
+                    | "false"
+                    | "0"
+                    | "no"
+                    | "off"
+                    | "disabled"
+                    | "disable"
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for EnvVarType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvVarType;
+    use multiplatform_test::multiplatform_test;
+
+    #[multiplatform_test]
+    fn accepts() {
+        assert!(EnvVarType::Int.accepts("42"));
+        assert!(EnvVarType::Int.accepts(" -7 "));
+        assert!(!EnvVarType::Int.accepts("4.2"));
+
+        assert!(EnvVarType::Float.accepts("4.2"));
+        assert!(!EnvVarType::Float.accepts("NaN"));
+
+        assert!(EnvVarType::Decimal.accepts("-10.25"));
+        assert!(EnvVarType::Decimal.accepts("10"));
+        assert!(!EnvVarType::Decimal.accepts("1e5"));
+        assert!(!EnvVarType::Decimal.accepts(".5"));
+
+        assert!(EnvVarType::Boolean.accepts("Yes"));
+        assert!(EnvVarType::Boolean.accepts("off"));
+        assert!(!EnvVarType::Boolean.accepts("maybe"));
+
+        assert!(EnvVarType::String.accepts(""));
+    }
+}
@@ -16,4 +16,9 @@ pub enum ModelSerializationError {
 
     #[error("Unable to deserialize model {0}")]
     Deserialize(#[source] bincode::error::DecodeError),
+
+    #[error(
+        "Incompatible exo_ir file: {0}. Rebuild required: run `exo build` with the same Exograph version as the server"
+    )]
+    IncompatibleVersion(String),
 }
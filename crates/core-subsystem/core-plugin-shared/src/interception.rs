@@ -44,6 +44,9 @@ pub enum InterceptionTree {
 pub struct InterceptorIndexWithSubsystemIndex {
     pub subsystem_index: usize,
     pub interceptor_index: InterceptorIndex,
+    /// What to do if the interceptor fails (applies only to before/after interceptors)
+    #[serde(default)]
+    pub error_policy: InterceptorErrorPolicy,
 }
 
 impl InterceptorIndexWithSubsystemIndex {
@@ -51,6 +54,14 @@ impl InterceptorIndexWithSubsystemIndex {
         Self {
             subsystem_index,
             interceptor_index,
+            error_policy: InterceptorErrorPolicy::default(),
+        }
+    }
+
+    pub fn with_error_policy(self, error_policy: InterceptorErrorPolicy) -> Self {
+        Self {
+            error_policy,
+            ..self
         }
     }
 }
@@ -62,6 +73,17 @@ impl InterceptorIndexWithSubsystemIndex {
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct InterceptorIndex(pub usize);
 
+/// What to do when a before/after interceptor fails (specified with `@onError`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InterceptorErrorPolicy {
+    /// Fail the request (and roll back its transaction)
+    #[default]
+    Abort,
+    /// Log the error and continue with the request, rolling back only the database work done by
+    /// the interceptor (through a savepoint)
+    Continue,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum InterceptorKind {
     Before,
@@ -7,9 +7,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod env_requirement;
 pub mod error;
 pub mod interception;
+pub mod operation_limit;
 pub mod profile;
+pub mod response_headers;
+pub mod routes;
 pub mod serializable_system;
 pub mod system_serializer;
 pub mod trusted_documents;
@@ -0,0 +1,23 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Concurrency limits that the model declares for expensive operations (through
+//! `@concurrencyLimit` on module methods).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OperationLimit {
+    /// Name of the top-level query or mutation such as `salesReport`
+    pub operation: String,
+    /// Maximum number of executions that may run at the same time
+    pub max_concurrent: usize,
+    /// Maximum number of executions that may wait for a free slot (others are rejected)
+    pub max_queued: usize,
+}
@@ -0,0 +1,21 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Response headers that the model declares for operations (through `@responseHeaders` on module
+//! methods).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OperationResponseHeaders {
+    /// Name of the top-level query or mutation such as `salesReport`
+    pub operation: String,
+    /// The headers to set (as name and value pairs, sorted by name)
+    pub headers: Vec<(String, String)>,
+}
@@ -0,0 +1,44 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! HTTP routes (outside the GraphQL endpoint) that the model declares (through `@redirects` on
+//! modules and `@route` on module methods).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeclaredRoute {
+    /// The path to serve such as `/robots.txt` (starting with a slash)
+    pub path: String,
+    pub target: RouteTarget,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RouteTarget {
+    /// Redirect to the location (a URL or another path)
+    Redirect { location: String },
+    /// Respond with the value that the operation returns
+    Operation(RouteOperation),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RouteOperation {
+    /// Name of the top-level query or mutation such as `robots`
+    pub name: String,
+    /// Mutations serve `POST` requests, and queries `GET` (and `HEAD`) requests
+    pub mutation: bool,
+    /// The arguments (taken from the query parameters of the same name)
+    pub arguments: Vec<RouteArgument>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RouteArgument {
+    pub name: String,
+    pub optional: bool,
+}
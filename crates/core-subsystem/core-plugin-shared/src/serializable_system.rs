@@ -7,9 +7,15 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::{profile::SchemaProfiles, trusted_documents::TrustedDocuments};
+use crate::{
+    env_requirement::EnvRequirement, operation_limit::OperationLimit, profile::SchemaProfiles,
+    response_headers::OperationResponseHeaders, routes::DeclaredRoute,
+    trusted_documents::TrustedDocuments,
+};
 
 use super::{
     error::ModelSerializationError, interception::InterceptionMap,
@@ -27,6 +33,16 @@ pub struct SerializableSystem {
     pub trusted_documents: TrustedDocuments,
     pub declaration_doc_comments: Option<String>,
     pub schema_profiles: Option<SchemaProfiles>,
+    /// Env var defaults declared in the model (`@env("LOG_LEVEL") logLevel: String = "info"`)
+    pub env_defaults: HashMap<String, String>,
+    /// Env vars that modules require (checked at startup)
+    pub env_requirements: Vec<EnvRequirement>,
+    /// Concurrency limits declared for operations (through `@concurrencyLimit`)
+    pub operation_limits: Vec<OperationLimit>,
+    /// Response headers declared for operations (through `@responseHeaders`)
+    pub operation_response_headers: Vec<OperationResponseHeaders>,
+    /// HTTP routes declared outside the GraphQL endpoint (through `@redirects` and `@route`)
+    pub routes: Vec<DeclaredRoute>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -64,9 +80,30 @@ pub struct SerializableRestSystem {
     pub serialized_subsystems: Vec<(String, usize, SerializableRestBytes)>,
 }
 
+/// The version of the serialized system layout (the bytes following the header).
+///
+/// Bump this whenever a change to [`SerializableSystem`] or any subsystem's serialized model
+/// makes older exo_ir files undecodable. Files with a different format version are rejected
+/// with a "rebuild required" error regardless of the crate version that produced them.
+///
+/// There is no shim to decode older formats: a file is accepted only if it has exactly this
+/// format version (see [`Header::check_header`] for the accepted crate versions). In particular,
+/// files written before the format version was recorded (in the version 1 layout) must be
+/// rebuilt.
+///
+/// Version 2 changed the Deno model (for `@stream`).
+/// Version 3 added `env_defaults` to [`SerializableSystem`].
+/// Version 4 added `env_requirements`.
+/// Version 5 added `operation_limits`.
+/// Version 6 added `operation_response_headers`.
+/// Version 7 added `routes`.
+/// Version 8 added the connection settings of the Postgres model.
+/// Version 9 added the chunk queries of the Postgres GraphQL model (for `@chunked`).
+pub const IR_FORMAT_VERSION: u32 = 9;
+
 /// File header data for exo_ir files.
-/// Used to check that the file version information matches the current version
-/// of the code. The list of plugin names is also stored in the header but is
+/// Used to check that the file version information is compatible with the current
+/// version of the code. The list of plugin names is also stored in the header but is
 /// not checked. Deserializing a file without the necessary subsysytem loader
 /// should fail at a later stage since a matching loader won't be found.
 #[derive(Serialize, Deserialize, Debug)]
@@ -74,6 +111,9 @@ struct Header {
     builder_version: String,
     ir_version: String,
     plugins: Vec<String>,
+    // Appended after the fields of headers written before the format version was recorded, so
+    // that such headers fail to decode
+    format_version: u32,
 }
 
 impl Header {
@@ -83,24 +123,97 @@ impl Header {
             builder_version: version.clone(),
             ir_version: version,
             plugins,
+            format_version: IR_FORMAT_VERSION,
         }
     }
 
-    fn check_header(&self, header: Header) -> Result<(), String> {
-        if self.ir_version != header.ir_version {
-            return Err(format!(
-                "Version for this file {0} does not match current version {1}",
-                header.ir_version, self.ir_version
-            ));
+    fn decode(header_bytes: &[u8]) -> Result<Header, ModelSerializationError> {
+        let unversioned = || {
+            ModelSerializationError::IncompatibleVersion(
+                "The file has no format version (it was built by an older version)".to_string(),
+            )
+        };
+
+        let (header, size) = bincode::serde::decode_from_slice::<Header, _>(
+            header_bytes,
+            bincode::config::standard(),
+        )
+        .map_err(|_| unversioned())?;
+
+        if size != header_bytes.len() {
+            return Err(unversioned());
         }
-        if self.builder_version != header.builder_version {
-            return Err(format!(
-                "Builder version for this file {0} does not match current version {1}",
-                header.builder_version, self.builder_version
-            ));
+
+        Ok(header)
+    }
+
+    /// Check if a file with the given header can be loaded by the current version.
+    ///
+    /// The format versions must match exactly. The versions that produced the file may either
+    /// match the current version's `major.minor` or be from the immediately preceding minor
+    /// version of the same major version (to allow deploying a new server before rebuilding the
+    /// exo_ir file). Since the format version must still match, this only helps when the format
+    /// didn't change between the two minor versions.
+    fn check_header(&self, header: Header) -> Result<(), ModelSerializationError> {
+        if self.format_version != header.format_version {
+            return Err(ModelSerializationError::IncompatibleVersion(format!(
+                "Format version for this file {0} does not match current format version {1}",
+                header.format_version, self.format_version
+            )));
         }
+
+        Self::check_version("Version", &header.ir_version, &self.ir_version)?;
+        Self::check_version(
+            "Builder version",
+            &header.builder_version,
+            &self.builder_version,
+        )?;
+
         Ok(())
     }
+
+    fn check_version(
+        label: &str,
+        file_version: &str,
+        current_version: &str,
+    ) -> Result<(), ModelSerializationError> {
+        if file_version == current_version {
+            return Ok(());
+        }
+
+        let incompatible = || {
+            ModelSerializationError::IncompatibleVersion(format!(
+                "{label} for this file {file_version} does not match current version {current_version}"
+            ))
+        };
+
+        let (file_major, file_minor) = major_minor(file_version).ok_or_else(incompatible)?;
+        let (current_major, current_minor) =
+            major_minor(current_version).ok_or_else(incompatible)?;
+
+        if file_major != current_major {
+            return Err(incompatible());
+        }
+
+        if file_minor == current_minor {
+            Ok(())
+        } else if file_minor + 1 == current_minor {
+            tracing::warn!(
+                "{label} for this file {file_version} is older than current version {current_version}. Loading it in compatibility mode; consider rebuilding it"
+            );
+            Ok(())
+        } else {
+            Err(incompatible())
+        }
+    }
+}
+
+/// Parse the `major.minor` part of a version such as "0.30.5" or "0.31.0-alpha.1"
+fn major_minor(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
 }
 
 impl SystemSerializer for SerializableSystem {
@@ -111,69 +224,66 @@ impl SystemSerializer for SerializableSystem {
     }
 
     fn deserialize_reader(
-        mut reader: impl std::io::Read,
+        reader: impl std::io::Read,
     ) -> Result<Self::Underlying, ModelSerializationError> {
-        // TODO: ModelSerializationError should not be dependent on bincode errors since
-        // it is used by subsystem serializers which may use other formats.
-        fn error(msg: &str, io_error: Option<std::io::Error>) -> ModelSerializationError {
-            let msg = match io_error {
-                Some(e) => format!("{msg}: {e}"),
-                None => msg.to_string(),
-            };
-            ModelSerializationError::Deserialize(bincode::error::DecodeError::OtherString(msg))
-        }
-        {
-            // Check the file prefix
-            let mut prefix = [0_u8; PREFIX_TAG_LEN];
-            reader
-                .read_exact(&mut prefix)
-                .map_err(|e| error("Failed to read exograph prefix", Some(e)))?;
-
-            if prefix != PREFIX_TAG {
-                return Err(error("Invalid exograph file prefix", None));
-            }
-        }
-        // Serialize header len as u64 to make exo_ir platform independent (32-bit vs 64-bit systems)
-        let header_len = {
-            let mut header_len = [0_u8; std::mem::size_of::<u64>()];
-            reader
-                .read_exact(&mut header_len)
-                .map_err(|e| error("Failed to read exograph header size", Some(e)))?;
-            u64::from_le_bytes(header_len)
-        };
-        let header_len = header_len.try_into().map_err(|_| {
-            error(
-                "Failed to convert the exo_ir file header size to usize",
-                None,
-            )
-        })?;
-        // To allow each exo_ir version to have different header sizes, we read the header for the exact bytes specified by header_len.
-        let mut header_bytes = vec![0_u8; header_len];
+        deserialize_with_current_header(reader, &Header::new(vec![]))
+    }
+}
 
+fn deserialize_with_current_header(
+    mut reader: impl std::io::Read,
+    current_header: &Header,
+) -> Result<SerializableSystem, ModelSerializationError> {
+    // TODO: ModelSerializationError should not be dependent on bincode errors since
+    // it is used by subsystem serializers which may use other formats.
+    fn error(msg: &str, io_error: Option<std::io::Error>) -> ModelSerializationError {
+        let msg = match io_error {
+            Some(e) => format!("{msg}: {e}"),
+            None => msg.to_string(),
+        };
+        ModelSerializationError::Deserialize(bincode::error::DecodeError::OtherString(msg))
+    }
+    {
+        // Check the file prefix
+        let mut prefix = [0_u8; PREFIX_TAG_LEN];
         reader
-            .read_exact(&mut header_bytes)
-            .map_err(|e| error("Failed to read the exo_ir file header", Some(e)))?;
+            .read_exact(&mut prefix)
+            .map_err(|e| error("Failed to read exograph prefix", Some(e)))?;
 
-        let (header, size) = bincode::serde::decode_from_slice::<Header, _>(
-            &header_bytes,
-            bincode::config::standard(),
-        )
-        .map_err(ModelSerializationError::Deserialize)?;
-        if size != header_bytes.len() {
-            return Err(error("Incomplete header deserialization", None));
+        if prefix != PREFIX_TAG {
+            return Err(error("Invalid exograph file prefix", None));
         }
-        let current_header = Header::new(vec![]);
-        current_header
-            .check_header(header)
-            .map_err(|e| error(&e, None))?;
-
-        bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())
-            .map_err(ModelSerializationError::Deserialize)
     }
+    // Serialize header len as u64 to make exo_ir platform independent (32-bit vs 64-bit systems)
+    let header_len = {
+        let mut header_len = [0_u8; std::mem::size_of::<u64>()];
+        reader
+            .read_exact(&mut header_len)
+            .map_err(|e| error("Failed to read exograph header size", Some(e)))?;
+        u64::from_le_bytes(header_len)
+    };
+    let header_len = header_len.try_into().map_err(|_| {
+        error(
+            "Failed to convert the exo_ir file header size to usize",
+            None,
+        )
+    })?;
+    // To allow each exo_ir version to have different header sizes, we read the header for the exact bytes specified by header_len.
+    let mut header_bytes = vec![0_u8; header_len];
+
+    reader
+        .read_exact(&mut header_bytes)
+        .map_err(|e| error("Failed to read the exo_ir file header", Some(e)))?;
+
+    let header = Header::decode(&header_bytes)?;
+    current_header.check_header(header)?;
+
+    bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())
+        .map_err(ModelSerializationError::Deserialize)
 }
 
 fn serialize_header_and_system(
-    header: &Header,
+    header: &impl Serialize,
     system: &SerializableSystem,
 ) -> Result<Vec<u8>, ModelSerializationError> {
     let header: Vec<u8> = bincode::serde::encode_to_vec(header, bincode::config::standard())
@@ -193,7 +303,10 @@ fn serialize_header_and_system(
 #[cfg(test)]
 mod test {
     use super::{SerializableSubsystem, SerializableSystem};
-    use crate::{interception::InterceptionMap, system_serializer::SystemSerializer};
+    use crate::{
+        error::ModelSerializationError, interception::InterceptionMap,
+        system_serializer::SystemSerializer,
+    };
     use multiplatform_test::multiplatform_test;
     use std::collections::HashMap;
 
@@ -218,6 +331,11 @@ mod test {
             }],
             declaration_doc_comments: None,
             schema_profiles: None,
+            env_defaults: HashMap::new(),
+            env_requirements: vec![],
+            operation_limits: vec![],
+            operation_response_headers: vec![],
+            routes: vec![],
         }
     }
 
@@ -229,24 +347,106 @@ mod test {
             .expect("Deserialization should succeed");
     }
 
-    #[multiplatform_test]
-    fn deserialize_different_version() {
-        let system = mk_system();
+    fn header_with_version(version: &str) -> super::Header {
         let mut header = super::Header::new(vec![]);
-        header.builder_version = "0.0.1".to_string();
-        let system_bytes =
-            super::serialize_header_and_system(&header, &system).expect("Should serialize");
-        let result = SerializableSystem::deserialize_reader(system_bytes.as_slice());
+        header.builder_version = version.to_string();
+        header.ir_version = version.to_string();
+        header
+    }
+
+    /// Load a file with the given header in a server of the given version (the tests use explicit
+    /// versions rather than the crate's, so that they don't depend on the current release)
+    fn load(
+        file_header: &impl serde::Serialize,
+        current_version: &str,
+    ) -> Result<SerializableSystem, ModelSerializationError> {
+        let system_bytes = super::serialize_header_and_system(file_header, &mk_system())
+            .expect("Should serialize");
+        super::deserialize_with_current_header(
+            system_bytes.as_slice(),
+            &header_with_version(current_version),
+        )
+    }
+
+    fn assert_incompatible(result: Result<SerializableSystem, ModelSerializationError>) {
         assert!(
-            result.is_err(),
-            "Old builder_version should fail to deserialize"
+            matches!(result, Err(ModelSerializationError::IncompatibleVersion(_))),
+            "Expected an incompatible version error, got {result:?}"
         );
+    }
 
-        let mut header = super::Header::new(vec![]);
+    #[multiplatform_test]
+    fn deserialize_different_version() {
+        let mut header = header_with_version("0.30.5");
+        header.builder_version = "0.0.1".to_string();
+        assert_incompatible(load(&header, "0.30.5"));
+
+        let mut header = header_with_version("0.30.5");
         header.ir_version = "0.0.1".to_string();
-        let system_bytes =
-            super::serialize_header_and_system(&header, &system).expect("Should serialize");
-        let result = SerializableSystem::deserialize_reader(system_bytes.as_slice());
-        assert!(result.is_err(), "Old ir_version should fail to deserialize");
+        assert_incompatible(load(&header, "0.30.5"));
+    }
+
+    #[multiplatform_test]
+    fn deserialize_same_minor_version() {
+        load(&header_with_version("0.30.1"), "0.30.5")
+            .expect("Same minor version should deserialize");
+        load(&header_with_version("1.0.0"), "1.0.4")
+            .expect("Same minor version should deserialize");
+    }
+
+    #[multiplatform_test]
+    fn deserialize_previous_minor_version() {
+        load(&header_with_version("0.29.3"), "0.30.5")
+            .expect("Previous minor version should deserialize");
+        load(&header_with_version("1.4.2"), "1.5.0-alpha.1")
+            .expect("Previous minor version should deserialize");
+    }
+
+    #[multiplatform_test]
+    fn deserialize_previous_major_version() {
+        // There is no minor version preceding x.0 in the same major version
+        assert_incompatible(load(&header_with_version("0.30.5"), "1.0.0"));
+        assert_incompatible(load(&header_with_version("0.30.5"), "1.1.0"));
+    }
+
+    #[multiplatform_test]
+    fn deserialize_older_minor_version() {
+        assert_incompatible(load(&header_with_version("0.28.9"), "0.30.5"));
+    }
+
+    #[multiplatform_test]
+    fn deserialize_newer_minor_version() {
+        let mut header = header_with_version("0.30.5");
+        header.builder_version = "0.31.0".to_string();
+        assert_incompatible(load(&header, "0.30.5"));
+    }
+
+    #[multiplatform_test]
+    fn deserialize_different_format_version() {
+        for format_version in [super::IR_FORMAT_VERSION - 1, super::IR_FORMAT_VERSION + 1] {
+            let mut header = header_with_version("0.30.5");
+            header.format_version = format_version;
+            assert_incompatible(load(&header, "0.30.5"));
+        }
+    }
+
+    /// The header written before the format version was recorded
+    #[derive(serde::Serialize)]
+    struct UnversionedHeader {
+        builder_version: String,
+        ir_version: String,
+        plugins: Vec<String>,
+    }
+
+    #[multiplatform_test]
+    fn deserialize_unversioned_header() {
+        let header = UnversionedHeader {
+            builder_version: "0.29.3".to_string(),
+            ir_version: "0.29.3".to_string(),
+            plugins: vec![],
+        };
+
+        // Such files are in the version 1 layout, which the current version can't decode
+        assert_incompatible(load(&header, "0.30.5"));
     }
 }
@@ -71,6 +71,17 @@ impl TrustedDocuments {
         }
     }
 
+    /// The documents along with their hashes
+    pub fn documents(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mapping = match self {
+            TrustedDocuments::All(mapping) => mapping,
+            TrustedDocuments::MatchingOnly(mapping) => mapping,
+        };
+        mapping
+            .iter()
+            .map(|(hash, document)| (hash.as_str(), document.as_str()))
+    }
+
     fn get<'a>(&'a self, key: &str) -> Option<&'a str> {
         match self {
             TrustedDocuments::All(mapping) => mapping.get(key),
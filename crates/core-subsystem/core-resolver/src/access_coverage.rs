@@ -0,0 +1,266 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Building the coverage report of access rules from the outcomes recorded while solving them
+//! (see [common::access_coverage]).
+
+use common::access_coverage::{AccessCoverage, BranchCoverage, RuleCoverage};
+use core_model::{
+    access::{
+        AccessLogicalExpression, AccessPredicateExpression, AccessRelationalOp,
+        CommonAccessPrimitiveExpression,
+    },
+    context_type::{ContextSelection, ContextSelectionElement},
+    primitive_type::{NumberLiteral, PrimitiveValue},
+};
+
+/// The coverage of a rule, with a branch for each node of its expression (in pre-order).
+///
+/// The `describe_primitive` function renders the subsystem-specific primitive expressions (such
+/// as a column path).
+pub fn rule_coverage<PrimExpr: Send + Sync>(
+    rule: impl Into<String>,
+    expr: &AccessPredicateExpression<PrimExpr>,
+    access_coverage: &AccessCoverage,
+    describe_primitive: &impl Fn(&PrimExpr) -> String,
+) -> RuleCoverage {
+    let mut branches = vec![];
+    collect_branches(expr, 0, access_coverage, describe_primitive, &mut branches);
+
+    RuleCoverage {
+        rule: rule.into(),
+        branches,
+    }
+}
+
+fn collect_branches<PrimExpr: Send + Sync>(
+    expr: &AccessPredicateExpression<PrimExpr>,
+    depth: usize,
+    access_coverage: &AccessCoverage,
+    describe_primitive: &impl Fn(&PrimExpr) -> String,
+    branches: &mut Vec<BranchCoverage>,
+) {
+    branches.push(BranchCoverage {
+        expression: describe_expression(expr, describe_primitive),
+        depth,
+        counts: access_coverage.counts(expr),
+    });
+
+    if let AccessPredicateExpression::LogicalOp(op) = expr {
+        match op {
+            AccessLogicalExpression::Not(underlying) => collect_branches(
+                underlying,
+                depth + 1,
+                access_coverage,
+                describe_primitive,
+                branches,
+            ),
+            AccessLogicalExpression::And(left, right)
+            | AccessLogicalExpression::Or(left, right) => {
+                for side in [left, right] {
+                    collect_branches(
+                        side,
+                        depth + 1,
+                        access_coverage,
+                        describe_primitive,
+                        branches,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// A source-like form of the expression, for example `AuthContext.role == "admin" || self.public`
+pub fn describe_expression<PrimExpr: Send + Sync>(
+    expr: &AccessPredicateExpression<PrimExpr>,
+    describe_primitive: &impl Fn(&PrimExpr) -> String,
+) -> String {
+    // Parenthesize operands that are themselves logical operations (other than `!`), so that the
+    // rendered form doesn't depend on operator precedence
+    let operand = |expr: &AccessPredicateExpression<PrimExpr>| {
+        let described = describe_expression(expr, describe_primitive);
+        match expr {
+            AccessPredicateExpression::LogicalOp(
+                AccessLogicalExpression::And(..) | AccessLogicalExpression::Or(..),
+            ) => format!("({described})"),
+            _ => described,
+        }
+    };
+
+    match expr {
+        AccessPredicateExpression::LogicalOp(op) => match op {
+            AccessLogicalExpression::Not(underlying) => match underlying.as_ref() {
+                AccessPredicateExpression::RelationalOp(_) => {
+                    format!("!({})", describe_expression(underlying, describe_primitive))
+                }
+                _ => format!("!{}", operand(underlying)),
+            },
+            AccessLogicalExpression::And(left, right) => {
+                format!("{} && {}", operand(left), operand(right))
+            }
+            AccessLogicalExpression::Or(left, right) => {
+                format!("{} || {}", operand(left), operand(right))
+            }
+        },
+        AccessPredicateExpression::RelationalOp(op) => {
+            let operator = match op {
+                AccessRelationalOp::Eq(..) => "==",
+                AccessRelationalOp::Neq(..) => "!=",
+                AccessRelationalOp::Lt(..) => "<",
+                AccessRelationalOp::Lte(..) => "<=",
+                AccessRelationalOp::Gt(..) => ">",
+                AccessRelationalOp::Gte(..) => ">=",
+                AccessRelationalOp::In(..) => "in",
+            };
+            let (left, right) = op.sides();
+            format!(
+                "{} {operator} {}",
+                describe_primitive(left),
+                describe_primitive(right)
+            )
+        }
+        AccessPredicateExpression::BooleanLiteral(value) => value.to_string(),
+    }
+}
+
+pub fn describe_common_primitive(expr: &CommonAccessPrimitiveExpression) -> String {
+    match expr {
+        CommonAccessPrimitiveExpression::ContextSelection(selection) => {
+            describe_context_selection(selection)
+        }
+        CommonAccessPrimitiveExpression::StringLiteral(value) => format!("{value:?}"),
+        CommonAccessPrimitiveExpression::BooleanLiteral(value) => value.to_string(),
+        CommonAccessPrimitiveExpression::NumberLiteral(value) => value.clone(),
+        CommonAccessPrimitiveExpression::EnumLiteral { enum_name, value } => {
+            format!("{enum_name}.{value}")
+        }
+        CommonAccessPrimitiveExpression::NullLiteral => "null".to_string(),
+    }
+}
+
+fn describe_context_selection(selection: &ContextSelection) -> String {
+    let (head, tail) = &selection.path;
+
+    let mut described = format!("{}.{head}", selection.context_name);
+    for element in tail {
+        match element {
+            ContextSelectionElement::Identifier(name) => {
+                described.push('.');
+                described.push_str(name);
+            }
+            ContextSelectionElement::NormalCall {
+                function_name,
+                args,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|arg| match arg {
+                        PrimitiveValue::Number(NumberLiteral::Int(value)) => value.to_string(),
+                        PrimitiveValue::Number(NumberLiteral::Float(value)) => value.to_string(),
+                        PrimitiveValue::String(value) => format!("{value:?}"),
+                        PrimitiveValue::Boolean(value) => value.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                described.push_str(&format!(".{function_name}({args})"));
+            }
+        }
+    }
+
+    described
+}
+
+#[cfg(test)]
+mod tests {
+    use common::access_coverage::{AccessOutcome, OutcomeCounts};
+
+    use super::*;
+
+    fn context_selection(context_name: &str, head: &str) -> Box<CommonAccessPrimitiveExpression> {
+        Box::new(CommonAccessPrimitiveExpression::ContextSelection(
+            ContextSelection {
+                context_name: context_name.to_string(),
+                path: (head.to_string(), vec![]),
+            },
+        ))
+    }
+
+    fn string_literal(value: &str) -> Box<CommonAccessPrimitiveExpression> {
+        Box::new(CommonAccessPrimitiveExpression::StringLiteral(
+            value.to_string(),
+        ))
+    }
+
+    #[test]
+    fn branches_in_pre_order() {
+        // AuthContext.role == "admin" || !(AuthContext.role == "guest")
+        let expr = AccessPredicateExpression::LogicalOp(AccessLogicalExpression::Or(
+            Box::new(AccessPredicateExpression::RelationalOp(
+                AccessRelationalOp::Eq(
+                    context_selection("AuthContext", "role"),
+                    string_literal("admin"),
+                ),
+            )),
+            Box::new(AccessPredicateExpression::LogicalOp(
+                AccessLogicalExpression::Not(Box::new(AccessPredicateExpression::RelationalOp(
+                    AccessRelationalOp::Eq(
+                        context_selection("AuthContext", "role"),
+                        string_literal("guest"),
+                    ),
+                ))),
+            )),
+        ));
+
+        let access_coverage = AccessCoverage::default();
+        access_coverage.record(&expr, AccessOutcome::Denied);
+        if let AccessPredicateExpression::LogicalOp(AccessLogicalExpression::Or(left, _)) = &expr {
+            access_coverage.record(left.as_ref(), AccessOutcome::Denied);
+        }
+
+        let coverage = rule_coverage(
+            "Todo.query",
+            &expr,
+            &access_coverage,
+            &describe_common_primitive,
+        );
+
+        assert_eq!(
+            coverage
+                .branches
+                .iter()
+                .map(|branch| (
+                    branch.expression.as_str(),
+                    branch.depth,
+                    branch.counts.total()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    r#"AuthContext.role == "admin" || !(AuthContext.role == "guest")"#,
+                    0,
+                    1
+                ),
+                (r#"AuthContext.role == "admin""#, 1, 1),
+                (r#"!(AuthContext.role == "guest")"#, 1, 0),
+                (r#"AuthContext.role == "guest""#, 2, 0),
+            ]
+        );
+        assert_eq!(
+            coverage.branches[0].counts,
+            OutcomeCounts {
+                allowed: 0,
+                denied: 1,
+                residual: 0
+            }
+        );
+        assert!(coverage.is_evaluated());
+        assert_eq!(coverage.evaluated_branches(), 2);
+    }
+}
@@ -17,6 +17,7 @@ use core_model::access::{
 };
 use thiserror::Error;
 
+use common::access_coverage::AccessOutcome;
 use common::context::{ContextExtractionError, RequestContext};
 use common::value::Val;
 
@@ -208,6 +209,19 @@ impl<Res> AccessSolution<Res>
 where
     Res: AccessPredicate + std::fmt::Debug,
 {
+    /// The outcome to report for access coverage
+    fn outcome(&self) -> AccessOutcome {
+        let (AccessSolution::Solved(res) | AccessSolution::Unsolvable(res)) = self;
+
+        if res.is_true() {
+            AccessOutcome::Allowed
+        } else if res.is_false() {
+            AccessOutcome::Denied
+        } else {
+            AccessOutcome::Residual
+        }
+    }
+
     fn not(self) -> Self {
         match self {
             AccessSolution::Solved(res) => AccessSolution::Solved(res.not()),
@@ -285,19 +299,25 @@ where
         input_value: Option<&AccessInput<'a>>,
         expr: &AccessPredicateExpression<PrimExpr>,
     ) -> Result<AccessSolution<Res>, AccessSolverError> {
-        match expr {
+        let solution = match expr {
             AccessPredicateExpression::LogicalOp(op) => {
                 self.solve_logical_op(request_context, input_value, op)
-                    .await
+                    .await?
             }
             AccessPredicateExpression::RelationalOp(op) => {
                 self.solve_relational_op(request_context, input_value, op)
-                    .await
+                    .await?
             }
             AccessPredicateExpression::BooleanLiteral(value) => {
-                Ok(AccessSolution::Solved((*value).into()))
+                AccessSolution::Solved((*value).into())
             }
+        };
+
+        if let Some(access_coverage) = &request_context.system_context.access_coverage {
+            access_coverage.record(expr, solution.outcome());
         }
+
+        Ok(solution)
     }
 
     /// Solve relational operation such as `=`, `!=`, `<`, `>`, `<=`, `>=`.
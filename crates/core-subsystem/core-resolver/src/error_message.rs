@@ -0,0 +1,81 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+/// The key of a user-facing error message in a message catalog along with the values for its
+/// placeholders, so that the message can be rendered in another language (instead of the default
+/// English message).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorMessageKey {
+    /// A stable code such as `NOT_AUTHORIZED`
+    pub code: &'static str,
+    /// The values for the placeholders (`{field}` is replaced by the value of the `field` argument)
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl ErrorMessageKey {
+    pub fn new(code: &'static str) -> Self {
+        Self { code, args: vec![] }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    /// Render the message from a template such as `Field '{field}' is not valid`. Unknown
+    /// placeholders are left as is.
+    pub fn render(&self, template: &str) -> String {
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest.find('}').and_then(|end| {
+                let name = &rest[1..end];
+                self.args
+                    .iter()
+                    .find(|(arg_name, _)| *arg_name == name)
+                    .map(|(_, value)| (value, end))
+            });
+
+            match value {
+                Some((value, end)) => {
+                    message.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        message.push_str(rest);
+
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let key = ErrorMessageKey::new("INVALID_FIELD")
+            .arg("field", "{type}")
+            .arg("type", "Concert");
+
+        assert_eq!(
+            key.render("Le champ '{field}' n'est pas valide pour le type '{type}' ({unknown})"),
+            "Le champ '{type}' n'est pas valide pour le type 'Concert' ({unknown})"
+        );
+    }
+}
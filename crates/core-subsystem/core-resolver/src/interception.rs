@@ -10,7 +10,9 @@
 use async_graphql_parser::types::OperationType;
 use async_recursion::async_recursion;
 
-use core_plugin_shared::interception::{InterceptionTree, InterceptorIndexWithSubsystemIndex};
+use core_plugin_shared::interception::{
+    InterceptionTree, InterceptorErrorPolicy, InterceptorIndexWithSubsystemIndex,
+};
 
 use super::{QueryResponse, validation::field::ValidatedField};
 
@@ -44,6 +46,22 @@ impl<'a> InterceptedOperation<'a> {
         self.operation
     }
 
+    /// Proceed with a different operation through the same interception tree.
+    ///
+    /// Useful for around interceptors that need to select additional fields (for example, the
+    /// primary key) than the ones requested by the client.
+    pub fn with_operation<'b>(&self, operation: &'b ValidatedField) -> InterceptedOperation<'b>
+    where
+        'a: 'b,
+    {
+        InterceptedOperation {
+            interception_tree: self.interception_tree,
+            operation_type: self.operation_type,
+            operation,
+            system_resolver: self.system_resolver,
+        }
+    }
+
     #[async_recursion]
     pub async fn resolve(
         &self,
@@ -110,17 +128,72 @@ impl<'a> InterceptedOperation<'a> {
         request_context: &'a RequestContext<'a>,
     ) -> Result<(), SystemResolutionError> {
         for interceptor in interceptors {
-            self.system_resolver
-                .invoke_interceptor(
-                    interceptor,
-                    self.operation_type,
-                    self.operation,
-                    None,
-                    request_context,
-                )
-                .await?;
+            match interceptor.error_policy {
+                InterceptorErrorPolicy::Abort => {
+                    self.invoke_non_proceeding_interceptor(interceptor, request_context)
+                        .await?;
+                }
+                InterceptorErrorPolicy::Continue => {
+                    self.invoke_continuing_interceptor(interceptor, request_context)
+                        .await?;
+                }
+            }
         }
 
         Ok(())
     }
+
+    /// Invoke an interceptor with `@onError("continue")`: If it fails, undo its database work (by
+    /// rolling back to a savepoint taken before invoking it), log the error, and carry on with the
+    /// request.
+    async fn invoke_continuing_interceptor(
+        &self,
+        interceptor: &InterceptorIndexWithSubsystemIndex,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<(), SystemResolutionError> {
+        let transaction_holder = &request_context.system_context.transaction_holder;
+
+        let savepoint = transaction_holder
+            .lock()
+            .await
+            .savepoint()
+            .await
+            .map_err(|e| SystemResolutionError::Delegate(Box::new(e)))?;
+
+        let result = self
+            .invoke_non_proceeding_interceptor(interceptor, request_context)
+            .await;
+
+        let mut transaction_holder = transaction_holder.lock().await;
+        match result {
+            Ok(()) => transaction_holder.release_savepoint(savepoint).await,
+            Err(error) => {
+                tracing::warn!(
+                    operation = %self.operation.name,
+                    %error,
+                    "Continuing after an interceptor failure (as specified with @onError)"
+                );
+                transaction_holder.rollback_to_savepoint(savepoint).await
+            }
+        }
+        .map_err(|e| SystemResolutionError::Delegate(Box::new(e)))
+    }
+
+    async fn invoke_non_proceeding_interceptor(
+        &self,
+        interceptor: &InterceptorIndexWithSubsystemIndex,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<(), SystemResolutionError> {
+        self.system_resolver
+            .invoke_interceptor(
+                interceptor,
+                self.operation_type,
+                self.operation,
+                None,
+                request_context,
+            )
+            .await?;
+
+        Ok(())
+    }
 }
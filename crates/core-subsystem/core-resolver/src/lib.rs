@@ -7,14 +7,20 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod access_coverage;
 pub mod access_solver;
 pub mod context_extractor;
+pub mod error_message;
 pub mod introspection;
+pub mod operation_limiter;
 pub mod operation_resolver;
 pub mod plugin;
+pub mod replay_guard;
+pub mod response_headers;
 pub mod system_resolver;
 pub mod system_rest_resolver;
 pub mod system_rpc_resolver;
+pub mod trusted_services;
 pub mod validation;
 
 mod field_resolver;
@@ -23,4 +29,4 @@ mod query_response;
 
 pub use field_resolver::FieldResolver;
 pub use interception::InterceptedOperation;
-pub use query_response::{QueryResponse, QueryResponseBody};
+pub use query_response::{JsonValueStream, QueryResponse, QueryResponseBody, QueryResponseStream};
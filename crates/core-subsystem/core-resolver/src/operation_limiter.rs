@@ -0,0 +1,260 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Caps on the number of concurrent executions of expensive operations.
+//!
+//! Limits come from `@concurrencyLimit` in the model and from `EXO_OPERATION_CONCURRENCY` (which
+//! takes precedence, so limits can be tuned per deployment). An execution that finds all slots
+//! taken waits in a bounded queue for up to `EXO_OPERATION_QUEUE_TIMEOUT` seconds. Executions
+//! that find the queue full or time out waiting are rejected with [`OperationLimitError`].
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use common::env_const::{EXO_OPERATION_CONCURRENCY, EXO_OPERATION_QUEUE_TIMEOUT};
+use core_plugin_shared::operation_limit::OperationLimit;
+use exo_env::{EnvError, Environment};
+use thiserror::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+const DEFAULT_QUEUE_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug)]
+pub struct OperationLimiter {
+    limits: HashMap<String, LimitState>,
+    #[cfg_attr(target_family = "wasm", allow(dead_code))]
+    queue_timeout: Duration,
+}
+
+#[derive(Debug)]
+struct LimitState {
+    slots: Arc<Semaphore>,
+    max_queued: usize,
+    queued: AtomicUsize,
+}
+
+/// The error for an operation rejected because too many executions are running or waiting
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("Too many concurrent executions of '{operation}', try again later")]
+pub struct OperationLimitError {
+    pub operation: String,
+}
+
+/// Slots held for the duration of an operation
+pub type OperationPermits = Vec<OwnedSemaphorePermit>;
+
+impl OperationLimiter {
+    pub fn new(declared: &[OperationLimit], env: &dyn Environment) -> Result<Self, EnvError> {
+        let queue_timeout_secs = match env.get(EXO_OPERATION_QUEUE_TIMEOUT) {
+            Some(value) => value.parse::<u64>().map_err(|_| EnvError::InvalidEnum {
+                env_key: EXO_OPERATION_QUEUE_TIMEOUT,
+                env_value: value.clone(),
+                message: "Must be a number of seconds".to_string(),
+            })?,
+            None => DEFAULT_QUEUE_TIMEOUT_SECS,
+        };
+
+        let mut limits: HashMap<String, OperationLimit> = declared
+            .iter()
+            .map(|limit| (limit.operation.clone(), limit.clone()))
+            .collect();
+
+        for spec in env.get_list(EXO_OPERATION_CONCURRENCY, vec![]) {
+            if spec.is_empty() {
+                continue;
+            }
+
+            let limit = parse_limit(&spec).ok_or_else(|| EnvError::InvalidEnum {
+                env_key: EXO_OPERATION_CONCURRENCY,
+                env_value: spec.clone(),
+                message:
+                    "Must be of the form <operation>=<limit>[:<queue size>] with a positive limit"
+                        .to_string(),
+            })?;
+            limits.insert(limit.operation.clone(), limit);
+        }
+
+        Ok(Self {
+            limits: limits
+                .into_iter()
+                .map(|(operation, limit)| {
+                    (
+                        operation,
+                        LimitState {
+                            slots: Arc::new(Semaphore::new(limit.max_concurrent)),
+                            max_queued: limit.max_queued,
+                            queued: AtomicUsize::new(0),
+                        },
+                    )
+                })
+                .collect(),
+            queue_timeout: Duration::from_secs(queue_timeout_secs),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Acquire a slot for each limited operation among the given top-level fields (waiting in
+    /// the queue if necessary). The operation may run as long as the returned permits are held.
+    pub async fn acquire<'a>(
+        &self,
+        operation_names: impl Iterator<Item = &'a str>,
+    ) -> Result<OperationPermits, OperationLimitError> {
+        if self.limits.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Acquire in a consistent order, so that concurrent requests for the same set of
+        // operations can't hold each other's slots
+        let mut limited: Vec<_> = operation_names
+            .filter_map(|name| self.limits.get_key_value(name))
+            .collect();
+        limited.sort_by_key(|(name, _)| *name);
+        limited.dedup_by_key(|(name, _)| *name);
+
+        let mut permits = Vec::with_capacity(limited.len());
+        for (operation, state) in limited {
+            let permit = self
+                .acquire_one(state)
+                .await
+                .ok_or_else(|| OperationLimitError {
+                    operation: operation.clone(),
+                })?;
+            permits.push(permit);
+        }
+
+        Ok(permits)
+    }
+
+    async fn acquire_one(&self, state: &LimitState) -> Option<OwnedSemaphorePermit> {
+        if let Ok(permit) = state.slots.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+
+        let _queue_entry = QueueEntry::reserve(state)?;
+
+        #[cfg(not(target_family = "wasm"))]
+        {
+            tokio::time::timeout(self.queue_timeout, state.slots.clone().acquire_owned())
+                .await
+                .ok()?
+                .ok()
+        }
+
+        #[cfg(target_family = "wasm")]
+        {
+            state.slots.clone().acquire_owned().await.ok()
+        }
+    }
+}
+
+/// A place in the queue of an operation (released when dropped, even if the waiting request is
+/// cancelled)
+struct QueueEntry<'a> {
+    queued: &'a AtomicUsize,
+}
+
+impl<'a> QueueEntry<'a> {
+    fn reserve(state: &'a LimitState) -> Option<Self> {
+        state
+            .queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                (queued < state.max_queued).then_some(queued + 1)
+            })
+            .ok()
+            .map(|_| Self {
+                queued: &state.queued,
+            })
+    }
+}
+
+impl Drop for QueueEntry<'_> {
+    fn drop(&mut self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn parse_limit(spec: &str) -> Option<OperationLimit> {
+    let (operation, limit) = spec.split_once('=')?;
+    let (max_concurrent, max_queued) = match limit.split_once(':') {
+        Some((max_concurrent, max_queued)) => (max_concurrent, max_queued.trim().parse().ok()?),
+        None => (limit, 0),
+    };
+    let max_concurrent = max_concurrent.trim().parse::<usize>().ok()?;
+    let operation = operation.trim();
+
+    (max_concurrent > 0 && !operation.is_empty()).then(|| OperationLimit {
+        operation: operation.to_string(),
+        max_concurrent,
+        max_queued,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exo_env::MapEnvironment;
+
+    #[tokio::test]
+    async fn queues_and_rejects() {
+        let env = MapEnvironment::from([
+            (EXO_OPERATION_CONCURRENCY, "report=1:1, orders=1"),
+            (EXO_OPERATION_QUEUE_TIMEOUT, "0"),
+        ]);
+        let declared = vec![OperationLimit {
+            operation: "report".to_string(),
+            max_concurrent: 5,
+            max_queued: 0,
+        }];
+        let limiter = OperationLimiter::new(&declared, &env).unwrap();
+
+        let held = limiter.acquire(["report", "orders"].into_iter()).await;
+        assert_eq!(held.map(|permits| permits.len()), Ok(2));
+
+        // The queue has room, but the wait times out
+        assert_eq!(
+            limiter.acquire(["report"].into_iter()).await.err(),
+            Some(OperationLimitError {
+                operation: "report".to_string()
+            })
+        );
+        // No queue at all
+        assert!(limiter.acquire(["orders"].into_iter()).await.is_err());
+        assert_eq!(
+            limiter
+                .acquire(["customers"].into_iter())
+                .await
+                .map(|p| p.len()),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_limit("report=2:10"),
+            Some(OperationLimit {
+                operation: "report".to_string(),
+                max_concurrent: 2,
+                max_queued: 10,
+            })
+        );
+        assert!(parse_limit("report=0").is_none());
+        assert!(parse_limit("report").is_none());
+        assert!(parse_limit("=2").is_none());
+    }
+}
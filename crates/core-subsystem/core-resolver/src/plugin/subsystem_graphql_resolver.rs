@@ -8,8 +8,8 @@
 // by the Apache License, Version 2.0.
 
 use crate::{
-    InterceptedOperation, QueryResponse, system_resolver::GraphQLSystemResolver,
-    validation::field::ValidatedField,
+    InterceptedOperation, QueryResponse, error_message::ErrorMessageKey,
+    system_resolver::GraphQLSystemResolver, validation::field::ValidatedField,
 };
 use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
 use async_trait::async_trait;
@@ -123,6 +123,21 @@ pub enum SubsystemResolutionError {
 
     #[error("No interceptor found")]
     NoInterceptorFound, // Almost certainly a programming error (we asked a wrong subsystem)
+
+    #[error("{message}")]
+    Unavailable {
+        message: String,
+        retry_after_secs: u64,
+    }, // A dependency (such as the database) is down, so the subsystem is failing fast
+
+    #[error("{message}")]
+    ConstraintViolation {
+        message: String,
+        extensions: serde_json::Value,
+    }, // A mutation would violate a database constraint (such as uniqueness or a reference to another entity)
+
+    #[error("The request exceeded its budget of {budget} SQL statements")]
+    StatementBudgetExceeded { budget: u64 }, // Likely a statement per row (for example, through nested selections or computed fields)
 }
 
 impl SubsystemResolutionError {
@@ -135,6 +150,44 @@ impl SubsystemResolutionError {
             SubsystemResolutionError::UserDisplayError(message) => Some(message.to_string()),
             SubsystemResolutionError::ContextExtraction(ce) => Some(ce.user_error_message()),
             SubsystemResolutionError::NoInterceptorFound => None,
+            SubsystemResolutionError::Unavailable { message, .. } => Some(message.to_string()),
+            SubsystemResolutionError::ConstraintViolation { message, .. } => {
+                Some(message.to_string())
+            }
+            SubsystemResolutionError::StatementBudgetExceeded { .. } => Some(self.to_string()),
+        }
+    }
+
+    /// The `extensions` to include in the GraphQL error (so clients can handle it without parsing the message)
+    pub fn error_extensions(&self) -> Option<serde_json::Value> {
+        match self {
+            SubsystemResolutionError::ConstraintViolation { extensions, .. } => {
+                Some(extensions.clone())
+            }
+            SubsystemResolutionError::StatementBudgetExceeded { budget } => {
+                Some(serde_json::json!({
+                    "code": "STATEMENT_BUDGET_EXCEEDED",
+                    "budget": budget,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// The key of the message in the message catalog (`None` for messages that aren't in the
+    /// catalog, such as those supplied by the application)
+    pub fn message_key(&self) -> Option<ErrorMessageKey> {
+        match self {
+            SubsystemResolutionError::InvalidField(field_name, container_type) => Some(
+                ErrorMessageKey::new("INVALID_FIELD")
+                    .arg("field", field_name)
+                    .arg("type", container_type),
+            ),
+            SubsystemResolutionError::Authorization => Some(ErrorMessageKey::new("NOT_AUTHORIZED")),
+            SubsystemResolutionError::StatementBudgetExceeded { budget } => {
+                Some(ErrorMessageKey::new("STATEMENT_BUDGET_EXCEEDED").arg("budget", budget))
+            }
+            _ => None,
         }
     }
 }
@@ -7,6 +7,14 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use futures::{Stream, StreamExt};
+use serde::de::Error as _;
 use serde_json::Value as JsonValue;
 
 #[derive(Debug, Clone)]
@@ -19,6 +27,47 @@ pub struct QueryResponse {
 pub enum QueryResponseBody {
     Json(JsonValue),
     Raw(Option<String>),
+    /// Items of a list produced incrementally (for example, by a Deno function returning an async
+    /// iterable). Routers that cannot stream the response should `collect` it.
+    Stream(QueryResponseStream),
+}
+
+pub type JsonValueStream = Pin<Box<dyn Stream<Item = Result<JsonValue, String>> + Send>>;
+
+/// A stream of JSON values that can be consumed only once.
+///
+/// The stream is wrapped so that `QueryResponse` can remain `Clone` (clones share the stream).
+#[derive(Clone)]
+pub struct QueryResponseStream(Arc<Mutex<Option<JsonValueStream>>>);
+
+impl QueryResponseStream {
+    pub fn new(stream: JsonValueStream) -> Self {
+        Self(Arc::new(Mutex::new(Some(stream))))
+    }
+
+    /// Take the underlying stream. Returns `None` if the stream has already been taken.
+    pub fn take(&self) -> Option<JsonValueStream> {
+        self.0.lock().ok()?.take()
+    }
+
+    /// Consume the stream into a JSON array
+    pub async fn collect(&self) -> Result<JsonValue, String> {
+        let mut stream = self
+            .take()
+            .ok_or_else(|| "Response stream has already been consumed".to_string())?;
+
+        let mut items = vec![];
+        while let Some(item) = stream.next().await {
+            items.push(item?);
+        }
+        Ok(JsonValue::Array(items))
+    }
+}
+
+impl Debug for QueryResponseStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("QueryResponseStream")
+    }
 }
 
 impl QueryResponseBody {
@@ -32,6 +81,9 @@ impl QueryResponseBody {
                     Ok(JsonValue::Null)
                 }
             }
+            QueryResponseBody::Stream(_) => Err(serde_json::Error::custom(
+                "A streaming response must be collected before converting it to JSON",
+            )),
         }
     }
 }
@@ -0,0 +1,418 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Protection against replayed mutations (such as a captured webhook delivery sent again).
+//!
+//! Set `EXO_REPLAY_WINDOW` (in seconds) to enable it. Each mutation request must then have a nonce
+//! header (`EXO_REPLAY_NONCE_HEADER`, defaults to `webhook-id`) and a timestamp header
+//! (`EXO_REPLAY_TIMESTAMP_HEADER`, defaults to `webhook-timestamp`) with the Unix time it was
+//! sent. A request is rejected if a request with the same nonce was seen within the window or if
+//! its timestamp is outside the window (so that a nonce can't be reused once forgotten). Timestamps
+//! in the future are accepted only up to a small clock skew, and nonces are remembered until their
+//! timestamp leaves the window. A mutation that fails releases its nonce, so that the sender can
+//! retry the delivery.
+//!
+//! This complements signature verification (see `@webhook`): the signature proves the delivery is
+//! genuine, while the nonce ensures that it is applied only once. Seen nonces are kept in memory
+//! (up to `EXO_REPLAY_CACHE_SIZE` of them), so each server instance tracks its own requests.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use common::{
+    clock::Clock,
+    env_const::{
+        EXO_REPLAY_CACHE_SIZE, EXO_REPLAY_NONCE_HEADER, EXO_REPLAY_TIMESTAMP_HEADER,
+        EXO_REPLAY_WINDOW,
+    },
+    http::RequestHead,
+};
+use exo_env::{EnvError, Environment};
+use thiserror::Error;
+
+const DEFAULT_NONCE_HEADER: &str = "webhook-id";
+const DEFAULT_TIMESTAMP_HEADER: &str = "webhook-timestamp";
+const DEFAULT_CACHE_SIZE: usize = 100_000;
+/// How far in the future (in seconds) a request timestamp may be, to allow for the clocks of the
+/// sender and the server to differ
+const MAX_CLOCK_SKEW_SECS: u64 = 60;
+
+#[derive(Debug, Default)]
+pub struct ReplayGuard {
+    config: Option<ReplayConfig>,
+    seen: Mutex<SeenNonces>,
+}
+
+#[derive(Debug)]
+struct ReplayConfig {
+    window_secs: u64,
+    nonce_header: String,
+    timestamp_header: String,
+    cache_size: usize,
+}
+
+/// Nonces in the order they were seen (so that expired ones can be dropped from the front), along
+/// with the time until which they must be remembered
+#[derive(Debug, Default)]
+struct SeenNonces {
+    order: VecDeque<(String, u64)>,
+    nonces: HashSet<String>,
+}
+
+/// The error for a mutation rejected as a (possible) replay
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    #[error("The request has already been processed")]
+    Replayed,
+
+    #[error("The request timestamp is outside the allowed window")]
+    Expired,
+
+    #[error("The request timestamp must be a Unix time in seconds")]
+    InvalidTimestamp,
+
+    #[error("The request must have a '{0}' header")]
+    MissingHeader(String),
+}
+
+impl ReplayError {
+    /// The error code to report to clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReplayError::Replayed => "REPLAYED_REQUEST",
+            ReplayError::Expired | ReplayError::InvalidTimestamp => "STALE_REQUEST",
+            ReplayError::MissingHeader(_) => "MISSING_REPLAY_HEADER",
+        }
+    }
+}
+
+impl ReplayGuard {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, EnvError> {
+        let Some(window) = env.get(EXO_REPLAY_WINDOW) else {
+            return Ok(Self::default());
+        };
+
+        let window_secs = window
+            .parse::<u64>()
+            .ok()
+            .filter(|window| *window > 0)
+            .ok_or_else(|| EnvError::InvalidEnum {
+                env_key: EXO_REPLAY_WINDOW,
+                env_value: window.clone(),
+                message: "Must be a positive number of seconds".to_string(),
+            })?;
+
+        let cache_size = match env.get(EXO_REPLAY_CACHE_SIZE) {
+            Some(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| EnvError::InvalidEnum {
+                    env_key: EXO_REPLAY_CACHE_SIZE,
+                    env_value: value.clone(),
+                    message: "Must be a positive number".to_string(),
+                })?,
+            None => DEFAULT_CACHE_SIZE,
+        };
+
+        Ok(Self {
+            config: Some(ReplayConfig {
+                window_secs,
+                nonce_header: env
+                    .get_or_else(EXO_REPLAY_NONCE_HEADER, DEFAULT_NONCE_HEADER)
+                    .to_lowercase(),
+                timestamp_header: env
+                    .get_or_else(EXO_REPLAY_TIMESTAMP_HEADER, DEFAULT_TIMESTAMP_HEADER)
+                    .to_lowercase(),
+                cache_size,
+            }),
+            seen: Mutex::new(SeenNonces::default()),
+        })
+    }
+
+    /// Check a mutation request, recording its nonce as seen. Returns the recorded nonce (if
+    /// replay protection is enabled), which the caller must [release](Self::release) if the
+    /// mutation fails.
+    pub fn check(
+        &self,
+        request_head: &(dyn RequestHead + Send + Sync),
+        clock: &Clock,
+    ) -> Result<Option<String>, ReplayError> {
+        let Some(config) = &self.config else {
+            return Ok(None);
+        };
+
+        let nonce = request_head.get_header(&config.nonce_header);
+        self.check_at(
+            nonce.as_deref(),
+            request_head.get_header(&config.timestamp_header).as_deref(),
+            clock.now().timestamp().max(0) as u64,
+        )?;
+
+        Ok(nonce)
+    }
+
+    /// Forget a nonce recorded by [`Self::check`], so that a request with the same nonce (such as
+    /// a provider retrying a delivery that failed) is accepted again
+    pub fn release(&self, nonce: &str) {
+        let mut seen = self.seen.lock().unwrap();
+
+        if seen.nonces.remove(nonce) {
+            seen.order.retain(|(seen_nonce, _)| seen_nonce != nonce);
+        }
+    }
+
+    fn check_at(
+        &self,
+        nonce: Option<&str>,
+        timestamp: Option<&str>,
+        now: u64,
+    ) -> Result<(), ReplayError> {
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+
+        let nonce = nonce.ok_or_else(|| ReplayError::MissingHeader(config.nonce_header.clone()))?;
+        let timestamp =
+            timestamp.ok_or_else(|| ReplayError::MissingHeader(config.timestamp_header.clone()))?;
+
+        let timestamp = timestamp
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| ReplayError::InvalidTimestamp)?;
+        let max_skew = MAX_CLOCK_SKEW_SECS.min(config.window_secs);
+        if now.saturating_sub(timestamp) > config.window_secs
+            || timestamp.saturating_sub(now) > max_skew
+        {
+            return Err(ReplayError::Expired);
+        }
+
+        let mut seen = self.seen.lock().unwrap();
+
+        while let Some((_, remember_until)) = seen.order.front()
+            && now > *remember_until
+        {
+            let (expired, _) = seen.order.pop_front().unwrap();
+            seen.nonces.remove(&expired);
+        }
+
+        if seen.nonces.contains(nonce) {
+            return Err(ReplayError::Replayed);
+        }
+
+        if seen.order.len() >= config.cache_size
+            && let Some((evicted, _)) = seen.order.pop_front()
+        {
+            seen.nonces.remove(&evicted);
+        }
+        // Remember the nonce for as long as its timestamp is accepted (which, for a timestamp
+        // slightly in the future, is longer than the window from now)
+        seen.order
+            .push_back((nonce.to_string(), now.max(timestamp) + config.window_secs));
+        seen.nonces.insert(nonce.to_string());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{DateTime, Duration};
+    use common::http::MemoryRequestHead;
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    fn replay_guard(values: &[(&str, &str)]) -> Result<ReplayGuard, EnvError> {
+        let env = MapEnvironment::from(
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        );
+        ReplayGuard::from_env(&env)
+    }
+
+    fn request_head(headers: &[(&str, &str)]) -> MemoryRequestHead {
+        MemoryRequestHead::new(
+            headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), vec![v.to_string()]))
+                .collect(),
+            HashMap::new(),
+            http::Method::POST,
+            "/graphql".to_string(),
+            serde_json::Value::Null,
+            None,
+        )
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let guard = replay_guard(&[]).unwrap();
+
+        assert_eq!(guard.check_at(Some("n1"), None, 1000), Ok(()));
+        assert_eq!(guard.check_at(None, None, 1000), Ok(()));
+        assert_eq!(guard.check_at(Some("n1"), Some("1"), 1000), Ok(()));
+    }
+
+    #[test]
+    fn rejects_seen_nonces_within_window() {
+        let guard = replay_guard(&[(EXO_REPLAY_WINDOW, "300")]).unwrap();
+
+        assert_eq!(guard.check_at(Some("n1"), Some("1000"), 1000), Ok(()));
+        assert_eq!(
+            guard.check_at(Some("n1"), Some("1100"), 1100),
+            Err(ReplayError::Replayed)
+        );
+        assert_eq!(guard.check_at(Some("n2"), Some("1100"), 1100), Ok(()));
+        // Forgotten once the window has passed
+        assert_eq!(guard.check_at(Some("n1"), Some("1301"), 1301), Ok(()));
+    }
+
+    #[test]
+    fn rejects_missing_headers() {
+        let guard = replay_guard(&[(EXO_REPLAY_WINDOW, "300")]).unwrap();
+
+        assert_eq!(
+            guard.check_at(None, Some("1000"), 1000),
+            Err(ReplayError::MissingHeader("webhook-id".to_string()))
+        );
+        assert_eq!(
+            guard.check_at(Some("n1"), None, 1000),
+            Err(ReplayError::MissingHeader("webhook-timestamp".to_string()))
+        );
+        // A rejected request doesn't record its nonce
+        assert_eq!(guard.check_at(Some("n1"), Some("1000"), 1000), Ok(()));
+
+        let guard = replay_guard(&[
+            (EXO_REPLAY_WINDOW, "300"),
+            (EXO_REPLAY_NONCE_HEADER, "X-GitHub-Delivery"),
+        ])
+        .unwrap();
+        assert_eq!(
+            guard.check_at(None, Some("1000"), 1000),
+            Err(ReplayError::MissingHeader("x-github-delivery".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_stale_timestamps() {
+        let guard = replay_guard(&[(EXO_REPLAY_WINDOW, "300")]).unwrap();
+
+        assert_eq!(guard.check_at(Some("n1"), Some("900"), 1000), Ok(()));
+        assert_eq!(
+            guard.check_at(Some("n2"), Some("600"), 1000),
+            Err(ReplayError::Expired)
+        );
+        assert_eq!(
+            guard.check_at(Some("n3"), Some("1400"), 1000),
+            Err(ReplayError::Expired)
+        );
+        // At most a small clock skew into the future
+        assert_eq!(
+            guard.check_at(Some("n3"), Some("1300"), 1000),
+            Err(ReplayError::Expired)
+        );
+        assert_eq!(
+            guard.check_at(Some("n3"), Some("1061"), 1000),
+            Err(ReplayError::Expired)
+        );
+        assert_eq!(guard.check_at(Some("n3"), Some("1060"), 1000), Ok(()));
+        assert_eq!(
+            guard.check_at(Some("n4"), Some("yesterday"), 1000),
+            Err(ReplayError::InvalidTimestamp)
+        );
+    }
+
+    #[test]
+    fn remembers_future_timestamps_until_they_leave_the_window() {
+        let guard = replay_guard(&[(EXO_REPLAY_WINDOW, "300")]).unwrap();
+
+        assert_eq!(guard.check_at(Some("n1"), Some("1060"), 1000), Ok(()));
+        // The timestamp is still within the window after the window has passed since the request
+        assert_eq!(
+            guard.check_at(Some("n1"), Some("1060"), 1301),
+            Err(ReplayError::Replayed)
+        );
+        assert_eq!(
+            guard.check_at(Some("n1"), Some("1060"), 1360),
+            Err(ReplayError::Replayed)
+        );
+        // Forgotten only once the timestamp is stale
+        assert_eq!(
+            guard.check_at(Some("n1"), Some("1060"), 1361),
+            Err(ReplayError::Expired)
+        );
+    }
+
+    #[test]
+    fn released_nonces_may_be_retried() {
+        let guard = replay_guard(&[(EXO_REPLAY_WINDOW, "300")]).unwrap();
+        let clock = Clock::frozen_at(DateTime::from_timestamp(1_000_000, 0).unwrap());
+        let request = request_head(&[("webhook-id", "n1"), ("webhook-timestamp", "1000000")]);
+
+        assert_eq!(guard.check(&request, &clock), Ok(Some("n1".to_string())));
+        assert_eq!(guard.check(&request, &clock), Err(ReplayError::Replayed));
+
+        // The mutation failed, so the sender may deliver the request again
+        guard.release("n1");
+        assert_eq!(guard.check(&request, &clock), Ok(Some("n1".to_string())));
+        assert_eq!(guard.check(&request, &clock), Err(ReplayError::Replayed));
+
+        // Releasing other nonces has no effect
+        guard.release("n2");
+        assert_eq!(guard.check(&request, &clock), Err(ReplayError::Replayed));
+
+        // Nothing to release when replay protection is disabled
+        let guard = replay_guard(&[]).unwrap();
+        assert_eq!(guard.check(&request, &clock), Ok(None));
+    }
+
+    #[test]
+    fn evicts_oldest_nonces_when_full() {
+        let guard =
+            replay_guard(&[(EXO_REPLAY_WINDOW, "300"), (EXO_REPLAY_CACHE_SIZE, "2")]).unwrap();
+
+        assert_eq!(guard.check_at(Some("n1"), Some("1000"), 1000), Ok(()));
+        assert_eq!(guard.check_at(Some("n2"), Some("1000"), 1000), Ok(()));
+        assert_eq!(guard.check_at(Some("n3"), Some("1000"), 1000), Ok(()));
+        assert_eq!(guard.check_at(Some("n1"), Some("1000"), 1000), Ok(()));
+        assert_eq!(
+            guard.check_at(Some("n3"), Some("1000"), 1000),
+            Err(ReplayError::Replayed)
+        );
+    }
+
+    #[test]
+    fn uses_the_clock() {
+        let guard = replay_guard(&[(EXO_REPLAY_WINDOW, "300")]).unwrap();
+        let clock = Clock::frozen_at(DateTime::from_timestamp(1_000_000, 0).unwrap());
+
+        let request = request_head(&[("webhook-id", "n1"), ("webhook-timestamp", "1000000")]);
+        assert_eq!(guard.check(&request, &clock), Ok(Some("n1".to_string())));
+        assert_eq!(guard.check(&request, &clock), Err(ReplayError::Replayed));
+
+        // The same timestamp becomes stale once the clock moves past the window
+        clock.advance(Duration::seconds(301)).unwrap();
+        let request = request_head(&[("webhook-id", "n2"), ("webhook-timestamp", "1000000")]);
+        assert_eq!(guard.check(&request, &clock), Err(ReplayError::Expired));
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(replay_guard(&[(EXO_REPLAY_WINDOW, "0")]).is_err());
+        assert!(replay_guard(&[(EXO_REPLAY_WINDOW, "5m")]).is_err());
+        assert!(replay_guard(&[(EXO_REPLAY_WINDOW, "300"), (EXO_REPLAY_CACHE_SIZE, "0")]).is_err());
+    }
+}
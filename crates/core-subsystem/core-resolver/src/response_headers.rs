@@ -0,0 +1,127 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Response headers declared for operations in the model (through `@responseHeaders`), added to
+//! the responses of those operations.
+
+use std::collections::HashMap;
+
+use core_plugin_shared::response_headers::OperationResponseHeaders;
+
+use crate::{QueryResponse, validation::field::ValidatedField};
+
+#[derive(Debug, Default)]
+pub struct DeclaredResponseHeaders {
+    headers: HashMap<String, Vec<(String, String)>>,
+}
+
+impl DeclaredResponseHeaders {
+    pub fn new(declared: &[OperationResponseHeaders]) -> Self {
+        Self {
+            headers: declared
+                .iter()
+                .map(|declared| (declared.operation.clone(), declared.headers.clone()))
+                .collect(),
+        }
+    }
+
+    /// Add the headers declared for the operation of each field to its response (keyed by the
+    /// field's output name). A header that the response already has (such as one a module set
+    /// while resolving the operation) keeps its value.
+    pub fn apply(&self, fields: &[ValidatedField], responses: &mut [(String, QueryResponse)]) {
+        if self.headers.is_empty() {
+            return;
+        }
+
+        for (output_name, response) in responses.iter_mut() {
+            let Some(headers) = fields
+                .iter()
+                .find(|field| &field.output_name() == output_name)
+                .and_then(|field| self.headers.get(field.name.as_str()))
+            else {
+                continue;
+            };
+
+            for (name, value) in headers {
+                let already_set = response
+                    .headers
+                    .iter()
+                    .any(|(existing, _)| existing.eq_ignore_ascii_case(name));
+                if !already_set {
+                    response.headers.push((name.clone(), value.clone()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::{Name, indexmap::IndexMap};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::QueryResponseBody;
+
+    fn field(name: &str, alias: Option<&str>) -> ValidatedField {
+        ValidatedField {
+            alias: alias.map(Name::new),
+            name: Name::new(name),
+            arguments: IndexMap::new(),
+            subfields: vec![],
+        }
+    }
+
+    fn response(headers: Vec<(&str, &str)>) -> QueryResponse {
+        QueryResponse {
+            body: QueryResponseBody::Json(Value::Null),
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn adds_declared_headers_by_operation() {
+        let declared = DeclaredResponseHeaders::new(&[OperationResponseHeaders {
+            operation: "salesReport".to_string(),
+            headers: vec![
+                (
+                    "Cache-Control".to_string(),
+                    "public, max-age=60".to_string(),
+                ),
+                ("X-Report".to_string(), "sales".to_string()),
+            ],
+        }]);
+
+        let fields = vec![field("salesReport", Some("report")), field("todos", None)];
+        let mut responses = vec![
+            (
+                "report".to_string(),
+                response(vec![("x-report", "set by the module")]),
+            ),
+            ("todos".to_string(), response(vec![])),
+        ];
+
+        declared.apply(&fields, &mut responses);
+
+        assert_eq!(
+            responses[0].1.headers,
+            vec![
+                ("x-report".to_string(), "set by the module".to_string()),
+                (
+                    "Cache-Control".to_string(),
+                    "public, max-age=60".to_string()
+                ),
+            ]
+        );
+        assert!(responses[1].1.headers.is_empty());
+    }
+}
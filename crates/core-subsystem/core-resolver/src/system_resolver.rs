@@ -11,7 +11,7 @@ use std::sync::Arc;
 
 use async_graphql_parser::{
     Pos,
-    types::{ExecutableDocument, OperationType},
+    types::{DocumentOperations, ExecutableDocument, OperationType},
 };
 #[cfg(not(target_family = "wasm"))]
 use common::env_const::{get_enforce_trusted_documents, is_production};
@@ -22,23 +22,36 @@ use core_plugin_shared::{
     },
 };
 use futures::{StreamExt, future::BoxFuture};
+use serde::Serialize;
 use serde_json::{Map, Value};
 use thiserror::Error;
 use tokio::runtime::Handle;
-use tracing::{error, instrument, warn};
+use tracing::{debug, error, instrument, warn};
 
 use exo_env::Environment;
 
 use common::context::RequestContext;
+use common::env_const::is_read_only;
+use common::http::RequestPayload;
+use common::introspection::{
+    INTROSPECTION_TOKEN_HEADER, introspection_token, matches_introspection_token,
+};
+use common::maintenance::{MaintenanceError, MaintenanceMode};
 use common::operation_payload::OperationsPayload;
 
 use crate::{
     FieldResolver, InterceptedOperation, QueryResponse,
+    error_message::ErrorMessageKey,
     introspection::definition::schema::Schema,
+    operation_limiter::{OperationLimitError, OperationLimiter},
     plugin::{SubsystemResolutionError, subsystem_graphql_resolver::SubsystemGraphQLResolver},
+    replay_guard::{ReplayError, ReplayGuard},
+    response_headers::DeclaredResponseHeaders,
+    trusted_services::TrustedServices,
     validation::{
-        document_validator::DocumentValidator, field::ValidatedField,
-        operation::ValidatedOperation, validation_error::ValidationError,
+        document_limits::DocumentLimits, document_validator::DocumentValidator,
+        field::ValidatedField, operation::ValidatedOperation,
+        placeholder_variables::placeholder_variables, validation_error::ValidationError,
     },
 };
 
@@ -63,6 +76,15 @@ pub struct GraphQLSystemResolver {
     pub schema: Arc<Schema>,
     normal_query_depth_limit: usize,
     introspection_query_depth_limit: usize,
+    document_limits: DocumentLimits,
+    maintenance_mode: Arc<MaintenanceMode>,
+    operation_limiter: Arc<OperationLimiter>,
+    response_headers: Arc<DeclaredResponseHeaders>,
+    replay_guard: ReplayGuard,
+    read_only: bool,
+    /// If set, introspection queries (other than `__typename`) must provide this token
+    introspection_token: Option<String>,
+    trusted_services: TrustedServices,
 }
 
 impl GraphQLSystemResolver {
@@ -76,6 +98,12 @@ impl GraphQLSystemResolver {
         env: Arc<dyn Environment>,
         normal_query_depth_limit: usize,
         introspection_query_depth_limit: usize,
+        document_limits: DocumentLimits,
+        maintenance_mode: Arc<MaintenanceMode>,
+        operation_limiter: Arc<OperationLimiter>,
+        response_headers: Arc<DeclaredResponseHeaders>,
+        trusted_services: TrustedServices,
+        replay_guard: ReplayGuard,
     ) -> Self {
         #[cfg(not(target_family = "wasm"))]
         let trusted_documents =
@@ -89,6 +117,10 @@ impl GraphQLSystemResolver {
                 }
             };
 
+        // The system router has already validated the value (so we don't need to report an error)
+        let read_only = is_read_only(env.as_ref()).unwrap_or(false);
+        let introspection_token = introspection_token(env.as_ref());
+
         Self {
             subsystem_resolvers,
             query_interception_map,
@@ -97,6 +129,14 @@ impl GraphQLSystemResolver {
             schema,
             normal_query_depth_limit,
             introspection_query_depth_limit,
+            document_limits,
+            maintenance_mode,
+            operation_limiter,
+            response_headers,
+            replay_guard,
+            read_only,
+            introspection_token,
+            trusted_services,
         }
     }
 
@@ -142,6 +182,25 @@ impl GraphQLSystemResolver {
         request_context: &RequestContext<'a>,
         trusted_document_enforcement: TrustedDocumentEnforcement,
     ) -> Result<Vec<(String, QueryResponse)>, SystemResolutionError> {
+        // Trusted services (such as internal batch jobs) aren't subject to the protections meant
+        // for clients (but access control still applies)
+        let trusted_service = if request_context.is_internal() {
+            None
+        } else {
+            self.trusted_services.identify(request_context.get_head())
+        };
+
+        let trusted_document_enforcement = match &trusted_service {
+            Some(service) => {
+                debug!(
+                    "Request from trusted service '{}' bypasses trusted documents and operation limits",
+                    service
+                );
+                TrustedDocumentEnforcement::DoNotEnforce
+            }
+            None => trusted_document_enforcement,
+        };
+
         let query = self.trusted_documents.resolve(
             operations_payload.query.as_deref(),
             operations_payload.query_hash.as_deref(),
@@ -188,13 +247,70 @@ impl GraphQLSystemResolver {
             }
         };
 
+        if let Some(introspection_token) = &self.introspection_token
+            && !request_context.is_internal()
+            && operation
+                .fields
+                .iter()
+                .any(|field| field.name == "__schema" || field.name == "__type")
+        {
+            let provided = request_context
+                .get_head()
+                .get_header(INTROSPECTION_TOKEN_HEADER);
+            if !matches_introspection_token(introspection_token, provided.as_deref()) {
+                return Err(ValidationError::IntrospectionTokenRequired.into());
+            }
+        }
+
+        // The nonce recorded by the replay guard (released if the mutation fails)
+        let mut replay_nonce = None;
+
+        // Internal requests are made on behalf of an operation that was already allowed (and
+        // holds any concurrency slots it needs)
+        let _permits = if request_context.is_internal() {
+            vec![]
+        } else {
+            self.maintenance_mode.check(
+                operation.typ == OperationType::Query,
+                operation.fields.iter().map(|field| field.name.as_str()),
+            )?;
+
+            if operation.typ == OperationType::Mutation {
+                replay_nonce = self.replay_guard.check(
+                    request_context.get_head(),
+                    &request_context.system_context.clock,
+                )?;
+            }
+
+            if trusted_service.is_some() {
+                vec![]
+            } else {
+                self.operation_limiter
+                    .acquire(operation.fields.iter().map(|field| field.name.as_str()))
+                    .await?
+            }
+        };
+
         // If multiple operations are present, we need to ensure that we have a transaction
         if operation.fields.len() > 1 {
             request_context.ensure_transaction().await;
         }
-        operation
+        let responses = operation
             .resolve_fields(&operation.fields, self, request_context)
-            .await
+            .await;
+
+        // Let the sender retry a delivery whose mutation failed (its changes were not applied)
+        if responses.is_err()
+            && let Some(nonce) = &replay_nonce
+        {
+            self.replay_guard.release(nonce);
+        }
+
+        let mut responses = responses?;
+        self.response_headers
+            .apply(&operation.fields, &mut responses);
+
+        Ok(responses)
     }
 
     /// Obtain the interception tree associated with the given operation
@@ -210,7 +326,11 @@ impl GraphQLSystemResolver {
         }
     }
 
-    pub(super) async fn resolve_operation(
+    /// Resolve a single operation (without applying interceptors).
+    ///
+    /// Subsystems may use this to delegate to operations of other subsystems as part of the
+    /// same request (and thus the same transaction and access context).
+    pub async fn resolve_operation(
         &self,
         operation_type: OperationType,
         operation: &ValidatedField,
@@ -272,6 +392,69 @@ impl GraphQLSystemResolver {
             .map_err(|e| e.into())
     }
 
+    /// Check that each operation of the trusted documents still validates against the schema (for
+    /// example, that a model change hasn't removed a field it selects), returning the ones that
+    /// don't.
+    ///
+    /// Since the documents don't come with the values of their variables, operations are validated
+    /// with placeholder values (so problems that depend on the values aren't reported).
+    pub fn check_trusted_documents(&self) -> Vec<InvalidTrustedDocument> {
+        let mut invalid_documents = vec![];
+
+        for (hash, document) in self.trusted_documents.documents() {
+            let invalid_document =
+                |operation_name: Option<&str>, message: String| InvalidTrustedDocument {
+                    hash: hash.to_string(),
+                    operation_name: operation_name.map(str::to_string),
+                    message,
+                };
+
+            let parsed = match async_graphql_parser::parse_query(document) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    invalid_documents.push(invalid_document(None, e.to_string()));
+                    continue;
+                }
+            };
+
+            let operations: Vec<_> = match &parsed.operations {
+                DocumentOperations::Single(operation) => vec![(None, operation)],
+                DocumentOperations::Multiple(operations) => operations
+                    .iter()
+                    .map(|(name, operation)| (Some(name.as_str()), operation))
+                    .collect(),
+            };
+
+            for (operation_name, operation) in operations {
+                let variables =
+                    match placeholder_variables(&operation.node.variable_definitions, &self.schema)
+                    {
+                        Ok(variables) => variables,
+                        Err(message) => {
+                            invalid_documents.push(invalid_document(operation_name, message));
+                            continue;
+                        }
+                    };
+
+                match self.validate_operation(
+                    document,
+                    operation_name.map(str::to_string),
+                    Some(variables),
+                ) {
+                    // Mutations are expected to be rejected in read-only mode
+                    Ok(_) | Err(ValidationError::MutationInReadOnlyMode(_)) => {}
+                    Err(e) => {
+                        invalid_documents.push(invalid_document(operation_name, e.to_string()))
+                    }
+                }
+            }
+        }
+
+        invalid_documents
+            .sort_by(|a, b| (&a.hash, &a.operation_name).cmp(&(&b.hash, &b.operation_name)));
+        invalid_documents
+    }
+
     #[instrument(skip_all)]
     fn validate_operation(
         &self,
@@ -281,6 +464,17 @@ impl GraphQLSystemResolver {
     ) -> Result<ValidatedOperation, ValidationError> {
         let document = parse_query(query)?;
 
+        if self.read_only
+            && let Some(pos) = selected_mutation(&document, operation_name.as_deref())
+        {
+            return Err(ValidationError::MutationInReadOnlyMode(pos));
+        }
+
+        // Reject pathological documents (such as thousands of aliases of the same field) before
+        // the more expensive validation
+        self.document_limits
+            .check(&document, operation_name.as_deref())?;
+
         let document_validator = DocumentValidator::new(
             &self.schema,
             operation_name,
@@ -329,11 +523,18 @@ macro_rules! exograph_execute_query {
                     .flat_map(|(_, response)| response.headers.clone())
                     .collect::<Vec<_>>();
 
-                // generate the body
-                let body = result
-                    .into_iter()
-                    .map(|(name, response)| (name, response.body.to_json().unwrap()))
-                    .collect::<serde_json::Map<_, _>>();
+                // generate the body (collecting any streamed responses)
+                let mut body = serde_json::Map::new();
+                for (name, response) in result {
+                    let value = match response.body {
+                        QueryResponseBody::Stream(stream) => stream
+                            .collect()
+                            .await
+                            .map_err($crate::system_resolver::SystemResolutionError::Generic)?,
+                        other => other.to_json().unwrap(),
+                    };
+                    body.insert(name, value);
+                }
 
                 Ok(QueryResponse {
                     body: QueryResponseBody::Json(serde_json::Value::Object(body)),
@@ -345,6 +546,27 @@ macro_rules! exograph_execute_query {
     };
 }
 
+/// A trusted document with an operation that doesn't validate against the schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvalidTrustedDocument {
+    pub hash: String,
+    /// The name of the operation (`None` for an anonymous operation or an unparseable document)
+    pub operation_name: Option<String>,
+    pub message: String,
+}
+
+/// The position of the operation that will be executed if it is a mutation
+fn selected_mutation(document: &ExecutableDocument, operation_name: Option<&str>) -> Option<Pos> {
+    let operation = match &document.operations {
+        DocumentOperations::Single(operation) => Some(operation),
+        DocumentOperations::Multiple(operations) => {
+            operation_name.and_then(|name| operations.get(name))
+        }
+    }?;
+
+    (operation.node.ty == OperationType::Mutation).then_some(operation.pos)
+}
+
 #[instrument(name = "system_resolver::parse_query")]
 fn parse_query(query: &str) -> Result<ExecutableDocument, ValidationError> {
     async_graphql_parser::parse_query(query).map_err(|error| {
@@ -431,6 +653,15 @@ pub enum SystemResolutionError {
 
     #[error("Invalid request {0}")]
     RequestError(#[from] RequestError),
+
+    #[error("{0}")]
+    Maintenance(#[from] MaintenanceError),
+
+    #[error("{0}")]
+    OperationLimit(#[from] OperationLimitError),
+
+    #[error("{0}")]
+    Replay(#[from] ReplayError),
 }
 
 impl SystemResolutionError {
@@ -453,7 +684,69 @@ impl SystemResolutionError {
             SystemResolutionError::Delegate(error) => error
                 .downcast_ref::<SystemResolutionError>()
                 .map(|error| error.user_error_message()),
+            SystemResolutionError::Maintenance(error) => Some(error.to_string()),
+            SystemResolutionError::OperationLimit(error) => Some(error.to_string()),
+            SystemResolutionError::Replay(error) => Some(error.to_string()),
             _ => None,
         }
     }
+
+    /// The key of the message in the message catalog (`None` if the message isn't in the catalog,
+    /// in which case the [`user_error_message`](Self::user_error_message) should be used)
+    pub fn message_key(&self) -> Option<ErrorMessageKey> {
+        match self {
+            SystemResolutionError::Validation(error) => Some(error.message_key()),
+            SystemResolutionError::SubsystemResolutionError(error) => match error {
+                // Hidden behind the generic message (see `user_error_message`)
+                SubsystemResolutionError::NoInterceptorFound => {
+                    Some(ErrorMessageKey::new("INTERNAL_SERVER_ERROR"))
+                }
+                error => error.message_key(),
+            },
+            SystemResolutionError::TrustedDocumentResolution(_) => {
+                Some(ErrorMessageKey::new("OPERATION_NOT_ALLOWED"))
+            }
+            SystemResolutionError::Delegate(error) => {
+                match error.downcast_ref::<SystemResolutionError>() {
+                    Some(error) => error.message_key(),
+                    None => Some(ErrorMessageKey::new("INTERNAL_SERVER_ERROR")),
+                }
+            }
+            SystemResolutionError::Maintenance(_)
+            | SystemResolutionError::OperationLimit(_)
+            | SystemResolutionError::Replay(_) => None,
+            _ => Some(ErrorMessageKey::new("INTERNAL_SERVER_ERROR")),
+        }
+    }
+
+    pub fn error_extensions(&self) -> Option<serde_json::Value> {
+        match self {
+            SystemResolutionError::SubsystemResolutionError(error) => error.error_extensions(),
+            SystemResolutionError::Delegate(error) => error
+                .downcast_ref::<SystemResolutionError>()
+                .and_then(|error| error.error_extensions()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_mutation() {
+        let single = parse_query("mutation { createTodo(data: {title: \"t\"}) { id } }").unwrap();
+        assert!(selected_mutation(&single, None).is_some());
+
+        let query = parse_query("{ todos { id } }").unwrap();
+        assert!(selected_mutation(&query, None).is_none());
+
+        let multiple = parse_query(
+            "query list { todos { id } } mutation create { createTodo(data: {title: \"t\"}) { id } }",
+        )
+        .unwrap();
+        assert!(selected_mutation(&multiple, Some("list")).is_none());
+        assert!(selected_mutation(&multiple, Some("create")).is_some());
+    }
 }
@@ -0,0 +1,161 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Internal services (such as batch jobs) exempt from client-focused protections.
+//!
+//! Requests from a trusted service skip the trusted documents check and the operation
+//! concurrency limits, but are otherwise treated as any other request (in particular, access
+//! control rules still apply). A service is identified by either:
+//! - a verified client certificate whose common name is listed in `EXO_TRUSTED_SERVICE_CERTS`
+//! - an API key in the `X-Exo-Service-Key` header that matches one listed in
+//!   `EXO_TRUSTED_SERVICE_KEYS` (as comma-separated `<service>:<key>` pairs)
+
+use common::{
+    env_const::{EXO_TRUSTED_SERVICE_CERTS, EXO_TRUSTED_SERVICE_KEYS},
+    http::RequestHead,
+    introspection::matches_introspection_token,
+};
+use exo_env::{EnvError, Environment};
+
+pub const SERVICE_KEY_HEADER: &str = "x-exo-service-key";
+
+#[derive(Default)]
+pub struct TrustedServices {
+    certificate_names: Vec<String>,
+    /// Service names along with their keys
+    keys: Vec<(String, String)>,
+}
+
+impl TrustedServices {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, EnvError> {
+        let certificate_names = env
+            .get(EXO_TRUSTED_SERVICE_CERTS)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let keys = match env.get(EXO_TRUSTED_SERVICE_KEYS) {
+            Some(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| match entry.split_once(':') {
+                    Some((service, key)) if !service.is_empty() && !key.is_empty() => {
+                        Ok((service.to_string(), key.to_string()))
+                    }
+                    _ => Err(EnvError::InvalidEnum {
+                        env_key: EXO_TRUSTED_SERVICE_KEYS,
+                        // Don't echo the keys
+                        env_value: "<redacted>".to_string(),
+                        message: "Must be comma-separated '<service>:<key>' pairs".to_string(),
+                    }),
+                })
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            certificate_names,
+            keys,
+        })
+    }
+
+    /// The name of the trusted service making the request (if any)
+    pub fn identify(&self, request_head: &(dyn RequestHead + Send + Sync)) -> Option<String> {
+        if !self.certificate_names.is_empty()
+            && let Some(common_name) = request_head
+                .get_client_certificate()
+                .and_then(|certificate| certificate.common_name.as_ref())
+            && self.certificate_names.contains(common_name)
+        {
+            return Some(common_name.clone());
+        }
+
+        if !self.keys.is_empty()
+            && let Some(provided) = request_head.get_header(SERVICE_KEY_HEADER)
+        {
+            // Check every key (rather than stopping at the first match) to not reveal through
+            // timing which key matched
+            let mut matched = None;
+            for (service, key) in &self.keys {
+                if matches_introspection_token(key, Some(provided.as_str())) && matched.is_none() {
+                    matched = Some(service.clone());
+                }
+            }
+            return matched;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common::http::MemoryRequestHead;
+    use exo_env::MapEnvironment;
+    use serde_json::Value;
+
+    use super::*;
+
+    fn trusted_services(values: &[(&str, &str)]) -> Result<TrustedServices, EnvError> {
+        let env = MapEnvironment::from(
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        );
+        TrustedServices::from_env(&env)
+    }
+
+    fn request_head(service_key: Option<&str>) -> MemoryRequestHead {
+        let mut headers = HashMap::new();
+        if let Some(service_key) = service_key {
+            headers.insert(
+                SERVICE_KEY_HEADER.to_string(),
+                vec![service_key.to_string()],
+            );
+        }
+
+        MemoryRequestHead::new(
+            headers,
+            HashMap::new(),
+            http::Method::POST,
+            "/graphql".to_string(),
+            Value::Null,
+            None,
+        )
+    }
+
+    #[test]
+    fn identifies_by_key() {
+        let services =
+            trusted_services(&[(EXO_TRUSTED_SERVICE_KEYS, "billing:k1, reports:k2")]).unwrap();
+
+        assert_eq!(
+            services.identify(&request_head(Some("k2"))),
+            Some("reports".to_string())
+        );
+        assert_eq!(services.identify(&request_head(Some("k3"))), None);
+        assert_eq!(services.identify(&request_head(None)), None);
+    }
+
+    #[test]
+    fn rejects_malformed_keys() {
+        assert!(trusted_services(&[(EXO_TRUSTED_SERVICE_KEYS, "billing")]).is_err());
+        assert!(trusted_services(&[(EXO_TRUSTED_SERVICE_KEYS, "billing:")]).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Cheap limits on the shape of a document, checked before the full validation.
+//!
+//! A document may alias the same expensive field thousands of times (or select it as many root
+//! fields), which the depth limit doesn't catch. These limits reject such documents early. Fields
+//! are counted after expanding fragment spreads, so a fragment spread multiple times counts
+//! multiple times.
+
+use std::collections::HashMap;
+
+use async_graphql_parser::{
+    Positioned,
+    types::{
+        DocumentOperations, ExecutableDocument, FragmentDefinition, OperationDefinition, Selection,
+        SelectionSet,
+    },
+};
+use async_graphql_value::Name;
+use common::env_const::{EXO_MAX_ALIASES, EXO_MAX_ROOT_FIELDS};
+use exo_env::{EnvError, Environment};
+
+use super::validation_error::ValidationError;
+
+const DEFAULT_MAX_ALIASES: usize = 200;
+const DEFAULT_MAX_ROOT_FIELDS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DocumentLimits {
+    pub max_aliases: usize,
+    pub max_root_fields: usize,
+}
+
+impl Default for DocumentLimits {
+    fn default() -> Self {
+        Self {
+            max_aliases: DEFAULT_MAX_ALIASES,
+            max_root_fields: DEFAULT_MAX_ROOT_FIELDS,
+        }
+    }
+}
+
+impl DocumentLimits {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, EnvError> {
+        Ok(Self {
+            max_aliases: positive_integer(env, EXO_MAX_ALIASES, DEFAULT_MAX_ALIASES)?,
+            max_root_fields: positive_integer(env, EXO_MAX_ROOT_FIELDS, DEFAULT_MAX_ROOT_FIELDS)?,
+        })
+    }
+
+    /// Check the operation that will be executed (other operations in the document are ignored).
+    ///
+    /// If the operation to execute can't be determined, the check passes (the document validator
+    /// will report the problem).
+    pub fn check(
+        &self,
+        document: &ExecutableDocument,
+        operation_name: Option<&str>,
+    ) -> Result<(), ValidationError> {
+        let Some(operation) = selected_operation(document, operation_name) else {
+            return Ok(());
+        };
+
+        let mut counter = FieldCounter {
+            fragments: &document.fragments,
+            fragment_counts: HashMap::new(),
+        };
+
+        let root_count = counter.selection_set_count(&operation.node.selection_set.node);
+
+        if root_count.root_fields > self.max_root_fields {
+            return Err(ValidationError::TooManyRootFields(
+                self.max_root_fields,
+                operation.pos,
+            ));
+        }
+        if root_count.aliases > self.max_aliases {
+            return Err(ValidationError::TooManyAliases(
+                self.max_aliases,
+                operation.pos,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn positive_integer(
+    env: &dyn Environment,
+    env_key: &'static str,
+    default_value: usize,
+) -> Result<usize, EnvError> {
+    match env.get(env_key) {
+        Some(value) => match value.parse::<usize>() {
+            Ok(parsed) if parsed > 0 => Ok(parsed),
+            _ => Err(EnvError::InvalidEnum {
+                env_key,
+                env_value: value,
+                message: "Must be a positive integer".to_string(),
+            }),
+        },
+        None => Ok(default_value),
+    }
+}
+
+fn selected_operation<'a>(
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+) -> Option<&'a Positioned<OperationDefinition>> {
+    match &document.operations {
+        DocumentOperations::Single(operation) => Some(operation),
+        DocumentOperations::Multiple(operations) => match operation_name {
+            Some(name) => operations.get(name),
+            None if operations.len() == 1 => operations.values().next(),
+            None => None,
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct FieldCount {
+    /// Fields directly in the selection set (including through fragments)
+    root_fields: usize,
+    /// Aliased fields at any depth
+    aliases: usize,
+}
+
+impl FieldCount {
+    fn add(self, other: FieldCount) -> FieldCount {
+        FieldCount {
+            root_fields: self.root_fields.saturating_add(other.root_fields),
+            aliases: self.aliases.saturating_add(other.aliases),
+        }
+    }
+}
+
+struct FieldCounter<'a> {
+    fragments: &'a HashMap<Name, Positioned<FragmentDefinition>>,
+    /// Memoized counts for fragments (so that deeply nested spreads are counted in linear time).
+    /// A `None` marks a fragment being counted (to guard against cycles, which the document
+    /// validator will report).
+    fragment_counts: HashMap<&'a str, Option<FieldCount>>,
+}
+
+impl<'a> FieldCounter<'a> {
+    fn selection_set_count(&mut self, selection_set: &'a SelectionSet) -> FieldCount {
+        selection_set
+            .items
+            .iter()
+            .fold(FieldCount::default(), |count, selection| {
+                let selection_count = match &selection.node {
+                    Selection::Field(field) => {
+                        let field = &field.node;
+                        let nested = self.selection_set_count(&field.selection_set.node);
+
+                        FieldCount {
+                            root_fields: 1,
+                            aliases: nested
+                                .aliases
+                                .saturating_add(usize::from(field.alias.is_some())),
+                        }
+                    }
+                    Selection::FragmentSpread(spread) => {
+                        self.fragment_count(spread.node.fragment_name.node.as_str())
+                    }
+                    Selection::InlineFragment(inline_fragment) => {
+                        self.selection_set_count(&inline_fragment.node.selection_set.node)
+                    }
+                };
+
+                count.add(selection_count)
+            })
+    }
+
+    fn fragment_count(&mut self, name: &'a str) -> FieldCount {
+        if let Some(count) = self.fragment_counts.get(name) {
+            return count.unwrap_or_default();
+        }
+
+        let Some(fragment) = self.fragments.get(name) else {
+            return FieldCount::default();
+        };
+
+        self.fragment_counts.insert(name, None);
+        let count = self.selection_set_count(&fragment.node.selection_set.node);
+        self.fragment_counts.insert(name, Some(count));
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exo_env::MapEnvironment;
+
+    const LIMITS: DocumentLimits = DocumentLimits {
+        max_aliases: 3,
+        max_root_fields: 2,
+    };
+
+    fn check(query: &str, operation_name: Option<&str>) -> Result<(), ValidationError> {
+        let document = async_graphql_parser::parse_query(query).unwrap();
+        LIMITS.check(&document, operation_name)
+    }
+
+    #[test]
+    fn within_limits() {
+        assert!(check("{ a: concerts { id } b: concerts { c: id } }", None).is_ok());
+    }
+
+    #[test]
+    fn too_many_root_fields() {
+        assert!(matches!(
+            check("{ concerts { id } venues { id } artists { id } }", None),
+            Err(ValidationError::TooManyRootFields(2, _))
+        ));
+    }
+
+    #[test]
+    fn aliases_through_fragments() {
+        let query = r#"
+            query {
+                concerts { ...F ...F }
+            }
+            fragment F on Concert { a: id b: id }
+        "#;
+        assert!(matches!(
+            check(query, None),
+            Err(ValidationError::TooManyAliases(3, _))
+        ));
+    }
+
+    #[test]
+    fn root_fields_through_fragments() {
+        let query = r#"
+            query Q {
+                ...F
+                venues { id }
+            }
+            fragment F on Query { concerts { id } artists { id } }
+        "#;
+        assert!(matches!(
+            check(query, Some("Q")),
+            Err(ValidationError::TooManyRootFields(2, _))
+        ));
+    }
+
+    #[test]
+    fn only_selected_operation() {
+        let query = r#"
+            query Small { concerts { id } }
+            query Large { a: concerts { id } b: concerts { id } c: concerts { id } d: concerts { id } }
+        "#;
+        assert!(check(query, Some("Small")).is_ok());
+        assert!(check(query, Some("Large")).is_err());
+    }
+
+    #[test]
+    fn fragment_cycle() {
+        let query = r#"
+            query { concerts { ...F } }
+            fragment F on Concert { a: id ...F }
+        "#;
+        assert!(check(query, None).is_ok());
+    }
+
+    #[test]
+    fn from_env() {
+        assert_eq!(
+            DocumentLimits::from_env(&MapEnvironment::from([(EXO_MAX_ALIASES, "10")])).unwrap(),
+            DocumentLimits {
+                max_aliases: 10,
+                max_root_fields: DEFAULT_MAX_ROOT_FIELDS,
+            }
+        );
+        assert!(
+            DocumentLimits::from_env(&MapEnvironment::from([(EXO_MAX_ROOT_FIELDS, "0")])).is_err()
+        );
+    }
+}
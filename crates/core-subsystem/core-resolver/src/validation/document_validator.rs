@@ -117,6 +117,8 @@ mod tests {
     use exo_env::MapEnvironment;
     use exo_sql::DatabaseClientManager;
 
+    use crate::validation::field::ValidatedField;
+
     macro_rules! assert_debug {
         ($src:expr, $fn_name:expr) => {
             insta::with_settings!({prepend_module_to_snapshot => false}, {
@@ -216,6 +218,27 @@ mod tests {
         );
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn subscription_invalid() {
+        let schema = create_test_schema().await;
+
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+
+        let query = r#"
+            subscription {
+                concert(id: 1) {
+                    id
+                }
+            }
+        "#;
+
+        assert_debug!(
+            validator.validate(create_query_document(query)),
+            "subscription_invalid"
+        );
+    }
+
     #[cfg_attr(not(target_family = "wasm"), tokio::test)]
     #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
     async fn unspecified_required_argument_invalid() {
@@ -717,6 +740,71 @@ mod tests {
         );
     }
 
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn inline_fragments() {
+        let schema = create_test_schema().await;
+
+        let query = r#"
+            query {
+                concerts {
+                    id
+                    ... on Concert {
+                        headLine: title
+                        venue {
+                            ... {
+                                name
+                            }
+                        }
+                    }
+                    ... {
+                        id
+                    }
+                }
+            }
+        "#;
+
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+        let operation = validator.validate(create_query_document(query)).unwrap();
+
+        let concerts = &operation.fields[0];
+        let output_names = |fields: &[ValidatedField]| {
+            fields
+                .iter()
+                .map(|field| field.output_name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            output_names(&concerts.subfields),
+            ["id", "headLine", "venue"]
+        );
+        assert_eq!(output_names(&concerts.subfields[2].subfields), ["name"]);
+    }
+
+    #[cfg_attr(not(target_family = "wasm"), tokio::test)]
+    #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
+    async fn inline_fragment_type_mismatch() {
+        let schema = create_test_schema().await;
+
+        let query = r#"
+            query {
+                concerts {
+                    ... on Venue {
+                        name
+                    }
+                }
+            }
+        "#;
+
+        let validator = DocumentValidator::new(&schema, None, None, 10, 10);
+
+        assert!(matches!(
+            validator.validate(create_query_document(query)),
+            Err(ValidationError::InlineFragmentTypeMismatch(type_name, container_type_name, _))
+                if type_name == "Venue" && container_type_name == "Concert"
+        ));
+    }
+
     #[cfg_attr(not(target_family = "wasm"), tokio::test)]
     #[cfg_attr(target_family = "wasm", wasm_bindgen_test::wasm_bindgen_test)]
     async fn query_depth_limit_direct() {
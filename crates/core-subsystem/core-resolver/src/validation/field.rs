@@ -12,7 +12,7 @@ use serde::Serialize;
 
 use common::value::Val;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidatedField {
     pub alias: Option<Name>,
     /// The name of the field.
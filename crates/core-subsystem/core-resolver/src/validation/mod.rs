@@ -16,10 +16,12 @@ use async_graphql_value::Name;
 /// and transform the query into a validated form (in the process, validate the query).
 pub mod operation;
 
+pub mod document_limits;
 pub mod document_validator;
 
 mod arguments_validator;
 mod operation_validator;
+pub(crate) mod placeholder_variables;
 mod selection_set_validator;
 
 pub fn underlying_type(typ: &Type) -> &Name {
@@ -108,7 +108,9 @@ impl<'a> OperationValidator<'a> {
         let operation_type_name = match operation.node.ty {
             OperationType::Query => QUERY_ROOT_TYPENAME,
             OperationType::Mutation => MUTATION_ROOT_TYPENAME,
-            OperationType::Subscription => todo!(),
+            OperationType::Subscription => {
+                return Err(ValidationError::SubscriptionNotSupported(operation.pos));
+            }
         };
 
         let container_type = match self.schema.get_type_definition(operation_type_name) {
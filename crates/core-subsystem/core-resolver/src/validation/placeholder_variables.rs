@@ -0,0 +1,130 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Placeholder values for the variables of an operation, so that a stored operation (such as a
+//! trusted document) can be validated against the schema without the values a client would send.
+//!
+//! Placeholders are never null (even for nullable variables), since clients may always send a
+//! value for a variable that an operation passes to a required argument.
+
+use async_graphql_parser::{
+    Positioned,
+    types::{BaseType, Type, TypeKind, VariableDefinition},
+};
+use serde_json::{Map, Value};
+
+use crate::introspection::definition::schema::Schema;
+
+/// The maximum nesting of input objects in a placeholder (input types may be recursive, such as
+/// the `and` field of predicates)
+const MAX_DEPTH: usize = 8;
+
+/// Placeholders for the variables (or the name and type of a variable whose type isn't in the
+/// schema)
+pub(crate) fn placeholder_variables(
+    variable_definitions: &[Positioned<VariableDefinition>],
+    schema: &Schema,
+) -> Result<Map<String, Value>, String> {
+    variable_definitions
+        .iter()
+        .map(|definition| {
+            let name = definition.node.name.node.to_string();
+            let value = placeholder_value(&definition.node.var_type.node, schema, 0)
+                .map_err(|type_name| format!("Unknown type '{type_name}' of variable '{name}'"))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// A placeholder for the type (or the name of an unknown type in it)
+fn placeholder_value(typ: &Type, schema: &Schema, depth: usize) -> Result<Value, String> {
+    let type_name = match &typ.base {
+        BaseType::List(element_type) => {
+            // Check that the element type exists (even though the placeholder has no elements)
+            placeholder_value(element_type, schema, depth)?;
+            return Ok(Value::Array(vec![]));
+        }
+        BaseType::Named(type_name) => type_name.as_str(),
+    };
+
+    match type_name {
+        "Int" | "Float" => return Ok(Value::from(0)),
+        "Boolean" => return Ok(Value::Bool(false)),
+        "String" | "ID" => return Ok(Value::String(String::new())),
+        _ => {}
+    }
+
+    let type_definition = schema
+        .get_type_definition(type_name)
+        .ok_or_else(|| type_name.to_string())?;
+
+    match &type_definition.kind {
+        TypeKind::Enum(enum_type) => Ok(enum_type
+            .values
+            .first()
+            .map(|value| Value::String(value.node.value.node.to_string()))
+            .unwrap_or(Value::Null)),
+        TypeKind::InputObject(_) if depth >= MAX_DEPTH => Ok(Value::Object(Map::new())),
+        TypeKind::InputObject(input_object_type) => input_object_type
+            .fields
+            .iter()
+            .filter(|field| !field.node.ty.node.nullable && field.node.default_value.is_none())
+            .map(|field| {
+                let value = placeholder_value(&field.node.ty.node, schema, depth + 1)?;
+                Ok((field.node.name.node.to_string(), value))
+            })
+            .collect::<Result<Map<_, _>, _>>()
+            .map(Value::Object),
+        _ => Ok(Value::String(String::new())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_graphql_parser::{parse_query, types::DocumentOperations};
+    use serde_json::json;
+
+    use super::*;
+
+    fn placeholders(query: &str) -> Result<Map<String, Value>, String> {
+        let schema = Schema::new(vec![], vec![], vec![], Arc::new(None));
+        let document = parse_query(query).unwrap();
+        let DocumentOperations::Single(operation) = document.operations else {
+            panic!("Expected a single operation");
+        };
+        placeholder_variables(&operation.node.variable_definitions, &schema)
+    }
+
+    #[test]
+    fn placeholders_by_type() {
+        assert_eq!(
+            placeholders(
+                "query($id: Int!, $ratio: Float, $active: Boolean, $title: String, $tags: [String!]!) { __typename }"
+            )
+            .map(Value::Object),
+            Ok(json!({
+                "id": 0,
+                "ratio": 0,
+                "active": false,
+                "title": "",
+                "tags": []
+            }))
+        );
+    }
+
+    #[test]
+    fn unknown_types() {
+        assert_eq!(
+            placeholders("query($data: ConcertInput!) { __typename }"),
+            Err("Unknown type 'ConcertInput' of variable 'data'".to_string())
+        );
+    }
+}
@@ -62,6 +62,7 @@ impl<'a> SelectionSetValidator<'a> {
     /// Validations performed:
     /// - Each field is defined in the `container_type`
     /// - Each fragment referred is defined
+    /// - Each inline fragment applies to the `container_type`
     /// - Arguments to each field are valid (see [validate_arguments] for more details)
     ///
     /// # Returns
@@ -197,9 +198,28 @@ impl<'a> SelectionSetValidator<'a> {
                         )
                     })
             }
-            Selection::InlineFragment(inline_fragment) => Err(
-                ValidationError::InlineFragmentNotSupported(inline_fragment.pos),
-            ),
+            Selection::InlineFragment(inline_fragment) => {
+                // Since the schema has no interfaces or unions, an inline fragment may only
+                // (redundantly) name the type of the selection, so we inline its fields
+                if let Some(type_condition) = &inline_fragment.node.type_condition {
+                    let type_name = type_condition.node.on.node.as_str();
+                    let container_type_name = self.container_type.name.node.as_str();
+                    if type_name != container_type_name {
+                        return Err(ValidationError::InlineFragmentTypeMismatch(
+                            type_name.to_string(),
+                            container_type_name.to_string(),
+                            inline_fragment.pos,
+                        ));
+                    }
+                }
+
+                self.validate_selection_set(
+                    &inline_fragment.node.selection_set,
+                    fragment_trail,
+                    selection_depth,
+                    selection_depth_check,
+                )
+            }
         }
     }
 
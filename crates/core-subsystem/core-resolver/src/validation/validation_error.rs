@@ -10,6 +10,8 @@
 use async_graphql_parser::Pos;
 use thiserror::Error;
 
+use crate::error_message::ErrorMessageKey;
+
 #[derive(Error, Debug)]
 pub enum ValidationError {
     #[error("{0}")]
@@ -24,8 +26,17 @@ pub enum ValidationError {
     #[error("Fragment definition '{0}' not found")]
     FragmentDefinitionNotFound(String, Pos),
 
-    #[error("Inline fragments are not supported")]
-    InlineFragmentNotSupported(Pos),
+    #[error("Fragment on '{0}' cannot be used in a selection on '{1}'")]
+    InlineFragmentTypeMismatch(String, String, Pos),
+
+    #[error("Subscriptions are not supported")]
+    SubscriptionNotSupported(Pos),
+
+    #[error("Mutations are not allowed since the server is in read-only mode")]
+    MutationInReadOnlyMode(Pos),
+
+    #[error("Introspection queries require a valid introspection token")]
+    IntrospectionTokenRequired,
 
     #[error("No such operation '{0}'")]
     OperationNotFound(String, Pos),
@@ -75,6 +86,12 @@ pub enum ValidationError {
     #[error("Selection set too deep")]
     SelectionSetTooDeep(Pos),
 
+    #[error("Too many aliases (the maximum allowed is {0})")]
+    TooManyAliases(usize, Pos),
+
+    #[error("Too many root fields (the maximum allowed is {0})")]
+    TooManyRootFields(usize, Pos),
+
     #[error("Invalid value for '{value_name}': {range_detail}, {value_detail}")]
     ValueOutOfRange {
         value_name: String,
@@ -93,7 +110,10 @@ impl ValidationError {
             ValidationError::VariableNotFound(_, pos) => vec![*pos],
             ValidationError::MalformedVariable(_, pos, _) => vec![*pos],
             ValidationError::FragmentDefinitionNotFound(_, pos) => vec![*pos],
-            ValidationError::InlineFragmentNotSupported(pos) => vec![*pos],
+            ValidationError::InlineFragmentTypeMismatch(_, _, pos) => vec![*pos],
+            ValidationError::SubscriptionNotSupported(pos) => vec![*pos],
+            ValidationError::MutationInReadOnlyMode(pos) => vec![*pos],
+            ValidationError::IntrospectionTokenRequired => vec![],
             ValidationError::OperationNotFound(_, pos) => vec![*pos],
             ValidationError::InvalidField(_, _, pos) => vec![*pos],
             ValidationError::InvalidFieldType(_, pos) => vec![*pos],
@@ -107,7 +127,107 @@ impl ValidationError {
             ValidationError::InvalidArgumentType { pos, .. } => vec![*pos],
             ValidationError::FragmentCycle(_, pos) => vec![*pos],
             ValidationError::SelectionSetTooDeep(pos) => vec![*pos],
+            ValidationError::TooManyAliases(_, pos) => vec![*pos],
+            ValidationError::TooManyRootFields(_, pos) => vec![*pos],
             ValidationError::ValueOutOfRange { pos, .. } => vec![*pos],
         }
     }
+
+    /// The key of the message (in the message catalog), which renders the same message as
+    /// `to_string()` with the default (English) templates
+    pub fn message_key(&self) -> ErrorMessageKey {
+        match self {
+            ValidationError::QueryParsingFailed(message, _, _) => {
+                ErrorMessageKey::new("QUERY_PARSING_FAILED").arg("message", message)
+            }
+            ValidationError::VariableNotFound(name, _) => {
+                ErrorMessageKey::new("VARIABLE_NOT_FOUND").arg("variable", name)
+            }
+            ValidationError::MalformedVariable(name, _, error) => {
+                ErrorMessageKey::new("MALFORMED_VARIABLE")
+                    .arg("variable", name)
+                    .arg("error", error)
+            }
+            ValidationError::FragmentDefinitionNotFound(name, _) => {
+                ErrorMessageKey::new("FRAGMENT_DEFINITION_NOT_FOUND").arg("fragment", name)
+            }
+            ValidationError::InlineFragmentTypeMismatch(fragment_type, selection_type, _) => {
+                ErrorMessageKey::new("INLINE_FRAGMENT_TYPE_MISMATCH")
+                    .arg("fragmentType", fragment_type)
+                    .arg("selectionType", selection_type)
+            }
+            ValidationError::SubscriptionNotSupported(_) => {
+                ErrorMessageKey::new("SUBSCRIPTION_NOT_SUPPORTED")
+            }
+            ValidationError::MutationInReadOnlyMode(_) => {
+                ErrorMessageKey::new("MUTATION_IN_READ_ONLY_MODE")
+            }
+            ValidationError::IntrospectionTokenRequired => {
+                ErrorMessageKey::new("INTROSPECTION_TOKEN_REQUIRED")
+            }
+            ValidationError::OperationNotFound(name, _) => {
+                ErrorMessageKey::new("OPERATION_NOT_FOUND").arg("operation", name)
+            }
+            ValidationError::InvalidField(name, type_name, _) => {
+                ErrorMessageKey::new("INVALID_FIELD")
+                    .arg("field", name)
+                    .arg("type", type_name)
+            }
+            ValidationError::ScalarWithField(name, _) => {
+                ErrorMessageKey::new("SCALAR_WITH_FIELD").arg("field", name)
+            }
+            ValidationError::InvalidFieldType(type_name, _) => {
+                ErrorMessageKey::new("INVALID_FIELD_TYPE").arg("type", type_name)
+            }
+            ValidationError::RequiredArgumentNotFound(name, _) => {
+                ErrorMessageKey::new("REQUIRED_ARGUMENT_NOT_FOUND").arg("argument", name)
+            }
+            ValidationError::StrayArguments(names, field_name, _) => {
+                ErrorMessageKey::new("STRAY_ARGUMENTS")
+                    .arg("arguments", format!("{names:?}"))
+                    .arg("field", field_name)
+            }
+            ValidationError::InvalidArgumentType {
+                argument_name,
+                expected_type,
+                actual_type,
+                ..
+            } => ErrorMessageKey::new("INVALID_ARGUMENT_TYPE")
+                .arg("argument", argument_name)
+                .arg("expectedType", expected_type)
+                .arg("actualType", actual_type),
+            ValidationError::MergeFields(name, _) => {
+                ErrorMessageKey::new("MERGE_FIELDS").arg("field", name)
+            }
+            ValidationError::NoOperationFound => ErrorMessageKey::new("NO_OPERATION_FOUND"),
+            ValidationError::MultipleOperationsNoOperationName => {
+                ErrorMessageKey::new("MULTIPLE_OPERATIONS_NO_OPERATION_NAME")
+            }
+            ValidationError::MultipleOperationsUnmatchedOperationName(name) => {
+                ErrorMessageKey::new("MULTIPLE_OPERATIONS_UNMATCHED_OPERATION_NAME")
+                    .arg("operation", name)
+            }
+            ValidationError::FragmentCycle(cycle, _) => {
+                ErrorMessageKey::new("FRAGMENT_CYCLE").arg("cycle", cycle)
+            }
+            ValidationError::SelectionSetTooDeep(_) => {
+                ErrorMessageKey::new("SELECTION_SET_TOO_DEEP")
+            }
+            ValidationError::TooManyAliases(max, _) => {
+                ErrorMessageKey::new("TOO_MANY_ALIASES").arg("max", max)
+            }
+            ValidationError::TooManyRootFields(max, _) => {
+                ErrorMessageKey::new("TOO_MANY_ROOT_FIELDS").arg("max", max)
+            }
+            ValidationError::ValueOutOfRange {
+                value_name,
+                range_detail,
+                value_detail,
+                ..
+            } => ErrorMessageKey::new("VALUE_OUT_OF_RANGE")
+                .arg("value", value_name)
+                .arg("rangeDetail", range_detail)
+                .arg("valueDetail", value_detail),
+        }
+    }
 }
@@ -0,0 +1,119 @@
+use std::fmt::Display;
+
+use core_plugin_shared::env_requirement::EnvRequirement;
+use exo_env::Environment;
+
+/// All problems with the env vars that modules require (so that they can be reported together)
+#[derive(Debug)]
+pub struct EnvValidationReport {
+    pub problems: Vec<EnvProblem>,
+}
+
+#[derive(Debug)]
+pub enum EnvProblem {
+    Missing(EnvRequirement),
+    Invalid {
+        requirement: EnvRequirement,
+        value: String,
+    },
+}
+
+/// Check that each required env var is set (unless optional) to a value of the declared type
+pub fn validate_env(
+    requirements: &[EnvRequirement],
+    env: &dyn Environment,
+) -> Result<(), EnvValidationReport> {
+    let problems: Vec<_> = requirements
+        .iter()
+        .filter_map(|requirement| match env.get(&requirement.key) {
+            None if requirement.optional => None,
+            None => Some(EnvProblem::Missing(requirement.clone())),
+            Some(value) if requirement.typ.accepts(&value) => None,
+            Some(value) => Some(EnvProblem::Invalid {
+                requirement: requirement.clone(),
+                value,
+            }),
+        })
+        .collect();
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(EnvValidationReport { problems })
+    }
+}
+
+impl Display for EnvValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for problem in self.problems.iter() {
+            let (requirement, message) = match problem {
+                EnvProblem::Missing(requirement) => (requirement, "not set".to_string()),
+                EnvProblem::Invalid { requirement, value } => (
+                    requirement,
+                    format!("'{value}' is not a valid {}", requirement.typ),
+                ),
+            };
+
+            writeln!(
+                f,
+                "  - {} ({}): {message} (required by {})",
+                requirement.key,
+                requirement.typ,
+                requirement.required_by.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EnvValidationReport {}
+
+#[cfg(test)]
+mod tests {
+    use core_plugin_shared::env_requirement::EnvVarType;
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    fn requirement(key: &str, typ: EnvVarType, optional: bool) -> EnvRequirement {
+        EnvRequirement {
+            key: key.to_string(),
+            typ,
+            optional,
+            required_by: vec!["PaymentModule".to_string()],
+        }
+    }
+
+    #[test]
+    fn reports_all_problems() {
+        let requirements = vec![
+            requirement("STRIPE_API_KEY", EnvVarType::String, false),
+            requirement("STRIPE_MAX_RETRIES", EnvVarType::Int, false),
+            requirement("STRIPE_SANDBOX", EnvVarType::Boolean, true),
+            requirement("STRIPE_WEBHOOK_SECRET", EnvVarType::String, true),
+        ];
+
+        let env =
+            MapEnvironment::from([("STRIPE_MAX_RETRIES", "three"), ("STRIPE_SANDBOX", "yes")]);
+
+        let report = validate_env(&requirements, &env).unwrap_err();
+
+        assert_eq!(
+            report.to_string(),
+            "  - STRIPE_API_KEY (String): not set (required by PaymentModule)\n  - STRIPE_MAX_RETRIES (Int): 'three' is not a valid Int (required by PaymentModule)\n"
+        );
+    }
+
+    #[test]
+    fn valid_env() {
+        let requirements = vec![
+            requirement("STRIPE_API_KEY", EnvVarType::String, false),
+            requirement("STRIPE_MAX_RETRIES", EnvVarType::Int, false),
+        ];
+
+        let env =
+            MapEnvironment::from([("STRIPE_API_KEY", "sk_test"), ("STRIPE_MAX_RETRIES", "3")]);
+
+        assert!(validate_env(&requirements, &env).is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod env_validation;
 pub mod system_loading_error;
 
 pub use system_loading_error::SystemLoadingError;
@@ -2,6 +2,8 @@ use thiserror::Error;
 
 use exo_env::EnvError;
 
+use crate::env_validation::EnvValidationReport;
+
 use core_plugin_interface::interface::{LibraryLoadingError, SubsystemLoadingError};
 use core_plugin_shared::error::ModelSerializationError;
 
@@ -27,4 +29,10 @@ pub enum SystemLoadingError {
 
     #[error("{0}")]
     EnvError(#[from] EnvError),
+
+    #[error("Invalid environment:\n{0}")]
+    InvalidEnvironment(#[from] EnvValidationReport),
+
+    #[error("The {0} lifecycle hook failed: {1}")]
+    LifecycleHook(String, #[source] Box<dyn std::error::Error + Send + Sync>),
 }
@@ -14,7 +14,7 @@ use core_model_builder::{
     error::ModelBuildingError,
     plugin::{BuildMode, CoreSubsystemBuild, GraphQLSubsystemBuild, Interception},
     typechecker::{
-        annotation::{AnnotationSpec, AnnotationTarget},
+        annotation::{AnnotationSpec, AnnotationTarget, MappedAnnotationParamSpec},
         typ::TypecheckedSystem,
     },
 };
@@ -46,15 +46,65 @@ impl SubsystemBuilder for DenoSubsystemBuilder {
     }
 
     fn annotations(&self) -> Vec<(&'static str, AnnotationSpec)> {
-        vec![(
-            "deno",
-            AnnotationSpec {
-                targets: &[AnnotationTarget::Module],
-                no_params: false,
-                single_params: true,
-                mapped_params: None,
-            },
-        )]
+        vec![
+            (
+                "deno",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Module],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "stream",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Method],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "fetchPolicy",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Module],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "timeout",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "retries",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "retryDelay",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "breakerThreshold",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "breakerCooldown",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "executionTimeout",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Module, AnnotationTarget::Method],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+        ]
     }
 
     async fn build(
@@ -123,6 +173,7 @@ impl GraphQLSubsystemBuilder for DenoGraphQLSubsystemBuilder {
                     expr,
                     kind,
                     index: InterceptorIndex(index.to_idx()),
+                    error_policy: interceptor.error_policy,
                 }
             })
             .collect();
@@ -8,24 +8,29 @@
 // by the Apache License, Version 2.0.
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
     path::{Path, PathBuf},
 };
 
 use async_trait::async_trait;
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
 use common::download::{download_dir_if_needed, exo_cache_root};
 use core_model::mapped_arena::{MappedArena, SerializableSlabIndex};
 use core_model_builder::{
-    ast::ast_types::{AstExpr, AstModule},
+    ast::ast_types::{AstAnnotation, AstAnnotationParams, AstExpr, AstFieldType, AstModule},
     builder::{resolved_builder::AnnotationMapHelper, system_builder::BaseModelSystem},
     error::ModelBuildingError,
     plugin::BuildMode,
-    typechecker::{Typed, typ::TypecheckedSystem},
+    typechecker::{
+        Typed,
+        typ::{Module, TypecheckedSystem},
+    },
 };
 
 use deno_core::ModuleType;
 use deno_graphql_model::{
+    fetch_policy::{FetchCircuitBreakerPolicy, FetchPolicy},
     interceptor::Interceptor,
     operation::{DenoMutation, DenoQuery},
     subsystem::DenoSubsystem,
@@ -38,6 +43,9 @@ use crate::module_skeleton_generator;
 
 const DENO_VERSION: &str = "2.5.4";
 
+const DEFAULT_RETRY_DELAY_MS: u64 = 200;
+const DEFAULT_BREAKER_COOLDOWN_SECS: u64 = 30;
+
 const DENO_BUNDLE_WARNING: &[u8] = b"is experimental and subject to changes";
 
 async fn bundle_source(module_fs_path: &Path) -> Result<String, ModelBuildingError> {
@@ -49,6 +57,8 @@ async fn bundle_source(module_fs_path: &Path) -> Result<String, ModelBuildingErr
         .arg("--allow-import")
         .arg("--quiet")
         .arg("--node-modules-dir=auto")
+        // Keep the original file/line information so that stack traces can be mapped back
+        .arg("--sourcemap=inline")
         .arg(module_fs_path.to_string_lossy().as_ref());
 
     if let Some(config_path) = find_deno_config(module_fs_path) {
@@ -221,19 +231,31 @@ impl ScriptProcessor for DenoScriptProcessor {
             )?;
         }
 
-        let root = Url::from_file_path(std::fs::canonicalize(module_fs_path).unwrap()).unwrap();
-
-        let bundled = bundle_source(module_fs_path).await?;
+        bundle_script(module_fs_path).await
+    }
+}
 
-        let script_defn = DenoScriptDefn {
-            modules: HashMap::from([(
-                root.clone(),
-                ResolvedModule::Module(bundled, ModuleType::JavaScript, root.clone(), false),
-            )]),
-        };
+/// Bundle the module at the given path and return its script path along with the serialized
+/// [`DenoScriptDefn`].
+pub async fn bundle_script(module_fs_path: &Path) -> Result<(String, Vec<u8>), ModelBuildingError> {
+    let root = Url::from_file_path(std::fs::canonicalize(module_fs_path).map_err(|e| {
+        ModelBuildingError::Generic(format!(
+            "Failed to locate module {}: {e}",
+            module_fs_path.display()
+        ))
+    })?)
+    .unwrap();
+
+    let bundled = bundle_source(module_fs_path).await?;
+
+    let script_defn = DenoScriptDefn {
+        modules: HashMap::from([(
+            root.clone(),
+            ResolvedModule::Module(bundled, ModuleType::JavaScript, root.clone(), false),
+        )]),
+    };
 
-        Ok((root.to_string(), serde_json::to_vec(&script_defn).unwrap()))
-    }
+    Ok((root.to_string(), serde_json::to_vec(&script_defn).unwrap()))
 }
 
 pub async fn build(
@@ -241,6 +263,11 @@ pub async fn build(
     base_system: &BaseModelSystem,
     build_mode: BuildMode,
 ) -> Result<Option<ModelDenoSystemWithInterceptors>, ModelBuildingError> {
+    let streaming_methods = streaming_methods(typechecked_system)?;
+    let module_fetch_policies = module_fetch_policies(typechecked_system)?;
+    let (module_execution_timeouts, method_execution_timeouts) =
+        execution_timeouts(typechecked_system)?;
+
     let module_selection_closure =
         |module: &AstModule<Typed>| module.annotations.get("deno").map(|_| "deno".to_string());
 
@@ -263,6 +290,40 @@ pub async fn build(
         return Ok(None);
     }
 
+    // The path of the script that the module's methods and interceptors share
+    let script_path = |module: &AstModule<Typed>| {
+        let script = module
+            .methods
+            .iter()
+            .find_map(|method| {
+                underlying_module_system
+                    .methods
+                    .iter()
+                    .find(|(_, m)| m.name == method.name)
+                    .map(|(_, m)| m.script)
+            })
+            .or_else(|| {
+                underlying_module_system
+                    .interceptors
+                    .iter()
+                    .find(|(_, i)| i.module_name == module.name)
+                    .map(|(_, i)| i.script)
+            })?;
+
+        Some(underlying_module_system.scripts[script].path.clone())
+    };
+
+    // Policies apply to all calls from the module's script (which its methods and interceptors share)
+    let fetch_policies = module_fetch_policies
+        .into_iter()
+        .filter_map(|(module, policy)| Some((script_path(module)?, policy)))
+        .collect();
+
+    let script_execution_timeouts = module_execution_timeouts
+        .into_iter()
+        .filter_map(|(module, timeout_ms)| Some((script_path(module)?, timeout_ms)))
+        .collect();
+
     let mut queries = MappedArena::default();
     for query in underlying_module_system.queries.values().into_iter() {
         queries.add(&query.name.clone(), DenoQuery(query));
@@ -282,7 +343,372 @@ pub async fn build(
             methods: underlying_module_system.methods,
             scripts: underlying_module_system.scripts,
             interceptors: underlying_module_system.interceptors,
+            streaming_methods,
+            fetch_policies,
+            script_execution_timeouts,
+            method_execution_timeouts,
         },
         interceptors: module_system.interceptors,
     }))
 }
+
+/// Names of the methods annotated with `@stream`, whose (async) iterable results are streamed to
+/// the client
+fn streaming_methods(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<HashSet<String>, ModelBuildingError> {
+    let mut streaming_methods = HashSet::new();
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let is_deno_module = module.annotations.contains("deno");
+
+        for method in module.methods.iter() {
+            let Some(annotation) = method.annotations.annotations.get("stream") else {
+                continue;
+            };
+
+            let message = if !is_deno_module {
+                Some(format!(
+                    "@stream may only be used on methods in a @deno module (found on '{}')",
+                    method.name
+                ))
+            } else if !is_list(&method.return_type) {
+                Some(format!(
+                    "@stream method '{}' must return an Array or a Set",
+                    method.name
+                ))
+            } else {
+                None
+            };
+
+            match message {
+                Some(message) => errors.push(Diagnostic {
+                    level: Level::Error,
+                    message,
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: annotation.span,
+                        style: SpanStyle::Primary,
+                        label: None,
+                    }],
+                }),
+                None => {
+                    streaming_methods.insert(method.name.clone());
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(streaming_methods)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+/// The timeouts (in milliseconds) declared with `@executionTimeout` on modules (along with the
+/// module) and on methods (by method name)
+#[allow(clippy::type_complexity)]
+fn execution_timeouts(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<(Vec<(&AstModule<Typed>, u64)>, HashMap<String, u64>), ModelBuildingError> {
+    let mut module_timeouts = vec![];
+    let mut method_timeouts = HashMap::new();
+    let mut errors = vec![];
+
+    let timeout = |annotation: &AstAnnotation<Typed>, errors: &mut Vec<Diagnostic>| {
+        if let AstAnnotationParams::Single(AstExpr::NumberLiteral(value, _), _) = &annotation.params
+            && let Ok(timeout_ms) = value.parse::<u64>()
+            && timeout_ms > 0
+        {
+            return Some(timeout_ms);
+        }
+
+        errors.push(annotation_error(
+            annotation,
+            "@executionTimeout expects a positive timeout in milliseconds (for example, @executionTimeout(5000))"
+                .to_string(),
+        ));
+        None
+    };
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let is_deno_module = module.annotations.contains("deno");
+
+        if let Some(annotation) = module.annotations.annotations.get("executionTimeout") {
+            if !is_deno_module {
+                errors.push(annotation_error(
+                    annotation,
+                    format!(
+                        "@executionTimeout may only be used on @deno modules and their methods (found on '{}')",
+                        module.name
+                    ),
+                ));
+            } else if let Some(timeout_ms) = timeout(annotation, &mut errors) {
+                module_timeouts.push((module, timeout_ms));
+            }
+        }
+
+        for method in module.methods.iter() {
+            let Some(annotation) = method.annotations.annotations.get("executionTimeout") else {
+                continue;
+            };
+
+            if !is_deno_module {
+                errors.push(annotation_error(
+                    annotation,
+                    format!(
+                        "@executionTimeout may only be used on @deno modules and their methods (found on '{}')",
+                        method.name
+                    ),
+                ));
+            } else if let Some(timeout_ms) = timeout(annotation, &mut errors) {
+                method_timeouts.insert(method.name.clone(), timeout_ms);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok((module_timeouts, method_timeouts))
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+/// The policies declared with `@fetchPolicy` (along with the module they are declared on)
+fn module_fetch_policies(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Vec<(&AstModule<Typed>, FetchPolicy)>, ModelBuildingError> {
+    let mut policies = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let Some(annotation) = module.annotations.annotations.get("fetchPolicy") else {
+            continue;
+        };
+
+        if !module.annotations.contains("deno") {
+            errors.push(annotation_error(
+                annotation,
+                format!(
+                    "@fetchPolicy may only be used on @deno modules (found on '{}')",
+                    module.name
+                ),
+            ));
+            continue;
+        }
+
+        match fetch_policy(&module.name, annotation) {
+            Ok(policy) => policies.push((module, policy)),
+            Err(message) => errors.push(annotation_error(annotation, message)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(policies)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
+
+fn fetch_policy(
+    module_name: &str,
+    annotation: &AstAnnotation<Typed>,
+) -> Result<FetchPolicy, String> {
+    let AstAnnotationParams::Map(params, _) = &annotation.params else {
+        return Err(
+            "@fetchPolicy expects named parameters (for example, @fetchPolicy(timeout=5000, retries=2))"
+                .to_string(),
+        );
+    };
+
+    let number = |name: &str| -> Result<Option<u64>, String> {
+        match params.get(name) {
+            Some(AstExpr::NumberLiteral(value, _)) => value
+                .parse::<u64>()
+                .map(Some)
+                .map_err(|_| format!("Expected a non-negative integer for '{name}'")),
+            Some(_) => Err(format!("Expected a non-negative integer for '{name}'")),
+            None => Ok(None),
+        }
+    };
+
+    let timeout_ms = number("timeout")?;
+    if timeout_ms == Some(0) {
+        return Err("Expected a positive timeout (in milliseconds)".to_string());
+    }
+
+    let retries = number("retries")?
+        .unwrap_or(0)
+        .try_into()
+        .map_err(|_| "Too many retries".to_string())?;
+
+    let breaker_threshold = number("breakerThreshold")?;
+    let breaker_cooldown = number("breakerCooldown")?;
+    let circuit_breaker = match breaker_threshold {
+        Some(0) => return Err("Expected a positive 'breakerThreshold'".to_string()),
+        Some(threshold) => Some(FetchCircuitBreakerPolicy {
+            threshold: threshold
+                .try_into()
+                .map_err(|_| "'breakerThreshold' is too large".to_string())?,
+            cooldown_secs: breaker_cooldown.unwrap_or(DEFAULT_BREAKER_COOLDOWN_SECS),
+        }),
+        None if breaker_cooldown.is_some() => {
+            return Err("'breakerCooldown' requires 'breakerThreshold'".to_string());
+        }
+        None => None,
+    };
+
+    Ok(FetchPolicy {
+        module_name: module_name.to_string(),
+        timeout_ms,
+        retries,
+        retry_delay_ms: number("retryDelay")?.unwrap_or(DEFAULT_RETRY_DELAY_MS),
+        circuit_breaker,
+    })
+}
+
+fn annotation_error(annotation: &AstAnnotation<Typed>, message: String) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message,
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span: annotation.span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
+
+fn is_list(typ: &AstFieldType<Typed>) -> bool {
+    match typ {
+        AstFieldType::Optional(underlying) => is_list(underlying),
+        AstFieldType::Plain(_, name, _, _, _) => name == "Array" || name == "Set",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codemap::CodeMap;
+
+    use super::*;
+
+    fn fabricate_span() -> codemap::Span {
+        CodeMap::new()
+            .add_file("".to_string(), "".to_string())
+            .span
+            .subspan(0, 0)
+    }
+
+    fn fetch_policy_annotation(params: &[(&str, AstExpr<Typed>)]) -> AstAnnotation<Typed> {
+        AstAnnotation {
+            name: "fetchPolicy".to_string(),
+            params: AstAnnotationParams::Map(
+                params
+                    .iter()
+                    .map(|(name, expr)| (name.to_string(), expr.clone()))
+                    .collect(),
+                HashMap::new(),
+            ),
+            span: fabricate_span(),
+        }
+    }
+
+    fn number(value: &str) -> AstExpr<Typed> {
+        AstExpr::NumberLiteral(value.to_string(), fabricate_span())
+    }
+
+    #[test]
+    fn fetch_policy_defaults() {
+        assert_eq!(
+            fetch_policy("Payments", &fetch_policy_annotation(&[])),
+            Ok(FetchPolicy {
+                module_name: "Payments".to_string(),
+                timeout_ms: None,
+                retries: 0,
+                retry_delay_ms: DEFAULT_RETRY_DELAY_MS,
+                circuit_breaker: None,
+            })
+        );
+
+        assert_eq!(
+            fetch_policy(
+                "Payments",
+                &fetch_policy_annotation(&[("breakerThreshold", number("5"))])
+            )
+            .map(|policy| policy.circuit_breaker),
+            Ok(Some(FetchCircuitBreakerPolicy {
+                threshold: 5,
+                cooldown_secs: DEFAULT_BREAKER_COOLDOWN_SECS,
+            }))
+        );
+    }
+
+    #[test]
+    fn fetch_policy_with_all_params() {
+        let annotation = fetch_policy_annotation(&[
+            ("timeout", number("5000")),
+            ("retries", number("2")),
+            ("retryDelay", number("100")),
+            ("breakerThreshold", number("3")),
+            ("breakerCooldown", number("60")),
+        ]);
+
+        assert_eq!(
+            fetch_policy("Payments", &annotation),
+            Ok(FetchPolicy {
+                module_name: "Payments".to_string(),
+                timeout_ms: Some(5000),
+                retries: 2,
+                retry_delay_ms: 100,
+                circuit_breaker: Some(FetchCircuitBreakerPolicy {
+                    threshold: 3,
+                    cooldown_secs: 60,
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn fetch_policy_errors() {
+        let span = fabricate_span();
+
+        let error = |params: &[(&str, AstExpr<Typed>)]| {
+            fetch_policy("Payments", &fetch_policy_annotation(params)).unwrap_err()
+        };
+
+        assert_eq!(
+            error(&[("timeout", number("0"))]),
+            "Expected a positive timeout (in milliseconds)"
+        );
+        assert_eq!(
+            error(&[("retries", number("-1"))]),
+            "Expected a non-negative integer for 'retries'"
+        );
+        assert_eq!(
+            error(&[("retryDelay", AstExpr::StringLiteral("1s".to_string(), span))]),
+            "Expected a non-negative integer for 'retryDelay'"
+        );
+        assert_eq!(
+            error(&[("breakerThreshold", number("0"))]),
+            "Expected a positive 'breakerThreshold'"
+        );
+        assert_eq!(
+            error(&[("breakerCooldown", number("10"))]),
+            "'breakerCooldown' requires 'breakerThreshold'"
+        );
+
+        let single = AstAnnotation {
+            name: "fetchPolicy".to_string(),
+            params: AstAnnotationParams::Single(number("5000"), span),
+            span,
+        };
+        assert!(
+            fetch_policy("Payments", &single)
+                .unwrap_err()
+                .starts_with("@fetchPolicy expects named parameters")
+        );
+    }
+}
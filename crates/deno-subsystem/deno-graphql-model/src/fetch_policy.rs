@@ -0,0 +1,34 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+/// The resilience policy for outbound HTTP calls made through `Exograph.fetch` by a module
+/// (declared with the `@fetchPolicy` annotation)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchPolicy {
+    pub module_name: String,
+    /// The timeout for each attempt (no timeout, if not specified)
+    pub timeout_ms: Option<u64>,
+    /// The number of times to retry idempotent requests that fail with a network error or a 5xx
+    /// status
+    pub retries: u32,
+    pub retry_delay_ms: u64,
+    pub circuit_breaker: Option<FetchCircuitBreakerPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchCircuitBreakerPolicy {
+    /// The number of consecutive failed calls that open the circuit
+    pub threshold: u32,
+    /// How long the circuit stays open before allowing a trial call
+    pub cooldown_secs: u64,
+}
@@ -8,5 +8,6 @@
 // by the Apache License, Version 2.0.
 
 pub use subsystem_model_util::*;
+pub mod fetch_policy;
 pub mod operation;
 pub mod subsystem;
@@ -7,6 +7,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::{HashMap, HashSet};
+
 use async_graphql_parser::types::{FieldDefinition, TypeDefinition};
 
 use core_model::{
@@ -21,6 +23,7 @@ use serde::{Deserialize, Serialize};
 
 use super::module::Script;
 use crate::{
+    fetch_policy::FetchPolicy,
     interceptor::Interceptor,
     module::ModuleMethod,
     operation::{DenoMutation, DenoQuery},
@@ -42,6 +45,20 @@ pub struct DenoSubsystem {
     pub methods: SerializableSlab<ModuleMethod>,
     pub scripts: SerializableSlab<Script>,
     pub interceptors: SerializableSlab<Interceptor>,
+
+    /// Methods (by name) whose results are streamed to the client as they are produced
+    pub streaming_methods: HashSet<String>,
+
+    /// Policies for outbound HTTP calls, keyed by the path of the module's script
+    pub fetch_policies: HashMap<String, FetchPolicy>,
+
+    /// Timeouts (in milliseconds) for calls into modules annotated with `@executionTimeout`, keyed
+    /// by the path of the module's script
+    pub script_execution_timeouts: HashMap<String, u64>,
+
+    /// Timeouts (in milliseconds) for methods (by name) annotated with `@executionTimeout`, which
+    /// override the timeouts of their modules
+    pub method_execution_timeouts: HashMap<String, u64>,
 }
 
 impl DenoSubsystem {
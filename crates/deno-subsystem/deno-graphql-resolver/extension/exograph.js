@@ -11,6 +11,16 @@ const {
     op_exograph_execute_query_priv,
     op_exograph_add_header,
     op_exograph_version,
+    op_exograph_now,
+    op_exograph_mint_service_token,
+    op_exograph_fetch_policy,
+    op_exograph_fetch_start,
+    op_exograph_fetch_end,
+    op_exograph_session_cookie,
+    op_exograph_saga_begin,
+    op_exograph_saga_step,
+    op_exograph_saga_compensated,
+    op_exograph_saga_end,
     op_operation_name,
     op_operation_query,
     op_operation_proceed,
@@ -20,6 +30,168 @@ export function exograph_version() {
     return op_exograph_version();
 }
 
+// Retrying these methods is safe, since repeating the request has the same effect as making it once
+const IDEMPOTENT_METHODS = ["GET", "HEAD", "OPTIONS", "PUT", "DELETE"];
+
+function fetchAttemptInit(init, timeoutMs) {
+    if (!timeoutMs) {
+        return init;
+    }
+    const timeoutSignal = AbortSignal.timeout(timeoutMs);
+    const signal = init && init.signal ? AbortSignal.any([init.signal, timeoutSignal]) : timeoutSignal;
+    return { ...init, signal };
+}
+
+// `fetch` with the module's `@fetchPolicy` (timeout, retries, and circuit breaker) applied, and a
+// tracing span around the call
+async function fetchWithPolicy(input, init) {
+    const policy = op_exograph_fetch_policy() || {};
+
+    const isRequest = input instanceof Request;
+    const method = ((init && init.method) || (isRequest ? input.method : "GET")).toUpperCase();
+    const url = isRequest ? input.url : String(input);
+    const retries = IDEMPOTENT_METHODS.includes(method) ? (policy.retries || 0) : 0;
+
+    // Throws if the module's circuit is open
+    const callId = op_exograph_fetch_start(method, url);
+
+    let attempts = 0;
+    while (true) {
+        attempts++;
+        const canRetry = attempts <= retries;
+        try {
+            // A request's body can be read only once, so each attempt gets a copy
+            const attemptInput = isRequest && retries > 0 ? input.clone() : input;
+            const response = await fetch(attemptInput, fetchAttemptInit(init, policy.timeoutMs));
+
+            if (response.status >= 500 && canRetry) {
+                await response.body?.cancel();
+            } else {
+                op_exograph_fetch_end(callId, response.status, attempts, null);
+                return response;
+            }
+        } catch (error) {
+            // Don't retry if the caller aborted the request
+            const callerAborted = init && init.signal && init.signal.aborted;
+            if (!canRetry || callerAborted) {
+                op_exograph_fetch_end(callId, 0, attempts, String(error));
+                throw error;
+            }
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, policy.retryDelayMs || 0));
+    }
+}
+
+// The value of the Set-Cookie header for the cookie
+// (https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Set-Cookie)
+function setCookieHeader(cookie) {
+    if (cookie.sameSite === "None" && !cookie.secure) {
+        // Browsers reject such cookies
+        throw new Error(`Cookie '${cookie.name}' with SameSite=None must also be secure`);
+    }
+
+    let cookieString = `${encodeURIComponent(cookie.name)}=${encodeURIComponent(cookie.value)}`;
+
+    if (cookie.expires) {
+        cookieString += `; Expires=${cookie.expires.toUTCString()}`;
+    }
+
+    // A `maxAge` of 0 (which expires the cookie right away) is valid
+    if (cookie.maxAge !== undefined && cookie.maxAge !== null) {
+        cookieString += `; Max-Age=${Math.trunc(cookie.maxAge)}`;
+    }
+
+    if (cookie.domain) {
+        cookieString += `; Domain=${cookie.domain}`;
+    }
+
+    if (cookie.path) {
+        cookieString += `; Path=${cookie.path}`;
+    }
+
+    if (cookie.secure) {
+        cookieString += `; Secure`;
+    }
+
+    if (cookie.httpOnly) {
+        cookieString += `; HttpOnly`;
+    }
+
+    if (cookie.sameSite) {
+        cookieString += `; SameSite=${cookie.sameSite}`;
+    }
+
+    return cookieString;
+}
+
+function setCookie(cookie) {
+    return op_exograph_add_header("Set-Cookie", setCookieHeader(cookie));
+}
+
+// Browsers remove a cookie only if its domain and path match the ones it was set with
+function clearCookie(name, options) {
+    return setCookie({ ...options, name, value: "", expires: new Date(0), maxAge: 0 });
+}
+
+// The cookie that Exograph reads the JWT from (`EXO_JWT_SOURCE_COOKIE`)
+function sessionCookieName() {
+    const name = op_exograph_session_cookie();
+    if (!name) {
+        throw new Error("Session cookies require EXO_JWT_SOURCE_COOKIE to be set");
+    }
+    return name;
+}
+
+// Run `body` as a saga: each step given a compensation is recorded once it completes and, if a
+// later step (or `body` itself) fails, the recorded steps are compensated in the reverse order. The
+// compensations must be exported functions of the module, so that they can also be called (by
+// name) to compensate a saga interrupted by a crash.
+async function runSaga(exograph, name, body) {
+    const id = op_exograph_saga_begin(name);
+    const completed = [];
+
+    const saga = {
+        step: async function (stepName, action, compensate) {
+            if (compensate && !compensate.name) {
+                throw new Error(`The compensation for step '${stepName}' must be an exported function`);
+            }
+
+            const result = await action();
+
+            if (compensate) {
+                const recordedResult = result === undefined ? null : result;
+                completed.push({ compensate, result: recordedResult });
+                op_exograph_saga_step(id, stepName, compensate.name, recordedResult);
+            }
+
+            return result;
+        },
+    };
+
+    let result;
+    try {
+        result = await body(saga);
+    } catch (error) {
+        try {
+            while (completed.length > 0) {
+                const step = completed[completed.length - 1];
+                await step.compensate(step.result, exograph);
+                completed.pop();
+                op_exograph_saga_compensated(id);
+            }
+        } catch (compensationError) {
+            // The remaining steps stay in the journal (to be compensated once the server restarts)
+            console.error(`Failed to compensate saga '${name}': ${compensationError}`);
+        }
+        op_exograph_saga_end(id, false);
+        throw error;
+    }
+
+    op_exograph_saga_end(id, true);
+    return result;
+}
+
 // TODO: There's a lot of duplication between the shim code and the extension.
 // Ideally we'd get rid of the shim code and just expose the code directly from the extension.
 //
@@ -34,6 +206,48 @@ globalThis.ExographExtension = ({
         return op_exograph_add_header(header, value)
     },
 
+    now: function () {
+        return op_exograph_now()
+    },
+
+    mintServiceToken: function (claims, options) {
+        const normalizedOptions = options === undefined ? null : options;
+        return op_exograph_mint_service_token(claims, normalizedOptions)
+    },
+
+    fetch: async function (input, init) {
+        return await fetchWithPolicy(input, init)
+    },
+
+    setCookie: function (cookie) {
+        return setCookie(cookie)
+    },
+
+    clearCookie: function (name, options) {
+        return clearCookie(name, options)
+    },
+
+    // Secure defaults, since the cookie authenticates the requests that carry it
+    setSessionCookie: function (token, options) {
+        return setCookie({
+            path: "/",
+            secure: true,
+            httpOnly: true,
+            sameSite: "Lax",
+            ...options,
+            name: sessionCookieName(),
+            value: token,
+        })
+    },
+
+    clearSessionCookie: function (options) {
+        return clearCookie(sessionCookieName(), { path: "/", ...options })
+    },
+
+    saga: async function (exograph, name, body) {
+        return await runSaga(exograph, name, body)
+    },
+
     executeQueryPriv: async function (query_string, variables, context_override) {
         const normalizedVars = variables === undefined ? null : variables;
         const normalizedContext = context_override === undefined ? null : context_override;
@@ -14,7 +14,7 @@ use core_resolver::{
 };
 use thiserror::Error;
 
-use exo_deno::error::DenoError;
+use exo_deno::error::{DenoError, DenoInternalError};
 
 #[derive(Error, Debug)]
 pub enum DenoExecutionError {
@@ -40,10 +40,25 @@ impl DenoExecutionError {
             DenoExecutionError::Authorization => Some("Not authorized".to_string()),
             DenoExecutionError::ContextExtraction(ce) => Some(ce.user_error_message()),
             DenoExecutionError::Deno(DenoError::Explicit(error)) => Some(error.to_string()),
+            DenoExecutionError::Deno(DenoError::Timeout(_)) => {
+                Some("Operation timed out".to_string())
+            }
             _ => self.explicit_message(),
         }
     }
 
+    /// The runtime error (such as an undefined variable) along with its stack trace. The stack
+    /// frames refer to the original script files since bundled scripts retain their source maps.
+    pub fn runtime_error_message(&self) -> Option<String> {
+        match self {
+            DenoExecutionError::Deno(DenoError::JsError(error))
+            | DenoExecutionError::Deno(DenoError::Internal(DenoInternalError::JsError(error))) => {
+                Some(error.to_string())
+            }
+            _ => None,
+        }
+    }
+
     pub fn explicit_message(&self) -> Option<String> {
         fn root_error<'a>(
             error: &'a (dyn std::error::Error + 'static),
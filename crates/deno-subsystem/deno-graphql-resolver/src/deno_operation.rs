@@ -13,7 +13,7 @@ use common::context::RequestContext;
 use common::value::Val;
 use core_model::types::FieldType;
 use core_resolver::{
-    QueryResponse, QueryResponseBody,
+    QueryResponse, QueryResponseBody, QueryResponseStream,
     access_solver::{AccessSolver, AccessSolverError},
     context_extractor::ContextExtractor,
     exograph_execute_query,
@@ -39,6 +39,7 @@ use crate::{
 
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Instant;
 
 pub struct DenoOperation<'a> {
     pub method: &'a ModuleMethod,
@@ -139,7 +140,62 @@ impl DenoOperation<'_> {
 
         let deserialized: DenoScriptDefn = serde_json::from_slice(&script.script).unwrap();
 
-        let (result, response) = self
+        let call_context = Some(InterceptedOperationInfo::new(
+            self.field.name.to_string(),
+            operation_to_value(self.field),
+            &self.request_context.system_context.clock,
+            self.subsystem_resolver.service_token_issuer.clone(),
+            self.subsystem_resolver.fetch_policy(&script.path),
+            self.subsystem_resolver.session_cookie.clone(),
+            Some(self.subsystem_resolver.saga_context(&script.path)),
+        ));
+
+        let timeout = self
+            .subsystem_resolver
+            .execution_timeout(&script.path, Some(&self.method.name));
+
+        if subsystem.streaming_methods.contains(&self.method.name) {
+            let items = self
+                .subsystem_resolver
+                .executor
+                .execute_streaming(
+                    &script.path,
+                    deserialized,
+                    &self.method.name,
+                    arg_sequence,
+                    call_context,
+                    callback_processor,
+                    timeout,
+                )
+                .await
+                .map_err(DenoExecutionError::Deno)?;
+
+            // Errors are reported to the client after the items produced so far, so map them to
+            // their user-facing messages here
+            let method_name = self.method.name.clone();
+            let items = items.map(move |item| {
+                item.map_err(|error| {
+                    let error = DenoExecutionError::Deno(error);
+                    warn!(
+                        "[DenoOperation] streaming '{}' failed: {}",
+                        method_name, error
+                    );
+                    error
+                        .user_error_message()
+                        .unwrap_or_else(|| "Internal server error".to_string())
+                })
+            });
+
+            // Streaming functions cannot set response headers (the headers are sent before the
+            // function completes)
+            return Ok(QueryResponse {
+                body: QueryResponseBody::Stream(QueryResponseStream::new(Box::pin(items))),
+                headers: vec![],
+            });
+        }
+
+        let start = Instant::now();
+        let result = self
             .subsystem_resolver
             .executor
             .execute_and_get_r(
@@ -147,14 +203,16 @@ impl DenoOperation<'_> {
                 deserialized,
                 &self.method.name,
                 arg_sequence,
-                Some(InterceptedOperationInfo {
-                    name: self.field.name.to_string(),
-                    query: operation_to_value(self.field),
-                }),
+                call_context,
                 callback_processor,
+                timeout,
             )
-            .await
-            .map_err(DenoExecutionError::Deno)?;
+            .await;
+        self.request_context
+            .system_context
+            .stats
+            .record_deno_call(start.elapsed());
+        let (result, response) = result.map_err(DenoExecutionError::Deno)?;
 
         Ok(QueryResponse {
             body: QueryResponseBody::Json(result),
@@ -128,6 +128,16 @@ deno_core::extension!(
         super::exograph_ops::op_exograph_execute_query_priv,
         super::exograph_ops::op_exograph_add_header,
         super::exograph_ops::op_exograph_version,
+        super::exograph_ops::op_exograph_now,
+        super::exograph_ops::op_exograph_mint_service_token,
+        super::exograph_ops::op_exograph_fetch_policy,
+        super::exograph_ops::op_exograph_fetch_start,
+        super::exograph_ops::op_exograph_fetch_end,
+        super::exograph_ops::op_exograph_session_cookie,
+        super::exograph_ops::op_exograph_saga_begin,
+        super::exograph_ops::op_exograph_saga_step,
+        super::exograph_ops::op_exograph_saga_compensated,
+        super::exograph_ops::op_exograph_saga_end,
         super::exograph_ops::op_operation_name,
         super::exograph_ops::op_operation_query,
         super::exograph_ops::op_operation_proceed,
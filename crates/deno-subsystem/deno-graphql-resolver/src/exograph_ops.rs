@@ -13,12 +13,23 @@ use exo_deno::{
     deno_error,
 };
 
+use common::circuit_breaker::CircuitBreaker;
+use common::clock::Clock;
+use common::service_token::{ServiceTokenIssuer, ServiceTokenOptions};
 use core_resolver::system_resolver::SystemResolutionError;
+use deno_graphql_model::fetch_policy::FetchPolicy;
 use serde_json::Value;
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use tokio::sync::mpsc::Sender;
 
 use crate::exo_execution::ExographMethodResponse;
+use crate::saga::{ActiveSagas, SagaContext, SagaRecord, SagaStep};
 
 use super::exo_execution::{RequestFromDenoMessage, ResponseForDenoMessage};
 
@@ -26,6 +37,45 @@ use super::exo_execution::{RequestFromDenoMessage, ResponseForDenoMessage};
 pub struct InterceptedOperationInfo {
     pub name: String,
     pub query: Value,
+    /// The time (milliseconds since the epoch) of the request's clock, if it is frozen
+    pub frozen_time_millis: Option<i64>,
+    /// Mints tokens for the module to call other services with (if configured)
+    pub service_token_issuer: Option<Arc<ServiceTokenIssuer>>,
+    /// The policy for outbound calls made through `Exograph.fetch` (if the module declares one)
+    pub fetch_policy: Option<ModuleFetchPolicy>,
+    /// The cookie that Exograph reads the JWT from (if `EXO_JWT_SOURCE_COOKIE` is set)
+    pub session_cookie: Option<String>,
+    /// For running sagas with `Exograph.saga` (not available to compensations run after a restart)
+    pub saga: Option<SagaContext>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleFetchPolicy {
+    pub policy: FetchPolicy,
+    /// Shared by all calls from the module
+    pub circuit_breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl InterceptedOperationInfo {
+    pub fn new(
+        name: String,
+        query: Value,
+        clock: &Clock,
+        service_token_issuer: Option<Arc<ServiceTokenIssuer>>,
+        fetch_policy: Option<ModuleFetchPolicy>,
+        session_cookie: Option<String>,
+        saga: Option<SagaContext>,
+    ) -> Self {
+        Self {
+            name,
+            query,
+            frozen_time_millis: clock.is_frozen().then(|| clock.now().timestamp_millis()),
+            service_token_issuer,
+            fetch_policy,
+            session_cookie,
+            saga,
+        }
+    }
 }
 
 pub async fn op_exograph_execute_query_helper(
@@ -150,6 +200,286 @@ pub fn op_exograph_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
+/// The current time in milliseconds since the epoch (per the request's clock)
+#[op2(fast)]
+pub fn op_exograph_now(state: &mut OpState) -> f64 {
+    current_time_millis(state) as f64
+}
+
+fn current_time_millis(state: &OpState) -> i64 {
+    let frozen_time_millis = state
+        .try_borrow::<Option<InterceptedOperationInfo>>()
+        .and_then(|info| info.as_ref())
+        .and_then(|info| info.frozen_time_millis);
+
+    match frozen_time_millis {
+        Some(millis) => millis,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or_default(),
+    }
+}
+
+/// Mint a short-lived token carrying the given claims (see [`ServiceTokenIssuer`])
+#[op2]
+#[string]
+pub fn op_exograph_mint_service_token(
+    state: &mut OpState,
+    #[serde] claims: Value,
+    #[serde] options: Option<Value>,
+) -> Result<String, DenoExecutionError> {
+    let Value::Object(claims) = claims else {
+        return Err(DenoExecutionError::Explicit(format!(
+            "mintServiceToken expects claims to be an object, got {}",
+            claims
+        )));
+    };
+
+    let options: ServiceTokenOptions = match options {
+        Some(Value::Null) | None => ServiceTokenOptions::default(),
+        Some(options) => serde_json::from_value(options).map_err(|err| {
+            DenoExecutionError::Explicit(format!("Invalid options for mintServiceToken: {err}"))
+        })?,
+    };
+
+    // Only available to resolvers and interceptors (which get the operation info)
+    let issuer = state
+        .try_borrow::<Option<InterceptedOperationInfo>>()
+        .and_then(|info| info.as_ref())
+        .and_then(|info| info.service_token_issuer.clone())
+        .ok_or_else(|| {
+            DenoExecutionError::Explicit(
+                common::service_token::ServiceTokenError::NotConfigured.to_string(),
+            )
+        })?;
+
+    let now = chrono::DateTime::from_timestamp_millis(current_time_millis(state))
+        .unwrap_or_else(chrono::Utc::now);
+
+    issuer
+        .mint(claims, options, now)
+        .map_err(|err| DenoExecutionError::Explicit(err.to_string()))
+}
+
+/// Outbound calls (made through `Exograph.fetch`) in flight
+#[derive(Default)]
+struct OutboundCalls {
+    next_id: u32,
+    calls: HashMap<u32, OutboundCall>,
+}
+
+struct OutboundCall {
+    span: tracing::Span,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+}
+
+fn current_fetch_policy(state: &OpState) -> Option<&ModuleFetchPolicy> {
+    state
+        .try_borrow::<Option<InterceptedOperationInfo>>()
+        .and_then(|info| info.as_ref())
+        .and_then(|info| info.fetch_policy.as_ref())
+}
+
+/// The fetch policy of the executing module (null, if it doesn't declare one)
+#[op2]
+#[serde]
+pub fn op_exograph_fetch_policy(state: &mut OpState) -> Option<FetchPolicy> {
+    current_fetch_policy(state).map(|fetch_policy| fetch_policy.policy.clone())
+}
+
+/// The name of the cookie to set for `setSessionCookie` (null, if `EXO_JWT_SOURCE_COOKIE` isn't set)
+#[op2]
+#[string]
+pub fn op_exograph_session_cookie(state: &mut OpState) -> Option<String> {
+    state
+        .try_borrow::<Option<InterceptedOperationInfo>>()
+        .and_then(|info| info.as_ref())
+        .and_then(|info| info.session_cookie.clone())
+}
+
+/// Start an outbound call: check the module's circuit breaker and open a span for the call.
+/// Returns the id to pass to `op_exograph_fetch_end` once the call (including any retries) is
+/// complete.
+#[op2]
+pub fn op_exograph_fetch_start(
+    state: &mut OpState,
+    #[string] method: String,
+    #[string] url: String,
+) -> Result<u32, DenoExecutionError> {
+    let fetch_policy = current_fetch_policy(state);
+    let module_name = fetch_policy.map(|fetch_policy| fetch_policy.policy.module_name.clone());
+    let circuit_breaker =
+        fetch_policy.and_then(|fetch_policy| fetch_policy.circuit_breaker.clone());
+
+    if let Some(circuit_breaker) = &circuit_breaker {
+        circuit_breaker
+            .check()
+            .map_err(|err| DenoExecutionError::Explicit(err.to_string()))?;
+    }
+
+    // Leave out the query and fragment (which may carry secrets, and make for unbounded values)
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let span = tracing::info_span!(
+        "outbound_http",
+        http.method = %method,
+        http.url = %url,
+        module = module_name.as_deref(),
+        http.status_code = tracing::field::Empty,
+        attempts = tracing::field::Empty,
+        error = tracing::field::Empty,
+    );
+
+    if !state.has::<OutboundCalls>() {
+        state.put(OutboundCalls::default());
+    }
+    let calls = state.borrow_mut::<OutboundCalls>();
+    let id = calls.next_id;
+    calls.next_id = calls.next_id.wrapping_add(1);
+    calls.calls.insert(
+        id,
+        OutboundCall {
+            span,
+            circuit_breaker,
+        },
+    );
+
+    Ok(id)
+}
+
+/// Complete an outbound call: record the outcome in its span and the module's circuit breaker. A
+/// `status` of 0 means that no response was received (see `error`).
+#[op2]
+pub fn op_exograph_fetch_end(
+    state: &mut OpState,
+    id: u32,
+    status: u32,
+    attempts: u32,
+    #[serde] error: Option<String>,
+) {
+    let Some(call) = state
+        .try_borrow_mut::<OutboundCalls>()
+        .and_then(|calls| calls.calls.remove(&id))
+    else {
+        return;
+    };
+
+    if status != 0 {
+        call.span.record("http.status_code", status);
+    }
+    call.span.record("attempts", attempts);
+    if let Some(error) = &error {
+        call.span.record("error", error.as_str());
+    }
+
+    if let Some(circuit_breaker) = call.circuit_breaker {
+        circuit_breaker.record(error.is_none() && status < 500);
+    }
+}
+
+fn current_saga_context(state: &OpState) -> Result<SagaContext, DenoExecutionError> {
+    state
+        .try_borrow::<Option<InterceptedOperationInfo>>()
+        .and_then(|info| info.as_ref())
+        .and_then(|info| info.saga.clone())
+        .ok_or_else(|| {
+            DenoExecutionError::Explicit("Sagas are not available in this context".to_string())
+        })
+}
+
+/// Update a running saga and persist it (if the server keeps a saga journal)
+fn update_saga(
+    state: &mut OpState,
+    id: &str,
+    update: impl FnOnce(&mut SagaRecord),
+) -> Result<(), DenoExecutionError> {
+    let journal = current_saga_context(state)?.journal;
+
+    let record = state
+        .try_borrow_mut::<ActiveSagas>()
+        .and_then(|sagas| sagas.0.get_mut(id))
+        .ok_or_else(|| DenoExecutionError::Generic(format!("No running saga with id '{id}'")))?;
+    update(record);
+
+    match journal {
+        Some(journal) => journal.save(record).map_err(|err| {
+            DenoExecutionError::Generic(format!("Failed to persist saga '{}': {err}", record.name))
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Start a saga, returning its id
+#[op2]
+#[string]
+pub fn op_exograph_saga_begin(
+    state: &mut OpState,
+    #[string] name: String,
+) -> Result<String, DenoExecutionError> {
+    let context = current_saga_context(state)?;
+    let record = SagaRecord::new(name, context.script_path, current_time_millis(state));
+    let id = record.id.clone();
+
+    if !state.has::<ActiveSagas>() {
+        state.put(ActiveSagas::default());
+    }
+    state
+        .borrow_mut::<ActiveSagas>()
+        .0
+        .insert(id.clone(), record);
+
+    Ok(id)
+}
+
+/// Record a completed step of a saga along with the (exported) function that compensates it
+#[op2]
+pub fn op_exograph_saga_step(
+    state: &mut OpState,
+    #[string] id: String,
+    #[string] name: String,
+    #[string] compensation: String,
+    #[serde] result: Value,
+) -> Result<(), DenoExecutionError> {
+    update_saga(state, &id, |record| {
+        record.steps.push(SagaStep {
+            name,
+            compensation,
+            result,
+        })
+    })
+}
+
+/// Record that the last recorded step of a saga has been compensated
+#[op2]
+pub fn op_exograph_saga_compensated(
+    state: &mut OpState,
+    #[string] id: String,
+) -> Result<(), DenoExecutionError> {
+    update_saga(state, &id, |record| {
+        record.steps.pop();
+    })
+}
+
+/// Finish a saga. Once it completes, nothing needs to be compensated, so its record is removed.
+/// Otherwise, the record keeps any steps that couldn't be compensated (to compensate them once the
+/// server restarts).
+#[op2]
+pub fn op_exograph_saga_end(
+    state: &mut OpState,
+    #[string] id: String,
+    completed: bool,
+) -> Result<(), DenoExecutionError> {
+    if completed {
+        update_saga(state, &id, |record| record.steps.clear())?;
+    }
+
+    state
+        .try_borrow_mut::<ActiveSagas>()
+        .and_then(|sagas| sagas.0.remove(&id));
+
+    Ok(())
+}
+
 #[op2]
 #[string]
 pub fn op_operation_name(state: &mut OpState) -> Result<String, DenoExecutionError> {
@@ -18,40 +18,35 @@
         return ExographExtension.addResponseHeader(header, value)
     },
 
-    setCookie: function (
-        cookie
-    ) {
-        // https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Set-Cookie
-        let cookieString = `${encodeURIComponent(cookie.name)}=${encodeURIComponent(cookie.value)}`;
-
-        if (cookie.expires) {
-            cookieString += `; Expires=${cookie.expires.toUTCString()}`
-        }
+    now: function () {
+        return new Date(ExographExtension.now())
+    },
 
-        if (cookie.maxAge) {
-            cookieString += `; Max-Age=${cookie.maxAge}`
-        }
+    mintServiceToken: function (claims, options) {
+        return ExographExtension.mintServiceToken(claims, options)
+    },
 
-        if (cookie.domain) {
-            cookieString += `; Domain=${cookie.domain}`
-        }
+    fetch: async function (input, init) {
+        return await ExographExtension.fetch(input, init)
+    },
 
-        if (cookie.path) {
-            cookieString += `; Path=${cookie.path}`
-        }
+    setCookie: function (cookie) {
+        return ExographExtension.setCookie(cookie)
+    },
 
-        if (cookie.secure) {
-            cookieString += `; Secure`
-        }
+    clearCookie: function (name, options) {
+        return ExographExtension.clearCookie(name, options)
+    },
 
-        if (cookie.httpOnly) {
-            cookieString += `; HttpOnly`
-        }
+    setSessionCookie: function (token, options) {
+        return ExographExtension.setSessionCookie(token, options)
+    },
 
-        if (cookie.sameSite) {
-            cookieString += `; SameSite=${cookie.sameSite}`
-        }
+    clearSessionCookie: function (options) {
+        return ExographExtension.clearSessionCookie(options)
+    },
 
-        return ExographExtension.addResponseHeader("Set-Cookie", cookieString)
+    saga: async function (name, body) {
+        return await ExographExtension.saga(this, name, body)
     }
 })
@@ -7,6 +7,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::time::Instant;
+
 use common::context::RequestContext;
 use core_resolver::{
     InterceptedOperation, system_resolver::ExographExecuteQueryFn,
@@ -51,21 +53,33 @@ pub async fn execute_interceptor<'a>(
 
     let deserialized: DenoScriptDefn = serde_json::from_slice(&script.script).unwrap();
 
-    subsystem_resolver
+    let start = Instant::now();
+    let result = subsystem_resolver
         .executor
         .execute_and_get_r(
             &script.path,
             deserialized,
             &interceptor.method_name,
             arg_sequence,
-            Some(InterceptedOperationInfo {
-                name: intercepted_operation.operation().name.to_string(),
-                query: operation_to_value(intercepted_operation.operation()),
-            }),
+            Some(InterceptedOperationInfo::new(
+                intercepted_operation.operation().name.to_string(),
+                operation_to_value(intercepted_operation.operation()),
+                &request_context.system_context.clock,
+                subsystem_resolver.service_token_issuer.clone(),
+                subsystem_resolver.fetch_policy(&script.path),
+                subsystem_resolver.session_cookie.clone(),
+                Some(subsystem_resolver.saga_context(&script.path)),
+            )),
             callback_processor,
+            subsystem_resolver.execution_timeout(&script.path, None),
         )
-        .await
-        .map_err(DenoExecutionError::Deno)
+        .await;
+    request_context
+        .system_context
+        .stats
+        .record_deno_call(start.elapsed());
+
+    result.map_err(DenoExecutionError::Deno)
 }
 
 // We can't use Value::to_json, since the coversion from `Val` to `Value` doesn't map carries additional tags
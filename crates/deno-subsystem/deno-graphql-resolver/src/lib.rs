@@ -7,9 +7,12 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub use deno_execution_error::DenoExecutionError;
 pub use exo_execution::{ExoCallbackProcessor, exo_config};
-pub use exograph_ops::InterceptedOperationInfo;
+pub use exograph_ops::{InterceptedOperationInfo, ModuleFetchPolicy};
+pub use module_test_runner::{ExecutedQuery, MockExographClient, MockQuery, ModuleTestRunner};
 pub use resolver::DenoSubsystemResolver;
+pub use saga::SagaJournal;
 
 mod access_solver;
 mod deno_execution_error;
@@ -18,4 +21,6 @@ mod exo_execution;
 mod exograph_ops;
 mod interceptor_execution;
 mod module_access_predicate;
+mod module_test_runner;
 mod resolver;
+mod saga;
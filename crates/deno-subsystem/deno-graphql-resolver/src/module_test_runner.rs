@@ -0,0 +1,233 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Run functions exported by a module outside of a server, so that resolvers and computed fields
+//! can be unit tested. Queries the module issues through `Exograph.executeQuery` are answered by a
+//! [`MockExographClient`] instead of the system resolver.
+
+use std::sync::{Arc, Mutex};
+
+use exo_env::Environment;
+use futures::FutureExt;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use core_resolver::{
+    QueryResponse, QueryResponseBody,
+    system_resolver::{ExographExecuteQueryFn, SystemResolutionError},
+};
+use exo_deno::{
+    Arg,
+    deno_executor_pool::{DenoExecutorPool, DenoScriptDefn},
+};
+
+use crate::{
+    deno_execution_error::DenoExecutionError,
+    exo_execution::{ExoCallbackProcessor, exo_config},
+    resolver::ExoDenoExecutorPool,
+};
+
+/// A canned response for queries issued by the module under test
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockQuery {
+    /// Matches queries whose text contains this string
+    pub query: String,
+    /// If specified, matches only queries executed with these variables
+    #[serde(default)]
+    pub variables: Option<Value>,
+    /// The result returned to the module (the `data` part of a GraphQL response)
+    pub response: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutedQuery {
+    pub query: String,
+    pub variables: Option<Map<String, Value>>,
+}
+
+/// Stands in for the in-process execution client. Responds with the first matching mock and
+/// records every query so that tests may assert on them.
+#[derive(Debug, Default)]
+pub struct MockExographClient {
+    mocks: Vec<MockQuery>,
+    executed: Mutex<Vec<ExecutedQuery>>,
+}
+
+impl MockExographClient {
+    pub fn new(mocks: Vec<MockQuery>) -> Self {
+        Self {
+            mocks,
+            executed: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn executed_queries(&self) -> Vec<ExecutedQuery> {
+        self.executed.lock().unwrap().clone()
+    }
+
+    fn respond(
+        &self,
+        query: String,
+        variables: Option<Map<String, Value>>,
+    ) -> Result<QueryResponse, SystemResolutionError> {
+        let variables_value = variables.clone().map(Value::Object);
+
+        let mock = self.mocks.iter().find(|mock| {
+            query.contains(&mock.query)
+                && mock
+                    .variables
+                    .as_ref()
+                    .is_none_or(|expected| Some(expected) == variables_value.as_ref())
+        });
+
+        let result = match mock {
+            Some(mock) => Ok(QueryResponse {
+                body: QueryResponseBody::Json(mock.response.clone()),
+                headers: vec![],
+            }),
+            None => Err(SystemResolutionError::Generic(format!(
+                "No mock response for query: {query}"
+            ))),
+        };
+
+        self.executed
+            .lock()
+            .unwrap()
+            .push(ExecutedQuery { query, variables });
+
+        result
+    }
+}
+
+/// Loads a bundled module (as produced by the Deno script processor) into a Deno executor
+pub struct ModuleTestRunner {
+    script_path: String,
+    script: Vec<u8>,
+    executor: ExoDenoExecutorPool,
+}
+
+impl ModuleTestRunner {
+    pub fn new(script_path: String, script: Vec<u8>, env: Arc<dyn Environment>) -> Self {
+        Self {
+            script_path,
+            script,
+            executor: DenoExecutorPool::new_from_config(exo_config(env)),
+        }
+    }
+
+    /// Call an exported function. Use `Arg::Shim("Exograph")` (or "ExographPriv") to pass the
+    /// injected Exograph client.
+    pub async fn call(
+        &self,
+        function_name: &str,
+        args: Vec<Arg>,
+        client: &MockExographClient,
+    ) -> Result<Value, DenoExecutionError> {
+        let script: DenoScriptDefn = serde_json::from_slice(&self.script).map_err(|e| {
+            DenoExecutionError::Generic(format!("Invalid script {}: {e}", self.script_path))
+        })?;
+
+        let exograph_execute_query: &ExographExecuteQueryFn =
+            &move |query, variables, _enforce_trusted_documents, _context_override| {
+                let result = client.respond(query, variables);
+                async move { result }.boxed()
+            };
+
+        let callback_processor = ExoCallbackProcessor {
+            exograph_execute_query,
+            exograph_proceed: None,
+        };
+
+        let (result, _) = self
+            .executor
+            .execute_and_get_r(
+                &self.script_path,
+                script,
+                function_name,
+                args,
+                None,
+                callback_processor,
+                None,
+            )
+            .await
+            .map_err(DenoExecutionError::Deno)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use deno_core::{ModuleType, url::Url};
+    use exo_deno::deno_executor_pool::ResolvedModule;
+    use exo_env::MapEnvironment;
+    use serde_json::json;
+    use test_log::test;
+
+    use super::*;
+
+    fn runner() -> ModuleTestRunner {
+        let root = Url::parse("file:///test/test_module_runner.js").unwrap();
+        let script = DenoScriptDefn {
+            modules: HashMap::from([(
+                root.clone(),
+                ResolvedModule::Module(
+                    include_str!("test_js/test_module_runner.js").to_string(),
+                    ModuleType::JavaScript,
+                    root.clone(),
+                    false,
+                ),
+            )]),
+        };
+
+        ModuleTestRunner::new(
+            root.to_string(),
+            serde_json::to_vec(&script).unwrap(),
+            Arc::new(MapEnvironment::default()),
+        )
+    }
+
+    #[test(tokio::test)]
+    async fn answers_queries_with_mocks() {
+        let client = MockExographClient::new(vec![MockQuery {
+            query: "todos".to_string(),
+            variables: None,
+            response: json!({ "todos": [{ "completed": true }, { "completed": false }] }),
+        }]);
+
+        let result = runner()
+            .call(
+                "completedCount",
+                vec![Arg::Shim("Exograph".to_string())],
+                &client,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result, json!(1));
+        assert_eq!(client.executed_queries().len(), 1);
+    }
+
+    #[test(tokio::test)]
+    async fn reports_missing_mocks() {
+        let client = MockExographClient::default();
+
+        let result = runner()
+            .call(
+                "completedCount",
+                vec![Arg::Shim("Exograph".to_string())],
+                &client,
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+}
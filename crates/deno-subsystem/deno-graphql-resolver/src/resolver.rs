@@ -9,8 +9,12 @@
 
 use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
 use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use common::context::RequestContext;
+use common::{
+    circuit_breaker::CircuitBreaker, context::RequestContext, env_const::is_production,
+    service_token::ServiceTokenIssuer,
+};
 use core_model::mapped_arena::SerializableSlabIndex;
 use core_plugin_shared::interception::InterceptorIndex;
 use core_resolver::{
@@ -26,7 +30,8 @@ use super::{
     deno_execution_error::DenoExecutionError,
     deno_operation::DenoOperation,
     exo_execution::{ExographMethodResponse, RequestFromDenoMessage},
-    exograph_ops::InterceptedOperationInfo,
+    exograph_ops::{InterceptedOperationInfo, ModuleFetchPolicy},
+    saga::{SagaContext, SagaJournal},
 };
 
 pub type ExoDenoExecutorPool = DenoExecutorPool<
@@ -39,6 +44,71 @@ pub struct DenoSubsystemResolver {
     pub id: &'static str,
     pub subsystem: DenoSubsystem,
     pub executor: ExoDenoExecutorPool,
+    /// Mints tokens for modules to call other services with (if `EXO_SERVICE_TOKEN_KEY` is set)
+    pub service_token_issuer: Option<Arc<ServiceTokenIssuer>>,
+    /// Circuit breakers for outbound calls of modules whose `@fetchPolicy` declares one (keyed by
+    /// the script path, so all calls to a module share the breaker)
+    pub fetch_circuit_breakers: HashMap<String, Arc<CircuitBreaker>>,
+    /// The timeout for calls into modules without `@executionTimeout` (from
+    /// `EXO_DENO_EXECUTION_TIMEOUT`)
+    pub default_execution_timeout: Option<Duration>,
+    /// The cookie that modules set with `setSessionCookie` (from `EXO_JWT_SOURCE_COOKIE`)
+    pub session_cookie: Option<String>,
+    /// Where to persist running sagas (from `EXO_SAGA_DIR`)
+    pub saga_journal: Option<Arc<SagaJournal>>,
+}
+
+impl DenoSubsystemResolver {
+    pub fn create_fetch_circuit_breakers(
+        subsystem: &DenoSubsystem,
+    ) -> HashMap<String, Arc<CircuitBreaker>> {
+        subsystem
+            .fetch_policies
+            .iter()
+            .filter_map(|(script_path, policy)| {
+                let breaker_policy = policy.circuit_breaker.as_ref()?;
+                Some((
+                    script_path.clone(),
+                    CircuitBreaker::new(
+                        format!("{} (outbound HTTP)", policy.module_name),
+                        breaker_policy.threshold,
+                        Duration::from_secs(breaker_policy.cooldown_secs),
+                    ),
+                ))
+            })
+            .collect()
+    }
+
+    /// The fetch policy of the module with the given script (if it declares one)
+    pub(crate) fn fetch_policy(&self, script_path: &str) -> Option<ModuleFetchPolicy> {
+        let policy = self.subsystem.fetch_policies.get(script_path)?;
+
+        Some(ModuleFetchPolicy {
+            policy: policy.clone(),
+            circuit_breaker: self.fetch_circuit_breakers.get(script_path).cloned(),
+        })
+    }
+
+    pub(crate) fn saga_context(&self, script_path: &str) -> SagaContext {
+        SagaContext {
+            script_path: script_path.to_string(),
+            journal: self.saga_journal.clone(),
+        }
+    }
+
+    /// The timeout for a call into the script: the method's `@executionTimeout` (if the call is to
+    /// a method), else the module's, else the default
+    pub fn execution_timeout(
+        &self,
+        script_path: &str,
+        method_name: Option<&str>,
+    ) -> Option<Duration> {
+        method_name
+            .and_then(|method_name| self.subsystem.method_execution_timeouts.get(method_name))
+            .or_else(|| self.subsystem.script_execution_timeouts.get(script_path))
+            .map(|timeout_ms| Duration::from_millis(*timeout_ms))
+            .or(self.default_execution_timeout)
+    }
 }
 
 #[async_trait]
@@ -101,8 +171,13 @@ impl SubsystemGraphQLResolver for DenoSubsystemResolver {
         };
 
         match deno_operation {
-            Some(Ok(operation)) => Ok(Some(operation.execute().await?)),
-            Some(Err(e)) => Err(e.into()),
+            Some(Ok(operation)) => Ok(Some(
+                operation
+                    .execute()
+                    .await
+                    .map_err(|e| to_subsystem_error(e, request_context))?,
+            )),
+            Some(Err(e)) => Err(to_subsystem_error(e, request_context)),
             None => Ok(None),
         }
     }
@@ -125,7 +200,8 @@ impl SubsystemGraphQLResolver for DenoSubsystemResolver {
             &exograph_execute_query,
             intercepted_operation,
         )
-        .await?;
+        .await
+        .map_err(|e| to_subsystem_error(e, request_context))?;
 
         let body = match result {
             serde_json::Value::String(value) => QueryResponseBody::Raw(Some(value)),
@@ -170,6 +246,24 @@ pub(crate) fn create_deno_operation<'a>(
     })
 }
 
+/// Convert to a subsystem error. Outside production, runtime errors (which would otherwise be
+/// reported as "Internal server error") include the message and the source-mapped stack trace to
+/// help during development.
+fn to_subsystem_error(
+    e: DenoExecutionError,
+    request_context: &RequestContext,
+) -> SubsystemResolutionError {
+    if !is_production(request_context.system_context.env)
+        && e.user_error_message().is_none()
+        && let Some(message) = e.runtime_error_message()
+    {
+        tracing::error!("Error while resolving operation: {message}");
+        return SubsystemResolutionError::UserDisplayError(message);
+    }
+
+    e.into()
+}
+
 impl From<DenoExecutionError> for SubsystemResolutionError {
     fn from(e: DenoExecutionError) -> Self {
         match e {
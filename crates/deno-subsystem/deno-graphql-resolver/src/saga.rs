@@ -0,0 +1,300 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The state of sagas (run with `Exograph.saga`), persisted so that sagas interrupted by a crash
+//! can be compensated once the server restarts.
+//!
+//! Each running saga is a JSON file in the journal directory (`EXO_SAGA_DIR`) listing its completed
+//! steps that have a compensation. The file is removed once the saga completes or is fully
+//! compensated, so any file left on startup belongs to a saga that didn't finish.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, warn};
+
+use common::{clock::Clock, env_const::EXO_SAGA_DIR};
+use core_resolver::system_resolver::{ExographExecuteQueryFn, SystemResolutionError};
+use exo_deno::{Arg, deno_executor_pool::DenoScriptDefn};
+use exo_env::Environment;
+
+use crate::{
+    DenoSubsystemResolver, deno_execution_error::DenoExecutionError,
+    exo_execution::ExoCallbackProcessor, exograph_ops::InterceptedOperationInfo,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SagaRecord {
+    pub id: String,
+    pub name: String,
+    /// The script of the module running the saga (whose exports are the compensations)
+    pub script_path: String,
+    /// The completed steps that have a compensation (in the order they ran)
+    pub steps: Vec<SagaStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SagaStep {
+    pub name: String,
+    /// The exported function that undoes the step
+    pub compensation: String,
+    /// The result of the step (passed to the compensation)
+    pub result: Value,
+}
+
+impl SagaRecord {
+    pub fn new(name: String, script_path: String, now_millis: i64) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        Self {
+            id: format!(
+                "{now_millis}-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ),
+            name,
+            script_path,
+            steps: vec![],
+        }
+    }
+}
+
+/// The directory holding the records of running sagas
+#[derive(Debug)]
+pub struct SagaJournal {
+    dir: PathBuf,
+}
+
+impl SagaJournal {
+    /// The journal in the directory set by `EXO_SAGA_DIR` (if any)
+    pub fn from_env(env: &dyn Environment) -> io::Result<Option<Self>> {
+        env.get(EXO_SAGA_DIR)
+            .map(|dir| Self::open(PathBuf::from(dir)))
+            .transpose()
+    }
+
+    pub fn open(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Save the record, or remove it if no step needs compensating. The file is replaced
+    /// atomically, so a crash never leaves a partial record behind.
+    pub fn save(&self, record: &SagaRecord) -> io::Result<()> {
+        if record.steps.is_empty() {
+            return self.remove(&record.id);
+        }
+
+        let path = self.record_path(&record.id);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec(record)?)?;
+        fs::rename(temp_path, path)
+    }
+
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        match fs::remove_file(self.record_path(id)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// The records of the sagas that didn't finish (oldest first)
+    pub fn records(&self) -> io::Result<Vec<SagaRecord>> {
+        let mut records = vec![];
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            let record = fs::read(&path)
+                .and_then(|content| Ok(serde_json::from_slice::<SagaRecord>(&content)?));
+            match record {
+                Ok(record) => records.push(record),
+                Err(e) => warn!(
+                    "Skipping the unreadable saga record '{}': {e}",
+                    path.display()
+                ),
+            }
+        }
+
+        records.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(records)
+    }
+
+    fn record_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+}
+
+/// What a call into a module needs to run sagas
+#[derive(Debug, Clone)]
+pub struct SagaContext {
+    pub script_path: String,
+    /// Where to persist the sagas (if configured)
+    pub journal: Option<Arc<SagaJournal>>,
+}
+
+/// The sagas running in a Deno module (kept in its `OpState`)
+#[derive(Default)]
+pub(crate) struct ActiveSagas(pub HashMap<String, SagaRecord>);
+
+impl DenoSubsystemResolver {
+    /// Compensate the sagas that didn't finish before the server stopped (undoing their steps in
+    /// the reverse order). If a compensation fails, the saga stays in the journal to be compensated
+    /// again on the next start.
+    pub async fn recover_sagas(&self) {
+        let Some(journal) = &self.saga_journal else {
+            return;
+        };
+
+        let records = match journal.records() {
+            Ok(records) => records,
+            Err(e) => {
+                error!("Failed to read the saga journal: {e}");
+                return;
+            }
+        };
+
+        for record in records {
+            let (id, name) = (record.id.clone(), record.name.clone());
+
+            match self.compensate_saga(journal, record).await {
+                Ok(()) => info!("Compensated the interrupted saga '{name}' ({id})"),
+                Err(e) => error!("Failed to compensate the interrupted saga '{name}' ({id}): {e}"),
+            }
+        }
+    }
+
+    async fn compensate_saga(
+        &self,
+        journal: &SagaJournal,
+        mut record: SagaRecord,
+    ) -> Result<(), DenoExecutionError> {
+        let script = self
+            .subsystem
+            .scripts
+            .iter()
+            .map(|(_, script)| script)
+            .find(|script| script.path == record.script_path)
+            .ok_or_else(|| {
+                DenoExecutionError::Generic(format!(
+                    "The module '{}' no longer exists",
+                    record.script_path
+                ))
+            })?;
+
+        // Compensations run outside of any request, so there is nothing to execute queries against
+        let exograph_execute_query: &ExographExecuteQueryFn = &|_, _, _, _| {
+            async {
+                Err(SystemResolutionError::Generic(
+                    "executeQuery is not available to compensations run after a restart"
+                        .to_string(),
+                ))
+            }
+            .boxed()
+        };
+
+        while let Some(step) = record.steps.last() {
+            let callback_processor = ExoCallbackProcessor {
+                exograph_execute_query,
+                exograph_proceed: None,
+            };
+            let deserialized: DenoScriptDefn = serde_json::from_slice(&script.script).unwrap();
+
+            self.executor
+                .execute(
+                    &script.path,
+                    deserialized,
+                    &step.compensation,
+                    vec![
+                        Arg::Serde(step.result.clone()),
+                        Arg::Shim("Exograph".to_string()),
+                    ],
+                    Some(InterceptedOperationInfo::new(
+                        step.compensation.clone(),
+                        Value::Null,
+                        &Clock::system(),
+                        self.service_token_issuer.clone(),
+                        self.fetch_policy(&script.path),
+                        self.session_cookie.clone(),
+                        None,
+                    )),
+                    callback_processor,
+                    self.execution_timeout(&script.path, None),
+                )
+                .await
+                .map_err(DenoExecutionError::Deno)?;
+
+            record.steps.pop();
+            journal.save(&record).map_err(|e| {
+                DenoExecutionError::Generic(format!("Failed to persist the saga: {e}"))
+            })?;
+        }
+
+        journal
+            .remove(&record.id)
+            .map_err(|e| DenoExecutionError::Generic(format!("Failed to persist the saga: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn step(name: &str) -> SagaStep {
+        SagaStep {
+            name: name.to_string(),
+            compensation: format!("undo_{name}"),
+            result: json!({ "id": name }),
+        }
+    }
+
+    #[test]
+    fn journal_keeps_unfinished_sagas() {
+        let dir = std::env::temp_dir().join(format!("exo-saga-test-{}", std::process::id()));
+        let journal = SagaJournal::open(dir.clone()).unwrap();
+
+        let mut first = SagaRecord::new("placeOrder".to_string(), "checkout.ts".to_string(), 1);
+        let mut second = SagaRecord::new("placeOrder".to_string(), "checkout.ts".to_string(), 2);
+
+        // Sagas without steps to compensate are not recorded
+        journal.save(&first).unwrap();
+        assert_eq!(journal.records().unwrap(), vec![]);
+
+        first.steps.push(step("charge"));
+        second.steps.push(step("reserve"));
+        journal.save(&first).unwrap();
+        journal.save(&second).unwrap();
+        assert_eq!(
+            journal.records().unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        second.steps.pop();
+        journal.save(&second).unwrap();
+        journal.remove(&first.id).unwrap();
+        assert_eq!(journal.records().unwrap(), vec![]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -7,11 +7,19 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 
-use core_plugin_interface::interface::{SubsystemLoader, SubsystemLoadingError, SubsystemResolver};
+use common::{
+    env_const::{EXO_DENO_EXECUTION_TIMEOUT, EXO_JWT_SOURCE_COOKIE, EXO_SAGA_DIR},
+    service_token::ServiceTokenIssuer,
+};
+
+use core_plugin_interface::interface::{
+    SubsystemBackgroundService, SubsystemDiagnostics, SubsystemLoader, SubsystemLoadingError,
+    SubsystemResolver, SystemRouterRef,
+};
 
 use core_plugin_shared::{
     serializable_system::SerializableSubsystem, system_serializer::SystemSerializer,
@@ -19,7 +27,7 @@ use core_plugin_shared::{
 use core_resolver::plugin::SubsystemGraphQLResolver;
 
 use deno_graphql_model::subsystem::DenoSubsystem;
-use deno_graphql_resolver::{DenoSubsystemResolver, exo_config};
+use deno_graphql_resolver::{DenoSubsystemResolver, SagaJournal, exo_config};
 use exo_deno::DenoExecutorPool;
 use exo_env::Environment;
 
@@ -38,20 +46,97 @@ impl SubsystemLoader for DenoSubsystemLoader {
     ) -> Result<Box<SubsystemResolver>, SubsystemLoadingError> {
         exo_deno::initialize();
 
-        let graphql = match serialized_subsystem.graphql {
+        let resolver = match serialized_subsystem.graphql {
             Some(graphql) => {
                 let subsystem = DenoSubsystem::deserialize(graphql.0)?;
                 let executor = DenoExecutorPool::new_from_config(exo_config(env.clone()));
+                let service_token_issuer = ServiceTokenIssuer::from_env(env.as_ref())
+                    .map_err(|e| SubsystemLoadingError::Config(e.to_string()))?
+                    .map(Arc::new);
+                let fetch_circuit_breakers =
+                    DenoSubsystemResolver::create_fetch_circuit_breakers(&subsystem);
+                let default_execution_timeout = env
+                    .get(EXO_DENO_EXECUTION_TIMEOUT)
+                    .map(|value| match value.parse::<u64>() {
+                        Ok(timeout_ms) if timeout_ms > 0 => Ok(Duration::from_millis(timeout_ms)),
+                        _ => Err(SubsystemLoadingError::Config(format!(
+                            "{EXO_DENO_EXECUTION_TIMEOUT} must be a positive number of milliseconds"
+                        ))),
+                    })
+                    .transpose()?;
+                let session_cookie = env.get(EXO_JWT_SOURCE_COOKIE);
+                let saga_journal = SagaJournal::from_env(env.as_ref())
+                    .map_err(|e| {
+                        SubsystemLoadingError::Config(format!(
+                            "Failed to open the saga journal ({EXO_SAGA_DIR}): {e}"
+                        ))
+                    })?
+                    .map(Arc::new);
                 Ok::<_, SubsystemLoadingError>(Some(Arc::new(DenoSubsystemResolver {
                     id: self.id(),
                     subsystem,
                     executor,
-                })
-                    as Arc<dyn SubsystemGraphQLResolver + Send + Sync>))
+                    service_token_issuer,
+                    fetch_circuit_breakers,
+                    default_execution_timeout,
+                    session_cookie,
+                    saga_journal,
+                })))
             }
             None => Ok(None),
         }?;
 
-        Ok(Box::new(SubsystemResolver::new(graphql, None, None)))
+        let graphql = resolver
+            .clone()
+            .map(|resolver| resolver as Arc<dyn SubsystemGraphQLResolver + Send + Sync>);
+
+        let mut subsystem_resolver = SubsystemResolver::new(graphql, None, None);
+
+        if let Some(resolver) = resolver {
+            if resolver.saga_journal.is_some() {
+                subsystem_resolver =
+                    subsystem_resolver.with_background_service(Arc::new(SagaRecoveryService {
+                        resolver: resolver.clone(),
+                    }));
+            }
+            subsystem_resolver =
+                subsystem_resolver.with_diagnostics(Arc::new(DenoPoolDiagnostics { resolver }));
+        }
+
+        Ok(Box::new(subsystem_resolver))
+    }
+}
+
+/// Compensates the sagas interrupted by a crash (or another abrupt stop) of the server
+struct SagaRecoveryService {
+    resolver: Arc<DenoSubsystemResolver>,
+}
+
+#[async_trait]
+impl SubsystemBackgroundService for SagaRecoveryService {
+    async fn run(&self, _router: SystemRouterRef, _env: Arc<dyn Environment>) {
+        self.resolver.recover_sagas().await;
+    }
+}
+
+/// Reports the number of modules and the Deno actors allocated for each (actors are allocated on
+/// first use, so a freshly started server reports none)
+struct DenoPoolDiagnostics {
+    resolver: Arc<DenoSubsystemResolver>,
+}
+
+#[async_trait]
+impl SubsystemDiagnostics for DenoPoolDiagnostics {
+    fn name(&self) -> &'static str {
+        "deno_pool"
+    }
+
+    async fn diagnose(&self) -> serde_json::Value {
+        let modules = self.resolver.executor.status().await;
+
+        serde_json::json!({
+            "modules": self.resolver.subsystem.scripts.len(),
+            "actors": modules,
+        })
     }
 }
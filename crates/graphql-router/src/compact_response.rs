@@ -0,0 +1,125 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Compact responses, which omit null-valued fields and empty lists to reduce the size of
+//! responses with sparse entities.
+//!
+//! Since GraphQL requires every selected field to be present in the response, compaction is
+//! opt-in: through the `x-exo-compact` header or `EXO_COMPACT_RESPONSES` (which the header
+//! overrides).
+
+use common::http::RequestHead;
+use serde_json::Value as JsonValue;
+
+/// The header a client sets (to "true" or "false") to request (or opt out of) a compact response
+pub(crate) const COMPACT_RESPONSE_HEADER: &str = "x-exo-compact";
+
+/// Should the response to the request be compacted (given the default set through
+/// `EXO_COMPACT_RESPONSES`)?
+pub(crate) fn requested_compaction(
+    request_head: &(dyn RequestHead + Sync),
+    compact_by_default: bool,
+) -> bool {
+    match request_head.get_header(COMPACT_RESPONSE_HEADER).as_deref() {
+        Some("true") => true,
+        Some("false") => false,
+        Some(other) => {
+            tracing::debug!("Ignoring unknown compact response value '{other}'");
+            compact_by_default
+        }
+        None => compact_by_default,
+    }
+}
+
+/// Remove the null-valued fields and empty lists from the objects in the value.
+///
+/// Elements of lists are compacted, but never removed (their position is meaningful).
+pub(crate) fn compact(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(fields) => JsonValue::Object(
+            fields
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let value = compact(value);
+                    (!is_empty(&value)).then_some((name, value))
+                })
+                .collect(),
+        ),
+        JsonValue::Array(elements) => JsonValue::Array(elements.into_iter().map(compact).collect()),
+        value => value,
+    }
+}
+
+/// Compact a value already serialized by a subsystem (such as the JSON produced by Postgres).
+/// Values that aren't valid JSON are returned as is.
+pub(crate) fn compact_raw(raw: String) -> String {
+    match serde_json::from_str(&raw) {
+        Ok(value) => compact(value).to_string(),
+        Err(_) => raw,
+    }
+}
+
+fn is_empty(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Null => true,
+        JsonValue::Array(elements) => elements.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn removes_nulls_and_empty_lists() {
+        let value = json!({
+            "id": 1,
+            "title": null,
+            "tags": [],
+            "venue": { "id": 2, "name": null },
+            "artists": [{ "id": 3, "bio": null }, null],
+            "published": false,
+            "notes": "",
+            "metadata": {}
+        });
+
+        assert_eq!(
+            compact(value),
+            json!({
+                "id": 1,
+                "venue": { "id": 2 },
+                "artists": [{ "id": 3 }, null],
+                "published": false,
+                "notes": "",
+                "metadata": {}
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_objects_emptied_by_compaction() {
+        // The object is still present (it was selected), even if all its fields were removed
+        assert_eq!(
+            compact(json!({ "a": { "b": [] }, "c": [null] })),
+            json!({ "a": {}, "c": [null] })
+        );
+    }
+
+    #[test]
+    fn compacts_raw_values() {
+        assert_eq!(
+            compact_raw(r#"[{"id": 1, "title": null}]"#.to_string()),
+            r#"[{"id":1}]"#
+        );
+        assert_eq!(compact_raw("not json".to_string()), "not json");
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Reporting errors to Sentry.
+//!
+//! Reports carry a fingerprint based on the kind of error (and, for database errors, the SQLSTATE
+//! code and table) along with the operation name, so that Sentry groups recurring errors into one
+//! issue even if their messages differ (for example, by the values involved). The operation and its
+//! variables (with sensitive values redacted) are attached as context.
+
+use std::error::Error;
+
+use http::StatusCode;
+use sentry::{Level, protocol::Map as SentryMap};
+use serde_json::{Map, Value};
+use tokio_postgres::error::DbError;
+
+use common::{context::RequestContext, http::RequestHead, operation_payload::OperationsPayload};
+use core_resolver::system_resolver::SystemResolutionError;
+
+/// Variables whose names contain any of these (case-insensitively) are redacted
+const SENSITIVE_VARIABLE_NAMES: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "apikey",
+    "api_key",
+    "authorization",
+    "credential",
+];
+const MAX_REPORTED_STRING_LENGTH: usize = 256;
+const REDACTED: &str = "[redacted]";
+
+/// The parts of the operation to report with errors (captured only if Sentry is enabled)
+pub(crate) struct ReportedOperation {
+    name: Option<String>,
+    query: Option<String>,
+    variables: Option<Map<String, Value>>,
+}
+
+impl ReportedOperation {
+    pub(crate) fn capture(operations_payload: &OperationsPayload) -> Option<Self> {
+        if sentry::Hub::current().client().is_none() {
+            return None;
+        }
+
+        Some(Self {
+            name: operations_payload.operation_name.clone(),
+            query: operations_payload.query.clone(),
+            variables: operations_payload.variables.clone(),
+        })
+    }
+}
+
+pub(crate) fn capture_graphql_error(
+    err: &SystemResolutionError,
+    request_context: &RequestContext<'_>,
+    operation: Option<&ReportedOperation>,
+    request_id: Option<&str>,
+    status_code: StatusCode,
+) {
+    if sentry::Hub::current().client().is_none() {
+        return;
+    }
+
+    let request_head = request_context.get_head();
+    let message = err.user_error_message();
+    let operation_name = operation.and_then(|operation| operation.name.as_deref());
+    let fingerprint = fingerprint(err, operation_name);
+
+    sentry::with_scope(
+        |scope| {
+            scope.set_tag("graphql.path", request_head.get_path());
+            scope.set_tag("http.method", request_head.get_method().as_str());
+            if let Some(request_id) = request_id {
+                scope.set_tag("request_id", request_id);
+            }
+            scope.set_tag("error.kind", error_kind(err));
+            scope.set_tag(
+                "internal_request",
+                request_context.is_internal().to_string(),
+            );
+            scope.set_tag(
+                "auth_present",
+                request_context.is_authentication_info_present().to_string(),
+            );
+            scope.set_extra("status_code", status_code.as_u16().into());
+
+            let fingerprint: Vec<&str> = fingerprint.iter().map(String::as_str).collect();
+            scope.set_fingerprint(Some(fingerprint.as_slice()));
+
+            if let Some(operation) = operation {
+                scope.set_context("graphql", graphql_context(operation));
+            }
+        },
+        || {
+            sentry::capture_message(&message, Level::Error);
+        },
+    );
+}
+
+fn graphql_context(operation: &ReportedOperation) -> sentry::protocol::Context {
+    let mut context = SentryMap::new();
+    if let Some(name) = &operation.name {
+        context.insert("operationName".to_string(), Value::String(name.clone()));
+    }
+    if let Some(query) = &operation.query {
+        context.insert("query".to_string(), Value::String(query.clone()));
+    }
+    if let Some(variables) = &operation.variables {
+        context.insert(
+            "variables".to_string(),
+            sanitize(&Value::Object(variables.clone()), false),
+        );
+    }
+    sentry::protocol::Context::Other(context)
+}
+
+fn fingerprint(err: &SystemResolutionError, operation_name: Option<&str>) -> Vec<String> {
+    let operation_name = operation_name.unwrap_or("<anonymous>").to_string();
+
+    match find_db_error(err) {
+        Some(db_error) => vec![
+            "postgres".to_string(),
+            db_error.code().code().to_string(),
+            db_error.table().unwrap_or("<unknown>").to_string(),
+            operation_name,
+        ],
+        None => vec!["graphql".to_string(), error_kind(err), operation_name],
+    }
+}
+
+fn find_db_error<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a DbError> {
+    let mut current = Some(err);
+
+    while let Some(err) = current {
+        if let Some(db_error) = err.downcast_ref::<DbError>() {
+            return Some(db_error);
+        }
+        if let Some(db_error) = err
+            .downcast_ref::<tokio_postgres::Error>()
+            .and_then(|err| err.as_db_error())
+        {
+            return Some(db_error);
+        }
+        current = err.source();
+    }
+
+    None
+}
+
+/// The (nested) variant names of the error without any of its data, such as
+/// `Validation/QueryParsingFailed` (so it doesn't vary with the message)
+fn error_kind(err: &SystemResolutionError) -> String {
+    let debug = format!("{err:?}");
+    let mut kinds = vec![];
+    let mut rest = debug.as_str();
+
+    loop {
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if end > 0 {
+            kinds.push(&rest[..end]);
+        }
+
+        match rest[end..].strip_prefix('(') {
+            Some(inner) if end > 0 => rest = inner,
+            _ => break,
+        }
+    }
+
+    kinds.join("/")
+}
+
+/// Redact sensitive values and truncate long strings
+fn sanitize(value: &Value, redact: bool) -> Value {
+    if redact && !value.is_null() {
+        return Value::String(REDACTED.to_string());
+    }
+
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), sanitize(value, is_sensitive(key))))
+                .collect(),
+        ),
+        Value::Array(elements) => Value::Array(
+            elements
+                .iter()
+                .map(|element| sanitize(element, false))
+                .collect(),
+        ),
+        Value::String(s) if s.chars().count() > MAX_REPORTED_STRING_LENGTH => {
+            Value::String(format!(
+                "{}...",
+                s.chars()
+                    .take(MAX_REPORTED_STRING_LENGTH)
+                    .collect::<String>()
+            ))
+        }
+        _ => value.clone(),
+    }
+}
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_VARIABLE_NAMES
+        .iter()
+        .any(|sensitive| name.contains(sensitive))
+}
+
+#[cfg(test)]
+mod tests {
+    use core_resolver::{plugin::SubsystemResolutionError, system_resolver::RequestError};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn error_kind_ignores_data() {
+        assert_eq!(
+            error_kind(&SystemResolutionError::Generic("some message".to_string())),
+            "Generic"
+        );
+        assert_eq!(
+            error_kind(&SystemResolutionError::SubsystemResolutionError(
+                SubsystemResolutionError::Authorization
+            )),
+            "SubsystemResolutionError/Authorization"
+        );
+        assert_eq!(
+            error_kind(&SystemResolutionError::RequestError(
+                RequestError::InvalidBodyJson(serde_json::from_str::<Value>("{").unwrap_err())
+            )),
+            "RequestError/InvalidBodyJson/Error"
+        );
+    }
+
+    #[test]
+    fn fingerprint_uses_kind_and_operation() {
+        let err = SystemResolutionError::Generic("Failed for id 42".to_string());
+
+        assert_eq!(
+            fingerprint(&err, Some("getConcert")),
+            vec!["graphql", "Generic", "getConcert"]
+        );
+        assert_eq!(
+            fingerprint(&err, None),
+            vec!["graphql", "Generic", "<anonymous>"]
+        );
+    }
+
+    #[test]
+    fn sanitizes_variables() {
+        let variables = json!({
+            "email": "alice@example.com",
+            "password": "hunter2",
+            "input": { "apiKey": "abc", "notes": [null, "x".repeat(300)] },
+            "resetToken": null
+        });
+
+        let sanitized = sanitize(&variables, false);
+
+        assert_eq!(sanitized["email"], json!("alice@example.com"));
+        assert_eq!(sanitized["password"], json!(REDACTED));
+        assert_eq!(sanitized["input"]["apiKey"], json!(REDACTED));
+        assert_eq!(
+            sanitized["input"]["notes"][1].as_str().unwrap().len(),
+            MAX_REPORTED_STRING_LENGTH + 3
+        );
+        assert_eq!(sanitized["resetToken"], Value::Null);
+    }
+}
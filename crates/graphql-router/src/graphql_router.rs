@@ -7,37 +7,57 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
-use common::env_const::get_graphql_http_path;
+use common::deployment::DeploymentInfo;
+use common::env_const::{
+    EXO_COMPACT_RESPONSES, EXO_DEPLOYMENT_EXTENSIONS, EXO_ERROR_MESSAGES_DIR,
+    EXO_EXECUTION_STATS_ROLE, get_graphql_http_path,
+};
 
-use common::env_const::is_production;
+use common::env_const::{DeploymentMode, get_deployment_mode, is_production};
 use common::http::{Headers, RequestHead, RequestPayload, ResponseBody, ResponsePayload};
+use common::maintenance::{MaintenanceError, MaintenanceMode};
 use common::router::Router;
+use common::value::Val;
 use core_plugin_shared::interception::InterceptionMap;
 use core_plugin_shared::trusted_documents::TrustedDocumentEnforcement;
 use core_plugin_shared::trusted_documents::TrustedDocuments;
-use core_resolver::QueryResponse;
 use core_resolver::introspection::definition::schema::Schema;
+use core_resolver::operation_limiter::{OperationLimitError, OperationLimiter};
 use core_resolver::plugin::SubsystemGraphQLResolver;
+use core_resolver::plugin::SubsystemResolutionError;
+use core_resolver::replay_guard::ReplayError;
+use core_resolver::response_headers::DeclaredResponseHeaders;
+use core_resolver::{JsonValueStream, QueryResponse};
 use core_router::SystemLoadingError;
+use futures::{Stream, StreamExt};
 use http::StatusCode;
-use sentry::Level;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
 
 use ::tracing::instrument;
 use async_graphql_parser::Pos;
 use async_stream::try_stream;
 use bytes::Bytes;
-use common::context::RequestContext;
+use common::context::{RequestContext, RequestStats};
 use common::operation_payload::OperationsPayload;
 use core_resolver::QueryResponseBody;
 use core_resolver::system_resolver::GraphQLSystemResolver;
 use core_resolver::system_resolver::{RequestError, SystemResolutionError};
 
 use exo_env::Environment;
+use exo_sql::{COMMIT_LSN_HEADER, PlanRecorder, plans_to_dot};
 
-use crate::system_loader::SystemLoader;
+use crate::{
+    compact_response::{compact, compact_raw, requested_compaction},
+    error_report::{ReportedOperation, capture_graphql_error},
+    message_catalog::MessageCatalog,
+    response_anomaly::ResponseAnomalyDetector,
+    system_loader::SystemLoader,
+};
 
 fn extract_request_id(request_head: &(dyn RequestHead + Sync)) -> Option<String> {
     request_head
@@ -47,9 +67,32 @@ fn extract_request_id(request_head: &(dyn RequestHead + Sync)) -> Option<String>
         .or_else(|| request_head.get_header("traceparent"))
 }
 
+/// The header a client sets (to "true") to request execution stats in the response
+const EXECUTION_STATS_HEADER: &str = "x-exo-stats";
+
+/// The header a client sets (to "json" or "dot") to request the plans of the database operations
+/// in the response (honored only in the dev and yolo modes)
+const EXECUTION_PLAN_HEADER: &str = "x-exo-plan";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlanFormat {
+    Json,
+    Dot,
+}
+
 pub struct GraphQLRouter {
     resolver: Arc<GraphQLSystemResolver>,
     env: Arc<dyn Environment>,
+    /// The `extensions` to add to responses (deployment metadata, if `EXO_DEPLOYMENT_EXTENSIONS` is enabled)
+    response_extensions: JsonMap<String, JsonValue>,
+    /// The JWT `role` claim required to receive execution stats (if `EXO_EXECUTION_STATS_ROLE` is set)
+    execution_stats_role: Option<String>,
+    /// Whether to compact responses unless the client opts out (`EXO_COMPACT_RESPONSES`)
+    compact_responses: bool,
+    /// Translations of error messages (if `EXO_ERROR_MESSAGES_DIR` is set)
+    message_catalog: MessageCatalog,
+    /// Detection of responses that deviate from their baseline (if `EXO_RESPONSE_ANOMALY_FACTOR` is set)
+    anomaly_detector: Arc<ResponseAnomalyDetector>,
 }
 
 impl GraphQLRouter {
@@ -57,6 +100,11 @@ impl GraphQLRouter {
         Self {
             resolver: Arc::new(resolver),
             env,
+            response_extensions: JsonMap::new(),
+            execution_stats_role: None,
+            compact_responses: false,
+            message_catalog: MessageCatalog::default(),
+            anomaly_detector: Arc::new(ResponseAnomalyDetector::default()),
         }
     }
 
@@ -65,6 +113,7 @@ impl GraphQLRouter {
             && request_head.get_method() == http::Method::POST
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn from_resolvers(
         graphql_resolvers: Vec<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
         introspection_resolver: Option<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
@@ -73,6 +122,9 @@ impl GraphQLRouter {
         mutation_interception_map: Arc<InterceptionMap>,
         trusted_documents: TrustedDocuments,
         env: Arc<dyn Environment>,
+        maintenance_mode: Arc<MaintenanceMode>,
+        operation_limiter: Arc<OperationLimiter>,
+        response_headers: Arc<DeclaredResponseHeaders>,
     ) -> Result<Self, SystemLoadingError> {
         let graphql_resolver = SystemLoader::create_system_resolver(
             graphql_resolvers,
@@ -82,9 +134,68 @@ impl GraphQLRouter {
             trusted_documents,
             env.clone(),
             schema,
+            maintenance_mode,
+            operation_limiter,
+            response_headers,
         )?;
 
-        Ok(Self::new(graphql_resolver, env))
+        let mut response_extensions = JsonMap::new();
+        if env.enabled(EXO_DEPLOYMENT_EXTENSIONS, false)? {
+            response_extensions.insert(
+                "deployment".to_string(),
+                json!(DeploymentInfo::from_env(env.as_ref())),
+            );
+        }
+
+        let execution_stats_role = env.get(EXO_EXECUTION_STATS_ROLE);
+        let compact_responses = env.enabled(EXO_COMPACT_RESPONSES, false)?;
+        let message_catalog = match env.get(EXO_ERROR_MESSAGES_DIR) {
+            Some(dir) => MessageCatalog::load(Path::new(&dir))?,
+            None => MessageCatalog::default(),
+        };
+        let anomaly_detector = Arc::new(ResponseAnomalyDetector::from_env(env.as_ref())?);
+
+        Ok(Self {
+            response_extensions,
+            execution_stats_role,
+            compact_responses,
+            message_catalog,
+            anomaly_detector,
+            ..Self::new(graphql_resolver, env)
+        })
+    }
+
+    /// The stats to report for the request, if the client asked for them (through the
+    /// `x-exo-stats` header) and has the role set through `EXO_EXECUTION_STATS_ROLE`
+    async fn requested_stats(
+        &self,
+        request_context: &RequestContext<'_>,
+    ) -> Option<Arc<RequestStats>> {
+        let stats_role = self.execution_stats_role.as_ref()?;
+
+        let requested = request_context
+            .get_head()
+            .get_header(EXECUTION_STATS_HEADER)
+            .is_some_and(|value| value == "true");
+        if !requested {
+            return None;
+        }
+
+        let role = request_context
+            .extract_context_field("ExecutionStatsContext", "jwt", &Some("role"), "role", &Ok)
+            .await
+            .ok()
+            .flatten();
+
+        match role {
+            Some(Val::String(role)) if role == stats_role => {
+                Some(request_context.system_context.stats.clone())
+            }
+            _ => {
+                tracing::debug!("Execution stats requested without the required role");
+                None
+            }
+        }
     }
 
     pub fn resolver(&self) -> Arc<GraphQLSystemResolver> {
@@ -92,40 +203,27 @@ impl GraphQLRouter {
     }
 }
 
-fn capture_graphql_error(
-    err: &SystemResolutionError,
-    request_context: &RequestContext<'_>,
-    status_code: StatusCode,
-) {
+fn current_sentry_transaction() -> Option<sentry::TransactionOrSpan> {
     if sentry::Hub::current().client().is_none() {
-        return;
+        return None;
     }
 
-    let request_head = request_context.get_head();
-    let message = err.user_error_message();
+    sentry::configure_scope(|scope| scope.get_span())
+}
 
-    sentry::with_scope(
-        |scope| {
-            scope.set_tag("graphql.path", request_head.get_path());
-            scope.set_tag("http.method", request_head.get_method().as_str());
-            if let Some(request_id) = extract_request_id(request_head) {
-                scope.set_tag("request_id", request_id);
-            }
-            scope.set_tag("error.kind", format!("{:?}", err));
-            scope.set_tag(
-                "internal_request",
-                request_context.is_internal().to_string(),
-            );
-            scope.set_tag(
-                "auth_present",
-                request_context.is_authentication_info_present().to_string(),
-            );
-            scope.set_extra("status_code", status_code.as_u16().into());
-        },
-        || {
-            sentry::capture_message(&message, Level::Error);
-        },
-    );
+/// The `sub` claim of the JWT (if any)
+async fn extract_user_id(request_context: &RequestContext<'_>) -> Option<String> {
+    let user_id = request_context
+        .extract_context_field("SentryContext", "jwt", &Some("sub"), "sub", &Ok)
+        .await
+        .ok()
+        .flatten()?;
+
+    match user_id {
+        Val::String(user_id) => Some(user_id.clone()),
+        Val::Number(user_id) => Some(user_id.to_string()),
+        _ => None,
+    }
 }
 
 #[async_trait]
@@ -147,6 +245,12 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
             return None;
         }
 
+        let started_at = Instant::now();
+
+        // Captured before entering child spans, so that it refers to the transaction (if Sentry
+        // performance tracing is enabled)
+        let sentry_transaction = current_sentry_transaction();
+
         let request_id = extract_request_id(request_head);
         let request_span = tracing::info_span!(
             "graphql_request",
@@ -159,6 +263,12 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
         let _request_guard = request_span.enter();
         tracing::info!("GraphQL request received");
 
+        if let Some(transaction) = &sentry_transaction
+            && let Some(user_id) = extract_user_id(request_context).await
+        {
+            transaction.set_tag("user.id", user_id);
+        }
+
         let playground_request = request_head
             .get_header("_exo_playground")
             .map(|value| value == "true")
@@ -167,6 +277,19 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
         let is_production = is_production(self.env.as_ref());
         let is_internal = request_context.is_internal();
 
+        let is_development = matches!(
+            get_deployment_mode(self.env.as_ref()),
+            Ok(Some(DeploymentMode::Dev | DeploymentMode::Yolo))
+        );
+        let plan_format = if is_development {
+            requested_plan_format(request_head)
+        } else {
+            None
+        };
+        if plan_format.is_some() {
+            request_context.system_context.plan_recorder.enable();
+        }
+
         let should_enforce = !is_internal && (is_production || !playground_request);
         let trusted_document_enforcement = if should_enforce {
             TrustedDocumentEnforcement::Enforce
@@ -174,18 +297,72 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
             TrustedDocumentEnforcement::DoNotEnforce
         };
 
-        let response = resolve_in_memory(
-            request_context,
-            &self.resolver,
-            trusted_document_enforcement,
-            request_context,
-        )
-        .await;
+        let mut reported_operation = None;
+        let mut operation_name = None;
+
+        let response = match OperationsPayload::from_json(request_context.take_body()) {
+            Ok(operations_payload) => {
+                reported_operation = ReportedOperation::capture(&operations_payload);
+                operation_name = operations_payload.operation_name.clone();
+
+                if let Some(transaction) = &sentry_transaction
+                    && let Some(operation_name) = &operations_payload.operation_name
+                {
+                    transaction.set_tag("graphql.operation_name", operation_name);
+                }
+
+                resolve_in_memory_for_payload(
+                    operations_payload,
+                    &self.resolver,
+                    trusted_document_enforcement,
+                    request_context,
+                )
+                .await
+            }
+            Err(e) => Err(SystemResolutionError::RequestError(
+                RequestError::InvalidBodyJson(e),
+            )),
+        };
+
+        // A streamed field is sent as it is produced, so it cannot be combined with other fields
+        let response = match response {
+            Ok(parts) if parts.len() > 1 && parts.iter().any(|(_, part)| is_streamed(part)) => {
+                Err(SystemResolutionError::SubsystemResolutionError(
+                    SubsystemResolutionError::UserDisplayError(
+                        "A streaming field must be the only field in the operation".to_string(),
+                    ),
+                ))
+            }
+            response => response,
+        };
 
         match &response {
+            Err(SystemResolutionError::Maintenance(e)) => {
+                return Some(maintenance_response(e));
+            }
+            Err(SystemResolutionError::OperationLimit(e)) => {
+                return Some(operation_limit_response(e));
+            }
+            Err(SystemResolutionError::Replay(e)) => {
+                return Some(replay_response(e));
+            }
+            Err(SystemResolutionError::SubsystemResolutionError(
+                SubsystemResolutionError::Unavailable {
+                    message,
+                    retry_after_secs,
+                },
+            )) => {
+                return Some(unavailable_response(message, *retry_after_secs));
+            }
             Err(err @ SystemResolutionError::RequestError(e)) => {
                 tracing::error!("Error while resolving request: {:?}", e);
-                capture_graphql_error(err, request_context, StatusCode::BAD_REQUEST);
+                capture_graphql_error(
+                    err,
+                    request_context,
+                    reported_operation.as_ref(),
+                    request_id.as_deref(),
+                    StatusCode::BAD_REQUEST,
+                );
                 return Some(ResponsePayload {
                     body: ResponseBody::None,
                     headers: Headers::new(),
@@ -193,7 +370,13 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
                 });
             }
             Err(err) => {
-                capture_graphql_error(err, request_context, StatusCode::OK);
+                capture_graphql_error(
+                    err,
+                    request_context,
+                    reported_operation.as_ref(),
+                    request_id.as_deref(),
+                    StatusCode::OK,
+                );
             }
             Ok(_) => {}
         }
@@ -209,8 +392,62 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
             Headers::new()
         };
 
+        // Lets the client read its writes from a read replica in later requests
+        if let Some(commit_lsn) = request_context.commit_lsn().await {
+            headers.insert(COMMIT_LSN_HEADER.into(), commit_lsn);
+        }
+
+        let accepts_event_stream = request_head
+            .get_header("accept")
+            .map(|accept| accept.contains("text/event-stream"))
+            .unwrap_or(false);
+
+        let compact_response = requested_compaction(request_head, self.compact_responses);
+
+        if accepts_event_stream
+            && let Ok(parts) = &response
+            && let [(name, part)] = parts.as_slice()
+            && let QueryResponseBody::Stream(stream) = &part.body
+        {
+            headers.insert("content-type".into(), "text/event-stream".into());
+            headers.insert("cache-control".into(), "no-cache".into());
+
+            return Some(ResponsePayload {
+                body: ResponseBody::Stream(Box::pin(event_stream(
+                    name.clone(),
+                    stream.take(),
+                    compact_response,
+                ))),
+                headers,
+                status_code: StatusCode::OK,
+            });
+        }
+
         headers.insert("content-type".into(), "application/json".into());
 
+        let localized_message = response.as_ref().err().and_then(|err| {
+            self.message_catalog
+                .localize(request_head.get_header("accept-language").as_deref(), err)
+        });
+        let error_message = match localized_message {
+            Some((message, locale)) => {
+                headers.insert("content-language".into(), locale);
+                Some(message)
+            }
+            None => None,
+        };
+
+        let response_extensions = self.response_extensions.clone();
+        let request_stats = self.requested_stats(request_context).await;
+        let plan = plan_format
+            .map(|format| (format, request_context.system_context.plan_recorder.clone()));
+        let mut response_sample = match &response {
+            Ok(_) => self
+                .anomaly_detector
+                .sample(operation_name.as_deref(), started_at),
+            Err(_) => None,
+        };
+
         let stream = try_stream! {
             macro_rules! report_position {
                 ($position:expr) => {
@@ -224,6 +461,31 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
                 };
             }
 
+            macro_rules! report_extensions {
+                () => {
+                    let extensions = serialize_extensions(
+                        response_extensions,
+                        request_stats.as_deref(),
+                        plan.as_ref().map(|(format, recorder)| (*format, recorder.as_ref())),
+                    );
+                    if let Some(extensions) = extensions {
+                        yield Bytes::from_static(br#", "extensions": "#);
+                        yield extensions;
+                    }
+                };
+            }
+
+            // Yield (part of) the value of a top-level field, adding its size to the sample
+            macro_rules! report_field_value {
+                ($alias:expr, $value:expr) => {
+                    let value: Bytes = $value;
+                    if let Some(sample) = &mut response_sample {
+                        sample.add_field_size($alias, value.len());
+                    }
+                    yield value;
+                };
+            }
+
             macro_rules! report_positions {
                 ($positions:expr) => {
                     let mut first = true;
@@ -240,26 +502,82 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
             match response {
                 Ok(parts) => {
                     let parts_len = parts.len();
+                    let mut stream_error = None;
                     yield Bytes::from_static(br#"{"data": {"#);
-                    for (index, part) in parts.into_iter().enumerate() {
+                    for (index, (alias, part)) in parts.into_iter().enumerate() {
                         yield Bytes::from_static(b"\"");
-                        yield Bytes::from(part.0);
+                        yield Bytes::from(alias.clone());
                         yield Bytes::from_static(br#"":"#);
-                        match part.1.body {
-                            QueryResponseBody::Json(value) => yield Bytes::from(value.to_string()),
-                            QueryResponseBody::Raw(Some(value)) => yield Bytes::from(value),
+                        match part.body {
+                            QueryResponseBody::Json(value) if compact_response => {
+                                report_field_value!(&alias, Bytes::from(compact(value).to_string()));
+                            }
+                            QueryResponseBody::Json(value) => {
+                                report_field_value!(&alias, Bytes::from(value.to_string()));
+                            }
+                            QueryResponseBody::Raw(Some(value)) if compact_response => {
+                                report_field_value!(&alias, Bytes::from(compact_raw(value)));
+                            }
+                            QueryResponseBody::Raw(Some(value)) => {
+                                report_field_value!(&alias, Bytes::from(value));
+                            }
                             QueryResponseBody::Raw(None) => yield Bytes::from_static(b"null"),
+                            QueryResponseBody::Stream(stream) => {
+                                if let Some(sample) = &mut response_sample {
+                                    sample.mark_streamed();
+                                }
+                                // Emit items as they are produced; an error ends the list and is
+                                // reported after the data
+                                yield Bytes::from_static(b"[");
+                                match stream.take() {
+                                    Some(mut stream) => {
+                                        let mut first = true;
+                                        while let Some(item) = stream.next().await {
+                                            match item {
+                                                Ok(item) => {
+                                                    if !first {
+                                                        yield Bytes::from_static(b", ");
+                                                    }
+                                                    first = false;
+                                                    let item = if compact_response {
+                                                        compact(item)
+                                                    } else {
+                                                        item
+                                                    };
+                                                    report_field_value!(&alias, Bytes::from(item.to_string()));
+                                                }
+                                                Err(err) => {
+                                                    stream_error = Some(err);
+                                                    break;
+                                                }
+                                            }
+                                        }
+                                    }
+                                    None => stream_error = Some(STREAM_CONSUMED_MESSAGE.to_string()),
+                                }
+                                yield Bytes::from_static(b"]");
+                            }
                         };
                         if index != parts_len - 1 {
                             yield Bytes::from_static(b", ");
                         }
                     };
-                    yield Bytes::from_static(b"}}");
+                    yield Bytes::from_static(b"}");
+                    if let Some(message) = stream_error {
+                        yield Bytes::from_static(br#", "errors": [{"message": "#);
+                        yield Bytes::from(JsonValue::String(message).to_string());
+                        yield Bytes::from_static(b"}]");
+                    }
+                    report_extensions!();
+                    yield Bytes::from_static(b"}");
+                    if let Some(sample) = response_sample.take() {
+                        sample.finish();
+                    }
                 },
                 Err(err) => {
                     yield Bytes::from_static(br#"{"errors": [{"message":""#);
                     yield Bytes::from(
-                        err.user_error_message().to_string()
+                        error_message.unwrap_or_else(|| err.user_error_message())
                             .replace('\"', "")
                             .replace('\n', "; ")
                     );
@@ -269,8 +587,14 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
                         report_positions!(err.positions());
                         yield Bytes::from_static(br#"]"#);
                     };
+                    if let Some(error_extensions) = err.error_extensions() {
+                        yield Bytes::from_static(br#", "extensions": "#);
+                        yield Bytes::from(error_extensions.to_string());
+                    }
                     yield Bytes::from_static(br#"}"#);
-                    yield Bytes::from_static(b"]}");
+                    yield Bytes::from_static(b"]");
+                    report_extensions!();
+                    yield Bytes::from_static(b"}");
                 },
             }
         };
@@ -283,28 +607,162 @@ impl<'a> Router<RequestContext<'a>> for GraphQLRouter {
     }
 }
 
-#[instrument(
-    name = "resolver::resolve_in_memory"
-    skip(system_resolver, request, request_context)
-)]
-pub async fn resolve_in_memory<'a>(
-    request: &(dyn RequestPayload + Sync),
-    system_resolver: &GraphQLSystemResolver,
-    trusted_document_enforcement: TrustedDocumentEnforcement,
-    request_context: &RequestContext<'a>,
-) -> Result<Vec<(String, QueryResponse)>, SystemResolutionError> {
-    let body = request.take_body();
-
-    let operations_payload = OperationsPayload::from_json(body.clone())
-        .map_err(|e| SystemResolutionError::RequestError(RequestError::InvalidBodyJson(e)))?;
-
-    resolve_in_memory_for_payload(
-        operations_payload,
-        system_resolver,
-        trusted_document_enforcement,
-        request_context,
-    )
-    .await
+/// The format of the plans requested through the `x-exo-plan` header (if any)
+fn requested_plan_format(request_head: &(dyn RequestHead + Sync)) -> Option<PlanFormat> {
+    match request_head.get_header(EXECUTION_PLAN_HEADER)?.as_str() {
+        "json" => Some(PlanFormat::Json),
+        "dot" => Some(PlanFormat::Dot),
+        other => {
+            tracing::debug!("Ignoring unknown execution plan format '{other}'");
+            None
+        }
+    }
+}
+
+/// Combine the static extensions with the execution stats and plans (read once the response is
+/// complete, so that they include all the work done for it)
+fn serialize_extensions(
+    mut extensions: JsonMap<String, JsonValue>,
+    stats: Option<&RequestStats>,
+    plan: Option<(PlanFormat, &PlanRecorder)>,
+) -> Option<Bytes> {
+    if let Some(stats) = stats {
+        extensions.insert("stats".to_string(), stats.to_json());
+    }
+
+    if let Some((format, recorder)) = plan {
+        let plans = recorder.plans();
+        let plan = match format {
+            PlanFormat::Json => json!(plans),
+            PlanFormat::Dot => JsonValue::String(plans_to_dot(&plans)),
+        };
+        extensions.insert("plan".to_string(), plan);
+    }
+
+    (!extensions.is_empty()).then(|| Bytes::from(JsonValue::Object(extensions).to_string()))
+}
+
+/// A 503 response with a structured error (so clients can distinguish maintenance from other
+/// failures) and a `Retry-After` header
+pub(crate) fn maintenance_response(error: &MaintenanceError) -> ResponsePayload {
+    let body = json!({
+        "errors": [{
+            "message": error.message,
+            "extensions": {
+                "code": "MAINTENANCE_MODE",
+                "retryAfter": error.retry_after_secs,
+            },
+        }]
+    });
+
+    let mut headers = Headers::new();
+    headers.insert("retry-after".into(), error.retry_after_secs.to_string());
+
+    rejection_response(body, headers, StatusCode::SERVICE_UNAVAILABLE)
+}
+
+pub(crate) fn operation_limit_response(error: &OperationLimitError) -> ResponsePayload {
+    let body = json!({
+        "errors": [{
+            "message": error.to_string(),
+            "extensions": {
+                "code": "CONCURRENCY_LIMIT_EXCEEDED",
+                "operation": error.operation,
+            },
+        }]
+    });
+
+    rejection_response(body, Headers::new(), StatusCode::TOO_MANY_REQUESTS)
+}
+
+/// Response for a mutation rejected as a replay (409) or as too old to check for one (400)
+pub(crate) fn replay_response(error: &ReplayError) -> ResponsePayload {
+    let body = json!({
+        "errors": [{
+            "message": error.to_string(),
+            "extensions": {
+                "code": error.code(),
+            },
+        }]
+    });
+
+    let status_code = match error {
+        ReplayError::Replayed => StatusCode::CONFLICT,
+        ReplayError::Expired | ReplayError::InvalidTimestamp | ReplayError::MissingHeader(_) => {
+            StatusCode::BAD_REQUEST
+        }
+    };
+
+    rejection_response(body, Headers::new(), status_code)
+}
+
+/// Response for an operation that failed fast because a dependency is down (circuit open)
+pub(crate) fn unavailable_response(message: &str, retry_after_secs: u64) -> ResponsePayload {
+    let body = json!({
+        "errors": [{
+            "message": message,
+            "extensions": {
+                "code": "CIRCUIT_OPEN",
+                "retryAfter": retry_after_secs,
+            },
+        }]
+    });
+
+    let mut headers = Headers::new();
+    headers.insert("retry-after".into(), retry_after_secs.to_string());
+
+    rejection_response(body, headers, StatusCode::SERVICE_UNAVAILABLE)
+}
+
+/// Response with just a structured error (instead of the results of the operation)
+fn rejection_response(
+    body: JsonValue,
+    mut headers: Headers,
+    status_code: StatusCode,
+) -> ResponsePayload {
+    headers.insert("content-type".into(), "application/json".into());
+
+    ResponsePayload {
+        body: ResponseBody::Bytes(body.to_string().into_bytes()),
+        headers,
+        status_code,
+    }
+}
+
+const STREAM_CONSUMED_MESSAGE: &str = "Response stream has already been consumed";
+
+fn is_streamed(response: &QueryResponse) -> bool {
+    matches!(response.body, QueryResponseBody::Stream(_))
+}
+
+/// Server-sent events for a streamed field: a `next` event with the data for each item (or the
+/// errors, if producing an item fails), followed by a `complete` event.
+fn event_stream(
+    name: String,
+    stream: Option<JsonValueStream>,
+    compact_response: bool,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send {
+    try_stream! {
+        match stream {
+            Some(mut stream) => {
+                while let Some(item) = stream.next().await {
+                    let payload = match item {
+                        Ok(item) if compact_response => {
+                            json!({ "data": { name.as_str(): compact(item) } })
+                        }
+                        Ok(item) => json!({ "data": { name.as_str(): item } }),
+                        Err(message) => json!({ "errors": [{ "message": message }] }),
+                    };
+                    yield Bytes::from(format!("event: next\ndata: {payload}\n\n"));
+                }
+            }
+            None => {
+                let payload = json!({ "errors": [{ "message": STREAM_CONSUMED_MESSAGE }] });
+                yield Bytes::from(format!("event: next\ndata: {payload}\n\n"));
+            }
+        }
+        yield Bytes::from_static(b"event: complete\ndata:\n\n");
+    }
 }
 
 pub async fn resolve_in_memory_for_payload(
@@ -329,3 +787,90 @@ pub async fn resolve_in_memory_for_payload(
         })
         .and(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_stream(items: Vec<Result<JsonValue, String>>) -> JsonValueStream {
+        Box::pin(futures::stream::iter(items))
+    }
+
+    async fn event_stream_text(stream: Option<JsonValueStream>, compact_response: bool) -> String {
+        let chunks: Vec<Bytes> = event_stream("rows".to_string(), stream, compact_response)
+            .map(|chunk| chunk.expect("Event stream should not fail"))
+            .collect()
+            .await;
+
+        chunks
+            .iter()
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn event_stream_sends_each_item_as_a_next_event() {
+        let stream = item_stream(vec![
+            Ok(json!({ "id": 1, "title": null })),
+            Ok(json!({ "id": 2, "title": "Second" })),
+        ]);
+
+        assert_eq!(
+            event_stream_text(Some(stream), false).await,
+            concat!(
+                "event: next\ndata: {\"data\":{\"rows\":{\"id\":1,\"title\":null}}}\n\n",
+                "event: next\ndata: {\"data\":{\"rows\":{\"id\":2,\"title\":\"Second\"}}}\n\n",
+                "event: complete\ndata:\n\n",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn event_stream_of_no_items() {
+        assert_eq!(
+            event_stream_text(Some(item_stream(vec![])), false).await,
+            "event: complete\ndata:\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn event_stream_compacts_items() {
+        let stream = item_stream(vec![Ok(json!({ "id": 1, "title": null, "tags": [] }))]);
+
+        assert_eq!(
+            event_stream_text(Some(stream), true).await,
+            concat!(
+                "event: next\ndata: {\"data\":{\"rows\":{\"id\":1}}}\n\n",
+                "event: complete\ndata:\n\n",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn event_stream_reports_errors_after_the_items_so_far() {
+        let stream = item_stream(vec![
+            Ok(json!({ "id": 1 })),
+            Err("Export failed: \"rows\" unavailable".to_string()),
+        ]);
+
+        assert_eq!(
+            event_stream_text(Some(stream), false).await,
+            concat!(
+                "event: next\ndata: {\"data\":{\"rows\":{\"id\":1}}}\n\n",
+                "event: next\ndata: {\"errors\":[{\"message\":\"Export failed: \\\"rows\\\" unavailable\"}]}\n\n",
+                "event: complete\ndata:\n\n",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn event_stream_of_a_consumed_stream() {
+        assert_eq!(
+            event_stream_text(None, false).await,
+            concat!(
+                "event: next\ndata: {\"errors\":[{\"message\":\"Response stream has already been consumed\"}]}\n\n",
+                "event: complete\ndata:\n\n",
+            )
+        );
+    }
+}
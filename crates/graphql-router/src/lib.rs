@@ -12,9 +12,15 @@
 ///
 /// The `resolve` function is responsible for doing the work, using information
 /// extracted from an incoming request, and returning the response as a stream.
+mod compact_response;
+mod error_report;
 mod graphql_router;
+mod message_catalog;
+mod response_anomaly;
+mod route_router;
 mod system_loader;
 
 pub use graphql_router::GraphQLRouter;
 pub use graphql_router::resolve_in_memory_for_payload;
 pub use introspection_resolver::IntrospectionResolver;
+pub use route_router::RouteRouter;
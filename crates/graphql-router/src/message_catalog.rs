@@ -0,0 +1,189 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Translated error messages, selected through the `Accept-Language` header.
+//!
+//! The catalog is a directory (set through `EXO_ERROR_MESSAGES_DIR`) with a JSON file per locale
+//! (such as `fr.json` or `pt-BR.json`), each mapping error codes to message templates:
+//!
+//! ```json
+//! {
+//!   "NOT_AUTHORIZED": "Non autorisé",
+//!   "INVALID_FIELD": "Le champ '{field}' n'est pas valide pour le type '{type}'"
+//! }
+//! ```
+//!
+//! Errors without a template for the selected locale use the default (English) message.
+
+use std::{collections::HashMap, path::Path};
+
+use core_resolver::system_resolver::SystemResolutionError;
+use core_router::SystemLoadingError;
+
+#[derive(Debug, Default)]
+pub(crate) struct MessageCatalog {
+    /// Templates by error code for each locale (keyed by the lowercased locale such as `pt-br`)
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageCatalog {
+    pub fn load(dir: &Path) -> Result<Self, SystemLoadingError> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| SystemLoadingError::FileOpen(dir.display().to_string(), e))?;
+
+        let mut locales = HashMap::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| SystemLoadingError::FileOpen(dir.display().to_string(), e))?
+                .path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| SystemLoadingError::FileOpen(path.display().to_string(), e))?;
+            let templates: HashMap<String, String> =
+                serde_json::from_str(&content).map_err(|e| {
+                    SystemLoadingError::Config(format!(
+                        "Invalid error messages in {}: {e}",
+                        path.display()
+                    ))
+                })?;
+
+            locales.insert(locale.to_lowercase(), templates);
+        }
+
+        Ok(Self { locales })
+    }
+
+    /// The message for the error in the most preferred locale of the `Accept-Language` header
+    /// that has a template for it (along with that locale)
+    pub fn localize(
+        &self,
+        accept_language: Option<&str>,
+        error: &SystemResolutionError,
+    ) -> Option<(String, String)> {
+        if self.locales.is_empty() {
+            return None;
+        }
+
+        let key = error.message_key()?;
+
+        preferred_locales(accept_language?)
+            .into_iter()
+            .find_map(|locale| {
+                let template = self.templates(&locale)?.get(key.code)?;
+                Some((key.render(template), locale))
+            })
+    }
+
+    /// The templates for the locale, falling back to its language (`fr` for `fr-CA`)
+    fn templates(&self, locale: &str) -> Option<&HashMap<String, String>> {
+        let locale = locale.to_lowercase();
+        self.locales.get(&locale).or_else(|| {
+            let (language, _) = locale.split_once('-')?;
+            self.locales.get(language)
+        })
+    }
+}
+
+/// The locales of an `Accept-Language` header (such as `fr-CA, fr;q=0.9, en;q=0.8`) from the most
+/// to the least preferred
+fn preferred_locales(accept_language: &str) -> Vec<String> {
+    let mut locales = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';').map(str::trim);
+            let locale = parts
+                .next()
+                .filter(|locale| !locale.is_empty() && *locale != "*")?;
+            let quality = parts
+                .find_map(|part| part.strip_prefix("q="))
+                .map_or(Some(1.0), |quality| quality.parse::<f32>().ok())?;
+
+            (quality > 0.0).then(|| (locale.to_string(), quality))
+        })
+        .collect::<Vec<_>>();
+
+    // Stable, so that locales with the same quality stay in the order of the header
+    locales.sort_by(|(_, q1), (_, q2)| q2.total_cmp(q1));
+
+    locales.into_iter().map(|(locale, _)| locale).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use core_resolver::plugin::SubsystemResolutionError;
+    use core_resolver::validation::validation_error::ValidationError;
+
+    use super::*;
+
+    fn catalog() -> MessageCatalog {
+        let french = HashMap::from([
+            ("NOT_AUTHORIZED".to_string(), "Non autorisé".to_string()),
+            (
+                "REQUIRED_ARGUMENT_NOT_FOUND".to_string(),
+                "L'argument obligatoire '{argument}' est absent".to_string(),
+            ),
+        ]);
+        let german = HashMap::from([(
+            "NOT_AUTHORIZED".to_string(),
+            "Nicht autorisiert".to_string(),
+        )]);
+
+        MessageCatalog {
+            locales: HashMap::from([("fr".to_string(), french), ("de".to_string(), german)]),
+        }
+    }
+
+    #[test]
+    fn parses_accept_language() {
+        assert_eq!(
+            preferred_locales("en;q=0.5, fr-CA, de;q=0.8, *;q=0.1, es;q=0"),
+            vec!["fr-CA", "de", "en"]
+        );
+        assert!(preferred_locales("").is_empty());
+    }
+
+    #[test]
+    fn localizes_errors() {
+        let catalog = catalog();
+        let not_authorized = SystemResolutionError::SubsystemResolutionError(
+            SubsystemResolutionError::Authorization,
+        );
+
+        assert_eq!(
+            catalog.localize(Some("fr-CA, de;q=0.9"), &not_authorized),
+            Some(("Non autorisé".to_string(), "fr-CA".to_string()))
+        );
+        assert_eq!(
+            catalog.localize(Some("ja, de;q=0.5"), &not_authorized),
+            Some(("Nicht autorisiert".to_string(), "de".to_string()))
+        );
+        assert_eq!(catalog.localize(Some("ja"), &not_authorized), None);
+        assert_eq!(catalog.localize(None, &not_authorized), None);
+
+        let missing_argument = SystemResolutionError::Validation(
+            ValidationError::RequiredArgumentNotFound("id".to_string(), Default::default()),
+        );
+        assert_eq!(
+            catalog.localize(Some("fr"), &missing_argument),
+            Some((
+                "L'argument obligatoire 'id' est absent".to_string(),
+                "fr".to_string()
+            ))
+        );
+
+        // No German template, so the default message is used
+        assert_eq!(catalog.localize(Some("de"), &missing_argument), None);
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Detection of responses that deviate strongly from their operation's baseline (such as a query
+//! that lost its `limit` in a client change and now returns an entire table).
+//!
+//! Set `EXO_RESPONSE_ANOMALY_FACTOR` to enable it. For each sampled response, the detector tracks
+//! the size of each top-level field (by its alias, so that `recent: concerts(limit: 10)` and
+//! `all: concerts` have separate baselines) and the latency of the operation (by its name). A value
+//! more than the factor times its baseline is logged as a warning and, if Sentry is enabled,
+//! reported to it.
+//!
+//! Baselines are moving averages kept in memory, so each server instance learns its own, and a
+//! lasting change (an operation that legitimately returns more data) becomes the new baseline
+//! after a few responses.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use common::env_const::{EXO_RESPONSE_ANOMALY_FACTOR, EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL};
+use exo_env::{EnvError, Environment};
+use sentry::Level;
+
+/// The number of samples needed before a baseline is used to detect anomalies
+const WARM_UP_SAMPLES: u64 = 20;
+/// The weight of a new sample in a baseline (once warmed up)
+const SMOOTHING: f64 = 0.1;
+/// The maximum number of baselines (to bound memory with many distinct operations)
+const MAX_BASELINES: usize = 1000;
+/// Sizes (in bytes) and latencies (in milliseconds) below these are never anomalous, so that
+/// small absolute changes (an empty list that now has a few elements) aren't reported
+const MIN_ANOMALOUS_SIZE: f64 = 10_000.0;
+const MIN_ANOMALOUS_LATENCY: f64 = 100.0;
+
+const ANONYMOUS_OPERATION: &str = "<anonymous>";
+
+#[derive(Debug, Default)]
+pub(crate) struct ResponseAnomalyDetector {
+    config: Option<AnomalyConfig>,
+    baselines: Mutex<HashMap<(String, Metric), Baseline>>,
+    responses: AtomicU64,
+}
+
+#[derive(Debug)]
+struct AnomalyConfig {
+    factor: f64,
+    sample_interval: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Metric {
+    /// The size of a top-level field in bytes
+    Size,
+    /// The time to produce the response in milliseconds
+    Latency,
+}
+
+impl Metric {
+    fn name(&self) -> &'static str {
+        match self {
+            Metric::Size => "size",
+            Metric::Latency => "latency",
+        }
+    }
+
+    fn min_anomalous_value(&self) -> f64 {
+        match self {
+            Metric::Size => MIN_ANOMALOUS_SIZE,
+            Metric::Latency => MIN_ANOMALOUS_LATENCY,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Baseline {
+    samples: u64,
+    mean: f64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Anomaly {
+    key: String,
+    metric: Metric,
+    value: f64,
+    baseline: f64,
+}
+
+impl ResponseAnomalyDetector {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, EnvError> {
+        let Some(factor) = env.get(EXO_RESPONSE_ANOMALY_FACTOR) else {
+            return Ok(Self::default());
+        };
+
+        let factor = factor
+            .parse::<f64>()
+            .ok()
+            .filter(|factor| *factor > 1.0)
+            .ok_or_else(|| EnvError::InvalidEnum {
+                env_key: EXO_RESPONSE_ANOMALY_FACTOR,
+                env_value: factor.clone(),
+                message: "Must be a number greater than 1".to_string(),
+            })?;
+
+        let sample_interval = match env.get(EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL) {
+            Some(value) => value
+                .parse::<u64>()
+                .ok()
+                .filter(|interval| *interval > 0)
+                .ok_or_else(|| EnvError::InvalidEnum {
+                    env_key: EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL,
+                    env_value: value.clone(),
+                    message: "Must be a positive number".to_string(),
+                })?,
+            None => 1,
+        };
+
+        Ok(Self {
+            config: Some(AnomalyConfig {
+                factor,
+                sample_interval,
+            }),
+            ..Self::default()
+        })
+    }
+
+    /// Start sampling a response (`None` if detection is disabled or the response isn't sampled)
+    pub fn sample(
+        self: &Arc<Self>,
+        operation_name: Option<&str>,
+        started_at: Instant,
+    ) -> Option<ResponseSample> {
+        let config = self.config.as_ref()?;
+
+        let response = self.responses.fetch_add(1, Ordering::Relaxed);
+        if response % config.sample_interval != 0 {
+            return None;
+        }
+
+        Some(ResponseSample {
+            detector: self.clone(),
+            operation: operation_name.unwrap_or(ANONYMOUS_OPERATION).to_string(),
+            started_at,
+            field_sizes: vec![],
+            streamed: false,
+        })
+    }
+
+    /// Add a sample to the baseline for the key, returning an anomaly if the value deviates
+    /// strongly from the baseline (before adding it)
+    fn observe(&self, key: &str, metric: Metric, value: f64) -> Option<Anomaly> {
+        let factor = self.config.as_ref()?.factor;
+
+        let mut baselines = self.baselines.lock().unwrap();
+        let baseline_key = (key.to_string(), metric);
+        if !baselines.contains_key(&baseline_key) && baselines.len() >= MAX_BASELINES {
+            tracing::debug!("Not tracking response {} of '{key}'", metric.name());
+            return None;
+        }
+        let baseline = baselines.entry(baseline_key).or_default();
+
+        let anomaly = (baseline.samples >= WARM_UP_SAMPLES
+            && value >= metric.min_anomalous_value()
+            && value > baseline.mean * factor)
+            .then(|| Anomaly {
+                key: key.to_string(),
+                metric,
+                value,
+                baseline: baseline.mean,
+            });
+
+        // A cumulative average while warming up (so that the first samples don't dominate), and
+        // a moving one afterwards (so that lasting changes become the new baseline)
+        baseline.samples += 1;
+        let weight = (1.0 / baseline.samples as f64).max(SMOOTHING);
+        baseline.mean += (value - baseline.mean) * weight;
+
+        anomaly
+    }
+}
+
+/// The measurements of a response being produced
+pub(crate) struct ResponseSample {
+    detector: Arc<ResponseAnomalyDetector>,
+    operation: String,
+    started_at: Instant,
+    /// The size of each top-level field (by alias)
+    field_sizes: Vec<(String, usize)>,
+    /// Whether the response includes a streamed field (whose latency depends on the items
+    /// produced, so isn't compared to a baseline)
+    streamed: bool,
+}
+
+impl ResponseSample {
+    pub fn add_field_size(&mut self, alias: &str, size: usize) {
+        match self.field_sizes.last_mut() {
+            Some((last_alias, last_size)) if last_alias == alias => *last_size += size,
+            _ => self.field_sizes.push((alias.to_string(), size)),
+        }
+    }
+
+    pub fn mark_streamed(&mut self) {
+        self.streamed = true;
+    }
+
+    /// Compare the measurements to their baselines (once the response is complete), reporting any
+    /// anomalies
+    pub fn finish(self) {
+        let latency = self.started_at.elapsed().as_secs_f64() * 1000.0;
+
+        let size_anomalies = self.field_sizes.iter().filter_map(|(alias, size)| {
+            self.detector.observe(
+                &format!("{}.{alias}", self.operation),
+                Metric::Size,
+                *size as f64,
+            )
+        });
+        let latency_anomaly = (!self.streamed)
+            .then(|| {
+                self.detector
+                    .observe(&self.operation, Metric::Latency, latency)
+            })
+            .flatten();
+
+        for anomaly in size_anomalies.chain(latency_anomaly) {
+            report_anomaly(&self.operation, &anomaly);
+        }
+    }
+}
+
+fn report_anomaly(operation: &str, anomaly: &Anomaly) {
+    let message = format!(
+        "Response {} of '{}' ({:.0}) deviates from its baseline ({:.0})",
+        anomaly.metric.name(),
+        anomaly.key,
+        anomaly.value,
+        anomaly.baseline
+    );
+    tracing::warn!(
+        operation,
+        metric = anomaly.metric.name(),
+        value = anomaly.value,
+        baseline = anomaly.baseline,
+        "{message}"
+    );
+
+    if sentry::Hub::current().client().is_none() {
+        return;
+    }
+
+    sentry::with_scope(
+        |scope| {
+            scope.set_tag("graphql.operation_name", operation);
+            scope.set_tag("anomaly.metric", anomaly.metric.name());
+            scope.set_extra("value", anomaly.value.into());
+            scope.set_extra("baseline", anomaly.baseline.into());
+            scope.set_fingerprint(Some(
+                [
+                    "response-anomaly",
+                    anomaly.metric.name(),
+                    anomaly.key.as_str(),
+                ]
+                .as_slice(),
+            ));
+        },
+        || {
+            sentry::capture_message(&message, Level::Warning);
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    fn detector(values: &[(&str, &str)]) -> Result<ResponseAnomalyDetector, EnvError> {
+        let env = MapEnvironment::from(
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        );
+        ResponseAnomalyDetector::from_env(&env)
+    }
+
+    fn warm_up(detector: &ResponseAnomalyDetector, key: &str, metric: Metric, value: f64) {
+        for _ in 0..WARM_UP_SAMPLES {
+            assert_eq!(detector.observe(key, metric, value), None);
+        }
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let detector = Arc::new(detector(&[]).unwrap());
+
+        assert!(detector.sample(Some("q"), Instant::now()).is_none());
+        assert_eq!(detector.observe("q.a", Metric::Size, 1e9), None);
+    }
+
+    #[test]
+    fn detects_deviations_after_warm_up() {
+        let detector = detector(&[(EXO_RESPONSE_ANOMALY_FACTOR, "5")]).unwrap();
+
+        // Not compared while warming up
+        assert_eq!(detector.observe("q.a", Metric::Size, 20_000.0), None);
+        warm_up(&detector, "q.a", Metric::Size, 20_000.0);
+
+        assert_eq!(detector.observe("q.a", Metric::Size, 90_000.0), None);
+        let anomaly = detector.observe("q.a", Metric::Size, 1_000_000.0).unwrap();
+        assert_eq!(anomaly.key, "q.a");
+        assert_eq!(anomaly.metric, Metric::Size);
+
+        // Each alias has its own baseline
+        assert_eq!(detector.observe("q.b", Metric::Size, 1_000_000.0), None);
+    }
+
+    #[test]
+    fn ignores_small_values() {
+        let detector = detector(&[(EXO_RESPONSE_ANOMALY_FACTOR, "5")]).unwrap();
+
+        warm_up(&detector, "q.a", Metric::Size, 10.0);
+        assert_eq!(detector.observe("q.a", Metric::Size, 5_000.0), None);
+
+        warm_up(&detector, "q", Metric::Latency, 1.0);
+        assert_eq!(detector.observe("q", Metric::Latency, 50.0), None);
+        assert!(detector.observe("q", Metric::Latency, 500.0).is_some());
+    }
+
+    #[test]
+    fn adapts_to_lasting_changes() {
+        let detector = detector(&[(EXO_RESPONSE_ANOMALY_FACTOR, "5")]).unwrap();
+
+        warm_up(&detector, "q.a", Metric::Size, 20_000.0);
+
+        let anomalies = (0..50)
+            .filter(|_| detector.observe("q.a", Metric::Size, 500_000.0).is_some())
+            .count();
+        assert!(anomalies > 0 && anomalies < 50);
+        assert_eq!(detector.observe("q.a", Metric::Size, 500_000.0), None);
+    }
+
+    #[test]
+    fn samples_at_interval() {
+        let detector = Arc::new(
+            detector(&[
+                (EXO_RESPONSE_ANOMALY_FACTOR, "5"),
+                (EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL, "3"),
+            ])
+            .unwrap(),
+        );
+
+        let sampled = (0..9)
+            .filter(|_| detector.sample(None, Instant::now()).is_some())
+            .count();
+        assert_eq!(sampled, 3);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(detector(&[(EXO_RESPONSE_ANOMALY_FACTOR, "1")]).is_err());
+        assert!(detector(&[(EXO_RESPONSE_ANOMALY_FACTOR, "ten")]).is_err());
+        assert!(
+            detector(&[
+                (EXO_RESPONSE_ANOMALY_FACTOR, "5"),
+                (EXO_RESPONSE_ANOMALY_SAMPLE_INTERVAL, "0")
+            ])
+            .is_err()
+        );
+    }
+}
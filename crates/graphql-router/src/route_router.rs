@@ -0,0 +1,413 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Routes that the model declares outside the GraphQL endpoint (see
+//! [`core_plugin_shared::routes`]): redirects, and paths served by an operation (such as a Deno
+//! query serving `/robots.txt`).
+//!
+//! The operation of a route executes as if a client had sent it to the GraphQL endpoint (so access
+//! control, interceptors, and response headers such as cookies apply), and its value describes the
+//! response. A `String` value is the body of a `text/plain` response. A `Json` value is an object
+//! with the optional `status` (200 by default), `headers`, `contentType`, and `body` (served as
+//! is if a string, and as JSON otherwise) or `redirect` (a location to redirect to). A `null`
+//! value is a 404 response.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use http::{Method, StatusCode};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use common::{
+    context::RequestContext,
+    http::{Headers, RequestHead, ResponseBody, ResponsePayload},
+    operation_payload::OperationsPayload,
+    router::Router,
+};
+use core_plugin_shared::{
+    routes::{DeclaredRoute, RouteOperation, RouteTarget},
+    trusted_documents::TrustedDocumentEnforcement,
+};
+use core_resolver::{
+    QueryResponseBody,
+    plugin::SubsystemResolutionError,
+    system_resolver::{GraphQLSystemResolver, SystemResolutionError},
+};
+
+use crate::graphql_router::{
+    maintenance_response, operation_limit_response, replay_response, resolve_in_memory_for_payload,
+    unavailable_response,
+};
+
+pub struct RouteRouter {
+    resolver: Arc<GraphQLSystemResolver>,
+    /// The target of each route (keyed by its path)
+    routes: HashMap<String, RouteTarget>,
+}
+
+impl RouteRouter {
+    pub fn new(routes: &[DeclaredRoute], resolver: Arc<GraphQLSystemResolver>) -> Self {
+        Self {
+            resolver,
+            routes: routes
+                .iter()
+                .map(|route| (route.path.clone(), route.target.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    async fn resolve(
+        &self,
+        operation: &RouteOperation,
+        request_context: &RequestContext<'_>,
+    ) -> ResponsePayload {
+        let query = request_context.get_head().get_query();
+
+        let mut variables = JsonMap::new();
+        for argument in operation.arguments.iter() {
+            match query.get(&argument.name).and_then(JsonValue::as_str) {
+                Some(value) => {
+                    variables.insert(argument.name.clone(), JsonValue::String(value.to_string()));
+                }
+                None if argument.optional => {}
+                None => {
+                    return text_response(
+                        format!("Missing query parameter '{}'", argument.name),
+                        StatusCode::BAD_REQUEST,
+                    );
+                }
+            }
+        }
+
+        let operations_payload = OperationsPayload {
+            operation_name: None,
+            query: Some(operation_document(operation)),
+            variables: Some(variables),
+            query_hash: None,
+        };
+
+        // The document comes from the model (not from the client), so it is trusted
+        let response = resolve_in_memory_for_payload(
+            operations_payload,
+            &self.resolver,
+            TrustedDocumentEnforcement::DoNotEnforce,
+            request_context,
+        )
+        .await;
+
+        let (value, headers) = match response {
+            Ok(mut parts) if !parts.is_empty() => {
+                let (_, part) = parts.swap_remove(0);
+                let value = match &part.body {
+                    QueryResponseBody::Stream(stream) => stream.collect().await,
+                    body => body.to_json().map_err(|e| e.to_string()),
+                };
+                match value {
+                    Ok(value) => (value, part.headers),
+                    Err(e) => {
+                        tracing::error!("Invalid value of operation '{}': {e}", operation.name);
+                        return internal_error_response();
+                    }
+                }
+            }
+            Ok(_) => return internal_error_response(),
+            Err(e) => return error_response(&e),
+        };
+
+        match route_response(value, Headers::from_vec(headers)) {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::error!("Invalid response of operation '{}': {e}", operation.name);
+                internal_error_response()
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> Router<RequestContext<'a>> for RouteRouter {
+    async fn route(&self, request_context: &RequestContext<'a>) -> Option<ResponsePayload> {
+        let request_head = request_context.get_head();
+        let target = self.routes.get(&request_head.get_path())?;
+
+        match target {
+            RouteTarget::Redirect { location } => Some(ResponsePayload {
+                body: ResponseBody::Redirect(location.clone()),
+                headers: Headers::new(),
+                status_code: StatusCode::FOUND,
+            }),
+            RouteTarget::Operation(operation) => {
+                let method = request_head.get_method();
+                let allowed = if operation.mutation {
+                    method == Method::POST
+                } else {
+                    method == Method::GET || method == Method::HEAD
+                };
+
+                if !allowed {
+                    let mut headers = Headers::new();
+                    let allow = if operation.mutation {
+                        "POST"
+                    } else {
+                        "GET, HEAD"
+                    };
+                    headers.insert("allow".into(), allow.into());
+
+                    return Some(ResponsePayload {
+                        body: ResponseBody::None,
+                        headers,
+                        status_code: StatusCode::METHOD_NOT_ALLOWED,
+                    });
+                }
+
+                Some(self.resolve(operation, request_context).await)
+            }
+        }
+    }
+}
+
+/// The GraphQL document that executes the operation (with a variable for each argument)
+fn operation_document(operation: &RouteOperation) -> String {
+    let operation_type = if operation.mutation {
+        "mutation"
+    } else {
+        "query"
+    };
+
+    if operation.arguments.is_empty() {
+        return format!("{operation_type} {{ {} }}", operation.name);
+    }
+
+    let variables = operation
+        .arguments
+        .iter()
+        .map(|argument| {
+            let required = if argument.optional { "" } else { "!" };
+            format!("${}: String{required}", argument.name)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let arguments = operation
+        .arguments
+        .iter()
+        .map(|argument| format!("{0}: ${0}", argument.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{operation_type}({variables}) {{ {}({arguments}) }}",
+        operation.name
+    )
+}
+
+/// The response described by the value of an operation (added to the headers that the operation
+/// set, such as cookies)
+fn route_response(value: JsonValue, mut headers: Headers) -> Result<ResponsePayload, String> {
+    let mut description = match value {
+        JsonValue::Null => {
+            headers.insert("content-type".into(), "text/plain; charset=utf-8".into());
+            return Ok(ResponsePayload {
+                body: ResponseBody::Bytes(b"Not found".to_vec()),
+                headers,
+                status_code: StatusCode::NOT_FOUND,
+            });
+        }
+        JsonValue::String(body) => {
+            headers.insert("content-type".into(), "text/plain; charset=utf-8".into());
+            return Ok(ResponsePayload {
+                body: ResponseBody::Bytes(body.into_bytes()),
+                headers,
+                status_code: StatusCode::OK,
+            });
+        }
+        JsonValue::Object(description) => description,
+        _ => return Err("expected a string, an object, or null".to_string()),
+    };
+
+    let status_code = match description.remove("status") {
+        None => None,
+        Some(status) => Some(
+            status
+                .as_u64()
+                .and_then(|status| u16::try_from(status).ok())
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .ok_or_else(|| format!("invalid status {status}"))?,
+        ),
+    };
+
+    if let Some(declared_headers) = description.remove("headers") {
+        let JsonValue::Object(declared_headers) = declared_headers else {
+            return Err("expected the headers as an object".to_string());
+        };
+        for (name, value) in declared_headers {
+            let JsonValue::String(value) = value else {
+                return Err(format!("expected a string value for the header '{name}'"));
+            };
+            headers.insert(name, value);
+        }
+    }
+
+    if let Some(location) = description.remove("redirect") {
+        let JsonValue::String(location) = location else {
+            return Err("expected the redirect location as a string".to_string());
+        };
+        return Ok(ResponsePayload {
+            body: ResponseBody::Redirect(location),
+            headers,
+            status_code: status_code.unwrap_or(StatusCode::FOUND),
+        });
+    }
+
+    let content_type = match description.remove("contentType") {
+        None => None,
+        Some(JsonValue::String(content_type)) => Some(content_type),
+        Some(_) => return Err("expected the content type as a string".to_string()),
+    };
+
+    let (body, default_content_type) = match description.remove("body") {
+        None | Some(JsonValue::Null) => (ResponseBody::None, None),
+        Some(JsonValue::String(body)) => (
+            ResponseBody::Bytes(body.into_bytes()),
+            Some("text/plain; charset=utf-8"),
+        ),
+        Some(body) => (
+            ResponseBody::Bytes(body.to_string().into_bytes()),
+            Some("application/json"),
+        ),
+    };
+
+    if let Some(content_type) = content_type.or(default_content_type.map(str::to_string)) {
+        headers.insert("content-type".into(), content_type);
+    }
+
+    Ok(ResponsePayload {
+        body,
+        headers,
+        status_code: status_code.unwrap_or(StatusCode::OK),
+    })
+}
+
+fn error_response(error: &SystemResolutionError) -> ResponsePayload {
+    match error {
+        SystemResolutionError::Maintenance(e) => maintenance_response(e),
+        SystemResolutionError::OperationLimit(e) => operation_limit_response(e),
+        SystemResolutionError::Replay(e) => replay_response(e),
+        SystemResolutionError::SubsystemResolutionError(
+            SubsystemResolutionError::Unavailable {
+                message,
+                retry_after_secs,
+            },
+        ) => unavailable_response(message, *retry_after_secs),
+        SystemResolutionError::SubsystemResolutionError(
+            SubsystemResolutionError::Authorization,
+        ) => text_response("Not authorized".to_string(), StatusCode::FORBIDDEN),
+        error => match error.explicit_message() {
+            Some(message) => text_response(message, StatusCode::BAD_REQUEST),
+            None => {
+                tracing::error!("Error while resolving route: {error}");
+                internal_error_response()
+            }
+        },
+    }
+}
+
+fn internal_error_response() -> ResponsePayload {
+    text_response(
+        "Internal server error".to_string(),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    )
+}
+
+fn text_response(message: String, status_code: StatusCode) -> ResponsePayload {
+    let mut headers = Headers::new();
+    headers.insert("content-type".into(), "text/plain; charset=utf-8".into());
+
+    ResponsePayload {
+        body: ResponseBody::Bytes(message.into_bytes()),
+        headers,
+        status_code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_plugin_shared::routes::RouteArgument;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn operation_documents() {
+        let operation = |mutation, arguments: &[(&str, bool)]| RouteOperation {
+            name: "oauthCallback".to_string(),
+            mutation,
+            arguments: arguments
+                .iter()
+                .map(|(name, optional)| RouteArgument {
+                    name: name.to_string(),
+                    optional: *optional,
+                })
+                .collect(),
+        };
+
+        assert_eq!(
+            operation_document(&operation(false, &[])),
+            "query { oauthCallback }"
+        );
+        assert_eq!(
+            operation_document(&operation(true, &[("code", false), ("state", true)])),
+            "mutation($code: String!, $state: String) { oauthCallback(code: $code, state: $state) }"
+        );
+    }
+
+    #[tokio::test]
+    async fn described_responses() {
+        let response = route_response(
+            json!({"status": 201, "body": {"id": 1}, "headers": {"X-Id": "1"}}),
+            Headers::new(),
+        )
+        .unwrap();
+        assert_eq!(response.status_code, StatusCode::CREATED);
+        assert_eq!(
+            response.headers.get("content-type").as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(response.headers.get("x-id").as_deref(), Some("1"));
+        assert_eq!(response.body.to_json().await.unwrap(), json!({"id": 1}));
+
+        let response = route_response(
+            json!({"body": "User-agent: *", "contentType": "text/plain"}),
+            Headers::from_vec(vec![("Set-Cookie".to_string(), "a=b".to_string())]),
+        )
+        .unwrap();
+        assert_eq!(response.status_code, StatusCode::OK);
+        assert_eq!(
+            response.headers.get("content-type").as_deref(),
+            Some("text/plain")
+        );
+        assert_eq!(response.headers.get("set-cookie").as_deref(), Some("a=b"));
+        assert_eq!(response.body.to_string().await.unwrap(), "User-agent: *");
+
+        let response = route_response(json!({"redirect": "/welcome"}), Headers::new()).unwrap();
+        assert_eq!(response.status_code, StatusCode::FOUND);
+        assert!(
+            matches!(response.body, ResponseBody::Redirect(location) if location == "/welcome")
+        );
+
+        let response = route_response(JsonValue::Null, Headers::new()).unwrap();
+        assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+
+        assert!(route_response(json!({"status": 1000}), Headers::new()).is_err());
+        assert!(route_response(json!(42), Headers::new()).is_err());
+    }
+}
@@ -9,21 +9,25 @@
 
 use std::sync::Arc;
 
+use common::maintenance::MaintenanceMode;
 use core_plugin_shared::interception::InterceptionMap;
 use core_plugin_shared::trusted_documents::TrustedDocuments;
 use core_router::SystemLoadingError;
 
+use common::env_const::{EXO_MAX_INTROSPECTION_SELECTION_DEPTH, EXO_MAX_SELECTION_DEPTH};
 use core_resolver::plugin::SubsystemGraphQLResolver;
 use core_resolver::{
-    introspection::definition::schema::Schema, system_resolver::GraphQLSystemResolver,
+    introspection::definition::schema::Schema, operation_limiter::OperationLimiter,
+    replay_guard::ReplayGuard, response_headers::DeclaredResponseHeaders,
+    system_resolver::GraphQLSystemResolver, trusted_services::TrustedServices,
+    validation::document_limits::DocumentLimits,
 };
 use exo_env::Environment;
 
 pub struct SystemLoader;
 
-const EXO_MAX_SELECTION_DEPTH: &str = "EXO_MAX_SELECTION_DEPTH";
-
 impl SystemLoader {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_system_resolver(
         mut subsystem_resolvers: Vec<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
         introspection_resolver: Option<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
@@ -32,6 +36,9 @@ impl SystemLoader {
         trusted_documents: TrustedDocuments,
         env: Arc<dyn Environment>,
         schema: Arc<Schema>,
+        maintenance_mode: Arc<MaintenanceMode>,
+        operation_limiter: Arc<OperationLimiter>,
+        response_headers: Arc<DeclaredResponseHeaders>,
     ) -> Result<GraphQLSystemResolver, SystemLoadingError> {
         if let Some(introspection_resolver) = introspection_resolver {
             subsystem_resolvers.push(introspection_resolver);
@@ -39,6 +46,9 @@ impl SystemLoader {
 
         let (normal_query_depth_limit, introspection_query_depth_limit) =
             query_depth_limits(env.as_ref())?;
+        let document_limits = DocumentLimits::from_env(env.as_ref())?;
+        let trusted_services = TrustedServices::from_env(env.as_ref())?;
+        let replay_guard = ReplayGuard::from_env(env.as_ref())?;
 
         Ok(GraphQLSystemResolver::new(
             subsystem_resolvers,
@@ -49,26 +59,45 @@ impl SystemLoader {
             env,
             normal_query_depth_limit,
             introspection_query_depth_limit,
+            document_limits,
+            maintenance_mode,
+            operation_limiter,
+            response_headers,
+            trusted_services,
+            replay_guard,
         ))
     }
 }
 
-/// Returns the maximum depth of a selection set for normal queries and introspection queries. We
-/// hard-code the introspection query depth to 15 to accommodate the query invoked by GraphQL
+/// Returns the maximum depth of a selection set for normal queries and introspection queries. The
+/// introspection query depth defaults to 15 to accommodate the query invoked by GraphQL
 /// Playground
 pub fn query_depth_limits(env: &dyn Environment) -> Result<(usize, usize), SystemLoadingError> {
     const DEFAULT_QUERY_DEPTH: usize = 5;
     const DEFAULT_INTROSPECTION_QUERY_DEPTH: usize = 15;
 
-    let query_depth = match env.get(EXO_MAX_SELECTION_DEPTH) {
+    let query_depth = depth_limit(env, EXO_MAX_SELECTION_DEPTH, DEFAULT_QUERY_DEPTH)?;
+    let introspection_query_depth = depth_limit(
+        env,
+        EXO_MAX_INTROSPECTION_SELECTION_DEPTH,
+        DEFAULT_INTROSPECTION_QUERY_DEPTH,
+    )?;
+
+    Ok((query_depth, introspection_query_depth))
+}
+
+fn depth_limit(
+    env: &dyn Environment,
+    env_key: &str,
+    default_value: usize,
+) -> Result<usize, SystemLoadingError> {
+    match env.get(env_key) {
         Some(e) => match e.parse::<usize>() {
             Ok(v) => Ok(v),
             Err(_) => Err(SystemLoadingError::Config(format!(
-                "{EXO_MAX_SELECTION_DEPTH} env var must be set to a positive integer"
+                "{env_key} env var must be set to a positive integer"
             ))),
         },
-        None => Ok(DEFAULT_QUERY_DEPTH),
-    }?;
-
-    Ok((query_depth, DEFAULT_INTROSPECTION_QUERY_DEPTH))
+        None => Ok(default_value),
+    }
 }
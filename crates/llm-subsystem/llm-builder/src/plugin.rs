@@ -0,0 +1,179 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+
+use core_model_builder::{
+    ast::ast_types::{AstExpr, default_span},
+    builder::system_builder::BaseModelSystem,
+    error::ModelBuildingError,
+    plugin::{BuildMode, CoreSubsystemBuild, GraphQLSubsystemBuild, Interception},
+    typechecker::{
+        annotation::{AnnotationSpec, AnnotationTarget, MappedAnnotationParamSpec},
+        typ::TypecheckedSystem,
+    },
+};
+use core_plugin_interface::interface::{SubsystemBuild, SubsystemBuilder};
+use core_plugin_shared::{
+    interception::{InterceptorErrorPolicy, InterceptorIndex, InterceptorKind},
+    serializable_system::{SerializableCoreBytes, SerializableGraphQLBytes},
+    system_serializer::SystemSerializer,
+};
+
+#[derive(Default)]
+pub struct LlmSubsystemBuilder {}
+
+#[async_trait]
+impl SubsystemBuilder for LlmSubsystemBuilder {
+    fn id(&self) -> &'static str {
+        "llm"
+    }
+
+    fn annotations(&self) -> Vec<(&'static str, AnnotationSpec)> {
+        vec![
+            (
+                // `@llm(provider: "openai", model: "...", embeddingModel: "...", baseUrl: "...", apiKeyEnv: "...")`
+                "llm",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Module],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "provider",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "model",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "embeddingModel",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "baseUrl",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "apiKeyEnv",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                // `@llmFunction("<function>")` (defaults to the method name)
+                "llmFunction",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Method],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                // `@embedding(source: "title + body", model: "<embedding query>")`
+                "embedding",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "source",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "model",
+                            optional: false,
+                        },
+                    ]),
+                },
+            ),
+            (
+                // `@embedInto(type: "<Type>", field: "<Vector field>")`
+                "embedInto",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Method],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "type",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "field",
+                            optional: false,
+                        },
+                    ]),
+                },
+            ),
+        ]
+    }
+
+    async fn build(
+        &self,
+        typechecked_system: &TypecheckedSystem,
+        base_system: &BaseModelSystem,
+        _build_mode: BuildMode,
+    ) -> Result<Option<SubsystemBuild>, ModelBuildingError> {
+        let Some(subsystem) = crate::system_builder::build(typechecked_system, base_system).await?
+        else {
+            return Ok(None);
+        };
+
+        let serialized_subsystem = subsystem
+            .serialize()
+            .map_err(ModelBuildingError::Serialize)?;
+
+        // Keep `@embedding` fields in sync by wrapping each create and update mutation of their entity
+        let interceptions = subsystem
+            .embedding_interceptors
+            .iter()
+            .enumerate()
+            .map(|(index, interceptor)| Interception {
+                expr: AstExpr::StringLiteral(
+                    format!("mutation {}", interceptor.mutation_name),
+                    default_span(),
+                ),
+                kind: InterceptorKind::Around,
+                index: InterceptorIndex(index),
+                error_policy: InterceptorErrorPolicy::Abort,
+            })
+            .collect();
+
+        let graphql = GraphQLSubsystemBuild {
+            id: self.id().to_string(),
+            serialized_subsystem: SerializableGraphQLBytes(serialized_subsystem),
+            query_names: subsystem
+                .queries
+                .iter()
+                .map(|(_, q)| q.name.clone())
+                .collect(),
+            mutation_names: subsystem
+                .mutations
+                .iter()
+                .map(|(_, q)| q.name.clone())
+                .collect(),
+            interceptions,
+        };
+
+        Ok(Some(SubsystemBuild {
+            id: self.id(),
+            graphql: Some(graphql),
+            rest: None,
+            rpc: None,
+            core: CoreSubsystemBuild {
+                serialized_subsystem: SerializableCoreBytes(vec![]),
+            },
+        }))
+    }
+}
@@ -0,0 +1,585 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{collections::HashMap, path::Path};
+
+use async_trait::async_trait;
+use core_model::{
+    mapped_arena::{MappedArena, SerializableSlab},
+    types::FieldType,
+};
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr, AstMethodType, AstModel, AstModule},
+    builder::{resolved_builder::AnnotationMapHelper, system_builder::BaseModelSystem},
+    error::ModelBuildingError,
+    typechecker::{
+        Typed,
+        typ::{Module, Type, TypecheckedSystem},
+    },
+};
+use heck::ToLowerCamelCase;
+use llm_model::{
+    embedding::{EmbeddingField, EmbeddingInterceptor, EmbeddingSyncKind},
+    function::{EmbeddingTarget, LlmClientConfig, LlmFunction, LlmOperation, LlmProvider},
+    module::{ModuleMethod, ModuleMethodType},
+    operation::{LlmMutation, LlmQuery},
+    subsystem::LlmSubsystem,
+    types::{ModuleOperationReturnType, ModuleType},
+};
+use postgres_core_builder::naming::ToPlural;
+use subsystem_model_builder_util::ScriptProcessor;
+
+pub async fn build(
+    typechecked_system: &TypecheckedSystem,
+    base_system: &BaseModelSystem,
+) -> Result<Option<LlmSubsystem>, ModelBuildingError> {
+    let module_selection_closure =
+        |module: &AstModule<Typed>| module.annotations.get("llm").map(|_| "llm".to_string());
+
+    let module_system = subsystem_model_builder_util::build_with_selection(
+        typechecked_system,
+        base_system,
+        module_selection_closure,
+        LlmScriptProcessor {},
+    )
+    .await?;
+
+    let underlying_module_system = module_system.underlying;
+
+    let (embedding_fields, embedding_interceptors) = embedding_fields(typechecked_system)?;
+
+    if underlying_module_system.queries.is_empty()
+        && underlying_module_system.mutations.is_empty()
+        && embedding_fields.is_empty()
+    {
+        return Ok(None);
+    }
+
+    if !underlying_module_system.interceptors.is_empty() {
+        return Err(ModelBuildingError::Generic(
+            "LLM modules may not declare interceptors".to_string(),
+        ));
+    }
+
+    let module_functions = llm_module_functions(typechecked_system)?;
+
+    let mut operations = MappedArena::default();
+    for (_, method) in underlying_module_system.methods.iter() {
+        // The "script" of an llm module records the module name (see `LlmScriptProcessor`)
+        let module_name = &underlying_module_system.scripts[method.script].path;
+        let LlmModuleFunctions { client, functions } = &module_functions[module_name];
+
+        let (function, embedding_target) = &functions[&method.name];
+        validate_method(
+            method,
+            *function,
+            embedding_target.as_ref(),
+            &underlying_module_system.module_types,
+        )?;
+
+        if function.uses_embeddings() && !client.provider.supports_embeddings() {
+            return Err(ModelBuildingError::Generic(format!(
+                "Method '{}' computes embeddings, which the '{}' provider does not support",
+                method.name,
+                client.provider.name()
+            )));
+        }
+
+        operations.add(
+            &method.name,
+            LlmOperation {
+                method_name: method.name.clone(),
+                function: *function,
+                client: client.clone(),
+                embedding_target: embedding_target.clone(),
+            },
+        );
+    }
+
+    let mut queries = MappedArena::default();
+    for query in underlying_module_system.queries.values().into_iter() {
+        queries.add(&query.name.clone(), LlmQuery(query));
+    }
+
+    let mut mutations = MappedArena::default();
+    for mutation in underlying_module_system.mutations.values().into_iter() {
+        mutations.add(&mutation.name.clone(), LlmMutation(mutation));
+    }
+
+    Ok(Some(LlmSubsystem {
+        contexts: underlying_module_system.contexts,
+        module_types: underlying_module_system.module_types,
+        queries,
+        mutations,
+        methods: underlying_module_system.methods,
+        operations,
+        embedding_fields,
+        embedding_interceptors,
+    }))
+}
+
+struct LlmModuleFunctions {
+    client: LlmClientConfig,
+    functions: HashMap<String, (LlmFunction, Option<EmbeddingTarget>)>,
+}
+
+fn string_param<'a>(
+    params: &'a AstAnnotationParams<Typed>,
+    annotation: &str,
+    owner: &str,
+) -> Result<&'a str, ModelBuildingError> {
+    match params {
+        AstAnnotationParams::Single(AstExpr::StringLiteral(value, _), _) => Ok(value),
+        _ => Err(ModelBuildingError::Generic(format!(
+            "@{annotation} on '{owner}' expects a single string parameter"
+        ))),
+    }
+}
+
+fn mapped_string_param<'a>(
+    params: &'a AstAnnotationParams<Typed>,
+    name: &str,
+    annotation: &str,
+    owner: &str,
+) -> Result<Option<&'a str>, ModelBuildingError> {
+    match params {
+        AstAnnotationParams::Map(params, _) => match params.get(name) {
+            Some(AstExpr::StringLiteral(value, _)) => Ok(Some(value)),
+            Some(_) => Err(ModelBuildingError::Generic(format!(
+                "@{annotation} on '{owner}' expects a string for '{name}'"
+            ))),
+            None => Ok(None),
+        },
+        _ => Err(ModelBuildingError::Generic(format!(
+            "@{annotation} on '{owner}' expects named parameters"
+        ))),
+    }
+}
+
+/// Compute the client configuration and the function for each method of every `@llm` module
+/// (keyed by the module name)
+fn llm_module_functions(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<HashMap<String, LlmModuleFunctions>, ModelBuildingError> {
+    let mut module_functions = HashMap::new();
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let Some(llm_params) = module.annotations.get("llm") else {
+            continue;
+        };
+
+        let client = client_config(llm_params, &module.name)?;
+
+        let functions = module
+            .methods
+            .iter()
+            .map(|method| {
+                let embedding_target = method
+                    .annotations
+                    .get("embedInto")
+                    .map(|params| embedding_target(params, &method.name, typechecked_system))
+                    .transpose()?;
+
+                let function_name = match method.annotations.get("llmFunction") {
+                    Some(params) => string_param(params, "llmFunction", &method.name)?,
+                    None if embedding_target.is_some() => LlmFunction::EmbedInto.name(),
+                    None => method.name.as_str(),
+                };
+
+                let function = LlmFunction::from_name(function_name).ok_or_else(|| {
+                    ModelBuildingError::Generic(format!(
+                        "Unknown LLM function '{function_name}' for method '{}' in module '{}'. Expected one of: {}",
+                        method.name,
+                        module.name,
+                        LlmFunction::ALL.map(|function| function.name()).join(", ")
+                    ))
+                })?;
+
+                if (function == LlmFunction::EmbedInto) != embedding_target.is_some() {
+                    return Err(ModelBuildingError::Generic(format!(
+                        "Method '{}' in module '{}' must use @embedInto if and only if it executes the '{}' function",
+                        method.name,
+                        module.name,
+                        LlmFunction::EmbedInto.name()
+                    )));
+                }
+
+                Ok((method.name.clone(), (function, embedding_target)))
+            })
+            .collect::<Result<_, ModelBuildingError>>()?;
+
+        module_functions.insert(
+            module.name.clone(),
+            LlmModuleFunctions { client, functions },
+        );
+    }
+
+    Ok(module_functions)
+}
+
+fn client_config(
+    params: &AstAnnotationParams<Typed>,
+    module_name: &str,
+) -> Result<LlmClientConfig, ModelBuildingError> {
+    let param = |name| mapped_string_param(params, name, "llm", module_name);
+
+    let provider_name = param("provider")?.unwrap_or_default();
+    let provider = LlmProvider::from_name(provider_name).ok_or_else(|| {
+        ModelBuildingError::Generic(format!(
+            "Unknown LLM provider '{provider_name}' for module '{module_name}'. Expected one of: {}",
+            LlmProvider::ALL.map(|provider| provider.name()).join(", ")
+        ))
+    })?;
+
+    let model = param("model")?.ok_or_else(|| {
+        ModelBuildingError::Generic(format!("@llm on '{module_name}' requires a 'model'"))
+    })?;
+
+    Ok(LlmClientConfig {
+        provider,
+        model: model.to_string(),
+        embedding_model: param("embeddingModel")?.map(str::to_string),
+        base_url: param("baseUrl")?
+            .unwrap_or(provider.default_base_url())
+            .trim_end_matches('/')
+            .to_string(),
+        api_key_env: param("apiKeyEnv")?
+            .unwrap_or(provider.default_api_key_env())
+            .to_string(),
+    })
+}
+
+/// Resolve `@embedInto(type: "...", field: "...")` to a `Vector` field of a Postgres entity
+fn embedding_target(
+    params: &AstAnnotationParams<Typed>,
+    method_name: &str,
+    typechecked_system: &TypecheckedSystem,
+) -> Result<EmbeddingTarget, ModelBuildingError> {
+    let param = |name| {
+        mapped_string_param(params, name, "embedInto", method_name)?.ok_or_else(|| {
+            ModelBuildingError::Generic(format!(
+                "@embedInto on '{method_name}' requires a '{name}'"
+            ))
+        })
+    };
+    let type_name = param("type")?;
+    let field_name = param("field")?;
+
+    let is_postgres_type = typechecked_system
+        .modules
+        .iter()
+        .any(|(_, Module(module))| {
+            module.annotations.contains("postgres")
+                && module.types.iter().any(|typ| typ.name == type_name)
+        });
+    let entity = match typechecked_system.types.get_by_key(type_name) {
+        Some(Type::Composite(entity)) if is_postgres_type => entity,
+        _ => {
+            return Err(ModelBuildingError::Generic(format!(
+                "@embedInto on '{method_name}' refers to '{type_name}', which is not a type in a @postgres module"
+            )));
+        }
+    };
+
+    vector_target(entity, field_name, "embedInto", method_name)
+}
+
+/// The `Vector` field of a Postgres entity to write embeddings to
+fn vector_target(
+    entity: &AstModel<Typed>,
+    field_name: &str,
+    annotation: &str,
+    owner: &str,
+) -> Result<EmbeddingTarget, ModelBuildingError> {
+    let type_name = &entity.name;
+
+    match entity.fields.iter().find(|field| field.name == field_name) {
+        Some(field) if field.typ.name() == "Vector" => {}
+        Some(_) => {
+            return Err(ModelBuildingError::Generic(format!(
+                "@{annotation} on '{owner}' requires '{type_name}.{field_name}' to be a Vector field"
+            )));
+        }
+        None => {
+            return Err(ModelBuildingError::Generic(format!(
+                "@{annotation} on '{owner}' refers to '{field_name}', which is not a field of '{type_name}'"
+            )));
+        }
+    }
+
+    let pk_fields: Vec<_> = entity
+        .fields
+        .iter()
+        .filter(|field| field.annotations.contains("pk"))
+        .collect();
+    let [pk_field] = pk_fields.as_slice() else {
+        return Err(ModelBuildingError::Generic(format!(
+            "@{annotation} on '{owner}' requires '{type_name}' to have exactly one @pk field"
+        )));
+    };
+
+    Ok(EmbeddingTarget {
+        entity_name: type_name.to_string(),
+        pk_field: pk_field.name.clone(),
+        pk_type_name: pk_field.typ.name(),
+        vector_field: field_name.to_string(),
+        update_mutation_name: format!("update{type_name}"),
+    })
+}
+
+/// Collect the `@embedding` fields of Postgres entities along with the interceptors that keep
+/// them in sync
+fn embedding_fields(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<(Vec<EmbeddingField>, Vec<EmbeddingInterceptor>), ModelBuildingError> {
+    let mut embedding_fields = vec![];
+    let mut interceptors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        for typ in module.types.iter() {
+            let Some(Type::Composite(entity)) = typechecked_system.types.get_by_key(&typ.name)
+            else {
+                continue;
+            };
+
+            for field in entity.fields.iter() {
+                let Some(params) = field.annotations.get("embedding") else {
+                    continue;
+                };
+                let owner = format!("{}.{}", entity.name, field.name);
+
+                if !module.annotations.contains("postgres") {
+                    return Err(ModelBuildingError::Generic(format!(
+                        "@embedding on '{owner}' may only be used on types in a @postgres module"
+                    )));
+                }
+
+                let param = |name| {
+                    mapped_string_param(params, name, "embedding", &owner)?.ok_or_else(|| {
+                        ModelBuildingError::Generic(format!(
+                            "@embedding on '{owner}' requires a '{name}'"
+                        ))
+                    })
+                };
+
+                let target = vector_target(entity, &field.name, "embedding", &owner)?;
+                let source_fields = source_fields(entity, param("source")?, &owner)?;
+                let model_query_name = param("model")?;
+                validate_model_query(model_query_name, &owner, typechecked_system)?;
+
+                let plural_name = plural_name(entity);
+                let field_index = embedding_fields.len();
+                let mutations = [
+                    (format!("create{}", entity.name), EmbeddingSyncKind::Create),
+                    (format!("create{plural_name}"), EmbeddingSyncKind::Create),
+                    (format!("update{}", entity.name), EmbeddingSyncKind::Update),
+                    (format!("update{plural_name}"), EmbeddingSyncKind::Update),
+                ];
+                interceptors.extend(mutations.into_iter().map(|(mutation_name, kind)| {
+                    EmbeddingInterceptor {
+                        mutation_name,
+                        field_index,
+                        kind,
+                    }
+                }));
+
+                embedding_fields.push(EmbeddingField {
+                    target,
+                    source_fields,
+                    model_query_name: model_query_name.to_string(),
+                    collection_query_name: plural_name.to_lower_camel_case(),
+                });
+            }
+        }
+    }
+
+    Ok((embedding_fields, interceptors))
+}
+
+/// Parse the `source` of `@embedding` (such as "title + body") into the `String` fields to embed
+fn source_fields(
+    entity: &AstModel<Typed>,
+    source: &str,
+    owner: &str,
+) -> Result<Vec<String>, ModelBuildingError> {
+    source
+        .split('+')
+        .map(|field_name| {
+            let field_name = field_name.trim();
+
+            match entity.fields.iter().find(|field| field.name == field_name) {
+                Some(field) if field.typ.name() == "String" => Ok(field_name.to_string()),
+                Some(_) => Err(ModelBuildingError::Generic(format!(
+                    "@embedding on '{owner}' requires the source field '{field_name}' to be a String field"
+                ))),
+                None => Err(ModelBuildingError::Generic(format!(
+                    "@embedding on '{owner}' refers to '{field_name}', which is not a field of '{}'",
+                    entity.name
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Check that the model of `@embedding` names a query with the signature `(text: String): Vector`
+/// (besides any injected arguments)
+fn validate_model_query(
+    model_query_name: &str,
+    owner: &str,
+    typechecked_system: &TypecheckedSystem,
+) -> Result<(), ModelBuildingError> {
+    let method = typechecked_system
+        .modules
+        .iter()
+        .flat_map(|(_, Module(module))| module.methods.iter())
+        .find(|method| method.name == model_query_name)
+        .ok_or_else(|| {
+            ModelBuildingError::Generic(format!(
+                "@embedding on '{owner}' refers to '{model_query_name}', which is not a query of any module"
+            ))
+        })?;
+
+    let arguments: Vec<_> = method
+        .arguments
+        .iter()
+        .filter(|argument| !argument.annotations.contains("inject"))
+        .collect();
+    let is_valid = method.typ == AstMethodType::Query
+        && matches!(arguments.as_slice(), [argument] if argument.name == "text" && argument.typ.name() == "String")
+        && method.return_type.name() == "Vector";
+
+    if !is_valid {
+        return Err(ModelBuildingError::Generic(format!(
+            "@embedding on '{owner}' requires '{model_query_name}' to be declared as `query {model_query_name}(text: String): Vector`"
+        )));
+    }
+
+    Ok(())
+}
+
+/// The plural name as used by the Postgres subsystem to name operations (e.g. `Documents`)
+fn plural_name(entity: &AstModel<Typed>) -> String {
+    entity
+        .annotations
+        .annotations
+        .get("plural")
+        .and_then(|annotation| match &annotation.params {
+            AstAnnotationParams::Single(AstExpr::StringLiteral(plural, _), _) => {
+                Some(plural.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| entity.name.to_plural())
+}
+
+/// Check that the declared method matches the signature of the function
+fn validate_method(
+    method: &ModuleMethod,
+    function: LlmFunction,
+    embedding_target: Option<&EmbeddingTarget>,
+    module_types: &SerializableSlab<ModuleType>,
+) -> Result<(), ModelBuildingError> {
+    let signature_error = |detail: String| {
+        ModelBuildingError::Generic(format!(
+            "Method '{}' does not match the signature of the LLM '{}' function: {detail}",
+            method.name,
+            function.name()
+        ))
+    };
+
+    let is_query = matches!(method.operation_kind, ModuleMethodType::Query(_));
+    if is_query != function.is_query() {
+        return Err(signature_error(format!(
+            "must be declared as a {}",
+            if function.is_query() {
+                "query"
+            } else {
+                "mutation"
+            }
+        )));
+    }
+
+    let arguments: Vec<_> = method.arguments.iter().filter(|a| !a.is_injected).collect();
+
+    // (name, type name, optional)
+    let mut parameters: Vec<(&str, &str, bool)> = function
+        .parameters()
+        .iter()
+        .map(|parameter| (parameter.name, parameter.type_name, parameter.optional))
+        .collect();
+    if let Some(target) = embedding_target {
+        parameters.push((
+            target.pk_field.as_str(),
+            target.pk_type_name.as_str(),
+            false,
+        ));
+    }
+
+    for (name, type_name, optional) in parameters.iter() {
+        match arguments.iter().find(|a| a.name == *name) {
+            Some(argument) => {
+                let argument_type_name = &module_types[*argument.type_id.innermost()].name;
+                let is_optional = matches!(argument.type_id, FieldType::Optional(_));
+
+                if argument_type_name != type_name || is_optional != *optional {
+                    return Err(signature_error(format!(
+                        "argument '{name}' must be of type {type_name}{}",
+                        if *optional { "?" } else { "" }
+                    )));
+                }
+            }
+            None if *optional => {}
+            None => {
+                return Err(signature_error(format!("missing argument '{name}'")));
+            }
+        }
+    }
+
+    if let Some(extra) = arguments
+        .iter()
+        .find(|argument| !parameters.iter().any(|(name, _, _)| *name == argument.name))
+    {
+        return Err(signature_error(format!(
+            "unexpected argument '{}'",
+            extra.name
+        )));
+    }
+
+    let return_type_name = match &method.return_type {
+        ModuleOperationReturnType::Own(return_type) => return_type.type_name(),
+        ModuleOperationReturnType::Foreign(return_type) => {
+            &return_type.innermost().return_type_name
+        }
+    };
+    if return_type_name != function.return_type_name() {
+        return Err(signature_error(format!(
+            "must return {}",
+            function.return_type_name()
+        )));
+    }
+
+    Ok(())
+}
+
+/// LLM modules have no script. We record the module name as the script path so that each
+/// method can be traced back to its module (and thus to its client configuration).
+struct LlmScriptProcessor {}
+
+#[async_trait]
+impl ScriptProcessor for LlmScriptProcessor {
+    async fn process_script(
+        &self,
+        module: &AstModule<Typed>,
+        _base_system: &BaseModelSystem,
+        _typechecked_system: &TypecheckedSystem,
+        _module_fs_path: &Path,
+    ) -> Result<(String, Vec<u8>), ModelBuildingError> {
+        Ok((module.name.clone(), vec![]))
+    }
+}
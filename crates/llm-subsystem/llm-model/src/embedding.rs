@@ -0,0 +1,116 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::function::EmbeddingTarget;
+
+/// A `Vector` field kept in sync with other fields of its entity through
+/// `@embedding(source: "title + body", model: "<query>")`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddingField {
+    pub target: EmbeddingTarget,
+    /// Fields whose values make up the text to embed (in order)
+    pub source_fields: Vec<String>,
+    /// The query computing the embedding of a text (`query <name>(text: String): Vector`),
+    /// implemented by an `@llm` module or any other module (such as a Deno module)
+    pub model_query_name: String,
+    /// The collection query (e.g. `documents`), used to backfill existing entities
+    pub collection_query_name: String,
+}
+
+/// Separator between the values of the source fields in the text to embed
+pub const SOURCE_SEPARATOR: &str = "\n\n";
+
+impl EmbeddingField {
+    /// The text to embed for an entity, made of the (non-null, non-empty) values of the source
+    /// fields (keyed by the field name). Returns `None` if there is nothing to embed.
+    pub fn source_text(&self, values: &Map<String, Value>) -> Option<String> {
+        let parts: Vec<&str> = self
+            .source_fields
+            .iter()
+            .filter_map(|field| match values.get(field) {
+                Some(Value::String(value)) if !value.trim().is_empty() => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(SOURCE_SEPARATOR))
+        }
+    }
+}
+
+/// Which of an entity's mutations an [EmbeddingInterceptor] wraps
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingSyncKind {
+    /// Always compute the embedding of the created entities
+    Create,
+    /// Recompute the embedding only if the mutation changes one of the source fields
+    Update,
+}
+
+/// Wraps a mutation of an entity with an `@embedding` field to keep the embedding in sync
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddingInterceptor {
+    pub mutation_name: String,
+    /// Index into [crate::subsystem::LlmSubsystem::embedding_fields]
+    pub field_index: usize,
+    pub kind: EmbeddingSyncKind,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedding_field() -> EmbeddingField {
+        EmbeddingField {
+            target: EmbeddingTarget {
+                entity_name: "Document".to_string(),
+                pk_field: "id".to_string(),
+                pk_type_name: "Int".to_string(),
+                vector_field: "contentVector".to_string(),
+                update_mutation_name: "updateDocument".to_string(),
+            },
+            source_fields: vec!["title".to_string(), "body".to_string()],
+            model_query_name: "embed".to_string(),
+            collection_query_name: "documents".to_string(),
+        }
+    }
+
+    #[test]
+    fn joins_source_fields() {
+        let values = serde_json::json!({"title": "Hello", "body": "World", "id": 1});
+
+        assert_eq!(
+            embedding_field().source_text(values.as_object().unwrap()),
+            Some("Hello\n\nWorld".to_string())
+        );
+    }
+
+    #[test]
+    fn skips_missing_and_empty_fields() {
+        let values = serde_json::json!({"__title": null, "__body": " Only body "});
+
+        assert_eq!(
+            embedding_field()
+                .source_text(values.as_object().unwrap(), |field| format!("__{field}")),
+            Some(" Only body ".to_string())
+        );
+
+        let values = serde_json::json!({"title": "", "body": null});
+        assert_eq!(
+            embedding_field().source_text(values.as_object().unwrap()),
+            None
+        );
+    }
+}
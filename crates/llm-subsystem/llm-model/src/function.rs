@@ -0,0 +1,189 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+/// The API used to talk to the model of an `@llm` module
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProvider {
+    /// The OpenAI API (or any API compatible with it, by setting `baseUrl`)
+    OpenAi,
+    /// The Anthropic Messages API
+    Anthropic,
+}
+
+impl LlmProvider {
+    pub const ALL: [LlmProvider; 2] = [LlmProvider::OpenAi, LlmProvider::Anthropic];
+
+    /// Name used to refer to the provider in `@llm(provider: "...")`
+    pub fn name(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "openai",
+            LlmProvider::Anthropic => "anthropic",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|provider| provider.name() == name)
+    }
+
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "https://api.openai.com/v1",
+            LlmProvider::Anthropic => "https://api.anthropic.com/v1",
+        }
+    }
+
+    /// Environment variable holding the API key (unless overridden with `apiKeyEnv`)
+    pub fn default_api_key_env(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "OPENAI_API_KEY",
+            LlmProvider::Anthropic => "ANTHROPIC_API_KEY",
+        }
+    }
+
+    pub fn supports_embeddings(&self) -> bool {
+        matches!(self, LlmProvider::OpenAi)
+    }
+}
+
+/// The function backing a method in an `@llm` module.
+///
+/// Each function has a fixed signature (see [LlmFunction::parameters] and
+/// [LlmFunction::return_type_name]), which the builder checks against the declared method.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmFunction {
+    /// `query complete(prompt: String, system: String?, maxTokens: Int?, temperature: Float?): String`
+    Complete,
+    /// `query embed(text: String): Vector`
+    Embed,
+    /// `@embedInto(type: "<Type>", field: "<field>") mutation embedInto(<pk>: <pk type>, text: String): Boolean`
+    ///
+    /// Computes the embedding of `text` and stores it in a `Vector` field of a Postgres entity
+    /// (through the entity's update mutation, so its access control applies).
+    EmbedInto,
+}
+
+/// A parameter of an LLM function
+pub struct LlmFunctionParameter {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub optional: bool,
+}
+
+const fn required(name: &'static str, type_name: &'static str) -> LlmFunctionParameter {
+    LlmFunctionParameter {
+        name,
+        type_name,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, type_name: &'static str) -> LlmFunctionParameter {
+    LlmFunctionParameter {
+        name,
+        type_name,
+        optional: true,
+    }
+}
+
+impl LlmFunction {
+    pub const ALL: [LlmFunction; 3] = [
+        LlmFunction::Complete,
+        LlmFunction::Embed,
+        LlmFunction::EmbedInto,
+    ];
+
+    /// Name used to refer to the function in `@llmFunction("...")` (or as the method name)
+    pub fn name(&self) -> &'static str {
+        match self {
+            LlmFunction::Complete => "complete",
+            LlmFunction::Embed => "embed",
+            LlmFunction::EmbedInto => "embedInto",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|function| function.name() == name)
+    }
+
+    /// Does the function leave the data unchanged (and thus is to be declared as a `query`)?
+    pub fn is_query(&self) -> bool {
+        !matches!(self, LlmFunction::EmbedInto)
+    }
+
+    pub fn uses_embeddings(&self) -> bool {
+        matches!(self, LlmFunction::Embed | LlmFunction::EmbedInto)
+    }
+
+    /// Parameters of the function. `EmbedInto` additionally takes the primary key of the target
+    /// entity (named and typed as the entity's `@pk` field).
+    pub fn parameters(&self) -> &'static [LlmFunctionParameter] {
+        match self {
+            LlmFunction::Complete => &[
+                required("prompt", "String"),
+                optional("system", "String"),
+                optional("maxTokens", "Int"),
+                optional("temperature", "Float"),
+            ],
+            LlmFunction::Embed | LlmFunction::EmbedInto => &[required("text", "String")],
+        }
+    }
+
+    pub fn return_type_name(&self) -> &'static str {
+        match self {
+            LlmFunction::Complete => "String",
+            LlmFunction::Embed => "Vector",
+            LlmFunction::EmbedInto => "Boolean",
+        }
+    }
+}
+
+/// The model (and how to reach it) declared by an `@llm` module
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LlmClientConfig {
+    pub provider: LlmProvider,
+    pub model: String,
+    /// Model used by the embedding functions (defaults to `model`)
+    pub embedding_model: Option<String>,
+    pub base_url: String,
+    /// Name of the environment variable holding the API key
+    pub api_key_env: String,
+}
+
+impl LlmClientConfig {
+    pub fn embedding_model(&self) -> &str {
+        self.embedding_model.as_deref().unwrap_or(&self.model)
+    }
+}
+
+/// The `Vector` field written by an `EmbedInto` function
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddingTarget {
+    pub entity_name: String,
+    pub pk_field: String,
+    pub pk_type_name: String,
+    pub vector_field: String,
+    /// The Postgres mutation that updates a single entity by its primary key (e.g. `updateDocument`)
+    pub update_mutation_name: String,
+}
+
+/// A method of an `@llm` module along with the function it executes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LlmOperation {
+    pub method_name: String,
+    pub function: LlmFunction,
+    pub client: LlmClientConfig,
+    /// Set for (and only for) `EmbedInto` functions
+    pub embedding_target: Option<EmbeddingTarget>,
+}
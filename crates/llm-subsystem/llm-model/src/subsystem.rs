@@ -0,0 +1,92 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::types::{FieldDefinition, TypeDefinition};
+
+use core_model::{
+    context_type::{ContextContainer, ContextType},
+    mapped_arena::{MappedArena, SerializableSlab},
+    type_normalization::{FieldDefinitionProvider, TypeDefinitionProvider},
+};
+use core_plugin_shared::{error::ModelSerializationError, system_serializer::SystemSerializer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    embedding::{EmbeddingField, EmbeddingInterceptor},
+    function::LlmOperation,
+    module::ModuleMethod,
+    operation::{LlmMutation, LlmQuery},
+    types::ModuleType,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LlmSubsystem {
+    pub contexts: MappedArena<ContextType>,
+    pub module_types: SerializableSlab<ModuleType>,
+
+    // query related
+    pub queries: MappedArena<LlmQuery>,
+
+    // mutation related
+    pub mutations: MappedArena<LlmMutation>,
+
+    // module related
+    pub methods: SerializableSlab<ModuleMethod>,
+    /// Keyed by the method name
+    pub operations: MappedArena<LlmOperation>,
+
+    // embedding sync related
+    pub embedding_fields: Vec<EmbeddingField>,
+    pub embedding_interceptors: Vec<EmbeddingInterceptor>,
+}
+
+impl LlmSubsystem {
+    pub fn schema_queries(&self) -> Vec<FieldDefinition> {
+        self.queries
+            .iter()
+            .map(|(_, query)| query.field_definition(self))
+            .collect()
+    }
+
+    pub fn schema_mutations(&self) -> Vec<FieldDefinition> {
+        self.mutations
+            .iter()
+            .map(|(_, query)| query.field_definition(self))
+            .collect()
+    }
+
+    pub fn schema_types(&self) -> Vec<TypeDefinition> {
+        self.module_types
+            .iter()
+            .map(|typ| typ.1.type_definition(&self.module_types))
+            .collect()
+    }
+}
+
+impl SystemSerializer for LlmSubsystem {
+    type Underlying = Self;
+
+    fn serialize(&self) -> Result<Vec<u8>, ModelSerializationError> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(ModelSerializationError::Serialize)
+    }
+
+    fn deserialize_reader(
+        mut reader: impl std::io::Read,
+    ) -> Result<Self::Underlying, ModelSerializationError> {
+        bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())
+            .map_err(ModelSerializationError::Deserialize)
+    }
+}
+
+impl ContextContainer for LlmSubsystem {
+    fn contexts(&self) -> &MappedArena<ContextType> {
+        &self.contexts
+    }
+}
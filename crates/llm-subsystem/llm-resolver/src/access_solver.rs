@@ -0,0 +1,100 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! [`AccessSolver`] for the LLM subsystem.
+
+use async_trait::async_trait;
+
+use common::context::RequestContext;
+use common::value::Val;
+use core_model::access::AccessRelationalOp;
+use core_resolver::access_solver::{
+    AccessInput, AccessPredicate, AccessSolution, AccessSolver, AccessSolverError, eq_values,
+    gt_values, gte_values, in_values, lt_values, lte_values, neq_values,
+    reduce_common_primitive_expression,
+};
+
+use llm_model::{access::ModuleAccessPrimitiveExpression, subsystem::LlmSubsystem};
+
+/// LLM module access rules may refer only to contexts, so they always reduce to a boolean
+#[derive(Debug)]
+pub struct LlmAccessPredicate(pub bool);
+
+impl std::ops::Not for LlmAccessPredicate {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        LlmAccessPredicate(!self.0)
+    }
+}
+
+impl From<bool> for LlmAccessPredicate {
+    fn from(value: bool) -> Self {
+        LlmAccessPredicate(value)
+    }
+}
+
+impl AccessPredicate for LlmAccessPredicate {
+    fn and(self, other: Self) -> Self {
+        LlmAccessPredicate(self.0 && other.0)
+    }
+
+    fn or(self, other: Self) -> Self {
+        LlmAccessPredicate(self.0 || other.0)
+    }
+
+    fn is_true(&self) -> bool {
+        self.0
+    }
+
+    fn is_false(&self) -> bool {
+        !self.0
+    }
+}
+
+#[async_trait]
+impl<'a> AccessSolver<'a, ModuleAccessPrimitiveExpression, LlmAccessPredicate> for LlmSubsystem {
+    async fn solve_relational_op(
+        &self,
+        request_context: &RequestContext<'a>,
+        _input_value: Option<&AccessInput<'a>>,
+        op: &AccessRelationalOp<ModuleAccessPrimitiveExpression>,
+    ) -> Result<AccessSolution<LlmAccessPredicate>, AccessSolverError> {
+        async fn reduce_primitive_expression<'a>(
+            solver: &LlmSubsystem,
+            request_context: &'a RequestContext<'a>,
+            expr: &'a ModuleAccessPrimitiveExpression,
+        ) -> Result<Option<Val>, AccessSolverError> {
+            Ok(match expr {
+                ModuleAccessPrimitiveExpression::Common(common_expr) => {
+                    reduce_common_primitive_expression(solver, request_context, common_expr).await?
+                }
+            })
+        }
+
+        let (left, right) = op.sides();
+        let left_value = reduce_primitive_expression(self, request_context, left).await?;
+        let right_value = reduce_primitive_expression(self, request_context, right).await?;
+
+        Ok(match (left_value, right_value) {
+            (None, _) | (_, None) => AccessSolution::Unsolvable(LlmAccessPredicate(false)),
+            (Some(ref left_value), Some(ref right_value)) => {
+                AccessSolution::Solved(LlmAccessPredicate(match op {
+                    AccessRelationalOp::Eq(..) => eq_values(left_value, right_value),
+                    AccessRelationalOp::Neq(_, _) => neq_values(left_value, right_value),
+                    AccessRelationalOp::Lt(_, _) => lt_values(left_value, right_value),
+                    AccessRelationalOp::Lte(_, _) => lte_values(left_value, right_value),
+                    AccessRelationalOp::Gt(_, _) => gt_values(left_value, right_value),
+                    AccessRelationalOp::Gte(_, _) => gte_values(left_value, right_value),
+                    AccessRelationalOp::In(..) => in_values(left_value, right_value),
+                }))
+            }
+        })
+    }
+}
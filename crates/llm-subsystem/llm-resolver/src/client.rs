@@ -0,0 +1,395 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::time::Duration;
+
+use llm_model::function::{LlmClientConfig, LlmProvider};
+use reqwest::{
+    Client, StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+use serde_json::{Map, Value, json};
+
+use crate::llm_execution_error::LlmExecutionError;
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// The Anthropic API requires `max_tokens`, so we use this if the caller doesn't specify it
+const DEFAULT_ANTHROPIC_MAX_TOKENS: i64 = 1024;
+
+pub struct CompletionRequest<'a> {
+    pub prompt: &'a str,
+    pub system: Option<&'a str>,
+    pub max_tokens: Option<i64>,
+    pub temperature: Option<f64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// A minimal client for the completion and embedding APIs of the supported providers, retrying
+/// rate-limited and transiently failing requests.
+pub struct LlmClient {
+    http: Client,
+    max_retries: u32,
+}
+
+impl LlmClient {
+    pub fn new(timeout: Duration, max_retries: u32) -> Result<Self, LlmExecutionError> {
+        let http = Client::builder().timeout(timeout).build()?;
+        Ok(Self { http, max_retries })
+    }
+
+    pub async fn complete(
+        &self,
+        config: &LlmClientConfig,
+        api_key: &str,
+        request: &CompletionRequest<'_>,
+    ) -> Result<String, LlmExecutionError> {
+        let (path, body) = completion_request(config, request);
+        let response = self.post(config, api_key, path, &body).await?;
+        let (text, usage) = parse_completion(config.provider, &response)?;
+        record_usage(config, &config.model, usage);
+        Ok(text)
+    }
+
+    pub async fn embed(
+        &self,
+        config: &LlmClientConfig,
+        api_key: &str,
+        text: &str,
+    ) -> Result<Vec<f64>, LlmExecutionError> {
+        let model = config.embedding_model();
+        let body = json!({ "model": model, "input": text });
+        let response = self.post(config, api_key, "embeddings", &body).await?;
+        let (embedding, usage) = parse_embedding(&response)?;
+        record_usage(config, model, usage);
+        Ok(embedding)
+    }
+
+    async fn post(
+        &self,
+        config: &LlmClientConfig,
+        api_key: &str,
+        path: &str,
+        body: &Value,
+    ) -> Result<Value, LlmExecutionError> {
+        let url = format!("{}/{path}", config.base_url);
+
+        let mut attempt = 0;
+        loop {
+            let request = self.http.post(&url).json(body);
+            let request = match config.provider {
+                LlmProvider::OpenAi => request.bearer_auth(api_key),
+                LlmProvider::Anthropic => request
+                    .header("x-api-key", api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION),
+            };
+
+            let (error, retry_after) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    return Ok(response.json().await?);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = retry_after(response.headers());
+                    let message = response.text().await.unwrap_or_default();
+                    let error = LlmExecutionError::Status(status, message);
+                    if !is_retryable(status) {
+                        return Err(error);
+                    }
+                    (error, retry_after)
+                }
+                Err(error) if error.is_connect() || error.is_timeout() => (error.into(), None),
+                Err(error) => return Err(error.into()),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error);
+            }
+
+            let delay = retry_after
+                .unwrap_or_else(|| retry_delay(attempt))
+                .min(MAX_RETRY_DELAY);
+            tracing::warn!("LLM request failed ({error}), retrying in {delay:?}");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// Exponential backoff for the given (zero-based) retry attempt
+fn retry_delay(attempt: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// The delay requested by the provider (only the delay-seconds form is supported)
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Token counts are emitted as counters for an OpenTelemetry metrics layer (`monotonic_counter.`
+/// fields), tagged with the provider and the model.
+fn record_usage(config: &LlmClientConfig, model: &str, usage: TokenUsage) {
+    tracing::info!(
+        monotonic_counter.exograph_llm_input_tokens = usage.input_tokens,
+        monotonic_counter.exograph_llm_output_tokens = usage.output_tokens,
+        llm_provider = config.provider.name(),
+        llm_model = model,
+        "LLM token usage"
+    );
+}
+
+/// The path (relative to the base URL) and the body of a completion request
+fn completion_request(
+    config: &LlmClientConfig,
+    request: &CompletionRequest<'_>,
+) -> (&'static str, Value) {
+    let mut body = Map::new();
+    body.insert("model".to_string(), config.model.clone().into());
+
+    let user_message = json!({ "role": "user", "content": request.prompt });
+
+    let path = match config.provider {
+        LlmProvider::OpenAi => {
+            let messages = match request.system {
+                Some(system) => vec![json!({ "role": "system", "content": system }), user_message],
+                None => vec![user_message],
+            };
+            body.insert("messages".to_string(), messages.into());
+            if let Some(max_tokens) = request.max_tokens {
+                body.insert("max_tokens".to_string(), max_tokens.into());
+            }
+            "chat/completions"
+        }
+        LlmProvider::Anthropic => {
+            if let Some(system) = request.system {
+                body.insert("system".to_string(), system.into());
+            }
+            body.insert("messages".to_string(), vec![user_message].into());
+            body.insert(
+                "max_tokens".to_string(),
+                request
+                    .max_tokens
+                    .unwrap_or(DEFAULT_ANTHROPIC_MAX_TOKENS)
+                    .into(),
+            );
+            "messages"
+        }
+    };
+
+    if let Some(temperature) = request.temperature {
+        body.insert("temperature".to_string(), temperature.into());
+    }
+
+    (path, Value::Object(body))
+}
+
+fn parse_completion(
+    provider: LlmProvider,
+    response: &Value,
+) -> Result<(String, TokenUsage), LlmExecutionError> {
+    let usage = &response["usage"];
+
+    match provider {
+        LlmProvider::OpenAi => {
+            let text = response["choices"][0]["message"]["content"]
+                .as_str()
+                .ok_or_else(|| {
+                    LlmExecutionError::InvalidResponse(
+                        "missing 'choices[0].message.content'".to_string(),
+                    )
+                })?;
+            Ok((
+                text.to_string(),
+                TokenUsage {
+                    input_tokens: usage["prompt_tokens"].as_u64().unwrap_or_default(),
+                    output_tokens: usage["completion_tokens"].as_u64().unwrap_or_default(),
+                },
+            ))
+        }
+        LlmProvider::Anthropic => {
+            let blocks = response["content"].as_array().ok_or_else(|| {
+                LlmExecutionError::InvalidResponse("missing 'content'".to_string())
+            })?;
+            let text = blocks
+                .iter()
+                .filter(|block| block["type"] == "text")
+                .filter_map(|block| block["text"].as_str())
+                .collect::<String>();
+            Ok((
+                text,
+                TokenUsage {
+                    input_tokens: usage["input_tokens"].as_u64().unwrap_or_default(),
+                    output_tokens: usage["output_tokens"].as_u64().unwrap_or_default(),
+                },
+            ))
+        }
+    }
+}
+
+fn parse_embedding(response: &Value) -> Result<(Vec<f64>, TokenUsage), LlmExecutionError> {
+    let embedding = response["data"][0]["embedding"]
+        .as_array()
+        .and_then(|values| values.iter().map(Value::as_f64).collect::<Option<Vec<_>>>())
+        .ok_or_else(|| {
+            LlmExecutionError::InvalidResponse("missing 'data[0].embedding'".to_string())
+        })?;
+
+    Ok((
+        embedding,
+        TokenUsage {
+            input_tokens: response["usage"]["prompt_tokens"]
+                .as_u64()
+                .unwrap_or_default(),
+            output_tokens: 0,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: LlmProvider) -> LlmClientConfig {
+        LlmClientConfig {
+            provider,
+            model: "test-model".to_string(),
+            embedding_model: None,
+            base_url: provider.default_base_url().to_string(),
+            api_key_env: provider.default_api_key_env().to_string(),
+        }
+    }
+
+    #[test]
+    fn builds_completion_requests() {
+        let request = CompletionRequest {
+            prompt: "Hello",
+            system: Some("Be brief"),
+            max_tokens: None,
+            temperature: Some(0.5),
+        };
+
+        assert_eq!(
+            completion_request(&config(LlmProvider::OpenAi), &request),
+            (
+                "chat/completions",
+                json!({
+                    "model": "test-model",
+                    "messages": [
+                        { "role": "system", "content": "Be brief" },
+                        { "role": "user", "content": "Hello" }
+                    ],
+                    "temperature": 0.5
+                })
+            )
+        );
+
+        assert_eq!(
+            completion_request(&config(LlmProvider::Anthropic), &request),
+            (
+                "messages",
+                json!({
+                    "model": "test-model",
+                    "system": "Be brief",
+                    "messages": [{ "role": "user", "content": "Hello" }],
+                    "max_tokens": DEFAULT_ANTHROPIC_MAX_TOKENS,
+                    "temperature": 0.5
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn parses_responses() {
+        let openai = json!({
+            "choices": [{ "message": { "role": "assistant", "content": "Hi!" } }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 3 }
+        });
+        assert_eq!(
+            parse_completion(LlmProvider::OpenAi, &openai).unwrap(),
+            (
+                "Hi!".to_string(),
+                TokenUsage {
+                    input_tokens: 12,
+                    output_tokens: 3
+                }
+            )
+        );
+
+        let anthropic = json!({
+            "content": [{ "type": "text", "text": "Hi" }, { "type": "text", "text": "!" }],
+            "usage": { "input_tokens": 10, "output_tokens": 2 }
+        });
+        assert_eq!(
+            parse_completion(LlmProvider::Anthropic, &anthropic).unwrap(),
+            (
+                "Hi!".to_string(),
+                TokenUsage {
+                    input_tokens: 10,
+                    output_tokens: 2
+                }
+            )
+        );
+
+        let embedding = json!({
+            "data": [{ "embedding": [0.5, -1.0, 2] }],
+            "usage": { "prompt_tokens": 4, "total_tokens": 4 }
+        });
+        assert_eq!(
+            parse_embedding(&embedding).unwrap(),
+            (
+                vec![0.5, -1.0, 2.0],
+                TokenUsage {
+                    input_tokens: 4,
+                    output_tokens: 0
+                }
+            )
+        );
+
+        assert!(parse_completion(LlmProvider::OpenAi, &json!({ "choices": [] })).is_err());
+    }
+
+    #[test]
+    fn backs_off_retries() {
+        assert_eq!(retry_delay(0), Duration::from_millis(500));
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(3), Duration::from_secs(4));
+        assert_eq!(retry_delay(10), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use async_graphql_parser::types::OperationType;
+use async_graphql_value::{Name, indexmap::IndexMap};
+use futures::future::join_all;
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use common::{
+    context::RequestContext,
+    env_const::get_graphql_http_path,
+    http::{MemoryRequestHead, MemoryRequestPayload},
+    value::Val,
+};
+use core_resolver::{
+    InterceptedOperation, QueryResponse, QueryResponseBody, system_resolver::GraphQLSystemResolver,
+    validation::field::ValidatedField,
+};
+use llm_model::embedding::{EmbeddingField, EmbeddingSyncKind};
+
+use crate::{LlmSubsystemResolver, llm_execution_error::LlmExecutionError};
+
+/// Alias for the primary key we add to selections to identify the entities to update
+const PK_ALIAS: &str = "__embedding_pk";
+
+impl LlmSubsystemResolver {
+    /// Proceed with a create or update mutation and then compute and store the embedding of each
+    /// affected entity.
+    ///
+    /// The embedding query and the update run within the mutation's transaction, so a failure to
+    /// compute an embedding fails the mutation.
+    pub(crate) async fn sync_embeddings<'a>(
+        &'a self,
+        embedding_field: &'a EmbeddingField,
+        kind: EmbeddingSyncKind,
+        intercepted_operation: &'a InterceptedOperation<'a>,
+        request_context: &'a RequestContext<'a>,
+        system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<QueryResponse, LlmExecutionError> {
+        let original_operation = intercepted_operation.operation();
+
+        if !requires_sync(embedding_field, kind, original_operation) {
+            return Ok(intercepted_operation.resolve(request_context).await?);
+        }
+
+        // Proceed with the mutation, additionally selecting what we need to compute the embeddings
+        let mut operation = original_operation.clone();
+        operation
+            .subfields
+            .push(aliased_field(PK_ALIAS, &embedding_field.target.pk_field));
+        operation.subfields.extend(
+            embedding_field
+                .source_fields
+                .iter()
+                .map(|field| aliased_field(&source_field_alias(field), field)),
+        );
+
+        let response = intercepted_operation
+            .with_operation(&operation)
+            .resolve(request_context)
+            .await?;
+        let mut body = response.body.to_json()?;
+
+        // Single mutations return an object (or null if nothing matched), collection mutations an array
+        let mut entities: Vec<&mut Map<String, Value>> = match &mut body {
+            Value::Array(entities) => entities
+                .iter_mut()
+                .filter_map(Value::as_object_mut)
+                .collect(),
+            Value::Object(entity) => vec![entity],
+            _ => vec![],
+        };
+
+        let pending: Vec<(usize, Val, String)> = entities
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, entity)| {
+                let pk = entity.remove(PK_ALIAS)?;
+                let source_values: Map<String, Value> = embedding_field
+                    .source_fields
+                    .iter()
+                    .filter_map(|field| {
+                        entity
+                            .remove(&source_field_alias(field))
+                            .map(|value| (field.clone(), value))
+                    })
+                    .collect();
+
+                // Entities with nothing to embed keep their current embedding
+                let text = embedding_field.source_text(&source_values)?;
+                Some((index, Val::from(pk), text))
+            })
+            .collect();
+
+        debug!(
+            "[LlmEmbedding] computing {} embedding(s) for '{}.{}'",
+            pending.len(),
+            embedding_field.target.entity_name,
+            embedding_field.target.vector_field
+        );
+
+        // The selections that expect the (new) embedding in the response
+        let vector_output_keys: Vec<String> = original_operation
+            .subfields
+            .iter()
+            .filter(|subfield| subfield.name.as_str() == embedding_field.target.vector_field)
+            .map(|subfield| subfield.output_name())
+            .collect();
+
+        for batch in pending.chunks(self.embedding_batch_size) {
+            let vectors = join_all(batch.iter().map(|(_, pk, text)| {
+                self.embed_and_store(
+                    embedding_field,
+                    pk.clone(),
+                    text.clone(),
+                    request_context,
+                    system_resolver,
+                )
+            }))
+            .await;
+
+            for ((index, _, _), vector) in batch.iter().zip(vectors) {
+                let vector = vector?;
+                for key in vector_output_keys.iter() {
+                    entities[*index].insert(key.clone(), vector.clone());
+                }
+            }
+        }
+
+        Ok(QueryResponse {
+            body: QueryResponseBody::Json(body),
+            headers: response.headers,
+        })
+    }
+
+    /// Compute the embedding of `text` with the model query and store it in the entity with the
+    /// given primary key. Returns the embedding.
+    ///
+    /// The model query is typically not exposed to clients (`@access(false)`), so it runs as an
+    /// internal request. The update runs with the mutation's request context (so the entity's
+    /// access control applies).
+    async fn embed_and_store(
+        &self,
+        embedding_field: &EmbeddingField,
+        pk: Val,
+        text: String,
+        request_context: &RequestContext<'_>,
+        system_resolver: &GraphQLSystemResolver,
+    ) -> Result<Value, LlmExecutionError> {
+        // Equivalent to `embed(text: "...")`
+        let model_field = ValidatedField {
+            alias: None,
+            name: Name::new(&embedding_field.model_query_name),
+            arguments: IndexMap::from([("text".to_string(), Val::String(text))]),
+            subfields: vec![],
+        };
+
+        let internal_request = MemoryRequestPayload::new(
+            Value::Null,
+            MemoryRequestHead::new(
+                HashMap::new(),
+                HashMap::new(),
+                http::Method::POST,
+                get_graphql_http_path(request_context.system_context.env),
+                Value::Null,
+                None,
+            ),
+        );
+        let internal_context = request_context.with_request(&internal_request);
+
+        let vector = system_resolver
+            .resolve_operation(OperationType::Query, &model_field, &internal_context)
+            .await?
+            .body
+            .to_json()?;
+
+        if !vector.is_array() {
+            return Err(LlmExecutionError::Generic(format!(
+                "'{}' did not return an embedding",
+                embedding_field.model_query_name
+            )));
+        }
+
+        self.store_embedding(
+            &embedding_field.target,
+            pk,
+            Val::from(vector.clone()),
+            request_context,
+            system_resolver,
+        )
+        .await?;
+
+        Ok(vector)
+    }
+}
+
+/// Does the mutation require (re)computing the embedding?
+fn requires_sync(
+    embedding_field: &EmbeddingField,
+    kind: EmbeddingSyncKind,
+    operation: &ValidatedField,
+) -> bool {
+    let data = operation.arguments.get("data");
+
+    // An explicitly provided embedding takes precedence
+    if sets_field(data, &embedding_field.target.vector_field) {
+        return false;
+    }
+
+    match kind {
+        EmbeddingSyncKind::Create => true,
+        EmbeddingSyncKind::Update => embedding_field
+            .source_fields
+            .iter()
+            .any(|field| sets_field(data, field)),
+    }
+}
+
+/// Does the `data` argument set the field (in every element, for collection mutations)?
+fn sets_field(data: Option<&Val>, field_name: &str) -> bool {
+    match data {
+        Some(Val::Object(data)) => data.contains_key(field_name),
+        Some(Val::List(elements)) => {
+            !elements.is_empty()
+                && elements
+                    .iter()
+                    .all(|element| sets_field(Some(element), field_name))
+        }
+        _ => false,
+    }
+}
+
+fn aliased_field(alias: &str, name: &str) -> ValidatedField {
+    ValidatedField {
+        alias: Some(Name::new(alias)),
+        name: Name::new(name),
+        arguments: IndexMap::new(),
+        subfields: vec![],
+    }
+}
+
+fn source_field_alias(field_name: &str) -> String {
+    format!("__embedding_{field_name}")
+}
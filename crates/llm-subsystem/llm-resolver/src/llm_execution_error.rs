@@ -0,0 +1,80 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use common::context::ContextExtractionError;
+use core_resolver::{
+    access_solver::AccessSolverError, plugin::SubsystemResolutionError,
+    system_resolver::SystemResolutionError,
+};
+use reqwest::StatusCode;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LlmExecutionError {
+    #[error("{0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("LLM provider responded with {0}: {1}")]
+    Status(StatusCode, String),
+
+    #[error("Unexpected response from the LLM provider: {0}")]
+    InvalidResponse(String),
+
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid argument {0}")]
+    InvalidArgument(String),
+
+    #[error("Not authorized")]
+    Authorization,
+
+    #[error("{0}")]
+    Generic(String),
+
+    #[error("{0}")]
+    ContextExtraction(#[from] ContextExtractionError),
+
+    #[error("{0}")]
+    Resolution(#[from] SystemResolutionError),
+}
+
+impl From<AccessSolverError> for LlmExecutionError {
+    fn from(error: AccessSolverError) -> Self {
+        match error {
+            AccessSolverError::ContextExtraction(e) => LlmExecutionError::ContextExtraction(e),
+            _ => LlmExecutionError::Generic(error.to_string()),
+        }
+    }
+}
+
+impl From<LlmExecutionError> for SubsystemResolutionError {
+    fn from(e: LlmExecutionError) -> Self {
+        match e {
+            LlmExecutionError::Authorization => SubsystemResolutionError::Authorization,
+            LlmExecutionError::ContextExtraction(ce) => {
+                SubsystemResolutionError::ContextExtraction(ce)
+            }
+            LlmExecutionError::InvalidArgument(_) => {
+                SubsystemResolutionError::UserDisplayError(e.to_string())
+            }
+            // Errors from the delegated update mutation (of `embedInto`) have already been
+            // classified by the subsystem that produced them
+            LlmExecutionError::Resolution(SystemResolutionError::SubsystemResolutionError(e)) => e,
+            LlmExecutionError::Resolution(e) => {
+                SubsystemResolutionError::UserDisplayError(e.user_error_message())
+            }
+            _ => {
+                tracing::error!("LLM operation failed: {e}");
+                // Do not reveal too much information about the error
+                SubsystemResolutionError::UserDisplayError("Internal server error".to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use common::env_const::{EXO_LLM_EMBEDDING_BATCH_SIZE, EXO_LLM_MAX_RETRIES, EXO_LLM_TIMEOUT_SECS};
+use core_plugin_interface::interface::{SubsystemLoader, SubsystemLoadingError, SubsystemResolver};
+use core_plugin_shared::{
+    serializable_system::SerializableSubsystem, system_serializer::SystemSerializer,
+};
+use core_resolver::plugin::SubsystemGraphQLResolver;
+use exo_env::Environment;
+use llm_model::subsystem::LlmSubsystem;
+
+use crate::{LlmClient, LlmSubsystemResolver};
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 16;
+
+pub struct LlmSubsystemLoader {}
+
+#[async_trait]
+impl SubsystemLoader for LlmSubsystemLoader {
+    fn id(&self) -> &'static str {
+        "llm"
+    }
+
+    async fn init(
+        &mut self,
+        serialized_subsystem: SerializableSubsystem,
+        env: Arc<dyn Environment>,
+    ) -> Result<Box<SubsystemResolver>, SubsystemLoadingError> {
+        let graphql = match serialized_subsystem.graphql {
+            Some(graphql) => {
+                let subsystem = LlmSubsystem::deserialize(graphql.0)?;
+
+                // Read every API key upfront, so that a missing key fails the startup (and not
+                // the first request)
+                let mut api_keys = HashMap::new();
+                for (_, operation) in subsystem.operations.iter() {
+                    let key_env = &operation.client.api_key_env;
+                    if api_keys.contains_key(key_env) {
+                        continue;
+                    }
+                    let api_key = env.get(key_env).ok_or_else(|| {
+                        SubsystemLoadingError::Config(format!(
+                            "{key_env} must be set to use @llm modules with the '{}' provider",
+                            operation.client.provider.name()
+                        ))
+                    })?;
+                    api_keys.insert(key_env.clone(), api_key);
+                }
+
+                let max_retries =
+                    env_number(env.as_ref(), EXO_LLM_MAX_RETRIES)?.unwrap_or(DEFAULT_MAX_RETRIES);
+                let timeout_secs =
+                    env_number(env.as_ref(), EXO_LLM_TIMEOUT_SECS)?.unwrap_or(DEFAULT_TIMEOUT_SECS);
+                let embedding_batch_size = env_number(env.as_ref(), EXO_LLM_EMBEDDING_BATCH_SIZE)?
+                    .unwrap_or(DEFAULT_EMBEDDING_BATCH_SIZE);
+                if embedding_batch_size == 0 {
+                    return Err(SubsystemLoadingError::Config(format!(
+                        "{EXO_LLM_EMBEDDING_BATCH_SIZE} must be a positive integer"
+                    )));
+                }
+
+                let client = LlmClient::new(Duration::from_secs(timeout_secs), max_retries)
+                    .map_err(|e| {
+                        SubsystemLoadingError::Config(format!(
+                            "Failed to set up the LLM client: {e}"
+                        ))
+                    })?;
+
+                Ok::<_, SubsystemLoadingError>(Some(Arc::new(LlmSubsystemResolver {
+                    id: self.id(),
+                    subsystem,
+                    client,
+                    api_keys,
+                    embedding_batch_size,
+                })
+                    as Arc<dyn SubsystemGraphQLResolver + Send + Sync>))
+            }
+            None => Ok(None),
+        }?;
+
+        Ok(Box::new(SubsystemResolver::new(graphql, None, None)))
+    }
+}
+
+fn env_number<T: std::str::FromStr>(
+    env: &dyn Environment,
+    key: &str,
+) -> Result<Option<T>, SubsystemLoadingError> {
+    env.get(key)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                SubsystemLoadingError::Config(format!("{key} must be a non-negative integer"))
+            })
+        })
+        .transpose()
+}
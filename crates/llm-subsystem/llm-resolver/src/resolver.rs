@@ -0,0 +1,312 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
+use async_graphql_value::{Name, indexmap::IndexMap};
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use common::context::RequestContext;
+use common::value::Val;
+use core_plugin_shared::interception::InterceptorIndex;
+use core_resolver::{
+    InterceptedOperation, QueryResponse, QueryResponseBody,
+    access_solver::AccessSolver,
+    plugin::{SubsystemGraphQLResolver, SubsystemResolutionError},
+    system_resolver::GraphQLSystemResolver,
+    validation::field::ValidatedField,
+};
+use llm_model::{
+    function::{EmbeddingTarget, LlmFunction, LlmOperation},
+    module::ModuleMethod,
+    subsystem::LlmSubsystem,
+};
+
+use crate::{LlmClient, client::CompletionRequest, llm_execution_error::LlmExecutionError};
+
+pub struct LlmSubsystemResolver {
+    pub id: &'static str,
+    pub subsystem: LlmSubsystem,
+    pub client: LlmClient,
+    /// Keyed by the name of the environment variable that provided the key
+    pub api_keys: HashMap<String, String>,
+    /// Maximum number of embeddings computed concurrently when syncing `@embedding` fields
+    pub embedding_batch_size: usize,
+}
+
+#[async_trait]
+impl SubsystemGraphQLResolver for LlmSubsystemResolver {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn resolve<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        operation_type: OperationType,
+        request_context: &'a RequestContext<'a>,
+        system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        let operation_name = &field.name;
+
+        let method_id = match operation_type {
+            OperationType::Query => self
+                .subsystem
+                .queries
+                .get_by_key(operation_name)
+                .map(|query| query.method_id),
+            OperationType::Mutation => self
+                .subsystem
+                .mutations
+                .get_by_key(operation_name)
+                .map(|mutation| mutation.method_id),
+            OperationType::Subscription => {
+                return Err(SubsystemResolutionError::UserDisplayError(
+                    "Subscriptions are not supported".to_string(),
+                ));
+            }
+        };
+
+        // TODO: Remove unwrap() by changing the type of method_id
+        let Some(method) = method_id.map(|method_id| &self.subsystem.methods[method_id.unwrap()])
+        else {
+            return Ok(None);
+        };
+
+        let Some(operation) = self.subsystem.operations.get_by_key(&method.name) else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.execute(method, operation, field, request_context, system_resolver)
+                .await?,
+        ))
+    }
+
+    async fn invoke_interceptor<'a>(
+        &'a self,
+        interceptor_index: InterceptorIndex,
+        intercepted_operation: &'a InterceptedOperation<'a>,
+        request_context: &'a RequestContext<'a>,
+        system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        let interceptor = self
+            .subsystem
+            .embedding_interceptors
+            .get(interceptor_index.0)
+            .ok_or(SubsystemResolutionError::NoInterceptorFound)?;
+        let embedding_field = self
+            .subsystem
+            .embedding_fields
+            .get(interceptor.field_index)
+            .ok_or(SubsystemResolutionError::NoInterceptorFound)?;
+
+        Ok(Some(
+            self.sync_embeddings(
+                embedding_field,
+                interceptor.kind,
+                intercepted_operation,
+                request_context,
+                system_resolver,
+            )
+            .await?,
+        ))
+    }
+
+    fn schema_queries(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_queries()
+    }
+
+    fn schema_mutations(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_mutations()
+    }
+
+    fn schema_types(&self) -> Vec<TypeDefinition> {
+        self.subsystem.schema_types()
+    }
+}
+
+impl LlmSubsystemResolver {
+    async fn execute(
+        &self,
+        method: &ModuleMethod,
+        operation: &LlmOperation,
+        field: &ValidatedField,
+        request_context: &RequestContext<'_>,
+        system_resolver: &GraphQLSystemResolver,
+    ) -> Result<QueryResponse, LlmExecutionError> {
+        if !self.check_access(method, request_context).await? {
+            warn!("[LlmOperation] access denied for '{}'", method.name);
+            return Err(LlmExecutionError::Authorization);
+        }
+
+        debug!(
+            "[LlmOperation] executing '{}' ({})",
+            method.name,
+            operation.function.name()
+        );
+
+        let client = &operation.client;
+        // Keys for all operations are read when the subsystem is loaded
+        let api_key = self.api_keys.get(&client.api_key_env).ok_or_else(|| {
+            LlmExecutionError::Generic(format!("{} is not set", client.api_key_env))
+        })?;
+
+        let result = match operation.function {
+            LlmFunction::Complete => {
+                let request = CompletionRequest {
+                    prompt: &string_arg(field, "prompt")?,
+                    system: optional_string_arg(field, "system")?,
+                    max_tokens: optional_int_arg(field, "maxTokens")?,
+                    temperature: optional_float_arg(field, "temperature")?,
+                };
+                self.client
+                    .complete(client, api_key, &request)
+                    .await?
+                    .into()
+            }
+            LlmFunction::Embed => {
+                let text = string_arg(field, "text")?;
+                self.client.embed(client, api_key, &text).await?.into()
+            }
+            LlmFunction::EmbedInto => {
+                let target = operation.embedding_target.as_ref().ok_or_else(|| {
+                    LlmExecutionError::Generic(format!(
+                        "Missing embedding target for '{}'",
+                        method.name
+                    ))
+                })?;
+                let pk = field
+                    .arguments
+                    .get(&target.pk_field)
+                    .cloned()
+                    .ok_or_else(|| LlmExecutionError::InvalidArgument(target.pk_field.clone()))?;
+                let text = string_arg(field, "text")?;
+
+                let embedding = self.client.embed(client, api_key, &text).await?;
+                let vector = Val::List(
+                    embedding
+                        .into_iter()
+                        .map(|value| Val::Number(value.into()))
+                        .collect(),
+                );
+
+                self.store_embedding(target, pk, vector, request_context, system_resolver)
+                    .await?
+                    .into()
+            }
+        };
+
+        Ok(QueryResponse {
+            body: QueryResponseBody::Json(result),
+            headers: vec![],
+        })
+    }
+
+    /// Write the embedding through the entity's update mutation (so that its access control
+    /// applies). Returns whether an entity was updated.
+    pub(crate) async fn store_embedding(
+        &self,
+        target: &EmbeddingTarget,
+        pk: Val,
+        vector: Val,
+        request_context: &RequestContext<'_>,
+        system_resolver: &GraphQLSystemResolver,
+    ) -> Result<bool, LlmExecutionError> {
+        // Equivalent to `updateDocument(id: ..., data: {embedding: [...]}) { id }`
+        let update_field = ValidatedField {
+            alias: None,
+            name: Name::new(&target.update_mutation_name),
+            arguments: IndexMap::from([
+                (target.pk_field.clone(), pk),
+                (
+                    "data".to_string(),
+                    Val::Object(HashMap::from([(target.vector_field.clone(), vector)])),
+                ),
+            ]),
+            subfields: vec![ValidatedField {
+                alias: None,
+                name: Name::new(&target.pk_field),
+                arguments: IndexMap::new(),
+                subfields: vec![],
+            }],
+        };
+
+        let response = system_resolver
+            .resolve_operation(OperationType::Mutation, &update_field, request_context)
+            .await?;
+
+        // The update mutation returns null if no (accessible) entity matched the primary key
+        Ok(!response.body.to_json()?.is_null())
+    }
+
+    async fn check_access(
+        &self,
+        method: &ModuleMethod,
+        request_context: &RequestContext<'_>,
+    ) -> Result<bool, LlmExecutionError> {
+        if request_context.is_internal() {
+            return Ok(true);
+        }
+
+        Ok(self
+            .subsystem
+            .solve(request_context, None, &method.access.value)
+            .await?
+            .map(|predicate| predicate.0)
+            .resolve())
+    }
+}
+
+fn optional_int_arg(field: &ValidatedField, name: &str) -> Result<Option<i64>, LlmExecutionError> {
+    match field.arguments.get(name) {
+        None | Some(Val::Null) => Ok(None),
+        Some(Val::Number(number)) => number
+            .as_i64()
+            .map(Some)
+            .ok_or_else(|| LlmExecutionError::InvalidArgument(name.to_string())),
+        Some(_) => Err(LlmExecutionError::InvalidArgument(name.to_string())),
+    }
+}
+
+fn optional_float_arg(
+    field: &ValidatedField,
+    name: &str,
+) -> Result<Option<f64>, LlmExecutionError> {
+    match field.arguments.get(name) {
+        None | Some(Val::Null) => Ok(None),
+        Some(Val::Number(number)) => Ok(number.as_f64()),
+        Some(_) => Err(LlmExecutionError::InvalidArgument(name.to_string())),
+    }
+}
+
+fn optional_string_arg<'a>(
+    field: &'a ValidatedField,
+    name: &str,
+) -> Result<Option<&'a str>, LlmExecutionError> {
+    match field.arguments.get(name) {
+        None | Some(Val::Null) => Ok(None),
+        Some(Val::String(value)) => Ok(Some(value)),
+        Some(_) => Err(LlmExecutionError::InvalidArgument(name.to_string())),
+    }
+}
+
+fn string_arg(field: &ValidatedField, name: &str) -> Result<String, LlmExecutionError> {
+    match field.arguments.get(name) {
+        Some(Val::String(value)) => Ok(value.clone()),
+        _ => Err(LlmExecutionError::InvalidArgument(name.to_string())),
+    }
+}
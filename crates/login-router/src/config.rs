@@ -0,0 +1,103 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use common::env_const::{
+    EXO_JWT_SOURCE_COOKIE, EXO_LOGIN_CLIENT_ID, EXO_LOGIN_CLIENT_SECRET,
+    EXO_LOGIN_POST_LOGOUT_REDIRECT_URL, EXO_LOGIN_REDIRECT_URL, EXO_LOGIN_SCOPES,
+    EXO_LOGIN_SESSION_TOKEN, EXO_OIDC_URL, get_login_http_path,
+};
+use core_router::SystemLoadingError;
+use exo_env::Environment;
+use url::Url;
+
+/// The token kept in the session cookie (and so, the one the server validates with each request)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SessionToken {
+    IdToken,
+    AccessToken,
+}
+
+impl SessionToken {
+    /// The name of the token in the provider's token response
+    pub fn name(&self) -> &'static str {
+        match self {
+            SessionToken::IdToken => "id_token",
+            SessionToken::AccessToken => "access_token",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LoginConfig {
+    /// The path under which the endpoints are served (without a trailing slash)
+    pub path: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: Url,
+    pub scopes: String,
+    pub session_token: SessionToken,
+    /// The cookie to keep the session token in (the one the server reads the token from). Without
+    /// it, the tokens are handed to the application in the fragment of the redirect.
+    pub session_cookie: Option<String>,
+    pub post_logout_redirect_url: Option<String>,
+}
+
+impl LoginConfig {
+    /// The configuration of the login endpoints (or `None` if they aren't enabled)
+    pub fn from_env(env: &dyn Environment) -> Result<Option<Self>, SystemLoadingError> {
+        let Some(client_id) = env.get(EXO_LOGIN_CLIENT_ID) else {
+            return Ok(None);
+        };
+
+        let issuer = env.get(EXO_OIDC_URL).ok_or_else(|| {
+            SystemLoadingError::Config(format!(
+                "{EXO_LOGIN_CLIENT_ID} requires {EXO_OIDC_URL} to be set"
+            ))
+        })?;
+
+        let redirect_url = env.get(EXO_LOGIN_REDIRECT_URL).ok_or_else(|| {
+            SystemLoadingError::Config(format!(
+                "{EXO_LOGIN_CLIENT_ID} requires {EXO_LOGIN_REDIRECT_URL} to be set"
+            ))
+        })?;
+        let redirect_url = Url::parse(&redirect_url).map_err(|e| {
+            SystemLoadingError::Config(format!("Invalid {EXO_LOGIN_REDIRECT_URL}: {e}"))
+        })?;
+
+        let session_token = match env.get(EXO_LOGIN_SESSION_TOKEN).as_deref() {
+            None | Some("id_token") => SessionToken::IdToken,
+            Some("access_token") => SessionToken::AccessToken,
+            Some(other) => {
+                return Err(SystemLoadingError::Config(format!(
+                    "Invalid {EXO_LOGIN_SESSION_TOKEN} '{other}' (expected 'id_token' or 'access_token')"
+                )));
+            }
+        };
+
+        Ok(Some(Self {
+            path: get_login_http_path(env).trim_end_matches('/').to_string(),
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret: env.get(EXO_LOGIN_CLIENT_SECRET),
+            redirect_url,
+            scopes: env
+                .get(EXO_LOGIN_SCOPES)
+                .unwrap_or_else(|| "openid profile email".to_string()),
+            session_token,
+            session_cookie: env.get(EXO_JWT_SOURCE_COOKIE),
+            post_logout_redirect_url: env.get(EXO_LOGIN_POST_LOGOUT_REDIRECT_URL),
+        }))
+    }
+
+    /// Whether the cookies may only be sent over HTTPS (when the server is reached over HTTPS)
+    pub fn secure_cookies(&self) -> bool {
+        self.redirect_url.scheme() == "https"
+    }
+}
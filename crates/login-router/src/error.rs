@@ -0,0 +1,25 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub(crate) enum LoginError {
+    #[error("Unable to discover the OIDC provider's endpoints: {0}")]
+    Discovery(String),
+
+    #[error("Unable to exchange the authorization code: {0}")]
+    TokenExchange(String),
+
+    #[error("The provider's response doesn't include an `{0}`")]
+    MissingToken(&'static str),
+
+    #[error("The ID token's nonce doesn't match the login request")]
+    NonceMismatch,
+}
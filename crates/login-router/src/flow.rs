@@ -0,0 +1,164 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The state of a login in progress, which is kept in a short-lived cookie between redirecting the
+//! user to the provider and the provider redirecting them back.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::{Rng, distr::Alphanumeric};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// The name of the cookie with the login flow's state
+pub(crate) const FLOW_COOKIE: &str = "exo_login";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LoginFlow {
+    /// Sent to the provider and expected back unchanged (guards against forged callbacks)
+    pub state: String,
+    /// The PKCE code verifier (the provider gets only its challenge)
+    pub verifier: String,
+    /// Sent to the provider and expected in the ID token (guards against replayed tokens)
+    pub nonce: String,
+    /// Where to send the user once logged in
+    pub return_to: String,
+}
+
+impl LoginFlow {
+    pub fn new(return_to: String) -> Self {
+        Self {
+            state: random_string(32),
+            // RFC 7636 requires 43 to 128 characters
+            verifier: random_string(64),
+            nonce: random_string(32),
+            return_to,
+        }
+    }
+
+    pub fn code_challenge(&self) -> String {
+        code_challenge(&self.verifier)
+    }
+
+    /// The value of the flow cookie
+    pub fn encode(&self) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            self.state,
+            self.verifier,
+            self.nonce,
+            URL_SAFE_NO_PAD.encode(&self.return_to)
+        )
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.split('.');
+        let (Some(state), Some(verifier), Some(nonce), Some(return_to), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return None;
+        };
+
+        let return_to = String::from_utf8(URL_SAFE_NO_PAD.decode(return_to).ok()?).ok()?;
+
+        Some(Self {
+            state: state.to_string(),
+            verifier: verifier.to_string(),
+            nonce: nonce.to_string(),
+            return_to: sanitize_return_to(Some(&return_to)),
+        })
+    }
+}
+
+/// The S256 PKCE challenge for the verifier
+pub(crate) fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// The path to return to after logging in or out. Only paths on this server are allowed (so that
+/// the endpoints can't be used to redirect users to arbitrary sites).
+pub(crate) fn sanitize_return_to(return_to: Option<&str>) -> String {
+    match return_to {
+        Some(return_to)
+            if return_to.starts_with('/')
+                && !return_to.starts_with("//")
+                && !return_to.starts_with("/\\")
+                && !return_to.chars().any(|c| c.is_control()) =>
+        {
+            // Drop any fragment, since the tokens may be passed in it
+            return_to.split('#').next().unwrap_or(return_to).to_string()
+        }
+        _ => "/".to_string(),
+    }
+}
+
+/// The `nonce` claim of the ID token. The token's signature isn't verified here, since it comes
+/// straight from the provider's token endpoint (and the server verifies it with each request).
+pub(crate) fn id_token_nonce(id_token: &str) -> Option<String> {
+    let payload = id_token.split('.').nth(1)?;
+    let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    claims["nonce"].as_str().map(|nonce| nonce.to_string())
+}
+
+fn random_string(len: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_of_verifier() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mJ0kzV8IDIgDVuzBHSJV_BFAZ1CoXZ"),
+            "PVLN2xf61kSVIE3IBZg-zFzeq1y4LnOzgmhsrHf8WcQ"
+        );
+    }
+
+    #[test]
+    fn return_to_paths() {
+        assert_eq!(sanitize_return_to(None), "/");
+        assert_eq!(
+            sanitize_return_to(Some("/concerts?page=2")),
+            "/concerts?page=2"
+        );
+        assert_eq!(sanitize_return_to(Some("/concerts#top")), "/concerts");
+        assert_eq!(sanitize_return_to(Some("https://evil.example.com")), "/");
+        assert_eq!(sanitize_return_to(Some("//evil.example.com")), "/");
+        assert_eq!(sanitize_return_to(Some("/\\evil.example.com")), "/");
+        assert_eq!(sanitize_return_to(Some("concerts")), "/");
+    }
+
+    #[test]
+    fn flow_cookie_roundtrip() {
+        let flow = LoginFlow::new("/concerts?page=2".to_string());
+
+        assert_eq!(flow.verifier.len(), 64);
+        assert_eq!(LoginFlow::decode(&flow.encode()), Some(flow));
+        assert_eq!(LoginFlow::decode("state.verifier.nonce"), None);
+    }
+
+    #[test]
+    fn nonce_of_id_token() {
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"1","nonce":"abc"}"#);
+        assert_eq!(
+            id_token_nonce(&format!("header.{payload}.signature")),
+            Some("abc".to_string())
+        );
+        assert_eq!(id_token_nonce("not-a-token"), None);
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Built-in endpoints that log users in through the authorization code flow (with PKCE) of the
+//! configured OIDC provider, so that applications (such as single-page apps) don't need to
+//! implement the flow themselves.
+
+#![cfg(not(target_family = "wasm"))]
+
+mod config;
+mod error;
+mod flow;
+mod login_router;
+mod provider;
+
+pub use login_router::LoginRouter;
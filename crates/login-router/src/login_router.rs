@@ -0,0 +1,315 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::context::RequestContext;
+use common::http::{Headers, RequestHead, RequestPayload, ResponseBody, ResponsePayload};
+use common::router::Router;
+use core_router::SystemLoadingError;
+use exo_env::Environment;
+use http::{Method, StatusCode};
+use tracing::{error, warn};
+use url::Url;
+
+use crate::{
+    config::{LoginConfig, SessionToken},
+    error::LoginError,
+    flow::{FLOW_COOKIE, LoginFlow, id_token_nonce, sanitize_return_to},
+    provider::{Provider, TokenResponse},
+};
+
+/// How long a login may take (between redirecting to the provider and its callback)
+const FLOW_MAX_AGE_SECS: u64 = 600;
+
+/// Serves the login endpoints:
+/// - `{path}/login?returnTo=/page`: redirects to the provider's authorization endpoint
+/// - `{path}/callback`: exchanges the provider's authorization code for tokens and establishes the
+///   session (or hands the tokens to the application)
+/// - `{path}/logout?returnTo=/page`: clears the session and logs out of the provider (if it
+///   supports it)
+pub struct LoginRouter {
+    config: LoginConfig,
+    provider: Provider,
+}
+
+impl LoginRouter {
+    /// The router for the configured login endpoints (or `None` if they aren't enabled)
+    pub fn from_env(env: Arc<dyn Environment>) -> Result<Option<Self>, SystemLoadingError> {
+        Ok(LoginConfig::from_env(env.as_ref())?.map(|config| Self {
+            provider: Provider::new(config.issuer.clone()),
+            config,
+        }))
+    }
+
+    async fn login(&self, request_head: &(dyn RequestHead + Send + Sync)) -> ResponsePayload {
+        let metadata = match self.provider.metadata().await {
+            Ok(metadata) => metadata,
+            Err(e) => return provider_error(e),
+        };
+
+        let flow = LoginFlow::new(sanitize_return_to(
+            query_param(request_head, "returnTo").as_deref(),
+        ));
+
+        let mut location = match Url::parse(&metadata.authorization_endpoint) {
+            Ok(location) => location,
+            Err(e) => {
+                return provider_error(LoginError::Discovery(format!(
+                    "Invalid authorization endpoint: {e}"
+                )));
+            }
+        };
+        location
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", self.config.redirect_url.as_str())
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", &flow.state)
+            .append_pair("nonce", &flow.nonce)
+            .append_pair("code_challenge", &flow.code_challenge())
+            .append_pair("code_challenge_method", "S256");
+
+        let mut headers = Headers::new();
+        headers.insert(
+            "Set-Cookie".to_string(),
+            self.set_cookie(
+                FLOW_COOKIE,
+                &flow.encode(),
+                self.config.redirect_url.path(),
+                Some(FLOW_MAX_AGE_SECS),
+            ),
+        );
+
+        redirect(location.to_string(), headers)
+    }
+
+    async fn callback(&self, request_head: &(dyn RequestHead + Send + Sync)) -> ResponsePayload {
+        if let Some(error) = query_param(request_head, "error") {
+            let description = query_param(request_head, "error_description")
+                .map(|description| format!(" ({description})"))
+                .unwrap_or_default();
+            return bad_request(format!(
+                "The provider declined the login: {error}{description}"
+            ));
+        }
+
+        let flow =
+            request_cookie(request_head, FLOW_COOKIE).and_then(|value| LoginFlow::decode(&value));
+        let (Some(flow), Some(state), Some(code)) = (
+            flow,
+            query_param(request_head, "state"),
+            query_param(request_head, "code"),
+        ) else {
+            return bad_request("Missing login state (the login may have expired)".to_string());
+        };
+
+        if flow.state != state {
+            return bad_request("Mismatched login state".to_string());
+        }
+
+        let tokens = match self.exchange_code(&code, &flow).await {
+            Ok(tokens) => tokens,
+            Err(e) => return provider_error(e),
+        };
+
+        let mut headers = Headers::new();
+        headers.insert(
+            "Set-Cookie".to_string(),
+            self.clear_cookie(FLOW_COOKIE, self.config.redirect_url.path()),
+        );
+
+        let location = match &self.config.session_cookie {
+            Some(session_cookie) => {
+                let token = match self.config.session_token {
+                    SessionToken::IdToken => tokens.id_token.as_deref(),
+                    SessionToken::AccessToken => tokens.access_token.as_deref(),
+                };
+                let Some(token) = token else {
+                    return provider_error(LoginError::MissingToken(
+                        self.config.session_token.name(),
+                    ));
+                };
+
+                headers.insert(
+                    "Set-Cookie".to_string(),
+                    self.set_cookie(session_cookie, token, "/", tokens.expires_in),
+                );
+                flow.return_to
+            }
+            None => {
+                // Hand the tokens to the application in the fragment (which browsers don't send
+                // to servers)
+                let mut fragment = url::form_urlencoded::Serializer::new(String::new());
+                for (name, value) in [
+                    ("access_token", tokens.access_token),
+                    ("id_token", tokens.id_token),
+                    ("token_type", tokens.token_type),
+                    ("expires_in", tokens.expires_in.map(|e| e.to_string())),
+                ] {
+                    if let Some(value) = value {
+                        fragment.append_pair(name, &value);
+                    }
+                }
+                format!("{}#{}", flow.return_to, fragment.finish())
+            }
+        };
+
+        redirect(location, headers)
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        flow: &LoginFlow,
+    ) -> Result<TokenResponse, LoginError> {
+        let tokens = self
+            .provider
+            .exchange_code(&self.config, code, &flow.verifier)
+            .await?;
+
+        if let Some(id_token) = &tokens.id_token
+            && id_token_nonce(id_token).as_deref() != Some(flow.nonce.as_str())
+        {
+            return Err(LoginError::NonceMismatch);
+        }
+
+        Ok(tokens)
+    }
+
+    async fn logout(&self, request_head: &(dyn RequestHead + Send + Sync)) -> ResponsePayload {
+        let return_to = sanitize_return_to(query_param(request_head, "returnTo").as_deref());
+
+        let mut headers = Headers::new();
+        let mut id_token_hint = None;
+        if let Some(session_cookie) = &self.config.session_cookie {
+            if self.config.session_token == SessionToken::IdToken {
+                id_token_hint = request_cookie(request_head, session_cookie);
+            }
+            headers.insert(
+                "Set-Cookie".to_string(),
+                self.clear_cookie(session_cookie, "/"),
+            );
+        }
+
+        let end_session_endpoint = match self.provider.metadata().await {
+            Ok(metadata) => metadata.end_session_endpoint.as_deref(),
+            Err(e) => {
+                // Still clear the session, even if the provider is unreachable
+                warn!("{e}");
+                None
+            }
+        };
+
+        let location = match end_session_endpoint.and_then(|endpoint| Url::parse(endpoint).ok()) {
+            Some(mut location) => {
+                {
+                    let mut query = location.query_pairs_mut();
+                    query.append_pair("client_id", &self.config.client_id);
+                    if let Some(post_logout_redirect_url) = &self.config.post_logout_redirect_url {
+                        query.append_pair("post_logout_redirect_uri", post_logout_redirect_url);
+                    }
+                    if let Some(id_token_hint) = &id_token_hint {
+                        query.append_pair("id_token_hint", id_token_hint);
+                    }
+                }
+                location.to_string()
+            }
+            None => return_to,
+        };
+
+        redirect(location, headers)
+    }
+
+    fn set_cookie(&self, name: &str, value: &str, path: &str, max_age: Option<u64>) -> String {
+        let mut cookie = format!("{name}={value}; Path={path}; HttpOnly; SameSite=Lax");
+        if let Some(max_age) = max_age {
+            cookie.push_str(&format!("; Max-Age={max_age}"));
+        }
+        if self.config.secure_cookies() {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+
+    fn clear_cookie(&self, name: &str, path: &str) -> String {
+        format!(
+            "{}; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+            self.set_cookie(name, "", path, Some(0))
+        )
+    }
+}
+
+#[async_trait]
+impl<'a> Router<RequestContext<'a>> for LoginRouter {
+    async fn route(&self, request_context: &RequestContext<'a>) -> Option<ResponsePayload> {
+        let request_head = request_context.get_head();
+        let path = request_head.get_path();
+        let endpoint = path.strip_prefix(&self.config.path)?;
+        let method = request_head.get_method();
+
+        match endpoint {
+            "/login" if method == Method::GET => Some(self.login(request_head).await),
+            "/callback" if method == Method::GET => Some(self.callback(request_head).await),
+            "/logout" if method == Method::GET || method == Method::POST => {
+                Some(self.logout(request_head).await)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn query_param(request_head: &(dyn RequestHead + Send + Sync), name: &str) -> Option<String> {
+    request_head.get_query()[name]
+        .as_str()
+        .map(|value| value.to_string())
+}
+
+fn request_cookie(request_head: &(dyn RequestHead + Send + Sync), name: &str) -> Option<String> {
+    request_head
+        .get_headers("cookie")
+        .iter()
+        .flat_map(|header| header.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie_name, _)| *cookie_name == name)
+        .map(|(_, value)| value.to_string())
+}
+
+fn redirect(location: String, mut headers: Headers) -> ResponsePayload {
+    headers.insert("Cache-Control".to_string(), "no-store".to_string());
+
+    ResponsePayload {
+        body: ResponseBody::Redirect(location),
+        headers,
+        status_code: StatusCode::FOUND,
+    }
+}
+
+fn bad_request(message: String) -> ResponsePayload {
+    text_response(StatusCode::BAD_REQUEST, message)
+}
+
+fn provider_error(e: LoginError) -> ResponsePayload {
+    error!("Login failed: {e}");
+    text_response(StatusCode::BAD_GATEWAY, e.to_string())
+}
+
+fn text_response(status_code: StatusCode, message: String) -> ResponsePayload {
+    let mut headers = Headers::new();
+    headers.insert("Content-Type".to_string(), "text/plain".to_string());
+    headers.insert("Cache-Control".to_string(), "no-store".to_string());
+
+    ResponsePayload {
+        body: ResponseBody::Bytes(message.into_bytes()),
+        headers,
+        status_code,
+    }
+}
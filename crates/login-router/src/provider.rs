@@ -0,0 +1,106 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use crate::{config::LoginConfig, error::LoginError};
+
+/// The endpoints of the provider (from its discovery document)
+#[derive(Debug, Deserialize)]
+pub(crate) struct ProviderMetadata {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub end_session_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TokenResponse {
+    pub access_token: Option<String>,
+    pub id_token: Option<String>,
+    pub token_type: Option<String>,
+    pub expires_in: Option<u64>,
+}
+
+pub(crate) struct Provider {
+    issuer: String,
+    client: reqwest::Client,
+    // Discovered on the first login (so that an unreachable provider doesn't fail the startup)
+    metadata: OnceCell<ProviderMetadata>,
+}
+
+impl Provider {
+    pub fn new(issuer: String) -> Self {
+        Self {
+            issuer,
+            client: reqwest::Client::new(),
+            metadata: OnceCell::new(),
+        }
+    }
+
+    pub async fn metadata(&self) -> Result<&ProviderMetadata, LoginError> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!("{}/.well-known/openid-configuration", self.issuer);
+
+                self.client
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| LoginError::Discovery(e.to_string()))?
+                    .json::<ProviderMetadata>()
+                    .await
+                    .map_err(|e| LoginError::Discovery(e.to_string()))
+            })
+            .await
+    }
+
+    /// Exchange the authorization code for tokens (authenticating with the client secret, if
+    /// any, in the request body)
+    pub async fn exchange_code(
+        &self,
+        config: &LoginConfig,
+        code: &str,
+        verifier: &str,
+    ) -> Result<TokenResponse, LoginError> {
+        let token_endpoint = &self.metadata().await?.token_endpoint;
+
+        let mut form = url::form_urlencoded::Serializer::new(String::new());
+        form.append_pair("grant_type", "authorization_code")
+            .append_pair("code", code)
+            .append_pair("redirect_uri", config.redirect_url.as_str())
+            .append_pair("client_id", &config.client_id)
+            .append_pair("code_verifier", verifier);
+        if let Some(client_secret) = &config.client_secret {
+            form.append_pair("client_secret", client_secret);
+        }
+
+        let response = self
+            .client
+            .post(token_endpoint)
+            .header("content-type", "application/x-www-form-urlencoded")
+            .header("accept", "application/json")
+            .body(form.finish())
+            .send()
+            .await
+            .map_err(|e| LoginError::TokenExchange(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(LoginError::TokenExchange(format!("{status}: {body}")));
+        }
+
+        response
+            .json::<TokenResponse>()
+            .await
+            .map_err(|e| LoginError::TokenExchange(e.to_string()))
+    }
+}
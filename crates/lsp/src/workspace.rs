@@ -1,5 +1,5 @@
 use anyhow::Result;
-use builder::{FileSystem, build_from_ast_system, error::ParserError, parser};
+use builder::{FileSystem, build_from_ast_system, error::ParserError, parser, report};
 use codemap::CodeMap;
 use core_plugin_shared::trusted_documents::TrustedDocuments;
 use std::{
@@ -8,9 +8,9 @@ use std::{
 };
 
 use dashmap::DashMap;
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
 
-use core_model_builder::{error::ModelBuildingError, plugin::BuildMode};
+use core_model_builder::plugin::BuildMode;
 use core_plugin_interface::interface::SubsystemBuilder;
 
 #[derive(Debug)]
@@ -66,6 +66,10 @@ impl Workspace {
             Box::new(postgres_builder::PostgresSubsystemBuilder::default()),
             Box::new(deno_builder::DenoSubsystemBuilder::default()),
             Box::new(wasm_builder::WasmSubsystemBuilder::default()),
+            Box::new(redis_builder::RedisSubsystemBuilder::default()),
+            Box::new(search_builder::SearchSubsystemBuilder::default()),
+            Box::new(messaging_builder::MessagingSubsystemBuilder::default()),
+            Box::new(llm_builder::LlmSubsystemBuilder::default()),
         ];
 
         let file_content = self.read_file(&index_file)?;
@@ -101,21 +105,7 @@ impl Workspace {
                     .collect::<Vec<_>>();
                 Ok(diagnostics)
             }
-            Ok(Err(err)) => match err {
-                ParserError::Diagnosis(diagnosis) => {
-                    let diagnostics: Vec<_> = self.compute_diagnostics(diagnosis, &codemap);
-                    Ok(diagnostics)
-                }
-                ParserError::ModelBuildingError(parser_error) => {
-                    if let ModelBuildingError::Diagnosis(diagnosis) = parser_error {
-                        let diagnostics: Vec<_> = self.compute_diagnostics(diagnosis, &codemap);
-                        Ok(diagnostics)
-                    } else {
-                        Ok(self.generic_diagnostic(parser_error.to_string()))
-                    }
-                }
-                err => Ok(self.generic_diagnostic(err.to_string())),
-            },
+            Ok(Err(err)) => Ok(self.compute_diagnostics(report::diagnostics(&err, &codemap))),
             Err(err) => {
                 eprintln!("build_result: {:?}", err);
                 Ok(self.generic_diagnostic("Unknown error while building model".to_string()))
@@ -137,53 +127,58 @@ impl Workspace {
 
     pub fn compute_diagnostics(
         &self,
-        exo_diagnostics: Vec<codemap_diagnostic::Diagnostic>,
-        codemap: &CodeMap,
+        exo_diagnostics: Vec<report::Diagnostic>,
     ) -> Vec<(PathBuf, Option<i32>, Vec<Diagnostic>)> {
         exo_diagnostics
             .into_iter()
             .map(|d| {
-                if d.spans.is_empty() {
-                    (
+                let code = d.code.clone().map(NumberOrString::String);
+
+                let primary_span = d.primary_span().map(|span| {
+                    let range = Range {
+                        start: Position {
+                            line: span.start.line as u32,
+                            character: span.start.column as u32,
+                        },
+                        end: Position {
+                            line: span.end.line as u32,
+                            character: span.end.column as u32,
+                        },
+                    };
+                    (PathBuf::from(&span.file_name), range)
+                });
+
+                match primary_span {
+                    None => (
                         self.root.join("src").join("index.exo"),
                         None,
                         vec![Diagnostic {
                             message: d.message,
+                            code,
                             severity: Some(DiagnosticSeverity::ERROR),
                             ..Default::default()
                         }],
-                    )
-                } else {
-                    let span = d.spans[0].span;
-                    let loc = codemap.look_up_span(span);
-
-                    let message = if d.spans.len() == 1 {
-                        d.message
-                    } else {
-                        format!("{} ({} more)", d.message, d.spans.len() - 1)
-                    };
-
-                    let path = PathBuf::from(loc.file.name());
-                    let version = self.documents.get(&path).unwrap().version;
-                    (
-                        path,
-                        version,
-                        vec![Diagnostic {
-                            range: Range {
-                                start: Position {
-                                    line: loc.begin.line as u32,
-                                    character: loc.begin.column as u32,
-                                },
-                                end: Position {
-                                    line: loc.end.line as u32,
-                                    character: loc.end.column as u32,
-                                },
-                            },
-                            message,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            ..Default::default()
-                        }],
-                    )
+                    ),
+                    Some((path, range)) => {
+                        let message = if d.spans.len() == 1 {
+                            d.message
+                        } else {
+                            format!("{} ({} more)", d.message, d.spans.len() - 1)
+                        };
+
+                        let version = self.documents.get(&path).unwrap().version;
+                        (
+                            path,
+                            version,
+                            vec![Diagnostic {
+                                range,
+                                message,
+                                code,
+                                severity: Some(DiagnosticSeverity::ERROR),
+                                ..Default::default()
+                            }],
+                        )
+                    }
                 }
             })
             .collect()
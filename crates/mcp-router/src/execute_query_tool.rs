@@ -144,23 +144,35 @@ impl Tool for ExecuteQueryTool {
 
         match graphql_response {
             Ok(graphql_response) => {
-                let (response_contents, response_headers): (Vec<_>, Vec<_>) = graphql_response
-                    .into_iter()
-                    .map(|(name, response)| {
-                        let content_string = match response.body {
-                            QueryResponseBody::Json(value) => value.to_string(),
-                            QueryResponseBody::Raw(value) => value.unwrap_or_default(),
-                        };
-
-                        let text = json!({
-                            "name": name,
-                            "response": content_string,
-                        })
-                        .to_string();
-
-                        (text, response.headers)
+                let mut response_contents = Vec::with_capacity(graphql_response.len());
+                let mut response_headers = Vec::with_capacity(graphql_response.len());
+
+                for (name, response) in graphql_response {
+                    let content_string = match response.body {
+                        QueryResponseBody::Json(value) => value.to_string(),
+                        QueryResponseBody::Raw(value) => value.unwrap_or_default(),
+                        QueryResponseBody::Stream(stream) => stream
+                            .collect()
+                            .await
+                            .map_err(|err| {
+                                tracing::error!(
+                                    "Error while collecting streamed response: {}",
+                                    err
+                                );
+                                SubsystemRpcError::InternalError
+                            })?
+                            .to_string(),
+                    };
+
+                    let text = json!({
+                        "name": name,
+                        "response": content_string,
                     })
-                    .unzip();
+                    .to_string();
+
+                    response_contents.push(text);
+                    response_headers.push(response.headers);
+                }
 
                 Ok((
                     response_contents,
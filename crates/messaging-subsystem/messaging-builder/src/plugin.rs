@@ -0,0 +1,100 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+use async_trait::async_trait;
+
+use core_model_builder::{
+    builder::system_builder::BaseModelSystem,
+    error::ModelBuildingError,
+    plugin::{BuildMode, CoreSubsystemBuild, GraphQLSubsystemBuild},
+    typechecker::{
+        annotation::{AnnotationSpec, AnnotationTarget, MappedAnnotationParamSpec},
+        typ::TypecheckedSystem,
+    },
+};
+use core_plugin_interface::interface::{SubsystemBuild, SubsystemBuilder};
+use core_plugin_shared::{
+    serializable_system::{SerializableCoreBytes, SerializableGraphQLBytes},
+    system_serializer::SystemSerializer,
+};
+
+#[derive(Default)]
+pub struct MessagingSubsystemBuilder {}
+
+#[async_trait]
+impl SubsystemBuilder for MessagingSubsystemBuilder {
+    fn id(&self) -> &'static str {
+        "messaging"
+    }
+
+    fn annotations(&self) -> Vec<(&'static str, AnnotationSpec)> {
+        vec![(
+            // `@consumes("orders.created")` or
+            // `@consumes(subject="orders.created", group="billing", maxAttempts=3, deadLetter="orders.dlq")`
+            "consumes",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Method],
+                no_params: false,
+                single_params: true,
+                mapped_params: Some(&[
+                    MappedAnnotationParamSpec {
+                        name: "subject",
+                        optional: false,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "group",
+                        optional: true,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "maxAttempts",
+                        optional: true,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "deadLetter",
+                        optional: true,
+                    },
+                ]),
+            },
+        )]
+    }
+
+    async fn build(
+        &self,
+        typechecked_system: &TypecheckedSystem,
+        _base_system: &BaseModelSystem,
+        _build_mode: BuildMode,
+    ) -> Result<Option<SubsystemBuild>, ModelBuildingError> {
+        let Some(subsystem) = crate::system_builder::build(typechecked_system)? else {
+            return Ok(None);
+        };
+
+        let serialized_subsystem = subsystem
+            .serialize()
+            .map_err(ModelBuildingError::Serialize)?;
+
+        // Consumers invoke the (Deno-provided) mutations through the system router, so this
+        // subsystem doesn't contribute any operations of its own
+        let graphql = GraphQLSubsystemBuild {
+            id: self.id().to_string(),
+            serialized_subsystem: SerializableGraphQLBytes(serialized_subsystem),
+            query_names: vec![],
+            mutation_names: vec![],
+            interceptions: vec![],
+        };
+
+        Ok(Some(SubsystemBuild {
+            id: self.id(),
+            graphql: Some(graphql),
+            rest: None,
+            rpc: None,
+            core: CoreSubsystemBuild {
+                serialized_subsystem: SerializableCoreBytes(vec![]),
+            },
+        }))
+    }
+}
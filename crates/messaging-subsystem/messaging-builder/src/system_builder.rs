@@ -0,0 +1,276 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+
+use core_model_builder::{
+    ast::ast_types::{
+        AstAnnotation, AstAnnotationParams, AstExpr, AstFieldType, AstMethod, AstMethodType,
+    },
+    builder::resolved_builder::AnnotationMapHelper,
+    error::ModelBuildingError,
+    typechecker::{
+        Typed,
+        typ::{Module, Type, TypecheckedSystem},
+    },
+};
+use messaging_model::{
+    consumer::{ConsumerArgument, DEFAULT_MAX_ATTEMPTS, MessageConsumer, PayloadKind},
+    subsystem::MessagingSubsystem,
+};
+
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Option<MessagingSubsystem>, ModelBuildingError> {
+    let mut consumers = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let is_deno_module = module.annotations.contains("deno");
+
+        for method in module.methods.iter() {
+            let Some(annotation) = method.annotations.annotations.get("consumes") else {
+                continue;
+            };
+
+            if !is_deno_module {
+                errors.push(error(
+                    annotation,
+                    format!(
+                        "@consumes may only be used on methods in a @deno module (found on '{}')",
+                        method.name
+                    ),
+                ));
+                continue;
+            }
+
+            if let Some(consumer) =
+                build_consumer(method, annotation, typechecked_system, &mut errors)
+            {
+                consumers.push(consumer);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(ModelBuildingError::Diagnosis(errors));
+    }
+
+    for (index, consumer) in consumers.iter().enumerate() {
+        if consumers[..index]
+            .iter()
+            .any(|other| other.group == consumer.group && other.subject == consumer.subject)
+        {
+            return Err(ModelBuildingError::Generic(format!(
+                "Multiple @consumes methods use the group '{}' for subject '{}'",
+                consumer.group, consumer.subject
+            )));
+        }
+    }
+
+    if consumers.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(MessagingSubsystem { consumers }))
+}
+
+fn build_consumer(
+    method: &AstMethod<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    typechecked_system: &TypecheckedSystem,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<MessageConsumer> {
+    if method.typ != AstMethodType::Mutation {
+        errors.push(error(
+            annotation,
+            format!(
+                "@consumes may only be used on mutations ('{}' is a query)",
+                method.name
+            ),
+        ));
+        return None;
+    }
+
+    let (subject, group, max_attempts, dead_letter_subject) = match &annotation.params {
+        AstAnnotationParams::Single(AstExpr::StringLiteral(subject, _), _) => {
+            (subject.clone(), None, None, None)
+        }
+        AstAnnotationParams::Map(params, _) => {
+            let subject = match params.get("subject") {
+                Some(AstExpr::StringLiteral(subject, _)) => subject.clone(),
+                _ => {
+                    errors.push(error(annotation, "Expected a string for 'subject'"));
+                    return None;
+                }
+            };
+            let group = match params.get("group") {
+                Some(AstExpr::StringLiteral(group, _)) => Some(group.clone()),
+                Some(_) => {
+                    errors.push(error(annotation, "Expected a string for 'group'"));
+                    return None;
+                }
+                None => None,
+            };
+            let max_attempts = match params.get("maxAttempts") {
+                Some(AstExpr::NumberLiteral(value, _)) => match value.parse::<u32>() {
+                    Ok(value) if value > 0 => Some(value),
+                    _ => {
+                        errors.push(error(
+                            annotation,
+                            "Expected a positive integer for 'maxAttempts'",
+                        ));
+                        return None;
+                    }
+                },
+                Some(_) => {
+                    errors.push(error(
+                        annotation,
+                        "Expected a positive integer for 'maxAttempts'",
+                    ));
+                    return None;
+                }
+                None => None,
+            };
+            let dead_letter_subject = match params.get("deadLetter") {
+                Some(AstExpr::StringLiteral(subject, _)) => Some(subject.clone()),
+                Some(_) => {
+                    errors.push(error(annotation, "Expected a string for 'deadLetter'"));
+                    return None;
+                }
+                None => None,
+            };
+            (subject, group, max_attempts, dead_letter_subject)
+        }
+        _ => {
+            errors.push(error(
+                annotation,
+                "@consumes expects a subject (for example, @consumes(\"orders.created\"))",
+            ));
+            return None;
+        }
+    };
+
+    if subject.trim().is_empty() {
+        errors.push(error(
+            annotation,
+            "The subject for @consumes cannot be empty",
+        ));
+        return None;
+    }
+
+    if dead_letter_subject.as_ref() == Some(&subject) {
+        errors.push(error(
+            annotation,
+            "The dead-letter subject must differ from the consumed subject",
+        ));
+        return None;
+    }
+
+    let group = group.unwrap_or_else(|| format!("exograph_{}", method.name));
+    if group.is_empty()
+        || group
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '.' | '*' | '>'))
+    {
+        errors.push(error(
+            annotation,
+            format!(
+                "Invalid consumer group '{group}'. Groups may not be empty or contain whitespace, '.', '*', or '>'"
+            ),
+        ));
+        return None;
+    }
+
+    // The message is passed through the only non-injected argument
+    let payload_arguments: Vec<_> = method
+        .arguments
+        .iter()
+        .filter(|argument| !argument.annotations.contains("inject"))
+        .collect();
+    let [argument] = payload_arguments.as_slice() else {
+        errors.push(error(
+            annotation,
+            format!(
+                "'{}' must have exactly one (non-injected) argument to receive the message",
+                method.name
+            ),
+        ));
+        return None;
+    };
+
+    let kind = match &argument.typ {
+        AstFieldType::Plain(_, name, _, _, _) if name == "String" => PayloadKind::Text,
+        _ => PayloadKind::Json,
+    };
+
+    Some(MessageConsumer {
+        subject,
+        group,
+        mutation_name: method.name.clone(),
+        argument: ConsumerArgument {
+            name: argument.name.clone(),
+            variable_type: variable_type(&argument.typ),
+            kind,
+        },
+        returns_scalar: matches!(
+            typechecked_system
+                .types
+                .get_by_key(&element_type_name(&method.return_type)),
+            Some(Type::Primitive(_))
+        ),
+        max_attempts: max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS),
+        dead_letter_subject,
+    })
+}
+
+/// The GraphQL type of an argument (for example, `[OrderLine!]!` for `Array<OrderLine>`)
+fn variable_type(typ: &AstFieldType<Typed>) -> String {
+    match typ {
+        AstFieldType::Optional(underlying) => {
+            let underlying = variable_type(underlying);
+            underlying
+                .strip_suffix('!')
+                .unwrap_or(&underlying)
+                .to_string()
+        }
+        AstFieldType::Plain(_, name, type_params, _, _) => {
+            match (name.as_str(), type_params.as_slice()) {
+                ("Array" | "Set", [element]) => format!("[{}]!", variable_type(element)),
+                _ => format!("{name}!"),
+            }
+        }
+    }
+}
+
+/// The name of the type, looking through optionals and collections (for example, `Order` for
+/// `Array<Order>?`)
+fn element_type_name(typ: &AstFieldType<Typed>) -> String {
+    match typ {
+        AstFieldType::Optional(underlying) => element_type_name(underlying),
+        AstFieldType::Plain(_, name, type_params, _, _) => {
+            match (name.as_str(), type_params.as_slice()) {
+                ("Array" | "Set", [element]) => element_type_name(element),
+                _ => name.clone(),
+            }
+        }
+    }
+}
+
+fn error(annotation: &AstAnnotation<Typed>, message: impl Into<String>) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message: message.into(),
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span: annotation.span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
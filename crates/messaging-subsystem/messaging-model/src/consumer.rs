@@ -0,0 +1,50 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+use serde::{Deserialize, Serialize};
+
+/// The number of delivery attempts before a message is dead-lettered, unless specified with
+/// `@consumes(maxAttempts=...)`
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// A Deno mutation that handles messages published to a subject
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageConsumer {
+    /// The subject (topic) to consume from (for example, `orders.created`)
+    pub subject: String,
+    /// The consumer group. Messages are load-balanced among servers in the same group and
+    /// the group's progress (acknowledged messages) survives server restarts.
+    pub group: String,
+    /// The mutation that handles each message
+    pub mutation_name: String,
+    /// The argument of the mutation that receives the message payload
+    pub argument: ConsumerArgument,
+    /// Whether the mutation returns a scalar (and therefore must be invoked without a selection set)
+    pub returns_scalar: bool,
+    /// Number of delivery attempts before giving up on a message
+    pub max_attempts: u32,
+    /// The subject to publish messages to once `max_attempts` is exhausted (dropped if not set)
+    pub dead_letter_subject: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConsumerArgument {
+    pub name: String,
+    /// The GraphQL type of the argument used when invoking the mutation (for example, `String!`
+    /// or `OrderCreated!`)
+    pub variable_type: String,
+    pub kind: PayloadKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// Pass the raw payload (decoded as UTF-8) as a string
+    Text,
+    /// Parse the payload as JSON and pass it as the argument
+    Json,
+}
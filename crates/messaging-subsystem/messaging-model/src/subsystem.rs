@@ -0,0 +1,33 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+use core_plugin_shared::{error::ModelSerializationError, system_serializer::SystemSerializer};
+use serde::{Deserialize, Serialize};
+
+use crate::consumer::MessageConsumer;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MessagingSubsystem {
+    pub consumers: Vec<MessageConsumer>,
+}
+
+impl SystemSerializer for MessagingSubsystem {
+    type Underlying = Self;
+
+    fn serialize(&self) -> Result<Vec<u8>, ModelSerializationError> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(ModelSerializationError::Serialize)
+    }
+
+    fn deserialize_reader(
+        mut reader: impl std::io::Read,
+    ) -> Result<Self::Underlying, ModelSerializationError> {
+        bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())
+            .map_err(ModelSerializationError::Deserialize)
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MessagingError {
+    #[error("Message broker error: {0}")]
+    Broker(String),
+
+    #[error("Invalid message payload: {0}")]
+    InvalidPayload(String),
+
+    #[error("Consumer '{0}' failed: {1}")]
+    Handler(String, String),
+}
+
+impl MessagingError {
+    pub fn broker(e: impl std::fmt::Display) -> Self {
+        MessagingError::Broker(e.to_string())
+    }
+
+    /// Retrying will not help (the message will fail in the same way on every delivery)
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, MessagingError::InvalidPayload(_))
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use common::env_const::EXO_MESSAGING_URL;
+use core_plugin_interface::interface::{SubsystemLoader, SubsystemLoadingError, SubsystemResolver};
+use core_plugin_shared::{
+    serializable_system::SerializableSubsystem, system_serializer::SystemSerializer,
+};
+use exo_env::Environment;
+use messaging_model::subsystem::MessagingSubsystem;
+
+use crate::MessagingService;
+
+pub struct MessagingSubsystemLoader {}
+
+#[async_trait]
+impl SubsystemLoader for MessagingSubsystemLoader {
+    fn id(&self) -> &'static str {
+        "messaging"
+    }
+
+    async fn init(
+        &mut self,
+        serialized_subsystem: SerializableSubsystem,
+        env: Arc<dyn Environment>,
+    ) -> Result<Box<SubsystemResolver>, SubsystemLoadingError> {
+        // Consumers don't serve any operations, so there is no GraphQL resolver. The subsystem
+        // only contributes a background service that invokes the consuming mutations.
+        let resolver = SubsystemResolver::new(None, None, None);
+
+        let Some(graphql) = serialized_subsystem.graphql else {
+            return Ok(Box::new(resolver));
+        };
+
+        let subsystem = MessagingSubsystem::deserialize(graphql.0)?;
+
+        let url = env.get(EXO_MESSAGING_URL).ok_or_else(|| {
+            SubsystemLoadingError::Config(format!(
+                "{EXO_MESSAGING_URL} must be set to use @consumes"
+            ))
+        })?;
+        let client = async_nats::connect(&url).await.map_err(|e| {
+            SubsystemLoadingError::Config(format!("Failed to connect to the message broker: {e}"))
+        })?;
+
+        Ok(Box::new(resolver.with_background_service(Arc::new(
+            MessagingService::new(subsystem, client),
+        ))))
+    }
+}
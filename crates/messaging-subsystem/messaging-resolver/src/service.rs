@@ -0,0 +1,399 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+//! Invokes the `@consumes` mutations for messages published to NATS JetStream subjects.
+//!
+//! Each consumer is a durable pull consumer named after its group, so servers sharing a group
+//! split the messages between them and the group's progress survives restarts. A message is
+//! acknowledged only once its mutation succeeds. Failed messages are redelivered with a backoff
+//! and, once the attempts are exhausted, published to the dead-letter subject (if any).
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_nats::{
+    Client, HeaderMap,
+    jetstream::{
+        self, AckKind,
+        consumer::{AckPolicy, PullConsumer, pull},
+    },
+};
+use async_trait::async_trait;
+use futures::{StreamExt, future::join_all};
+use serde_json::{Map, Value};
+use tracing::{debug, error, warn};
+
+use common::{
+    context::RequestContext,
+    env_const::get_graphql_http_path,
+    http::{MemoryRequestHead, MemoryRequestPayload},
+    operation_payload::OperationsPayload,
+    router::{PlainRequestPayload, Router},
+};
+use core_plugin_interface::interface::{SubsystemBackgroundService, SystemRouterRef};
+use exo_env::Environment;
+use messaging_model::{
+    consumer::{MessageConsumer, PayloadKind},
+    subsystem::MessagingSubsystem,
+};
+
+use crate::messaging_error::MessagingError;
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+const BASE_REDELIVERY_DELAY: Duration = Duration::from_millis(500);
+const MAX_REDELIVERY_DELAY: Duration = Duration::from_secs(60);
+
+/// The variable through which the message is passed to the consuming mutation
+const MESSAGE_VARIABLE: &str = "message";
+
+pub struct MessagingService {
+    subsystem: MessagingSubsystem,
+    client: Client,
+}
+
+#[async_trait]
+impl SubsystemBackgroundService for MessagingService {
+    async fn run(&self, router: SystemRouterRef, env: Arc<dyn Environment>) {
+        join_all(
+            self.subsystem
+                .consumers
+                .iter()
+                .map(|consumer| self.consume(consumer, &router, env.as_ref())),
+        )
+        .await;
+    }
+}
+
+impl MessagingService {
+    pub fn new(subsystem: MessagingSubsystem, client: Client) -> Self {
+        Self { subsystem, client }
+    }
+
+    /// Consume messages for the lifetime of the server, resubscribing (with a backoff) if the
+    /// subscription fails
+    async fn consume(
+        &self,
+        consumer: &MessageConsumer,
+        router: &SystemRouterRef,
+        env: &dyn Environment,
+    ) {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+
+        loop {
+            if let Err(e) = self
+                .consume_messages(consumer, router, env, &mut delay)
+                .await
+            {
+                error!(
+                    "Consumer '{}' for subject '{}' failed (retrying in {delay:?}): {e}",
+                    consumer.mutation_name, consumer.subject
+                );
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn consume_messages(
+        &self,
+        consumer: &MessageConsumer,
+        router: &SystemRouterRef,
+        env: &dyn Environment,
+        reconnect_delay: &mut Duration,
+    ) -> Result<(), MessagingError> {
+        let jetstream = jetstream::new(self.client.clone());
+
+        let stream_name = jetstream
+            .stream_by_subject(consumer.subject.clone())
+            .await
+            .map_err(MessagingError::broker)?;
+        let stream = jetstream
+            .get_stream(stream_name)
+            .await
+            .map_err(MessagingError::broker)?;
+
+        // An existing durable consumer keeps its configuration (and progress)
+        let nats_consumer: PullConsumer = stream
+            .get_or_create_consumer(
+                &consumer.group,
+                pull::Config {
+                    durable_name: Some(consumer.group.clone()),
+                    filter_subject: consumer.subject.clone(),
+                    ack_policy: AckPolicy::Explicit,
+                    max_deliver: consumer.max_attempts as i64,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(MessagingError::broker)?;
+
+        let mut messages = nats_consumer
+            .messages()
+            .await
+            .map_err(MessagingError::broker)?;
+
+        debug!(
+            "Consuming subject '{}' as group '{}'",
+            consumer.subject, consumer.group
+        );
+        *reconnect_delay = INITIAL_RECONNECT_DELAY;
+
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(MessagingError::broker)?;
+            self.handle_message(consumer, message, router, env).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_message(
+        &self,
+        consumer: &MessageConsumer,
+        message: jetstream::Message,
+        router: &SystemRouterRef,
+        env: &dyn Environment,
+    ) -> Result<(), MessagingError> {
+        let attempt = message.info().map(|info| info.delivered).unwrap_or(1);
+
+        let result = match decode_payload(consumer, &message.payload) {
+            Ok(argument) => invoke_consumer(consumer, argument, router, env).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => message.ack().await.map_err(MessagingError::broker),
+            Err(e) if e.is_permanent() || attempt >= consumer.max_attempts as i64 => {
+                warn!(
+                    "Giving up on message from '{}' after {attempt} attempt(s): {e}",
+                    consumer.subject
+                );
+                self.dead_letter(consumer, &message, attempt).await?;
+                message
+                    .ack_with(AckKind::Term)
+                    .await
+                    .map_err(MessagingError::broker)
+            }
+            Err(e) => {
+                warn!(
+                    "Message from '{}' failed (attempt {attempt} of {}): {e}",
+                    consumer.subject, consumer.max_attempts
+                );
+                message
+                    .ack_with(AckKind::Nak(Some(redelivery_delay(attempt))))
+                    .await
+                    .map_err(MessagingError::broker)
+            }
+        }
+    }
+
+    async fn dead_letter(
+        &self,
+        consumer: &MessageConsumer,
+        message: &jetstream::Message,
+        attempt: i64,
+    ) -> Result<(), MessagingError> {
+        let Some(dead_letter_subject) = &consumer.dead_letter_subject else {
+            return Ok(());
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Exo-Original-Subject", message.subject.as_str());
+        headers.insert("Exo-Consumer-Group", consumer.group.as_str());
+        headers.insert("Exo-Delivery-Attempts", attempt.to_string().as_str());
+
+        self.client
+            .publish_with_headers(
+                dead_letter_subject.clone(),
+                headers,
+                message.payload.clone(),
+            )
+            .await
+            .map_err(MessagingError::broker)?;
+
+        // Make sure the message has reached the broker before terminating the original
+        self.client.flush().await.map_err(MessagingError::broker)
+    }
+}
+
+fn decode_payload(consumer: &MessageConsumer, payload: &[u8]) -> Result<Value, MessagingError> {
+    match consumer.argument.kind {
+        PayloadKind::Text => std::str::from_utf8(payload)
+            .map(|text| Value::String(text.to_string()))
+            .map_err(|e| MessagingError::InvalidPayload(e.to_string())),
+        PayloadKind::Json => serde_json::from_slice(payload)
+            .map_err(|e| MessagingError::InvalidPayload(e.to_string())),
+    }
+}
+
+/// Invoke the consuming mutation through the system router.
+///
+/// The operation is routed as an internal request (the consumer is part of the server, not an
+/// external client), so it isn't subject to access control or trusted document enforcement.
+async fn invoke_consumer(
+    consumer: &MessageConsumer,
+    argument: Value,
+    router: &SystemRouterRef,
+    env: &dyn Environment,
+) -> Result<(), MessagingError> {
+    let handler_error =
+        |message: String| MessagingError::Handler(consumer.mutation_name.clone(), message);
+
+    let operations_payload = OperationsPayload {
+        operation_name: None,
+        query: Some(operation_query(consumer)),
+        variables: Some(Map::from_iter([(MESSAGE_VARIABLE.to_string(), argument)])),
+        query_hash: None,
+    };
+    let body = operations_payload
+        .to_json()
+        .map_err(|e| MessagingError::InvalidPayload(e.to_string()))?;
+
+    let request_head = || {
+        MemoryRequestHead::new(
+            HashMap::new(),
+            HashMap::new(),
+            http::Method::POST,
+            get_graphql_http_path(env),
+            Value::Null,
+            None,
+        )
+    };
+
+    // The base context owns the transaction, which must be finalized once the operation completes
+    let base_request = MemoryRequestPayload::new(Value::Null, request_head());
+    let request = MemoryRequestPayload::new(body, request_head());
+    let jwt_authenticator = None;
+    let base_context = RequestContext::new(
+        &base_request,
+        vec![],
+        router.as_ref(),
+        &jwt_authenticator,
+        env,
+    );
+    let request_context = base_context.with_request(&request);
+
+    let result = match router
+        .route(&PlainRequestPayload::internal(&request_context))
+        .await
+    {
+        Some(response) if response.status_code.is_success() => {
+            let body = response
+                .body
+                .to_json()
+                .await
+                .map_err(|e| handler_error(e.to_string()))?;
+
+            match body.get("errors") {
+                Some(errors) => Err(handler_error(errors.to_string())),
+                None => Ok(()),
+            }
+        }
+        Some(response) => Err(handler_error(format!(
+            "Responded with status {}",
+            response.status_code
+        ))),
+        None => Err(handler_error("No route for the mutation".to_string())),
+    };
+
+    base_context
+        .finalize_transaction(result.is_ok())
+        .await
+        .map_err(|e| handler_error(format!("Error while finalizing transaction: {e}")))?;
+
+    result
+}
+
+fn operation_query(consumer: &MessageConsumer) -> String {
+    // Object-returning mutations require a selection set, but the result isn't used
+    let selection = if consumer.returns_scalar {
+        ""
+    } else {
+        " { __typename }"
+    };
+
+    format!(
+        "mutation(${MESSAGE_VARIABLE}: {}) {{ {}({}: ${MESSAGE_VARIABLE}){selection} }}",
+        consumer.argument.variable_type, consumer.mutation_name, consumer.argument.name
+    )
+}
+
+fn redelivery_delay(attempt: i64) -> Duration {
+    let exponent = attempt.clamp(1, 16) as u32 - 1;
+    (BASE_REDELIVERY_DELAY * 2u32.pow(exponent)).min(MAX_REDELIVERY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use messaging_model::consumer::ConsumerArgument;
+
+    use super::*;
+
+    fn consumer(kind: PayloadKind, variable_type: &str, returns_scalar: bool) -> MessageConsumer {
+        MessageConsumer {
+            subject: "orders.created".to_string(),
+            group: "billing".to_string(),
+            mutation_name: "handleOrder".to_string(),
+            argument: ConsumerArgument {
+                name: "order".to_string(),
+                variable_type: variable_type.to_string(),
+                kind,
+            },
+            returns_scalar,
+            max_attempts: 3,
+            dead_letter_subject: None,
+        }
+    }
+
+    #[test]
+    fn builds_operation_query() {
+        assert_eq!(
+            operation_query(&consumer(PayloadKind::Text, "String!", true)),
+            "mutation($message: String!) { handleOrder(order: $message) }"
+        );
+        assert_eq!(
+            operation_query(&consumer(PayloadKind::Json, "OrderCreated!", false)),
+            "mutation($message: OrderCreated!) { handleOrder(order: $message) { __typename } }"
+        );
+    }
+
+    #[test]
+    fn decodes_payloads() {
+        assert_eq!(
+            decode_payload(
+                &consumer(PayloadKind::Text, "String!", true),
+                b"{\"id\": 1}"
+            )
+            .unwrap(),
+            Value::String("{\"id\": 1}".to_string())
+        );
+        assert_eq!(
+            decode_payload(
+                &consumer(PayloadKind::Json, "OrderCreated!", true),
+                b"{\"id\": 1}"
+            )
+            .unwrap(),
+            serde_json::json!({"id": 1})
+        );
+        assert!(
+            decode_payload(
+                &consumer(PayloadKind::Json, "OrderCreated!", true),
+                b"not json"
+            )
+            .unwrap_err()
+            .is_permanent()
+        );
+    }
+
+    #[test]
+    fn backs_off_redelivery() {
+        assert_eq!(redelivery_delay(1), BASE_REDELIVERY_DELAY);
+        assert_eq!(redelivery_delay(3), BASE_REDELIVERY_DELAY * 4);
+        assert_eq!(redelivery_delay(100), MAX_REDELIVERY_DELAY);
+    }
+}
@@ -7,6 +7,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::{ffi::OsStr, io::Write, path::Path};
+
+use flate2::write::GzEncoder;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-env-changed=TARGET");
     if !std::env::var("TARGET").unwrap().starts_with("wasm") {
@@ -62,7 +66,66 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 panic!("Failed to build playground");
             }
         }
+
+        precompress(&playground_app_path.join("dist"))?;
     }
 
     Ok(())
 }
+
+/// Extensions of the assets worth compressing (images and fonts are compressed already)
+const COMPRESSIBLE_EXTENSIONS: &[&str] = &["js", "mjs", "css", "html", "svg", "json", "map", "txt"];
+
+/// Write the brotli (`.br`) and gzip (`.gz`) variants of the compressible assets next to them, so
+/// that the server doesn't compress the same assets on every request. The index page is skipped,
+/// since the server substitutes the configuration into it.
+fn precompress(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            precompress(&path)?;
+            continue;
+        }
+
+        let compressible = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| COMPRESSIBLE_EXTENSIONS.contains(&extension));
+        if !compressible || path.file_name() == Some(OsStr::new("index.html")) {
+            continue;
+        }
+
+        let contents = std::fs::read(&path)?;
+
+        let mut gzip = GzEncoder::new(vec![], flate2::Compression::best());
+        gzip.write_all(&contents)?;
+        write_variant(&path, "gz", &contents, &gzip.finish()?)?;
+
+        let mut brotli = vec![];
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut brotli, 4096, 11, 22);
+            writer.write_all(&contents)?;
+        }
+        write_variant(&path, "br", &contents, &brotli)?;
+    }
+
+    Ok(())
+}
+
+/// Write the compressed variant (unless it isn't any smaller than the original)
+fn write_variant(
+    path: &Path,
+    extension: &str,
+    contents: &[u8],
+    compressed: &[u8],
+) -> std::io::Result<()> {
+    if compressed.len() >= contents.len() {
+        return Ok(());
+    }
+
+    let mut variant_path = path.as_os_str().to_owned();
+    variant_path.push(".");
+    variant_path.push(extension);
+    std::fs::write(variant_path, compressed)
+}
@@ -14,15 +14,135 @@ use common::env_const::{
     EXO_JWT_SOURCE_COOKIE, EXO_JWT_SOURCE_HEADER, get_mcp_http_path,
 };
 use exo_env::Environment;
-use include_dir::{Dir, include_dir};
+use include_dir::{Dir, File, include_dir};
 use serde::Serialize;
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{LazyLock, Mutex},
+};
 
 use common::env_const::{get_graphql_http_path, get_playground_http_path};
+use common::introspection::introspection_token;
 
 static PLAYGROUND_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../../playground/app/dist");
 
-pub fn get_asset_bytes<P: AsRef<Path>>(file_name: P, env: &dyn Environment) -> Option<Vec<u8>> {
+/// The encodings of the pre-compressed variants of assets (written by the build script) along with
+/// the extension of their files, in the order of preference
+const ENCODINGS: &[(&str, &str)] = &[("br", "br"), ("gzip", "gz")];
+
+/// Entity tags of the embedded files (computed on first use, since hashing the larger bundles
+/// takes a while)
+static ETAGS: LazyLock<Mutex<HashMap<&'static Path, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub struct Asset {
+    pub bytes: Vec<u8>,
+    /// The encoding of the bytes (if the client accepts a pre-compressed variant of the asset)
+    pub content_encoding: Option<&'static str>,
+    /// Whether the asset has variants in other encodings (so caches must key it by the
+    /// `Accept-Encoding` header)
+    pub has_variants: bool,
+    pub etag: String,
+}
+
+/// The asset to serve for the file: its pre-compressed variant if the client accepts one (given
+/// its `Accept-Encoding` header), and the original otherwise
+pub fn get_asset(
+    file_name: &str,
+    accept_encoding: Option<&str>,
+    env: &dyn Environment,
+) -> Option<Asset> {
+    if file_name == "index.html" {
+        let bytes = get_asset_bytes(file_name, env)?;
+        let etag = etag(&bytes);
+
+        return Some(Asset {
+            bytes,
+            content_encoding: None,
+            has_variants: false,
+            etag,
+        });
+    }
+
+    let file = PLAYGROUND_DIR.get_file(file_name)?;
+
+    let variants: Vec<_> = ENCODINGS
+        .iter()
+        .filter_map(|(encoding, extension)| {
+            PLAYGROUND_DIR
+                .get_file(format!("{file_name}.{extension}"))
+                .map(|variant| (*encoding, variant))
+        })
+        .collect();
+
+    let accepted_variant = variants.iter().find(|(encoding, _)| {
+        accept_encoding.is_some_and(|accept_encoding| accepts_encoding(accept_encoding, encoding))
+    });
+
+    let (content_encoding, file) = match accepted_variant {
+        Some((encoding, variant)) => (Some(*encoding), *variant),
+        None => (None, file),
+    };
+
+    Some(Asset {
+        bytes: file.contents().to_owned(),
+        content_encoding,
+        has_variants: !variants.is_empty(),
+        etag: file_etag(file),
+    })
+}
+
+/// Whether the value of an `If-None-Match` header matches the entity tag (using the weak
+/// comparison, as required for `GET` requests)
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Whether the value of an `Accept-Encoding` header allows the encoding (explicitly or through
+/// `*`, with a non-zero quality)
+fn accepts_encoding(accept_encoding: &str, encoding: &str) -> bool {
+    let quality = |name: &str| {
+        accept_encoding.split(',').find_map(|entry| {
+            let mut parts = entry.split(';');
+            parts.next()?.trim().eq_ignore_ascii_case(name).then(|| {
+                parts
+                    .find_map(|param| param.trim().strip_prefix("q=")?.trim().parse().ok())
+                    .unwrap_or(1.0)
+            })
+        })
+    };
+
+    quality(encoding)
+        .or_else(|| quality("*"))
+        .is_some_and(|quality: f32| quality > 0.0)
+}
+
+fn file_etag(file: &'static File<'static>) -> String {
+    ETAGS
+        .lock()
+        .expect("Playground entity tags lock poisoned")
+        .entry(file.path())
+        .or_insert_with(|| etag(file.contents()))
+        .clone()
+}
+
+/// A strong entity tag derived from the content (so it stays the same across servers and
+/// restarts, as long as the playground doesn't change)
+fn etag(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let hash: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("\"{hash}\"")
+}
+
+fn get_asset_bytes<P: AsRef<Path>>(file_name: P, env: &dyn Environment) -> Option<Vec<u8>> {
     PLAYGROUND_DIR.get_file(file_name.as_ref()).map(|file| {
         if file_name.as_ref() == Path::new("index.html") {
             let str = file
@@ -73,6 +193,9 @@ struct PlaygroundConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     jwt_source_cookie: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    introspection_token: Option<String>,
 }
 
 fn exo_playground_config(env: &dyn Environment) -> PlaygroundConfig {
@@ -95,6 +218,9 @@ fn exo_playground_config(env: &dyn Environment) -> PlaygroundConfig {
     let jwt_source_header = env.get(EXO_JWT_SOURCE_HEADER);
     let jwt_source_cookie = env.get(EXO_JWT_SOURCE_COOKIE);
 
+    // The playground needs the schema, so it passes the introspection token with its requests
+    let introspection_token = introspection_token(env);
+
     PlaygroundConfig {
         playground_http_path,
         graphql_http_path,
@@ -106,5 +232,36 @@ fn exo_playground_config(env: &dyn Environment) -> PlaygroundConfig {
 
         jwt_source_header,
         jwt_source_cookie,
+
+        introspection_token,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepted_encodings() {
+        assert!(accepts_encoding("gzip, deflate, br", "br"));
+        assert!(accepts_encoding("gzip, deflate, br", "gzip"));
+        assert!(accepts_encoding("GZIP;q=0.5", "gzip"));
+        assert!(accepts_encoding("*", "br"));
+
+        assert!(!accepts_encoding("gzip, deflate", "br"));
+        assert!(!accepts_encoding("gzip, br;q=0", "br"));
+        assert!(!accepts_encoding("*, br;q=0", "br"));
+        assert!(!accepts_encoding("identity", "gzip"));
+    }
+
+    #[test]
+    fn matching_etags() {
+        let etag = etag(b"console.log('playground')");
+
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("\"other\", W/{etag}"), &etag));
+        assert!(etag_matches("*", &etag));
+
+        assert!(!etag_matches("\"other\"", &etag));
     }
 }
@@ -108,27 +108,60 @@ impl<'a> Router<RequestContext<'a>> for PlaygroundRouter {
 
         // we shouldn't cache the index page, as we substitute in the endpoint path dynamically
         let cache_control = if asset_path == index_path {
-            vec![(
-                http::header::CACHE_CONTROL.to_string(),
-                "no-cache".to_string(),
-            )]
+            "no-cache".to_string()
+        } else if asset_path.starts_with("assets/") {
+            // The bundler names these assets after their content, so a changed asset gets a new
+            // path and caches (including CDNs) may keep them forever
+            format!("public, max-age={}, immutable", 60 * 60 * 24 * 365) //seconds in one year
         } else {
-            vec![(
-                http::header::CACHE_CONTROL.to_string(),
-                format!("public, max-age={}", 60 * 60 * 24 * 365), //seconds in one year
-            )]
+            // Other assets (such as the icon) keep their path, so caches must revalidate them
+            // (through their entity tag) once in a while
+            format!("public, max-age={}", 60 * 60) //seconds in one hour
         };
 
-        match playground::get_asset_bytes(asset_path, env) {
+        let request_head = request_context.get_head();
+        let accept_encoding = request_head.get_header(http::header::ACCEPT_ENCODING.as_str());
+
+        match playground::get_asset(&asset_path, accept_encoding.as_deref(), env) {
             Some(asset) => {
-                let headers = cache_control.into_iter().chain(vec![(
+                let mut headers = vec![
+                    (http::header::CACHE_CONTROL.to_string(), cache_control),
+                    (http::header::ETAG.to_string(), asset.etag.clone()),
+                ];
+                if asset.has_variants {
+                    headers.push((
+                        http::header::VARY.to_string(),
+                        http::header::ACCEPT_ENCODING.to_string(),
+                    ));
+                }
+
+                let not_modified = request_head
+                    .get_header(http::header::IF_NONE_MATCH.as_str())
+                    .is_some_and(|if_none_match| {
+                        playground::etag_matches(&if_none_match, &asset.etag)
+                    });
+                if not_modified {
+                    return Some(ResponsePayload {
+                        body: ResponseBody::None,
+                        headers: Headers::from_vec(headers),
+                        status_code: StatusCode::NOT_MODIFIED,
+                    });
+                }
+
+                headers.push((
                     http::header::CONTENT_TYPE.to_string(),
                     content_type.to_string(),
-                )]);
+                ));
+                if let Some(content_encoding) = asset.content_encoding {
+                    headers.push((
+                        http::header::CONTENT_ENCODING.to_string(),
+                        content_encoding.to_string(),
+                    ));
+                }
 
                 Some(ResponsePayload {
-                    body: ResponseBody::Bytes(asset),
-                    headers: Headers::from_vec(headers.collect()),
+                    body: ResponseBody::Bytes(asset.bytes),
+                    headers: Headers::from_vec(headers),
                     status_code: StatusCode::OK,
                 })
             }
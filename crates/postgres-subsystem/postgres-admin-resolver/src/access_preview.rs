@@ -0,0 +1,329 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Preview of the effective schema for a given set of context values.
+//!
+//! Access expressions are partially evaluated: context selections are resolved against the
+//! supplied context values, while the parts that depend on data (such as `self.owner.id`) are left
+//! unresolved. Each operation (and field) is then reported as allowed (for any data), conditional
+//! (depending on the data), or denied (omitted from the preview).
+
+use std::collections::HashMap;
+
+use async_recursion::async_recursion;
+use http::Method;
+use serde_json::{Map, Value, json};
+
+use common::context::{JwtAuthenticator, RequestContext};
+use common::http::{MemoryRequestHead, MemoryRequestPayload};
+use common::value::Val;
+use core_model::access::{
+    AccessLogicalExpression, AccessPredicateExpression, AccessRelationalOp,
+    CommonAccessPrimitiveExpression,
+};
+use core_resolver::access_solver::{
+    AccessSolverError, eq_values, lt_values, lte_values, neq_values,
+    reduce_common_primitive_expression,
+};
+use postgres_core_model::{
+    access::{Access, DatabaseAccessPrimitiveExpression, PrecheckAccessPrimitiveExpression},
+    subsystem::PostgresCoreSubsystem,
+    types::EntityType,
+};
+
+/// The outcome of partially evaluating an access expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AccessOutcome {
+    Allowed,
+    Denied,
+    /// Depends on the data (or the mutation input)
+    Conditional,
+    /// A context value used in a relational expression is missing. As at runtime, this acts as
+    /// `false`, except that negating it doesn't turn it into `true`.
+    Missing,
+}
+
+impl AccessOutcome {
+    fn not(self) -> Self {
+        match self {
+            Self::Allowed => Self::Denied,
+            Self::Denied => Self::Allowed,
+            other => other,
+        }
+    }
+
+    fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Missing, Self::Missing) => Self::Missing,
+            (Self::Denied, _) | (_, Self::Denied) | (Self::Missing, _) | (_, Self::Missing) => {
+                Self::Denied
+            }
+            (Self::Allowed, other) | (other, Self::Allowed) => other,
+            (Self::Conditional, Self::Conditional) => Self::Conditional,
+        }
+    }
+
+    fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Allowed, _) | (_, Self::Allowed) => Self::Allowed,
+            (Self::Missing, Self::Missing) => Self::Missing,
+            (Self::Missing, other) | (other, Self::Missing) => other,
+            (Self::Denied, other) | (other, Self::Denied) => other,
+            (Self::Conditional, Self::Conditional) => Self::Conditional,
+        }
+    }
+
+    fn name(self) -> Option<&'static str> {
+        match self {
+            Self::Allowed => Some("allowed"),
+            Self::Conditional => Some("conditional"),
+            Self::Denied | Self::Missing => None,
+        }
+    }
+}
+
+/// Primitive expressions whose value may be known without looking at the data
+trait CommonPrimitive: Send + Sync {
+    fn common(&self) -> Option<&CommonAccessPrimitiveExpression>;
+}
+
+impl CommonPrimitive for DatabaseAccessPrimitiveExpression {
+    fn common(&self) -> Option<&CommonAccessPrimitiveExpression> {
+        match self {
+            DatabaseAccessPrimitiveExpression::Common(expr) => Some(expr),
+            _ => None,
+        }
+    }
+}
+
+impl CommonPrimitive for PrecheckAccessPrimitiveExpression {
+    fn common(&self) -> Option<&CommonAccessPrimitiveExpression> {
+        match self {
+            PrecheckAccessPrimitiveExpression::Common(expr) => Some(expr),
+            _ => None,
+        }
+    }
+}
+
+/// Compute the operations and fields accessible with the supplied context values (such as
+/// `{"AuthContext": {"role": "user", "id": 1}}`).
+///
+/// The preview doesn't use the context of the admin making the request: context values not
+/// supplied are considered missing (except for those that don't depend on the request, such as
+/// environment variables).
+pub(crate) async fn preview(
+    subsystem: &PostgresCoreSubsystem,
+    entity_types: impl Iterator<Item = &EntityType>,
+    context: Value,
+    request_context: &RequestContext<'_>,
+) -> Result<Value, AccessSolverError> {
+    let request = MemoryRequestPayload::new(
+        Value::Null,
+        MemoryRequestHead::new(
+            HashMap::new(),
+            HashMap::new(),
+            Method::POST,
+            String::new(),
+            Value::Null,
+            None,
+        ),
+    );
+    let jwt_authenticator: Option<JwtAuthenticator> = None;
+    let base_context = RequestContext::new(
+        &request,
+        vec![],
+        request_context.system_context.system_router,
+        &jwt_authenticator,
+        request_context.system_context.env,
+    );
+    let preview_context = base_context.with_override(context);
+
+    let evaluator = Evaluator {
+        subsystem,
+        request_context: &preview_context,
+    };
+
+    let mut entities = vec![];
+
+    for entity_type in entity_types {
+        let entity_access = evaluator.access(&entity_type.access).await?;
+
+        let operations = access_json(&entity_access);
+        if operations.is_empty() {
+            continue;
+        }
+
+        let mut fields = Map::new();
+        for field in entity_type.fields.iter() {
+            let field_access = evaluator.access(&field.access).await?;
+            let effective_access = [
+                entity_access[0].and(field_access[0]),
+                entity_access[1].and(field_access[1]),
+                entity_access[2].and(field_access[2]),
+                entity_access[3],
+            ];
+
+            let field_operations = access_json(&effective_access);
+            if !field_operations.is_empty() {
+                fields.insert(field.name.clone(), Value::Object(field_operations));
+            }
+        }
+
+        entities.push(json!({
+            "name": entity_type.name,
+            "operations": operations,
+            "fields": fields,
+        }));
+    }
+
+    Ok(json!({ "entities": entities }))
+}
+
+/// Operations (other than denied ones) along with their outcome
+fn access_json(access: &[AccessOutcome; 4]) -> Map<String, Value> {
+    ["query", "create", "update", "delete"]
+        .into_iter()
+        .zip(access)
+        .filter_map(|(operation, outcome)| {
+            outcome
+                .name()
+                .map(|name| (operation.to_string(), Value::String(name.to_string())))
+        })
+        .collect()
+}
+
+struct Evaluator<'a> {
+    subsystem: &'a PostgresCoreSubsystem,
+    request_context: &'a RequestContext<'a>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Outcomes for query, create, update, and delete
+    async fn access(&self, access: &'a Access) -> Result<[AccessOutcome; 4], AccessSolverError> {
+        let subsystem = self.subsystem;
+
+        let query = self
+            .evaluate(&subsystem.database_access_expressions[access.read])
+            .await?;
+        let create = self
+            .evaluate(&subsystem.precheck_expressions[access.creation.precheck])
+            .await?;
+        let update = self
+            .evaluate(&subsystem.precheck_expressions[access.update.precheck])
+            .await?
+            .and(
+                self.evaluate(&subsystem.database_access_expressions[access.update.database])
+                    .await?,
+            );
+        let delete = self
+            .evaluate(&subsystem.database_access_expressions[access.delete])
+            .await?;
+
+        Ok([query, create, update, delete])
+    }
+
+    #[async_recursion]
+    async fn evaluate<PrimExpr: CommonPrimitive>(
+        &self,
+        expr: &'a AccessPredicateExpression<PrimExpr>,
+    ) -> Result<AccessOutcome, AccessSolverError> {
+        Ok(match expr {
+            AccessPredicateExpression::BooleanLiteral(true) => AccessOutcome::Allowed,
+            AccessPredicateExpression::BooleanLiteral(false) => AccessOutcome::Denied,
+            AccessPredicateExpression::LogicalOp(op) => match op {
+                AccessLogicalExpression::Not(underlying) => self.evaluate(underlying).await?.not(),
+                AccessLogicalExpression::And(left, right) => {
+                    let left = self.evaluate(left).await?;
+                    if left == AccessOutcome::Denied {
+                        return Ok(left);
+                    }
+                    left.and(self.evaluate(right).await?)
+                }
+                AccessLogicalExpression::Or(left, right) => {
+                    let left = self.evaluate(left).await?;
+                    if left == AccessOutcome::Allowed {
+                        return Ok(left);
+                    }
+                    left.or(self.evaluate(right).await?)
+                }
+            },
+            AccessPredicateExpression::RelationalOp(op) => self.evaluate_relational(op).await?,
+        })
+    }
+
+    async fn evaluate_relational<PrimExpr: CommonPrimitive>(
+        &self,
+        op: &'a AccessRelationalOp<PrimExpr>,
+    ) -> Result<AccessOutcome, AccessSolverError> {
+        let (left, right) = op.sides();
+
+        let left = self.reduce(left).await?;
+        let right = self.reduce(right).await?;
+
+        let (left, right) = match (left, right) {
+            (Some(None), _) | (_, Some(None)) => return Ok(AccessOutcome::Missing),
+            (Some(Some(left)), Some(Some(right))) => (left, right),
+            _ => return Ok(AccessOutcome::Conditional),
+        };
+
+        let numbers = matches!((&left, &right), (Val::Number(_), Val::Number(_)));
+
+        let result = match op {
+            AccessRelationalOp::Eq(..) => eq_values(&left, &right),
+            AccessRelationalOp::Neq(..) => neq_values(&left, &right),
+            AccessRelationalOp::Lt(..) if numbers => lt_values(&left, &right),
+            AccessRelationalOp::Lte(..) if numbers => lte_values(&left, &right),
+            AccessRelationalOp::Gt(..) if numbers => lt_values(&right, &left),
+            AccessRelationalOp::Gte(..) if numbers => lte_values(&right, &left),
+            AccessRelationalOp::In(..) => match &right {
+                Val::List(values) => values.contains(&left),
+                _ => false,
+            },
+            // Leave comparisons of other values to the database
+            _ => return Ok(AccessOutcome::Conditional),
+        };
+
+        Ok(if result {
+            AccessOutcome::Allowed
+        } else {
+            AccessOutcome::Denied
+        })
+    }
+
+    /// Reduce a primitive expression to a value (`None` if it depends on the data and `Some(None)`
+    /// if it is a missing context value)
+    async fn reduce<PrimExpr: CommonPrimitive>(
+        &self,
+        expr: &'a PrimExpr,
+    ) -> Result<Option<Option<Val>>, AccessSolverError> {
+        match expr.common() {
+            Some(common) => Ok(Some(
+                reduce_common_primitive_expression(self.subsystem, self.request_context, common)
+                    .await?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccessOutcome::*;
+
+    #[test]
+    fn outcome_logic() {
+        assert_eq!(Allowed.and(Conditional), Conditional);
+        assert_eq!(Missing.and(Allowed), Denied);
+        assert_eq!(Missing.and(Missing), Missing);
+        assert_eq!(Missing.or(Conditional), Conditional);
+        assert_eq!(Missing.not(), Missing);
+        assert_eq!(Denied.or(Allowed), Allowed);
+        assert_eq!(Conditional.not(), Conditional);
+    }
+}
@@ -0,0 +1,5 @@
+mod access_preview;
+mod resolver;
+mod statistics;
+
+pub use resolver::PostgresSubsystemAdminResolver;
@@ -0,0 +1,794 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::{Method, StatusCode};
+use serde_json::{Map, Value, json};
+
+use common::context::RequestContext;
+use common::env_const::{EXO_ADMIN_TABLE_STATISTICS, is_production};
+use common::http::{Headers, RequestPayload, ResponseBody, ResponsePayload};
+use common::value::Val;
+
+use core_resolver::plugin::{SubsystemResolutionError, SubsystemRestResolver};
+use exo_sql::{
+    AbstractOperation, AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, AbstractSelect,
+    AbstractUpdate, AliasedSelectionElement, ColumnId, ColumnPath, Database, DatabaseExecutor,
+    Function, Limit, Offset, Ordering, PhysicalColumnPath, PhysicalColumnTypeExt, Selection,
+    SelectionCardinality, SelectionElement, TableId,
+};
+use postgres_core_model::{
+    relation::PostgresRelation, subsystem::PostgresCoreSubsystem, types::EntityType,
+};
+use postgres_core_resolver::cast::{literal_column, literal_column_path};
+use postgres_core_resolver::database_helper::extractor;
+use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_core_resolver::retention::RetentionStats;
+use postgres_core_resolver::session_settings::apply_session_settings;
+
+use crate::{access_preview, statistics};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 1000;
+
+const AUDIT_TARGET: &str = "exograph::admin::audit";
+
+/// Serves the admin API for the Postgres subsystem:
+///
+/// - `GET <prefix>schema`: entities along with their tables and columns
+/// - `GET <prefix>tables/<Entity>?limit=&offset=`: a page of rows (ordered by the primary key)
+/// - `GET <prefix>tables/<Entity>/<pk>`: a single row
+/// - `PATCH <prefix>tables/<Entity>/<pk>`: update columns of a row (the body is a JSON object of
+///   column values) and return the updated row
+/// - `GET <prefix>retention`: the `@retention` policies along with the number of rows purged
+/// - `GET <prefix>statistics` and `GET <prefix>statistics/<Entity>`: storage statistics of the
+///   tables behind entities (when enabled through `EXO_ADMIN_TABLE_STATISTICS`)
+/// - `POST <prefix>access-preview`: the entities, operations, and fields accessible with the
+///   context values supplied in the body (outside production only)
+///
+/// Access to these endpoints is checked by the admin router, so the resolver doesn't consult
+/// the access rules of the entities. Every edit is logged under the `exograph::admin::audit`
+/// target.
+pub struct PostgresSubsystemAdminResolver {
+    pub core_subsystem: Arc<PostgresCoreSubsystem>,
+    pub executor: Arc<DatabaseExecutor>,
+    pub api_path_prefix: String,
+    pub retention_stats: Arc<RetentionStats>,
+}
+
+enum AdminError {
+    /// An error to report to the admin client (such as an unknown entity or an invalid value)
+    Client(StatusCode, String),
+    Execution(PostgresExecutionError),
+}
+
+impl AdminError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::Client(StatusCode::BAD_REQUEST, message.into())
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::Client(StatusCode::NOT_FOUND, message.into())
+    }
+}
+
+impl From<PostgresExecutionError> for AdminError {
+    fn from(e: PostgresExecutionError) -> Self {
+        Self::Execution(e)
+    }
+}
+
+#[async_trait]
+impl SubsystemRestResolver for PostgresSubsystemAdminResolver {
+    fn id(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn resolve<'a>(
+        &self,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<Option<ResponsePayload>, SubsystemResolutionError> {
+        let head = request_context.get_head();
+        let path = head.get_path();
+
+        let Some(sub_path) = path.strip_prefix(&self.api_path_prefix) else {
+            return Ok(None);
+        };
+        let segments: Vec<&str> = sub_path.trim_end_matches('/').split('/').collect();
+
+        let response = match (head.get_method(), segments.as_slice()) {
+            (Method::GET, ["schema"]) => Ok(self.schema()),
+            (Method::GET, ["retention"]) => Ok(self.retention_stats.to_json()),
+            (Method::GET, ["statistics", rest @ ..]) if rest.len() <= 1 => {
+                let enabled = request_context
+                    .system_context
+                    .env
+                    .enabled(EXO_ADMIN_TABLE_STATISTICS, false)
+                    .map_err(|e| SubsystemResolutionError::UserDisplayError(e.to_string()))?;
+                if !enabled {
+                    return Ok(None);
+                }
+                match rest.first() {
+                    Some(entity_name) => match self.entity_type(entity_name) {
+                        Ok(entity_type) => self.statistics(&[entity_type]).await,
+                        Err(e) => Err(e),
+                    },
+                    None => {
+                        let entity_types: Vec<_> = self.browsable_entity_types().collect();
+                        self.statistics(&entity_types).await
+                    }
+                }
+            }
+            (Method::POST, ["access-preview"]) => {
+                // Reveals the access rules, so meant only for verifying them during development
+                if is_production(request_context.system_context.env) {
+                    return Ok(None);
+                }
+                self.access_preview(request_context).await
+            }
+            (Method::GET, ["tables", entity_name]) => match self.entity_type(entity_name) {
+                Ok(entity_type) => {
+                    self.list_rows(entity_type, &head.get_query(), request_context)
+                        .await
+                }
+                Err(e) => Err(e),
+            },
+            (Method::GET, ["tables", entity_name, pk]) => match self.entity_type(entity_name) {
+                Ok(entity_type) => self.get_row(entity_type, pk, request_context).await,
+                Err(e) => Err(e),
+            },
+            (Method::PATCH, ["tables", entity_name, pk]) => match self.entity_type(entity_name) {
+                Ok(entity_type) => self.update_row(entity_type, pk, request_context).await,
+                Err(e) => Err(e),
+            },
+            _ => return Ok(None),
+        };
+
+        let (status_code, body) = match response {
+            Ok(body) => (StatusCode::OK, body),
+            Err(AdminError::Client(status_code, message)) => {
+                (status_code, json!({ "error": message }))
+            }
+            Err(AdminError::Execution(e)) => return Err(e.into()),
+        };
+
+        Ok(Some(ResponsePayload {
+            body: ResponseBody::Bytes(body.to_string().into()),
+            headers: Headers::from_vec(vec![(
+                "content-type".to_string(),
+                "application/json".to_string(),
+            )]),
+            status_code,
+        }))
+    }
+}
+
+impl PostgresSubsystemAdminResolver {
+    fn schema(&self) -> Value {
+        let database = &self.core_subsystem.database;
+
+        let entities: Vec<Value> = self
+            .browsable_entity_types()
+            .map(|entity_type| {
+                let table = database.get_table(entity_type.table_id);
+
+                let columns: Vec<Value> = table
+                    .columns
+                    .iter()
+                    .map(|column| {
+                        json!({
+                            "name": column.name,
+                            "type": column.typ.type_name(),
+                            "nullable": column.is_nullable,
+                            "primaryKey": column.is_pk,
+                        })
+                    })
+                    .collect();
+
+                let fields: Vec<Value> = entity_type
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        json!({
+                            "name": field.name,
+                            "type": field.typ.innermost().type_name,
+                            "column": match &field.relation {
+                                PostgresRelation::Scalar { column_id, .. } => {
+                                    Some(column_id.get_column(database).name.clone())
+                                }
+                                _ => None,
+                            },
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "name": entity_type.name,
+                    "pluralName": entity_type.plural_name,
+                    "table": table.name.fully_qualified_name(),
+                    "columns": columns,
+                    "fields": fields,
+                })
+            })
+            .collect();
+
+        json!({ "entities": entities })
+    }
+
+    async fn statistics(&self, entity_types: &[&EntityType]) -> Result<Value, AdminError> {
+        let database = &self.core_subsystem.database;
+
+        let entities: Vec<_> = entity_types
+            .iter()
+            .map(|entity_type| {
+                (
+                    entity_type.name.as_str(),
+                    &database.get_table(entity_type.table_id).name,
+                )
+            })
+            .collect();
+
+        Ok(statistics::statistics(&self.executor, &entities).await?)
+    }
+
+    async fn access_preview(
+        &self,
+        request_context: &RequestContext<'_>,
+    ) -> Result<Value, AdminError> {
+        let context = match request_context.take_body() {
+            context @ Value::Object(_) => context,
+            _ => {
+                return Err(AdminError::bad_request(
+                    "Expected a JSON object of context values (such as {\"AuthContext\": {\"role\": \"user\"}})",
+                ));
+            }
+        };
+
+        access_preview::preview(
+            &self.core_subsystem,
+            self.browsable_entity_types(),
+            context,
+            request_context,
+        )
+        .await
+        .map_err(|e| AdminError::Execution(e.into()))
+    }
+
+    async fn list_rows(
+        &self,
+        entity_type: &EntityType,
+        query: &Value,
+        request_context: &RequestContext<'_>,
+    ) -> Result<Value, AdminError> {
+        let limit = query_param(query, "limit")?
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(0, MAX_PAGE_SIZE);
+        let offset = query_param(query, "offset")?.unwrap_or(0).max(0);
+
+        let table_id = entity_type.table_id;
+        let database = &self.core_subsystem.database;
+
+        let order_by = AbstractOrderBy(
+            database
+                .get_pk_column_ids(table_id)
+                .into_iter()
+                .map(|column_id| {
+                    (
+                        AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(column_id)),
+                        Ordering::Asc,
+                    )
+                })
+                .collect(),
+        );
+
+        let rows_select = AbstractSelect {
+            table_id,
+            selection: Selection::Json(self.row_selection(table_id), SelectionCardinality::Many),
+            predicate: AbstractPredicate::True,
+            order_by: Some(order_by),
+            offset: Some(Offset(offset)),
+            limit: Some(Limit(limit)),
+            lock: None,
+        };
+        let rows = self
+            .execute(AbstractOperation::Select(rows_select), request_context)
+            .await?;
+
+        let count_column_id = database.get_column_ids(table_id)[0];
+        let count_select = AbstractSelect {
+            table_id,
+            selection: Selection::Json(
+                vec![AliasedSelectionElement::new(
+                    "count".to_string(),
+                    SelectionElement::Function(Function::Named {
+                        function_name: "count".to_string(),
+                        column_id: count_column_id,
+                    }),
+                )],
+                SelectionCardinality::One,
+            ),
+            predicate: AbstractPredicate::True,
+            order_by: None,
+            offset: None,
+            limit: None,
+            lock: None,
+        };
+        let count = self
+            .execute(AbstractOperation::Select(count_select), request_context)
+            .await?;
+
+        Ok(json!({
+            "rows": rows,
+            "total": count.get("count").cloned().unwrap_or(Value::Null),
+            "limit": limit,
+            "offset": offset,
+        }))
+    }
+
+    async fn get_row(
+        &self,
+        entity_type: &EntityType,
+        pk: &str,
+        request_context: &RequestContext<'_>,
+    ) -> Result<Value, AdminError> {
+        let predicate = pk_predicate(&self.core_subsystem.database, entity_type, pk)?;
+
+        let table_id = entity_type.table_id;
+
+        let select = AbstractSelect {
+            table_id,
+            selection: Selection::Json(self.row_selection(table_id), SelectionCardinality::One),
+            predicate,
+            order_by: None,
+            offset: None,
+            limit: None,
+            lock: None,
+        };
+
+        let row = self
+            .execute(AbstractOperation::Select(select), request_context)
+            .await?;
+
+        found_row(row, entity_type, pk)
+    }
+
+    async fn update_row(
+        &self,
+        entity_type: &EntityType,
+        pk: &str,
+        request_context: &RequestContext<'_>,
+    ) -> Result<Value, AdminError> {
+        let predicate = pk_predicate(&self.core_subsystem.database, entity_type, pk)?;
+
+        let changes = match request_context.take_body() {
+            Value::Object(changes) if !changes.is_empty() => changes,
+            _ => {
+                return Err(AdminError::bad_request(
+                    "Expected a non-empty JSON object of column values",
+                ));
+            }
+        };
+
+        let table_id = entity_type.table_id;
+        let column_values = column_values(&self.core_subsystem.database, table_id, &changes)?;
+
+        let update = AbstractUpdate {
+            table_id,
+            predicate,
+            column_values,
+            nested_updates: vec![],
+            nested_inserts: vec![],
+            nested_deletes: vec![],
+            selection: AbstractSelect {
+                table_id,
+                selection: Selection::Json(self.row_selection(table_id), SelectionCardinality::One),
+                predicate: AbstractPredicate::True,
+                order_by: None,
+                offset: None,
+                limit: None,
+                lock: None,
+            },
+            precheck_predicates: vec![],
+        };
+
+        request_context.ensure_transaction().await;
+
+        let row = self
+            .execute(AbstractOperation::Update(update), request_context)
+            .await?;
+
+        let row = found_row(row, entity_type, pk)?;
+
+        let actor = admin_actor(request_context).await;
+        tracing::info!(
+            target: AUDIT_TARGET,
+            entity = entity_type.name.as_str(),
+            pk,
+            actor = actor.as_str(),
+            changes = Value::Object(changes).to_string().as_str(),
+            "Admin edit"
+        );
+
+        Ok(row)
+    }
+
+    async fn execute(
+        &self,
+        operation: AbstractOperation,
+        request_context: &RequestContext<'_>,
+    ) -> Result<Value, PostgresExecutionError> {
+        apply_session_settings(&self.core_subsystem, request_context).await?;
+
+        let mut tx = request_context
+            .system_context
+            .transaction_holder
+            .try_lock()
+            .unwrap();
+
+        let mut result = self
+            .executor
+            .execute(operation, &mut tx, &self.core_subsystem.database)
+            .await
+            .map_err(PostgresExecutionError::Postgres)?;
+
+        if result.len() != 1 {
+            return Err(PostgresExecutionError::NonUniqueResult(result.len()));
+        }
+
+        let string_result: String = extractor(result.swap_remove(0))?;
+        serde_json::from_str(&string_result)
+            .map_err(|e| PostgresExecutionError::Generic(e.to_string()))
+    }
+
+    fn browsable_entity_types(&self) -> impl Iterator<Item = &EntityType> {
+        self.core_subsystem
+            .entity_types
+            .iter()
+            .map(|(_, entity_type)| entity_type)
+            .filter(|entity_type| !entity_type.representation.is_json_like())
+    }
+
+    fn entity_type(&self, name: &str) -> Result<&EntityType, AdminError> {
+        self.browsable_entity_types()
+            .find(|entity_type| entity_type.name == name)
+            .ok_or_else(|| AdminError::not_found(format!("Unknown entity '{name}'")))
+    }
+
+    /// Select all columns of the table (aliased by their names)
+    fn row_selection(&self, table_id: TableId) -> Vec<AliasedSelectionElement> {
+        let database = &self.core_subsystem.database;
+
+        database
+            .get_column_ids(table_id)
+            .into_iter()
+            .map(|column_id| {
+                AliasedSelectionElement::new(
+                    column_id.get_column(database).name.clone(),
+                    SelectionElement::Physical(column_id),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Predicate to match the row with the given primary key (supplied as a path segment)
+fn pk_predicate(
+    database: &Database,
+    entity_type: &EntityType,
+    pk: &str,
+) -> Result<AbstractPredicate, AdminError> {
+    let pk_column_ids = database.get_pk_column_ids(entity_type.table_id);
+    let [pk_column_id] = pk_column_ids.as_slice() else {
+        return Err(AdminError::bad_request(format!(
+            "Entity '{}' doesn't have a single-column primary key",
+            entity_type.name
+        )));
+    };
+    let pk_column = pk_column_id.get_column(database);
+
+    // The path segment is untyped, so try it as a JSON value first (to support numeric keys)
+    // and then as a plain string.
+    let candidates = serde_json::from_str::<Value>(pk)
+        .ok()
+        .map(Val::from)
+        .into_iter()
+        .chain(std::iter::once(Val::String(pk.to_string())));
+
+    let pk_value = candidates
+        .filter_map(|value| literal_column_path(&value, pk_column.typ.inner(), false).ok())
+        .next()
+        .ok_or_else(|| {
+            AdminError::bad_request(format!(
+                "Invalid primary key '{pk}' for entity '{}'",
+                entity_type.name
+            ))
+        })?;
+
+    Ok(AbstractPredicate::eq(
+        ColumnPath::Physical(PhysicalColumnPath::leaf(*pk_column_id)),
+        pk_value,
+    ))
+}
+
+fn column_values(
+    database: &Database,
+    table_id: TableId,
+    changes: &Map<String, Value>,
+) -> Result<Vec<(ColumnId, exo_sql::Column)>, AdminError> {
+    changes
+        .iter()
+        .map(|(column_name, value)| {
+            let column_id = database
+                .get_column_id(table_id, column_name)
+                .ok_or_else(|| {
+                    AdminError::bad_request(format!("Unknown column '{column_name}'"))
+                })?;
+            let column = column_id.get_column(database);
+
+            if column.is_pk {
+                return Err(AdminError::bad_request(format!(
+                    "Primary key column '{column_name}' cannot be edited"
+                )));
+            }
+
+            let value = literal_column(&Val::from(value.clone()), column).map_err(|e| {
+                AdminError::bad_request(format!(
+                    "Invalid value for column '{column_name}': {}",
+                    e.user_error_message()
+                ))
+            })?;
+
+            Ok((column_id, value))
+        })
+        .collect()
+}
+
+fn found_row(row: Value, entity_type: &EntityType, pk: &str) -> Result<Value, AdminError> {
+    if row.is_null() {
+        Err(AdminError::not_found(format!(
+            "No '{}' with primary key '{pk}'",
+            entity_type.name
+        )))
+    } else {
+        Ok(row)
+    }
+}
+
+/// Parse an integer query parameter (which may be supplied as a string or a number)
+fn query_param(query: &Value, name: &str) -> Result<Option<i64>, AdminError> {
+    let invalid = || AdminError::bad_request(format!("Invalid value for '{name}'"));
+
+    match query.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) => n.as_i64().map(Some).ok_or_else(invalid),
+        Some(Value::String(s)) => s.parse().map(Some).map_err(|_| invalid()),
+        Some(_) => Err(invalid()),
+    }
+}
+
+/// Identify the admin making the request for the audit log (`sub` claim, if available)
+async fn admin_actor(request_context: &RequestContext<'_>) -> String {
+    let sub = request_context
+        .extract_context_field("AdminContext", "jwt", &Some("sub"), "sub", &Ok)
+        .await;
+
+    match sub {
+        Ok(Some(Val::String(sub))) => sub.clone(),
+        Ok(Some(sub)) => sub.to_string(),
+        _ => "<unknown>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_model_builder::plugin::BuildMode;
+    use core_plugin_shared::system_serializer::SystemSerializer;
+    use exo_sql::Predicate;
+
+    use super::*;
+
+    async fn create_core_subsystem(model: &str) -> PostgresCoreSubsystem {
+        let system = builder::build_system_from_str(
+            model,
+            "index.exo".to_string(),
+            vec![Box::new(
+                postgres_builder::PostgresSubsystemBuilder::default(),
+            )],
+            BuildMode::Build,
+        )
+        .await
+        .unwrap();
+
+        let subsystem = system
+            .subsystems
+            .into_iter()
+            .find(|subsystem| subsystem.id == "postgres")
+            .unwrap();
+
+        PostgresCoreSubsystem::deserialize(subsystem.core.0).unwrap()
+    }
+
+    async fn test_subsystem() -> PostgresCoreSubsystem {
+        create_core_subsystem(
+            r#"
+            @postgres
+            module ShopModule {
+                type Product {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                    price: Int
+                }
+
+                type Tag {
+                    @pk code: String
+                }
+
+                type Membership {
+                    @pk groupId: Int
+                    @pk memberId: Int
+                }
+            }
+            "#,
+        )
+        .await
+    }
+
+    fn entity_type<'a>(subsystem: &'a PostgresCoreSubsystem, name: &str) -> &'a EntityType {
+        subsystem
+            .entity_types
+            .iter()
+            .map(|(_, entity_type)| entity_type)
+            .find(|entity_type| entity_type.name == name)
+            .unwrap()
+    }
+
+    fn client_error<T>(result: Result<T, AdminError>) -> (StatusCode, String) {
+        match result {
+            Err(AdminError::Client(status_code, message)) => (status_code, message),
+            Err(AdminError::Execution(e)) => panic!("Expected a client error, got {e}"),
+            Ok(_) => panic!("Expected a client error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn pk_predicate_accepts_numeric_and_string_keys() {
+        let subsystem = test_subsystem().await;
+        let database = &subsystem.database;
+
+        for (entity_name, pk) in [("Product", "42"), ("Tag", "sale"), ("Tag", "42")] {
+            let predicate = pk_predicate(database, entity_type(&subsystem, entity_name), pk);
+            assert!(
+                matches!(
+                    predicate,
+                    Ok(Predicate::Eq(ColumnPath::Physical(_), ColumnPath::Param(_)))
+                ),
+                "Unexpected predicate for {entity_name} '{pk}'"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn pk_predicate_rejects_invalid_keys() {
+        let subsystem = test_subsystem().await;
+        let database = &subsystem.database;
+
+        assert_eq!(
+            client_error(pk_predicate(
+                database,
+                entity_type(&subsystem, "Product"),
+                "abc"
+            )),
+            (
+                StatusCode::BAD_REQUEST,
+                "Invalid primary key 'abc' for entity 'Product'".to_string()
+            )
+        );
+        assert_eq!(
+            client_error(pk_predicate(
+                database,
+                entity_type(&subsystem, "Membership"),
+                "1"
+            )),
+            (
+                StatusCode::BAD_REQUEST,
+                "Entity 'Membership' doesn't have a single-column primary key".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn column_values_for_edits() {
+        let subsystem = test_subsystem().await;
+        let database = &subsystem.database;
+        let table_id = entity_type(&subsystem, "Product").table_id;
+
+        let changes = |changes: Value| match changes {
+            Value::Object(changes) => changes,
+            _ => unreachable!(),
+        };
+
+        let values = column_values(
+            database,
+            table_id,
+            &changes(json!({ "name": "Lamp", "price": 30 })),
+        )
+        .ok()
+        .unwrap();
+        let column_names: Vec<_> = values
+            .iter()
+            .map(|(column_id, _)| column_id.get_column(database).name.as_str())
+            .collect();
+        assert_eq!(column_names, vec!["name", "price"]);
+
+        assert_eq!(
+            client_error(column_values(
+                database,
+                table_id,
+                &changes(json!({ "color": "red" }))
+            )),
+            (
+                StatusCode::BAD_REQUEST,
+                "Unknown column 'color'".to_string()
+            )
+        );
+        assert_eq!(
+            client_error(column_values(
+                database,
+                table_id,
+                &changes(json!({ "id": 2 }))
+            )),
+            (
+                StatusCode::BAD_REQUEST,
+                "Primary key column 'id' cannot be edited".to_string()
+            )
+        );
+
+        let (status_code, message) = client_error(column_values(
+            database,
+            table_id,
+            &changes(json!({ "price": "cheap" })),
+        ));
+        assert_eq!(status_code, StatusCode::BAD_REQUEST);
+        assert!(
+            message.starts_with("Invalid value for column 'price'"),
+            "{message}"
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_rows_are_not_found() {
+        let subsystem = test_subsystem().await;
+        let product = entity_type(&subsystem, "Product");
+
+        assert_eq!(
+            found_row(json!({ "id": 1 }), product, "1").ok(),
+            Some(json!({ "id": 1 }))
+        );
+        assert_eq!(
+            client_error(found_row(Value::Null, product, "1")),
+            (
+                StatusCode::NOT_FOUND,
+                "No 'Product' with primary key '1'".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn query_params() {
+        let query = json!({ "limit": "20", "offset": 40, "page": "first", "size": true });
+
+        assert_eq!(query_param(&query, "limit").ok(), Some(Some(20)));
+        assert_eq!(query_param(&query, "offset").ok(), Some(Some(40)));
+        assert_eq!(query_param(&query, "missing").ok(), Some(None));
+        assert_eq!(
+            client_error(query_param(&query, "page")),
+            (
+                StatusCode::BAD_REQUEST,
+                "Invalid value for 'page'".to_string()
+            )
+        );
+        assert!(matches!(
+            query_param(&query, "size"),
+            Err(AdminError::Client(StatusCode::BAD_REQUEST, _))
+        ));
+    }
+}
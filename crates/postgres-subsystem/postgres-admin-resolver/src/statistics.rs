@@ -0,0 +1,178 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Storage statistics of the tables behind entities (approximate rows, sizes, and the last vacuum
+//! and analyze), read from the Postgres statistics views.
+//!
+//! The row counts are the planner's estimates (updated by vacuum and analyze), so they are cheap
+//! to read even for large tables, but may lag behind the actual counts.
+
+use serde_json::{Map, Value, json};
+
+use exo_sql::{DatabaseExecutor, SchemaObjectName};
+use postgres_core_resolver::database_helper::extractor;
+use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+
+/// Statistics of the tables in the given schemas and with the given names (as a JSON array, with
+/// an element for each table that exists)
+const STATISTICS_QUERY: &str = r#"
+SELECT coalesce(json_agg(json_build_object(
+    'schema', s.schemaname,
+    'table', s.relname,
+    'approximateRows', nullif(c.reltuples, -1)::int8,
+    'totalBytes', pg_total_relation_size(c.oid),
+    'indexBytes', pg_indexes_size(c.oid),
+    'lastVacuum', greatest(s.last_vacuum, s.last_autovacuum),
+    'lastAnalyze', greatest(s.last_analyze, s.last_autoanalyze)
+)), '[]')::text
+FROM pg_stat_user_tables s
+JOIN pg_class c ON c.oid = s.relid
+WHERE (s.schemaname, s.relname) IN (SELECT * FROM unnest($1::text[], $2::text[]))
+"#;
+
+/// The statistics for each entity (keyed by the entity name), given the entity names along with
+/// their tables
+pub(crate) async fn statistics(
+    executor: &DatabaseExecutor,
+    entities: &[(&str, &SchemaObjectName)],
+) -> Result<Value, PostgresExecutionError> {
+    let schemas: Vec<String> = entities
+        .iter()
+        .map(|(_, table_name)| table_name.schema_name())
+        .collect();
+    let names: Vec<String> = entities
+        .iter()
+        .map(|(_, table_name)| table_name.name.clone())
+        .collect();
+
+    let client = executor.database_client.get_client().await?;
+    let mut rows = client
+        .query(STATISTICS_QUERY, &[&schemas, &names])
+        .await
+        .map_err(|e| PostgresExecutionError::Postgres(e.into()))?;
+
+    if rows.len() != 1 {
+        return Err(PostgresExecutionError::NonUniqueResult(rows.len()));
+    }
+    let table_statistics: String = extractor(rows.swap_remove(0))?;
+    let table_statistics = serde_json::from_str(&table_statistics)
+        .map_err(|e| PostgresExecutionError::Generic(e.to_string()))?;
+
+    Ok(entity_statistics(entities, table_statistics))
+}
+
+/// Key the statistics of each table by its entity (with `null` for entities whose table doesn't
+/// exist, such as before the migration that creates it)
+fn entity_statistics(entities: &[(&str, &SchemaObjectName)], table_statistics: Value) -> Value {
+    let table_statistics = match table_statistics {
+        Value::Array(table_statistics) => table_statistics,
+        _ => vec![],
+    };
+
+    let entities: Map<String, Value> = entities
+        .iter()
+        .map(|(entity_name, table_name)| {
+            let statistics = table_statistics
+                .iter()
+                .find(|statistics| {
+                    statistics["schema"] == table_name.schema_name()
+                        && statistics["table"] == table_name.name.as_str()
+                })
+                .map(|statistics| {
+                    json!({
+                        "table": table_name.fully_qualified_name(),
+                        "approximateRows": statistics["approximateRows"],
+                        "totalBytes": statistics["totalBytes"],
+                        "indexBytes": statistics["indexBytes"],
+                        "lastVacuum": statistics["lastVacuum"],
+                        "lastAnalyze": statistics["lastAnalyze"],
+                    })
+                })
+                .unwrap_or(Value::Null);
+
+            (entity_name.to_string(), statistics)
+        })
+        .collect();
+
+    json!({ "entities": entities })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_statistics_by_entity() {
+        let concerts = SchemaObjectName {
+            name: "concerts".to_string(),
+            schema: None,
+        };
+        let venues = SchemaObjectName {
+            name: "venues".to_string(),
+            schema: Some("events".to_string()),
+        };
+        let artists = SchemaObjectName {
+            name: "artists".to_string(),
+            schema: None,
+        };
+
+        let table_statistics = json!([
+            {
+                "schema": "events",
+                "table": "venues",
+                "approximateRows": 12,
+                "totalBytes": 16384,
+                "indexBytes": 8192,
+                "lastVacuum": null,
+                "lastAnalyze": "2026-10-01T10:00:00+00:00"
+            },
+            {
+                "schema": "public",
+                "table": "concerts",
+                "approximateRows": null,
+                "totalBytes": 8192,
+                "indexBytes": 8192,
+                "lastVacuum": null,
+                "lastAnalyze": null
+            }
+        ]);
+
+        assert_eq!(
+            entity_statistics(
+                &[
+                    ("Concert", &concerts),
+                    ("Venue", &venues),
+                    ("Artist", &artists)
+                ],
+                table_statistics
+            ),
+            json!({
+                "entities": {
+                    "Concert": {
+                        "table": "concerts",
+                        "approximateRows": null,
+                        "totalBytes": 8192,
+                        "indexBytes": 8192,
+                        "lastVacuum": null,
+                        "lastAnalyze": null
+                    },
+                    "Venue": {
+                        "table": "events.venues",
+                        "approximateRows": 12,
+                        "totalBytes": 16384,
+                        "indexBytes": 8192,
+                        "lastVacuum": null,
+                        "lastAnalyze": "2026-10-01T10:00:00+00:00"
+                    },
+                    "Artist": null
+                }
+            })
+        );
+    }
+}
@@ -60,6 +60,42 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                             name: "managed",
                             optional: true,
                         },
+                        MappedAnnotationParamSpec {
+                            name: "maxRelationPathDepth",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "naming",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "pluralizeTables",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "tablePrefix",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "applicationName",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "connectionOptions",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "keepaliveIdle",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "keepaliveInterval",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "keepaliveRetries",
+                            optional: true,
+                        },
                     ]),
                 },
             ),
@@ -84,6 +120,24 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     mapped_params: None,
                 },
             ),
+            (
+                "onDelete",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "onUpdate",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
             (
                 "relationPath",
                 AnnotationSpec {
@@ -93,6 +147,18 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     mapped_params: None,
                 },
             ),
+            (
+                "manyToMany",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: Some(&[MappedAnnotationParamSpec {
+                        name: "through",
+                        optional: true,
+                    }]),
+                },
+            ),
             (
                 "dbtype",
                 AnnotationSpec {
@@ -128,6 +194,24 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     ]),
                 },
             ),
+            (
+                "validate",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field, AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "source",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "export",
+                            optional: false,
+                        },
+                    ]),
+                },
+            ),
             (
                 "pk",
                 AnnotationSpec {
@@ -183,9 +267,104 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                             name: "managed",
                             optional: true,
                         },
+                        MappedAnnotationParamSpec {
+                            name: "naming",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "pluralize",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "prefix",
+                            optional: true,
+                        },
                     ]),
                 },
             ),
+            (
+                "renamedFrom",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type, AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "retention",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "days",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "field",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "softDelete",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "batchSize",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "partitionBy",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "range",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "list",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "interval",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "values",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "premake",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "window",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "mapsTo",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Enum],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
             (
                 "joinTable",
                 AnnotationSpec {
@@ -234,6 +413,69 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     mapped_params: None,
                 },
             ),
+            (
+                "readDefault",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "mask",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "roles",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "strategy",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "storage",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "strategy",
+                            optional: true,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "compression",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "chunked",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: true,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "sqlExpression",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
             (
                 "json",
                 AnnotationSpec {
@@ -243,6 +485,24 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                     mapped_params: None,
                 },
             ),
+            (
+                "paginated",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: None,
+                },
+            ),
+            (
+                "opaqueId",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: true,
+                    single_params: false,
+                    mapped_params: None,
+                },
+            ),
             (
                 "ownership",
                 AnnotationSpec {
@@ -278,9 +538,43 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
                             name: "autoAssign",
                             optional: true,
                         },
+                        MappedAnnotationParamSpec {
+                            name: "transfer",
+                            optional: true,
+                        },
+                    ]),
+                },
+            ),
+            (
+                "requiresApproval",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Type],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[
+                        MappedAnnotationParamSpec {
+                            name: "approver",
+                            optional: false,
+                        },
+                        MappedAnnotationParamSpec {
+                            name: "requester",
+                            optional: false,
+                        },
                     ]),
                 },
             ),
+            (
+                "guard",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Field],
+                    no_params: false,
+                    single_params: false,
+                    mapped_params: Some(&[MappedAnnotationParamSpec {
+                        name: "maxChangesPerDay",
+                        optional: false,
+                    }]),
+                },
+            ),
             (
                 "visibility",
                 AnnotationSpec {
@@ -307,6 +601,8 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
         _build_mode: BuildMode,
     ) -> Result<Option<SubsystemBuild>, ModelBuildingError> {
         let resolved_types = postgres_core_builder::resolved_builder::build(typechecked_system)?;
+        let connection_settings =
+            postgres_core_builder::connection_builder::build(typechecked_system)?;
 
         let resolved_env = ResolvedTypeEnv {
             contexts: &base_system.contexts,
@@ -345,9 +641,10 @@ impl SubsystemBuilder for PostgresSubsystemBuilder {
         }?;
 
         let serialized_core_subsystem = {
-            let core_subsystem = Arc::into_inner(core_subsystem_building)
+            let mut core_subsystem = Arc::into_inner(core_subsystem_building)
                 .unwrap()
                 .into_core_subsystem(base_system);
+            core_subsystem.connection_settings = connection_settings;
             core_subsystem
                 .serialize()
                 .map_err(ModelBuildingError::Serialize)?
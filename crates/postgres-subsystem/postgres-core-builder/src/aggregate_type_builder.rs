@@ -169,7 +169,11 @@ fn expand_type(resolved_type: &ResolvedType, building: &mut SystemContextBuildin
                 //   }
                 // }
                 // ```
-                if field.typ.innermost().is_primitive || field.is_pk {
+                // Opaque ids are skipped, since their aggregates (such as `max`) would reveal the
+                // underlying values.
+                if c.opaque_id && field.is_pk {
+                    None
+                } else if field.typ.innermost().is_primitive || field.is_pk {
                     let type_name = aggregate_type_name(field.typ.name());
 
                     let type_id = building.aggregate_types.get_id(&type_name).unwrap();
@@ -82,6 +82,8 @@ async fn bundle_source(module_fs_path: &Path) -> Result<String, ModelBuildingErr
         .arg("--allow-import")
         .arg("--quiet")
         .arg("--node-modules-dir=auto")
+        // Keep the original file/line information so that stack traces can be mapped back
+        .arg("--sourcemap=inline")
         .arg(module_fs_path.to_string_lossy().as_ref())
         .current_dir(module_dir);
 
@@ -0,0 +1,213 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build the connection options declared with `@postgres(applicationName=..., ...)`
+
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr},
+    error::ModelBuildingError,
+    typechecker::{
+        Typed,
+        typ::{Module, TypecheckedSystem},
+    },
+};
+use postgres_core_model::connection_settings::ConnectionSettings;
+
+/// Collect the connection options of the `@postgres` modules. Since all modules share the same
+/// connections, modules that set the same option must agree on its value.
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<ConnectionSettings, ModelBuildingError> {
+    let mut settings = ConnectionSettings::default();
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let Some(AstAnnotationParams::Map(params, _)) = module.annotations.get("postgres") else {
+            continue;
+        };
+
+        if let Some(value) = params.get("applicationName") {
+            merge(
+                &mut settings.application_name,
+                string_param("applicationName", value)?,
+                "applicationName",
+            )?;
+        }
+        if let Some(value) = params.get("connectionOptions") {
+            merge(
+                &mut settings.options,
+                string_param("connectionOptions", value)?,
+                "connectionOptions",
+            )?;
+        }
+        if let Some(value) = params.get("keepaliveIdle") {
+            merge(
+                &mut settings.keepalive_idle_secs,
+                positive_param("keepaliveIdle", value)?,
+                "keepaliveIdle",
+            )?;
+        }
+        if let Some(value) = params.get("keepaliveInterval") {
+            merge(
+                &mut settings.keepalive_interval_secs,
+                positive_param("keepaliveInterval", value)?,
+                "keepaliveInterval",
+            )?;
+        }
+        if let Some(value) = params.get("keepaliveRetries") {
+            merge(
+                &mut settings.keepalive_retries,
+                positive_param("keepaliveRetries", value)?,
+                "keepaliveRetries",
+            )?;
+        }
+    }
+
+    Ok(settings)
+}
+
+fn merge<T: PartialEq>(
+    existing: &mut Option<T>,
+    value: T,
+    param_name: &str,
+) -> Result<(), ModelBuildingError> {
+    match existing {
+        Some(existing) if *existing != value => Err(ModelBuildingError::Generic(format!(
+            "Conflicting @postgres({param_name}) values (all @postgres modules share the same database connections)"
+        ))),
+        _ => {
+            *existing = Some(value);
+            Ok(())
+        }
+    }
+}
+
+fn string_param(param_name: &str, value: &AstExpr<Typed>) -> Result<String, ModelBuildingError> {
+    match value {
+        AstExpr::StringLiteral(value, _) => Ok(value.clone()),
+        _ => Err(ModelBuildingError::Generic(format!(
+            "@postgres({param_name}) must be a string"
+        ))),
+    }
+}
+
+fn positive_param<T: std::str::FromStr + Default + PartialOrd>(
+    param_name: &str,
+    value: &AstExpr<Typed>,
+) -> Result<T, ModelBuildingError> {
+    match value {
+        AstExpr::NumberLiteral(value, _) => value.parse::<T>().ok(),
+        _ => None,
+    }
+    .filter(|value| *value > T::default())
+    .ok_or_else(|| {
+        ModelBuildingError::Generic(format!(
+            "@postgres({param_name}) must be a positive integer"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::create_typechecked_system_from_src;
+
+    use multiplatform_test::multiplatform_test;
+
+    fn connection_settings(src: &str) -> Result<ConnectionSettings, ModelBuildingError> {
+        build(&create_typechecked_system_from_src(src)?)
+    }
+
+    #[multiplatform_test]
+    fn default_settings() {
+        let settings = connection_settings(
+            r#"
+            @postgres
+            module Database {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings, ConnectionSettings::default());
+    }
+
+    #[multiplatform_test]
+    fn settings_from_annotation() {
+        let settings = connection_settings(
+            r#"
+            @postgres(applicationName="concerts", connectionOptions="-c statement_timeout=5000", keepaliveIdle=60, keepaliveInterval=10, keepaliveRetries=3)
+            module Database {
+                type Concert {
+                    @pk id: Int = autoIncrement()
+                }
+            }
+
+            @postgres(applicationName="concerts")
+            module OtherDatabase {
+                type Venue {
+                    @pk id: Int = autoIncrement()
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings,
+            ConnectionSettings {
+                application_name: Some("concerts".to_string()),
+                options: Some("-c statement_timeout=5000".to_string()),
+                keepalive_idle_secs: Some(60),
+                keepalive_interval_secs: Some(10),
+                keepalive_retries: Some(3),
+            }
+        );
+    }
+
+    #[multiplatform_test]
+    fn invalid_settings() {
+        assert!(
+            connection_settings(
+                r#"
+                @postgres(keepaliveIdle=0)
+                module Database {
+                    type Concert {
+                        @pk id: Int = autoIncrement()
+                    }
+                }
+                "#,
+            )
+            .is_err()
+        );
+
+        assert!(
+            connection_settings(
+                r#"
+                @postgres(applicationName="concerts")
+                module Database {
+                    type Concert {
+                        @pk id: Int = autoIncrement()
+                    }
+                }
+
+                @postgres(applicationName="venues")
+                module OtherDatabase {
+                    type Venue {
+                        @pk id: Int = autoIncrement()
+                    }
+                }
+                "#,
+            )
+            .is_err()
+        );
+    }
+}
@@ -16,7 +16,7 @@ use crate::type_provider::PRIMITIVE_TYPE_PROVIDER_REGISTRY;
 use crate::{
     resolved_type::{
         ExplicitTypeHint, ResolvedCompositeType, ResolvedEnumType, ResolvedField,
-        ResolvedFieldDefault, ResolvedFieldType, ResolvedType, ResolvedTypeEnv,
+        ResolvedFieldDefault, ResolvedFieldType, ResolvedPartition, ResolvedType, ResolvedTypeEnv,
     },
     type_provider::VectorTypeHint,
 };
@@ -30,12 +30,16 @@ use core_model_builder::ast::ast_types::{FieldSelection, FieldSelectionElement};
 use core_model_builder::{ast::ast_types::AstExpr, error::ModelBuildingError};
 
 use exo_sql::schema::column_spec::{ColumnAutoincrement, ColumnDefault, UuidGenerationMethod};
+use exo_sql::schema::identifier::fit_identifier;
+use exo_sql::schema::partition_spec::{PartitionSpec, PartitionStrategy};
 use exo_sql::{
-    ArrayColumnType, BooleanColumnType, ColumnId, EnumColumnType, JsonColumnType, ManyToOne,
-    PhysicalColumn, PhysicalColumnType, PhysicalIndex, PhysicalTable, TableId,
-    schema::index_spec::IndexKind,
+    ArrayColumnType, BooleanColumnType, ColumnId, DateColumnType, EnumColumnType, JsonColumnType,
+    ManyToOne, PhysicalColumn, PhysicalColumnType, PhysicalColumnTypeExt, PhysicalIndex,
+    PhysicalTable, TableId, TimestampColumnType, schema::index_spec::IndexKind,
+};
+use exo_sql::{
+    ColumnReference, Database, PhysicalEnum, RelationColumnPair, SchemaObjectName, SchemaRename,
 };
-use exo_sql::{ColumnReference, Database, PhysicalEnum, RelationColumnPair};
 
 use heck::ToSnakeCase;
 use postgres_core_model::types::EntityRepresentation;
@@ -118,10 +122,34 @@ fn expand_database_info(
         columns: vec![],
         indices: vec![],
         managed: resolved_type.representation == EntityRepresentation::Managed,
+        partition: None,
     };
 
     let table_id = building.database.insert_table(table);
 
+    // Renames only matter for migrations, which don't touch unmanaged tables
+    if resolved_type.representation == EntityRepresentation::Managed {
+        if let Some(old_name) = &resolved_type.renamed_from {
+            building.database.renames.push(SchemaRename::Table {
+                old_name: SchemaObjectName {
+                    name: old_name.clone(),
+                    schema: resolved_type.table_name.schema.clone(),
+                },
+                new_name: resolved_type.table_name.clone(),
+            });
+        }
+
+        for field in resolved_type.fields.iter() {
+            if let (Some(old_name), [new_name]) = (&field.renamed_from, &field.column_names[..]) {
+                building.database.renames.push(SchemaRename::Column {
+                    table_name: resolved_type.table_name.clone(),
+                    old_name: old_name.clone(),
+                    new_name: new_name.clone(),
+                });
+            }
+        }
+    }
+
     {
         let mut created_columns: IndexMap<String, PhysicalColumn> = IndexMap::new(); // column name -> column
 
@@ -138,6 +166,11 @@ fn expand_database_info(
         building.database.get_table_mut(table_id).columns = created_columns.into_values().collect();
     }
 
+    if let Some(partition) = &resolved_type.partition {
+        let partition = partition_spec(resolved_type, partition, table_id, building)?;
+        building.database.get_table_mut(table_id).partition = Some(partition);
+    }
+
     {
         let mut indices: Vec<PhysicalIndex> = vec![];
         resolved_type.fields.iter().for_each(|field| {
@@ -181,6 +214,56 @@ fn expand_database_info(
     Ok(())
 }
 
+fn partition_spec(
+    resolved_type: &ResolvedCompositeType,
+    partition: &ResolvedPartition,
+    table_id: TableId,
+    building: &DatabaseBuilding,
+) -> Result<PartitionSpec, ModelBuildingError> {
+    let column_name = match resolved_type
+        .fields
+        .iter()
+        .find(|field| field.name == partition.field)
+    {
+        Some(field) if field.self_column && field.column_names.len() == 1 => {
+            field.column_names[0].clone()
+        }
+        Some(_) => {
+            return Err(ModelBuildingError::Generic(format!(
+                "The @partitionBy field `{}.{}` must be a scalar field stored in the table",
+                resolved_type.name, partition.field
+            )));
+        }
+        None => {
+            return Err(ModelBuildingError::Generic(format!(
+                "The @partitionBy field `{}` was not found on type `{}`",
+                partition.field, resolved_type.name
+            )));
+        }
+    };
+
+    if let PartitionStrategy::Range { .. } = partition.strategy {
+        let column = building
+            .database
+            .get_column_id(table_id, &column_name)
+            .map(|column_id| column_id.get_column(&building.database));
+
+        if !column.is_some_and(|column| {
+            column.typ.is::<TimestampColumnType>() || column.typ.is::<DateColumnType>()
+        }) {
+            return Err(ModelBuildingError::Generic(format!(
+                "The @partitionBy range field `{}.{}` must be of a date or timestamp type",
+                resolved_type.name, partition.field
+            )));
+        }
+    }
+
+    Ok(PartitionSpec {
+        column_name,
+        strategy: partition.strategy.clone(),
+    })
+}
+
 fn expand_enum_info(
     resolved_enum: &ResolvedEnumType,
     building: &mut DatabaseBuilding,
@@ -188,6 +271,7 @@ fn expand_enum_info(
     let table = PhysicalEnum {
         name: resolved_enum.enum_name.clone(),
         variants: resolved_enum.fields.clone(),
+        variant_mappings: resolved_enum.variant_mappings.clone(),
     };
 
     let _ = building.database.insert_enum(table);
@@ -222,7 +306,7 @@ fn expand_type_relations(
         .fields
         .iter()
         .map(|field| -> Result<(), ModelBuildingError> {
-            if field.computed.is_some() {
+            if field.computed.is_some() || field.sql_expression.is_some() {
                 return Ok(());
             }
 
@@ -274,6 +358,16 @@ fn expand_type_relations(
                     resolved_env,
                     building,
                 )? {
+                    let linked_table = building.database.get_table(relation.linked_table_id);
+                    if linked_table.partition.is_some() {
+                        return Err(ModelBuildingError::Generic(format!(
+                            "The field `{}.{}` refers to the partitioned table `{}`. Postgres requires references to a partitioned table to include the partition column, which is not supported",
+                            resolved_type.name,
+                            field.name,
+                            linked_table.name.fully_qualified_name()
+                        )));
+                    }
+
                     // In the earlier phase, we set the type of a many-to-one column to a placeholder value
                     // Now that we have the foreign type, we can set the type of the column to the foreign type's PK
                     for RelationColumnPair {
@@ -281,16 +375,20 @@ fn expand_type_relations(
                         foreign_column_id,
                     } in relation.column_pairs.iter()
                     {
-                        let foreign_column_typ =
-                            foreign_column_id.get_column(&building.database).typ.clone();
-
-                        building.database.get_column_mut(*self_column_id).typ = foreign_column_typ;
+                        let foreign_column = foreign_column_id.get_column(&building.database);
+                        let foreign_column_typ = foreign_column.typ.clone();
+                        let foreign_column_opaque_id = foreign_column.opaque_id;
 
                         let self_column = building.database.get_column_mut(*self_column_id);
+                        self_column.typ = foreign_column_typ;
+                        // A foreign key refers to an opaque id the same way as the primary key
+                        self_column.opaque_id |= foreign_column_opaque_id;
 
                         let column_reference = ColumnReference {
                             foreign_column_id: *foreign_column_id,
                             group_name: field.name.to_string(),
+                            on_delete: field.on_delete,
+                            on_update: field.on_update,
                         };
 
                         match self_column.column_references {
@@ -415,7 +513,9 @@ fn create_columns(
         .unique_constraints
         .iter()
         .map(|constraint| {
-            format!("unique_constraint_{}_{}", resolved_type.name, constraint).to_snake_case()
+            fit_identifier(
+                &format!("unique_constraint_{}_{}", resolved_type.name, constraint).to_snake_case(),
+            )
         })
         .collect();
 
@@ -461,6 +561,8 @@ fn create_columns(
                                         unique_constraints: unique_constraint_name.clone(),
                                         default_value: default_value.clone(),
                                         update_sync,
+                                        storage: field.storage,
+                                        opaque_id: field.is_pk && resolved_type.opaque_id,
                                         column_references: None,
                                     },
                                 );
@@ -507,6 +609,8 @@ fn create_columns(
                                         unique_constraints: unique_constraint_name.clone(),
                                         default_value: default_value.clone(),
                                         update_sync,
+                                        storage: field.storage,
+                                        opaque_id: false,
                                         column_references: None,
                                     },
                                 );
@@ -537,6 +641,8 @@ fn create_columns(
                                         unique_constraints: unique_constraint_name.clone(),
                                         default_value: default_value.clone(),
                                         update_sync,
+                                        storage: field.storage,
+                                        opaque_id: false,
                                         column_references: None,
                                     },
                                 );
@@ -599,6 +705,8 @@ fn create_columns(
                                     unique_constraints: unique_constraint_name.clone(),
                                     default_value: default_value.clone(),
                                     update_sync,
+                                    storage: field.storage,
+                                    opaque_id: false,
                                     column_references: None,
                                 },
                             );
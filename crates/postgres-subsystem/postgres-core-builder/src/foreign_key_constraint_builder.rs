@@ -0,0 +1,101 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build the mapping from database foreign key constraints to the many-to-one relation fields of
+//! entity types
+
+use core_model_builder::error::ModelBuildingError;
+use exo_sql::schema::identifier::fit_identifier;
+use postgres_core_model::{
+    foreign_key_constraint::ForeignKeyConstraint, relation::PostgresRelation,
+};
+
+use crate::system_builder::SystemContextBuilding;
+
+pub(super) fn build(building: &mut SystemContextBuilding) -> Result<(), ModelBuildingError> {
+    let mut foreign_key_constraints: Vec<ForeignKeyConstraint> = vec![];
+
+    for (_, entity_type) in building.entity_types.iter() {
+        if entity_type.representation.is_json_like() {
+            continue;
+        }
+
+        let table = building.database.get_table(entity_type.table_id);
+
+        for field in entity_type.fields.iter() {
+            let PostgresRelation::ManyToOne { relation, .. } = &field.relation else {
+                continue;
+            };
+
+            // The constraint lists the columns sorted by name (and so do the key values in a violation)
+            let mut column_pairs = relation
+                .relation_id
+                .deref(&building.database)
+                .column_pairs
+                .clone();
+            column_pairs.sort_by_key(|pair| {
+                pair.self_column_id
+                    .get_column(&building.database)
+                    .name
+                    .clone()
+            });
+
+            let Some(group_name) = column_pairs.first().and_then(|pair| {
+                pair.self_column_id
+                    .get_column(&building.database)
+                    .column_references
+                    .as_ref()
+                    .and_then(|references| references.first())
+                    .map(|reference| reference.group_name.clone())
+            }) else {
+                continue;
+            };
+
+            let referenced_entity = &building.entity_types[relation.foreign_entity_id];
+            let referenced_field_names = column_pairs
+                .iter()
+                .map(|pair| {
+                    referenced_entity
+                        .fields
+                        .iter()
+                        .find(|field| match &field.relation {
+                            PostgresRelation::Scalar { column_id, .. } => {
+                                *column_id == pair.foreign_column_id
+                            }
+                            _ => false,
+                        })
+                        .map(|field| field.name.clone())
+                        .unwrap_or_else(|| {
+                            pair.foreign_column_id
+                                .get_column(&building.database)
+                                .name
+                                .clone()
+                        })
+                })
+                .collect();
+
+            foreign_key_constraints.push(ForeignKeyConstraint {
+                // Must match the name used when creating the constraint (see `SchemaOp::CreateForeignKeyReference`)
+                name: fit_identifier(&format!(
+                    "{}_{}_fk",
+                    table.name.fully_qualified_name_with_sep("_"),
+                    group_name
+                )),
+                entity_name: entity_type.name.clone(),
+                field_name: field.name.clone(),
+                referenced_entity_name: referenced_entity.name.clone(),
+                referenced_field_names,
+            });
+        }
+    }
+
+    building.foreign_key_constraints = foreign_key_constraints;
+
+    Ok(())
+}
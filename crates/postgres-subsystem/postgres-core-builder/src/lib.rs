@@ -1,14 +1,18 @@
 pub mod access;
 pub mod aggregate_type_builder;
 mod computed_script;
+pub mod connection_builder;
 pub mod database_builder;
+mod foreign_key_constraint_builder;
 pub mod naming;
 pub mod resolved_builder;
 pub mod resolved_type;
+mod retention_builder;
 pub mod shallow;
 pub mod system_builder;
 pub mod type_builder;
 pub mod type_provider;
+mod unique_constraint_builder;
 
 pub use system_builder::SystemContextBuilding;
 
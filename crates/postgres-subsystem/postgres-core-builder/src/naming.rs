@@ -7,7 +7,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use heck::ToSnakeCase;
+use exo_sql::schema::identifier::fit_identifier;
+use heck::{ToSnakeCase, ToUpperCamelCase};
 use postgres_core_model::types::EntityType;
 
 /// A type with both singular and plural versions of itself.
@@ -55,6 +56,89 @@ impl ToTableName for str {
     }
 }
 
+/// How the names of tables and columns are derived from the names of types and fields (unless
+/// specified with `@table` or `@column`). Derived names longer than Postgres allows are truncated
+/// (see [`fit_identifier`]).
+///
+/// Set for a module with `@postgres(naming=..., pluralizeTables=..., tablePrefix=...)` and
+/// overridden for a type with `@table(naming=..., pluralize=..., prefix=...)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamingStrategy {
+    pub case: NamingCase,
+    /// Use the plural form of the type name as the table name (`Concert` -> `concerts`)
+    pub pluralize_tables: bool,
+    /// Prefix added to every table name (after applying the case)
+    pub table_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamingCase {
+    /// `ConcertArtist` -> `concert_artist`, `mainVenue` -> `main_venue` (the default)
+    SnakeCase,
+    /// Names are used as is: `ConcertArtist` -> `ConcertArtist`, `mainVenue` -> `mainVenue`
+    Preserve,
+}
+
+impl Default for NamingStrategy {
+    fn default() -> Self {
+        Self {
+            case: NamingCase::SnakeCase,
+            pluralize_tables: true,
+            table_prefix: None,
+        }
+    }
+}
+
+impl NamingCase {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "snake_case" => Some(NamingCase::SnakeCase),
+            "preserve" => Some(NamingCase::Preserve),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            NamingCase::SnakeCase => name.to_snake_case(),
+            NamingCase::Preserve => name.to_string(),
+        }
+    }
+}
+
+impl NamingStrategy {
+    pub fn table_name(&self, type_name: &str, plural_name: Option<String>) -> String {
+        let name = if self.pluralize_tables {
+            plural_name.unwrap_or_else(|| type_name.to_plural())
+        } else {
+            type_name.to_string()
+        };
+
+        fit_identifier(&format!(
+            "{}{}",
+            self.table_prefix.as_deref().unwrap_or_default(),
+            self.case.apply(&name)
+        ))
+    }
+
+    pub fn column_name(&self, field_name: &str) -> String {
+        fit_identifier(&self.case.apply(field_name))
+    }
+
+    /// The name of the column referring to the `pk_field_name` field of the type of the `field_name`
+    /// field (for example, `venue_id` for the `venue` field)
+    pub fn foreign_key_column_name(&self, field_name: &str, pk_field_name: &str) -> String {
+        let name = match self.case {
+            NamingCase::SnakeCase => format!("{}_{}", field_name.to_snake_case(), pk_field_name),
+            NamingCase::Preserve => {
+                format!("{}{}", field_name, pk_field_name.to_upper_camel_case())
+            }
+        };
+
+        fit_identifier(&name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +154,43 @@ mod tests {
 
         assert_eq!("concert_artists", "ConcertArtist".table_name(None));
     }
+
+    #[multiplatform_test]
+    fn naming_strategies() {
+        let default = NamingStrategy::default();
+        assert_eq!("concert_artists", default.table_name("ConcertArtist", None));
+        assert_eq!(
+            "cons_foos",
+            default.table_name("Concert", Some("consFoos".to_string()))
+        );
+        assert_eq!("main_venue", default.column_name("mainVenue"));
+        assert_eq!(
+            "main_venue_id",
+            default.foreign_key_column_name("mainVenue", "id")
+        );
+
+        let prefixed_singular = NamingStrategy {
+            pluralize_tables: false,
+            table_prefix: Some("app_".to_string()),
+            ..NamingStrategy::default()
+        };
+        assert_eq!(
+            "app_concert_artist",
+            prefixed_singular.table_name("ConcertArtist", None)
+        );
+
+        let preserved = NamingStrategy {
+            case: NamingCase::Preserve,
+            ..NamingStrategy::default()
+        };
+        assert_eq!(
+            "ConcertArtists",
+            preserved.table_name("ConcertArtist", None)
+        );
+        assert_eq!("mainVenue", preserved.column_name("mainVenue"));
+        assert_eq!(
+            "mainVenueId",
+            preserved.foreign_key_column_name("mainVenue", "id")
+        );
+    }
 }
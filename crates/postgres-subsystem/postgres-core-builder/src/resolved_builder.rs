@@ -21,31 +21,33 @@ use std::{
 
 use codemap::Span;
 use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
-use postgres_core_model::types::EntityRepresentation;
+use postgres_core_model::types::{EntityRepresentation, FieldMask};
 use serde::{Deserialize, Serialize};
 
 use super::{
     access_builder::{ResolvedAccess, build_access},
-    naming::{ToPlural, ToTableName},
+    naming::{NamingCase, NamingStrategy, ToPlural},
 };
 use crate::{
     resolved_type::{
-        ExplicitTypeHint, JoinTableShortcutCardinality, JoinTableShortcutConfig,
+        ExplicitTypeHint, JoinTableShortcutCardinality, JoinTableShortcutConfig, ResolvedApproval,
         ResolvedCompositeType, ResolvedComputedField, ResolvedEnumType, ResolvedField,
-        ResolvedFieldDefault, ResolvedFieldType, ResolvedJoinTableConfig,
-        ResolvedJoinTableIntermediateField, ResolvedJoinTableShortcutField, ResolvedType,
+        ResolvedFieldDefault, ResolvedFieldGuard, ResolvedFieldGuards, ResolvedFieldType,
+        ResolvedJoinTableConfig, ResolvedJoinTableIntermediateField,
+        ResolvedJoinTableShortcutField, ResolvedManyToMany, ResolvedOwnershipTransfer,
+        ResolvedPartition, ResolvedRetention, ResolvedType, ResolvedValidator, ResolvedWindowField,
         SerializableTypeHint,
     },
     type_provider::{PRIMITIVE_TYPE_PROVIDER_REGISTRY, validate_hint_annotations},
 };
 use core_model::{
     mapped_arena::MappedArena,
-    primitive_type::{self},
+    primitive_type::{self, PrimitiveBaseType},
     types::{FieldType, Named},
 };
 use core_model_builder::{
     ast::ast_types::{
-        AstAnnotation, AstAnnotationParams, AstExpr, AstField, AstFieldDefault,
+        AstAnnotation, AstAnnotationParams, AstEnum, AstExpr, AstField, AstFieldDefault,
         AstFieldDefaultKind, AstFieldType, AstModel, AstModelKind, FieldSelection,
         FieldSelectionElement, LogicalOp, RelationalOp, default_span,
     },
@@ -56,9 +58,16 @@ use core_model_builder::{
         typ::{Module, Type, TypecheckedSystem},
     },
 };
-use exo_sql::SchemaObjectName;
+use exo_sql::{
+    ColumnStorage, CompressionMethod, MaskStrategy, Ordering, ReferentialAction, SchemaObjectName,
+    StorageStrategy, WindowFunction,
+    schema::{
+        identifier::{MAX_IDENTIFIER_LENGTH, fit_identifier, is_plain_identifier},
+        partition_spec::{DEFAULT_PREMAKE, PartitionInterval, PartitionStrategy},
+    },
+};
 
-use heck::ToSnakeCase;
+use heck::ToLowerCamelCase;
 
 const DEFAULT_FN_AUTO_INCREMENT: &str = "autoIncrement";
 const DEFAULT_FN_CURRENT_TIME: &str = "now";
@@ -77,6 +86,8 @@ struct OwnershipConfig {
     restrict_roles: Option<Vec<String>>,
     exempt_roles: Option<Vec<String>>,
     auto_assign: bool,
+    /// Access rule for the ownership transfer mutation (if one is to be generated)
+    transfer: Option<AstExpr<Typed>>,
 }
 
 fn field_selection_from_str(path: &str) -> FieldSelection<Typed> {
@@ -175,6 +186,38 @@ fn parse_bool_literal(
     }
 }
 
+fn parse_positive_integer_literal(
+    ct: &AstModel<Typed>,
+    label: &str,
+    expr: &AstExpr<Typed>,
+    span: Span,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<u64> {
+    match expr {
+        AstExpr::NumberLiteral(value, _) => match value.parse::<u64>() {
+            Ok(value) if value > 0 => Some(value),
+            _ => {
+                push_type_error(
+                    ct,
+                    span,
+                    format!("Expected a positive integer for '{}'", label),
+                    errors,
+                );
+                None
+            }
+        },
+        _ => {
+            push_type_error(
+                ct,
+                span,
+                format!("Expected a number literal for '{}'", label),
+                errors,
+            );
+            None
+        }
+    }
+}
+
 fn parse_visibility_annotation(
     ct: &AstModel<Typed>,
     annotation: Option<&AstAnnotation<Typed>>,
@@ -337,6 +380,8 @@ fn parse_ownership_annotation(
         .and_then(|expr| parse_bool_literal(ct, "autoAssign", expr, annotation.span, errors))
         .unwrap_or(true);
 
+    let transfer = params.get("transfer").cloned();
+
     Some(OwnershipConfig {
         field,
         field_path,
@@ -348,1898 +393,4590 @@ fn parse_ownership_annotation(
         restrict_roles,
         exempt_roles,
         auto_assign,
+        transfer,
     })
 }
 
-fn parse_join_table_annotation(
+fn parse_retention_annotation(
     ct: &AstModel<Typed>,
     annotation: &AstAnnotation<Typed>,
     errors: &mut Vec<Diagnostic>,
-) -> Option<ResolvedJoinTableConfig> {
+) -> Option<ResolvedRetention> {
     let params = match &annotation.params {
         AstAnnotationParams::Map(map, _) => map,
         _ => {
             push_type_error(
                 ct,
                 annotation.span,
-                "@joinTable expects named parameters".to_string(),
+                "@retention expects named parameters".to_string(),
                 errors,
             );
             return None;
         }
     };
 
-    let shortcuts_expr = match params.get("shortcuts") {
-        Some(expr) => expr,
-        None => {
+    let days = params
+        .get("days")
+        .and_then(|expr| parse_positive_integer_literal(ct, "days", expr, expr.span(), errors));
+    let field = params
+        .get("field")
+        .and_then(|expr| parse_string_literal(ct, "field", expr, expr.span(), errors).cloned());
+    let soft_delete_field = match params.get("softDelete") {
+        Some(expr) => {
+            Some(parse_string_literal(ct, "softDelete", expr, expr.span(), errors)?.clone())
+        }
+        None => None,
+    };
+    let batch_size = match params.get("batchSize") {
+        Some(expr) => {
+            Some(
+                parse_positive_integer_literal(ct, "batchSize", expr, expr.span(), errors)?
+                    as usize,
+            )
+        }
+        None => None,
+    };
+
+    let days = match u32::try_from(days?) {
+        Ok(days) => days,
+        Err(_) => {
             push_type_error(
                 ct,
                 annotation.span,
-                "@joinTable requires a 'shortcuts' parameter".to_string(),
+                "@retention 'days' is too large".to_string(),
                 errors,
             );
             return None;
         }
     };
 
-    let shortcuts_object = match shortcuts_expr {
-        AstExpr::ObjectLiteral(map, _) => map,
+    Some(ResolvedRetention {
+        days,
+        field: field?,
+        soft_delete_field,
+        batch_size,
+    })
+}
+
+fn parse_partition_annotation(
+    ct: &AstModel<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedPartition> {
+    let params = match &annotation.params {
+        AstAnnotationParams::Map(map, _) => map,
         _ => {
             push_type_error(
                 ct,
-                shortcuts_expr.span(),
-                "@joinTable 'shortcuts' must be an object literal (e.g. shortcuts={...})"
-                    .to_string(),
+                annotation.span,
+                "@partitionBy expects named parameters".to_string(),
                 errors,
             );
             return None;
         }
     };
 
-    let mut shortcuts = Vec::new();
-
-    for (key, expr) in shortcuts_object {
-        let config_map = match expr {
-            AstExpr::ObjectLiteral(map, _) => map,
-            _ => {
+    match (params.get("range"), params.get("list")) {
+        (Some(field_expr), None) => {
+            if params.contains_key("values") {
                 push_type_error(
                     ct,
-                    expr.span(),
-                    format!(
-                        "Shortcut '{}' must be specified using an object literal",
-                        key
-                    ),
+                    annotation.span,
+                    "@partitionBy 'values' applies only to list partitioning".to_string(),
                     errors,
                 );
-                continue;
+                return None;
             }
-        };
 
-        let mut get_required = |label: &str| -> Option<String> {
-            config_map.get(label).and_then(|expr| {
-                parse_string_literal(ct, label, expr, expr.span(), errors).cloned()
+            let field =
+                parse_string_literal(ct, "range", field_expr, field_expr.span(), errors)?.clone();
+
+            let interval = match params.get("interval") {
+                Some(expr) => {
+                    let interval = parse_string_literal(ct, "interval", expr, expr.span(), errors)?;
+                    match interval.parse::<PartitionInterval>() {
+                        Ok(interval) => interval,
+                        Err(message) => {
+                            push_type_error(ct, expr.span(), message, errors);
+                            return None;
+                        }
+                    }
+                }
+                None => {
+                    push_type_error(
+                        ct,
+                        annotation.span,
+                        "@partitionBy with 'range' requires an 'interval' parameter".to_string(),
+                        errors,
+                    );
+                    return None;
+                }
+            };
+
+            let premake = match params.get("premake") {
+                Some(expr) => {
+                    let premake =
+                        parse_positive_integer_literal(ct, "premake", expr, expr.span(), errors)?;
+                    u32::try_from(premake).unwrap_or(u32::MAX)
+                }
+                None => DEFAULT_PREMAKE,
+            };
+
+            Some(ResolvedPartition {
+                field,
+                strategy: PartitionStrategy::Range {
+                    interval: Some(interval),
+                    premake,
+                },
             })
-        };
+        }
+        (None, Some(field_expr)) => {
+            if params.contains_key("interval") || params.contains_key("premake") {
+                push_type_error(
+                    ct,
+                    annotation.span,
+                    "@partitionBy 'interval' and 'premake' apply only to range partitioning"
+                        .to_string(),
+                    errors,
+                );
+                return None;
+            }
 
-        let source_entity = match get_required("sourceEntity") {
-            Some(value) => value,
-            None => continue,
-        };
-        let source_foreign_key = match get_required("sourceForeignKey") {
-            Some(value) => value,
-            None => continue,
-        };
-        let join_reference_key = match get_required("joinReferenceKey") {
-            Some(value) => value,
-            None => continue,
-        };
+            let field =
+                parse_string_literal(ct, "list", field_expr, field_expr.span(), errors)?.clone();
 
-        let expose_as = if let Some(expr) = config_map.get("exposeAs") {
-            parse_string_literal(ct, "exposeAs", expr, expr.span(), errors)
-                .cloned()
-                .unwrap_or_else(|| key.clone())
-        } else {
-            key.clone()
-        };
+            let values = match params.get("values") {
+                Some(expr) => parse_string_list(ct, "values", expr, false, expr.span(), errors)?,
+                None => {
+                    push_type_error(
+                        ct,
+                        annotation.span,
+                        "@partitionBy with 'list' requires a 'values' parameter".to_string(),
+                        errors,
+                    );
+                    return None;
+                }
+            };
 
-        let join_reference = config_map.get("joinReference").and_then(|expr| {
-            parse_string_literal(ct, "joinReference", expr, expr.span(), errors).cloned()
-        });
+            Some(ResolvedPartition {
+                field,
+                strategy: PartitionStrategy::List { values },
+            })
+        }
+        _ => {
+            push_type_error(
+                ct,
+                annotation.span,
+                "@partitionBy requires exactly one of 'range' or 'list'".to_string(),
+                errors,
+            );
+            None
+        }
+    }
+}
 
-        let cardinality = config_map.get("cardinality").map_or(
-            JoinTableShortcutCardinality::Many,
-            |expr| {
-                parse_string_literal(ct, "cardinality", expr, expr.span(), errors)
-                    .map(|value| match value.to_lowercase().as_str() {
-                        "many" => JoinTableShortcutCardinality::Many,
-                        "optional" | "one" | "single" => JoinTableShortcutCardinality::Optional,
-                        other => {
-                            push_type_error(
-                                ct,
-                                expr.span(),
-                                format!(
-                                    "Unsupported cardinality '{}' for @joinTable shortcut '{}'. Expected 'many' or 'optional'",
-                                    other, key
-                                ),
-                                errors,
-                            );
-                            JoinTableShortcutCardinality::Many
-                        }
-                    })
-                    .unwrap_or(JoinTableShortcutCardinality::Many)
-            },
+/// Validate `@opaqueId`, which requires the type to have a single `Int` primary key (with values
+/// that fit in 31 bits, so not a 64-bit one)
+fn validate_opaque_id_annotation(
+    ct: &AstModel<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    is_json_like: bool,
+    resolved_fields: &[ResolvedField],
+    errors: &mut Vec<Diagnostic>,
+) -> bool {
+    if is_json_like {
+        push_type_error(
+            ct,
+            annotation.span,
+            format!("Type '{}' cannot use @opaqueId without a table", ct.name),
+            errors,
         );
-
-        shortcuts.push(JoinTableShortcutConfig {
-            source_entity,
-            source_foreign_key,
-            join_reference_key,
-            expose_as,
-            join_reference,
-            cardinality,
-        });
+        return false;
     }
 
-    if shortcuts.is_empty() {
+    let pk_fields = resolved_fields
+        .iter()
+        .filter(|field| field.is_pk)
+        .collect::<Vec<_>>();
+
+    let valid = match &pk_fields[..] {
+        [pk_field] => {
+            let is_int = matches!(
+                &pk_field.typ,
+                FieldType::Plain(ResolvedFieldType { type_name, is_primitive: true })
+                    if type_name == primitive_type::IntType::NAME
+            );
+            let is_64_bit = ct
+                .fields
+                .iter()
+                .find(|field| field.name == pk_field.name)
+                .is_some_and(|field| {
+                    field.annotations.contains("bits64") || field.annotations.contains("dbtype")
+                });
+
+            is_int && !is_64_bit
+        }
+        _ => false,
+    };
+
+    if !valid {
         push_type_error(
             ct,
             annotation.span,
-            "@joinTable requires at least one shortcut definition".to_string(),
+            format!(
+                "Type '{}' with @opaqueId must have a single primary key field of type Int (without @bits64 or @dbtype)",
+                ct.name
+            ),
             errors,
         );
-        return None;
     }
 
-    Some(ResolvedJoinTableConfig { shortcuts })
+    valid
 }
 
-fn combine_with_guard(
-    existing: Option<AstExpr<Typed>>,
-    guard: AstExpr<Typed>,
-) -> Option<AstExpr<Typed>> {
-    Some(match existing {
-        Some(expr) => AstExpr::LogicalOp(LogicalOp::And(
-            Box::new(expr),
-            Box::new(guard),
-            default_span(),
-            Type::Defer,
-        )),
-        None => guard,
-    })
-}
+/// Parse `@mapsTo({Urgent: "High"})`, which maps variants removed from an enum to the remaining
+/// variants (so that migrations can move the existing values to them)
+fn parse_maps_to_annotation(
+    et: &AstEnum<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    errors: &mut Vec<Diagnostic>,
+) -> Vec<(String, String)> {
+    let push_error = |span: Span, message: String, errors: &mut Vec<Diagnostic>| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    };
 
-fn build_ownership_guard_expr(config: &OwnershipConfig) -> AstExpr<Typed> {
-    let owner_expr =
-        AstExpr::FieldSelection(field_selection_from_str(&format!("self.{}", config.field)));
-    let context_expr = AstExpr::FieldSelection(field_selection_from_str(&config.context_path));
+    let mappings_object = match &annotation.params {
+        AstAnnotationParams::Single(AstExpr::ObjectLiteral(map, _), _) => map,
+        _ => {
+            push_error(
+                annotation.span,
+                "@mapsTo expects an object literal mapping removed variants to existing ones (e.g. @mapsTo({Urgent: \"High\"}))"
+                    .to_string(),
+                errors,
+            );
+            return vec![];
+        }
+    };
 
-    let context_not_null = AstExpr::LogicalOp(LogicalOp::Not(
-        Box::new(AstExpr::RelationalOp(RelationalOp::Eq(
-            Box::new(context_expr.clone()),
-            Box::new(AstExpr::NullLiteral(default_span())),
-            Type::Defer,
-        ))),
-        default_span(),
-        Type::Defer,
-    ));
+    // Sort by name for a stable order (object literals don't preserve the declaration order)
+    let mut removed_variants: Vec<_> = mappings_object.keys().collect();
+    removed_variants.sort();
 
-    let equality = AstExpr::RelationalOp(RelationalOp::Eq(
-        Box::new(owner_expr),
-        Box::new(context_expr),
-        Type::Defer,
-    ));
+    removed_variants
+        .into_iter()
+        .filter_map(|removed_variant| {
+            let expr = &mappings_object[removed_variant];
 
-    let mut guard = AstExpr::LogicalOp(LogicalOp::And(
-        Box::new(context_not_null),
-        Box::new(equality),
-        default_span(),
-        Type::Defer,
-    ));
+            if et.fields.iter().any(|field| &field.name == removed_variant) {
+                push_error(
+                    expr.span(),
+                    format!(
+                        "@mapsTo variant '{}' is still a variant of '{}'",
+                        removed_variant, et.name
+                    ),
+                    errors,
+                );
+                return None;
+            }
 
-    let build_role_equals = |role: &str| {
-        AstExpr::RelationalOp(RelationalOp::Eq(
-            Box::new(AstExpr::FieldSelection(field_selection_from_str(
-                &config.role_path,
-            ))),
-            Box::new(AstExpr::StringLiteral(role.to_string(), default_span())),
-            Type::Defer,
-        ))
-    };
+            let target_variant = match expr {
+                AstExpr::StringLiteral(value, _) => value,
+                _ => {
+                    push_error(
+                        expr.span(),
+                        format!(
+                            "@mapsTo variant '{}' must be mapped to a variant name string",
+                            removed_variant
+                        ),
+                        errors,
+                    );
+                    return None;
+                }
+            };
 
-    let build_role_membership = |roles: &[String]| -> Option<AstExpr<Typed>> {
-        let mut iter = roles.iter();
-        let first = iter.next()?;
-        let mut combined = build_role_equals(first);
+            if !et.fields.iter().any(|field| &field.name == target_variant) {
+                push_error(
+                    expr.span(),
+                    format!(
+                        "@mapsTo maps '{}' to '{}', which is not a variant of '{}'",
+                        removed_variant, target_variant, et.name
+                    ),
+                    errors,
+                );
+                return None;
+            }
 
-        for role in iter {
-            let next = build_role_equals(role);
-            combined = AstExpr::LogicalOp(LogicalOp::Or(
-                Box::new(combined),
-                Box::new(next),
-                default_span(),
-                Type::Defer,
-            ));
-        }
+            Some((removed_variant.clone(), target_variant.clone()))
+        })
+        .collect()
+}
 
-        Some(combined)
+/// Parse `@window({gameRank: {function: "rank", partitionBy: "game", orderBy: "points desc"}})`
+fn parse_window_annotation(
+    ct: &AstModel<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    errors: &mut Vec<Diagnostic>,
+) -> Vec<ResolvedWindowField> {
+    let fields_object = match &annotation.params {
+        AstAnnotationParams::Single(AstExpr::ObjectLiteral(map, _), _) => map,
+        _ => {
+            push_type_error(
+                ct,
+                annotation.span,
+                "@window expects an object literal mapping field names to their definitions (e.g. @window({rank: {...}}))"
+                    .to_string(),
+                errors,
+            );
+            return vec![];
+        }
     };
 
-    if let Some(restrict_roles) = &config.restrict_roles
-        && !restrict_roles.is_empty()
-        && let Some(role_in_restrict) = build_role_membership(restrict_roles)
-    {
-        let not_role_in_restrict = AstExpr::LogicalOp(LogicalOp::Not(
-            Box::new(role_in_restrict),
-            default_span(),
-            Type::Defer,
-        ));
+    // Sort by name for a stable order (object literals don't preserve the declaration order)
+    let mut field_names: Vec<_> = fields_object.keys().collect();
+    field_names.sort();
 
-        guard = AstExpr::LogicalOp(LogicalOp::Or(
-            Box::new(not_role_in_restrict),
-            Box::new(guard),
-            default_span(),
-            Type::Defer,
-        ));
-    }
+    field_names
+        .into_iter()
+        .filter_map(|name| {
+            let expr = &fields_object[name];
 
-    if let Some(exempt_roles) = &config.exempt_roles
-        && !exempt_roles.is_empty()
-        && let Some(role_in_exempt) = build_role_membership(exempt_roles)
-    {
-        guard = AstExpr::LogicalOp(LogicalOp::Or(
-            Box::new(role_in_exempt),
-            Box::new(guard),
-            default_span(),
-            Type::Defer,
-        ));
-    }
+            if ct.fields.iter().any(|field| &field.name == name) {
+                push_type_error(
+                    ct,
+                    expr.span(),
+                    format!(
+                        "@window field '{}' conflicts with an existing field on '{}'",
+                        name, ct.name
+                    ),
+                    errors,
+                );
+                return None;
+            }
 
-    guard
-}
+            let config = match expr {
+                AstExpr::ObjectLiteral(map, _) => map,
+                _ => {
+                    push_type_error(
+                        ct,
+                        expr.span(),
+                        format!("@window field '{}' must be specified using an object literal", name),
+                        errors,
+                    );
+                    return None;
+                }
+            };
 
-/// Represents the different ways a field's column name(s) can be configured
-#[derive(Debug, Clone, PartialEq)]
-pub enum ColumnMapping {
-    /// Single column name for simple fields: @column("custom_name")
-    Single(String),
-    /// Mapping for composite types: @column(mapping={field1: "col1", field2: "col2"})
-    Map(HashMap<String, String>),
-}
+            if let Some(unknown) = config
+                .keys()
+                .find(|key| !["function", "partitionBy", "orderBy"].contains(&key.as_str()))
+            {
+                push_type_error(
+                    ct,
+                    expr.span(),
+                    format!(
+                        "Unknown key '{}' for @window field '{}'. Expected 'function', 'partitionBy', or 'orderBy'",
+                        unknown, name
+                    ),
+                    errors,
+                );
+                return None;
+            }
 
-/// Consume typed-checked types and build resolved types
-pub fn build(
-    typechecked_system: &TypecheckedSystem,
-) -> Result<MappedArena<ResolvedType>, ModelBuildingError> {
-    let mut errors = Vec::new();
+            let function = match config.get("function") {
+                Some(expr) => match parse_string_literal(ct, "function", expr, expr.span(), errors)?
+                    .as_str()
+                {
+                    "rank" => WindowFunction::Rank,
+                    "denseRank" => WindowFunction::DenseRank,
+                    "rowNumber" => WindowFunction::RowNumber,
+                    other => {
+                        push_type_error(
+                            ct,
+                            expr.span(),
+                            format!(
+                                "Unsupported window function '{}'. Expected 'rank', 'denseRank', or 'rowNumber'",
+                                other
+                            ),
+                            errors,
+                        );
+                        return None;
+                    }
+                },
+                None => WindowFunction::Rank,
+            };
 
-    let resolved_system = resolve(typechecked_system, &mut errors)?;
+            let partition_by = match config.get("partitionBy") {
+                Some(expr) => parse_string_list(ct, "partitionBy", expr, true, expr.span(), errors)?,
+                None => vec![],
+            };
 
-    if errors.is_empty() {
-        Ok(resolved_system)
-    } else {
-        Err(ModelBuildingError::Diagnosis(errors))
-    }
-}
+            let order_by = match config.get("orderBy") {
+                Some(expr) => {
+                    let entries = parse_string_list(ct, "orderBy", expr, true, expr.span(), errors)?;
+                    let order_by = entries
+                        .iter()
+                        .map(|entry| match entry.split_whitespace().collect::<Vec<_>>()[..] {
+                            [field] => Some((field.to_string(), Ordering::Asc)),
+                            [field, ordering] if ordering.eq_ignore_ascii_case("asc") => {
+                                Some((field.to_string(), Ordering::Asc))
+                            }
+                            [field, ordering] if ordering.eq_ignore_ascii_case("desc") => {
+                                Some((field.to_string(), Ordering::Desc))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>();
 
-fn resolve_field_type(typ: &Type, types: &MappedArena<Type>) -> FieldType<ResolvedFieldType> {
-    match typ {
-        Type::Optional(underlying) => {
-            FieldType::Optional(Box::new(resolve_field_type(underlying.as_ref(), types)))
-        }
-        Type::Reference(id) => FieldType::Plain(ResolvedFieldType {
-            type_name: types[*id].get_underlying_typename(types).unwrap(),
-            is_primitive: matches!(types[*id], Type::Primitive(_) | Type::Enum(_)),
-        }),
-        Type::Set(underlying) | Type::Array(underlying) => {
-            FieldType::List(Box::new(resolve_field_type(underlying.as_ref(), types)))
-        }
-        _ => todo!("Unsupported field type"),
-    }
+                    match order_by {
+                        Some(order_by) if !order_by.is_empty() => order_by,
+                        _ => {
+                            push_type_error(
+                                ct,
+                                expr.span(),
+                                format!(
+                                    "Invalid 'orderBy' for @window field '{}'. Expected entries such as \"points\" or \"points desc\"",
+                                    name
+                                ),
+                                errors,
+                            );
+                            return None;
+                        }
+                    }
+                }
+                None => {
+                    push_type_error(
+                        ct,
+                        expr.span(),
+                        format!("@window field '{}' requires an 'orderBy'", name),
+                        errors,
+                    );
+                    return None;
+                }
+            };
+
+            Some(ResolvedWindowField {
+                name: name.clone(),
+                function,
+                partition_by,
+                order_by,
+            })
+        })
+        .collect()
 }
 
-fn resolve(
-    typechecked_system: &TypecheckedSystem,
+fn parse_join_table_annotation(
+    ct: &AstModel<Typed>,
+    annotation: &AstAnnotation<Typed>,
     errors: &mut Vec<Diagnostic>,
-) -> Result<MappedArena<ResolvedType>, ModelBuildingError> {
-    let mut resolved_postgres_types: MappedArena<ResolvedType> = MappedArena::default();
-
-    for (_, typ) in typechecked_system.types.iter() {
-        // Adopt the primitive types as a PostgresType
-        if let Type::Primitive(pt) = typ {
-            resolved_postgres_types.add(&pt.name(), ResolvedType::Primitive(pt.clone()));
+) -> Option<ResolvedJoinTableConfig> {
+    let params = match &annotation.params {
+        AstAnnotationParams::Map(map, _) => map,
+        _ => {
+            push_type_error(
+                ct,
+                annotation.span,
+                "@joinTable expects named parameters".to_string(),
+                errors,
+            );
+            return None;
         }
-    }
+    };
 
-    for (_, Module(module)) in typechecked_system.modules.iter() {
-        // Process each persistent type to create a PostgresType
-        let module_annotation = module.annotations.get("postgres");
+    let shortcuts_expr = match params.get("shortcuts") {
+        Some(expr) => expr,
+        None => {
+            push_type_error(
+                ct,
+                annotation.span,
+                "@joinTable requires a 'shortcuts' parameter".to_string(),
+                errors,
+            );
+            return None;
+        }
+    };
 
-        let module_schema_name = match module_annotation {
-            Some(a) => {
-                if let AstAnnotationParams::Map(map, _) = a {
-                    map.get("schema").map(|s| s.as_string())
-                } else {
-                    None
-                }
-            }
-            None => continue,
-        };
+    let shortcuts_object = match shortcuts_expr {
+        AstExpr::ObjectLiteral(map, _) => map,
+        _ => {
+            push_type_error(
+                ct,
+                shortcuts_expr.span(),
+                "@joinTable 'shortcuts' must be an object literal (e.g. shortcuts={...})"
+                    .to_string(),
+                errors,
+            );
+            return None;
+        }
+    };
 
-        let module_managed: Option<bool> = module_annotation.and_then(|a| {
-            if let AstAnnotationParams::Map(map, _) = a {
-                map.get("managed").map(|s| s.as_boolean())
-            } else {
-                None
-            }
-        });
+    let mut shortcuts = Vec::new();
 
-        for typ in module.types.iter() {
-            if let Some(Type::Composite(ct)) = typechecked_system.types.get_by_key(&typ.name) {
-                resolve_composite_type(
+    for (key, expr) in shortcuts_object {
+        let config_map = match expr {
+            AstExpr::ObjectLiteral(map, _) => map,
+            _ => {
+                push_type_error(
                     ct,
-                    &module_schema_name,
-                    module_managed,
-                    &module.base_exofile,
-                    typechecked_system,
-                    &mut resolved_postgres_types,
+                    expr.span(),
+                    format!(
+                        "Shortcut '{}' must be specified using an object literal",
+                        key
+                    ),
                     errors,
-                )
-                .map_err(|e| ModelBuildingError::Diagnosis(vec![e]))?;
+                );
+                continue;
             }
-        }
+        };
 
-        for typ in module.enums.iter() {
-            if let Some(Type::Enum(et)) = typechecked_system.types.get_by_key(&typ.name) {
-                resolved_postgres_types.add(
-                    &et.name,
-                    ResolvedType::Enum(ResolvedEnumType {
-                        name: et.name.clone(),
-                        fields: et.fields.iter().map(|f| f.name.clone()).collect(),
-                        enum_name: SchemaObjectName::new(
-                            et.name.to_snake_case(),
-                            module_schema_name.as_deref(),
-                        ),
-                        doc_comments: et.doc_comments.clone(),
-                        span: et.span,
-                    }),
-                );
-            }
-        }
-    }
+        let mut get_required = |label: &str| -> Option<String> {
+            config_map.get(label).and_then(|expr| {
+                parse_string_literal(ct, label, expr, expr.span(), errors).cloned()
+            })
+        };
 
-    apply_join_table_shortcuts(&mut resolved_postgres_types, errors);
+        let source_entity = match get_required("sourceEntity") {
+            Some(value) => value,
+            None => continue,
+        };
+        let source_foreign_key = match get_required("sourceForeignKey") {
+            Some(value) => value,
+            None => continue,
+        };
+        let join_reference_key = match get_required("joinReferenceKey") {
+            Some(value) => value,
+            None => continue,
+        };
 
-    Ok(resolved_postgres_types)
-}
+        let expose_as = if let Some(expr) = config_map.get("exposeAs") {
+            parse_string_literal(ct, "exposeAs", expr, expr.span(), errors)
+                .cloned()
+                .unwrap_or_else(|| key.clone())
+        } else {
+            key.clone()
+        };
 
-fn apply_join_table_shortcuts(
-    resolved_types: &mut MappedArena<ResolvedType>,
-    errors: &mut Vec<Diagnostic>,
-) {
-    let mut join_configs: Vec<(String, ResolvedJoinTableConfig)> = Vec::new();
-    for (_, typ) in resolved_types.iter() {
-        if let ResolvedType::Composite(composite) = typ
-            && let Some(config) = &composite.join_table
-        {
-            join_configs.push((composite.name.clone(), config.clone()));
-        }
+        let join_reference = config_map.get("joinReference").and_then(|expr| {
+            parse_string_literal(ct, "joinReference", expr, expr.span(), errors).cloned()
+        });
+
+        let cardinality = config_map.get("cardinality").map_or(
+            JoinTableShortcutCardinality::Many,
+            |expr| {
+                parse_string_literal(ct, "cardinality", expr, expr.span(), errors)
+                    .map(|value| match value.to_lowercase().as_str() {
+                        "many" => JoinTableShortcutCardinality::Many,
+                        "optional" | "one" | "single" => JoinTableShortcutCardinality::Optional,
+                        other => {
+                            push_type_error(
+                                ct,
+                                expr.span(),
+                                format!(
+                                    "Unsupported cardinality '{}' for @joinTable shortcut '{}'. Expected 'many' or 'optional'",
+                                    other, key
+                                ),
+                                errors,
+                            );
+                            JoinTableShortcutCardinality::Many
+                        }
+                    })
+                    .unwrap_or(JoinTableShortcutCardinality::Many)
+            },
+        );
+
+        shortcuts.push(JoinTableShortcutConfig {
+            source_entity,
+            source_foreign_key,
+            join_reference_key,
+            expose_as,
+            join_reference,
+            cardinality,
+        });
     }
 
-    for (join_type_name, config) in join_configs {
-        if config.shortcuts.len() != 2 {
-            errors.push(Diagnostic {
-                level: Level::Error,
-                message: format!(
-                    "@joinTable on '{}' currently supports exactly two shortcuts",
-                    join_type_name
-                ),
-                code: Some("C000".to_string()),
-                spans: vec![],
-            });
-            continue;
-        }
+    if shortcuts.is_empty() {
+        push_type_error(
+            ct,
+            annotation.span,
+            "@joinTable requires at least one shortcut definition".to_string(),
+            errors,
+        );
+        return None;
+    }
 
-        let shortcuts = config.shortcuts;
+    Some(ResolvedJoinTableConfig { shortcuts })
+}
 
-        for (index, shortcut) in shortcuts.iter().enumerate() {
-            let target_index = (index + 1) % shortcuts.len();
-            let target = &shortcuts[target_index];
+fn combine_with_guard(
+    existing: Option<AstExpr<Typed>>,
+    guard: AstExpr<Typed>,
+) -> Option<AstExpr<Typed>> {
+    Some(match existing {
+        Some(expr) => AstExpr::LogicalOp(LogicalOp::And(
+            Box::new(expr),
+            Box::new(guard),
+            default_span(),
+            Type::Defer,
+        )),
+        None => guard,
+    })
+}
 
-            if shortcut.source_entity == target.source_entity {
-                errors.push(Diagnostic {
-                    level: Level::Error,
-                    message: format!(
-                        "@joinTable shortcut '{}' on '{}' must reference a different sourceEntity than its counterpart",
-                        shortcut.expose_as, join_type_name
-                    ),
-                    code: Some("C000".to_string()),
-                    spans: vec![],
-                });
-                continue;
-            }
+fn build_ownership_guard_expr(config: &OwnershipConfig) -> AstExpr<Typed> {
+    let owner_expr =
+        AstExpr::FieldSelection(field_selection_from_str(&format!("self.{}", config.field)));
+    let context_expr = AstExpr::FieldSelection(field_selection_from_str(&config.context_path));
 
-            let direct_field_name = shortcut.expose_as.clone();
+    let context_not_null = AstExpr::LogicalOp(LogicalOp::Not(
+        Box::new(AstExpr::RelationalOp(RelationalOp::Eq(
+            Box::new(context_expr.clone()),
+            Box::new(AstExpr::NullLiteral(default_span())),
+            Type::Defer,
+        ))),
+        default_span(),
+        Type::Defer,
+    ));
 
-            let default_join_name = format!("_{}_join", shortcut.expose_as);
-            let join_field_name = shortcut.join_reference.clone().unwrap_or(default_join_name);
+    let equality = AstExpr::RelationalOp(RelationalOp::Eq(
+        Box::new(owner_expr),
+        Box::new(context_expr),
+        Type::Defer,
+    ));
 
-            let target_entity_name = target.source_entity.clone();
+    let mut guard = AstExpr::LogicalOp(LogicalOp::And(
+        Box::new(context_not_null),
+        Box::new(equality),
+        default_span(),
+        Type::Defer,
+    ));
 
-            let target_relation_name = {
-                let Some(ResolvedType::Composite(join_entity)) =
-                    resolved_types.get_by_key(&join_type_name)
-                else {
-                    errors.push(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "@joinTable on '{}' references unknown join table type",
-                            join_type_name
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![],
-                    });
-                    continue;
-                };
+    let build_role_equals = |role: &str| {
+        AstExpr::RelationalOp(RelationalOp::Eq(
+            Box::new(AstExpr::FieldSelection(field_selection_from_str(
+                &config.role_path,
+            ))),
+            Box::new(AstExpr::StringLiteral(role.to_string(), default_span())),
+            Type::Defer,
+        ))
+    };
 
-                join_entity
-                    .fields
-                    .iter()
-                    .find(|field| {
-                        field
-                            .column_names
-                            .iter()
-                            .any(|column| column == &target.join_reference_key)
-                            && match &field.typ {
-                                FieldType::Plain(inner) => inner.type_name == target_entity_name,
-                                FieldType::Optional(inner) => matches!(
-                                    &**inner,
-                                    FieldType::Plain(inner_plain)
-                                        if inner_plain.type_name == target_entity_name
-                                ),
-                                _ => false,
-                            }
-                    })
-                    .map(|field| field.name.clone())
-            };
+    let build_role_membership = |roles: &[String]| -> Option<AstExpr<Typed>> {
+        let mut iter = roles.iter();
+        let first = iter.next()?;
+        let mut combined = build_role_equals(first);
 
-            let target_relation_name = match target_relation_name {
-                Some(name) => name,
-                None => {
-                    errors.push(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "@joinTable shortcut '{}' on '{}' could not find a relation to '{}' using column '{}'",
-                            shortcut.expose_as, join_type_name, target_entity_name, target.join_reference_key
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![],
-                    });
-                    continue;
-                }
-            };
+        for role in iter {
+            let next = build_role_equals(role);
+            combined = AstExpr::LogicalOp(LogicalOp::Or(
+                Box::new(combined),
+                Box::new(next),
+                default_span(),
+                Type::Defer,
+            ));
+        }
 
-            let Some(ResolvedType::Composite(source_type)) =
-                resolved_types.get_by_key_mut(&shortcut.source_entity)
-            else {
-                errors.push(Diagnostic {
-                    level: Level::Error,
-                    message: format!(
-                        "@joinTable shortcut '{}' references unknown sourceEntity '{}'",
-                        shortcut.expose_as, shortcut.source_entity
-                    ),
-                    code: Some("C000".to_string()),
-                    spans: vec![],
-                });
-                continue;
-            };
+        Some(combined)
+    };
 
-            if source_type
-                .fields
-                .iter()
-                .any(|field| field.name == direct_field_name)
-            {
-                errors.push(Diagnostic {
-                    level: Level::Error,
-                    message: format!(
-                        "@joinTable shortcut '{}' on '{}' conflicts with an existing field on '{}'",
-                        direct_field_name, join_type_name, shortcut.source_entity
-                    ),
-                    code: Some("C000".to_string()),
-                    spans: vec![],
-                });
-                continue;
-            }
-
-            if source_type
-                .fields
-                .iter()
-                .any(|field| field.name == join_field_name)
-            {
-                errors.push(Diagnostic {
-                    level: Level::Error,
-                    message: format!(
-                        "@joinTable shortcut '{}' would create a join helper field '{}' on '{}' but that field already exists",
-                        shortcut.expose_as, join_field_name, shortcut.source_entity
-                    ),
-                    code: Some("C000".to_string()),
-                    spans: vec![],
-                });
-                continue;
-            }
-
-            let base_field_type = FieldType::Plain(ResolvedFieldType {
-                type_name: target_entity_name.clone(),
-                is_primitive: false,
-            });
+    if let Some(restrict_roles) = &config.restrict_roles
+        && !restrict_roles.is_empty()
+        && let Some(role_in_restrict) = build_role_membership(restrict_roles)
+    {
+        let not_role_in_restrict = AstExpr::LogicalOp(LogicalOp::Not(
+            Box::new(role_in_restrict),
+            default_span(),
+            Type::Defer,
+        ));
 
-            let field_type = match shortcut.cardinality {
-                JoinTableShortcutCardinality::Many => {
-                    FieldType::List(Box::new(base_field_type.clone()))
-                }
-                JoinTableShortcutCardinality::Optional => {
-                    FieldType::Optional(Box::new(base_field_type.clone()))
-                }
-            };
+        guard = AstExpr::LogicalOp(LogicalOp::Or(
+            Box::new(not_role_in_restrict),
+            Box::new(guard),
+            default_span(),
+            Type::Defer,
+        ));
+    }
 
-            let is_many = matches!(shortcut.cardinality, JoinTableShortcutCardinality::Many);
+    if let Some(exempt_roles) = &config.exempt_roles
+        && !exempt_roles.is_empty()
+        && let Some(role_in_exempt) = build_role_membership(exempt_roles)
+    {
+        guard = AstExpr::LogicalOp(LogicalOp::Or(
+            Box::new(role_in_exempt),
+            Box::new(guard),
+            default_span(),
+            Type::Defer,
+        ));
+    }
 
-            let allow_all_access = ResolvedAccess {
-                default: Some(AstExpr::BooleanLiteral(true, default_span())),
-                ..Default::default()
-            };
+    guard
+}
 
-            let shortcut_field = ResolvedField {
-                name: direct_field_name,
-                typ: field_type,
-                column_names: vec![],
-                self_column: false,
-                is_pk: false,
-                access: allow_all_access.clone(),
-                type_hint: None,
-                unique_constraints: vec![],
-                indices: vec![],
-                cardinality: None,
-                default_value: None,
-                update_sync: false,
-                readonly: true,
-                relation_path: None,
-                doc_comments: None,
-                computed: None,
-                join_table_shortcut: Some(ResolvedJoinTableShortcutField {
-                    join_table_type: join_type_name.clone(),
-                    join_table_source_column: shortcut.join_reference_key.clone(),
-                    join_table_target_column: target.join_reference_key.clone(),
-                    source_field: shortcut.source_foreign_key.clone(),
-                    target_entity: target_entity_name.clone(),
-                    target_field: target.source_foreign_key.clone(),
-                    is_many,
-                    join_reference_field_name: Some(join_field_name.clone()),
-                    join_table_target_relation: target_relation_name.clone(),
-                }),
-                join_table_intermediate: None,
-                span: default_span(),
-            };
+/// Represents the different ways a field's column name(s) can be configured
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnMapping {
+    /// Single column name for simple fields: @column("custom_name")
+    Single(String),
+    /// Mapping for composite types: @column(mapping={field1: "col1", field2: "col2"})
+    Map(HashMap<String, String>),
+}
 
-            source_type.fields.push(shortcut_field);
+/// Consume typed-checked types and build resolved types
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<MappedArena<ResolvedType>, ModelBuildingError> {
+    let mut errors = Vec::new();
 
-            let join_field_type = if is_many {
-                FieldType::List(Box::new(FieldType::Plain(ResolvedFieldType {
-                    type_name: join_type_name.clone(),
-                    is_primitive: false,
-                })))
-            } else {
-                FieldType::Optional(Box::new(FieldType::Plain(ResolvedFieldType {
-                    type_name: join_type_name.clone(),
-                    is_primitive: false,
-                })))
-            };
+    let resolved_system = resolve(typechecked_system, &mut errors)?;
 
-            let join_field = ResolvedField {
-                name: join_field_name.clone(),
-                typ: join_field_type,
-                column_names: vec![],
-                self_column: false,
-                is_pk: false,
-                access: allow_all_access,
-                type_hint: None,
-                unique_constraints: vec![],
-                indices: vec![],
-                cardinality: None,
-                default_value: None,
-                update_sync: false,
-                readonly: true,
-                relation_path: None,
-                doc_comments: None,
-                computed: None,
-                join_table_shortcut: None,
-                join_table_intermediate: Some(ResolvedJoinTableIntermediateField {
-                    join_table_type: join_type_name.clone(),
-                    join_table_source_column: shortcut.join_reference_key.clone(),
-                    source_field: shortcut.source_foreign_key.clone(),
-                }),
-                span: default_span(),
-            };
+    if errors.is_empty() {
+        Ok(resolved_system)
+    } else {
+        Err(ModelBuildingError::Diagnosis(errors))
+    }
+}
 
-            source_type.fields.push(join_field);
+fn resolve_field_type(typ: &Type, types: &MappedArena<Type>) -> FieldType<ResolvedFieldType> {
+    match typ {
+        Type::Optional(underlying) => {
+            FieldType::Optional(Box::new(resolve_field_type(underlying.as_ref(), types)))
+        }
+        Type::Reference(id) => FieldType::Plain(ResolvedFieldType {
+            type_name: types[*id].get_underlying_typename(types).unwrap(),
+            is_primitive: matches!(types[*id], Type::Primitive(_) | Type::Enum(_)),
+        }),
+        Type::Set(underlying) | Type::Array(underlying) => {
+            FieldType::List(Box::new(resolve_field_type(underlying.as_ref(), types)))
         }
+        _ => todo!("Unsupported field type"),
     }
 }
 
-fn resolve_composite_type(
-    ct: &AstModel<Typed>,
-    module_schema_name: &Option<String>,
-    module_managed: Option<bool>,
-    module_base_path: &Path,
+fn resolve(
     typechecked_system: &TypecheckedSystem,
-    resolved_postgres_types: &mut MappedArena<ResolvedType>,
     errors: &mut Vec<Diagnostic>,
-) -> Result<(), Diagnostic> {
-    if ct.kind == AstModelKind::Type {
-        let plural_annotation_value = ct
-            .annotations
-            .get("plural")
-            .map(|p| p.as_single().as_string());
-
-        let table_annotation = ct.annotations.annotations.get("table");
+) -> Result<MappedArena<ResolvedType>, ModelBuildingError> {
+    let mut resolved_postgres_types: MappedArena<ResolvedType> = MappedArena::default();
+    // The maximum relation path depth for each type (set through the module's `@postgres` annotation)
+    let mut relation_path_depth_limits: HashMap<String, usize> = HashMap::new();
 
-        let TableInfo {
-            name: table_name,
-            schema: schema_name,
-            managed: table_managed,
-        } = extract_table_annotation(table_annotation, &ct.name, plural_annotation_value.clone())?;
+    for (_, typ) in typechecked_system.types.iter() {
+        // Adopt the primitive types as a PostgresType
+        if let Type::Primitive(pt) = typ {
+            resolved_postgres_types.add(&pt.name(), ResolvedType::Primitive(pt.clone()));
+        }
+    }
 
-        // If the table didn't specify a schema, use the module schema
-        let schema_name = module_schema_name.clone().or(schema_name);
+    // The naming strategy of each type (computed upfront, since the columns for a relation may be
+    // named after the field of the type on the other side)
+    let mut naming_strategies: HashMap<String, NamingStrategy> = HashMap::new();
+    // The naming strategy of the module declaring each type or enum (for the enum names and the
+    // join types generated for many-to-many relations)
+    let mut module_naming_strategies: HashMap<String, NamingStrategy> = HashMap::new();
 
-        // If there is an explicit table managed attribute, that takes precedence.
-        // Otherwise, if there is an explicit module managed attribute, use that.
-        // Otherwise, default to managed.
-        let table_managed = match (table_managed, module_managed) {
-            (Some(table_managed), _) => table_managed,
-            (None, Some(module_managed)) => module_managed,
-            (None, None) => true,
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let Some(module_annotation) = module.annotations.get("postgres") else {
+            continue;
         };
 
-        let is_json = ct.annotations.contains("json");
-        let is_computed = ct.annotations.contains("computed");
+        let module_naming = parse_naming_strategy(
+            module_annotation,
+            ["naming", "pluralizeTables", "tablePrefix"],
+            &NamingStrategy::default(),
+            errors,
+        );
 
-        if is_json && is_computed {
-            errors.push(Diagnostic {
-                level: Level::Error,
-                message: format!("Type '{}' cannot use both @json and @computed", ct.name),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: ct.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            });
+        for typ in module.types.iter() {
+            if let Some(Type::Composite(ct)) = typechecked_system.types.get_by_key(&typ.name) {
+                let naming = match ct.annotations.get("table") {
+                    Some(table_annotation) => parse_naming_strategy(
+                        table_annotation,
+                        ["naming", "pluralize", "prefix"],
+                        &module_naming,
+                        errors,
+                    ),
+                    None => module_naming.clone(),
+                };
+                naming_strategies.insert(ct.name.clone(), naming);
+                module_naming_strategies.insert(ct.name.clone(), module_naming.clone());
+            }
         }
 
-        if is_computed && table_annotation.is_some() {
-            errors.push(Diagnostic {
-                level: Level::Error,
-                message: format!(
-                    "Type '{}' cannot use @table when marked as @computed",
-                    ct.name
-                ),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: ct.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            });
+        for typ in module.enums.iter() {
+            module_naming_strategies.insert(typ.name.clone(), module_naming.clone());
         }
+    }
 
-        let representation = if is_json {
-            EntityRepresentation::Json
-        } else if is_computed {
-            EntityRepresentation::Computed
-        } else if table_managed {
-            EntityRepresentation::Managed
-        } else {
-            EntityRepresentation::NotManaged
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        // Process each persistent type to create a PostgresType
+        let module_annotation = module.annotations.get("postgres");
+
+        let module_schema_name = match module_annotation {
+            Some(a) => {
+                if let AstAnnotationParams::Map(map, _) = a {
+                    map.get("schema").map(|s| s.as_string())
+                } else {
+                    None
+                }
+            }
+            None => continue,
         };
 
-        let access_annotation = ct.annotations.get("access");
+        let module_managed: Option<bool> = module_annotation.and_then(|a| {
+            if let AstAnnotationParams::Map(map, _) = a {
+                map.get("managed").map(|s| s.as_boolean())
+            } else {
+                None
+            }
+        });
 
-        let is_json_like = representation.is_json_like();
+        let module_max_relation_path_depth = module_annotation
+            .and_then(|a| {
+                if let AstAnnotationParams::Map(map, _) = a {
+                    map.get("maxRelationPathDepth")
+                        .and_then(|depth| parse_max_relation_path_depth(depth, errors))
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(DEFAULT_MAX_RELATION_PATH_DEPTH);
 
-        if is_json_like && access_annotation.is_some() {
-            errors.push(Diagnostic {
-                level: Level::Error,
-                message: format!(
-                    "Cannot use @access for type {}. Json and computed types behave like a primitive (and thus have always-allowed access)",
-                    ct.name
-                ),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: ct.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            });
+        for typ in module.types.iter() {
+            if let Some(Type::Composite(ct)) = typechecked_system.types.get_by_key(&typ.name) {
+                relation_path_depth_limits.insert(ct.name.clone(), module_max_relation_path_depth);
+                resolve_composite_type(
+                    ct,
+                    &module_schema_name,
+                    module_managed,
+                    &module.base_exofile,
+                    &naming_strategies,
+                    typechecked_system,
+                    &mut resolved_postgres_types,
+                    errors,
+                )
+                .map_err(|e| ModelBuildingError::Diagnosis(vec![e]))?;
+            }
         }
 
-        let visibility_annotation = ct.annotations.annotations.get("visibility");
-        let root_visible = parse_visibility_annotation(ct, visibility_annotation, errors);
+        for typ in module.enums.iter() {
+            if let Some(Type::Enum(et)) = typechecked_system.types.get_by_key(&typ.name) {
+                resolved_postgres_types.add(
+                    &et.name,
+                    ResolvedType::Enum(ResolvedEnumType {
+                        name: et.name.clone(),
+                        fields: et.fields.iter().map(|f| f.name.clone()).collect(),
+                        enum_name: SchemaObjectName::new(
+                            module_naming_strategies
+                                .get(&et.name)
+                                .map(|naming| naming.case.apply(&et.name))
+                                .unwrap_or_else(|| NamingCase::SnakeCase.apply(&et.name)),
+                            module_schema_name.as_deref(),
+                        ),
+                        variant_mappings: et
+                            .annotations
+                            .annotations
+                            .get("mapsTo")
+                            .map(|annotation| parse_maps_to_annotation(et, annotation, errors))
+                            .unwrap_or_default(),
+                        doc_comments: et.doc_comments.clone(),
+                        span: et.span,
+                    }),
+                );
+            }
+        }
+    }
 
-        let ownership_annotation = ct.annotations.annotations.get("ownership");
+    apply_join_table_shortcuts(&mut resolved_postgres_types, errors);
+    apply_many_to_many_fields(
+        &mut resolved_postgres_types,
+        &module_naming_strategies,
+        errors,
+    );
+    add_change_types(
+        &mut resolved_postgres_types,
+        &module_naming_strategies,
+        errors,
+    );
+    add_field_change_types(
+        &mut resolved_postgres_types,
+        &module_naming_strategies,
+        errors,
+    );
+    validate_relation_paths(
+        &resolved_postgres_types,
+        &relation_path_depth_limits,
+        typechecked_system,
+        errors,
+    );
+    validate_identifiers(&resolved_postgres_types, errors);
 
-        let ownership_config = ownership_annotation
-            .and_then(|annotation| parse_ownership_annotation(ct, annotation, errors));
+    Ok(resolved_postgres_types)
+}
 
-        let join_table_annotation = ct.annotations.annotations.get("joinTable");
-        let join_table_config = join_table_annotation
-            .and_then(|annotation| parse_join_table_annotation(ct, annotation, errors));
+/// Reject identifiers Postgres can't use as specified.
+///
+/// Derived names are already shortened to fit (see `fit_identifier`), so an identifier that is too
+/// long must have been specified explicitly (through `@table`, `@column`, or `@index`). Columns
+/// kept in sync through `@update` are referred to unquoted in the trigger that updates them, so
+/// they must also be plain identifiers (lowercase and not a reserved word). Other identifiers are
+/// always quoted, so may be reserved words.
+fn validate_identifiers(resolved_types: &MappedArena<ResolvedType>, errors: &mut Vec<Diagnostic>) {
+    let mut error = |message: String, span: Span| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        })
+    };
 
-        let mut access = if is_json_like {
-            // As if the user has annotated with `access(true)`
-            ResolvedAccess {
-                default: Some(AstExpr::BooleanLiteral(true, default_span())),
-                ..Default::default()
-            }
-        } else {
-            build_access(access_annotation)
-        };
-        let name = ct.name.clone();
-        let plural_name = plural_annotation_value.unwrap_or_else(|| ct.name.to_plural()); // fallback to automatically pluralizing name
+    let too_long = |kind: &str, name: &str| {
+        format!(
+            "The {kind} name '{name}' is {} bytes long, but Postgres allows at most {MAX_IDENTIFIER_LENGTH}",
+            name.len()
+        )
+    };
 
-        let (resolved_fields, ownership_field_found) = resolve_composite_type_fields(
-            ct,
-            is_json_like,
-            table_managed,
-            module_base_path,
-            typechecked_system,
-            ownership_config.as_ref(),
-            errors,
-        );
+    for (_, typ) in resolved_types.iter() {
+        match typ {
+            ResolvedType::Composite(composite) => {
+                if composite.representation.is_json_like() {
+                    continue;
+                }
 
-        if let Some(annotation) = ownership_annotation
-            && let Some(config) = ownership_config.as_ref()
-            && !ownership_field_found
-        {
-            push_type_error(
-                ct,
-                annotation.span,
-                format!(
-                    "Field '{}' specified in @ownership was not found on type '{}'",
-                    config.field, ct.name
-                ),
-                errors,
-            );
-        }
+                if composite.table_name.name.len() > MAX_IDENTIFIER_LENGTH {
+                    error(
+                        too_long("table", &composite.table_name.name),
+                        composite.span,
+                    );
+                }
 
-        if let Some(config) = ownership_config.as_ref()
-            && ownership_field_found
-        {
-            let guard_expr = build_ownership_guard_expr(config);
+                // Columns in another table (such as the `concerts` field of `Venue`) are validated
+                // along with the field in that table
+                for field in composite.fields.iter().filter(|field| field.self_column) {
+                    for column_name in field.column_names.iter() {
+                        if column_name.len() > MAX_IDENTIFIER_LENGTH {
+                            error(too_long("column", column_name), field.span);
+                        }
 
-            if config.enforce_create {
-                access.creation = combine_with_guard(access.creation.take(), guard_expr.clone());
-            }
+                        if field.update_sync && !is_plain_identifier(column_name) {
+                            error(
+                                format!(
+                                    "The column '{column_name}' of a field with @update must be a lowercase identifier that isn't a Postgres reserved word"
+                                ),
+                                field.span,
+                            );
+                        }
+                    }
 
-            if config.enforce_update {
-                access.update = combine_with_guard(access.update.take(), guard_expr.clone());
+                    for index_name in field.indices.iter() {
+                        if index_name.len() > MAX_IDENTIFIER_LENGTH {
+                            error(too_long("index", index_name), field.span);
+                        }
+                    }
+                }
             }
-
-            if config.enforce_delete {
-                access.delete = combine_with_guard(access.delete.take(), guard_expr);
+            ResolvedType::Enum(enum_type) => {
+                if enum_type.enum_name.name.len() > MAX_IDENTIFIER_LENGTH {
+                    error(too_long("enum", &enum_type.enum_name.name), enum_type.span);
+                }
             }
+            ResolvedType::Primitive(_) => {}
         }
-
-        resolved_postgres_types.add(
-            &ct.name,
-            ResolvedType::Composite(ResolvedCompositeType {
-                name,
-                plural_name: plural_name.clone(),
-                representation,
-                root_visible,
-                fields: resolved_fields,
-                table_name: SchemaObjectName {
-                    name: table_name,
-                    schema: schema_name,
-                },
-                access: access.clone(),
-                doc_comments: ct.doc_comments.clone(),
-                join_table: join_table_config,
-                span: ct.span,
-            }),
-        );
     }
-
-    Ok(())
 }
 
-fn resolve_composite_type_fields(
-    ct: &AstModel<Typed>,
-    is_json_like: bool,
-    table_managed: bool,
-    module_base_path: &Path,
-    typechecked_system: &TypecheckedSystem,
-    ownership_config: Option<&OwnershipConfig>,
+/// Parse the naming strategy parameters of `@postgres` (for a module) or `@table` (for a type). The
+/// `keys` are the names of the parameters for the case, pluralization, and table prefix. Any
+/// parameter not specified is taken from `base`.
+fn parse_naming_strategy(
+    params: &AstAnnotationParams<Typed>,
+    [case_key, pluralize_key, prefix_key]: [&str; 3],
+    base: &NamingStrategy,
     errors: &mut Vec<Diagnostic>,
-) -> (Vec<ResolvedField>, bool) {
-    let fragment_fields = compute_fragment_fields(ct, errors, typechecked_system);
+) -> NamingStrategy {
+    let mut strategy = base.clone();
 
-    let mut resolved_fields = Vec::new();
-    let mut ownership_field_found = false;
+    let AstAnnotationParams::Map(map, _) = params else {
+        return strategy;
+    };
 
-    for field in ct.fields.iter().chain(fragment_fields.into_iter()) {
-        let update_sync = field.annotations.contains("update");
-        let mut readonly = field.annotations.contains("readonly");
+    let mut error = |message: String, expr: &AstExpr<Typed>| {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message,
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: expr.span(),
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        })
+    };
 
-        let access_annotation = field.annotations.get("access");
+    if let Some(expr) = map.get(case_key) {
+        match expr {
+            AstExpr::StringLiteral(value, _) => match NamingCase::parse(value) {
+                Some(case) => strategy.case = case,
+                None => error(
+                    format!("{case_key} must be either \"snake_case\" or \"preserve\""),
+                    expr,
+                ),
+            },
+            _ => error(format!("{case_key} must be a string"), expr),
+        }
+    }
 
-        if is_json_like && access_annotation.is_some() {
-            errors.push(Diagnostic {
-            level: Level::Error,
-            message: format!(
-                "Cannot use @access for field '{}' in a type with a '@json' or '@computed' annotation",
-                field.name
-            ),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: field.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            });
+    if let Some(expr) = map.get(pluralize_key) {
+        match expr {
+            AstExpr::BooleanLiteral(value, _) => strategy.pluralize_tables = *value,
+            _ => error(format!("{pluralize_key} must be a boolean"), expr),
         }
+    }
 
-        // For fields, by default, we assume the `access(true)` annotation
-        let access = match access_annotation {
-            Some(_) => build_access(access_annotation),
-            None => ResolvedAccess {
-                default: AstExpr::BooleanLiteral(true, default_span()).into(),
-                ..Default::default()
-            },
-        };
+    if let Some(expr) = map.get(prefix_key) {
+        match expr {
+            AstExpr::StringLiteral(value, _) => strategy.table_prefix = Some(value.clone()),
+            _ => error(format!("{prefix_key} must be a string"), expr),
+        }
+    }
 
-        let relation_path = field.annotations.get("relationPath").and_then(|params| {
-            match params {
-                AstAnnotationParams::Single(expr, _) => match expr {
-                    AstExpr::StringLiteral(path, _) => {
-                        let segments: Vec<String> = path
-                            .split('.')
-                            .map(|segment| segment.trim().to_string())
-                            .filter(|segment| !segment.is_empty())
-                            .collect();
+    strategy
+}
 
-                        if segments.is_empty() {
-                            errors.push(Diagnostic {
-                                level: Level::Error,
-                                message: format!(
-                                    "@relationPath for field '{}' must contain at least one segment",
-                                    field.name
-                                ),
-                                code: Some("C000".to_string()),
-                                spans: vec![SpanLabel {
-                                    span: field.span,
-                                    style: SpanStyle::Primary,
-                                    label: None,
-                                }],
-                            });
-                            None
-                        } else {
-                            Some(segments)
-                        }
-                    }
-                    _ => {
-                        errors.push(Diagnostic {
-                            level: Level::Error,
-                            message: format!(
-                                "@relationPath for field '{}' must be a string literal",
-                                field.name
-                            ),
-                            code: Some("C000".to_string()),
-                            spans: vec![SpanLabel {
-                                span: field.span,
-                                style: SpanStyle::Primary,
-                                label: None,
-                            }],
-                        });
-                        None
-                    }
-                },
-                _ => {
-                    errors.push(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "@relationPath for field '{}' must use the syntax @relationPath(\"segment1.segment2\")",
-                            field.name
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: field.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
-                    });
-                    None
-                }
-            }
-        });
+/// The maximum number of steps a `@relationPath` may expand to (after expanding any relation
+/// paths used as its segments), unless overridden with `@postgres(maxRelationPathDepth=...)`
+const DEFAULT_MAX_RELATION_PATH_DEPTH: usize = 8;
 
-        let typ = resolve_field_type(
-            &field.typ.to_typ(&typechecked_system.types),
-            &typechecked_system.types,
-        );
+fn parse_max_relation_path_depth(
+    depth: &AstExpr<Typed>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<usize> {
+    let parsed = match depth {
+        AstExpr::NumberLiteral(value, _) => value.parse::<usize>().ok().filter(|depth| *depth > 0),
+        _ => None,
+    };
 
-        let mut field_default = field
-            .default_value
-            .as_ref()
-            .map(|v| resolve_field_default_type(v, &typ, errors));
+    if parsed.is_none() {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message: "maxRelationPathDepth must be a positive integer".to_string(),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: depth.span(),
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    }
 
-        if relation_path.is_some() && field.annotations.get("computed").is_some() {
-            errors.push(Diagnostic {
-                level: Level::Error,
-                message: format!(
-                    "Field '{}' cannot use both @computed and @relationPath",
-                    field.name
-                ),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: field.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            });
+    parsed
+}
+
+/// Reject relation paths that form a cycle (through relation paths used as segments) or that
+/// expand to more steps than the module allows.
+fn validate_relation_paths(
+    resolved_types: &MappedArena<ResolvedType>,
+    depth_limits: &HashMap<String, usize>,
+    typechecked_system: &TypecheckedSystem,
+    errors: &mut Vec<Diagnostic>,
+) {
+    for (_, typ) in resolved_types.iter() {
+        let ResolvedType::Composite(composite) = typ else {
             continue;
-        }
+        };
 
-        if let Some(computed_params) = field.annotations.get("computed") {
-            let resolved_computed =
-                match parse_computed_field(module_base_path, field, computed_params, errors) {
-                    Some(c) => c,
-                    None => continue,
-                };
+        for field in composite.fields.iter() {
+            let Some(path) = &field.relation_path else {
+                continue;
+            };
 
-            validate_computed_field_annotations(field, update_sync, errors);
+            let segment_spans =
+                relation_path_segment_spans(&composite.name, field, path, typechecked_system);
+            let qualified_name = format!("{}.{}", composite.name, field.name);
 
-            resolved_fields.push(ResolvedField {
-                name: field.name.clone(),
-                typ,
-                column_names: vec![],
-                self_column: false,
-                is_pk: false,
-                access,
-                type_hint: None,
-                unique_constraints: vec![],
-                indices: vec![],
-                cardinality: None,
-                default_value: None,
-                update_sync: false,
-                readonly: true,
-                relation_path: None,
-                doc_comments: field.doc_comments.clone(),
-                computed: Some(resolved_computed),
-                join_table_shortcut: None,
-                join_table_intermediate: None,
-                span: field.span,
-            });
-            continue;
-        }
+            let mut visiting = vec![qualified_name.clone()];
+            let mut cycle_spans = vec![];
+            let mut segment_depths = vec![];
+            let mut current_type_name = composite.name.clone();
 
-        if relation_path.is_some() {
-            readonly = true;
-        }
+            for (segment, segment_span) in path.iter().zip(segment_spans.iter()) {
+                // Unknown segments are reported when building the relation
+                let Some(segment_field) =
+                    resolved_composite_field(resolved_types, &current_type_name, segment)
+                else {
+                    break;
+                };
 
-        let column_info = if relation_path.is_some() {
-            Ok(ColumnInfo {
-                names: vec![],
-                self_column: false,
-                unique_constraints: vec![],
-                indices: vec![],
-                cardinality: None,
-            })
-        } else {
-            compute_column_info(ct, field, &typechecked_system.types, table_managed)
-        };
+                match relation_path_segment_depth(
+                    &current_type_name,
+                    segment_field,
+                    resolved_types,
+                    &mut visiting,
+                ) {
+                    Ok(depth) => segment_depths.push((depth, *segment_span)),
+                    Err(cycle) => cycle_spans.push(SpanLabel {
+                        span: *segment_span,
+                        style: SpanStyle::Primary,
+                        label: Some(format!("cycle: {}", cycle.join(" -> "))),
+                    }),
+                }
 
-        let ColumnInfo {
-            names: column_names,
-            self_column,
-            unique_constraints,
-            indices,
-            cardinality,
-        } = match column_info {
-            Ok(info) => info,
-            Err(e) => {
-                errors.push(e);
-                continue;
+                current_type_name = segment_field.typ.innermost().type_name.clone();
             }
-        };
-
-        if let Some(config) = ownership_config
-            && let Some(base_field) = config.field_path.first()
-            && base_field == &field.name
-        {
-            ownership_field_found = true;
 
-            if config.auto_assign && config.field_path.len() == 1 {
-                readonly = true;
-                if field_default.is_none() {
-                    let context_expr =
-                        AstExpr::FieldSelection(field_selection_from_str(&config.context_path));
-                    field_default = Some(ResolvedFieldDefault::Value(Box::new(context_expr)));
-                }
+            if !cycle_spans.is_empty() {
+                errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!(
+                        "@relationPath for field '{qualified_name}' forms a cycle through other relation paths"
+                    ),
+                    code: Some("C000".to_string()),
+                    spans: cycle_spans,
+                });
+                continue;
             }
 
-            if config.field_path.len() > 1 {
+            let max_depth = depth_limits
+                .get(&composite.name)
+                .copied()
+                .unwrap_or(DEFAULT_MAX_RELATION_PATH_DEPTH);
+            let depth: usize = segment_depths.iter().map(|(depth, _)| depth).sum();
+
+            if depth > max_depth {
                 errors.push(Diagnostic {
                     level: Level::Error,
                     message: format!(
-                        "Ownership context path '{}' must be a single field name",
-                        config.context_path
+                        "@relationPath for field '{qualified_name}' expands to {depth} steps, exceeding the maximum of {max_depth} (set with @postgres(maxRelationPathDepth=...))"
                     ),
                     code: Some("C000".to_string()),
-                    spans: vec![SpanLabel {
-                        span: field.span,
-                        style: SpanStyle::Primary,
-                        label: Some("Ownership field must refer to a single field".to_string()),
-                    }],
+                    spans: segment_depths
+                        .into_iter()
+                        .map(|(depth, span)| SpanLabel {
+                            span,
+                            style: SpanStyle::Primary,
+                            label: Some(if depth == 1 {
+                                "1 step".to_string()
+                            } else {
+                                format!("{depth} steps")
+                            }),
+                        })
+                        .collect(),
                 });
             }
         }
+    }
+}
 
-        resolved_fields.push(ResolvedField {
-            name: field.name.clone(),
-            typ,
-            column_names,
-            self_column,
-            is_pk: field.annotations.contains("pk"),
-            access,
-            type_hint: build_type_hint(field, &typechecked_system.types, errors),
-            unique_constraints,
-            indices,
-            cardinality,
-            default_value: field_default,
-            update_sync,
-            readonly,
-            relation_path,
-            doc_comments: field.doc_comments.clone(),
-            computed: None,
-            join_table_shortcut: None,
-            join_table_intermediate: None,
-            span: field.span,
-        });
+/// The number of steps a segment expands to: one for a plain relation, or the depth of its
+/// relation path (recursively). Fails with the (qualified) names of the fields forming a cycle.
+fn relation_path_segment_depth(
+    type_name: &str,
+    field: &ResolvedField,
+    resolved_types: &MappedArena<ResolvedType>,
+    visiting: &mut Vec<String>,
+) -> Result<usize, Vec<String>> {
+    let Some(path) = &field.relation_path else {
+        return Ok(1);
+    };
+
+    let qualified_name = format!("{type_name}.{}", field.name);
+    if let Some(index) = visiting.iter().position(|name| name == &qualified_name) {
+        let mut cycle = visiting[index..].to_vec();
+        cycle.push(qualified_name);
+        return Err(cycle);
     }
 
-    (resolved_fields, ownership_field_found)
+    visiting.push(qualified_name);
+    let depth = relation_path_depth(type_name, path, resolved_types, visiting);
+    visiting.pop();
+
+    depth
 }
 
-fn push_field_error(
-    field: &AstField<Typed>,
-    message: impl Into<String>,
-    errors: &mut Vec<Diagnostic>,
-) {
-    errors.push(Diagnostic {
-        level: Level::Error,
-        message: message.into(),
-        code: Some("C000".to_string()),
-        spans: vec![SpanLabel {
-            span: field.span,
-            style: SpanStyle::Primary,
-            label: None,
-        }],
-    });
+fn relation_path_depth(
+    type_name: &str,
+    path: &[String],
+    resolved_types: &MappedArena<ResolvedType>,
+    visiting: &mut Vec<String>,
+) -> Result<usize, Vec<String>> {
+    let mut depth = 0;
+    let mut current_type_name = type_name.to_string();
+
+    for segment in path {
+        // Unknown segments are reported when building the relation
+        let Some(segment_field) =
+            resolved_composite_field(resolved_types, &current_type_name, segment)
+        else {
+            break;
+        };
+        depth += relation_path_segment_depth(
+            &current_type_name,
+            segment_field,
+            resolved_types,
+            visiting,
+        )?;
+        current_type_name = segment_field.typ.innermost().type_name.clone();
+    }
+
+    Ok(depth)
 }
 
-fn push_type_error(
-    _ct: &AstModel<Typed>,
-    span: Span,
-    message: impl Into<String>,
-    errors: &mut Vec<Diagnostic>,
-) {
-    errors.push(Diagnostic {
-        level: Level::Error,
-        message: message.into(),
-        code: Some("C000".to_string()),
-        spans: vec![SpanLabel {
-            span,
-            style: SpanStyle::Primary,
-            label: None,
-        }],
-    });
+fn resolved_composite_field<'a>(
+    resolved_types: &'a MappedArena<ResolvedType>,
+    type_name: &str,
+    field_name: &str,
+) -> Option<&'a ResolvedField> {
+    match resolved_types.get_by_key(type_name) {
+        Some(ResolvedType::Composite(composite)) => composite
+            .fields
+            .iter()
+            .find(|field| field.name == field_name),
+        _ => None,
+    }
 }
 
-fn extract_string_literal(
-    expr: &AstExpr<Typed>,
-    field: &AstField<Typed>,
-    label: &str,
-    errors: &mut Vec<Diagnostic>,
-) -> Option<String> {
-    match expr {
-        AstExpr::StringLiteral(value, _) => Some(value.clone()),
-        _ => {
-            push_field_error(
-                field,
-                format!(
-                    "Computed field '{}' parameter '{}' must be a string literal",
-                    field.name, label
-                ),
-                errors,
-            );
-            None
+/// The span of each segment of a relation path, pointing into the `@relationPath` annotation if
+/// there is one (falling back to the field's span, for example, for `@manyToMany` fields).
+fn relation_path_segment_spans(
+    type_name: &str,
+    field: &ResolvedField,
+    path: &[String],
+    typechecked_system: &TypecheckedSystem,
+) -> Vec<Span> {
+    let annotation_path = match typechecked_system.types.get_by_key(type_name) {
+        Some(Type::Composite(ct)) => ct
+            .fields
+            .iter()
+            .find(|ast_field| ast_field.name == field.name)
+            .and_then(|ast_field| ast_field.annotations.get("relationPath")),
+        _ => None,
+    };
+
+    match annotation_path {
+        Some(AstAnnotationParams::Single(AstExpr::StringLiteral(value, span), _)) => {
+            let mut spans = vec![];
+            let mut offset = 0;
+            for segment in value.split('.') {
+                let trimmed = segment.trim_start();
+                let start = offset + (segment.len() - trimmed.len());
+                let end = start + trimmed.trim_end().len();
+                if end > start {
+                    spans.push(span.subspan(start as u64, end as u64));
+                }
+                offset += segment.len() + 1;
+            }
+            spans
         }
+        _ => vec![field.span; path.len()],
     }
 }
 
-fn parse_computed_field(
-    module_base_path: &Path,
-    field: &AstField<Typed>,
-    params: &AstAnnotationParams<Typed>,
+fn apply_join_table_shortcuts(
+    resolved_types: &mut MappedArena<ResolvedType>,
     errors: &mut Vec<Diagnostic>,
-) -> Option<ResolvedComputedField> {
-    let map = match params {
-        AstAnnotationParams::Map(map, _) => map,
-        _ => {
-            push_field_error(
-                field,
-                format!(
-                    "Computed field '{}' must use map-style parameters (e.g. source = \"...\")",
-                    field.name
-                ),
-                errors,
-            );
-            return None;
-        }
-    };
-
-    let source_expr = match map.get("source") {
-        Some(expr) => expr,
-        None => {
-            push_field_error(
-                field,
-                format!(
-                    "Computed field '{}' must specify a 'source' parameter",
-                    field.name
-                ),
-                errors,
-            );
-            return None;
+) {
+    let mut join_configs: Vec<(String, ResolvedJoinTableConfig)> = Vec::new();
+    for (_, typ) in resolved_types.iter() {
+        if let ResolvedType::Composite(composite) = typ
+            && let Some(config) = &composite.join_table
+        {
+            join_configs.push((composite.name.clone(), config.clone()));
         }
-    };
-
-    let source = extract_string_literal(source_expr, field, "source", errors)?;
-
-    let mut source_path = module_base_path.to_path_buf();
-    source_path.pop();
-    source_path.push(&source);
+    }
 
-    let canonical = match fs::canonicalize(&source_path) {
-        Ok(path) => path,
-        Err(_) => {
-            push_field_error(
-                field,
-                format!(
-                    "Unable to resolve computed field '{}' source file: {}",
-                    field.name,
-                    source_path.to_string_lossy()
+    for (join_type_name, config) in join_configs {
+        if config.shortcuts.len() != 2 {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "@joinTable on '{}' currently supports exactly two shortcuts",
+                    join_type_name
                 ),
-                errors,
-            );
-            return None;
+                code: Some("C000".to_string()),
+                spans: vec![],
+            });
+            continue;
         }
-    };
-
-    let function_name = match map.get("export") {
-        Some(expr) => extract_string_literal(expr, field, "export", errors)?,
-        None => field.name.clone(),
-    };
 
-    let subsystem = match map.get("subsystem") {
-        Some(expr) => Some(extract_string_literal(expr, field, "subsystem", errors)?),
-        None => Some("deno".to_string()),
-    };
+        let shortcuts = config.shortcuts;
 
-    let dependencies = match map.get("select") {
-        Some(AstExpr::StringList(list, _)) => list.clone(),
-        Some(AstExpr::StringLiteral(value, _)) => value
-            .split(',')
-            .map(|item| item.trim().to_string())
-            .filter(|item| !item.is_empty())
-            .collect(),
-        Some(other) => {
-            push_field_error(
-                field,
-                format!(
-                    "Computed field '{}' select parameter must be a list of strings, found {:?}",
-                    field.name, other
-                ),
-                errors,
-            );
-            return None;
-        }
-        None => vec![],
-    };
+        for (index, shortcut) in shortcuts.iter().enumerate() {
+            let target_index = (index + 1) % shortcuts.len();
+            let target = &shortcuts[target_index];
 
-    Some(ResolvedComputedField {
-        source_path: canonical.to_string_lossy().to_string(),
-        function_name,
-        subsystem,
-        dependencies,
-    })
-}
+            if shortcut.source_entity == target.source_entity {
+                errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!(
+                        "@joinTable shortcut '{}' on '{}' must reference a different sourceEntity than its counterpart",
+                        shortcut.expose_as, join_type_name
+                    ),
+                    code: Some("C000".to_string()),
+                    spans: vec![],
+                });
+                continue;
+            }
 
-fn validate_computed_field_annotations(
-    field: &AstField<Typed>,
-    update_sync: bool,
-    errors: &mut Vec<Diagnostic>,
-) {
-    let disallowed = [
-        ("pk", "@pk"),
-        ("column", "@column"),
-        ("relation", "@relation"),
-        ("manyToOne", "@manyToOne"),
-        ("oneToOne", "@oneToOne"),
-        ("dbtype", "@dbtype"),
-        ("index", "@index"),
-        ("unique", "@unique"),
-    ];
+            let direct_field_name = shortcut.expose_as.clone();
 
-    for (annotation, label) in disallowed {
-        if field.annotations.contains(annotation) {
-            push_field_error(
-                field,
-                format!(
-                    "Computed field '{}' cannot use the {} annotation",
-                    field.name, label
-                ),
-                errors,
-            );
-        }
-    }
+            let default_join_name = format!("_{}_join", shortcut.expose_as);
+            let join_field_name = shortcut.join_reference.clone().unwrap_or(default_join_name);
 
-    if field.default_value.is_some() {
-        push_field_error(
-            field,
-            format!(
-                "Computed field '{}' cannot specify a default value",
-                field.name
-            ),
-            errors,
-        );
-    }
+            let target_entity_name = target.source_entity.clone();
 
-    if update_sync {
-        push_field_error(
-            field,
-            format!(
-                "Computed field '{}' cannot use the @update annotation",
-                field.name
-            ),
-            errors,
-        );
-    }
-}
+            let target_relation_name = {
+                let Some(ResolvedType::Composite(join_entity)) =
+                    resolved_types.get_by_key(&join_type_name)
+                else {
+                    errors.push(Diagnostic {
+                        level: Level::Error,
+                        message: format!(
+                            "@joinTable on '{}' references unknown join table type",
+                            join_type_name
+                        ),
+                        code: Some("C000".to_string()),
+                        spans: vec![],
+                    });
+                    continue;
+                };
 
-fn resolve_field_default_type(
-    default_value: &AstFieldDefault<Typed>,
-    field_type: &FieldType<ResolvedFieldType>,
-    errors: &mut Vec<Diagnostic>,
-) -> ResolvedFieldDefault {
-    let field_underlying_type = field_type.name();
+                join_entity
+                    .fields
+                    .iter()
+                    .find(|field| {
+                        field
+                            .column_names
+                            .iter()
+                            .any(|column| column == &target.join_reference_key)
+                            && match &field.typ {
+                                FieldType::Plain(inner) => inner.type_name == target_entity_name,
+                                FieldType::Optional(inner) => matches!(
+                                    &**inner,
+                                    FieldType::Plain(inner_plain)
+                                        if inner_plain.type_name == target_entity_name
+                                ),
+                                _ => false,
+                            }
+                    })
+                    .map(|field| field.name.clone())
+            };
 
-    match &default_value.kind {
-        AstFieldDefaultKind::Value(expr) => ResolvedFieldDefault::Value(Box::new(expr.to_owned())),
-        AstFieldDefaultKind::Function(fn_name, args) => match fn_name.as_str() {
-            DEFAULT_FN_AUTO_INCREMENT => {
-                if field_underlying_type != primitive_type::IntType::NAME {
+            let target_relation_name = match target_relation_name {
+                Some(name) => name,
+                None => {
                     errors.push(Diagnostic {
                         level: Level::Error,
-                        message: format!("{DEFAULT_FN_AUTO_INCREMENT}() can only be used on Ints"),
+                        message: format!(
+                            "@joinTable shortcut '{}' on '{}' could not find a relation to '{}' using column '{}'",
+                            shortcut.expose_as, join_type_name, target_entity_name, target.join_reference_key
+                        ),
                         code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: default_value.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
+                        spans: vec![],
                     });
+                    continue;
                 }
+            };
 
-                match &args[..] {
-                    [] => ResolvedFieldDefault::AutoIncrement(None),
-                    [AstExpr::StringLiteral(sequence_name, _)] => {
-                        // Split the sequence name by '.' and use the last part as the sequence name
-                        match sequence_name.split('.').collect::<Vec<&str>>()[..] {
-                            [schema, name] => ResolvedFieldDefault::AutoIncrement(Some(
-                                SchemaObjectName::new(name.to_string(), Some(schema)),
-                            )),
-                            [name] => ResolvedFieldDefault::AutoIncrement(Some(
-                                SchemaObjectName::new(name.to_string(), None),
-                            )),
-                            _ => {
-                                errors.push(Diagnostic {
-                                    level: Level::Error,
-                                    message: format!(
-                                        "{}() can have arguments of the form 'schema.name' or 'name'",
-                                        DEFAULT_FN_AUTO_INCREMENT
-                                    ),
-                                    code: Some("C000".to_string()),
-                                    spans: vec![SpanLabel {
-                                        span: default_value.span,
-                                        style: SpanStyle::Primary,
-                                        label: None,
-                                    }],
-                                });
-                                ResolvedFieldDefault::AutoIncrement(None)
-                            }
-                        }
-                    }
-                    _ => {
-                        errors.push(Diagnostic {
-                            level: Level::Error,
-                            message: format!(
-                                "{}() can only have 0 (for default serial) or 1 argument (for a custom sequence name like 'schema.name')",
-                                DEFAULT_FN_AUTO_INCREMENT
-                            ),
-                            code: Some("C000".to_string()),
-                            spans: vec![SpanLabel {
-                                span: default_value.span,
-                                style: SpanStyle::Primary,
-                                label: None,
-                            }],
-                        });
-                        ResolvedFieldDefault::AutoIncrement(None)
-                    }
-                }
-            }
-            DEFAULT_FN_CURRENT_TIME => {
-                if field_underlying_type != primitive_type::InstantType::NAME
-                    && field_underlying_type != primitive_type::LocalDateType::NAME
-                    && field_underlying_type != primitive_type::LocalTimeType::NAME
-                    && field_underlying_type != primitive_type::LocalDateTimeType::NAME
-                {
-                    errors.push(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "{DEFAULT_FN_CURRENT_TIME}() can only be used for time-related types"
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: default_value.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
-                    });
-                }
-
-                ResolvedFieldDefault::PostgresFunction("now()".to_string())
-            }
-            DEFAULT_FN_GENERATE_UUID => {
-                if field_underlying_type != primitive_type::UuidType::NAME {
-                    errors.push(Diagnostic {
-                        level: Level::Error,
-                        message: format!("{DEFAULT_FN_GENERATE_UUID}() can only be used on Uuids"),
-                        code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: default_value.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
-                    });
-                }
+            let Some(ResolvedType::Composite(source_type)) =
+                resolved_types.get_by_key_mut(&shortcut.source_entity)
+            else {
+                errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!(
+                        "@joinTable shortcut '{}' references unknown sourceEntity '{}'",
+                        shortcut.expose_as, shortcut.source_entity
+                    ),
+                    code: Some("C000".to_string()),
+                    spans: vec![],
+                });
+                continue;
+            };
 
-                ResolvedFieldDefault::PostgresFunction("gen_random_uuid()".to_string())
+            if source_type
+                .fields
+                .iter()
+                .any(|field| field.name == direct_field_name)
+            {
+                errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!(
+                        "@joinTable shortcut '{}' on '{}' conflicts with an existing field on '{}'",
+                        direct_field_name, join_type_name, shortcut.source_entity
+                    ),
+                    code: Some("C000".to_string()),
+                    spans: vec![],
+                });
+                continue;
             }
-            DEFAULT_FN_UUID_GENERATE_V4 => {
-                if field_underlying_type != primitive_type::UuidType::NAME {
-                    errors.push(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "{DEFAULT_FN_UUID_GENERATE_V4}() can only be used on Uuids"
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: default_value.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
-                    });
-                }
 
-                ResolvedFieldDefault::PostgresFunction("uuid_generate_v4()".to_string())
-            }
-            _ => {
+            if source_type
+                .fields
+                .iter()
+                .any(|field| field.name == join_field_name)
+            {
                 errors.push(Diagnostic {
                     level: Level::Error,
-                    message: format!("Unknown function specified for default value: {fn_name}"),
+                    message: format!(
+                        "@joinTable shortcut '{}' would create a join helper field '{}' on '{}' but that field already exists",
+                        shortcut.expose_as, join_field_name, shortcut.source_entity
+                    ),
                     code: Some("C000".to_string()),
-                    spans: vec![SpanLabel {
-                        span: default_value.span,
-                        style: SpanStyle::Primary,
-                        label: Some("unknown function".to_string()),
-                    }],
+                    spans: vec![],
                 });
-                // Proceed with a reasonable value. Since we already reported an error, this is not going to be used.
-                ResolvedFieldDefault::PostgresFunction(fn_name.to_string())
+                continue;
             }
-        },
-    }
-}
 
-fn build_type_hint(
-    field: &AstField<Typed>,
-    types: &MappedArena<Type>,
-    errors: &mut Vec<Diagnostic>,
-) -> Option<SerializableTypeHint> {
-    let type_name = field.typ.get_underlying_typename(types)?;
+            let base_field_type = FieldType::Plain(ResolvedFieldType {
+                type_name: target_entity_name.clone(),
+                is_primitive: false,
+            });
 
-    let explicit_dbtype_hint = field
-        .annotations
-        .get("dbtype")
-        .map(|p| p.as_single().as_string())
-        .map(|s| {
-            SerializableTypeHint(Box::new(ExplicitTypeHint {
-                dbtype: s.to_uppercase(),
-            }))
-        });
+            let field_type = match shortcut.cardinality {
+                JoinTableShortcutCardinality::Many => {
+                    FieldType::List(Box::new(base_field_type.clone()))
+                }
+                JoinTableShortcutCardinality::Optional => {
+                    FieldType::Optional(Box::new(base_field_type.clone()))
+                }
+            };
 
-    let primitive_hint = {
-        let type_hint_provider = PRIMITIVE_TYPE_PROVIDER_REGISTRY.get(type_name.as_str())?;
-        validate_hint_annotations(field, *type_hint_provider, errors);
-        type_hint_provider.compute_type_hint(field, errors)
-    };
+            let is_many = matches!(shortcut.cardinality, JoinTableShortcutCardinality::Many);
 
-    // Validate that we don't have conflicting hints
-    if explicit_dbtype_hint.is_some() && primitive_hint.is_some() {
-        errors.push(Diagnostic {
-            level: Level::Error,
-            message: format!(
-                "Cannot specify both @dbtype and a primitive specific hint for {}",
-                field.name
-            ),
-            code: Some("C000".to_string()),
-            spans: vec![SpanLabel {
-                span: field.span,
-                style: SpanStyle::Primary,
-                label: None,
-            }],
-        });
-    }
+            let allow_all_access = ResolvedAccess {
+                default: Some(AstExpr::BooleanLiteral(true, default_span())),
+                ..Default::default()
+            };
 
-    // Return the appropriate hint (explicit takes precedence)
-    explicit_dbtype_hint.or(primitive_hint)
-}
-struct ColumnInfo {
-    names: Vec<String>,
-    self_column: bool,
-    unique_constraints: Vec<String>,
-    indices: Vec<String>,
-    cardinality: Option<Cardinality>,
-}
+            let shortcut_field = ResolvedField {
+                name: direct_field_name,
+                typ: field_type,
+                column_names: vec![],
+                self_column: false,
+                is_pk: false,
+                access: allow_all_access.clone(),
+                type_hint: None,
+                unique_constraints: vec![],
+                indices: vec![],
+                cardinality: None,
+                default_value: None,
+                read_default: None,
+                mask: None,
+                update_sync: false,
+                readonly: true,
+                relation_path: None,
+                many_to_many: None,
+                renamed_from: None,
+                on_delete: ReferentialAction::NoAction,
+                on_update: ReferentialAction::NoAction,
+                storage: ColumnStorage::default(),
+                chunk_size: None,
+                doc_comments: None,
+                computed: None,
+                sql_expression: None,
+                join_table_shortcut: Some(ResolvedJoinTableShortcutField {
+                    join_table_type: join_type_name.clone(),
+                    join_table_source_column: shortcut.join_reference_key.clone(),
+                    join_table_target_column: target.join_reference_key.clone(),
+                    source_field: shortcut.source_foreign_key.clone(),
+                    target_entity: target_entity_name.clone(),
+                    target_field: target.source_foreign_key.clone(),
+                    is_many,
+                    join_reference_field_name: Some(join_field_name.clone()),
+                    join_table_target_relation: target_relation_name.clone(),
+                }),
+                join_table_intermediate: None,
+                span: default_span(),
+            };
 
-fn compute_unique_constraints(field: &AstField<Typed>) -> Result<Vec<String>, Diagnostic> {
-    match field.annotations.get("unique") {
-        None => Ok(vec![]),
-        Some(p) => match p {
-            AstAnnotationParams::Single(expr, _) => match expr {
-                AstExpr::StringLiteral(string, _) => Ok(vec![string.clone()]),
-                AstExpr::StringList(string_list, _) => Ok(string_list.clone()),
-                _ => Err(Diagnostic {
-                    level: Level::Error,
-                    message: "Not a string nor a string list when specifying unique".to_string(),
-                    code: Some("C000".to_string()),
-                    spans: vec![SpanLabel {
-                        span: field.span,
-                        style: SpanStyle::Primary,
-                        label: None,
-                    }],
+            source_type.fields.push(shortcut_field);
+
+            let join_field_type = if is_many {
+                FieldType::List(Box::new(FieldType::Plain(ResolvedFieldType {
+                    type_name: join_type_name.clone(),
+                    is_primitive: false,
+                })))
+            } else {
+                FieldType::Optional(Box::new(FieldType::Plain(ResolvedFieldType {
+                    type_name: join_type_name.clone(),
+                    is_primitive: false,
+                })))
+            };
+
+            let join_field = ResolvedField {
+                name: join_field_name.clone(),
+                typ: join_field_type,
+                column_names: vec![],
+                self_column: false,
+                is_pk: false,
+                access: allow_all_access,
+                type_hint: None,
+                unique_constraints: vec![],
+                indices: vec![],
+                cardinality: None,
+                default_value: None,
+                read_default: None,
+                mask: None,
+                update_sync: false,
+                readonly: true,
+                relation_path: None,
+                many_to_many: None,
+                renamed_from: None,
+                on_delete: ReferentialAction::NoAction,
+                on_update: ReferentialAction::NoAction,
+                storage: ColumnStorage::default(),
+                chunk_size: None,
+                doc_comments: None,
+                computed: None,
+                sql_expression: None,
+                join_table_shortcut: None,
+                join_table_intermediate: Some(ResolvedJoinTableIntermediateField {
+                    join_table_type: join_type_name.clone(),
+                    join_table_source_column: shortcut.join_reference_key.clone(),
+                    source_field: shortcut.source_foreign_key.clone(),
                 }),
-            },
-            AstAnnotationParams::None => Ok(vec![field.name.clone()]),
-            AstAnnotationParams::Map(_, _) => Err(Diagnostic {
-                level: Level::Error,
-                message: "Cannot specify a map when specifying unique".to_string(),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: field.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            }),
-        },
+                span: default_span(),
+            };
+
+            source_type.fields.push(join_field);
+        }
     }
 }
 
-fn compute_indices(
-    field: &AstField<Typed>,
-    enclosing_type: &AstModel<Typed>,
-) -> Result<Vec<String>, Diagnostic> {
-    let index_annotation = field.annotations.get("index");
-
-    match index_annotation {
-        None => Ok(vec![]),
-        Some(p) => match p {
-            AstAnnotationParams::Single(expr, _) => match expr {
-                AstExpr::StringLiteral(string, _) => Ok(vec![string.clone()]),
-                AstExpr::StringList(string_list, _) => Ok(string_list.clone()),
-                _ => Err(Diagnostic {
-                    level: Level::Error,
-                    message: "Not a string nor a string list when specifying index".to_string(),
-                    code: Some("C000".to_string()),
-                    spans: vec![SpanLabel {
-                        span: field.span,
-                        style: SpanStyle::Primary,
-                        label: None,
-                    }],
-                }),
-            },
-            AstAnnotationParams::None => {
-                let index_computed_name =
-                    format!("{}_{}_idx", enclosing_type.name, field.name).to_ascii_lowercase();
-                Ok(vec![index_computed_name.clone()])
+/// Resolve the join type of each `@manyToMany` field (generating one if the field doesn't specify
+/// `through`), and express the field as a path through the rows of the join type.
+///
+/// For example, `@manyToMany(through="BookAuthor") authors: Set<Author>` in `Book` follows the
+/// `Set<BookAuthor>` field of `Book` (added as `_authors_join` if not declared) and then the
+/// `Author` field of `BookAuthor`.
+fn apply_many_to_many_fields(
+    resolved_types: &mut MappedArena<ResolvedType>,
+    naming_strategies: &HashMap<String, NamingStrategy>,
+    errors: &mut Vec<Diagnostic>,
+) {
+    let mut many_to_many_fields = Vec::new();
+    for (_, typ) in resolved_types.iter() {
+        if let ResolvedType::Composite(composite) = typ {
+            for field in composite.fields.iter() {
+                if let Some(many_to_many) = &field.many_to_many {
+                    many_to_many_fields.push((
+                        composite.name.clone(),
+                        field.name.clone(),
+                        field.typ.name().to_string(),
+                        many_to_many.through.clone(),
+                        field.span,
+                    ));
+                }
             }
-            AstAnnotationParams::Map(_, _) => Err(Diagnostic {
+        }
+    }
+
+    // Generated join types (keyed by the source and target types of the field that generated it)
+    // waiting for the field on the other side of the relationship
+    let mut unpaired_join_types: HashMap<(String, String), String> = HashMap::new();
+
+    for (source_name, field_name, target_name, through, span) in many_to_many_fields {
+        let mut error = |message: String| {
+            errors.push(Diagnostic {
                 level: Level::Error,
-                message: "Cannot specify a map when specifying index".to_string(),
+                message,
                 code: Some("C000".to_string()),
                 spans: vec![SpanLabel {
-                    span: field.span,
+                    span,
                     style: SpanStyle::Primary,
                     label: None,
                 }],
-            }),
-        },
-    }
-}
-
-fn compute_column_info(
-    enclosing_type: &AstModel<Typed>,
-    field: &AstField<Typed>,
-    types: &MappedArena<Type>,
-    table_managed: bool,
-) -> Result<ColumnInfo, Diagnostic> {
-    let unique_constraints = compute_unique_constraints(field)?;
-    let indices = compute_indices(field, enclosing_type)?;
-    let update_sync = field.annotations.contains("update");
-    let readonly = field.annotations.contains("readonly");
-
-    if (update_sync || readonly) && field.default_value.is_none() && table_managed {
-        return Err(Diagnostic {
-            level: Level::Error,
-            message: "Fields with @readonly or @update must have a default value".to_string(),
-            code: Some("C000".to_string()),
-            spans: vec![SpanLabel {
-                span: field.span,
-                style: SpanStyle::Primary,
-                label: None,
-            }],
-        });
-    }
+            });
+        };
 
-    let user_supplied_column_mapping = column_annotation_mapping(field);
+        if source_name == target_name {
+            error(format!(
+                "@manyToMany field '{}' relates '{}' to itself, which is not supported. Consider a linking type with @relationPath",
+                field_name, source_name
+            ));
+            continue;
+        }
 
-    // Validate column mapping once to avoid duplicate errors
-    if let Some(ColumnMapping::Map(mapping)) = &user_supplied_column_mapping {
-        let field_base_type = match &field.typ {
-            AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
-            _ => &field.typ,
+        let join_type_name = match through {
+            Some(through) => through,
+            None => match unpaired_join_types.remove(&(target_name.clone(), source_name.clone())) {
+                Some(join_type_name) => join_type_name,
+                None => {
+                    let join_type = match generate_join_type(
+                        &source_name,
+                        &target_name,
+                        &field_name,
+                        &naming_strategies
+                            .get(&source_name)
+                            .cloned()
+                            .unwrap_or_default(),
+                        resolved_types,
+                    ) {
+                        Ok(join_type) => join_type,
+                        Err(message) => {
+                            error(message);
+                            continue;
+                        }
+                    };
+                    let join_type_name = join_type.name.clone();
+                    resolved_types.add(&join_type_name, ResolvedType::Composite(join_type));
+                    unpaired_join_types.insert(
+                        (source_name.clone(), target_name.clone()),
+                        join_type_name.clone(),
+                    );
+                    join_type_name
+                }
+            },
         };
-        let field_type = field_base_type.to_typ(types).deref(types);
 
-        if let Type::Composite(ct) = field_type {
-            // Pre-compute field names for efficient lookups and simplify the code
-            let type_field_names: HashSet<&String> = ct.fields.iter().map(|f| &f.name).collect();
+        let Some(ResolvedType::Composite(join_type)) = resolved_types.get_by_key(&join_type_name)
+        else {
+            error(format!(
+                "@manyToMany field '{}' references unknown join type '{}'",
+                field_name, join_type_name
+            ));
+            continue;
+        };
 
-            // Collect all primary key field names from the target type
-            let pk_field_names: Vec<&String> = ct
+        let link_field = |entity_name: &str| {
+            let links: Vec<_> = join_type
                 .fields
                 .iter()
-                .filter(|f| f.annotations.contains("pk"))
-                .map(|f| &f.name)
+                .filter(|field| {
+                    field.self_column
+                        && !matches!(field.typ, FieldType::List(_))
+                        && field.typ.name() == entity_name
+                })
                 .collect();
 
-            // Check if all mapping keys correspond to actual fields in the target type
-            for (mapping_key, _) in mapping.iter() {
-                if !type_field_names.contains(mapping_key) {
-                    return Err(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "Field '{}' specified in column mapping does not exist in type '{}'",
-                            mapping_key, ct.name
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: field.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
-                    });
-                }
+            match &links[..] {
+                [link] => Ok(*link),
+                _ => Err(format!(
+                    "Join type '{}' of @manyToMany field '{}' must have exactly one field of type '{}'",
+                    join_type_name, field_name, entity_name
+                )),
             }
+        };
 
-            // Check if all primary key fields are included in the mapping
-            for pk_field_name in &pk_field_names {
-                if !mapping.contains_key(*pk_field_name) {
-                    return Err(Diagnostic {
-                        level: Level::Error,
-                        message: format!(
-                            "Primary key field '{}' from type '{}' is missing in the column mapping",
-                            pk_field_name, ct.name
-                        ),
-                        code: Some("C000".to_string()),
-                        spans: vec![SpanLabel {
-                            span: field.span,
-                            style: SpanStyle::Primary,
-                            label: None,
-                        }],
-                    });
+        let (source_link_columns, target_link_name) =
+            match (link_field(&source_name), link_field(&target_name)) {
+                (Ok(source_link), Ok(target_link)) => {
+                    (source_link.column_names.clone(), target_link.name.clone())
                 }
-            }
-        }
-    }
+                (Err(message), _) | (_, Err(message)) => {
+                    error(message);
+                    continue;
+                }
+            };
 
-    let compute_column_name = |field_name: &str| match &user_supplied_column_mapping {
-        Some(ColumnMapping::Single(name)) => name.clone(),
-        _ => field_name.to_snake_case(),
-    };
-    let enclosing_is_json = enclosing_type.annotations.contains("json");
-    let enclosing_is_computed = enclosing_type.annotations.contains("computed");
-    let enclosing_is_json_like = enclosing_is_json || enclosing_is_computed;
+        let Some(ResolvedType::Composite(source_type)) =
+            resolved_types.get_by_key_mut(&source_name)
+        else {
+            continue;
+        };
 
-    let id_column_names = |field: &AstField<Typed>| -> Result<Vec<String>, Diagnostic> {
-        let user_supplied_column_mapping = column_annotation_mapping(field);
+        let declared_join_field = source_type.fields.iter().find(|field| {
+            matches!(field.typ, FieldType::List(_))
+                && field.typ.name() == join_type_name
+                && !field.self_column
+                && field.column_names == source_link_columns
+        });
 
-        // Handle simple column name for non-composite types
-        if let Some(ColumnMapping::Single(name)) = user_supplied_column_mapping {
-            return Ok(vec![name]);
-        }
+        let join_field_name = match declared_join_field {
+            Some(join_field) => join_field.name.clone(),
+            None => {
+                let join_field_name = format!("_{field_name}_join");
+                if source_type
+                    .fields
+                    .iter()
+                    .any(|field| field.name == join_field_name)
+                {
+                    error(format!(
+                        "@manyToMany field '{}' would create a join helper field '{}' on '{}' but that field already exists",
+                        field_name, join_field_name, source_name
+                    ));
+                    continue;
+                }
 
-        let field_base_type = match &field.typ {
-            AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
-            _ => &field.typ,
+                source_type.fields.push(many_to_many_support_field(
+                    join_field_name.clone(),
+                    FieldType::List(Box::new(FieldType::Plain(ResolvedFieldType {
+                        type_name: join_type_name.clone(),
+                        is_primitive: false,
+                    }))),
+                    source_link_columns,
+                    false,
+                ));
+                join_field_name
+            }
         };
-        let field_type = field_base_type.to_typ(types).deref(types);
 
-        let base_name = field.name.to_snake_case();
+        if let Some(field) = source_type
+            .fields
+            .iter_mut()
+            .find(|field| field.name == field_name)
+        {
+            field.relation_path = Some(vec![join_field_name, target_link_name]);
+        }
+    }
+}
 
-        if let Type::Composite(ct) = field_type {
-            // Validation is already done upfront, no need to repeat it here
+/// Generate the join type for a `@manyToMany` field without `through` (such as `BookAuthor` for
+/// `authors: Set<Author>` in `Book`). The type links the two types with its primary key and is
+/// reachable only through the field.
+fn generate_join_type(
+    source_name: &str,
+    target_name: &str,
+    field_name: &str,
+    naming: &NamingStrategy,
+    resolved_types: &MappedArena<ResolvedType>,
+) -> Result<ResolvedCompositeType, String> {
+    let name = format!("{source_name}{target_name}");
+
+    if resolved_types.get_by_key(&name).is_some() {
+        return Err(format!(
+            "Cannot generate the join type '{}' for @manyToMany field '{}', since a type with that name already exists. Specify it with @manyToMany(through=\"{}\") to use it as the join type",
+            name, field_name, name
+        ));
+    }
 
-            Ok(ct
-                .fields
-                .iter()
-                .filter_map(|f| {
-                    if f.annotations.contains("pk") {
-                        match &user_supplied_column_mapping {
-                            Some(ColumnMapping::Map(mapping)) => {
-                                // Use the mapping if provided
-                                mapping.get(&f.name).cloned()
-                            }
-                            _ => {
-                                // Use the default naming convention
-                                Some(format!("{}_{}", base_name, f.name))
-                            }
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>())
-        } else {
-            Ok(vec![])
-        }
+    let (Some(ResolvedType::Composite(source)), Some(ResolvedType::Composite(target))) = (
+        resolved_types.get_by_key(source_name),
+        resolved_types.get_by_key(target_name),
+    ) else {
+        return Err(format!(
+            "@manyToMany field '{}' must refer to a type",
+            field_name
+        ));
     };
 
-    // we can treat Optional fields as their inner type for the purposes
-    // of computing their default column name
-    let field_base_type = match &field.typ {
-        AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
-        _ => &field.typ,
+    let link_field = |entity: &ResolvedCompositeType| {
+        let link_name = entity.name.to_lower_camel_case();
+        let column_names: Vec<_> = entity
+            .fields
+            .iter()
+            .filter(|field| field.is_pk)
+            .map(|pk_field| naming.foreign_key_column_name(&link_name, &pk_field.name))
+            .collect();
+
+        if column_names.is_empty() {
+            return Err(format!(
+                "Type '{}' must have a primary key to be used in @manyToMany field '{}'",
+                entity.name, field_name
+            ));
+        }
+
+        let mut field = many_to_many_support_field(
+            link_name,
+            FieldType::Plain(ResolvedFieldType {
+                type_name: entity.name.clone(),
+                is_primitive: false,
+            }),
+            column_names,
+            true,
+        );
+        // Removing either side removes the link
+        field.on_delete = ReferentialAction::Cascade;
+        Ok(field)
     };
 
-    match field_base_type {
-        AstFieldType::Optional(_) => {
-            // We've already unwrapped any Optional. A nested optional (e.g. venue: Venue??) doesn't make sense in our model.
-            Err(Diagnostic {
-                level: Level::Error,
-                message: "Cannot have optional of an optional".to_string(),
+    Ok(ResolvedCompositeType {
+        name: name.clone(),
+        plural_name: name.to_plural(),
+        representation: source.representation,
+        root_visible: false,
+        fields: vec![link_field(source)?, link_field(target)?],
+        table_name: SchemaObjectName {
+            name: naming.table_name(&name, None),
+            schema: source.table_name.schema.clone(),
+        },
+        access: ResolvedAccess {
+            query: Some(AstExpr::BooleanLiteral(true, default_span())),
+            mutation: Some(AstExpr::BooleanLiteral(false, default_span())),
+            ..Default::default()
+        },
+        doc_comments: None,
+        join_table: None,
+        renamed_from: None,
+        retention: None,
+        partition: None,
+        ownership_transfer: None,
+        approval: None,
+        field_guards: None,
+        window_fields: vec![],
+        paginated: false,
+        opaque_id: false,
+        validators: vec![],
+        span: default_span(),
+    })
+}
+
+/// Add the type holding the pending changes of each type declared with `@requiresApproval`
+fn add_change_types(
+    resolved_types: &mut MappedArena<ResolvedType>,
+    naming_strategies: &HashMap<String, NamingStrategy>,
+    errors: &mut Vec<Diagnostic>,
+) {
+    let approval_types: Vec<_> = resolved_types
+        .iter()
+        .filter_map(|(_, typ)| match typ {
+            ResolvedType::Composite(composite) => composite
+                .approval
+                .as_ref()
+                .map(|approval| (composite.name.clone(), approval.clone(), composite.span)),
+            _ => None,
+        })
+        .collect();
+
+    for (type_name, approval, span) in approval_types {
+        if resolved_types.get_by_key(&approval.change_type).is_some() {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "Cannot generate the type '{}' for the pending changes of '{}', since a type with that name already exists",
+                    approval.change_type, type_name
+                ),
                 code: Some("C000".to_string()),
                 spans: vec![SpanLabel {
-                    span: field.span,
+                    span,
                     style: SpanStyle::Primary,
                     label: None,
                 }],
-            })
+            });
+            continue;
         }
-        AstFieldType::Plain(..) => {
-            match field_base_type.to_typ(types).deref(types) {
-                Type::Composite(field_type) => {
-                    if field_type.annotations.contains("json")
-                        || (enclosing_is_json_like && enclosing_is_computed)
-                    {
-                        return Ok(ColumnInfo {
-                            names: vec![compute_column_name(&field.name)],
-                            self_column: true,
-                            unique_constraints,
-                            indices,
-                            cardinality: None,
-                        });
-                    }
 
-                    let matching_field =
-                        get_matching_field(field, enclosing_type, &field_type, types);
+        let Some(ResolvedType::Composite(subject)) = resolved_types.get_by_key(&type_name) else {
+            continue;
+        };
+        let change_type = generate_change_type(
+            subject,
+            &approval,
+            &naming_strategies
+                .get(&type_name)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        resolved_types.add(&approval.change_type, ResolvedType::Composite(change_type));
+    }
+}
 
-                    match &field.typ {
-                        AstFieldType::Optional(_) => {
-                            // If the field is optional, we need to look at the cardinality of the matching field to determine the column name.
-                            //
-                            // If the cardinality is `One` (thus forming a one-to-one relationship),
-                            // we need to use the matching field's name as the basis for the column name.
+/// Generate the type holding the pending changes of a type declared with `@requiresApproval`
+/// (such as `PayoutChange` for `Payout`). Approvers may query all changes and requesters their own,
+/// but the changes are only created and updated through the mutations of the type and the
+/// generated approve and reject mutations.
+fn generate_change_type(
+    subject: &ResolvedCompositeType,
+    approval: &ResolvedApproval,
+    naming: &NamingStrategy,
+) -> ResolvedCompositeType {
+    let name = approval.change_type.clone();
+
+    let field = |name: &str, type_name: &str, optional: bool| {
+        let typ = FieldType::Plain(ResolvedFieldType {
+            type_name: type_name.to_string(),
+            is_primitive: true,
+        });
+        change_type_field(
+            name,
+            if optional {
+                FieldType::Optional(Box::new(typ))
+            } else {
+                typ
+            },
+            naming,
+        )
+    };
 
-                            // For example, if we have the following model, we will have a `user_id` column in the `memberships` table,
-                            // but have no column in the `users` table:
-                            //
-                            // type User {
-                            //     ...
-                            //     membership: Membership?
-                            // }
-                            // type Membership {
-                            //     ...
-                            //     user: User
-                            // }
-                            //
-                            // If the cardinality is `Unbounded`, then we need to use the field's name. For example, if we have
-                            // the following model, we will have a `venue_id` column in the `concerts` table.
-                            //
-                            // type Concert {
-                            //    ...
-                            //    venue: Venue?
-                            // }
-                            // type Venue {
-                            //    ...
-                            //    concerts: Set<Concert>
-                            // }
+    let mut id = field("id", primitive_type::IntType::NAME, false);
+    id.is_pk = true;
+    id.default_value = Some(ResolvedFieldDefault::AutoIncrement(None));
+
+    let mut status = field("status", primitive_type::StringType::NAME, false);
+    status.default_value = Some(ResolvedFieldDefault::Value(Box::new(
+        AstExpr::StringLiteral("pending".to_string(), default_span()),
+    )));
+
+    let mut requested_at = field("requestedAt", primitive_type::InstantType::NAME, false);
+    requested_at.default_value = Some(ResolvedFieldDefault::PostgresFunction("now()".to_string()));
+
+    let fields = vec![
+        id,
+        field("mutation", primitive_type::StringType::NAME, false),
+        field("arguments", primitive_type::JsonType::NAME, false),
+        field("preview", primitive_type::JsonType::NAME, true),
+        status,
+        field("requestedBy", &approval.requester_type, false),
+        requested_at,
+        field("reviewedBy", &approval.requester_type, true),
+        field("reviewedAt", primitive_type::InstantType::NAME, true),
+    ];
 
-                            let matching_field_cardinality = match matching_field {
-                                Ok(matching_field) => Ok(field_cardinality(&matching_field.typ)),
-                                Err(_) => annotation_cardinality(field),
-                            }?;
+    let own_change = AstExpr::RelationalOp(RelationalOp::Eq(
+        Box::new(AstExpr::FieldSelection(field_selection_from_str(
+            "self.requestedBy",
+        ))),
+        Box::new(AstExpr::FieldSelection(field_selection_from_str(
+            &approval.requester,
+        ))),
+        Type::Defer,
+    ));
 
-                            match matching_field_cardinality {
-                                Cardinality::ZeroOrOne => Err(Diagnostic {
-                                    level: Level::Error,
-                                    message:
-                                        "Both side of one-to-one relationship cannot be optional"
-                                            .to_string(),
-                                    code: Some("C000".to_string()),
-                                    spans: vec![SpanLabel {
-                                        span: field.span,
-                                        style: SpanStyle::Primary,
-                                        label: None,
-                                    }],
-                                }),
-                                Cardinality::One => {
-                                    if user_supplied_column_mapping.is_some() {
-                                        Err(Diagnostic {
-                                                    level: Level::Error,
-                                                    message: "Cannot specify @column with the optional side of a one-to-one relationship"
-                                                        .to_string(),
-                                                    code: Some("C000".to_string()),
-                                                    spans: vec![SpanLabel {
-                                                        span: field.span,
-                                                        style: SpanStyle::Primary,
-                                                        label: None,
-                                                    }],
-                                                })
-                                    } else {
-                                        Ok(ColumnInfo {
-                                            names: id_column_names(matching_field?)?,
-                                            self_column: false,
-                                            unique_constraints,
-                                            indices,
-                                            cardinality: Some(matching_field_cardinality),
-                                        })
-                                    }
-                                }
-                                Cardinality::Unbounded => Ok(ColumnInfo {
-                                    names: id_column_names(field)?,
-                                    self_column: true,
-                                    unique_constraints,
-                                    indices,
-                                    cardinality: Some(matching_field_cardinality),
-                                }),
-                            }
-                        }
-                        AstFieldType::Plain(..) => {
-                            let matching_field_cardinality = match matching_field {
-                                Ok(matching_field) => Ok(field_cardinality(&matching_field.typ)),
-                                Err(_) => annotation_cardinality(field),
-                            }?;
+    ResolvedCompositeType {
+        name: name.clone(),
+        plural_name: name.to_plural(),
+        representation: subject.representation,
+        root_visible: true,
+        fields,
+        table_name: SchemaObjectName {
+            name: naming.table_name(&name, None),
+            schema: subject.table_name.schema.clone(),
+        },
+        access: ResolvedAccess {
+            query: Some(AstExpr::LogicalOp(LogicalOp::Or(
+                Box::new(approval.approver.clone()),
+                Box::new(own_change),
+                default_span(),
+                Type::Defer,
+            ))),
+            mutation: Some(AstExpr::BooleanLiteral(false, default_span())),
+            ..Default::default()
+        },
+        doc_comments: Some(format!(
+            "Changes to {} waiting for approval (or already reviewed)",
+            subject.name
+        )),
+        join_table: None,
+        renamed_from: None,
+        retention: None,
+        partition: None,
+        ownership_transfer: None,
+        approval: None,
+        field_guards: None,
+        window_fields: vec![],
+        paginated: false,
+        opaque_id: false,
+        validators: vec![],
+        span: default_span(),
+    }
+}
 
-                            let unique_constraints =
-                                if matches!(matching_field_cardinality, Cardinality::ZeroOrOne) {
-                                    // Add an explicit unique constraint to enforce one-to-one constraint
-                                    vec![field.name.clone()]
-                                } else {
-                                    unique_constraints
-                                };
+/// Add the type recording the changes to the guarded fields of each type using `@guard`
+fn add_field_change_types(
+    resolved_types: &mut MappedArena<ResolvedType>,
+    naming_strategies: &HashMap<String, NamingStrategy>,
+    errors: &mut Vec<Diagnostic>,
+) {
+    let guarded_types: Vec<_> = resolved_types
+        .iter()
+        .filter_map(|(_, typ)| match typ {
+            ResolvedType::Composite(composite) => composite
+                .field_guards
+                .as_ref()
+                .map(|field_guards| (composite.name.clone(), field_guards.clone(), composite.span)),
+            _ => None,
+        })
+        .collect();
 
-                            Ok(ColumnInfo {
-                                names: id_column_names(field)?,
-                                self_column: true,
-                                unique_constraints,
-                                indices,
-                                cardinality: Some(matching_field_cardinality),
-                            })
-                        }
-                    }
-                }
-                Type::Set(typ) => {
-                    if let Type::Composite(field_type) = typ.deref(types)
-                        && (field_type.annotations.contains("json")
-                            || (enclosing_is_json_like && enclosing_is_computed))
-                    {
-                        return Ok(ColumnInfo {
-                            names: vec![compute_column_name(&field.name)],
-                            self_column: true,
-                            unique_constraints,
-                            indices,
-                            cardinality: None,
-                        });
-                    }
+    for (type_name, field_guards, span) in guarded_types {
+        if resolved_types
+            .get_by_key(&field_guards.change_type)
+            .is_some()
+        {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "Cannot generate the type '{}' for the changes to the guarded fields of '{}', since a type with that name already exists",
+                    field_guards.change_type, type_name
+                ),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            });
+            continue;
+        }
 
-                    if let Type::Composite(field_type) = typ.deref(types) {
-                        // OneToMany
-                        let matching_field =
-                            get_matching_field(field, enclosing_type, &field_type, types)?;
+        let Some(ResolvedType::Composite(subject)) = resolved_types.get_by_key(&type_name) else {
+            continue;
+        };
+        let change_type = generate_field_change_type(
+            subject,
+            &field_guards,
+            &naming_strategies
+                .get(&type_name)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        resolved_types.add(
+            &field_guards.change_type,
+            ResolvedType::Composite(change_type),
+        );
+    }
+}
 
-                        let matching_field_cardinality = field_cardinality(&matching_field.typ);
+/// Generate the type recording the changes to the guarded fields of a type (such as
+/// `UserFieldChange` for `User`). It is written and read only when enforcing the guards, so it has
+/// no queries or mutations.
+fn generate_field_change_type(
+    subject: &ResolvedCompositeType,
+    field_guards: &ResolvedFieldGuards,
+    naming: &NamingStrategy,
+) -> ResolvedCompositeType {
+    let name = field_guards.change_type.clone();
+    let table_name = naming.table_name(&name, None);
+
+    // Guards look up the recent changes to a field of an entity
+    let lookup_index = fit_identifier(&format!("{table_name}_entity_idx").to_ascii_lowercase());
+
+    let field = |name: &str, type_name: &str, indexed: bool| {
+        let mut field = change_type_field(
+            name,
+            FieldType::Plain(ResolvedFieldType {
+                type_name: type_name.to_string(),
+                is_primitive: true,
+            }),
+            naming,
+        );
+        if indexed {
+            field.indices = vec![lookup_index.clone()];
+        }
+        field
+    };
 
-                        if matching_field_cardinality == Cardinality::Unbounded {
-                            let referring_type_name = &enclosing_type.name;
-                            let referred_type_name = &field_type.name;
-                            let suggested_linking_type_name =
-                                if referring_type_name < referred_type_name {
-                                    format!("{}{}", referring_type_name, referred_type_name)
-                                } else {
-                                    format!("{}{}", referred_type_name, referring_type_name)
-                                };
+    let mut id = field("id", primitive_type::IntType::NAME, false);
+    id.is_pk = true;
+    id.default_value = Some(ResolvedFieldDefault::AutoIncrement(None));
 
-                            // We don't support direct many-to-many relationships
-                            Err(Diagnostic {
-                                level: Level::Error,
-                                message: format!(
-                                    "Many-to-many relationships without a linking type are not supported. Consider adding a type such as '{suggested_linking_type_name}' to connect '{referring_type_name}' and '{referred_type_name}",
-                                ),
-                                code: Some("C000".to_string()),
-                                spans: vec![SpanLabel {
-                                    span: field.span,
-                                    style: SpanStyle::Primary,
-                                    label: None,
-                                }],
-                            })
-                        } else if user_supplied_column_mapping.is_some() {
-                            Err(Diagnostic {
-                                level: Level::Error,
-                                message: "Cannot specify @column with a collection field"
-                                    .to_string(),
-                                code: Some("C000".to_string()),
-                                spans: vec![SpanLabel {
-                                    span: field.span,
-                                    style: SpanStyle::Primary,
-                                    label: None,
-                                }],
-                            })
-                        } else {
-                            Ok(ColumnInfo {
-                                names: id_column_names(matching_field)?,
-                                self_column: false,
-                                unique_constraints,
-                                indices,
-                                cardinality: Some(matching_field_cardinality),
-                            })
-                        }
-                    } else {
-                        Err(Diagnostic {
-                            level: Level::Error,
-                            message: "Sets of non-composites are not supported (consider using an `Array` instead)".to_string(),
-                            code: Some("C000".to_string()),
-                            spans: vec![SpanLabel {
+    let mut changed_at = field("changedAt", primitive_type::InstantType::NAME, true);
+    changed_at.default_value = Some(ResolvedFieldDefault::PostgresFunction("now()".to_string()));
+
+    let fields = vec![
+        id,
+        field("entityId", &field_guards.entity_id_type, true),
+        field("field", primitive_type::StringType::NAME, true),
+        changed_at,
+    ];
+
+    ResolvedCompositeType {
+        name: name.clone(),
+        plural_name: name.to_plural(),
+        representation: subject.representation,
+        root_visible: false,
+        fields,
+        table_name: SchemaObjectName {
+            name: table_name,
+            schema: subject.table_name.schema.clone(),
+        },
+        access: ResolvedAccess {
+            default: Some(AstExpr::BooleanLiteral(false, default_span())),
+            ..Default::default()
+        },
+        doc_comments: Some(format!("Changes to the guarded fields of {}", subject.name)),
+        join_table: None,
+        renamed_from: None,
+        retention: None,
+        partition: None,
+        ownership_transfer: None,
+        approval: None,
+        field_guards: None,
+        window_fields: vec![],
+        paginated: false,
+        opaque_id: false,
+        validators: vec![],
+        span: default_span(),
+    }
+}
+
+fn change_type_field(
+    name: &str,
+    typ: FieldType<ResolvedFieldType>,
+    naming: &NamingStrategy,
+) -> ResolvedField {
+    ResolvedField {
+        name: name.to_string(),
+        typ,
+        column_names: vec![naming.column_name(name)],
+        self_column: true,
+        is_pk: false,
+        access: ResolvedAccess {
+            default: Some(AstExpr::BooleanLiteral(true, default_span())),
+            ..Default::default()
+        },
+        type_hint: None,
+        unique_constraints: vec![],
+        indices: vec![],
+        cardinality: None,
+        default_value: None,
+        read_default: None,
+        mask: None,
+        update_sync: false,
+        readonly: false,
+        relation_path: None,
+        many_to_many: None,
+        renamed_from: None,
+        on_delete: ReferentialAction::NoAction,
+        on_update: ReferentialAction::NoAction,
+        storage: ColumnStorage::default(),
+        chunk_size: None,
+        doc_comments: None,
+        computed: None,
+        sql_expression: None,
+        join_table_shortcut: None,
+        join_table_intermediate: None,
+        span: default_span(),
+    }
+}
+
+/// A field that supports a `@manyToMany` field: either a link of a generated join type (`is_link`) or
+/// the collection of join type rows added to the type declaring the field
+fn many_to_many_support_field(
+    name: String,
+    typ: FieldType<ResolvedFieldType>,
+    column_names: Vec<String>,
+    is_link: bool,
+) -> ResolvedField {
+    ResolvedField {
+        name,
+        typ,
+        column_names,
+        self_column: is_link,
+        is_pk: is_link,
+        access: ResolvedAccess {
+            default: Some(AstExpr::BooleanLiteral(true, default_span())),
+            ..Default::default()
+        },
+        type_hint: None,
+        unique_constraints: vec![],
+        indices: vec![],
+        cardinality: Some(Cardinality::Unbounded),
+        default_value: None,
+        read_default: None,
+        mask: None,
+        update_sync: false,
+        readonly: !is_link,
+        relation_path: None,
+        many_to_many: None,
+        renamed_from: None,
+        on_delete: ReferentialAction::NoAction,
+        on_update: ReferentialAction::NoAction,
+        storage: ColumnStorage::default(),
+        chunk_size: None,
+        doc_comments: None,
+        computed: None,
+        sql_expression: None,
+        join_table_shortcut: None,
+        join_table_intermediate: None,
+        span: default_span(),
+    }
+}
+
+fn resolve_composite_type(
+    ct: &AstModel<Typed>,
+    module_schema_name: &Option<String>,
+    module_managed: Option<bool>,
+    module_base_path: &Path,
+    naming_strategies: &HashMap<String, NamingStrategy>,
+    typechecked_system: &TypecheckedSystem,
+    resolved_postgres_types: &mut MappedArena<ResolvedType>,
+    errors: &mut Vec<Diagnostic>,
+) -> Result<(), Diagnostic> {
+    if ct.kind == AstModelKind::Type {
+        let plural_annotation_value = ct
+            .annotations
+            .get("plural")
+            .map(|p| p.as_single().as_string());
+
+        let table_annotation = ct.annotations.annotations.get("table");
+
+        let TableInfo {
+            name: table_name,
+            schema: schema_name,
+            managed: table_managed,
+        } = extract_table_annotation(
+            table_annotation,
+            &ct.name,
+            plural_annotation_value.clone(),
+            &naming_strategies.get(&ct.name).cloned().unwrap_or_default(),
+        )?;
+
+        // If the table didn't specify a schema, use the module schema
+        let schema_name = module_schema_name.clone().or(schema_name);
+
+        // If there is an explicit table managed attribute, that takes precedence.
+        // Otherwise, if there is an explicit module managed attribute, use that.
+        // Otherwise, default to managed.
+        let table_managed = match (table_managed, module_managed) {
+            (Some(table_managed), _) => table_managed,
+            (None, Some(module_managed)) => module_managed,
+            (None, None) => true,
+        };
+
+        let is_json = ct.annotations.contains("json");
+        let is_computed = ct.annotations.contains("computed");
+
+        if is_json && is_computed {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!("Type '{}' cannot use both @json and @computed", ct.name),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: ct.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            });
+        }
+
+        if is_computed && table_annotation.is_some() {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "Type '{}' cannot use @table when marked as @computed",
+                    ct.name
+                ),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: ct.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            });
+        }
+
+        let representation = if is_json {
+            EntityRepresentation::Json
+        } else if is_computed {
+            EntityRepresentation::Computed
+        } else if table_managed {
+            EntityRepresentation::Managed
+        } else {
+            EntityRepresentation::NotManaged
+        };
+
+        let access_annotation = ct.annotations.get("access");
+
+        let is_json_like = representation.is_json_like();
+
+        if is_json_like && access_annotation.is_some() {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "Cannot use @access for type {}. Json and computed types behave like a primitive (and thus have always-allowed access)",
+                    ct.name
+                ),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: ct.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            });
+        }
+
+        let visibility_annotation = ct.annotations.annotations.get("visibility");
+        let root_visible = parse_visibility_annotation(ct, visibility_annotation, errors);
+
+        let ownership_annotation = ct.annotations.annotations.get("ownership");
+
+        let ownership_config = ownership_annotation
+            .and_then(|annotation| parse_ownership_annotation(ct, annotation, errors));
+
+        let join_table_annotation = ct.annotations.annotations.get("joinTable");
+        let join_table_config = join_table_annotation
+            .and_then(|annotation| parse_join_table_annotation(ct, annotation, errors));
+
+        let retention = ct
+            .annotations
+            .annotations
+            .get("retention")
+            .and_then(|annotation| parse_retention_annotation(ct, annotation, errors));
+
+        let partition = ct
+            .annotations
+            .annotations
+            .get("partitionBy")
+            .and_then(|annotation| parse_partition_annotation(ct, annotation, errors));
+
+        let window_fields = ct
+            .annotations
+            .annotations
+            .get("window")
+            .map(|annotation| parse_window_annotation(ct, annotation, errors))
+            .unwrap_or_default();
+
+        let mut access = if is_json_like {
+            // As if the user has annotated with `access(true)`
+            ResolvedAccess {
+                default: Some(AstExpr::BooleanLiteral(true, default_span())),
+                ..Default::default()
+            }
+        } else {
+            build_access(access_annotation)
+        };
+        let name = ct.name.clone();
+        let plural_name = plural_annotation_value.unwrap_or_else(|| ct.name.to_plural()); // fallback to automatically pluralizing name
+
+        let (resolved_fields, ownership_field_found) = resolve_composite_type_fields(
+            ct,
+            is_json_like,
+            table_managed,
+            module_base_path,
+            naming_strategies,
+            typechecked_system,
+            ownership_config.as_ref(),
+            errors,
+        );
+
+        let opaque_id = ct
+            .annotations
+            .annotations
+            .get("opaqueId")
+            .is_some_and(|annotation| {
+                validate_opaque_id_annotation(
+                    ct,
+                    annotation,
+                    is_json_like,
+                    &resolved_fields,
+                    errors,
+                )
+            });
+
+        let validators = parse_validators(ct, is_json_like, module_base_path, errors);
+
+        let approval = ct
+            .annotations
+            .annotations
+            .get("requiresApproval")
+            .and_then(|annotation| {
+                parse_approval_annotation(
+                    ct,
+                    annotation,
+                    is_json_like,
+                    &resolved_fields,
+                    typechecked_system,
+                    errors,
+                )
+            });
+
+        let field_guards = parse_field_guards(ct, is_json_like, &resolved_fields, errors);
+
+        if let Some(annotation) = ownership_annotation
+            && let Some(config) = ownership_config.as_ref()
+            && !ownership_field_found
+        {
+            push_type_error(
+                ct,
+                annotation.span,
+                format!(
+                    "Field '{}' specified in @ownership was not found on type '{}'",
+                    config.field, ct.name
+                ),
+                errors,
+            );
+        }
+
+        let ownership_transfer = ownership_config
+            .as_ref()
+            .filter(|_| ownership_field_found)
+            .and_then(|config| {
+                config
+                    .transfer
+                    .clone()
+                    .map(|access| ResolvedOwnershipTransfer {
+                        field: config.field.clone(),
+                        access,
+                    })
+            });
+
+        if let Some(config) = ownership_config.as_ref()
+            && ownership_field_found
+        {
+            let guard_expr = build_ownership_guard_expr(config);
+
+            if config.enforce_create {
+                access.creation = combine_with_guard(access.creation.take(), guard_expr.clone());
+            }
+
+            if config.enforce_update {
+                access.update = combine_with_guard(access.update.take(), guard_expr.clone());
+            }
+
+            if config.enforce_delete {
+                access.delete = combine_with_guard(access.delete.take(), guard_expr);
+            }
+        }
+
+        resolved_postgres_types.add(
+            &ct.name,
+            ResolvedType::Composite(ResolvedCompositeType {
+                name,
+                plural_name: plural_name.clone(),
+                representation,
+                root_visible,
+                fields: resolved_fields,
+                table_name: SchemaObjectName {
+                    name: table_name,
+                    schema: schema_name,
+                },
+                access: access.clone(),
+                doc_comments: ct.doc_comments.clone(),
+                join_table: join_table_config,
+                renamed_from: ct
+                    .annotations
+                    .get("renamedFrom")
+                    .map(|annotation| annotation.as_single().as_string()),
+                retention,
+                partition,
+                ownership_transfer,
+                approval,
+                field_guards,
+                window_fields,
+                paginated: ct.annotations.contains("paginated"),
+                opaque_id,
+                validators,
+                span: ct.span,
+            }),
+        );
+    }
+
+    Ok(())
+}
+
+fn resolve_composite_type_fields(
+    ct: &AstModel<Typed>,
+    is_json_like: bool,
+    table_managed: bool,
+    module_base_path: &Path,
+    naming_strategies: &HashMap<String, NamingStrategy>,
+    typechecked_system: &TypecheckedSystem,
+    ownership_config: Option<&OwnershipConfig>,
+    errors: &mut Vec<Diagnostic>,
+) -> (Vec<ResolvedField>, bool) {
+    let fragment_fields = compute_fragment_fields(ct, errors, typechecked_system);
+
+    let mut resolved_fields = Vec::new();
+    let mut ownership_field_found = false;
+
+    for field in ct.fields.iter().chain(fragment_fields.into_iter()) {
+        let update_sync = field.annotations.contains("update");
+        let mut readonly = field.annotations.contains("readonly");
+
+        let access_annotation = field.annotations.get("access");
+
+        if is_json_like && access_annotation.is_some() {
+            errors.push(Diagnostic {
+            level: Level::Error,
+            message: format!(
+                "Cannot use @access for field '{}' in a type with a '@json' or '@computed' annotation",
+                field.name
+            ),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            });
+        }
+
+        // For fields, by default, we assume the `access(true)` annotation
+        let access = match access_annotation {
+            Some(_) => build_access(access_annotation),
+            None => ResolvedAccess {
+                default: AstExpr::BooleanLiteral(true, default_span()).into(),
+                ..Default::default()
+            },
+        };
+
+        let relation_path = field.annotations.get("relationPath").and_then(|params| {
+            match params {
+                AstAnnotationParams::Single(expr, _) => match expr {
+                    AstExpr::StringLiteral(path, _) => {
+                        let segments: Vec<String> = path
+                            .split('.')
+                            .map(|segment| segment.trim().to_string())
+                            .filter(|segment| !segment.is_empty())
+                            .collect();
+
+                        if segments.is_empty() {
+                            errors.push(Diagnostic {
+                                level: Level::Error,
+                                message: format!(
+                                    "@relationPath for field '{}' must contain at least one segment",
+                                    field.name
+                                ),
+                                code: Some("C000".to_string()),
+                                spans: vec![SpanLabel {
+                                    span: field.span,
+                                    style: SpanStyle::Primary,
+                                    label: None,
+                                }],
+                            });
+                            None
+                        } else {
+                            Some(segments)
+                        }
+                    }
+                    _ => {
+                        errors.push(Diagnostic {
+                            level: Level::Error,
+                            message: format!(
+                                "@relationPath for field '{}' must be a string literal",
+                                field.name
+                            ),
+                            code: Some("C000".to_string()),
+                            spans: vec![SpanLabel {
+                                span: field.span,
+                                style: SpanStyle::Primary,
+                                label: None,
+                            }],
+                        });
+                        None
+                    }
+                },
+                _ => {
+                    errors.push(Diagnostic {
+                        level: Level::Error,
+                        message: format!(
+                            "@relationPath for field '{}' must use the syntax @relationPath(\"segment1.segment2\")",
+                            field.name
+                        ),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: field.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                    None
+                }
+            }
+        });
+
+        let typ = resolve_field_type(
+            &field.typ.to_typ(&typechecked_system.types),
+            &typechecked_system.types,
+        );
+
+        let mut field_default = field
+            .default_value
+            .as_ref()
+            .map(|v| resolve_field_default_type(v, &typ, errors));
+
+        let many_to_many = match field.annotations.get("manyToMany") {
+            Some(params) => {
+                match parse_many_to_many_field(field, params, &typ, relation_path.is_some(), errors)
+                {
+                    Some(many_to_many) => Some(many_to_many),
+                    None => continue,
+                }
+            }
+            None => None,
+        };
+
+        if relation_path.is_some() && field.annotations.get("computed").is_some() {
+            errors.push(Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "Field '{}' cannot use both @computed and @relationPath",
+                    field.name
+                ),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            });
+            continue;
+        }
+
+        if let Some(computed_params) = field.annotations.get("computed") {
+            let resolved_computed =
+                match parse_computed_field(module_base_path, field, computed_params, errors) {
+                    Some(c) => c,
+                    None => continue,
+                };
+
+            validate_computed_field_annotations(field, update_sync, errors);
+
+            resolved_fields.push(ResolvedField {
+                name: field.name.clone(),
+                typ,
+                column_names: vec![],
+                self_column: false,
+                is_pk: false,
+                access,
+                type_hint: None,
+                unique_constraints: vec![],
+                indices: vec![],
+                cardinality: None,
+                default_value: None,
+                read_default: None,
+                mask: None,
+                update_sync: false,
+                readonly: true,
+                relation_path: None,
+                many_to_many: None,
+                renamed_from: None,
+                on_delete: ReferentialAction::NoAction,
+                on_update: ReferentialAction::NoAction,
+                storage: ColumnStorage::default(),
+                chunk_size: None,
+                doc_comments: field.doc_comments.clone(),
+                computed: Some(resolved_computed),
+                sql_expression: None,
+                join_table_shortcut: None,
+                join_table_intermediate: None,
+                span: field.span,
+            });
+            continue;
+        }
+
+        if let Some(annotation) = field.annotations.get("sqlExpression") {
+            let sql_expression =
+                match parse_sql_expression_field(field, annotation, &typ, is_json_like, errors) {
+                    Some(expression) => expression,
+                    None => continue,
+                };
+
+            resolved_fields.push(ResolvedField {
+                name: field.name.clone(),
+                typ,
+                column_names: vec![],
+                self_column: false,
+                is_pk: false,
+                access,
+                type_hint: None,
+                unique_constraints: vec![],
+                indices: vec![],
+                cardinality: None,
+                default_value: None,
+                read_default: None,
+                mask: None,
+                update_sync: false,
+                readonly: true,
+                relation_path: None,
+                many_to_many: None,
+                renamed_from: None,
+                on_delete: ReferentialAction::NoAction,
+                on_update: ReferentialAction::NoAction,
+                storage: ColumnStorage::default(),
+                chunk_size: None,
+                doc_comments: field.doc_comments.clone(),
+                computed: None,
+                sql_expression: Some(sql_expression),
+                join_table_shortcut: None,
+                join_table_intermediate: None,
+                span: field.span,
+            });
+            continue;
+        }
+
+        // The path of a many-to-many field is filled in once the join type is known (see `apply_many_to_many_fields`)
+        if relation_path.is_some() || many_to_many.is_some() {
+            readonly = true;
+        }
+
+        let column_info = if relation_path.is_some() || many_to_many.is_some() {
+            Ok(ColumnInfo {
+                names: vec![],
+                self_column: false,
+                unique_constraints: vec![],
+                indices: vec![],
+                cardinality: None,
+            })
+        } else {
+            compute_column_info(
+                ct,
+                field,
+                &typechecked_system.types,
+                table_managed,
+                naming_strategies,
+            )
+        };
+
+        let ColumnInfo {
+            names: column_names,
+            self_column,
+            unique_constraints,
+            indices,
+            cardinality,
+        } = match column_info {
+            Ok(info) => info,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+
+        // A unique query looks up the entity by the constraint's columns, so each field in the
+        // constraint must be stored in the type's table
+        let unique_constraints = if field.annotations.contains("unique") && !self_column {
+            push_field_error(
+                field,
+                format!(
+                    "Field '{}' cannot be part of a unique constraint, since it isn't stored in its type's table",
+                    field.name
+                ),
+                errors,
+            );
+            vec![]
+        } else {
+            unique_constraints
+        };
+
+        let renamed_from = field.annotations.get("renamedFrom").and_then(|annotation| {
+            if self_column && column_names.len() == 1 {
+                Some(annotation.as_single().as_string())
+            } else {
+                push_field_error(
+                    field,
+                    format!(
+                        "Field '{}' cannot use @renamedFrom, since it doesn't map to a single column in its type's table",
+                        field.name
+                    ),
+                    errors,
+                );
+                None
+            }
+        });
+
+        let on_delete = compute_referential_action(
+            field,
+            "onDelete",
+            self_column,
+            &typechecked_system.types,
+            errors,
+        );
+        let on_update = compute_referential_action(
+            field,
+            "onUpdate",
+            self_column,
+            &typechecked_system.types,
+            errors,
+        );
+        let read_default =
+            compute_read_default(field, self_column, &typechecked_system.types, errors);
+        let mask = compute_mask(field, self_column, &typechecked_system.types, errors);
+        let storage = compute_storage(field, self_column, &typechecked_system.types, errors);
+        let chunk_size = compute_chunk_size(field, self_column, &typechecked_system.types, errors);
+
+        if let Some(config) = ownership_config
+            && let Some(base_field) = config.field_path.first()
+            && base_field == &field.name
+        {
+            ownership_field_found = true;
+
+            if config.auto_assign && config.field_path.len() == 1 {
+                readonly = true;
+                if field_default.is_none() {
+                    let context_expr =
+                        AstExpr::FieldSelection(field_selection_from_str(&config.context_path));
+                    field_default = Some(ResolvedFieldDefault::Value(Box::new(context_expr)));
+                }
+            }
+
+            if config.field_path.len() > 1 {
+                errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!(
+                        "Ownership context path '{}' must be a single field name",
+                        config.context_path
+                    ),
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: field.span,
+                        style: SpanStyle::Primary,
+                        label: Some("Ownership field must refer to a single field".to_string()),
+                    }],
+                });
+            }
+        }
+
+        resolved_fields.push(ResolvedField {
+            name: field.name.clone(),
+            typ,
+            column_names,
+            self_column,
+            is_pk: field.annotations.contains("pk"),
+            access,
+            type_hint: build_type_hint(field, &typechecked_system.types, errors),
+            unique_constraints,
+            indices,
+            cardinality,
+            default_value: field_default,
+            read_default,
+            mask,
+            update_sync,
+            readonly,
+            relation_path,
+            many_to_many,
+            renamed_from,
+            on_delete,
+            on_update,
+            storage,
+            chunk_size,
+            doc_comments: field.doc_comments.clone(),
+            computed: None,
+            sql_expression: None,
+            join_table_shortcut: None,
+            join_table_intermediate: None,
+            span: field.span,
+        });
+    }
+
+    (resolved_fields, ownership_field_found)
+}
+
+/// Compute the referential action declared with `@onDelete`/`@onUpdate` (such as `@onDelete("cascade")`).
+/// These are allowed only on fields that hold a foreign key (many-to-one or the owning side of one-to-one).
+fn compute_referential_action(
+    field: &AstField<Typed>,
+    annotation_name: &str,
+    self_column: bool,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> ReferentialAction {
+    let Some(annotation) = field.annotations.get(annotation_name) else {
+        return ReferentialAction::NoAction;
+    };
+
+    let field_base_type = match &field.typ {
+        AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
+        _ => &field.typ,
+    };
+    let refers_to_composite = matches!(
+        field_base_type.to_typ(types).deref(types),
+        Type::Composite(_)
+    );
+
+    if !self_column || !refers_to_composite {
+        push_field_error(
+            field,
+            format!(
+                "@{annotation_name} is allowed only on fields that refer to another type through a foreign key (such as `venue: Venue`)"
+            ),
+            errors,
+        );
+        return ReferentialAction::NoAction;
+    }
+
+    let action = match annotation.as_single().as_string().as_str() {
+        "cascade" => ReferentialAction::Cascade,
+        "restrict" => ReferentialAction::Restrict,
+        "setNull" => ReferentialAction::SetNull,
+        other => {
+            push_field_error(
+                field,
+                format!(
+                    "Invalid @{annotation_name} action '{other}'. Expected one of 'cascade', 'restrict', or 'setNull'"
+                ),
+                errors,
+            );
+            return ReferentialAction::NoAction;
+        }
+    };
+
+    if action == ReferentialAction::SetNull && !matches!(field.typ, AstFieldType::Optional(_)) {
+        push_field_error(
+            field,
+            format!(
+                "@{annotation_name}(\"setNull\") requires the field '{}' to be optional",
+                field.name
+            ),
+            errors,
+        );
+        return ReferentialAction::NoAction;
+    }
+
+    action
+}
+
+/// Compute the literal declared with `@readDefault` (such as `@readDefault("Untitled")`), which
+/// replaces null values of an optional scalar field when reading it (through `COALESCE`)
+fn compute_read_default(
+    field: &AstField<Typed>,
+    self_column: bool,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let annotation = field.annotations.get("readDefault")?;
+
+    let AstFieldType::Optional(inner_typ) = &field.typ else {
+        push_field_error(
+            field,
+            format!(
+                "@readDefault requires the field '{}' to be optional",
+                field.name
+            ),
+            errors,
+        );
+        return None;
+    };
+
+    let type_name = match inner_typ.to_typ(types).deref(types) {
+        Type::Primitive(primitive_type::PrimitiveType::Plain(pt))
+            if self_column
+                && ![
+                    primitive_type::JsonType::NAME,
+                    primitive_type::BlobType::NAME,
+                    primitive_type::VectorType::NAME,
+                ]
+                .contains(&pt.name()) =>
+        {
+            pt.name()
+        }
+        Type::Enum(_) if self_column => "Enum",
+        _ => {
+            push_field_error(
+                field,
+                format!(
+                    "@readDefault is allowed only on fields of a scalar or enum type, but '{}' is not",
+                    field.name
+                ),
+                errors,
+            );
+            return None;
+        }
+    };
+
+    let is_number = [
+        primitive_type::IntType::NAME,
+        primitive_type::FloatType::NAME,
+        primitive_type::DecimalType::NAME,
+    ]
+    .contains(&type_name);
+    let is_boolean = type_name == primitive_type::BooleanType::NAME;
+
+    match annotation.as_single() {
+        AstExpr::NumberLiteral(number, _) if is_number => Some(number.to_string()),
+        AstExpr::BooleanLiteral(boolean, _) if is_boolean => Some(boolean.to_string()),
+        AstExpr::StringLiteral(string, _) if !is_number && !is_boolean => Some(string.to_string()),
+        _ => {
+            push_field_error(
+                field,
+                format!(
+                    "@readDefault for field '{}' must be a literal of the field's type",
+                    field.name
+                ),
+                errors,
+            );
+            None
+        }
+    }
+}
+
+/// Compute the masking declared with `@mask` (such as `@mask(roles: ["support"], strategy: "last4")`).
+/// The strategies that keep part of the value apply only to string fields, and "null" only to optional fields.
+fn compute_mask(
+    field: &AstField<Typed>,
+    self_column: bool,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<FieldMask> {
+    let annotation = field.annotations.get("mask")?;
+    let params = annotation.as_map();
+
+    let roles = match params.get("roles") {
+        Some(AstExpr::StringList(roles, _)) if !roles.is_empty() => roles.clone(),
+        Some(AstExpr::StringLiteral(role, _)) => vec![role.clone()],
+        _ => {
+            push_field_error(
+                field,
+                format!(
+                    "@mask for field '{}' must specify a non-empty list of roles",
+                    field.name
+                ),
+                errors,
+            );
+            return None;
+        }
+    };
+
+    let strategy = match params.get("strategy") {
+        None => Some(MaskStrategy::Full),
+        Some(AstExpr::StringLiteral(name, _)) => MaskStrategy::from_name(name),
+        _ => None,
+    };
+    let Some(strategy) = strategy else {
+        push_field_error(
+            field,
+            format!(
+                "@mask strategy for field '{}' must be one of \"full\", \"last4\", \"email\", or \"null\"",
+                field.name
+            ),
+            errors,
+        );
+        return None;
+    };
+
+    // A field with `@readDefault` is non-nullable in the API, so it cannot be masked to null
+    let (optional, inner_typ) = match &field.typ {
+        AstFieldType::Optional(inner_typ) => (
+            !field.annotations.contains("readDefault"),
+            inner_typ.as_ref(),
+        ),
+        typ => (false, typ),
+    };
+
+    let valid = self_column
+        && match inner_typ.to_typ(types).deref(types) {
+            Type::Primitive(primitive_type::PrimitiveType::Plain(pt)) => {
+                if strategy == MaskStrategy::Null {
+                    optional
+                } else {
+                    pt.name() == primitive_type::StringType::NAME
+                }
+            }
+            Type::Enum(_) => strategy == MaskStrategy::Null && optional,
+            _ => false,
+        };
+
+    if !valid {
+        let requirement = if strategy == MaskStrategy::Null {
+            "an optional scalar field (without @readDefault)"
+        } else {
+            "a String field"
+        };
+        push_field_error(
+            field,
+            format!(
+                "@mask strategy \"{}\" requires {requirement}, but '{}' is not",
+                strategy.name(),
+                field.name
+            ),
+            errors,
+        );
+        return None;
+    }
+
+    Some(FieldMask { roles, strategy })
+}
+
+/// Compute the storage parameters declared with `@storage` (such as `@storage("external")` or
+/// `@storage(strategy="main", compression="lz4")`). These are allowed only on fields of the types
+/// whose values may be large (`String`, `Blob`, and `Json`).
+fn compute_storage(
+    field: &AstField<Typed>,
+    self_column: bool,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> ColumnStorage {
+    let Some(annotation) = field.annotations.get("storage") else {
+        return ColumnStorage::default();
+    };
+
+    let (strategy, compression) = match annotation {
+        AstAnnotationParams::Single(strategy, _) => (Some(strategy), None),
+        AstAnnotationParams::Map(params, _) => (params.get("strategy"), params.get("compression")),
+        AstAnnotationParams::None => (None, None),
+    };
+
+    let mut valid = true;
+
+    let strategy = strategy.and_then(|strategy| match strategy {
+        AstExpr::StringLiteral(name, _) if StorageStrategy::from_name(name).is_some() => {
+            StorageStrategy::from_name(name)
+        }
+        _ => {
+            valid = false;
+            push_field_error(
+                field,
+                format!(
+                    "@storage strategy for field '{}' must be one of \"plain\", \"external\", \"extended\", or \"main\"",
+                    field.name
+                ),
+                errors,
+            );
+            None
+        }
+    });
+
+    let compression = compression.and_then(|compression| match compression {
+        AstExpr::StringLiteral(name, _) if CompressionMethod::from_name(name).is_some() => {
+            CompressionMethod::from_name(name)
+        }
+        _ => {
+            valid = false;
+            push_field_error(
+                field,
+                format!(
+                    "@storage compression for field '{}' must be either \"pglz\" or \"lz4\"",
+                    field.name
+                ),
+                errors,
+            );
+            None
+        }
+    });
+
+    let inner_typ = match &field.typ {
+        AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
+        typ => typ,
+    };
+    let large_value_type = self_column
+        && match inner_typ.to_typ(types).deref(types) {
+            Type::Primitive(primitive_type::PrimitiveType::Plain(pt)) => [
+                primitive_type::StringType::NAME,
+                primitive_type::BlobType::NAME,
+                primitive_type::JsonType::NAME,
+            ]
+            .contains(&pt.name()),
+            _ => false,
+        };
+
+    if !large_value_type {
+        push_field_error(
+            field,
+            format!(
+                "@storage is allowed only on fields of type String, Blob, or Json, but '{}' is not",
+                field.name
+            ),
+            errors,
+        );
+        return ColumnStorage::default();
+    }
+
+    if !valid {
+        return ColumnStorage::default();
+    }
+
+    ColumnStorage {
+        strategy,
+        compression,
+    }
+}
+
+/// The number of characters or bytes per chunk of a field with `@chunked` without a size
+const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Compute the chunk size declared with `@chunked` (such as `@chunked` or `@chunked(65536)`), which
+/// lets clients stream the field's value in chunks. This is allowed only on `String` and `Blob`
+/// fields.
+fn compute_chunk_size(
+    field: &AstField<Typed>,
+    self_column: bool,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<usize> {
+    let annotation = field.annotations.get("chunked")?;
+
+    let inner_typ = match &field.typ {
+        AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
+        typ => typ,
+    };
+    let chunkable_type = self_column
+        && match inner_typ.to_typ(types).deref(types) {
+            Type::Primitive(primitive_type::PrimitiveType::Plain(pt)) => [
+                primitive_type::StringType::NAME,
+                primitive_type::BlobType::NAME,
+            ]
+            .contains(&pt.name()),
+            _ => false,
+        };
+
+    if !chunkable_type {
+        push_field_error(
+            field,
+            format!(
+                "@chunked is allowed only on fields of type String or Blob, but '{}' is not",
+                field.name
+            ),
+            errors,
+        );
+        return None;
+    }
+
+    match annotation {
+        AstAnnotationParams::None => Some(DEFAULT_CHUNK_SIZE),
+        AstAnnotationParams::Single(AstExpr::NumberLiteral(size, _), _)
+            if size.parse::<usize>().is_ok_and(|size| size > 0) =>
+        {
+            size.parse().ok()
+        }
+        _ => {
+            push_field_error(
+                field,
+                format!(
+                    "@chunked for field '{}' expects a positive chunk size (such as @chunked(65536))",
+                    field.name
+                ),
+                errors,
+            );
+            None
+        }
+    }
+}
+
+fn push_field_error(
+    field: &AstField<Typed>,
+    message: impl Into<String>,
+    errors: &mut Vec<Diagnostic>,
+) {
+    errors.push(Diagnostic {
+        level: Level::Error,
+        message: message.into(),
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span: field.span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    });
+}
+
+fn push_type_error(
+    _ct: &AstModel<Typed>,
+    span: Span,
+    message: impl Into<String>,
+    errors: &mut Vec<Diagnostic>,
+) {
+    errors.push(Diagnostic {
+        level: Level::Error,
+        message: message.into(),
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    });
+}
+
+fn extract_string_literal(
+    expr: &AstExpr<Typed>,
+    field: &AstField<Typed>,
+    label: &str,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    match expr {
+        AstExpr::StringLiteral(value, _) => Some(value.clone()),
+        _ => {
+            push_field_error(
+                field,
+                format!(
+                    "Computed field '{}' parameter '{}' must be a string literal",
+                    field.name, label
+                ),
+                errors,
+            );
+            None
+        }
+    }
+}
+
+fn parse_computed_field(
+    module_base_path: &Path,
+    field: &AstField<Typed>,
+    params: &AstAnnotationParams<Typed>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedComputedField> {
+    let map = match params {
+        AstAnnotationParams::Map(map, _) => map,
+        _ => {
+            push_field_error(
+                field,
+                format!(
+                    "Computed field '{}' must use map-style parameters (e.g. source = \"...\")",
+                    field.name
+                ),
+                errors,
+            );
+            return None;
+        }
+    };
+
+    let source_expr = match map.get("source") {
+        Some(expr) => expr,
+        None => {
+            push_field_error(
+                field,
+                format!(
+                    "Computed field '{}' must specify a 'source' parameter",
+                    field.name
+                ),
+                errors,
+            );
+            return None;
+        }
+    };
+
+    let source = extract_string_literal(source_expr, field, "source", errors)?;
+
+    let mut source_path = module_base_path.to_path_buf();
+    source_path.pop();
+    source_path.push(&source);
+
+    let canonical = match fs::canonicalize(&source_path) {
+        Ok(path) => path,
+        Err(_) => {
+            push_field_error(
+                field,
+                format!(
+                    "Unable to resolve computed field '{}' source file: {}",
+                    field.name,
+                    source_path.to_string_lossy()
+                ),
+                errors,
+            );
+            return None;
+        }
+    };
+
+    let function_name = match map.get("export") {
+        Some(expr) => extract_string_literal(expr, field, "export", errors)?,
+        None => field.name.clone(),
+    };
+
+    let subsystem = match map.get("subsystem") {
+        Some(expr) => Some(extract_string_literal(expr, field, "subsystem", errors)?),
+        None => Some("deno".to_string()),
+    };
+
+    let dependencies = match map.get("select") {
+        Some(AstExpr::StringList(list, _)) => list.clone(),
+        Some(AstExpr::StringLiteral(value, _)) => value
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+        Some(other) => {
+            push_field_error(
+                field,
+                format!(
+                    "Computed field '{}' select parameter must be a list of strings, found {:?}",
+                    field.name, other
+                ),
+                errors,
+            );
+            return None;
+        }
+        None => vec![],
+    };
+
+    Some(ResolvedComputedField {
+        source_path: canonical.to_string_lossy().to_string(),
+        function_name,
+        subsystem,
+        dependencies,
+    })
+}
+
+/// Parse `@requiresApproval(approver=AuthContext.role == "admin", requester="AuthContext.id")`
+fn parse_approval_annotation(
+    ct: &AstModel<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    is_json_like: bool,
+    resolved_fields: &[ResolvedField],
+    typechecked_system: &TypecheckedSystem,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedApproval> {
+    let mut error = |message: String| {
+        push_type_error(ct, annotation.span, message, errors);
+        None
+    };
+
+    if is_json_like {
+        return error(format!(
+            "Cannot use @requiresApproval on type '{}', since only types backed by a table have mutations",
+            ct.name
+        ));
+    }
+
+    // Changes are reviewed by their primary key and replayed through the type's mutations
+    if !resolved_fields.iter().any(|field| field.is_pk) {
+        return error(format!(
+            "Type '{}' must have a primary key to use @requiresApproval",
+            ct.name
+        ));
+    }
+
+    let AstAnnotationParams::Map(params, _) = &annotation.params else {
+        return error(format!(
+            "@requiresApproval on type '{}' expects map-style parameters (such as @requiresApproval(approver=AuthContext.role == \"admin\", requester=\"AuthContext.id\"))",
+            ct.name
+        ));
+    };
+
+    let approver = params.get("approver")?.clone();
+
+    let requester = match params.get("requester") {
+        Some(AstExpr::StringLiteral(requester, _)) => requester.clone(),
+        _ => {
+            return error(format!(
+                "@requiresApproval on type '{}' expects the requester as a context field (such as requester=\"AuthContext.id\")",
+                ct.name
+            ));
+        }
+    };
+
+    // The requester identifies the user making a request (recorded with each change), so it must
+    // be a field of a context
+    let requester_type = requester
+        .split_once('.')
+        .and_then(|(context_name, field_name)| {
+            match typechecked_system.types.get_by_key(context_name) {
+                Some(Type::Composite(context)) if context.kind == AstModelKind::Context => {
+                    context.fields.iter().find(|field| field.name == field_name)
+                }
+                _ => None,
+            }
+        })
+        .and_then(|field| field.typ.get_underlying_typename(&typechecked_system.types));
+
+    let requester_type = match requester_type {
+        Some(requester_type)
+            if PRIMITIVE_TYPE_PROVIDER_REGISTRY.contains_key(requester_type.as_str()) =>
+        {
+            requester_type
+        }
+        _ => {
+            return error(format!(
+                "The requester '{}' of type '{}' must be a primitive field of a context (such as \"AuthContext.id\")",
+                requester, ct.name
+            ));
+        }
+    };
+
+    Some(ResolvedApproval {
+        approver,
+        requester,
+        requester_type,
+        change_type: format!("{}Change", ct.name),
+    })
+}
+
+/// Parse `@guard(maxChangesPerDay=3)` on the fields of a type
+fn parse_field_guards(
+    ct: &AstModel<Typed>,
+    is_json_like: bool,
+    resolved_fields: &[ResolvedField],
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedFieldGuards> {
+    let declarations: Vec<_> = ct
+        .fields
+        .iter()
+        .filter_map(|field| {
+            field
+                .annotations
+                .annotations
+                .get("guard")
+                .map(|annotation| (field.name.clone(), annotation))
+        })
+        .collect();
+
+    let first_span = declarations.first()?.1.span;
+
+    if is_json_like {
+        push_type_error(
+            ct,
+            first_span,
+            format!(
+                "Cannot use @guard on fields of type '{}', since only types backed by a table have mutations",
+                ct.name
+            ),
+            errors,
+        );
+        return None;
+    }
+
+    // Changes are recorded against the primary key of the entity
+    let pk_fields: Vec<_> = resolved_fields.iter().filter(|field| field.is_pk).collect();
+    let entity_id_type = match pk_fields.as_slice() {
+        [pk_field] if pk_field.typ.innermost().is_primitive => {
+            pk_field.typ.innermost().type_name.clone()
+        }
+        _ => {
+            push_type_error(
+                ct,
+                first_span,
+                format!(
+                    "Type '{}' must have a single scalar primary key to use @guard",
+                    ct.name
+                ),
+                errors,
+            );
+            return None;
+        }
+    };
+
+    let mut guards = vec![];
+
+    for (field_name, annotation) in declarations {
+        let target = format!("field '{}.{field_name}'", ct.name);
+
+        // Only fields stored in the table's own columns are updated through mutation input
+        let guardable = resolved_fields.iter().any(|field| {
+            field.name == field_name
+                && field.self_column
+                && !field.is_pk
+                && field.typ.innermost().is_primitive
+                && field.computed.is_none()
+                && field.sql_expression.is_none()
+        });
+        if !guardable {
+            push_type_error(
+                ct,
+                annotation.span,
+                format!("Cannot use @guard on {target}, since only scalar fields can be guarded"),
+                errors,
+            );
+            continue;
+        }
+
+        let max_changes_per_day = match &annotation.params {
+            AstAnnotationParams::Map(params, _) => {
+                params.get("maxChangesPerDay").and_then(|expr| {
+                    parse_positive_integer_literal(
+                        ct,
+                        "maxChangesPerDay",
+                        expr,
+                        annotation.span,
+                        errors,
+                    )
+                })
+            }
+            _ => {
+                push_type_error(
+                    ct,
+                    annotation.span,
+                    format!(
+                        "@guard on {target} expects map-style parameters (such as @guard(maxChangesPerDay=3))"
+                    ),
+                    errors,
+                );
+                None
+            }
+        };
+
+        if let Some(max_changes_per_day) = max_changes_per_day {
+            guards.push(ResolvedFieldGuard {
+                field_name,
+                max_changes_per_day,
+            });
+        }
+    }
+
+    (!guards.is_empty()).then(|| ResolvedFieldGuards {
+        guards,
+        entity_id_type,
+        change_type: format!("{}FieldChange", ct.name),
+    })
+}
+
+/// Parse `@validate(source="validators.ts", export="validateConcert")` on the type and on its fields
+fn parse_validators(
+    ct: &AstModel<Typed>,
+    is_json_like: bool,
+    module_base_path: &Path,
+    errors: &mut Vec<Diagnostic>,
+) -> Vec<ResolvedValidator> {
+    let declarations = ct
+        .annotations
+        .annotations
+        .get("validate")
+        .map(|annotation| (None, annotation))
+        .into_iter()
+        .chain(ct.fields.iter().filter_map(|field| {
+            field
+                .annotations
+                .annotations
+                .get("validate")
+                .map(|annotation| (Some(field.name.clone()), annotation))
+        }));
+
+    let mut validators = vec![];
+
+    for (field_name, annotation) in declarations {
+        let target = match &field_name {
+            Some(field_name) => format!("field '{}.{field_name}'", ct.name),
+            None => format!("type '{}'", ct.name),
+        };
+        let mut error = |message: String| push_type_error(ct, annotation.span, message, errors);
+
+        if is_json_like {
+            error(format!(
+                "Cannot use @validate on {target}, since only types backed by a table have mutations"
+            ));
+            continue;
+        }
+
+        // Such fields are never part of mutation input
+        if let Some(field_name) = &field_name
+            && let Some(other) = ["computed", "sqlExpression"].iter().find(|other| {
+                ct.fields
+                    .iter()
+                    .any(|field| &field.name == field_name && field.annotations.contains(other))
+            })
+        {
+            error(format!(
+                "Cannot use both @validate and @{other} on {target}"
+            ));
+            continue;
+        }
+
+        let (source, function_name) = match &annotation.params {
+            AstAnnotationParams::Map(params, _) => {
+                match (params.get("source"), params.get("export")) {
+                    (
+                        Some(AstExpr::StringLiteral(source, _)),
+                        Some(AstExpr::StringLiteral(function_name, _)),
+                    ) => (source, function_name),
+                    _ => {
+                        error(format!(
+                            "@validate on {target} expects the script and the function as strings (such as @validate(source=\"validators.ts\", export=\"validateConcert\"))"
+                        ));
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                error(format!(
+                    "@validate on {target} expects map-style parameters (such as @validate(source=\"validators.ts\", export=\"validateConcert\"))"
+                ));
+                continue;
+            }
+        };
+
+        let mut source_path = module_base_path.to_path_buf();
+        source_path.pop();
+        source_path.push(source);
+
+        match fs::canonicalize(&source_path) {
+            Ok(canonical) => validators.push(ResolvedValidator {
+                field_name,
+                source_path: canonical.to_string_lossy().to_string(),
+                function_name: function_name.clone(),
+            }),
+            Err(_) => error(format!(
+                "Unable to resolve the validator script of {target}: {}",
+                source_path.to_string_lossy()
+            )),
+        }
+    }
+
+    validators
+}
+
+/// Parse `@manyToMany` (with an optional `through` join type, such as `@manyToMany(through="BookAuthor")`).
+/// The join type is resolved (or generated) once all types are known (see `apply_many_to_many_fields`).
+fn parse_many_to_many_field(
+    field: &AstField<Typed>,
+    annotation: &AstAnnotationParams<Typed>,
+    typ: &FieldType<ResolvedFieldType>,
+    has_relation_path: bool,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<ResolvedManyToMany> {
+    let mut error = |message: String| {
+        push_field_error(field, message, errors);
+        None
+    };
+
+    if has_relation_path {
+        return error(format!(
+            "Field '{}' cannot use both @manyToMany and @relationPath",
+            field.name
+        ));
+    }
+
+    let conflicting = [
+        "computed",
+        "sqlExpression",
+        "pk",
+        "column",
+        "relation",
+        "manyToOne",
+        "oneToOne",
+    ];
+    if let Some(other) = conflicting
+        .iter()
+        .find(|annotation| field.annotations.contains(annotation))
+    {
+        return error(format!(
+            "Field '{}' cannot use both @manyToMany and @{}",
+            field.name, other
+        ));
+    }
+
+    let is_composite_set = match typ {
+        FieldType::List(inner) => matches!(
+            inner.as_ref(),
+            FieldType::Plain(ResolvedFieldType {
+                is_primitive: false,
+                ..
+            })
+        ),
+        _ => false,
+    };
+    if !is_composite_set {
+        return error(format!(
+            "Field '{}' must be a set of a type (such as 'Set<Author>') to use @manyToMany",
+            field.name
+        ));
+    }
+
+    let through = match annotation {
+        AstAnnotationParams::None => None,
+        AstAnnotationParams::Map(params, _) => match params.get("through") {
+            Some(AstExpr::StringLiteral(through, _)) => Some(through.clone()),
+            _ => {
+                return error(format!(
+                    "@manyToMany for field '{}' expects the join type as a string (such as @manyToMany(through=\"BookAuthor\"))",
+                    field.name
+                ));
+            }
+        },
+        AstAnnotationParams::Single(..) => {
+            return error(format!(
+                "@manyToMany for field '{}' must use the syntax @manyToMany or @manyToMany(through=\"<type>\")",
+                field.name
+            ));
+        }
+    };
+
+    Some(ResolvedManyToMany { through })
+}
+
+/// Compute the expression declared with `@sqlExpression` (such as `@sqlExpression("price * quantity")`).
+/// The expression itself is validated against the table's columns when building the type.
+fn parse_sql_expression_field(
+    field: &AstField<Typed>,
+    annotation: &AstAnnotationParams<Typed>,
+    typ: &FieldType<ResolvedFieldType>,
+    is_json_like: bool,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let mut error = |message: String| {
+        push_field_error(field, message, errors);
+        None
+    };
+
+    if is_json_like {
+        return error(format!(
+            "Field '{}' cannot use @sqlExpression in a type with a '@json' or '@computed' annotation",
+            field.name
+        ));
+    }
+
+    let conflicting = [
+        "computed",
+        "relationPath",
+        "readDefault",
+        "pk",
+        "column",
+        "relation",
+        "dbtype",
+        "index",
+        "unique",
+        "update",
+        "storage",
+        "chunked",
+    ];
+    if let Some(other) = conflicting
+        .iter()
+        .find(|annotation| field.annotations.contains(annotation))
+    {
+        return error(format!(
+            "Field '{}' cannot use both @sqlExpression and @{}",
+            field.name, other
+        ));
+    }
+
+    if field.default_value.is_some() {
+        return error(format!(
+            "Field '{}' cannot specify a default value, since it uses @sqlExpression",
+            field.name
+        ));
+    }
+
+    let is_scalar = match typ.base_type() {
+        FieldType::Plain(ResolvedFieldType {
+            type_name,
+            is_primitive: true,
+        }) => ![
+            primitive_type::JsonType::NAME,
+            primitive_type::BlobType::NAME,
+            primitive_type::VectorType::NAME,
+        ]
+        .contains(&type_name.as_str()),
+        _ => false,
+    };
+    if !is_scalar {
+        return error(format!(
+            "@sqlExpression is allowed only on fields of a scalar type, but '{}' is not",
+            field.name
+        ));
+    }
+
+    match annotation.as_single() {
+        AstExpr::StringLiteral(expression, _) if !expression.trim().is_empty() => {
+            Some(expression.clone())
+        }
+        _ => error(format!(
+            "@sqlExpression for field '{}' must be a non-empty string",
+            field.name
+        )),
+    }
+}
+
+fn validate_computed_field_annotations(
+    field: &AstField<Typed>,
+    update_sync: bool,
+    errors: &mut Vec<Diagnostic>,
+) {
+    let disallowed = [
+        ("pk", "@pk"),
+        ("column", "@column"),
+        ("relation", "@relation"),
+        ("manyToOne", "@manyToOne"),
+        ("oneToOne", "@oneToOne"),
+        ("dbtype", "@dbtype"),
+        ("index", "@index"),
+        ("unique", "@unique"),
+        ("storage", "@storage"),
+        ("chunked", "@chunked"),
+    ];
+
+    for (annotation, label) in disallowed {
+        if field.annotations.contains(annotation) {
+            push_field_error(
+                field,
+                format!(
+                    "Computed field '{}' cannot use the {} annotation",
+                    field.name, label
+                ),
+                errors,
+            );
+        }
+    }
+
+    if field.default_value.is_some() {
+        push_field_error(
+            field,
+            format!(
+                "Computed field '{}' cannot specify a default value",
+                field.name
+            ),
+            errors,
+        );
+    }
+
+    if update_sync {
+        push_field_error(
+            field,
+            format!(
+                "Computed field '{}' cannot use the @update annotation",
+                field.name
+            ),
+            errors,
+        );
+    }
+}
+
+fn resolve_field_default_type(
+    default_value: &AstFieldDefault<Typed>,
+    field_type: &FieldType<ResolvedFieldType>,
+    errors: &mut Vec<Diagnostic>,
+) -> ResolvedFieldDefault {
+    let field_underlying_type = field_type.name();
+
+    match &default_value.kind {
+        AstFieldDefaultKind::Value(expr) => ResolvedFieldDefault::Value(Box::new(expr.to_owned())),
+        AstFieldDefaultKind::Function(fn_name, args) => match fn_name.as_str() {
+            DEFAULT_FN_AUTO_INCREMENT => {
+                if field_underlying_type != primitive_type::IntType::NAME {
+                    errors.push(Diagnostic {
+                        level: Level::Error,
+                        message: format!("{DEFAULT_FN_AUTO_INCREMENT}() can only be used on Ints"),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: default_value.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                }
+
+                match &args[..] {
+                    [] => ResolvedFieldDefault::AutoIncrement(None),
+                    [AstExpr::StringLiteral(sequence_name, _)] => {
+                        // Split the sequence name by '.' and use the last part as the sequence name
+                        match sequence_name.split('.').collect::<Vec<&str>>()[..] {
+                            [schema, name] => ResolvedFieldDefault::AutoIncrement(Some(
+                                SchemaObjectName::new(name.to_string(), Some(schema)),
+                            )),
+                            [name] => ResolvedFieldDefault::AutoIncrement(Some(
+                                SchemaObjectName::new(name.to_string(), None),
+                            )),
+                            _ => {
+                                errors.push(Diagnostic {
+                                    level: Level::Error,
+                                    message: format!(
+                                        "{}() can have arguments of the form 'schema.name' or 'name'",
+                                        DEFAULT_FN_AUTO_INCREMENT
+                                    ),
+                                    code: Some("C000".to_string()),
+                                    spans: vec![SpanLabel {
+                                        span: default_value.span,
+                                        style: SpanStyle::Primary,
+                                        label: None,
+                                    }],
+                                });
+                                ResolvedFieldDefault::AutoIncrement(None)
+                            }
+                        }
+                    }
+                    _ => {
+                        errors.push(Diagnostic {
+                            level: Level::Error,
+                            message: format!(
+                                "{}() can only have 0 (for default serial) or 1 argument (for a custom sequence name like 'schema.name')",
+                                DEFAULT_FN_AUTO_INCREMENT
+                            ),
+                            code: Some("C000".to_string()),
+                            spans: vec![SpanLabel {
+                                span: default_value.span,
+                                style: SpanStyle::Primary,
+                                label: None,
+                            }],
+                        });
+                        ResolvedFieldDefault::AutoIncrement(None)
+                    }
+                }
+            }
+            DEFAULT_FN_CURRENT_TIME => {
+                if field_underlying_type != primitive_type::InstantType::NAME
+                    && field_underlying_type != primitive_type::LocalDateType::NAME
+                    && field_underlying_type != primitive_type::LocalTimeType::NAME
+                    && field_underlying_type != primitive_type::LocalDateTimeType::NAME
+                {
+                    errors.push(Diagnostic {
+                        level: Level::Error,
+                        message: format!(
+                            "{DEFAULT_FN_CURRENT_TIME}() can only be used for time-related types"
+                        ),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: default_value.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                }
+
+                ResolvedFieldDefault::PostgresFunction("now()".to_string())
+            }
+            DEFAULT_FN_GENERATE_UUID => {
+                if field_underlying_type != primitive_type::UuidType::NAME {
+                    errors.push(Diagnostic {
+                        level: Level::Error,
+                        message: format!("{DEFAULT_FN_GENERATE_UUID}() can only be used on Uuids"),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: default_value.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                }
+
+                ResolvedFieldDefault::PostgresFunction("gen_random_uuid()".to_string())
+            }
+            DEFAULT_FN_UUID_GENERATE_V4 => {
+                if field_underlying_type != primitive_type::UuidType::NAME {
+                    errors.push(Diagnostic {
+                        level: Level::Error,
+                        message: format!(
+                            "{DEFAULT_FN_UUID_GENERATE_V4}() can only be used on Uuids"
+                        ),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: default_value.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                }
+
+                ResolvedFieldDefault::PostgresFunction("uuid_generate_v4()".to_string())
+            }
+            _ => {
+                errors.push(Diagnostic {
+                    level: Level::Error,
+                    message: format!("Unknown function specified for default value: {fn_name}"),
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: default_value.span,
+                        style: SpanStyle::Primary,
+                        label: Some("unknown function".to_string()),
+                    }],
+                });
+                // Proceed with a reasonable value. Since we already reported an error, this is not going to be used.
+                ResolvedFieldDefault::PostgresFunction(fn_name.to_string())
+            }
+        },
+    }
+}
+
+fn build_type_hint(
+    field: &AstField<Typed>,
+    types: &MappedArena<Type>,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<SerializableTypeHint> {
+    let type_name = field.typ.get_underlying_typename(types)?;
+
+    let explicit_dbtype_hint = field
+        .annotations
+        .get("dbtype")
+        .map(|p| p.as_single().as_string())
+        .map(|s| {
+            SerializableTypeHint(Box::new(ExplicitTypeHint {
+                dbtype: s.to_uppercase(),
+            }))
+        });
+
+    let primitive_hint = {
+        let type_hint_provider = PRIMITIVE_TYPE_PROVIDER_REGISTRY.get(type_name.as_str())?;
+        validate_hint_annotations(field, *type_hint_provider, errors);
+        type_hint_provider.compute_type_hint(field, errors)
+    };
+
+    // Validate that we don't have conflicting hints
+    if explicit_dbtype_hint.is_some() && primitive_hint.is_some() {
+        errors.push(Diagnostic {
+            level: Level::Error,
+            message: format!(
+                "Cannot specify both @dbtype and a primitive specific hint for {}",
+                field.name
+            ),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    }
+
+    // Return the appropriate hint (explicit takes precedence)
+    explicit_dbtype_hint.or(primitive_hint)
+}
+struct ColumnInfo {
+    names: Vec<String>,
+    self_column: bool,
+    unique_constraints: Vec<String>,
+    indices: Vec<String>,
+    cardinality: Option<Cardinality>,
+}
+
+fn compute_unique_constraints(field: &AstField<Typed>) -> Result<Vec<String>, Diagnostic> {
+    match field.annotations.get("unique") {
+        None => Ok(vec![]),
+        Some(p) => match p {
+            AstAnnotationParams::Single(expr, _) => match expr {
+                AstExpr::StringLiteral(string, _) => Ok(vec![string.clone()]),
+                AstExpr::StringList(string_list, _) => Ok(string_list.clone()),
+                _ => Err(Diagnostic {
+                    level: Level::Error,
+                    message: "Not a string nor a string list when specifying unique".to_string(),
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: field.span,
+                        style: SpanStyle::Primary,
+                        label: None,
+                    }],
+                }),
+            },
+            AstAnnotationParams::None => Ok(vec![field.name.clone()]),
+            AstAnnotationParams::Map(_, _) => Err(Diagnostic {
+                level: Level::Error,
+                message: "Cannot specify a map when specifying unique".to_string(),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            }),
+        },
+    }
+}
+
+fn compute_indices(
+    field: &AstField<Typed>,
+    enclosing_type: &AstModel<Typed>,
+) -> Result<Vec<String>, Diagnostic> {
+    let index_annotation = field.annotations.get("index");
+
+    match index_annotation {
+        None => Ok(vec![]),
+        Some(p) => match p {
+            AstAnnotationParams::Single(expr, _) => match expr {
+                AstExpr::StringLiteral(string, _) => Ok(vec![string.clone()]),
+                AstExpr::StringList(string_list, _) => Ok(string_list.clone()),
+                _ => Err(Diagnostic {
+                    level: Level::Error,
+                    message: "Not a string nor a string list when specifying index".to_string(),
+                    code: Some("C000".to_string()),
+                    spans: vec![SpanLabel {
+                        span: field.span,
+                        style: SpanStyle::Primary,
+                        label: None,
+                    }],
+                }),
+            },
+            AstAnnotationParams::None => {
+                let index_computed_name = fit_identifier(
+                    &format!("{}_{}_idx", enclosing_type.name, field.name).to_ascii_lowercase(),
+                );
+                Ok(vec![index_computed_name.clone()])
+            }
+            AstAnnotationParams::Map(_, _) => Err(Diagnostic {
+                level: Level::Error,
+                message: "Cannot specify a map when specifying index".to_string(),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            }),
+        },
+    }
+}
+
+fn compute_column_info(
+    enclosing_type: &AstModel<Typed>,
+    field: &AstField<Typed>,
+    types: &MappedArena<Type>,
+    table_managed: bool,
+    naming_strategies: &HashMap<String, NamingStrategy>,
+) -> Result<ColumnInfo, Diagnostic> {
+    let unique_constraints = compute_unique_constraints(field)?;
+    let indices = compute_indices(field, enclosing_type)?;
+    let update_sync = field.annotations.contains("update");
+    let readonly = field.annotations.contains("readonly");
+
+    if (update_sync || readonly) && field.default_value.is_none() && table_managed {
+        return Err(Diagnostic {
+            level: Level::Error,
+            message: "Fields with @readonly or @update must have a default value".to_string(),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        });
+    }
+
+    let user_supplied_column_mapping = column_annotation_mapping(field);
+
+    // Validate column mapping once to avoid duplicate errors
+    if let Some(ColumnMapping::Map(mapping)) = &user_supplied_column_mapping {
+        let field_base_type = match &field.typ {
+            AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
+            _ => &field.typ,
+        };
+        let field_type = field_base_type.to_typ(types).deref(types);
+
+        if let Type::Composite(ct) = field_type {
+            // Pre-compute field names for efficient lookups and simplify the code
+            let type_field_names: HashSet<&String> = ct.fields.iter().map(|f| &f.name).collect();
+
+            // Collect all primary key field names from the target type
+            let pk_field_names: Vec<&String> = ct
+                .fields
+                .iter()
+                .filter(|f| f.annotations.contains("pk"))
+                .map(|f| &f.name)
+                .collect();
+
+            // Check if all mapping keys correspond to actual fields in the target type
+            for (mapping_key, _) in mapping.iter() {
+                if !type_field_names.contains(mapping_key) {
+                    return Err(Diagnostic {
+                        level: Level::Error,
+                        message: format!(
+                            "Field '{}' specified in column mapping does not exist in type '{}'",
+                            mapping_key, ct.name
+                        ),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: field.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                }
+            }
+
+            // Check if all primary key fields are included in the mapping
+            for pk_field_name in &pk_field_names {
+                if !mapping.contains_key(*pk_field_name) {
+                    return Err(Diagnostic {
+                        level: Level::Error,
+                        message: format!(
+                            "Primary key field '{}' from type '{}' is missing in the column mapping",
+                            pk_field_name, ct.name
+                        ),
+                        code: Some("C000".to_string()),
+                        spans: vec![SpanLabel {
+                            span: field.span,
+                            style: SpanStyle::Primary,
+                            label: None,
+                        }],
+                    });
+                }
+            }
+        }
+    }
+
+    let naming = |type_name: &str| {
+        naming_strategies
+            .get(type_name)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let compute_column_name = |field_name: &str| match &user_supplied_column_mapping {
+        Some(ColumnMapping::Single(name)) => name.clone(),
+        _ => naming(&enclosing_type.name).column_name(field_name),
+    };
+    let enclosing_is_json = enclosing_type.annotations.contains("json");
+    let enclosing_is_computed = enclosing_type.annotations.contains("computed");
+    let enclosing_is_json_like = enclosing_is_json || enclosing_is_computed;
+
+    // The `owner` is the type declaring the field (whose naming strategy applies to its columns)
+    let id_column_names =
+        |field: &AstField<Typed>, owner: &AstModel<Typed>| -> Result<Vec<String>, Diagnostic> {
+            let user_supplied_column_mapping = column_annotation_mapping(field);
+
+            // Handle simple column name for non-composite types
+            if let Some(ColumnMapping::Single(name)) = user_supplied_column_mapping {
+                return Ok(vec![name]);
+            }
+
+            let field_base_type = match &field.typ {
+                AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
+                _ => &field.typ,
+            };
+            let field_type = field_base_type.to_typ(types).deref(types);
+
+            let naming = naming(&owner.name);
+
+            if let Type::Composite(ct) = field_type {
+                // Validation is already done upfront, no need to repeat it here
+
+                Ok(ct
+                    .fields
+                    .iter()
+                    .filter_map(|f| {
+                        if f.annotations.contains("pk") {
+                            match &user_supplied_column_mapping {
+                                Some(ColumnMapping::Map(mapping)) => {
+                                    // Use the mapping if provided
+                                    mapping.get(&f.name).cloned()
+                                }
+                                _ => {
+                                    // Use the default naming convention
+                                    Some(naming.foreign_key_column_name(&field.name, &f.name))
+                                }
+                            }
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>())
+            } else {
+                Ok(vec![])
+            }
+        };
+
+    // we can treat Optional fields as their inner type for the purposes
+    // of computing their default column name
+    let field_base_type = match &field.typ {
+        AstFieldType::Optional(inner_typ) => inner_typ.as_ref(),
+        _ => &field.typ,
+    };
+
+    match field_base_type {
+        AstFieldType::Optional(_) => {
+            // We've already unwrapped any Optional. A nested optional (e.g. venue: Venue??) doesn't make sense in our model.
+            Err(Diagnostic {
+                level: Level::Error,
+                message: "Cannot have optional of an optional".to_string(),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            })
+        }
+        AstFieldType::Plain(..) => {
+            match field_base_type.to_typ(types).deref(types) {
+                Type::Composite(field_type) => {
+                    if field_type.annotations.contains("json")
+                        || (enclosing_is_json_like && enclosing_is_computed)
+                    {
+                        return Ok(ColumnInfo {
+                            names: vec![compute_column_name(&field.name)],
+                            self_column: true,
+                            unique_constraints,
+                            indices,
+                            cardinality: None,
+                        });
+                    }
+
+                    let matching_field =
+                        get_matching_field(field, enclosing_type, &field_type, types);
+
+                    match &field.typ {
+                        AstFieldType::Optional(_) => {
+                            // If the field is optional, we need to look at the cardinality of the matching field to determine the column name.
+                            //
+                            // If the cardinality is `One` (thus forming a one-to-one relationship),
+                            // we need to use the matching field's name as the basis for the column name.
+
+                            // For example, if we have the following model, we will have a `user_id` column in the `memberships` table,
+                            // but have no column in the `users` table:
+                            //
+                            // type User {
+                            //     ...
+                            //     membership: Membership?
+                            // }
+                            // type Membership {
+                            //     ...
+                            //     user: User
+                            // }
+                            //
+                            // If the cardinality is `Unbounded`, then we need to use the field's name. For example, if we have
+                            // the following model, we will have a `venue_id` column in the `concerts` table.
+                            //
+                            // type Concert {
+                            //    ...
+                            //    venue: Venue?
+                            // }
+                            // type Venue {
+                            //    ...
+                            //    concerts: Set<Concert>
+                            // }
+
+                            let matching_field_cardinality = match matching_field {
+                                Ok(matching_field) => Ok(field_cardinality(&matching_field.typ)),
+                                Err(_) => annotation_cardinality(field),
+                            }?;
+
+                            match matching_field_cardinality {
+                                Cardinality::ZeroOrOne => Err(Diagnostic {
+                                    level: Level::Error,
+                                    message:
+                                        "Both side of one-to-one relationship cannot be optional"
+                                            .to_string(),
+                                    code: Some("C000".to_string()),
+                                    spans: vec![SpanLabel {
+                                        span: field.span,
+                                        style: SpanStyle::Primary,
+                                        label: None,
+                                    }],
+                                }),
+                                Cardinality::One => {
+                                    if user_supplied_column_mapping.is_some() {
+                                        Err(Diagnostic {
+                                                    level: Level::Error,
+                                                    message: "Cannot specify @column with the optional side of a one-to-one relationship"
+                                                        .to_string(),
+                                                    code: Some("C000".to_string()),
+                                                    spans: vec![SpanLabel {
+                                                        span: field.span,
+                                                        style: SpanStyle::Primary,
+                                                        label: None,
+                                                    }],
+                                                })
+                                    } else {
+                                        Ok(ColumnInfo {
+                                            names: id_column_names(matching_field?, &field_type)?,
+                                            self_column: false,
+                                            unique_constraints,
+                                            indices,
+                                            cardinality: Some(matching_field_cardinality),
+                                        })
+                                    }
+                                }
+                                Cardinality::Unbounded => Ok(ColumnInfo {
+                                    names: id_column_names(field, enclosing_type)?,
+                                    self_column: true,
+                                    unique_constraints,
+                                    indices,
+                                    cardinality: Some(matching_field_cardinality),
+                                }),
+                            }
+                        }
+                        AstFieldType::Plain(..) => {
+                            let matching_field_cardinality = match matching_field {
+                                Ok(matching_field) => Ok(field_cardinality(&matching_field.typ)),
+                                Err(_) => annotation_cardinality(field),
+                            }?;
+
+                            let unique_constraints =
+                                if matches!(matching_field_cardinality, Cardinality::ZeroOrOne) {
+                                    // Add an explicit unique constraint to enforce one-to-one constraint
+                                    vec![field.name.clone()]
+                                } else {
+                                    unique_constraints
+                                };
+
+                            Ok(ColumnInfo {
+                                names: id_column_names(field, enclosing_type)?,
+                                self_column: true,
+                                unique_constraints,
+                                indices,
+                                cardinality: Some(matching_field_cardinality),
+                            })
+                        }
+                    }
+                }
+                Type::Set(typ) => {
+                    if let Type::Composite(field_type) = typ.deref(types)
+                        && (field_type.annotations.contains("json")
+                            || (enclosing_is_json_like && enclosing_is_computed))
+                    {
+                        return Ok(ColumnInfo {
+                            names: vec![compute_column_name(&field.name)],
+                            self_column: true,
+                            unique_constraints,
+                            indices,
+                            cardinality: None,
+                        });
+                    }
+
+                    if let Type::Composite(field_type) = typ.deref(types) {
+                        // OneToMany
+                        let matching_field =
+                            get_matching_field(field, enclosing_type, &field_type, types)?;
+
+                        let matching_field_cardinality = field_cardinality(&matching_field.typ);
+
+                        if matching_field_cardinality == Cardinality::Unbounded {
+                            let referring_type_name = &enclosing_type.name;
+                            let referred_type_name = &field_type.name;
+                            let suggested_linking_type_name =
+                                if referring_type_name < referred_type_name {
+                                    format!("{}{}", referring_type_name, referred_type_name)
+                                } else {
+                                    format!("{}{}", referred_type_name, referring_type_name)
+                                };
+
+                            // We don't support direct many-to-many relationships (other than through `@manyToMany`)
+                            Err(Diagnostic {
+                                level: Level::Error,
+                                message: format!(
+                                    "Many-to-many relationships without a linking type are not supported. Consider annotating the fields with @manyToMany or adding a type such as '{suggested_linking_type_name}' to connect '{referring_type_name}' and '{referred_type_name}",
+                                ),
+                                code: Some("C000".to_string()),
+                                spans: vec![SpanLabel {
+                                    span: field.span,
+                                    style: SpanStyle::Primary,
+                                    label: None,
+                                }],
+                            })
+                        } else if user_supplied_column_mapping.is_some() {
+                            Err(Diagnostic {
+                                level: Level::Error,
+                                message: "Cannot specify @column with a collection field"
+                                    .to_string(),
+                                code: Some("C000".to_string()),
+                                spans: vec![SpanLabel {
+                                    span: field.span,
+                                    style: SpanStyle::Primary,
+                                    label: None,
+                                }],
+                            })
+                        } else {
+                            Ok(ColumnInfo {
+                                names: id_column_names(matching_field, &field_type)?,
+                                self_column: false,
+                                unique_constraints,
+                                indices,
+                                cardinality: Some(matching_field_cardinality),
+                            })
+                        }
+                    } else {
+                        Err(Diagnostic {
+                            level: Level::Error,
+                            message: "Sets of non-composites are not supported (consider using an `Array` instead)".to_string(),
+                            code: Some("C000".to_string()),
+                            spans: vec![SpanLabel {
+                                span: field.span,
+                                style: SpanStyle::Primary,
+                                label: None,
+                            }],
+                        })
+                    }
+                }
+                Type::Array(typ) => {
+                    // unwrap type
+                    let mut underlying_typ = &typ;
+                    while let Type::Array(t) = &**underlying_typ {
+                        underlying_typ = t;
+                    }
+
+                    let underlying_resolved = underlying_typ.deref(types);
+
+                    if let Type::Primitive(_) = &underlying_resolved {
+                        // base type is a primitive, which means this is an Array
+                        Ok(ColumnInfo {
+                            names: vec![compute_column_name(&field.name)],
+                            self_column: true,
+                            unique_constraints,
+                            indices,
+                            cardinality: None,
+                        })
+                    } else if let Type::Composite(field_type) = &underlying_resolved {
+                        if field_type.annotations.contains("json")
+                            || (enclosing_is_json_like && enclosing_is_computed)
+                        {
+                            Ok(ColumnInfo {
+                                names: vec![compute_column_name(&field.name)],
+                                self_column: true,
+                                unique_constraints,
+                                indices,
+                                cardinality: None,
+                            })
+                        } else {
+                            Err(Diagnostic {
+                                level: Level::Error,
+                                message: "Arrays of non-primitives are not supported".to_string(),
+                                code: Some("C000".to_string()),
+                                spans: vec![SpanLabel {
+                                    span: field.span,
+                                    style: SpanStyle::Primary,
+                                    label: None,
+                                }],
+                            })
+                        }
+                    } else {
+                        Err(Diagnostic {
+                            level: Level::Error,
+                            message: "Arrays of non-primitives are not supported".to_string(),
+                            code: Some("C000".to_string()),
+                            spans: vec![SpanLabel {
                                 span: field.span,
                                 style: SpanStyle::Primary,
                                 label: None,
@@ -2247,731 +4984,1188 @@ fn compute_column_info(
                         })
                     }
                 }
-                Type::Array(typ) => {
-                    // unwrap type
-                    let mut underlying_typ = &typ;
-                    while let Type::Array(t) = &**underlying_typ {
-                        underlying_typ = t;
-                    }
+                _ => Ok(ColumnInfo {
+                    names: vec![compute_column_name(&field.name)],
+                    self_column: true,
+                    unique_constraints,
+                    indices,
+                    cardinality: None,
+                }),
+            }
+        }
+    }
+}
+
+fn column_annotation_mapping(field: &AstField<Typed>) -> Option<ColumnMapping> {
+    field.annotations.get("column").and_then(|annotation| {
+        match annotation {
+            // Handle the object literal syntax: @column(mapping={zip: "azip", city: "acity"})
+            AstAnnotationParams::Map(map, _) => {
+                if let Some(AstExpr::ObjectLiteral(object_map, _)) = map.get("mapping") {
+                    // Extract string values from the object literal
+                    let mut result = HashMap::new();
+                    for (key, value) in object_map {
+                        if let AstExpr::StringLiteral(string_value, _) = value {
+                            result.insert(key.clone(), string_value.clone());
+                        }
+                    }
+                    if result.is_empty() {
+                        None
+                    } else {
+                        Some(ColumnMapping::Map(result))
+                    }
+                } else {
+                    None
+                }
+            }
+            AstAnnotationParams::Single(AstExpr::StringLiteral(s, _), _) => {
+                Some(ColumnMapping::Single(s.clone()))
+            }
+            _ => None,
+        }
+    })
+}
+
+fn get_matching_field<'a>(
+    field: &AstField<Typed>,
+    enclosing_type: &AstModel<Typed>,
+    field_type: &'a AstModel<Typed>,
+    types: &MappedArena<Type>,
+) -> Result<&'a AstField<Typed>, Diagnostic> {
+    fn relation_field_name(field: &AstField<Typed>) -> Option<String> {
+        field
+            .annotations
+            .get("relation")
+            .map(|p| p.as_single().as_string())
+    }
+
+    // Look into the type of the field. For example, while considering the `mainVenue: Venue` field in `Concert` (the enclosing type), look into the `Venue` type.
+    let matching_fields: Vec<_> = field_type
+        .fields
+        .iter()
+        .filter(|candidate_field| {
+            // The type of the field must match the enclosing type. For example, the type must match the `Concert` type (or its variation such as `Set<Concert>`)
+            let type_matches = candidate_field
+                .typ
+                .to_typ(types)
+                .get_underlying_typename(types)
+                .as_ref()
+                == Some(&enclosing_type.name);
+
+            // Ensure that relation annotation matches the field name.
+            let relation1_matches = match relation_field_name(candidate_field).as_ref() {
+                Some(relation_field_name) => relation_field_name == &field.name,
+                None => true,
+            };
+            // Check the other way around
+            let relation2_matches = match relation_field_name(field).as_ref() {
+                Some(relation_field_name) => relation_field_name == &candidate_field.name,
+                None => true,
+            };
+
+            // Both ways must match
+            let relation_field_name_matches = relation1_matches && relation2_matches;
+
+            // A many-to-many field is linked through a join type, so it is never the matching field
+            let is_many_to_many = candidate_field.annotations.contains("manyToMany");
+
+            type_matches
+                && relation_field_name_matches
+                && !is_many_to_many
+                && *candidate_field != field
+        })
+        .collect();
+
+    match &matching_fields[..] {
+        [matching_field] => Ok(matching_field),
+        [] => Err(Diagnostic {
+            level: Level::Error,
+            message: format!(
+                "Could not find the matching field of the '{}' type '{}'. Ensure that there is only one field of that type or the '@relation' annotation specifies the matching field name.",
+                enclosing_type.name, field.name
+            ),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        }),
+        _ => Err(Diagnostic {
+            level: Level::Error,
+            message: format!(
+                "Found multiple matching fields ({}) of the '{}' type when determining the matching column for '{}'. Consider using the `@relation` annotation to resolve this ambiguity.",
+                matching_fields
+                    .into_iter()
+                    .map(|f| format!("'{}'", f.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                enclosing_type.name,
+                field.name
+            ),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        }),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Cardinality {
+    ZeroOrOne,
+    One,
+    Unbounded,
+}
+
+fn field_cardinality(field_type: &AstFieldType<Typed>) -> Cardinality {
+    match field_type {
+        AstFieldType::Optional(underlying) => {
+            let underlying_cardinality = field_cardinality(underlying);
+            if underlying_cardinality == Cardinality::Unbounded {
+                Cardinality::Unbounded
+            } else {
+                Cardinality::ZeroOrOne
+            }
+        }
+        AstFieldType::Plain(_, name, ..) => {
+            if name == "Set" {
+                Cardinality::Unbounded
+            } else {
+                Cardinality::One
+            }
+        }
+    }
+}
+
+fn annotation_cardinality(field: &AstField<Typed>) -> Result<Cardinality, Diagnostic> {
+    let many_to_one = field.annotations.contains("manyToOne");
+    let one_to_one = field.annotations.contains("oneToOne");
+
+    if one_to_one && many_to_one {
+        Err(Diagnostic {
+            level: Level::Error,
+            message: "Cannot specify both @oneToOne and @manyToOne on the same field".to_string(),
+            code: Some("C000".to_string()),
+            spans: vec![SpanLabel {
+                span: field.span,
+                style: SpanStyle::Primary,
+                label: None,
+            }],
+        })
+    } else if many_to_one {
+        Ok(Cardinality::Unbounded)
+    } else if one_to_one {
+        // The field itself is a plain type, so the other side's cardinality is optional (ZeroOrOne)
+        if matches!(field.typ, AstFieldType::Plain(..)) {
+            Ok(Cardinality::ZeroOrOne)
+        } else {
+            Err(Diagnostic {
+                level: Level::Error,
+                message: "Cannot specify @oneToOne on an optional field. Either specify @manyToOne or include a matching field in the other type.".to_string(),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            })
+        }
+    } else {
+        Err(Diagnostic {
+                level: Level::Error,
+                message: "Cannot determine cardinality of field. Either specify @oneToOne or @manyToOne on the field, or add a matching field to the other type.".to_string(),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: field.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            })
+    }
+}
+
+struct TableInfo {
+    name: String,
+    schema: Option<String>,
+    managed: Option<bool>,
+}
+
+/// Given parameters for `@table(name=<table-name>, schema=<schema-name>)` extract table and schema name.
+///
+/// If a single string is provided (for example, `@table("t_name")), it is assumed to be the table name and the schema name is assumed to be `public`.
+/// If a map is provided (for example, `@table(name="t_name", schema="s_name")`), the table name is extracted from the `name` key and the schema name from the `schema` key.
+/// If a map is provided with only one key (for example, `@table(name="t_name")`), the table name is extracted from the key and the schema name is assumed to be `public`.
+///
+///
+/// If no parameters are provided, the table name is derived from the type name and the schema name is assumed to be `public`.
+///
+fn extract_table_annotation(
+    table_annotation: Option<&AstAnnotation<Typed>>,
+    type_name: &str,
+    plural_annotation_value: Option<String>,
+    naming: &NamingStrategy,
+) -> Result<TableInfo, Diagnostic> {
+    let default_table_name = || naming.table_name(type_name, plural_annotation_value.clone());
+
+    match table_annotation {
+        Some(table_annotation) => match &table_annotation.params {
+            AstAnnotationParams::Single(value, _) => Ok(TableInfo {
+                name: value.as_string(),
+                schema: None,
+                managed: None,
+            }),
+            AstAnnotationParams::Map(m, _) => {
+                let name = m
+                    .get("name")
+                    .map(|value| value.as_string())
+                    .unwrap_or_else(default_table_name);
+                let schema = m.get("schema").cloned().map(|value| value.as_string());
+                let managed = m.get("managed").cloned().map(|value| value.as_boolean());
+
+                Ok(TableInfo {
+                    name,
+                    schema,
+                    managed,
+                })
+            }
+            AstAnnotationParams::None => Err(Diagnostic {
+                level: Level::Error,
+                message: "The `@table` annotation must not be empty".to_string(),
+                code: Some("C000".to_string()),
+                spans: vec![SpanLabel {
+                    span: table_annotation.span,
+                    style: SpanStyle::Primary,
+                    label: None,
+                }],
+            }),
+        },
+        None => {
+            let name = default_table_name();
+            Ok(TableInfo {
+                name: name.clone(),
+                schema: None,
+                managed: None,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resolved_type::ResolvedFieldDefault;
+    use crate::test_util::create_resolved_system_from_src;
+
+    use core_model_builder::ast::ast_types::{AstExpr, FieldSelectionElement};
+    use exo_sql::{MaskStrategy, ReferentialAction};
+    use multiplatform_test::multiplatform_test;
+    use postgres_core_model::types::FieldMask;
+    use std::fs::File;
+
+    macro_rules! assert_resolved {
+        ($src:expr, $fn_name:expr) => {
+            let resolved = create_resolved_system_from_src($src).unwrap();
+            insta::with_settings!({sort_maps => true, prepend_module_to_snapshot => false}, {
+                #[cfg(target_family = "wasm")]
+                {
+                    let expected = include_str!(concat!("./snapshots/", $fn_name, ".snap"));
+                    let split_expected = expected.split("---\n").skip(2).collect::<Vec<&str>>().join("---");
+                    let serialized = insta::_macro_support::serialize_value(
+                        &resolved,
+                        insta::_macro_support::SerializationFormat::Yaml,
+                    );
+                    assert_eq!(split_expected, serialized);
+                }
 
-                    let underlying_resolved = underlying_typ.deref(types);
+                #[cfg(not(target_family = "wasm"))]
+                {
 
-                    if let Type::Primitive(_) = &underlying_resolved {
-                        // base type is a primitive, which means this is an Array
-                        Ok(ColumnInfo {
-                            names: vec![compute_column_name(&field.name)],
-                            self_column: true,
-                            unique_constraints,
-                            indices,
-                            cardinality: None,
-                        })
-                    } else if let Type::Composite(field_type) = &underlying_resolved {
-                        if field_type.annotations.contains("json")
-                            || (enclosing_is_json_like && enclosing_is_computed)
-                        {
-                            Ok(ColumnInfo {
-                                names: vec![compute_column_name(&field.name)],
-                                self_column: true,
-                                unique_constraints,
-                                indices,
-                                cardinality: None,
-                            })
-                        } else {
-                            Err(Diagnostic {
-                                level: Level::Error,
-                                message: "Arrays of non-primitives are not supported".to_string(),
-                                code: Some("C000".to_string()),
-                                spans: vec![SpanLabel {
-                                    span: field.span,
-                                    style: SpanStyle::Primary,
-                                    label: None,
-                                }],
-                            })
-                        }
-                    } else {
-                        Err(Diagnostic {
-                            level: Level::Error,
-                            message: "Arrays of non-primitives are not supported".to_string(),
-                            code: Some("C000".to_string()),
-                            spans: vec![SpanLabel {
-                                span: field.span,
-                                style: SpanStyle::Primary,
-                                label: None,
-                            }],
-                        })
-                    }
+                    insta::assert_yaml_snapshot!(resolved)
                 }
-                _ => Ok(ColumnInfo {
-                    names: vec![compute_column_name(&field.name)],
-                    self_column: true,
-                    unique_constraints,
-                    indices,
-                    cardinality: None,
-                }),
+            })
+        };
+    }
+
+    macro_rules! assert_resolved_err {
+        ($src:expr, $error_string:expr) => {
+            let system = create_resolved_system_from_src($src);
+            assert_eq!(system.is_err(), true, $error_string);
+        };
+    }
+
+    #[test]
+    fn with_annotations() {
+        File::create("bar.js").unwrap();
+
+        assert_resolved!(
+            r#"
+        @postgres
+        module ConcertModule {
+            @table("custom_concerts")
+            type Concert {
+              @pk @dbtype("bigint") @column("custom_id") id: Int = autoIncrement() 
+              @column("custom_title") @maxLength(12) title: String
+              @column("custom_venue_id") venue: Venue 
+              @range(min=0, max=300) reserved: Int 
+              @precision(4) time: Instant 
+              @precision(10) @scale(2) price: Decimal
+            }
+        
+            @table("venues")
+            @plural("Venuess")
+            type Venue {
+              @pk @column("custom_id") id: Int = autoIncrement() 
+              @column("custom_name") name: String 
+              concerts: Set<Concert> 
+              @bits16 capacity: Int
+              @singlePrecision latitude: Float
+            }       
+        }
+
+        @deno("bar.js")
+        module Foo {
+            export query qux(@inject exograph: Exograph, x: Int, y: String): Int
+            mutation quuz(): String
+        }
+        "#,
+            "with_annotations"
+        );
+    }
+
+    #[multiplatform_test]
+    fn with_defaults() {
+        // Note the swapped order between @pk and @dbtype to assert that our parsing logic permits any order
+        assert_resolved!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @dbtype("BIGINT") @pk  id: Int = autoIncrement() 
+              title: String 
+              @unique("unique_concert") venue: Venue 
+              attending: Array<String>
+              seating: Array<Array<Boolean>>
+            }
+
+            type Venue             {
+              @pk @dbtype("BIGINT") id: Int  = autoIncrement() 
+              name:String 
+              concerts: Set<Concert> 
+            }        
+        }
+        "#,
+            "with_defaults"
+        );
+    }
+
+    #[multiplatform_test]
+    fn with_optional_fields() {
+        assert_resolved!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+              @pk id: Int = autoIncrement() 
+              title: String 
+              venue: Venue? 
+              icon: Blob?
+            }
+
+            type Venue {
+              @pk id: Int = autoIncrement()
+              name: String
+              @column("custom_address") address: String? 
+              concerts: Set<Concert>?
+            }    
+        }
+        "#,
+            "with_optional_fields"
+        );
+    }
+
+    #[multiplatform_test]
+    fn json_collection_of_json_types() {
+        let resolved = create_resolved_system_from_src(
+            r#"
+        @postgres
+        module ContentModule {
+            @json
+            type TeamAvailableContent {
+                tags: Array<TeamAvailableContentTag>
+                lessons: Set<TeamAvailableContentLesson>
+            }
+
+            @json
+            type TeamAvailableContentTag {
+                label: String
+            }
+
+            @json
+            type TeamAvailableContentLesson {
+                id: Int
             }
         }
+        "#,
+        );
+
+        assert!(
+            resolved.is_ok(),
+            "Expected @json collection fields to resolve without errors"
+        );
     }
-}
 
-fn column_annotation_mapping(field: &AstField<Typed>) -> Option<ColumnMapping> {
-    field.annotations.get("column").and_then(|annotation| {
-        match annotation {
-            // Handle the object literal syntax: @column(mapping={zip: "azip", city: "acity"})
-            AstAnnotationParams::Map(map, _) => {
-                if let Some(AstExpr::ObjectLiteral(object_map, _)) = map.get("mapping") {
-                    // Extract string values from the object literal
-                    let mut result = HashMap::new();
-                    for (key, value) in object_map {
-                        if let AstExpr::StringLiteral(string_value, _) = value {
-                            result.insert(key.clone(), string_value.clone());
-                        }
-                    }
-                    if result.is_empty() {
-                        None
-                    } else {
-                        Some(ColumnMapping::Map(result))
-                    }
-                } else {
-                    None
-                }
+    #[multiplatform_test]
+    fn computed_collection_of_computed_types() {
+        let resolved = create_resolved_system_from_src(
+            r#"
+        @postgres
+        module ContentModule {
+            @computed
+            type TeamAvailablePlaybook {
+                tags: Array<TeamAvailablePlaybookTag>
+                lessons: Set<TeamAvailablePlaybookLesson>
             }
-            AstAnnotationParams::Single(AstExpr::StringLiteral(s, _), _) => {
-                Some(ColumnMapping::Single(s.clone()))
+
+            @computed
+            type TeamAvailablePlaybookTag {
+                label: String
+            }
+
+            @computed
+            type TeamAvailablePlaybookLesson {
+                id: Int
             }
-            _ => None,
         }
-    })
-}
+        "#,
+        );
 
-fn get_matching_field<'a>(
-    field: &AstField<Typed>,
-    enclosing_type: &AstModel<Typed>,
-    field_type: &'a AstModel<Typed>,
-    types: &MappedArena<Type>,
-) -> Result<&'a AstField<Typed>, Diagnostic> {
-    fn relation_field_name(field: &AstField<Typed>) -> Option<String> {
-        field
-            .annotations
-            .get("relation")
-            .map(|p| p.as_single().as_string())
+        assert!(
+            resolved.is_ok(),
+            "Expected @computed collection fields to resolve without errors"
+        );
     }
 
-    // Look into the type of the field. For example, while considering the `mainVenue: Venue` field in `Concert` (the enclosing type), look into the `Venue` type.
-    let matching_fields: Vec<_> = field_type
-        .fields
-        .iter()
-        .filter(|candidate_field| {
-            // The type of the field must match the enclosing type. For example, the type must match the `Concert` type (or its variation such as `Set<Concert>`)
-            let type_matches = candidate_field
-                .typ
-                .to_typ(types)
-                .get_underlying_typename(types)
-                .as_ref()
-                == Some(&enclosing_type.name);
+    #[test]
+    fn with_access() {
+        File::create("logger.js").unwrap();
 
-            // Ensure that relation annotation matches the field name.
-            let relation1_matches = match relation_field_name(candidate_field).as_ref() {
-                Some(relation_field_name) => relation_field_name == &field.name,
-                None => true,
-            };
-            // Check the other way around
-            let relation2_matches = match relation_field_name(field).as_ref() {
-                Some(relation_field_name) => relation_field_name == &candidate_field.name,
-                None => true,
-            };
+        assert_resolved!(
+            r#"
+        context AuthContext {
+            @jwt("role") role: String 
+        }
+        
+        @postgres
+        module ConcertModule {
+            @access(AuthContext.role == "ROLE_ADMIN" || self.public)
+            type Concert {
+              @pk id: Int = autoIncrement() 
+              title: String
+              public: Boolean
+            }      
 
-            // Both ways must match
-            let relation_field_name_matches = relation1_matches && relation2_matches;
 
-            type_matches && relation_field_name_matches && *candidate_field != field
-        })
-        .collect();
+            @access(true)
+            type Venue {
+              @pk id: Int = autoIncrement() 
+              name: String
+            }   
 
-    match &matching_fields[..] {
-        [matching_field] => Ok(matching_field),
-        [] => Err(Diagnostic {
-            level: Level::Error,
-            message: format!(
-                "Could not find the matching field of the '{}' type '{}'. Ensure that there is only one field of that type or the '@relation' annotation specifies the matching field name.",
-                enclosing_type.name, field.name
-            ),
-            code: Some("C000".to_string()),
-            spans: vec![SpanLabel {
-                span: field.span,
-                style: SpanStyle::Primary,
-                label: None,
-            }],
-        }),
-        _ => Err(Diagnostic {
-            level: Level::Error,
-            message: format!(
-                "Found multiple matching fields ({}) of the '{}' type when determining the matching column for '{}'. Consider using the `@relation` annotation to resolve this ambiguity.",
-                matching_fields
-                    .into_iter()
-                    .map(|f| format!("'{}'", f.name))
-                    .collect::<Vec<_>>()
-                    .join(", "),
-                enclosing_type.name,
-                field.name
-            ),
-            code: Some("C000".to_string()),
-            spans: vec![SpanLabel {
-                span: field.span,
-                style: SpanStyle::Primary,
-                label: None,
-            }],
-        }),
+            @access(false)
+            type Artist {
+              @pk id: Int = autoIncrement() 
+              name: String
+            }  
+        }
+
+        @deno("logger.js")
+        module Logger {
+            @access(AuthContext.role == "ROLE_ADMIN")
+            export query log(@inject exograph: Exograph): Boolean
+        }
+        "#,
+            "with_access"
+        );
+    }
+
+    #[multiplatform_test]
+    fn with_access_default_values() {
+        assert_resolved!(
+            r#"
+        context AuthContext {
+            @jwt role: String
+        }
+        
+        @postgres
+        module ConcertModule {
+            @access(AuthContext.role == "ROLE_ADMIN" || self.public)
+            type Concert {
+              @pk id: Int = autoIncrement() 
+              title: String
+              public: Boolean
+            }      
+        }
+        "#,
+            "with_access_default_values"
+        );
+    }
+
+    #[multiplatform_test]
+    fn field_name_variations() {
+        assert_resolved!(
+            r#"
+        @postgres
+        module EntityModule {
+            type Entity {
+              @pk _id: Int = autoIncrement()
+              title_main: String
+              title_main1: String
+              public1: Boolean
+              PUBLIC2: Boolean
+              foo123: Int
+            }
+        }"#,
+            "field_name_variations"
+        );
+    }
+
+    #[multiplatform_test]
+    fn column_names_for_non_standard_relational_field_names() {
+        assert_resolved!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+              @pk id: Int = autoIncrement()
+              title: String
+              venuex: Venue // non-standard name
+              published: Boolean
+            }
+        
+            type Venue {
+              @pk id: Int = autoIncrement()
+              name: String
+              concerts: Set<Concert>
+              published: Boolean
+            }             
+        }
+        "#,
+            "column_names_for_non_standard_relational_field_names"
+        );
     }
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub enum Cardinality {
-    ZeroOrOne,
-    One,
-    Unbounded,
-}
 
-fn field_cardinality(field_type: &AstFieldType<Typed>) -> Cardinality {
-    match field_type {
-        AstFieldType::Optional(underlying) => {
-            let underlying_cardinality = field_cardinality(underlying);
-            if underlying_cardinality == Cardinality::Unbounded {
-                Cardinality::Unbounded
-            } else {
-                Cardinality::ZeroOrOne
+    #[multiplatform_test]
+    fn with_multiple_matching_field_no_column_annotation() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement() 
+                title: String 
+                //@column("ticket_office")
+                ticket_office: Venue 
+                //@column("main")
+                main: Venue 
             }
+          
+            type Venue {
+                @pk id: Int = autoIncrement() 
+                name:String 
+                //@column("ticket_office")
+                ticket_events: Set<Concert> 
+                //@column("main")
+                main_events: Set<Concert> 
+            }  
         }
-        AstFieldType::Plain(_, name, ..) => {
-            if name == "Set" {
-                Cardinality::Unbounded
-            } else {
-                Cardinality::One
+        "#;
+
+        let resolved = create_resolved_system_from_src(src);
+
+        assert!(resolved.is_err());
+    }
+
+    #[multiplatform_test]
+    fn column_annotation_on_collection_field() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement() 
+                title: String 
+                venue: Venue 
             }
+          
+            type Venue {
+                @pk id: Int = autoIncrement() 
+                name:String 
+                @column("concerts") concerts: Set<Concert> 
+            }  
         }
-    }
-}
+        "#;
 
-fn annotation_cardinality(field: &AstField<Typed>) -> Result<Cardinality, Diagnostic> {
-    let many_to_one = field.annotations.contains("manyToOne");
-    let one_to_one = field.annotations.contains("oneToOne");
+        let resolved = create_resolved_system_from_src(src);
 
-    if one_to_one && many_to_one {
-        Err(Diagnostic {
-            level: Level::Error,
-            message: "Cannot specify both @oneToOne and @manyToOne on the same field".to_string(),
-            code: Some("C000".to_string()),
-            spans: vec![SpanLabel {
-                span: field.span,
-                style: SpanStyle::Primary,
-                label: None,
-            }],
-        })
-    } else if many_to_one {
-        Ok(Cardinality::Unbounded)
-    } else if one_to_one {
-        // The field itself is a plain type, so the other side's cardinality is optional (ZeroOrOne)
-        if matches!(field.typ, AstFieldType::Plain(..)) {
-            Ok(Cardinality::ZeroOrOne)
-        } else {
-            Err(Diagnostic {
-                level: Level::Error,
-                message: "Cannot specify @oneToOne on an optional field. Either specify @manyToOne or include a matching field in the other type.".to_string(),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: field.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            })
-        }
-    } else {
-        Err(Diagnostic {
-                level: Level::Error,
-                message: "Cannot determine cardinality of field. Either specify @oneToOne or @manyToOne on the field, or add a matching field to the other type.".to_string(),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: field.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            })
+        assert!(resolved.is_err());
     }
-}
 
-struct TableInfo {
-    name: String,
-    schema: Option<String>,
-    managed: Option<bool>,
-}
+    #[multiplatform_test]
+    fn column_annotation_on_collectionn_field() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement() 
+                title: String 
+                venue: Venue 
+            }
+          
+            type Venue {
+                @pk id: Int = autoIncrement() 
+                name:String 
+                @column("concerts") concerts: Set<Concert>?
+            }  
+        }
+        "#;
 
-/// Given parameters for `@table(name=<table-name>, schema=<schema-name>)` extract table and schema name.
-///
-/// If a single string is provided (for example, `@table("t_name")), it is assumed to be the table name and the schema name is assumed to be `public`.
-/// If a map is provided (for example, `@table(name="t_name", schema="s_name")`), the table name is extracted from the `name` key and the schema name from the `schema` key.
-/// If a map is provided with only one key (for example, `@table(name="t_name")`), the table name is extracted from the key and the schema name is assumed to be `public`.
-///
-///
-/// If no parameters are provided, the table name is derived from the type name and the schema name is assumed to be `public`.
-///
-fn extract_table_annotation(
-    table_annotation: Option<&AstAnnotation<Typed>>,
-    type_name: &str,
-    plural_annotation_value: Option<String>,
-) -> Result<TableInfo, Diagnostic> {
-    let default_table_name = || type_name.table_name(plural_annotation_value.clone());
+        let resolved = create_resolved_system_from_src(src);
 
-    match table_annotation {
-        Some(table_annotation) => match &table_annotation.params {
-            AstAnnotationParams::Single(value, _) => Ok(TableInfo {
-                name: value.as_string(),
-                schema: None,
-                managed: None,
-            }),
-            AstAnnotationParams::Map(m, _) => {
-                let name = m
-                    .get("name")
-                    .map(|value| value.as_string())
-                    .unwrap_or_else(default_table_name);
-                let schema = m.get("schema").cloned().map(|value| value.as_string());
-                let managed = m.get("managed").cloned().map(|value| value.as_boolean());
+        assert!(resolved.is_err());
+    }
 
-                Ok(TableInfo {
-                    name,
-                    schema,
-                    managed,
-                })
+    #[multiplatform_test]
+    fn column_annotation_on_optional_side_of_one_to_one() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement() 
+                title: String 
+                venue: Venue 
             }
-            AstAnnotationParams::None => Err(Diagnostic {
-                level: Level::Error,
-                message: "The `@table` annotation must not be empty".to_string(),
-                code: Some("C000".to_string()),
-                spans: vec![SpanLabel {
-                    span: table_annotation.span,
-                    style: SpanStyle::Primary,
-                    label: None,
-                }],
-            }),
-        },
-        None => {
-            let name = default_table_name();
-            Ok(TableInfo {
-                name: name.clone(),
-                schema: None,
-                managed: None,
-            })
+          
+            type Venue {
+                @pk id: Int = autoIncrement() 
+                name:String 
+                @column("concert") concert: Concert?
+            }  
         }
-    }
-}
+        "#;
 
-#[cfg(test)]
-mod tests {
-    use crate::resolved_type::ResolvedFieldDefault;
-    use crate::test_util::create_resolved_system_from_src;
+        let resolved = create_resolved_system_from_src(src);
 
-    use core_model_builder::ast::ast_types::{AstExpr, FieldSelectionElement};
-    use multiplatform_test::multiplatform_test;
-    use std::fs::File;
+        assert!(resolved.is_err());
+    }
 
-    macro_rules! assert_resolved {
-        ($src:expr, $fn_name:expr) => {
-            let resolved = create_resolved_system_from_src($src).unwrap();
-            insta::with_settings!({sort_maps => true, prepend_module_to_snapshot => false}, {
-                #[cfg(target_family = "wasm")]
-                {
-                    let expected = include_str!(concat!("./snapshots/", $fn_name, ".snap"));
-                    let split_expected = expected.split("---\n").skip(2).collect::<Vec<&str>>().join("---");
-                    let serialized = insta::_macro_support::serialize_value(
-                        &resolved,
-                        insta::_macro_support::SerializationFormat::Yaml,
-                    );
-                    assert_eq!(split_expected, serialized);
-                }
+    #[multiplatform_test]
+    fn ownership_annotation_enforces_owner_bindings() {
+        let src = r#"
+        context AuthContext {
+            @jwt("sub") userId: Int
+            @jwt("role") role: String
+        }
+
+        @postgres
+        module OwnershipModule {
+            @ownership(
+                field = "ownerId",
+                context = "AuthContext.userId",
+                restrictRoles = "coach",
+                exemptRoles = "admin"
+            )
+            type Note {
+                @pk id: Int = autoIncrement()
+                ownerId: Int
+                body: String
+            }
+        }
+        "#;
 
-                #[cfg(not(target_family = "wasm"))]
-                {
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let note_type = resolved.get_by_key("Note").unwrap().as_composite();
 
-                    insta::assert_yaml_snapshot!(resolved)
+        let owner_field = note_type
+            .fields
+            .iter()
+            .find(|field| field.name == "ownerId")
+            .expect("ownerId field exists");
+        assert!(
+            owner_field.readonly,
+            "ownership auto-assign should mark the field as readonly"
+        );
+        match owner_field
+            .default_value
+            .as_ref()
+            .expect("ownership should provide a default")
+        {
+            ResolvedFieldDefault::Value(expr) => match expr.as_ref() {
+                AstExpr::FieldSelection(selection) => {
+                    let path: Vec<String> = selection
+                        .path()
+                        .iter()
+                        .map(|element| match element {
+                            FieldSelectionElement::Identifier(name, _, _) => name.clone(),
+                            _ => panic!("unexpected non-identifier in context path"),
+                        })
+                        .collect();
+                    assert_eq!(path, vec!["AuthContext".to_string(), "userId".to_string()]);
                 }
-            })
-        };
-    }
+                other => panic!("unexpected default expression: {:?}", other),
+            },
+            other => panic!("unexpected default variant: {:?}", other),
+        }
 
-    macro_rules! assert_resolved_err {
-        ($src:expr, $error_string:expr) => {
-            let system = create_resolved_system_from_src($src);
-            assert_eq!(system.is_err(), true, $error_string);
-        };
+        assert!(
+            note_type.access.creation.is_some(),
+            "creation guard should be generated"
+        );
+        assert!(
+            note_type.access.update.is_some(),
+            "update guard should be generated"
+        );
+        assert!(
+            note_type.access.delete.is_some(),
+            "delete guard should be generated"
+        );
+        assert!(note_type.ownership_transfer.is_none());
     }
 
-    #[test]
-    fn with_annotations() {
-        File::create("bar.js").unwrap();
-
-        assert_resolved!(
-            r#"
+    #[multiplatform_test]
+    fn referential_actions() {
+        let src = r#"
         @postgres
         module ConcertModule {
-            @table("custom_concerts")
             type Concert {
-              @pk @dbtype("bigint") @column("custom_id") id: Int = autoIncrement() 
-              @column("custom_title") @maxLength(12) title: String
-              @column("custom_venue_id") venue: Venue 
-              @range(min=0, max=300) reserved: Int 
-              @precision(4) time: Instant 
-              @precision(10) @scale(2) price: Decimal
+                @pk id: Int = autoIncrement()
+                @onDelete("cascade") venue: Venue
+                @onDelete("setNull") @onUpdate("cascade") mainArtist: Artist?
             }
-        
-            @table("venues")
-            @plural("Venuess")
+
             type Venue {
-              @pk @column("custom_id") id: Int = autoIncrement() 
-              @column("custom_name") name: String 
-              concerts: Set<Concert> 
-              @bits16 capacity: Int
-              @singlePrecision latitude: Float
-            }       
-        }
+                @pk id: Int = autoIncrement()
+                concerts: Set<Concert>?
+            }
 
-        @deno("bar.js")
-        module Foo {
-            export query qux(@inject exograph: Exograph, x: Int, y: String): Int
-            mutation quuz(): String
+            type Artist {
+                @pk id: Int = autoIncrement()
+                concerts: Set<Concert>?
+            }
         }
-        "#,
-            "with_annotations"
-        );
+        "#;
+
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let concert_type = resolved.get_by_key("Concert").unwrap().as_composite();
+        let field = |name: &str| {
+            concert_type
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .unwrap()
+        };
+
+        assert_eq!(field("venue").on_delete, ReferentialAction::Cascade);
+        assert_eq!(field("venue").on_update, ReferentialAction::NoAction);
+        assert_eq!(field("mainArtist").on_delete, ReferentialAction::SetNull);
+        assert_eq!(field("mainArtist").on_update, ReferentialAction::Cascade);
     }
 
     #[multiplatform_test]
-    fn with_defaults() {
-        // Note the swapped order between @pk and @dbtype to assert that our parsing logic permits any order
-        assert_resolved!(
-            r#"
+    fn referential_actions_invalid() {
+        let set_null_on_required = r#"
         @postgres
         module ConcertModule {
             type Concert {
-                @dbtype("BIGINT") @pk  id: Int = autoIncrement() 
-              title: String 
-              @unique("unique_concert") venue: Venue 
-              attending: Array<String>
-              seating: Array<Array<Boolean>>
+                @pk id: Int = autoIncrement()
+                @onDelete("setNull") venue: Venue
             }
 
-            type Venue             {
-              @pk @dbtype("BIGINT") id: Int  = autoIncrement() 
-              name:String 
-              concerts: Set<Concert> 
-            }        
+            type Venue {
+                @pk id: Int = autoIncrement()
+                concerts: Set<Concert>?
+            }
         }
-        "#,
-            "with_defaults"
-        );
-    }
+        "#;
+        assert!(create_resolved_system_from_src(set_null_on_required).is_err());
 
-    #[multiplatform_test]
-    fn with_optional_fields() {
-        assert_resolved!(
-            r#"
+        let on_collection = r#"
         @postgres
         module ConcertModule {
             type Concert {
-              @pk id: Int = autoIncrement() 
-              title: String 
-              venue: Venue? 
-              icon: Blob?
+                @pk id: Int = autoIncrement()
+                venue: Venue
             }
 
             type Venue {
-              @pk id: Int = autoIncrement()
-              name: String
-              @column("custom_address") address: String? 
-              concerts: Set<Concert>?
-            }    
+                @pk id: Int = autoIncrement()
+                @onDelete("cascade") concerts: Set<Concert>?
+            }
         }
-        "#,
-            "with_optional_fields"
-        );
+        "#;
+        assert!(create_resolved_system_from_src(on_collection).is_err());
     }
 
     #[multiplatform_test]
-    fn json_collection_of_json_types() {
-        let resolved = create_resolved_system_from_src(
-            r#"
+    fn read_defaults() {
+        let src = r#"
         @postgres
-        module ContentModule {
-            @json
-            type TeamAvailableContent {
-                tags: Array<TeamAvailableContentTag>
-                lessons: Set<TeamAvailableContentLesson>
-            }
-
-            @json
-            type TeamAvailableContentTag {
-                label: String
-            }
-
-            @json
-            type TeamAvailableContentLesson {
-                id: Int
+        module TodoModule {
+            type Todo {
+                @pk id: Int = autoIncrement()
+                @readDefault("Untitled") title: String?
+                @readDefault(0) priority: Int?
+                @readDefault(false) completed: Boolean?
+                notes: String?
             }
         }
-        "#,
-        );
+        "#;
 
-        assert!(
-            resolved.is_ok(),
-            "Expected @json collection fields to resolve without errors"
-        );
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let todo_type = resolved.get_by_key("Todo").unwrap().as_composite();
+        let read_default = |name: &str| {
+            todo_type
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .unwrap()
+                .read_default
+                .clone()
+        };
+
+        assert_eq!(read_default("title"), Some("Untitled".to_string()));
+        assert_eq!(read_default("priority"), Some("0".to_string()));
+        assert_eq!(read_default("completed"), Some("false".to_string()));
+        assert_eq!(read_default("notes"), None);
     }
 
     #[multiplatform_test]
-    fn computed_collection_of_computed_types() {
-        let resolved = create_resolved_system_from_src(
-            r#"
+    fn read_defaults_invalid() {
+        let on_required = r#"
         @postgres
-        module ContentModule {
-            @computed
-            type TeamAvailablePlaybook {
-                tags: Array<TeamAvailablePlaybookTag>
-                lessons: Set<TeamAvailablePlaybookLesson>
-            }
-
-            @computed
-            type TeamAvailablePlaybookTag {
-                label: String
+        module TodoModule {
+            type Todo {
+                @pk id: Int = autoIncrement()
+                @readDefault("Untitled") title: String
             }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(on_required).is_err());
 
-            @computed
-            type TeamAvailablePlaybookLesson {
-                id: Int
+        let mismatched_type = r#"
+        @postgres
+        module TodoModule {
+            type Todo {
+                @pk id: Int = autoIncrement()
+                @readDefault("none") priority: Int?
             }
         }
-        "#,
-        );
-
-        assert!(
-            resolved.is_ok(),
-            "Expected @computed collection fields to resolve without errors"
-        );
-    }
-
-    #[test]
-    fn with_access() {
-        File::create("logger.js").unwrap();
+        "#;
+        assert!(create_resolved_system_from_src(mismatched_type).is_err());
 
-        assert_resolved!(
-            r#"
-        context AuthContext {
-            @jwt("role") role: String 
-        }
-        
+        let on_relation = r#"
         @postgres
-        module ConcertModule {
-            @access(AuthContext.role == "ROLE_ADMIN" || self.public)
-            type Concert {
-              @pk id: Int = autoIncrement() 
-              title: String
-              public: Boolean
-            }      
-
-
-            @access(true)
-            type Venue {
-              @pk id: Int = autoIncrement() 
-              name: String
-            }   
+        module TodoModule {
+            type Todo {
+                @pk id: Int = autoIncrement()
+                @readDefault("none") owner: User?
+            }
 
-            @access(false)
-            type Artist {
-              @pk id: Int = autoIncrement() 
-              name: String
-            }  
+            type User {
+                @pk id: Int = autoIncrement()
+                todos: Set<Todo>?
+            }
         }
+        "#;
+        assert!(create_resolved_system_from_src(on_relation).is_err());
+    }
 
-        @deno("logger.js")
-        module Logger {
-            @access(AuthContext.role == "ROLE_ADMIN")
-            export query log(@inject exograph: Exograph): Boolean
+    #[multiplatform_test]
+    fn masks() {
+        let src = r#"
+        @postgres
+        module CustomerModule {
+            type Customer {
+                @pk id: Int = autoIncrement()
+                @mask(roles: ["support"], strategy: "last4") cardNumber: String
+                @mask(roles: ["support", "analyst"], strategy: "email") email: String
+                @mask(roles: ["analyst"], strategy: "null") age: Int?
+                @mask(roles: ["analyst"]) name: String
+                notes: String?
+            }
         }
-        "#,
-            "with_access"
+        "#;
+
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let customer_type = resolved.get_by_key("Customer").unwrap().as_composite();
+        let mask = |name: &str| {
+            customer_type
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .unwrap()
+                .mask
+                .clone()
+        };
+
+        assert_eq!(
+            mask("cardNumber"),
+            Some(FieldMask {
+                roles: vec!["support".to_string()],
+                strategy: MaskStrategy::Last4
+            })
+        );
+        assert_eq!(
+            mask("email"),
+            Some(FieldMask {
+                roles: vec!["support".to_string(), "analyst".to_string()],
+                strategy: MaskStrategy::Email
+            })
+        );
+        assert_eq!(
+            mask("age"),
+            Some(FieldMask {
+                roles: vec!["analyst".to_string()],
+                strategy: MaskStrategy::Null
+            })
+        );
+        assert_eq!(
+            mask("name"),
+            Some(FieldMask {
+                roles: vec!["analyst".to_string()],
+                strategy: MaskStrategy::Full
+            })
         );
+        assert_eq!(mask("notes"), None);
     }
 
     #[multiplatform_test]
-    fn with_access_default_values() {
-        assert_resolved!(
-            r#"
-        context AuthContext {
-            @jwt role: String
+    fn masks_invalid() {
+        let unknown_strategy = r#"
+        @postgres
+        module CustomerModule {
+            type Customer {
+                @pk id: Int = autoIncrement()
+                @mask(roles: ["support"], strategy: "hash") cardNumber: String
+            }
         }
-        
+        "#;
+        assert!(create_resolved_system_from_src(unknown_strategy).is_err());
+
+        let non_string = r#"
         @postgres
-        module ConcertModule {
-            @access(AuthContext.role == "ROLE_ADMIN" || self.public)
-            type Concert {
-              @pk id: Int = autoIncrement() 
-              title: String
-              public: Boolean
-            }      
+        module CustomerModule {
+            type Customer {
+                @pk id: Int = autoIncrement()
+                @mask(roles: ["support"], strategy: "last4") age: Int
+            }
         }
-        "#,
-            "with_access_default_values"
-        );
+        "#;
+        assert!(create_resolved_system_from_src(non_string).is_err());
+
+        let null_on_required = r#"
+        @postgres
+        module CustomerModule {
+            type Customer {
+                @pk id: Int = autoIncrement()
+                @mask(roles: ["support"], strategy: "null") name: String
+            }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(null_on_required).is_err());
     }
 
     #[multiplatform_test]
-    fn field_name_variations() {
-        assert_resolved!(
-            r#"
+    fn chunked_fields() {
+        let src = r#"
         @postgres
-        module EntityModule {
-            type Entity {
-              @pk _id: Int = autoIncrement()
-              title_main: String
-              title_main1: String
-              public1: Boolean
-              PUBLIC2: Boolean
-              foo123: Int
+        module DocumentModule {
+            type Document {
+                @pk id: Int = autoIncrement()
+                @chunked content: String
+                @chunked(65536) attachment: Blob?
+                title: String
             }
-        }"#,
-            "field_name_variations"
-        );
+        }
+        "#;
+
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let document_type = resolved.get_by_key("Document").unwrap().as_composite();
+        let chunk_size = |name: &str| {
+            document_type
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .unwrap()
+                .chunk_size
+        };
+
+        assert_eq!(chunk_size("content"), Some(super::DEFAULT_CHUNK_SIZE));
+        assert_eq!(chunk_size("attachment"), Some(65536));
+        assert_eq!(chunk_size("title"), None);
     }
 
     #[multiplatform_test]
-    fn column_names_for_non_standard_relational_field_names() {
-        assert_resolved!(
-            r#"
+    fn chunked_fields_invalid() {
+        let non_string = r#"
         @postgres
-        module ConcertModule {
-            type Concert {
-              @pk id: Int = autoIncrement()
-              title: String
-              venuex: Venue // non-standard name
-              published: Boolean
+        module DocumentModule {
+            type Document {
+                @pk id: Int = autoIncrement()
+                @chunked pageCount: Int
             }
-        
-            type Venue {
-              @pk id: Int = autoIncrement()
-              name: String
-              concerts: Set<Concert>
-              published: Boolean
-            }             
         }
-        "#,
-            "column_names_for_non_standard_relational_field_names"
-        );
+        "#;
+        assert!(create_resolved_system_from_src(non_string).is_err());
+
+        let zero_size = r#"
+        @postgres
+        module DocumentModule {
+            type Document {
+                @pk id: Int = autoIncrement()
+                @chunked(0) content: String
+            }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(zero_size).is_err());
+
+        let with_sql_expression = r#"
+        @postgres
+        module DocumentModule {
+            type Document {
+                @pk id: Int = autoIncrement()
+                title: String
+                @chunked @sqlExpression("upper(title)") upperTitle: String
+            }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(with_sql_expression).is_err());
     }
 
     #[multiplatform_test]
-    fn with_multiple_matching_field_no_column_annotation() {
+    fn opaque_ids() {
         let src = r#"
         @postgres
         module ConcertModule {
+            @opaqueId
             type Concert {
-                @pk id: Int = autoIncrement() 
-                title: String 
-                //@column("ticket_office")
-                ticket_office: Venue 
-                //@column("main")
-                main: Venue 
+                @pk id: Int = autoIncrement()
+                title: String
             }
-          
+
             type Venue {
-                @pk id: Int = autoIncrement() 
-                name:String 
-                //@column("ticket_office")
-                ticket_events: Set<Concert> 
-                //@column("main")
-                main_events: Set<Concert> 
-            }  
+                @pk id: Int = autoIncrement()
+                name: String
+            }
         }
         "#;
 
-        let resolved = create_resolved_system_from_src(src);
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let opaque_id = |name: &str| resolved.get_by_key(name).unwrap().as_composite().opaque_id;
 
-        assert!(resolved.is_err());
+        assert!(opaque_id("Concert"));
+        assert!(!opaque_id("Venue"));
     }
 
     #[multiplatform_test]
-    fn column_annotation_on_collection_field() {
-        let src = r#"
+    fn opaque_ids_invalid() {
+        let string_pk = r#"
         @postgres
         module ConcertModule {
+            @opaqueId
             type Concert {
-                @pk id: Int = autoIncrement() 
-                title: String 
-                venue: Venue 
+                @pk id: String
             }
-          
-            type Venue {
-                @pk id: Int = autoIncrement() 
-                name:String 
-                @column("concerts") concerts: Set<Concert> 
-            }  
         }
         "#;
+        assert!(create_resolved_system_from_src(string_pk).is_err());
 
-        let resolved = create_resolved_system_from_src(src);
+        let bits64_pk = r#"
+        @postgres
+        module ConcertModule {
+            @opaqueId
+            type Concert {
+                @pk @bits64 id: Int = autoIncrement()
+            }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(bits64_pk).is_err());
 
-        assert!(resolved.is_err());
+        let composite_pk = r#"
+        @postgres
+        module ConcertModule {
+            @opaqueId
+            type Concert {
+                @pk id: Int
+                @pk edition: Int
+            }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(composite_pk).is_err());
     }
 
     #[multiplatform_test]
-    fn column_annotation_on_collectionn_field() {
+    fn sql_expressions() {
         let src = r#"
         @postgres
-        module ConcertModule {
-            type Concert {
-                @pk id: Int = autoIncrement() 
-                title: String 
-                venue: Venue 
+        module OrderModule {
+            type LineItem {
+                @pk id: Int = autoIncrement()
+                price: Float
+                quantity: Int
+                @sqlExpression("price * quantity") total: Float
             }
-          
-            type Venue {
-                @pk id: Int = autoIncrement() 
-                name:String 
-                @column("concerts") concerts: Set<Concert>?
-            }  
         }
         "#;
 
-        let resolved = create_resolved_system_from_src(src);
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let line_item_type = resolved.get_by_key("LineItem").unwrap().as_composite();
+        let total = line_item_type
+            .fields
+            .iter()
+            .find(|field| field.name == "total")
+            .unwrap();
 
-        assert!(resolved.is_err());
+        assert_eq!(total.sql_expression, Some("price * quantity".to_string()));
+        assert!(total.readonly);
+        assert!(total.column_names.is_empty());
     }
 
     #[multiplatform_test]
-    fn column_annotation_on_optional_side_of_one_to_one() {
-        let src = r#"
+    fn sql_expressions_invalid() {
+        let with_default = r#"
         @postgres
-        module ConcertModule {
-            type Concert {
-                @pk id: Int = autoIncrement() 
-                title: String 
-                venue: Venue 
+        module OrderModule {
+            type LineItem {
+                @pk id: Int = autoIncrement()
+                price: Float
+                @sqlExpression("price * 2") total: Float = 0
             }
-          
-            type Venue {
-                @pk id: Int = autoIncrement() 
-                name:String 
-                @column("concert") concert: Concert?
-            }  
         }
         "#;
+        assert!(create_resolved_system_from_src(with_default).is_err());
 
-        let resolved = create_resolved_system_from_src(src);
+        let on_relation = r#"
+        @postgres
+        module OrderModule {
+            type LineItem {
+                @pk id: Int = autoIncrement()
+                @sqlExpression("order_id") order: Order
+            }
 
-        assert!(resolved.is_err());
+            type Order {
+                @pk id: Int = autoIncrement()
+                items: Set<LineItem>
+            }
+        }
+        "#;
+        assert!(create_resolved_system_from_src(on_relation).is_err());
     }
 
     #[multiplatform_test]
-    fn ownership_annotation_enforces_owner_bindings() {
+    fn ownership_annotation_with_transfer() {
         let src = r#"
         context AuthContext {
             @jwt("sub") userId: Int
-            @jwt("role") role: String
         }
 
         @postgres
@@ -2979,8 +6173,7 @@ mod tests {
             @ownership(
                 field = "ownerId",
                 context = "AuthContext.userId",
-                restrictRoles = "coach",
-                exemptRoles = "admin"
+                transfer = self.ownerId == AuthContext.userId
             )
             type Note {
                 @pk id: Int = autoIncrement()
@@ -2993,49 +6186,140 @@ mod tests {
         let resolved = create_resolved_system_from_src(src).expect("should resolve");
         let note_type = resolved.get_by_key("Note").unwrap().as_composite();
 
-        let owner_field = note_type
+        let transfer = note_type
+            .ownership_transfer
+            .as_ref()
+            .expect("transfer should be declared");
+        assert_eq!(transfer.field, "ownerId");
+        assert!(matches!(transfer.access, AstExpr::RelationalOp(_)));
+    }
+
+    #[multiplatform_test]
+    fn approval_annotation() {
+        let src = r#"
+        context AuthContext {
+            @jwt("sub") id: Int
+            @jwt role: String
+        }
+
+        @postgres
+        module PayoutModule {
+            @requiresApproval(approver=AuthContext.role == "lead", requester="AuthContext.id")
+            type Payout {
+                @pk id: Int = autoIncrement()
+                amount: Int
+            }
+        }
+        "#;
+
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let payout_type = resolved.get_by_key("Payout").unwrap().as_composite();
+
+        let approval = payout_type
+            .approval
+            .as_ref()
+            .expect("approval should be declared");
+        assert_eq!(approval.change_type, "PayoutChange");
+        assert_eq!(approval.requester_type, "Int");
+
+        let change_type = resolved.get_by_key("PayoutChange").unwrap().as_composite();
+        let field_names: Vec<_> = change_type
             .fields
             .iter()
-            .find(|field| field.name == "ownerId")
-            .expect("ownerId field exists");
-        assert!(
-            owner_field.readonly,
-            "ownership auto-assign should mark the field as readonly"
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(
+            field_names,
+            vec![
+                "id",
+                "mutation",
+                "arguments",
+                "preview",
+                "status",
+                "requestedBy",
+                "requestedAt",
+                "reviewedBy",
+                "reviewedAt"
+            ]
         );
-        match owner_field
-            .default_value
-            .as_ref()
-            .expect("ownership should provide a default")
-        {
-            ResolvedFieldDefault::Value(expr) => match expr.as_ref() {
-                AstExpr::FieldSelection(selection) => {
-                    let path: Vec<String> = selection
-                        .path()
-                        .iter()
-                        .map(|element| match element {
-                            FieldSelectionElement::Identifier(name, _, _) => name.clone(),
-                            _ => panic!("unexpected non-identifier in context path"),
-                        })
-                        .collect();
-                    assert_eq!(path, vec!["AuthContext".to_string(), "userId".to_string()]);
-                }
-                other => panic!("unexpected default expression: {:?}", other),
-            },
-            other => panic!("unexpected default variant: {:?}", other),
+    }
+
+    #[multiplatform_test]
+    fn approval_annotation_without_pk() {
+        let src = r#"
+        context AuthContext {
+            @jwt("sub") id: Int
+        }
+
+        @postgres
+        module PayoutModule {
+            @requiresApproval(approver=true, requester="AuthContext.id")
+            type Payout {
+                amount: Int
+            }
+        }
+        "#;
+
+        assert!(create_resolved_system_from_src(src).is_err());
+    }
+
+    #[multiplatform_test]
+    fn field_guard_annotation() {
+        let src = r#"
+        @postgres
+        module UserModule {
+            type User {
+                @pk id: Int = autoIncrement()
+                @guard(maxChangesPerDay=3) email: String
+                name: String
+            }
         }
+        "#;
 
-        assert!(
-            note_type.access.creation.is_some(),
-            "creation guard should be generated"
-        );
-        assert!(
-            note_type.access.update.is_some(),
-            "update guard should be generated"
-        );
-        assert!(
-            note_type.access.delete.is_some(),
-            "delete guard should be generated"
-        );
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let user_type = resolved.get_by_key("User").unwrap().as_composite();
+
+        let field_guards = user_type
+            .field_guards
+            .as_ref()
+            .expect("guards should be declared");
+        assert_eq!(field_guards.change_type, "UserFieldChange");
+        assert_eq!(field_guards.entity_id_type, "Int");
+        assert_eq!(field_guards.guards.len(), 1);
+        assert_eq!(field_guards.guards[0].field_name, "email");
+        assert_eq!(field_guards.guards[0].max_changes_per_day, 3);
+
+        let change_type = resolved
+            .get_by_key("UserFieldChange")
+            .unwrap()
+            .as_composite();
+        assert!(!change_type.root_visible);
+        let field_names: Vec<_> = change_type
+            .fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(field_names, vec!["id", "entityId", "field", "changedAt"]);
+    }
+
+    #[multiplatform_test]
+    fn field_guard_on_relation() {
+        let src = r#"
+        @postgres
+        module UserModule {
+            type User {
+                @pk id: Int = autoIncrement()
+                @guard(maxChangesPerDay=3) team: Team
+            }
+
+            type Team {
+                @pk id: Int = autoIncrement()
+                users: Set<User>?
+            }
+        }
+        "#;
+
+        assert!(create_resolved_system_from_src(src).is_err());
     }
 
     #[multiplatform_test]
@@ -3103,6 +6387,210 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn naming_strategies() {
+        let src = r#"
+        @postgres(naming="preserve", pluralizeTables=false, tablePrefix="app_")
+        module ConcertModule {
+            type ConcertInfo {
+                @pk concertId: Int = autoIncrement()
+                mainTitle: String
+                mainVenue: Venue
+            }
+
+            @table(naming="snake_case", pluralize=true, prefix="")
+            type Venue {
+                @pk venueId: Int = autoIncrement()
+                @column("venue_name") name: String
+                concerts: Set<ConcertInfo>?
+            }
+
+            @table(name="artists")
+            type Artist {
+                @pk id: Int = autoIncrement()
+                stageName: String
+            }
+        }
+        "#;
+
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let composite = |name: &str| resolved.get_by_key(name).unwrap().as_composite();
+        let column_names = |type_name: &str, field_name: &str| {
+            composite(type_name)
+                .fields
+                .iter()
+                .find(|field| field.name == field_name)
+                .unwrap()
+                .column_names
+                .clone()
+        };
+
+        assert_eq!(composite("ConcertInfo").table_name.name, "app_ConcertInfo");
+        assert_eq!(column_names("ConcertInfo", "mainTitle"), vec!["mainTitle"]);
+        assert_eq!(
+            column_names("ConcertInfo", "mainVenue"),
+            vec!["mainVenueVenueId"]
+        );
+
+        // The type-level strategy overrides the module-level one
+        assert_eq!(composite("Venue").table_name.name, "venues");
+        assert_eq!(column_names("Venue", "venueId"), vec!["venue_id"]);
+        assert_eq!(column_names("Venue", "name"), vec!["venue_name"]);
+        // The column is in the `ConcertInfo` table, so it follows that type's strategy
+        assert_eq!(column_names("Venue", "concerts"), vec!["mainVenueVenueId"]);
+
+        // An explicit table name isn't affected by the strategy (but its columns are)
+        assert_eq!(composite("Artist").table_name.name, "artists");
+        assert_eq!(column_names("Artist", "stageName"), vec!["stageName"]);
+    }
+
+    #[multiplatform_test]
+    fn invalid_naming_strategies() {
+        assert_resolved_err!(
+            r#"
+        @postgres(naming="kebab-case")
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+            }
+        }
+        "#,
+            "Unknown naming case should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            @table(pluralize="yes")
+            type Concert {
+                @pk id: Int = autoIncrement()
+            }
+        }
+        "#,
+            "Non-boolean pluralize should be rejected"
+        );
+    }
+
+    #[multiplatform_test]
+    fn long_derived_identifiers_are_shortened() {
+        let src = r#"
+        @postgres
+        module ConcertModule {
+            type ConcertPerformanceScheduleWithRehearsalAndSoundCheckDetail {
+                @pk id: Int = autoIncrement()
+                @index primaryRehearsalAndSoundCheckCoordinatorName: String
+                limit: Int
+            }
+        }
+        "#;
+
+        let resolved = create_resolved_system_from_src(src).expect("should resolve");
+        let composite = resolved
+            .get_by_key("ConcertPerformanceScheduleWithRehearsalAndSoundCheckDetail")
+            .unwrap()
+            .as_composite();
+
+        assert!(composite.table_name.name.len() <= 63);
+        assert!(
+            composite
+                .table_name
+                .name
+                .starts_with("concert_performance_schedule_with_rehearsal")
+        );
+
+        let field = &composite.fields[1];
+        assert!(field.indices[0].len() <= 63);
+        assert_eq!(
+            field.column_names,
+            vec!["primary_rehearsal_and_sound_check_coordinator_name"]
+        );
+
+        // Reserved words are fine for (always quoted) columns
+        assert_eq!(composite.fields[2].column_names, vec!["limit"]);
+    }
+
+    #[multiplatform_test]
+    fn invalid_identifiers() {
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            @table("concert_performance_schedule_with_rehearsal_and_sound_check_details")
+            type Concert {
+                @pk id: Int = autoIncrement()
+            }
+        }
+        "#,
+            "Explicit table names longer than 63 bytes should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @column("primary_rehearsal_and_sound_check_coordinator_name_for_the_concert") coordinator: String
+            }
+        }
+        "#,
+            "Explicit column names longer than 63 bytes should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                @update @column("end") updatedAt: Instant = now()
+            }
+        }
+        "#,
+            "Reserved words should be rejected for @update columns"
+        );
+    }
+
+    #[multiplatform_test]
+    fn unique_on_field_without_column() {
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module ConcertModule {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                venue: Venue
+            }
+
+            type Venue {
+                @pk id: Int = autoIncrement()
+                @unique concerts: Set<Concert>?
+            }
+        }
+        "#,
+            "Unique constraints on one-to-many fields should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module MembershipModule {
+            type User {
+                @pk id: Int = autoIncrement()
+                @unique membership: Membership?
+            }
+
+            type Membership {
+                @pk id: Int = autoIncrement()
+                user: User
+            }
+        }
+        "#,
+            "Unique constraints on the optional side of a one-to-one relationship should be rejected"
+        );
+    }
+
     #[multiplatform_test]
     fn many_to_many_without_linking_type() {
         assert_resolved_err!(
@@ -3166,6 +6654,128 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn many_to_many_validation() {
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module Database {
+            type Book {
+                @pk id: Int = autoIncrement()
+                @manyToMany author: Author
+            }
+
+            type Author {
+                @pk id: Int = autoIncrement()
+            }
+        }
+        "#,
+            "@manyToMany on a non-set field should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module Database {
+            type Person {
+                @pk id: Int = autoIncrement()
+                @manyToMany friends: Set<Person>
+            }
+        }
+        "#,
+            "Self-referencing @manyToMany should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module Database {
+            type Book {
+                @pk id: Int = autoIncrement()
+                @manyToMany(through="BookAuthor") authors: Set<Author>
+            }
+
+            type Author {
+                @pk id: Int = autoIncrement()
+            }
+
+            type BookAuthor {
+                @pk id: Int = autoIncrement()
+                @manyToOne author: Author
+            }
+        }
+        "#,
+            "@manyToMany through a join type without a link to the source should be rejected"
+        );
+    }
+
+    #[multiplatform_test]
+    fn relation_path_validation() {
+        assert_resolved_err!(
+            r#"
+        @postgres
+        module Database {
+            type Venue {
+                @pk id: Int = autoIncrement()
+                concerts: Set<Concert>?
+                @relationPath("concerts.venues") otherVenues: Set<Venue>?
+            }
+
+            type Concert {
+                @pk id: Int = autoIncrement()
+                venue: Venue
+                @relationPath("venue.otherVenues") venues: Set<Venue>?
+            }
+        }
+        "#,
+            "Relation paths forming a cycle should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres(maxRelationPathDepth=2)
+        module Database {
+            type Concert {
+                @pk id: Int = autoIncrement()
+                venue: Venue
+                @relationPath("venue.city") city: City
+                @relationPath("city.country") country: Country
+            }
+
+            type Venue {
+                @pk id: Int = autoIncrement()
+                city: City
+                concerts: Set<Concert>?
+            }
+
+            type City {
+                @pk id: Int = autoIncrement()
+                country: Country
+                venues: Set<Venue>?
+            }
+
+            type Country {
+                @pk id: Int = autoIncrement()
+                cities: Set<City>?
+            }
+        }
+        "#,
+            "Relation paths exceeding the maximum depth (after expanding nested paths) should be rejected"
+        );
+
+        assert_resolved_err!(
+            r#"
+        @postgres(maxRelationPathDepth=0)
+        module Database {
+            type Concert {
+                @pk id: Int = autoIncrement()
+            }
+        }
+        "#,
+            "A non-positive maxRelationPathDepth should be rejected"
+        );
+    }
+
     #[multiplatform_test]
     fn column_mapping_validation() {
         // Test invalid field in mapping
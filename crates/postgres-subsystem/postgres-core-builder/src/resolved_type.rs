@@ -1,8 +1,14 @@
 use std::collections::HashMap;
 
 use codemap::Span;
-use exo_sql::SchemaObjectName;
-use postgres_core_model::types::EntityRepresentation;
+use exo_sql::{
+    ColumnStorage, Ordering, ReferentialAction, SchemaObjectName, WindowFunction,
+    schema::partition_spec::PartitionStrategy,
+};
+use postgres_core_model::{
+    relation::ManyToManyJoin,
+    types::{EntityRepresentation, FieldMask},
+};
 use serde::{Deserialize, Serialize};
 
 use core_model::{
@@ -45,6 +51,9 @@ pub struct ResolvedEnumType {
     pub name: String,
     pub fields: Vec<String>,
     pub enum_name: SchemaObjectName,
+    /// Variants removed from the enum along with the variants to migrate their values to (from
+    /// `@mapsTo`)
+    pub variant_mappings: Vec<(String, String)>,
     pub doc_comments: Option<String>,
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -57,6 +66,74 @@ pub struct ResolvedJoinTableConfig {
     pub shortcuts: Vec<JoinTableShortcutConfig>,
 }
 
+/// Retention policy declared with `@retention`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedRetention {
+    pub days: u32,
+    /// The (timestamp) field whose value determines whether a row has expired
+    pub field: String,
+    /// If present, expired rows are marked through this field instead of being deleted
+    pub soft_delete_field: Option<String>,
+    pub batch_size: Option<usize>,
+}
+
+/// Ownership transfer mutation declared with `@ownership(transfer=...)`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedOwnershipTransfer {
+    /// The field that holds the owner
+    pub field: String,
+    /// The access rule for the transfer (evaluated against the existing row)
+    pub access: AstExpr<Typed>,
+}
+
+/// Approval workflow declared with `@requiresApproval`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedApproval {
+    /// Who may approve or reject changes (evaluated against the context)
+    pub approver: AstExpr<Typed>,
+    /// The context field identifying the user (such as `AuthContext.id`)
+    pub requester: String,
+    /// The type of the requester field (such as `Int`)
+    pub requester_type: String,
+    /// The generated type that holds the pending changes (such as `PayoutChange`)
+    pub change_type: String,
+}
+
+/// Rate-of-change limits on fields declared with `@guard`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedFieldGuards {
+    pub guards: Vec<ResolvedFieldGuard>,
+    /// The type of the primary key of the guarded type (such as `Int`)
+    pub entity_id_type: String,
+    /// The generated type that records the changes to guarded fields (such as `UserFieldChange`)
+    pub change_type: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedFieldGuard {
+    pub field_name: String,
+    pub max_changes_per_day: u64,
+}
+
+/// A window function field declared with `@window` (such as `gameRank`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedWindowField {
+    pub name: String,
+    pub function: WindowFunction,
+    /// The fields whose values group the rows
+    pub partition_by: Vec<String>,
+    /// The fields (and their ordering) that order the rows within each group
+    pub order_by: Vec<(String, Ordering)>,
+}
+
+/// Partitioning declared with `@partitionBy`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedPartition {
+    /// The field whose column partitions the table
+    pub field: String,
+    pub strategy: PartitionStrategy,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JoinTableShortcutConfig {
     pub source_entity: String,
@@ -86,6 +163,22 @@ pub struct ResolvedJoinTableShortcutField {
     pub join_table_target_relation: String,
 }
 
+/// A many-to-many field declared with `@manyToMany`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedManyToMany {
+    /// The join type from `@manyToMany(through=...)` (`None` to generate one)
+    pub through: Option<String>,
+}
+
+impl ResolvedManyToMany {
+    pub fn join(&self) -> ManyToManyJoin {
+        match self.through {
+            Some(_) => ManyToManyJoin::Declared,
+            None => ManyToManyJoin::Generated,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResolvedJoinTableIntermediateField {
     pub join_table_type: String,
@@ -106,6 +199,30 @@ pub struct ResolvedCompositeType {
     pub doc_comments: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub join_table: Option<ResolvedJoinTableConfig>,
+    /// The previous table name (from `@renamedFrom`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<ResolvedRetention>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<ResolvedPartition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ownership_transfer: Option<ResolvedOwnershipTransfer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<ResolvedApproval>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_guards: Option<ResolvedFieldGuards>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub window_fields: Vec<ResolvedWindowField>,
+    /// Should a page query (reporting whether more entities follow the page) be offered (from `@paginated`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paginated: bool,
+    /// Should the primary key be exposed as an opaque id (from `@opaqueId`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub opaque_id: bool,
+    /// Functions checking the data of mutations (from `@validate`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validators: Vec<ResolvedValidator>,
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     #[serde(default = "default_span")]
@@ -135,12 +252,39 @@ pub struct ResolvedField {
     pub indices: Vec<String>,
     pub cardinality: Option<Cardinality>,
     pub default_value: Option<ResolvedFieldDefault>,
+    /// The literal to return instead of null when reading the field (from `@readDefault`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_default: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<FieldMask>,
     pub update_sync: bool,
     pub readonly: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub relation_path: Option<Vec<String>>,
+    /// Set for a field declared with `@manyToMany` (its `relation_path` goes through the join type)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub many_to_many: Option<ResolvedManyToMany>,
+    /// The previous column name (from `@renamedFrom`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    /// The action on this field's foreign key when the referenced row is deleted (from `@onDelete`)
+    #[serde(default)]
+    pub on_delete: ReferentialAction,
+    /// The action on this field's foreign key when the referenced row's key is updated (from `@onUpdate`)
+    #[serde(default)]
+    pub on_update: ReferentialAction,
+    /// How the field's column stores its values (from `@storage`)
+    #[serde(default, skip_serializing_if = "ColumnStorage::is_default")]
+    pub storage: ColumnStorage,
+    /// The number of characters (for `String`) or bytes (for `Blob`) per chunk when streaming the
+    /// field's value (from `@chunked`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
     pub doc_comments: Option<String>,
     pub computed: Option<ResolvedComputedField>,
+    /// The SQL expression that computes the field's value (from `@sqlExpression`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sql_expression: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub join_table_shortcut: Option<ResolvedJoinTableShortcutField>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -148,10 +292,17 @@ pub struct ResolvedField {
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     #[serde(default = "default_span")]
-    #[allow(unused)]
     pub span: Span,
 }
 
+/// A validator declared through `@validate` (on the type or one of its fields)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedValidator {
+    pub field_name: Option<String>,
+    pub source_path: String,
+    pub function_name: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResolvedComputedField {
     pub source_path: String,
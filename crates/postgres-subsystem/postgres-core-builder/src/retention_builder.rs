@@ -0,0 +1,109 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build retention policies for types annotated with `@retention`
+
+use core_model_builder::error::ModelBuildingError;
+use exo_sql::{
+    BooleanColumnType, ColumnId, DateColumnType, PhysicalColumnTypeExt, TimestampColumnType,
+};
+use postgres_core_model::{
+    relation::PostgresRelation,
+    retention::{RetentionAction, RetentionPolicy},
+    types::EntityType,
+};
+
+use crate::resolved_type::{ResolvedCompositeType, ResolvedType, ResolvedTypeEnv};
+use crate::system_builder::SystemContextBuilding;
+
+pub(super) fn build(
+    resolved_env: &ResolvedTypeEnv,
+    building: &mut SystemContextBuilding,
+) -> Result<(), ModelBuildingError> {
+    for (_, resolved_type) in resolved_env.resolved_types.iter() {
+        if let ResolvedType::Composite(composite) = resolved_type
+            && let Some(retention) = &composite.retention
+        {
+            if composite.representation.is_json_like() {
+                return Err(ModelBuildingError::Generic(format!(
+                    "@retention cannot be used on type '{}' since it is not backed by a table",
+                    composite.name
+                )));
+            }
+
+            let entity_type = building
+                .entity_types
+                .get_by_key(&composite.name)
+                .ok_or_else(|| {
+                    ModelBuildingError::Generic(format!(
+                        "Entity type '{}' not found",
+                        composite.name
+                    ))
+                })?;
+
+            let timestamp_column_id = scalar_column_id(composite, entity_type, &retention.field)?;
+            let timestamp_column = timestamp_column_id.get_column(&building.database);
+            if !(timestamp_column.typ.is::<TimestampColumnType>()
+                || timestamp_column.typ.is::<DateColumnType>())
+            {
+                return Err(ModelBuildingError::Generic(format!(
+                    "@retention field '{}' on type '{}' must be of a date or timestamp type",
+                    retention.field, composite.name
+                )));
+            }
+
+            let action = match &retention.soft_delete_field {
+                Some(soft_delete_field) => {
+                    let column_id = scalar_column_id(composite, entity_type, soft_delete_field)?;
+                    let column = column_id.get_column(&building.database);
+
+                    let valid = column.typ.is::<BooleanColumnType>()
+                        || (column.is_nullable && column.typ.is::<TimestampColumnType>());
+                    if !valid {
+                        return Err(ModelBuildingError::Generic(format!(
+                            "@retention softDelete field '{}' on type '{}' must be a Boolean or an optional timestamp",
+                            soft_delete_field, composite.name
+                        )));
+                    }
+
+                    RetentionAction::SoftDelete { column_id }
+                }
+                None => RetentionAction::Delete,
+            };
+
+            building.retention_policies.push(RetentionPolicy {
+                entity_name: composite.name.clone(),
+                table_id: entity_type.table_id,
+                timestamp_column_id,
+                days: retention.days,
+                action,
+                batch_size: retention.batch_size,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn scalar_column_id(
+    composite: &ResolvedCompositeType,
+    entity_type: &EntityType,
+    field_name: &str,
+) -> Result<ColumnId, ModelBuildingError> {
+    match entity_type
+        .field_by_name(field_name)
+        .map(|field| &field.relation)
+    {
+        Some(PostgresRelation::Scalar { column_id, .. }) => Ok(*column_id),
+        _ => Err(ModelBuildingError::Generic(format!(
+            "@retention field '{}' on type '{}' must be a scalar field stored in the table",
+            field_name, composite.name
+        ))),
+    }
+}
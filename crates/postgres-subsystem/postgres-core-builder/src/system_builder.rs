@@ -18,8 +18,11 @@ use core_model_builder::{builder::system_builder::BaseModelSystem, error::ModelB
 use postgres_core_model::{
     access::PrecheckAccessPrimitiveExpression,
     aggregate::AggregateType,
+    foreign_key_constraint::ForeignKeyConstraint,
+    retention::RetentionPolicy,
     subsystem::PostgresCoreSubsystem,
     types::{ComputedScript, EntityType, PostgresPrimitiveType},
+    unique_constraint::UniqueConstraint,
     vector_distance::VectorDistanceType,
 };
 
@@ -27,7 +30,10 @@ use postgres_core_model::access::DatabaseAccessPrimitiveExpression;
 
 use exo_sql::Database;
 
-use crate::{aggregate_type_builder, database_builder, type_builder};
+use crate::{
+    aggregate_type_builder, database_builder, foreign_key_constraint_builder, retention_builder,
+    type_builder, unique_constraint_builder,
+};
 
 use crate::resolved_type::ResolvedTypeEnv;
 
@@ -60,6 +66,12 @@ fn build_expanded(
 
     aggregate_type_builder::build_expanded(resolved_env, building)?;
 
+    retention_builder::build(resolved_env, building)?;
+
+    unique_constraint_builder::build(building)?;
+
+    foreign_key_constraint_builder::build(building)?;
+
     Ok(())
 }
 
@@ -77,6 +89,12 @@ pub struct SystemContextBuilding {
     pub precheck_access_expressions:
         Mutex<AccessExpressionsBuilding<PrecheckAccessPrimitiveExpression>>,
 
+    pub retention_policies: Vec<RetentionPolicy>,
+
+    pub unique_constraints: Vec<UniqueConstraint>,
+
+    pub foreign_key_constraints: Vec<ForeignKeyConstraint>,
+
     pub database: Database,
 }
 
@@ -88,8 +106,12 @@ impl SystemContextBuilding {
             entity_types: self.entity_types.values(),
             aggregate_types: self.aggregate_types.values(),
             computed_scripts: self.computed_scripts,
+            retention_policies: self.retention_policies,
+            unique_constraints: self.unique_constraints,
+            foreign_key_constraints: self.foreign_key_constraints,
 
             database: self.database,
+            connection_settings: Default::default(),
 
             database_access_expressions: self
                 .database_access_expressions
@@ -97,6 +119,10 @@ impl SystemContextBuilding {
                 .unwrap()
                 .elems,
             precheck_expressions: self.precheck_access_expressions.into_inner().unwrap().elems,
+
+            access_predicate_cache: Default::default(),
+            session_settings: Default::default(),
+            opaque_id_key: None,
         }
     }
 }
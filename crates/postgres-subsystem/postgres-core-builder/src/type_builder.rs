@@ -7,18 +7,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::{
-    collections::{HashMap, HashSet},
-    path::Path,
-};
+use std::{collections::HashMap, path::Path};
 
 use crate::resolved_builder::Cardinality;
 use crate::{access_builder::ResolvedAccess, computed_script::bundle_computed_script};
 use crate::{
     resolved_type::{
-        ResolvedCompositeType, ResolvedComputedField, ResolvedField, ResolvedFieldDefault,
-        ResolvedFieldType, ResolvedFieldTypeHelper, ResolvedJoinTableIntermediateField,
-        ResolvedJoinTableShortcutField, ResolvedType, ResolvedTypeEnv,
+        ResolvedApproval, ResolvedCompositeType, ResolvedField, ResolvedFieldDefault,
+        ResolvedFieldGuards, ResolvedFieldType, ResolvedFieldTypeHelper,
+        ResolvedJoinTableIntermediateField, ResolvedJoinTableShortcutField,
+        ResolvedOwnershipTransfer, ResolvedType, ResolvedTypeEnv, ResolvedWindowField,
     },
     type_provider::VectorTypeHint,
 };
@@ -30,7 +28,8 @@ use core_model::primitive_type;
 use core_model::types::{Named, TypeValidationProvider};
 use postgres_core_model::access::{CreationAccessExpression, PrecheckAccessPrimitiveExpression};
 use postgres_core_model::types::{
-    ComputedField, ComputedFieldDependency, PostgresFieldDefaultValue, PostgresPrimitiveTypeKind,
+    ComputedField, ComputedFieldDependency, ComputedFieldDependencySource, FieldGuard, FieldGuards,
+    InputValidator, PostgresFieldDefaultValue, PostgresPrimitiveTypeKind,
 };
 
 use crate::{aggregate_type_builder::aggregate_type_name, shallow::Shallow};
@@ -47,12 +46,15 @@ use core_model_builder::{
 };
 
 use exo_sql::{
-    ColumnId, DEFAULT_VECTOR_SIZE, RelationId, VectorDistanceFunction,
+    ColumnId, DEFAULT_VECTOR_SIZE, RelationId, SqlExpression, VectorDistanceFunction,
     get_mto_relation_for_columns, get_otm_relation_for_columns,
 };
 
 use postgres_core_model::{
-    access::{Access, DatabaseAccessPrimitiveExpression, UpdateAccessExpression},
+    access::{
+        Access, ApprovalWorkflow, DatabaseAccessPrimitiveExpression, OwnershipTransfer,
+        UpdateAccessExpression,
+    },
     aggregate::{AggregateField, AggregateFieldType},
     relation::{
         ManyToOneRelation, OneToManyRelation, PostgresRelation, RelationCardinality,
@@ -63,6 +65,7 @@ use postgres_core_model::{
         TypeIndex,
     },
     vector_distance::{VectorDistanceField, VectorDistanceType},
+    window::WindowField,
 };
 
 use super::system_builder::SystemContextBuilding;
@@ -152,9 +155,14 @@ fn create_shallow_type(
                 fields: vec![],
                 agg_fields: vec![],
                 vector_distance_fields: vec![],
+                window_fields: vec![],
                 table_id: SerializableSlabIndex::shallow(),
                 access: restrictive_access(),
                 doc_comments: composite.doc_comments.clone(),
+                ownership_transfer: None,
+                approval: None,
+                field_guards: None,
+                validators: vec![],
             };
 
             building.entity_types.add(&resolved_type.name(), typ);
@@ -239,14 +247,73 @@ fn expand_type_fields(
         })
         .collect();
 
+    let validators = resolved_type
+        .validators
+        .iter()
+        .map(|validator| {
+            Ok(InputValidator {
+                field_name: validator.field_name.clone(),
+                script_id: get_or_insert_computed_script(&validator.source_path, building)?,
+                function_name: validator.function_name.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, ModelBuildingError>>()?;
+
+    let field_guards = resolved_type
+        .field_guards
+        .as_ref()
+        .map(|field_guards| compute_field_guards(field_guards, building))
+        .transpose()?;
+
     let existing_type = &mut building.entity_types[existing_type_id];
     existing_type.fields = entity_fields;
+    existing_type.validators = validators;
+    existing_type.field_guards = field_guards;
     existing_type.agg_fields = agg_fields;
     existing_type.vector_distance_fields = vector_distance_fields;
 
+    let window_fields = resolved_type
+        .window_fields
+        .iter()
+        .map(|window_field| create_window_field(window_field, existing_type))
+        .collect::<Result<Vec<_>, _>>()?;
+    existing_type.window_fields = window_fields;
+
     Ok(())
 }
 
+fn create_window_field(
+    window_field: &ResolvedWindowField,
+    entity_type: &EntityType,
+) -> Result<WindowField, ModelBuildingError> {
+    // The window may only partition and order by columns of the type itself
+    let column_id = |field_name: &str| match entity_type
+        .field_by_name(field_name)
+        .map(|field| &field.relation)
+    {
+        Some(PostgresRelation::Scalar { column_id, .. }) => Ok(*column_id),
+        _ => Err(ModelBuildingError::Generic(format!(
+            "Window field '{}' of type '{}' refers to '{}', which is not a scalar field",
+            window_field.name, entity_type.name, field_name
+        ))),
+    };
+
+    Ok(WindowField {
+        name: window_field.name.clone(),
+        function: window_field.function,
+        partition_by: window_field
+            .partition_by
+            .iter()
+            .map(|field_name| column_id(field_name))
+            .collect::<Result<_, _>>()?,
+        order_by: window_field
+            .order_by
+            .iter()
+            .map(|(field_name, ordering)| Ok((column_id(field_name)?, *ordering)))
+            .collect::<Result<_, ModelBuildingError>>()?,
+    })
+}
+
 // Expand dynamic default values (pre-condition: all type fields have been populated)
 fn expand_dynamic_default_values(
     resolved_type: &ResolvedCompositeType,
@@ -291,7 +358,7 @@ fn expand_dynamic_default_values(
             .fields
             .iter()
             .map(|resolved_field| -> Result<Option<(String, ContextSelection)>, ModelBuildingError> {
-                if resolved_field.computed.is_some() {
+                if resolved_field.computed.is_some() || resolved_field.sql_expression.is_some() {
                     return Ok(None);
                 }
 
@@ -413,6 +480,17 @@ fn expand_type_access(
         existing_type.access = expr;
     }
 
+    if let Some(transfer) = &resolved_type.ownership_transfer {
+        let ownership_transfer =
+            compute_ownership_transfer(transfer, existing_type_id, resolved_env, building)?;
+        building.entity_types[existing_type_id].ownership_transfer = Some(ownership_transfer);
+    }
+
+    if let Some(approval) = &resolved_type.approval {
+        let approval = compute_approval(approval, existing_type_id, resolved_env, building)?;
+        building.entity_types[existing_type_id].approval = Some(approval);
+    }
+
     for field in resolved_type.fields.iter() {
         let expr = compute_access(&field.access, existing_type_id, resolved_env, building)?;
 
@@ -429,6 +507,101 @@ fn expand_type_access(
     Ok(())
 }
 
+fn compute_ownership_transfer(
+    transfer: &ResolvedOwnershipTransfer,
+    entity_id: SerializableSlabIndex<EntityType>,
+    resolved_env: &ResolvedTypeEnv,
+    building: &SystemContextBuilding,
+) -> Result<OwnershipTransfer, ModelBuildingError> {
+    let entity = &building.entity_types[entity_id];
+
+    // The new owner is passed as the value of the owner column, so the field must map to a
+    // single (non-pk) column
+    if !matches!(
+        entity
+            .field_by_name(&transfer.field)
+            .map(|field| &field.relation),
+        Some(PostgresRelation::Scalar { is_pk: false, .. })
+    ) {
+        return Err(ModelBuildingError::Generic(format!(
+            "Ownership transfer of type '{}' requires the ownership field '{}' to be a non-primary-key scalar field",
+            entity.name, transfer.field
+        )));
+    }
+
+    let access = compute_database_access_expr(
+        &[&Some(transfer.access.clone())],
+        entity_id,
+        resolved_env,
+        building,
+    )?;
+
+    Ok(OwnershipTransfer {
+        field_name: transfer.field.clone(),
+        access,
+    })
+}
+
+fn compute_approval(
+    approval: &ResolvedApproval,
+    entity_id: SerializableSlabIndex<EntityType>,
+    resolved_env: &ResolvedTypeEnv,
+    building: &SystemContextBuilding,
+) -> Result<ApprovalWorkflow, ModelBuildingError> {
+    let change_type_id = building
+        .get_entity_type_id(&approval.change_type)
+        .ok_or_else(|| {
+            ModelBuildingError::Generic(format!("Change type '{}' not found", approval.change_type))
+        })?;
+
+    let approver = compute_database_access_expr(
+        &[&Some(approval.approver.clone())],
+        entity_id,
+        resolved_env,
+        building,
+    )?;
+
+    // Validated when resolving the annotation
+    let (context_name, field_name) = approval.requester.split_once('.').ok_or_else(|| {
+        ModelBuildingError::Generic(format!("Invalid requester '{}'", approval.requester))
+    })?;
+
+    Ok(ApprovalWorkflow {
+        change_type_id,
+        approver,
+        requester: ContextSelection {
+            context_name: context_name.to_string(),
+            path: (field_name.to_string(), vec![]),
+        },
+    })
+}
+
+fn compute_field_guards(
+    field_guards: &ResolvedFieldGuards,
+    building: &SystemContextBuilding,
+) -> Result<FieldGuards, ModelBuildingError> {
+    let change_type_id = building
+        .get_entity_type_id(&field_guards.change_type)
+        .ok_or_else(|| {
+            ModelBuildingError::Generic(format!(
+                "Change type '{}' not found",
+                field_guards.change_type
+            ))
+        })?;
+
+    Ok(FieldGuards {
+        guards: field_guards
+            .guards
+            .iter()
+            .map(|guard| FieldGuard {
+                field_name: guard.field_name.clone(),
+                max_changes_per_day: guard.max_changes_per_day,
+            })
+            .collect(),
+        change_type_id,
+    })
+}
+
 fn first_non_optional_access_expr<'a>(
     ast_exprs: &[&'a Option<AstExpr<Typed>>],
 ) -> Option<&'a AstExpr<Typed>> {
@@ -626,7 +799,7 @@ fn create_persistent_field(
     };
 
     if let Some(computed) = &field.computed {
-        let script_id = get_or_insert_computed_script(computed, building)?;
+        let script_id = get_or_insert_computed_script(&computed.source_path, building)?;
         let subsystem = computed
             .subsystem
             .clone()
@@ -641,84 +814,85 @@ fn create_persistent_field(
                 .map(|pk_field| pk_field.name.clone()),
         );
 
-        let resolved_type = env.get_by_key(&self_type.name).ok_or_else(|| {
-            ModelBuildingError::Generic(format!(
-                "Failed to locate resolved type for entity '{}'",
-                self_type.name
-            ))
-        })?;
-
-        let resolved_composite = match resolved_type {
-            ResolvedType::Composite(composite) => composite,
-            _ => {
-                return Err(ModelBuildingError::Generic(format!(
-                    "Resolved type '{}' is not a composite type",
-                    self_type.name
-                )));
-            }
-        };
-
-        let mut seen_dependencies = HashSet::new();
         let mut dependencies = Vec::new();
 
         for dependency_name in dependency_names {
-            if !seen_dependencies.insert(dependency_name.clone()) {
-                continue;
-            }
+            let path: Vec<&str> = dependency_name.split('.').map(str::trim).collect();
+
+            add_computed_dependency(
+                &mut dependencies,
+                &path,
+                &field.name,
+                &dependency_name,
+                *type_id,
+                building,
+                env,
+            )?;
+        }
 
-            let dependency_field = resolved_composite
-                .fields
-                .iter()
-                .find(|resolved_field| resolved_field.name == dependency_name)
-                .ok_or_else(|| {
-                    ModelBuildingError::Generic(format!(
-                        "Computed field '{}' references unknown dependency field '{}'",
-                        field.name, dependency_name
-                    ))
-                })?;
+        return Ok(PostgresField {
+            name: field.name.to_owned(),
+            typ: field.typ.wrap(base_field_type),
+            relation: PostgresRelation::Computed(ComputedField {
+                script_id,
+                function_name: computed.function_name.clone(),
+                subsystem,
+                dependencies,
+            }),
+            access: placeholder_access,
+            default_value: None,
+            read_default: None,
+            mask: None,
+            readonly: true,
+            type_validation: None,
+            doc_comments: field.doc_comments.clone(),
+        });
+    }
 
-            if !dependency_field.self_column {
-                return Err(ModelBuildingError::Generic(format!(
-                    "Computed field '{}' dependency '{}' must refer to a column on the same table",
-                    field.name, dependency_name
-                )));
-            }
+    if let Some(expression) = &field.sql_expression {
+        let self_type = &building.entity_types[*type_id];
+        let table_id = self_type.table_id;
 
-            if dependency_field.column_names.len() != 1 {
+        let resolved_composite = match env.get_by_key(&self_type.name) {
+            Some(ResolvedType::Composite(composite)) => composite,
+            _ => {
                 return Err(ModelBuildingError::Generic(format!(
-                    "Computed field '{}' dependency '{}' must reference a single column",
-                    field.name, dependency_name
+                    "Failed to locate resolved type for entity '{}'",
+                    self_type.name
                 )));
             }
+        };
 
-            let column_name = &dependency_field.column_names[0];
-            let column_id = building
-                .database
-                .get_column_id(self_type.table_id, column_name)
-                .ok_or_else(|| {
-                    ModelBuildingError::Generic(format!(
-                        "Computed field '{}' dependency '{}' refers to unknown column '{}'",
-                        field.name, dependency_name, column_name
-                    ))
-                })?;
+        // An identifier may refer to a field of the type or directly to a column of its table
+        let lookup_column = |name: &str| {
+            let column_name = resolved_composite
+                .fields
+                .iter()
+                .find(|resolved_field| {
+                    resolved_field.name == name
+                        && resolved_field.self_column
+                        && resolved_field.column_names.len() == 1
+                })
+                .map(|resolved_field| resolved_field.column_names[0].as_str())
+                .unwrap_or(name);
+            building.database.get_column_id(table_id, column_name)
+        };
 
-            dependencies.push(ComputedFieldDependency {
-                field_name: dependency_name,
-                column_id,
-            });
-        }
+        let sql_expression = SqlExpression::parse(expression, lookup_column).map_err(|e| {
+            ModelBuildingError::Generic(format!(
+                "Invalid @sqlExpression for field '{}.{}': {}",
+                self_type.name, field.name, e
+            ))
+        })?;
 
         return Ok(PostgresField {
             name: field.name.to_owned(),
             typ: field.typ.wrap(base_field_type),
-            relation: PostgresRelation::Computed(ComputedField {
-                script_id,
-                function_name: computed.function_name.clone(),
-                subsystem,
-                dependencies,
-            }),
+            relation: PostgresRelation::SqlExpression(sql_expression),
             access: placeholder_access,
             default_value: None,
+            read_default: None,
+            mask: None,
             readonly: true,
             type_validation: None,
             doc_comments: field.doc_comments.clone(),
@@ -727,6 +901,27 @@ fn create_persistent_field(
 
     let relation = create_relation(field, *type_id, building, env, expand_foreign_relations)?;
 
+    // Opaque ids (see `@opaqueId`) are exposed as strings
+    let base_field_type =
+        match &relation {
+            PostgresRelation::Scalar { column_id, .. }
+                if column_id.get_column(&building.database).opaque_id =>
+            {
+                let type_name = primitive_type::StringType::NAME;
+                let type_id = building.primitive_types.get_id(type_name).ok_or(
+                    ModelBuildingError::Generic(format!(
+                        "Primitive type `{}` not found",
+                        type_name
+                    )),
+                )?;
+                PostgresFieldType {
+                    type_name: type_name.to_owned(),
+                    type_id: TypeIndex::Primitive(type_id),
+                }
+            }
+            _ => base_field_type,
+        };
+
     let type_validation = match &field.type_hint {
         Some(th) => th.get_type_validation(),
         None => None,
@@ -797,12 +992,176 @@ fn create_persistent_field(
         relation,
         access: placeholder_access,
         default_value,
+        read_default: field.read_default.clone(),
+        mask: field.mask.clone(),
         readonly: field.readonly || field.update_sync,
         type_validation,
         doc_comments: field.doc_comments.clone(),
     })
 }
 
+/// Add the dependency at the path (such as `["author", "name"]` for `author.name`) to the
+/// dependencies of a computed field, given the entity that the path starts from. Each segment
+/// before the last must be a relation to a single entity, and the last one must be a field backed
+/// by a column. Dependencies through the same relation are merged (so that the related entity is
+/// selected once).
+fn add_computed_dependency(
+    dependencies: &mut Vec<ComputedFieldDependency>,
+    path: &[&str],
+    computed_field_name: &str,
+    dependency_name: &str,
+    type_id: SerializableSlabIndex<EntityType>,
+    building: &SystemContextBuilding,
+    env: &ResolvedTypeEnv,
+) -> Result<(), ModelBuildingError> {
+    let entity_type = &building.entity_types[type_id];
+
+    let resolved_composite = match env.get_by_key(&entity_type.name) {
+        Some(ResolvedType::Composite(composite)) => composite,
+        _ => {
+            return Err(ModelBuildingError::Generic(format!(
+                "Failed to locate resolved type for entity '{}'",
+                entity_type.name
+            )));
+        }
+    };
+
+    let (field_name, rest) = match path {
+        [field_name, rest @ ..] if !field_name.is_empty() => (*field_name, rest),
+        _ => {
+            return Err(ModelBuildingError::Generic(format!(
+                "Computed field '{}' has an invalid dependency '{}'",
+                computed_field_name, dependency_name
+            )));
+        }
+    };
+
+    let dependency_field = resolved_composite
+        .fields
+        .iter()
+        .find(|resolved_field| resolved_field.name == field_name)
+        .ok_or_else(|| {
+            ModelBuildingError::Generic(format!(
+                "Computed field '{}' references unknown dependency field '{}' (of type '{}')",
+                computed_field_name, dependency_name, entity_type.name
+            ))
+        })?;
+
+    if dependency_field.computed.is_some() {
+        return Err(ModelBuildingError::Generic(format!(
+            "Computed field '{}' dependency '{}' refers to the computed field '{}'. Dependencies must refer to stored fields",
+            computed_field_name, dependency_name, field_name
+        )));
+    }
+
+    let conflict_error = || {
+        ModelBuildingError::Generic(format!(
+            "Computed field '{}' depends on both '{}' and fields of it (such as '{}'). Select only the fields of '{}'",
+            computed_field_name, field_name, dependency_name, field_name
+        ))
+    };
+
+    let existing = dependencies
+        .iter()
+        .position(|dependency| dependency.field_name == field_name);
+
+    if rest.is_empty() {
+        if let Some(existing) = existing {
+            return match dependencies[existing].source {
+                ComputedFieldDependencySource::Column(_) => Ok(()),
+                ComputedFieldDependencySource::Relation { .. } => Err(conflict_error()),
+            };
+        }
+
+        if !dependency_field.self_column {
+            return Err(ModelBuildingError::Generic(format!(
+                "Computed field '{}' dependency '{}' must refer to a column on the same table",
+                computed_field_name, dependency_name
+            )));
+        }
+
+        if dependency_field.column_names.len() != 1 {
+            return Err(ModelBuildingError::Generic(format!(
+                "Computed field '{}' dependency '{}' must reference a single column",
+                computed_field_name, dependency_name
+            )));
+        }
+
+        let column_name = &dependency_field.column_names[0];
+        let column_id = building
+            .database
+            .get_column_id(entity_type.table_id, column_name)
+            .ok_or_else(|| {
+                ModelBuildingError::Generic(format!(
+                    "Computed field '{}' dependency '{}' refers to unknown column '{}'",
+                    computed_field_name, dependency_name, column_name
+                ))
+            })?;
+
+        dependencies.push(ComputedFieldDependency {
+            field_name: field_name.to_string(),
+            source: ComputedFieldDependencySource::Column(column_id),
+        });
+
+        return Ok(());
+    }
+
+    let (relation_id, foreign_entity_id) = match create_relation(
+        dependency_field,
+        type_id,
+        building,
+        env,
+        true,
+    )? {
+        PostgresRelation::ManyToOne { relation, .. } => (
+            RelationId::ManyToOne(relation.relation_id),
+            relation.foreign_entity_id,
+        ),
+        PostgresRelation::OneToMany(relation)
+            if relation.cardinality == RelationCardinality::Optional =>
+        {
+            (
+                RelationId::OneToMany(relation.relation_id),
+                relation.foreign_entity_id,
+            )
+        }
+        _ => {
+            return Err(ModelBuildingError::Generic(format!(
+                "Computed field '{}' dependency '{}' goes through '{}', which is not a relation to a single entity",
+                computed_field_name, dependency_name, field_name
+            )));
+        }
+    };
+
+    let position = match existing {
+        Some(position) => position,
+        None => {
+            dependencies.push(ComputedFieldDependency {
+                field_name: field_name.to_string(),
+                source: ComputedFieldDependencySource::Relation {
+                    relation_id,
+                    table_id: building.entity_types[foreign_entity_id].table_id,
+                    dependencies: vec![],
+                },
+            });
+            dependencies.len() - 1
+        }
+    };
+
+    match &mut dependencies[position].source {
+        ComputedFieldDependencySource::Relation { dependencies, .. } => add_computed_dependency(
+            dependencies,
+            rest,
+            computed_field_name,
+            dependency_name,
+            foreign_entity_id,
+            building,
+            env,
+        ),
+        ComputedFieldDependencySource::Column(_) => Err(conflict_error()),
+    }
+}
+
 fn create_agg_field(
     field: &ResolvedField,
     type_id: &SerializableSlabIndex<EntityType>,
@@ -810,7 +1169,7 @@ fn create_agg_field(
     env: &ResolvedTypeEnv,
     expand_foreign_relations: bool,
 ) -> Result<Option<AggregateField>, ModelBuildingError> {
-    if field.computed.is_some() {
+    if field.computed.is_some() || field.sql_expression.is_some() {
         return Ok(None);
     }
 
@@ -862,7 +1221,7 @@ fn create_vector_distance_field(
     building: &SystemContextBuilding,
     env: &ResolvedTypeEnv,
 ) -> Option<VectorDistanceField> {
-    if field.computed.is_some() {
+    if field.computed.is_some() || field.sql_expression.is_some() {
         return None;
     }
 
@@ -897,19 +1256,16 @@ fn create_vector_distance_field(
     }
 }
 
+/// The bundled script (for computed fields and validators), bundling it on first use
 fn get_or_insert_computed_script(
-    computed: &ResolvedComputedField,
+    source_path: &str,
     building: &mut SystemContextBuilding,
 ) -> Result<SerializableSlabIndex<ComputedScript>, ModelBuildingError> {
-    if let Some(id) = building
-        .computed_script_ids
-        .get(&computed.source_path)
-        .copied()
-    {
+    if let Some(id) = building.computed_script_ids.get(source_path).copied() {
         return Ok(id);
     }
 
-    let (script_path, script_bytes) = bundle_computed_script(Path::new(&computed.source_path))?;
+    let (script_path, script_bytes) = bundle_computed_script(Path::new(source_path))?;
 
     let script_id = building.computed_scripts.insert(ComputedScript {
         path: script_path.clone(),
@@ -918,7 +1274,7 @@ fn get_or_insert_computed_script(
 
     building
         .computed_script_ids
-        .insert(computed.source_path.clone(), script_id);
+        .insert(source_path.to_string(), script_id);
 
     Ok(script_id)
 }
@@ -1250,6 +1606,10 @@ fn create_transitive_relation(
         path: path.to_vec(),
         steps: vec![],
         final_entity_id: SerializableSlabIndex::shallow(),
+        many_to_many: field
+            .many_to_many
+            .as_ref()
+            .map(|many_to_many| many_to_many.join()),
     }))
 }
 
@@ -1352,6 +1712,7 @@ fn create_join_table_relation(
         ],
         steps,
         final_entity_id: target_entity_id,
+        many_to_many: None,
     }))
 }
 
@@ -1452,14 +1813,20 @@ fn compute_transitive_steps(
                 });
                 current_entity_id = next_entity_id;
             }
-            PostgresRelation::Transitive(_) => {
-                return Err(ModelBuildingError::Generic(format!(
-                    "Field '{}' references transitive relation '{}' in its path, which is not supported",
-                    field_name, segment
-                )));
+            PostgresRelation::Transitive(transitive) => {
+                // Expand the segment's own path (cycles are rejected when resolving types)
+                let (segment_steps, next_entity_id) = compute_transitive_steps(
+                    field_name,
+                    &transitive.path,
+                    current_entity_id,
+                    building,
+                )?;
+                steps.extend(segment_steps);
+                current_entity_id = next_entity_id;
             }
             PostgresRelation::Scalar { .. }
             | PostgresRelation::Computed(_)
+            | PostgresRelation::SqlExpression(_)
             | PostgresRelation::Embedded => {
                 return Err(ModelBuildingError::Generic(format!(
                     "Field '{}' references non-relational segment '{}' on type '{}' (relation: {:?})",
@@ -1570,8 +1937,22 @@ fn finalize_transitive_relations(
                 )));
             }
 
+            let entity = &mut building.entity_types[entity_id];
+
+            // The aggregate field (such as `booksAgg`) follows the same path
+            let agg_field_name = format!("{field_name}Agg");
+            if let Some(PostgresRelation::Transitive(transitive)) = entity
+                .agg_fields
+                .iter_mut()
+                .find(|agg_field| agg_field.name == agg_field_name)
+                .and_then(|agg_field| agg_field.relation.as_mut())
+            {
+                transitive.steps = steps.clone();
+                transitive.final_entity_id = final_entity_id;
+            }
+
             if let PostgresRelation::Transitive(transitive) =
-                &mut building.entity_types[entity_id].fields[field_index].relation
+                &mut entity.fields[field_index].relation
             {
                 transitive.steps = steps;
                 transitive.final_entity_id = final_entity_id;
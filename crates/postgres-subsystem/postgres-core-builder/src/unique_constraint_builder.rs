@@ -0,0 +1,179 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build the mapping from database unique constraints (including primary keys) to the fields of
+//! entity types
+
+use core_model_builder::error::ModelBuildingError;
+use exo_sql::ColumnId;
+use postgres_core_model::{relation::PostgresRelation, unique_constraint::UniqueConstraint};
+
+use crate::system_builder::SystemContextBuilding;
+
+pub(super) fn build(building: &mut SystemContextBuilding) -> Result<(), ModelBuildingError> {
+    let mut unique_constraints: Vec<UniqueConstraint> = vec![];
+
+    for (_, entity_type) in building.entity_types.iter() {
+        if entity_type.representation.is_json_like() {
+            continue;
+        }
+
+        let table = building.database.get_table(entity_type.table_id);
+        let mut entity_constraints: Vec<UniqueConstraint> = vec![];
+
+        let mut add_field = |constraint_name: &str, field_name: &str| match entity_constraints
+            .iter_mut()
+            .find(|constraint| constraint.name == constraint_name)
+        {
+            Some(constraint) => {
+                if !constraint.field_names.iter().any(|name| name == field_name) {
+                    constraint.field_names.push(field_name.to_string());
+                }
+            }
+            None => entity_constraints.push(UniqueConstraint {
+                name: constraint_name.to_string(),
+                entity_name: entity_type.name.clone(),
+                field_names: vec![field_name.to_string()],
+            }),
+        };
+
+        // Postgres names the primary key constraint `<table>_pkey` (we don't name it explicitly)
+        let pk_constraint_name = format!("{}_pkey", table.name.name);
+
+        for field in entity_type.fields.iter() {
+            let column_ids: Vec<ColumnId> = match &field.relation {
+                PostgresRelation::Scalar { column_id, .. } => vec![*column_id],
+                PostgresRelation::ManyToOne { relation, .. } => relation
+                    .relation_id
+                    .deref(&building.database)
+                    .column_pairs
+                    .iter()
+                    .map(|pair| pair.self_column_id)
+                    .collect(),
+                _ => continue,
+            };
+
+            if field.relation.is_pk() {
+                add_field(&pk_constraint_name, &field.name);
+            }
+
+            for column_id in column_ids {
+                let column = column_id.get_column(&building.database);
+                for constraint_name in column.unique_constraints.iter() {
+                    add_field(constraint_name, &field.name);
+                }
+            }
+        }
+
+        unique_constraints.extend(entity_constraints);
+    }
+
+    building.unique_constraints = unique_constraints;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use multiplatform_test::multiplatform_test;
+
+    use crate::test_util::{
+        create_base_model_system, create_postgres_core_subsystem,
+        create_typechecked_system_from_src,
+    };
+
+    use super::*;
+
+    fn build_unique_constraints(src: &str) -> Vec<UniqueConstraint> {
+        let typechecked_system = create_typechecked_system_from_src(src).unwrap();
+        let base_system = create_base_model_system(&typechecked_system).unwrap();
+
+        create_postgres_core_subsystem(&base_system, &typechecked_system)
+            .unwrap()
+            .unique_constraints
+    }
+
+    fn field_names<'a>(
+        constraints: &'a [UniqueConstraint],
+        entity_name: &str,
+    ) -> Vec<&'a [String]> {
+        constraints
+            .iter()
+            .filter(|constraint| constraint.entity_name == entity_name)
+            .map(|constraint| constraint.field_names.as_slice())
+            .collect()
+    }
+
+    #[multiplatform_test]
+    fn maps_constraints_to_fields() {
+        let constraints = build_unique_constraints(
+            r#"
+            @postgres
+            module MembershipModule {
+                type Team {
+                    @pk id: Int = autoIncrement()
+                    @unique name: String
+                    members: Set<Member>?
+                }
+
+                type Member {
+                    @pk id: Int = autoIncrement()
+                    @unique("team_nickname") team: Team
+                    @unique("team_nickname") nickname: String
+                    email: String
+                }
+            }
+            "#,
+        );
+
+        assert_eq!(
+            field_names(&constraints, "Team"),
+            vec![&["id".to_string()][..], &["name".to_string()][..]]
+        );
+        assert_eq!(
+            field_names(&constraints, "Member"),
+            vec![
+                &["id".to_string()][..],
+                &["team".to_string(), "nickname".to_string()][..]
+            ]
+        );
+
+        let team_pk = constraints
+            .iter()
+            .find(|constraint| constraint.entity_name == "Team" && constraint.field_names == ["id"])
+            .unwrap();
+        assert_eq!(team_pk.name, "teams_pkey");
+    }
+
+    #[multiplatform_test]
+    fn composite_primary_key() {
+        let constraints = build_unique_constraints(
+            r#"
+            @postgres
+            module PeopleModule {
+                @table("people")
+                type Person {
+                    @pk firstName: String
+                    @pk lastName: String
+                    age: Int
+                }
+            }
+            "#,
+        );
+
+        assert_eq!(
+            constraints,
+            vec![UniqueConstraint {
+                name: "people_pkey".to_string(),
+                entity_name: "Person".to_string(),
+                field_names: vec!["firstName".to_string(), "lastName".to_string()],
+            }]
+        );
+    }
+}
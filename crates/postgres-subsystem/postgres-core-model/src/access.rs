@@ -9,6 +9,7 @@
 
 use core_model::{
     access::{AccessPredicateExpression, CommonAccessPrimitiveExpression, FunctionCall},
+    context_type::ContextSelection,
     mapped_arena::SerializableSlabIndex,
 };
 use core_resolver::access_solver::AccessSolverError;
@@ -16,7 +17,7 @@ use core_resolver::access_solver::AccessSolverError;
 use exo_sql::PhysicalColumnPath;
 use serde::{Deserialize, Serialize};
 
-use crate::types::PostgresFieldDefaultValue;
+use crate::types::{EntityType, PostgresFieldDefaultValue};
 
 /// Access specification for a model
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,6 +42,30 @@ pub struct UpdateAccessExpression {
         SerializableSlabIndex<AccessPredicateExpression<DatabaseAccessPrimitiveExpression>>,
 }
 
+/// The ownership transfer mutation (declared with `@ownership(transfer=...)`), which updates only
+/// the owner field and is guarded by its own rule instead of the update rule
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OwnershipTransfer {
+    /// The (scalar) field that holds the owner
+    pub field_name: String,
+    /// Evaluated against the existing row (like the delete rule)
+    pub access: SerializableSlabIndex<AccessPredicateExpression<DatabaseAccessPrimitiveExpression>>,
+}
+
+/// The approval workflow of a type (declared with `@requiresApproval`). Its mutations are recorded
+/// as pending changes (in the generated change type) and applied only once another user approves
+/// them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApprovalWorkflow {
+    /// The generated type holding the pending changes (such as `PayoutChange`)
+    pub change_type_id: SerializableSlabIndex<EntityType>,
+    /// Who may approve or reject changes (evaluated against the context)
+    pub approver:
+        SerializableSlabIndex<AccessPredicateExpression<DatabaseAccessPrimitiveExpression>>,
+    /// The context field identifying the user who requests or reviews a change
+    pub requester: ContextSelection,
+}
+
 /// Primitive expression (that doesn't contain any other expressions).
 /// Used as sides of `AccessRelationalExpression` to form more complex expressions
 /// such as equal and less than.
@@ -0,0 +1,148 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Cache of solved database access predicates.
+//!
+//! Solving a database access expression depends only on the context values it references (such
+//! as `AuthContext.role`), so the solved predicate (which may have a residue such as
+//! `self.published`) can be reused by later requests with the same values. An expression that
+//! keeps seeing new values (typically, because it references a per-request value such as
+//! `AuthContext.id`) stops being cached, since its entries would rarely be reused.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use common::value::Val;
+use core_model::{access::AccessPredicateExpression, mapped_arena::SerializableSlabIndex};
+use exo_sql::AbstractPredicate;
+
+use crate::access::DatabaseAccessPrimitiveExpression;
+
+pub type DatabaseAccessExpressionId =
+    SerializableSlabIndex<AccessPredicateExpression<DatabaseAccessPrimitiveExpression>>;
+
+/// The number of distinct context values cached for an expression, beyond which the expression
+/// is considered to depend on per-request values
+const MAX_ENTRIES_PER_EXPRESSION: usize = 16;
+
+#[derive(Debug, Default)]
+pub struct AccessPredicateCache {
+    expressions: Mutex<HashMap<DatabaseAccessExpressionId, CachedExpression>>,
+}
+
+#[derive(Debug)]
+enum CachedExpression {
+    /// Solved predicates keyed by the values of the context selections the expression references
+    /// (in the order they appear in the expression)
+    Solved(Vec<(Vec<Option<Val>>, AbstractPredicate)>),
+    /// The expression saw too many distinct context values to be worth caching
+    Uncacheable,
+}
+
+impl AccessPredicateCache {
+    /// The predicate previously solved for the expression with the same context values
+    pub fn get(
+        &self,
+        expression_id: DatabaseAccessExpressionId,
+        context_values: &[Option<Val>],
+    ) -> Option<AbstractPredicate> {
+        let expressions = self.expressions.lock().unwrap();
+
+        match expressions.get(&expression_id)? {
+            CachedExpression::Solved(entries) => entries
+                .iter()
+                .find(|(values, _)| values.as_slice() == context_values)
+                .map(|(_, predicate)| predicate.clone()),
+            CachedExpression::Uncacheable => None,
+        }
+    }
+
+    pub fn insert(
+        &self,
+        expression_id: DatabaseAccessExpressionId,
+        context_values: Vec<Option<Val>>,
+        predicate: AbstractPredicate,
+    ) {
+        let mut expressions = self.expressions.lock().unwrap();
+
+        let cached = expressions
+            .entry(expression_id)
+            .or_insert_with(|| CachedExpression::Solved(vec![]));
+
+        if let CachedExpression::Solved(entries) = cached {
+            if entries.iter().any(|(values, _)| values == &context_values) {
+                return;
+            }
+
+            if entries.len() >= MAX_ENTRIES_PER_EXPRESSION {
+                *cached = CachedExpression::Uncacheable;
+            } else {
+                entries.push((context_values, predicate));
+            }
+        }
+    }
+
+    /// Whether predicates for the expression are still being cached
+    pub fn is_cacheable(&self, expression_id: DatabaseAccessExpressionId) -> bool {
+        !matches!(
+            self.expressions.lock().unwrap().get(&expression_id),
+            Some(CachedExpression::Uncacheable)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_predicates_for_same_context_values() {
+        let cache = AccessPredicateCache::default();
+        let expression_id = DatabaseAccessExpressionId::from_idx(0);
+
+        let admin = vec![Some(Val::String("admin".to_string()))];
+        let user = vec![Some(Val::String("user".to_string()))];
+
+        cache.insert(expression_id, admin.clone(), AbstractPredicate::True);
+        cache.insert(expression_id, user.clone(), AbstractPredicate::False);
+
+        assert_eq!(
+            cache.get(expression_id, &admin),
+            Some(AbstractPredicate::True)
+        );
+        assert_eq!(
+            cache.get(expression_id, &user),
+            Some(AbstractPredicate::False)
+        );
+        assert_eq!(cache.get(expression_id, &[None]), None);
+        assert_eq!(
+            cache.get(DatabaseAccessExpressionId::from_idx(1), &admin),
+            None
+        );
+    }
+
+    #[test]
+    fn stops_caching_per_request_values() {
+        let cache = AccessPredicateCache::default();
+        let expression_id = DatabaseAccessExpressionId::from_idx(0);
+
+        for id in 0..=MAX_ENTRIES_PER_EXPRESSION {
+            cache.insert(
+                expression_id,
+                vec![Some(Val::String(id.to_string()))],
+                AbstractPredicate::True,
+            );
+        }
+
+        assert!(!cache.is_cacheable(expression_id));
+        assert_eq!(
+            cache.get(expression_id, &[Some(Val::String("0".to_string()))]),
+            None
+        );
+    }
+}
@@ -0,0 +1,27 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Connection options declared in the model with `@postgres(applicationName=..., ...)`. The
+//! corresponding environment variables (such as `EXO_POSTGRES_APPLICATION_NAME`) take precedence.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionSettings {
+    /// The `application_name` the connections report to the database
+    pub application_name: Option<String>,
+    /// Command-line options sent to the server at connection startup
+    pub options: Option<String>,
+    /// Idle time in seconds before sending TCP keepalive probes
+    pub keepalive_idle_secs: Option<u64>,
+    /// Interval in seconds between unanswered TCP keepalive probes
+    pub keepalive_interval_secs: Option<u64>,
+    /// Number of unanswered TCP keepalive probes before the connection is considered dead
+    pub keepalive_retries: Option<u32>,
+}
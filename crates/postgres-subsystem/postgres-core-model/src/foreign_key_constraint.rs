@@ -0,0 +1,27 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Foreign key constraints backing many-to-one relations, so that we can report a violation in
+//! terms of the model (instead of the database).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForeignKeyConstraint {
+    /// The name of the constraint in the database
+    pub name: String,
+    /// The entity with the relation field (for example, `Book`)
+    pub entity_name: String,
+    /// The relation field (for example, `author`)
+    pub field_name: String,
+    /// The entity the relation refers to (for example, `Author`)
+    pub referenced_entity_name: String,
+    /// The key fields of the referenced entity (in the order of the constraint's columns)
+    pub referenced_field_names: Vec<String>,
+}
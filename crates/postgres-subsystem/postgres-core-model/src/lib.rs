@@ -1,10 +1,17 @@
 pub mod access;
+pub mod access_predicate_cache;
 pub mod aggregate;
+pub mod connection_settings;
+pub mod foreign_key_constraint;
 pub mod predicate;
 pub mod relation;
+pub mod retention;
+pub mod session_settings;
 pub mod subsystem;
 pub mod types;
+pub mod unique_constraint;
 pub mod vector_distance;
+pub mod window;
 
 #[cfg(test)]
 mod migration_tests;
@@ -0,0 +1,4 @@
+ALTER TABLE "documents" ALTER COLUMN "content" SET STORAGE EXTENDED;
+
+ALTER TABLE "documents" ALTER COLUMN "attachment" SET STORAGE EXTENDED, ALTER COLUMN "attachment" SET COMPRESSION DEFAULT;
+
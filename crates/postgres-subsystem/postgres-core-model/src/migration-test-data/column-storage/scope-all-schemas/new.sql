@@ -0,0 +1,11 @@
+CREATE TABLE "documents" (
+	"id" SERIAL PRIMARY KEY,
+	"title" TEXT NOT NULL,
+	"content" TEXT NOT NULL,
+	"attachment" BYTEA
+);
+
+ALTER TABLE "documents" ALTER COLUMN "content" SET STORAGE EXTERNAL;
+
+ALTER TABLE "documents" ALTER COLUMN "attachment" SET STORAGE MAIN, ALTER COLUMN "attachment" SET COMPRESSION pglz;
+
@@ -0,0 +1,7 @@
+CREATE TABLE "documents" (
+	"id" SERIAL PRIMARY KEY,
+	"title" TEXT NOT NULL,
+	"content" TEXT NOT NULL,
+	"attachment" BYTEA
+);
+
@@ -0,0 +1,4 @@
+ALTER TABLE "documents" ALTER COLUMN "content" SET STORAGE EXTERNAL;
+
+ALTER TABLE "documents" ALTER COLUMN "attachment" SET STORAGE MAIN, ALTER COLUMN "attachment" SET COMPRESSION pglz;
+
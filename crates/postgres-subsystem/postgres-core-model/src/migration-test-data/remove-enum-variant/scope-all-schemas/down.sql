@@ -0,0 +1,11 @@
+DO $$
+BEGIN
+  ALTER TYPE "priority" RENAME TO "priority_old";
+  CREATE TYPE "priority" AS ENUM ('LOW', 'MEDIUM', 'HIGH', 'URGENT');
+  ALTER TABLE "todos" ALTER COLUMN "priority" TYPE "priority" USING ("priority"::text)::"priority";
+  ALTER TABLE "todos" ALTER COLUMN "priority_with_default" DROP DEFAULT;
+  ALTER TABLE "todos" ALTER COLUMN "priority_with_default" TYPE "priority" USING ("priority_with_default"::text)::"priority";
+  ALTER TABLE "todos" ALTER COLUMN "priority_with_default" SET DEFAULT 'MEDIUM';
+  DROP TYPE "priority_old";
+END $$;
+
@@ -0,0 +1,9 @@
+CREATE TYPE "priority" AS ENUM ('LOW', 'MEDIUM', 'HIGH', 'URGENT');
+
+CREATE TABLE "todos" (
+	"id" SERIAL PRIMARY KEY,
+	"title" TEXT NOT NULL,
+	"priority" "priority" NOT NULL,
+	"priority_with_default" "priority" NOT NULL DEFAULT 'MEDIUM'
+);
+
@@ -0,0 +1,11 @@
+DO $$
+BEGIN
+  ALTER TYPE "priority" RENAME TO "priority_old";
+  CREATE TYPE "priority" AS ENUM ('LOW', 'MEDIUM', 'HIGH');
+  ALTER TABLE "todos" ALTER COLUMN "priority" TYPE "priority" USING (CASE "priority"::text WHEN 'URGENT' THEN 'HIGH' ELSE "priority"::text END)::"priority";
+  ALTER TABLE "todos" ALTER COLUMN "priority_with_default" DROP DEFAULT;
+  ALTER TABLE "todos" ALTER COLUMN "priority_with_default" TYPE "priority" USING (CASE "priority_with_default"::text WHEN 'URGENT' THEN 'HIGH' ELSE "priority_with_default"::text END)::"priority";
+  ALTER TABLE "todos" ALTER COLUMN "priority_with_default" SET DEFAULT 'MEDIUM';
+  DROP TYPE "priority_old";
+END $$;
+
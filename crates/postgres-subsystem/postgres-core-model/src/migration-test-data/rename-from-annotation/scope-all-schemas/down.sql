@@ -0,0 +1,8 @@
+-- DROP TABLE "t_todos" CASCADE;
+
+CREATE TABLE "todos" (
+	"id" SERIAL PRIMARY KEY,
+	"title" TEXT NOT NULL,
+	"completed" BOOLEAN NOT NULL
+);
+
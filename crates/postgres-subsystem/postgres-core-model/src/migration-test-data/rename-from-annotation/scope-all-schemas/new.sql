@@ -0,0 +1,6 @@
+CREATE TABLE "t_todos" (
+	"id" SERIAL PRIMARY KEY,
+	"name" TEXT NOT NULL,
+	"completed" BOOLEAN NOT NULL
+);
+
@@ -0,0 +1,6 @@
+CREATE TABLE "todos" (
+	"id" SERIAL PRIMARY KEY,
+	"title" TEXT NOT NULL,
+	"completed" BOOLEAN NOT NULL
+);
+
@@ -0,0 +1,6 @@
+ALTER TABLE "todos" RENAME TO "t_todos";
+
+ALTER SEQUENCE "todos_id_seq" RENAME TO "t_todos_id_seq";
+
+ALTER TABLE "t_todos" RENAME COLUMN "title" TO "name";
+
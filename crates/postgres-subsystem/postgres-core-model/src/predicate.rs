@@ -13,7 +13,7 @@ use core_model::{
     types::{FieldType, Named, TypeValidation},
 };
 
-use crate::{access::Access, types::EntityType};
+use crate::{access::Access, relation::EmbeddedFieldLink, types::EntityType};
 
 use exo_sql::{ColumnPathLink, VectorDistanceFunction};
 use serde::{Deserialize, Serialize};
@@ -35,9 +35,14 @@ pub struct PredicateParameter {
     ///   where: None
     /// ```
     pub column_path_link: Option<ColumnPathLink>,
+    /// For a relation-path field (from `@relationPath`), the links to follow after
+    /// `column_path_link` to reach the final entity
+    pub transitive_links: Vec<ColumnPathLink>,
     pub access: Option<Access>,
     // TODO: Generalize this to support more than just vector distance functions
     pub vector_distance_function: Option<VectorDistanceFunction>,
+    /// For a field inside a JSON column, the key to follow (in place of `column_path_link`)
+    pub embedded_field: Option<EmbeddedFieldLink>,
 }
 
 /// Thw wrapper around PredicateParameterType to be able to satisfy the Named trait, without cloning the parameter type.
@@ -10,7 +10,10 @@
 use crate::types::{ComputedField, EntityFieldId, EntityType};
 
 use core_model::mapped_arena::SerializableSlabIndex;
-use exo_sql::{ColumnId, ColumnPathLink, Database, ManyToOneId, OneToManyId, RelationId};
+use exo_sql::{
+    ColumnId, ColumnPathLink, Database, ManyToOneId, OneToManyId, PhysicalColumnType, RelationId,
+    SqlExpression,
+};
 use serde::{Deserialize, Serialize};
 
 // We model one-to-one (more precisely one-to-one_or_zero and one_or_zero-to-one) relations as
@@ -34,10 +37,22 @@ pub enum PostgresRelation {
     },
     OneToMany(OneToManyRelation),
     Computed(ComputedField),
+    /// A field computed in the database from other columns of the table (from `@sqlExpression`)
+    SqlExpression(SqlExpression),
     Embedded, // Such as a field in typed json
     Transitive(TransitiveRelation),
 }
 
+/// How a predicate or order-by parameter reaches a field embedded in a JSON column (a field with
+/// the [`PostgresRelation::Embedded`] relation)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddedFieldLink {
+    /// The key of the field in the JSON object
+    pub key: String,
+    /// The type to interpret a non-object value as (`None` for a nested JSON object)
+    pub typ: Option<Box<dyn PhysicalColumnType>>,
+}
+
 impl PostgresRelation {
     pub fn is_pk(&self) -> bool {
         matches!(
@@ -62,6 +77,37 @@ pub struct TransitiveRelation {
     pub path: Vec<String>,
     pub steps: Vec<TransitiveRelationStep>,
     pub final_entity_id: SerializableSlabIndex<EntityType>,
+    /// Set for a many-to-many field (from `@manyToMany`), whose path goes through the rows of a
+    /// join entity (the first step) to the related entity (the second step)
+    #[serde(default)]
+    pub many_to_many: Option<ManyToManyJoin>,
+}
+
+impl TransitiveRelation {
+    /// The links to follow (one for each step) to reach the final entity
+    pub fn column_path_links(&self, database: &Database) -> Vec<ColumnPathLink> {
+        self.steps
+            .iter()
+            .map(|step| match step.relation_id {
+                RelationId::ManyToOne(relation_id) => {
+                    relation_id.deref(database).column_path_link()
+                }
+                RelationId::OneToMany(relation_id) => {
+                    relation_id.deref(database).column_path_link()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The join entity of a many-to-many field
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManyToManyJoin {
+    /// Declared in the schema (`@manyToMany(through="BookAuthor")`)
+    Declared,
+    /// Generated for the field (`@manyToMany`). Its rows are managed only through the field, so
+    /// connecting and disconnecting is governed by the update access of the field's entity.
+    Generated,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -113,6 +159,9 @@ impl PostgresRelation {
             PostgresRelation::Computed(_) => {
                 panic!("Computed fields do not have column path links")
             }
+            PostgresRelation::SqlExpression(_) => {
+                panic!("SQL expression fields do not have column path links")
+            }
             PostgresRelation::Embedded => {
                 panic!("Embedded relations cannot be used in queries")
             }
@@ -0,0 +1,35 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Retention policies declared on entity types with `@retention`.
+
+use exo_sql::{ColumnId, TableId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RetentionPolicy {
+    /// Name of the entity type (for logging and metrics)
+    pub entity_name: String,
+    pub table_id: TableId,
+    /// The timestamp column that determines if a row has expired
+    pub timestamp_column_id: ColumnId,
+    /// Rows older than this many days are purged
+    pub days: u32,
+    pub action: RetentionAction,
+    /// Overrides the default batch size (`EXO_RETENTION_BATCH_SIZE`) for this entity
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionAction {
+    /// Delete the expired rows
+    Delete,
+    /// Mark the expired rows through the given column (a boolean set to `true` or a timestamp set to the current time)
+    SoftDelete { column_id: ColumnId },
+}
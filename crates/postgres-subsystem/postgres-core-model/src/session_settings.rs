@@ -0,0 +1,169 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Postgres settings (such as `app.user_id`) set from the request context at the start of each
+//! transaction, so that triggers, row-level security policies, and audit functions in the
+//! database can see the acting principal (with `current_setting('app.user_id', true)`).
+//!
+//! The settings are specified as comma-separated `name=Context.field` pairs. For example, with
+//! `app.user_id=AuthContext.id,app.tenant=AuthContext.tenantId`, each transaction sets
+//! `app.user_id` to the value of `AuthContext.id` and `app.tenant` to that of
+//! `AuthContext.tenantId`.
+
+use core_model::{
+    context_type::{ContextSelection, ContextType},
+    mapped_arena::MappedArena,
+};
+
+#[derive(Debug, Default)]
+pub struct SessionSettings(Vec<SessionSetting>);
+
+#[derive(Debug, PartialEq)]
+pub struct SessionSetting {
+    /// The name of the setting such as `app.user_id`
+    pub name: String,
+    /// The context value to set it to such as `AuthContext.id`
+    pub context_selection: ContextSelection,
+}
+
+impl SessionSettings {
+    /// Parse the settings, checking that they refer to the fields of the given contexts
+    pub fn parse(raw: &str, contexts: &MappedArena<ContextType>) -> Result<Self, String> {
+        let settings = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|setting| !setting.is_empty())
+            .map(|setting| parse_setting(setting, contexts))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SessionSettings(settings))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SessionSetting> {
+        self.0.iter()
+    }
+}
+
+fn parse_setting(
+    setting: &str,
+    contexts: &MappedArena<ContextType>,
+) -> Result<SessionSetting, String> {
+    let (name, selection) = setting
+        .split_once('=')
+        .ok_or_else(|| format!("Expected 'name=Context.field', found '{setting}'"))?;
+    let (name, selection) = (name.trim(), selection.trim());
+
+    // Postgres allows setting arbitrary parameters only if they are qualified (such as
+    // `app.user_id`), which also keeps the built-in ones (such as `role`) off limits
+    let is_identifier = |part: &str| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let parts = name.split('.').collect::<Vec<_>>();
+    if parts.len() < 2 || !parts.iter().all(|part| is_identifier(part)) {
+        return Err(format!(
+            "Invalid setting name '{name}' (expected a qualified name such as 'app.user_id')"
+        ));
+    }
+
+    let (context_name, field_name) = selection.split_once('.').ok_or_else(|| {
+        format!("Expected a context field such as 'AuthContext.id', found '{selection}'")
+    })?;
+
+    let context_type = contexts
+        .get_by_key(context_name)
+        .ok_or_else(|| format!("Unknown context '{context_name}' for the setting '{name}'"))?;
+    if !context_type
+        .fields
+        .iter()
+        .any(|field| field.name == field_name)
+    {
+        return Err(format!(
+            "Context '{context_name}' has no field '{field_name}' (for the setting '{name}')"
+        ));
+    }
+
+    Ok(SessionSetting {
+        name: name.to_string(),
+        context_selection: ContextSelection {
+            context_name: context_name.to_string(),
+            path: (field_name.to_string(), vec![]),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core_model::{
+        context_type::{ContextField, ContextSource},
+        primitive_type::{INT_TYPE, PrimitiveType},
+        types::FieldType,
+    };
+
+    use super::*;
+
+    fn contexts() -> MappedArena<ContextType> {
+        let mut contexts = MappedArena::default();
+        contexts.add(
+            "AuthContext",
+            ContextType {
+                name: "AuthContext".to_string(),
+                fields: vec![ContextField {
+                    name: "id".to_string(),
+                    typ: FieldType::Plain(PrimitiveType::Plain(INT_TYPE)),
+                    source: ContextSource {
+                        annotation_name: "jwt".to_string(),
+                        value: Some("sub".to_string()),
+                    },
+                    default_value: None,
+                }],
+                doc_comments: None,
+            },
+        );
+        contexts
+    }
+
+    #[test]
+    fn parse_settings() {
+        let settings = SessionSettings::parse(
+            " app.user_id = AuthContext.id, audit.actor=AuthContext.id,",
+            &contexts(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["app.user_id", "audit.actor"]
+        );
+        assert_eq!(
+            settings.iter().next().unwrap().context_selection,
+            ContextSelection {
+                context_name: "AuthContext".to_string(),
+                path: ("id".to_string(), vec![]),
+            }
+        );
+        assert!(SessionSettings::parse("", &contexts()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let contexts = contexts();
+
+        assert!(SessionSettings::parse("app.user_id", &contexts).is_err());
+        assert!(SessionSettings::parse("role=AuthContext.id", &contexts).is_err());
+        assert!(SessionSettings::parse("app.user-id=AuthContext.id", &contexts).is_err());
+        assert!(SessionSettings::parse("app.user_id=AuthContext", &contexts).is_err());
+        assert!(SessionSettings::parse("app.user_id=UserContext.id", &contexts).is_err());
+        assert!(SessionSettings::parse("app.user_id=AuthContext.role", &contexts).is_err());
+    }
+}
@@ -13,13 +13,19 @@ use core_model::{
     mapped_arena::{MappedArena, SerializableSlab},
 };
 use core_plugin_shared::{error::ModelSerializationError, system_serializer::SystemSerializer};
-use exo_sql::Database;
+use exo_sql::{Database, OpaqueIdKey};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     access::{DatabaseAccessPrimitiveExpression, PrecheckAccessPrimitiveExpression},
+    access_predicate_cache::AccessPredicateCache,
     aggregate::AggregateType,
+    connection_settings::ConnectionSettings,
+    foreign_key_constraint::ForeignKeyConstraint,
+    retention::RetentionPolicy,
+    session_settings::SessionSettings,
     types::{ComputedScript, EntityType, PostgresPrimitiveType},
+    unique_constraint::UniqueConstraint,
 };
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,7 +42,46 @@ pub struct PostgresCoreSubsystem {
     pub precheck_expressions:
         SerializableSlab<AccessPredicateExpression<PrecheckAccessPrimitiveExpression>>,
 
+    pub retention_policies: Vec<RetentionPolicy>,
+
+    pub unique_constraints: Vec<UniqueConstraint>,
+
+    pub foreign_key_constraints: Vec<ForeignKeyConstraint>,
+
     pub database: Database,
+
+    /// Connection options declared in the model
+    pub connection_settings: ConnectionSettings,
+
+    /// Database access predicates solved for earlier requests
+    #[serde(skip)]
+    pub access_predicate_cache: AccessPredicateCache,
+
+    /// Settings to set from the request context at the start of each transaction (configured
+    /// when loading the subsystem)
+    #[serde(skip)]
+    pub session_settings: SessionSettings,
+
+    /// The key to encode and decode opaque ids (from `EXO_OPAQUE_ID_KEY`; required if a type uses
+    /// `@opaqueId`)
+    #[serde(skip)]
+    pub opaque_id_key: Option<OpaqueIdKey>,
+}
+
+impl PostgresCoreSubsystem {
+    /// The unique constraint with the given database name
+    pub fn unique_constraint(&self, name: &str) -> Option<&UniqueConstraint> {
+        self.unique_constraints
+            .iter()
+            .find(|constraint| constraint.name == name)
+    }
+
+    /// The foreign key constraint with the given database name
+    pub fn foreign_key_constraint(&self, name: &str) -> Option<&ForeignKeyConstraint> {
+        self.foreign_key_constraints
+            .iter()
+            .find(|constraint| constraint.name == name)
+    }
 }
 
 impl SystemSerializer for PostgresCoreSubsystem {
@@ -67,7 +112,19 @@ impl Default for PostgresCoreSubsystem {
             database_access_expressions: SerializableSlab::new(),
             precheck_expressions: SerializableSlab::new(),
 
+            retention_policies: vec![],
+
+            unique_constraints: vec![],
+
+            foreign_key_constraints: vec![],
+
             database: Database::default(),
+
+            connection_settings: ConnectionSettings::default(),
+
+            access_predicate_cache: AccessPredicateCache::default(),
+            session_settings: SessionSettings::default(),
+            opaque_id_key: None,
         }
     }
 }
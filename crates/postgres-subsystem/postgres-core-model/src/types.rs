@@ -10,6 +10,7 @@
 use super::relation::PostgresRelation;
 use crate::aggregate::AggregateField;
 use crate::vector_distance::VectorDistanceField;
+use crate::window::WindowField;
 
 use common::value::Val;
 use core_model::context_type::ContextSelection;
@@ -19,9 +20,9 @@ use core_model::{
     types::{FieldType, Named},
 };
 
-use crate::access::Access;
+use crate::access::{Access, ApprovalWorkflow, OwnershipTransfer};
 
-use exo_sql::{ColumnId, PhysicalTable, SchemaObjectName};
+use exo_sql::{ColumnId, MaskStrategy, PhysicalTable, RelationId, SchemaObjectName, TableId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -97,10 +98,22 @@ pub struct EntityType {
     pub fields: Vec<PostgresField<EntityType>>,
     pub agg_fields: Vec<AggregateField>,
     pub vector_distance_fields: Vec<VectorDistanceField>,
+    pub window_fields: Vec<WindowField>,
 
     pub table_id: SerializableSlabIndex<PhysicalTable>,
     pub access: Access,
     pub doc_comments: Option<String>,
+    /// Set if a `transfer<Type>Ownership` mutation is to be generated
+    pub ownership_transfer: Option<OwnershipTransfer>,
+    /// Set if mutations must be approved before they are applied (from `@requiresApproval`)
+    #[serde(default)]
+    pub approval: Option<ApprovalWorkflow>,
+    /// Limits on how often fields may change (from `@guard`)
+    #[serde(default)]
+    pub field_guards: Option<FieldGuards>,
+    /// Functions that check the data of create and update mutations (from `@validate`)
+    #[serde(default)]
+    pub validators: Vec<InputValidator>,
 }
 
 /// Encapsulates a field on an entity type (mirros how `ColumnId` is structured)
@@ -157,6 +170,10 @@ impl EntityType {
             .iter()
             .find(|field| field.name == name)
     }
+
+    pub fn window_field_by_name(&self, name: &str) -> Option<&WindowField> {
+        self.window_fields.iter().find(|field| field.name == name)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -173,18 +190,54 @@ pub struct PostgresField<CT> {
     pub typ: FieldType<PostgresFieldType<CT>>,
     pub relation: PostgresRelation,
     pub default_value: Option<PostgresFieldDefaultValue>,
+    /// The literal to return instead of null when reading the field (from `@readDefault`)
+    pub read_default: Option<String>,
+    /// How to mask the field for some roles (from `@mask`)
+    pub mask: Option<FieldMask>,
     pub readonly: bool,
     pub access: Access,
     pub type_validation: Option<TypeValidation>,
     pub doc_comments: Option<String>,
 }
 
+/// Masking of a field's value for readers with any of the roles (such as showing only the last
+/// four digits of a card number to support staff)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldMask {
+    pub roles: Vec<String>,
+    pub strategy: MaskStrategy,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ComputedScript {
     pub path: String,
     pub definition: Vec<u8>,
 }
 
+/// Limits on how often fields of an entity may change. Each change to a guarded field is recorded
+/// in the generated change type, which the limits are checked against before an update.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FieldGuards {
+    pub guards: Vec<FieldGuard>,
+    /// The generated type recording the changes (such as `UserFieldChange`)
+    pub change_type_id: SerializableSlabIndex<EntityType>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FieldGuard {
+    pub field_name: String,
+    pub max_changes_per_day: u64,
+}
+
+/// A function (in a Deno script) that checks the data of a mutation before it is executed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputValidator {
+    /// The field whose value the function checks (or `None` if it checks the whole entity)
+    pub field_name: Option<String>,
+    pub script_id: SerializableSlabIndex<ComputedScript>,
+    pub function_name: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ComputedField {
     pub script_id: SerializableSlabIndex<ComputedScript>,
@@ -193,10 +246,25 @@ pub struct ComputedField {
     pub dependencies: Vec<ComputedFieldDependency>,
 }
 
+/// A field whose value a computed field's resolver needs (from the `select` parameter of
+/// `@computed`), selected along with the entity and passed in the parent snapshot
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ComputedFieldDependency {
     pub field_name: String,
-    pub column_id: ColumnId,
+    pub source: ComputedFieldDependencySource,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ComputedFieldDependencySource {
+    /// A column of the entity's table
+    Column(ColumnId),
+    /// A relation to a single entity, with the dependencies within that entity (such as `name` for
+    /// `author.name`)
+    Relation {
+        relation_id: RelationId,
+        table_id: TableId,
+        dependencies: Vec<ComputedFieldDependency>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -0,0 +1,22 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Unique constraints of entity types, so that we can report a violation in terms of the model
+//! (instead of the database).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UniqueConstraint {
+    /// The name of the constraint in the database
+    pub name: String,
+    pub entity_name: String,
+    /// The fields whose values must be unique together
+    pub field_names: Vec<String>,
+}
@@ -0,0 +1,18 @@
+use exo_sql::{ColumnId, Ordering, WindowFunction};
+use serde::{Deserialize, Serialize};
+
+/// Field computed using a window function (declared with `@window`)
+/// Represents:
+/// ```graphql
+/// score {
+///    points: Int!
+///    gameRank: Int! <--- This is the field (rank of the score within its game by points)
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WindowField {
+    pub name: String,
+    pub function: WindowFunction,
+    pub partition_by: Vec<ColumnId>,
+    pub order_by: Vec<(ColumnId, Ordering)>,
+}
@@ -0,0 +1,174 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The coverage of the access rules of the entities (see [common::access_coverage]).
+
+use common::access_coverage::{AccessCoverage, RuleCoverage};
+use core_model::{access::AccessPredicateExpression, mapped_arena::SerializableSlabIndex};
+use core_resolver::access_coverage::{
+    describe_common_primitive, describe_expression, rule_coverage,
+};
+use exo_sql::{ColumnPathLink, Database, PhysicalColumnPath};
+use postgres_core_model::{
+    access::{
+        AccessPrimitiveExpressionPath, DatabaseAccessPrimitiveExpression, FieldPath,
+        PrecheckAccessPrimitiveExpression,
+    },
+    access_predicate_cache::DatabaseAccessExpressionId,
+    subsystem::PostgresCoreSubsystem,
+};
+
+type PrecheckAccessExpressionId =
+    SerializableSlabIndex<AccessPredicateExpression<PrecheckAccessPrimitiveExpression>>;
+
+pub fn access_coverage(
+    subsystem: &PostgresCoreSubsystem,
+    access_coverage: &AccessCoverage,
+) -> Vec<RuleCoverage> {
+    let database = &subsystem.database;
+    let describe_database =
+        |expr: &DatabaseAccessPrimitiveExpression| describe_database_primitive(expr, database);
+
+    let database_rule = |name: String, index: DatabaseAccessExpressionId| {
+        rule_coverage(
+            name,
+            &subsystem.database_access_expressions[index],
+            access_coverage,
+            &describe_database,
+        )
+    };
+    let precheck_rule = |name: String, index: PrecheckAccessExpressionId| {
+        rule_coverage(
+            name,
+            &subsystem.precheck_expressions[index],
+            access_coverage,
+            &describe_precheck_primitive,
+        )
+    };
+
+    subsystem
+        .entity_types
+        .iter()
+        .flat_map(|(_, entity_type)| {
+            let name = &entity_type.name;
+            let access = &entity_type.access;
+
+            let mut rules = vec![
+                database_rule(format!("{name}.query"), access.read),
+                precheck_rule(format!("{name}.create"), access.creation.precheck),
+                precheck_rule(format!("{name}.update (input)"), access.update.precheck),
+                database_rule(format!("{name}.update"), access.update.database),
+                database_rule(format!("{name}.delete"), access.delete),
+            ];
+
+            if let Some(ownership_transfer) = &entity_type.ownership_transfer {
+                rules.push(database_rule(
+                    format!("{name}.transferOwnership"),
+                    ownership_transfer.access,
+                ));
+            }
+
+            rules
+        })
+        .collect()
+}
+
+fn describe_database_primitive(
+    expr: &DatabaseAccessPrimitiveExpression,
+    database: &Database,
+) -> String {
+    match expr {
+        DatabaseAccessPrimitiveExpression::Column(column_path, parameter_name) => {
+            describe_column_path(column_path, parameter_name.as_deref(), database)
+        }
+        DatabaseAccessPrimitiveExpression::Function(column_path, function_call) => {
+            format!(
+                "{}.{}({} => {})",
+                describe_column_path(column_path, None, database),
+                function_call.name,
+                function_call.parameter_name,
+                describe_expression(&function_call.expr, &|expr| {
+                    describe_database_primitive(expr, database)
+                })
+            )
+        }
+        DatabaseAccessPrimitiveExpression::Common(expr) => describe_common_primitive(expr),
+    }
+}
+
+/// The path in terms of the columns it goes through, for example `self.owner_id.id`
+fn describe_column_path(
+    column_path: &PhysicalColumnPath,
+    parameter_name: Option<&str>,
+    database: &Database,
+) -> String {
+    let mut described = parameter_name.unwrap_or("self").to_string();
+
+    for link in column_path.links() {
+        let column_names = link
+            .self_column_ids()
+            .iter()
+            .map(|column_id| column_id.get_column(database).name.as_str())
+            .collect::<Vec<_>>();
+
+        described.push('.');
+        match link {
+            ColumnPathLink::Relation(_) if column_names.len() > 1 => {
+                described.push_str(&format!("({})", column_names.join(", ")))
+            }
+            _ => described.push_str(&column_names.join(", ")),
+        }
+    }
+
+    described
+}
+
+fn describe_precheck_primitive(expr: &PrecheckAccessPrimitiveExpression) -> String {
+    match expr {
+        PrecheckAccessPrimitiveExpression::Path(path, parameter_name) => {
+            describe_field_path(path, parameter_name.as_deref())
+        }
+        PrecheckAccessPrimitiveExpression::Function(path, function_call) => {
+            format!(
+                "{}.{}({} => {})",
+                describe_field_path(path, None),
+                function_call.name,
+                function_call.parameter_name,
+                describe_expression(&function_call.expr, &describe_precheck_primitive)
+            )
+        }
+        PrecheckAccessPrimitiveExpression::Common(expr) => describe_common_primitive(expr),
+    }
+}
+
+/// The path in terms of the fields it goes through, for example `self.project.id`
+fn describe_field_path(
+    path: &AccessPrimitiveExpressionPath,
+    parameter_name: Option<&str>,
+) -> String {
+    let fields: Vec<&String> = match &path.field_path {
+        FieldPath::Normal(fields, _) => fields.iter().collect(),
+        FieldPath::Pk {
+            lead, pk_fields, ..
+        } => lead.iter().chain(pk_fields).collect(),
+    };
+
+    let root = parameter_name.unwrap_or("self");
+
+    // Paths within a function already start with the function's parameter
+    let fields = match fields.first() {
+        Some(first) if parameter_name.is_some() && first.as_str() == root => &fields[1..],
+        _ => &fields[..],
+    };
+
+    std::iter::once(root)
+        .chain(fields.iter().map(|field| field.as_str()))
+        .collect::<Vec<_>>()
+        .join(".")
+}
@@ -20,16 +20,22 @@ use async_trait::async_trait;
 use common::context::RequestContext;
 use common::value::Val;
 
-use core_model::access::AccessRelationalOp;
+use core_model::access::{
+    AccessLogicalExpression, AccessPredicateExpression, AccessRelationalOp,
+    CommonAccessPrimitiveExpression,
+};
+use core_model::context_type::ContextSelection;
 use core_resolver::access_solver::{
     AccessInput, AccessPredicate, AccessSolution, AccessSolver, AccessSolverError, eq_values,
     neq_values, reduce_common_primitive_expression,
 };
+use core_resolver::context_extractor::ContextExtractor;
 use exo_sql::{
     AbstractPredicate, ColumnPath, ColumnPathLink, PhysicalColumnPath, SQLParamContainer,
 };
 use postgres_core_model::{
-    access::DatabaseAccessPrimitiveExpression, subsystem::PostgresCoreSubsystem,
+    access::DatabaseAccessPrimitiveExpression, access_predicate_cache::DatabaseAccessExpressionId,
+    subsystem::PostgresCoreSubsystem,
 };
 
 use crate::cast;
@@ -274,6 +280,98 @@ impl<'a> AccessSolver<'a, DatabaseAccessPrimitiveExpression, AbstractPredicateWr
     }
 }
 
+/// Solve a database access expression, reusing the predicate solved for an earlier request with
+/// the same values for the context selections in the expression (see
+/// [`postgres_core_model::access_predicate_cache`]).
+pub async fn solve_database_access<'a>(
+    subsystem: &PostgresCoreSubsystem,
+    request_context: &'a RequestContext<'a>,
+    expression_id: DatabaseAccessExpressionId,
+) -> Result<AbstractPredicate, AccessSolverError> {
+    let expr = &subsystem.database_access_expressions[expression_id];
+    let cache = &subsystem.access_predicate_cache;
+
+    // Reusing a solved predicate would skip recording the evaluation for access coverage
+    let context_values = if cache.is_cacheable(expression_id)
+        && request_context.system_context.access_coverage.is_none()
+    {
+        cache_key(subsystem, request_context, expr).await
+    } else {
+        None
+    };
+
+    if let Some(context_values) = &context_values
+        && let Some(predicate) = cache.get(expression_id, context_values)
+    {
+        return Ok(predicate);
+    }
+
+    let predicate = subsystem
+        .solve(request_context, None, expr)
+        .await?
+        .map(|p| p.0)
+        .resolve();
+
+    if let Some(context_values) = context_values {
+        cache.insert(expression_id, context_values, predicate.clone());
+    }
+
+    Ok(predicate)
+}
+
+/// The values of the context selections in the expression. If any of them can't be extracted, we
+/// don't cache, so that solving the expression reports the error (or skips the selection, if it
+/// isn't needed to solve the expression) as usual.
+async fn cache_key<'a>(
+    subsystem: &PostgresCoreSubsystem,
+    request_context: &'a RequestContext<'a>,
+    expr: &AccessPredicateExpression<DatabaseAccessPrimitiveExpression>,
+) -> Option<Vec<Option<Val>>> {
+    let mut context_selections = vec![];
+    collect_context_selections(expr, &mut context_selections);
+
+    let mut context_values = Vec::with_capacity(context_selections.len());
+    for context_selection in context_selections {
+        let value = subsystem
+            .extract_context_selection(request_context, context_selection)
+            .await
+            .ok()?;
+        context_values.push(value.cloned());
+    }
+
+    Some(context_values)
+}
+
+fn collect_context_selections<'e>(
+    expr: &'e AccessPredicateExpression<DatabaseAccessPrimitiveExpression>,
+    context_selections: &mut Vec<&'e ContextSelection>,
+) {
+    match expr {
+        AccessPredicateExpression::LogicalOp(op) => match op {
+            AccessLogicalExpression::Not(underlying) => {
+                collect_context_selections(underlying, context_selections)
+            }
+            AccessLogicalExpression::And(left, right)
+            | AccessLogicalExpression::Or(left, right) => {
+                collect_context_selections(left, context_selections);
+                collect_context_selections(right, context_selections);
+            }
+        },
+        AccessPredicateExpression::RelationalOp(op) => {
+            let (left, right) = op.sides();
+            for side in [left, right] {
+                if let DatabaseAccessPrimitiveExpression::Common(
+                    CommonAccessPrimitiveExpression::ContextSelection(context_selection),
+                ) = side
+                {
+                    context_selections.push(context_selection);
+                }
+            }
+        }
+        AccessPredicateExpression::BooleanLiteral(_) => {}
+    }
+}
+
 pub fn to_column_path(physical_column_path: &PhysicalColumnPath) -> ColumnPath {
     ColumnPath::Physical(physical_column_path.clone())
 }
@@ -1,3 +1,4 @@
 pub mod access_op;
+pub mod coverage;
 pub mod database_solver;
 pub mod precheck_solver;
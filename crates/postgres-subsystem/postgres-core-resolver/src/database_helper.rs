@@ -1,5 +1,17 @@
+use std::time::Duration;
+
+use common::env_const::{
+    DeploymentMode, EXO_DB_IN_LIST_CHUNK_SIZE, EXO_POOL_KEEPALIVE_IDLE,
+    EXO_POOL_KEEPALIVE_INTERVAL, EXO_POOL_KEEPALIVE_RETRIES, EXO_POSTGRES_APPLICATION_NAME,
+    EXO_POSTGRES_OPTIONS, EXO_SQL_EXPLAIN_THRESHOLD, EXO_SQL_LOG, EXO_SQL_LOG_MAX_PARAM_LENGTH,
+    get_deployment_mode,
+};
 use exo_env::Environment;
-use exo_sql::{DatabaseClientManager, DatabaseExecutor, TransactionMode};
+use exo_sql::{
+    DEFAULT_IN_LIST_CHUNK_SIZE, DEFAULT_MAX_PARAM_LENGTH, DatabaseClientManager, DatabaseExecutor,
+    ExecutionConfig, StatementLogConfig, StatementLogFormat, TransactionMode, extract_column,
+};
+use postgres_core_model::connection_settings::ConnectionSettings;
 use thiserror::Error;
 use tokio_postgres::{Row, types::FromSqlOwned};
 
@@ -8,18 +20,22 @@ use crate::postgres_execution_error::PostgresExecutionError;
 pub async fn create_database_executor(
     existing_client: Option<DatabaseClientManager>,
     env: &dyn Environment,
+    connection_settings: &ConnectionSettings,
 ) -> Result<DatabaseExecutor, DatabaseHelperError> {
-    let database_client = if let Some(existing) = existing_client {
-        existing
+    let (database_client, replica_client) = if let Some(existing) = existing_client {
+        (existing, None)
     } else {
         #[cfg(feature = "network")]
         {
             use common::env_const::{
                 DATABASE_URL, EXO_CHECK_CONNECTION_ON_STARTUP, EXO_CONNECTION_POOL_SIZE,
-                EXO_POOL_CREATE_TIMEOUT, EXO_POOL_MAX_LIFETIME, EXO_POOL_RECYCLE_TIMEOUT,
-                EXO_POOL_WAIT_TIMEOUT, EXO_POSTGRES_READ_WRITE, EXO_POSTGRES_URL,
+                EXO_POOL_ADAPTIVE_INTERVAL, EXO_POOL_ADAPTIVE_MAX_SIZE, EXO_POOL_ADAPTIVE_MIN_SIZE,
+                EXO_POOL_CREATE_TIMEOUT, EXO_POOL_DNS_REFRESH_INTERVAL, EXO_POOL_MAX_LIFETIME,
+                EXO_POOL_MIN_IDLE, EXO_POOL_PROBE_INTERVAL, EXO_POOL_RECYCLE_TIMEOUT,
+                EXO_POOL_WAIT_TIMEOUT, EXO_POSTGRES_READ_REPLICA_URL, EXO_POSTGRES_READ_WRITE,
+                EXO_POSTGRES_URL, is_read_only,
             };
-            use exo_sql::PoolConfig;
+            use exo_sql::{AdaptivePoolConfig, PoolConfig};
 
             let url = env
                 .get(EXO_POSTGRES_URL)
@@ -28,6 +44,8 @@ pub async fn create_database_executor(
                     DatabaseHelperError::Config("Env EXO_POSTGRES_URL not set".to_string())
                 })?;
 
+            let connection_settings = effective_connection_settings(env, connection_settings);
+
             // Build pool configuration from environment variables
             let pool_config = PoolConfig {
                 max_size: env
@@ -41,37 +59,163 @@ pub async fn create_database_executor(
                     .get(EXO_POOL_RECYCLE_TIMEOUT)
                     .and_then(|s| s.parse().ok()),
                 max_lifetime_secs: env.get(EXO_POOL_MAX_LIFETIME).and_then(|s| s.parse().ok()),
+                min_idle: env.get(EXO_POOL_MIN_IDLE).and_then(|s| s.parse().ok()),
+                probe_interval_secs: env
+                    .get(EXO_POOL_PROBE_INTERVAL)
+                    .and_then(|s| s.parse().ok()),
+                dns_refresh_secs: env
+                    .get(EXO_POOL_DNS_REFRESH_INTERVAL)
+                    .and_then(|s| s.parse().ok()),
+                adaptive: env
+                    .get(EXO_POOL_ADAPTIVE_MAX_SIZE)
+                    .and_then(|s| s.parse().ok())
+                    .map(|max_size| AdaptivePoolConfig {
+                        min_size: env
+                            .get(EXO_POOL_ADAPTIVE_MIN_SIZE)
+                            .and_then(|s| s.parse().ok())
+                            .unwrap_or(1),
+                        max_size,
+                        interval_secs: env
+                            .get(EXO_POOL_ADAPTIVE_INTERVAL)
+                            .and_then(|s| s.parse().ok()),
+                    }),
+                keepalive_idle_secs: connection_settings.keepalive_idle_secs,
+                keepalive_interval_secs: connection_settings.keepalive_interval_secs,
+                keepalive_retries: connection_settings.keepalive_retries,
+                application_name: connection_settings.application_name,
+                options: connection_settings.options,
             };
 
             let check_connection = env
                 .enabled(EXO_CHECK_CONNECTION_ON_STARTUP, true)
                 .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?;
-            let transaction_mode = if env
-                .enabled(EXO_POSTGRES_READ_WRITE, false)
+            // Read-only mode overrides `EXO_POSTGRES_READ_WRITE` (so that nothing may write even
+            // if the database permits it)
+            let read_write = !is_read_only(env)
                 .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?
-            {
+                && env
+                    .enabled(EXO_POSTGRES_READ_WRITE, false)
+                    .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?;
+            let transaction_mode = if read_write {
                 TransactionMode::ReadWrite
             } else {
                 TransactionMode::ReadOnly
             };
 
-            DatabaseClientManager::from_url_with_pool_config(
+            // The replica can't accept writes, and all writes go to the primary anyway
+            let replica_client = match env.get(EXO_POSTGRES_READ_REPLICA_URL) {
+                Some(replica_url) => Some(
+                    DatabaseClientManager::from_url_with_pool_config(
+                        &replica_url,
+                        check_connection,
+                        pool_config.clone(),
+                        TransactionMode::ReadOnly,
+                    )
+                    .await
+                    .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?,
+                ),
+                None => None,
+            };
+
+            let database_client = DatabaseClientManager::from_url_with_pool_config(
                 &url,
                 check_connection,
                 pool_config,
                 transaction_mode,
             )
             .await
-            .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?
+            .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?;
+
+            (database_client, replica_client)
         }
 
         #[cfg(not(feature = "network"))]
         {
-            let _ = env;
+            let _ = (env, connection_settings);
             panic!("Postgres URL feature is not enabled");
         }
     };
-    Ok(DatabaseExecutor { database_client })
+
+    #[cfg(not(target_family = "wasm"))]
+    let database_client = match common::circuit_breaker::circuit_breaker_from_env("Database", env)
+        .map_err(|e| DatabaseHelperError::BoxedError(Box::new(e)))?
+    {
+        Some(circuit_breaker) => database_client.with_circuit_breaker(circuit_breaker),
+        None => database_client,
+    };
+
+    Ok(DatabaseExecutor {
+        database_client,
+        replica_client,
+        config: execution_config(env),
+    })
+}
+
+fn execution_config(env: &dyn Environment) -> ExecutionConfig {
+    ExecutionConfig {
+        in_list_chunk_size: env
+            .get(EXO_DB_IN_LIST_CHUNK_SIZE)
+            .and_then(|s| s.parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_IN_LIST_CHUNK_SIZE),
+        statement_log: statement_log_config(env),
+    }
+}
+
+/// Statements are logged by default in the dev mode
+fn statement_log_config(env: &dyn Environment) -> StatementLogConfig {
+    let is_dev = matches!(get_deployment_mode(env), Ok(Some(DeploymentMode::Dev)));
+
+    let format = match env.get(EXO_SQL_LOG).as_deref() {
+        Some("on" | "true") => Some(StatementLogFormat::Multiline),
+        Some("pretty") => Some(StatementLogFormat::Pretty),
+        Some("off" | "false") => None,
+        Some(other) => {
+            tracing::warn!("Ignoring unknown {EXO_SQL_LOG} value '{other}'");
+            is_dev.then_some(StatementLogFormat::Multiline)
+        }
+        None => is_dev.then_some(StatementLogFormat::Multiline),
+    };
+
+    StatementLogConfig {
+        format,
+        max_param_length: env
+            .get(EXO_SQL_LOG_MAX_PARAM_LENGTH)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_PARAM_LENGTH),
+        explain_threshold: env
+            .get(EXO_SQL_EXPLAIN_THRESHOLD)
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_millis),
+    }
+}
+
+/// The connection options declared in the model, overridden by those set through the environment
+#[cfg_attr(not(feature = "network"), allow(dead_code))]
+fn effective_connection_settings(
+    env: &dyn Environment,
+    model_settings: &ConnectionSettings,
+) -> ConnectionSettings {
+    ConnectionSettings {
+        application_name: env
+            .get(EXO_POSTGRES_APPLICATION_NAME)
+            .or_else(|| model_settings.application_name.clone()),
+        options: env
+            .get(EXO_POSTGRES_OPTIONS)
+            .or_else(|| model_settings.options.clone()),
+        keepalive_idle_secs: env
+            .get(EXO_POOL_KEEPALIVE_IDLE)
+            .and_then(|s| s.parse().ok())
+            .or(model_settings.keepalive_idle_secs),
+        keepalive_interval_secs: env
+            .get(EXO_POOL_KEEPALIVE_INTERVAL)
+            .and_then(|s| s.parse().ok())
+            .or(model_settings.keepalive_interval_secs),
+        keepalive_retries: env
+            .get(EXO_POOL_KEEPALIVE_RETRIES)
+            .and_then(|s| s.parse().ok())
+            .or(model_settings.keepalive_retries),
+    }
 }
 
 #[derive(Error, Debug)]
@@ -84,8 +228,118 @@ pub enum DatabaseHelperError {
 }
 
 pub fn extractor<T: FromSqlOwned>(row: Row) -> Result<T, PostgresExecutionError> {
-    match row.try_get(0) {
-        Ok(col) => Ok(col),
-        Err(err) => Err(PostgresExecutionError::EmptyRow(err)),
+    extract_column(&row, 0).map_err(|error| PostgresExecutionError::Postgres(error.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common::env_const::EXO_ENV;
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    #[test]
+    fn environment_overrides_model_settings() {
+        let model_settings = ConnectionSettings {
+            application_name: Some("concerts".to_string()),
+            options: Some("-c statement_timeout=5000".to_string()),
+            keepalive_idle_secs: Some(60),
+            keepalive_interval_secs: None,
+            keepalive_retries: Some(3),
+        };
+
+        let env = MapEnvironment::new();
+        assert_eq!(
+            effective_connection_settings(&env, &model_settings),
+            model_settings
+        );
+
+        let env = MapEnvironment::from(HashMap::from([
+            (
+                EXO_POSTGRES_APPLICATION_NAME.to_string(),
+                "concerts-staging".to_string(),
+            ),
+            (EXO_POOL_KEEPALIVE_IDLE.to_string(), "30".to_string()),
+            (EXO_POOL_KEEPALIVE_INTERVAL.to_string(), "5".to_string()),
+        ]));
+        assert_eq!(
+            effective_connection_settings(&env, &model_settings),
+            ConnectionSettings {
+                application_name: Some("concerts-staging".to_string()),
+                options: Some("-c statement_timeout=5000".to_string()),
+                keepalive_idle_secs: Some(30),
+                keepalive_interval_secs: Some(5),
+                keepalive_retries: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn chunk_size_from_env() {
+        let chunk_size = |value: &str| {
+            let env = MapEnvironment::from(HashMap::from([(
+                EXO_DB_IN_LIST_CHUNK_SIZE.to_string(),
+                value.to_string(),
+            )]));
+            execution_config(&env).in_list_chunk_size
+        };
+
+        assert_eq!(chunk_size("500"), 500);
+        assert_eq!(chunk_size("1"), 1);
+        assert_eq!(chunk_size("0"), DEFAULT_IN_LIST_CHUNK_SIZE);
+        assert_eq!(chunk_size("-1"), DEFAULT_IN_LIST_CHUNK_SIZE);
+        assert_eq!(chunk_size("many"), DEFAULT_IN_LIST_CHUNK_SIZE);
+        assert_eq!(
+            execution_config(&MapEnvironment::new()).in_list_chunk_size,
+            DEFAULT_IN_LIST_CHUNK_SIZE
+        );
+    }
+    #[test]
+    fn statement_log_from_env() {
+        let env = |vars: &[(&str, &str)]| {
+            MapEnvironment::from(
+                vars.iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            )
+        };
+
+        assert_eq!(
+            statement_log_config(&env(&[])),
+            StatementLogConfig::default()
+        );
+
+        // On by default in the dev mode (unless turned off)
+        assert_eq!(
+            statement_log_config(&env(&[(EXO_ENV, "dev")])).format,
+            Some(StatementLogFormat::Multiline)
+        );
+        assert_eq!(
+            statement_log_config(&env(&[(EXO_ENV, "dev"), (EXO_SQL_LOG, "off")])).format,
+            None
+        );
+        assert_eq!(
+            statement_log_config(&env(&[(EXO_ENV, "dev"), (EXO_SQL_LOG, "loud")])).format,
+            Some(StatementLogFormat::Multiline)
+        );
+        assert_eq!(
+            statement_log_config(&env(&[(EXO_SQL_LOG, "loud")])).format,
+            None
+        );
+
+        assert_eq!(
+            statement_log_config(&env(&[
+                (EXO_SQL_LOG, "pretty"),
+                (EXO_SQL_LOG_MAX_PARAM_LENGTH, "20"),
+                (EXO_SQL_EXPLAIN_THRESHOLD, "250"),
+            ])),
+            StatementLogConfig {
+                format: Some(StatementLogFormat::Pretty),
+                max_param_length: 20,
+                explain_threshold: Some(Duration::from_millis(250)),
+            }
+        );
     }
 }
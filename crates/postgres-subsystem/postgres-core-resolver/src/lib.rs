@@ -1,4 +1,7 @@
 pub mod access;
 pub mod cast;
 pub mod database_helper;
+pub mod opaque_id;
 pub mod postgres_execution_error;
+pub mod retention;
+pub mod session_settings;
@@ -0,0 +1,117 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Translation of opaque ids (the primary keys of types with `@opaqueId`) in requests. Selected
+//! values are encoded by the database (see [`exo_sql::OpaqueIdKey`]).
+
+use std::borrow::Cow;
+
+use common::value::{Val, val::ValNumber};
+use exo_sql::{OpaqueIdKey, PhysicalColumn};
+use postgres_core_model::{relation::PostgresRelation, subsystem::PostgresCoreSubsystem};
+
+use crate::cast::CastError;
+
+/// The value to use for the column: the id for an opaque id (or the ids for a list of them, for
+/// operators such as `in`) if the column holds opaque ids, and the value as is otherwise.
+///
+/// Numbers are used as is, since they come from the server side (such as a context value in an
+/// access expression) and not from a client that knows only the opaque ids.
+pub fn decode_opaque_ids<'a>(
+    value: &'a Val,
+    column: &PhysicalColumn,
+    core_subsystem: &PostgresCoreSubsystem,
+) -> Result<Cow<'a, Val>, CastError> {
+    if !column.opaque_id {
+        return Ok(Cow::Borrowed(value));
+    }
+
+    let key = core_subsystem.opaque_id_key.as_ref().ok_or_else(|| {
+        CastError::Generic(format!(
+            "No key to decode the opaque ids of column '{}'",
+            column.name
+        ))
+    })?;
+
+    decode(value, key)
+}
+
+fn decode<'a>(value: &'a Val, key: &OpaqueIdKey) -> Result<Cow<'a, Val>, CastError> {
+    match value {
+        Val::String(opaque_id) => key
+            .decode(opaque_id)
+            .map(|id| Cow::Owned(Val::Number(ValNumber::I64(id))))
+            .ok_or_else(|| CastError::Generic(format!("Invalid id '{opaque_id}'"))),
+        Val::List(values) => values
+            .iter()
+            .map(|value| decode(value, key).map(Cow::into_owned))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|values| Cow::Owned(Val::List(values))),
+        _ => Ok(Cow::Borrowed(value)),
+    }
+}
+
+/// The key values of an entity reported by the database (such as the missing key in a foreign key
+/// violation) as a client knows them: encoded if the entity's primary key is an opaque id (and
+/// omitted if they can't be)
+pub fn exposed_key_values(
+    entity_name: &str,
+    key_values: Option<String>,
+    core_subsystem: &PostgresCoreSubsystem,
+) -> Option<String> {
+    let has_opaque_id = core_subsystem
+        .entity_types
+        .iter()
+        .find(|(_, entity_type)| entity_type.name == entity_name)
+        .is_some_and(|(_, entity_type)| {
+            entity_type.pk_fields().iter().any(|field| {
+                matches!(field.relation, PostgresRelation::Scalar { column_id, .. }
+                    if column_id.get_column(&core_subsystem.database).opaque_id)
+            })
+        });
+
+    if !has_opaque_id {
+        return key_values;
+    }
+
+    let key = core_subsystem.opaque_id_key.as_ref()?;
+    let id = key_values?.parse().ok()?;
+    key.encode(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_strings_and_lists() {
+        let key = OpaqueIdKey::from_secret("secret");
+        let opaque_id = |id| Val::String(key.encode(id).unwrap());
+
+        assert_eq!(
+            decode(&opaque_id(42), &key).unwrap().into_owned(),
+            Val::Number(ValNumber::I64(42))
+        );
+        assert_eq!(
+            decode(&Val::List(vec![opaque_id(1), opaque_id(2)]), &key)
+                .unwrap()
+                .into_owned(),
+            Val::List(vec![
+                Val::Number(ValNumber::I64(1)),
+                Val::Number(ValNumber::I64(2))
+            ])
+        );
+
+        // Numbers (from the server side) are used as is
+        let number = Val::Number(ValNumber::I64(7));
+        assert_eq!(decode(&number, &key).unwrap().into_owned(), number);
+
+        assert!(decode(&Val::String("42".to_string()), &key).is_err());
+    }
+}
@@ -9,11 +9,13 @@
 
 use common::context::ContextExtractionError;
 use core_resolver::{access_solver::AccessSolverError, plugin::SubsystemResolutionError};
+use exo_sql::database_error::{DatabaseError, ForeignKeyViolationKind};
+use postgres_core_model::subsystem::PostgresCoreSubsystem;
 
 use thiserror::Error;
 use tracing::error;
 
-use super::cast::CastError;
+use super::{cast::CastError, opaque_id::exposed_key_values};
 
 #[derive(Error, Debug)]
 pub enum PostgresExecutionError {
@@ -24,7 +26,7 @@ pub enum PostgresExecutionError {
     Validation(String, String),
 
     #[error("{0}")]
-    Postgres(#[from] exo_sql::database_error::DatabaseError),
+    Postgres(#[from] DatabaseError),
 
     #[error("{0}")]
     EmptyRow(#[from] tokio_postgres::Error),
@@ -46,6 +48,72 @@ pub enum PostgresExecutionError {
 
     #[error("{0}")]
     ContextExtraction(#[from] ContextExtractionError),
+
+    #[error("Another {entity_name} with the same {} already exists", .field_names.join(", "))]
+    UniqueViolation {
+        entity_name: String,
+        field_names: Vec<String>,
+    },
+
+    #[error("Referenced {entity_name}{} does not exist", key_description(.key_field_names, .key_values))]
+    ReferenceNotFound {
+        /// The relation field whose value refers to the missing entity
+        field_name: String,
+        entity_name: String,
+        key_field_names: Vec<String>,
+        key_values: Option<String>,
+    },
+
+    #[error("{entity_name}{} is still referenced by {referencing_entity_name}", key_description(.key_field_names, .key_values))]
+    ReferenceConflict {
+        entity_name: String,
+        key_field_names: Vec<String>,
+        key_values: Option<String>,
+        referencing_entity_name: String,
+        /// The relation field (of the referencing entity) that refers to this entity
+        referencing_field_name: String,
+    },
+
+    #[error("Invalid {entity_name}: {}", input_errors_description(.errors))]
+    InputRejected {
+        entity_name: String,
+        errors: Vec<InputError>,
+    },
+
+    #[error("{entity_name}.{field_name} may change at most {max_changes_per_day} times per day")]
+    ChangeLimitExceeded {
+        entity_name: String,
+        field_name: String,
+        max_changes_per_day: u64,
+    },
+}
+
+/// A problem with mutation input reported by a `@validate` function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    /// The field the problem is about (`None` for problems with the input as a whole)
+    pub field: Option<String>,
+    pub message: String,
+}
+
+fn input_errors_description(errors: &[InputError]) -> String {
+    errors
+        .iter()
+        .map(|error| match &error.field {
+            Some(field) => format!("{field}: {}", error.message),
+            None => error.message.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Describe the key of an entity for an error message (for example, " with id 42")
+fn key_description(key_field_names: &[String], key_values: &Option<String>) -> String {
+    match (key_field_names, key_values) {
+        ([field_name], Some(value)) => format!(" with {field_name} {value}"),
+        (field_names, Some(values)) => format!(" with ({}) ({values})", field_names.join(", ")),
+        (_, None) => "".to_string(),
+    }
 }
 
 impl PostgresExecutionError {
@@ -53,10 +121,151 @@ impl PostgresExecutionError {
         PostgresExecutionError::WithContext(context, Box::new(self))
     }
 
+    /// Translate a database error into an error in terms of the model. Constraint violations that
+    /// map to a known constraint become specific errors (so that clients get a precise message and
+    /// a code to branch on); everything else stays opaque.
+    pub fn from_database_error(
+        error: DatabaseError,
+        core_subsystem: &PostgresCoreSubsystem,
+    ) -> PostgresExecutionError {
+        if let DatabaseError::Precheck(_) = error {
+            return PostgresExecutionError::Authorization;
+        }
+
+        if let Some(constraint) = error
+            .unique_violation_constraint()
+            .and_then(|name| core_subsystem.unique_constraint(name))
+        {
+            return PostgresExecutionError::UniqueViolation {
+                entity_name: constraint.entity_name.clone(),
+                field_names: constraint.field_names.clone(),
+            };
+        }
+
+        if let Some(violation) = error.foreign_key_violation()
+            && let Some(constraint) = core_subsystem.foreign_key_constraint(violation.constraint)
+        {
+            let key_values = exposed_key_values(
+                &constraint.referenced_entity_name,
+                violation.key_values.map(|values| values.to_string()),
+                core_subsystem,
+            );
+
+            return match violation.kind {
+                ForeignKeyViolationKind::MissingReference => {
+                    PostgresExecutionError::ReferenceNotFound {
+                        field_name: constraint.field_name.clone(),
+                        entity_name: constraint.referenced_entity_name.clone(),
+                        key_field_names: constraint.referenced_field_names.clone(),
+                        key_values,
+                    }
+                }
+                ForeignKeyViolationKind::StillReferenced => {
+                    PostgresExecutionError::ReferenceConflict {
+                        entity_name: constraint.referenced_entity_name.clone(),
+                        key_field_names: constraint.referenced_field_names.clone(),
+                        key_values,
+                        referencing_entity_name: constraint.entity_name.clone(),
+                        referencing_field_name: constraint.field_name.clone(),
+                    }
+                }
+            };
+        }
+
+        PostgresExecutionError::Postgres(error)
+    }
+
+    /// The `extensions` to include in the GraphQL error for constraint violations
+    fn error_extensions(&self) -> Option<serde_json::Value> {
+        match self {
+            PostgresExecutionError::UniqueViolation {
+                entity_name,
+                field_names,
+            } => Some(serde_json::json!({
+                "code": "UNIQUE_VIOLATION",
+                "type": entity_name,
+                "fields": field_names,
+            })),
+            PostgresExecutionError::ReferenceNotFound {
+                field_name,
+                entity_name,
+                ..
+            } => Some(serde_json::json!({
+                "code": "NOT_FOUND",
+                "type": entity_name,
+                "field": field_name,
+            })),
+            PostgresExecutionError::ReferenceConflict {
+                entity_name,
+                referencing_entity_name,
+                referencing_field_name,
+                ..
+            } => Some(serde_json::json!({
+                "code": "CONFLICT",
+                "type": entity_name,
+                "referencedBy": {
+                    "type": referencing_entity_name,
+                    "field": referencing_field_name,
+                },
+            })),
+            PostgresExecutionError::InputRejected {
+                entity_name,
+                errors,
+            } => Some(serde_json::json!({
+                "code": "VALIDATION_FAILED",
+                "type": entity_name,
+                "errors": errors
+                    .iter()
+                    .map(|error| serde_json::json!({
+                        "field": error.field,
+                        "message": error.message,
+                    }))
+                    .collect::<Vec<_>>(),
+            })),
+            PostgresExecutionError::ChangeLimitExceeded {
+                entity_name,
+                field_name,
+                max_changes_per_day,
+            } => Some(serde_json::json!({
+                "code": "CHANGE_LIMIT_EXCEEDED",
+                "type": entity_name,
+                "field": field_name,
+                "maxChangesPerDay": max_changes_per_day,
+            })),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn circuit_open(&self) -> Option<&exo_sql::CircuitOpenError> {
+        match self {
+            PostgresExecutionError::Postgres(error) => error.circuit_open(),
+            PostgresExecutionError::WithContext(_, error) => error.circuit_open(),
+            _ => None,
+        }
+    }
+
+    /// The budget, if the operation was aborted because the request exceeded its statement budget
+    fn statement_budget_exceeded(&self) -> Option<u64> {
+        match self {
+            PostgresExecutionError::Postgres(error) => error.statement_budget_exceeded(),
+            PostgresExecutionError::WithContext(_, error) => error.statement_budget_exceeded(),
+            _ => None,
+        }
+    }
+
     pub fn user_error_message(&self) -> String {
         match self {
             PostgresExecutionError::Authorization => "Not authorized".to_string(),
             PostgresExecutionError::Validation(_, _) => self.to_string(),
+            // Names only the model fields (and the key values supplied by the client), so it is safe to show
+            PostgresExecutionError::UniqueViolation { .. }
+            | PostgresExecutionError::ReferenceNotFound { .. }
+            | PostgresExecutionError::ReferenceConflict { .. } => self.to_string(),
+            // The messages come from the application's own validators
+            PostgresExecutionError::InputRejected { .. } => self.to_string(),
+            // Names only the model field and its declared limit
+            PostgresExecutionError::ChangeLimitExceeded { .. } => self.to_string(),
             PostgresExecutionError::CastError(e) => {
                 error!("Cast error: {}", e);
                 "Unable to convert input to the expected type".to_string()
@@ -64,6 +273,9 @@ impl PostgresExecutionError {
             PostgresExecutionError::WithContext(context, e) => {
                 format!("{}: {}", e.user_error_message(), context)
             }
+            PostgresExecutionError::Postgres(DatabaseError::StatementBudgetExceeded { .. }) => {
+                self.to_string()
+            }
             // Do not reveal the underlying database error as it may expose sensitive details (such as column names or data involved in constraint violation).
             _ => {
                 error!("Postgres operation failed: {:?}", self);
@@ -96,6 +308,25 @@ impl<T> WithContext for Result<T, PostgresExecutionError> {
 
 impl From<PostgresExecutionError> for SubsystemResolutionError {
     fn from(e: PostgresExecutionError) -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(error) = e.circuit_open() {
+            return SubsystemResolutionError::Unavailable {
+                message: error.to_string(),
+                retry_after_secs: error.retry_after_secs,
+            };
+        }
+
+        if let Some(budget) = e.statement_budget_exceeded() {
+            return SubsystemResolutionError::StatementBudgetExceeded { budget };
+        }
+
+        if let Some(extensions) = e.error_extensions() {
+            return SubsystemResolutionError::ConstraintViolation {
+                message: e.user_error_message(),
+                extensions,
+            };
+        }
+
         match e {
             PostgresExecutionError::Authorization => SubsystemResolutionError::Authorization,
             PostgresExecutionError::ContextExtraction(ce) => {
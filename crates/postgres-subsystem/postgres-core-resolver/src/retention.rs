@@ -0,0 +1,154 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Purging of rows that have expired according to the `@retention` policies.
+//!
+//! Each batch selects (and locks, skipping rows locked by another server) up to `batch_size`
+//! expired rows and deletes them (or marks them through the soft-delete column), so that purging
+//! a large backlog doesn't hold locks on many rows or produce a single large transaction.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use exo_sql::{
+    BooleanColumnType, ColumnId, Database, DatabaseClient, PhysicalColumnTypeExt,
+    database_error::DatabaseError,
+};
+use postgres_core_model::retention::{RetentionAction, RetentionPolicy};
+use serde_json::{Value, json};
+
+/// The SQL statement to purge a batch of expired rows for the policy.
+///
+/// The statement takes three parameters: the number of days to retain (`int4`), the batch size
+/// (`int8`), and the current time (`timestamptz`).
+pub fn purge_statement(policy: &RetentionPolicy, database: &Database) -> String {
+    let table_name = database.get_table(policy.table_id).name.sql_name();
+    let timestamp_column = quoted_column_name(policy.timestamp_column_id, database);
+
+    let expired = format!("{timestamp_column} < $3::timestamptz - make_interval(days => $1)");
+
+    match policy.action {
+        RetentionAction::Delete => format!(
+            "DELETE FROM {table_name} WHERE ctid IN (SELECT ctid FROM {table_name} WHERE {expired} LIMIT $2 FOR UPDATE SKIP LOCKED)"
+        ),
+        RetentionAction::SoftDelete { column_id } => {
+            let soft_delete_column = quoted_column_name(column_id, database);
+
+            let (marked_value, unmarked) =
+                if column_id.get_column(database).typ.is::<BooleanColumnType>() {
+                    ("true", format!("{soft_delete_column} IS NOT TRUE"))
+                } else {
+                    ("$3::timestamptz", format!("{soft_delete_column} IS NULL"))
+                };
+
+            format!(
+                "UPDATE {table_name} SET {soft_delete_column} = {marked_value} WHERE ctid IN (SELECT ctid FROM {table_name} WHERE {expired} AND {unmarked} LIMIT $2 FOR UPDATE SKIP LOCKED)"
+            )
+        }
+    }
+}
+
+/// Purge one batch of expired rows, returning the number of rows purged
+pub async fn purge_batch(
+    client: &DatabaseClient,
+    statement: &str,
+    policy: &RetentionPolicy,
+    batch_size: usize,
+    now: DateTime<Utc>,
+) -> Result<u64, DatabaseError> {
+    let days = i32::try_from(policy.days).unwrap_or(i32::MAX);
+    let batch_size = i64::try_from(batch_size).unwrap_or(i64::MAX);
+
+    Ok(client
+        .execute(statement, &[&days, &batch_size, &now])
+        .await?)
+}
+
+fn quoted_column_name(column_id: ColumnId, database: &Database) -> String {
+    format!("\"{}\"", column_id.get_column(database).name)
+}
+
+/// Purge counts for each retention policy (in the same order as the policies in the subsystem)
+#[derive(Debug)]
+pub struct RetentionStats {
+    policies: Vec<PolicyStats>,
+}
+
+#[derive(Debug)]
+struct PolicyStats {
+    entity_name: String,
+    days: u32,
+    soft_delete: bool,
+    purged_total: AtomicU64,
+    last_purged: AtomicU64,
+    /// Milliseconds since the epoch (0 if the policy hasn't run yet)
+    last_run_millis: AtomicI64,
+    failures: AtomicU64,
+}
+
+impl RetentionStats {
+    pub fn new(policies: &[RetentionPolicy]) -> Self {
+        Self {
+            policies: policies
+                .iter()
+                .map(|policy| PolicyStats {
+                    entity_name: policy.entity_name.clone(),
+                    days: policy.days,
+                    soft_delete: matches!(policy.action, RetentionAction::SoftDelete { .. }),
+                    purged_total: AtomicU64::new(0),
+                    last_purged: AtomicU64::new(0),
+                    last_run_millis: AtomicI64::new(0),
+                    failures: AtomicU64::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    /// Record a completed run (at `at`) of the policy at `index` that purged `purged` rows
+    pub fn record_run(&self, index: usize, purged: u64, at: DateTime<Utc>) {
+        if let Some(stats) = self.policies.get(index) {
+            stats.purged_total.fetch_add(purged, Ordering::Relaxed);
+            stats.last_purged.store(purged, Ordering::Relaxed);
+            stats
+                .last_run_millis
+                .store(at.timestamp_millis(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_failure(&self, index: usize) {
+        if let Some(stats) = self.policies.get(index) {
+            stats.failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let policies: Vec<Value> = self
+            .policies
+            .iter()
+            .map(|stats| {
+                let last_run = match stats.last_run_millis.load(Ordering::Relaxed) {
+                    0 => None,
+                    millis => DateTime::from_timestamp_millis(millis).map(|time| time.to_rfc3339()),
+                };
+
+                json!({
+                    "entity": stats.entity_name,
+                    "days": stats.days,
+                    "action": if stats.soft_delete { "softDelete" } else { "delete" },
+                    "purgedTotal": stats.purged_total.load(Ordering::Relaxed),
+                    "lastPurged": stats.last_purged.load(Ordering::Relaxed),
+                    "lastRun": last_run,
+                    "failures": stats.failures.load(Ordering::Relaxed),
+                })
+            })
+            .collect();
+
+        json!({ "policies": policies })
+    }
+}
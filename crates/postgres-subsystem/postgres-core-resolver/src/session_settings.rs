@@ -0,0 +1,76 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use common::{context::RequestContext, value::Val};
+use core_resolver::context_extractor::ContextExtractor;
+use postgres_core_model::subsystem::PostgresCoreSubsystem;
+
+use crate::postgres_execution_error::PostgresExecutionError;
+
+/// Set the session settings (see
+/// [`SessionSettings`](postgres_core_model::session_settings::SessionSettings)) for the request's
+/// transaction. Must be called before locking the transaction holder (since extracting a context
+/// may need to execute a query).
+pub async fn apply_session_settings<'a>(
+    core_subsystem: &PostgresCoreSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<(), PostgresExecutionError> {
+    if let Some(settings) = session_settings(core_subsystem, request_context).await? {
+        request_context
+            .system_context
+            .transaction_holder
+            .lock()
+            .await
+            .set_session_settings(settings);
+    }
+
+    Ok(())
+}
+
+/// The values of the session settings for the request (to pass to
+/// [`TransactionHolder::set_session_settings`](exo_sql::TransactionHolder::set_session_settings)).
+///
+/// Returns `None` for internal requests (such as those made to extract a `@query` context), since
+/// they share the transaction of the request they are part of, which sets the settings itself.
+/// Settings whose context value is absent are left unset.
+pub async fn session_settings<'a>(
+    core_subsystem: &PostgresCoreSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<Option<Vec<(String, String)>>, PostgresExecutionError> {
+    if core_subsystem.session_settings.is_empty() || request_context.is_internal() {
+        return Ok(None);
+    }
+
+    let mut settings = vec![];
+    for setting in core_subsystem.session_settings.iter() {
+        let value = core_subsystem
+            .extract_context_selection(request_context, &setting.context_selection)
+            .await?;
+
+        if let Some(value) = value.and_then(setting_value) {
+            settings.push((setting.name.clone(), value));
+        }
+    }
+
+    Ok(Some(settings))
+}
+
+fn setting_value(value: &Val) -> Option<String> {
+    match value {
+        Val::Null | Val::Binary(_) => None,
+        Val::String(value) | Val::Enum(value) => Some(value.clone()),
+        Val::Bool(value) => Some(value.to_string()),
+        Val::Number(value) => Some(value.to_string()),
+        // Lists and objects (such as roles) as JSON, which functions can parse with `::jsonb`
+        Val::List(_) | Val::Object(_) => {
+            let value: serde_json::Value = value.clone().try_into().ok()?;
+            Some(value.to_string())
+        }
+    }
+}
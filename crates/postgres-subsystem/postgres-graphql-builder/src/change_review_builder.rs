@@ -0,0 +1,120 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build the mutations reviewing pending changes (`approve<Type>Change` and `reject<Type>Change`)
+//! for types that declare `@requiresApproval`
+
+use core_model::{
+    mapped_arena::SerializableSlabIndex,
+    types::{BaseOperationReturnType, OperationReturnType},
+};
+use core_model_builder::error::ModelBuildingError;
+use postgres_core_model::types::EntityType;
+use postgres_graphql_model::mutation::{
+    ChangeDecision, PostgresMutation, PostgresMutationParameters,
+};
+
+use super::{
+    naming::ToPostgresMutationNames, query_builder, system_builder::SystemContextBuilding,
+};
+
+pub fn build_expanded(building: &mut SystemContextBuilding) -> Result<(), ModelBuildingError> {
+    let mutations: Vec<_> = building
+        .core_subsystem
+        .entity_types
+        .iter()
+        .flat_map(|(entity_type_id, entity_type)| {
+            build_mutations(entity_type_id, entity_type, building)
+        })
+        .collect();
+
+    for mutation in mutations {
+        building.mutations.add(&mutation.name.to_owned(), mutation);
+    }
+
+    Ok(())
+}
+
+fn build_mutations(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> Vec<PostgresMutation> {
+    let Some(approval) = &entity_type.approval else {
+        return vec![];
+    };
+
+    let change_type = &building.core_subsystem.entity_types[approval.change_type_id];
+
+    let mutation = |name: String, decision: ChangeDecision, doc_comments: String| {
+        PostgresMutation {
+            name,
+            parameters: PostgresMutationParameters::ReviewChange {
+                predicate_params: query_builder::pk_predicate_params(
+                    change_type,
+                    &building.predicate_types,
+                    &building.core_subsystem.database,
+                ),
+                subject_type_id: entity_type_id,
+                decision,
+            },
+            // We return null if the specified change doesn't exist (or is no longer pending)
+            return_type: OperationReturnType::Optional(Box::new(OperationReturnType::Plain(
+                BaseOperationReturnType {
+                    associated_type_id: approval.change_type_id,
+                    type_name: change_type.name.clone(),
+                },
+            ))),
+            doc_comments: Some(doc_comments),
+            exposed_in_schema: true,
+        }
+    };
+
+    vec![
+        mutation(
+            entity_type.pk_approve_change(),
+            ChangeDecision::Approve,
+            format!(
+                "Approve the pending change to {} with the provided primary key (applying it).",
+                entity_type.name
+            ),
+        ),
+        mutation(
+            entity_type.pk_reject_change(),
+            ChangeDecision::Reject,
+            format!(
+                "Reject the pending change to {} with the provided primary key.",
+                entity_type.name
+            ),
+        ),
+    ]
+}
+
+/// Is the type the change type of a type declared with `@requiresApproval` (or using `@guard`)?
+/// Its rows are created and updated only through the approval workflow (or when enforcing the
+/// guards), so its own mutations are not exposed.
+pub fn is_change_type(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    building: &SystemContextBuilding,
+) -> bool {
+    building
+        .core_subsystem
+        .entity_types
+        .iter()
+        .any(|(_, entity_type)| {
+            entity_type
+                .approval
+                .as_ref()
+                .is_some_and(|approval| approval.change_type_id == entity_type_id)
+                || entity_type
+                    .field_guards
+                    .as_ref()
+                    .is_some_and(|field_guards| field_guards.change_type_id == entity_type_id)
+        })
+}
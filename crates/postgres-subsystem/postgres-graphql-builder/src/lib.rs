@@ -10,11 +10,13 @@
 pub use plugin::PostgresGraphQLSubsystemBuilder;
 
 mod builder;
+mod change_review_builder;
 mod create_mutation_builder;
 mod delete_mutation_builder;
 mod mutation_builder;
 mod naming;
 mod order_by_type_builder;
+mod ownership_transfer_builder;
 mod plugin;
 mod predicate_builder;
 mod query_builder;
@@ -24,7 +24,7 @@ use postgres_graphql_model::{
 
 use postgres_core_model::{
     access::DatabaseAccessPrimitiveExpression,
-    relation::PostgresRelation,
+    relation::{PostgresRelation, TransitiveRelation},
     types::{EntityType, PostgresField, PostgresFieldType, PostgresType, TypeIndex, base_type},
 };
 
@@ -36,8 +36,8 @@ use postgres_core_builder::resolved_type::{
 };
 
 use super::{
-    builder::Builder, create_mutation_builder::CreateMutationBuilder,
-    delete_mutation_builder::DeleteMutationBuilder,
+    builder::Builder, change_review_builder, create_mutation_builder::CreateMutationBuilder,
+    delete_mutation_builder::DeleteMutationBuilder, ownership_transfer_builder,
     reference_input_type_builder::ReferenceInputTypeBuilder, system_builder::SystemContextBuilding,
     update_mutation_builder::UpdateMutationBuilder,
 };
@@ -73,6 +73,9 @@ pub fn build_expanded(building: &mut SystemContextBuilding) -> Result<(), ModelB
     UpdateMutationBuilder {}.build_expanded(building)?;
     DeleteMutationBuilder {}.build_expanded(building)?;
 
+    ownership_transfer_builder::build_expanded(building)?;
+    change_review_builder::build_expanded(building)?;
+
     Ok(())
 }
 
@@ -106,6 +109,7 @@ pub trait MutationBuilder {
         }
 
         let pk_fields = entity_type.pk_fields();
+        let exposed_in_schema = !change_review_builder::is_change_type(entity_type_id, building);
 
         let single_mutation = if pk_fields.is_empty() {
             None
@@ -118,7 +122,7 @@ pub trait MutationBuilder {
                     type_name: entity_type.name.clone(),
                 }),
                 doc_comments: Self::single_mutation_doc_comments(entity_type),
-                exposed_in_schema: true,
+                exposed_in_schema,
             })
         };
 
@@ -132,7 +136,7 @@ pub trait MutationBuilder {
                 },
             ))),
             doc_comments: Self::multi_mutation_doc_comments(entity_type),
-            exposed_in_schema: true,
+            exposed_in_schema,
         };
 
         match single_mutation {
@@ -329,6 +333,19 @@ pub trait DataParamBuilder<D> {
         container_type: Option<&str>,
         building: &SystemContextBuilding,
     ) -> Option<PostgresField<MutationType>> {
+        if let PostgresRelation::Transitive(TransitiveRelation {
+            many_to_many: Some(_),
+            ..
+        }) = &field.relation
+        {
+            return Self::compute_many_to_many_data_field(
+                field,
+                top_level_type,
+                container_type,
+                building,
+            );
+        }
+
         if field.readonly {
             return None;
         }
@@ -363,6 +380,8 @@ pub trait DataParamBuilder<D> {
                         typ: to_mutation_type(&field.typ, mutation_type_kind, building).optional(),
                         relation: field.relation.clone(),
                         default_value: field.default_value.clone(),
+                        read_default: None,
+                        mask: None,
                         access: field.access.clone(),
                         readonly: field.readonly,
                         type_validation: field.type_validation.clone(),
@@ -398,6 +417,8 @@ pub trait DataParamBuilder<D> {
                             access: field.access.clone(),
                             relation: field.relation.clone(),
                             default_value: field.default_value.clone(),
+                            read_default: None,
+                            mask: None,
                             readonly: field.readonly,
                             type_validation: field.type_validation.clone(),
                             doc_comments: None,
@@ -416,6 +437,8 @@ pub trait DataParamBuilder<D> {
                     access: field.access.clone(),
                     relation: field.relation.clone(),
                     default_value: field.default_value.clone(),
+                    read_default: None,
+                    mask: None,
                     readonly: field.readonly,
                     type_validation: field.type_validation.clone(),
                     doc_comments: None,
@@ -461,14 +484,52 @@ pub trait DataParamBuilder<D> {
                         access: field.access.clone(),
                         relation: field.relation.clone(),
                         default_value: field.default_value.clone(),
+                        read_default: None,
+                        mask: None,
                         readonly: field.readonly,
                         type_validation: field.type_validation.clone(),
                         doc_comments: None,
                     }),
                 }
             }
-            PostgresRelation::Computed(_) | PostgresRelation::Transitive(_) => None,
+            PostgresRelation::Computed(_)
+            | PostgresRelation::SqlExpression(_)
+            | PostgresRelation::Transitive(_) => None,
+        }
+    }
+
+    /// A many-to-many field accepts `connect` and `disconnect` (through the `<Type>LinkInput` type) when
+    /// updating its (top-level) entity, since the entities must exist to be linked
+    fn compute_many_to_many_data_field(
+        field: &PostgresField<EntityType>,
+        top_level_type: Option<&EntityType>,
+        container_type: Option<&str>,
+        building: &SystemContextBuilding,
+    ) -> Option<PostgresField<MutationType>> {
+        if Self::data_param_role() != DataParamRole::Update
+            || top_level_type.map(|t| t.name.as_str()) != container_type
+        {
+            return None;
         }
+
+        let field_type_name = field.typ.name().link_type();
+        let field_type_id = building.mutation_types.get_id(&field_type_name)?;
+
+        Some(PostgresField {
+            name: field.name.clone(),
+            typ: FieldType::Optional(Box::new(FieldType::Plain(PostgresFieldType {
+                type_name: field_type_name,
+                type_id: TypeIndex::Composite(field_type_id),
+            }))),
+            access: field.access.clone(),
+            relation: field.relation.clone(),
+            default_value: None,
+            read_default: None,
+            mask: None,
+            readonly: false,
+            type_validation: None,
+            doc_comments: None,
+        })
     }
 
     fn compute_one_to_many_data_field(
@@ -519,6 +580,8 @@ pub trait DataParamBuilder<D> {
                         access: field.access.clone(),
                         relation: field.relation.clone(),
                         default_value: field.default_value.clone(),
+                        read_default: None,
+                        mask: None,
                         readonly: field.readonly,
                         type_validation: field.type_validation.clone(),
                         doc_comments: None,
@@ -9,10 +9,25 @@ pub(super) trait ToPostgresQueryName {
     fn collection_query(&self) -> String;
     /// Aggregate query name (e.g. `concertAgg`)
     fn aggregate_query(&self) -> String;
+    /// Page query name (e.g. `concertsPage`)
+    fn page_query(&self) -> String;
+    /// Batch query name (e.g. `concertsByIds`)
+    fn batch_query(&self) -> String;
 
     /// Unique query name (e.g. `concertByTitle`)
     /// `constraint_name` is the name of the unique constraint in the database (possibly in snake case or camel case)
     fn unique_query(&self, constraint_name: &str) -> String;
+
+    /// Similarity query name (e.g. `similarConcerts`)
+    /// `vector_field_name` is needed to disambiguate when the type has multiple vector fields (e.g. `similarConcertsByPosterEmbedding`)
+    fn similarity_query(&self, vector_field_name: Option<&str>) -> String;
+
+    /// Tree query names for a self-referential relation (e.g. `categoryDescendants` and `categoryAncestors`)
+    fn descendants_query(&self) -> String;
+    fn ancestors_query(&self) -> String;
+
+    /// Chunk query name for a field declared with `@chunked` (e.g. `concertDescriptionChunks`)
+    fn chunks_query(&self, field_name: &str) -> String;
 }
 
 fn to_query(name: &str) -> String {
@@ -27,6 +42,12 @@ pub(crate) trait ToPostgresTypeNames {
     fn update_type(&self) -> String;
     /// Reference type name (e.g. `ConcertReferenceInput`)
     fn reference_type(&self) -> String;
+    /// Type to connect and disconnect entities of a many-to-many field (e.g. `ConcertLinkInput`)
+    fn link_type(&self) -> String;
+    /// Similarity query result type name (e.g. `ConcertSimilarity`)
+    fn similarity_type(&self) -> String;
+    /// Page query result type name (e.g. `ConcertPage`)
+    fn page_type(&self) -> String;
 }
 
 fn to_creation_type(name: &str) -> String {
@@ -41,6 +62,18 @@ fn to_reference_type(name: &str) -> String {
     format!("{name}ReferenceInput")
 }
 
+fn to_link_type(name: &str) -> String {
+    format!("{name}LinkInput")
+}
+
+fn to_similarity_type(name: &str) -> String {
+    format!("{name}Similarity")
+}
+
+fn to_page_type(name: &str) -> String {
+    format!("{name}Page")
+}
+
 impl ToPostgresTypeNames for str {
     fn creation_type(&self) -> String {
         to_creation_type(self)
@@ -53,6 +86,18 @@ impl ToPostgresTypeNames for str {
     fn reference_type(&self) -> String {
         to_reference_type(self)
     }
+
+    fn link_type(&self) -> String {
+        to_link_type(self)
+    }
+
+    fn similarity_type(&self) -> String {
+        to_similarity_type(self)
+    }
+
+    fn page_type(&self) -> String {
+        to_page_type(self)
+    }
 }
 
 impl<T: ToPlural> ToPostgresTypeNames for T {
@@ -67,6 +112,18 @@ impl<T: ToPlural> ToPostgresTypeNames for T {
     fn reference_type(&self) -> String {
         to_reference_type(&self.self_name())
     }
+
+    fn link_type(&self) -> String {
+        to_link_type(&self.self_name())
+    }
+
+    fn similarity_type(&self) -> String {
+        to_similarity_type(&self.self_name())
+    }
+
+    fn page_type(&self) -> String {
+        to_page_type(&self.self_name())
+    }
 }
 
 impl<T: ToPlural> ToPostgresQueryName for T {
@@ -82,6 +139,14 @@ impl<T: ToPlural> ToPostgresQueryName for T {
         format!("{}Agg", self.collection_query())
     }
 
+    fn page_query(&self) -> String {
+        format!("{}Page", self.collection_query())
+    }
+
+    fn batch_query(&self) -> String {
+        format!("{}ByIds", self.collection_query())
+    }
+
     fn unique_query(&self, constraint_name: &str) -> String {
         format!(
             "{}By{}",
@@ -89,6 +154,33 @@ impl<T: ToPlural> ToPostgresQueryName for T {
             constraint_name.to_upper_camel_case()
         )
     }
+
+    fn similarity_query(&self, vector_field_name: Option<&str>) -> String {
+        let name = format!("similar{}", self.to_plural().to_upper_camel_case());
+
+        match vector_field_name {
+            Some(vector_field_name) => {
+                format!("{name}By{}", vector_field_name.to_upper_camel_case())
+            }
+            None => name,
+        }
+    }
+
+    fn descendants_query(&self) -> String {
+        format!("{}Descendants", self.pk_query())
+    }
+
+    fn ancestors_query(&self) -> String {
+        format!("{}Ancestors", self.pk_query())
+    }
+
+    fn chunks_query(&self, field_name: &str) -> String {
+        format!(
+            "{}{}Chunks",
+            self.pk_query(),
+            field_name.to_upper_camel_case()
+        )
+    }
 }
 
 fn to_create(name: &str) -> String {
@@ -117,6 +209,12 @@ pub trait ToPostgresMutationNames {
     fn collection_delete(&self) -> String;
     /// Plural update name (e.g. `updateConcerts`)
     fn collection_update(&self) -> String;
+    /// Ownership transfer name (e.g. `transferConcertOwnership`)
+    fn pk_transfer_ownership(&self) -> String;
+    /// Change approval name (e.g. `approveConcertChange`)
+    fn pk_approve_change(&self) -> String;
+    /// Change rejection name (e.g. `rejectConcertChange`)
+    fn pk_reject_change(&self) -> String;
 }
 
 impl<T: ToPlural> ToPostgresMutationNames for T {
@@ -143,4 +241,16 @@ impl<T: ToPlural> ToPostgresMutationNames for T {
     fn collection_update(&self) -> String {
         to_update(&self.to_plural())
     }
+
+    fn pk_transfer_ownership(&self) -> String {
+        format!("transfer{}Ownership", self.self_name())
+    }
+
+    fn pk_approve_change(&self) -> String {
+        format!("approve{}Change", self.self_name())
+    }
+
+    fn pk_reject_change(&self) -> String {
+        format!("reject{}Change", self.self_name())
+    }
 }
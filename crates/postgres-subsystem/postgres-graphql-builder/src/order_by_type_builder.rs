@@ -21,16 +21,15 @@ use postgres_graphql_model::{
 use postgres_core_model::relation::PostgresRelation;
 use postgres_core_model::types::{EntityType, PostgresField, PostgresPrimitiveType, PostgresType};
 
-use postgres_core_model::access::Access;
+use postgres_core_model::{access::Access, relation::EmbeddedFieldLink};
 
 use postgres_core_builder::shallow::Shallow;
 
 use super::system_builder::SystemContextBuilding;
+use crate::utils::embedded_field_link;
 
 use postgres_core_builder::{
-    resolved_type::{
-        ResolvedCompositeType, ResolvedField, ResolvedType, ResolvedTypeEnv, SerializableTypeHint,
-    },
+    resolved_type::{ResolvedField, ResolvedType, ResolvedTypeEnv, SerializableTypeHint},
     type_provider::VectorTypeHint,
 };
 
@@ -40,8 +39,10 @@ impl crate::shallow::Shallow for OrderByParameter {
             name: String::default(),
             typ: FieldType::Plain(OrderByParameterTypeWrapper::shallow()),
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         }
     }
 }
@@ -75,10 +76,7 @@ pub fn build_shallow(resolved_env: &ResolvedTypeEnv, building: &mut SystemContex
         .add(&vector_ordering_type_name, vector_ordering_type);
 
     for (_, typ) in resolved_env.resolved_types.iter() {
-        if let ResolvedType::Composite(ResolvedCompositeType { representation, .. }) = typ {
-            if representation.is_json_like() {
-                continue;
-            }
+        if let ResolvedType::Composite(_) = typ {
             let shallow_type = create_shallow_type(typ);
             let param_type_name = shallow_type.name.clone();
             building.order_by_types.add(&param_type_name, shallow_type);
@@ -87,12 +85,7 @@ pub fn build_shallow(resolved_env: &ResolvedTypeEnv, building: &mut SystemContex
 }
 
 pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemContextBuilding) {
-    for (_, entity_type) in building
-        .core_subsystem
-        .entity_types
-        .iter()
-        .filter(|(_, et)| !et.representation.is_json_like())
-    {
+    for (_, entity_type) in building.core_subsystem.entity_types.iter() {
         let param_type_name = get_parameter_type_name(&entity_type.name, false);
         let existing_param_id = building.order_by_types.get_id(&param_type_name);
 
@@ -166,6 +159,7 @@ fn new_param(
     order_by_types: &MappedArena<OrderByParameterType>,
     access: Option<Access>,
     type_hint: Option<&SerializableTypeHint>,
+    embedded_field: Option<EmbeddedFieldLink>,
 ) -> OrderByParameter {
     let (param_type_name, param_type_id) =
         order_by_param_type(entity_type_name, is_primitive, order_by_types);
@@ -195,12 +189,14 @@ fn new_param(
         name: name.to_string(),
         typ: FieldType::Optional(Box::new(wrapped_type)),
         column_path_link,
+        transitive_links: vec![],
         access,
         vector_distance_function: type_hint.and_then(|hint| {
             (hint.0.as_ref() as &dyn std::any::Any)
                 .downcast_ref::<VectorTypeHint>()
                 .and_then(|v| v.distance_function)
         }),
+        embedded_field,
     }
 }
 
@@ -228,27 +224,42 @@ pub fn new_field_param(
     let field_entity_type =
         field_type_id.to_type(primitive_types.values_ref(), entity_types.values_ref());
 
-    if let PostgresType::Composite(ct) = &field_entity_type
-        && ct.representation.is_json_like()
-    {
-        return None;
-    }
-
-    let column_path_link = match &entity_field.relation {
-        PostgresRelation::Computed(_) | PostgresRelation::Embedded => return None,
-        PostgresRelation::Transitive(_) => return None,
-        relation => Some(relation.column_path_link(database)),
+    let mut transitive_links = vec![];
+    let (column_path_link, embedded_field) = match &entity_field.relation {
+        PostgresRelation::Computed(_) | PostgresRelation::SqlExpression(_) => return None,
+        // A to-one relation path (ordered by the entity at the end of the path)
+        PostgresRelation::Transitive(transitive) => {
+            let mut links = transitive.column_path_links(database).into_iter();
+            let first_link = links.next()?;
+            transitive_links = links.collect();
+            (Some(first_link), None)
+        }
+        // A field inside a JSON value (ordered by the value under its key)
+        PostgresRelation::Embedded => (
+            None,
+            Some(embedded_field_link(
+                resolved_field,
+                entity_field,
+                primitive_types,
+                entity_types,
+            )?),
+        ),
+        relation => (Some(relation.column_path_link(database)), None),
     };
 
-    Some(new_param(
-        &entity_field.name,
-        field_entity_type.name(),
-        matches!(field_entity_type, PostgresType::Primitive(_)),
-        column_path_link,
-        order_by_types,
-        Some(entity_field.access.clone()),
-        resolved_field.type_hint.as_ref(),
-    ))
+    Some(OrderByParameter {
+        transitive_links,
+        ..new_param(
+            &entity_field.name,
+            field_entity_type.name(),
+            matches!(field_entity_type, PostgresType::Primitive(_)),
+            column_path_link,
+            order_by_types,
+            Some(entity_field.access.clone()),
+            resolved_field.type_hint.as_ref(),
+            embedded_field,
+        )
+    })
 }
 
 pub fn new_root_param(
@@ -264,6 +275,7 @@ pub fn new_root_param(
         order_by_types,
         None,
         None,
+        None,
     )
 }
 
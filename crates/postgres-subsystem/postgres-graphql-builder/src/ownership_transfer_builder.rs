@@ -0,0 +1,92 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Build the ownership transfer mutations (`transfer<Type>Ownership`) for types that declare
+//! `@ownership(transfer=...)`
+
+use core_model::{
+    mapped_arena::SerializableSlabIndex,
+    types::{BaseOperationReturnType, FieldType, OperationReturnType},
+};
+use core_model_builder::error::ModelBuildingError;
+use postgres_core_model::types::EntityType;
+use postgres_graphql_model::mutation::{
+    NewOwnerParameter, PostgresMutation, PostgresMutationParameters,
+};
+
+use super::{
+    naming::ToPostgresMutationNames, query_builder, system_builder::SystemContextBuilding,
+};
+
+pub fn build_expanded(building: &mut SystemContextBuilding) -> Result<(), ModelBuildingError> {
+    let mutations = building
+        .core_subsystem
+        .entity_types
+        .iter()
+        .map(|(entity_type_id, entity_type)| build_mutation(entity_type_id, entity_type, building))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for mutation in mutations.into_iter().flatten() {
+        building.mutations.add(&mutation.name.to_owned(), mutation);
+    }
+
+    Ok(())
+}
+
+fn build_mutation(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> Result<Option<PostgresMutation>, ModelBuildingError> {
+    let Some(transfer) = &entity_type.ownership_transfer else {
+        return Ok(None);
+    };
+
+    if entity_type.representation.is_json_like() || entity_type.pk_fields().is_empty() {
+        return Ok(None);
+    }
+
+    let owner_field = entity_type
+        .field_by_name(&transfer.field_name)
+        .ok_or_else(|| {
+            ModelBuildingError::Generic(format!(
+                "Owner field '{}' not found in type '{}'",
+                transfer.field_name, entity_type.name
+            ))
+        })?;
+
+    let new_owner_param = NewOwnerParameter {
+        name: "newOwner".to_string(),
+        typ: FieldType::Plain(owner_field.typ.innermost().clone()),
+    };
+
+    Ok(Some(PostgresMutation {
+        name: entity_type.pk_transfer_ownership(),
+        parameters: PostgresMutationParameters::TransferOwnership {
+            predicate_params: query_builder::pk_predicate_params(
+                entity_type,
+                &building.predicate_types,
+                &building.core_subsystem.database,
+            ),
+            new_owner_param,
+        },
+        // We return null if the specified id doesn't exist (or the transfer isn't permitted for that row)
+        return_type: OperationReturnType::Optional(Box::new(OperationReturnType::Plain(
+            BaseOperationReturnType {
+                associated_type_id: entity_type_id,
+                type_name: entity_type.name.clone(),
+            },
+        ))),
+        doc_comments: Some(format!(
+            "Transfer the ownership of the {} with the provided primary key to the provided owner.",
+            entity_type.name
+        )),
+        exposed_in_schema: true,
+    }))
+}
@@ -54,9 +54,44 @@ impl PostgresGraphQLSubsystemBuilder {
                     .filter(|(_, q)| q.exposed_in_schema)
                     .map(|(_, q)| q.name.clone());
 
+                let similarity_query_names = subsystem
+                    .similarity_queries
+                    .iter()
+                    .filter(|(_, q)| q.exposed_in_schema)
+                    .map(|(_, q)| q.name.clone());
+
+                let tree_query_names = subsystem
+                    .tree_queries
+                    .iter()
+                    .filter(|(_, q)| q.exposed_in_schema)
+                    .map(|(_, q)| q.name.clone());
+
+                let batch_query_names = subsystem
+                    .batch_queries
+                    .iter()
+                    .filter(|(_, q)| q.exposed_in_schema)
+                    .map(|(_, q)| q.name.clone());
+
+                let page_query_names = subsystem
+                    .page_queries
+                    .iter()
+                    .filter(|(_, q)| q.exposed_in_schema)
+                    .map(|(_, q)| q.name.clone());
+
+                let chunk_query_names = subsystem
+                    .chunk_queries
+                    .iter()
+                    .filter(|(_, q)| q.exposed_in_schema)
+                    .map(|(_, q)| q.name.clone());
+
                 pk_query_names
                     .chain(collection_query_names)
                     .chain(aggregate_query_names)
+                    .chain(similarity_query_names)
+                    .chain(tree_query_names)
+                    .chain(batch_query_names)
+                    .chain(page_query_names)
+                    .chain(chunk_query_names)
                     .collect()
             },
             mutation_names: subsystem
@@ -23,6 +23,7 @@ use postgres_core_model::{
 };
 
 use super::system_builder::SystemContextBuilding;
+use crate::utils::embedded_field_link;
 
 use postgres_core_builder::shallow::Shallow;
 use postgres_core_builder::type_provider::PRIMITIVE_TYPE_PROVIDER_REGISTRY;
@@ -37,8 +38,10 @@ impl crate::shallow::Shallow for PredicateParameter {
             name: String::new(),
             typ: FieldType::Plain(PredicateParameterTypeWrapper::shallow()),
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         }
     }
 }
@@ -120,6 +123,15 @@ pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemCon
             }
             ResolvedType::Composite(c @ ResolvedCompositeType { .. }) => {
                 if c.representation.is_json_like() {
+                    // Only the generic filter type (for filtering on the embedded fields)
+                    let shallow_type = PredicateParameterType {
+                        name: get_filter_type_name(&c.name),
+                        kind: PredicateParameterTypeKind::ImplicitEqual, // Will be set to the correct value in expand_type
+                        underlying_type: None,
+                    };
+                    building
+                        .predicate_types
+                        .add(&shallow_type.name.clone(), shallow_type);
                     continue;
                 }
 
@@ -175,25 +187,45 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
         let existing_param_id = building.predicate_types.get_id(&param_type_name);
 
         let new_kind = expand_primitive_type(primitive_type, building);
+        let nullable_kind =
+            with_null_checks(expand_primitive_type(primitive_type, building), building);
+        add_nullable_filter_type(
+            &param_type_name,
+            nullable_kind,
+            &mut building.predicate_types,
+        );
         building.predicate_types[existing_param_id.unwrap()].kind = new_kind;
 
         let array_param_type_name = get_array_filter_type_name(&primitive_type.name);
         if let Some(array_param_id) = building.predicate_types.get_id(&array_param_type_name) {
             let array_kind = expand_array_type(primitive_type, building);
+            let nullable_kind =
+                with_null_checks(expand_array_type(primitive_type, building), building);
+            add_nullable_filter_type(
+                &array_param_type_name,
+                nullable_kind,
+                &mut building.predicate_types,
+            );
             building.predicate_types[array_param_id].kind = array_kind;
         }
     }
 
     for (entity_type_id, entity_type) in building.core_subsystem.entity_types.iter() {
-        if entity_type.representation.is_json_like() {
-            continue;
-        }
-
         let resolved_type = resolved_env
             .resolved_types
             .get_by_key(&entity_type.name)
             .unwrap();
 
+        if entity_type.representation.is_json_like() {
+            let param_type_name = get_filter_type_name(&entity_type.name);
+            let existing_param_id = building.predicate_types.get_id(&param_type_name);
+
+            let new_kind = expand_embedded_type(resolved_type, entity_type, building);
+            // No `underlying_type`, since access is controlled by the field holding the JSON value
+            building.predicate_types[existing_param_id.unwrap()].kind = new_kind;
+            continue;
+        }
+
         {
             let param_type_name = get_filter_type_name(&entity_type.name);
             let existing_param_id = building.predicate_types.get_id(&param_type_name);
@@ -242,6 +274,74 @@ pub fn get_some_filter_type_name(type_name: &str) -> String {
     format!("{type_name}SomeFilter")
 }
 
+/// The filter type for optional fields (such as `IntNullableFilter` for `IntFilter`), which also
+/// offers the null checks
+fn get_nullable_filter_type_name(filter_type_name: &str) -> String {
+    format!(
+        "{}NullableFilter",
+        filter_type_name.trim_end_matches("Filter")
+    )
+}
+
+/// The operators of an operator filter type along with the null checks (`None` for other kinds of
+/// filter types)
+fn with_null_checks(
+    kind: PredicateParameterTypeKind,
+    building: &SystemContextBuilding,
+) -> Option<PredicateParameterTypeKind> {
+    match kind {
+        PredicateParameterTypeKind::Operator(parameters) => {
+            Some(PredicateParameterTypeKind::Operator(
+                parameters
+                    .into_iter()
+                    .chain(null_check_parameters(building))
+                    .collect(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Add the nullable counterpart of a filter type (if it has operators)
+fn add_nullable_filter_type(
+    filter_type_name: &str,
+    nullable_kind: Option<PredicateParameterTypeKind>,
+    predicate_types: &mut MappedArena<PredicateParameterType>,
+) {
+    let Some(kind) = nullable_kind else {
+        return;
+    };
+
+    let nullable_type_name = get_nullable_filter_type_name(filter_type_name);
+    predicate_types.add(
+        &nullable_type_name,
+        PredicateParameterType {
+            name: nullable_type_name.clone(),
+            kind,
+            underlying_type: None,
+        },
+    );
+}
+
+/// The filter type to use for a scalar field: the nullable one (if any) for optional fields
+fn scalar_filter_type_name(
+    filter_type_name: String,
+    optional: bool,
+    building: &SystemContextBuilding,
+) -> String {
+    let nullable_type_name = get_nullable_filter_type_name(&filter_type_name);
+    if optional
+        && building
+            .predicate_types
+            .get_id(&nullable_type_name)
+            .is_some()
+    {
+        nullable_type_name
+    } else {
+        filter_type_name
+    }
+}
+
 fn expand_primitive_type(
     typ: &PostgresPrimitiveType,
     building: &SystemContextBuilding,
@@ -268,8 +368,10 @@ fn expand_enum_type(
                 type_id: param_type_id,
             }))),
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         })
         .collect();
 
@@ -292,8 +394,8 @@ fn expand_entity_type(
         if matches!(
             field.relation,
             PostgresRelation::Computed(_)
+                | PostgresRelation::SqlExpression(_)
                 | PostgresRelation::Embedded
-                | PostgresRelation::Transitive(_)
         ) {
             return false;
         }
@@ -301,7 +403,9 @@ fn expand_entity_type(
         let field_type_id = &field.typ.innermost().type_id;
         if let TypeIndex::Composite(index) = field_type_id {
             let field_type = &building.core_subsystem.entity_types[*index];
+            // Fields of a JSON type may be filtered on their embedded fields (but not lists of them)
             !field_type.representation.is_json_like()
+                || !matches!(field.typ.base_type(), FieldType::List(_))
         } else {
             true
         }
@@ -314,24 +418,35 @@ fn expand_entity_type(
         .iter()
         .filter(|field| is_normal_field(field, building))
         .map(|field| {
+            let optional = matches!(field.typ, FieldType::Optional(_));
             let param_type_name = if matches!(field.relation, PostgresRelation::Scalar { .. })
                 && matches!(field.typ.base_type(), FieldType::List(_))
             {
-                get_array_filter_type_name(field.typ.name())
-            } else if matches!(
-                field.relation,
-                PostgresRelation::OneToMany(_) | PostgresRelation::Transitive(_)
-            ) {
+                scalar_filter_type_name(
+                    get_array_filter_type_name(field.typ.name()),
+                    optional,
+                    building,
+                )
+            } else if matches!(field.relation, PostgresRelation::Scalar { .. }) {
+                scalar_filter_type_name(get_filter_type_name(field.typ.name()), optional, building)
+            } else if matches!(field.relation, PostgresRelation::OneToMany(_))
+                || (matches!(field.relation, PostgresRelation::Transitive(_))
+                    && matches!(field.typ.base_type(), FieldType::List(_)))
+            {
                 get_some_filter_type_name(field.typ.name())
             } else {
                 get_filter_type_name(field.typ.name())
             };
 
-            let column_path_link = Some(
-                field
-                    .relation
-                    .column_path_link(&building.core_subsystem.database),
-            );
+            let database = &building.core_subsystem.database;
+            // A relation path is followed link by link (starting with the link of its first step)
+            let (column_path_link, transitive_links) = match &field.relation {
+                PostgresRelation::Transitive(transitive) => {
+                    let mut links = transitive.column_path_links(database).into_iter();
+                    (links.next(), links.collect())
+                }
+                relation => (Some(relation.column_path_link(database)), vec![]),
+            };
 
             let resolved_field = resolved_type
                 .as_composite()
@@ -349,16 +464,82 @@ fn expand_entity_type(
                     },
                 ))),
                 column_path_link,
+                transitive_links,
                 access: Some(field.access.clone()),
                 vector_distance_function: resolved_field.type_hint.as_ref().and_then(|hint| {
                     (hint.0.as_ref() as &dyn std::any::Any)
                         .downcast_ref::<VectorTypeHint>()
                         .and_then(|v| v.distance_function)
                 }),
+                embedded_field: None,
             }
         })
         .collect();
 
+    PredicateParameterTypeKind::Composite {
+        field_params,
+        logical_op_params: logical_op_params(entity_type_name, building),
+    }
+}
+
+/// Expand the filter type of a JSON type (such as `AddressFilter`), whose parameters follow the
+/// keys of the JSON value
+fn expand_embedded_type(
+    resolved_type: &ResolvedType,
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> PredicateParameterTypeKind {
+    let field_params = entity_type
+        .fields
+        .iter()
+        .flat_map(|field| {
+            let resolved_field = resolved_type
+                .as_composite()
+                .fields
+                .iter()
+                .find(|f| f.name == field.name)
+                .unwrap();
+
+            let embedded_field = embedded_field_link(
+                resolved_field,
+                field,
+                &building.core_subsystem.primitive_types,
+                &building.core_subsystem.entity_types,
+            )?;
+
+            let param_type_name = scalar_filter_type_name(
+                get_filter_type_name(field.typ.name()),
+                matches!(field.typ, FieldType::Optional(_)),
+                building,
+            );
+
+            Some(PredicateParameter {
+                name: field.name.to_string(),
+                typ: FieldType::Optional(Box::new(FieldType::Plain(
+                    PredicateParameterTypeWrapper {
+                        type_id: building.predicate_types.get_id(&param_type_name)?,
+                        name: param_type_name,
+                    },
+                ))),
+                column_path_link: None,
+                transitive_links: vec![],
+                access: None,
+                vector_distance_function: None,
+                embedded_field: Some(embedded_field),
+            })
+        })
+        .collect();
+
+    PredicateParameterTypeKind::Composite {
+        field_params,
+        logical_op_params: logical_op_params(&entity_type.name, building),
+    }
+}
+
+fn logical_op_params(
+    entity_type_name: &str,
+    building: &SystemContextBuilding,
+) -> Vec<PredicateParameter> {
     #[derive(Debug, PartialEq, Eq)]
     enum LogicalOpModifier {
         List,     // logical op takes a list of predicates
@@ -371,7 +552,7 @@ fn expand_entity_type(
         ("not", LogicalOpModifier::Optional),
     ];
 
-    let logical_op_params = logical_ops
+    logical_ops
         .into_iter()
         .map(|(name, type_modifier)| {
             let param_type_name = get_filter_type_name(entity_type_name);
@@ -393,16 +574,13 @@ fn expand_entity_type(
                 name: name.to_string(),
                 typ: param_field_type,
                 column_path_link: None,
+                transitive_links: vec![],
                 access: None,
                 vector_distance_function: None,
+                embedded_field: None,
             }
         })
-        .collect();
-
-    PredicateParameterTypeKind::Composite {
-        field_params,
-        logical_op_params,
-    }
+        .collect()
 }
 
 fn expand_some_filter_type(
@@ -437,8 +615,10 @@ fn expand_some_filter_type(
             name: "some".to_string(),
             typ: param_type,
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         },
     );
 
@@ -476,7 +656,9 @@ fn expand_unique_type(
                     typ: param_type,
                     access: Some(field.access.clone()),
                     column_path_link: Some(ColumnPathLink::Leaf(*column_id)),
+                    transitive_links: vec![],
                     vector_distance_function: None,
+                    embedded_field: None,
                 })
             }
             PostgresRelation::ManyToOne { is_pk: true, .. } => {
@@ -496,7 +678,9 @@ fn expand_unique_type(
                     typ: param_type,
                     access: Some(field.access.clone()),
                     column_path_link: None,
+                    transitive_links: vec![],
                     vector_distance_function: None,
+                    embedded_field: None,
                 })
             }
             _ => None,
@@ -533,8 +717,10 @@ fn create_operator_filter_type_kind(
             name: operator.to_string(),
             typ: wrapped_type,
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         }
     };
 
@@ -581,31 +767,61 @@ fn expand_array_type(
             name: "eq".to_string(),
             typ: scalar_operand.clone(),
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         },
         PredicateParameter {
             name: "neq".to_string(),
             typ: scalar_operand.clone(),
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         },
         PredicateParameter {
             name: "contains".to_string(),
             typ: scalar_operand,
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         },
         PredicateParameter {
             name: "overlaps".to_string(),
             typ: list_operand,
             column_path_link: None,
+            transitive_links: vec![],
             access: None,
             vector_distance_function: None,
+            embedded_field: None,
         },
     ];
 
     PredicateParameterTypeKind::Operator(parameters)
 }
+
+/// The `isNull` and `isNotNull` operators (each taking a boolean), available in the filters of
+/// optional fields
+fn null_check_parameters(building: &SystemContextBuilding) -> Vec<PredicateParameter> {
+    let boolean_type_id = building.predicate_types.get_id("Boolean").unwrap();
+
+    ["isNull", "isNotNull"]
+        .into_iter()
+        .map(|operator| PredicateParameter {
+            name: operator.to_string(),
+            typ: FieldType::Optional(Box::new(FieldType::Plain(PredicateParameterTypeWrapper {
+                name: "Boolean".to_string(),
+                type_id: boolean_type_id,
+            }))),
+            column_path_link: None,
+            transitive_links: vec![],
+            access: None,
+            vector_distance_function: None,
+            embedded_field: None,
+        })
+        .collect()
+}
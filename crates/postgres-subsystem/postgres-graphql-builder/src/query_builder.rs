@@ -14,14 +14,20 @@ use core_model::{
     types::{BaseOperationReturnType, FieldType, Named, OperationReturnType},
 };
 
-use exo_sql::Database;
+use exo_sql::{Database, TraversalDirection};
 use postgres_graphql_model::{
+    chunk::ChunkQuery,
     limit_offset::{LimitParameter, LimitParameterType, OffsetParameter, OffsetParameterType},
+    lock::LockParameter,
     order::{OrderByParameter, OrderByParameterType},
+    page::PageType,
     query::{
-        AggregateQuery, AggregateQueryParameters, CollectionQuery, CollectionQueryParameters,
-        UniqueQuery, UniqueQueryParameters,
+        AggregateQuery, AggregateQueryParameters, BatchQuery, BatchQueryParameters,
+        CollectionQuery, CollectionQueryParameters, PageQuery, SimilarityQuery,
+        SimilarityQueryParameters, TreeQuery, TreeQueryParameters, UniqueQuery,
+        UniqueQueryParameters,
     },
+    similarity::{SimilarityType, VectorParameter},
 };
 
 use postgres_core_model::{
@@ -37,7 +43,7 @@ use postgres_core_builder::aggregate_type_builder::aggregate_type_name;
 
 use super::{order_by_type_builder, predicate_builder, system_builder::SystemContextBuilding};
 
-use super::naming::ToPostgresQueryName;
+use super::naming::{ToPostgresQueryName, ToPostgresTypeNames};
 use postgres_core_builder::resolved_type::{ResolvedCompositeType, ResolvedType, ResolvedTypeEnv};
 
 pub fn build_shallow(types: &MappedArena<ResolvedType>, building: &mut SystemContextBuilding) {
@@ -102,7 +108,7 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
         )
     };
 
-    for (_, entity_type) in building
+    for (entity_type_id, entity_type) in building
         .core_subsystem
         .entity_types
         .iter()
@@ -137,6 +143,50 @@ pub fn build_expanded(resolved_env: &ResolvedTypeEnv, building: &mut SystemConte
             resolved_env,
             &building.core_subsystem.database,
         );
+        build_similarity_queries(
+            entity_type_id,
+            entity_type,
+            resolved_env,
+            &building.core_subsystem.primitive_types,
+            &building.predicate_types,
+            &mut building.similarity_queries,
+            &mut building.similarity_types,
+        );
+        build_tree_queries(
+            entity_type_id,
+            entity_type,
+            resolved_env,
+            &building.core_subsystem.primitive_types,
+            &building.predicate_types,
+            &mut building.tree_queries,
+            &building.core_subsystem.database,
+        );
+        build_batch_query(
+            entity_type_id,
+            entity_type,
+            resolved_env,
+            &building.predicate_types,
+            &mut building.batch_queries,
+            &building.core_subsystem.database,
+        );
+        build_page_query(
+            entity_type_id,
+            entity_type,
+            resolved_env,
+            &building.core_subsystem.primitive_types,
+            &building.predicate_types,
+            &building.order_by_types,
+            &mut building.page_queries,
+            &mut building.page_types,
+        );
+        build_chunk_queries(
+            entity_type_id,
+            entity_type,
+            resolved_env,
+            &building.predicate_types,
+            &mut building.chunk_queries,
+            &building.core_subsystem.database,
+        );
     }
 }
 
@@ -150,6 +200,7 @@ fn shallow_unique_query(
         name,
         parameters: UniqueQueryParameters {
             predicate_params: vec![],
+            lock_param: LockParameter::default(),
         },
         return_type: OperationReturnType::Optional(Box::new(OperationReturnType::Plain(
             BaseOperationReturnType {
@@ -238,8 +289,10 @@ fn implicit_equals_predicate_param(
         name: field.name.to_string(),
         typ: FieldType::Plain(param_type),
         column_path_link: Some(field.relation.column_path_link(database)),
+        transitive_links: vec![],
         access: None,
         vector_distance_function: None,
+        embedded_field: None,
     }
 }
 
@@ -255,6 +308,7 @@ fn shallow_collection_query(
             order_by_param: OrderByParameter::shallow(),
             limit_param: LimitParameter::shallow(),
             offset_param: OffsetParameter::shallow(),
+            lock_param: LockParameter::default(),
         },
         return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
             BaseOperationReturnType {
@@ -350,8 +404,10 @@ fn compute_unique_query_predicate_param(
                     name: field.name.to_string(),
                     typ: FieldType::Plain(param_type),
                     column_path_link: Some(field.relation.column_path_link(database)),
+                    transitive_links: vec![],
                     access: None,
                     vector_distance_function: None,
+                    embedded_field: None,
                 }
             }
             PostgresRelation::OneToMany { .. } => {
@@ -363,6 +419,9 @@ fn compute_unique_query_predicate_param(
             PostgresRelation::Computed(_) => {
                 panic!("Computed relations cannot be used in unique queries")
             }
+            PostgresRelation::SqlExpression(_) => {
+                panic!("SQL expression fields cannot be used in unique queries")
+            }
             PostgresRelation::Transitive(_) => {
                 panic!("Transitive relations cannot be used in unique queries")
             }
@@ -397,6 +456,330 @@ pub fn expand_unique_queries(
     }
 }
 
+/// Build a similarity query for each vector field of the entity (e.g. `similarDocuments(to: [...], where: {...}, limit: 10)`),
+/// ranking the matching entities by their distance to the supplied vector.
+fn build_similarity_queries(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    resolved_env: &ResolvedTypeEnv,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    similarity_queries: &mut MappedArena<SimilarityQuery>,
+    similarity_types: &mut MappedArena<SimilarityType>,
+) {
+    if entity_type.vector_distance_fields.is_empty() {
+        return;
+    }
+
+    let root_visible = match resolved_env.get_by_key(entity_type.name.as_str()) {
+        Some(ResolvedType::Composite(resolved_composite_type)) => {
+            resolved_composite_type.root_visible
+        }
+        _ => true,
+    };
+
+    let similarity_type_name = entity_type.similarity_type();
+    similarity_types.add(
+        &similarity_type_name,
+        SimilarityType {
+            name: similarity_type_name.clone(),
+            entity_type_name: entity_type.name.clone(),
+        },
+    );
+
+    // Only qualify the query name with the vector field if there is more than one to choose from
+    let multiple_vector_fields = entity_type.vector_distance_fields.len() > 1;
+
+    for vector_distance_field in entity_type.vector_distance_fields.iter() {
+        // The vector distance field is named `<vectorField>Distance`
+        let vector_field_name = vector_distance_field
+            .name
+            .strip_suffix("Distance")
+            .unwrap_or(&vector_distance_field.name);
+
+        let operation_name =
+            entity_type.similarity_query(multiple_vector_fields.then_some(vector_field_name));
+
+        let query = SimilarityQuery {
+            name: operation_name.clone(),
+            parameters: SimilarityQueryParameters {
+                to_param: VectorParameter {
+                    name: "to".to_string(),
+                },
+                predicate_param: collection_predicate_param(entity_type, predicate_types),
+                limit_param: limit_param(primitive_types),
+                vector_distance_field_name: vector_distance_field.name.clone(),
+            },
+            return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
+                BaseOperationReturnType {
+                    associated_type_id: entity_type_id,
+                    type_name: similarity_type_name.clone(),
+                },
+            ))),
+            doc_comments: Some(format!(
+                "Get `{}`s most similar to the provided vector by their `{}` field, given the provided `where` filter and limit",
+                entity_type.name, vector_field_name
+            )),
+            exposed_in_schema: root_visible,
+        };
+
+        similarity_queries.add(&operation_name, query);
+    }
+}
+
+/// Build `<type>Descendants(of: <pk>, maxDepth: Int)` and `<type>Ancestors(of: <pk>, maxDepth: Int)`
+/// queries for an entity with a (single) self-referential relation such as `Category.parent`.
+fn build_tree_queries(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    resolved_env: &ResolvedTypeEnv,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    tree_queries: &mut MappedArena<TreeQuery>,
+    database: &Database,
+) {
+    let self_relations: Vec<_> = entity_type
+        .fields
+        .iter()
+        .filter_map(|field| match &field.relation {
+            PostgresRelation::ManyToOne { relation, .. }
+                if relation.foreign_entity_id == entity_type_id =>
+            {
+                Some(relation.relation_id)
+            }
+            _ => None,
+        })
+        .collect();
+
+    // With multiple self-referential relations (such as `manager` and `mentor`), it is ambiguous
+    // which one to follow, so we generate queries only for the simple (and common) case
+    let [relation_id] = self_relations[..] else {
+        return;
+    };
+
+    let pk_fields = entity_type.pk_fields();
+    let [pk_field] = pk_fields[..] else {
+        return;
+    };
+    if !matches!(pk_field.relation, PostgresRelation::Scalar { .. }) {
+        return;
+    }
+
+    let root_visible = match resolved_env.get_by_key(entity_type.name.as_str()) {
+        Some(ResolvedType::Composite(resolved_composite_type)) => {
+            resolved_composite_type.root_visible
+        }
+        _ => true,
+    };
+
+    for (operation_name, direction, description) in [
+        (
+            entity_type.descendants_query(),
+            TraversalDirection::Descendants,
+            "descendants",
+        ),
+        (
+            entity_type.ancestors_query(),
+            TraversalDirection::Ancestors,
+            "ancestors",
+        ),
+    ] {
+        let of_param = PredicateParameter {
+            name: "of".to_string(),
+            ..implicit_equals_predicate_param(pk_field, predicate_types, database)
+        };
+
+        let query = TreeQuery {
+            name: operation_name.clone(),
+            parameters: TreeQueryParameters {
+                of_param,
+                max_depth_param: LimitParameter {
+                    name: "maxDepth".to_string(),
+                    ..limit_param(primitive_types)
+                },
+                relation_id,
+                direction,
+            },
+            return_type: OperationReturnType::List(Box::new(OperationReturnType::Plain(
+                BaseOperationReturnType {
+                    associated_type_id: entity_type_id,
+                    type_name: entity_type.name.clone(),
+                },
+            ))),
+            doc_comments: Some(format!(
+                "Get the {description} of the `{}` with the provided primary key (up to `maxDepth` levels)",
+                entity_type.name
+            )),
+            exposed_in_schema: root_visible,
+        };
+
+        tree_queries.add(&operation_name, query);
+    }
+}
+
+/// Build a `<types>ByIds(ids: [...])` query for an entity with a single (scalar) primary key field,
+/// which returns the entities in the order of the provided keys
+fn build_batch_query(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    resolved_env: &ResolvedTypeEnv,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    batch_queries: &mut MappedArena<BatchQuery>,
+    database: &Database,
+) {
+    let pk_fields = entity_type.pk_fields();
+    let [pk_field] = pk_fields[..] else {
+        return;
+    };
+    if !matches!(pk_field.relation, PostgresRelation::Scalar { .. }) {
+        return;
+    }
+
+    let root_visible = match resolved_env.get_by_key(entity_type.name.as_str()) {
+        Some(ResolvedType::Composite(resolved_composite_type)) => {
+            resolved_composite_type.root_visible
+        }
+        _ => true,
+    };
+
+    let pk_param = implicit_equals_predicate_param(pk_field, predicate_types, database);
+    let ids_param = PredicateParameter {
+        name: "ids".to_string(),
+        typ: FieldType::List(Box::new(pk_param.typ)),
+        ..pk_param
+    };
+
+    let operation_name = entity_type.batch_query();
+
+    let query = BatchQuery {
+        name: operation_name.clone(),
+        parameters: BatchQueryParameters { ids_param },
+        return_type: OperationReturnType::List(Box::new(OperationReturnType::Optional(Box::new(
+            OperationReturnType::Plain(BaseOperationReturnType {
+                associated_type_id: entity_type_id,
+                type_name: entity_type.name.clone(),
+            }),
+        )))),
+        doc_comments: Some(format!(
+            "Get multiple `{}`s given their primary keys (in the same order, with `null` for the keys that don't match)",
+            entity_type.name
+        )),
+        exposed_in_schema: root_visible,
+    };
+
+    batch_queries.add(&operation_name, query);
+}
+
+/// Build a `<type><Field>Chunks(<pk>: ...)` query for each field declared with `@chunked` of an
+/// entity with a single (scalar) primary key field, which streams the field's value in chunks
+fn build_chunk_queries(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    resolved_env: &ResolvedTypeEnv,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    chunk_queries: &mut MappedArena<ChunkQuery>,
+    database: &Database,
+) {
+    let Some(ResolvedType::Composite(resolved_composite_type)) =
+        resolved_env.get_by_key(entity_type.name.as_str())
+    else {
+        return;
+    };
+
+    let pk_fields = entity_type.pk_fields();
+    let [pk_field] = pk_fields[..] else {
+        return;
+    };
+    if !matches!(pk_field.relation, PostgresRelation::Scalar { .. }) {
+        return;
+    }
+
+    for resolved_field in resolved_composite_type.fields.iter() {
+        let Some(chunk_size) = resolved_field.chunk_size else {
+            continue;
+        };
+
+        let operation_name = entity_type.chunks_query(&resolved_field.name);
+
+        let query = ChunkQuery {
+            name: operation_name.clone(),
+            entity_type_id,
+            field_name: resolved_field.name.clone(),
+            chunk_type_name: resolved_field.typ.innermost().type_name.clone(),
+            chunk_size,
+            pk_param: implicit_equals_predicate_param(pk_field, predicate_types, database),
+            doc_comments: Some(format!(
+                "Get the `{}` of the `{}` with the provided primary key in chunks (streamed as they are read when requested with `Accept: text/event-stream`)",
+                resolved_field.name, entity_type.name
+            )),
+            exposed_in_schema: resolved_composite_type.root_visible,
+        };
+
+        chunk_queries.add(&operation_name, query);
+    }
+}
+
+/// Build a `<types>Page(where: ..., orderBy: ..., limit: ..., offset: ...)` query for an entity
+/// annotated with `@paginated`, which returns the page along with whether more entities follow it
+#[allow(clippy::too_many_arguments)]
+fn build_page_query(
+    entity_type_id: SerializableSlabIndex<EntityType>,
+    entity_type: &EntityType,
+    resolved_env: &ResolvedTypeEnv,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+    predicate_types: &MappedArena<PredicateParameterType>,
+    order_by_types: &MappedArena<OrderByParameterType>,
+    page_queries: &mut MappedArena<PageQuery>,
+    page_types: &mut MappedArena<PageType>,
+) {
+    let Some(ResolvedType::Composite(resolved_composite_type)) =
+        resolved_env.get_by_key(entity_type.name.as_str())
+    else {
+        return;
+    };
+
+    if !resolved_composite_type.paginated {
+        return;
+    }
+
+    let page_type_name = entity_type.page_type();
+    page_types.add(
+        &page_type_name,
+        PageType {
+            name: page_type_name.clone(),
+            entity_type_name: entity_type.name.clone(),
+        },
+    );
+
+    let operation_name = entity_type.page_query();
+
+    let query = PageQuery {
+        name: operation_name.clone(),
+        parameters: CollectionQueryParameters {
+            predicate_param: collection_predicate_param(entity_type, predicate_types),
+            order_by_param: order_by_type_builder::new_root_param(
+                &entity_type.name,
+                false,
+                order_by_types,
+            ),
+            limit_param: limit_param(primitive_types),
+            offset_param: offset_param(primitive_types),
+            lock_param: LockParameter::default(),
+        },
+        return_type: OperationReturnType::Plain(BaseOperationReturnType {
+            associated_type_id: entity_type_id,
+            type_name: page_type_name,
+        }),
+        doc_comments: Some(format!(
+            "Get a page of `{}`s given the provided `where` filter, order by, limit, and offset (along with whether more `{}`s follow it)",
+            entity_type.name, entity_type.name
+        )),
+        exposed_in_schema: resolved_composite_type.root_visible,
+    };
+
+    page_queries.add(&operation_name, query);
+}
+
 pub fn limit_param(primitive_types: &MappedArena<PostgresPrimitiveType>) -> LimitParameter {
     let param_type_name = primitive_type::IntType::NAME;
 
@@ -437,8 +820,10 @@ pub fn collection_predicate_param(
         name: "where".to_string(),
         typ: FieldType::Optional(Box::new(FieldType::Plain(param_type))),
         column_path_link: None,
+        transitive_links: vec![],
         access: None,
         vector_distance_function: None,
+        embedded_field: None,
     }
 }
 
@@ -487,7 +872,7 @@ mod tests {
     use super::*;
     use core_model::types::FieldType;
     use core_model_builder::ast::ast_types::default_span;
-    use exo_sql::SchemaObjectName;
+    use exo_sql::{ReferentialAction, SchemaObjectName};
     use postgres_core_builder::{
         resolved_type::{ResolvedField, ResolvedFieldType},
         shallow::Shallow,
@@ -510,11 +895,20 @@ mod tests {
             indices: vec![],
             cardinality: None,
             default_value: None,
+            read_default: None,
+            mask: None,
             update_sync: false,
             readonly: false,
             relation_path: None,
+            many_to_many: None,
+            renamed_from: None,
+            on_delete: ReferentialAction::NoAction,
+            on_update: ReferentialAction::NoAction,
+            storage: Default::default(),
+            chunk_size: None,
             doc_comments: None,
             computed: None,
+            sql_expression: None,
             join_table_shortcut: None,
             join_table_intermediate: None,
             span: default_span(),
@@ -530,6 +924,18 @@ mod tests {
             access: Default::default(),
             doc_comments: None,
             join_table: None,
+            renamed_from: None,
+            on_delete: ReferentialAction::NoAction,
+            on_update: ReferentialAction::NoAction,
+            retention: None,
+            partition: None,
+            ownership_transfer: None,
+            approval: None,
+            field_guards: None,
+            window_fields: vec![],
+            paginated: false,
+            opaque_id: false,
+            validators: vec![],
             span: default_span(),
         }
     }
@@ -97,6 +97,8 @@ fn expanded_reference_types(
                 access: field.access.clone(),
                 relation: field.relation.clone(),
                 default_value: field.default_value.clone(),
+                read_default: None,
+                mask: None,
                 readonly: field.readonly,
                 type_validation: None,
                 doc_comments: None,
@@ -107,6 +109,8 @@ fn expanded_reference_types(
                 access: field.access.clone(),
                 relation: field.relation.clone(),
                 default_value: field.default_value.clone(),
+                read_default: None,
+                mask: None,
                 readonly: field.readonly,
                 type_validation: None,
                 doc_comments: None,
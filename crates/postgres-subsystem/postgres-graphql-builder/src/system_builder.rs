@@ -13,9 +13,15 @@ use core_model::mapped_arena::{MappedArena, SerializableSlabIndex};
 use core_model_builder::error::ModelBuildingError;
 
 use postgres_graphql_model::{
+    chunk::ChunkQuery,
     mutation::PostgresMutation,
     order::OrderByParameterType,
-    query::{AggregateQuery, CollectionQuery, UniqueQuery},
+    page::PageType,
+    query::{
+        AggregateQuery, BatchQuery, CollectionQuery, PageQuery, SimilarityQuery, TreeQuery,
+        UniqueQuery,
+    },
+    similarity::SimilarityType,
     subsystem::PostgresGraphQLSubsystem,
     types::MutationType,
 };
@@ -47,6 +53,13 @@ pub fn build(
             collection_queries: building.collection_queries,
             aggregate_queries: building.aggregate_queries,
             unique_queries: building.unique_queries,
+            similarity_queries: building.similarity_queries,
+            similarity_types: building.similarity_types.values(),
+            tree_queries: building.tree_queries,
+            batch_queries: building.batch_queries,
+            page_queries: building.page_queries,
+            page_types: building.page_types.values(),
+            chunk_queries: building.chunk_queries,
             mutation_types: building.mutation_types.values(),
             mutations: building.mutations,
 
@@ -114,6 +127,13 @@ pub struct SystemContextBuilding {
     pub collection_queries: MappedArena<CollectionQuery>,
     pub aggregate_queries: MappedArena<AggregateQuery>,
     pub unique_queries: MappedArena<UniqueQuery>,
+    pub similarity_queries: MappedArena<SimilarityQuery>,
+    pub similarity_types: MappedArena<SimilarityType>,
+    pub tree_queries: MappedArena<TreeQuery>,
+    pub batch_queries: MappedArena<BatchQuery>,
+    pub page_queries: MappedArena<PageQuery>,
+    pub page_types: MappedArena<PageType>,
+    pub chunk_queries: MappedArena<ChunkQuery>,
 
     pub pk_queries_map:
         HashMap<SerializableSlabIndex<EntityType>, SerializableSlabIndex<UniqueQuery>>,
@@ -20,7 +20,10 @@ use postgres_graphql_model::{
     types::MutationType,
 };
 
-use postgres_core_model::types::{EntityType, PostgresField, PostgresFieldType, TypeIndex};
+use postgres_core_model::{
+    relation::{PostgresRelation, TransitiveRelation},
+    types::{EntityType, PostgresField, PostgresFieldType, TypeIndex},
+};
 
 use crate::{
     mutation_builder::DataParamRole,
@@ -53,6 +56,7 @@ impl Builder for UpdateMutationBuilder {
             return vec![];
         }
         let mut field_types = self.data_param_field_type_names(resolved_composite_type, types);
+        field_types.extend(link_type_names(resolved_composite_type, types));
         field_types.push(Self::data_param_type_name(resolved_composite_type));
         field_types
     }
@@ -86,6 +90,10 @@ impl Builder for UpdateMutationBuilder {
                 {
                     building.mutation_types[existing_id] = expanded_type;
                 }
+
+                for (existing_id, expanded_type) in expanded_link_types(entity_type, building) {
+                    building.mutation_types[existing_id] = expanded_type;
+                }
             }
         }
 
@@ -105,6 +113,82 @@ impl Builder for UpdateMutationBuilder {
     }
 }
 
+/// The `<Type>LinkInput` types for the many-to-many fields (if the related type accepts references)
+fn link_type_names(
+    resolved_composite_type: &ResolvedCompositeType,
+    types: &MappedArena<ResolvedType>,
+) -> Vec<String> {
+    resolved_composite_type
+        .fields
+        .iter()
+        .filter(|field| field.many_to_many.is_some())
+        .filter_map(|field| match types.get_by_key(field.typ.name()) {
+            Some(ResolvedType::Composite(target))
+                if target.access.creation_allowed() || target.access.update_allowed() =>
+            {
+                Some(target.link_type())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Expand the `<Type>LinkInput` types, whose `connect` and `disconnect` fields take references to
+/// the entities to link and unlink
+fn expanded_link_types(
+    entity_type: &EntityType,
+    building: &SystemContextBuilding,
+) -> Vec<(SerializableSlabIndex<MutationType>, MutationType)> {
+    entity_type
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let PostgresRelation::Transitive(TransitiveRelation {
+                many_to_many: Some(_),
+                final_entity_id,
+                ..
+            }) = &field.relation
+            else {
+                return None;
+            };
+
+            let link_type_name = field.typ.name().link_type();
+            let link_type_id = building.mutation_types.get_id(&link_type_name)?;
+            let reference_type_name = field.typ.name().reference_type();
+            let reference_type_id = building.mutation_types.get_id(&reference_type_name)?;
+
+            let references_field = |name: &str| PostgresField {
+                name: name.to_string(),
+                typ: FieldType::Optional(Box::new(FieldType::List(Box::new(FieldType::Plain(
+                    PostgresFieldType {
+                        type_name: reference_type_name.clone(),
+                        type_id: TypeIndex::Composite(reference_type_id),
+                    },
+                ))))),
+                access: field.access.clone(),
+                relation: field.relation.clone(),
+                default_value: None,
+                read_default: None,
+                mask: None,
+                readonly: false,
+                type_validation: None,
+                doc_comments: None,
+            };
+
+            Some((
+                link_type_id,
+                MutationType {
+                    name: link_type_name,
+                    fields: vec![references_field("connect"), references_field("disconnect")],
+                    entity_id: *final_entity_id,
+                    database_access: None,
+                    doc_comments: None,
+                },
+            ))
+        })
+        .collect()
+}
+
 impl MutationBuilder for UpdateMutationBuilder {
     fn single_mutation_name(entity_type: &EntityType) -> String {
         entity_type.pk_update()
@@ -275,6 +359,8 @@ impl DataParamBuilder<DataParameter> for UpdateMutationBuilder {
                         access: field.access.clone(),
                         relation: field.relation.clone(),
                         default_value: field.default_value.clone(),
+                        read_default: None,
+                        mask: None,
                         readonly: field.readonly,
                         type_validation: None,
                         doc_comments: None,
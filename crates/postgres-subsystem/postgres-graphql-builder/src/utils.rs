@@ -7,8 +7,18 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use core_model::types::FieldType;
-use postgres_core_model::types::{EntityType, PostgresFieldType, TypeIndex};
+use core_model::{mapped_arena::MappedArena, types::FieldType};
+use exo_sql::{PhysicalColumnType, StringColumnType};
+use postgres_core_builder::{
+    resolved_type::ResolvedField, type_provider::PRIMITIVE_TYPE_PROVIDER_REGISTRY,
+};
+use postgres_core_model::{
+    relation::{EmbeddedFieldLink, PostgresRelation},
+    types::{
+        EntityType, PostgresField, PostgresFieldType, PostgresPrimitiveType,
+        PostgresPrimitiveTypeKind, TypeIndex,
+    },
+};
 use postgres_graphql_model::types::MutationType;
 
 use crate::{naming::ToPostgresTypeNames, system_builder::SystemContextBuilding};
@@ -55,3 +65,52 @@ pub(super) fn to_mutation_type(
         FieldType::List(ft) => FieldType::List(Box::new(to_mutation_type(ft, kind, building))),
     }
 }
+
+/// The link to follow to reach a field embedded in a JSON column from predicates and orderings.
+///
+/// Only non-list fields with a scalar or a (nested) JSON type qualify.
+pub(super) fn embedded_field_link(
+    resolved_field: &ResolvedField,
+    entity_field: &PostgresField<EntityType>,
+    primitive_types: &MappedArena<PostgresPrimitiveType>,
+    entity_types: &MappedArena<EntityType>,
+) -> Option<EmbeddedFieldLink> {
+    if !matches!(entity_field.relation, PostgresRelation::Embedded)
+        || matches!(entity_field.typ.base_type(), FieldType::List(_))
+    {
+        return None;
+    }
+
+    let typ = match &entity_field.typ.innermost().type_id {
+        TypeIndex::Primitive(index) => {
+            let primitive_type = &primitive_types[*index];
+
+            match &primitive_type.kind {
+                // Enum values are stored as their names
+                PostgresPrimitiveTypeKind::Enum(_) => {
+                    Some(Box::new(StringColumnType { max_length: None })
+                        as Box<dyn PhysicalColumnType>)
+                }
+                PostgresPrimitiveTypeKind::Builtin => {
+                    if primitive_type.name == "Vector" {
+                        return None;
+                    }
+                    let type_provider =
+                        PRIMITIVE_TYPE_PROVIDER_REGISTRY.get(primitive_type.name.as_str())?;
+                    Some(type_provider.determine_column_type(resolved_field))
+                }
+            }
+        }
+        TypeIndex::Composite(index) => {
+            if !entity_types[*index].representation.is_json_like() {
+                return None;
+            }
+            None
+        }
+    };
+
+    Some(EmbeddedFieldLink {
+        key: entity_field.name.clone(),
+        typ,
+    })
+}
@@ -19,7 +19,7 @@ use core_model::type_normalization::{
     FieldDefinitionProvider, InputValueProvider, TypeDefinitionProvider, default_positioned,
     default_positioned_name,
 };
-use postgres_core_model::relation::{OneToManyRelation, PostgresRelation};
+use postgres_core_model::relation::{OneToManyRelation, PostgresRelation, TransitiveRelation};
 
 impl TypeDefinitionProvider<PostgresGraphQLSubsystem> for AggregateType {
     fn type_definition(&self, system: &PostgresGraphQLSubsystem) -> TypeDefinition {
@@ -67,9 +67,18 @@ impl FieldDefinitionProvider<PostgresGraphQLSubsystem> for AggregateField {
 
                     vec![default_positioned(predicate_param.input_value())]
                 }
+                PostgresRelation::Transitive(TransitiveRelation {
+                    final_entity_id, ..
+                }) => {
+                    let aggregate_query = system.get_aggregate_query(*final_entity_id);
+
+                    let AggregateQueryParameters { predicate_param } = &aggregate_query.parameters;
+
+                    vec![default_positioned(predicate_param.input_value())]
+                }
                 PostgresRelation::Embedded
                 | PostgresRelation::Computed(_)
-                | PostgresRelation::Transitive(_) => {
+                | PostgresRelation::SqlExpression(_) => {
                     vec![]
                 }
             },
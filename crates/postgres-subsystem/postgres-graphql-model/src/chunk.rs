@@ -0,0 +1,64 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Queries that stream a large value of a field declared with `@chunked` in chunks (such as
+//! `documentContentChunks(id: 1)`), so that neither the server nor the client has to hold the
+//! whole value in memory.
+
+use async_graphql_parser::types::{BaseType, FieldDefinition, Type};
+use async_graphql_value::Name;
+use core_model::{
+    mapped_arena::SerializableSlabIndex,
+    type_normalization::{
+        FieldDefinitionProvider, InputValueProvider, default_positioned, default_positioned_name,
+    },
+};
+use postgres_core_model::{predicate::PredicateParameter, types::EntityType};
+use serde::{Deserialize, Serialize};
+
+use crate::subsystem::PostgresGraphQLSubsystem;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChunkQuery {
+    /// The name of the query such as `documentContentChunks`
+    pub name: String,
+    /// The type whose field the query streams
+    pub entity_type_id: SerializableSlabIndex<EntityType>,
+    /// The name of the streamed field such as `content`
+    pub field_name: String,
+    /// The type of the streamed field (`String` or `Blob`), which is also the type of each chunk
+    pub chunk_type_name: String,
+    /// The number of characters (for `String`) or bytes (for `Blob`) per chunk
+    pub chunk_size: usize,
+    /// The primary key parameter such as `id: 1`
+    pub pk_param: PredicateParameter,
+    pub doc_comments: Option<String>,
+    pub exposed_in_schema: bool,
+}
+
+impl FieldDefinitionProvider<PostgresGraphQLSubsystem> for ChunkQuery {
+    fn field_definition(&self, _system: &PostgresGraphQLSubsystem) -> FieldDefinition {
+        // [<chunk type>!]!
+        let chunks_type = Type {
+            base: BaseType::List(Box::new(Type {
+                base: BaseType::Named(Name::new(&self.chunk_type_name)),
+                nullable: false,
+            })),
+            nullable: false,
+        };
+
+        FieldDefinition {
+            description: self.doc_comments.clone().map(default_positioned),
+            name: default_positioned_name(&self.name),
+            arguments: vec![default_positioned(self.pk_param.input_value())],
+            ty: default_positioned(chunks_type),
+            directives: vec![],
+        }
+    }
+}
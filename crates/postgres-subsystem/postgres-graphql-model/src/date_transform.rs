@@ -0,0 +1,72 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Arguments of date and time fields that format or truncate the value in the database, such as
+//! `startTime(truncate: MONTH, format: "YYYY-MM", timezone: "America/New_York")`.
+
+use async_graphql_parser::{
+    Pos, Positioned,
+    types::{EnumType, EnumValueDefinition, InputValueDefinition, TypeDefinition, TypeKind},
+};
+use async_graphql_value::Name;
+use core_model::type_normalization::{default_positioned, default_positioned_name};
+
+use crate::string_transform::input_value;
+
+pub const FORMAT_ARG_NAME: &str = "format";
+pub const TRUNCATE_ARG_NAME: &str = "truncate";
+pub const TIMEZONE_ARG_NAME: &str = "timezone";
+
+pub const DATE_TRUNCATION_TYPE_NAME: &str = "DateTruncation";
+
+pub const DATE_TRUNCATION_OPTIONS: [&str; 8] = [
+    "YEAR", "QUARTER", "MONTH", "WEEK", "DAY", "HOUR", "MINUTE", "SECOND",
+];
+
+/// The primitive types whose fields get the date arguments
+pub const DATE_TYPE_NAMES: [&str; 3] = ["LocalDate", "LocalDateTime", "Instant"];
+
+/// The arguments to add to a date field (the truncation applies before the formatting). Only
+/// `Instant` fields accept a timezone, since other types don't carry one.
+pub fn date_field_arguments(type_name: &str) -> Vec<Positioned<InputValueDefinition>> {
+    let mut arguments = vec![
+        input_value(FORMAT_ARG_NAME, "String", true),
+        input_value(TRUNCATE_ARG_NAME, DATE_TRUNCATION_TYPE_NAME, true),
+    ];
+
+    if type_name == "Instant" {
+        arguments.push(input_value(TIMEZONE_ARG_NAME, "String", true));
+    }
+
+    arguments.into_iter().map(default_positioned).collect()
+}
+
+pub fn date_transform_type_definitions() -> Vec<TypeDefinition> {
+    vec![TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(DATE_TRUNCATION_TYPE_NAME),
+        directives: vec![],
+        kind: TypeKind::Enum(EnumType {
+            values: DATE_TRUNCATION_OPTIONS
+                .iter()
+                .map(|value| {
+                    Positioned::new(
+                        EnumValueDefinition {
+                            description: None,
+                            value: Positioned::new(Name::new(value), Pos::default()),
+                            directives: vec![],
+                        },
+                        Pos::default(),
+                    )
+                })
+                .collect(),
+        }),
+    }]
+}
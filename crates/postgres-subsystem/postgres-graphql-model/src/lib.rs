@@ -8,12 +8,19 @@
 // by the Apache License, Version 2.0.
 
 pub mod aggregate;
+pub mod chunk;
+pub mod date_transform;
 pub mod limit_offset;
+pub mod lock;
 pub mod mutation;
 pub mod operation;
 pub mod order;
+pub mod page;
 pub mod predicate;
 pub mod query;
+pub mod similarity;
+pub mod string_transform;
 pub mod subsystem;
 pub mod types;
 pub mod vector_distance;
+pub mod window;
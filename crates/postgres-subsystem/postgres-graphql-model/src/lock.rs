@@ -0,0 +1,83 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The `lock` argument of unique and collection queries such as
+//! `jobs(where: { status: { eq: "pending" } }, limit: 1, lock: SKIP_LOCKED)`, which locks the
+//! returned rows until the end of the request's transaction.
+
+use async_graphql_parser::{
+    Pos, Positioned,
+    types::{EnumType, EnumValueDefinition, TypeDefinition, TypeKind},
+};
+use async_graphql_value::Name;
+use core_model::{
+    type_normalization::{BaseType, Parameter, Type, default_positioned_name},
+    types::TypeValidation,
+};
+use serde::{Deserialize, Serialize};
+
+pub const LOCK_ARG_NAME: &str = "lock";
+
+pub const ROW_LOCK_TYPE_NAME: &str = "RowLock";
+
+pub const ROW_LOCK_OPTIONS: [&str; 3] = ["FOR_UPDATE", "FOR_SHARE", "SKIP_LOCKED"];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LockParameter {
+    pub name: String,
+}
+
+impl Default for LockParameter {
+    fn default() -> Self {
+        Self {
+            name: LOCK_ARG_NAME.to_string(),
+        }
+    }
+}
+
+impl Parameter for LockParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        Type {
+            base: BaseType::Leaf(ROW_LOCK_TYPE_NAME.to_string()),
+            nullable: true,
+        }
+    }
+
+    fn type_validation(&self) -> Option<TypeValidation> {
+        None
+    }
+}
+
+pub fn row_lock_type_definition() -> TypeDefinition {
+    TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(ROW_LOCK_TYPE_NAME),
+        directives: vec![],
+        kind: TypeKind::Enum(EnumType {
+            values: ROW_LOCK_OPTIONS
+                .iter()
+                .map(|value| {
+                    Positioned::new(
+                        EnumValueDefinition {
+                            description: None,
+                            value: Positioned::new(Name::new(value), Pos::default()),
+                            directives: vec![],
+                        },
+                        Pos::default(),
+                    )
+                })
+                .collect(),
+        }),
+    }
+}
@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use postgres_core_model::predicate::PredicateParameter;
+use postgres_core_model::types::{EntityType, PostgresFieldType};
 use serde::{Deserialize, Serialize};
 
 use crate::types::MutationType;
@@ -43,13 +44,52 @@ pub enum PostgresMutationParameters {
         data_param: DataParameter,
         predicate_params: Vec<PredicateParameter>,
     },
+
+    /// Parameters for an ownership transfer mutation such as `transferTodoOwnership`
+    /// It takes the primary key of the entity such as `id: 1` and the new owner such as `newOwner: 5`.
+    /// This allows mutations such as `{ transferTodoOwnership(id: 1, newOwner: 5) }`, which update
+    /// only the owner field.
+    TransferOwnership {
+        predicate_params: Vec<PredicateParameter>,
+        new_owner_param: NewOwnerParameter,
+    },
+
+    /// Parameters for a mutation reviewing a pending change such as `approvePayoutChange` or
+    /// `rejectPayoutChange` (for a type declared with `@requiresApproval`)
+    /// It takes the primary key of the change such as `id: 1`. This allows mutations such as
+    /// `{ approvePayoutChange(id: 1) }`, which apply the change and record the decision.
+    ReviewChange {
+        predicate_params: Vec<PredicateParameter>,
+        /// The type whose change is reviewed (such as `Payout`)
+        subject_type_id: SerializableSlabIndex<EntityType>,
+        decision: ChangeDecision,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeDecision {
+    Approve,
+    Reject,
+}
+
+impl ChangeDecision {
+    /// The status recorded for the change
+    pub fn status(&self) -> &'static str {
+        match self {
+            ChangeDecision::Approve => "approved",
+            ChangeDecision::Reject => "rejected",
+        }
+    }
 }
 
 impl OperationParameters for PostgresMutationParameters {
     fn introspect(&self) -> Vec<&dyn Parameter> {
         match &self {
             PostgresMutationParameters::Create(data_param) => vec![data_param],
-            PostgresMutationParameters::Delete(predicate_params) => predicate_params
+            PostgresMutationParameters::Delete(predicate_params)
+            | PostgresMutationParameters::ReviewChange {
+                predicate_params, ..
+            } => predicate_params
                 .iter()
                 .map(|p| p as &dyn Parameter)
                 .collect(),
@@ -64,6 +104,17 @@ impl OperationParameters for PostgresMutationParameters {
                 params.push(data_param);
                 params
             }
+            PostgresMutationParameters::TransferOwnership {
+                predicate_params,
+                new_owner_param,
+            } => {
+                let mut params: Vec<&dyn Parameter> = predicate_params
+                    .iter()
+                    .map(|p| p as &dyn Parameter)
+                    .collect();
+                params.push(new_owner_param);
+                params
+            }
         }
     }
 }
@@ -81,6 +132,29 @@ pub struct DataParameter {
     pub doc_comments: Option<String>,
 }
 
+/// The new owner parameter of an ownership transfer mutation
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NewOwnerParameter {
+    /// Name of the parameter (typically `newOwner`).
+    pub name: String,
+    /// Type of the parameter (the type of the owner field such as `Int`).
+    pub typ: FieldType<PostgresFieldType<EntityType>>,
+}
+
+impl Parameter for NewOwnerParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> Type {
+        (&self.typ).into()
+    }
+
+    fn type_validation(&self) -> Option<TypeValidation> {
+        None
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DataParameterType {
     /// The name of the type such as `TodoCreateInput` and `TodoUpdateInput`.
@@ -27,7 +27,7 @@ use core_model::{
     types::{FieldType, Named, TypeValidation},
 };
 
-use postgres_core_model::access::Access;
+use postgres_core_model::{access::Access, relation::EmbeddedFieldLink};
 
 use exo_sql::{ColumnPathLink, VectorDistanceFunction};
 use serde::{Deserialize, Serialize};
@@ -45,9 +45,14 @@ pub struct OrderByParameter {
     ///   order_by: None
     /// ```
     pub column_path_link: Option<ColumnPathLink>,
+    /// For a relation-path field (from `@relationPath`), the links to follow after
+    /// `column_path_link` to reach the final entity
+    pub transitive_links: Vec<ColumnPathLink>,
     pub access: Option<Access>,
     // TODO: Generalize this to support more than just vector distance functions
     pub vector_distance_function: Option<VectorDistanceFunction>,
+    /// For a field inside a JSON column, the key to follow (in place of `column_path_link`)
+    pub embedded_field: Option<EmbeddedFieldLink>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -0,0 +1,80 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Types supporting page queries such as `todosPage(where: {...}, orderBy: {...}, limit: 10, offset: 20)`
+//! (generated for types annotated with `@paginated`), which return a page of entities along with
+//! whether more entities follow it.
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, ObjectType, Type, TypeDefinition, TypeKind,
+};
+use async_graphql_value::Name;
+use core_model::type_normalization::{
+    TypeDefinitionProvider, default_positioned, default_positioned_name,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::subsystem::PostgresGraphQLSubsystem;
+
+/// The field holding the entities in the page
+pub const PAGE_ITEMS_FIELD: &str = "items";
+/// The field reporting whether more entities follow the page
+pub const PAGE_HAS_NEXT_PAGE_FIELD: &str = "hasNextPage";
+
+/// The result type of a page query (e.g. `TodoPage { items: [Todo!]!, hasNextPage: Boolean! }`)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PageType {
+    pub name: String,
+    pub entity_type_name: String,
+}
+
+impl TypeDefinitionProvider<PostgresGraphQLSubsystem> for PageType {
+    fn type_definition(&self, _system: &PostgresGraphQLSubsystem) -> TypeDefinition {
+        let field = |name: &str, base: BaseType, description: &str| {
+            default_positioned(FieldDefinition {
+                description: Some(default_positioned(description.to_string())),
+                name: default_positioned_name(name),
+                arguments: vec![],
+                ty: default_positioned(Type {
+                    base,
+                    nullable: false,
+                }),
+                directives: vec![],
+            })
+        };
+
+        TypeDefinition {
+            extend: false,
+            description: Some(default_positioned(format!(
+                "A page of `{}`s",
+                self.entity_type_name
+            ))),
+            name: default_positioned_name(&self.name),
+            directives: vec![],
+            kind: TypeKind::Object(ObjectType {
+                implements: vec![],
+                fields: vec![
+                    field(
+                        PAGE_ITEMS_FIELD,
+                        BaseType::List(Box::new(Type {
+                            base: BaseType::Named(Name::new(&self.entity_type_name)),
+                            nullable: false,
+                        })),
+                        "The entities in the page",
+                    ),
+                    field(
+                        PAGE_HAS_NEXT_PAGE_FIELD,
+                        BaseType::Named(Name::new("Boolean")),
+                        "Whether more entities follow the page",
+                    ),
+                ],
+            }),
+        }
+    }
+}
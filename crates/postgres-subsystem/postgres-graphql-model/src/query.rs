@@ -7,6 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use exo_sql::{ManyToOneId, TraversalDirection};
 use postgres_core_model::predicate::PredicateParameter;
 use serde::{Deserialize, Serialize};
 
@@ -14,7 +15,9 @@ use core_model::type_normalization::Parameter;
 
 use crate::{
     limit_offset::{LimitParameter, OffsetParameter},
+    lock::LockParameter,
     order::OrderByParameter,
+    similarity::VectorParameter,
 };
 
 use super::operation::{OperationParameters, PostgresOperation};
@@ -33,6 +36,8 @@ pub struct CollectionQueryParameters {
     pub limit_param: LimitParameter,
     /// The offset parameter such as `offset: 20`
     pub offset_param: OffsetParameter,
+    /// The lock parameter such as `lock: SKIP_LOCKED`
+    pub lock_param: LockParameter,
 }
 
 impl OperationParameters for CollectionQueryParameters {
@@ -42,10 +47,15 @@ impl OperationParameters for CollectionQueryParameters {
             &self.order_by_param,
             &self.limit_param,
             &self.offset_param,
+            &self.lock_param,
         ]
     }
 }
 
+/// A query for a page of entities (see [crate::page]), which takes the same parameters as the
+/// collection query
+pub type PageQuery = PostgresOperation<CollectionQueryParameters>;
+
 /// Query that returns an aggregate such as `todosAgg(where: { title: { eq: "Hello" } })`
 pub type AggregateQuery = PostgresOperation<AggregateQueryParameters>;
 
@@ -69,6 +79,8 @@ pub type UniqueQuery = PostgresOperation<UniqueQueryParameters>;
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UniqueQueryParameters {
     pub predicate_params: Vec<PredicateParameter>,
+    /// The lock parameter such as `lock: FOR_UPDATE`
+    pub lock_param: LockParameter,
 }
 
 impl OperationParameters for UniqueQueryParameters {
@@ -76,6 +88,64 @@ impl OperationParameters for UniqueQueryParameters {
         self.predicate_params
             .iter()
             .map(|p| p as &dyn Parameter)
+            .chain(std::iter::once(&self.lock_param as &dyn Parameter))
             .collect()
     }
 }
+
+pub type SimilarityQuery = PostgresOperation<SimilarityQueryParameters>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimilarityQueryParameters {
+    /// The vector to compare against such as `to: [0.1, 0.2, 0.3]`
+    pub to_param: VectorParameter,
+    /// The predicate parameter such as `where: { title: { eq: "Hello" } }`
+    pub predicate_param: PredicateParameter,
+    /// The limit parameter such as `limit: 10`
+    pub limit_param: LimitParameter,
+    /// The name of the vector distance field (such as `embeddingDistance`) that ranks the results
+    pub vector_distance_field_name: String,
+}
+
+impl OperationParameters for SimilarityQueryParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        vec![&self.to_param, &self.predicate_param, &self.limit_param]
+    }
+}
+
+/// Query that returns entities by a list of primary keys such as `todosByIds(ids: [3, 1, 2])`, in
+/// the order of the provided keys (with `null` for the keys that don't match a readable entity)
+pub type BatchQuery = PostgresOperation<BatchQueryParameters>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchQueryParameters {
+    /// The primary keys of the entities such as `ids: [3, 1, 2]`
+    pub ids_param: PredicateParameter,
+}
+
+impl OperationParameters for BatchQueryParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        vec![&self.ids_param]
+    }
+}
+
+/// Query that follows a self-referential relation such as `categoryDescendants(of: 1, maxDepth: 3)`
+/// or `categoryAncestors(of: 5)`
+pub type TreeQuery = PostgresOperation<TreeQueryParameters>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TreeQueryParameters {
+    /// The primary key of the starting entity such as `of: 1`
+    pub of_param: PredicateParameter,
+    /// The maximum number of levels to traverse such as `maxDepth: 3`
+    pub max_depth_param: LimitParameter,
+    /// The self-referential relation to follow (such as the one for `Category.parent`)
+    pub relation_id: ManyToOneId,
+    pub direction: TraversalDirection,
+}
+
+impl OperationParameters for TreeQueryParameters {
+    fn introspect(&self) -> Vec<&dyn Parameter> {
+        vec![&self.of_param, &self.max_depth_param]
+    }
+}
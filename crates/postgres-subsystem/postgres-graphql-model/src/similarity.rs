@@ -0,0 +1,100 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Types supporting similarity queries such as `similarTodos(to: [...], where: {...}, limit: 10)`,
+//! which return entities ranked by the distance of one of their vector fields to the supplied vector.
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, ObjectType, Type, TypeDefinition, TypeKind,
+};
+use async_graphql_value::Name;
+use core_model::{
+    primitive_type::vector_introspection_type,
+    type_normalization::{
+        self, Parameter, TypeDefinitionProvider, default_positioned, default_positioned_name,
+    },
+    types::TypeValidation,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::subsystem::PostgresGraphQLSubsystem;
+
+/// The field holding the matched entity in a similarity result
+pub const SIMILARITY_ITEM_FIELD: &str = "item";
+/// The field holding the distance between the matched entity's vector and the supplied vector
+pub const SIMILARITY_SCORE_FIELD: &str = "score";
+
+/// The element type of a similarity query result (e.g. `TodoSimilarity { item: Todo!, score: Float! }`)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimilarityType {
+    pub name: String,
+    pub entity_type_name: String,
+}
+
+impl TypeDefinitionProvider<PostgresGraphQLSubsystem> for SimilarityType {
+    fn type_definition(&self, _system: &PostgresGraphQLSubsystem) -> TypeDefinition {
+        let field = |name: &str, type_name: &str, description: &str| {
+            default_positioned(FieldDefinition {
+                description: Some(default_positioned(description.to_string())),
+                name: default_positioned_name(name),
+                arguments: vec![],
+                ty: default_positioned(Type {
+                    base: BaseType::Named(Name::new(type_name)),
+                    nullable: false,
+                }),
+                directives: vec![],
+            })
+        };
+
+        TypeDefinition {
+            extend: false,
+            description: Some(default_positioned(format!(
+                "A `{}` matched by a similarity query, along with its score",
+                self.entity_type_name
+            ))),
+            name: default_positioned_name(&self.name),
+            directives: vec![],
+            kind: TypeKind::Object(ObjectType {
+                implements: vec![],
+                fields: vec![
+                    field(
+                        SIMILARITY_ITEM_FIELD,
+                        &self.entity_type_name,
+                        "The matched entity",
+                    ),
+                    field(
+                        SIMILARITY_SCORE_FIELD,
+                        "Float",
+                        "The distance to the supplied vector (lower is more similar)",
+                    ),
+                ],
+            }),
+        }
+    }
+}
+
+/// A vector parameter such as `to: [0.1, 0.2, 0.3]`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VectorParameter {
+    pub name: String,
+}
+
+impl Parameter for VectorParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn typ(&self) -> type_normalization::Type {
+        vector_introspection_type(false)
+    }
+
+    fn type_validation(&self) -> Option<TypeValidation> {
+        None
+    }
+}
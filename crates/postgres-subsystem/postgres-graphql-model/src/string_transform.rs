@@ -0,0 +1,98 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Arguments of `String` fields that transform the value in the database, such as
+//! `title(transform: LOWER, substring: { start: 1, length: 10 })`.
+
+use async_graphql_parser::{
+    Pos, Positioned,
+    types::{
+        EnumType, EnumValueDefinition, InputObjectType, InputValueDefinition, TypeDefinition,
+        TypeKind,
+    },
+};
+use async_graphql_value::Name;
+use core_model::type_normalization::{BaseType, Type, default_positioned, default_positioned_name};
+
+pub const TRANSFORM_ARG_NAME: &str = "transform";
+pub const SUBSTRING_ARG_NAME: &str = "substring";
+
+pub const STRING_TRANSFORM_TYPE_NAME: &str = "StringTransform";
+pub const STRING_SUBSTRING_TYPE_NAME: &str = "StringSubstring";
+
+pub const STRING_TRANSFORM_OPTIONS: [&str; 3] = ["LOWER", "UPPER", "TRIM"];
+
+/// The arguments to add to a `String` field (the transform applies before the substring)
+pub fn string_field_arguments() -> Vec<Positioned<InputValueDefinition>> {
+    [
+        input_value(TRANSFORM_ARG_NAME, STRING_TRANSFORM_TYPE_NAME, true),
+        input_value(SUBSTRING_ARG_NAME, STRING_SUBSTRING_TYPE_NAME, true),
+    ]
+    .into_iter()
+    .map(default_positioned)
+    .collect()
+}
+
+pub fn string_transform_type_definitions() -> Vec<TypeDefinition> {
+    let transform_type = TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(STRING_TRANSFORM_TYPE_NAME),
+        directives: vec![],
+        kind: TypeKind::Enum(EnumType {
+            values: STRING_TRANSFORM_OPTIONS
+                .iter()
+                .map(|value| {
+                    Positioned::new(
+                        EnumValueDefinition {
+                            description: None,
+                            value: Positioned::new(Name::new(value), Pos::default()),
+                            directives: vec![],
+                        },
+                        Pos::default(),
+                    )
+                })
+                .collect(),
+        }),
+    };
+
+    let substring_type = TypeDefinition {
+        extend: false,
+        description: None,
+        name: default_positioned_name(STRING_SUBSTRING_TYPE_NAME),
+        directives: vec![],
+        kind: TypeKind::InputObject(InputObjectType {
+            fields: [
+                input_value("start", "Int", false),
+                input_value("length", "Int", true),
+            ]
+            .into_iter()
+            .map(default_positioned)
+            .collect(),
+        }),
+    };
+
+    vec![transform_type, substring_type]
+}
+
+pub(crate) fn input_value(name: &str, type_name: &str, nullable: bool) -> InputValueDefinition {
+    InputValueDefinition {
+        description: None,
+        name: default_positioned_name(name),
+        directives: vec![],
+        default_value: None,
+        ty: default_positioned(
+            Type {
+                base: BaseType::Leaf(type_name.to_string()),
+                nullable,
+            }
+            .to_graphql_type(),
+        ),
+    }
+}
@@ -11,9 +11,19 @@ use std::{collections::HashMap, sync::Arc, vec};
 
 use async_graphql_parser::types::{FieldDefinition, TypeDefinition};
 
-use super::{mutation::PostgresMutation, order::OrderByParameterType};
+use super::{
+    date_transform::date_transform_type_definitions, lock::row_lock_type_definition,
+    mutation::PostgresMutation, order::OrderByParameterType,
+    string_transform::string_transform_type_definitions,
+};
 use crate::{
-    query::{AggregateQuery, CollectionQuery, UniqueQuery},
+    chunk::ChunkQuery,
+    page::PageType,
+    query::{
+        AggregateQuery, BatchQuery, CollectionQuery, PageQuery, SimilarityQuery, TreeQuery,
+        UniqueQuery,
+    },
+    similarity::SimilarityType,
     types::MutationType,
 };
 use core_model::{
@@ -37,6 +47,13 @@ pub struct PostgresGraphQLSubsystem {
     pub collection_queries: MappedArena<CollectionQuery>,
     pub aggregate_queries: MappedArena<AggregateQuery>,
     pub unique_queries: MappedArena<UniqueQuery>,
+    pub similarity_queries: MappedArena<SimilarityQuery>,
+    pub similarity_types: SerializableSlab<SimilarityType>, // result types of similarity queries such as `TodoSimilarity`
+    pub tree_queries: MappedArena<TreeQuery>,
+    pub batch_queries: MappedArena<BatchQuery>,
+    pub page_queries: MappedArena<PageQuery>,
+    pub page_types: SerializableSlab<PageType>, // result types of page queries such as `TodoPage`
+    pub chunk_queries: MappedArena<ChunkQuery>, // streaming queries for fields with `@chunked` such as `documentContentChunks`
 
     pub pk_queries_map:
         HashMap<SerializableSlabIndex<EntityType>, SerializableSlabIndex<UniqueQuery>>,
@@ -79,10 +96,45 @@ impl PostgresGraphQLSubsystem {
             .filter(|(_, query)| query.exposed_in_schema)
             .map(|(_, query)| query.field_definition(self));
 
+        let similarity_queries_defn = self
+            .similarity_queries
+            .iter()
+            .filter(|(_, query)| query.exposed_in_schema)
+            .map(|(_, query)| query.field_definition(self));
+
+        let tree_queries_defn = self
+            .tree_queries
+            .iter()
+            .filter(|(_, query)| query.exposed_in_schema)
+            .map(|(_, query)| query.field_definition(self));
+
+        let batch_queries_defn = self
+            .batch_queries
+            .iter()
+            .filter(|(_, query)| query.exposed_in_schema)
+            .map(|(_, query)| query.field_definition(self));
+
+        let page_queries_defn = self
+            .page_queries
+            .iter()
+            .filter(|(_, query)| query.exposed_in_schema)
+            .map(|(_, query)| query.field_definition(self));
+
+        let chunk_queries_defn = self
+            .chunk_queries
+            .iter()
+            .filter(|(_, query)| query.exposed_in_schema)
+            .map(|(_, query)| query.field_definition(self));
+
         pk_queries_defn
             .chain(collection_queries_defn)
             .chain(aggregate_queries_defn)
             .chain(unique_queries_defn)
+            .chain(similarity_queries_defn)
+            .chain(tree_queries_defn)
+            .chain(batch_queries_defn)
+            .chain(page_queries_defn)
+            .chain(chunk_queries_defn)
             .collect()
     }
 
@@ -111,6 +163,14 @@ impl PostgresGraphQLSubsystem {
             .iter()
             .for_each(|typ| all_type_definitions.push(typ.1.type_definition(self)));
 
+        self.similarity_types
+            .iter()
+            .for_each(|typ| all_type_definitions.push(typ.1.type_definition(self)));
+
+        self.page_types
+            .iter()
+            .for_each(|typ| all_type_definitions.push(typ.1.type_definition(self)));
+
         self.order_by_types.iter().for_each(|parameter_type| {
             all_type_definitions.push(parameter_type.1.type_definition(self))
         });
@@ -123,6 +183,10 @@ impl PostgresGraphQLSubsystem {
             all_type_definitions.push(parameter_type.1.type_definition(self))
         });
 
+        all_type_definitions.extend(string_transform_type_definitions());
+        all_type_definitions.extend(date_transform_type_definitions());
+        all_type_definitions.push(row_lock_type_definition());
+
         all_type_definitions
     }
 
@@ -157,6 +221,13 @@ impl Default for PostgresGraphQLSubsystem {
             collection_queries: MappedArena::default(),
             aggregate_queries: MappedArena::default(),
             unique_queries: MappedArena::default(),
+            similarity_queries: MappedArena::default(),
+            similarity_types: SerializableSlab::new(),
+            tree_queries: MappedArena::default(),
+            batch_queries: MappedArena::default(),
+            page_queries: MappedArena::default(),
+            page_types: SerializableSlab::new(),
+            chunk_queries: MappedArena::default(),
             mutation_types: SerializableSlab::new(),
             mutations: MappedArena::default(),
 
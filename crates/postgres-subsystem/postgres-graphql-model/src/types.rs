@@ -7,8 +7,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::date_transform::{DATE_TYPE_NAMES, date_field_arguments};
 use crate::operation::{OperationParameters, PostgresOperation};
 use crate::query::CollectionQueryParameters;
+use crate::string_transform::string_field_arguments;
 use crate::subsystem::PostgresGraphQLSubsystem;
 use async_graphql_parser::types::{
     EnumType, EnumValueDefinition, FieldDefinition, InputObjectType, ObjectType, TypeDefinition,
@@ -88,6 +90,7 @@ impl TypeDefinitionProvider<PostgresGraphQLSubsystem> for EntityType {
             fields,
             agg_fields,
             vector_distance_fields,
+            window_fields,
             ..
         } = self;
 
@@ -104,9 +107,14 @@ impl TypeDefinitionProvider<PostgresGraphQLSubsystem> for EntityType {
                 .iter()
                 .map(|field| default_positioned(field.field_definition(system)));
 
+            let window_fields = window_fields
+                .iter()
+                .map(|field| default_positioned(field.field_definition(system)));
+
             let fields = entity
                 .chain(agg_fields)
                 .chain(vector_distance_fields)
+                .chain(window_fields)
                 .collect();
 
             TypeKind::Object(ObjectType {
@@ -181,11 +189,25 @@ impl<CT> FieldDefinitionProvider<PostgresGraphQLSubsystem> for PostgresField<CT>
             };
         }
 
+        let plain_type_name = match self.typ.base_type() {
+            FieldType::Plain(base) => Some(base.name()),
+            _ => None,
+        };
+
         let arguments = match &self.relation {
+            PostgresRelation::Scalar { .. } if plain_type_name == Some("String") => {
+                string_field_arguments()
+            }
+            PostgresRelation::Scalar { .. }
+                if plain_type_name.is_some_and(|name| DATE_TYPE_NAMES.contains(&name)) =>
+            {
+                date_field_arguments(self.typ.name())
+            }
             PostgresRelation::Scalar { .. }
             | PostgresRelation::ManyToOne { .. }
             | PostgresRelation::Embedded
-            | PostgresRelation::Computed(_) => {
+            | PostgresRelation::Computed(_)
+            | PostgresRelation::SqlExpression(_) => {
                 vec![]
             }
             PostgresRelation::OneToMany(OneToManyRelation {
@@ -198,6 +220,7 @@ impl<CT> FieldDefinitionProvider<PostgresGraphQLSubsystem> for PostgresField<CT>
                     order_by_param,
                     limit_param,
                     offset_param,
+                    ..
                 } = &collection_query.parameters;
 
                 [
@@ -225,6 +248,7 @@ impl<CT> FieldDefinitionProvider<PostgresGraphQLSubsystem> for PostgresField<CT>
                             order_by_param,
                             limit_param,
                             offset_param,
+                            ..
                         } = &collection_query.parameters;
 
                         [
@@ -247,6 +271,7 @@ impl<CT> FieldDefinitionProvider<PostgresGraphQLSubsystem> for PostgresField<CT>
                                     order_by_param,
                                     limit_param,
                                     offset_param,
+                                    ..
                                 } = &collection_query.parameters;
 
                                 [
@@ -267,7 +292,11 @@ impl<CT> FieldDefinitionProvider<PostgresGraphQLSubsystem> for PostgresField<CT>
             },
         };
 
-        let field_type: Type = (&self.typ).into();
+        let mut field_type: Type = (&self.typ).into();
+        // A field with a read default never returns null
+        if self.read_default.is_some() {
+            field_type.nullable = false;
+        }
         let field_type = default_positioned(field_type.to_graphql_type());
 
         FieldDefinition {
@@ -0,0 +1,33 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::types::{BaseType, FieldDefinition, Type};
+use async_graphql_value::Name;
+use core_model::type_normalization::{
+    FieldDefinitionProvider, default_positioned, default_positioned_name,
+};
+
+use crate::subsystem::PostgresGraphQLSubsystem;
+
+use postgres_core_model::window::WindowField;
+
+impl FieldDefinitionProvider<PostgresGraphQLSubsystem> for WindowField {
+    fn field_definition(&self, _system: &PostgresGraphQLSubsystem) -> FieldDefinition {
+        FieldDefinition {
+            description: None,
+            name: default_positioned_name(&self.name),
+            arguments: vec![],
+            ty: default_positioned(Type {
+                base: BaseType::Named(Name::new("Int")),
+                nullable: false,
+            }),
+            directives: vec![],
+        }
+    }
+}
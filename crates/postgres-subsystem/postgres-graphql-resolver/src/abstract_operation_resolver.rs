@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use exo_sql::database_error::DatabaseError;
+use exo_sql::AbstractOperation;
 
 use common::context::RequestContext;
 use core_resolver::{
@@ -15,6 +15,7 @@ use core_resolver::{
     validation::field::ValidatedField,
 };
 use postgres_core_resolver::database_helper::extractor;
+use postgres_core_resolver::session_settings::apply_session_settings;
 
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
 
@@ -35,6 +36,33 @@ pub async fn resolve_operation<'e>(
         return_type,
     } = resolved_operation;
 
+    let mut response = execute_operation(operation, subsystem_resolver, request_context).await?;
+
+    apply_computed_fields_to_body(
+        &mut response.body,
+        return_type,
+        field,
+        subsystem_resolver,
+        system_resolver,
+        request_context,
+    )
+    .await?;
+
+    Ok(response)
+}
+
+/// Execute the operation and return the raw response (without any post-processing such as computing fields)
+pub(crate) async fn execute_operation<'e>(
+    operation: AbstractOperation,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    apply_session_settings(
+        &subsystem_resolver.subsystem.core_subsystem,
+        request_context,
+    )
+    .await?;
+
     let result = {
         let mut tx = request_context
             .system_context
@@ -52,11 +80,12 @@ pub async fn resolve_operation<'e>(
             .await
     };
 
-    if let Err(DatabaseError::Precheck(_)) = result {
-        return Err(PostgresExecutionError::Authorization);
-    }
-
-    let mut result = result.map_err(PostgresExecutionError::Postgres)?;
+    let mut result = result.map_err(|error| {
+        PostgresExecutionError::from_database_error(
+            error,
+            &subsystem_resolver.subsystem.core_subsystem,
+        )
+    })?;
 
     let body = if result.len() == 1 {
         let string_result = extractor(result.swap_remove(0))?;
@@ -67,20 +96,8 @@ pub async fn resolve_operation<'e>(
         Err(PostgresExecutionError::NonUniqueResult(result.len()))
     }?;
 
-    let mut response = QueryResponse {
+    Ok(QueryResponse {
         body,
         headers: vec![],
-    };
-
-    apply_computed_fields_to_body(
-        &mut response.body,
-        return_type,
-        field,
-        subsystem_resolver,
-        system_resolver,
-        request_context,
-    )
-    .await?;
-
-    Ok(response)
+    })
 }
@@ -233,7 +233,7 @@ impl TestSystem {
                 }
             }
             PostgresRelation::Embedded => panic!("Cannot append field to embedded relation"),
-            PostgresRelation::Computed(_) => {
+            PostgresRelation::Computed(_) | PostgresRelation::SqlExpression(_) => {
                 panic!("Cannot append field to computed relation in test system")
             }
             PostgresRelation::Transitive(_) => {
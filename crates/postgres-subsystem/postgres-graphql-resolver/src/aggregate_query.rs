@@ -52,12 +52,9 @@ impl OperationSelectionResolver for AggregateQuery {
         .await?;
 
         let return_entity_type = self.return_type.typ(&subsystem.core_subsystem.entity_types);
-        let parent_read_predicate = check_retrieve_access(
-            &subsystem.core_subsystem.database_access_expressions[return_entity_type.access.read],
-            subsystem,
-            request_context,
-        )
-        .await?;
+        let parent_read_predicate =
+            check_retrieve_access(return_entity_type.access.read, subsystem, request_context)
+                .await?;
         let restrict_relations = parent_read_predicate != AbstractPredicate::True;
 
         let query_predicate = super::predicate_mapper::compute_predicate(
@@ -92,6 +89,7 @@ impl OperationSelectionResolver for AggregateQuery {
                 order_by: None,
                 offset: None,
                 limit: None,
+                lock: None,
             },
             return_type: &self.return_type,
         })
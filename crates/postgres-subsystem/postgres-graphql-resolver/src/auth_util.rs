@@ -13,14 +13,15 @@ use core_model::mapped_arena::SerializableSlabIndex;
 use core_resolver::access_solver::AccessInput;
 use futures::stream::TryStreamExt;
 use postgres_core_model::access::{
-    CreationAccessExpression, DatabaseAccessPrimitiveExpression, PrecheckAccessPrimitiveExpression,
-    UpdateAccessExpression,
+    CreationAccessExpression, PrecheckAccessPrimitiveExpression, UpdateAccessExpression,
 };
+use postgres_core_model::access_predicate_cache::DatabaseAccessExpressionId;
 use postgres_core_model::{
     relation::PostgresRelation,
     types::{EntityType, PostgresField},
 };
 
+use postgres_core_resolver::access::database_solver::solve_database_access;
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
 
 use super::sql_mapper::SQLOperationKind;
@@ -84,12 +85,9 @@ pub(crate) async fn check_access<'a>(
                 }?
             }
             SQLOperationKind::Retrieve => {
-                let entity_access = check_retrieve_access(
-                    &subsystem.core_subsystem.database_access_expressions[return_type.access.read],
-                    subsystem,
-                    request_context,
-                )
-                .await?;
+                let entity_access =
+                    check_retrieve_access(return_type.access.read, subsystem, request_context)
+                        .await?;
 
                 if entity_access == Predicate::False {
                     // Short circuit this common case
@@ -142,13 +140,9 @@ pub(crate) async fn check_access<'a>(
                 }?
             }
             SQLOperationKind::Delete => {
-                let (precheck_predicate, entity_predicate) = check_delete_access(
-                    &subsystem.core_subsystem.database_access_expressions
-                        [return_type.access.delete],
-                    subsystem,
-                    request_context,
-                )
-                .await?;
+                let (precheck_predicate, entity_predicate) =
+                    check_delete_access(return_type.access.delete, subsystem, request_context)
+                        .await?;
 
                 (precheck_predicate, entity_predicate, vec![])
             }
@@ -192,16 +186,11 @@ async fn check_create_access<'a>(
 }
 
 pub(super) async fn check_retrieve_access<'a>(
-    expr: &AccessPredicateExpression<DatabaseAccessPrimitiveExpression>,
+    expression_id: DatabaseAccessExpressionId,
     subsystem: &'a PostgresGraphQLSubsystem,
     request_context: &'a RequestContext<'a>,
 ) -> Result<AbstractPredicate, PostgresExecutionError> {
-    Ok(subsystem
-        .core_subsystem
-        .solve(request_context, None, expr)
-        .await?
-        .map(|p| p.0)
-        .resolve())
+    Ok(solve_database_access(&subsystem.core_subsystem, request_context, expression_id).await?)
 }
 
 async fn check_update_access<'a>(
@@ -229,33 +218,20 @@ async fn check_update_access<'a>(
     }
 
     // Now compute the database access predicate (the "where" clause to the update statement)
-    let database_predicate = subsystem
-        .core_subsystem
-        .solve(
-            request_context,
-            None,
-            &subsystem.core_subsystem.database_access_expressions[expr.database],
-        )
-        .await?
-        .map(|p| p.0)
-        .resolve();
+    let database_predicate =
+        solve_database_access(&subsystem.core_subsystem, request_context, expr.database).await?;
 
     Ok((precheck_predicate, database_predicate))
 }
 
 async fn check_delete_access<'a>(
-    expr: &AccessPredicateExpression<DatabaseAccessPrimitiveExpression>,
+    expression_id: DatabaseAccessExpressionId,
     subsystem: &'a PostgresGraphQLSubsystem,
     request_context: &'a RequestContext<'a>,
 ) -> Result<(AbstractPredicate, AbstractPredicate), PostgresExecutionError> {
     Ok((
         AbstractPredicate::True,
-        subsystem
-            .core_subsystem
-            .solve(request_context, None, expr)
-            .await?
-            .map(|p| p.0)
-            .resolve(),
+        solve_database_access(&subsystem.core_subsystem, request_context, expression_id).await?,
     ))
 }
 
@@ -277,8 +253,7 @@ async fn check_selection_access<'a>(
                 let (field_access_predicate, is_relation_field) = match postgres_field {
                     Some(postgres_field) => {
                         let predicate = check_retrieve_access(
-                            &subsystem.core_subsystem.database_access_expressions
-                                [postgres_field.access.read],
+                            postgres_field.access.read,
                             subsystem,
                             request_context,
                         )
@@ -297,8 +272,7 @@ async fn check_selection_access<'a>(
                         {
                             Some(vector_distance_field) => {
                                 check_retrieve_access(
-                                    &subsystem.core_subsystem.database_access_expressions
-                                        [vector_distance_field.access.read],
+                                    vector_distance_field.access.read,
                                     subsystem,
                                     request_context,
                                 )
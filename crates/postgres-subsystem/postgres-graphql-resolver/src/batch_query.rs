@@ -0,0 +1,193 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_value::Name;
+use async_trait::async_trait;
+use common::{context::RequestContext, value::Val};
+use core_resolver::{
+    QueryResponse, QueryResponseBody, system_resolver::GraphQLSystemResolver,
+    validation::field::ValidatedField,
+};
+use exo_sql::{AbstractPredicate, ColumnPath, PhysicalColumnPath};
+use postgres_core_model::relation::PostgresRelation;
+use postgres_core_resolver::{
+    cast::literal_column_path, opaque_id::decode_opaque_ids,
+    postgres_execution_error::PostgresExecutionError,
+};
+use postgres_graphql_model::{
+    query::{BatchQuery, BatchQueryParameters},
+    subsystem::PostgresGraphQLSubsystem,
+};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    abstract_operation_resolver::resolve_operation,
+    operation_resolver::{OperationResolver, OperationSelectionResolver, ResolvedSelect},
+    postgres_query::compute_select,
+    resolver::PostgresSubsystemResolver,
+};
+
+/// The output name of the primary key selected (in addition to the user's selection) to match the
+/// fetched entities with the requested keys
+const BATCH_KEY_FIELD: &str = "__batch_key";
+
+#[async_trait]
+impl OperationSelectionResolver for BatchQuery {
+    async fn resolve_select<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+        subsystem: &'a PostgresGraphQLSubsystem,
+    ) -> Result<ResolvedSelect<'a>, PostgresExecutionError> {
+        let BatchQueryParameters { ids_param } = &self.parameters;
+
+        let return_entity_type = self.return_type.typ(&subsystem.core_subsystem.entity_types);
+
+        let ids = field.arguments.get(&ids_param.name).ok_or_else(|| {
+            PostgresExecutionError::Validation(ids_param.name.clone(), "Missing argument".into())
+        })?;
+
+        let pk_column_id = match return_entity_type.pk_fields()[..] {
+            [pk_field] => match pk_field.relation {
+                PostgresRelation::Scalar { column_id, .. } => Some(column_id),
+                _ => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "Type '{}' doesn't have a single scalar primary key",
+                return_entity_type.name
+            ))
+        })?;
+        let pk_column = pk_column_id.get_column(&subsystem.core_subsystem.database);
+
+        // Same as the `in` filter (`id IN (SELECT unnest($1))`), so all the keys are passed as a single parameter
+        let predicate = AbstractPredicate::In(
+            ColumnPath::Physical(PhysicalColumnPath::leaf(pk_column_id)),
+            literal_column_path(
+                &decode_opaque_ids(ids, pk_column, &subsystem.core_subsystem)?,
+                pk_column.typ.inner(),
+                true,
+            )?,
+        );
+
+        let select = compute_select(
+            predicate,
+            None,
+            None,
+            None,
+            &self.return_type,
+            &field.subfields,
+            subsystem,
+            request_context,
+        )
+        .await?;
+
+        Ok(ResolvedSelect {
+            select,
+            return_type: &self.return_type,
+        })
+    }
+}
+
+/// Resolve a batch query by fetching the matching entities (along with their primary key) and then
+/// arranging them in the order of the requested keys
+pub(crate) async fn resolve_batch_query<'e>(
+    query: &'e BatchQuery,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let return_entity_type = query
+        .return_type
+        .typ(&subsystem.core_subsystem.entity_types);
+    let pk_field = return_entity_type
+        .pk_fields()
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "Type '{}' doesn't have a primary key",
+                return_entity_type.name
+            ))
+        })?;
+
+    let requested_ids = match field.arguments.get(&query.parameters.ids_param.name) {
+        Some(Val::List(ids)) => ids.clone(),
+        _ => {
+            return Err(PostgresExecutionError::Validation(
+                query.parameters.ids_param.name.clone(),
+                "Expected a list of primary keys".into(),
+            ));
+        }
+    };
+
+    let mut keyed_field = field.clone();
+    keyed_field.subfields.push(ValidatedField {
+        alias: Some(Name::new(BATCH_KEY_FIELD)),
+        name: Name::new(&pk_field.name),
+        arguments: Default::default(),
+        subfields: vec![],
+    });
+
+    let operation = query
+        .resolve(&keyed_field, request_context, subsystem)
+        .await?;
+    let response = resolve_operation(
+        operation,
+        &keyed_field,
+        subsystem_resolver,
+        request_context,
+        system_resolver,
+    )
+    .await?;
+
+    let entities = match response
+        .body
+        .to_json()
+        .map_err(|e| PostgresExecutionError::Generic(e.to_string()))?
+    {
+        JsonValue::Array(entities) => entities,
+        JsonValue::Null => vec![],
+        _ => {
+            return Err(PostgresExecutionError::Generic(
+                "Expected a list of entities for a batch query".into(),
+            ));
+        }
+    };
+
+    let entities: Vec<(JsonValue, JsonValue)> = entities
+        .into_iter()
+        .filter_map(|mut entity| {
+            let key = entity.as_object_mut()?.remove(BATCH_KEY_FIELD)?;
+            Some((key, entity))
+        })
+        .collect();
+
+    let ordered = requested_ids
+        .into_iter()
+        .map(|id| {
+            let id: JsonValue = id.try_into().unwrap_or(JsonValue::Null);
+            entities
+                .iter()
+                .find(|(key, _)| *key == id)
+                .map(|(_, entity)| entity.clone())
+                .unwrap_or(JsonValue::Null)
+        })
+        .collect();
+
+    Ok(QueryResponse {
+        body: QueryResponseBody::Json(JsonValue::Array(ordered)),
+        headers: response.headers,
+    })
+}
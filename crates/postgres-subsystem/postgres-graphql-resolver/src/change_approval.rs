@@ -0,0 +1,511 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The approval workflow of types declared with `@requiresApproval`: their mutations are recorded
+//! as pending changes (in the generated change type) and applied only once another user approves
+//! them.
+
+use async_graphql_value::Name;
+use common::{context::RequestContext, value::Val};
+use core_model::{
+    mapped_arena::SerializableSlabIndex,
+    types::{BaseOperationReturnType, OperationReturnType},
+};
+use core_resolver::{
+    QueryResponse, QueryResponseBody, context_extractor::ContextExtractor,
+    system_resolver::GraphQLSystemResolver, validation::field::ValidatedField,
+};
+use exo_sql::{
+    AbstractInsert, AbstractOperation, AbstractPredicate, AbstractUpdate, Column, ColumnId,
+    ColumnPath, ColumnValuePair, InsertionElement, InsertionRow, PhysicalColumnPath, Predicate,
+    SQLParamContainer,
+};
+use indexmap::IndexMap;
+use postgres_core_model::{
+    access::ApprovalWorkflow, predicate::PredicateParameter, relation::PostgresRelation,
+    types::EntityType,
+};
+use postgres_core_resolver::{cast, postgres_execution_error::PostgresExecutionError};
+use postgres_graphql_model::{
+    mutation::{ChangeDecision, PostgresMutation, PostgresMutationParameters},
+    subsystem::PostgresGraphQLSubsystem,
+};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::{
+    abstract_operation_resolver::{execute_operation, resolve_operation},
+    auth_util::check_retrieve_access,
+    computed_fields::serde_helpers::args_to_json,
+    operation_resolver::OperationResolver,
+    postgres_query::compute_select,
+    predicate_mapper::compute_predicate,
+    resolver::PostgresSubsystemResolver,
+};
+
+/// The response header carrying the id of the change recorded for a mutation requiring approval
+pub(crate) const PENDING_CHANGE_HEADER: &str = "exo-pending-change";
+
+const PENDING_STATUS: &str = "pending";
+
+/// Resolve a mutation through the approval workflow if it takes part in one: a mutation of a type
+/// requiring approval (recorded as a pending change) or a review of such a change. Returns `None`
+/// for other mutations, which resolve as usual.
+pub(crate) async fn resolve_approval_workflow<'e>(
+    mutation: &'e PostgresMutation,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<Option<QueryResponse>, PostgresExecutionError> {
+    if let PostgresMutationParameters::ReviewChange {
+        predicate_params,
+        subject_type_id,
+        decision,
+    } = &mutation.parameters
+    {
+        return review_change(
+            mutation,
+            predicate_params,
+            *subject_type_id,
+            *decision,
+            field,
+            subsystem_resolver,
+            request_context,
+            system_resolver,
+        )
+        .await
+        .map(Some);
+    }
+
+    let entity_type = mutation
+        .return_type
+        .typ(&subsystem_resolver.subsystem.core_subsystem.entity_types);
+
+    match &entity_type.approval {
+        Some(approval) => request_approval(
+            mutation,
+            approval,
+            field,
+            subsystem_resolver,
+            request_context,
+            system_resolver,
+        )
+        .await
+        .map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Record a mutation of a type requiring approval as a pending change instead of applying it.
+///
+/// The mutation first runs (with the access rules of the requester) inside a savepoint that is
+/// always rolled back, so the client gets the outcome it would have (or the error it would fail
+/// with) without changing any data. That outcome is stored as the preview of the change and
+/// returned along with the id of the change in the [`PENDING_CHANGE_HEADER`] header.
+async fn request_approval<'e>(
+    mutation: &'e PostgresMutation,
+    approval: &'e ApprovalWorkflow,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+    let requester = context_identity(approval, subsystem, request_context).await?;
+
+    let preview = dry_run(
+        mutation,
+        field,
+        subsystem_resolver,
+        request_context,
+        system_resolver,
+    )
+    .await?;
+
+    let change_type = &subsystem.core_subsystem.entity_types[approval.change_type_id];
+    let column_values = vec![
+        column_value(
+            change_type,
+            "mutation",
+            &Val::String(field.name.to_string()),
+            subsystem,
+        )?,
+        column_value(
+            change_type,
+            "arguments",
+            &Val::from(args_to_json(&field.arguments)?),
+            subsystem,
+        )?,
+        column_value(
+            change_type,
+            "preview",
+            &Val::from(body_to_json(&preview.body)?),
+            subsystem,
+        )?,
+        column_value(change_type, "requestedBy", &requester, subsystem)?,
+        column_value(change_type, "requestedAt", &now(request_context), subsystem)?,
+    ];
+
+    let insert = AbstractInsert {
+        table_id: change_type.table_id,
+        rows: vec![InsertionRow {
+            elems: column_values
+                .into_iter()
+                .map(|(column_id, value)| {
+                    InsertionElement::SelfInsert(ColumnValuePair::new(column_id, value))
+                })
+                .collect(),
+        }],
+        selection: compute_select(
+            AbstractPredicate::True,
+            None,
+            None,
+            None,
+            &change_return_type(approval.change_type_id, change_type),
+            &[scalar_field("id")],
+            subsystem,
+            request_context,
+        )
+        .await?,
+        precheck_predicates: vec![],
+    };
+
+    let change = execute_operation(
+        AbstractOperation::Insert(insert),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    let change_id = match body_to_json(&change.body)?.get("id") {
+        Some(JsonValue::String(id)) => id.clone(),
+        Some(id) => id.to_string(),
+        None => {
+            return Err(PostgresExecutionError::Generic(format!(
+                "Failed to record the change to '{}'",
+                change_type.name
+            )));
+        }
+    };
+
+    let mut headers = preview.headers;
+    headers.push((PENDING_CHANGE_HEADER.to_string(), change_id));
+
+    Ok(QueryResponse {
+        body: preview.body,
+        headers,
+    })
+}
+
+/// Approve or reject a pending change (through mutations such as `approvePayoutChange`).
+///
+/// Only users satisfying the approver rule may review changes and never the user who requested the
+/// change. Approving replays the recorded mutation (against the data as it is now and with the
+/// access rules of the approver) before recording the decision. If the change doesn't exist or
+/// isn't pending anymore, nothing happens and the result is null.
+#[allow(clippy::too_many_arguments)]
+async fn review_change<'e>(
+    mutation: &'e PostgresMutation,
+    predicate_params: &'e [PredicateParameter],
+    subject_type_id: SerializableSlabIndex<EntityType>,
+    decision: ChangeDecision,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+    let subject_type = &subsystem.core_subsystem.entity_types[subject_type_id];
+
+    let approval = subject_type.approval.as_ref().ok_or_else(|| {
+        PostgresExecutionError::Generic(format!(
+            "Changes to '{}' don't require approval",
+            subject_type.name
+        ))
+    })?;
+
+    // The approver rule refers only to the context, so it must be satisfied outright
+    let approver_predicate =
+        check_retrieve_access(approval.approver, subsystem, request_context).await?;
+    if approver_predicate != AbstractPredicate::True {
+        return Err(PostgresExecutionError::Authorization);
+    }
+    let reviewer = context_identity(approval, subsystem, request_context).await?;
+
+    let change_type = &subsystem.core_subsystem.entity_types[approval.change_type_id];
+
+    let arg_predicate = compute_predicate(
+        &predicate_params.iter().collect::<Vec<_>>(),
+        &field.arguments,
+        subsystem,
+        request_context,
+        false,
+    )
+    .await?;
+    let pending_predicate = Predicate::and(
+        arg_predicate,
+        AbstractPredicate::eq(
+            ColumnPath::Physical(PhysicalColumnPath::leaf(column_id(change_type, "status")?)),
+            ColumnPath::Param(SQLParamContainer::string(PENDING_STATUS.to_string())),
+        ),
+    );
+
+    let change_select = compute_select(
+        pending_predicate.clone(),
+        None,
+        None,
+        None,
+        &change_return_type(approval.change_type_id, change_type),
+        &[
+            scalar_field("mutation"),
+            scalar_field("arguments"),
+            scalar_field("requestedBy"),
+        ],
+        subsystem,
+        request_context,
+    )
+    .await?;
+    let change = execute_operation(
+        AbstractOperation::Select(change_select),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    let JsonValue::Object(change) = body_to_json(&change.body)? else {
+        return Ok(QueryResponse {
+            body: QueryResponseBody::Json(JsonValue::Null),
+            headers: vec![],
+        });
+    };
+
+    // The two-person rule: a change must be reviewed by someone other than its requester
+    let reviewer_json: JsonValue = reviewer.clone().try_into().map_err(|_| {
+        PostgresExecutionError::Generic("Failed to convert the reviewer to JSON".to_string())
+    })?;
+    if change.get("requestedBy") == Some(&reviewer_json) {
+        return Err(PostgresExecutionError::Authorization);
+    }
+
+    if decision == ChangeDecision::Approve {
+        apply_change(
+            &change,
+            subject_type,
+            subsystem_resolver,
+            request_context,
+            system_resolver,
+        )
+        .await?;
+    }
+
+    let update = AbstractUpdate {
+        table_id: change_type.table_id,
+        predicate: pending_predicate,
+        column_values: vec![
+            column_value(
+                change_type,
+                "status",
+                &Val::String(decision.status().to_string()),
+                subsystem,
+            )?,
+            column_value(change_type, "reviewedBy", &reviewer, subsystem)?,
+            column_value(change_type, "reviewedAt", &now(request_context), subsystem)?,
+        ],
+        selection: compute_select(
+            AbstractPredicate::True,
+            None,
+            None,
+            None,
+            &mutation.return_type,
+            &field.subfields,
+            subsystem,
+            request_context,
+        )
+        .await?,
+        nested_updates: vec![],
+        nested_inserts: vec![],
+        nested_deletes: vec![],
+        precheck_predicates: vec![],
+    };
+
+    execute_operation(
+        AbstractOperation::Update(update),
+        subsystem_resolver,
+        request_context,
+    )
+    .await
+}
+
+/// Run the mutation and roll back its effects (regardless of whether it succeeded)
+async fn dry_run<'e>(
+    mutation: &'e PostgresMutation,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let transaction_holder = &request_context.system_context.transaction_holder;
+
+    let savepoint = transaction_holder.lock().await.savepoint().await?;
+
+    let result = async {
+        let operation = mutation
+            .resolve(field, request_context, &subsystem_resolver.subsystem)
+            .await?;
+        resolve_operation(
+            operation,
+            field,
+            subsystem_resolver,
+            request_context,
+            system_resolver,
+        )
+        .await
+    }
+    .await;
+
+    transaction_holder
+        .lock()
+        .await
+        .rollback_to_savepoint(savepoint)
+        .await?;
+
+    result
+}
+
+/// Apply an approved change by replaying the recorded mutation with the recorded arguments
+async fn apply_change<'e>(
+    change: &JsonMap<String, JsonValue>,
+    subject_type: &'e EntityType,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<(), PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let mutation_name = change
+        .get("mutation")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic("The change doesn't record its mutation".to_string())
+        })?;
+    let mutation = subsystem
+        .mutations
+        .get_by_key(mutation_name)
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "The mutation '{mutation_name}' of the change no longer exists"
+            ))
+        })?;
+
+    let arguments: IndexMap<String, Val> = match change.get("arguments") {
+        Some(JsonValue::Object(arguments)) => arguments
+            .iter()
+            .map(|(name, value)| (name.clone(), Val::from(value.clone())))
+            .collect(),
+        _ => IndexMap::new(),
+    };
+
+    let replay_field = ValidatedField {
+        alias: None,
+        name: Name::new(mutation_name),
+        arguments,
+        subfields: subject_type
+            .pk_fields()
+            .iter()
+            .map(|pk_field| scalar_field(&pk_field.name))
+            .collect(),
+    };
+
+    let operation = mutation
+        .resolve(&replay_field, request_context, subsystem)
+        .await?;
+    resolve_operation(
+        operation,
+        &replay_field,
+        subsystem_resolver,
+        request_context,
+        system_resolver,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The identity of the current user (as selected by the `requester` of `@requiresApproval`)
+async fn context_identity(
+    approval: &ApprovalWorkflow,
+    subsystem: &PostgresGraphQLSubsystem,
+    request_context: &RequestContext<'_>,
+) -> Result<Val, PostgresExecutionError> {
+    let identity = subsystem
+        .core_subsystem
+        .extract_context_selection(request_context, &approval.requester)
+        .await?;
+
+    match identity {
+        Some(identity) if !matches!(identity, Val::Null) => Ok(identity.clone()),
+        _ => Err(PostgresExecutionError::Authorization),
+    }
+}
+
+fn change_return_type(
+    change_type_id: SerializableSlabIndex<EntityType>,
+    change_type: &EntityType,
+) -> OperationReturnType<EntityType> {
+    OperationReturnType::Plain(BaseOperationReturnType {
+        associated_type_id: change_type_id,
+        type_name: change_type.name.clone(),
+    })
+}
+
+fn scalar_field(name: &str) -> ValidatedField {
+    ValidatedField {
+        alias: None,
+        name: Name::new(name),
+        arguments: IndexMap::new(),
+        subfields: vec![],
+    }
+}
+
+pub(crate) fn column_id(
+    entity_type: &EntityType,
+    field_name: &str,
+) -> Result<ColumnId, PostgresExecutionError> {
+    match entity_type
+        .field_by_name(field_name)
+        .map(|field| &field.relation)
+    {
+        Some(PostgresRelation::Scalar { column_id, .. }) => Ok(*column_id),
+        _ => Err(PostgresExecutionError::Generic(format!(
+            "Field '{field_name}' of '{}' is not a column",
+            entity_type.name
+        ))),
+    }
+}
+
+pub(crate) fn column_value(
+    entity_type: &EntityType,
+    field_name: &str,
+    value: &Val,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Result<(ColumnId, Column), PostgresExecutionError> {
+    let column_id = column_id(entity_type, field_name)?;
+    let column = column_id.get_column(&subsystem.core_subsystem.database);
+
+    Ok((column_id, cast::literal_column(value, column)?))
+}
+
+pub(crate) fn body_to_json(body: &QueryResponseBody) -> Result<JsonValue, PostgresExecutionError> {
+    body.to_json()
+        .map_err(|e| PostgresExecutionError::Generic(e.to_string()))
+}
+
+fn now(request_context: &RequestContext<'_>) -> Val {
+    Val::String(request_context.system_context.clock.now().to_rfc3339())
+}
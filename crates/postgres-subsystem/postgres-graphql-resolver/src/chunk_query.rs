@@ -0,0 +1,304 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Streaming of the value of a field declared with `@chunked` (see [`ChunkQuery`]).
+
+use async_stream::try_stream;
+use base64::Engine;
+use common::context::RequestContext;
+use core_model::primitive_type::BlobType;
+use core_resolver::{
+    QueryResponse, QueryResponseBody, QueryResponseStream, validation::field::ValidatedField,
+};
+use exo_sql::{
+    AbstractOperation, AbstractPredicate, AbstractSelect, AliasedSelectionElement, ColumnId,
+    Database, DatabaseExecutor, Function, RowLock, Selection, SelectionCardinality,
+    SelectionElement, StringFunction, TableId, TransactionHolder, database_error::DatabaseError,
+};
+use postgres_core_model::relation::PostgresRelation;
+use postgres_core_resolver::{
+    database_helper::extractor, postgres_execution_error::PostgresExecutionError,
+    session_settings::session_settings,
+};
+use postgres_graphql_model::chunk::ChunkQuery;
+use serde_json::Value as JsonValue;
+use tracing::warn;
+
+use crate::{
+    auth_util::check_retrieve_access, predicate_mapper::compute_predicate,
+    resolver::PostgresSubsystemResolver,
+};
+
+/// Resolve a chunk query by streaming the field's value a chunk at a time.
+///
+/// Access control and the primary key predicate are resolved upfront using the caller's context,
+/// so that the stream can fetch the chunks on its own. The stream reads the chunks in a
+/// transaction that keeps the row share-locked, so the value can't change between chunks.
+pub(crate) async fn resolve_chunk_query<'e>(
+    query: &'e ChunkQuery,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+    let core_subsystem = subsystem.core_subsystem.clone();
+
+    let entity_type = &core_subsystem.entity_types[query.entity_type_id];
+    let chunked_field = entity_type
+        .field_by_name(&query.field_name)
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "Field '{}' not found in type '{}'",
+                query.field_name, entity_type.name
+            ))
+        })?;
+    let PostgresRelation::Scalar { column_id, .. } = chunked_field.relation else {
+        return Err(PostgresExecutionError::Generic(format!(
+            "Chunked field '{}' of type '{}' must be a scalar",
+            query.field_name, entity_type.name
+        )));
+    };
+
+    let entity_read_predicate =
+        check_retrieve_access(entity_type.access.read, subsystem, request_context).await?;
+    let field_read_predicate =
+        check_retrieve_access(chunked_field.access.read, subsystem, request_context).await?;
+    if entity_read_predicate == AbstractPredicate::False
+        || field_read_predicate == AbstractPredicate::False
+    {
+        return Err(PostgresExecutionError::Authorization);
+    }
+    let restrict_relations = entity_read_predicate != AbstractPredicate::True;
+
+    let pk_predicate = compute_predicate(
+        &[&query.pk_param],
+        &field.arguments,
+        subsystem,
+        request_context,
+        restrict_relations,
+    )
+    .await?;
+
+    let predicate = AbstractPredicate::and(
+        pk_predicate,
+        AbstractPredicate::and(entity_read_predicate, field_read_predicate),
+    );
+
+    let chunk_source = ChunkSource {
+        table_id: entity_type.table_id,
+        column_id,
+        predicate,
+        is_blob: query.chunk_type_name == BlobType::NAME,
+        chunk_size: query.chunk_size as i64,
+    };
+
+    // The stream outlives the request, so it uses its own transaction holder
+    let session_settings = session_settings(&core_subsystem, request_context).await?;
+
+    let executor = subsystem_resolver.executor.clone();
+    let query_name = query.name.clone();
+
+    let chunks = try_stream! {
+        let database = &core_subsystem.database;
+        let mut tx_holder = TransactionHolder::new();
+        if let Some(session_settings) = session_settings {
+            tx_holder.set_session_settings(session_settings);
+        }
+        // Keep the row locked across the chunk selects
+        tx_holder.ensure_transaction();
+
+        let length = chunk_source
+            .fetch_length(&executor, &mut tx_holder, database)
+            .await
+            .map_err(|error| user_error(&query_name, error))?;
+
+        let mut start = 1;
+        while start <= length {
+            let chunk = chunk_source
+                .fetch_chunk(start, &executor, &mut tx_holder, database)
+                .await
+                .map_err(|error| user_error(&query_name, error))?;
+            yield chunk;
+
+            start += chunk_source.chunk_size;
+        }
+
+        tx_holder
+            .finalize(true)
+            .await
+            .map_err(|error| user_error(&query_name, DatabaseError::from(error).into()))?;
+    };
+
+    // Chunk queries cannot set response headers (the headers are sent before the last chunk is
+    // read)
+    Ok(QueryResponse {
+        body: QueryResponseBody::Stream(QueryResponseStream::new(Box::pin(chunks))),
+        headers: vec![],
+    })
+}
+
+/// The value to stream (the column of the row matching the predicate)
+struct ChunkSource {
+    table_id: TableId,
+    column_id: ColumnId,
+    predicate: AbstractPredicate,
+    is_blob: bool,
+    chunk_size: i64,
+}
+
+impl ChunkSource {
+    /// The length of the value in characters (for `String`) or bytes (for `Blob`), which is zero
+    /// if no row matches (or the value is null). Locks the row until the end of the transaction.
+    async fn fetch_length(
+        &self,
+        executor: &DatabaseExecutor,
+        tx_holder: &mut TransactionHolder,
+        database: &Database,
+    ) -> Result<i64, PostgresExecutionError> {
+        let function_name = if self.is_blob {
+            "octet_length"
+        } else {
+            "char_length"
+        };
+
+        let length = self
+            .fetch(
+                SelectionElement::Function(Function::Named {
+                    function_name: function_name.to_string(),
+                    column_id: self.column_id,
+                }),
+                Some(RowLock::ForShare),
+                executor,
+                tx_holder,
+                database,
+            )
+            .await?;
+
+        Ok(length.as_i64().unwrap_or(0))
+    }
+
+    /// The chunk starting at the given (1-based) position
+    async fn fetch_chunk(
+        &self,
+        start: i64,
+        executor: &DatabaseExecutor,
+        tx_holder: &mut TransactionHolder,
+        database: &Database,
+    ) -> Result<JsonValue, PostgresExecutionError> {
+        let chunk = self
+            .fetch(
+                SelectionElement::Function(Function::StringTransform {
+                    column_id: self.column_id,
+                    transforms: vec![StringFunction::Substring {
+                        start,
+                        length: Some(self.chunk_size),
+                    }],
+                }),
+                None,
+                executor,
+                tx_holder,
+                database,
+            )
+            .await?;
+
+        match chunk {
+            // A bytea value computed by a function is serialized as hex (unlike a bytea column,
+            // which is selected as base64), so convert it to match the other `Blob` values
+            JsonValue::String(hex) if self.is_blob => Ok(JsonValue::String(hex_to_base64(&hex)?)),
+            JsonValue::String(_) => Ok(chunk),
+            _ => Err(PostgresExecutionError::Generic(
+                "The chunked value changed while streaming it".to_string(),
+            )),
+        }
+    }
+
+    async fn fetch(
+        &self,
+        element: SelectionElement,
+        lock: Option<RowLock>,
+        executor: &DatabaseExecutor,
+        tx_holder: &mut TransactionHolder,
+        database: &Database,
+    ) -> Result<JsonValue, PostgresExecutionError> {
+        let select = AbstractSelect {
+            table_id: self.table_id,
+            selection: Selection::Json(
+                vec![AliasedSelectionElement::new("value".to_string(), element)],
+                SelectionCardinality::One,
+            ),
+            predicate: self.predicate.clone(),
+            order_by: None,
+            offset: None,
+            limit: None,
+            lock,
+        };
+
+        let mut result = executor
+            .execute(AbstractOperation::Select(select), tx_holder, database)
+            .await?;
+
+        if result.len() != 1 {
+            return Err(PostgresExecutionError::NonUniqueResult(result.len()));
+        }
+
+        let row: String = extractor(result.swap_remove(0))?;
+
+        match serde_json::from_str(&row)
+            .map_err(|e| PostgresExecutionError::Generic(e.to_string()))?
+        {
+            JsonValue::Object(mut row) => Ok(row.remove("value").unwrap_or(JsonValue::Null)),
+            _ => Ok(JsonValue::Null),
+        }
+    }
+}
+
+/// Errors are reported to the client after the chunks streamed so far, so map them to their
+/// user-facing messages here
+fn user_error(query_name: &str, error: PostgresExecutionError) -> String {
+    warn!("[ChunkQuery] streaming '{}' failed: {}", query_name, error);
+    error.user_error_message()
+}
+
+/// Convert the Postgres hex form of a bytea value (such as `\x48656c6c6f`) to base64
+fn hex_to_base64(hex: &str) -> Result<String, PostgresExecutionError> {
+    let invalid = || PostgresExecutionError::Generic(format!("Invalid bytea value '{hex}'"));
+
+    let digits = hex.strip_prefix("\\x").ok_or_else(invalid)?;
+    if !digits.is_ascii() || digits.len() % 2 != 0 {
+        return Err(invalid());
+    }
+
+    let bytes = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_hex_chunks_to_base64() {
+        assert_eq!(hex_to_base64("\\x48656c6c6f").unwrap(), "SGVsbG8=");
+        assert_eq!(hex_to_base64("\\x00FF").unwrap(), "AP8=");
+        assert_eq!(hex_to_base64("\\x").unwrap(), "");
+    }
+
+    #[test]
+    fn rejects_invalid_hex_chunks() {
+        assert!(hex_to_base64("48656c").is_err());
+        assert!(hex_to_base64("\\x486").is_err());
+        assert!(hex_to_base64("\\xzz").is_err());
+        // Multi-byte characters must not panic when slicing
+        assert!(hex_to_base64("\\x€a").is_err());
+    }
+}
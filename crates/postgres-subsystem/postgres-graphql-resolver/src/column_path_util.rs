@@ -43,6 +43,19 @@ pub fn to_column_path(
     }
 }
 
+/// Like [`to_column_path`], but also follows the links after `next_column_path_link` for a
+/// relation-path parameter
+pub fn to_transitive_column_path(
+    parent_column_path: &Option<PhysicalColumnPath>,
+    next_column_path_link: &Option<ColumnPathLink>,
+    transitive_links: &[ColumnPathLink],
+) -> Option<PhysicalColumnPath> {
+    transitive_links.iter().fold(
+        to_column_path(parent_column_path, next_column_path_link),
+        |column_path, link| column_path.map(|column_path| column_path.push(link.clone())),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,8 @@
+use std::time::Instant;
+
 use async_recursion::async_recursion;
 use common::context::RequestContext;
+use core_model::mapped_arena::SerializableSlabIndex;
 use core_model::types::{BaseOperationReturnType, FieldType, OperationReturnType};
 use core_resolver::{
     QueryResponse, QueryResponseBody, access_solver::AccessSolver,
@@ -8,20 +11,21 @@ use core_resolver::{
 use deno_graphql_resolver::{
     DenoSubsystemResolver, ExoCallbackProcessor, InterceptedOperationInfo,
 };
-use exo_deno::Arg;
+use exo_deno::{Arg, error::DenoError};
 use exo_sql::AbstractPredicate;
 use serde_json::{Map as JsonMap, Value, map::Entry as JsonEntry};
 
 use crate::resolver::PostgresSubsystemResolver;
 use postgres_core_model::relation::PostgresRelation;
 use postgres_core_model::types::{
-    ComputedField, EntityType, PostgresField, PostgresFieldType, TypeIndex,
+    ComputedField, ComputedScript, EntityType, PostgresField, PostgresFieldType, TypeIndex,
 };
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_graphql_model::similarity::SIMILARITY_ITEM_FIELD;
 
 use crate::computed_fields::serde_helpers::args_to_json;
 
-mod serde_helpers {
+pub(crate) mod serde_helpers {
     use common::value::Val;
     use indexmap::IndexMap;
     use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
@@ -86,6 +90,65 @@ pub async fn apply_computed_fields_to_body(
     Ok(())
 }
 
+/// Similar to [`apply_computed_fields_to_body`], but for the result of a similarity query, where
+/// each element wraps the entity in its `item` field.
+pub async fn apply_computed_fields_to_similarity_body(
+    body: &mut QueryResponseBody,
+    entity_type: &EntityType,
+    field: &ValidatedField,
+    subsystem_resolver: &PostgresSubsystemResolver,
+    system_resolver: &GraphQLSystemResolver,
+    request_context: &RequestContext<'_>,
+) -> Result<(), PostgresExecutionError> {
+    let item_fields: Vec<_> = field
+        .subfields
+        .iter()
+        .filter(|subfield| {
+            subfield.name == SIMILARITY_ITEM_FIELD
+                && needs_postprocess(entity_type, &subfield.subfields, subsystem_resolver)
+        })
+        .collect();
+
+    if item_fields.is_empty() {
+        return Ok(());
+    }
+
+    let json_str = match body {
+        QueryResponseBody::Raw(Some(s)) => s,
+        _ => return Ok(()),
+    };
+
+    let mut value: Value = serde_json::from_str(json_str).map_err(|e| {
+        PostgresExecutionError::Generic(format!("Failed to parse query result JSON: {e}"))
+    })?;
+
+    if let Value::Array(elements) = &mut value {
+        for element in elements.iter_mut() {
+            for item_field in item_fields.iter() {
+                if let Some(item) = element.get_mut(item_field.output_name()) {
+                    process_entity(
+                        item,
+                        entity_type,
+                        &item_field.subfields,
+                        subsystem_resolver,
+                        system_resolver,
+                        request_context,
+                    )
+                    .await?;
+                }
+            }
+        }
+    }
+
+    let updated = serde_json::to_string(&value).map_err(|e| {
+        PostgresExecutionError::Generic(format!("Failed to serialize query result JSON: {e}"))
+    })?;
+
+    *body = QueryResponseBody::Raw(Some(updated));
+
+    Ok(())
+}
+
 fn selection_to_json(selection: &[ValidatedField]) -> Result<Value, PostgresExecutionError> {
     let mut fields = Vec::with_capacity(selection.len());
     for field in selection {
@@ -198,19 +261,36 @@ async fn process_entity(
         }
     };
 
-    let mut projected_keys: Vec<String> = Vec::with_capacity(selection.len() + 1);
+    // The value of a JSON-like entity holds all its fields under their names (unlike an entity
+    // selected through SQL, whose value holds only the selected fields under their output names).
+    // So build the value from the selection, which takes care of aliases (including the same
+    // field selected under multiple aliases) and fields missing from the stored value.
+    let source = if entity_type.representation.is_json_like() {
+        Some(std::mem::take(obj))
+    } else {
+        None
+    };
 
     for selection_field in selection {
         let field_name = &selection_field.name;
+        let output_name = selection_field.output_name();
+
+        if let Some(source) = &source {
+            let value = if field_name == "__typename" {
+                Value::String(entity_type.name.clone())
+            } else {
+                source
+                    .get(field_name.as_str())
+                    .cloned()
+                    .unwrap_or(Value::Null)
+            };
+            obj.insert(output_name.clone(), value);
+        }
 
         if field_name == "__typename" {
-            projected_keys.push(field_name.to_string());
             continue;
         }
 
-        let output_name = selection_field.output_name();
-        projected_keys.push(output_name.clone());
-
         let entity_field = match entity_type.field_by_name(field_name) {
             Some(field) => field,
             None => continue,
@@ -223,16 +303,11 @@ async fn process_entity(
         if let PostgresRelation::Computed(computed) = &entity_field.relation {
             let dependency_placeholder = obj.get(&output_name).cloned();
 
-            let mut parent_snapshot = Value::Object(obj.clone());
-            if let Value::Object(ref mut parent_map) = parent_snapshot
-                && let Some(Value::Object(dependency_values)) = dependency_placeholder.as_ref()
-            {
-                for (dep_name, dep_value) in dependency_values {
-                    parent_map
-                        .entry(dep_name.clone())
-                        .or_insert(dep_value.clone());
-                }
+            let mut parent_map = source.clone().unwrap_or_else(|| obj.clone());
+            if let Some(Value::Object(dependency_values)) = dependency_placeholder.as_ref() {
+                merge_dependency_values(&mut parent_map, dependency_values);
             }
+            let parent_snapshot = Value::Object(parent_map);
 
             let computed_value = execute_computed_field(
                 computed,
@@ -281,13 +356,26 @@ async fn process_entity(
         }
     }
 
-    if entity_type.representation.is_json_like() {
-        obj.retain(|key, _| projected_keys.iter().any(|allowed| allowed == key));
-    }
-
     Ok(())
 }
 
+/// Add the values of a computed field's dependencies to the parent snapshot. The values of related
+/// entities (such as `author` for an `author.name` dependency) are merged with the selected ones,
+/// so that the snapshot has both the selected fields and the dependencies.
+fn merge_dependency_values(target: &mut JsonMap<String, Value>, values: &JsonMap<String, Value>) {
+    for (name, value) in values {
+        match (target.get_mut(name), value) {
+            (Some(Value::Object(existing)), Value::Object(nested)) => {
+                merge_dependency_values(existing, nested)
+            }
+            (Some(_), _) => {}
+            (None, _) => {
+                target.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
 async fn execute_computed_field(
     computed: &ComputedField,
     parent_snapshot: &Value,
@@ -296,7 +384,48 @@ async fn execute_computed_field(
     system_resolver: &GraphQLSystemResolver,
     request_context: &RequestContext<'_>,
 ) -> Result<Value, PostgresExecutionError> {
-    let subsystem_id = computed.subsystem.as_str();
+    let args_value = args_to_json(&selection_field.arguments)?;
+    let selection_value = selection_to_json(&selection_field.subfields)?;
+
+    // Pass four arguments: parent, args, selection, exograph
+    // The 4th argument (Exograph) is required for computed resolvers
+    let arg_sequence = vec![
+        Arg::Serde(parent_snapshot.clone()),
+        Arg::Serde(args_value),
+        Arg::Serde(selection_value),
+        Arg::Shim("Exograph".to_string()), // Injected Exograph client
+    ];
+
+    call_script_function(
+        &computed.subsystem,
+        computed.script_id,
+        &computed.function_name,
+        arg_sequence,
+        subsystem_resolver,
+        system_resolver,
+        request_context,
+    )
+    .await?
+    .map_err(|e| {
+        PostgresExecutionError::Generic(format!(
+            "Failed to evaluate computed field '{}': {e}",
+            selection_field.name
+        ))
+    })
+}
+
+/// Call a function of a bundled script (of a computed field or a validator) in the given
+/// subsystem. Returns the outcome of the call (which callers report in their own terms), or an
+/// error if the function couldn't be called at all.
+pub(crate) async fn call_script_function(
+    subsystem_id: &str,
+    script_id: SerializableSlabIndex<ComputedScript>,
+    function_name: &str,
+    arguments: Vec<Arg>,
+    subsystem_resolver: &PostgresSubsystemResolver,
+    system_resolver: &GraphQLSystemResolver,
+    request_context: &RequestContext<'_>,
+) -> Result<Result<Value, DenoError>, PostgresExecutionError> {
     if subsystem_id != "deno" {
         return Err(PostgresExecutionError::Generic(format!(
             "Unsupported computed field subsystem '{}'",
@@ -324,7 +453,7 @@ async fn execute_computed_field(
             ))
         })?;
 
-    let script = &subsystem_resolver.subsystem.core_subsystem.computed_scripts[computed.script_id];
+    let script = &subsystem_resolver.subsystem.core_subsystem.computed_scripts[script_id];
 
     let script_defn: exo_deno::deno_executor_pool::DenoScriptDefn =
         serde_json::from_slice(&script.definition).map_err(|e| {
@@ -334,9 +463,6 @@ async fn execute_computed_field(
             ))
         })?;
 
-    let args_value = args_to_json(&selection_field.arguments)?;
-    let selection_value = selection_to_json(&selection_field.subfields)?;
-
     let exograph_execute_query =
         core_resolver::exograph_execute_query!(system_resolver, request_context);
     let callback_processor = ExoCallbackProcessor {
@@ -344,33 +470,25 @@ async fn execute_computed_field(
         exograph_proceed: None,
     };
 
-    // Pass four arguments: parent, args, selection, exograph
-    // The 4th argument (Exograph) is required for computed resolvers
-    let arg_sequence = vec![
-        Arg::Serde(parent_snapshot.clone()),
-        Arg::Serde(args_value),
-        Arg::Serde(selection_value),
-        Arg::Shim("Exograph".to_string()), // Injected Exograph client
-    ];
-
-    deno_resolver
+    let start = Instant::now();
+    let result = deno_resolver
         .executor
         .execute_and_get_r(
             &script.path,
             script_defn,
-            &computed.function_name,
-            arg_sequence,
+            function_name,
+            arguments,
             Option::<InterceptedOperationInfo>::None,
             callback_processor,
+            deno_resolver.execution_timeout(&script.path, Some(function_name)),
         )
-        .await
-        .map(|(value, _)| value)
-        .map_err(|e| {
-            PostgresExecutionError::Generic(format!(
-                "Failed to evaluate computed field '{}': {e}",
-                selection_field.name
-            ))
-        })
+        .await;
+    request_context
+        .system_context
+        .stats
+        .record_deno_call(start.elapsed());
+
+    Ok(result.map(|(value, _)| value))
 }
 
 async fn is_field_authorized(
@@ -409,7 +527,9 @@ fn needs_postprocess(
         if let Some(entity_field) = entity_type.field_by_name(&selection_field.name) {
             match &entity_field.relation {
                 PostgresRelation::Computed(_) => return true,
-                PostgresRelation::Embedded => {
+                // A field of a JSON-like type (stored in a column or embedded in another JSON-like
+                // value), whose value must be projected to the selection
+                PostgresRelation::Scalar { .. } | PostgresRelation::Embedded => {
                     if !selection_field.subfields.is_empty() {
                         return true;
                     }
@@ -455,3 +575,41 @@ fn field_operation_return_type(
             .map(|inner_type| OperationReturnType::List(Box::new(inner_type))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn merges_nested_dependencies() {
+        let Value::Object(mut parent) = json!({
+            "id": 1,
+            "author": { "id": 2 },
+            "title": "Selected"
+        }) else {
+            unreachable!()
+        };
+        let Value::Object(dependencies) = json!({
+            "id": 1,
+            "author": { "name": "Alice" },
+            "title": "Dependency",
+            "published": true
+        }) else {
+            unreachable!()
+        };
+
+        merge_dependency_values(&mut parent, &dependencies);
+
+        assert_eq!(
+            Value::Object(parent),
+            json!({
+                "id": 1,
+                "author": { "id": 2, "name": "Alice" },
+                "title": "Selected",
+                "published": true
+            })
+        );
+    }
+}
@@ -35,6 +35,7 @@ use crate::{
 
 use postgres_core_resolver::{
     cast,
+    opaque_id::decode_opaque_ids,
     postgres_execution_error::{PostgresExecutionError, WithContext},
 };
 
@@ -186,7 +187,7 @@ async fn map_single<'a>(
                     PostgresRelation::Embedded => {
                         panic!("Embedded relations cannot be used in create operations")
                     }
-                    PostgresRelation::Computed(_) => {
+                    PostgresRelation::Computed(_) | PostgresRelation::SqlExpression(_) => {
                         vec![]
                     }
                     PostgresRelation::Transitive(_) => {
@@ -207,6 +208,9 @@ async fn map_single<'a>(
     let readonly_defaults =
         map_readonly_defaults(data_type, argument, subsystem, request_context).await?;
     row.extend(readonly_defaults);
+    let clock_defaults =
+        map_clock_defaults(data_type, argument, subsystem, request_context).await?;
+    row.extend(clock_defaults);
 
     Ok((InsertionRow { elems: row }, vec![precheck_predicate]))
 }
@@ -329,6 +333,7 @@ async fn map_readonly_defaults<'a>(
             PostgresRelation::OneToMany(_)
             | PostgresRelation::Embedded
             | PostgresRelation::Computed(_)
+            | PostgresRelation::SqlExpression(_)
             | PostgresRelation::Transitive(_) => {}
         }
     }
@@ -336,6 +341,47 @@ async fn map_readonly_defaults<'a>(
     Ok(defaults)
 }
 
+/// Supply the values of `now()` defaults from the request's clock if it is frozen (otherwise, the
+/// column default takes care of it)
+async fn map_clock_defaults<'a>(
+    data_type: &'a MutationType,
+    argument: &'a Val,
+    subsystem: &'a PostgresGraphQLSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<Vec<InsertionElement>, PostgresExecutionError> {
+    let clock = &request_context.system_context.clock;
+
+    if !clock.is_frozen() {
+        return Ok(vec![]);
+    }
+
+    let now = Val::String(clock.now().to_rfc3339());
+    let entity_type = &subsystem.core_subsystem.entity_types[data_type.entity_id];
+
+    let mut defaults = Vec::new();
+
+    for field in entity_type.fields.iter() {
+        let now_default = matches!(
+            &field.default_value,
+            Some(PostgresFieldDefaultValue::Function(function)) if function == "now()"
+        );
+
+        if !now_default {
+            continue;
+        }
+
+        if super::util::get_argument_field(argument, &field.name).is_some() {
+            continue;
+        }
+
+        if let PostgresRelation::Scalar { column_id, .. } = &field.relation {
+            defaults.push(map_self_column(*column_id, field, &now, subsystem).await?);
+        }
+    }
+
+    Ok(defaults)
+}
+
 async fn map_self_column<'a, CT>(
     key_column_id: ColumnId,
     field: &'a PostgresField<CT>,
@@ -393,7 +439,8 @@ async fn map_self_column<'a, CT>(
         _ => argument,
     };
 
-    let value_column = cast::literal_column(argument_value, key_column).with_context(format!(
+    let argument_value = decode_opaque_ids(argument_value, key_column, &subsystem.core_subsystem)?;
+    let value_column = cast::literal_column(&argument_value, key_column).with_context(format!(
         "trying to convert the '{}' field to the '{}' type",
         field.name,
         key_column.typ.type_string()
@@ -0,0 +1,207 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_stream::try_stream;
+use bytes::Bytes;
+use futures::Stream;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use common::context::RequestContext;
+use common::value::Val;
+use exo_sql::{
+    AbstractOperation, AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, AbstractSelect,
+    AliasedSelectionElement, ColumnId, Database, DatabaseExecutor, Limit, Offset, Ordering,
+    PhysicalColumnPath, Selection, SelectionCardinality, SelectionElement, TransactionHolder,
+};
+use postgres_core_model::types::EntityType;
+use postgres_core_resolver::{
+    database_helper::extractor, postgres_execution_error::PostgresExecutionError,
+    session_settings::session_settings,
+};
+
+use crate::{
+    auth_util::check_retrieve_access, predicate_mapper::compute_predicate,
+    resolver::PostgresSubsystemResolver,
+};
+
+use super::{DataTransferError, field_columns, format::DataFormat};
+
+/// Number of rows to fetch from the database at a time
+const EXPORT_PAGE_SIZE: i64 = 1000;
+
+/// Stream rows of an entity that the caller may read.
+///
+/// Access control (and the `where` argument) is resolved upfront using the caller's context, so
+/// that the stream can fetch pages of rows (ordered by the primary key) on its own.
+pub(super) async fn export_stream<'a>(
+    resolver: &PostgresSubsystemResolver,
+    entity_type: &EntityType,
+    format: DataFormat,
+    where_arg: Option<Val>,
+    request_context: &'a RequestContext<'a>,
+) -> Result<impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static, DataTransferError> {
+    let subsystem = &resolver.subsystem;
+    let core_subsystem = subsystem.core_subsystem.clone();
+    let database = &core_subsystem.database;
+
+    let entity_read_predicate =
+        check_retrieve_access(entity_type.access.read, subsystem, request_context).await?;
+    if entity_read_predicate == AbstractPredicate::False {
+        return Err(PostgresExecutionError::Authorization.into());
+    }
+    let restrict_relations = entity_read_predicate != AbstractPredicate::True;
+
+    // Export the columns of fields that the caller may read (and, as in queries, restrict the
+    // rows to those for which the field access holds)
+    let mut predicate = entity_read_predicate;
+    let mut column_ids: Vec<ColumnId> = vec![];
+    for field in entity_type.fields.iter() {
+        let field_column_ids = field_columns(&field.relation, database);
+        if field_column_ids.is_empty() {
+            continue;
+        }
+
+        let field_read_predicate =
+            check_retrieve_access(field.access.read, subsystem, request_context).await?;
+        if field_read_predicate == AbstractPredicate::False {
+            continue;
+        }
+
+        predicate = AbstractPredicate::and(predicate, field_read_predicate);
+        for column_id in field_column_ids {
+            if !column_ids.contains(&column_id) {
+                column_ids.push(column_id);
+            }
+        }
+    }
+
+    if let Some(where_arg) = where_arg {
+        let collection_query = subsystem
+            .collection_queries
+            .iter()
+            .map(|(_, query)| query)
+            .find(|query| query.return_type.type_name() == entity_type.name)
+            .ok_or_else(|| {
+                DataTransferError::bad_request(format!(
+                    "Entity '{}' doesn't support filtering",
+                    entity_type.name
+                ))
+            })?;
+        let predicate_param = &collection_query.parameters.predicate_param;
+
+        let arguments = IndexMap::from([(predicate_param.name.clone(), where_arg)]);
+        let where_predicate = compute_predicate(
+            &[predicate_param],
+            &arguments,
+            subsystem,
+            request_context,
+            restrict_relations,
+        )
+        .await?;
+
+        predicate = AbstractPredicate::and(predicate, where_predicate);
+    }
+
+    let table_id = entity_type.table_id;
+    let pk_column_ids = database.get_pk_column_ids(table_id);
+    let column_names: Vec<String> = column_ids
+        .iter()
+        .map(|column_id| column_id.get_column(database).name.clone())
+        .collect();
+
+    // The stream outlives the request, so it uses its own transaction holder
+    let session_settings = session_settings(&core_subsystem, request_context).await?;
+
+    let executor = resolver.executor.clone();
+
+    Ok(try_stream! {
+        let database = &core_subsystem.database;
+        let mut tx_holder = TransactionHolder::new();
+        if let Some(session_settings) = session_settings {
+            tx_holder.set_session_settings(session_settings);
+        }
+
+        if let Some(header) = format.encode_header(&column_names).map_err(std::io::Error::other)? {
+            yield Bytes::from(header);
+        }
+
+        let mut offset = 0;
+        loop {
+            let select = AbstractSelect {
+                table_id,
+                selection: Selection::Json(
+                    column_ids
+                        .iter()
+                        .zip(column_names.iter())
+                        .map(|(column_id, name)| {
+                            AliasedSelectionElement::new(
+                                name.clone(),
+                                SelectionElement::Physical(*column_id),
+                            )
+                        })
+                        .collect(),
+                    SelectionCardinality::Many,
+                ),
+                predicate: predicate.clone(),
+                order_by: Some(AbstractOrderBy(
+                    pk_column_ids
+                        .iter()
+                        .map(|column_id| {
+                            (
+                                AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(*column_id)),
+                                Ordering::Asc,
+                            )
+                        })
+                        .collect(),
+                )),
+                offset: Some(Offset(offset)),
+                limit: Some(Limit(EXPORT_PAGE_SIZE)),
+                lock: None,
+            };
+
+            let rows = fetch_rows(&executor, select, &mut tx_holder, database)
+                .await
+                .map_err(|e| std::io::Error::other(e.user_error_message()))?;
+
+            if rows.is_empty() {
+                break;
+            }
+
+            yield Bytes::from(format.encode_rows(&column_names, &rows).map_err(std::io::Error::other)?);
+
+            if (rows.len() as i64) < EXPORT_PAGE_SIZE {
+                break;
+            }
+            offset += EXPORT_PAGE_SIZE;
+        }
+    })
+}
+
+async fn fetch_rows(
+    executor: &DatabaseExecutor,
+    select: AbstractSelect,
+    tx_holder: &mut TransactionHolder,
+    database: &Database,
+) -> Result<Vec<Value>, PostgresExecutionError> {
+    let mut result = executor
+        .execute(AbstractOperation::Select(select), tx_holder, database)
+        .await?;
+
+    if result.len() != 1 {
+        return Err(PostgresExecutionError::NonUniqueResult(result.len()));
+    }
+
+    let rows: String = extractor(result.swap_remove(0))?;
+
+    match serde_json::from_str(&rows).map_err(|e| PostgresExecutionError::Generic(e.to_string()))? {
+        Value::Array(rows) => Ok(rows),
+        _ => Ok(vec![]),
+    }
+}
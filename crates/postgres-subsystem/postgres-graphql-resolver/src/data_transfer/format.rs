@@ -0,0 +1,286 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Encoding (for export) and decoding (for import) of rows in the supported formats.
+
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum DataFormat {
+    /// Comma-separated values with a header row of column names. An empty cell stands for `null`.
+    Csv,
+    /// Newline-delimited JSON, with one object (keyed by column names) per line.
+    Ndjson,
+}
+
+/// A value of a decoded row
+#[derive(Debug, PartialEq)]
+pub(super) enum RawValue {
+    /// A CSV cell, whose interpretation depends on the type of the column
+    Text(String),
+    Json(Value),
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct DecodedRow {
+    /// The (1-based) line where the row starts
+    pub line: u64,
+    pub values: Vec<(String, RawValue)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct LineError {
+    pub line: u64,
+    pub message: String,
+}
+
+impl DataFormat {
+    /// Determine the format from the `format` query parameter (defaults to CSV)
+    pub fn from_query(query: &Value) -> Result<Self, String> {
+        match query.get("format") {
+            None | Some(Value::Null) => Ok(Self::Csv),
+            Some(Value::String(format)) if format == "csv" => Ok(Self::Csv),
+            Some(Value::String(format)) if format == "ndjson" => Ok(Self::Ndjson),
+            Some(format) => Err(format!(
+                "Unsupported format {format} (expected 'csv' or 'ndjson')"
+            )),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// Encode the header to precede all rows (if the format has one)
+    pub fn encode_header(&self, column_names: &[String]) -> Result<Option<Vec<u8>>, String> {
+        match self {
+            Self::Csv => {
+                let mut writer = csv::Writer::from_writer(vec![]);
+                writer
+                    .write_record(column_names)
+                    .map_err(|e| e.to_string())?;
+                writer.into_inner().map(Some).map_err(|e| e.to_string())
+            }
+            Self::Ndjson => Ok(None),
+        }
+    }
+
+    /// Encode rows (JSON objects keyed by the column names)
+    pub fn encode_rows(&self, column_names: &[String], rows: &[Value]) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Csv => {
+                let mut writer = csv::Writer::from_writer(vec![]);
+                for row in rows {
+                    let record = column_names.iter().map(|name| match row.get(name) {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(value)) => value.clone(),
+                        Some(value) => value.to_string(),
+                    });
+                    writer.write_record(record).map_err(|e| e.to_string())?;
+                }
+                writer.into_inner().map_err(|e| e.to_string())
+            }
+            Self::Ndjson => {
+                let mut bytes = vec![];
+                for row in rows {
+                    let object: Map<String, Value> = column_names
+                        .iter()
+                        .map(|name| (name.clone(), row.get(name).cloned().unwrap_or(Value::Null)))
+                        .collect();
+                    bytes.extend(Value::Object(object).to_string().into_bytes());
+                    bytes.push(b'\n');
+                }
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decode rows, reporting rows that couldn't be decoded along with their line
+    pub fn decode(&self, data: &str) -> Vec<Result<DecodedRow, LineError>> {
+        match self {
+            Self::Csv => decode_csv(data),
+            Self::Ndjson => decode_ndjson(data),
+        }
+    }
+}
+
+fn decode_csv(data: &str) -> Vec<Result<DecodedRow, LineError>> {
+    let mut reader = csv::Reader::from_reader(data.as_bytes());
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            return vec![Err(LineError {
+                line: 1,
+                message: format!("Invalid header: {e}"),
+            })];
+        }
+    };
+
+    reader
+        .records()
+        .map(|record| match record {
+            Ok(record) => Ok(DecodedRow {
+                line: record.position().map(|p| p.line()).unwrap_or_default(),
+                values: headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(name, cell)| {
+                        let value = if cell.is_empty() {
+                            RawValue::Json(Value::Null)
+                        } else {
+                            RawValue::Text(cell.to_string())
+                        };
+                        (name.to_string(), value)
+                    })
+                    .collect(),
+            }),
+            Err(e) => Err(LineError {
+                line: e.position().map(|p| p.line()).unwrap_or_default(),
+                message: format!("Invalid row: {e}"),
+            }),
+        })
+        .collect()
+}
+
+fn decode_ndjson(data: &str) -> Vec<Result<DecodedRow, LineError>> {
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let line_number = index as u64 + 1;
+
+            match serde_json::from_str::<Value>(line) {
+                Ok(Value::Object(object)) => Ok(DecodedRow {
+                    line: line_number,
+                    values: object
+                        .into_iter()
+                        .map(|(name, value)| (name, RawValue::Json(value)))
+                        .collect(),
+                }),
+                Ok(_) => Err(LineError {
+                    line: line_number,
+                    message: "Expected a JSON object".to_string(),
+                }),
+                Err(e) => Err(LineError {
+                    line: line_number,
+                    message: format!("Invalid JSON: {e}"),
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn column_names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn encodes_csv() {
+        let columns = column_names(&["id", "title", "tags"]);
+        let rows = vec![
+            json!({"id": 1, "title": "Hello, world", "tags": ["a", "b"]}),
+            json!({"id": 2, "title": null, "tags": null}),
+        ];
+
+        let mut bytes = DataFormat::Csv.encode_header(&columns).unwrap().unwrap();
+        bytes.extend(DataFormat::Csv.encode_rows(&columns, &rows).unwrap());
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "id,title,tags\n1,\"Hello, world\",\"[\"\"a\"\",\"\"b\"\"]\"\n2,,\n"
+        );
+    }
+
+    #[test]
+    fn encodes_ndjson() {
+        let columns = column_names(&["id", "title"]);
+        let rows = vec![json!({"id": 1, "title": "Hello"}), json!({"id": 2})];
+
+        assert_eq!(DataFormat::Ndjson.encode_header(&columns).unwrap(), None);
+        assert_eq!(
+            String::from_utf8(DataFormat::Ndjson.encode_rows(&columns, &rows).unwrap()).unwrap(),
+            "{\"id\":1,\"title\":\"Hello\"}\n{\"id\":2,\"title\":null}\n"
+        );
+    }
+
+    #[test]
+    fn decodes_csv_with_line_errors() {
+        let rows = DataFormat::Csv.decode("id,title\n1,Hello\n2\n3,\n");
+
+        assert_eq!(
+            rows[0],
+            Ok(DecodedRow {
+                line: 2,
+                values: vec![
+                    ("id".to_string(), RawValue::Text("1".to_string())),
+                    ("title".to_string(), RawValue::Text("Hello".to_string())),
+                ],
+            })
+        );
+        assert!(matches!(&rows[1], Err(LineError { line: 3, .. })));
+        assert_eq!(
+            rows[2],
+            Ok(DecodedRow {
+                line: 4,
+                values: vec![
+                    ("id".to_string(), RawValue::Text("3".to_string())),
+                    ("title".to_string(), RawValue::Json(Value::Null)),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_ndjson_with_line_errors() {
+        let rows = DataFormat::Ndjson.decode("{\"id\": 1}\n\n[1]\n{\"id\":\n");
+
+        assert_eq!(
+            rows[0],
+            Ok(DecodedRow {
+                line: 1,
+                values: vec![("id".to_string(), RawValue::Json(json!(1)))],
+            })
+        );
+        assert_eq!(
+            rows[1],
+            Err(LineError {
+                line: 3,
+                message: "Expected a JSON object".to_string(),
+            })
+        );
+        assert!(matches!(&rows[2], Err(LineError { line: 4, .. })));
+    }
+
+    #[test]
+    fn parses_format() {
+        assert_eq!(DataFormat::from_query(&json!({})), Ok(DataFormat::Csv));
+        assert_eq!(
+            DataFormat::from_query(&json!({"format": "ndjson"})),
+            Ok(DataFormat::Ndjson)
+        );
+        assert!(DataFormat::from_query(&json!({"format": "xml"})).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use http::StatusCode;
+use serde_json::{Value, json};
+
+use common::context::RequestContext;
+use common::value::Val;
+use core_resolver::access_solver::AccessInput;
+use exo_sql::{
+    AbstractPredicate, ColumnId, JsonColumnType, PhysicalColumn, PhysicalColumnTypeExt,
+    SQLParamContainer,
+};
+use postgres_core_model::{
+    relation::PostgresRelation, subsystem::PostgresCoreSubsystem, types::EntityType,
+};
+use postgres_core_resolver::{
+    cast::cast_value, postgres_execution_error::PostgresExecutionError,
+    session_settings::apply_session_settings,
+};
+
+use crate::{
+    auth_util::check_access, resolver::PostgresSubsystemResolver, sql_mapper::SQLOperationKind,
+};
+
+use super::{
+    AUDIT_TARGET, DataTransferError, admin_actor, field_columns,
+    format::{DataFormat, DecodedRow, LineError, RawValue},
+};
+
+/// Number of rows to send to the database in a single `COPY`
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// A row that passed validation and is ready to be copied into the table
+struct PreparedRow {
+    line: u64,
+    column_ids: Vec<ColumnId>,
+    values: Vec<Option<SQLParamContainer>>,
+    /// The row as seen by the access rules (keyed by field names)
+    access_value: Val,
+}
+
+/// Validate all rows and, only if every row is valid, insert them.
+///
+/// Returns the status code along with a report of the form `{"imported": n, "errors": [{"line":
+/// l, "message": m}]}`.
+pub(super) async fn import_rows<'a>(
+    resolver: &PostgresSubsystemResolver,
+    entity_type: &EntityType,
+    format: DataFormat,
+    data: &str,
+    request_context: &'a RequestContext<'a>,
+) -> Result<(StatusCode, Value), DataTransferError> {
+    let subsystem = &resolver.subsystem;
+    let core_subsystem = &subsystem.core_subsystem;
+
+    let mut rows = vec![];
+    let mut errors = vec![];
+
+    for decoded in format.decode(data) {
+        let row =
+            match decoded.and_then(|decoded| prepare_row(decoded, entity_type, core_subsystem)) {
+                Ok(row) => row,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+
+        let access_input = AccessInput {
+            value: &row.access_value,
+            ignore_missing_value: true,
+            aliases: HashMap::new(),
+        };
+        let access = check_access(
+            entity_type,
+            &[],
+            &SQLOperationKind::Create,
+            subsystem,
+            request_context,
+            Some(&access_input),
+        )
+        .await;
+
+        let message = match access {
+            Ok(outcome) if outcome.precheck_predicate == AbstractPredicate::True => {
+                rows.push(row);
+                continue;
+            }
+            Ok(_) => "Access rule requires a database check, which import doesn't support",
+            Err(PostgresExecutionError::Authorization) => "Not authorized",
+            Err(e) => return Err(e.into()),
+        };
+        errors.push(LineError {
+            line: row.line,
+            message: message.to_string(),
+        });
+    }
+
+    if !errors.is_empty() {
+        return Ok(failure_report(errors));
+    }
+
+    request_context.ensure_transaction().await;
+    apply_session_settings(core_subsystem, request_context).await?;
+
+    let mut imported = 0;
+    for batch in batches(&rows) {
+        let values: Vec<Vec<Option<SQLParamContainer>>> =
+            batch.iter().map(|row| row.values.clone()).collect();
+
+        let mut tx = request_context
+            .system_context
+            .transaction_holder
+            .try_lock()
+            .unwrap();
+
+        let copied = resolver
+            .executor
+            .copy_rows(
+                entity_type.table_id,
+                &batch[0].column_ids,
+                &values,
+                &mut tx,
+                &core_subsystem.database,
+            )
+            .await;
+
+        match copied {
+            Ok(copied) => imported += copied,
+            Err(e) => {
+                tracing::error!("Failed to import rows into {}: {e}", entity_type.name);
+                // The transaction will be rolled back, so none of the rows will be imported
+                return Ok(failure_report(vec![LineError {
+                    line: batch[0].line,
+                    message: format!(
+                        "Failed to import rows on lines {}-{}: {}",
+                        batch[0].line,
+                        batch[batch.len() - 1].line,
+                        PostgresExecutionError::from_database_error(e, core_subsystem)
+                            .user_error_message()
+                    ),
+                }]));
+            }
+        }
+    }
+
+    let actor = admin_actor(request_context).await;
+    tracing::info!(
+        target: AUDIT_TARGET,
+        entity = entity_type.name.as_str(),
+        rows = imported,
+        actor = actor.as_str(),
+        "Admin import"
+    );
+
+    Ok((
+        StatusCode::OK,
+        json!({ "imported": imported, "errors": [] }),
+    ))
+}
+
+fn failure_report(errors: Vec<LineError>) -> (StatusCode, Value) {
+    let errors: Vec<Value> = errors
+        .into_iter()
+        .map(|error| json!({ "line": error.line, "message": error.message }))
+        .collect();
+
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        json!({ "imported": 0, "errors": errors }),
+    )
+}
+
+/// Group consecutive rows with the same set of columns (so each group can use a single `COPY`)
+fn batches(rows: &[PreparedRow]) -> Vec<&[PreparedRow]> {
+    let mut batches = vec![];
+    let mut start = 0;
+
+    for end in 1..=rows.len() {
+        if end == rows.len()
+            || end - start == IMPORT_BATCH_SIZE
+            || rows[end].column_ids != rows[start].column_ids
+        {
+            batches.push(&rows[start..end]);
+            start = end;
+        }
+    }
+
+    batches
+}
+
+fn prepare_row(
+    decoded: DecodedRow,
+    entity_type: &EntityType,
+    core_subsystem: &PostgresCoreSubsystem,
+) -> Result<PreparedRow, LineError> {
+    let line = decoded.line;
+    let line_error = |message: String| LineError { line, message };
+
+    let database = &core_subsystem.database;
+
+    let mut column_ids = vec![];
+    let mut values = vec![];
+    let mut column_vals = HashMap::new();
+
+    for (name, raw_value) in decoded.values {
+        let column_id = database
+            .get_column_id(entity_type.table_id, &name)
+            .ok_or_else(|| line_error(format!("Unknown column '{name}'")))?;
+        if column_ids.contains(&column_id) {
+            return Err(line_error(format!("Duplicate column '{name}'")));
+        }
+
+        let column = column_id.get_column(database);
+        let (val, value) = cast_raw_value(raw_value, column)
+            .ok_or_else(|| line_error(format!("Invalid value for column '{name}'")))?;
+
+        if value.is_none() && !column.is_nullable {
+            return Err(line_error(format!("Column '{name}' may not be null")));
+        }
+
+        column_ids.push(column_id);
+        values.push(value);
+        column_vals.insert(column_id, val);
+    }
+
+    for column_id in database.get_column_ids(entity_type.table_id) {
+        let column = column_id.get_column(database);
+        if !column_ids.contains(&column_id) && !column.is_nullable && column.default_value.is_none()
+        {
+            return Err(line_error(format!(
+                "Missing value for column '{}'",
+                column.name
+            )));
+        }
+    }
+
+    Ok(PreparedRow {
+        line,
+        column_ids,
+        values,
+        access_value: access_value(&column_vals, entity_type, core_subsystem),
+    })
+}
+
+/// Interpret a decoded value for the column.
+///
+/// A CSV cell is taken as a string if the column accepts one (except for JSON columns) and as JSON
+/// (numbers, booleans, arrays, etc.) otherwise.
+fn cast_raw_value(
+    raw_value: RawValue,
+    column: &PhysicalColumn,
+) -> Option<(Val, Option<SQLParamContainer>)> {
+    let cast = |val: Val| {
+        cast_value(&val, column.typ.inner(), false)
+            .ok()
+            .map(|value| (val, value))
+    };
+    let cast_json = |text: &str| {
+        serde_json::from_str::<Value>(text)
+            .ok()
+            .and_then(|value| cast(Val::from(value)))
+    };
+
+    match raw_value {
+        RawValue::Json(value) => cast(Val::from(value)),
+        RawValue::Text(text) if column.typ.is::<JsonColumnType>() => cast_json(&text),
+        RawValue::Text(text) => cast(Val::String(text.clone())).or_else(|| cast_json(&text)),
+    }
+}
+
+/// The row, keyed by field names, for evaluating the entity's creation access rules
+fn access_value(
+    column_vals: &HashMap<ColumnId, Val>,
+    entity_type: &EntityType,
+    core_subsystem: &PostgresCoreSubsystem,
+) -> Val {
+    let database = &core_subsystem.database;
+
+    let fields = entity_type
+        .fields
+        .iter()
+        .filter_map(|field| match &field.relation {
+            PostgresRelation::Scalar { column_id, .. } => column_vals
+                .get(column_id)
+                .map(|val| (field.name.clone(), val.clone())),
+            PostgresRelation::ManyToOne { relation, .. } => {
+                match (
+                    field_columns(&field.relation, database).as_slice(),
+                    relation.foreign_pk_field_ids.as_slice(),
+                ) {
+                    ([column_id], [foreign_pk_field_id]) => column_vals.get(column_id).map(|val| {
+                        let foreign_pk_field =
+                            foreign_pk_field_id.resolve(&core_subsystem.entity_types);
+                        (
+                            field.name.clone(),
+                            Val::Object(HashMap::from([(
+                                foreign_pk_field.name.clone(),
+                                val.clone(),
+                            )])),
+                        )
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        });
+
+    Val::Object(fields.collect())
+}
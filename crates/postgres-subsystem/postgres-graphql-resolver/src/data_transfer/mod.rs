@@ -0,0 +1,245 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Export and import of entity rows (in CSV or NDJSON) for data migration and ops tasks.
+//!
+//! Served as a part of the admin API:
+//! - `GET <prefix>export/<Entity>?format=&where=`: streams rows that the caller may read (and that
+//!   match the optional `where` argument, which takes the same form as in collection queries)
+//! - `POST <prefix>import/<Entity>?format=`: validates all rows (supplied as the `data` field of
+//!   the JSON body) and inserts them using `COPY`. If any row is invalid, nothing is imported
+//!   and the errors are reported along with their line numbers.
+
+mod export;
+mod format;
+mod import;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::{Method, StatusCode};
+use serde_json::{Value, json};
+
+use common::context::RequestContext;
+use common::http::{Headers, RequestPayload, ResponseBody, ResponsePayload};
+use common::value::Val;
+use core_resolver::plugin::{SubsystemResolutionError, SubsystemRestResolver};
+use exo_sql::{ColumnId, Database};
+use postgres_core_model::{relation::PostgresRelation, types::EntityType};
+use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+
+use crate::resolver::PostgresSubsystemResolver;
+
+use format::DataFormat;
+
+const AUDIT_TARGET: &str = "exograph::admin::audit";
+
+pub struct PostgresDataTransferResolver {
+    pub resolver: Arc<PostgresSubsystemResolver>,
+    pub api_path_prefix: String,
+}
+
+enum DataTransferError {
+    /// An error to report to the client (such as an unknown entity or an invalid argument)
+    Client(StatusCode, String),
+    Execution(PostgresExecutionError),
+}
+
+impl DataTransferError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::Client(StatusCode::BAD_REQUEST, message.into())
+    }
+}
+
+impl From<PostgresExecutionError> for DataTransferError {
+    fn from(e: PostgresExecutionError) -> Self {
+        match e {
+            PostgresExecutionError::Authorization => {
+                Self::Client(StatusCode::FORBIDDEN, "Not authorized".to_string())
+            }
+            e => Self::Execution(e),
+        }
+    }
+}
+
+#[async_trait]
+impl SubsystemRestResolver for PostgresDataTransferResolver {
+    fn id(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn resolve<'a>(
+        &self,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<Option<ResponsePayload>, SubsystemResolutionError> {
+        let head = request_context.get_head();
+        let path = head.get_path();
+
+        let Some(sub_path) = path.strip_prefix(&self.api_path_prefix) else {
+            return Ok(None);
+        };
+        let segments: Vec<&str> = sub_path.trim_end_matches('/').split('/').collect();
+
+        let response = match (head.get_method(), segments.as_slice()) {
+            (Method::GET, ["export", entity_name]) => {
+                self.export(entity_name, &head.get_query(), request_context)
+                    .await
+            }
+            (Method::POST, ["import", entity_name]) => {
+                self.import(entity_name, &head.get_query(), request_context)
+                    .await
+            }
+            _ => return Ok(None),
+        };
+
+        match response {
+            Ok(response) => Ok(Some(response)),
+            Err(DataTransferError::Client(status_code, message)) => Ok(Some(json_response(
+                status_code,
+                json!({ "error": message }),
+            ))),
+            Err(DataTransferError::Execution(e)) => Err(e.into()),
+        }
+    }
+}
+
+impl PostgresDataTransferResolver {
+    async fn export<'a>(
+        &self,
+        entity_name: &str,
+        query: &Value,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<ResponsePayload, DataTransferError> {
+        let entity_type = self.entity_type(entity_name)?;
+        let format = DataFormat::from_query(query).map_err(DataTransferError::bad_request)?;
+
+        let where_arg = match query.get("where") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(where_arg)) => {
+                let where_arg: Value = serde_json::from_str(where_arg).map_err(|e| {
+                    DataTransferError::bad_request(format!("Invalid 'where' argument: {e}"))
+                })?;
+                Some(Val::from(where_arg))
+            }
+            Some(_) => return Err(DataTransferError::bad_request("Invalid 'where' argument")),
+        };
+
+        let stream = export::export_stream(
+            &self.resolver,
+            entity_type,
+            format,
+            where_arg,
+            request_context,
+        )
+        .await?;
+
+        Ok(ResponsePayload {
+            body: ResponseBody::Stream(Box::pin(stream)),
+            headers: Headers::from_vec(vec![
+                (
+                    "content-type".to_string(),
+                    format.content_type().to_string(),
+                ),
+                (
+                    "content-disposition".to_string(),
+                    format!(
+                        "attachment; filename=\"{}.{}\"",
+                        entity_type.plural_name,
+                        format.extension()
+                    ),
+                ),
+            ]),
+            status_code: StatusCode::OK,
+        })
+    }
+
+    async fn import<'a>(
+        &self,
+        entity_name: &str,
+        query: &Value,
+        request_context: &'a RequestContext<'a>,
+    ) -> Result<ResponsePayload, DataTransferError> {
+        let entity_type = self.entity_type(entity_name)?;
+        let format = DataFormat::from_query(query).map_err(DataTransferError::bad_request)?;
+
+        let data = match request_context.take_body() {
+            Value::Object(mut body) => match body.remove("data") {
+                Some(Value::String(data)) => data,
+                _ => return Err(missing_data()),
+            },
+            _ => return Err(missing_data()),
+        };
+
+        let (status_code, report) =
+            import::import_rows(&self.resolver, entity_type, format, &data, request_context)
+                .await?;
+
+        Ok(json_response(status_code, report))
+    }
+
+    fn entity_type(&self, name: &str) -> Result<&EntityType, DataTransferError> {
+        self.resolver
+            .subsystem
+            .core_subsystem
+            .entity_types
+            .iter()
+            .map(|(_, entity_type)| entity_type)
+            .find(|entity_type| {
+                entity_type.name == name && !entity_type.representation.is_json_like()
+            })
+            .ok_or_else(|| {
+                DataTransferError::Client(StatusCode::NOT_FOUND, format!("Unknown entity '{name}'"))
+            })
+    }
+}
+
+fn missing_data() -> DataTransferError {
+    DataTransferError::bad_request(
+        "Expected a JSON body with the data to import (as a string) in the 'data' field",
+    )
+}
+
+fn json_response(status_code: StatusCode, body: Value) -> ResponsePayload {
+    ResponsePayload {
+        body: ResponseBody::Bytes(body.to_string().into()),
+        headers: Headers::from_vec(vec![(
+            "content-type".to_string(),
+            "application/json".to_string(),
+        )]),
+        status_code,
+    }
+}
+
+/// The columns backing a field (a scalar column or the columns referring to the related entity)
+fn field_columns(relation: &PostgresRelation, database: &Database) -> Vec<ColumnId> {
+    match relation {
+        PostgresRelation::Scalar { column_id, .. } => vec![*column_id],
+        PostgresRelation::ManyToOne { relation, .. } => relation
+            .relation_id
+            .deref(database)
+            .column_pairs
+            .iter()
+            .map(|pair| pair.self_column_id)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Identify the admin making the request for the audit log (`sub` claim, if available)
+async fn admin_actor(request_context: &RequestContext<'_>) -> String {
+    let sub = request_context
+        .extract_context_field("AdminContext", "jwt", &Some("sub"), "sub", &Ok)
+        .await;
+
+    match sub {
+        Ok(Some(Val::String(sub))) => sub.clone(),
+        Ok(Some(sub)) => sub.to_string(),
+        _ => "<unknown>".to_string(),
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Rate-of-change limits on fields declared with `@guard`: each change to a guarded field is
+//! recorded (in the generated field change type), and an update that would change a field more
+//! often than its limit allows fails.
+
+use chrono::Duration;
+use common::{context::RequestContext, value::Val};
+use core_model::types::{BaseOperationReturnType, OperationReturnType};
+use core_resolver::validation::field::ValidatedField;
+use exo_sql::{
+    AbstractInsert, AbstractOperation, AbstractPredicate, AbstractSelect, AliasedSelectionElement,
+    ColumnPath, ColumnValuePair, InsertionElement, InsertionRow, PhysicalColumnPath, Predicate,
+    RowLock, Selection, SelectionCardinality, SelectionElement,
+};
+use postgres_core_model::types::{EntityType, FieldGuard};
+use postgres_core_resolver::{cast, postgres_execution_error::PostgresExecutionError};
+use postgres_graphql_model::{
+    mutation::{PostgresMutation, PostgresMutationParameters},
+    subsystem::PostgresGraphQLSubsystem,
+};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    abstract_operation_resolver::execute_operation,
+    change_approval::{body_to_json, column_id, column_value},
+    postgres_query::compute_select,
+    predicate_mapper::compute_predicate,
+    resolver::PostgresSubsystemResolver,
+};
+
+/// Check the guards of the fields an update mutation changes and record the changes.
+///
+/// The entities to update are locked until the end of the transaction, so concurrent updates can't
+/// both pass the check. Only fields whose value actually changes count towards the limits, and the
+/// recorded changes roll back along with the update if it fails.
+pub(crate) async fn enforce_field_guards<'e>(
+    mutation: &'e PostgresMutation,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+) -> Result<(), PostgresExecutionError> {
+    let PostgresMutationParameters::Update {
+        data_param,
+        predicate_params,
+    } = &mutation.parameters
+    else {
+        return Ok(());
+    };
+
+    let subsystem = &subsystem_resolver.subsystem;
+    let entity_type_id = mutation.return_type.typ_id();
+    let entity_type = &subsystem.core_subsystem.entity_types[entity_type_id];
+
+    let Some(field_guards) = &entity_type.field_guards else {
+        return Ok(());
+    };
+    let Some(Val::Object(data)) = field.arguments.get(&data_param.name) else {
+        return Ok(());
+    };
+
+    let guards: Vec<_> = field_guards
+        .guards
+        .iter()
+        .filter(|guard| data.contains_key(&guard.field_name))
+        .collect();
+    if guards.is_empty() {
+        return Ok(());
+    }
+
+    request_context.ensure_transaction().await;
+
+    let pk_field_name = match entity_type.pk_fields().as_slice() {
+        [pk_field] => pk_field.name.clone(),
+        _ => {
+            return Err(PostgresExecutionError::Generic(format!(
+                "Guarded type '{}' must have a single primary key",
+                entity_type.name
+            )));
+        }
+    };
+
+    let predicate = compute_predicate(
+        &predicate_params.iter().collect::<Vec<_>>(),
+        &field.arguments,
+        subsystem,
+        request_context,
+        false,
+    )
+    .await?;
+
+    // Select the entities the user may see (with the raw values of the key and the guarded fields)
+    let mut current_select = compute_select(
+        predicate,
+        None,
+        None,
+        None,
+        &OperationReturnType::List(Box::new(OperationReturnType::Plain(
+            BaseOperationReturnType {
+                associated_type_id: entity_type_id,
+                type_name: entity_type.name.clone(),
+            },
+        ))),
+        &[],
+        subsystem,
+        request_context,
+    )
+    .await?;
+    let mut current_columns = vec![];
+    for field_name in
+        std::iter::once(&pk_field_name).chain(guards.iter().map(|guard| &guard.field_name))
+    {
+        current_columns.push(AliasedSelectionElement::new(
+            field_name.clone(),
+            SelectionElement::Physical(column_id(entity_type, field_name)?),
+        ));
+    }
+    current_select.selection = Selection::Json(current_columns, SelectionCardinality::Many);
+    current_select.lock = Some(RowLock::ForUpdate);
+
+    let current = execute_operation(
+        AbstractOperation::Select(current_select),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+    let JsonValue::Array(entities) = body_to_json(&current.body)? else {
+        return Ok(());
+    };
+
+    let change_type = &subsystem.core_subsystem.entity_types[field_guards.change_type_id];
+    let now = request_context.system_context.clock.now();
+    let since = Val::String((now - Duration::days(1)).to_rfc3339());
+    let now = Val::String(now.to_rfc3339());
+
+    let mut changes = vec![];
+
+    for entity in entities.iter() {
+        let entity_id = Val::from(
+            entity
+                .get(&pk_field_name)
+                .cloned()
+                .unwrap_or(JsonValue::Null),
+        );
+
+        for guard in guards.iter() {
+            let new_value: Option<JsonValue> = data
+                .get(&guard.field_name)
+                .and_then(|value| value.clone().try_into().ok());
+            if new_value.as_ref() == entity.get(&guard.field_name) {
+                continue;
+            }
+
+            let recent_changes = count_recent_changes(
+                change_type,
+                &entity_id,
+                guard,
+                &since,
+                subsystem_resolver,
+                request_context,
+            )
+            .await?;
+            if recent_changes >= guard.max_changes_per_day {
+                return Err(PostgresExecutionError::ChangeLimitExceeded {
+                    entity_name: entity_type.name.clone(),
+                    field_name: guard.field_name.clone(),
+                    max_changes_per_day: guard.max_changes_per_day,
+                });
+            }
+
+            changes.push(InsertionRow {
+                elems: [
+                    column_value(change_type, "entityId", &entity_id, subsystem)?,
+                    column_value(
+                        change_type,
+                        "field",
+                        &Val::String(guard.field_name.clone()),
+                        subsystem,
+                    )?,
+                    column_value(change_type, "changedAt", &now, subsystem)?,
+                ]
+                .into_iter()
+                .map(|(column_id, value)| {
+                    InsertionElement::SelfInsert(ColumnValuePair::new(column_id, value))
+                })
+                .collect(),
+            });
+        }
+    }
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let insert = AbstractInsert {
+        table_id: change_type.table_id,
+        rows: changes,
+        selection: change_select(change_type, AbstractPredicate::True)?,
+        precheck_predicates: vec![],
+    };
+    execute_operation(
+        AbstractOperation::Insert(insert),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The number of changes to the guarded field of the entity since the given time
+async fn count_recent_changes<'e>(
+    change_type: &'e EntityType,
+    entity_id: &Val,
+    guard: &FieldGuard,
+    since: &Val,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+) -> Result<u64, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let predicate = Predicate::and(
+        Predicate::and(
+            column_eq(change_type, "entityId", entity_id, subsystem)?,
+            column_eq(
+                change_type,
+                "field",
+                &Val::String(guard.field_name.clone()),
+                subsystem,
+            )?,
+        ),
+        AbstractPredicate::Gte(
+            column_path(change_type, "changedAt")?,
+            literal_path(change_type, "changedAt", since, subsystem)?,
+        ),
+    );
+
+    let changes = execute_operation(
+        AbstractOperation::Select(change_select(change_type, predicate)?),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    Ok(match body_to_json(&changes.body)? {
+        JsonValue::Array(changes) => changes.len() as u64,
+        _ => 0,
+    })
+}
+
+/// Select the ids of the recorded changes (bypassing access control, since the change type is not
+/// accessible to anyone)
+fn change_select(
+    change_type: &EntityType,
+    predicate: AbstractPredicate,
+) -> Result<AbstractSelect, PostgresExecutionError> {
+    Ok(AbstractSelect {
+        table_id: change_type.table_id,
+        selection: Selection::Json(
+            vec![AliasedSelectionElement::new(
+                "id".to_string(),
+                SelectionElement::Physical(column_id(change_type, "id")?),
+            )],
+            SelectionCardinality::Many,
+        ),
+        predicate,
+        order_by: None,
+        offset: None,
+        limit: None,
+        lock: None,
+    })
+}
+
+fn column_eq(
+    entity_type: &EntityType,
+    field_name: &str,
+    value: &Val,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    Ok(AbstractPredicate::eq(
+        column_path(entity_type, field_name)?,
+        literal_path(entity_type, field_name, value, subsystem)?,
+    ))
+}
+
+fn column_path(
+    entity_type: &EntityType,
+    field_name: &str,
+) -> Result<ColumnPath, PostgresExecutionError> {
+    Ok(ColumnPath::Physical(PhysicalColumnPath::leaf(column_id(
+        entity_type,
+        field_name,
+    )?)))
+}
+
+fn literal_path(
+    entity_type: &EntityType,
+    field_name: &str,
+    value: &Val,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Result<ColumnPath, PostgresExecutionError> {
+    cast::literal_column_path(
+        value,
+        column_id(entity_type, field_name)?
+            .get_column(&subsystem.core_subsystem.database)
+            .typ
+            .inner(),
+        false,
+    )
+}
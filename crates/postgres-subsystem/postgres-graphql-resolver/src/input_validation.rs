@@ -0,0 +1,249 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Run the `@validate` functions of an entity against the data of a create or update mutation
+//! (before anything is written to the database).
+
+use common::context::RequestContext;
+use core_resolver::{system_resolver::GraphQLSystemResolver, validation::field::ValidatedField};
+use exo_deno::{Arg, error::DenoError};
+use postgres_core_model::types::{EntityType, InputValidator};
+use postgres_core_resolver::postgres_execution_error::{InputError, PostgresExecutionError};
+use postgres_graphql_model::mutation::{PostgresMutation, PostgresMutationParameters};
+use serde_json::{Value, json};
+
+use crate::{
+    computed_fields::call_script_function, resolver::PostgresSubsystemResolver, util::find_arg,
+};
+
+const INVALID_VALUE_MESSAGE: &str = "Invalid value";
+
+/// Check the data of a create or update mutation with the validators declared for the entity.
+/// All validators run (so that the client sees every problem at once) and any problems are
+/// reported together as [`PostgresExecutionError::InputRejected`].
+pub(crate) async fn validate_mutation_input(
+    mutation: &PostgresMutation,
+    field: &ValidatedField,
+    subsystem_resolver: &PostgresSubsystemResolver,
+    request_context: &RequestContext<'_>,
+    system_resolver: &GraphQLSystemResolver,
+) -> Result<(), PostgresExecutionError> {
+    let (data_param, operation) = match &mutation.parameters {
+        PostgresMutationParameters::Create(data_param) => (data_param, "create"),
+        PostgresMutationParameters::Update { data_param, .. } => (data_param, "update"),
+        PostgresMutationParameters::Delete(_)
+        | PostgresMutationParameters::TransferOwnership { .. }
+        | PostgresMutationParameters::ReviewChange { .. } => return Ok(()),
+    };
+
+    let entity_type = mutation
+        .return_type
+        .typ(&subsystem_resolver.subsystem.core_subsystem.entity_types);
+
+    if entity_type.validators.is_empty() {
+        return Ok(());
+    }
+
+    let Some(data) = find_arg(&field.arguments, &data_param.name) else {
+        return Ok(());
+    };
+    let data: Value = data.clone().try_into().map_err(|_| {
+        PostgresExecutionError::Generic(format!(
+            "Failed to convert argument '{}' to JSON",
+            data_param.name
+        ))
+    })?;
+
+    let context = json!({
+        "operation": operation,
+        "mutation": field.name.as_str(),
+    });
+
+    // Mutations such as `createTodos` take a list of inputs
+    let inputs = match data {
+        Value::Array(inputs) => inputs,
+        input => vec![input],
+    };
+
+    let mut errors = vec![];
+    for input in inputs.iter() {
+        for validator in entity_type.validators.iter() {
+            errors.extend(
+                run_validator(
+                    validator,
+                    input,
+                    &context,
+                    subsystem_resolver,
+                    request_context,
+                    system_resolver,
+                )
+                .await?,
+            );
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(input_rejected(entity_type, errors))
+    }
+}
+
+async fn run_validator(
+    validator: &InputValidator,
+    input: &Value,
+    context: &Value,
+    subsystem_resolver: &PostgresSubsystemResolver,
+    request_context: &RequestContext<'_>,
+    system_resolver: &GraphQLSystemResolver,
+) -> Result<Vec<InputError>, PostgresExecutionError> {
+    let mut arguments = vec![];
+    if let Some(field_name) = &validator.field_name {
+        // Nothing to check if the input doesn't set the field (such as in a partial update)
+        match input.get(field_name) {
+            Some(value) => arguments.push(Arg::Serde(value.clone())),
+            None => return Ok(vec![]),
+        }
+    }
+    arguments.extend([
+        Arg::Serde(input.clone()),
+        Arg::Serde(context.clone()),
+        Arg::Shim("Exograph".to_string()),
+    ]);
+
+    let result = call_script_function(
+        "deno",
+        validator.script_id,
+        &validator.function_name,
+        arguments,
+        subsystem_resolver,
+        system_resolver,
+        request_context,
+    )
+    .await?;
+
+    match result {
+        Ok(value) => interpret_result(&value, validator.field_name.as_deref()),
+        // A validator may also reject the input by throwing an `ExographError`
+        Err(DenoError::Explicit(message)) => Ok(vec![InputError {
+            field: validator.field_name.clone(),
+            message,
+        }]),
+        Err(e) => Err(PostgresExecutionError::Generic(format!(
+            "Failed to run validator '{}': {e}",
+            validator.function_name
+        ))),
+    }
+}
+
+/// Interpret the value returned by a validator:
+/// - `undefined`, `null`, or `true`: the input is valid
+/// - `false`: the input is invalid (with a generic message)
+/// - a string: the input is invalid with that message
+/// - an array of strings: the input is invalid with those messages
+/// - an object: maps field names to messages (only for entity-level validators)
+fn interpret_result(
+    value: &Value,
+    field_name: Option<&str>,
+) -> Result<Vec<InputError>, PostgresExecutionError> {
+    let error = |message: &str| InputError {
+        field: field_name.map(|name| name.to_string()),
+        message: message.to_string(),
+    };
+
+    match value {
+        Value::Null | Value::Bool(true) => Ok(vec![]),
+        Value::Bool(false) => Ok(vec![error(INVALID_VALUE_MESSAGE)]),
+        Value::String(message) => Ok(vec![error(message)]),
+        Value::Array(messages) => messages
+            .iter()
+            .map(|message| match message {
+                Value::String(message) => Ok(error(message)),
+                _ => Err(unexpected_result(value)),
+            })
+            .collect(),
+        Value::Object(field_messages) if field_name.is_none() => field_messages
+            .iter()
+            .filter(|(_, message)| !message.is_null())
+            .map(|(field, message)| match message {
+                Value::String(message) => Ok(InputError {
+                    field: Some(field.clone()),
+                    message: message.clone(),
+                }),
+                _ => Err(unexpected_result(value)),
+            })
+            .collect(),
+        _ => Err(unexpected_result(value)),
+    }
+}
+
+fn unexpected_result(value: &Value) -> PostgresExecutionError {
+    PostgresExecutionError::Generic(format!("Unexpected result from a validator: {value}"))
+}
+
+fn input_rejected(entity_type: &EntityType, errors: Vec<InputError>) -> PostgresExecutionError {
+    PostgresExecutionError::InputRejected {
+        entity_name: entity_type.name.clone(),
+        errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_error(field: Option<&str>, message: &str) -> InputError {
+        InputError {
+            field: field.map(|field| field.to_string()),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn valid_results() {
+        for value in [Value::Null, json!(true), json!([]), json!({})] {
+            assert_eq!(interpret_result(&value, None).unwrap(), vec![]);
+        }
+        assert_eq!(
+            interpret_result(&json!({"title": null}), None).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn rejections() {
+        assert_eq!(
+            interpret_result(&json!(false), Some("title")).unwrap(),
+            vec![input_error(Some("title"), INVALID_VALUE_MESSAGE)]
+        );
+        assert_eq!(
+            interpret_result(&json!("Too short"), Some("title")).unwrap(),
+            vec![input_error(Some("title"), "Too short")]
+        );
+        assert_eq!(
+            interpret_result(&json!(["Too early", "Too late"]), None).unwrap(),
+            vec![
+                input_error(None, "Too early"),
+                input_error(None, "Too late")
+            ]
+        );
+        assert_eq!(
+            interpret_result(&json!({"endsAt": "Must be after startsAt"}), None).unwrap(),
+            vec![input_error(Some("endsAt"), "Must be after startsAt")]
+        );
+    }
+
+    #[test]
+    fn unexpected_results() {
+        assert!(interpret_result(&json!(42), None).is_err());
+        assert!(interpret_result(&json!([42]), None).is_err());
+        // Field-level validators may not report problems with other fields
+        assert!(interpret_result(&json!({"title": "Too short"}), Some("title")).is_err());
+    }
+}
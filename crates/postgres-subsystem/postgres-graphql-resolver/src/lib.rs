@@ -7,22 +7,32 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub use data_transfer::PostgresDataTransferResolver;
 pub use resolver::PostgresSubsystemResolver;
 
 mod abstract_operation_resolver;
 mod access;
 mod aggregate_query;
 mod auth_util;
+mod batch_query;
+mod change_approval;
+mod chunk_query;
 mod column_path_util;
 mod computed_fields;
 mod create_data_param_mapper;
+mod data_transfer;
+mod field_guards;
+mod input_validation;
 mod limit_offset_mapper;
 mod operation_resolver;
 mod order_by_mapper;
+mod ownership_transfer_mapper;
+mod page_query;
 mod postgres_mutation;
 mod postgres_query;
 mod predicate_mapper;
 mod resolver;
+mod similarity_query;
 mod sql_mapper;
 mod update_data_param_mapper;
 mod util;
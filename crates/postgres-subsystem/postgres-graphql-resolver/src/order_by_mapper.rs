@@ -12,13 +12,14 @@ use futures::future::join_all;
 
 use crate::util::to_pg_vector;
 use crate::{
-    auth_util::check_retrieve_access, column_path_util::to_column_path, sql_mapper::SQLMapper,
+    auth_util::check_retrieve_access, column_path_util::to_transitive_column_path,
+    sql_mapper::SQLMapper,
 };
 use common::context::RequestContext;
 use common::value::Val;
 use exo_sql::{
-    AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, ColumnPathLink, Ordering,
-    PhysicalColumnPath,
+    AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, ColumnPathLink, JsonFieldPath,
+    Ordering, PhysicalColumnPath,
 };
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
 
@@ -135,15 +136,22 @@ async fn order_by_pair<'a>(
     match &typ.kind {
         OrderByParameterTypeKind::Composite { parameters } => {
             match parameters.iter().find(|p| p.name == parameter_name) {
+                Some(parameter) if parameter.embedded_field.is_some() => {
+                    // A field inside the JSON column (the parent column path)
+                    let json_column = parent_column_path.ok_or_else(|| {
+                        PostgresExecutionError::Validation(
+                            parameter_name.into(),
+                            "Invalid order by parameter".into(),
+                        )
+                    })?;
+
+                    embedded_order_by(parameter, parameter_value, &json_column, &[], subsystem)
+                        .map(|order_by| OrderByComputation::new(order_by, AbstractPredicate::True))
+                }
                 Some(parameter) => {
                     let field_access = match parameter.access {
                         Some(ref access) => {
-                            check_retrieve_access(
-                                &subsystem.core_subsystem.database_access_expressions[access.read],
-                                subsystem,
-                                request_context,
-                            )
-                            .await?
+                            check_retrieve_access(access.read, subsystem, request_context).await?
                         }
                         None => AbstractPredicate::True,
                     };
@@ -172,8 +180,11 @@ async fn order_by_pair<'a>(
                         &subsystem.order_by_types[parameter.typ.innermost().type_id];
 
                     // If this is a leaf node ({something: ASC} kind), then resolve the ordering. If not, then recurse with a new parent column path.
-                    let new_column_path =
-                        to_column_path(&parent_column_path, &parameter.column_path_link);
+                    let new_column_path = to_transitive_column_path(
+                        &parent_column_path,
+                        &parameter.column_path_link,
+                        &parameter.transitive_links,
+                    );
 
                     match &base_param_type.kind {
                         OrderByParameterTypeKind::Primitive => {
@@ -251,6 +262,75 @@ async fn order_by_pair<'a>(
     }
 }
 
+/// Map an ordering on a field embedded in a JSON column. The `path` leads to the object holding
+/// the field.
+fn embedded_order_by(
+    parameter: &OrderByParameter,
+    parameter_value: &Val,
+    json_column: &PhysicalColumnPath,
+    path: &[String],
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Result<AbstractOrderBy, PostgresExecutionError> {
+    let embedded_field = parameter
+        .embedded_field
+        .as_ref()
+        .expect("Embedded field parameter without an embedded field link");
+    let path = [path, std::slice::from_ref(&embedded_field.key)].concat();
+
+    let parameter_type = &subsystem.order_by_types[parameter.typ.innermost().type_id];
+
+    match (&parameter_type.kind, &embedded_field.typ) {
+        (OrderByParameterTypeKind::Primitive, Some(typ)) => {
+            ordering(parameter_value).map(|ordering| {
+                AbstractOrderBy(vec![(
+                    AbstractOrderByExpr::JsonField(JsonFieldPath {
+                        column: json_column.clone(),
+                        path,
+                        typ: typ.clone(),
+                    }),
+                    ordering,
+                )])
+            })
+        }
+        (OrderByParameterTypeKind::Composite { parameters }, None) => {
+            let elems: Vec<&Val> = match parameter_value {
+                Val::List(elems) => elems.iter().collect(),
+                elem => vec![elem],
+            };
+
+            let mut expressions = Vec::new();
+            for elem in elems {
+                let Val::Object(fields) = elem else {
+                    return Err(PostgresExecutionError::Validation(
+                        parameter.name.clone(),
+                        format!("Invalid argument ('{elem}')"),
+                    ));
+                };
+
+                for (name, value) in fields {
+                    let nested_parameter =
+                        parameters.iter().find(|p| &p.name == name).ok_or_else(|| {
+                            PostgresExecutionError::Validation(
+                                name.clone(),
+                                "Invalid order by parameter".into(),
+                            )
+                        })?;
+
+                    let AbstractOrderBy(mut exprs) =
+                        embedded_order_by(nested_parameter, value, json_column, &path, subsystem)?;
+                    expressions.append(&mut exprs);
+                }
+            }
+
+            Ok(AbstractOrderBy(expressions))
+        }
+        _ => Err(PostgresExecutionError::Validation(
+            parameter.name.clone(),
+            "Unsupported ordering on a field of a JSON type".into(),
+        )),
+    }
+}
+
 fn ordering(argument: &Val) -> Result<Ordering, PostgresExecutionError> {
     fn str_ordering(value: &str) -> Result<Ordering, PostgresExecutionError> {
         if value == "ASC" {
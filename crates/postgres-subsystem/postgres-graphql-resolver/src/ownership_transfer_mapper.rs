@@ -0,0 +1,79 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use common::context::RequestContext;
+use common::value::Val;
+use core_model::types::OperationReturnType;
+use exo_sql::{AbstractPredicate, AbstractSelect, AbstractUpdate};
+use postgres_core_model::{relation::PostgresRelation, types::EntityType};
+use postgres_graphql_model::{mutation::NewOwnerParameter, subsystem::PostgresGraphQLSubsystem};
+
+use crate::sql_mapper::SQLMapper;
+
+use postgres_core_resolver::{
+    cast, opaque_id::decode_opaque_ids, postgres_execution_error::PostgresExecutionError,
+};
+
+/// A specialized update that only sets the owner column (and nothing else) of the matching rows
+pub struct OwnershipTransferOperation<'a> {
+    pub new_owner_param: &'a NewOwnerParameter,
+    pub return_type: &'a OperationReturnType<EntityType>,
+    pub predicate: AbstractPredicate,
+    pub select: AbstractSelect,
+}
+
+#[async_trait]
+impl<'a> SQLMapper<'a, AbstractUpdate> for OwnershipTransferOperation<'a> {
+    async fn to_sql(
+        self,
+        argument: &'a Val,
+        subsystem: &'a PostgresGraphQLSubsystem,
+        _request_context: &'a RequestContext<'a>,
+    ) -> Result<AbstractUpdate, PostgresExecutionError> {
+        let entity_type = &subsystem.core_subsystem.entity_types[self.return_type.typ_id()];
+
+        let owner_field = entity_type
+            .ownership_transfer
+            .as_ref()
+            .and_then(|transfer| entity_type.field_by_name(&transfer.field_name))
+            .ok_or_else(|| {
+                PostgresExecutionError::Generic(format!(
+                    "Ownership transfer is not defined for '{}'",
+                    entity_type.name
+                ))
+            })?;
+
+        let PostgresRelation::Scalar { column_id, .. } = &owner_field.relation else {
+            return Err(PostgresExecutionError::Generic(format!(
+                "Owner field '{}' must be a scalar field",
+                owner_field.name
+            )));
+        };
+
+        let column = column_id.get_column(&subsystem.core_subsystem.database);
+        let argument = decode_opaque_ids(argument, column, &subsystem.core_subsystem)?;
+        let value_column = cast::literal_column(&argument, column)?;
+
+        Ok(AbstractUpdate {
+            table_id: entity_type.table_id,
+            predicate: self.predicate,
+            column_values: vec![(*column_id, value_column)],
+            selection: self.select,
+            nested_updates: vec![],
+            nested_inserts: vec![],
+            nested_deletes: vec![],
+            precheck_predicates: vec![],
+        })
+    }
+
+    fn param_name(&self) -> &str {
+        &self.new_owner_param.name
+    }
+}
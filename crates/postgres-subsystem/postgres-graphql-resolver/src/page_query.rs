@@ -0,0 +1,148 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use common::{
+    context::RequestContext,
+    value::{Val, val::ValNumber},
+};
+use core_resolver::{
+    QueryResponse, QueryResponseBody, system_resolver::GraphQLSystemResolver,
+    validation::field::ValidatedField,
+};
+use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_graphql_model::{
+    page::{PAGE_HAS_NEXT_PAGE_FIELD, PAGE_ITEMS_FIELD},
+    query::PageQuery,
+};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::{
+    abstract_operation_resolver::resolve_operation, operation_resolver::OperationResolver,
+    resolver::PostgresSubsystemResolver,
+};
+
+/// Resolve a page query by fetching one more entity than the requested limit through the
+/// corresponding collection query: if that extra entity exists, another page follows (and the
+/// entity is dropped from the items).
+pub(crate) async fn resolve_page_query<'e>(
+    query: &'e PageQuery,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let entity_type_id = query.return_type.typ_id();
+    let collection_query = subsystem
+        .collection_queries
+        .iter()
+        .map(|(_, collection_query)| collection_query)
+        .find(|collection_query| collection_query.return_type.typ_id() == entity_type_id)
+        .ok_or_else(|| {
+            PostgresExecutionError::Generic(format!(
+                "No collection query found for page query '{}'",
+                query.name
+            ))
+        })?;
+
+    let limit_param_name = &query.parameters.limit_param.name;
+    let limit = match field.arguments.get(limit_param_name) {
+        Some(Val::Number(limit)) => Some(limit.as_i64().ok_or_else(|| {
+            PostgresExecutionError::Validation(
+                limit_param_name.clone(),
+                "Limit must be an integer".into(),
+            )
+        })?),
+        _ => None,
+    };
+
+    let mut items_fields = field
+        .subfields
+        .iter()
+        .filter(|subfield| subfield.name == PAGE_ITEMS_FIELD);
+    let items_field = items_fields.next();
+    if items_fields.next().is_some() {
+        return Err(PostgresExecutionError::Generic(format!(
+            "The '{PAGE_ITEMS_FIELD}' field of a page may be selected only once"
+        )));
+    }
+
+    let mut arguments = field.arguments.clone();
+    if let Some(limit) = limit {
+        arguments.insert(
+            limit_param_name.clone(),
+            Val::Number(ValNumber::I64(limit + 1)),
+        );
+    }
+
+    let collection_field = ValidatedField {
+        alias: None,
+        name: field.name.clone(),
+        arguments,
+        subfields: items_field
+            .map(|items_field| items_field.subfields.clone())
+            .unwrap_or_default(),
+    };
+
+    let operation = collection_query
+        .resolve(&collection_field, request_context, subsystem)
+        .await?;
+    let response = resolve_operation(
+        operation,
+        &collection_field,
+        subsystem_resolver,
+        request_context,
+        system_resolver,
+    )
+    .await?;
+
+    let mut items = match response
+        .body
+        .to_json()
+        .map_err(|e| PostgresExecutionError::Generic(e.to_string()))?
+    {
+        JsonValue::Array(items) => items,
+        JsonValue::Null => vec![],
+        _ => {
+            return Err(PostgresExecutionError::Generic(
+                "Expected a list of items for a page query".into(),
+            ));
+        }
+    };
+
+    let has_next_page = match limit {
+        Some(limit) if items.len() as i64 > limit => {
+            items.truncate(limit.max(0) as usize);
+            true
+        }
+        _ => false,
+    };
+
+    let mut items = Some(items);
+    let mut page = Map::new();
+    for subfield in field.subfields.iter() {
+        let value = match subfield.name.as_str() {
+            "__typename" => JsonValue::String(query.return_type.type_name().to_string()),
+            PAGE_ITEMS_FIELD => JsonValue::Array(items.take().unwrap_or_default()),
+            PAGE_HAS_NEXT_PAGE_FIELD => JsonValue::Bool(has_next_page),
+            other => {
+                return Err(PostgresExecutionError::Generic(format!(
+                    "Unknown field '{other}' on a page"
+                )));
+            }
+        };
+        page.insert(subfield.output_name(), value);
+    }
+
+    Ok(QueryResponse {
+        body: QueryResponseBody::Json(JsonValue::Object(page)),
+        headers: response.headers,
+    })
+}
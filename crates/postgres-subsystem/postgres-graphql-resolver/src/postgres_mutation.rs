@@ -20,6 +20,7 @@ use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
 use crate::{
     create_data_param_mapper::InsertOperation,
     operation_resolver::{OperationResolver, PostgresResolvedOperation},
+    ownership_transfer_mapper::OwnershipTransferOperation,
     postgres_query::compute_select,
     predicate_mapper::compute_predicate,
     sql_mapper::SQLMapper,
@@ -36,7 +37,7 @@ use exo_sql::{
 };
 use postgres_core_model::{predicate::PredicateParameter, types::EntityType};
 use postgres_graphql_model::{
-    mutation::{DataParameter, PostgresMutation, PostgresMutationParameters},
+    mutation::{DataParameter, NewOwnerParameter, PostgresMutation, PostgresMutationParameters},
     subsystem::PostgresGraphQLSubsystem,
 };
 
@@ -103,6 +104,26 @@ impl OperationResolver for PostgresMutation {
                 )
                 .await?,
             ),
+            PostgresMutationParameters::TransferOwnership {
+                predicate_params,
+                new_owner_param,
+            } => AbstractOperation::Update(
+                transfer_ownership_operation(
+                    return_type,
+                    predicate_params,
+                    new_owner_param,
+                    field,
+                    abstract_select,
+                    subsystem,
+                    request_context,
+                )
+                .await?,
+            ),
+            PostgresMutationParameters::ReviewChange { .. } => {
+                return Err(PostgresExecutionError::Generic(
+                    "Reviewing a change is handled by the approval workflow".to_string(),
+                ));
+            }
         };
 
         Ok(PostgresResolvedOperation {
@@ -158,10 +179,10 @@ async fn delete_operation<'content>(
     .await?;
 
     let parent_read_predicate = check_retrieve_access(
-        &subsystem.core_subsystem.database_access_expressions[return_type
+        return_type
             .typ(&subsystem.core_subsystem.entity_types)
             .access
-            .read],
+            .read,
         subsystem,
         request_context,
     )
@@ -216,10 +237,10 @@ async fn update_operation<'content>(
     .await?;
 
     let parent_read_predicate = check_retrieve_access(
-        &subsystem.core_subsystem.database_access_expressions[return_type
+        return_type
             .typ(&subsystem.core_subsystem.entity_types)
             .access
-            .read],
+            .read,
         subsystem,
         request_context,
     )
@@ -257,3 +278,61 @@ async fn update_operation<'content>(
         )),
     }
 }
+
+async fn transfer_ownership_operation<'content>(
+    return_type: &'content OperationReturnType<EntityType>,
+    predicate_params: &'content [PredicateParameter],
+    new_owner_param: &'content NewOwnerParameter,
+    field: &'content ValidatedField,
+    select: AbstractSelect,
+    subsystem: &'content PostgresGraphQLSubsystem,
+    request_context: &'content RequestContext<'content>,
+) -> Result<AbstractUpdate, PostgresExecutionError> {
+    let entity_type = return_type.typ(&subsystem.core_subsystem.entity_types);
+
+    let transfer = entity_type.ownership_transfer.as_ref().ok_or_else(|| {
+        PostgresExecutionError::Generic(format!(
+            "Ownership transfer is not defined for '{}'",
+            entity_type.name
+        ))
+    })?;
+
+    // The transfer rule is evaluated against the existing row (and not the general update
+    // rule), so apps don't need to allow updating the owner field to permit transfers
+    let transfer_predicate =
+        check_retrieve_access(transfer.access, subsystem, request_context).await?;
+
+    if transfer_predicate == AbstractPredicate::False {
+        return Err(PostgresExecutionError::Authorization);
+    }
+
+    let parent_read_predicate =
+        check_retrieve_access(entity_type.access.read, subsystem, request_context).await?;
+    let restrict_relations = parent_read_predicate != AbstractPredicate::True;
+
+    let arg_predicate = compute_predicate(
+        &predicate_params.iter().collect::<Vec<_>>(),
+        &field.arguments,
+        subsystem,
+        request_context,
+        restrict_relations,
+    )
+    .await?;
+    let predicate = Predicate::and(transfer_predicate, arg_predicate);
+
+    match find_arg(&field.arguments, &new_owner_param.name) {
+        Some(argument) => {
+            OwnershipTransferOperation {
+                new_owner_param,
+                return_type,
+                predicate,
+                select,
+            }
+            .to_sql(argument, subsystem, request_context)
+            .await
+        }
+        None => Err(PostgresExecutionError::MissingArgument(
+            new_owner_param.name.clone(),
+        )),
+    }
+}
@@ -23,29 +23,41 @@ use async_graphql_value::Name;
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 use common::context::RequestContext;
-use core_model::types::OperationReturnType;
+use common::value::Val;
+use core_model::types::{FieldType, OperationReturnType};
 use core_resolver::validation::field::ValidatedField;
 use exo_sql::{
-    AbstractOrderBy, AbstractPredicate, AbstractSelect, AliasedSelectionElement, Limit, Offset,
-    RelationId, Selection, SelectionCardinality, SelectionElement,
+    AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate, AbstractSelect, AbstractTraversal,
+    AbstractWindow, AliasedSelectionElement, ColumnId, ColumnPath, DEFAULT_MAX_TRAVERSAL_DEPTH,
+    Limit, Offset, Ordering, PhysicalColumnPath, RelationId, RowLock, Selection,
+    SelectionCardinality, SelectionElement,
 };
-use exo_sql::{Function, SQLParamContainer};
+use exo_sql::{DateTruncUnit, Function, SQLParamContainer, StringFunction};
 use futures::StreamExt;
 use indexmap::IndexMap;
 use postgres_core_model::vector_distance::VectorDistanceField;
+use postgres_core_model::window::WindowField;
 use postgres_core_model::{
     aggregate::AggregateField,
     relation::{
         ManyToOneRelation, OneToManyRelation, PostgresRelation, RelationCardinality,
         TransitiveRelation, TransitiveRelationStep,
     },
-    types::{EntityType, PostgresField},
+    types::{
+        ComputedFieldDependency, ComputedFieldDependencySource, EntityType, PostgresField,
+        PostgresFieldType, TypeIndex,
+    },
 };
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_graphql_model::date_transform::{
+    FORMAT_ARG_NAME, TIMEZONE_ARG_NAME, TRUNCATE_ARG_NAME,
+};
+use postgres_graphql_model::lock::LockParameter;
 use postgres_graphql_model::query::UniqueQuery;
+use postgres_graphql_model::string_transform::{SUBSTRING_ARG_NAME, TRANSFORM_ARG_NAME};
 use postgres_graphql_model::{
     order::OrderByParameter,
-    query::{CollectionQuery, CollectionQueryParameters},
+    query::{CollectionQuery, CollectionQueryParameters, TreeQuery, TreeQueryParameters},
     subsystem::PostgresGraphQLSubsystem,
 };
 use std::collections::HashSet;
@@ -59,12 +71,9 @@ impl OperationSelectionResolver for UniqueQuery {
         subsystem: &'a PostgresGraphQLSubsystem,
     ) -> Result<ResolvedSelect<'a>, PostgresExecutionError> {
         let return_entity_type = self.return_type.typ(&subsystem.core_subsystem.entity_types);
-        let parent_read_predicate = check_retrieve_access(
-            &subsystem.core_subsystem.database_access_expressions[return_entity_type.access.read],
-            subsystem,
-            request_context,
-        )
-        .await?;
+        let parent_read_predicate =
+            check_retrieve_access(return_entity_type.access.read, subsystem, request_context)
+                .await?;
         let restrict_relations = parent_read_predicate != AbstractPredicate::True;
 
         let predicate = compute_predicate(
@@ -76,7 +85,7 @@ impl OperationSelectionResolver for UniqueQuery {
         )
         .await?;
 
-        let select = compute_select(
+        let mut select = compute_select(
             predicate,
             None,
             None,
@@ -87,6 +96,12 @@ impl OperationSelectionResolver for UniqueQuery {
             request_context,
         )
         .await?;
+        select.lock = compute_lock(
+            &self.parameters.lock_param,
+            &field.arguments,
+            request_context,
+        )
+        .await?;
 
         Ok(ResolvedSelect {
             select,
@@ -108,17 +123,15 @@ impl OperationSelectionResolver for CollectionQuery {
             order_by_param,
             limit_param,
             offset_param,
+            lock_param,
         } = &self.parameters;
 
         let arguments = &field.arguments;
 
         let return_entity_type = self.return_type.typ(&subsystem.core_subsystem.entity_types);
-        let parent_read_predicate = check_retrieve_access(
-            &subsystem.core_subsystem.database_access_expressions[return_entity_type.access.read],
-            subsystem,
-            request_context,
-        )
-        .await?;
+        let parent_read_predicate =
+            check_retrieve_access(return_entity_type.access.read, subsystem, request_context)
+                .await?;
         let restrict_relations = parent_read_predicate != AbstractPredicate::True;
 
         let base_predicate = compute_predicate(
@@ -143,7 +156,7 @@ impl OperationSelectionResolver for CollectionQuery {
 
         let combined_predicate = AbstractPredicate::and(base_predicate, order_by_predicate);
 
-        let select = compute_select(
+        let mut select = compute_select(
             combined_predicate,
             order_by,
             extract_and_map(limit_param, arguments, subsystem, request_context).await?,
@@ -154,6 +167,102 @@ impl OperationSelectionResolver for CollectionQuery {
             request_context,
         )
         .await?;
+        select.lock = compute_lock(lock_param, arguments, request_context).await?;
+
+        Ok(ResolvedSelect {
+            select,
+            return_type: &self.return_type,
+        })
+    }
+}
+
+#[async_trait]
+impl OperationSelectionResolver for TreeQuery {
+    async fn resolve_select<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+        subsystem: &'a PostgresGraphQLSubsystem,
+    ) -> Result<ResolvedSelect<'a>, PostgresExecutionError> {
+        let TreeQueryParameters {
+            of_param,
+            max_depth_param,
+            relation_id,
+            direction,
+        } = &self.parameters;
+
+        let arguments = &field.arguments;
+
+        let return_entity_type = self.return_type.typ(&subsystem.core_subsystem.entity_types);
+        let parent_read_predicate =
+            check_retrieve_access(return_entity_type.access.read, subsystem, request_context)
+                .await?;
+        let restrict_relations = parent_read_predicate != AbstractPredicate::True;
+
+        let start = compute_predicate(
+            &[of_param],
+            arguments,
+            subsystem,
+            request_context,
+            restrict_relations,
+        )
+        .await?;
+
+        let max_depth =
+            match extract_and_map(max_depth_param, arguments, subsystem, request_context).await? {
+                Some(Limit(max_depth)) if max_depth < 0 => {
+                    return Err(PostgresExecutionError::Validation(
+                        max_depth_param.name.clone(),
+                        "Must not be negative".into(),
+                    ));
+                }
+                // Cap the depth so that cyclic data doesn't lead to (practically) unbounded recursion
+                Some(Limit(max_depth)) => max_depth.min(DEFAULT_MAX_TRAVERSAL_DEPTH as i64) as u32,
+                None => DEFAULT_MAX_TRAVERSAL_DEPTH,
+            };
+
+        let column_pairs = relation_id
+            .deref(&subsystem.core_subsystem.database)
+            .column_pairs;
+        let pk_column_id = column_pairs
+            .first()
+            .ok_or_else(|| {
+                PostgresExecutionError::Generic("Invalid self-referential relation".into())
+            })?
+            .foreign_column_id;
+
+        // Apply the read access predicate at each level so that the traversal doesn't go through
+        // (and expose entities reachable only through) entities the user may not read
+        let traversal = AbstractTraversal {
+            column_pairs,
+            direction: *direction,
+            start,
+            level_predicate: parent_read_predicate,
+            max_depth,
+        };
+
+        let predicate = AbstractPredicate::In(
+            ColumnPath::Physical(PhysicalColumnPath::leaf(pk_column_id)),
+            ColumnPath::Traversal(Box::new(traversal)),
+        );
+
+        // Order by the primary key for a stable result
+        let order_by = AbstractOrderBy(vec![(
+            AbstractOrderByExpr::Column(PhysicalColumnPath::leaf(pk_column_id)),
+            Ordering::Asc,
+        )]);
+
+        let select = compute_select(
+            predicate,
+            Some(order_by),
+            None,
+            None,
+            &self.return_type,
+            &field.subfields,
+            subsystem,
+            request_context,
+        )
+        .await?;
 
         Ok(ResolvedSelect {
             select,
@@ -213,6 +322,7 @@ pub(super) async fn compute_select<'content>(
         order_by,
         offset,
         limit,
+        lock: None,
     })
 }
 
@@ -234,6 +344,42 @@ async fn compute_order_by<'content>(
     .await
 }
 
+/// Compute the lock requested through the `lock` argument (such as `lock: SKIP_LOCKED`).
+///
+/// A lock lasts only until the end of the transaction, so this also makes the request use a
+/// transaction, which lets the subsequent operations in the request (such as those made by an
+/// interceptor or a Deno module) work with the locked rows.
+async fn compute_lock<'content>(
+    param: &'content LockParameter,
+    arguments: &'content Arguments,
+    request_context: &'content RequestContext<'content>,
+) -> Result<Option<RowLock>, PostgresExecutionError> {
+    let lock = match arguments.get(&param.name) {
+        None | Some(Val::Null) => return Ok(None),
+        Some(Val::Enum(lock) | Val::String(lock)) => match lock.as_str() {
+            "FOR_UPDATE" => RowLock::ForUpdate,
+            "FOR_SHARE" => RowLock::ForShare,
+            "SKIP_LOCKED" => RowLock::SkipLocked,
+            other => {
+                return Err(PostgresExecutionError::Validation(
+                    param.name.clone(),
+                    format!("Unknown lock '{other}'"),
+                ));
+            }
+        },
+        Some(other) => {
+            return Err(PostgresExecutionError::Validation(
+                param.name.clone(),
+                format!("Invalid lock '{other}'"),
+            ));
+        }
+    };
+
+    request_context.ensure_transaction().await;
+
+    Ok(Some(lock))
+}
+
 #[async_recursion]
 async fn content_select<'content>(
     return_type: &EntityType,
@@ -285,18 +431,24 @@ async fn map_field<'content>(
                     Some(agg_field) => {
                         map_aggregate_field(agg_field, field, subsystem, request_context).await?
                     }
-                    None => {
-                        let vector_distance_field = return_type
-                            .vector_distance_field_by_name(&field.name)
-                            .ok_or_else(|| {
-                                PostgresExecutionError::Generic(format!(
-                                    "Unknown field '{}' on type '{}'",
-                                    field.name, return_type.name
-                                ))
-                            })?;
-
-                        map_vector_distance_field(vector_distance_field, field).await?
-                    }
+                    None => match return_type.window_field_by_name(&field.name) {
+                        Some(window_field) => {
+                            map_window_field(window_field, return_type, subsystem, request_context)
+                                .await?
+                        }
+                        None => {
+                            let vector_distance_field = return_type
+                                .vector_distance_field_by_name(&field.name)
+                                .ok_or_else(|| {
+                                    PostgresExecutionError::Generic(format!(
+                                        "Unknown field '{}' on type '{}'",
+                                        field.name, return_type.name
+                                    ))
+                                })?;
+
+                            map_vector_distance_field(vector_distance_field, field).await?
+                        }
+                    },
                 }
             }
         }
@@ -305,6 +457,25 @@ async fn map_field<'content>(
     Ok(AliasedSelectionElement::new(output_name, selection_elem))
 }
 
+/// Whether the role (from the JWT `role` claim) of the request is one of the roles.
+/// The claim may hold a single role or a list of roles.
+async fn applies_to_role(
+    roles: &[String],
+    request_context: &RequestContext<'_>,
+) -> Result<bool, PostgresExecutionError> {
+    let role = request_context
+        .extract_context_field("MaskContext", "jwt", &Some("role"), "role", &Ok)
+        .await?;
+
+    Ok(match role {
+        Some(Val::String(role)) => roles.contains(role),
+        Some(Val::List(request_roles)) => request_roles
+            .iter()
+            .any(|role| matches!(role, Val::String(role) if roles.contains(role))),
+        _ => false,
+    })
+}
+
 async fn map_persistent_field<'content>(
     entity_field: &PostgresField<EntityType>,
     field: &'content ValidatedField,
@@ -312,7 +483,61 @@ async fn map_persistent_field<'content>(
     request_context: &'content RequestContext<'content>,
 ) -> Result<SelectionElement, PostgresExecutionError> {
     match &entity_field.relation {
-        PostgresRelation::Scalar { column_id, .. } => Ok(SelectionElement::Physical(*column_id)),
+        PostgresRelation::Scalar { column_id, .. } => {
+            if let Some(projection) =
+                embedded_projection(entity_field, *column_id, field, subsystem)
+            {
+                return Ok(projection);
+            }
+
+            let transforms = string_transforms(field)?;
+            let opaque_id = column_id
+                .get_column(&subsystem.core_subsystem.database)
+                .opaque_id;
+
+            let column = if opaque_id {
+                if !transforms.is_empty() {
+                    return Err(PostgresExecutionError::Validation(
+                        field.name.to_string(),
+                        "Opaque ids cannot be transformed".to_string(),
+                    ));
+                }
+                let key = subsystem.core_subsystem.opaque_id_key.ok_or_else(|| {
+                    PostgresExecutionError::Generic("No key to encode opaque ids".to_string())
+                })?;
+                SelectionElement::OpaqueId {
+                    source: Box::new(SelectionElement::Physical(*column_id)),
+                    key,
+                }
+            } else if let Some(date_transform) = date_transform(field, *column_id)? {
+                SelectionElement::Function(date_transform)
+            } else if transforms.is_empty() {
+                SelectionElement::Physical(*column_id)
+            } else {
+                SelectionElement::Function(Function::StringTransform {
+                    column_id: *column_id,
+                    transforms,
+                })
+            };
+
+            let column = match &entity_field.read_default {
+                Some(fallback) => SelectionElement::Coalesce {
+                    source: Box::new(column),
+                    fallback: fallback.clone(),
+                },
+                None => column,
+            };
+
+            Ok(match &entity_field.mask {
+                Some(mask) if applies_to_role(&mask.roles, request_context).await? => {
+                    SelectionElement::Masked {
+                        source: Box::new(column),
+                        strategy: mask.strategy,
+                    }
+                }
+                _ => column,
+            })
+        }
         PostgresRelation::ManyToOne { relation, .. } => {
             let ManyToOneRelation {
                 foreign_entity_id, ..
@@ -362,28 +587,180 @@ async fn map_persistent_field<'content>(
             if computed.dependencies.is_empty() {
                 Ok(SelectionElement::Null)
             } else {
-                let object_fields = computed
-                    .dependencies
-                    .iter()
-                    .map(|dependency| {
-                        (
-                            dependency.field_name.clone(),
-                            SelectionElement::Physical(dependency.column_id),
-                        )
-                    })
-                    .collect();
-                Ok(SelectionElement::Object(object_fields))
+                Ok(SelectionElement::Object(dependency_selection(
+                    &computed.dependencies,
+                )))
             }
         }
-        PostgresRelation::Embedded => {
-            panic!("Embedded relations cannot be used in queries")
-        }
+        PostgresRelation::SqlExpression(expression) => Ok(SelectionElement::Function(
+            Function::Expression(expression.clone()),
+        )),
+        PostgresRelation::Embedded => Err(PostgresExecutionError::Generic(format!(
+            "Field '{}' of a JSON type can only be selected through the field holding the JSON value",
+            entity_field.name
+        ))),
         PostgresRelation::Transitive(transitive) => {
             map_transitive_field(transitive, field, subsystem, request_context).await
         }
     }
 }
 
+/// The values of a computed field's dependencies (selected as a JSON object such as `{"title":
+/// "posts"."title", "author": (SELECT json_build_object('name', "authors"."name") ...)}`)
+fn dependency_selection(
+    dependencies: &[ComputedFieldDependency],
+) -> Vec<(String, SelectionElement)> {
+    dependencies
+        .iter()
+        .map(|dependency| {
+            let element = match &dependency.source {
+                ComputedFieldDependencySource::Column(column_id) => {
+                    SelectionElement::Physical(*column_id)
+                }
+                ComputedFieldDependencySource::Relation {
+                    relation_id,
+                    table_id,
+                    dependencies,
+                } => SelectionElement::SubSelect(
+                    *relation_id,
+                    Box::new(AbstractSelect {
+                        table_id: *table_id,
+                        selection: Selection::Json(
+                            dependency_selection(dependencies)
+                                .into_iter()
+                                .map(|(name, element)| AliasedSelectionElement::new(name, element))
+                                .collect(),
+                            SelectionCardinality::One,
+                        ),
+                        predicate: AbstractPredicate::True,
+                        order_by: None,
+                        offset: None,
+                        limit: None,
+                        lock: None,
+                    }),
+                ),
+            };
+
+            (dependency.field_name.clone(), element)
+        })
+        .collect()
+}
+
+/// Select only the keys of a JSON value that the selection needs (for example, `{"city":
+/// "customers"."address" -> 'city'}` for `address { city }`) instead of the whole value. Values
+/// of nested JSON types are projected the same way (for example, `"customers"."address" -> 'geo'
+/// -> 'lat'` for `address { geo { lat } }`). The result is shaped to the selection (aliases,
+/// `__typename`, etc.) while post-processing.
+///
+/// Returns `None` if the whole value must be selected: for a list or if a field needs to be
+/// computed from the value.
+fn embedded_projection(
+    entity_field: &PostgresField<EntityType>,
+    column_id: ColumnId,
+    field: &ValidatedField,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Option<SelectionElement> {
+    if entity_field.read_default.is_some() {
+        return None;
+    }
+
+    let json_type = json_type_of(&entity_field.typ, subsystem)?;
+    let elements = json_projection_elements(json_type, column_id, &[], field, subsystem)?;
+
+    // Projecting a missing (optional) value would produce an object of nulls
+    Some(match entity_field.typ {
+        FieldType::Optional(_) => SelectionElement::JsonProjection {
+            source: Box::new(SelectionElement::Physical(column_id)),
+            elements,
+        },
+        _ => SelectionElement::Object(elements),
+    })
+}
+
+/// The elements selecting the subfields of the value (of a JSON type) at `path` in the column
+fn json_projection_elements(
+    json_type: &EntityType,
+    column_id: ColumnId,
+    path: &[String],
+    field: &ValidatedField,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Option<Vec<(String, SelectionElement)>> {
+    if field.subfields.is_empty() {
+        return None;
+    }
+
+    let mut elements: Vec<(String, SelectionElement)> = vec![];
+    for subfield in field.subfields.iter() {
+        if subfield.name.as_str() == "__typename" {
+            continue;
+        }
+
+        let json_field = json_type.field_by_name(&subfield.name)?;
+        if !matches!(json_field.relation, PostgresRelation::Embedded) {
+            return None;
+        }
+
+        if elements.iter().any(|(key, _)| key == &json_field.name) {
+            continue;
+        }
+
+        let mut field_path = path.to_vec();
+        field_path.push(json_field.name.clone());
+        let value = SelectionElement::JsonExtract {
+            source: Box::new(SelectionElement::Physical(column_id)),
+            path: field_path.clone(),
+        };
+
+        // A nested value selected more than once (with aliases) may have different subfields
+        // selected each time, so select it whole
+        let selected_once = field
+            .subfields
+            .iter()
+            .filter(|other| other.name == subfield.name)
+            .count()
+            == 1;
+        let nested_elements = json_type_of(&json_field.typ, subsystem)
+            .filter(|_| selected_once)
+            .and_then(|nested_type| {
+                json_projection_elements(nested_type, column_id, &field_path, subfield, subsystem)
+            });
+
+        let element = match nested_elements {
+            // The nested value may be missing in stored values, even if it is mandatory
+            Some(elements) => SelectionElement::JsonProjection {
+                source: Box::new(value),
+                elements,
+            },
+            None => value,
+        };
+        elements.push((json_field.name.clone(), element));
+    }
+
+    Some(elements)
+}
+
+/// The JSON type of a (possibly optional, but not a list) field
+fn json_type_of<'a>(
+    typ: &FieldType<PostgresFieldType<EntityType>>,
+    subsystem: &'a PostgresGraphQLSubsystem,
+) -> Option<&'a EntityType> {
+    let typ = match typ {
+        FieldType::Optional(inner) => inner.as_ref(),
+        typ => typ,
+    };
+
+    let FieldType::Plain(PostgresFieldType {
+        type_id: TypeIndex::Composite(type_index),
+        ..
+    }) = typ
+    else {
+        return None;
+    };
+
+    let json_type = &subsystem.core_subsystem.entity_types[*type_index];
+    json_type.representation.is_json_like().then_some(json_type)
+}
+
 const TRANSITIVE_VALUE_ALIAS: &str = "__transitive_value";
 
 async fn map_transitive_field<'content>(
@@ -572,10 +949,27 @@ async fn map_aggregate_field<'content>(
             RelationId::OneToMany(*relation_id),
             Box::new(nested_abstract_select.select),
         ))
+    } else if let Some(PostgresRelation::Transitive(transitive)) = &agg_field.relation {
+        // Aggregate over the entities at the end of the path (each counted once, even if
+        // reachable through multiple intermediate entities)
+        let aggregate_query = subsystem.get_aggregate_query(transitive.final_entity_id);
+        let nested_abstract_select = aggregate_query
+            .resolve_select(field, request_context, subsystem)
+            .await?;
+
+        Ok(SelectionElement::TransitiveSubSelect(
+            transitive
+                .steps
+                .iter()
+                .map(|step| step.relation_id)
+                .collect(),
+            Box::new(nested_abstract_select.select),
+        ))
     } else {
         // Reaching this point means our validation logic failed
         Err(PostgresExecutionError::Generic(
-            "Validation error: Aggregate is supported only for one-to-many".to_string(),
+            "Validation error: Aggregate is supported only for one-to-many and transitive relations"
+                .to_string(),
         ))
     }
 }
@@ -599,6 +993,149 @@ async fn map_vector_distance_field(
     }))
 }
 
+/// The string functions requested through the `transform` and `substring` arguments of a
+/// `String` field (the transform applies first)
+fn string_transforms(
+    field: &ValidatedField,
+) -> Result<Vec<StringFunction>, PostgresExecutionError> {
+    let mut transforms = vec![];
+
+    match field.arguments.get(TRANSFORM_ARG_NAME) {
+        None | Some(Val::Null) => {}
+        Some(Val::Enum(transform) | Val::String(transform)) => {
+            transforms.push(match transform.as_str() {
+                "LOWER" => StringFunction::Lower,
+                "UPPER" => StringFunction::Upper,
+                "TRIM" => StringFunction::Trim,
+                other => {
+                    return Err(PostgresExecutionError::Validation(
+                        field.name.to_string(),
+                        format!("Unknown transform '{other}'"),
+                    ));
+                }
+            })
+        }
+        Some(other) => {
+            return Err(PostgresExecutionError::Validation(
+                field.name.to_string(),
+                format!("Invalid transform '{other}'"),
+            ));
+        }
+    }
+
+    match field.arguments.get(SUBSTRING_ARG_NAME) {
+        None | Some(Val::Null) => {}
+        Some(substring) => {
+            let as_int = |key: &str| match substring.get(key) {
+                None | Some(Val::Null) => Ok(None),
+                Some(Val::Number(n)) if n.as_i64().is_some() => Ok(n.as_i64()),
+                Some(_) => Err(PostgresExecutionError::Validation(
+                    field.name.to_string(),
+                    format!("The substring '{key}' must be an integer"),
+                )),
+            };
+
+            let start = as_int("start")?.ok_or_else(|| {
+                PostgresExecutionError::Validation(
+                    field.name.to_string(),
+                    "The substring 'start' is required".to_string(),
+                )
+            })?;
+            let length = as_int("length")?;
+
+            if length.is_some_and(|length| length < 0) {
+                return Err(PostgresExecutionError::Validation(
+                    field.name.to_string(),
+                    "The substring 'length' must not be negative".to_string(),
+                ));
+            }
+
+            transforms.push(StringFunction::Substring { start, length });
+        }
+    }
+
+    Ok(transforms)
+}
+
+/// The date function requested through the `truncate`, `format`, and `timezone` arguments of a
+/// date field (if any)
+fn date_transform(
+    field: &ValidatedField,
+    column_id: ColumnId,
+) -> Result<Option<Function>, PostgresExecutionError> {
+    let string_arg = |name: &str| match field.arguments.get(name) {
+        None | Some(Val::Null) => Ok(None),
+        Some(Val::String(value)) if !value.is_empty() => {
+            Ok(Some(SQLParamContainer::string(value.clone())))
+        }
+        Some(_) => Err(PostgresExecutionError::Validation(
+            field.name.to_string(),
+            format!("The '{name}' argument must be a non-empty string"),
+        )),
+    };
+
+    let format = string_arg(FORMAT_ARG_NAME)?;
+    let timezone = string_arg(TIMEZONE_ARG_NAME)?;
+
+    let truncate = match field.arguments.get(TRUNCATE_ARG_NAME) {
+        None | Some(Val::Null) => None,
+        Some(Val::Enum(unit) | Val::String(unit)) => Some(match unit.as_str() {
+            "YEAR" => DateTruncUnit::Year,
+            "QUARTER" => DateTruncUnit::Quarter,
+            "MONTH" => DateTruncUnit::Month,
+            "WEEK" => DateTruncUnit::Week,
+            "DAY" => DateTruncUnit::Day,
+            "HOUR" => DateTruncUnit::Hour,
+            "MINUTE" => DateTruncUnit::Minute,
+            "SECOND" => DateTruncUnit::Second,
+            other => {
+                return Err(PostgresExecutionError::Validation(
+                    field.name.to_string(),
+                    format!("Unknown truncation '{other}'"),
+                ));
+            }
+        }),
+        Some(other) => {
+            return Err(PostgresExecutionError::Validation(
+                field.name.to_string(),
+                format!("Invalid truncation '{other}'"),
+            ));
+        }
+    };
+
+    if format.is_none() && truncate.is_none() {
+        // A timezone alone doesn't change the value (an instant is the same in every timezone)
+        return Ok(None);
+    }
+
+    Ok(Some(Function::DateTransform {
+        column_id,
+        truncate,
+        format,
+        timezone,
+    }))
+}
+
+async fn map_window_field<'content>(
+    window_field: &WindowField,
+    return_type: &EntityType,
+    subsystem: &'content PostgresGraphQLSubsystem,
+    request_context: &'content RequestContext<'content>,
+) -> Result<SelectionElement, PostgresExecutionError> {
+    // Compute the window only over the rows the user may read (otherwise, for example, the rank
+    // would reveal the existence of hidden rows)
+    let read_predicate =
+        check_retrieve_access(return_type.access.read, subsystem, request_context).await?;
+
+    Ok(SelectionElement::Window(Box::new(AbstractWindow {
+        table_id: return_type.table_id,
+        function: window_field.function,
+        partition_by: window_field.partition_by.clone(),
+        order_by: window_field.order_by.clone(),
+        predicate: read_predicate,
+    })))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -610,10 +1147,10 @@ mod tests {
         http::{RequestHead, RequestPayload, ResponsePayload},
         router::{PlainRequestPayload, Router},
     };
-    use core_model::types::FieldType;
     use exo_env::MapEnvironment;
     use http::Method;
     use indexmap::IndexMap;
+    use postgres_core_model::relation::ManyToManyJoin;
 
     struct DummyRequest;
 
@@ -1035,6 +1572,277 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn transitive_field_filter_and_order_by_params() {
+        use postgres_core_model::predicate::PredicateParameterTypeKind;
+        use postgres_graphql_model::order::OrderByParameterTypeKind;
+
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module Publishing {
+                @access(true)
+                type Publisher {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                    authors: Set<Author>
+                    @relationPath("authors.books")
+                    books: Set<Book>
+                }
+
+                @access(true)
+                type Author {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                    publisher: Publisher
+                    books: Set<Book>
+                }
+
+                @access(true)
+                type Book {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    author: Author
+                    @relationPath("author.publisher")
+                    publisher: Publisher
+                }
+            }
+            "#,
+            "transitive-filter.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let filter_param = |type_name: &str, param_name: &str| {
+            let (_, filter_type) = subsystem
+                .predicate_types
+                .iter()
+                .find(|(_, typ)| typ.name == type_name)
+                .unwrap_or_else(|| panic!("{type_name} not found"));
+            match &filter_type.kind {
+                PredicateParameterTypeKind::Composite { field_params, .. } => field_params
+                    .iter()
+                    .find(|param| param.name == param_name)
+                    .map(|param| {
+                        (
+                            param.typ.innermost().name.clone(),
+                            param.transitive_links.len(),
+                        )
+                    }),
+                _ => panic!("Expected a composite filter type"),
+            }
+        };
+
+        assert_eq!(
+            filter_param("PublisherFilter", "books"),
+            Some(("BookSomeFilter".to_string(), 1))
+        );
+        assert_eq!(
+            filter_param("BookFilter", "publisher"),
+            Some(("PublisherFilter".to_string(), 1))
+        );
+
+        let ordering_param = |type_name: &str, param_name: &str| {
+            let (_, ordering_type) = subsystem
+                .order_by_types
+                .iter()
+                .find(|(_, typ)| typ.name == type_name)
+                .unwrap_or_else(|| panic!("{type_name} not found"));
+            match &ordering_type.kind {
+                OrderByParameterTypeKind::Composite { parameters } => parameters
+                    .iter()
+                    .find(|param| param.name == param_name)
+                    .map(|param| param.transitive_links.len()),
+                _ => panic!("Expected a composite ordering type"),
+            }
+        };
+
+        // Only a relation path to a single entity can be ordered by
+        assert_eq!(ordering_param("BookOrdering", "publisher"), Some(1));
+        assert_eq!(ordering_param("PublisherOrdering", "books"), None);
+    }
+
+    #[tokio::test]
+    async fn transitive_aggregate_field_follows_path() {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module Publishing {
+                @access(true)
+                type Publisher {
+                    @pk id: Int = autoIncrement()
+                    authors: Set<Author>
+                    @relationPath("authors.books")
+                    books: Set<Book>
+                }
+
+                @access(true)
+                type Author {
+                    @pk id: Int = autoIncrement()
+                    publisher: Publisher
+                    books: Set<Book>
+                }
+
+                @access(true)
+                type Book {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    author: Author
+                }
+            }
+            "#,
+            "transitive-aggregate.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let (_, publisher_entity) = subsystem
+            .core_subsystem
+            .entity_types
+            .iter()
+            .find(|(_, entity)| entity.name == "Publisher")
+            .expect("Publisher entity not found");
+
+        let books_agg_field = publisher_entity
+            .aggregate_field_by_name("booksAgg")
+            .expect("booksAgg field missing");
+
+        match &books_agg_field.relation {
+            Some(PostgresRelation::Transitive(transitive)) => {
+                assert_eq!(transitive.steps.len(), 2);
+                assert_eq!(
+                    subsystem.core_subsystem.entity_types[transitive.final_entity_id].name,
+                    "Book"
+                );
+            }
+            other => panic!("Expected transitive relation, found {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn many_to_many_through_declared_join_type() {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module Library {
+                @access(true)
+                type Book {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    @manyToMany(through="BookAuthor")
+                    authors: Set<Author>
+                }
+
+                @access(true)
+                type Author {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                }
+
+                @access(true)
+                type BookAuthor {
+                    @pk @manyToOne book: Book
+                    @pk @manyToOne author: Author
+                }
+            }
+            "#,
+            "many-to-many.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let (_, book_entity) = subsystem
+            .core_subsystem
+            .entity_types
+            .iter()
+            .find(|(_, entity)| entity.name == "Book")
+            .expect("Book entity not found");
+
+        let join_field = book_entity
+            .field_by_name("_authors_join")
+            .expect("Expected generated join helper field");
+        assert!(matches!(
+            join_field.relation,
+            PostgresRelation::OneToMany(_)
+        ));
+
+        let authors_field = book_entity
+            .field_by_name("authors")
+            .expect("authors field missing");
+        match &authors_field.relation {
+            PostgresRelation::Transitive(transitive) => {
+                assert_eq!(transitive.path, vec!["_authors_join", "author"]);
+                assert_eq!(transitive.steps.len(), 2);
+                assert_eq!(transitive.many_to_many, Some(ManyToManyJoin::Declared));
+            }
+            other => panic!("Expected transitive relation, found {:?}", other),
+        }
+
+        let (_, link_type) = subsystem
+            .mutation_types
+            .iter()
+            .find(|(_, typ)| typ.name == "AuthorLinkInput")
+            .expect("Expected link input type");
+        let link_field_names: Vec<_> = link_type.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(link_field_names, vec!["connect", "disconnect"]);
+    }
+
+    #[tokio::test]
+    async fn many_to_many_generates_shared_join_type() {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module Library {
+                @access(true)
+                type Book {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    @manyToMany authors: Set<Author>
+                }
+
+                @access(true)
+                type Author {
+                    @pk id: Int = autoIncrement()
+                    name: String
+                    @manyToMany books: Set<Book>
+                }
+            }
+            "#,
+            "many-to-many.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let entity = |name: &str| {
+            subsystem
+                .core_subsystem
+                .entity_types
+                .iter()
+                .find(|(_, entity)| entity.name == name)
+                .unwrap_or_else(|| panic!("{name} entity not found"))
+        };
+
+        let (join_entity_id, join_entity) = entity("BookAuthor");
+        assert_eq!(join_entity.pk_fields().len(), 2);
+
+        for (entity_name, field_name) in [("Book", "authors"), ("Author", "books")] {
+            let field = entity(entity_name)
+                .1
+                .field_by_name(field_name)
+                .expect("many-to-many field missing");
+            match &field.relation {
+                PostgresRelation::Transitive(transitive) => {
+                    assert_eq!(transitive.many_to_many, Some(ManyToManyJoin::Generated));
+                    assert_eq!(transitive.steps[0].entity_id, join_entity_id);
+                }
+                other => panic!("Expected transitive relation, found {:?}", other),
+            }
+        }
+
+        // The generated join type is managed only through the many-to-many fields
+        assert!(subsystem.mutations.get_by_key("createBookAuthor").is_none());
+    }
+
     #[tokio::test]
     #[ignore] // TODO: Remove joinTable support - replaced with relationPath shortcuts
     async fn join_table_shortcut_creates_fields() {
@@ -1165,4 +1973,96 @@ mod tests {
             "Unexpected error message: {message}"
         );
     }
+
+    #[tokio::test]
+    async fn json_fields_project_selected_keys() {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module Customers {
+                @access(true)
+                type Customer {
+                    @pk id: Int = autoIncrement()
+                    address: Address
+                    billingAddress: Address?
+                }
+
+                @json
+                type Address {
+                    city: String
+                    geo: Geo
+                    tags: Array<Geo>
+                }
+
+                @json
+                type Geo {
+                    lat: Float
+                    lng: Float
+                }
+            }
+            "#,
+            "customers.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let (_, customer) = subsystem
+            .core_subsystem
+            .entity_types
+            .iter()
+            .find(|(_, entity)| entity.name == "Customer")
+            .unwrap();
+
+        let projection = |field_name: &str, selection: ValidatedField| {
+            let entity_field = customer.field_by_name(field_name).unwrap();
+            let PostgresRelation::Scalar { column_id, .. } = &entity_field.relation else {
+                panic!("Expected a scalar relation");
+            };
+            (
+                *column_id,
+                embedded_projection(entity_field, *column_id, &selection, &subsystem),
+            )
+        };
+
+        let (_, selection) = projection(
+            "address",
+            validated_field(
+                "address",
+                vec![
+                    validated_field("city", vec![]),
+                    validated_field("geo", vec![validated_field("lat", vec![])]),
+                    validated_field("tags", vec![validated_field("lat", vec![])]),
+                ],
+            ),
+        );
+        let is_extract = |element: &SelectionElement, expected: &[&str]| matches!(element, SelectionElement::JsonExtract { path, .. } if path == expected);
+        let Some(SelectionElement::Object(elements)) = selection else {
+            panic!("Expected an object, found {selection:?}");
+        };
+        assert_eq!(elements.len(), 3);
+        assert!(is_extract(&elements[0].1, &["city"]));
+        match &elements[1].1 {
+            SelectionElement::JsonProjection { source, elements } => {
+                assert!(is_extract(source, &["geo"]));
+                assert_eq!(elements.len(), 1);
+                assert!(is_extract(&elements[0].1, &["geo", "lat"]));
+            }
+            other => panic!("Expected JsonProjection, found {other:?}"),
+        }
+        // Lists are selected whole
+        assert!(is_extract(&elements[2].1, &["tags"]));
+
+        let (column_id, selection) = projection(
+            "billingAddress",
+            validated_field("billingAddress", vec![validated_field("city", vec![])]),
+        );
+        match selection {
+            Some(SelectionElement::JsonProjection { source, elements }) => {
+                assert!(matches!(*source, SelectionElement::Physical(id) if id == column_id));
+                assert_eq!(elements.len(), 1);
+                assert!(is_extract(&elements[0].1, &["city"]));
+            }
+            other => panic!("Expected JsonProjection, found {other:?}"),
+        }
+    }
 }
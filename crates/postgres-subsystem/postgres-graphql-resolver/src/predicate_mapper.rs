@@ -11,10 +11,12 @@ use async_trait::async_trait;
 use futures::{StreamExt, TryStreamExt};
 
 use common::context::RequestContext;
+use common::env_const::neq_includes_null;
 use common::value::Val;
 use exo_sql::{
-    AbstractPredicate, ArrayColumnType, CaseSensitivity, ColumnPath, ColumnPathLink, ParamEquality,
-    PhysicalColumnPath, PhysicalColumnType, PhysicalColumnTypeExt, Predicate, StringColumnType,
+    AbstractPredicate, ArrayColumnType, CaseSensitivity, ColumnPath, ColumnPathLink, JsonFieldPath,
+    ParamEquality, PhysicalColumnPath, PhysicalColumnType, PhysicalColumnTypeExt, Predicate,
+    StringColumnType,
 };
 
 use exo_sql::{NumericComparator, SQLParamContainer};
@@ -25,12 +27,13 @@ use postgres_graphql_model::subsystem::PostgresGraphQLSubsystem;
 
 use crate::{
     auth_util::check_retrieve_access,
-    column_path_util::to_column_path,
+    column_path_util::{to_column_path, to_transitive_column_path},
     sql_mapper::{SQLMapper, extract_and_map},
     util::{Arguments, get_argument_field},
 };
 
 use postgres_core_resolver::cast::{cast_value, literal_column_path};
+use postgres_core_resolver::opaque_id::decode_opaque_ids;
 
 use crate::util::to_pg_vector;
 
@@ -39,6 +42,10 @@ use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
 fn prefix_column_path(column_path: ColumnPath, prefix: &PhysicalColumnPath) -> ColumnPath {
     match column_path {
         ColumnPath::Physical(path) => ColumnPath::Physical(prefix.clone().join(path)),
+        ColumnPath::JsonField(json_field) => ColumnPath::JsonField(JsonFieldPath {
+            column: prefix.clone().join(json_field.column),
+            ..json_field
+        }),
         ColumnPath::Predicate(predicate) => {
             ColumnPath::Predicate(Box::new(prefix_predicate(*predicate, prefix)))
         }
@@ -148,12 +155,8 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
 
         let underlying_entity_access = if let Some(entity_id) = parameter_type.underlying_type {
             let entity_type = &subsystem.core_subsystem.entity_types[entity_id];
-            let predicate = check_retrieve_access(
-                &subsystem.core_subsystem.database_access_expressions[entity_type.access.read],
-                subsystem,
-                request_context,
-            )
-            .await?;
+            let predicate =
+                check_retrieve_access(entity_type.access.read, subsystem, request_context).await?;
 
             if predicate == AbstractPredicate::False {
                 return Err(PostgresExecutionError::Authorization);
@@ -209,12 +212,15 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                     None => panic!("Expected column path link"),
                                 };
 
+                                let leaf_column =
+                                    leaf_column_id.get_column(&subsystem.core_subsystem.database);
                                 let op_value = literal_column_path(
-                                    arg,
-                                    leaf_column_id
-                                        .get_column(&subsystem.core_subsystem.database)
-                                        .typ
-                                        .inner(),
+                                    &decode_opaque_ids(
+                                        arg,
+                                        leaf_column,
+                                        &subsystem.core_subsystem,
+                                    )?,
+                                    leaf_column.typ.inner(),
                                     false,
                                 )?;
 
@@ -253,6 +259,9 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                     })
             }
             PredicateParameterTypeKind::Operator(parameters) => {
+                let neq_includes_null = neq_includes_null(request_context.system_context.env)
+                    .map_err(|e| PostgresExecutionError::Generic(e.to_string()))?;
+
                 parameters
                     .iter()
                     .try_fold(AbstractPredicate::True, |acc, parameter| {
@@ -262,7 +271,20 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                 let arg_parameter_type =
                                     &subsystem.predicate_types[parameter.typ.innermost().type_id];
 
-                                if matches!(
+                                if NULL_CHECK_OPERATORS.contains(&parameter.name.as_str()) {
+                                    null_check_predicate(
+                                        &parameter.name,
+                                        op_value,
+                                        ColumnPath::Physical(
+                                            to_column_path(
+                                                &self.parent_column_path,
+                                                &self.param.column_path_link,
+                                            )
+                                            .unwrap(),
+                                        ),
+                                        &self.param.name,
+                                    )
+                                } else if matches!(
                                     arg_parameter_type.kind,
                                     PredicateParameterTypeKind::Vector
                                 ) {
@@ -337,13 +359,15 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                                     false,
                                                 )?;
                                                 let predicate = AbstractPredicate::ArrayContains(
-                                                    column_path,
+                                                    column_path.clone(),
                                                     op_value,
                                                 );
                                                 return Ok(match parameter.name.as_str() {
-                                                    "neq" => {
-                                                        AbstractPredicate::Not(Box::new(predicate))
-                                                    }
+                                                    "neq" => neq_predicate(
+                                                        AbstractPredicate::Not(Box::new(predicate)),
+                                                        column_path,
+                                                        neq_includes_null,
+                                                    ),
                                                     _ => predicate,
                                                 });
                                             }
@@ -401,6 +425,18 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                             _ => None,
                                         };
 
+                                    if is_opaque_id_param(self.param, subsystem)
+                                        && !OPAQUE_ID_OPERATORS.contains(&parameter.name.as_str())
+                                    {
+                                        return Err(PostgresExecutionError::Validation(
+                                            self.param.name.clone(),
+                                            format!(
+                                                "Unsupported operator '{}' for an opaque id",
+                                                parameter.name
+                                            ),
+                                        ));
+                                    }
+
                                     let (op_key_column, op_value_column) = operands(
                                         self.param,
                                         op_value,
@@ -410,10 +446,11 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                         parameter.name.as_str() == "in",
                                     )?;
 
-                                    Ok(predicate_from_name(
+                                    Ok(operator_predicate(
                                         &parameter.name,
                                         op_key_column,
                                         op_value_column,
+                                        neq_includes_null,
                                     ))
                                 }
                             }
@@ -540,9 +577,10 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                         futures::stream::iter(provided_field_params)
                             .map(Ok)
                             .try_fold(AbstractPredicate::True, |acc, (arg, parameter)| async {
-                                let new_column_path = to_column_path(
+                                let new_column_path = to_transitive_column_path(
                                     &self.parent_column_path,
                                     &self.param.column_path_link,
+                                    &self.param.transitive_links,
                                 );
                                 let child_column_path = new_column_path.clone();
 
@@ -550,7 +588,13 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                     match &parameter.column_path_link {
                                         Some(link @ ColumnPathLink::Relation(_)) => (
                                             true,
-                                            link.is_one_to_many(&subsystem.core_subsystem.database),
+                                            std::iter::once(link)
+                                                .chain(parameter.transitive_links.iter())
+                                                .any(|link| {
+                                                    link.is_one_to_many(
+                                                        &subsystem.core_subsystem.database,
+                                                    )
+                                                }),
                                         ),
                                         _ => (false, false),
                                     };
@@ -558,8 +602,7 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                 let field_access = match parameter.access {
                                     Some(ref access) => {
                                         check_retrieve_access(
-                                            &subsystem.core_subsystem.database_access_expressions
-                                                [access.read],
+                                            access.read,
                                             subsystem,
                                             request_context,
                                         )
@@ -576,13 +619,33 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
                                         self.restrict_relations
                                     };
 
-                                    let param_predicate = PredicateParamInput {
-                                        param: parameter,
-                                        parent_column_path: child_column_path,
-                                        restrict_relations: should_restrict_relations,
-                                    }
-                                    .to_sql(arg, subsystem, request_context)
-                                    .await?;
+                                    let param_predicate = match child_column_path {
+                                        // A field inside the JSON column of this parameter
+                                        Some(json_column) if parameter.embedded_field.is_some() => {
+                                            embedded_field_predicate(
+                                                parameter,
+                                                arg,
+                                                &json_column,
+                                                &[],
+                                                neq_includes_null(
+                                                    request_context.system_context.env,
+                                                )
+                                                .map_err(|e| {
+                                                    PostgresExecutionError::Generic(e.to_string())
+                                                })?,
+                                                subsystem,
+                                            )?
+                                        }
+                                        _ => {
+                                            PredicateParamInput {
+                                                param: parameter,
+                                                parent_column_path: child_column_path,
+                                                restrict_relations: should_restrict_relations,
+                                            }
+                                            .to_sql(arg, subsystem, request_context)
+                                            .await?
+                                        }
+                                    };
 
                                     let combined_param_predicate = if should_restrict_relations {
                                         if let (Some(rel_predicate), Some(column_path)) = (
@@ -629,6 +692,249 @@ impl<'a> SQLMapper<'a, AbstractPredicate> for PredicateParamInput<'a> {
     }
 }
 
+/// Map a filter on a field embedded in a JSON column. The `path` leads to the object holding the
+/// field.
+fn embedded_field_predicate(
+    param: &PredicateParameter,
+    argument: &Val,
+    json_column: &PhysicalColumnPath,
+    path: &[String],
+    neq_includes_null: bool,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let embedded_field = param
+        .embedded_field
+        .as_ref()
+        .expect("Embedded field parameter without an embedded field link");
+    let path = [path, std::slice::from_ref(&embedded_field.key)].concat();
+
+    let parameter_type = &subsystem.predicate_types[param.typ.innermost().type_id];
+
+    let field_path = |typ: &dyn PhysicalColumnType| {
+        ColumnPath::JsonField(JsonFieldPath {
+            column: json_column.clone(),
+            path: path.clone(),
+            typ: typ.clone_box(),
+        })
+    };
+
+    match (&parameter_type.kind, &embedded_field.typ) {
+        (
+            PredicateParameterTypeKind::Composite {
+                field_params,
+                logical_op_params,
+            },
+            None,
+        ) => embedded_object_predicate(
+            param,
+            field_params,
+            logical_op_params,
+            argument,
+            json_column,
+            &path,
+            neq_includes_null,
+            subsystem,
+        ),
+        (PredicateParameterTypeKind::ImplicitEqual, Some(typ)) => Ok(AbstractPredicate::eq(
+            field_path(typ.inner()),
+            literal_column_path(&enum_as_string(argument), typ.inner(), false)?,
+        )),
+        (PredicateParameterTypeKind::Operator(parameters), Some(typ)) => parameters
+            .iter()
+            .try_fold(
+                AbstractPredicate::True,
+                |acc, parameter| match get_argument_field(argument, &parameter.name) {
+                    Some(op_value) if NULL_CHECK_OPERATORS.contains(&parameter.name.as_str()) => {
+                        let new_predicate = null_check_predicate(
+                            &parameter.name,
+                            op_value,
+                            field_path(typ.inner()),
+                            &param.name,
+                        )?;
+                        Ok(AbstractPredicate::and(acc, new_predicate))
+                    }
+                    Some(op_value) => {
+                        let op_value = literal_column_path(
+                            &enum_as_string(op_value),
+                            typ.inner(),
+                            parameter.name == "in",
+                        )?;
+                        let new_predicate = operator_predicate(
+                            &parameter.name,
+                            field_path(typ.inner()),
+                            op_value,
+                            neq_includes_null,
+                        );
+                        Ok(AbstractPredicate::and(acc, new_predicate))
+                    }
+                    None => Ok(acc),
+                },
+            ),
+        _ => Err(PostgresExecutionError::Validation(
+            param.name.clone(),
+            "Unsupported filter on a field of a JSON type".into(),
+        )),
+    }
+}
+
+/// Map a filter on an object embedded in a JSON column (under `path`)
+fn embedded_object_predicate(
+    param: &PredicateParameter,
+    field_params: &[PredicateParameter],
+    logical_op_params: &[PredicateParameter],
+    argument: &Val,
+    json_column: &PhysicalColumnPath,
+    path: &[String],
+    neq_includes_null: bool,
+    subsystem: &PostgresGraphQLSubsystem,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    let object_predicate = |argument: &Val| {
+        embedded_object_predicate(
+            param,
+            field_params,
+            logical_op_params,
+            argument,
+            json_column,
+            path,
+            neq_includes_null,
+            subsystem,
+        )
+    };
+
+    let mut logical_ops = logical_op_params.iter().filter_map(|parameter| {
+        get_argument_field(argument, &parameter.name).map(|value| (parameter.name.as_str(), value))
+    });
+
+    match (logical_ops.next(), logical_ops.next()) {
+        (Some(_), Some(_)) => Err(PostgresExecutionError::Validation(
+            param.name.to_string(),
+            "Cannot specify more than one logical operation on the same level".into(),
+        )),
+        (Some(("not", value)), None) => Ok(!object_predicate(value)?),
+        (Some((logical_op_name, value)), None) => {
+            let (identity_predicate, predicate_connector): (
+                AbstractPredicate,
+                fn(AbstractPredicate, AbstractPredicate) -> AbstractPredicate,
+            ) = match logical_op_name {
+                "and" => (AbstractPredicate::True, AbstractPredicate::and),
+                _ => (AbstractPredicate::False, AbstractPredicate::or),
+            };
+
+            match value {
+                Val::List(arguments) if !arguments.is_empty() => arguments
+                    .iter()
+                    .try_fold(identity_predicate, |acc, argument| {
+                        Ok(predicate_connector(acc, object_predicate(argument)?))
+                    }),
+                _ => Err(PostgresExecutionError::Validation(
+                    param.name.clone(),
+                    "This logical operation predicate needs a non-empty list of queries".into(),
+                )),
+            }
+        }
+        (None, _) => field_params
+            .iter()
+            .try_fold(
+                AbstractPredicate::True,
+                |acc, parameter| match get_argument_field(argument, &parameter.name) {
+                    Some(arg) => Ok(AbstractPredicate::and(
+                        acc,
+                        embedded_field_predicate(
+                            parameter,
+                            arg,
+                            json_column,
+                            path,
+                            neq_includes_null,
+                            subsystem,
+                        )?,
+                    )),
+                    None => Ok(acc),
+                },
+            ),
+    }
+}
+
+/// Enum values are stored as their names in JSON values
+fn enum_as_string(value: &Val) -> Val {
+    match value {
+        Val::Enum(name) => Val::String(name.clone()),
+        Val::List(values) => Val::List(values.iter().map(enum_as_string).collect()),
+        other => other.clone(),
+    }
+}
+
+/// The operators that check if a field is null (`isNull: true` is the same as `isNotNull: false`)
+const NULL_CHECK_OPERATORS: [&str; 2] = ["isNull", "isNotNull"];
+
+/// The operators for opaque ids (ordering comparisons would let a client recover the order of the
+/// underlying values)
+const OPAQUE_ID_OPERATORS: [&str; 3] = ["eq", "neq", "in"];
+
+fn is_opaque_id_param(param: &PredicateParameter, subsystem: &PostgresGraphQLSubsystem) -> bool {
+    param.column_path_link.as_ref().is_some_and(|link| {
+        link.self_column_ids().iter().any(|column_id| {
+            column_id
+                .get_column(&subsystem.core_subsystem.database)
+                .opaque_id
+        })
+    })
+}
+
+fn null_check_predicate(
+    op_name: &str,
+    op_value: &Val,
+    column_path: ColumnPath,
+    param_name: &str,
+) -> Result<AbstractPredicate, PostgresExecutionError> {
+    match op_value {
+        Val::Bool(value) if (op_name == "isNull") == *value => {
+            Ok(AbstractPredicate::eq(column_path, ColumnPath::Null))
+        }
+        Val::Bool(_) => Ok(AbstractPredicate::neq(column_path, ColumnPath::Null)),
+        Val::Null => Ok(AbstractPredicate::True),
+        _ => Err(PostgresExecutionError::Validation(
+            param_name.to_string(),
+            format!("{op_name} expects a boolean value"),
+        )),
+    }
+}
+
+/// Map an operator to a predicate, where `neq` may also match nulls (see [`neq_predicate`])
+fn operator_predicate(
+    op_name: &str,
+    lhs: ColumnPath,
+    rhs: ColumnPath,
+    neq_includes_null: bool,
+) -> AbstractPredicate {
+    // `neq: null` is already the same as `isNotNull: true`
+    if op_name == "neq" && rhs != ColumnPath::Null {
+        neq_predicate(
+            AbstractPredicate::Neq(lhs.clone(), rhs),
+            lhs,
+            neq_includes_null,
+        )
+    } else {
+        predicate_from_name(op_name, lhs, rhs)
+    }
+}
+
+/// By default, `neq` follows SQL's three-valued logic, so a null field doesn't match (`NULL <> 5` is
+/// not true). With `EXO_NEQ_INCLUDES_NULL`, a null field is considered not equal to any value.
+fn neq_predicate(
+    predicate: AbstractPredicate,
+    column_path: ColumnPath,
+    neq_includes_null: bool,
+) -> AbstractPredicate {
+    if neq_includes_null {
+        AbstractPredicate::or(
+            predicate,
+            AbstractPredicate::eq(column_path, ColumnPath::Null),
+        )
+    } else {
+        predicate
+    }
+}
+
 /// Map predicate from GraphQL operation name to a Predicate
 pub fn predicate_from_name<C: PartialEq + ParamEquality>(
     op_name: &str,
@@ -695,7 +1001,7 @@ fn operands<'a>(
     let op_physical_column = op_physical_column_id.get_column(&subsystem.core_subsystem.database);
 
     let op_value = literal_column_path(
-        op_value,
+        &decode_opaque_ids(op_value, op_physical_column, &subsystem.core_subsystem)?,
         op_value_type.unwrap_or(op_physical_column.typ.inner()),
         unnest,
     )?;
@@ -902,7 +1208,11 @@ mod tests {
     }
 
     fn build_request_context() -> RequestContext<'static> {
-        let env = Box::leak(Box::new(MapEnvironment::new()));
+        build_request_context_with_env(MapEnvironment::new())
+    }
+
+    fn build_request_context_with_env(env: MapEnvironment) -> RequestContext<'static> {
+        let env = Box::leak(Box::new(env));
         let router = Box::leak(Box::new(DummyRouter));
         let jwt: &'static Option<common::context::JwtAuthenticator> = Box::leak(Box::new(None));
         let request = Box::leak(Box::new(DummyRequest));
@@ -1046,4 +1356,288 @@ mod tests {
 
         assert_eq!(predicate, expected);
     }
+
+    #[tokio::test]
+    async fn embedded_field_filter_maps_to_json_field_predicate() {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module TodoModule {
+                @access(true)
+                type Todo {
+                    @pk id: Int = autoIncrement()
+                    metadata: Metadata
+                }
+
+                @json
+                type Metadata {
+                    expectedEffortHrs: Int?
+                    reminder: Reminder?
+                }
+
+                @json
+                type Reminder {
+                    note: String
+                }
+            }
+            "#,
+            "todo.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let (todo_entity_id, todo_entity) = subsystem
+            .core_subsystem
+            .entity_types
+            .iter()
+            .find(|(_, entity)| entity.name.as_str() == "Todo")
+            .expect("Todo entity not found");
+
+        let metadata_column_id = subsystem
+            .core_subsystem
+            .database
+            .get_column_id(todo_entity.table_id, "metadata")
+            .unwrap();
+
+        let todos_collection_query = subsystem.get_collection_query(todo_entity_id);
+        let predicate_param = &todos_collection_query.parameters.predicate_param;
+
+        let where_filter = Val::Object(HashMap::from([(
+            "metadata".to_string(),
+            Val::Object(HashMap::from([
+                (
+                    "expectedEffortHrs".to_string(),
+                    Val::Object(HashMap::from([(
+                        "lt".to_string(),
+                        Val::Number(ValNumber::I32(2)),
+                    )])),
+                ),
+                (
+                    "reminder".to_string(),
+                    Val::Object(HashMap::from([(
+                        "note".to_string(),
+                        Val::Object(HashMap::from([(
+                            "eq".to_string(),
+                            Val::String("call".to_string()),
+                        )])),
+                    )])),
+                ),
+            ])),
+        )]));
+
+        let mut arguments: Arguments = IndexMap::new();
+        arguments.insert(predicate_param.name.clone(), where_filter);
+
+        let request_context = build_request_context();
+
+        let predicate = compute_predicate(
+            &[predicate_param],
+            &arguments,
+            &subsystem,
+            &request_context,
+            false,
+        )
+        .await
+        .expect("Predicate computation failed");
+
+        let json_field = |path: &[&str], typ: Box<dyn PhysicalColumnType>| {
+            ColumnPath::JsonField(JsonFieldPath {
+                column: PhysicalColumnPath::leaf(metadata_column_id),
+                path: path.iter().map(|key| key.to_string()).collect(),
+                typ,
+            })
+        };
+
+        let expected = AbstractPredicate::and(
+            AbstractPredicate::Lt(
+                json_field(
+                    &["expectedEffortHrs"],
+                    Box::new(exo_sql::IntColumnType {
+                        bits: exo_sql::IntBits::_32,
+                    }),
+                ),
+                ColumnPath::Param(SQLParamContainer::i32(2)),
+            ),
+            AbstractPredicate::Eq(
+                json_field(
+                    &["reminder", "note"],
+                    Box::new(StringColumnType { max_length: None }),
+                ),
+                ColumnPath::Param(SQLParamContainer::string("call".to_string())),
+            ),
+        );
+
+        assert_eq!(predicate, expected);
+    }
+
+    async fn score_predicate(
+        score_filter: Val,
+        env: MapEnvironment,
+    ) -> (AbstractPredicate, ColumnPath) {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module TrainingModule {
+                @access(true)
+                type Training {
+                    @pk id: Int = autoIncrement()
+                    score: Int?
+                }
+            }
+            "#,
+            "training.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let (training_entity_id, training_entity) = subsystem
+            .core_subsystem
+            .entity_types
+            .iter()
+            .find(|(_, entity)| entity.name.as_str() == "Training")
+            .expect("Training entity not found");
+
+        let score_column_id = subsystem
+            .core_subsystem
+            .database
+            .get_column_id(training_entity.table_id, "score")
+            .unwrap();
+
+        let trainings_collection_query = subsystem.get_collection_query(training_entity_id);
+        let predicate_param = &trainings_collection_query.parameters.predicate_param;
+
+        let mut arguments: Arguments = IndexMap::new();
+        arguments.insert(
+            predicate_param.name.clone(),
+            Val::Object(HashMap::from([("score".to_string(), score_filter)])),
+        );
+
+        let request_context = build_request_context_with_env(env);
+
+        let predicate = compute_predicate(
+            &[predicate_param],
+            &arguments,
+            &subsystem,
+            &request_context,
+            false,
+        )
+        .await
+        .expect("Predicate computation failed");
+
+        (
+            predicate,
+            ColumnPath::Physical(PhysicalColumnPath::leaf(score_column_id)),
+        )
+    }
+
+    #[tokio::test]
+    async fn null_check_filters_map_to_null_predicates() {
+        let filter = |op: &str, value: bool| {
+            Val::Object(HashMap::from([(op.to_string(), Val::Bool(value))]))
+        };
+
+        for (op, value, expect_null) in [
+            ("isNull", true, true),
+            ("isNull", false, false),
+            ("isNotNull", true, false),
+            ("isNotNull", false, true),
+        ] {
+            let (predicate, score) =
+                score_predicate(filter(op, value), MapEnvironment::new()).await;
+
+            let expected = if expect_null {
+                AbstractPredicate::Eq(score, ColumnPath::Null)
+            } else {
+                AbstractPredicate::Neq(score, ColumnPath::Null)
+            };
+            assert_eq!(predicate, expected, "{op}: {value}");
+        }
+    }
+
+    #[tokio::test]
+    async fn null_checks_only_for_optional_fields() {
+        let subsystem = create_postgres_system_from_str(
+            r#"
+            @postgres
+            module TrainingModule {
+                @access(true)
+                type Training {
+                    @pk id: Int = autoIncrement()
+                    score: Int?
+                }
+            }
+            "#,
+            "training.exo".to_string(),
+        )
+        .await
+        .expect("Failed to build subsystem");
+
+        let predicate_type = |name: &str| {
+            subsystem
+                .predicate_types
+                .iter()
+                .find(|typ| typ.name == name)
+                .unwrap_or_else(|| panic!("{name} not found"))
+        };
+        let operators = |name: &str| match &predicate_type(name).kind {
+            PredicateParameterTypeKind::Operator(parameters) => parameters
+                .iter()
+                .map(|parameter| parameter.name.clone())
+                .collect::<Vec<_>>(),
+            _ => panic!("{name} is not an operator filter"),
+        };
+
+        let PredicateParameterTypeKind::Composite { field_params, .. } =
+            &predicate_type("TrainingFilter").kind
+        else {
+            panic!("TrainingFilter is not a composite filter");
+        };
+        let field_filter = |field: &str| {
+            field_params
+                .iter()
+                .find(|param| param.name == field)
+                .unwrap()
+                .typ
+                .innermost()
+                .name
+                .clone()
+        };
+        assert_eq!(field_filter("id"), "IntFilter");
+        assert_eq!(field_filter("score"), "IntNullableFilter");
+
+        assert!(!operators("IntFilter").contains(&"isNull".to_string()));
+        let nullable_operators = operators("IntNullableFilter");
+        assert!(nullable_operators.contains(&"isNull".to_string()));
+        assert!(nullable_operators.contains(&"isNotNull".to_string()));
+        assert!(nullable_operators.contains(&"gte".to_string()));
+    }
+
+    #[tokio::test]
+    async fn neq_includes_null_only_when_enabled() {
+        let filter = || {
+            Val::Object(HashMap::from([(
+                "neq".to_string(),
+                Val::Number(ValNumber::I32(5)),
+            )]))
+        };
+        let neq = |score: ColumnPath| {
+            AbstractPredicate::Neq(score, ColumnPath::Param(SQLParamContainer::i32(5)))
+        };
+
+        let (predicate, score) = score_predicate(filter(), MapEnvironment::new()).await;
+        assert_eq!(predicate, neq(score));
+
+        let env = MapEnvironment::from(HashMap::from([(
+            "EXO_NEQ_INCLUDES_NULL".to_string(),
+            "true".to_string(),
+        )]));
+        let (predicate, score) = score_predicate(filter(), env).await;
+        assert_eq!(
+            predicate,
+            AbstractPredicate::or(
+                neq(score.clone()),
+                AbstractPredicate::Eq(score, ColumnPath::Null)
+            )
+        );
+    }
 }
@@ -10,7 +10,11 @@
 use std::sync::Arc;
 
 use crate::{
-    abstract_operation_resolver::resolve_operation, operation_resolver::OperationResolver,
+    abstract_operation_resolver::resolve_operation, batch_query::resolve_batch_query,
+    change_approval::resolve_approval_workflow, chunk_query::resolve_chunk_query,
+    field_guards::enforce_field_guards, input_validation::validate_mutation_input,
+    operation_resolver::OperationResolver, page_query::resolve_page_query,
+    similarity_query::resolve_similarity_query,
 };
 use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
 use async_trait::async_trait;
@@ -51,6 +55,39 @@ impl SubsystemGraphQLResolver for PostgresSubsystemResolver {
     ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
         let operation_name = &field.name;
 
+        if matches!(operation_type, OperationType::Query)
+            && let Some(query) = self.subsystem.similarity_queries.get_by_key(operation_name)
+        {
+            return Ok(Some(
+                resolve_similarity_query(query, field, self, request_context, system_resolver)
+                    .await?,
+            ));
+        }
+
+        if matches!(operation_type, OperationType::Query)
+            && let Some(query) = self.subsystem.batch_queries.get_by_key(operation_name)
+        {
+            return Ok(Some(
+                resolve_batch_query(query, field, self, request_context, system_resolver).await?,
+            ));
+        }
+
+        if matches!(operation_type, OperationType::Query)
+            && let Some(query) = self.subsystem.chunk_queries.get_by_key(operation_name)
+        {
+            return Ok(Some(
+                resolve_chunk_query(query, field, self, request_context).await?,
+            ));
+        }
+
+        if matches!(operation_type, OperationType::Query)
+            && let Some(query) = self.subsystem.page_queries.get_by_key(operation_name)
+        {
+            return Ok(Some(
+                resolve_page_query(query, field, self, request_context, system_resolver).await?,
+            ));
+        }
+
         let operation = match operation_type {
             OperationType::Query => match self.subsystem.pk_queries.get_by_key(operation_name) {
                 Some(query) => Some(query.resolve(field, request_context, &self.subsystem).await),
@@ -66,7 +103,12 @@ impl SubsystemGraphQLResolver for PostgresSubsystemResolver {
                             Some(query) => {
                                 Some(query.resolve(field, request_context, &self.subsystem).await)
                             }
-                            None => None,
+                            None => match self.subsystem.tree_queries.get_by_key(operation_name) {
+                                Some(query) => Some(
+                                    query.resolve(field, request_context, &self.subsystem).await,
+                                ),
+                                None => None,
+                            },
                         },
                     },
                 },
@@ -75,11 +117,36 @@ impl SubsystemGraphQLResolver for PostgresSubsystemResolver {
                 let mutation = self.subsystem.mutations.get_by_key(operation_name);
 
                 match mutation {
-                    Some(mutation) => Some(
-                        mutation
-                            .resolve(field, request_context, &self.subsystem)
-                            .await,
-                    ),
+                    Some(mutation) => {
+                        validate_mutation_input(
+                            mutation,
+                            field,
+                            self,
+                            request_context,
+                            system_resolver,
+                        )
+                        .await?;
+
+                        if let Some(response) = resolve_approval_workflow(
+                            mutation,
+                            field,
+                            self,
+                            request_context,
+                            system_resolver,
+                        )
+                        .await?
+                        {
+                            return Ok(Some(response));
+                        }
+
+                        enforce_field_guards(mutation, field, self, request_context).await?;
+
+                        Some(
+                            mutation
+                                .resolve(field, request_context, &self.subsystem)
+                                .await,
+                        )
+                    }
                     None => None,
                 }
             }
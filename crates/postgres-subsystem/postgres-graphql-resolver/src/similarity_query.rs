@@ -0,0 +1,223 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use common::context::RequestContext;
+use core_resolver::{
+    QueryResponse, system_resolver::GraphQLSystemResolver, validation::field::ValidatedField,
+};
+use exo_sql::{
+    AbstractOperation, AbstractOrderBy, AbstractOrderByExpr, AbstractPredicate,
+    AliasedSelectionElement, ColumnPath, Function, Ordering, PhysicalColumnPath, SQLParamContainer,
+    Selection, SelectionCardinality, SelectionElement,
+};
+use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_graphql_model::{
+    query::{SimilarityQuery, SimilarityQueryParameters},
+    similarity::{SIMILARITY_ITEM_FIELD, SIMILARITY_SCORE_FIELD},
+    subsystem::PostgresGraphQLSubsystem,
+};
+
+use crate::{
+    abstract_operation_resolver::execute_operation,
+    auth_util::check_retrieve_access,
+    computed_fields::apply_computed_fields_to_similarity_body,
+    operation_resolver::{OperationSelectionResolver, ResolvedSelect},
+    postgres_query::compute_select,
+    predicate_mapper::compute_predicate,
+    resolver::PostgresSubsystemResolver,
+    sql_mapper::extract_and_map,
+    util::to_pg_vector,
+};
+
+#[async_trait]
+impl OperationSelectionResolver for SimilarityQuery {
+    async fn resolve_select<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+        subsystem: &'a PostgresGraphQLSubsystem,
+    ) -> Result<ResolvedSelect<'a>, PostgresExecutionError> {
+        let SimilarityQueryParameters {
+            to_param,
+            predicate_param,
+            limit_param,
+            vector_distance_field_name,
+        } = &self.parameters;
+
+        let arguments = &field.arguments;
+
+        let return_entity_type = self.return_type.typ(&subsystem.core_subsystem.entity_types);
+        let vector_distance_field = return_entity_type
+            .vector_distance_field_by_name(vector_distance_field_name)
+            .ok_or_else(|| {
+                PostgresExecutionError::Generic(format!(
+                    "Unknown vector field '{}' on type '{}'",
+                    vector_distance_field_name, return_entity_type.name
+                ))
+            })?;
+
+        let to_arg = arguments.get(&to_param.name).ok_or_else(|| {
+            PostgresExecutionError::Validation(
+                to_param.name.clone(),
+                "Missing vector argument".into(),
+            )
+        })?;
+        let to_vector = to_pg_vector(to_arg, &to_param.name)?;
+
+        let parent_read_predicate =
+            check_retrieve_access(return_entity_type.access.read, subsystem, request_context)
+                .await?;
+        let restrict_relations = parent_read_predicate != AbstractPredicate::True;
+
+        // Ranking by a vector the user may not read would leak information about it, so restrict
+        // the candidates to those whose vector field is readable
+        let vector_read_predicate = check_retrieve_access(
+            vector_distance_field.access.read,
+            subsystem,
+            request_context,
+        )
+        .await?;
+        if vector_read_predicate == AbstractPredicate::False {
+            return Err(PostgresExecutionError::Authorization);
+        }
+
+        let base_predicate = compute_predicate(
+            &[predicate_param],
+            arguments,
+            subsystem,
+            request_context,
+            restrict_relations,
+        )
+        .await?;
+
+        let order_by = AbstractOrderBy(vec![(
+            AbstractOrderByExpr::VectorDistance(
+                ColumnPath::Physical(PhysicalColumnPath::leaf(vector_distance_field.column_id)),
+                ColumnPath::Param(SQLParamContainer::f32_array(to_vector.clone())),
+                vector_distance_field.distance_function,
+            ),
+            Ordering::Asc,
+        )]);
+
+        // Compute the select without any selection to obtain the (access-controlled) predicate;
+        // the selection is then assembled from the `item` and `score` fields
+        let mut select = compute_select(
+            AbstractPredicate::and(base_predicate, vector_read_predicate),
+            Some(order_by),
+            extract_and_map(limit_param, arguments, subsystem, request_context).await?,
+            None,
+            &self.return_type,
+            &[],
+            subsystem,
+            request_context,
+        )
+        .await?;
+
+        let mut content = vec![];
+
+        for subfield in field.subfields.iter() {
+            let element = match subfield.name.as_str() {
+                "__typename" => SelectionElement::Constant(self.return_type.type_name().to_owned()),
+                SIMILARITY_SCORE_FIELD => SelectionElement::Function(Function::VectorDistance {
+                    column_id: vector_distance_field.column_id,
+                    distance_function: vector_distance_field.distance_function,
+                    target: SQLParamContainer::f32_array(to_vector.clone()),
+                }),
+                SIMILARITY_ITEM_FIELD => {
+                    let item_select = compute_select(
+                        AbstractPredicate::True,
+                        None,
+                        None,
+                        None,
+                        &self.return_type,
+                        &subfield.subfields,
+                        subsystem,
+                        request_context,
+                    )
+                    .await?;
+
+                    // Field-level access on the selected fields may further restrict the entities
+                    select.predicate =
+                        AbstractPredicate::and(select.predicate, item_select.predicate);
+
+                    match item_select.selection {
+                        Selection::Json(item_content, _) => SelectionElement::Object(
+                            item_content
+                                .into_iter()
+                                .map(AliasedSelectionElement::into_parts)
+                                .collect(),
+                        ),
+                        Selection::Seq(_) => {
+                            return Err(PostgresExecutionError::Generic(
+                                "Unexpected selection for a similarity query item".into(),
+                            ));
+                        }
+                    }
+                }
+                _ => {
+                    return Err(PostgresExecutionError::Generic(format!(
+                        "Unknown field '{}' on type '{}'",
+                        subfield.name,
+                        self.return_type.type_name()
+                    )));
+                }
+            };
+
+            content.push(AliasedSelectionElement::new(
+                subfield.output_name(),
+                element,
+            ));
+        }
+
+        select.selection = Selection::Json(content, SelectionCardinality::Many);
+
+        Ok(ResolvedSelect {
+            select,
+            return_type: &self.return_type,
+        })
+    }
+}
+
+/// Resolve a similarity query. Unlike other queries, the entities are nested under the `item`
+/// field, so computed fields need to be applied to those (instead of the top-level elements).
+pub(crate) async fn resolve_similarity_query<'e>(
+    query: &'e SimilarityQuery,
+    field: &'e ValidatedField,
+    subsystem_resolver: &'e PostgresSubsystemResolver,
+    request_context: &'e RequestContext<'e>,
+    system_resolver: &'e GraphQLSystemResolver,
+) -> Result<QueryResponse, PostgresExecutionError> {
+    let subsystem = &subsystem_resolver.subsystem;
+
+    let resolved = query
+        .resolve_select(field, request_context, subsystem)
+        .await?;
+
+    let mut response = execute_operation(
+        AbstractOperation::Select(resolved.select),
+        subsystem_resolver,
+        request_context,
+    )
+    .await?;
+
+    apply_computed_fields_to_similarity_body(
+        &mut response.body,
+        query
+            .return_type
+            .typ(&subsystem.core_subsystem.entity_types),
+        field,
+        subsystem_resolver,
+        system_resolver,
+        request_context,
+    )
+    .await?;
+
+    Ok(response)
+}
@@ -14,15 +14,18 @@ use common::context::RequestContext;
 use common::value::Val;
 use core_model::types::OperationReturnType;
 use core_resolver::access_solver::AccessInput;
-use core_resolver::access_solver::AccessSolver;
 use exo_sql::{
     AbstractDelete, AbstractInsert, AbstractPredicate, AbstractSelect, AbstractUpdate, Column,
-    ColumnId, ColumnPath, ManyToOne, NestedAbstractDelete, NestedAbstractInsert,
-    NestedAbstractInsertSet, NestedAbstractUpdate, OneToMany, PhysicalColumnPath, Selection,
+    ColumnId, ColumnPath, ColumnValuePair, InsertionElement, InsertionRow, ManyToOne,
+    NestedAbstractDelete, NestedAbstractInsert, NestedAbstractInsertSet, NestedAbstractUpdate,
+    OneToMany, PhysicalColumnPath, RelationId, Selection,
 };
 use futures::StreamExt;
 use postgres_core_model::{
-    relation::{ManyToOneRelation, OneToManyRelation, PostgresRelation},
+    relation::{
+        ManyToManyJoin, ManyToOneRelation, OneToManyRelation, PostgresRelation, TransitiveRelation,
+        TransitiveRelationStep,
+    },
     types::{EntityType, PostgresType, TypeIndex, base_type},
 };
 use postgres_graphql_model::{
@@ -35,7 +38,10 @@ use crate::{
     util::get_argument_field,
 };
 
-use postgres_core_resolver::{cast, postgres_execution_error::PostgresExecutionError};
+use postgres_core_resolver::{
+    access::database_solver::solve_database_access, cast, opaque_id::decode_opaque_ids,
+    postgres_execution_error::PostgresExecutionError,
+};
 
 pub struct UpdateOperation<'a> {
     pub data_param: &'a DataParameter,
@@ -54,7 +60,7 @@ impl<'a> SQLMapper<'a, AbstractUpdate> for UpdateOperation<'a> {
     ) -> Result<AbstractUpdate, PostgresExecutionError> {
         let data_type = &subsystem.mutation_types[self.data_param.typ.innermost().type_id];
 
-        let self_update_columns = compute_update_columns(data_type, argument, subsystem);
+        let self_update_columns = compute_update_columns(data_type, argument, subsystem)?;
 
         let return_type = &subsystem.core_subsystem.entity_types[self.return_type.typ_id()];
         let table_id = return_type.table_id;
@@ -85,17 +91,19 @@ fn compute_update_columns<'a>(
     data_type: &'a MutationType,
     argument: &'a Val,
     subsystem: &'a PostgresGraphQLSubsystem,
-) -> Vec<(ColumnId, Column)> {
-    data_type
+) -> Result<Vec<(ColumnId, Column)>, PostgresExecutionError> {
+    let field_columns = data_type
         .fields
         .iter()
-        .flat_map(|field| match &field.relation {
+        .map(|field| match &field.relation {
             PostgresRelation::Scalar { column_id, .. } => get_argument_field(argument, &field.name)
                 .iter()
-                .map(|argument_value| {
+                .map(|argument_value| -> Result<_, PostgresExecutionError> {
                     let column = column_id.get_column(&subsystem.core_subsystem.database);
-                    let value_column = cast::literal_column(argument_value, column);
-                    (*column_id, value_column.unwrap())
+                    let argument_value =
+                        decode_opaque_ids(argument_value, column, &subsystem.core_subsystem)?;
+                    let value_column = cast::literal_column(&argument_value, column);
+                    Ok((*column_id, value_column.unwrap()))
                 })
                 .collect(),
 
@@ -114,7 +122,7 @@ fn compute_update_columns<'a>(
                 column_pairs
                     .iter()
                     .zip(foreign_pk_field_ids.iter())
-                    .flat_map(|(column_pair, foreign_pk_field_id)| {
+                    .filter_map(|(column_pair, foreign_pk_field_id)| {
                         let self_column_id = column_pair.self_column_id;
 
                         let self_column =
@@ -124,16 +132,26 @@ fn compute_update_columns<'a>(
                             .name;
 
                         match get_argument_field(argument, &field.name) {
-                            Some(Val::Null) => Some((self_column_id, Column::Null)), // `{..., foreign_field: null}` means set the column to null
+                            Some(Val::Null) => Some(Ok((self_column_id, Column::Null))), // `{..., foreign_field: null}` means set the column to null
                             Some(argument_value) => {
                                 // `{..., foreign_field: { id: 1 }}` means set the column to the id of the nested object
                                 match get_argument_field(argument_value, foreign_type_pk_field_name)
                                 {
-                                    Some(foreign_type_pk_arg) => {
-                                        let value_column =
-                                            cast::literal_column(foreign_type_pk_arg, self_column);
-                                        Some((self_column_id, value_column.unwrap()))
-                                    }
+                                    Some(foreign_type_pk_arg) => Some(
+                                        decode_opaque_ids(
+                                            foreign_type_pk_arg,
+                                            self_column,
+                                            &subsystem.core_subsystem,
+                                        )
+                                        .map(|foreign_type_pk_arg| {
+                                            let value_column = cast::literal_column(
+                                                &foreign_type_pk_arg,
+                                                self_column,
+                                            );
+                                            (self_column_id, value_column.unwrap())
+                                        })
+                                        .map_err(PostgresExecutionError::from),
+                                    ),
                                     None => unreachable!("Expected pk argument"), // Validation should have caught this
                                 }
                             }
@@ -142,14 +160,17 @@ fn compute_update_columns<'a>(
                     })
                     .collect()
             }
-            PostgresRelation::OneToMany { .. } => vec![],
+            PostgresRelation::OneToMany { .. } => Ok(vec![]),
             PostgresRelation::Embedded => {
                 panic!("Embedded relations cannot be used in update operations")
             }
-            PostgresRelation::Computed(_) => vec![],
-            PostgresRelation::Transitive(_) => vec![],
+            PostgresRelation::Computed(_) => Ok(vec![]),
+            PostgresRelation::SqlExpression(_) => Ok(vec![]),
+            PostgresRelation::Transitive(_) => Ok(vec![]),
         })
-        .collect()
+        .collect::<Result<Vec<Vec<_>>, PostgresExecutionError>>()?;
+
+    Ok(field_columns.into_iter().flatten().collect())
 }
 
 // A bit hacky way. Ideally, the nested parameter should have the same shape as the container type. Specifically, it should have
@@ -175,6 +196,27 @@ async fn compute_nested_ops<'a>(
     let mut nested_deletes = vec![];
 
     for field in arg_type.fields.iter() {
+        if let PostgresRelation::Transitive(
+            transitive @ TransitiveRelation {
+                many_to_many: Some(join),
+                ..
+            },
+        ) = &field.relation
+        {
+            if let Some(argument) = get_argument_field(arg, &field.name) {
+                let (nested_insert_set, deletes) = compute_many_to_many_ops(
+                    transitive,
+                    *join,
+                    argument,
+                    subsystem,
+                    request_context,
+                )
+                .await?;
+                nested_insert_sets.push(nested_insert_set);
+                nested_deletes.extend(deletes);
+            }
+        }
+
         if let PostgresRelation::OneToMany(OneToManyRelation { relation_id, .. }) = &field.relation
         {
             let nested_relation = &relation_id.deref(&subsystem.core_subsystem.database);
@@ -303,7 +345,7 @@ async fn compute_nested_update_object_arg<'a>(
 
     let table_id = subsystem.core_subsystem.entity_types[field_entity_type.entity_id].table_id;
 
-    let nested = compute_update_columns(field_entity_type, argument, subsystem);
+    let nested = compute_update_columns(field_entity_type, argument, subsystem)?;
     let (pk_columns, nested): (Vec<_>, Vec<_>) = nested.into_iter().partition(|elem| {
         let column = elem.0.get_column(&subsystem.core_subsystem.database);
         column.is_pk
@@ -344,6 +386,7 @@ async fn compute_nested_update_object_arg<'a>(
                 order_by: None,
                 offset: None,
                 limit: None,
+                lock: None,
             },
             nested_updates: vec![],
             nested_inserts: vec![],
@@ -395,6 +438,7 @@ async fn compute_nested_inserts<'a>(
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 },
             },
         })
@@ -436,16 +480,14 @@ async fn compute_nested_inserts<'a>(
     }?;
 
     let access_predicate = match field_entity_type.database_access {
-        Some(access_expr_index) => subsystem
-            .core_subsystem
-            .solve(
+        Some(access_expr_index) => {
+            solve_database_access(
+                &subsystem.core_subsystem,
                 request_context,
-                None,
-                &subsystem.core_subsystem.database_access_expressions[access_expr_index],
+                access_expr_index,
             )
             .await?
-            .map(|expr| expr.0)
-            .resolve(),
+        }
         None => AbstractPredicate::True,
     };
 
@@ -508,7 +550,7 @@ async fn compute_nested_delete_object_arg<'a>(
 ) -> Result<NestedAbstractDelete, PostgresExecutionError> {
     assert!(matches!(argument, Val::Object(..)));
 
-    let nested = compute_update_columns(field_mutation_type, argument, subsystem);
+    let nested = compute_update_columns(field_mutation_type, argument, subsystem)?;
     let (pk_columns, _nested): (Vec<_>, Vec<_>) = nested.into_iter().partition(|elem| {
         let column = elem.0.get_column(&subsystem.core_subsystem.database);
         column.is_pk
@@ -570,12 +612,212 @@ async fn compute_nested_delete_object_arg<'a>(
                 order_by: None,
                 offset: None,
                 limit: None,
+                lock: None,
             },
             precheck_predicates: vec![precheck_predicate],
         },
     })
 }
 
+/// Connect and disconnect the entities of a many-to-many field (the `connect` and `disconnect` fields
+/// of the argument) by inserting and deleting rows of its join entity
+async fn compute_many_to_many_ops<'a>(
+    transitive: &'a TransitiveRelation,
+    join: ManyToManyJoin,
+    argument: &'a Val,
+    subsystem: &'a PostgresGraphQLSubsystem,
+    request_context: &'a RequestContext<'a>,
+) -> Result<(NestedAbstractInsertSet, Vec<NestedAbstractDelete>), PostgresExecutionError> {
+    let database = &subsystem.core_subsystem.database;
+
+    let (join_step, join_relation_id, link_step) = match &transitive.steps[..] {
+        [
+            join_step @ TransitiveRelationStep {
+                relation_id: RelationId::OneToMany(join_relation_id),
+                ..
+            },
+            link_step @ TransitiveRelationStep {
+                relation_id: RelationId::ManyToOne(_),
+                ..
+            },
+        ] => (join_step, join_relation_id, link_step),
+        _ => {
+            return Err(PostgresExecutionError::Generic(
+                "A many-to-many field must go through the rows of a join entity".to_string(),
+            ));
+        }
+    };
+    let nesting_relation = join_relation_id.deref(database);
+
+    let join_entity = &subsystem.core_subsystem.entity_types[join_step.entity_id];
+    let Some(PostgresRelation::ManyToOne {
+        relation:
+            ManyToOneRelation {
+                foreign_pk_field_ids,
+                relation_id: link_relation_id,
+                ..
+            },
+        ..
+    }) = join_entity
+        .field_by_name(&link_step.field_name)
+        .map(|field| &field.relation)
+    else {
+        return Err(PostgresExecutionError::Generic(format!(
+            "Join entity '{}' has no relation '{}'",
+            join_entity.name, link_step.field_name
+        )));
+    };
+    let ManyToOne { column_pairs, .. } = link_relation_id.deref(database);
+
+    // The columns of the join entity's row that link to the referenced entity (`{id: 2}` becomes `author_id = 2`)
+    let link_columns =
+        |reference: &Val| -> Result<Vec<(ColumnId, Column)>, PostgresExecutionError> {
+            column_pairs
+                .iter()
+                .zip(foreign_pk_field_ids.iter())
+                .map(|(column_pair, foreign_pk_field_id)| {
+                    let pk_field_name = &foreign_pk_field_id
+                        .resolve(&subsystem.core_subsystem.entity_types)
+                        .name;
+                    let value = get_argument_field(reference, pk_field_name).ok_or_else(|| {
+                        PostgresExecutionError::MissingArgument(pk_field_name.clone())
+                    })?;
+                    let column = column_pair.self_column_id.get_column(database);
+                    let value = decode_opaque_ids(value, column, &subsystem.core_subsystem)?;
+                    Ok((
+                        column_pair.self_column_id,
+                        cast::literal_column(&value, column)?,
+                    ))
+                })
+                .collect()
+        };
+
+    // Rows of a declared join entity are subject to its access rules (a generated one is reachable
+    // only through this field, so the update access of the field's entity governs it)
+    let check_join_access = |kind: SQLOperationKind, row: Val| async move {
+        match join {
+            ManyToManyJoin::Declared => {
+                let input_value = AccessInput {
+                    value: &row,
+                    ignore_missing_value: true,
+                    aliases: HashMap::new(),
+                };
+                let AccessCheckOutcome {
+                    precheck_predicate,
+                    entity_predicate,
+                    ..
+                } = check_access(
+                    join_entity,
+                    &[],
+                    &kind,
+                    subsystem,
+                    request_context,
+                    Some(&input_value),
+                )
+                .await?;
+                Ok::<_, PostgresExecutionError>((precheck_predicate, entity_predicate))
+            }
+            ManyToManyJoin::Generated => Ok((AbstractPredicate::True, AbstractPredicate::True)),
+        }
+    };
+    let access_row = |reference: &Val| {
+        Val::Object(HashMap::from([(
+            link_step.field_name.clone(),
+            reference.clone(),
+        )]))
+    };
+
+    let references = |name: &str| match get_argument_field(argument, name) {
+        Some(Val::List(references)) => references.iter().collect::<Vec<_>>(),
+        Some(reference @ Val::Object(..)) => vec![reference],
+        _ => vec![],
+    };
+
+    let mut rows = vec![];
+    let mut precheck_predicates = vec![];
+    for reference in references("connect") {
+        let (precheck_predicate, _) =
+            check_join_access(SQLOperationKind::Create, access_row(reference)).await?;
+        precheck_predicates.push(precheck_predicate);
+
+        rows.push(InsertionRow {
+            elems: link_columns(reference)?
+                .into_iter()
+                .map(|(column, value)| {
+                    InsertionElement::SelfInsert(ColumnValuePair::new(column, value))
+                })
+                .collect(),
+        });
+    }
+
+    let join_table_id = join_entity.table_id;
+    let empty_selection = || AbstractSelect {
+        table_id: join_table_id,
+        selection: Selection::Seq(vec![]),
+        predicate: AbstractPredicate::True,
+        order_by: None,
+        offset: None,
+        limit: None,
+        lock: None,
+    };
+
+    let inserts = if rows.is_empty() {
+        vec![]
+    } else {
+        vec![NestedAbstractInsert {
+            relation_column_ids: nesting_relation
+                .column_pairs
+                .iter()
+                .map(|pair| pair.foreign_column_id)
+                .collect(),
+            insert: AbstractInsert {
+                table_id: join_table_id,
+                rows,
+                precheck_predicates,
+                selection: empty_selection(),
+            },
+        }]
+    };
+
+    let mut deletes = vec![];
+    for reference in references("disconnect") {
+        let (precheck_predicate, entity_predicate) =
+            check_join_access(SQLOperationKind::Delete, access_row(reference)).await?;
+
+        let link_predicate = link_columns(reference)?.into_iter().fold(
+            AbstractPredicate::True,
+            |acc, (column_id, value)| {
+                let value = match value {
+                    Column::Param(value) => ColumnPath::Param(value),
+                    _ => ColumnPath::Null,
+                };
+                AbstractPredicate::and(
+                    acc,
+                    AbstractPredicate::eq(
+                        ColumnPath::Physical(PhysicalColumnPath::leaf(column_id)),
+                        value,
+                    ),
+                )
+            },
+        );
+
+        deletes.push(NestedAbstractDelete {
+            nesting_relation: nesting_relation.clone(),
+            delete: AbstractDelete {
+                table_id: join_table_id,
+                predicate: AbstractPredicate::and(link_predicate, entity_predicate),
+                selection: empty_selection(),
+                precheck_predicates: vec![precheck_predicate],
+            },
+        });
+    }
+
+    Ok((
+        NestedAbstractInsertSet::new(inserts, AbstractPredicate::True),
+        deletes,
+    ))
+}
+
 fn extract_argument<'a>(
     argument: &'a Val,
     arg_type: &'a MutationType,
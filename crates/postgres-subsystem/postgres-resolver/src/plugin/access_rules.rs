@@ -0,0 +1,27 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The access rules of the entities, for the access coverage report.
+
+use std::sync::Arc;
+
+use common::access_coverage::{AccessCoverage, RuleCoverage};
+use core_plugin_interface::interface::SubsystemAccessRules;
+use postgres_core_model::subsystem::PostgresCoreSubsystem;
+use postgres_core_resolver::access::coverage::access_coverage;
+
+pub struct PostgresAccessRules {
+    pub core_subsystem: Arc<PostgresCoreSubsystem>,
+}
+
+impl SubsystemAccessRules for PostgresAccessRules {
+    fn coverage(&self, recorded: &AccessCoverage) -> Vec<RuleCoverage> {
+        access_coverage(&self.core_subsystem, recorded)
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Database status for the startup report: connectivity, round-trip latency, and pool usage.
+
+use std::{sync::Arc, time::Instant};
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use core_plugin_interface::interface::SubsystemDiagnostics;
+use exo_sql::DatabaseExecutor;
+
+pub struct DatabaseDiagnostics {
+    pub executor: Arc<DatabaseExecutor>,
+}
+
+#[async_trait]
+impl SubsystemDiagnostics for DatabaseDiagnostics {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    async fn diagnose(&self) -> Value {
+        let start = Instant::now();
+
+        let result = match self.executor.database_client.get_client().await {
+            Ok(client) => client
+                .query("SELECT 1", &[])
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        #[cfg_attr(not(feature = "network"), allow(unused_mut))]
+        let mut status = match result {
+            Ok(_) => json!({
+                "connected": true,
+                "latency_ms": start.elapsed().as_millis() as u64,
+            }),
+            Err(error) => json!({
+                "connected": false,
+                "error": error,
+            }),
+        };
+
+        #[cfg(feature = "network")]
+        if let Some(pool) = self.executor.database_client.pool_status() {
+            status["pool"] = json!({
+                "max_size": pool.max_size,
+                "size": pool.size,
+                "available": pool.available,
+                "waiting": pool.waiting,
+                "flushes": pool.flushes,
+                "secs_since_last_flush": pool.since_last_flush.map(|elapsed| elapsed.as_secs()),
+            });
+        }
+
+        status
+    }
+}
+
+#[cfg(all(test, feature = "network"))]
+mod tests {
+    use exo_sql::{DatabaseClientManager, ExecutionConfig, TransactionMode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn unreachable_database() {
+        // Nothing listens on port 1, so connecting fails right away
+        let database_client = DatabaseClientManager::from_url(
+            "postgres://localhost:1/exo_diagnostics",
+            false,
+            Some(3),
+            TransactionMode::ReadWrite,
+        )
+        .await
+        .unwrap();
+        let diagnostics = DatabaseDiagnostics {
+            executor: Arc::new(DatabaseExecutor {
+                database_client,
+                replica_client: None,
+                config: ExecutionConfig::default(),
+            }),
+        };
+
+        let status = diagnostics.diagnose().await;
+
+        assert_eq!(diagnostics.name(), "database");
+        assert_eq!(status["connected"], false);
+        assert!(
+            status["error"]
+                .as_str()
+                .is_some_and(|error| !error.is_empty())
+        );
+        assert!(status.get("latency_ms").is_none());
+        assert_eq!(status["pool"]["max_size"], 3);
+        assert_eq!(status["pool"]["size"], 0);
+    }
+}
@@ -7,4 +7,12 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod access_rules;
+#[cfg(not(target_family = "wasm"))]
+pub mod diagnostics;
+#[cfg(not(target_family = "wasm"))]
+pub mod partition_service;
+#[cfg(not(target_family = "wasm"))]
+pub mod retention_service;
+pub mod schema_drift;
 pub mod subsystem_loader;
@@ -0,0 +1,89 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Creates upcoming partitions of the range-partitioned tables (declared with `@partitionBy`).
+//!
+//! Runs at startup and then every `EXO_PARTITION_MAINTENANCE_INTERVAL` seconds, so that the
+//! partitions for the current and the next few periods always exist. Rows that don't fall into
+//! any partition go to the default partition, so a missed run doesn't fail inserts.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tracing::{debug, error};
+
+use common::env_const::EXO_PARTITION_MAINTENANCE_INTERVAL;
+use core_plugin_interface::interface::{SubsystemBackgroundService, SystemRouterRef};
+use exo_env::Environment;
+use exo_sql::{Database, DatabaseExecutor, SchemaObjectName, database_error::DatabaseError};
+
+const DEFAULT_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+pub struct PartitionMaintenanceService {
+    /// Table name and the statement that creates its upcoming partitions
+    statements: Vec<(SchemaObjectName, String)>,
+    executor: Arc<DatabaseExecutor>,
+}
+
+#[async_trait]
+impl SubsystemBackgroundService for PartitionMaintenanceService {
+    async fn run(&self, _router: SystemRouterRef, env: Arc<dyn Environment>) {
+        let interval_secs = env
+            .get(EXO_PARTITION_MAINTENANCE_INTERVAL)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+        if interval_secs == 0 {
+            debug!("Partition maintenance is disabled");
+            return;
+        }
+
+        loop {
+            for (table_name, statement) in self.statements.iter() {
+                match self.execute(statement).await {
+                    Ok(()) => debug!(
+                        "Created upcoming partitions of {}",
+                        table_name.fully_qualified_name()
+                    ),
+                    Err(e) => error!(
+                        "Failed to create upcoming partitions of {}: {e}",
+                        table_name.fully_qualified_name()
+                    ),
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        }
+    }
+}
+
+impl PartitionMaintenanceService {
+    /// Returns `None` if no (managed) table needs maintenance
+    pub fn new(database: &Database, executor: Arc<DatabaseExecutor>) -> Option<Self> {
+        let statements: Vec<_> = database
+            .tables()
+            .into_iter()
+            .filter(|(_, table)| table.managed)
+            .filter_map(|(_, table)| {
+                let statement = table.partition.as_ref()?.maintenance_sql(&table.name)?;
+                Some((table.name.clone(), statement))
+            })
+            .collect();
+
+        (!statements.is_empty()).then_some(Self {
+            statements,
+            executor,
+        })
+    }
+
+    async fn execute(&self, statement: &str) -> Result<(), DatabaseError> {
+        let client = self.executor.database_client.get_client().await?;
+        Ok(client.batch_execute(statement).await?)
+    }
+}
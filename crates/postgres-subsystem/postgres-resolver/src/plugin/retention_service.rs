@@ -0,0 +1,180 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Periodically purges expired rows of the types annotated with `@retention`.
+//!
+//! Every `EXO_RETENTION_INTERVAL` seconds, each policy is run in batches of
+//! `EXO_RETENTION_BATCH_SIZE` rows (unless the annotation specifies `batchSize`) until no expired
+//! rows remain, pausing `EXO_RETENTION_BATCH_DELAY_MS` milliseconds between batches to limit the
+//! load on the database.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tracing::{debug, error, info};
+
+use common::clock::Clock;
+use common::env_const::{
+    EXO_RETENTION_BATCH_DELAY_MS, EXO_RETENTION_BATCH_SIZE, EXO_RETENTION_INTERVAL,
+};
+use core_plugin_interface::interface::{SubsystemBackgroundService, SystemRouterRef};
+use exo_env::Environment;
+use exo_sql::{DatabaseExecutor, database_error::DatabaseError};
+use postgres_core_model::{retention::RetentionPolicy, subsystem::PostgresCoreSubsystem};
+use postgres_core_resolver::retention::{RetentionStats, purge_batch, purge_statement};
+
+const DEFAULT_INTERVAL_SECS: u64 = 3600;
+const DEFAULT_BATCH_SIZE: usize = 1000;
+const DEFAULT_BATCH_DELAY_MS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionConfig {
+    /// `None` if scheduled purging is disabled
+    pub interval: Option<Duration>,
+    pub batch_size: usize,
+    pub batch_delay: Duration,
+}
+
+impl RetentionConfig {
+    pub fn from_env(env: &dyn Environment) -> Self {
+        let interval_secs = env
+            .get(EXO_RETENTION_INTERVAL)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+        Self {
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+            batch_size: env
+                .get(EXO_RETENTION_BATCH_SIZE)
+                .and_then(|s| s.parse().ok())
+                .filter(|size| *size > 0)
+                .unwrap_or(DEFAULT_BATCH_SIZE),
+            batch_delay: Duration::from_millis(
+                env.get(EXO_RETENTION_BATCH_DELAY_MS)
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(DEFAULT_BATCH_DELAY_MS),
+            ),
+        }
+    }
+}
+
+pub struct RetentionService {
+    core_subsystem: Arc<PostgresCoreSubsystem>,
+    executor: Arc<DatabaseExecutor>,
+    stats: Arc<RetentionStats>,
+}
+
+#[async_trait]
+impl SubsystemBackgroundService for RetentionService {
+    async fn run(&self, _router: SystemRouterRef, env: Arc<dyn Environment>) {
+        let config = RetentionConfig::from_env(env.as_ref());
+        let clock = Clock::from_env(env.as_ref()).unwrap_or_else(|e| {
+            error!("{e}, using the system time to purge expired rows");
+            Clock::system()
+        });
+
+        let Some(interval) = config.interval else {
+            debug!("Scheduled purging of expired rows is disabled");
+            return;
+        };
+
+        loop {
+            for (index, policy) in self.core_subsystem.retention_policies.iter().enumerate() {
+                match self.purge(policy, &config, &clock).await {
+                    Ok(purged) => {
+                        if purged > 0 {
+                            info!(
+                                "Purged {purged} expired row(s) of '{}' (older than {} days)",
+                                policy.entity_name, policy.days
+                            );
+                        }
+                        self.stats.record_run(index, purged, clock.now());
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to purge expired rows of '{}': {e}",
+                            policy.entity_name
+                        );
+                        self.stats.record_failure(index);
+                    }
+                }
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+impl RetentionService {
+    pub fn new(
+        core_subsystem: Arc<PostgresCoreSubsystem>,
+        executor: Arc<DatabaseExecutor>,
+        stats: Arc<RetentionStats>,
+    ) -> Self {
+        Self {
+            core_subsystem,
+            executor,
+            stats,
+        }
+    }
+
+    /// Purge all expired rows for the policy, returning the number of rows purged
+    async fn purge(
+        &self,
+        policy: &RetentionPolicy,
+        config: &RetentionConfig,
+        clock: &Clock,
+    ) -> Result<u64, DatabaseError> {
+        let statement = purge_statement(policy, &self.core_subsystem.database);
+        let batch_size = policy.batch_size.unwrap_or(config.batch_size);
+
+        let mut purged = 0;
+
+        loop {
+            // Get a client per batch to return the connection to the pool while pausing
+            let client = self.executor.database_client.get_client().await?;
+            let batch_purged =
+                purge_batch(&client, &statement, policy, batch_size, clock.now()).await?;
+            drop(client);
+
+            purged += batch_purged;
+
+            if batch_purged < batch_size as u64 {
+                return Ok(purged);
+            }
+
+            tokio::time::sleep(config.batch_delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    #[test]
+    fn config_from_env() {
+        let config = RetentionConfig::from_env(&MapEnvironment::new());
+        assert_eq!(config.interval, Some(Duration::from_secs(3600)));
+        assert_eq!(config.batch_size, 1000);
+        assert_eq!(config.batch_delay, Duration::from_millis(100));
+
+        let mut env = MapEnvironment::new();
+        env.set(EXO_RETENTION_INTERVAL, "0");
+        env.set(EXO_RETENTION_BATCH_SIZE, "250");
+        env.set(EXO_RETENTION_BATCH_DELAY_MS, "0");
+
+        let config = RetentionConfig::from_env(&env);
+        assert_eq!(config.interval, None);
+        assert_eq!(config.batch_size, 250);
+        assert_eq!(config.batch_delay, Duration::ZERO);
+    }
+}
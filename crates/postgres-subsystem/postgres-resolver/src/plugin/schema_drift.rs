@@ -0,0 +1,134 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Optional startup check that the database schema matches the model.
+//!
+//! Controlled by `EXO_SCHEMA_DRIFT_CHECK`:
+//! - `off` (default): skip the check
+//! - `warn`: log every difference and continue
+//! - `strict`: log every difference and refuse to start
+//!
+//! If `EXO_SCHEMA_DRIFT_REPORT` is set, the report is also written as JSON to that path (even if
+//! no drift was found, so that deployment tooling can rely on its presence).
+
+use common::env_const::{EXO_SCHEMA_DRIFT_CHECK, EXO_SCHEMA_DRIFT_REPORT};
+use core_plugin_interface::interface::SubsystemLoadingError;
+use exo_env::{EnvError, Environment};
+use exo_sql::{
+    Database, DatabaseExecutor,
+    schema::{
+        migration::{DriftReport, Migration},
+        spec::MigrationScope,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaDriftCheck {
+    Off,
+    Warn,
+    Strict,
+}
+
+pub fn schema_drift_check(env: &dyn Environment) -> Result<SchemaDriftCheck, EnvError> {
+    match env.get(EXO_SCHEMA_DRIFT_CHECK) {
+        Some(value) => match value.to_lowercase().as_str() {
+            "off" | "false" => Ok(SchemaDriftCheck::Off),
+            "warn" => Ok(SchemaDriftCheck::Warn),
+            "strict" => Ok(SchemaDriftCheck::Strict),
+            _ => Err(EnvError::InvalidEnum {
+                env_key: EXO_SCHEMA_DRIFT_CHECK,
+                env_value: value,
+                message: "Must be set to either off, warn, or strict".to_string(),
+            }),
+        },
+        None => Ok(SchemaDriftCheck::Off),
+    }
+}
+
+pub async fn check_schema_drift(
+    executor: &DatabaseExecutor,
+    database: &Database,
+    env: &dyn Environment,
+) -> Result<(), SubsystemLoadingError> {
+    let mode =
+        schema_drift_check(env).map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?;
+
+    if mode == SchemaDriftCheck::Off {
+        return Ok(());
+    }
+
+    let client = executor
+        .database_client
+        .get_client()
+        .await
+        .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?;
+
+    let report = Migration::drift(&client, database, &MigrationScope::FromNewSpec)
+        .await
+        .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?;
+
+    if let Some(path) = env.get(EXO_SCHEMA_DRIFT_REPORT) {
+        write_report(&report, &path)?;
+    }
+
+    for warning in &report.warnings {
+        tracing::warn!("Schema introspection: {warning}");
+    }
+
+    if report.is_empty() {
+        tracing::info!("The database schema matches the model");
+        return Ok(());
+    }
+
+    for issue in &report.issues {
+        tracing::warn!(kind = ?issue.kind, "Schema drift: {}", issue.message);
+    }
+
+    match mode {
+        SchemaDriftCheck::Strict => Err(SubsystemLoadingError::Config(format!(
+            "The database schema doesn't match the model ({} issue(s) found). Migrate the database or set {EXO_SCHEMA_DRIFT_CHECK}=warn to start anyway.",
+            report.issues.len()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn write_report(report: &DriftReport, path: &str) -> Result<(), SubsystemLoadingError> {
+    let content = serde_json::to_string_pretty(report)
+        .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?;
+
+    std::fs::write(path, content).map_err(|e| {
+        SubsystemLoadingError::Config(format!(
+            "Failed to write the schema drift report to {path}: {e}"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use exo_env::MapEnvironment;
+
+    #[test]
+    fn parses_mode() {
+        let mode = |value: Option<&str>| {
+            let mut env = MapEnvironment::new();
+            if let Some(value) = value {
+                env.set(EXO_SCHEMA_DRIFT_CHECK, value);
+            }
+            schema_drift_check(&env)
+        };
+
+        assert_eq!(mode(None).unwrap(), SchemaDriftCheck::Off);
+        assert_eq!(mode(Some("warn")).unwrap(), SchemaDriftCheck::Warn);
+        assert_eq!(mode(Some("STRICT")).unwrap(), SchemaDriftCheck::Strict);
+        assert!(mode(Some("always")).is_err());
+    }
+}
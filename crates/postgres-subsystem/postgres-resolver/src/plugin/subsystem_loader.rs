@@ -11,9 +11,13 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use common::env_const::{get_rest_http_path, get_rpc_http_path};
-use postgres_core_model::subsystem::PostgresCoreSubsystem;
-use postgres_graphql_resolver::PostgresSubsystemResolver;
+use common::env_const::{
+    EXO_OPAQUE_ID_KEY, EXO_POSTGRES_SESSION_SETTINGS, get_admin_http_path, get_rest_http_path,
+    get_rpc_http_path,
+};
+use postgres_admin_resolver::PostgresSubsystemAdminResolver;
+use postgres_core_model::{session_settings::SessionSettings, subsystem::PostgresCoreSubsystem};
+use postgres_graphql_resolver::{PostgresDataTransferResolver, PostgresSubsystemResolver};
 
 use core_plugin_interface::interface::{SubsystemLoader, SubsystemLoadingError, SubsystemResolver};
 use core_plugin_shared::{
@@ -23,14 +27,24 @@ use core_resolver::plugin::{
     SubsystemGraphQLResolver, SubsystemRestResolver, SubsystemRpcResolver,
 };
 use exo_env::Environment;
-use exo_sql::DatabaseClientManager;
-use postgres_core_resolver::database_helper::create_database_executor;
+use exo_sql::{DatabaseClientManager, OpaqueIdKey};
+use postgres_core_resolver::{
+    database_helper::create_database_executor, retention::RetentionStats,
+};
 use postgres_graphql_model::subsystem::PostgresGraphQLSubsystem;
 use postgres_rest_model::subsystem::{PostgresRestSubsystem, PostgresRestSubsystemWithRouter};
 use postgres_rest_resolver::PostgresSubsystemRestResolver;
 use postgres_rpc_model::subsystem::{PostgresRpcSubsystem, PostgresRpcSubsystemWithRouter};
 use postgres_rpc_resolver::PostgresSubsystemRpcResolver;
 
+use super::access_rules::PostgresAccessRules;
+use super::schema_drift::check_schema_drift;
+#[cfg(not(target_family = "wasm"))]
+use super::{
+    diagnostics::DatabaseDiagnostics, partition_service::PartitionMaintenanceService,
+    retention_service::RetentionService,
+};
+
 pub struct PostgresSubsystemLoader {
     pub existing_client: Option<DatabaseClientManager>,
 }
@@ -46,12 +60,6 @@ impl SubsystemLoader for PostgresSubsystemLoader {
         subsystem: SerializableSubsystem,
         env: Arc<dyn Environment>,
     ) -> Result<Box<SubsystemResolver>, SubsystemLoadingError> {
-        let executor = Arc::new(
-            create_database_executor(self.existing_client.take(), env.as_ref())
-                .await
-                .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?,
-        );
-
         let SerializableSubsystem {
             graphql,
             rest,
@@ -60,10 +68,52 @@ impl SubsystemLoader for PostgresSubsystemLoader {
             ..
         } = subsystem;
 
-        let core_subsystem = PostgresCoreSubsystem::deserialize_reader(core.0.as_slice())?;
+        let mut core_subsystem = PostgresCoreSubsystem::deserialize_reader(core.0.as_slice())?;
+
+        let executor = Arc::new(
+            create_database_executor(
+                self.existing_client.take(),
+                env.as_ref(),
+                &core_subsystem.connection_settings,
+            )
+            .await
+            .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?,
+        );
+
+        if let Some(session_settings) = env.get(EXO_POSTGRES_SESSION_SETTINGS) {
+            core_subsystem.session_settings = SessionSettings::parse(
+                &session_settings,
+                &core_subsystem.contexts,
+            )
+            .map_err(|e| {
+                SubsystemLoadingError::Config(format!("{EXO_POSTGRES_SESSION_SETTINGS}: {e}"))
+            })?;
+        }
+
+        let has_opaque_ids = core_subsystem
+            .database
+            .tables()
+            .iter()
+            .any(|(_, table)| table.columns.iter().any(|column| column.opaque_id));
+        match env.get(EXO_OPAQUE_ID_KEY) {
+            Some(secret) if !secret.is_empty() => {
+                core_subsystem.opaque_id_key = Some(OpaqueIdKey::from_secret(&secret));
+            }
+            _ if has_opaque_ids => {
+                return Err(SubsystemLoadingError::Config(format!(
+                    "{EXO_OPAQUE_ID_KEY} must be set, since a type uses @opaqueId"
+                )));
+            }
+            _ => {}
+        }
+
+        check_schema_drift(&executor, &core_subsystem.database, env.as_ref()).await?;
+
         let core_subsystem = Arc::new(core_subsystem);
 
-        let graphql_system = graphql
+        let retention_stats = Arc::new(RetentionStats::new(&core_subsystem.retention_policies));
+
+        let graphql_resolver = graphql
             .map(|graphql| {
                 let mut subsystem = PostgresGraphQLSubsystem::deserialize(graphql.0)?;
                 subsystem.core_subsystem = core_subsystem.clone();
@@ -71,11 +121,14 @@ impl SubsystemLoader for PostgresSubsystemLoader {
                     id: self.id(),
                     subsystem,
                     executor: executor.clone(),
-                })
-                    as Arc<dyn SubsystemGraphQLResolver + Send + Sync>)
+                }))
             })
             .transpose()?;
 
+        let graphql_system = graphql_resolver
+            .clone()
+            .map(|resolver| resolver as Arc<dyn SubsystemGraphQLResolver + Send + Sync>);
+
         let rest_system = rest
             .map(|rest| {
                 let subsystem = PostgresRestSubsystem::deserialize(rest.0)?;
@@ -110,10 +163,54 @@ impl SubsystemLoader for PostgresSubsystemLoader {
             })
             .transpose()?;
 
-        Ok(Box::new(SubsystemResolver::new(
-            graphql_system,
-            rest_system,
-            rpc_system,
-        )))
+        let admin_path_prefix = format!("{}/", get_admin_http_path(env.as_ref()));
+
+        let admin_system = PostgresSubsystemAdminResolver {
+            core_subsystem: core_subsystem.clone(),
+            executor: executor.clone(),
+            api_path_prefix: admin_path_prefix.clone(),
+            retention_stats: retention_stats.clone(),
+        };
+
+        let mut subsystem_resolver =
+            SubsystemResolver::new(graphql_system, rest_system, rpc_system)
+                .with_admin(Box::new(admin_system))
+                .with_access_rules(Arc::new(PostgresAccessRules {
+                    core_subsystem: core_subsystem.clone(),
+                }));
+
+        // Export and import rely on the GraphQL subsystem to apply the access rules (and to map
+        // the `where` argument)
+        if let Some(resolver) = graphql_resolver {
+            subsystem_resolver =
+                subsystem_resolver.with_admin(Box::new(PostgresDataTransferResolver {
+                    resolver,
+                    api_path_prefix: admin_path_prefix,
+                }));
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        {
+            subsystem_resolver =
+                subsystem_resolver.with_diagnostics(Arc::new(DatabaseDiagnostics {
+                    executor: executor.clone(),
+                }));
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(service) =
+            PartitionMaintenanceService::new(&core_subsystem.database, executor.clone())
+        {
+            subsystem_resolver = subsystem_resolver.with_background_service(Arc::new(service));
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        if !core_subsystem.retention_policies.is_empty() {
+            subsystem_resolver = subsystem_resolver.with_background_service(Arc::new(
+                RetentionService::new(core_subsystem, executor, retention_stats),
+            ));
+        }
+
+        Ok(Box::new(subsystem_resolver))
     }
 }
@@ -11,6 +11,7 @@ use exo_sql::{
 };
 use postgres_core_resolver::database_helper::extractor;
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_core_resolver::session_settings::apply_session_settings;
 use postgres_rest_model::{
     operation::PostgresOperation, subsystem::PostgresRestSubsystemWithRouter,
 };
@@ -41,6 +42,8 @@ impl SubsystemRestResolver for PostgresSubsystemRestResolver {
         if let Some(operation) = operation {
             let operation = operation.resolve(request_context).await?;
 
+            apply_session_settings(&self.subsystem.core_subsystem, request_context).await?;
+
             let mut tx = request_context
                 .system_context
                 .transaction_holder
@@ -55,7 +58,12 @@ impl SubsystemRestResolver for PostgresSubsystemRestResolver {
                     &self.subsystem.core_subsystem.as_ref().database,
                 )
                 .await
-                .map_err(PostgresExecutionError::Postgres)?;
+                .map_err(|e| {
+                    PostgresExecutionError::from_database_error(
+                        e,
+                        self.subsystem.core_subsystem.as_ref(),
+                    )
+                })?;
 
             let body = if result.len() == 1 {
                 let string_result: String = extractor(result.swap_remove(0))?;
@@ -98,6 +106,7 @@ impl OperationResolver for PostgresOperation {
             order_by: None,
             offset: None,
             limit: None,
+            lock: None,
         };
 
         Ok(AbstractOperation::Select(select))
@@ -4,7 +4,6 @@ use async_trait::async_trait;
 
 use common::context::RequestContext;
 
-use core_resolver::access_solver::AccessSolver;
 use core_resolver::plugin::SubsystemRpcResolver;
 use core_resolver::plugin::subsystem_rpc_resolver::{SubsystemRpcError, SubsystemRpcResponse};
 use core_resolver::{QueryResponse, QueryResponseBody};
@@ -13,8 +12,10 @@ use exo_sql::{
     SelectionCardinality, SelectionElement,
 };
 use postgres_core_model::relation::PostgresRelation;
+use postgres_core_resolver::access::database_solver::solve_database_access;
 use postgres_core_resolver::database_helper::extractor;
 use postgres_core_resolver::postgres_execution_error::PostgresExecutionError;
+use postgres_core_resolver::session_settings::apply_session_settings;
 use postgres_rpc_model::operation::PostgresOperationKind;
 use postgres_rpc_model::{operation::PostgresOperation, subsystem::PostgresRpcSubsystemWithRouter};
 
@@ -42,6 +43,10 @@ impl SubsystemRpcResolver for PostgresSubsystemRpcResolver {
         if let Some(operation) = operation {
             let operation = operation.resolve(request_context, &self.subsystem).await?;
 
+            apply_session_settings(&self.subsystem.core_subsystem, request_context)
+                .await
+                .map_err(from_postgres_error)?;
+
             let mut tx = request_context
                 .system_context
                 .transaction_holder
@@ -56,7 +61,12 @@ impl SubsystemRpcResolver for PostgresSubsystemRpcResolver {
                     &self.subsystem.core_subsystem.as_ref().database,
                 )
                 .await
-                .map_err(|e| from_postgres_error(PostgresExecutionError::Postgres(e)))?;
+                .map_err(|e| {
+                    from_postgres_error(PostgresExecutionError::from_database_error(
+                        e,
+                        self.subsystem.core_subsystem.as_ref(),
+                    ))
+                })?;
 
             let body = if result.len() == 1 {
                 let string_result: String =
@@ -102,25 +112,22 @@ impl OperationResolver for PostgresOperation {
 
         let entity_type = &entity_types[self.entity_type_id];
 
-        let access_expr = {
-            let access_expr_index = match self.kind {
-                PostgresOperationKind::Query => entity_type.access.read,
-                _ => {
-                    return Err(SubsystemRpcError::UserDisplayError(
-                        "Only queries are supported for this operation".to_string(),
-                    ));
-                }
-            };
-            &subsystem.core_subsystem.database_access_expressions[access_expr_index]
+        let access_expr_index = match self.kind {
+            PostgresOperationKind::Query => entity_type.access.read,
+            _ => {
+                return Err(SubsystemRpcError::UserDisplayError(
+                    "Only queries are supported for this operation".to_string(),
+                ));
+            }
         };
 
-        let access_predicate = subsystem
-            .core_subsystem
-            .solve(request_context, None, access_expr)
-            .await
-            .map_err(|_| SubsystemRpcError::Authorization)?
-            .map(|p| p.0)
-            .resolve();
+        let access_predicate = solve_database_access(
+            &subsystem.core_subsystem,
+            request_context,
+            access_expr_index,
+        )
+        .await
+        .map_err(|_| SubsystemRpcError::Authorization)?;
 
         let selection = Selection::Json(
             entity_type
@@ -146,6 +153,7 @@ impl OperationResolver for PostgresOperation {
             order_by: None,
             offset: None,
             limit: None,
+            lock: None,
         };
 
         Ok(AbstractOperation::Select(select))
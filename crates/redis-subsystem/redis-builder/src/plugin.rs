@@ -0,0 +1,102 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+
+use core_model_builder::{
+    builder::system_builder::BaseModelSystem,
+    error::ModelBuildingError,
+    plugin::{BuildMode, CoreSubsystemBuild, GraphQLSubsystemBuild},
+    typechecker::{
+        annotation::{AnnotationSpec, AnnotationTarget},
+        typ::TypecheckedSystem,
+    },
+};
+use core_plugin_interface::interface::{SubsystemBuild, SubsystemBuilder};
+use core_plugin_shared::{
+    serializable_system::{SerializableCoreBytes, SerializableGraphQLBytes},
+    system_serializer::SystemSerializer,
+};
+
+#[derive(Default)]
+pub struct RedisSubsystemBuilder {}
+
+#[async_trait]
+impl SubsystemBuilder for RedisSubsystemBuilder {
+    fn id(&self) -> &'static str {
+        "redis"
+    }
+
+    fn annotations(&self) -> Vec<(&'static str, AnnotationSpec)> {
+        vec![
+            (
+                // `@redis("<key prefix>")`
+                "redis",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Module],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+            (
+                // `@redisCommand("<command>")` (defaults to the method name)
+                "redisCommand",
+                AnnotationSpec {
+                    targets: &[AnnotationTarget::Method],
+                    no_params: false,
+                    single_params: true,
+                    mapped_params: None,
+                },
+            ),
+        ]
+    }
+
+    async fn build(
+        &self,
+        typechecked_system: &TypecheckedSystem,
+        base_system: &BaseModelSystem,
+        _build_mode: BuildMode,
+    ) -> Result<Option<SubsystemBuild>, ModelBuildingError> {
+        let Some(subsystem) = crate::system_builder::build(typechecked_system, base_system).await?
+        else {
+            return Ok(None);
+        };
+
+        let serialized_subsystem = subsystem
+            .serialize()
+            .map_err(ModelBuildingError::Serialize)?;
+
+        let graphql = GraphQLSubsystemBuild {
+            id: self.id().to_string(),
+            serialized_subsystem: SerializableGraphQLBytes(serialized_subsystem),
+            query_names: subsystem
+                .queries
+                .iter()
+                .map(|(_, q)| q.name.clone())
+                .collect(),
+            mutation_names: subsystem
+                .mutations
+                .iter()
+                .map(|(_, q)| q.name.clone())
+                .collect(),
+            interceptions: vec![],
+        };
+
+        Ok(Some(SubsystemBuild {
+            id: self.id(),
+            graphql: Some(graphql),
+            rest: None,
+            rpc: None,
+            core: CoreSubsystemBuild {
+                serialized_subsystem: SerializableCoreBytes(vec![]),
+            },
+        }))
+    }
+}
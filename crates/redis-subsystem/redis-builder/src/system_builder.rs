@@ -0,0 +1,413 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{collections::HashMap, path::Path};
+
+use async_trait::async_trait;
+use core_model::{mapped_arena::MappedArena, types::FieldType};
+use core_model_builder::{
+    ast::ast_types::{AstAnnotationParams, AstExpr, AstModule},
+    builder::{resolved_builder::AnnotationMapHelper, system_builder::BaseModelSystem},
+    error::ModelBuildingError,
+    typechecker::{
+        Typed,
+        typ::{Module, TypecheckedSystem},
+    },
+};
+use redis_model::{
+    command::{RedisCommand, RedisOperation},
+    module::ModuleMethod,
+    operation::{RedisMutation, RedisQuery},
+    subsystem::RedisSubsystem,
+    types::{ModuleOperationReturnType, ModuleType},
+};
+use subsystem_model_builder_util::ScriptProcessor;
+
+pub async fn build(
+    typechecked_system: &TypecheckedSystem,
+    base_system: &BaseModelSystem,
+) -> Result<Option<RedisSubsystem>, ModelBuildingError> {
+    let module_selection_closure =
+        |module: &AstModule<Typed>| module.annotations.get("redis").map(|_| "redis".to_string());
+
+    let module_system = subsystem_model_builder_util::build_with_selection(
+        typechecked_system,
+        base_system,
+        module_selection_closure,
+        RedisScriptProcessor {},
+    )
+    .await?;
+
+    let underlying_module_system = module_system.underlying;
+
+    if underlying_module_system.queries.is_empty() && underlying_module_system.mutations.is_empty()
+    {
+        return Ok(None);
+    }
+
+    if !underlying_module_system.interceptors.is_empty() {
+        return Err(ModelBuildingError::Generic(
+            "Redis modules may not declare interceptors".to_string(),
+        ));
+    }
+
+    let module_commands = redis_module_commands(typechecked_system)?;
+
+    let mut operations = MappedArena::default();
+    for (_, method) in underlying_module_system.methods.iter() {
+        // The "script" of a redis module records the module name (see `RedisScriptProcessor`)
+        let module_name = &underlying_module_system.scripts[method.script].path;
+        let RedisModuleCommands {
+            key_prefix,
+            commands,
+        } = &module_commands[module_name];
+
+        let command = commands[&method.name];
+        validate_method(method, command, &underlying_module_system.module_types)?;
+
+        operations.add(
+            &method.name,
+            RedisOperation {
+                method_name: method.name.clone(),
+                command,
+                key_prefix: key_prefix.clone(),
+            },
+        );
+    }
+
+    let mut queries = MappedArena::default();
+    for query in underlying_module_system.queries.values().into_iter() {
+        queries.add(&query.name.clone(), RedisQuery(query));
+    }
+
+    let mut mutations = MappedArena::default();
+    for mutation in underlying_module_system.mutations.values().into_iter() {
+        mutations.add(&mutation.name.clone(), RedisMutation(mutation));
+    }
+
+    Ok(Some(RedisSubsystem {
+        contexts: underlying_module_system.contexts,
+        module_types: underlying_module_system.module_types,
+        queries,
+        mutations,
+        methods: underlying_module_system.methods,
+        operations,
+    }))
+}
+
+struct RedisModuleCommands {
+    key_prefix: String,
+    commands: HashMap<String, RedisCommand>,
+}
+
+/// Compute the key prefix and the command for each method of every `@redis` module (keyed by the module name)
+fn redis_module_commands(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<HashMap<String, RedisModuleCommands>, ModelBuildingError> {
+    fn string_param<'a>(
+        params: &'a AstAnnotationParams<Typed>,
+        annotation: &str,
+        owner: &str,
+    ) -> Result<&'a str, ModelBuildingError> {
+        match params {
+            AstAnnotationParams::Single(AstExpr::StringLiteral(value, _), _) => Ok(value),
+            _ => Err(ModelBuildingError::Generic(format!(
+                "@{annotation} on '{owner}' expects a single string parameter"
+            ))),
+        }
+    }
+
+    let mut module_commands = HashMap::new();
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let Some(redis_params) = module.annotations.get("redis") else {
+            continue;
+        };
+
+        let key_prefix = string_param(redis_params, "redis", &module.name)?.to_string();
+
+        let commands = module
+            .methods
+            .iter()
+            .map(|method| {
+                let command_name = match method.annotations.get("redisCommand") {
+                    Some(params) => string_param(params, "redisCommand", &method.name)?,
+                    None => method.name.as_str(),
+                };
+
+                let command = RedisCommand::from_name(command_name).ok_or_else(|| {
+                    ModelBuildingError::Generic(format!(
+                        "Unknown redis command '{command_name}' for method '{}' in module '{}'. Expected one of: {}",
+                        method.name,
+                        module.name,
+                        RedisCommand::ALL.map(|command| command.name()).join(", ")
+                    ))
+                })?;
+
+                Ok((method.name.clone(), command))
+            })
+            .collect::<Result<_, ModelBuildingError>>()?;
+
+        module_commands.insert(
+            module.name.clone(),
+            RedisModuleCommands {
+                key_prefix,
+                commands,
+            },
+        );
+    }
+
+    Ok(module_commands)
+}
+
+/// Check that the declared method matches the signature of the command
+fn validate_method(
+    method: &ModuleMethod,
+    command: RedisCommand,
+    module_types: &core_model::mapped_arena::SerializableSlab<ModuleType>,
+) -> Result<(), ModelBuildingError> {
+    let signature_error = |detail: String| {
+        ModelBuildingError::Generic(format!(
+            "Method '{}' does not match the signature of the redis '{}' command: {detail}",
+            method.name,
+            command.name()
+        ))
+    };
+
+    let is_query = matches!(
+        method.operation_kind,
+        redis_model::module::ModuleMethodType::Query(_)
+    );
+    if is_query != command.is_query() {
+        return Err(signature_error(format!(
+            "must be declared as a {}",
+            if command.is_query() {
+                "query"
+            } else {
+                "mutation"
+            }
+        )));
+    }
+
+    let arguments: Vec<_> = method.arguments.iter().filter(|a| !a.is_injected).collect();
+
+    for parameter in command.parameters() {
+        match arguments.iter().find(|a| a.name == parameter.name) {
+            Some(argument) => {
+                let type_name = &module_types[*argument.type_id.innermost()].name;
+                let is_optional = matches!(argument.type_id, FieldType::Optional(_));
+
+                if type_name != parameter.type_name || is_optional != parameter.optional {
+                    return Err(signature_error(format!(
+                        "argument '{}' must be of type {}{}",
+                        parameter.name,
+                        parameter.type_name,
+                        if parameter.optional { "?" } else { "" }
+                    )));
+                }
+            }
+            None if parameter.optional => {}
+            None => {
+                return Err(signature_error(format!(
+                    "missing argument '{}'",
+                    parameter.name
+                )));
+            }
+        }
+    }
+
+    if let Some(extra) = arguments.iter().find(|argument| {
+        !command
+            .parameters()
+            .iter()
+            .any(|parameter| parameter.name == argument.name)
+    }) {
+        return Err(signature_error(format!(
+            "unexpected argument '{}'",
+            extra.name
+        )));
+    }
+
+    let return_type_name = match &method.return_type {
+        ModuleOperationReturnType::Own(return_type) => return_type.type_name(),
+        ModuleOperationReturnType::Foreign(return_type) => {
+            &return_type.innermost().return_type_name
+        }
+    };
+    if return_type_name != command.return_type_name() {
+        return Err(signature_error(format!(
+            "must return {}",
+            command.return_type_name()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Redis modules have no script. We record the module name as the script path so that each
+/// method can be traced back to its module (and thus to its key prefix).
+struct RedisScriptProcessor {}
+
+#[async_trait]
+impl ScriptProcessor for RedisScriptProcessor {
+    async fn process_script(
+        &self,
+        module: &AstModule<Typed>,
+        _base_system: &BaseModelSystem,
+        _typechecked_system: &TypecheckedSystem,
+        _module_fs_path: &Path,
+    ) -> Result<(String, Vec<u8>), ModelBuildingError> {
+        Ok((module.name.clone(), vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_model_builder::plugin::BuildMode;
+    use core_plugin_shared::system_serializer::SystemSerializer;
+    use redis_model::{command::RedisCommand, subsystem::RedisSubsystem};
+
+    async fn build_redis_subsystem(src: &str) -> Result<Option<RedisSubsystem>, String> {
+        let system = builder::build_system_from_str(
+            src,
+            "index.exo".to_string(),
+            vec![Box::new(crate::RedisSubsystemBuilder::default())],
+            BuildMode::Build,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Ok(system
+            .subsystems
+            .into_iter()
+            .find(|subsystem| subsystem.id == "redis")
+            .and_then(|subsystem| subsystem.graphql)
+            .map(|graphql| RedisSubsystem::deserialize(graphql.0).unwrap()))
+    }
+
+    async fn build_error(src: &str) -> String {
+        match build_redis_subsystem(src).await {
+            Ok(_) => panic!("Expected the model to fail to build"),
+            Err(e) => e,
+        }
+    }
+
+    #[tokio::test]
+    async fn builds_operations() {
+        let src = r#"
+            @redis("app")
+            module Counters {
+                @access(true)
+                query get(key: String): String
+
+                @access(true)
+                @redisCommand("incr")
+                mutation incrementPageViews(key: String, by: Int?): Int
+
+                @access(true)
+                mutation rateLimit(key: String, limit: Int, windowSeconds: Int): Boolean
+            }
+
+            @redis("sessions")
+            module Sessions {
+                @access(true)
+                mutation set(key: String, value: String, ttlSeconds: Int?): Boolean
+            }
+        "#;
+
+        let subsystem = build_redis_subsystem(src).await.unwrap().unwrap();
+
+        let operation = |name: &str| {
+            let operation = subsystem.operations.get_by_key(name).unwrap();
+            (operation.command, operation.key_prefix.as_str())
+        };
+
+        assert_eq!(operation("get"), (RedisCommand::Get, "app"));
+        assert_eq!(operation("incrementPageViews"), (RedisCommand::Incr, "app"));
+        assert_eq!(operation("rateLimit"), (RedisCommand::RateLimit, "app"));
+        assert_eq!(operation("set"), (RedisCommand::Set, "sessions"));
+
+        assert!(subsystem.queries.get_by_key("get").is_some());
+        assert!(
+            subsystem
+                .mutations
+                .get_by_key("incrementPageViews")
+                .is_some()
+        );
+        assert!(subsystem.mutations.get_by_key("set").is_some());
+    }
+
+    #[tokio::test]
+    async fn no_redis_modules() {
+        let src = r#"
+            context AuthContext {
+                @jwt role: String
+            }
+        "#;
+
+        assert!(build_redis_subsystem(src).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_command() {
+        let error = build_error(
+            r#"
+            @redis("app")
+            module Counters {
+                @access(true)
+                mutation flushall(key: String): Boolean
+            }
+        "#,
+        )
+        .await;
+
+        assert!(
+            error.contains("Unknown redis command 'flushall'"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatched_signatures() {
+        let cases = [
+            (
+                "query incr(key: String, by: Int?): Int",
+                "must be declared as a mutation",
+            ),
+            (
+                "mutation incr(key: String, by: Int): Int",
+                "argument 'by' must be of type Int?",
+            ),
+            (
+                "mutation expire(key: String): Boolean",
+                "missing argument 'seconds'",
+            ),
+            (
+                "mutation del(key: String, force: Boolean): Boolean",
+                "unexpected argument 'force'",
+            ),
+            ("mutation del(key: String): Int", "must return Boolean"),
+        ];
+
+        for (method, expected_error) in cases {
+            let error = build_error(&format!(
+                r#"
+                @redis("app")
+                module Counters {{
+                    @access(true)
+                    {method}
+                }}
+            "#
+            ))
+            .await;
+
+            assert!(error.contains(expected_error), "{method}: {error}");
+        }
+    }
+}
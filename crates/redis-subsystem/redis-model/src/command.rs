@@ -0,0 +1,176 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+/// The Redis command backing a method in a `@redis` module.
+///
+/// Each command has a fixed signature (see [RedisCommand::parameters] and
+/// [RedisCommand::return_type_name]), which the builder checks against the declared method.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedisCommand {
+    /// `query get(key: String): String`
+    Get,
+    /// `mutation set(key: String, value: String, ttlSeconds: Int?): Boolean`
+    Set,
+    /// `mutation incr(key: String, by: Int?): Int`
+    Incr,
+    /// `mutation expire(key: String, seconds: Int): Boolean`
+    Expire,
+    /// `mutation del(key: String): Boolean`
+    Del,
+    /// `mutation rateLimit(key: String, limit: Int, windowSeconds: Int): Boolean`
+    ///
+    /// Fixed-window counter: increments the counter for the current window and returns whether
+    /// the number of calls in the window is still within the limit.
+    RateLimit,
+}
+
+/// A parameter of a Redis command
+pub struct RedisCommandParameter {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub optional: bool,
+}
+
+const fn required(name: &'static str, type_name: &'static str) -> RedisCommandParameter {
+    RedisCommandParameter {
+        name,
+        type_name,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, type_name: &'static str) -> RedisCommandParameter {
+    RedisCommandParameter {
+        name,
+        type_name,
+        optional: true,
+    }
+}
+
+impl RedisCommand {
+    pub const ALL: [RedisCommand; 6] = [
+        RedisCommand::Get,
+        RedisCommand::Set,
+        RedisCommand::Incr,
+        RedisCommand::Expire,
+        RedisCommand::Del,
+        RedisCommand::RateLimit,
+    ];
+
+    /// Name used to refer to the command in `@redisCommand("...")` (or as the method name)
+    pub fn name(&self) -> &'static str {
+        match self {
+            RedisCommand::Get => "get",
+            RedisCommand::Set => "set",
+            RedisCommand::Incr => "incr",
+            RedisCommand::Expire => "expire",
+            RedisCommand::Del => "del",
+            RedisCommand::RateLimit => "rateLimit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|command| command.name() == name)
+    }
+
+    /// Is the command read-only (and thus to be declared as a `query`)?
+    pub fn is_query(&self) -> bool {
+        matches!(self, RedisCommand::Get)
+    }
+
+    pub fn parameters(&self) -> &'static [RedisCommandParameter] {
+        match self {
+            RedisCommand::Get | RedisCommand::Del => &[required("key", "String")],
+            RedisCommand::Set => &[
+                required("key", "String"),
+                required("value", "String"),
+                optional("ttlSeconds", "Int"),
+            ],
+            RedisCommand::Incr => &[required("key", "String"), optional("by", "Int")],
+            RedisCommand::Expire => &[required("key", "String"), required("seconds", "Int")],
+            RedisCommand::RateLimit => &[
+                required("key", "String"),
+                required("limit", "Int"),
+                required("windowSeconds", "Int"),
+            ],
+        }
+    }
+
+    pub fn return_type_name(&self) -> &'static str {
+        match self {
+            RedisCommand::Get => "String",
+            RedisCommand::Incr => "Int",
+            RedisCommand::Set
+            | RedisCommand::Expire
+            | RedisCommand::Del
+            | RedisCommand::RateLimit => "Boolean",
+        }
+    }
+}
+
+/// A method of a `@redis` module along with the command it executes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RedisOperation {
+    pub method_name: String,
+    pub command: RedisCommand,
+    /// Prefix prepended (with a `:` separator) to every key used by the module
+    pub key_prefix: String,
+}
+
+impl RedisOperation {
+    pub fn key(&self, key: &str) -> String {
+        format!("{}:{key}", self.key_prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_names() {
+        for command in RedisCommand::ALL {
+            assert_eq!(RedisCommand::from_name(command.name()), Some(command));
+        }
+        assert_eq!(RedisCommand::from_name("flushall"), None);
+        // Names are case-sensitive (as method names are)
+        assert_eq!(RedisCommand::from_name("ratelimit"), None);
+    }
+
+    #[test]
+    fn only_get_is_query() {
+        for command in RedisCommand::ALL {
+            assert_eq!(command.is_query(), command == RedisCommand::Get);
+        }
+    }
+
+    #[test]
+    fn commands_take_a_required_key() {
+        for command in RedisCommand::ALL {
+            let key = &command.parameters()[0];
+            assert_eq!(
+                (key.name, key.type_name, key.optional),
+                ("key", "String", false)
+            );
+        }
+    }
+
+    #[test]
+    fn prefixes_keys() {
+        let operation = RedisOperation {
+            method_name: "incrementPageViews".to_string(),
+            command: RedisCommand::Incr,
+            key_prefix: "app".to_string(),
+        };
+
+        assert_eq!(operation.key("views:home"), "app:views:home");
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+use subsystem_model_util::operation::{ModuleMutation, ModuleQuery};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RedisQuery(pub ModuleQuery);
+
+impl Deref for RedisQuery {
+    type Target = ModuleQuery;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RedisMutation(pub ModuleMutation);
+
+impl Deref for RedisMutation {
+    type Target = ModuleMutation;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+pub use plugin::RedisSubsystemLoader;
+pub use resolver::RedisSubsystemResolver;
+pub use store::{RateLimitDecision, RedisStore};
+
+mod access_solver;
+mod plugin;
+mod redis_execution_error;
+mod resolver;
+mod store;
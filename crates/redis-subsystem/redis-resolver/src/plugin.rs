@@ -0,0 +1,63 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use common::env_const::EXO_REDIS_URL;
+use core_plugin_interface::interface::{SubsystemLoader, SubsystemLoadingError, SubsystemResolver};
+use core_plugin_shared::{
+    serializable_system::SerializableSubsystem, system_serializer::SystemSerializer,
+};
+use core_resolver::plugin::SubsystemGraphQLResolver;
+use exo_env::Environment;
+use redis_model::subsystem::RedisSubsystem;
+
+use crate::{RedisStore, RedisSubsystemResolver};
+
+pub struct RedisSubsystemLoader {}
+
+#[async_trait]
+impl SubsystemLoader for RedisSubsystemLoader {
+    fn id(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn init(
+        &mut self,
+        serialized_subsystem: SerializableSubsystem,
+        env: Arc<dyn Environment>,
+    ) -> Result<Box<SubsystemResolver>, SubsystemLoadingError> {
+        let graphql = match serialized_subsystem.graphql {
+            Some(graphql) => {
+                let subsystem = RedisSubsystem::deserialize(graphql.0)?;
+
+                let url = env.get(EXO_REDIS_URL).ok_or_else(|| {
+                    SubsystemLoadingError::Config(format!(
+                        "{EXO_REDIS_URL} must be set to use @redis modules"
+                    ))
+                })?;
+                let store = RedisStore::connect(&url).await.map_err(|e| {
+                    SubsystemLoadingError::Config(format!("Failed to connect to Redis: {e}"))
+                })?;
+
+                Ok::<_, SubsystemLoadingError>(Some(Arc::new(RedisSubsystemResolver {
+                    id: self.id(),
+                    subsystem,
+                    store,
+                })
+                    as Arc<dyn SubsystemGraphQLResolver + Send + Sync>))
+            }
+            None => Ok(None),
+        }?;
+
+        Ok(Box::new(SubsystemResolver::new(graphql, None, None)))
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use common::context::ContextExtractionError;
+use core_resolver::{access_solver::AccessSolverError, plugin::SubsystemResolutionError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RedisExecutionError {
+    #[error("{0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Invalid argument {0}")]
+    InvalidArgument(String),
+
+    #[error("Not authorized")]
+    Authorization,
+
+    #[error("{0}")]
+    Generic(String),
+
+    #[error("{0}")]
+    ContextExtraction(#[from] ContextExtractionError),
+}
+
+impl From<AccessSolverError> for RedisExecutionError {
+    fn from(error: AccessSolverError) -> Self {
+        match error {
+            AccessSolverError::ContextExtraction(e) => RedisExecutionError::ContextExtraction(e),
+            _ => RedisExecutionError::Generic(error.to_string()),
+        }
+    }
+}
+
+impl From<RedisExecutionError> for SubsystemResolutionError {
+    fn from(e: RedisExecutionError) -> Self {
+        match e {
+            RedisExecutionError::Authorization => SubsystemResolutionError::Authorization,
+            RedisExecutionError::ContextExtraction(ce) => {
+                SubsystemResolutionError::ContextExtraction(ce)
+            }
+            RedisExecutionError::InvalidArgument(_) => {
+                SubsystemResolutionError::UserDisplayError(e.to_string())
+            }
+            _ => {
+                tracing::error!("Redis operation failed: {e}");
+                // Do not reveal too much information about the error
+                SubsystemResolutionError::UserDisplayError("Internal server error".to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use common::context::RequestContext;
+use common::value::Val;
+use core_plugin_shared::interception::InterceptorIndex;
+use core_resolver::{
+    InterceptedOperation, QueryResponse, QueryResponseBody,
+    access_solver::AccessSolver,
+    plugin::{SubsystemGraphQLResolver, SubsystemResolutionError},
+    system_resolver::GraphQLSystemResolver,
+    validation::field::ValidatedField,
+};
+use redis_model::{
+    command::{RedisCommand, RedisOperation},
+    module::ModuleMethod,
+    subsystem::RedisSubsystem,
+};
+
+use crate::{RedisStore, redis_execution_error::RedisExecutionError};
+
+pub struct RedisSubsystemResolver {
+    pub id: &'static str,
+    pub subsystem: RedisSubsystem,
+    pub store: RedisStore,
+}
+
+#[async_trait]
+impl SubsystemGraphQLResolver for RedisSubsystemResolver {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn resolve<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        operation_type: OperationType,
+        request_context: &'a RequestContext<'a>,
+        _system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        let operation_name = &field.name;
+
+        let method_id = match operation_type {
+            OperationType::Query => self
+                .subsystem
+                .queries
+                .get_by_key(operation_name)
+                .map(|query| query.method_id),
+            OperationType::Mutation => self
+                .subsystem
+                .mutations
+                .get_by_key(operation_name)
+                .map(|mutation| mutation.method_id),
+            OperationType::Subscription => {
+                return Err(SubsystemResolutionError::UserDisplayError(
+                    "Subscriptions are not supported".to_string(),
+                ));
+            }
+        };
+
+        // TODO: Remove unwrap() by changing the type of method_id
+        let Some(method) = method_id.map(|method_id| &self.subsystem.methods[method_id.unwrap()])
+        else {
+            return Ok(None);
+        };
+
+        let Some(operation) = self.subsystem.operations.get_by_key(&method.name) else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.execute(method, operation, field, request_context)
+                .await?,
+        ))
+    }
+
+    async fn invoke_interceptor<'a>(
+        &'a self,
+        _interceptor_index: InterceptorIndex,
+        _intercepted_operation: &'a InterceptedOperation<'a>,
+        _request_context: &'a RequestContext<'a>,
+        _system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        Err(SubsystemResolutionError::NoInterceptorFound)
+    }
+
+    fn schema_queries(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_queries()
+    }
+
+    fn schema_mutations(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_mutations()
+    }
+
+    fn schema_types(&self) -> Vec<TypeDefinition> {
+        self.subsystem.schema_types()
+    }
+}
+
+impl RedisSubsystemResolver {
+    async fn execute(
+        &self,
+        method: &ModuleMethod,
+        operation: &RedisOperation,
+        field: &ValidatedField,
+        request_context: &RequestContext<'_>,
+    ) -> Result<QueryResponse, RedisExecutionError> {
+        if !self.check_access(method, request_context).await? {
+            warn!("[RedisOperation] access denied for '{}'", method.name);
+            return Err(RedisExecutionError::Authorization);
+        }
+
+        debug!(
+            "[RedisOperation] executing '{}' ({})",
+            method.name,
+            operation.command.name()
+        );
+
+        let key = operation.key(&string_arg(field, "key")?);
+
+        let result = match operation.command {
+            RedisCommand::Get => self.store.get(&key).await?.into(),
+            RedisCommand::Set => {
+                let value = string_arg(field, "value")?;
+                let ttl_seconds = optional_int_arg(field, "ttlSeconds")?
+                    .map(|ttl| {
+                        u64::try_from(ttl).map_err(|_| {
+                            RedisExecutionError::InvalidArgument("ttlSeconds".to_string())
+                        })
+                    })
+                    .transpose()?;
+                self.store.set(&key, &value, ttl_seconds).await?;
+                Value::Bool(true)
+            }
+            RedisCommand::Incr => {
+                let by = optional_int_arg(field, "by")?.unwrap_or(1);
+                self.store.incr(&key, by).await?.into()
+            }
+            RedisCommand::Expire => {
+                let seconds = int_arg(field, "seconds")?;
+                self.store.expire(&key, seconds).await?.into()
+            }
+            RedisCommand::Del => self.store.del(&key).await?.into(),
+            RedisCommand::RateLimit => {
+                let limit = int_arg(field, "limit")?;
+                let window_seconds = int_arg(field, "windowSeconds")?;
+                if window_seconds <= 0 {
+                    return Err(RedisExecutionError::InvalidArgument(
+                        "windowSeconds".to_string(),
+                    ));
+                }
+                self.store
+                    .rate_limit(&key, limit, window_seconds)
+                    .await?
+                    .allowed
+                    .into()
+            }
+        };
+
+        Ok(QueryResponse {
+            body: QueryResponseBody::Json(result),
+            headers: vec![],
+        })
+    }
+
+    async fn check_access(
+        &self,
+        method: &ModuleMethod,
+        request_context: &RequestContext<'_>,
+    ) -> Result<bool, RedisExecutionError> {
+        if request_context.is_internal() {
+            return Ok(true);
+        }
+
+        Ok(self
+            .subsystem
+            .solve(request_context, None, &method.access.value)
+            .await?
+            .map(|predicate| predicate.0)
+            .resolve())
+    }
+}
+
+fn optional_int_arg(
+    field: &ValidatedField,
+    name: &str,
+) -> Result<Option<i64>, RedisExecutionError> {
+    match field.arguments.get(name) {
+        None | Some(Val::Null) => Ok(None),
+        Some(Val::Number(number)) => number
+            .as_i64()
+            .map(Some)
+            .ok_or_else(|| RedisExecutionError::InvalidArgument(name.to_string())),
+        Some(_) => Err(RedisExecutionError::InvalidArgument(name.to_string())),
+    }
+}
+
+fn int_arg(field: &ValidatedField, name: &str) -> Result<i64, RedisExecutionError> {
+    optional_int_arg(field, name)?
+        .ok_or_else(|| RedisExecutionError::InvalidArgument(name.to_string()))
+}
+
+fn string_arg(field: &ValidatedField, name: &str) -> Result<String, RedisExecutionError> {
+    match field.arguments.get(name) {
+        Some(Val::String(value)) => Ok(value.clone()),
+        _ => Err(RedisExecutionError::InvalidArgument(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql_value::{Name, indexmap::IndexMap};
+
+    use super::*;
+
+    fn field(arguments: Vec<(&str, Val)>) -> ValidatedField {
+        ValidatedField {
+            alias: None,
+            name: Name::new("incr"),
+            arguments: arguments
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect::<IndexMap<_, _>>(),
+            subfields: vec![],
+        }
+    }
+
+    #[test]
+    fn int_arguments() {
+        let field = field(vec![
+            ("by", Val::Number(5i64.into())),
+            ("ttlSeconds", Val::Null),
+            ("seconds", Val::String("10".to_string())),
+        ]);
+
+        assert_eq!(optional_int_arg(&field, "by").unwrap(), Some(5));
+        assert_eq!(optional_int_arg(&field, "ttlSeconds").unwrap(), None);
+        assert_eq!(optional_int_arg(&field, "missing").unwrap(), None);
+        assert!(matches!(
+            optional_int_arg(&field, "seconds"),
+            Err(RedisExecutionError::InvalidArgument(name)) if name == "seconds"
+        ));
+
+        assert_eq!(int_arg(&field, "by").unwrap(), 5);
+        assert!(matches!(
+            int_arg(&field, "ttlSeconds"),
+            Err(RedisExecutionError::InvalidArgument(name)) if name == "ttlSeconds"
+        ));
+    }
+
+    #[test]
+    fn string_arguments() {
+        let field = field(vec![
+            ("key", Val::String("views".to_string())),
+            ("value", Val::Number(1i64.into())),
+        ]);
+
+        assert_eq!(string_arg(&field, "key").unwrap(), "views");
+        assert!(string_arg(&field, "value").is_err());
+        assert!(string_arg(&field, "missing").is_err());
+    }
+}
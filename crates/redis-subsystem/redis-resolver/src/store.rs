@@ -0,0 +1,172 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A thin wrapper around a Redis connection exposing the operations used by `@redis` modules.
+//!
+//! The store also backs the GraphQL endpoint's rate limiter when its counts are shared by the
+//! servers of a deployment (see [`RateLimitStore`]).
+
+use async_trait::async_trait;
+use common::rate_limit::{RateLimitStore, RateLimitStoreError};
+use redis::{AsyncCommands, RedisError, Script, aio::ConnectionManager};
+
+/// The prefix of the keys of the GraphQL endpoint's rate limiter
+const GRAPHQL_RATE_LIMIT_PREFIX: &str = "exo:graphql-rate-limit";
+
+/// Fixed-window counter: the expiry is set only when the window starts (i.e. on the first increment)
+const RATE_LIMIT_SCRIPT: &str = r#"
+local current = redis.call('INCR', KEYS[1])
+if current == 1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+local ttl = redis.call('TTL', KEYS[1])
+return {current, ttl}
+"#;
+
+#[derive(Clone)]
+pub struct RedisStore {
+    connection: ConnectionManager,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Number of calls remaining in the current window
+    pub remaining: i64,
+    /// Seconds until the current window resets
+    pub reset_in_seconds: i64,
+}
+
+impl RedisStore {
+    pub async fn connect(url: &str) -> Result<Self, RedisError> {
+        let client = redis::Client::open(url)?;
+        let connection = ConnectionManager::new(client).await?;
+
+        Ok(Self { connection })
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Option<String>, RedisError> {
+        self.connection.clone().get(key).await
+    }
+
+    /// Set the value, expiring it after `ttl_seconds` (if provided)
+    pub async fn set(
+        &self,
+        key: &str,
+        value: &str,
+        ttl_seconds: Option<u64>,
+    ) -> Result<(), RedisError> {
+        let mut connection = self.connection.clone();
+        match ttl_seconds {
+            Some(ttl_seconds) => connection.set_ex(key, value, ttl_seconds).await,
+            None => connection.set(key, value).await,
+        }
+    }
+
+    /// Increment the counter by `by` and return the new value (a missing key counts as 0)
+    pub async fn incr(&self, key: &str, by: i64) -> Result<i64, RedisError> {
+        self.connection.clone().incr(key, by).await
+    }
+
+    /// Set the expiry of the key. Returns `false` if the key does not exist.
+    pub async fn expire(&self, key: &str, seconds: i64) -> Result<bool, RedisError> {
+        self.connection.clone().expire(key, seconds).await
+    }
+
+    /// Delete the key. Returns `false` if the key did not exist.
+    pub async fn del(&self, key: &str) -> Result<bool, RedisError> {
+        let deleted: i64 = self.connection.clone().del(key).await?;
+        Ok(deleted > 0)
+    }
+
+    /// Count a call against a fixed window of `window_seconds` that allows at most `limit` calls
+    pub async fn rate_limit(
+        &self,
+        key: &str,
+        limit: i64,
+        window_seconds: i64,
+    ) -> Result<RateLimitDecision, RedisError> {
+        let (count, ttl) = self.count_in_window(key, window_seconds).await?;
+
+        Ok(RateLimitDecision::new(count, ttl, limit))
+    }
+
+    /// Increment the counter of the window (starting the window, if it is the first call) and
+    /// return the count along with the seconds until the window resets
+    async fn count_in_window(
+        &self,
+        key: &str,
+        window_seconds: i64,
+    ) -> Result<(i64, i64), RedisError> {
+        Script::new(RATE_LIMIT_SCRIPT)
+            .key(key)
+            .arg(window_seconds)
+            .invoke_async(&mut self.connection.clone())
+            .await
+    }
+}
+
+impl RateLimitDecision {
+    fn new(count: i64, ttl: i64, limit: i64) -> Self {
+        Self {
+            allowed: count <= limit,
+            remaining: (limit - count).max(0),
+            reset_in_seconds: ttl.max(0),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn increment(&self, key: &str, ttl_secs: u64) -> Result<u64, RateLimitStoreError> {
+        let (count, _) = self
+            .count_in_window(
+                &format!("{GRAPHQL_RATE_LIMIT_PREFIX}:{key}"),
+                ttl_secs.max(1) as i64,
+            )
+            .await
+            .map_err(|e| RateLimitStoreError::Generic(e.to_string()))?;
+
+        Ok(count.max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_decisions() {
+        assert_eq!(
+            RateLimitDecision::new(1, 60, 2),
+            RateLimitDecision {
+                allowed: true,
+                remaining: 1,
+                reset_in_seconds: 60
+            }
+        );
+        assert_eq!(
+            RateLimitDecision::new(2, 30, 2),
+            RateLimitDecision {
+                allowed: true,
+                remaining: 0,
+                reset_in_seconds: 30
+            }
+        );
+        // Over the limit (and a key without an expiry, for which Redis reports a negative TTL)
+        assert_eq!(
+            RateLimitDecision::new(3, -1, 2),
+            RateLimitDecision {
+                allowed: false,
+                remaining: 0,
+                reset_in_seconds: 0
+            }
+        );
+    }
+}
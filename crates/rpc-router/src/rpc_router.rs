@@ -115,6 +115,13 @@ impl<'a> Router<RequestContext<'a>> for RpcRouter {
                         QueryResponseBody::Json(value) => yield Bytes::from(value.to_string()),
                         QueryResponseBody::Raw(Some(value)) => yield Bytes::from(value),
                         QueryResponseBody::Raw(None) => yield Bytes::from_static(b"null"),
+                        QueryResponseBody::Stream(stream) => match stream.collect().await {
+                            Ok(value) => yield Bytes::from(value.to_string()),
+                            Err(err) => {
+                                tracing::error!("Error while collecting streamed response: {}", err);
+                                yield Bytes::from_static(b"null");
+                            }
+                        },
                     };
 
                     emit_id_and_close!();
@@ -0,0 +1,112 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+
+use core_model_builder::{
+    ast::ast_types::{AstExpr, default_span},
+    builder::system_builder::BaseModelSystem,
+    error::ModelBuildingError,
+    plugin::{BuildMode, CoreSubsystemBuild, GraphQLSubsystemBuild, Interception},
+    typechecker::{
+        annotation::{AnnotationSpec, AnnotationTarget, MappedAnnotationParamSpec},
+        typ::TypecheckedSystem,
+    },
+};
+use core_plugin_interface::interface::{SubsystemBuild, SubsystemBuilder};
+use core_plugin_shared::{
+    interception::{InterceptorErrorPolicy, InterceptorIndex, InterceptorKind},
+    serializable_system::{SerializableCoreBytes, SerializableGraphQLBytes},
+    system_serializer::SystemSerializer,
+};
+
+#[derive(Default)]
+pub struct SearchSubsystemBuilder {}
+
+#[async_trait]
+impl SubsystemBuilder for SearchSubsystemBuilder {
+    fn id(&self) -> &'static str {
+        "search"
+    }
+
+    fn annotations(&self) -> Vec<(&'static str, AnnotationSpec)> {
+        vec![(
+            // `@indexed(searchBackend="es", index="todos", fields=["title", "body"])`
+            "indexed",
+            AnnotationSpec {
+                targets: &[AnnotationTarget::Type],
+                no_params: false,
+                single_params: false,
+                mapped_params: Some(&[
+                    MappedAnnotationParamSpec {
+                        name: "searchBackend",
+                        optional: false,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "index",
+                        optional: true,
+                    },
+                    MappedAnnotationParamSpec {
+                        name: "fields",
+                        optional: true,
+                    },
+                ]),
+            },
+        )]
+    }
+
+    async fn build(
+        &self,
+        typechecked_system: &TypecheckedSystem,
+        _base_system: &BaseModelSystem,
+        _build_mode: BuildMode,
+    ) -> Result<Option<SubsystemBuild>, ModelBuildingError> {
+        let Some(subsystem) = crate::system_builder::build(typechecked_system)? else {
+            return Ok(None);
+        };
+
+        let serialized_subsystem = subsystem
+            .serialize()
+            .map_err(ModelBuildingError::Serialize)?;
+
+        // Keep the indices in sync by wrapping each mutation of an indexed entity
+        let interceptions = subsystem
+            .sync_interceptors
+            .iter()
+            .enumerate()
+            .map(|(index, interceptor)| Interception {
+                expr: AstExpr::StringLiteral(
+                    format!("mutation {}", interceptor.mutation_name),
+                    default_span(),
+                ),
+                kind: InterceptorKind::Around,
+                index: InterceptorIndex(index),
+                error_policy: InterceptorErrorPolicy::Abort,
+            })
+            .collect();
+
+        let graphql = GraphQLSubsystemBuild {
+            id: self.id().to_string(),
+            serialized_subsystem: SerializableGraphQLBytes(serialized_subsystem),
+            query_names: subsystem.indices.keys().cloned().collect(),
+            mutation_names: vec![],
+            interceptions,
+        };
+
+        Ok(Some(SubsystemBuild {
+            id: self.id(),
+            graphql: Some(graphql),
+            rest: None,
+            rpc: None,
+            core: CoreSubsystemBuild {
+                serialized_subsystem: SerializableCoreBytes(vec![]),
+            },
+        }))
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use codemap_diagnostic::{Diagnostic, Level, SpanLabel, SpanStyle};
+use heck::{ToLowerCamelCase, ToSnakeCase};
+
+use core_model::mapped_arena::MappedArena;
+use core_model_builder::{
+    ast::ast_types::{AstAnnotation, AstAnnotationParams, AstExpr, AstModel},
+    builder::resolved_builder::AnnotationMapHelper,
+    error::ModelBuildingError,
+    typechecker::{
+        Typed,
+        typ::{Module, Type, TypecheckedSystem},
+    },
+};
+use postgres_core_builder::naming::ToPlural;
+use search_model::{
+    index::{SearchBackendKind, SearchIndex, SearchPkKind, SyncInterceptor, SyncKind},
+    subsystem::SearchSubsystem,
+};
+
+pub fn build(
+    typechecked_system: &TypecheckedSystem,
+) -> Result<Option<SearchSubsystem>, ModelBuildingError> {
+    let mut indices = MappedArena::default();
+    let mut sync_interceptors = vec![];
+    let mut errors = vec![];
+
+    for (_, Module(module)) in typechecked_system.modules.iter() {
+        let is_postgres_module = module.annotations.contains("postgres");
+
+        for typ in module.types.iter() {
+            let Some(Type::Composite(ct)) = typechecked_system.types.get_by_key(&typ.name) else {
+                continue;
+            };
+            let Some(annotation) = ct.annotations.annotations.get("indexed") else {
+                continue;
+            };
+
+            if !is_postgres_module {
+                errors.push(error(
+                    annotation,
+                    format!(
+                        "@indexed may only be used on types in a @postgres module (found on '{}')",
+                        ct.name
+                    ),
+                ));
+                continue;
+            }
+
+            let Some(index) = build_index(ct, annotation, typechecked_system, &mut errors) else {
+                continue;
+            };
+
+            let plural_name = plural_name(ct);
+            let mutations = [
+                (format!("create{}", ct.name), SyncKind::Upsert),
+                (format!("create{plural_name}"), SyncKind::Upsert),
+                (format!("update{}", ct.name), SyncKind::Upsert),
+                (format!("update{plural_name}"), SyncKind::Upsert),
+                (format!("delete{}", ct.name), SyncKind::Delete),
+                (format!("delete{plural_name}"), SyncKind::Delete),
+            ];
+            sync_interceptors.extend(mutations.into_iter().map(|(mutation_name, kind)| {
+                SyncInterceptor {
+                    mutation_name,
+                    index_name: index.search_query_name.clone(),
+                    kind,
+                }
+            }));
+
+            indices.add(&index.search_query_name.clone(), index);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(ModelBuildingError::Diagnosis(errors));
+    }
+
+    if indices.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(SearchSubsystem {
+        indices,
+        sync_interceptors,
+    }))
+}
+
+fn build_index(
+    ct: &AstModel<Typed>,
+    annotation: &AstAnnotation<Typed>,
+    typechecked_system: &TypecheckedSystem,
+    errors: &mut Vec<Diagnostic>,
+) -> Option<SearchIndex> {
+    let AstAnnotationParams::Map(params, _) = &annotation.params else {
+        errors.push(error(annotation, "@indexed expects named parameters"));
+        return None;
+    };
+
+    let backend = match params.get("searchBackend") {
+        Some(AstExpr::StringLiteral(name, _)) => match SearchBackendKind::from_name(name) {
+            Some(backend) => backend,
+            None => {
+                errors.push(error(
+                    annotation,
+                    format!(
+                        "Unknown search backend '{name}'. Supported backends: es, elasticsearch, opensearch"
+                    ),
+                ));
+                return None;
+            }
+        },
+        _ => {
+            errors.push(error(
+                annotation,
+                "@indexed requires a string 'searchBackend' parameter",
+            ));
+            return None;
+        }
+    };
+
+    let pk_fields: Vec<_> = ct
+        .fields
+        .iter()
+        .filter(|field| field.annotations.contains("pk"))
+        .collect();
+    let [pk_field] = pk_fields.as_slice() else {
+        errors.push(error(
+            annotation,
+            format!(
+                "@indexed requires '{}' to have exactly one @pk field",
+                ct.name
+            ),
+        ));
+        return None;
+    };
+    let pk_kind = match pk_field.typ.name().as_str() {
+        "Int" => SearchPkKind::Int,
+        _ => SearchPkKind::String,
+    };
+
+    let is_scalar = |type_name: &str| {
+        matches!(
+            typechecked_system.types.get_by_key(type_name),
+            Some(Type::Primitive(_))
+        )
+    };
+
+    let fields = match params.get("fields") {
+        Some(AstExpr::StringList(fields, _)) => fields.clone(),
+        Some(AstExpr::StringLiteral(field, _)) => vec![field.clone()],
+        Some(_) => {
+            errors.push(error(
+                annotation,
+                "Expected a string or string list for 'fields'",
+            ));
+            return None;
+        }
+        // By default, index all string fields
+        None => ct
+            .fields
+            .iter()
+            .filter(|field| field.name != pk_field.name && field.typ.name() == "String")
+            .map(|field| field.name.clone())
+            .collect(),
+    };
+
+    for field_name in fields.iter() {
+        match ct.fields.iter().find(|field| &field.name == field_name) {
+            Some(field) if is_scalar(&field.typ.name()) => {}
+            Some(_) => errors.push(error(
+                annotation,
+                format!(
+                    "Field '{field_name}' of '{}' is not a scalar field and cannot be indexed",
+                    ct.name
+                ),
+            )),
+            None => errors.push(error(
+                annotation,
+                format!(
+                    "Field '{field_name}' specified in @indexed was not found on type '{}'",
+                    ct.name
+                ),
+            )),
+        }
+    }
+
+    if fields.is_empty() {
+        errors.push(error(
+            annotation,
+            format!(
+                "@indexed requires at least one field to index on '{}'",
+                ct.name
+            ),
+        ));
+        return None;
+    }
+
+    let plural_name = plural_name(ct);
+
+    let index_name = match params.get("index") {
+        Some(AstExpr::StringLiteral(index_name, _)) => index_name.clone(),
+        Some(_) => {
+            errors.push(error(annotation, "Expected a string for 'index'"));
+            return None;
+        }
+        None => plural_name.to_snake_case(),
+    };
+
+    Some(SearchIndex {
+        entity_name: ct.name.clone(),
+        index_name,
+        backend,
+        pk_field: pk_field.name.clone(),
+        pk_kind,
+        fields,
+        search_query_name: format!("search{plural_name}"),
+        collection_query_name: plural_name.to_lower_camel_case(),
+        doc_comments: Some(format!("Full-text search over {plural_name}")),
+    })
+}
+
+/// The plural name as used by the Postgres subsystem to name operations (e.g. `Todos`)
+fn plural_name(ct: &AstModel<Typed>) -> String {
+    ct.annotations
+        .annotations
+        .get("plural")
+        .and_then(|annotation| match &annotation.params {
+            AstAnnotationParams::Single(AstExpr::StringLiteral(plural, _), _) => {
+                Some(plural.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| ct.name.to_plural())
+}
+
+fn error(annotation: &AstAnnotation<Typed>, message: impl Into<String>) -> Diagnostic {
+    Diagnostic {
+        level: Level::Error,
+        message: message.into(),
+        code: Some("C000".to_string()),
+        spans: vec![SpanLabel {
+            span: annotation.span,
+            style: SpanStyle::Primary,
+            label: None,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use builder::{load_subsystem_builders, parser, typechecker};
+    use codemap::CodeMap;
+
+    use super::*;
+    use crate::SearchSubsystemBuilder;
+
+    fn build_from_src(src: &str) -> Result<Option<SearchSubsystem>, ModelBuildingError> {
+        let mut codemap = CodeMap::new();
+        let subsystem_builders = load_subsystem_builders(vec![
+            Box::new(postgres_builder::PostgresSubsystemBuilder::default()),
+            Box::new(SearchSubsystemBuilder::default()),
+        ])
+        .unwrap();
+        let parsed = parser::parse_str(src, &mut codemap, "input.exo").unwrap();
+        let typechecked_system = typechecker::build(&subsystem_builders, parsed).unwrap();
+
+        build(&typechecked_system)
+    }
+
+    fn error_messages(result: Result<Option<SearchSubsystem>, ModelBuildingError>) -> Vec<String> {
+        match result {
+            Err(ModelBuildingError::Diagnosis(errors)) => {
+                errors.into_iter().map(|error| error.message).collect()
+            }
+            _ => panic!("Expected diagnostics"),
+        }
+    }
+
+    #[test]
+    fn builds_indices_and_interceptors() {
+        let subsystem = build_from_src(
+            r#"
+            @postgres
+            module TodoModule {
+                @indexed(searchBackend="es")
+                type Todo {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                    notes: String
+                    completed: Boolean
+                }
+            }
+            "#,
+        )
+        .unwrap()
+        .unwrap();
+
+        let index = subsystem.indices.get_by_key("searchTodos").unwrap();
+        assert_eq!(index.entity_name, "Todo");
+        assert_eq!(index.index_name, "todos");
+        assert_eq!(index.backend, SearchBackendKind::Elasticsearch);
+        assert_eq!(index.pk_field, "id");
+        assert_eq!(index.pk_kind, SearchPkKind::Int);
+        // Only string fields by default
+        assert_eq!(index.fields, vec!["title", "notes"]);
+        assert_eq!(index.collection_query_name, "todos");
+
+        let interceptors: Vec<_> = subsystem
+            .sync_interceptors
+            .iter()
+            .map(|interceptor| {
+                assert_eq!(interceptor.index_name, "searchTodos");
+                (interceptor.mutation_name.as_str(), interceptor.kind)
+            })
+            .collect();
+        assert_eq!(
+            interceptors,
+            vec![
+                ("createTodo", SyncKind::Upsert),
+                ("createTodos", SyncKind::Upsert),
+                ("updateTodo", SyncKind::Upsert),
+                ("updateTodos", SyncKind::Upsert),
+                ("deleteTodo", SyncKind::Delete),
+                ("deleteTodos", SyncKind::Delete),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_index_and_fields() {
+        let subsystem = build_from_src(
+            r#"
+            @postgres
+            module ArticleModule {
+                @indexed(searchBackend="opensearch", index="published_articles", fields=["title", "views"])
+                @plural("Stories")
+                type Article {
+                    @pk slug: String
+                    title: String
+                    body: String
+                    views: Int
+                }
+            }
+            "#,
+        )
+        .unwrap()
+        .unwrap();
+
+        let index = subsystem.indices.get_by_key("searchStories").unwrap();
+        assert_eq!(index.index_name, "published_articles");
+        assert_eq!(index.backend, SearchBackendKind::OpenSearch);
+        assert_eq!(index.pk_kind, SearchPkKind::String);
+        assert_eq!(index.fields, vec!["title", "views"]);
+        assert_eq!(index.collection_query_name, "stories");
+        assert!(
+            subsystem
+                .sync_interceptors
+                .iter()
+                .any(|interceptor| interceptor.mutation_name == "deleteStories")
+        );
+    }
+
+    #[test]
+    fn no_subsystem_without_indexed_types() {
+        let subsystem = build_from_src(
+            r#"
+            @postgres
+            module TodoModule {
+                type Todo {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert!(subsystem.is_none());
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let errors = error_messages(build_from_src(
+            r#"
+            @postgres
+            module TodoModule {
+                @indexed(searchBackend="solr")
+                type Todo {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+
+                @indexed(searchBackend="es", fields=["title", "missing"])
+                type Note {
+                    @pk id: Int = autoIncrement()
+                    title: String
+                }
+
+                @indexed(searchBackend="es")
+                type Counter {
+                    @pk id: Int = autoIncrement()
+                    value: Int
+                }
+            }
+            "#,
+        ));
+
+        assert_eq!(
+            errors,
+            vec![
+                "Unknown search backend 'solr'. Supported backends: es, elasticsearch, opensearch",
+                "Field 'missing' specified in @indexed was not found on type 'Note'",
+                "@indexed requires at least one field to index on 'Counter'",
+            ]
+        );
+    }
+}
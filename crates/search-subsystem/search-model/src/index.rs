@@ -0,0 +1,129 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::types::{BaseType, FieldDefinition, InputValueDefinition, Type};
+use async_graphql_value::Name;
+use serde::{Deserialize, Serialize};
+
+use core_model::type_normalization::{
+    FieldDefinitionProvider, default_positioned, default_positioned_name,
+};
+
+use crate::subsystem::SearchSubsystem;
+
+/// The search engine that holds the documents of an index.
+///
+/// Elasticsearch and OpenSearch share the document and search APIs we use, so they differ only
+/// in how the user refers to them in `@indexed(searchBackend: ...)`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBackendKind {
+    Elasticsearch,
+    OpenSearch,
+}
+
+impl SearchBackendKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "es" | "elasticsearch" => Some(SearchBackendKind::Elasticsearch),
+            "opensearch" => Some(SearchBackendKind::OpenSearch),
+            _ => None,
+        }
+    }
+}
+
+/// How to convert a document id (always a string in the search engine) back to the primary key
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchPkKind {
+    Int,
+    String,
+}
+
+/// A Postgres entity annotated with `@indexed`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchIndex {
+    /// The entity type name (e.g. `Todo`)
+    pub entity_name: String,
+    /// The name of the index in the search engine (e.g. `todos`)
+    pub index_name: String,
+    pub backend: SearchBackendKind,
+
+    pub pk_field: String,
+    pub pk_kind: SearchPkKind,
+    /// The fields copied into the search document (and searched by default)
+    pub fields: Vec<String>,
+
+    /// The generated search query (e.g. `searchTodos`)
+    pub search_query_name: String,
+    /// The Postgres collection query used to hydrate search hits (e.g. `todos`)
+    pub collection_query_name: String,
+    pub doc_comments: Option<String>,
+}
+
+/// Parameter names of the generated search query
+pub const QUERY_PARAM: &str = "query";
+pub const LIMIT_PARAM: &str = "limit";
+pub const OFFSET_PARAM: &str = "offset";
+
+impl FieldDefinitionProvider<SearchSubsystem> for SearchIndex {
+    fn field_definition(&self, _system: &SearchSubsystem) -> FieldDefinition {
+        let argument = |name: &str, type_name: &str, nullable: bool| {
+            default_positioned(InputValueDefinition {
+                description: None,
+                name: default_positioned_name(name),
+                ty: default_positioned(Type {
+                    base: BaseType::Named(Name::new(type_name)),
+                    nullable,
+                }),
+                default_value: None,
+                directives: vec![],
+            })
+        };
+
+        // searchTodos(query: String!, limit: Int, offset: Int): [Todo!]!
+        FieldDefinition {
+            description: self.doc_comments.clone(),
+            name: default_positioned_name(&self.search_query_name),
+            arguments: vec![
+                argument(QUERY_PARAM, "String", false),
+                argument(LIMIT_PARAM, "Int", true),
+                argument(OFFSET_PARAM, "Int", true),
+            ],
+            ty: default_positioned(Type {
+                base: BaseType::List(Box::new(Type {
+                    base: BaseType::Named(Name::new(&self.entity_name)),
+                    nullable: false,
+                })),
+                nullable: false,
+            }),
+            directives: vec![],
+        }
+    }
+}
+
+/// The kind of change a mutation makes to the documents of an index
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncKind {
+    /// Create and update mutations: (re)index the returned entities
+    Upsert,
+    /// Delete mutations: remove the returned entities from the index
+    Delete,
+}
+
+/// An around interceptor that keeps an index in sync with a Postgres mutation.
+///
+/// The interceptor index (as seen by the interceptor weaver) is the position in
+/// [`SearchSubsystem::sync_interceptors`].
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SyncInterceptor {
+    /// The mutation being intercepted (e.g. `createTodo`)
+    pub mutation_name: String,
+    /// Key of the index in [`SearchSubsystem::indices`]
+    pub index_name: String,
+    pub kind: SyncKind,
+}
@@ -0,0 +1,64 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_graphql_parser::types::{FieldDefinition, TypeDefinition};
+
+use core_model::{mapped_arena::MappedArena, type_normalization::FieldDefinitionProvider};
+use core_plugin_shared::{error::ModelSerializationError, system_serializer::SystemSerializer};
+use serde::{Deserialize, Serialize};
+
+use crate::index::{SearchIndex, SyncInterceptor};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SearchSubsystem {
+    /// Keyed by the search query name
+    pub indices: MappedArena<SearchIndex>,
+    pub sync_interceptors: Vec<SyncInterceptor>,
+}
+
+impl SearchSubsystem {
+    pub fn schema_queries(&self) -> Vec<FieldDefinition> {
+        self.indices
+            .iter()
+            .map(|(_, index)| index.field_definition(self))
+            .collect()
+    }
+
+    pub fn schema_mutations(&self) -> Vec<FieldDefinition> {
+        vec![]
+    }
+
+    pub fn schema_types(&self) -> Vec<TypeDefinition> {
+        // Search queries return the entity types, which the Postgres subsystem already defines
+        vec![]
+    }
+
+    pub fn index_for_entity(&self, entity_name: &str) -> Option<&SearchIndex> {
+        self.indices
+            .iter()
+            .map(|(_, index)| index)
+            .find(|index| index.entity_name == entity_name)
+    }
+}
+
+impl SystemSerializer for SearchSubsystem {
+    type Underlying = Self;
+
+    fn serialize(&self) -> Result<Vec<u8>, ModelSerializationError> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(ModelSerializationError::Serialize)
+    }
+
+    fn deserialize_reader(
+        mut reader: impl std::io::Read,
+    ) -> Result<Self::Underlying, ModelSerializationError> {
+        bincode::serde::decode_from_std_read(&mut reader, bincode::config::standard())
+            .map_err(ModelSerializationError::Deserialize)
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use reqwest::{
+    Client, RequestBuilder, StatusCode, Url,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+};
+use serde_json::{Value, json};
+
+use crate::search_execution_error::SearchExecutionError;
+
+/// A minimal client for the document and search APIs shared by Elasticsearch and OpenSearch.
+pub struct SearchBackend {
+    client: Client,
+    base_url: Url,
+    api_key: Option<String>,
+}
+
+impl SearchBackend {
+    pub fn new(base_url: &str, api_key: Option<String>) -> Result<Self, SearchExecutionError> {
+        let client = Client::builder().build()?;
+        let base_url = Url::parse(base_url).map_err(|e| {
+            SearchExecutionError::Backend(format!("Invalid search backend URL '{base_url}': {e}"))
+        })?;
+
+        Ok(Self {
+            client,
+            base_url,
+            api_key,
+        })
+    }
+
+    /// Build a URL such as `<base>/todos/_doc/42`, escaping each segment
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url
+    }
+
+    /// Search the given fields and return document ids, ordered by relevance
+    pub async fn search(
+        &self,
+        index: &str,
+        query: &str,
+        fields: &[String],
+        from: i64,
+        size: i64,
+    ) -> Result<Vec<String>, SearchExecutionError> {
+        let body = json!({
+            "query": {
+                "multi_match": {
+                    "query": query,
+                    "fields": fields,
+                }
+            },
+            "from": from,
+            "size": size,
+            "_source": false,
+        });
+
+        let request = self.client.post(self.url(&[index, "_search"]));
+        let response = self.send(request, Some(&body)).await?;
+
+        let hits = response["hits"]["hits"].as_array().ok_or_else(|| {
+            SearchExecutionError::Backend("Unexpected search response (missing hits)".to_string())
+        })?;
+
+        Ok(hits
+            .iter()
+            .filter_map(|hit| hit["_id"].as_str().map(|id| id.to_string()))
+            .collect())
+    }
+
+    pub async fn index_document(
+        &self,
+        index: &str,
+        id: &str,
+        document: &Value,
+    ) -> Result<(), SearchExecutionError> {
+        let request = self.client.put(self.url(&[index, "_doc", id]));
+        self.send(request, Some(document)).await.map(|_| ())
+    }
+
+    pub async fn delete_document(&self, index: &str, id: &str) -> Result<(), SearchExecutionError> {
+        let request = self.client.delete(self.url(&[index, "_doc", id]));
+
+        match self.send(request, None).await {
+            // Deleting a document that was never indexed is not an error
+            Err(SearchExecutionError::Status(StatusCode::NOT_FOUND, _)) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn send(
+        &self,
+        request: RequestBuilder,
+        body: Option<&Value>,
+    ) -> Result<Value, SearchExecutionError> {
+        let request = match &self.api_key {
+            Some(api_key) => request.header(AUTHORIZATION, format!("ApiKey {api_key}")),
+            None => request,
+        };
+        let request = match body {
+            Some(body) => request
+                .header(CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(body)?),
+            None => request,
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+        let bytes = response.bytes().await?;
+
+        if !status.is_success() {
+            return Err(SearchExecutionError::Status(
+                status,
+                String::from_utf8_lossy(&bytes).to_string(),
+            ));
+        }
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_path_segments() {
+        let backend = SearchBackend::new("http://localhost:9200/", None).unwrap();
+
+        assert_eq!(
+            backend.url(&["todos", "_doc", "a/b c"]).as_str(),
+            "http://localhost:9200/todos/_doc/a%2Fb%20c"
+        );
+    }
+}
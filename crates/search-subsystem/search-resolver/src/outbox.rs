@@ -0,0 +1,283 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A transactional outbox that applies document changes to the search backend.
+//!
+//! Mutations record their changes in the `exograph.search_outbox` table in the same transaction
+//! as the mutation itself, so a change is recorded if and only if the mutation commits (and it
+//! survives a restart of the server). A background service applies the recorded changes in order,
+//! retrying failed changes with a backoff until they succeed, so the latency and availability of
+//! the search backend do not affect mutations.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio_postgres::types::Type;
+
+use common::context::RequestContext;
+use core_plugin_interface::interface::{SubsystemBackgroundService, SystemRouterRef};
+use exo_env::Environment;
+use exo_sql::{DatabaseExecutor, SQLParamContainer, database_error::DatabaseError};
+
+use crate::backend::SearchBackend;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const BATCH_SIZE: i64 = 100;
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Key of the advisory lock held while applying a batch, so that only one server applies changes
+/// at a time (and changes to the same document are applied in order)
+const OUTBOX_LOCK_KEY: i64 = 0x6578_6f5f_7365_6172;
+
+// The table lives in its own schema, so that migrations of the model's schemas leave it alone
+const CREATE_OUTBOX_TABLE: &str = r#"
+BEGIN;
+SELECT pg_advisory_xact_lock(7311716450784600434);
+CREATE SCHEMA IF NOT EXISTS exograph;
+CREATE TABLE IF NOT EXISTS exograph.search_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    index_name TEXT NOT NULL,
+    document_id TEXT NOT NULL,
+    document JSONB,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_error TEXT
+);
+CREATE INDEX IF NOT EXISTS search_outbox_document_idx
+    ON exograph.search_outbox (index_name, document_id, id);
+COMMIT;
+"#;
+
+const INSERT_CHANGE: &str =
+    "INSERT INTO exograph.search_outbox (index_name, document_id, document) VALUES ($1, $2, $3)";
+
+// Changes that are due, except those after a change to the same document that isn't (which must
+// be applied first)
+const SELECT_DUE_CHANGES: &str = r#"
+SELECT id, index_name, document_id, document, attempts
+FROM exograph.search_outbox pending
+WHERE pending.next_attempt_at <= now()
+  AND NOT EXISTS (
+    SELECT 1 FROM exograph.search_outbox earlier
+    WHERE earlier.index_name = pending.index_name
+      AND earlier.document_id = pending.document_id
+      AND earlier.id < pending.id
+      AND earlier.next_attempt_at > now()
+  )
+ORDER BY id
+LIMIT $1
+"#;
+
+const DELETE_CHANGE: &str = "DELETE FROM exograph.search_outbox WHERE id = $1";
+
+const RESCHEDULE_CHANGE: &str = "UPDATE exograph.search_outbox SET attempts = $2, next_attempt_at = now() + make_interval(secs => $3), last_error = $4 WHERE id = $1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboxEvent {
+    pub index_name: String,
+    pub document_id: String,
+    pub change: DocumentChange,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentChange {
+    Upsert(Value),
+    Delete,
+}
+
+#[derive(Clone)]
+pub struct SearchOutbox {
+    executor: Arc<DatabaseExecutor>,
+}
+
+impl SearchOutbox {
+    /// Create the outbox table (if it doesn't exist yet)
+    pub async fn new(executor: Arc<DatabaseExecutor>) -> Result<Self, DatabaseError> {
+        let client = executor.database_client.get_client().await?;
+        client.batch_execute(CREATE_OUTBOX_TABLE).await?;
+
+        Ok(Self { executor })
+    }
+
+    /// Record a change as a part of the request's transaction
+    pub async fn record(
+        &self,
+        event: OutboxEvent,
+        request_context: &RequestContext<'_>,
+    ) -> Result<(), DatabaseError> {
+        let document = match event.change {
+            DocumentChange::Upsert(document) => Some(document),
+            DocumentChange::Delete => None,
+        };
+
+        let mut tx_holder = request_context
+            .system_context
+            .transaction_holder
+            .lock()
+            .await;
+        self.executor
+            .execute_statement(
+                INSERT_CHANGE,
+                &[
+                    SQLParamContainer::string(event.index_name),
+                    SQLParamContainer::string(event.document_id),
+                    SQLParamContainer::new(document, Type::JSONB),
+                ],
+                &mut tx_holder,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Applies the changes recorded in the outbox to the search backend
+pub struct SearchOutboxService {
+    executor: Arc<DatabaseExecutor>,
+    backend: Arc<SearchBackend>,
+}
+
+#[async_trait]
+impl SubsystemBackgroundService for SearchOutboxService {
+    async fn run(&self, _router: SystemRouterRef, _env: Arc<dyn Environment>) {
+        loop {
+            match self.apply_batch().await {
+                // There may be more changes waiting, so continue right away
+                Ok(applied) if applied == BATCH_SIZE as usize => continue,
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to process the search outbox: {e}"),
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl SearchOutboxService {
+    pub fn new(executor: Arc<DatabaseExecutor>, backend: Arc<SearchBackend>) -> Self {
+        Self { executor, backend }
+    }
+
+    /// Apply a batch of due changes, returning the number of changes applied.
+    ///
+    /// A change is removed from the outbox only once the backend has applied it. If applying a
+    /// change fails, it is rescheduled with a backoff, and later changes to the same document wait
+    /// for it. Since the outbox is updated after the backend is, a change may be applied more than
+    /// once (which is harmless, since applying a change is idempotent).
+    async fn apply_batch(&self) -> Result<usize, DatabaseError> {
+        let mut client = self.executor.database_client.get_client().await?;
+        let tx = client.transaction().await?;
+
+        let locked: bool = tx
+            .query_one("SELECT pg_try_advisory_xact_lock($1)", &[&OUTBOX_LOCK_KEY])
+            .await?
+            .get(0);
+        if !locked {
+            // Another server is applying changes
+            return Ok(0);
+        }
+
+        let rows = tx.query(SELECT_DUE_CHANGES, &[&BATCH_SIZE]).await?;
+
+        let mut applied = 0;
+        let mut failed_documents = HashSet::new();
+
+        for row in rows {
+            let id: i64 = row.get("id");
+            let attempts: i32 = row.get("attempts");
+            let document: Option<Value> = row.get("document");
+            let event = OutboxEvent {
+                index_name: row.get("index_name"),
+                document_id: row.get("document_id"),
+                change: match document {
+                    Some(document) => DocumentChange::Upsert(document),
+                    None => DocumentChange::Delete,
+                },
+            };
+
+            let document_key = (event.index_name.clone(), event.document_id.clone());
+            if failed_documents.contains(&document_key) {
+                continue;
+            }
+
+            match self.apply(&event).await {
+                Ok(()) => {
+                    tx.execute(DELETE_CHANGE, &[&id]).await?;
+                    applied += 1;
+                }
+                Err(e) => {
+                    let attempts = attempts.saturating_add(1);
+                    let backoff = backoff(attempts);
+                    tracing::warn!(
+                        "Failed to sync document '{}' to index '{}' (attempt {attempts}, retrying in {}s): {e}",
+                        event.document_id,
+                        event.index_name,
+                        backoff.as_secs()
+                    );
+                    tx.execute(
+                        RESCHEDULE_CHANGE,
+                        &[&id, &attempts, &backoff.as_secs_f64(), &e],
+                    )
+                    .await?;
+                    failed_documents.insert(document_key);
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(applied)
+    }
+
+    async fn apply(&self, event: &OutboxEvent) -> Result<(), String> {
+        let result = match &event.change {
+            DocumentChange::Upsert(document) => {
+                self.backend
+                    .index_document(&event.index_name, &event.document_id, document)
+                    .await
+            }
+            DocumentChange::Delete => {
+                self.backend
+                    .delete_document(&event.index_name, &event.document_id)
+                    .await
+            }
+        };
+
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// The delay before the next attempt to apply a change that has failed `attempts` times
+fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    (BASE_BACKOFF * 2u32.pow(exponent)).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(5), Duration::from_secs(16));
+        assert_eq!(backoff(9), Duration::from_secs(256));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        assert_eq!(backoff(i32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn lock_keys_match() {
+        // The startup script can't take parameters, so it spells out the key
+        assert!(CREATE_OUTBOX_TABLE.contains(&format!("pg_advisory_xact_lock({OUTBOX_LOCK_KEY})")));
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use common::env_const::{EXO_SEARCH_API_KEY, EXO_SEARCH_URL};
+use core_plugin_interface::interface::{SubsystemLoader, SubsystemLoadingError, SubsystemResolver};
+use core_plugin_shared::{
+    serializable_system::SerializableSubsystem, system_serializer::SystemSerializer,
+};
+use core_resolver::plugin::SubsystemGraphQLResolver;
+use exo_env::Environment;
+use postgres_core_model::connection_settings::ConnectionSettings;
+use postgres_core_resolver::database_helper::create_database_executor;
+use search_model::subsystem::SearchSubsystem;
+
+use crate::{
+    SearchSubsystemResolver,
+    backend::SearchBackend,
+    outbox::{SearchOutbox, SearchOutboxService},
+};
+
+pub struct SearchSubsystemLoader {}
+
+#[async_trait]
+impl SubsystemLoader for SearchSubsystemLoader {
+    fn id(&self) -> &'static str {
+        "search"
+    }
+
+    async fn init(
+        &mut self,
+        serialized_subsystem: SerializableSubsystem,
+        env: Arc<dyn Environment>,
+    ) -> Result<Box<SubsystemResolver>, SubsystemLoadingError> {
+        let mut outbox_service = None;
+
+        let graphql = match serialized_subsystem.graphql {
+            Some(graphql) => {
+                let subsystem = SearchSubsystem::deserialize(graphql.0)?;
+
+                let url = env.get(EXO_SEARCH_URL).ok_or_else(|| {
+                    SubsystemLoadingError::Config(format!(
+                        "{EXO_SEARCH_URL} must be set to use @indexed types"
+                    ))
+                })?;
+                let backend = Arc::new(
+                    SearchBackend::new(&url, env.get(EXO_SEARCH_API_KEY)).map_err(|e| {
+                        SubsystemLoadingError::Config(format!(
+                            "Failed to set up the search backend: {e}"
+                        ))
+                    })?,
+                );
+
+                // The outbox lives in the same database as the indexed entities, so that changes
+                // are recorded in the transaction of the mutation making them
+                let executor = Arc::new(
+                    create_database_executor(None, env.as_ref(), &ConnectionSettings::default())
+                        .await
+                        .map_err(|e| SubsystemLoadingError::BoxedError(Box::new(e)))?,
+                );
+                let outbox = SearchOutbox::new(executor.clone()).await.map_err(|e| {
+                    SubsystemLoadingError::Config(format!(
+                        "Failed to set up the search outbox: {e}"
+                    ))
+                })?;
+                outbox_service = Some(Arc::new(SearchOutboxService::new(
+                    executor,
+                    backend.clone(),
+                )));
+
+                Ok::<_, SubsystemLoadingError>(Some(Arc::new(SearchSubsystemResolver {
+                    id: self.id(),
+                    subsystem,
+                    backend,
+                    outbox,
+                })
+                    as Arc<dyn SubsystemGraphQLResolver + Send + Sync>))
+            }
+            None => Ok(None),
+        }?;
+
+        let mut resolver = SubsystemResolver::new(graphql, None, None);
+        if let Some(outbox_service) = outbox_service {
+            resolver = resolver.with_background_service(outbox_service);
+        }
+
+        Ok(Box::new(resolver))
+    }
+}
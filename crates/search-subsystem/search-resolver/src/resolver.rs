@@ -0,0 +1,450 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql_parser::types::{FieldDefinition, OperationType, TypeDefinition};
+use async_graphql_value::{Name, indexmap::IndexMap};
+use async_trait::async_trait;
+use serde_json::Value;
+
+use common::context::RequestContext;
+use common::value::Val;
+use core_plugin_shared::interception::InterceptorIndex;
+use core_resolver::{
+    InterceptedOperation, QueryResponse, QueryResponseBody,
+    plugin::{SubsystemGraphQLResolver, SubsystemResolutionError},
+    system_resolver::GraphQLSystemResolver,
+    validation::field::ValidatedField,
+};
+use search_model::{
+    index::{LIMIT_PARAM, OFFSET_PARAM, QUERY_PARAM, SearchIndex, SearchPkKind, SyncKind},
+    subsystem::SearchSubsystem,
+};
+
+use crate::{
+    backend::SearchBackend,
+    outbox::{DocumentChange, OutboxEvent, SearchOutbox},
+    search_execution_error::SearchExecutionError,
+};
+
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 100;
+
+/// Alias for the primary key we add to selections to correlate results with documents
+const PK_ALIAS: &str = "__search_pk";
+
+pub struct SearchSubsystemResolver {
+    pub id: &'static str,
+    pub subsystem: SearchSubsystem,
+    pub backend: Arc<SearchBackend>,
+    pub outbox: SearchOutbox,
+}
+
+#[async_trait]
+impl SubsystemGraphQLResolver for SearchSubsystemResolver {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn resolve<'a>(
+        &'a self,
+        field: &'a ValidatedField,
+        operation_type: OperationType,
+        request_context: &'a RequestContext<'a>,
+        system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        if operation_type != OperationType::Query {
+            return Ok(None);
+        }
+
+        let Some(index) = self.subsystem.indices.get_by_key(&field.name) else {
+            return Ok(None);
+        };
+
+        let entities = self
+            .search(index, field, request_context, system_resolver)
+            .await?;
+
+        Ok(Some(QueryResponse {
+            body: QueryResponseBody::Json(Value::Array(entities)),
+            headers: vec![],
+        }))
+    }
+
+    async fn invoke_interceptor<'a>(
+        &'a self,
+        interceptor_index: InterceptorIndex,
+        intercepted_operation: &'a InterceptedOperation<'a>,
+        request_context: &'a RequestContext<'a>,
+        _system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Option<QueryResponse>, SubsystemResolutionError> {
+        let interceptor = self
+            .subsystem
+            .sync_interceptors
+            .get(interceptor_index.0)
+            .ok_or(SubsystemResolutionError::NoInterceptorFound)?;
+        let index = self
+            .subsystem
+            .indices
+            .get_by_key(&interceptor.index_name)
+            .ok_or(SubsystemResolutionError::NoInterceptorFound)?;
+
+        // Proceed with the mutation, additionally selecting what we need to build the documents
+        let mut operation = intercepted_operation.operation().clone();
+        operation
+            .subfields
+            .push(aliased_field(PK_ALIAS, &index.pk_field));
+        if interceptor.kind == SyncKind::Upsert {
+            operation.subfields.extend(
+                index
+                    .fields
+                    .iter()
+                    .map(|field| aliased_field(&document_field_alias(field), field)),
+            );
+        }
+
+        let response = intercepted_operation
+            .with_operation(&operation)
+            .resolve(request_context)
+            .await
+            .map_err(SearchExecutionError::from)?;
+        let mut body = response
+            .body
+            .to_json()
+            .map_err(SearchExecutionError::from)?;
+
+        // Single mutations return an object (or null if nothing matched), collection mutations an array
+        let entities: Vec<&mut Value> = match &mut body {
+            Value::Array(entities) => entities.iter_mut().collect(),
+            entity @ Value::Object(_) => vec![entity],
+            _ => vec![],
+        };
+        // Recorded in the mutation's transaction, so rolled back along with the mutation
+        for entity in entities {
+            if let Some(event) = outbox_event(index, interceptor.kind, entity) {
+                self.outbox
+                    .record(event, request_context)
+                    .await
+                    .map_err(SearchExecutionError::from)?;
+            }
+        }
+
+        Ok(Some(QueryResponse {
+            body: QueryResponseBody::Json(body),
+            headers: response.headers,
+        }))
+    }
+
+    fn schema_queries(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_queries()
+    }
+
+    fn schema_mutations(&self) -> Vec<FieldDefinition> {
+        self.subsystem.schema_mutations()
+    }
+
+    fn schema_types(&self) -> Vec<TypeDefinition> {
+        self.subsystem.schema_types()
+    }
+}
+
+impl SearchSubsystemResolver {
+    /// Find matching document ids in the search backend and hydrate them using the Postgres
+    /// collection query. Since the collection query runs with the same request context, the
+    /// entity's access control applies (inaccessible entities are dropped from the result).
+    async fn search<'a>(
+        &'a self,
+        index: &'a SearchIndex,
+        field: &'a ValidatedField,
+        request_context: &'a RequestContext<'a>,
+        system_resolver: &'a GraphQLSystemResolver,
+    ) -> Result<Vec<Value>, SearchExecutionError> {
+        let query = match field.arguments.get(QUERY_PARAM) {
+            Some(Val::String(query)) => query,
+            _ => {
+                return Err(SearchExecutionError::InvalidArgument(
+                    QUERY_PARAM.to_string(),
+                ));
+            }
+        };
+        let limit = int_argument(field, LIMIT_PARAM)?
+            .unwrap_or(DEFAULT_LIMIT)
+            .min(MAX_LIMIT);
+        let offset = int_argument(field, OFFSET_PARAM)?.unwrap_or(0);
+
+        let document_ids = self
+            .backend
+            .search(&index.index_name, query, &index.fields, offset, limit)
+            .await?;
+
+        if document_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let pk_values = document_ids
+            .iter()
+            .map(|id| pk_value(index, id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut subfields = field.subfields.clone();
+        subfields.push(aliased_field(PK_ALIAS, &index.pk_field));
+
+        // Equivalent to `todos(where: {id: {in: [...]}}) { ... }`
+        let hydration_field = ValidatedField {
+            alias: None,
+            name: Name::new(&index.collection_query_name),
+            arguments: IndexMap::from([(
+                "where".to_string(),
+                Val::Object(HashMap::from([(
+                    index.pk_field.clone(),
+                    Val::Object(HashMap::from([("in".to_string(), Val::List(pk_values))])),
+                )])),
+            )]),
+            subfields,
+        };
+
+        let response = system_resolver
+            .resolve_operation(OperationType::Query, &hydration_field, request_context)
+            .await?;
+
+        let Value::Array(entities) = response.body.to_json()? else {
+            return Err(SearchExecutionError::Backend(format!(
+                "Unexpected response from '{}'",
+                index.collection_query_name
+            )));
+        };
+
+        let mut entities_by_id: HashMap<String, Value> = entities
+            .into_iter()
+            .filter_map(|mut entity| {
+                let pk = entity.as_object_mut()?.remove(PK_ALIAS)?;
+                Some((document_id(&pk)?, entity))
+            })
+            .collect();
+
+        // Preserve the relevance order from the search backend
+        Ok(document_ids
+            .iter()
+            .filter_map(|id| entities_by_id.remove(id))
+            .collect())
+    }
+}
+
+/// Build the change to record for an entity returned by a mutation (stripping the fields we added
+/// to its selection), or `None` if the entity is missing (such as when nothing matched)
+fn outbox_event(index: &SearchIndex, kind: SyncKind, entity: &mut Value) -> Option<OutboxEvent> {
+    let entity = entity.as_object_mut()?;
+    let document_id = entity.remove(PK_ALIAS).as_ref().and_then(document_id)?;
+
+    let change = match kind {
+        SyncKind::Upsert => {
+            let document = index
+                .fields
+                .iter()
+                .filter_map(|field| {
+                    entity
+                        .remove(&document_field_alias(field))
+                        .map(|value| (field.clone(), value))
+                })
+                .collect();
+            DocumentChange::Upsert(Value::Object(document))
+        }
+        SyncKind::Delete => DocumentChange::Delete,
+    };
+
+    Some(OutboxEvent {
+        index_name: index.index_name.clone(),
+        document_id,
+        change,
+    })
+}
+
+fn aliased_field(alias: &str, name: &str) -> ValidatedField {
+    ValidatedField {
+        alias: Some(Name::new(alias)),
+        name: Name::new(name),
+        arguments: IndexMap::new(),
+        subfields: vec![],
+    }
+}
+
+fn document_field_alias(field_name: &str) -> String {
+    format!("__search_{field_name}")
+}
+
+fn int_argument(field: &ValidatedField, name: &str) -> Result<Option<i64>, SearchExecutionError> {
+    match field.arguments.get(name) {
+        None | Some(Val::Null) => Ok(None),
+        Some(Val::Number(number)) => match number.as_i64() {
+            Some(value) if value >= 0 => Ok(Some(value)),
+            _ => Err(SearchExecutionError::InvalidArgument(name.to_string())),
+        },
+        Some(_) => Err(SearchExecutionError::InvalidArgument(name.to_string())),
+    }
+}
+
+fn pk_value(index: &SearchIndex, document_id: &str) -> Result<Val, SearchExecutionError> {
+    match index.pk_kind {
+        SearchPkKind::Int => document_id
+            .parse::<i64>()
+            .map(|id| Val::Number(id.into()))
+            .map_err(|_| {
+                SearchExecutionError::Backend(format!(
+                    "Document id '{document_id}' in index '{}' is not an integer",
+                    index.index_name
+                ))
+            }),
+        SearchPkKind::String => Ok(Val::String(document_id.to_string())),
+    }
+}
+
+fn document_id(pk: &Value) -> Option<String> {
+    match pk {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use search_model::index::SearchBackendKind;
+    use serde_json::json;
+
+    use super::*;
+
+    fn index(pk_kind: SearchPkKind) -> SearchIndex {
+        SearchIndex {
+            entity_name: "Todo".to_string(),
+            index_name: "todos".to_string(),
+            backend: SearchBackendKind::Elasticsearch,
+            pk_field: "id".to_string(),
+            pk_kind,
+            fields: vec!["title".to_string(), "notes".to_string()],
+            search_query_name: "searchTodos".to_string(),
+            collection_query_name: "todos".to_string(),
+            doc_comments: None,
+        }
+    }
+
+    #[test]
+    fn upsert_events_strip_the_added_fields() {
+        let index = index(SearchPkKind::Int);
+        let mut entity = json!({
+            "id": 1,
+            "title": "Selected by the user",
+            "__search_pk": 1,
+            "__search_title": "Buy milk",
+            "__search_notes": null,
+        });
+
+        let event = outbox_event(&index, SyncKind::Upsert, &mut entity);
+
+        assert_eq!(
+            event,
+            Some(OutboxEvent {
+                index_name: "todos".to_string(),
+                document_id: "1".to_string(),
+                change: DocumentChange::Upsert(json!({ "title": "Buy milk", "notes": null })),
+            })
+        );
+        // The response includes only what the user selected
+        assert_eq!(entity, json!({ "id": 1, "title": "Selected by the user" }));
+    }
+
+    #[test]
+    fn delete_events() {
+        let index = index(SearchPkKind::String);
+        let mut entity = json!({ "__search_pk": "a1" });
+
+        assert_eq!(
+            outbox_event(&index, SyncKind::Delete, &mut entity),
+            Some(OutboxEvent {
+                index_name: "todos".to_string(),
+                document_id: "a1".to_string(),
+                change: DocumentChange::Delete,
+            })
+        );
+        assert_eq!(entity, json!({}));
+    }
+
+    #[test]
+    fn no_events_without_entities() {
+        let index = index(SearchPkKind::Int);
+
+        // Such as a single mutation that matched nothing
+        assert_eq!(
+            outbox_event(&index, SyncKind::Delete, &mut Value::Null),
+            None
+        );
+        assert_eq!(
+            outbox_event(&index, SyncKind::Upsert, &mut json!({ "title": "t" })),
+            None
+        );
+        assert_eq!(
+            outbox_event(
+                &index,
+                SyncKind::Upsert,
+                &mut json!({ "__search_pk": null })
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn pk_values_from_document_ids() {
+        assert_eq!(
+            pk_value(&index(SearchPkKind::Int), "42").unwrap(),
+            Val::Number(42i64.into())
+        );
+        assert_eq!(
+            pk_value(&index(SearchPkKind::String), "42").unwrap(),
+            Val::String("42".to_string())
+        );
+        assert!(matches!(
+            pk_value(&index(SearchPkKind::Int), "a1"),
+            Err(SearchExecutionError::Backend(_))
+        ));
+
+        assert_eq!(document_id(&json!(42)), Some("42".to_string()));
+        assert_eq!(document_id(&json!("a1")), Some("a1".to_string()));
+        assert_eq!(document_id(&json!(true)), None);
+    }
+
+    #[test]
+    fn int_arguments() {
+        let field = ValidatedField {
+            alias: None,
+            name: Name::new("searchTodos"),
+            arguments: IndexMap::from([
+                ("limit".to_string(), Val::Number(5i64.into())),
+                ("offset".to_string(), Val::Null),
+                ("negative".to_string(), Val::Number((-1i64).into())),
+                ("fractional".to_string(), Val::Number(1.5f64.into())),
+                ("string".to_string(), Val::String("5".to_string())),
+            ]),
+            subfields: vec![],
+        };
+
+        assert_eq!(int_argument(&field, "limit").unwrap(), Some(5));
+        assert_eq!(int_argument(&field, "offset").unwrap(), None);
+        assert_eq!(int_argument(&field, "missing").unwrap(), None);
+        for name in ["negative", "fractional", "string"] {
+            assert!(matches!(
+                int_argument(&field, name),
+                Err(SearchExecutionError::InvalidArgument(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use core_resolver::{plugin::SubsystemResolutionError, system_resolver::SystemResolutionError};
+use exo_sql::database_error::DatabaseError;
+use reqwest::StatusCode;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SearchExecutionError {
+    #[error("{0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Search backend responded with {0}: {1}")]
+    Status(StatusCode, String),
+
+    #[error("{0}")]
+    Backend(String),
+
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid argument {0}")]
+    InvalidArgument(String),
+
+    #[error("{0}")]
+    Resolution(#[from] SystemResolutionError),
+
+    #[error("{0}")]
+    Database(#[from] DatabaseError),
+}
+
+impl From<SearchExecutionError> for SubsystemResolutionError {
+    fn from(e: SearchExecutionError) -> Self {
+        match e {
+            SearchExecutionError::InvalidArgument(_) => {
+                SubsystemResolutionError::UserDisplayError(e.to_string())
+            }
+            // Errors from delegated operations (hydration or the intercepted mutation) have
+            // already been classified by the subsystem that produced them
+            SearchExecutionError::Resolution(SystemResolutionError::SubsystemResolutionError(
+                e,
+            )) => e,
+            SearchExecutionError::Resolution(e) => {
+                SubsystemResolutionError::UserDisplayError(e.user_error_message())
+            }
+            _ => {
+                tracing::error!("Search operation failed: {e}");
+                // Do not reveal too much information about the error
+                SubsystemResolutionError::UserDisplayError("Internal server error".to_string())
+            }
+        }
+    }
+}
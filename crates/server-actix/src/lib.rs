@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 mod request;
+pub mod tls;
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -15,6 +16,9 @@ use std::sync::Mutex;
 
 use actix_web::{
     HttpRequest, HttpResponse, HttpResponseBuilder, Responder,
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::{Next, from_fn},
     web::{self, ServiceConfig},
 };
 use exo_env::Environment;
@@ -23,7 +27,8 @@ use system_router::SystemRouter;
 use url::Url;
 
 use common::{
-    env_const::{DeploymentMode, get_deployment_mode, get_graphql_http_path},
+    circuit_breaker::{CircuitBreaker, circuit_breaker_from_env},
+    env_const::{DeploymentMode, HEALTHZ_HTTP_PATH, get_deployment_mode, get_graphql_http_path},
     router::Router,
 };
 use common::{
@@ -51,15 +56,23 @@ struct GraphQLPaths {
     graphql_http_path: String,
 }
 
+/// The endpoint to forward requests to (in the playground mode)
+struct Upstream {
+    url: Url,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+}
+
 pub fn configure_router(
     system_router: web::Data<SystemRouter>,
     env: Arc<dyn Environment>,
 ) -> impl FnOnce(&mut ServiceConfig) {
     let graphql_http_path = get_graphql_http_path(env.as_ref());
-    let endpoint_url = match get_deployment_mode(env.as_ref()) {
-        Ok(Some(DeploymentMode::Playground(url))) => {
-            Some(Url::parse(&url).expect("Failed to parse upstream endpoint URL"))
-        }
+    let upstream = match get_deployment_mode(env.as_ref()) {
+        Ok(Some(DeploymentMode::Playground(url))) => Some(Upstream {
+            url: Url::parse(&url).expect("Failed to parse upstream endpoint URL"),
+            circuit_breaker: circuit_breaker_from_env("Upstream endpoint", env.as_ref())
+                .expect("Invalid circuit breaker configuration"),
+        }),
         _ => None,
     };
 
@@ -69,24 +82,52 @@ pub fn configure_router(
                 graphql_http_path: graphql_http_path.clone(),
             }))
             .app_data(web::Data::new(env.clone()))
-            .app_data(web::Data::new(endpoint_url))
-            .default_service(web::to(resolve));
+            .app_data(web::Data::new(upstream))
+            // Match the limit of the JSON extractor (we parse the body ourselves to retain the raw bytes)
+            .app_data(web::PayloadConfig::new(2 * 1024 * 1024))
+            .default_service(web::to(resolve).wrap(from_fn(enforce_ip_filter)));
+    }
+}
+
+/// Reject requests from IPs not allowed for the endpoint (before reading the body)
+async fn enforce_ip_filter(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let allowed = match req.app_data::<web::Data<SystemRouter>>() {
+        Some(system_router) => system_router.is_ip_allowed(&ActixRequestHead::from_request(
+            req.request().clone(),
+            Value::Null,
+        )),
+        None => true,
+    };
+
+    if allowed {
+        next.call(req)
+            .await
+            .map(ServiceResponse::map_into_left_body)
+    } else {
+        Ok(req
+            .into_response(HttpResponse::Forbidden().finish())
+            .map_into_right_body())
     }
 }
 
 /// Resolve a GraphQL request
 ///
 /// # Arguments
-/// * `endpoint_url` - The target URL for resolving data (None implies that the current server is also the target)
+/// * `upstream` - The target for resolving data (None implies that the current server is also the target)
 async fn resolve(
     http_request: HttpRequest,
-    body: Option<web::Json<Value>>,
+    body: web::Bytes,
     query: web::Query<Value>,
-    endpoint_url: web::Data<Option<Url>>,
+    upstream: web::Data<Option<Upstream>>,
     system_router: web::Data<SystemRouter>,
     env: web::Data<Arc<dyn Environment>>,
 ) -> impl Responder {
-    if http_request.path() == "/healthz" && http_request.method() == actix_web::http::Method::GET {
+    if http_request.path() == HEALTHZ_HTTP_PATH
+        && http_request.method() == actix_web::http::Method::GET
+    {
         let graphql_http_path = http_request
             .app_data::<GraphQLPaths>()
             .map(|paths| paths.graphql_http_path.clone())
@@ -100,8 +141,8 @@ async fn resolve(
         .await;
     }
 
-    match endpoint_url.as_ref() {
-        Some(endpoint_url) => {
+    match upstream.as_ref() {
+        Some(upstream) => {
             // In the playground mode, locally serve the schema query or playground assets
             let schema_query = http_request
                 .headers()
@@ -117,7 +158,7 @@ async fn resolve(
             {
                 resolve_locally(http_request, body, query.into_inner(), system_router).await
             } else {
-                forward_request(http_request, body, endpoint_url).await
+                forward_request(http_request, body, upstream).await
             }
         }
         None => {
@@ -132,6 +173,15 @@ async fn handle_healthz(
     env: &dyn Environment,
     graphql_http_path: &str,
 ) -> HttpResponse {
+    // Report healthy without querying (the database may be unavailable during maintenance), so
+    // that load balancers keep routing to the server (which responds with a structured 503)
+    if system_router.maintenance_mode().is_enabled() {
+        return HttpResponse::Ok().json(json!({
+            "status": "ok",
+            "check": "maintenance",
+        }));
+    }
+
     let default_query = "{ __typename }".to_string();
     let mut query = env
         .get(EXO_HEALTHZ_QUERY)
@@ -186,23 +236,32 @@ async fn handle_healthz(
     )
     .await
     {
-        Ok(()) => HttpResponse::Ok().json(json!({
+        Ok(()) => HttpResponse::Ok().json(with_circuit_breakers(json!({
             "status": "ok",
             "check": "graphql",
             "query": if used_default_query { "default" } else { "custom" },
-        })),
+        }))),
         Err(err) => {
             tracing::error!("GraphQL health check failed: {}", err);
-            HttpResponse::ServiceUnavailable().json(json!({
+            HttpResponse::ServiceUnavailable().json(with_circuit_breakers(json!({
                 "status": "error",
                 "message": err,
                 "check": "graphql",
                 "query": if used_default_query { "default" } else { "custom" },
-            }))
+            })))
         }
     }
 }
 
+/// Add the state and counters of circuit breakers (if any are enabled)
+fn with_circuit_breakers(mut body: Value) -> Value {
+    let stats = CircuitBreaker::all_stats();
+    if !stats.is_empty() {
+        body["circuitBreakers"] = json!(stats);
+    }
+    body
+}
+
 fn expand_env_placeholders(raw: &str, env: &dyn Environment) -> Result<String, String> {
     let mut output = String::with_capacity(raw.len());
     let mut chars = raw.chars().peekable();
@@ -312,6 +371,7 @@ async fn execute_graphql_health_check(
 struct ActixRequestPayload {
     head: ActixRequestHead,
     body: Mutex<Value>,
+    raw_body: web::Bytes,
 }
 
 impl RequestPayload for ActixRequestPayload {
@@ -322,17 +382,25 @@ impl RequestPayload for ActixRequestPayload {
     fn take_body(&self) -> Value {
         self.body.lock().unwrap().take()
     }
+
+    fn get_raw_body(&self) -> Option<&[u8]> {
+        Some(&self.raw_body)
+    }
 }
 
 async fn resolve_locally(
     req: HttpRequest,
-    body: Option<web::Json<Value>>,
+    body: web::Bytes,
     query: Value,
     system_router: web::Data<SystemRouter>,
 ) -> HttpResponse {
+    // Keep the raw body around (in addition to the parsed one) to verify signatures over it
+    let parsed_body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
     let request = ActixRequestPayload {
         head: ActixRequestHead::from_request(req, query),
-        body: Mutex::new(body.map(|b| b.into_inner()).unwrap_or(Value::Null)),
+        body: Mutex::new(parsed_body),
+        raw_body: body,
     };
 
     let response = system_router
@@ -372,18 +440,41 @@ async fn resolve_locally(
     }
 }
 
-async fn forward_request(
+async fn forward_request(req: HttpRequest, body: web::Bytes, upstream: &Upstream) -> HttpResponse {
+    // Fail fast while the endpoint is down (instead of piling up requests waiting to time out)
+    if let Some(circuit_breaker) = &upstream.circuit_breaker
+        && let Err(err) = circuit_breaker.check()
+    {
+        return HttpResponse::ServiceUnavailable()
+            .insert_header(("Retry-After", err.retry_after_secs.to_string()))
+            .json(json!({
+                "errors": [{
+                    "message": err.to_string(),
+                    "extensions": {
+                        "code": "CIRCUIT_OPEN",
+                        "retryAfter": err.retry_after_secs,
+                    },
+                }]
+            }));
+    }
+
+    let response = send_forwarded_request(req, body, &upstream.url).await;
+
+    if let Some(circuit_breaker) = &upstream.circuit_breaker {
+        circuit_breaker.record(!response.status().is_server_error());
+    }
+
+    response
+}
+
+async fn send_forwarded_request(
     req: HttpRequest,
-    body: Option<web::Json<Value>>,
+    body: web::Bytes,
     forward_url: &Url,
 ) -> HttpResponse {
     let mut forward_url = forward_url.clone();
     forward_url.set_query(req.uri().query());
 
-    let body = body
-        .map(|b| b.into_inner().to_string())
-        .unwrap_or("".to_string());
-
     let forwarded_req = reqwest::Client::default()
         .request(to_reqwest_method(req.method()), forward_url)
         .body(body);
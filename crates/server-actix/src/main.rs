@@ -11,7 +11,7 @@ use actix_web::{App, HttpServer, middleware, web};
 
 use common::env_const::get_mcp_http_path;
 use common::env_processing::EnvProcessing;
-use server_actix::configure_router;
+use server_actix::{configure_router, tls};
 use thiserror::Error;
 use tracing_actix_web::TracingLogger;
 
@@ -41,6 +41,8 @@ enum ServerError {
     EnvError(#[from] exo_env::EnvError),
     #[error("{0}")]
     ServerInitError(#[from] server_common::ServerInitError),
+    #[error("{0}")]
+    TlsConfigError(#[from] tls::TlsConfigError),
 }
 
 // A custom `Debug` implementation for `ServerError` (that delegate to the `Display` impl), so that
@@ -59,6 +61,20 @@ async fn main() -> Result<(), ServerError> {
     let env = Arc::new(EnvProcessing::Process(SystemEnvironment.get("EXO_ENV")).load_env());
 
     let system_router = web::Data::new(server_common::init(env.clone()).await?);
+    system_router
+        .clone()
+        .into_inner()
+        .start_background_services();
+
+    #[cfg(unix)]
+    actix_web::rt::spawn(toggle_maintenance_on_signal(
+        system_router.maintenance_mode().clone(),
+    ));
+
+    #[cfg(unix)]
+    actix_web::rt::spawn(reload_authentication_on_signal(
+        system_router.clone().into_inner(),
+    ));
 
     let server_port = env
         .get(EXO_SERVER_PORT)
@@ -69,7 +85,15 @@ async fn main() -> Result<(), ServerError> {
         })
         .unwrap_or(9876);
 
+    let tls_config = tls::load_tls_config(env.as_ref())?;
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+
     let env_clone = env.clone();
+    let shutdown_router = system_router.clone();
 
     let server = HttpServer::new(move || {
         App::new()
@@ -78,12 +102,15 @@ async fn main() -> Result<(), ServerError> {
                 middleware::TrailingSlash::Trim,
             ))
             .configure(configure_router(system_router.clone(), env_clone.clone()))
-    });
+    })
+    .on_connect(tls::client_certificate_on_connect)
+    // We handle the signals ourselves to run the before-drain lifecycle hooks before stopping
+    .disable_signals();
 
     let server_host = env.as_ref().get(EXO_SERVER_HOST);
 
-    let server = match server_host {
-        Some(host) => server.bind((host, server_port)),
+    let server_host = match server_host {
+        Some(host) => host,
         None => {
             match get_deployment_mode(env.as_ref())? {
                 Some(
@@ -98,19 +125,24 @@ async fn main() -> Result<(), ServerError> {
                     // Note that tools such as "@graphql-codegen/cli" are unable to connect to
                     // "localhost:<port>" if we only bind to "0.0.0.0" or even "127.0.0.1" (but
                     // works fine, if we bind to IPv6 loopback address "::1").
-                    server.bind(("localhost", server_port))
+                    "localhost".to_string()
                 }
                 Some(DeploymentMode::Production) | None => {
                     // Bind to "0.0.0.0" (all interfaces; needed for production; see the
                     // recommendation in `HttpServer::bind` documentation). This allows the server
                     // to be accessed from outside the host machine (e.g. when the server is in a
                     // Docker container in a fly.io deployment).
-                    server.bind(("0.0.0.0", server_port))
+                    "0.0.0.0".to_string()
                 }
             }
         }
     };
 
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_0_23((server_host, server_port), tls_config),
+        None => server.bind((server_host, server_port)),
+    };
+
     match server {
         Ok(server) => {
             let pretty_addr = pretty_addr(&server.addrs());
@@ -123,12 +155,15 @@ async fn main() -> Result<(), ServerError> {
                 );
                 println!("- GraphQL endpoint hosted at:");
                 println!(
-                    "\thttp://{pretty_addr}{}",
+                    "\t{scheme}://{pretty_addr}{}",
                     get_graphql_http_path(env.as_ref())
                 );
                 if env.as_ref().enabled(EXO_ENABLE_MCP, true)? {
                     println!("- MCP endpoint hosted at:");
-                    println!("\thttp://{pretty_addr}{}", get_mcp_http_path(env.as_ref()));
+                    println!(
+                        "\t{scheme}://{pretty_addr}{}",
+                        get_mcp_http_path(env.as_ref())
+                    );
                 }
 
                 Ok::<(), EnvError>(())
@@ -137,7 +172,7 @@ async fn main() -> Result<(), ServerError> {
             let print_playground_info = || {
                 println!("- Playground hosted at:");
                 println!(
-                    "\thttp://{pretty_addr}{}",
+                    "\t{scheme}://{pretty_addr}{}",
                     get_playground_http_path(env.as_ref())
                 );
             };
@@ -154,7 +189,9 @@ async fn main() -> Result<(), ServerError> {
                     print_playground_info();
                 }
             }
-            Ok(server.run().await?)
+            let server = server.run();
+            actix_web::rt::spawn(stop_on_signal(server.handle(), shutdown_router));
+            Ok(server.await?)
         }
         Err(e) => Err(if e.kind() == ErrorKind::AddrInUse {
             ServerError::PortInUse(server_port)
@@ -164,6 +201,92 @@ async fn main() -> Result<(), ServerError> {
     }
 }
 
+/// Stop the server gracefully (letting the in-flight requests finish) on `SIGINT` or `SIGTERM`,
+/// after running the before-drain lifecycle hooks
+async fn stop_on_signal(
+    server: actix_web::dev::ServerHandle,
+    system_router: web::Data<system_router::SystemRouter>,
+) {
+    wait_for_stop_signal().await;
+
+    tracing::info!("Shutting down");
+    system_router.run_before_drain_hooks().await;
+    server.stop(true).await;
+}
+
+#[cfg(unix)]
+async fn wait_for_stop_signal() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Failed to listen for SIGTERM to stop the server: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_stop_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Toggle the maintenance mode on each `SIGUSR2` (for example, `kill -USR2 <pid>`)
+#[cfg(unix)]
+async fn toggle_maintenance_on_signal(maintenance_mode: Arc<common::maintenance::MaintenanceMode>) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut signals = match signal(SignalKind::user_defined2()) {
+        Ok(signals) => signals,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to listen for SIGUSR2 to toggle maintenance mode: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    while signals.recv().await.is_some() {
+        let enabled = maintenance_mode.toggle();
+        tracing::info!(
+            "Maintenance mode {} (SIGUSR2)",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+}
+
+/// Reload the JWT configuration (re-reading the env files) on each `SIGHUP` (for example,
+/// `kill -HUP <pid>`)
+#[cfg(unix)]
+async fn reload_authentication_on_signal(system_router: Arc<system_router::SystemRouter>) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut signals = match signal(SignalKind::hangup()) {
+        Ok(signals) => signals,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to listen for SIGHUP to reload the JWT configuration: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    while signals.recv().await.is_some() {
+        match system_router.reload_authentication().await {
+            Ok(()) => tracing::info!("JWT configuration reloaded (SIGHUP)"),
+            Err(e) => tracing::error!("Failed to reload the JWT configuration (SIGHUP): {}", e),
+        }
+    }
+}
+
 fn pretty_addr(addrs: &[SocketAddr]) -> String {
     let loopback_addr = addrs.iter().find(|addr| addr.ip().is_loopback());
 
@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use actix_web::{HttpRequest, dev::ConnectionInfo, http::header::HeaderMap};
-use common::http::RequestHead;
+use common::http::{ClientCertificate, RequestHead};
 
 use crate::to_reqwest_method;
 
@@ -22,6 +22,7 @@ pub struct ActixRequestHead {
     method: actix_web::http::Method,
     path: String,
     query: serde_json::Value,
+    client_certificate: Option<ClientCertificate>,
 }
 
 impl ActixRequestHead {
@@ -32,6 +33,7 @@ impl ActixRequestHead {
             method: req.method().clone(),
             path: req.path().to_string(),
             query,
+            client_certificate: req.conn_data::<ClientCertificate>().cloned(),
         }
     }
 }
@@ -50,6 +52,12 @@ impl RequestHead for ActixRequestHead {
             .and_then(|realip| realip.parse().ok())
     }
 
+    fn get_peer_ip(&self) -> Option<std::net::IpAddr> {
+        self.connection_info
+            .peer_addr()
+            .and_then(|peer_addr| peer_addr.parse().ok())
+    }
+
     fn get_method(&self) -> http::Method {
         to_reqwest_method(&self.method)
     }
@@ -61,4 +69,8 @@ impl RequestHead for ActixRequestHead {
     fn get_query(&self) -> serde_json::Value {
         self.query.clone()
     }
+
+    fn get_client_certificate(&self) -> Option<&ClientCertificate> {
+        self.client_certificate.as_ref()
+    }
 }
@@ -0,0 +1,429 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! TLS termination in the server, optionally verifying client certificates (mutual TLS).
+//!
+//! With `EXO_TLS_CLIENT_CA_FILE` set, the server verifies client certificates against the CAs in
+//! the file and makes the attributes of a verified certificate available to the `@clientCert`
+//! context source.
+
+use std::{any::Any, fs::File, io::BufReader, net::IpAddr, sync::Arc};
+
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::{dev::Extensions, rt::net::TcpStream};
+use common::http::ClientCertificate;
+use exo_env::{EnvError, Environment};
+use rustls::{
+    RootCertStore, ServerConfig,
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::{VerifierBuilderError, WebPkiClientVerifier},
+};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+pub const EXO_TLS_CERT_FILE: &str = "EXO_TLS_CERT_FILE";
+pub const EXO_TLS_KEY_FILE: &str = "EXO_TLS_KEY_FILE";
+pub const EXO_TLS_CLIENT_CA_FILE: &str = "EXO_TLS_CLIENT_CA_FILE";
+pub const EXO_TLS_REQUIRE_CLIENT_CERT: &str = "EXO_TLS_REQUIRE_CLIENT_CERT";
+
+#[derive(Error, Debug)]
+pub enum TlsConfigError {
+    #[error("{0} and {1} must be set together")]
+    Incomplete(&'static str, &'static str),
+
+    #[error("{0} requires {1} and {2} to be set")]
+    ClientCaWithoutTls(&'static str, &'static str, &'static str),
+
+    #[error("Failed to read {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+
+    #[error("No certificates found in {0}")]
+    NoCertificates(String),
+
+    #[error("No private key found in {0}")]
+    NoPrivateKey(String),
+
+    #[error("Invalid TLS configuration: {0}")]
+    Rustls(#[from] rustls::Error),
+
+    #[error("Invalid client CA certificates: {0}")]
+    ClientVerifier(#[from] VerifierBuilderError),
+
+    #[error("{0}")]
+    Env(#[from] EnvError),
+}
+
+/// The TLS configuration, if the server should terminate TLS (`EXO_TLS_CERT_FILE` and
+/// `EXO_TLS_KEY_FILE` are set)
+pub fn load_tls_config(env: &dyn Environment) -> Result<Option<ServerConfig>, TlsConfigError> {
+    let (cert_file, key_file) = match (env.get(EXO_TLS_CERT_FILE), env.get(EXO_TLS_KEY_FILE)) {
+        (Some(cert_file), Some(key_file)) => (cert_file, key_file),
+        (None, None) => {
+            return if env.get(EXO_TLS_CLIENT_CA_FILE).is_some() {
+                Err(TlsConfigError::ClientCaWithoutTls(
+                    EXO_TLS_CLIENT_CA_FILE,
+                    EXO_TLS_CERT_FILE,
+                    EXO_TLS_KEY_FILE,
+                ))
+            } else {
+                Ok(None)
+            };
+        }
+        _ => {
+            return Err(TlsConfigError::Incomplete(
+                EXO_TLS_CERT_FILE,
+                EXO_TLS_KEY_FILE,
+            ));
+        }
+    };
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+
+    let builder = match env.get(EXO_TLS_CLIENT_CA_FILE) {
+        Some(ca_file) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certs(&ca_file)? {
+                roots.add(cert)?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            // Unless required, let clients without a certificate connect (access rules can
+            // still require a certificate through `@clientCert` fields)
+            let verifier = if env.enabled(EXO_TLS_REQUIRE_CLIENT_CERT, false)? {
+                verifier
+            } else {
+                verifier.allow_unauthenticated()
+            };
+
+            builder.with_client_cert_verifier(verifier.build()?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    Ok(Some(builder.with_single_cert(
+        read_certs(&cert_file)?,
+        read_private_key(&key_file)?,
+    )?))
+}
+
+/// Record the verified client certificate (if any) in the connection data, so that requests
+/// can access it (see `ActixRequestHead`)
+pub fn client_certificate_on_connect(connection: &dyn Any, data: &mut Extensions) {
+    let Some(tls_stream) = connection.downcast_ref::<TlsStream<TcpStream>>() else {
+        return;
+    };
+
+    let (_, session) = tls_stream.get_ref();
+
+    // The first certificate is the client's (the rest are intermediates)
+    if let Some(certificate) = session
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .and_then(|cert| parse_client_certificate(cert.as_ref()))
+    {
+        data.insert(certificate);
+    }
+}
+
+fn parse_client_certificate(der: &[u8]) -> Option<ClientCertificate> {
+    let (_, certificate) = X509Certificate::from_der(der).ok()?;
+
+    let common_name = certificate
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string());
+
+    let sans = match certificate.subject_alternative_name() {
+        Ok(Some(extension)) => extension
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(name)
+                | GeneralName::URI(name)
+                | GeneralName::RFC822Name(name) => Some(name.to_string()),
+                GeneralName::IPAddress(bytes) => ip_address(bytes).map(|ip| ip.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    Some(ClientCertificate {
+        common_name,
+        subject: certificate.subject().to_string(),
+        issuer: certificate.issuer().to_string(),
+        sans,
+        fingerprint: hex(&Sha256::digest(der)),
+        serial_number: hex(certificate.raw_serial()),
+    })
+}
+
+fn ip_address(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes).ok().map(IpAddr::from),
+        16 => <[u8; 16]>::try_from(bytes).ok().map(IpAddr::from),
+        _ => None,
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn read_certs(file: &str) -> Result<Vec<CertificateDer<'static>>, TlsConfigError> {
+    let mut reader =
+        BufReader::new(File::open(file).map_err(|e| TlsConfigError::Io(file.to_string(), e))?);
+
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TlsConfigError::Io(file.to_string(), e))?;
+
+    if certs.is_empty() {
+        Err(TlsConfigError::NoCertificates(file.to_string()))
+    } else {
+        Ok(certs)
+    }
+}
+
+fn read_private_key(file: &str) -> Result<PrivateKeyDer<'static>, TlsConfigError> {
+    let mut reader =
+        BufReader::new(File::open(file).map_err(|e| TlsConfigError::Io(file.to_string(), e))?);
+
+    rustls_pemfile::private_key(&mut reader)
+        .map_err(|e| TlsConfigError::Io(file.to_string(), e))?
+        .ok_or_else(|| TlsConfigError::NoPrivateKey(file.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+    use rustls::{
+        ClientConfig, ClientConnection, Connection, ServerConnection, pki_types::ServerName,
+    };
+
+    use super::*;
+
+    // Generated with openssl: a CA, a server certificate for "localhost", and a client certificate
+    // (CN=billing, serial 0x1234abcd) with DNS, IP, URI, and email SANs. All are valid for 100 years.
+    fn fixture(name: &str) -> String {
+        format!("{}/src/test_certs/{name}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn tls_env(client_ca: bool, require_client_cert: Option<&str>) -> MapEnvironment {
+        let mut env = MapEnvironment::new();
+        env.set(EXO_TLS_CERT_FILE, &fixture("server.pem"));
+        env.set(EXO_TLS_KEY_FILE, &fixture("server-key.pem"));
+        if client_ca {
+            env.set(EXO_TLS_CLIENT_CA_FILE, &fixture("ca.pem"));
+        }
+        if let Some(require_client_cert) = require_client_cert {
+            env.set(EXO_TLS_REQUIRE_CLIENT_CERT, require_client_cert);
+        }
+        env
+    }
+
+    fn client_config(with_certificate: bool) -> ClientConfig {
+        let mut roots = RootCertStore::empty();
+        for cert in read_certs(&fixture("ca.pem")).unwrap() {
+            roots.add(cert).unwrap();
+        }
+
+        let builder =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots);
+
+        if with_certificate {
+            builder
+                .with_client_auth_cert(
+                    read_certs(&fixture("client.pem")).unwrap(),
+                    read_private_key(&fixture("client-key.pem")).unwrap(),
+                )
+                .unwrap()
+        } else {
+            builder.with_no_client_auth()
+        }
+    }
+
+    /// Perform a handshake in memory, returning the client certificate the server verified (if any)
+    fn handshake(
+        server_config: ServerConfig,
+        client_config: ClientConfig,
+    ) -> Result<Option<ClientCertificate>, rustls::Error> {
+        let mut server: Connection = ServerConnection::new(Arc::new(server_config))?.into();
+        let mut client: Connection = ClientConnection::new(
+            Arc::new(client_config),
+            ServerName::try_from("localhost").unwrap(),
+        )?
+        .into();
+
+        fn transfer(from: &mut Connection, to: &mut Connection) -> Result<(), rustls::Error> {
+            let mut bytes = vec![];
+            while from.wants_write() {
+                from.write_tls(&mut bytes).unwrap();
+            }
+
+            let mut bytes = bytes.as_slice();
+            while !bytes.is_empty() {
+                to.read_tls(&mut bytes).unwrap();
+                to.process_new_packets()?;
+            }
+            Ok(())
+        }
+
+        while client.is_handshaking() || server.is_handshaking() {
+            transfer(&mut client, &mut server)?;
+            transfer(&mut server, &mut client)?;
+        }
+
+        Ok(server
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| parse_client_certificate(cert.as_ref())))
+    }
+
+    #[test]
+    fn no_tls() {
+        assert!(matches!(load_tls_config(&MapEnvironment::new()), Ok(None)));
+    }
+
+    #[test]
+    fn incomplete_tls() {
+        let mut env = MapEnvironment::new();
+        env.set(EXO_TLS_CERT_FILE, &fixture("server.pem"));
+        assert!(matches!(
+            load_tls_config(&env),
+            Err(TlsConfigError::Incomplete(..))
+        ));
+
+        let mut env = MapEnvironment::new();
+        env.set(EXO_TLS_KEY_FILE, &fixture("server-key.pem"));
+        assert!(matches!(
+            load_tls_config(&env),
+            Err(TlsConfigError::Incomplete(..))
+        ));
+    }
+
+    #[test]
+    fn client_ca_without_tls() {
+        let mut env = MapEnvironment::new();
+        env.set(EXO_TLS_CLIENT_CA_FILE, &fixture("ca.pem"));
+        assert!(matches!(
+            load_tls_config(&env),
+            Err(TlsConfigError::ClientCaWithoutTls(..))
+        ));
+    }
+
+    #[test]
+    fn unreadable_files() {
+        let mut env = tls_env(false, None);
+        env.set(EXO_TLS_CERT_FILE, &fixture("missing.pem"));
+        assert!(matches!(load_tls_config(&env), Err(TlsConfigError::Io(..))));
+
+        // A certificate file has no private key
+        let mut env = tls_env(false, None);
+        env.set(EXO_TLS_KEY_FILE, &fixture("server.pem"));
+        assert!(matches!(
+            load_tls_config(&env),
+            Err(TlsConfigError::NoPrivateKey(..))
+        ));
+
+        let mut env = tls_env(false, None);
+        env.set(EXO_TLS_CERT_FILE, &fixture("server-key.pem"));
+        assert!(matches!(
+            load_tls_config(&env),
+            Err(TlsConfigError::NoCertificates(..))
+        ));
+    }
+
+    #[test]
+    fn without_client_ca() {
+        let config = load_tls_config(&tls_env(false, None)).unwrap().unwrap();
+
+        // The server doesn't ask for a certificate, so it has none even if the client has one
+        assert_eq!(handshake(config, client_config(true)), Ok(None));
+    }
+
+    #[test]
+    fn optional_client_certificate() {
+        for require_client_cert in [None, Some("false")] {
+            let env = tls_env(true, require_client_cert);
+
+            let config = load_tls_config(&env).unwrap().unwrap();
+            assert_eq!(handshake(config, client_config(false)), Ok(None));
+
+            let config = load_tls_config(&env).unwrap().unwrap();
+            let certificate = handshake(config, client_config(true)).unwrap();
+            assert_eq!(
+                certificate.and_then(|certificate| certificate.common_name),
+                Some("billing".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn required_client_certificate() {
+        let env = tls_env(true, Some("true"));
+
+        let config = load_tls_config(&env).unwrap().unwrap();
+        assert_eq!(
+            handshake(config, client_config(false)),
+            Err(rustls::Error::NoCertificatesPresented)
+        );
+
+        let config = load_tls_config(&env).unwrap().unwrap();
+        assert!(handshake(config, client_config(true)).unwrap().is_some());
+    }
+
+    #[test]
+    fn invalid_require_client_cert() {
+        assert!(matches!(
+            load_tls_config(&tls_env(true, Some("sometimes"))),
+            Err(TlsConfigError::Env(_))
+        ));
+    }
+
+    #[test]
+    fn client_certificate_attributes() {
+        let der = read_certs(&fixture("client.pem")).unwrap().remove(0);
+
+        assert_eq!(
+            parse_client_certificate(der.as_ref()),
+            Some(ClientCertificate {
+                common_name: Some("billing".to_string()),
+                subject: "O=Acme, CN=billing".to_string(),
+                issuer: "O=Exograph Test, CN=Exograph Test CA".to_string(),
+                sans: vec![
+                    "billing.internal".to_string(),
+                    "10.0.0.7".to_string(),
+                    "2001:db8::7".to_string(),
+                    "spiffe://acme/billing".to_string(),
+                    "billing@acme.test".to_string(),
+                ],
+                // `openssl x509 -in client.pem -noout -fingerprint -sha256`
+                fingerprint: "e951bc68352458a8ad92432c64f4546ddcdcb6a17bf9bc2bd1507d74b8b6ef0f"
+                    .to_string(),
+                serial_number: "1234abcd".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_client_certificate() {
+        assert_eq!(parse_client_certificate(b"not a certificate"), None);
+    }
+}
@@ -27,6 +27,7 @@ use system_router::SystemRouter;
 struct AwsLambdaRequestPayload {
     head: LambdaRequest,
     body: Mutex<Value>,
+    raw_body: Option<String>,
 }
 
 impl RequestPayload for AwsLambdaRequestPayload {
@@ -37,14 +38,18 @@ impl RequestPayload for AwsLambdaRequestPayload {
     fn take_body(&self) -> Value {
         self.body.lock().unwrap().take()
     }
+
+    fn get_raw_body(&self) -> Option<&[u8]> {
+        self.raw_body.as_ref().map(|body| body.as_bytes())
+    }
 }
 
 pub async fn resolve(
     event: LambdaEvent<Value>,
     system_router: Arc<SystemRouter>,
 ) -> Result<Value, Error> {
-    let body_str = event.payload["body"].as_str();
-    let body = match body_str {
+    let raw_body = event.payload["body"].as_str().map(String::from);
+    let body = match &raw_body {
         Some(body_str) => serde_json::from_str(body_str)?,
         None => Value::Null,
     };
@@ -52,6 +57,7 @@ pub async fn resolve(
     let request_payload = AwsLambdaRequestPayload {
         head: LambdaRequest::new(event),
         body: Mutex::new(body),
+        raw_body,
     };
 
     let response_payload = system_router
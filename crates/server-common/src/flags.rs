@@ -0,0 +1,88 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use common::{
+    env_const::{DATABASE_URL, EXO_FLAGS_TABLE, EXO_POSTGRES_URL},
+    feature_flags::{FlagSource, FlagSourceError, parse_flag_value},
+};
+use exo_env::Environment;
+use exo_sql::{DatabaseClientManager, TransactionMode};
+use serde_json::Value;
+
+/// Flags from a Postgres table (`EXO_FLAGS_TABLE`) with `name` and `value` columns (of any type
+/// castable to text, such as `text` or `jsonb`)
+pub struct PostgresFlagSource {
+    url: String,
+    table: String,
+}
+
+impl PostgresFlagSource {
+    pub fn from_env(env: &dyn Environment) -> Option<Self> {
+        let table = env.get(EXO_FLAGS_TABLE)?;
+        let url = env
+            .get(EXO_POSTGRES_URL)
+            .or_else(|| env.get(DATABASE_URL))?;
+
+        Some(Self { url, table })
+    }
+
+    /// The table name quoted as an identifier (supporting `schema.table`)
+    fn quoted_table(&self) -> String {
+        self.table
+            .split('.')
+            .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+#[async_trait]
+impl FlagSource for PostgresFlagSource {
+    fn name(&self) -> String {
+        format!("table {}", self.table)
+    }
+
+    async fn load(&self) -> Result<HashMap<String, Value>, FlagSourceError> {
+        let to_error = |e: &dyn std::fmt::Display| FlagSourceError::Generic(e.to_string());
+
+        let client_manager =
+            DatabaseClientManager::from_url_direct(&self.url, false, TransactionMode::ReadOnly)
+                .await
+                .map_err(|e| to_error(&e))?;
+        let client = client_manager
+            .get_client()
+            .await
+            .map_err(|e| to_error(&e))?;
+
+        let rows = client
+            .query(
+                &format!("SELECT name, value::text FROM {}", self.quoted_table()),
+                &[],
+            )
+            .await
+            .map_err(|e| to_error(&e))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let name: String = row.try_get(0).map_err(|e| to_error(&e))?;
+                let value: Option<String> = row.try_get(1).map_err(|e| to_error(&e))?;
+
+                Ok((
+                    name,
+                    value
+                        .map(|value| parse_flag_value(&value))
+                        .unwrap_or(Value::Null),
+                ))
+            })
+            .collect()
+    }
+}
@@ -11,16 +11,20 @@ use exo_env::Environment;
 use std::{env, process::exit, sync::Arc};
 use thiserror::Error;
 
-use common::logging_tracing::{self, OtelError};
-
+mod flags;
+pub mod logging;
+mod rate_limit_store;
 mod sentry;
 use core_plugin_interface::interface::SubsystemLoader;
 
 use core_router::SystemLoadingError;
-use system_router::{SystemRouter, create_system_router_from_file};
+use system_router::{
+    SystemRouter, create_system_router_from_file_with_hooks, lifecycle::LifecycleHooks,
+    schema_versions::create_schema_version_routers,
+};
 
 /// Initialize the server by:
-/// - Initializing tracing
+/// - Initializing logging and tracing
 /// - Creating the system resolver (and return it)
 ///
 /// The `[SystemResolver]` uses static resolvers for subsystems if the corresponding features
@@ -30,8 +34,20 @@ use system_router::{SystemRouter, create_system_router_from_file};
 /// # Exit codes
 /// - 1 - If the exo_ir file doesn't exist or can't be loaded.
 pub async fn init(env: Arc<dyn Environment>) -> Result<SystemRouter, ServerInitError> {
-    logging_tracing::init(env.as_ref()).await?;
+    init_with_hooks(env, LifecycleHooks::default()).await
+}
+
+/// Initialize the server (as [init] does) for an application that embeds it, running the
+/// application's lifecycle hooks along the way (see [system_router::lifecycle]).
+///
+/// The server must call [SystemRouter::run_before_drain_hooks] when shutting down.
+pub async fn init_with_hooks(
+    env: Arc<dyn Environment>,
+    lifecycle_hooks: LifecycleHooks,
+) -> Result<SystemRouter, ServerInitError> {
+    // Initialize Sentry first, so that logging can bridge spans to Sentry transactions
     sentry::init(env.as_ref());
+    logging::init(env.as_ref()).await?;
 
     println!(
         "Exograph server starting (version {})",
@@ -40,7 +56,28 @@ pub async fn init(env: Arc<dyn Environment>) -> Result<SystemRouter, ServerInitE
 
     let exo_ir_file = get_exo_ir_file_name();
 
-    Ok(create_system_router_from_file(&exo_ir_file, create_static_loaders(), env).await?)
+    let schema_versions = create_schema_version_routers(create_static_loaders, env.clone()).await?;
+
+    let mut system_router = create_system_router_from_file_with_hooks(
+        &exo_ir_file,
+        create_static_loaders(),
+        env.clone(),
+        Arc::new(lifecycle_hooks),
+    )
+    .await?
+    .with_schema_versions(schema_versions);
+
+    if system_router.is_rate_limited()
+        && let Some(store) = rate_limit_store::from_env(env.as_ref()).await?
+    {
+        system_router = system_router.with_rate_limit_store(store);
+    }
+
+    if let Some(source) = flags::PostgresFlagSource::from_env(env.as_ref()) {
+        system_router.feature_flags().add_source(Arc::new(source));
+    }
+
+    Ok(system_router)
 }
 
 pub fn create_static_loaders() -> Vec<Box<dyn SubsystemLoader>> {
@@ -53,6 +90,14 @@ pub fn create_static_loaders() -> Vec<Box<dyn SubsystemLoader>> {
         Box::new(deno_resolver::DenoSubsystemLoader {}),
         #[cfg(feature = "static-wasm-resolver")]
         Box::new(wasm_resolver::WasmSubsystemLoader {}),
+        #[cfg(feature = "static-redis-resolver")]
+        Box::new(redis_resolver::RedisSubsystemLoader {}),
+        #[cfg(feature = "static-search-resolver")]
+        Box::new(search_resolver::SearchSubsystemLoader {}),
+        #[cfg(feature = "static-messaging-resolver")]
+        Box::new(messaging_resolver::MessagingSubsystemLoader {}),
+        #[cfg(feature = "static-llm-resolver")]
+        Box::new(llm_resolver::LlmSubsystemLoader {}),
     ]
 }
 
@@ -70,7 +115,7 @@ fn get_exo_ir_file_name() -> String {
 #[derive(Error, Debug)]
 pub enum ServerInitError {
     #[error(transparent)]
-    OtelError(#[from] OtelError),
+    LoggingError(#[from] logging::LoggingError),
 
     #[error(transparent)]
     SystemLoadingError(#[from] SystemLoadingError),
@@ -0,0 +1,268 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Logging configuration for the server.
+//!
+//! - `EXO_LOG` sets the filter (following the same conventions as `RUST_LOG`). The filter may be
+//!   changed at runtime through the admin API (see [`common::log_filter`]).
+//! - `EXO_LOG_FORMAT` selects the output format: `compact` (the default) or `json` (one JSON
+//!   object per line, suitable for log aggregators such as Loki or Datadog).
+//! - `EXO_LOG_SAMPLING` samples high-volume debug and trace events. For example,
+//!   `exo_sql=100,postgres_resolver=10` logs one in 100 such events from `exo_sql` and one in 10
+//!   from `postgres_resolver`. Sampling applies only to the log output (not to OpenTelemetry).
+//!
+//! If Sentry is configured, spans are also sent to Sentry as transactions (see
+//! [`crate::sentry::tracing_layer`]).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use exo_env::{EnvError, Environment};
+use thiserror::Error;
+use tracing::{Level, Metadata, subscriber::Interest};
+use tracing_subscriber::{
+    EnvFilter, Layer,
+    filter::{FilterExt, LevelFilter},
+    layer::{Context, Filter},
+    prelude::*,
+    reload,
+};
+
+use common::{
+    env_const::{EXO_LOG, EXO_LOG_FORMAT, EXO_LOG_SAMPLING},
+    log_filter,
+    logging_tracing::{self, OtelError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Compact,
+    Json,
+}
+
+#[derive(Debug)]
+pub struct SamplingRule {
+    /// Module path prefix of the events to sample (such as `exo_sql` or `exo_sql::sql`)
+    target: String,
+    /// Log one out of these many events
+    every: u64,
+    seen: AtomicU64,
+}
+
+impl SamplingRule {
+    fn matches(&self, target: &str) -> bool {
+        target == self.target
+            || target
+                .strip_prefix(self.target.as_str())
+                .is_some_and(|rest| rest.starts_with("::"))
+    }
+
+    fn sample(&self) -> bool {
+        self.seen.fetch_add(1, Ordering::Relaxed) % self.every == 0
+    }
+}
+
+#[derive(Debug)]
+pub struct LoggingConfig {
+    pub filter: String,
+    pub format: LogFormat,
+    pub sampling: Vec<SamplingRule>,
+}
+
+#[derive(Error, Debug)]
+pub enum LoggingError {
+    #[error(transparent)]
+    Env(#[from] EnvError),
+
+    #[error(transparent)]
+    Otel(#[from] OtelError),
+}
+
+impl LoggingConfig {
+    pub fn from_env(env: &dyn Environment) -> Result<Self, EnvError> {
+        let format = match env.get(EXO_LOG_FORMAT).as_deref() {
+            None | Some("compact") => LogFormat::Compact,
+            Some("json") => LogFormat::Json,
+            Some(other) => {
+                return Err(EnvError::InvalidEnum {
+                    env_key: EXO_LOG_FORMAT,
+                    env_value: other.to_string(),
+                    message: "Must be 'compact' or 'json'".to_string(),
+                });
+            }
+        };
+
+        let sampling = env
+            .get_list(EXO_LOG_SAMPLING, vec![])
+            .into_iter()
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| parse_sampling_rule(&rule))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            filter: env.get(EXO_LOG).unwrap_or_default(),
+            format,
+            sampling,
+        })
+    }
+}
+
+fn parse_sampling_rule(rule: &str) -> Result<SamplingRule, EnvError> {
+    let invalid = || EnvError::InvalidEnum {
+        env_key: EXO_LOG_SAMPLING,
+        env_value: rule.to_string(),
+        message: "Must be of the form 'target=N' with N a positive integer".to_string(),
+    };
+
+    let (target, every) = rule.split_once('=').ok_or_else(invalid)?;
+    let every = every
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|every| *every > 0)
+        .ok_or_else(invalid)?;
+
+    Ok(SamplingRule {
+        target: target.trim().to_string(),
+        every,
+        seen: AtomicU64::new(0),
+    })
+}
+
+/// Samples debug and trace events whose target matches a rule (the most specific rule wins)
+struct SamplingFilter {
+    rules: Vec<SamplingRule>,
+}
+
+impl SamplingFilter {
+    fn rule_for(&self, metadata: &Metadata<'_>) -> Option<&SamplingRule> {
+        if !metadata.is_event() || *metadata.level() < Level::DEBUG {
+            return None;
+        }
+
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(metadata.target()))
+            .max_by_key(|rule| rule.target.len())
+    }
+}
+
+impl<S> Filter<S> for SamplingFilter {
+    fn enabled(&self, metadata: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        self.rule_for(metadata).is_none_or(SamplingRule::sample)
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        // Sampled callsites must be asked each time (otherwise, the decision would be cached)
+        match self.rule_for(metadata) {
+            Some(_) => Interest::sometimes(),
+            None => Interest::always(),
+        }
+    }
+}
+
+fn env_filter(filter: &str) -> Result<EnvFilter, String> {
+    EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .parse(filter)
+        .map_err(|e| e.to_string())
+}
+
+/// Initialize the global tracing subscriber based on [`LoggingConfig`] (along with the
+/// OpenTelemetry and Sentry layers, if enabled).
+///
+/// The filter applies to the log output and OpenTelemetry. The Sentry layer selects its spans on
+/// its own, so that transactions are recorded regardless of the log level.
+pub async fn init(env: &dyn Environment) -> Result<(), LoggingError> {
+    let config = LoggingConfig::from_env(env)?;
+
+    let initial_filter = || {
+        env_filter(&config.filter).unwrap_or_else(|e| {
+            eprintln!("Ignoring invalid {EXO_LOG} value: {e}");
+            EnvFilter::default().add_directive(LevelFilter::WARN.into())
+        })
+    };
+    let (fmt_filter, fmt_filter_handle) = reload::Layer::new(initial_filter());
+    let (telemetry_filter, telemetry_filter_handle) = reload::Layer::new(initial_filter());
+
+    let fmt_filter = fmt_filter.and(SamplingFilter {
+        rules: config.sampling,
+    });
+    let fmt_layer = match config.format {
+        LogFormat::Compact => tracing_subscriber::fmt::layer()
+            .compact()
+            .with_filter(fmt_filter)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_filter(fmt_filter)
+            .boxed(),
+    };
+
+    let telemetry_layer = logging_tracing::telemetry_layer(env)
+        .await?
+        .map(|layer| layer.with_filter(telemetry_filter));
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(telemetry_layer)
+        .with(crate::sentry::tracing_layer())
+        .init();
+
+    log_filter::install(
+        config.filter,
+        Box::new(move |filter| {
+            fmt_filter_handle
+                .reload(env_filter(filter)?)
+                .map_err(|e| e.to_string())?;
+            telemetry_filter_handle
+                .reload(env_filter(filter)?)
+                .map_err(|e| e.to_string())
+        }),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    #[test]
+    fn parses_config() {
+        let env = MapEnvironment::from([
+            (EXO_LOG_FORMAT, "json"),
+            (EXO_LOG_SAMPLING, "exo_sql=100, postgres_resolver::sql=10"),
+        ]);
+        let config = LoggingConfig::from_env(&env).unwrap();
+
+        assert_eq!(config.format, LogFormat::Json);
+        assert_eq!(config.sampling.len(), 2);
+        assert!(config.sampling[0].matches("exo_sql"));
+        assert!(config.sampling[0].matches("exo_sql::sql::select"));
+        assert!(!config.sampling[0].matches("exo_sql_extra"));
+
+        let env = MapEnvironment::from([(EXO_LOG_SAMPLING, "exo_sql=0")]);
+        assert!(LoggingConfig::from_env(&env).is_err());
+
+        let env = MapEnvironment::from([(EXO_LOG_FORMAT, "xml")]);
+        assert!(LoggingConfig::from_env(&env).is_err());
+    }
+
+    #[test]
+    fn samples_one_in_every() {
+        let rule = parse_sampling_rule("exo_sql=3").unwrap();
+        let sampled: Vec<_> = (0..6).map(|_| rule.sample()).collect();
+
+        assert_eq!(sampled, vec![true, false, false, true, false, false]);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use common::{env_const::EXO_GRAPHQL_RATE_LIMIT_STORE, rate_limit::RateLimitStore};
+use core_router::SystemLoadingError;
+use exo_env::Environment;
+
+/// The store shared by the servers of a deployment to count requests against the GraphQL rate
+/// limit (`EXO_GRAPHQL_RATE_LIMIT_STORE`), or `None` to count them in memory
+pub async fn from_env(
+    env: &dyn Environment,
+) -> Result<Option<Arc<dyn RateLimitStore>>, SystemLoadingError> {
+    match env.get(EXO_GRAPHQL_RATE_LIMIT_STORE).as_deref() {
+        None | Some("memory") => Ok(None),
+        Some("redis") => redis_store(env).await.map(Some),
+        Some(other) => Err(SystemLoadingError::Config(format!(
+            "Invalid {EXO_GRAPHQL_RATE_LIMIT_STORE} '{other}' (expected 'memory' or 'redis')"
+        ))),
+    }
+}
+
+#[cfg(feature = "static-redis-resolver")]
+async fn redis_store(env: &dyn Environment) -> Result<Arc<dyn RateLimitStore>, SystemLoadingError> {
+    use common::env_const::EXO_REDIS_URL;
+
+    let url = env.get(EXO_REDIS_URL).ok_or_else(|| {
+        SystemLoadingError::Config(format!(
+            "{EXO_REDIS_URL} must be set to keep the rate limit counts in Redis"
+        ))
+    })?;
+    let store = redis_resolver::RedisStore::connect(&url)
+        .await
+        .map_err(|e| SystemLoadingError::Config(format!("Failed to connect to Redis: {e}")))?;
+
+    Ok(Arc::new(store))
+}
+
+#[cfg(not(feature = "static-redis-resolver"))]
+async fn redis_store(
+    _env: &dyn Environment,
+) -> Result<Arc<dyn RateLimitStore>, SystemLoadingError> {
+    Err(SystemLoadingError::Config(format!(
+        "{EXO_GRAPHQL_RATE_LIMIT_STORE} 'redis' requires a server built with Redis support"
+    )))
+}
@@ -1,5 +1,8 @@
 use exo_env::Environment;
+use sentry_tracing::EventFilter;
 use std::sync::OnceLock;
+use tracing::{Level, Metadata, Subscriber};
+use tracing_subscriber::{Layer, filter::filter_fn, registry::LookupSpan};
 
 static SENTRY_GUARD: OnceLock<sentry::ClientInitGuard> = OnceLock::new();
 
@@ -50,7 +53,122 @@ pub fn init(env: &dyn Environment) {
     let _ = SENTRY_GUARD.set(guard);
 }
 
-#[allow(dead_code)]
 pub fn enabled() -> bool {
     sentry::Hub::current().client().is_some()
 }
+
+/// Targets whose debug-level spans (database and Deno execution) are traced in addition to spans
+/// at the info level and above
+const TRACED_DEBUG_SPAN_TARGETS: &[&str] = &["exo_sql", "exo_deno"];
+
+fn is_traced_span(metadata: &Metadata<'_>) -> bool {
+    is_traced(*metadata.level(), metadata.target())
+}
+
+fn is_traced(level: Level, target: &str) -> bool {
+    level <= Level::INFO
+        || (level == Level::DEBUG
+            && TRACED_DEBUG_SPAN_TARGETS
+                .iter()
+                .any(|traced| target.starts_with(traced)))
+}
+
+/// Bridge tracing spans to Sentry transactions (sampled according to `traces_sample_rate`), so
+/// that slow operations show up along with their database and Deno child spans.
+///
+/// Other events become breadcrumbs; errors are reported explicitly (see the GraphQL router).
+pub fn tracing_layer<S>() -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    if !enabled() {
+        return None;
+    }
+
+    let layer = sentry_tracing::layer()
+        .event_filter(|metadata| match *metadata.level() {
+            Level::ERROR | Level::WARN | Level::INFO => EventFilter::Breadcrumb,
+            _ => EventFilter::Ignore,
+        })
+        .span_filter(is_traced_span);
+
+    // Keep other callsites disabled for this layer (so that verbose events cost nothing)
+    Some(layer.with_filter(filter_fn(|metadata| {
+        if metadata.is_span() {
+            is_traced_span(metadata)
+        } else {
+            *metadata.level() <= Level::INFO
+        }
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use sentry::protocol::EnvelopeItem;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn traced_levels_and_targets() {
+        assert!(is_traced(Level::ERROR, "server_actix"));
+        assert!(is_traced(Level::INFO, "graphql_router"));
+        assert!(is_traced(Level::DEBUG, "exo_sql"));
+        assert!(is_traced(Level::DEBUG, "exo_sql::sql::transaction"));
+        assert!(is_traced(Level::DEBUG, "exo_deno::deno_module"));
+
+        assert!(!is_traced(Level::DEBUG, "postgres_graphql_resolver"));
+        assert!(!is_traced(Level::TRACE, "exo_sql"));
+        assert!(!is_traced(Level::TRACE, "graphql_router"));
+    }
+
+    #[test]
+    fn no_layer_without_client() {
+        assert!(!enabled());
+        assert!(tracing_layer::<tracing_subscriber::Registry>().is_none());
+    }
+
+    #[test]
+    fn spans_become_transactions() {
+        let envelopes = sentry::test::with_captured_envelopes_options(
+            || {
+                let layer = tracing_layer().expect("Sentry is enabled in the test hub");
+                let subscriber = tracing_subscriber::registry().with(layer);
+
+                tracing::subscriber::with_default(subscriber, || {
+                    tracing::info_span!("resolve").in_scope(|| {
+                        tracing::debug_span!(target: "exo_sql::sql::transaction", "query")
+                            .in_scope(|| {});
+                        tracing::debug_span!(target: "exo_deno::deno_module", "execute")
+                            .in_scope(|| {});
+                        tracing::debug_span!(target: "postgres_graphql_resolver", "plan")
+                            .in_scope(|| {});
+                        tracing::trace_span!(target: "exo_sql", "row").in_scope(|| {});
+                    });
+                });
+            },
+            sentry::ClientOptions {
+                traces_sample_rate: 1.0,
+                ..Default::default()
+            },
+        );
+
+        let transactions: Vec<_> = envelopes
+            .iter()
+            .flat_map(|envelope| envelope.items())
+            .filter_map(|item| match item {
+                EnvelopeItem::Transaction(transaction) => Some(transaction),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(transactions.len(), 1);
+
+        let mut child_ops: Vec<_> = transactions[0]
+            .spans
+            .iter()
+            .filter_map(|span| span.op.as_deref())
+            .collect();
+        child_ops.sort();
+        assert_eq!(child_ops, vec!["execute", "query"]);
+    }
+}
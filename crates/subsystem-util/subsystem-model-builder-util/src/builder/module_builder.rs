@@ -317,5 +317,6 @@ pub fn create_shallow_interceptor(
                 is_injected: true, // implicitly set is_injected for interceptors
             })
             .collect(),
+        error_policy: resolved_interceptor.error_policy,
     });
 }
@@ -27,6 +27,7 @@ use core_model_builder::{
     error::ModelBuildingError,
     typechecker::{Typed, typ::Type},
 };
+use core_plugin_shared::interception::InterceptorErrorPolicy;
 use serde::{Deserialize, Serialize};
 
 use crate::ScriptProcessor;
@@ -127,6 +128,7 @@ pub struct ResolvedInterceptor {
     pub method_name: String,
     pub arguments: Vec<ResolvedArgument>,
     pub interceptor_kind: ResolvedInterceptorKind,
+    pub error_policy: InterceptorErrorPolicy,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -348,6 +350,21 @@ async fn resolve_module(
                         ),
                     }?;
 
+                    const ON_ERROR_MESSAGE: &str = "@onError must be either \"abort\" or \"continue\"";
+                    let error_policy = match extract_intercept_annot(&i.annotations, "onError") {
+                        None => Ok(InterceptorErrorPolicy::Abort),
+                        Some(_) if matches!(kind_annot, ResolvedInterceptorKind::Around(_)) => {
+                            // An around interceptor produces the response, so there is nothing to continue with
+                            create_diagnostic("@onError applies only to before/after interceptors", i.span, errors)
+                        }
+                        Some(AstExpr::StringLiteral(value, span)) => match value.as_str() {
+                            "abort" => Ok(InterceptorErrorPolicy::Abort),
+                            "continue" => Ok(InterceptorErrorPolicy::Continue),
+                            _ => create_diagnostic(ON_ERROR_MESSAGE, *span, errors),
+                        },
+                        Some(expr) => create_diagnostic(ON_ERROR_MESSAGE, expr.span(), errors),
+                    }?;
+
                     Result::<ResolvedInterceptor, ModelBuildingError>::Ok(ResolvedInterceptor {
                         module_name: module.name.clone(),
                         method_name: i.name.clone(),
@@ -357,6 +374,7 @@ async fn resolve_module(
                             .map(|a| resolve_argument(a, types))
                             .collect(),
                         interceptor_kind: kind_annot.clone(),
+                        error_policy,
                     })
                 })
                 .collect(),
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use super::module::{Argument, Script};
 use core_model::mapped_arena::SerializableSlabIndex;
-use core_plugin_shared::interception::InterceptorKind;
+use core_plugin_shared::interception::{InterceptorErrorPolicy, InterceptorKind};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Interceptor {
@@ -20,4 +20,6 @@ pub struct Interceptor {
     pub script: SerializableSlabIndex<Script>,
     pub interceptor_kind: InterceptorKind,
     pub arguments: Vec<Argument>,
+    #[serde(default)]
+    pub error_policy: InterceptorErrorPolicy,
 }
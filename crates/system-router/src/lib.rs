@@ -7,8 +7,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod lifecycle;
+mod public_sandbox;
+pub mod schema_versions;
+pub mod startup_report;
 pub mod system_router;
 
 pub use system_router::{
-    SystemRouter, create_system_router_from_file, create_system_router_from_system,
+    SystemRouter, create_system_router_from_file, create_system_router_from_file_with_hooks,
+    create_system_router_from_system, create_system_router_from_system_with_hooks,
 };
@@ -0,0 +1,181 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Hooks through which applications embedding the server tie their own resources (such as caches
+//! or schedulers) to its lifecycle.
+//!
+//! Hooks of a stage run one after another, in the order they were registered. A failing startup
+//! hook (after model load or after pool creation) fails the startup, while a failing shutdown hook
+//! (before drain) is only logged, so that the remaining hooks still run.
+
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use core_router::SystemLoadingError;
+use exo_env::Environment;
+use tracing::warn;
+
+pub type LifecycleHookError = Box<dyn std::error::Error + Send + Sync>;
+
+type LifecycleHookFuture = Pin<Box<dyn Future<Output = Result<(), LifecycleHookError>> + Send>>;
+type LifecycleHook = Box<dyn Fn(Arc<dyn Environment>) -> LifecycleHookFuture + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleStage {
+    /// The model is loaded (and the environment validated against it), but subsystems haven't
+    /// created their resources yet
+    AfterModelLoad,
+    /// Subsystems have created their resources (such as database connection pools), but the
+    /// server doesn't route requests yet
+    AfterPoolCreation,
+    /// The server is about to stop accepting requests and wait for the in-flight ones to finish
+    BeforeDrain,
+}
+
+impl fmt::Display for LifecycleStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LifecycleStage::AfterModelLoad => "after-model-load",
+            LifecycleStage::AfterPoolCreation => "after-pool-creation",
+            LifecycleStage::BeforeDrain => "before-drain",
+        })
+    }
+}
+
+/// The hooks to run at each stage of the server's lifecycle. Each hook receives the server's
+/// environment (with the model's defaults applied).
+#[derive(Default)]
+pub struct LifecycleHooks {
+    hooks: Vec<(LifecycleStage, LifecycleHook)>,
+}
+
+impl LifecycleHooks {
+    pub fn after_model_load<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(Arc<dyn Environment>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), LifecycleHookError>> + Send + 'static,
+    {
+        self.with_hook(LifecycleStage::AfterModelLoad, hook)
+    }
+
+    pub fn after_pool_creation<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(Arc<dyn Environment>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), LifecycleHookError>> + Send + 'static,
+    {
+        self.with_hook(LifecycleStage::AfterPoolCreation, hook)
+    }
+
+    pub fn before_drain<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(Arc<dyn Environment>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), LifecycleHookError>> + Send + 'static,
+    {
+        self.with_hook(LifecycleStage::BeforeDrain, hook)
+    }
+
+    fn with_hook<F, Fut>(mut self, stage: LifecycleStage, hook: F) -> Self
+    where
+        F: Fn(Arc<dyn Environment>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), LifecycleHookError>> + Send + 'static,
+    {
+        self.hooks
+            .push((stage, Box::new(move |env| Box::pin(hook(env)))));
+        self
+    }
+
+    /// Run the startup hooks of the stage (stopping at the first failure)
+    pub(crate) async fn run_startup(
+        &self,
+        stage: LifecycleStage,
+        env: &Arc<dyn Environment>,
+    ) -> Result<(), SystemLoadingError> {
+        for hook in self.stage_hooks(stage) {
+            hook(env.clone())
+                .await
+                .map_err(|e| SystemLoadingError::LifecycleHook(stage.to_string(), e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the shutdown hooks of the stage (logging the failures)
+    pub(crate) async fn run_shutdown(&self, stage: LifecycleStage, env: &Arc<dyn Environment>) {
+        for hook in self.stage_hooks(stage) {
+            if let Err(e) = hook(env.clone()).await {
+                warn!("The {stage} lifecycle hook failed: {e}");
+            }
+        }
+    }
+
+    fn stage_hooks(&self, stage: LifecycleStage) -> impl Iterator<Item = &LifecycleHook> {
+        self.hooks
+            .iter()
+            .filter(move |(hook_stage, _)| *hook_stage == stage)
+            .map(|(_, hook)| hook)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn runs_hooks_of_stage_in_order() {
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let record = |name: &'static str| {
+            let calls = calls.clone();
+            move |_env: Arc<dyn Environment>| {
+                calls.lock().unwrap().push(name);
+                async { Ok(()) }
+            }
+        };
+
+        let hooks = LifecycleHooks::default()
+            .after_pool_creation(record("cache"))
+            .before_drain(record("flush"))
+            .after_pool_creation(record("scheduler"));
+
+        let env: Arc<dyn Environment> = Arc::new(MapEnvironment::default());
+        hooks
+            .run_startup(LifecycleStage::AfterPoolCreation, &env)
+            .await
+            .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["cache", "scheduler"]);
+    }
+
+    #[tokio::test]
+    async fn startup_failure_stops_remaining_hooks() {
+        let ran = Arc::new(Mutex::new(false));
+        let ran_clone = ran.clone();
+
+        let hooks = LifecycleHooks::default()
+            .after_model_load(|_env| async { Err("cache unavailable".into()) })
+            .after_model_load(move |_env| {
+                *ran_clone.lock().unwrap() = true;
+                async { Ok(()) }
+            });
+
+        let env: Arc<dyn Environment> = Arc::new(MapEnvironment::default());
+        let result = hooks
+            .run_startup(LifecycleStage::AfterModelLoad, &env)
+            .await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The after-model-load lifecycle hook failed: cache unavailable"
+        );
+        assert!(!*ran.lock().unwrap());
+    }
+}
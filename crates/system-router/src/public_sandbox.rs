@@ -0,0 +1,164 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The public sandbox (enabled through `EXO_PUBLIC_SANDBOX`) for serving a read-only GraphQL
+//! endpoint to anonymous clients.
+//!
+//! It supplies strict defaults for the settings that bound what a client may do (selection depth,
+//! aliases, root fields, SQL statements, and per-IP rate), which the environment (or the model's
+//! defaults) may still tune. The settings that would open the endpoint up (mutations,
+//! unrestricted introspection, the MCP endpoint, and untrusted documents) are checked instead, so
+//! that the server refuses to start rather than silently serving a less restricted endpoint.
+
+use std::sync::Arc;
+
+#[cfg(not(target_family = "wasm"))]
+use common::env_const::get_enforce_trusted_documents;
+use common::{
+    env_const::{
+        EXO_ENABLE_MCP, EXO_GRAPHQL_ALLOW_MUTATIONS, EXO_GRAPHQL_RATE_LIMIT, EXO_INTROSPECTION,
+        EXO_MAX_ALIASES, EXO_MAX_ROOT_FIELDS, EXO_MAX_SELECTION_DEPTH, EXO_MAX_SQL_STATEMENTS,
+        EXO_PUBLIC_SANDBOX, is_production,
+    },
+    introspection::{IntrospectionMode, introspection_token},
+};
+use core_plugin_shared::trusted_documents::TrustedDocuments;
+use core_router::SystemLoadingError;
+use exo_env::{CompositeEnvironment, EnvError, Environment, MapEnvironment};
+
+const SANDBOX_DEFAULTS: [(&str, &str); 8] = [
+    (EXO_GRAPHQL_ALLOW_MUTATIONS, "false"),
+    (EXO_INTROSPECTION, "false"),
+    (EXO_ENABLE_MCP, "false"),
+    (EXO_MAX_SELECTION_DEPTH, "4"),
+    (EXO_MAX_ALIASES, "20"),
+    (EXO_MAX_ROOT_FIELDS, "5"),
+    (EXO_MAX_SQL_STATEMENTS, "10"),
+    (EXO_GRAPHQL_RATE_LIMIT, "60"),
+];
+
+pub(crate) fn is_public_sandbox(env: &dyn Environment) -> Result<bool, EnvError> {
+    env.enabled(EXO_PUBLIC_SANDBOX, false)
+}
+
+/// The environment with the sandbox's defaults (if the sandbox is enabled) under the given one
+pub(crate) fn with_public_sandbox_defaults(
+    env: Arc<dyn Environment>,
+) -> Result<Arc<dyn Environment>, EnvError> {
+    if !is_public_sandbox(env.as_ref())? {
+        return Ok(env);
+    }
+
+    Ok(Arc::new(CompositeEnvironment::new(vec![
+        env,
+        Arc::new(MapEnvironment::from(SANDBOX_DEFAULTS)),
+    ])))
+}
+
+/// Check that the effective configuration keeps the endpoint as restricted as the sandbox promises
+pub(crate) fn check_public_sandbox(
+    env: &dyn Environment,
+    allow_mutations: bool,
+    introspection: IntrospectionMode,
+    trusted_documents: &TrustedDocuments,
+) -> Result<(), SystemLoadingError> {
+    let mut problems = vec![];
+
+    if allow_mutations {
+        problems.push(format!(
+            "mutations are allowed ({EXO_GRAPHQL_ALLOW_MUTATIONS})"
+        ));
+    }
+
+    let introspection_restricted = match introspection {
+        IntrospectionMode::Disabled => true,
+        IntrospectionMode::Enabled => introspection_token(env).is_some(),
+        IntrospectionMode::Only => false,
+    };
+    if !introspection_restricted {
+        problems.push(format!(
+            "introspection is enabled without a token ({EXO_INTROSPECTION})"
+        ));
+    }
+
+    if env.enabled(EXO_ENABLE_MCP, true)? {
+        problems.push(format!("the MCP endpoint is enabled ({EXO_ENABLE_MCP})"));
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    let enforce_trusted_documents = is_production(env) || get_enforce_trusted_documents(env);
+    #[cfg(target_family = "wasm")]
+    let enforce_trusted_documents = true;
+
+    if !matches!(trusted_documents, TrustedDocuments::MatchingOnly(_)) {
+        problems.push("the model has no trusted documents".to_string());
+    } else if !enforce_trusted_documents {
+        problems.push("trusted documents aren't enforced".to_string());
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(SystemLoadingError::Config(format!(
+            "{EXO_PUBLIC_SANDBOX} is set, but {}",
+            problems.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common::env_const::EXO_ENV;
+
+    use super::*;
+
+    #[test]
+    fn defaults_under_explicit_settings() {
+        let env = with_public_sandbox_defaults(Arc::new(MapEnvironment::from([
+            (EXO_PUBLIC_SANDBOX, "true"),
+            (EXO_MAX_ALIASES, "50"),
+        ])))
+        .unwrap();
+
+        assert_eq!(env.get(EXO_MAX_ALIASES).as_deref(), Some("50"));
+        assert_eq!(env.get(EXO_MAX_ROOT_FIELDS).as_deref(), Some("5"));
+        assert_eq!(
+            env.get(EXO_GRAPHQL_ALLOW_MUTATIONS).as_deref(),
+            Some("false")
+        );
+
+        let env = with_public_sandbox_defaults(Arc::new(MapEnvironment::default())).unwrap();
+        assert_eq!(env.get(EXO_MAX_ROOT_FIELDS), None);
+    }
+
+    #[test]
+    fn rejects_open_configuration() {
+        let env = MapEnvironment::from([(EXO_ENV, "production"), (EXO_ENABLE_MCP, "false")]);
+        let trusted_documents = TrustedDocuments::from_map(HashMap::new(), false);
+
+        assert!(
+            check_public_sandbox(&env, false, IntrospectionMode::Disabled, &trusted_documents)
+                .is_ok()
+        );
+
+        assert_eq!(
+            check_public_sandbox(
+                &env,
+                true,
+                IntrospectionMode::Enabled,
+                &TrustedDocuments::all()
+            )
+            .unwrap_err()
+            .to_string(),
+            "Configuration error: EXO_PUBLIC_SANDBOX is set, but mutations are allowed (EXO_GRAPHQL_ALLOW_MUTATIONS), introspection is enabled without a token (EXO_INTROSPECTION), the model has no trusted documents"
+        );
+    }
+}
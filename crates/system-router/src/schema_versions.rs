@@ -0,0 +1,189 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Serving pinned schema snapshots alongside the current schema.
+//!
+//! After a breaking model change, clients may need time to migrate. `EXO_SCHEMA_VERSIONS` lists
+//! snapshots of earlier builds (copies of `target/index.exo_ir`) to keep serving, each under its
+//! own path prefix. For example, `EXO_SCHEMA_VERSIONS=v1=snapshots/v1.exo_ir` serves the snapshot
+//! at `/v1/graphql` (and the playground at `/v1/playground`), while the current schema continues
+//! to be served at `/graphql`.
+//!
+//! Each version gets its own [SystemRouter], which doesn't start background services and doesn't
+//! serve the admin API or MCP (those are served only for the current schema).
+
+use std::{collections::HashMap, sync::Arc};
+
+use common::env_const::{
+    EXO_ENABLE_ADMIN_API, EXO_ENABLE_MCP, EXO_GRAPHQL_HTTP_PATH, EXO_PLAYGROUND_HTTP_PATH,
+    EXO_REST_HTTP_PATH, EXO_RPC_HTTP_PATH, EXO_SCHEMA_VERSIONS, get_graphql_http_path,
+    get_playground_http_path, get_rest_http_path, get_rpc_http_path,
+};
+use core_router::SystemLoadingError;
+use exo_env::{CompositeEnvironment, EnvError, Environment, MapEnvironment};
+use tracing::info;
+
+use crate::system_router::{StaticLoaders, SystemRouter, create_system_router_from_file};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaVersion {
+    /// The path prefix (such as "/v1")
+    pub prefix: String,
+    pub exo_ir_file: String,
+}
+
+/// Parse `EXO_SCHEMA_VERSIONS` (comma-separated `<version>=<exo_ir file>` pairs)
+pub fn schema_versions(env: &dyn Environment) -> Result<Vec<SchemaVersion>, EnvError> {
+    let Some(value) = env.get(EXO_SCHEMA_VERSIONS) else {
+        return Ok(vec![]);
+    };
+
+    let invalid = |message: String| EnvError::InvalidEnum {
+        env_key: EXO_SCHEMA_VERSIONS,
+        env_value: value.clone(),
+        message,
+    };
+
+    let mut versions: Vec<SchemaVersion> = vec![];
+
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, exo_ir_file) = entry
+            .split_once('=')
+            .map(|(name, file)| (name.trim(), file.trim()))
+            .filter(|(name, file)| !name.is_empty() && !file.is_empty())
+            .ok_or_else(|| invalid(format!("Expected '<version>=<exo_ir file>', got '{entry}'")))?;
+
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        {
+            return Err(invalid(format!(
+                "Version '{name}' may contain only letters, digits, '-', '_', and '.'"
+            )));
+        }
+
+        let prefix = format!("/{name}");
+        if versions.iter().any(|version| version.prefix == prefix) {
+            return Err(invalid(format!(
+                "Version '{name}' is declared more than once"
+            )));
+        }
+
+        versions.push(SchemaVersion {
+            prefix,
+            exo_ir_file: exo_ir_file.to_string(),
+        });
+    }
+
+    Ok(versions)
+}
+
+/// Create a router for each version declared in `EXO_SCHEMA_VERSIONS` (to pass to
+/// [SystemRouter::with_schema_versions]).
+///
+/// Since each version loads its subsystems independently, `static_loaders` is called once per
+/// version.
+pub async fn create_schema_version_routers(
+    static_loaders: impl Fn() -> StaticLoaders,
+    env: Arc<dyn Environment>,
+) -> Result<Vec<(String, SystemRouter)>, SystemLoadingError> {
+    let mut routers = vec![];
+
+    for version in schema_versions(env.as_ref())? {
+        info!(
+            "Serving schema version '{}' from {}",
+            version.prefix, version.exo_ir_file
+        );
+
+        let version_env = versioned_env(&version.prefix, env.clone());
+        let router =
+            create_system_router_from_file(&version.exo_ir_file, static_loaders(), version_env)
+                .await?;
+
+        routers.push((version.prefix, router));
+    }
+
+    Ok(routers)
+}
+
+/// Prefix the paths of the APIs (and disable the APIs served only for the current schema)
+fn versioned_env(prefix: &str, env: Arc<dyn Environment>) -> Arc<dyn Environment> {
+    let overrides = HashMap::from([
+        (
+            EXO_GRAPHQL_HTTP_PATH.to_string(),
+            format!("{prefix}{}", get_graphql_http_path(env.as_ref())),
+        ),
+        (
+            EXO_PLAYGROUND_HTTP_PATH.to_string(),
+            format!("{prefix}{}", get_playground_http_path(env.as_ref())),
+        ),
+        (
+            EXO_REST_HTTP_PATH.to_string(),
+            format!("{prefix}{}", get_rest_http_path(env.as_ref())),
+        ),
+        (
+            EXO_RPC_HTTP_PATH.to_string(),
+            format!("{prefix}{}", get_rpc_http_path(env.as_ref())),
+        ),
+        (EXO_ENABLE_ADMIN_API.to_string(), "false".to_string()),
+        (EXO_ENABLE_MCP.to_string(), "false".to_string()),
+    ]);
+
+    Arc::new(CompositeEnvironment::new(vec![
+        Arc::new(MapEnvironment::from(overrides)),
+        env,
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Result<Vec<SchemaVersion>, EnvError> {
+        let env = MapEnvironment::from(HashMap::from([(
+            EXO_SCHEMA_VERSIONS.to_string(),
+            value.to_string(),
+        )]));
+        schema_versions(&env)
+    }
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(
+            parse("v1=snapshots/v1.exo_ir, v2 = snapshots/v2.exo_ir").unwrap(),
+            vec![
+                SchemaVersion {
+                    prefix: "/v1".to_string(),
+                    exo_ir_file: "snapshots/v1.exo_ir".to_string(),
+                },
+                SchemaVersion {
+                    prefix: "/v2".to_string(),
+                    exo_ir_file: "snapshots/v2.exo_ir".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_versions() {
+        assert!(parse("v1").is_err());
+        assert!(parse("v1=").is_err());
+        assert!(parse("v/1=snapshots/v1.exo_ir").is_err());
+        assert!(parse("v1=a.exo_ir,v1=b.exo_ir").is_err());
+    }
+
+    #[test]
+    fn prefixes_paths() {
+        let env = versioned_env("/v1", Arc::new(MapEnvironment::new()));
+
+        assert_eq!(get_graphql_http_path(env.as_ref()), "/v1/graphql");
+        assert_eq!(get_playground_http_path(env.as_ref()), "/v1/playground");
+        assert!(!env.enabled(EXO_ENABLE_ADMIN_API, true).unwrap());
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A structured report of how the system was loaded: the resolved configuration, the loaded
+//! subsystems, and the status of the resources they depend on (database, JWKS, Deno pool).
+//!
+//! The report is logged once the system is loaded and served by the admin API (at
+//! `<admin path>/startup-report`), so that a misbehaving deployment can be diagnosed at a glance.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use common::{
+    context::JwtAuthenticator,
+    env_const::{EXO_ENABLE_ADMIN_API, EXO_UNSTABLE_ENABLE_REST_API, EXO_UNSTABLE_ENABLE_RPC_API},
+    introspection::IntrospectionMode,
+};
+use core_plugin_interface::interface::SubsystemDiagnostics;
+use core_resolver::plugin::SubsystemGraphQLResolver;
+use core_resolver::system_resolver::InvalidTrustedDocument;
+use exo_env::{EnvError, Environment};
+
+#[derive(Serialize, Debug)]
+pub struct StartupReport {
+    pub version: &'static str,
+    pub configuration: Configuration,
+    pub subsystems: Vec<SubsystemSummary>,
+    /// How JWTs are validated (`null` if authentication isn't configured)
+    pub authentication: Value,
+    /// The status reported by each subsystem's diagnostics (keyed by their names)
+    pub resources: BTreeMap<&'static str, Value>,
+    /// The trusted documents with operations that don't validate against the schema
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid_trusted_documents: Vec<InvalidTrustedDocument>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Configuration {
+    pub read_only: bool,
+    pub graphql_mutations: bool,
+    pub introspection: &'static str,
+    pub rest_api: bool,
+    pub rpc_api: bool,
+    pub admin_api: bool,
+    pub maintenance: bool,
+    pub background_services: usize,
+}
+
+impl Configuration {
+    pub fn new(
+        env: &dyn Environment,
+        read_only: bool,
+        graphql_mutations: bool,
+        introspection: IntrospectionMode,
+        maintenance: bool,
+        background_services: usize,
+    ) -> Result<Self, EnvError> {
+        Ok(Self {
+            read_only,
+            graphql_mutations,
+            introspection: match introspection {
+                IntrospectionMode::Enabled => "enabled",
+                IntrospectionMode::Disabled => "disabled",
+                IntrospectionMode::Only => "only",
+            },
+            rest_api: env.enabled(EXO_UNSTABLE_ENABLE_REST_API, false)?,
+            rpc_api: env.enabled(EXO_UNSTABLE_ENABLE_RPC_API, false)?,
+            admin_api: env.enabled(EXO_ENABLE_ADMIN_API, false)?,
+            maintenance,
+            background_services,
+        })
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct SubsystemSummary {
+    pub id: &'static str,
+    pub queries: usize,
+    pub mutations: usize,
+    pub types: usize,
+}
+
+impl SubsystemSummary {
+    pub fn from_resolvers(
+        resolvers: &[Arc<dyn SubsystemGraphQLResolver + Send + Sync>],
+    ) -> Vec<Self> {
+        resolvers
+            .iter()
+            .map(|resolver| Self {
+                id: resolver.id(),
+                queries: resolver.schema_queries().len(),
+                mutations: resolver.schema_mutations().len(),
+                types: resolver.schema_types().len(),
+            })
+            .collect()
+    }
+}
+
+impl StartupReport {
+    pub async fn new(
+        configuration: Configuration,
+        subsystems: Vec<SubsystemSummary>,
+        authenticator: Option<&JwtAuthenticator>,
+        diagnostics: &[Arc<dyn SubsystemDiagnostics>],
+        invalid_trusted_documents: Vec<InvalidTrustedDocument>,
+    ) -> Self {
+        let mut resources = BTreeMap::new();
+        for diagnostic in diagnostics {
+            resources.insert(diagnostic.name(), diagnostic.diagnose().await);
+        }
+
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            configuration,
+            subsystems,
+            authentication: authenticator
+                .map(|authenticator| authenticator.status())
+                .unwrap_or(Value::Null),
+            resources,
+            invalid_trusted_documents,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).expect("Startup report should be serializable")
+    }
+
+    pub fn log(&self) {
+        for subsystem in &self.subsystems {
+            info!(
+                "Loaded subsystem '{}': {} queries, {} mutations, {} types",
+                subsystem.id, subsystem.queries, subsystem.mutations, subsystem.types
+            );
+        }
+
+        for document in &self.invalid_trusted_documents {
+            warn!(
+                hash = document.hash,
+                operation = document.operation_name.as_deref().unwrap_or(""),
+                "Trusted document doesn't validate against the schema: {}",
+                document.message
+            );
+        }
+
+        info!(startup_report = %self.to_json(), "Startup report");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use common::env_const::EXO_JWT_SECRET;
+    use exo_env::MapEnvironment;
+
+    use super::*;
+
+    struct StaticDiagnostics {
+        name: &'static str,
+        status: Value,
+    }
+
+    #[async_trait]
+    impl SubsystemDiagnostics for StaticDiagnostics {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn diagnose(&self) -> Value {
+            self.status.clone()
+        }
+    }
+
+    #[test]
+    fn configuration_from_env() {
+        let env = MapEnvironment::from([
+            (EXO_UNSTABLE_ENABLE_REST_API, "true"),
+            (EXO_ENABLE_ADMIN_API, "yes"),
+        ]);
+        let configuration =
+            Configuration::new(&env, true, false, IntrospectionMode::Only, false, 2).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&configuration).unwrap(),
+            json!({
+                "read_only": true,
+                "graphql_mutations": false,
+                "introspection": "only",
+                "rest_api": true,
+                "rpc_api": false,
+                "admin_api": true,
+                "maintenance": false,
+                "background_services": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn configuration_with_invalid_flag() {
+        let env = MapEnvironment::from([(EXO_UNSTABLE_ENABLE_RPC_API, "sometimes")]);
+        let result = Configuration::new(&env, false, true, IntrospectionMode::Enabled, false, 0);
+
+        assert!(matches!(result, Err(EnvError::InvalidBoolean { .. })));
+    }
+
+    #[tokio::test]
+    async fn report_with_resources() {
+        let env = MapEnvironment::default();
+        let configuration =
+            Configuration::new(&env, false, true, IntrospectionMode::Disabled, false, 0).unwrap();
+        let diagnostics: Vec<Arc<dyn SubsystemDiagnostics>> = vec![
+            Arc::new(StaticDiagnostics {
+                name: "deno",
+                status: json!({ "scripts": [] }),
+            }),
+            Arc::new(StaticDiagnostics {
+                name: "database",
+                status: json!({ "connected": false, "error": "connection refused" }),
+            }),
+        ];
+
+        let report = StartupReport::new(configuration, vec![], None, &diagnostics, vec![]).await;
+        let report = report.to_json();
+
+        assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(report["configuration"]["introspection"], "disabled");
+        assert_eq!(report["authentication"], Value::Null);
+        assert_eq!(
+            report["resources"],
+            json!({
+                "database": { "connected": false, "error": "connection refused" },
+                "deno": { "scripts": [] },
+            })
+        );
+        // Omitted unless some trusted documents are invalid
+        assert!(report.get("invalid_trusted_documents").is_none());
+    }
+
+    #[tokio::test]
+    async fn report_with_authentication_and_invalid_documents() {
+        let env = MapEnvironment::from([(EXO_JWT_SECRET, "do-not-report")]);
+        let authenticator = JwtAuthenticator::new_from_env(&env).await.unwrap().unwrap();
+        let configuration =
+            Configuration::new(&env, false, true, IntrospectionMode::Enabled, false, 0).unwrap();
+        let invalid_trusted_documents = vec![InvalidTrustedDocument {
+            hash: "abc123".to_string(),
+            operation_name: Some("getConcerts".to_string()),
+            message: "Field 'venu' is not valid for type 'Concert'".to_string(),
+        }];
+
+        let report = StartupReport::new(
+            configuration,
+            vec![],
+            Some(&authenticator),
+            &[],
+            invalid_trusted_documents,
+        )
+        .await;
+        let report = report.to_json();
+
+        assert_eq!(
+            report["authentication"]["validation"],
+            json!({ "kind": "secret" })
+        );
+        assert!(!report.to_string().contains("do-not-report"));
+        assert_eq!(
+            report["invalid_trusted_documents"],
+            json!([{
+                "hash": "abc123",
+                "operation_name": "getConcerts",
+                "message": "Field 'venu' is not valid for type 'Concert'",
+            }])
+        );
+    }
+}
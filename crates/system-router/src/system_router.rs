@@ -7,54 +7,107 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
 
-use common::env_const::{EXO_ENABLE_MCP, EXO_UNSTABLE_ENABLE_RPC_API};
+use common::env_const::{EXO_ENABLE_ADMIN_API, EXO_ENABLE_MCP, EXO_UNSTABLE_ENABLE_RPC_API};
 use common::introspection::{IntrospectionMode, introspection_mode};
 use common::router::PlainRequestPayload;
+#[cfg(not(target_family = "wasm"))]
+use core_plugin_shared::profile::OperationSet;
 use core_plugin_shared::profile::{SchemaProfile, SchemaProfiles};
 use core_resolver::introspection::definition::schema::Schema;
+use core_resolver::operation_limiter::OperationLimiter;
 use core_resolver::plugin::SubsystemRpcResolver;
+use core_resolver::response_headers::DeclaredResponseHeaders;
 use core_resolver::system_rpc_resolver::SystemRpcResolver;
 use core_resolver::{
     plugin::{SubsystemGraphQLResolver, SubsystemRestResolver},
     system_rest_resolver::SystemRestResolver,
 };
 
+#[cfg(not(target_family = "wasm"))]
+use login_router::LoginRouter;
 #[cfg(not(target_family = "wasm"))]
 use mcp_router::McpRouter;
 use rpc_router::RpcRouter;
-use tracing::debug;
+use tracing::{debug, info};
 
-use common::context::{JwtAuthenticator, RequestContext};
+use common::context::{
+    BUILT_IN_CONTEXT_ANNOTATIONS, ContextExtractor, FlagExtractor, JwtAuthenticator,
+    RequestContext, SharedContextExtractor,
+};
 use common::{
+    access_coverage::{AccessCoverage, AccessCoverageReport},
+    clock::Clock,
     cors::{CorsConfig, CorsRouter},
-    env_const::{EXO_CORS_DOMAINS, EXO_GRAPHQL_ALLOW_MUTATIONS, EXO_UNSTABLE_ENABLE_REST_API},
-    http::ResponsePayload,
+    env_const::{
+        EXO_ACCESS_COVERAGE, EXO_CORS_DOMAINS, EXO_GRAPHQL_ALLOW_MUTATIONS,
+        EXO_STRICT_TRUSTED_DOCUMENTS, EXO_UNSTABLE_ENABLE_REST_API, HEALTHZ_HTTP_PATH,
+        get_graphql_http_path, is_read_only,
+    },
+    feature_flags::FeatureFlags,
+    http::{Headers, RequestHead, ResponseBody, ResponsePayload},
+    ip_filter::{IpFilter, IpFilterEndpoint},
+    maintenance::MaintenanceMode,
+    rate_limit::{RateLimitStore, RateLimiter},
+    response_signing::ResponseSigner,
     router::{CompositeRouter, Router},
 };
-use core_plugin_interface::interface::{SubsystemLoader, SubsystemResolver};
+use core_plugin_interface::interface::{
+    SubsystemAccessRules, SubsystemBackgroundService, SubsystemDiagnostics, SubsystemLoader,
+    SubsystemResolver,
+};
 use core_plugin_shared::{
     interception::InterceptionMap, serializable_system::SerializableSystem,
     system_serializer::SystemSerializer, trusted_documents::TrustedDocuments,
 };
-use core_router::SystemLoadingError;
-use exo_env::Environment;
-use graphql_router::{GraphQLRouter, IntrospectionResolver};
+use core_router::{SystemLoadingError, env_validation::validate_env};
+use exo_env::{CompositeEnvironment, Environment, MapEnvironment};
+use graphql_router::{GraphQLRouter, IntrospectionResolver, RouteRouter};
 
 #[cfg(not(target_family = "wasm"))]
 use playground_router::PlaygroundRouter;
 #[cfg(not(target_family = "wasm"))]
 use playground_router::PlaygroundRouterConfig;
 
+use admin_router::AdminRouter;
 use rest_router::RestRouter;
 
+use crate::lifecycle::{LifecycleHooks, LifecycleStage};
+use crate::public_sandbox::{
+    check_public_sandbox, is_public_sandbox, with_public_sandbox_defaults,
+};
+use crate::startup_report::{Configuration, StartupReport, SubsystemSummary};
+
 pub type StaticLoaders = Vec<Box<dyn SubsystemLoader>>;
 
 pub async fn create_system_router_from_file(
     exo_ir_file: &str,
     static_loaders: StaticLoaders,
     env: Arc<dyn Environment>,
+) -> Result<SystemRouter, SystemLoadingError> {
+    create_system_router_from_file_with_hooks(
+        exo_ir_file,
+        static_loaders,
+        env,
+        Arc::new(LifecycleHooks::default()),
+    )
+    .await
+}
+
+/// Create the router (as [create_system_router_from_file] does), running the lifecycle hooks of
+/// the embedding application along the way
+pub async fn create_system_router_from_file_with_hooks(
+    exo_ir_file: &str,
+    static_loaders: StaticLoaders,
+    env: Arc<dyn Environment>,
+    lifecycle_hooks: Arc<LifecycleHooks>,
 ) -> Result<SystemRouter, SystemLoadingError> {
     if !Path::new(&exo_ir_file).exists() {
         return Err(SystemLoadingError::FileNotFound(exo_ir_file.to_string()));
@@ -67,7 +120,13 @@ pub async fn create_system_router_from_file(
             let serialized_system = SerializableSystem::deserialize_reader(exo_ir_file_buffer)
                 .map_err(SystemLoadingError::ModelSerializationError)?;
 
-            create_system_router_from_system(serialized_system, static_loaders, env).await
+            create_system_router_from_system_with_hooks(
+                serialized_system,
+                static_loaders,
+                env,
+                lifecycle_hooks,
+            )
+            .await
         }
         Err(e) => Err(SystemLoadingError::FileOpen(exo_ir_file.into(), e)),
     }
@@ -78,6 +137,41 @@ pub async fn create_system_router_from_system(
     static_loaders: StaticLoaders,
     env: Arc<dyn Environment>,
 ) -> Result<SystemRouter, SystemLoadingError> {
+    create_system_router_from_system_with_hooks(
+        system,
+        static_loaders,
+        env,
+        Arc::new(LifecycleHooks::default()),
+    )
+    .await
+}
+
+/// Create the router (as [create_system_router_from_system] does), running the lifecycle hooks of
+/// the embedding application along the way
+pub async fn create_system_router_from_system_with_hooks(
+    mut system: SerializableSystem,
+    static_loaders: StaticLoaders,
+    env: Arc<dyn Environment>,
+    lifecycle_hooks: Arc<LifecycleHooks>,
+) -> Result<SystemRouter, SystemLoadingError> {
+    let env = with_public_sandbox_defaults(with_model_defaults(env, &system.env_defaults))?;
+
+    // Fail fast (and report all problems together) instead of failing at first use of a var
+    validate_env(&system.env_requirements, env.as_ref())?;
+
+    lifecycle_hooks
+        .run_startup(LifecycleStage::AfterModelLoad, &env)
+        .await?;
+
+    let operation_limiter = Arc::new(OperationLimiter::new(
+        &system.operation_limits,
+        env.as_ref(),
+    )?);
+    let response_headers = Arc::new(DeclaredResponseHeaders::new(
+        &system.operation_response_headers,
+    ));
+    let routes = std::mem::take(&mut system.routes);
+
     let (
         subsystem_resolvers,
         query_interception_map,
@@ -87,6 +181,10 @@ pub async fn create_system_router_from_system(
         schema_profiles,
     ) = create_system_resolvers(system, static_loaders, env.clone()).await?;
 
+    lifecycle_hooks
+        .run_startup(LifecycleStage::AfterPoolCreation, &env)
+        .await?;
+
     let query_interception_map = Arc::new(query_interception_map);
     let mutation_interception_map = Arc::new(mutation_interception_map);
 
@@ -95,9 +193,28 @@ pub async fn create_system_router_from_system(
     let mut graphql_resolvers: Vec<Arc<dyn SubsystemGraphQLResolver + Send + Sync>> = vec![];
     let mut rest_resolvers: Vec<Box<dyn SubsystemRestResolver + Send + Sync>> = vec![];
     let mut rpc_resolvers: Vec<Box<dyn SubsystemRpcResolver + Send + Sync>> = vec![];
+    let mut admin_resolvers: Vec<Box<dyn SubsystemRestResolver + Send + Sync>> = vec![];
+    let mut background_services: Vec<Arc<dyn SubsystemBackgroundService>> = vec![];
+    let mut diagnostics: Vec<Arc<dyn SubsystemDiagnostics>> = vec![];
+    let mut access_rules: Vec<Arc<dyn SubsystemAccessRules>> = vec![];
+    let mut context_providers: Vec<SharedContextExtractor> = vec![];
 
     for resolver in subsystem_resolvers {
-        let SubsystemResolver { graphql, rest, rpc } = *resolver;
+        let SubsystemResolver {
+            graphql,
+            rest,
+            rpc,
+            admin,
+            background_services: subsystem_background_services,
+            diagnostics: subsystem_diagnostics,
+            access_rules: subsystem_access_rules,
+            context_providers: subsystem_context_providers,
+        } = *resolver;
+
+        background_services.extend(subsystem_background_services);
+        diagnostics.extend(subsystem_diagnostics);
+        access_rules.extend(subsystem_access_rules);
+        context_providers.extend(subsystem_context_providers);
 
         if let Some(graphql) = graphql {
             graphql_resolvers.push(graphql);
@@ -110,11 +227,52 @@ pub async fn create_system_router_from_system(
         if let Some(rpc) = rpc {
             rpc_resolvers.push(rpc);
         }
+
+        admin_resolvers.extend(admin);
     }
 
-    let graphql_router = {
-        let allow_mutations = env.enabled(EXO_GRAPHQL_ALLOW_MUTATIONS, true)?;
+    validate_context_providers(&context_providers)?;
+
+    let subsystem_summaries = SubsystemSummary::from_resolvers(&graphql_resolvers);
+
+    let maintenance_mode = Arc::new(MaintenanceMode::from_env(env.as_ref())?);
+
+    // In read-only mode (such as when connected to a read replica), mutations are rejected (and
+    // hidden from introspection) and background services (which may write) are not started
+    let read_only = is_read_only(env.as_ref())?;
+    if read_only {
+        info!("Running in read-only mode (mutations will be rejected)");
+        if !background_services.is_empty() {
+            info!(
+                "Skipping {} background service(s) in read-only mode",
+                background_services.len()
+            );
+            background_services.clear();
+        }
+    }
+
+    let allow_mutations = !read_only && env.enabled(EXO_GRAPHQL_ALLOW_MUTATIONS, true)?;
+    let introspection = introspection_mode(env.as_ref())?;
+
+    if is_public_sandbox(env.as_ref())? {
+        check_public_sandbox(
+            env.as_ref(),
+            allow_mutations,
+            introspection,
+            &trusted_documents,
+        )?;
+    }
+
+    let configuration = Configuration::new(
+        env.as_ref(),
+        read_only,
+        allow_mutations,
+        introspection,
+        maintenance_mode.is_enabled(),
+        background_services.len(),
+    )?;
 
+    let graphql_router = {
         let profile = if allow_mutations {
             SchemaProfile::all()
         } else {
@@ -130,7 +288,7 @@ pub async fn create_system_router_from_system(
         let (introspection_resolver, graphql_resolvers): (
             Option<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
             _,
-        ) = match introspection_mode(env.as_ref())? {
+        ) = match introspection {
             IntrospectionMode::Disabled => (None, graphql_resolvers.clone()),
             IntrospectionMode::Enabled => {
                 let introspection_resolver =
@@ -153,15 +311,46 @@ pub async fn create_system_router_from_system(
             mutation_interception_map.clone(),
             trusted_documents,
             env.clone(),
+            maintenance_mode.clone(),
+            operation_limiter.clone(),
+            response_headers.clone(),
         )?
     };
 
+    let invalid_trusted_documents = graphql_router.resolver().check_trusted_documents();
+    if !invalid_trusted_documents.is_empty() && env.enabled(EXO_STRICT_TRUSTED_DOCUMENTS, false)? {
+        let documents = invalid_trusted_documents
+            .iter()
+            .map(|document| match &document.operation_name {
+                Some(operation_name) => {
+                    format!("{} ({operation_name}): {}", document.hash, document.message)
+                }
+                None => format!("{}: {}", document.hash, document.message),
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(SystemLoadingError::Config(format!(
+            "Trusted documents don't validate against the schema: {documents}"
+        )));
+    }
+
+    let route_router = RouteRouter::new(&routes, graphql_router.resolver());
+
     let rest_resolver = SystemRestResolver::new(rest_resolvers, env.clone());
     let rest_router = RestRouter::new(rest_resolver, env.clone());
 
     let rpc_resolver = SystemRpcResolver::new(rpc_resolvers, env.clone());
     let rpc_router = RpcRouter::new(rpc_resolver, env.clone());
 
+    let admin_resolver = SystemRestResolver::new(admin_resolvers, env.clone());
+    let startup_report = Arc::new(OnceLock::new());
+    let admin_router = AdminRouter::new(
+        admin_resolver,
+        env.clone(),
+        maintenance_mode.clone(),
+        startup_report.clone(),
+    );
+
     #[cfg(not(target_family = "wasm"))]
     let mcp_router = create_mcp_router(
         env.clone(),
@@ -170,21 +359,78 @@ pub async fn create_system_router_from_system(
         query_interception_map,
         mutation_interception_map,
         schema_profiles,
+        maintenance_mode.clone(),
+        operation_limiter,
+        read_only,
     )
     .await?;
 
     #[cfg(not(target_family = "wasm"))]
-    {
-        create_system_router(graphql_router, rest_router, rpc_router, mcp_router, env).await
-    }
+    let system_router = create_system_router(
+        graphql_router,
+        rest_router,
+        rpc_router,
+        admin_router,
+        route_router,
+        mcp_router,
+        env,
+    )
+    .await?;
 
     #[cfg(target_family = "wasm")]
-    {
-        create_system_router(graphql_router, rest_router, rpc_router, env).await
+    let system_router = create_system_router(
+        graphql_router,
+        rest_router,
+        rpc_router,
+        admin_router,
+        route_router,
+        env,
+    )
+    .await?;
+
+    let report = StartupReport::new(
+        configuration,
+        subsystem_summaries,
+        system_router.authenticator.as_ref().as_ref(),
+        &diagnostics,
+        invalid_trusted_documents,
+    )
+    .await;
+    report.log();
+    let _ = startup_report.set(report.to_json());
+
+    Ok(SystemRouter {
+        background_services,
+        maintenance_mode,
+        access_rules,
+        context_providers,
+        lifecycle_hooks,
+        ..system_router
+    })
+}
+
+/// Ensure that each context annotation has a single source (otherwise, which one provides the
+/// values of a field would depend on the order of the subsystems)
+fn validate_context_providers(
+    context_providers: &[SharedContextExtractor],
+) -> Result<(), SystemLoadingError> {
+    let mut annotations: Vec<&str> = BUILT_IN_CONTEXT_ANNOTATIONS.to_vec();
+
+    for provider in context_providers {
+        let annotation = provider.annotation_name();
+        if annotations.contains(&annotation) {
+            return Err(SystemLoadingError::Config(format!(
+                "More than one context provider for the '@{annotation}' annotation"
+            )));
+        }
+        annotations.push(annotation);
     }
+
+    Ok(())
 }
 
 #[cfg(not(target_family = "wasm"))]
+#[allow(clippy::too_many_arguments)]
 async fn create_mcp_router(
     env: Arc<dyn Environment>,
     graphql_resolvers: Vec<Arc<dyn SubsystemGraphQLResolver + Send + Sync>>,
@@ -192,14 +438,26 @@ async fn create_mcp_router(
     query_interception_map: Arc<InterceptionMap>,
     mutation_interception_map: Arc<InterceptionMap>,
     schema_profiles: Option<SchemaProfiles>,
+    maintenance_mode: Arc<MaintenanceMode>,
+    operation_limiter: Arc<OperationLimiter>,
+    read_only: bool,
 ) -> Result<McpRouter, SystemLoadingError> {
     let env_clone = env.clone();
     let declaration_doc_comments_clone = declaration_doc_comments.clone();
 
     let create_resolver = |profile: &SchemaProfile| {
+        let profile = if read_only {
+            SchemaProfile {
+                mutations: OperationSet::none(),
+                ..profile.clone()
+            }
+        } else {
+            profile.clone()
+        };
+
         let introspection_schema = Arc::new(Schema::new_from_resolvers(
             &graphql_resolvers,
-            profile,
+            &profile,
             declaration_doc_comments_clone.clone(),
         ));
 
@@ -214,6 +472,10 @@ async fn create_mcp_router(
             mutation_interception_map.clone(),
             TrustedDocuments::all(),
             env.clone(),
+            maintenance_mode.clone(),
+            operation_limiter.clone(),
+            // MCP responses have no HTTP headers of their own
+            Arc::new(DeclaredResponseHeaders::default()),
         )?;
 
         Ok(graphql_router.resolver())
@@ -222,6 +484,21 @@ async fn create_mcp_router(
     McpRouter::new(env_clone, create_resolver, schema_profiles)
 }
 
+/// Add the env defaults declared in the model as the lowest precedence layer of the environment
+fn with_model_defaults(
+    env: Arc<dyn Environment>,
+    env_defaults: &HashMap<String, String>,
+) -> Arc<dyn Environment> {
+    if env_defaults.is_empty() {
+        env
+    } else {
+        Arc::new(CompositeEnvironment::new(vec![
+            env,
+            Arc::new(MapEnvironment::from(env_defaults.clone())),
+        ]))
+    }
+}
+
 pub async fn create_system_resolvers(
     system: SerializableSystem,
     mut static_loaders: StaticLoaders,
@@ -239,6 +516,7 @@ pub async fn create_system_resolvers(
 > {
     fn get_loader(
         static_loaders: &mut StaticLoaders,
+        dynamic_loader_source: &DynamicLoaderSource,
         subsystem_id: String,
     ) -> Result<Box<dyn SubsystemLoader>, SystemLoadingError> {
         // First try to find a static loader
@@ -257,17 +535,13 @@ pub async fn create_system_resolvers(
             #[cfg(not(target_family = "wasm"))]
             {
                 // Otherwise try to load a dynamic loader
-                debug!("Using dynamic loader for {}", subsystem_id);
-                let subsystem_library_name = format!("{subsystem_id}_resolver_dynamic");
-
-                let loader = core_plugin_interface::interface::load_subsystem_loader(
-                    &subsystem_library_name,
-                )?;
+                let loader = dynamic_loader_source.load(&subsystem_id)?;
                 Ok(loader)
             }
 
             #[cfg(target_family = "wasm")]
             {
+                let _ = dynamic_loader_source;
                 panic!("Dynamic loading is not supported on WASM");
             }
         }
@@ -282,10 +556,21 @@ pub async fn create_system_resolvers(
         trusted_documents,
         declaration_doc_comments,
         schema_profiles,
+        env_defaults: _,
+        env_requirements: _,
+        operation_limits: _,
+        operation_response_headers: _,
+        routes: _,
     } = system;
 
+    let dynamic_loader_source = DynamicLoaderSource::from_env(env.as_ref())?;
+
     for subsystem in subsystems {
-        let mut loader = get_loader(&mut static_loaders, subsystem.id.clone())?;
+        let mut loader = get_loader(
+            &mut static_loaders,
+            &dynamic_loader_source,
+            subsystem.id.clone(),
+        )?;
 
         let resolver = loader
             .init(subsystem, env.clone())
@@ -305,10 +590,95 @@ pub async fn create_system_resolvers(
     ))
 }
 
+/// Where to look for subsystem loaders that aren't statically linked into the server.
+///
+/// Subsystems declared in the manifest (`EXO_SUBSYSTEM_MANIFEST`) are loaded from the declared
+/// library. Others are looked up as `<id>_resolver_dynamic` in the directories listed in
+/// `EXO_SUBSYSTEM_LIBRARY_PATH` and then next to the server executable.
+struct DynamicLoaderSource {
+    #[cfg(not(target_family = "wasm"))]
+    manifest: Option<core_plugin_interface::manifest::SubsystemManifest>,
+    #[cfg(not(target_family = "wasm"))]
+    search_dirs: Vec<std::path::PathBuf>,
+}
+
+impl DynamicLoaderSource {
+    #[cfg(not(target_family = "wasm"))]
+    fn from_env(env: &dyn Environment) -> Result<Self, SystemLoadingError> {
+        use common::env_const::{EXO_SUBSYSTEM_LIBRARY_PATH, EXO_SUBSYSTEM_MANIFEST};
+        use core_plugin_interface::{interface::LibraryLoadingError, manifest::SubsystemManifest};
+
+        let manifest = env
+            .get(EXO_SUBSYSTEM_MANIFEST)
+            .map(|manifest_path| {
+                SubsystemManifest::load(Path::new(&manifest_path))
+                    .map_err(LibraryLoadingError::ManifestError)
+            })
+            .transpose()?;
+
+        let search_dirs = env
+            .get(EXO_SUBSYSTEM_LIBRARY_PATH)
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            manifest,
+            search_dirs,
+        })
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn from_env(_env: &dyn Environment) -> Result<Self, SystemLoadingError> {
+        Ok(Self {})
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn load(&self, subsystem_id: &str) -> Result<Box<dyn SubsystemLoader>, SystemLoadingError> {
+        use core_plugin_interface::interface::{
+            LibraryLoadingError, load_subsystem_loader, load_subsystem_loader_from_path,
+        };
+
+        let manifest_library = self
+            .manifest
+            .as_ref()
+            .and_then(|manifest| manifest.library_for(subsystem_id));
+
+        let loader = match manifest_library {
+            Some(library_path) => {
+                debug!(
+                    "Using dynamic loader for {} from manifest ({})",
+                    subsystem_id,
+                    library_path.display()
+                );
+                let loader = load_subsystem_loader_from_path(library_path)?;
+
+                if loader.id() != subsystem_id {
+                    return Err(LibraryLoadingError::SubsystemIdMismatch {
+                        library: library_path.to_path_buf(),
+                        expected: subsystem_id.to_string(),
+                        actual: loader.id().to_string(),
+                    }
+                    .into());
+                }
+                loader
+            }
+            None => {
+                debug!("Using dynamic loader for {}", subsystem_id);
+                let subsystem_library_name = format!("{subsystem_id}_resolver_dynamic");
+                load_subsystem_loader(&subsystem_library_name, &self.search_dirs)?
+            }
+        };
+
+        Ok(loader)
+    }
+}
+
 async fn create_system_router(
     graphql_router: GraphQLRouter,
     rest_router: RestRouter,
     rpc_router: RpcRouter,
+    admin_router: AdminRouter,
+    route_router: RouteRouter,
     #[cfg(not(target_family = "wasm"))] mcp_router: McpRouter,
     env: Arc<dyn Environment>,
 ) -> Result<SystemRouter, SystemLoadingError> {
@@ -323,11 +693,24 @@ async fn create_system_router(
         routers.push(Box::new(rpc_router));
     }
 
+    if env.enabled(EXO_ENABLE_ADMIN_API, false)? {
+        routers.push(Box::new(admin_router));
+    }
+
     #[cfg(not(target_family = "wasm"))]
     {
         if env.enabled(EXO_ENABLE_MCP, true)? {
             routers.push(Box::new(mcp_router));
         }
+
+        if let Some(login_router) = LoginRouter::from_env(env.clone())? {
+            routers.push(Box::new(login_router));
+        }
+    }
+
+    // Before the playground, so that the model's routes take precedence (for example, for "/")
+    if !route_router.is_empty() {
+        routers.push(Box::new(route_router));
     }
 
     #[cfg(target_family = "wasm")]
@@ -351,8 +734,26 @@ pub struct SystemRouter {
     underlying: CorsRouter<CompositeRouter<RequestContextRouter>>,
     env: Arc<dyn Environment>,
     authenticator: Arc<Option<JwtAuthenticator>>,
+    ip_filter: IpFilter,
+    rate_limiter: Option<RateLimiter>,
+    graphql_http_path: String,
     #[cfg(not(target_family = "wasm"))]
     playground_config: Option<Arc<PlaygroundRouterConfig>>,
+    background_services: Vec<Arc<dyn SubsystemBackgroundService>>,
+    maintenance_mode: Arc<MaintenanceMode>,
+    feature_flags: Arc<FeatureFlags>,
+    response_signer: ResponseSigner,
+    /// Routers for schema snapshots served under path prefixes (such as "/v1")
+    schema_versions: Vec<(String, SystemRouter)>,
+    clock: Clock,
+    /// The outcomes of the evaluated access expressions (recorded only when enabled through
+    /// `EXO_ACCESS_COVERAGE`)
+    access_coverage: Option<Arc<AccessCoverage>>,
+    access_rules: Vec<Arc<dyn SubsystemAccessRules>>,
+    /// Sources of context fields provided by subsystems (in addition to the built-in ones)
+    context_providers: Vec<SharedContextExtractor>,
+    /// Hooks of the embedding application (of which the router runs the shutdown ones)
+    lifecycle_hooks: Arc<LifecycleHooks>,
 }
 
 impl SystemRouter {
@@ -363,9 +764,23 @@ impl SystemRouter {
     ) -> Result<Self, SystemLoadingError> {
         let cors_domains = env.get(EXO_CORS_DOMAINS);
 
+        let clock =
+            Clock::from_env(env.as_ref()).map_err(|e| SystemLoadingError::Config(e.to_string()))?;
+
         let authenticator = JwtAuthenticator::new_from_env(env.as_ref())
             .await
+            .map_err(|e| SystemLoadingError::Config(e.to_string()))?
+            .map(|authenticator| authenticator.with_clock(clock.clone()));
+
+        let ip_filter = IpFilter::from_env(env.as_ref())
             .map_err(|e| SystemLoadingError::Config(e.to_string()))?;
+        let rate_limiter = RateLimiter::from_env(env.as_ref())?;
+        let graphql_http_path = get_graphql_http_path(env.as_ref());
+        let feature_flags = Arc::new(FeatureFlags::from_env(env.clone())?);
+        let response_signer = ResponseSigner::from_env(env.as_ref())?;
+        let access_coverage = env
+            .enabled(EXO_ACCESS_COVERAGE, false)?
+            .then(|| Arc::new(AccessCoverage::default()));
 
         Ok(Self {
             underlying: CorsRouter::new(
@@ -374,11 +789,171 @@ impl SystemRouter {
             ),
             env,
             authenticator: Arc::new(authenticator),
+            ip_filter,
+            rate_limiter,
+            graphql_http_path,
             #[cfg(not(target_family = "wasm"))]
             playground_config,
+            background_services: vec![],
+            maintenance_mode: Arc::new(MaintenanceMode::default()),
+            feature_flags,
+            response_signer,
+            schema_versions: vec![],
+            clock,
+            access_coverage,
+            access_rules: vec![],
+            context_providers: vec![],
+            lifecycle_hooks: Arc::new(LifecycleHooks::default()),
         })
     }
 
+    /// Serve schema snapshots under the given path prefixes (see [crate::schema_versions])
+    pub fn with_schema_versions(mut self, schema_versions: Vec<(String, SystemRouter)>) -> Self {
+        self.schema_versions = schema_versions;
+        self
+    }
+
+    /// Count requests against the rate limit (if enabled through `EXO_GRAPHQL_RATE_LIMIT`) in a
+    /// store shared by the servers of a deployment, instead of in memory
+    pub fn with_rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.rate_limiter = self
+            .rate_limiter
+            .map(|rate_limiter| rate_limiter.with_store(store.clone()));
+        self.schema_versions = self
+            .schema_versions
+            .into_iter()
+            .map(|(prefix, router)| (prefix, router.with_rate_limit_store(store.clone())))
+            .collect();
+        self
+    }
+
+    /// Whether requests are rate limited (through `EXO_GRAPHQL_RATE_LIMIT`)
+    pub fn is_rate_limited(&self) -> bool {
+        self.rate_limiter.is_some()
+    }
+
+    /// The clock used by requests (which tests may move when frozen through `EXO_TEST_CLOCK`)
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// The coverage of the access rules by the requests routed so far (if enabled through
+    /// `EXO_ACCESS_COVERAGE`)
+    pub fn access_coverage_report(&self) -> Option<AccessCoverageReport> {
+        let access_coverage = self.access_coverage.as_ref()?;
+
+        Some(AccessCoverageReport {
+            rules: self
+                .access_rules
+                .iter()
+                .flat_map(|access_rules| access_rules.coverage(access_coverage))
+                .collect(),
+        })
+    }
+
+    /// The router of the schema version whose prefix the path starts with (if any)
+    fn schema_version_router(&self, path: &str) -> Option<&SystemRouter> {
+        self.schema_versions
+            .iter()
+            .find(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .map(|(_, router)| router)
+    }
+
+    /// Start the background services of subsystems (such as message consumers).
+    ///
+    /// Only long-running servers should call this (once the router is ready to route requests).
+    #[cfg(not(target_family = "wasm"))]
+    pub fn start_background_services(self: &Arc<Self>) {
+        use core_plugin_interface::interface::SystemRouterRef;
+
+        for service in self.background_services.iter() {
+            let service = service.clone();
+            let router: SystemRouterRef = self.clone();
+            let env = self.env.clone();
+
+            tokio::spawn(async move { service.run(router, env).await });
+        }
+    }
+
+    /// Run the before-drain lifecycle hooks. Servers should call this once they decide to shut
+    /// down, before they stop accepting requests and wait for the in-flight ones.
+    pub async fn run_before_drain_hooks(&self) {
+        self.lifecycle_hooks
+            .run_shutdown(LifecycleStage::BeforeDrain, &self.env)
+            .await;
+    }
+
+    /// Re-read the environment (including env files) and rebuild the JWT validation from it (for
+    /// example, to rotate a secret or move to another OIDC provider without a restart).
+    ///
+    /// On an error, the current configuration stays in place. Does nothing if JWT authentication
+    /// isn't configured (enabling it requires a restart).
+    pub async fn reload_authentication(&self) -> Result<(), SystemLoadingError> {
+        self.env.reload();
+
+        if let Some(authenticator) = self.authenticator.as_ref() {
+            authenticator
+                .reload_from_env(self.env.as_ref())
+                .await
+                .map_err(|e| SystemLoadingError::Config(e.to_string()))?;
+        }
+
+        for (_, router) in self.schema_versions.iter() {
+            Box::pin(router.reload_authentication()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The maintenance mode (which servers may toggle at runtime, for example, on a signal)
+    pub fn maintenance_mode(&self) -> &Arc<MaintenanceMode> {
+        &self.maintenance_mode
+    }
+
+    /// The feature flags (to which servers may add sources such as a database table)
+    pub fn feature_flags(&self) -> &Arc<FeatureFlags> {
+        &self.feature_flags
+    }
+
+    /// Is the client allowed to access the endpoint of the request per the IP allow/deny lists?
+    ///
+    /// Servers may call this before reading the body (the router checks it again before routing).
+    pub fn is_ip_allowed(&self, request_head: &(dyn RequestHead + Send + Sync)) -> bool {
+        let path = request_head.get_path();
+
+        if let Some(router) = self.schema_version_router(&path) {
+            return router.is_ip_allowed(request_head);
+        }
+
+        let endpoint = if path == self.graphql_http_path {
+            IpFilterEndpoint::GraphQL
+        } else if path == HEALTHZ_HTTP_PATH {
+            IpFilterEndpoint::Health
+        } else if self.is_playground_assets_request(&path, request_head.get_method()) {
+            IpFilterEndpoint::Playground
+        } else {
+            return true;
+        };
+
+        self.ip_filter.allows(endpoint, request_head)
+    }
+
+    /// Count a GraphQL request against its client's rate limit (if configured through
+    /// `EXO_GRAPHQL_RATE_LIMIT`). Returns the seconds after which the client may retry, if it has
+    /// exceeded the limit.
+    async fn rate_limit(&self, request_head: &(dyn RequestHead + Send + Sync)) -> Option<u64> {
+        let rate_limiter = self.rate_limiter.as_ref()?;
+
+        if request_head.get_path() != self.graphql_http_path {
+            return None;
+        }
+
+        rate_limiter.check(request_head, self.clock.now()).await
+    }
+
     pub fn is_playground_assets_request(
         &self,
         request_path: &str,
@@ -391,6 +966,10 @@ impl SystemRouter {
 
         #[cfg(not(target_family = "wasm"))]
         {
+            if let Some(router) = self.schema_version_router(request_path) {
+                return router.is_playground_assets_request(request_path, request_method);
+            }
+
             if let Some(playground_config) = &self.playground_config {
                 playground_config.suitable(request_path, request_method)
             } else {
@@ -408,15 +987,55 @@ impl<'request> Router<PlainRequestPayload<'request>> for SystemRouter {
     ) -> Option<ResponsePayload> {
         match request_context {
             PlainRequestPayload::External(request) => {
+                if let Some(router) = self.schema_version_router(&request.get_head().get_path()) {
+                    return router.route(request_context).await;
+                }
+
+                if !self.is_ip_allowed(request.get_head()) {
+                    return Some(ResponsePayload {
+                        body: ResponseBody::None,
+                        headers: Headers::new(),
+                        status_code: http::StatusCode::FORBIDDEN,
+                    });
+                }
+
+                if let Some(retry_after) = self.rate_limit(request.get_head()).await {
+                    let mut headers = Headers::new();
+                    headers.insert("Retry-After".to_string(), retry_after.to_string());
+                    return Some(ResponsePayload {
+                        body: ResponseBody::None,
+                        headers,
+                        status_code: http::StatusCode::TOO_MANY_REQUESTS,
+                    });
+                }
+
+                let parsed_contexts = std::iter::once::<Box<dyn ContextExtractor + Send + Sync>>(
+                    Box::new(FlagExtractor::new(self.feature_flags.clone())),
+                )
+                .chain(
+                    self.context_providers
+                        .iter()
+                        .map(|provider| Box::new(provider.clone()) as _),
+                )
+                .collect();
+
                 let request_context = RequestContext::new(
                     request.as_ref(),
-                    vec![],
+                    parsed_contexts,
                     self,
                     &self.authenticator,
                     self.env.as_ref(),
-                );
+                )
+                .with_clock(self.clock.clone())
+                .with_access_coverage(self.access_coverage.clone());
+
+                let response = self.underlying.route(&request_context).await?;
 
-                self.underlying.route(&request_context).await
+                Some(
+                    self.response_signer
+                        .sign(&request.get_head().get_path(), response)
+                        .await,
+                )
             }
             PlainRequestPayload::Internal(request_context) => {
                 self.underlying.route(request_context).await
@@ -424,3 +1043,70 @@ impl<'request> Router<PlainRequestPayload<'request>> for SystemRouter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use serde_json::Value;
+
+    use common::context::ContextExtractionError;
+
+    use super::*;
+
+    struct AnnotationProvider(&'static str);
+
+    #[async_trait]
+    impl ContextExtractor for AnnotationProvider {
+        fn annotation_name(&self) -> &str {
+            self.0
+        }
+
+        async fn extract_context_field(
+            &self,
+            _key: &str,
+            _request_context: &RequestContext,
+        ) -> Result<Option<Value>, ContextExtractionError> {
+            Ok(None)
+        }
+    }
+
+    fn providers(annotations: &[&'static str]) -> Vec<SharedContextExtractor> {
+        annotations
+            .iter()
+            .map(|annotation| Arc::new(AnnotationProvider(annotation)) as SharedContextExtractor)
+            .collect()
+    }
+
+    fn validation_error(annotations: &[&'static str]) -> String {
+        match validate_context_providers(&providers(annotations)) {
+            Err(SystemLoadingError::Config(message)) => message,
+            other => panic!("Expected a configuration error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn distinct_context_providers() {
+        assert!(validate_context_providers(&providers(&[])).is_ok());
+        assert!(validate_context_providers(&providers(&["ldap", "geoIp"])).is_ok());
+    }
+
+    #[test]
+    fn duplicate_context_providers() {
+        assert_eq!(
+            validation_error(&["ldap", "geoIp", "ldap"]),
+            "More than one context provider for the '@ldap' annotation"
+        );
+    }
+
+    #[test]
+    fn context_provider_for_built_in_annotation() {
+        assert_eq!(
+            validation_error(&["jwt"]),
+            "More than one context provider for the '@jwt' annotation"
+        );
+        assert_eq!(
+            validation_error(&["flag"]),
+            "More than one context provider for the '@flag' annotation"
+        );
+    }
+}
@@ -10,9 +10,10 @@
 use anyhow::{Context, Result, anyhow, bail};
 use colored::Colorize;
 
+use common::access_coverage::AccessCoverageReport;
 use common::env_const::{
-    EXO_CHECK_CONNECTION_ON_STARTUP, EXO_CONNECTION_POOL_SIZE, EXO_INTROSPECTION, EXO_JWT_SECRET,
-    EXO_POSTGRES_READ_WRITE, EXO_POSTGRES_URL,
+    EXO_ACCESS_COVERAGE, EXO_CHECK_CONNECTION_ON_STARTUP, EXO_CONNECTION_POOL_SIZE,
+    EXO_INTROSPECTION, EXO_JWT_SECRET, EXO_POSTGRES_READ_WRITE, EXO_POSTGRES_URL,
 };
 use common::http::{MemoryRequestHead, MemoryRequestPayload, RequestPayload, ResponseBodyError};
 use common::operation_payload::OperationsPayload;
@@ -27,20 +28,24 @@ use regex::Regex;
 use serde_json::{Map, Value, json};
 use system_router::{SystemRouter, create_system_router_from_file};
 
+use std::collections::HashMap;
 use std::collections::HashSet;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use std::{collections::HashMap, time::SystemTime};
 
+use core_plugin_shared::{
+    serializable_system::SerializableSystem, system_serializer::SystemSerializer,
+};
 use exo_env::MapEnvironment;
 use exo_sql::TransactionMode;
 
 use crate::execution::assertion::assert_using_deno;
+use crate::fixtures::generate_fixtures;
 use crate::model::{
-    ApiOperation, ApiOperationInvariant, DatabaseOperation, InitOperation, IntegrationTest,
-    resolve_testvariable,
+    ApiOperation, ApiOperationInvariant, ClockAdjustment, DatabaseOperation, InitOperation,
+    IntegrationTest, resolve_testvariable,
 };
 
 use super::assertion::{dynamic_assert_using_deno, evaluate_using_deno};
@@ -54,6 +59,8 @@ struct TestfileContext {
     jwtsecret: String,
     cookies: HashMap<String, String>,
     testvariables: HashMap<String, serde_json::Value>,
+    /// Fixture factories for the model (prepended to the deno prelude of each testfile)
+    fixtures: String,
 }
 
 impl IntegrationTest {
@@ -61,15 +68,19 @@ impl IntegrationTest {
         &self,
         project_dir: &PathBuf,
         ephemeral_database: &dyn EphemeralDatabaseServer,
+        access_coverage: Option<&Mutex<AccessCoverageReport>>,
         tx: Sender<Result<TestResult>>,
     ) {
         let mut retries_left = self.retries;
         let mut pause = 1000;
         loop {
-            let result =
-                std::panic::AssertUnwindSafe(self.run_no_retry(project_dir, ephemeral_database))
-                    .catch_unwind()
-                    .await;
+            let result = std::panic::AssertUnwindSafe(self.run_no_retry(
+                project_dir,
+                ephemeral_database,
+                access_coverage,
+            ))
+            .catch_unwind()
+            .await;
 
             if result.is_err() {
                 // Don't retry after a panic
@@ -102,6 +113,7 @@ impl IntegrationTest {
         &self,
         project_dir: &PathBuf,
         ephemeral_database: &dyn EphemeralDatabaseServer,
+        access_coverage: Option<&Mutex<AccessCoverageReport>>,
     ) -> Result<TestResult> {
         let test_name = self.name();
         let log_prefix = format!("({})\n :: ", test_name).purple();
@@ -120,23 +132,33 @@ impl IntegrationTest {
                 .map(char::from)
                 .collect();
 
-            // create the schema
-            println!("{log_prefix} Initializing schema for {} ...", test_name);
-
-            let migrate_child = cmd("exo")
-                .args([
-                    "schema",
-                    "migrate",
-                    "--database",
-                    &db_instance.url(),
-                    "--apply-to-database",
-                ])
-                .current_dir(project_dir)
-                .output()?;
+            // create the schema (starting from a snapshot creates the schema as well)
+            if let Some(InitOperation::Snapshot(snapshot)) = self.init_operations.first() {
+                println!("{log_prefix} Restoring snapshot for {} ...", test_name);
 
-            if !migrate_child.status.success() {
-                eprintln!("{}", std::str::from_utf8(&migrate_child.stderr).unwrap());
-                bail!("Could not build schema for {}", test_name);
+                if let OperationResult::Fail(e) =
+                    restore_snapshot(snapshot, &db_instance.url(), false)?
+                {
+                    bail!("Could not restore snapshot for {}: {}", test_name, e);
+                }
+            } else {
+                println!("{log_prefix} Initializing schema for {} ...", test_name);
+
+                let migrate_child = cmd("exo")
+                    .args([
+                        "schema",
+                        "migrate",
+                        "--database",
+                        &db_instance.url(),
+                        "--apply-to-database",
+                    ])
+                    .current_dir(project_dir)
+                    .output()?;
+
+                if !migrate_child.status.success() {
+                    eprintln!("{}", std::str::from_utf8(&migrate_child.stderr).unwrap());
+                    bail!("Could not build schema for {}", test_name);
+                }
             }
 
             // Verify the schema to exercise the verification logic (which in-turn exercises the database introspection logic)
@@ -189,6 +211,10 @@ impl IntegrationTest {
                     (EXO_POSTGRES_READ_WRITE.to_string(), "true".to_string()),
                 ]);
 
+                if access_coverage.is_some() {
+                    env.insert(EXO_ACCESS_COVERAGE.to_string(), "true".to_string());
+                }
+
                 env.extend(extra_envs);
 
                 let env = MapEnvironment::from(env);
@@ -196,18 +222,29 @@ impl IntegrationTest {
                 create_system_router_from_file(&exo_ir_file, static_loaders, Arc::new(env)).await?
             };
 
+            let fixtures = {
+                let exo_ir_file = std::fs::File::open(self.exo_ir_file_path(project_dir))?;
+                let system = SerializableSystem::deserialize_reader(exo_ir_file)?;
+                generate_fixtures(&system)?
+            };
+
             TestfileContext {
                 database_url: db_instance.url(),
                 router,
                 jwtsecret,
                 cookies: HashMap::new(),
                 testvariables: HashMap::new(),
+                fixtures,
             }
         };
 
         // run the init section
         println!("{log_prefix} Initializing database...");
-        for operation in self.init_operations.iter() {
+        let restored_snapshots = match self.init_operations.first() {
+            Some(InitOperation::Snapshot(_)) => 1,
+            _ => 0,
+        };
+        for operation in self.init_operations.iter().skip(restored_snapshots) {
             let result = run_init_operation(operation, &mut ctx)
                 .await
                 .with_context(|| {
@@ -247,6 +284,12 @@ impl IntegrationTest {
             };
         }
 
+        if let Some(access_coverage) = access_coverage
+            && let Some(report) = ctx.router.access_coverage_report()
+        {
+            access_coverage.lock().unwrap().merge(report);
+        }
+
         let success = fail.unwrap_or(TestResultKind::Success);
 
         Ok(TestResult {
@@ -270,6 +313,28 @@ async fn run_init_operation(
     match operation {
         InitOperation::Database(operation) => run_database_operation(operation, ctx).await,
         InitOperation::Api(operation) => assert_api_operation(operation, ctx).await,
+        InitOperation::Snapshot(snapshot) => restore_snapshot(snapshot, &ctx.database_url, true),
+    }
+}
+
+fn restore_snapshot(snapshot: &Path, database_url: &str, clean: bool) -> Result<OperationResult> {
+    let snapshot = snapshot.display().to_string();
+
+    let mut args = vec!["schema", "restore", "--database", database_url];
+    if clean {
+        args.push("--clean");
+    }
+    args.push(&snapshot);
+
+    let restore_child = cmd("exo").args(args).output()?;
+
+    if restore_child.status.success() {
+        Ok(OperationResult::Pass)
+    } else {
+        Ok(OperationResult::Fail(anyhow!(
+            "Could not restore snapshot {snapshot}: {}",
+            String::from_utf8_lossy(&restore_child.stderr)
+        )))
     }
 }
 
@@ -298,10 +363,24 @@ async fn assert_api_operation(
         expected_response: expected_payload,
         deno_prelude,
         invariants,
+        clock,
         ..
     } = operation;
 
-    let deno_prelude = deno_prelude.clone().unwrap_or_default();
+    if let Some(clock) = clock {
+        let router_clock = ctx.router.clock();
+        match clock {
+            ClockAdjustment::Set(time) => router_clock.set(*time),
+            ClockAdjustment::Advance(by) => router_clock.advance(*by),
+        }
+        .context("Could not adjust the clock (set EXO_TEST_CLOCK in `envs` to freeze it)")?;
+    }
+
+    let deno_prelude = format!(
+        "{}\n{}",
+        ctx.fixtures,
+        deno_prelude.clone().unwrap_or_default()
+    );
 
     let pre_results = collect_invariants_results(invariants, ctx).await?;
 
@@ -421,7 +500,11 @@ async fn execute_api_operation(
         ..
     } = operation;
 
-    let deno_prelude = deno_prelude.clone().unwrap_or_default();
+    let deno_prelude = format!(
+        "{}\n{}",
+        ctx.fixtures,
+        deno_prelude.clone().unwrap_or_default()
+    );
 
     // process substitutions in query variables section
     // and extend our collection with the results
@@ -459,11 +542,13 @@ async fn execute_api_operation(
     if let Some(auth) = auth {
         let mut auth = evaluate_using_deno(auth, "", &ctx.testvariables).await?;
         let auth_ref = auth.as_object_mut().unwrap();
-        let epoch_time = SystemTime::UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let epoch_time = ctx.router.clock().now().timestamp();
 
-        // populate token with expiry information
-        auth_ref.insert("iat".to_string(), json!(epoch_time));
-        auth_ref.insert("exp".to_string(), json!(epoch_time + 60 * 60));
+        // populate token with expiry information (unless the testfile specifies it)
+        auth_ref.entry("iat").or_insert_with(|| json!(epoch_time));
+        auth_ref
+            .entry("exp")
+            .or_insert_with(|| json!(epoch_time + 60 * 60));
 
         let token = encode(
             &Header::default(),
@@ -516,11 +601,27 @@ pub async fn run_query(
 
     res.headers.into_iter().for_each(|(k, v)| {
         if k.eq_ignore_ascii_case("set-cookie") {
-            let cookie = v.split(';').next().unwrap();
-            let mut cookie = cookie.split('=');
+            let mut attributes = v.split(';');
+            let mut cookie = attributes.next().unwrap().split('=');
             let key = cookie.next().unwrap();
             let value = cookie.next().unwrap();
-            cookies.insert(key.to_string(), value.to_string());
+
+            // A cookie that is already expired clears the cookie (as browsers do)
+            let expired = attributes.any(|attribute| {
+                attribute
+                    .trim()
+                    .split_once('=')
+                    .is_some_and(|(name, max_age)| {
+                        name.eq_ignore_ascii_case("max-age")
+                            && max_age.parse::<i64>().is_ok_and(|max_age| max_age <= 0)
+                    })
+            });
+
+            if expired {
+                cookies.remove(key);
+            } else {
+                cookies.insert(key.to_string(), value.to_string());
+            }
         }
     });
 
@@ -530,6 +631,10 @@ pub async fn run_query(
 use std::process::Command;
 
 pub(crate) fn cmd(binary_name: &str) -> Command {
+    Command::new(exo_binary(binary_name))
+}
+
+pub(crate) fn exo_binary(binary_name: &str) -> PathBuf {
     // Pick up the current executable path and replace the file with the specified binary
     // This allows us to invoke `target/debug/exo test ...` or `target/release/exo test ...`
     // without updating the PATH env.
@@ -538,9 +643,12 @@ pub(crate) fn cmd(binary_name: &str) -> Command {
     let mut executable =
         std::env::current_exe().expect("Could not retrieve the current executable");
     executable.set_file_name(binary_name);
-    Command::new(
+
+    if executable.exists() {
         executable
-            .to_str()
-            .expect("Could not convert executable path to a string"),
-    )
+    } else {
+        // Running from another binary (such as a project's test binary through [crate::TestRunner]),
+        // so use the one on $PATH
+        PathBuf::from(binary_name)
+    }
 }
@@ -1,15 +1,19 @@
 use anyhow::{Context, Result, bail};
+use colored::Colorize;
 use futures::FutureExt;
 use std::{
     ffi::OsStr,
     io::Write,
     path::Path,
     process::Command,
-    sync::{Arc, mpsc::Sender},
+    sync::{Arc, Mutex, mpsc::Sender},
 };
 
+use common::access_coverage::AccessCoverageReport;
+
 use exo_sql::testing::db::EphemeralDatabaseServer;
 
+use super::integration_test::exo_binary;
 use super::introspection_tests::run_introspection_test;
 use crate::model::TestSuite;
 
@@ -19,6 +23,7 @@ impl TestSuite {
     pub fn run(
         self,
         run_introspection_tests: bool,
+        access_coverage: bool,
         ephemeral_server: Arc<Box<dyn EphemeralDatabaseServer + Send + Sync>>,
         tx: Sender<Result<TestResult>>,
         tasks: crossbeam_channel::Sender<Box<dyn FnOnce() + Send>>,
@@ -54,14 +59,30 @@ impl TestSuite {
                                 .unwrap();
                         };
 
+                        let access_coverage =
+                            access_coverage.then(|| Mutex::new(AccessCoverageReport::default()));
+
                         for test in self.tests.iter() {
                             test.run(
                                 &project_dir,
                                 ephemeral_server.as_ref().as_ref() as &dyn EphemeralDatabaseServer,
+                                access_coverage.as_ref(),
                                 tx.clone(),
                             )
                             .await;
                         }
+
+                        if let Some(access_coverage) = access_coverage
+                            && let Err(e) = write_access_coverage(
+                                &project_dir,
+                                &access_coverage.into_inner().unwrap(),
+                            )
+                        {
+                            println!(
+                                "{} {e:?}",
+                                "Could not write the access coverage report:".red()
+                            );
+                        }
                     })
                 }
                 Err(e) => tx
@@ -77,12 +98,12 @@ impl TestSuite {
             .unwrap();
     }
 
-    fn build_exo_ir_file(&self) -> Result<()> {
+    pub(crate) fn build_exo_ir_file(&self) -> Result<()> {
         self.build_prerequisites()?;
 
-        // Use std::env::current_exe() so that we run the same "exo" that invoked us (specifically, avoid using another exo on $PATH)
+        // Run the same "exo" that invoked us (specifically, avoid using another exo on $PATH)
         run_command(
-            std::env::current_exe()?.as_os_str().to_str().unwrap(),
+            exo_binary("exo").as_os_str().to_str().unwrap(),
             [OsStr::new("build")],
             Some(self.project_dir.as_ref()),
             "Could not build the exo_ir.",
@@ -121,6 +142,22 @@ impl TestSuite {
 }
 
 // Helper to run a command and return an error if it fails
+/// Write the report (as `target/access-coverage.json` in the project directory) and print its
+/// summary
+fn write_access_coverage(project_dir: &Path, report: &AccessCoverageReport) -> Result<()> {
+    let report_path = project_dir.join("target").join("access-coverage.json");
+    std::fs::create_dir_all(project_dir.join("target"))?;
+    std::fs::write(&report_path, serde_json::to_string_pretty(report)?)?;
+
+    println!(
+        "{} {}\n{report}",
+        "** Access coverage written to".blue().bold(),
+        report_path.display()
+    );
+
+    Ok(())
+}
+
 fn run_command<I, S>(
     program: &str,
     args: I,
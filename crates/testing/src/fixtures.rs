@@ -0,0 +1,318 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Generation of fixture factories (in JavaScript) for the Postgres entities of a model.
+//!
+//! For each entity, the generated `fixtures` object offers:
+//! - `fixtures.<Entity>.build(overrides)`: data for the `create<Entity>` mutation with generated
+//!   values for the required fields (fields with a default value are left to the default)
+//! - `fixtures.<Entity>.create(exograph, overrides, selection)`: creates the entity through the
+//!   `Exograph` object (in a Deno module), first creating entities for required relations that
+//!   `overrides` doesn't supply
+//!
+//! Generated values are unique across calls (so they satisfy uniqueness constraints) and respect
+//! the maximum length of string columns.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use core_model::{mapped_arena::SerializableSlabIndex, types::FieldType};
+use core_plugin_shared::{
+    serializable_system::SerializableSystem, system_serializer::SystemSerializer,
+};
+use exo_sql::{DEFAULT_VECTOR_SIZE, Database, StringColumnType, VectorColumnType};
+use postgres_core_model::{
+    relation::PostgresRelation,
+    subsystem::PostgresCoreSubsystem,
+    types::{
+        EntityRepresentation, EntityType, PostgresField, PostgresPrimitiveTypeKind, TypeIndex,
+    },
+};
+use postgres_graphql_model::{
+    mutation::PostgresMutationParameters, subsystem::PostgresGraphQLSubsystem,
+};
+
+const PRELUDE: &str = r#"// Fixture factories generated by `exo fixtures` (do not edit)
+
+let fixtureSequence = Math.floor(Math.random() * 1000000);
+
+function nextFixtureSequence() {
+  fixtureSequence += 1;
+  return fixtureSequence;
+}
+
+function fixtureString(prefix, n, maxLength) {
+  const value = `${prefix} ${n}`;
+  return maxLength === undefined || value.length <= maxLength ? value : String(n).slice(-maxLength);
+}
+
+function fixtureDate(n) {
+  return new Date(Date.UTC(2000, 0, 1) + (n % 10000) * 86400000);
+}
+"#;
+
+/// Generate the fixture factories for the Postgres subsystem of the system (or an empty `fixtures`
+/// object, if the system doesn't have one)
+pub fn generate_fixtures(system: &SerializableSystem) -> Result<String> {
+    let mut output = PRELUDE.to_string();
+    writeln!(output)?;
+    writeln!(output, "export const fixtures = {{")?;
+
+    let postgres_subsystem = system
+        .subsystems
+        .iter()
+        .find(|subsystem| subsystem.id == "postgres");
+
+    if let Some(subsystem) = postgres_subsystem {
+        let core_subsystem = PostgresCoreSubsystem::deserialize(subsystem.core.0.clone())?;
+        let graphql_subsystem = subsystem
+            .graphql
+            .as_ref()
+            .map(|graphql| PostgresGraphQLSubsystem::deserialize(graphql.0.clone()))
+            .transpose()?;
+
+        for (entity_id, entity_type) in core_subsystem.entity_types.iter() {
+            if matches!(entity_type.representation, EntityRepresentation::Computed) {
+                continue;
+            }
+
+            let mutation = graphql_subsystem
+                .as_ref()
+                .and_then(|graphql_subsystem| create_mutation(graphql_subsystem, entity_id));
+
+            write_entity_fixture(&mut output, entity_type, mutation, &core_subsystem)?;
+        }
+    }
+
+    writeln!(output, "}};")?;
+
+    Ok(output)
+}
+
+/// The name of the (single) create mutation and its input type for an entity
+fn create_mutation(
+    graphql_subsystem: &PostgresGraphQLSubsystem,
+    entity_id: SerializableSlabIndex<EntityType>,
+) -> Option<(&str, &str)> {
+    graphql_subsystem
+        .mutations
+        .iter()
+        .find_map(|(_, mutation)| match &mutation.parameters {
+            PostgresMutationParameters::Create(data_param) => match &data_param.typ {
+                FieldType::Plain(data_type) => {
+                    let mutation_type = &graphql_subsystem.mutation_types[data_type.type_id];
+                    (mutation_type.entity_id == entity_id)
+                        .then_some((mutation.name.as_str(), data_type.name.as_str()))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+fn write_entity_fixture(
+    output: &mut String,
+    entity_type: &EntityType,
+    mutation: Option<(&str, &str)>,
+    core_subsystem: &PostgresCoreSubsystem,
+) -> Result<()> {
+    let required_fields: Vec<&PostgresField<EntityType>> = entity_type
+        .fields
+        .iter()
+        .filter(|field| is_required(field))
+        .collect();
+
+    writeln!(output, "  {}: {{", entity_type.name)?;
+
+    writeln!(output, "    build(overrides = {{}}) {{")?;
+    writeln!(output, "      const n = nextFixtureSequence();")?;
+    writeln!(output, "      return {{")?;
+    for field in required_fields.iter() {
+        if let Some(value) = field_value(field, entity_type, core_subsystem) {
+            writeln!(output, "        {}: {value},", field.name)?;
+        }
+    }
+    writeln!(output, "        ...overrides,")?;
+    writeln!(output, "      }};")?;
+    writeln!(output, "    }},")?;
+
+    if let Some((mutation_name, input_type_name)) = mutation {
+        writeln!(
+            output,
+            "    async create(exograph, overrides = {{}}, selection = \"{}\") {{",
+            pk_selection(entity_type)
+        )?;
+        writeln!(output, "      const data = {{ ...overrides }};")?;
+
+        for field in required_fields.iter() {
+            if let PostgresRelation::ManyToOne { relation, .. } = &field.relation {
+                let foreign_entity_type = &core_subsystem.entity_types[relation.foreign_entity_id];
+
+                // Let the caller supply self-references (to avoid creating an infinite chain)
+                if foreign_entity_type.name == entity_type.name {
+                    continue;
+                }
+
+                let foreign_pk_names: Vec<&str> = relation
+                    .foreign_pk_field_ids
+                    .iter()
+                    .map(|field_id| field_id.resolve(&core_subsystem.entity_types).name.as_str())
+                    .collect();
+                let reference = foreign_pk_names
+                    .iter()
+                    .map(|name| format!("{name}: {}.{name}", field.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                writeln!(output, "      if (data.{} === undefined) {{", field.name)?;
+                writeln!(
+                    output,
+                    "        const {} = await fixtures.{}.create(exograph, {{}}, \"{}\");",
+                    field.name,
+                    foreign_entity_type.name,
+                    foreign_pk_names.join(" ")
+                )?;
+                writeln!(output, "        data.{} = {{ {reference} }};", field.name)?;
+                writeln!(output, "      }}")?;
+            }
+        }
+
+        writeln!(output, "      const result = await exograph.executeQuery(")?;
+        writeln!(
+            output,
+            "        `mutation($data: {input_type_name}!) {{ result: {mutation_name}(data: $data) {{ ${{selection}} }} }}`,"
+        )?;
+        writeln!(
+            output,
+            "        {{ data: fixtures.{}.build(data) }},",
+            entity_type.name
+        )?;
+        writeln!(output, "      );")?;
+        writeln!(output, "      return result.result;")?;
+        writeln!(output, "    }},")?;
+    }
+
+    writeln!(output, "  }},")?;
+
+    Ok(())
+}
+
+/// Is a value for the field required when creating the entity?
+fn is_required(field: &PostgresField<EntityType>) -> bool {
+    !matches!(field.typ, FieldType::Optional(_))
+        && field.default_value.is_none()
+        && !field.readonly
+        && matches!(
+            field.relation,
+            PostgresRelation::Scalar { .. }
+                | PostgresRelation::ManyToOne { .. }
+                | PostgresRelation::Embedded
+        )
+}
+
+/// A JavaScript expression for the value of a required field (`None` for relations, which `build`
+/// expects to be supplied)
+fn field_value(
+    field: &PostgresField<EntityType>,
+    entity_type: &EntityType,
+    core_subsystem: &PostgresCoreSubsystem,
+) -> Option<String> {
+    let database = &core_subsystem.database;
+
+    if matches!(field.relation, PostgresRelation::ManyToOne { .. }) {
+        return None;
+    }
+
+    if matches!(field.typ, FieldType::List(_)) && field.typ.innermost().type_name != "Vector" {
+        return Some("[]".to_string());
+    }
+
+    let value = match &field.typ.innermost().type_id {
+        TypeIndex::Composite(type_id) => {
+            format!(
+                "fixtures.{}.build()",
+                core_subsystem.entity_types[*type_id].name
+            )
+        }
+        TypeIndex::Primitive(type_id) => {
+            let primitive_type = &core_subsystem.primitive_types[*type_id];
+
+            match &primitive_type.kind {
+                PostgresPrimitiveTypeKind::Enum(values) => values
+                    .first()
+                    .map(|value| serde_json::to_string(value).unwrap())
+                    .unwrap_or_else(|| "null".to_string()),
+                PostgresPrimitiveTypeKind::Builtin => match primitive_type.name.as_str() {
+                    "String" => format!(
+                        "fixtureString({}, n, {})",
+                        serde_json::to_string(&format!("{} {}", entity_type.name, field.name))
+                            .unwrap(),
+                        max_length(field, database)
+                            .map(|max_length| max_length.to_string())
+                            .unwrap_or_else(|| "undefined".to_string())
+                    ),
+                    "Int" | "Float" => "n".to_string(),
+                    "Decimal" => "String(n)".to_string(),
+                    "Boolean" => "false".to_string(),
+                    "Uuid" => "crypto.randomUUID()".to_string(),
+                    "LocalDate" => "fixtureDate(n).toISOString().slice(0, 10)".to_string(),
+                    "LocalTime" => "fixtureDate(n).toISOString().slice(11, 19)".to_string(),
+                    "LocalDateTime" => "fixtureDate(n).toISOString().slice(0, 19)".to_string(),
+                    "Instant" => "fixtureDate(n).toISOString()".to_string(),
+                    "Json" => "{}".to_string(),
+                    "Blob" => "\"\"".to_string(),
+                    "Vector" => format!(
+                        "new Array({}).fill(1)",
+                        vector_size(field, database).unwrap_or(DEFAULT_VECTOR_SIZE)
+                    ),
+                    _ => "null".to_string(),
+                },
+            }
+        }
+    };
+
+    Some(value)
+}
+
+fn max_length(field: &PostgresField<EntityType>, database: &Database) -> Option<usize> {
+    match &field.relation {
+        PostgresRelation::Scalar { column_id, .. } => column_id
+            .get_column(database)
+            .typ
+            .as_any()
+            .downcast_ref::<StringColumnType>()
+            .and_then(|typ| typ.max_length),
+        _ => None,
+    }
+}
+
+fn vector_size(field: &PostgresField<EntityType>, database: &Database) -> Option<usize> {
+    match &field.relation {
+        PostgresRelation::Scalar { column_id, .. } => column_id
+            .get_column(database)
+            .typ
+            .as_any()
+            .downcast_ref::<VectorColumnType>()
+            .map(|typ| typ.size),
+        _ => None,
+    }
+}
+
+/// Selection of the primary key fields (scalar ones, since selecting a relation needs a nested
+/// selection)
+fn pk_selection(entity_type: &EntityType) -> String {
+    entity_type
+        .fields
+        .iter()
+        .filter(|field| matches!(field.relation, PostgresRelation::Scalar { is_pk: true, .. }))
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -8,8 +8,10 @@
 // by the Apache License, Version 2.0.
 
 pub(crate) mod execution;
+mod fixtures;
 pub(crate) mod loader;
 mod model;
+mod runner;
 
 use std::cmp::min;
 use std::ffi::OsStr;
@@ -24,12 +26,15 @@ use exo_sql::testing::db::{EphemeralDatabaseLauncher, pgvector_available};
 use model::TestSuite;
 
 pub use execution::get_introspection_result;
+pub use fixtures::generate_fixtures;
+pub use runner::{TestReport, TestRunner};
 
 /// Loads test files from the supplied directory and runs them using a thread pool.
 pub fn run(
     root_directory: &PathBuf,
     pattern: &Option<String>,
     run_introspection_tests: bool,
+    access_coverage: bool,
 ) -> Result<()> {
     // Make sure deno runtime is initialized in the main thread to work around deno segfault
     // on Linux issue. The tests are run in parallel and will initialize the deno module
@@ -59,6 +64,10 @@ pub fn run(
         println!("{}", "** Introspection tests enabled".blue().bold());
     };
 
+    if access_coverage {
+        println!("{}", "** Access coverage enabled".blue().bold());
+    }
+
     println!("{}", "** Running integration tests".blue().bold());
 
     let db_launcher = EphemeralDatabaseLauncher::from_env();
@@ -97,6 +106,7 @@ pub fn run(
     for project_test in project_tests {
         project_test.run(
             run_introspection_tests,
+            access_coverage,
             ephemeral_server.clone(),
             tx.clone(),
             tasks.clone(),
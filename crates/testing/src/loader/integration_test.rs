@@ -19,8 +19,8 @@ use async_graphql_parser::parse_query;
 use serde::Deserialize;
 
 use crate::model::{
-    ApiOperation, ApiOperationInvariant, DatabaseOperation, InitOperation, IntegrationTest,
-    OperationMetadata, build_operations_metadata,
+    ApiOperation, ApiOperationInvariant, ClockAdjustment, DatabaseOperation, InitOperation,
+    IntegrationTest, OperationMetadata, build_operations_metadata,
 };
 
 // serde file formats
@@ -33,6 +33,7 @@ struct TestfileStage {
     pub auth: Option<String>,
     pub response: Option<String>,
     pub invariants: Option<Vec<TestfileStageInvariant>>,
+    pub clock: Option<String>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
@@ -138,6 +139,8 @@ impl IntegrationTest {
             Ok(vec![InitOperation::Database(DatabaseOperation {
                 sql: contents,
             })])
+        } else if extension == "pgdump" {
+            Ok(vec![InitOperation::Snapshot(init_file_path.clone())])
         } else {
             bail!("Unsupported init file extension: {}", extension);
         }
@@ -209,6 +212,13 @@ impl IntegrationTest {
                 let invariants =
                     Self::load_invariants(testfile_path, stage.invariants.unwrap_or_default())?;
 
+                let clock = stage
+                    .clock
+                    .as_deref()
+                    .map(parse_clock_adjustment)
+                    .transpose()
+                    .with_context(|| format!("In {}", testfile_path.display()))?;
+
                 Ok(ApiOperation {
                     document: stage.operation,
                     metadata: operations_metadata,
@@ -218,6 +228,7 @@ impl IntegrationTest {
                     headers: stage.headers,
                     deno_prelude: stage.deno,
                     invariants,
+                    clock,
                 })
             })
             .collect::<Result<Vec<_>>>()?;
@@ -267,3 +278,68 @@ impl IntegrationTest {
         Ok(invariant_ops)
     }
 }
+
+/// Parse the `clock` of a stage: either an RFC3339 time (to move the clock to) or a duration
+/// prefixed with `+` (to move the clock forward by)
+fn parse_clock_adjustment(value: &str) -> Result<ClockAdjustment> {
+    let value = value.trim();
+
+    match value.strip_prefix('+') {
+        Some(duration) => {
+            let split_at = duration
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(duration.len());
+            let (amount, unit) = duration.split_at(split_at);
+            let amount: i64 = amount
+                .parse()
+                .with_context(|| format!("Invalid clock duration '{value}'"))?;
+
+            let duration = match unit {
+                "s" => chrono::Duration::seconds(amount),
+                "m" => chrono::Duration::minutes(amount),
+                "h" => chrono::Duration::hours(amount),
+                "d" => chrono::Duration::days(amount),
+                _ => bail!(
+                    "Invalid clock duration '{value}' (expected a unit of 's', 'm', 'h', or 'd')"
+                ),
+            };
+
+            Ok(ClockAdjustment::Advance(duration))
+        }
+        None => {
+            let time = chrono::DateTime::parse_from_rfc3339(value).with_context(|| {
+                format!("Invalid clock time '{value}' (expected an RFC3339 time or a duration such as '+1h')")
+            })?;
+            Ok(ClockAdjustment::Set(time.to_utc()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_adjustments() {
+        assert_eq!(
+            parse_clock_adjustment("+90s").unwrap(),
+            ClockAdjustment::Advance(chrono::Duration::seconds(90))
+        );
+        assert_eq!(
+            parse_clock_adjustment("+2d").unwrap(),
+            ClockAdjustment::Advance(chrono::Duration::days(2))
+        );
+        assert_eq!(
+            parse_clock_adjustment("2024-01-01T02:00:00+02:00").unwrap(),
+            ClockAdjustment::Set(
+                chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+                    .unwrap()
+                    .to_utc()
+            )
+        );
+
+        assert!(parse_clock_adjustment("+1w").is_err());
+        assert!(parse_clock_adjustment("+h").is_err());
+        assert!(parse_clock_adjustment("tomorrow").is_err());
+    }
+}
@@ -71,7 +71,7 @@ fn load_tests_dir(
                     // TODO: https://github.com/rust-lang/rust/issues/49802
                     //if filename.starts_with("init") {
                     if filename.to_str().unwrap().starts_with("init")
-                        && (extension == "sql" || extension == "gql")
+                        && (extension == "sql" || extension == "gql" || extension == "pgdump")
                     {
                         init_files.push(dir_entry.path());
                     }
@@ -9,6 +9,8 @@
 
 use std::{collections::HashMap, path::PathBuf};
 
+use chrono::{DateTime, Duration, Utc};
+
 mod operations_metadata;
 
 pub use operations_metadata::{OperationMetadata, build_operations_metadata, resolve_testvariable};
@@ -39,6 +41,9 @@ pub struct IntegrationTest {
 pub enum InitOperation {
     Database(DatabaseOperation),
     Api(ApiOperation),
+    /// A snapshot created by `exo schema dump` (in place of migrating the schema, if it is the first
+    /// operation)
+    Snapshot(PathBuf),
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +62,16 @@ pub struct ApiOperation {
 
     pub expected_response: Option<String>, // stringified
     pub invariants: Vec<ApiOperationInvariant>,
+    /// Change to the server's clock (frozen through `EXO_TEST_CLOCK`) before running the operation
+    pub clock: Option<ClockAdjustment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClockAdjustment {
+    /// Move the clock to the time (specified as an RFC3339 time such as `2024-01-01T00:00:00Z`)
+    Set(DateTime<Utc>),
+    /// Move the clock forward (specified as a duration such as `+90s`, `+15m`, `+2h`, or `+30d`)
+    Advance(Duration),
 }
 
 #[derive(Debug, Clone)]
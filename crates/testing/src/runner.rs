@@ -0,0 +1,214 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Running the testfiles of a project from Rust code.
+//!
+//! This allows keeping declarative API tests (`.exotest` files along with `init*` seed data) next
+//! to the model and running them from a project's own test binary (for example, in CI):
+//!
+//! ```rust
+//! #[test]
+//! fn api_tests() {
+//!     let report = testing::TestRunner::new("path/to/project").run().unwrap();
+//!     report.assert_success();
+//! }
+//! ```
+//!
+//! Each testfile runs against its own ephemeral database (launched as with `exo test`, so
+//! `EXO_SQL_EPHEMERAL_DATABASE_LAUNCH_PREFERENCE` applies). Building the model and migrating the
+//! schema uses the `exo` binary (from `PATH` unless it is next to the running executable).
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use exo_sql::testing::db::{EphemeralDatabaseLauncher, EphemeralDatabaseServer};
+
+use crate::execution::TestResult;
+use crate::model::TestSuite;
+
+pub struct TestRunner {
+    project_dir: PathBuf,
+    pattern: Option<String>,
+}
+
+impl TestRunner {
+    /// A runner for the tests of the project in the given directory (the one with `src/index.exo`
+    /// and `tests/`), or of all projects under it
+    pub fn new(project_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            project_dir: project_dir.into(),
+            pattern: None,
+        }
+    }
+
+    /// Run only the tests whose name matches the pattern (such as `*user*`)
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Build the model and run the tests (one after another).
+    ///
+    /// This blocks the current thread (and creates its own async runtime), so call it from a
+    /// plain `#[test]` rather than from an async context.
+    pub fn run(&self) -> Result<TestReport> {
+        exo_deno::initialize();
+
+        let suites = TestSuite::load(&self.project_dir, &self.pattern).with_context(|| {
+            format!(
+                "While loading testfiles from directory {}",
+                self.project_dir.display()
+            )
+        })?;
+
+        let ephemeral_server = EphemeralDatabaseLauncher::from_env().create_server()?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()?;
+
+        let (tx, rx) = mpsc::channel();
+
+        for suite in suites {
+            if let Err(e) = suite.build_exo_ir_file() {
+                tx.send(Err(e).with_context(|| {
+                    format!(
+                        "While trying to build exo_ir file for {}",
+                        suite.project_dir.display()
+                    )
+                }))?;
+                continue;
+            }
+
+            let local = tokio::task::LocalSet::new();
+            local.block_on(&runtime, async {
+                for test in suite.tests.iter() {
+                    test.run(
+                        &suite.project_dir,
+                        ephemeral_server.as_ref() as &dyn EphemeralDatabaseServer,
+                        None,
+                        tx.clone(),
+                    )
+                    .await;
+                }
+            });
+        }
+
+        drop(tx);
+
+        let mut results: Vec<_> = rx.into_iter().collect();
+        results.sort_by(|a, b| match (a, b) {
+            (Ok(a), Ok(b)) => a.cmp(b),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => std::cmp::Ordering::Equal,
+        });
+
+        Ok(TestReport { results })
+    }
+}
+
+/// The results of the testfiles run by a [TestRunner]
+pub struct TestReport {
+    results: Vec<Result<TestResult>>,
+}
+
+impl TestReport {
+    pub fn total(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.as_ref().is_ok_and(|result| result.is_success()))
+            .count()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.passed() == self.total()
+    }
+
+    /// Panic (with the details of the failures) unless all tests passed
+    pub fn assert_success(&self) {
+        if !self.is_success() {
+            panic!("{self}");
+        }
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in self.results.iter() {
+            match result {
+                Ok(result) => write!(f, "{result}")?,
+                Err(e) => writeln!(f, "{}\n{e:?}\n", "A testfile errored while running.".red())?,
+            }
+        }
+
+        let status = if self.is_success() {
+            "PASS.".green()
+        } else {
+            "FAIL.".red()
+        };
+
+        writeln!(
+            f,
+            "{} {} {} out of {} total",
+            "* Test results:".blue().bold(),
+            status,
+            format!("{} passed", self.passed()).bold(),
+            self.total()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::execution::TestResultKind;
+
+    use super::*;
+
+    #[test]
+    fn report_success() {
+        let passed = || {
+            Ok(TestResult {
+                log_prefix: "(a)".to_string(),
+                result: TestResultKind::Success,
+            })
+        };
+
+        let report = TestReport {
+            results: vec![passed(), passed()],
+        };
+        assert!(report.is_success());
+        report.assert_success();
+
+        let report = TestReport {
+            results: vec![
+                passed(),
+                Ok(TestResult {
+                    log_prefix: "(b)".to_string(),
+                    result: TestResultKind::Fail(anyhow!("Unexpected response")),
+                }),
+                Err(anyhow!("Could not build")),
+            ],
+        };
+        assert!(!report.is_success());
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.total(), 3);
+    }
+}
@@ -61,6 +61,7 @@ impl GraphQLSubsystemBuilder for GraphQLWasmSubsystemBuilder {
                     expr,
                     kind,
                     index: InterceptorIndex(index.to_idx()),
+                    error_policy: interceptor.error_policy,
                 }
             })
             .collect();
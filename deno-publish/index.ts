@@ -23,6 +23,12 @@ export interface Exograph {
     variables: V
   ): Promise<T>;
   addResponseHeader(name: string, value: string): Promise<void>;
+  now(): Date;
+  mintServiceToken(
+    claims: Record<string, any>,
+    options?: { audience?: string | string[], expiresIn?: number }
+  ): string;
+  fetch(input: string | URL | Request, init?: RequestInit): Promise<Response>;
   setCookie(cookie: {
     name: string,
     value: string,
@@ -34,6 +40,26 @@ export interface Exograph {
     httpOnly?: boolean,
     sameSite?: "Lax" | "Strict" | "None"
   }): Promise<void>;
+  clearCookie(name: string, options?: { domain?: string, path?: string }): Promise<void>;
+  setSessionCookie(token: string, options?: {
+    expires?: Date,
+    maxAge?: number,
+    domain?: string,
+    path?: string,
+    secure?: boolean,
+    httpOnly?: boolean,
+    sameSite?: "Lax" | "Strict" | "None"
+  }): Promise<void>;
+  clearSessionCookie(options?: { domain?: string, path?: string }): Promise<void>;
+  saga<T>(name: string, body: (saga: Saga) => Promise<T>): Promise<T>;
+}
+
+export interface Saga {
+  step<T>(
+    name: string,
+    action: () => Promise<T> | T,
+    compensate?: (result: T, exograph: Exograph) => Promise<void> | void
+  ): Promise<T>;
 }
 
 export type ContextOverride = Record<string, any> | undefined;
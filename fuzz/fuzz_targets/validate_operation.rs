@@ -0,0 +1,38 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![no_main]
+
+//! Validate arbitrary GraphQL documents against the schema of the fuzzing model.
+//!
+//! The input is the document, optionally followed by a NUL byte and the variables (as JSON).
+
+use async_graphql_parser::parse_query;
+use core_resolver::validation::document_validator::DocumentValidator;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut parts = data.splitn(2, |byte| *byte == 0);
+
+    let Some(Ok(document)) = parts.next().map(std::str::from_utf8) else {
+        return;
+    };
+
+    let variables = parts
+        .next()
+        .and_then(|variables| serde_json::from_slice::<serde_json::Value>(variables).ok())
+        .and_then(|variables| variables.as_object().cloned());
+
+    let Ok(document) = parse_query(document) else {
+        return;
+    };
+
+    let validator = DocumentValidator::new(exograph_fuzz::schema(), None, variables, 10, 10);
+    let _ = validator.validate(document);
+});
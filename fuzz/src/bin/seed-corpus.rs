@@ -0,0 +1,120 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Seed the fuzzing corpora from the test suite:
+//! - `corpus/validate_operation`: the operations in `.exotest` and init `.gql` files
+//! - `corpus/typecheck_exo`: the `.exo` files
+//!
+//! Additionally, the seeds in `seeds/<target>` (inputs that the test suite doesn't cover, such as
+//! subscriptions and operations with variables) are copied to the corresponding corpus.
+//!
+//! Run from the `fuzz` directory: `cargo run --bin seed-corpus [<test-dir>...]` (by default, the
+//! `integration-tests` and `error-report-testing` directories).
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+fn main() -> io::Result<()> {
+    let args: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    let test_dirs = if args.is_empty() {
+        vec![
+            PathBuf::from("../integration-tests"),
+            PathBuf::from("../error-report-testing"),
+        ]
+    } else {
+        args
+    };
+
+    let operation_corpus = Path::new("corpus/validate_operation");
+    let exo_corpus = Path::new("corpus/typecheck_exo");
+    fs::create_dir_all(operation_corpus)?;
+    fs::create_dir_all(exo_corpus)?;
+
+    let mut operation_count = 0;
+    let mut exo_count = 0;
+
+    for (target, corpus) in [
+        ("validate_operation", operation_corpus),
+        ("typecheck_exo", exo_corpus),
+    ] {
+        let seeds_dir = Path::new("seeds").join(target);
+        if seeds_dir.exists() {
+            for file in files(&seeds_dir)? {
+                fs::copy(&file, corpus.join(file.file_name().unwrap()))?;
+            }
+        }
+    }
+
+    for test_dir in test_dirs.iter().filter(|dir| dir.exists()) {
+        for file in files(test_dir)? {
+            match file.extension().and_then(|extension| extension.to_str()) {
+                Some("exo") => {
+                    write_seed(exo_corpus, &fs::read_to_string(&file)?)?;
+                    exo_count += 1;
+                }
+                Some("exotest") | Some("gql") => {
+                    let content = fs::read_to_string(&file)?;
+                    // Files that don't parse are reported by `exo test`, so just skip them here
+                    let Ok(testfile) = serde_saphyr::from_str::<Value>(&content) else {
+                        continue;
+                    };
+                    for operation in operations(&testfile) {
+                        write_seed(operation_corpus, operation)?;
+                        operation_count += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    println!("Seeded {operation_count} operations and {exo_count} exo files");
+
+    Ok(())
+}
+
+fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(self::files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The values of all `operation` keys (a testfile is either a single stage or a list of stages)
+fn operations(value: &Value) -> Vec<&str> {
+    match value {
+        Value::Array(stages) => stages.iter().flat_map(operations).collect(),
+        Value::Object(stage) => stage
+            .get("operation")
+            .and_then(Value::as_str)
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Write a seed named after its hash (so that seeding again doesn't duplicate entries)
+fn write_seed(corpus: &Path, content: &str) -> io::Result<()> {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    fs::write(corpus.join(format!("{:016x}", hasher.finish())), content)
+}
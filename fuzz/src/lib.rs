@@ -0,0 +1,94 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Shared setup for the fuzz targets.
+
+use std::sync::{Arc, OnceLock};
+
+use codemap::CodeMap;
+use core_model_builder::plugin::BuildMode;
+use core_plugin_interface::interface::{SubsystemBuilder, SubsystemLoader};
+use core_plugin_shared::profile::SchemaProfile;
+use core_resolver::introspection::definition::schema::Schema;
+use exo_env::MapEnvironment;
+use exo_sql::{DatabaseClientManager, TransactionMode};
+use postgres_resolver::PostgresSubsystemLoader;
+
+const MODEL: &str = include_str!("../models/index.exo");
+
+/// The schema to validate operations against (built once per fuzzing process)
+pub fn schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+
+    SCHEMA.get_or_init(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(create_schema())
+    })
+}
+
+async fn create_schema() -> Schema {
+    let system = builder::build_system_from_str(
+        MODEL,
+        "index.exo".to_string(),
+        subsystem_builders(),
+        BuildMode::Build,
+    )
+    .await
+    .expect("The fuzzing model must build");
+
+    let subsystem = system
+        .subsystems
+        .into_iter()
+        .find(|subsystem| subsystem.id == "postgres")
+        .unwrap();
+
+    // The client connects lazily, and validation never gets that far
+    let client = DatabaseClientManager::from_url(
+        "postgres://localhost/exo_fuzz",
+        false,
+        None,
+        TransactionMode::ReadWrite,
+    )
+    .await
+    .unwrap();
+
+    let resolver = PostgresSubsystemLoader {
+        existing_client: Some(client),
+    }
+    .init(subsystem, Arc::new(MapEnvironment::default()))
+    .await
+    .unwrap();
+
+    Schema::new_from_resolvers(
+        &[resolver.graphql.unwrap()],
+        &SchemaProfile::all(),
+        Arc::new(None),
+    )
+}
+
+/// Parse and typecheck an exo source. Errors are expected (most inputs are invalid); only panics
+/// are of interest.
+pub fn typecheck(source: &str) {
+    let mut codemap = CodeMap::new();
+
+    if let Ok(ast_system) = builder::parser::parse_str(source, &mut codemap, "index.exo") {
+        let subsystem_builders = builder::load_subsystem_builders(subsystem_builders()).unwrap();
+        let _ = builder::typechecker::build(&subsystem_builders, ast_system);
+    }
+}
+
+fn subsystem_builders() -> Vec<Box<dyn SubsystemBuilder + Send + Sync>> {
+    vec![Box::new(
+        postgres_builder::PostgresSubsystemBuilder::default(),
+    )]
+}
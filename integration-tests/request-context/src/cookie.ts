@@ -8,3 +8,9 @@ export async function getCookie(exograph: Exograph): Promise<boolean> {
 
 	return true
 }
+
+export async function clearCookie(exograph: Exograph): Promise<boolean> {
+	exograph.clearCookie("session_id");
+
+	return true
+}
@@ -0,0 +1,35 @@
+import type { Exograph, ExographError } from '../generated/exograph.d.ts';
+
+interface Item {
+	id: number;
+	name: string;
+}
+
+export function base(): number {
+	return 100;
+}
+
+export async function* items(count: number): AsyncGenerator<Item> {
+	for (let id = 1; id <= count; id++) {
+		yield { id, name: `item-${id}` };
+	}
+}
+
+// Exograph APIs may be used until the first item is produced
+export async function* numbered(count: number, exograph: Exograph): AsyncGenerator<number> {
+	const result = await exograph.executeQuery(`query { base }`);
+	for (let i = 1; i <= count; i++) {
+		yield result.base + i;
+	}
+}
+
+export async function* failingItems(count: number): AsyncGenerator<Item> {
+	yield* items(count);
+	throw new ExographError(`Failed after ${count} items`);
+}
+
+export async function* crashingItems(count: number): AsyncGenerator<Item> {
+	yield* items(count);
+	const missing: any = undefined;
+	yield missing.id;
+}
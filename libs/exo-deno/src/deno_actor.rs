@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use deno_core::Extension;
+use deno_core::{Extension, v8};
 use exo_env::Environment;
 use futures::pin_mut;
 use serde_json::Value;
@@ -18,12 +18,14 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::Duration,
 };
 use tokio::sync::{
     Mutex,
     mpsc::{Receiver, Sender},
     oneshot,
 };
+use tokio::time::Instant;
 use tracing::instrument;
 
 use crate::deno_module::{Arg, DenoModule, UserCode};
@@ -33,6 +35,14 @@ struct DenoCall<C, R> {
     method_name: String,
     arguments: Vec<Arg>,
     call_context: C,
+    /// The sender to communicate the items of a streaming function (see
+    /// `DenoModule::execute_streaming_function`). `None` for regular calls.
+    item_sender: Option<Sender<Value>>,
+    /// How long the call may run (from the start of its execution), if limited
+    timeout: Option<Duration>,
+    /// The sender to communicate when the execution starts (the timeout doesn't include the time
+    /// waiting for the module to initialize)
+    started_sender: oneshot::Sender<Instant>,
     /// The sender to communicate the final result
     final_response_sender: oneshot::Sender<Result<(Value, Option<R>), DenoError>>,
 }
@@ -57,6 +67,13 @@ struct DenoCall<C, R> {
 ///       it to the `DenoModule` instance.
 ///     - Sends the result to the sender of the request.
 ///
+/// # Timeouts:
+/// A call may have a timeout. Once it expires, the caller interrupts the JavaScript execution
+/// through the isolate's handle (which works even if the thread is stuck in a synchronous loop) and
+/// the thread stops waiting for the call (which covers a call waiting for a promise that never
+/// settles). Either way, the actor is marked terminated, its thread exits, and the pool replaces it
+/// (since an interrupted isolate may be left in an inconsistent state).
+///
 /// # Message flow:
 /// - A DenoActor may be asked to execute a JavaScript function by executing the `execute` method
 ///   passing it the function name, the arguments, an opaque "call context" as well as the sender
@@ -81,6 +98,10 @@ pub(crate) struct DenoActor<C, M, R> {
     // Sender to ask the actor to execute a JS/TS call. The actor will poll for messages on the corresponding receiver.
     call_sender: Sender<DenoCall<C, R>>,
     busy: Arc<std::sync::atomic::AtomicBool>,
+    // Set once a call times out (the actor must not be used after that)
+    terminated: Arc<AtomicBool>,
+    // Handle to interrupt the JavaScript execution (set once the module is initialized)
+    isolate_handle: Arc<std::sync::Mutex<Option<v8::IsolateHandle>>>,
 }
 
 impl<C, M, R> DenoActor<C, M, R>
@@ -104,8 +125,12 @@ where
         // we will receive DenoCall messages through this channel from call_method
         let (deno_call_sender, mut deno_call_receiver) = tokio::sync::mpsc::channel(1);
         let busy = Arc::new(AtomicBool::new(false));
+        let terminated = Arc::new(AtomicBool::new(false));
+        let isolate_handle = Arc::new(std::sync::Mutex::new(None));
 
         let busy_clone = busy.clone();
+        let terminated_clone = terminated.clone();
+        let isolate_handle_clone = isolate_handle.clone();
 
         // start the DenoModule thread
         std::thread::spawn(move || {
@@ -147,6 +172,10 @@ where
                     .put(callback_sender)
                     .unwrap_or_else(|_| panic!("Could not store request sender in DenoModule"));
 
+                *isolate_handle_clone
+                    .lock()
+                    .expect("Isolate handle lock poisoned") = Some(deno_module.isolate_handle());
+
                 // start a receive loop
                 loop {
                     // yield and wait for a DenoCall message
@@ -154,19 +183,57 @@ where
                         method_name,
                         arguments,
                         call_context,
+                        item_sender,
+                        timeout,
+                        started_sender,
                         final_response_sender,
                     } = match deno_call_receiver.recv().await {
                         Some(call_info) => call_info,
                         None => break,
                     };
 
+                    if terminated_clone.load(Ordering::Relaxed) {
+                        // The isolate may have been interrupted, so don't run any more calls on it
+                        let _ = final_response_sender.send(Err(DenoInternalError::Channel(
+                            "DenoActor was terminated after a timeout".to_string(),
+                        )
+                        .into()));
+                        break;
+                    }
+
                     busy_clone.store(true, Ordering::Relaxed); // mark DenoActor as busy
                     let _: Option<R> = deno_module.take().expect("take() should not have failed"); // clear any existing R from GothamStorage
 
                     process_call_context(&mut deno_module, call_context);
 
                     // execute function
-                    let result = deno_module.execute_function(&method_name, arguments).await;
+                    let execution = async {
+                        match &item_sender {
+                            Some(item_sender) => deno_module
+                                .execute_streaming_function(&method_name, arguments, item_sender)
+                                .await
+                                .map(|()| Value::Null),
+                            None => deno_module.execute_function(&method_name, arguments).await,
+                        }
+                    };
+
+                    let started = Instant::now();
+                    let _ = started_sender.send(started);
+
+                    let result = match timeout {
+                        Some(timeout) => {
+                            tokio::select! {
+                                result = execution => result,
+                                _ = tokio::time::sleep_until(started + timeout) => {
+                                    // The function is waiting (such as for a promise that never
+                                    // settles), so stop waiting for it
+                                    terminated_clone.store(true, Ordering::Relaxed);
+                                    Err(DenoError::Timeout(timeout))
+                                }
+                            }
+                        }
+                        None => execution.await,
+                    };
 
                     // take R from GothamStorage
                     let r: Option<R> = deno_module.take().expect("take() should not have failed");
@@ -182,6 +249,11 @@ where
                     }
 
                     busy_clone.store(false, Ordering::Relaxed); // unmark DenoActor as busy
+
+                    if terminated_clone.load(Ordering::Relaxed) {
+                        // Drop the (possibly interrupted) module along with this thread
+                        break;
+                    }
                 }
             });
         });
@@ -190,6 +262,8 @@ where
             callback_receiver: Arc::new(Mutex::new(callback_receiver)),
             call_sender: deno_call_sender,
             busy,
+            terminated,
+            isolate_handle,
         })
     }
 
@@ -197,6 +271,25 @@ where
         self.busy.load(Ordering::Relaxed)
     }
 
+    /// Has a call on this actor timed out (after which the actor may not be used)?
+    pub fn is_terminated(&self) -> bool {
+        self.terminated.load(Ordering::Relaxed)
+    }
+
+    /// Interrupt the JavaScript execution (if any) and mark the actor terminated
+    fn terminate(&self) {
+        self.terminated.store(true, Ordering::Relaxed);
+
+        if let Some(isolate_handle) = self
+            .isolate_handle
+            .lock()
+            .expect("Isolate handle lock poisoned")
+            .as_ref()
+        {
+            isolate_handle.terminate_execution();
+        }
+    }
+
     /// Call a deno method
     ///
     /// During the invocation there may be callbacks (such as `execute` a query or `proceed` form an interceptor). Those calls
@@ -207,6 +300,7 @@ where
     /// * `arguments` - the arguments to pass to the method
     /// * `call_context` - opaque call context
     /// * `callback_sender` - the sender to send request for intermediate steps (such as proceed() when performing an around interceptor)
+    /// * `timeout` - how long the call may run (including the time to process callbacks), if limited
     ///
     #[instrument(
         name = "deno_actor::call_method"
@@ -218,14 +312,64 @@ where
         arguments: Vec<Arg>,
         call_context: C,
         callback_sender: tokio::sync::mpsc::Sender<M>,
+        timeout: Option<Duration>,
+    ) -> Result<(Value, Option<R>), DenoError> {
+        self.execute_call(
+            method_name,
+            arguments,
+            call_context,
+            callback_sender,
+            None,
+            timeout,
+        )
+        .await
+    }
+
+    /// Call a deno method that returns an (async) iterable, sending each item to `item_sender`.
+    ///
+    /// Otherwise, behaves like `execute` (the final result is `null` once the iteration completes).
+    pub async fn execute_streaming(
+        &self,
+        method_name: String,
+        arguments: Vec<Arg>,
+        call_context: C,
+        callback_sender: tokio::sync::mpsc::Sender<M>,
+        item_sender: Sender<Value>,
+        timeout: Option<Duration>,
+    ) -> Result<(Value, Option<R>), DenoError> {
+        self.execute_call(
+            method_name,
+            arguments,
+            call_context,
+            callback_sender,
+            Some(item_sender),
+            timeout,
+        )
+        .await
+    }
+
+    async fn execute_call(
+        &self,
+        method_name: String,
+        arguments: Vec<Arg>,
+        call_context: C,
+        callback_sender: tokio::sync::mpsc::Sender<M>,
+        item_sender: Option<Sender<Value>>,
+        timeout: Option<Duration>,
     ) -> Result<(Value, Option<R>), DenoError> {
         // Channel to communicate the final result
         let (final_response_sender, final_result_receiver) = oneshot::channel();
 
+        // Channel to communicate when the execution starts
+        let (started_sender, started_receiver) = oneshot::channel();
+
         let deno_call = DenoCall {
             method_name,
             arguments,
             call_context,
+            item_sender,
+            timeout,
+            started_sender,
             final_response_sender,
         };
         // send it to the DenoModule thread
@@ -236,6 +380,13 @@ where
         })?;
 
         pin_mut!(final_result_receiver);
+        pin_mut!(started_receiver);
+
+        // Armed once the execution starts (if the call has a timeout)
+        let on_timeout = tokio::time::sleep(Duration::ZERO);
+        pin_mut!(on_timeout);
+        let mut waiting_for_start = true;
+        let mut timeout_armed = false;
 
         // receive loop
         loop {
@@ -256,6 +407,20 @@ where
                     // final result is received, break the loop with the result
                     break final_result.map_err(|err| DenoInternalError::Channel(format!("Could not receive result from DenoActor thread ({err})")))?;
                 }
+
+                started = &mut started_receiver, if waiting_for_start => {
+                    waiting_for_start = false;
+                    if let (Ok(started), Some(timeout)) = (started, timeout) {
+                        on_timeout.as_mut().reset(started + timeout);
+                        timeout_armed = true;
+                    }
+                }
+
+                _ = &mut on_timeout, if timeout_armed => {
+                    // interrupt the function (which may be stuck in a synchronous loop)
+                    self.terminate();
+                    break Err(DenoError::Timeout(timeout.unwrap_or_default()));
+                }
             };
         }
     }
@@ -268,6 +433,8 @@ impl<C, M, R> Clone for DenoActor<C, M, R> {
             callback_receiver: self.callback_receiver.clone(),
             call_sender: self.call_sender.clone(),
             busy: self.busy.clone(),
+            terminated: self.terminated.clone(),
+            isolate_handle: self.isolate_handle.clone(),
         }
     }
 }
@@ -303,6 +470,7 @@ mod tests {
                 vec![Arg::Serde(2_i32.into()), Arg::Serde(3_i32.into())],
                 (),
                 to_user_sender,
+                None,
             )
             .await
             .unwrap();
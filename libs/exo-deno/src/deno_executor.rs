@@ -7,14 +7,23 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use futures::pin_mut;
+use futures::{Stream, pin_mut};
 
-use crate::error::DenoError;
+use crate::error::{DenoError, DenoInternalError};
 
 use super::{deno_actor::DenoActor, deno_module::Arg};
 use async_trait::async_trait;
 use serde_json::Value;
 use std::fmt::Debug;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::{sync::mpsc::Receiver, task::JoinHandle};
+
+/// The items produced by a streaming function (see `DenoExecutor::execute_streaming`)
+pub type DenoItemStream = Pin<Box<dyn Stream<Item = Result<Value, DenoError>> + Send>>;
+
+/// The number of items a streaming function may produce ahead of the consumer
+const STREAM_BUFFER_SIZE: usize = 16;
 
 /// `DenoExecutor` provides a way to execute a method.
 ///
@@ -46,6 +55,7 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Debug + Sync
         arguments: Vec<Arg>,
         call_context: C,
         callback_processor: impl CallbackProcessor<M>,
+        timeout: Option<Duration>,
     ) -> Result<(Value, Option<R>), DenoError> {
         // set up a channel for Deno to talk to use through
         let (to_user_sender, mut to_user_receiver) = tokio::sync::mpsc::channel(1);
@@ -56,6 +66,7 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Debug + Sync
             arguments,
             call_context,
             to_user_sender,
+            timeout,
         );
 
         pin_mut!(on_function_result); // needs to be pinned to reuse it
@@ -78,4 +89,113 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Debug + Sync
             }
         }
     }
+
+    /// Execute a function that returns an (async) iterable, and obtain a stream of its items.
+    ///
+    /// Callbacks are processed using `callback_processor` until the function produces its first
+    /// item (or completes). The remaining items are produced after this method returns (and
+    /// therefore, after `callback_processor` may no longer be valid), so any callback made after
+    /// that point fails.
+    ///
+    /// The timeout (if any) covers the whole iteration, not only the time to the first item.
+    pub(super) async fn execute_streaming(
+        &self,
+        method_name: &str,
+        arguments: Vec<Arg>,
+        call_context: C,
+        callback_processor: impl CallbackProcessor<M>,
+        timeout: Option<Duration>,
+    ) -> Result<DenoItemStream, DenoError> {
+        let (to_user_sender, mut to_user_receiver) = tokio::sync::mpsc::channel(1);
+        let (item_sender, mut item_receiver) = tokio::sync::mpsc::channel(STREAM_BUFFER_SIZE);
+
+        // The call must outlive this method, so run it in its own task
+        let actor = self.actor.clone();
+        let method_name = method_name.to_string();
+        let on_function_result = tokio::spawn(async move {
+            actor
+                .execute_streaming(
+                    method_name,
+                    arguments,
+                    call_context,
+                    to_user_sender,
+                    item_sender,
+                    timeout,
+                )
+                .await
+        });
+
+        let mut callbacks_open = true;
+        let first_item = loop {
+            tokio::select! {
+                msg = to_user_receiver.recv(), if callbacks_open => {
+                    match msg {
+                        Some(msg) => callback_processor.process_callback(msg).await,
+                        None => callbacks_open = false,
+                    }
+                }
+
+                item = item_receiver.recv() => break item,
+            }
+        };
+
+        let Some(first_item) = first_item else {
+            // The function completed (or failed) without producing any items
+            return match on_function_result.await {
+                Ok(result) => result.map(|_| Box::pin(futures::stream::empty()) as DenoItemStream),
+                Err(err) => Err(DenoInternalError::Channel(format!(
+                    "Streaming function task failed ({err})"
+                ))
+                .into()),
+            };
+        };
+
+        // Fail any further callbacks by dropping them (which drops their response senders)
+        tokio::spawn(async move {
+            while let Some(msg) = to_user_receiver.recv().await {
+                tracing::warn!(
+                    "Callbacks are not supported once a streaming function produces items"
+                );
+                drop(msg);
+            }
+        });
+
+        struct StreamState<R> {
+            first_item: Option<Value>,
+            item_receiver: Receiver<Value>,
+            on_function_result: Option<JoinHandle<Result<(Value, Option<R>), DenoError>>>,
+        }
+
+        let stream = futures::stream::unfold(
+            StreamState {
+                first_item: Some(first_item),
+                item_receiver,
+                on_function_result: Some(on_function_result),
+            },
+            |mut state| async move {
+                if let Some(item) = state.first_item.take() {
+                    return Some((Ok(item), state));
+                }
+
+                if let Some(item) = state.item_receiver.recv().await {
+                    return Some((Ok(item), state));
+                }
+
+                // All items have been produced; report the error that ended the iteration (if any)
+                match state.on_function_result.take()?.await {
+                    Ok(Ok(_)) => None,
+                    Ok(Err(err)) => Some((Err(err), state)),
+                    Err(err) => Some((
+                        Err(DenoInternalError::Channel(format!(
+                            "Streaming function task failed ({err})"
+                        ))
+                        .into()),
+                        state,
+                    )),
+                }
+            },
+        );
+
+        Ok(Box::pin(stream))
+    }
 }
@@ -7,10 +7,11 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use std::{collections::HashMap, marker::PhantomData, sync::Arc, time::Duration};
 
 use deno_core::{Extension, ModuleType, url::Url};
 use exo_env::Environment;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::Mutex;
@@ -19,7 +20,7 @@ use crate::{Arg, error::DenoError};
 
 use super::{
     deno_actor::DenoActor,
-    deno_executor::{CallbackProcessor, DenoExecutor},
+    deno_executor::{CallbackProcessor, DenoExecutor, DenoItemStream},
     deno_module::{DenoModule, UserCode},
 };
 
@@ -27,6 +28,7 @@ use std::fmt::Debug;
 
 type DenoActorPoolMap<C, M, R> = HashMap<String, DenoActorPool<C, M, R>>;
 type DenoActorPool<C, M, R> = Vec<DenoActor<C, M, R>>;
+type TimeoutCounts = Arc<std::sync::Mutex<HashMap<String, usize>>>;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ResolvedModule {
@@ -40,6 +42,16 @@ pub struct DenoScriptDefn {
     pub modules: HashMap<Url, ResolvedModule>,
 }
 
+/// The actors allocated for a script (see [DenoExecutorPool::status])
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DenoActorPoolStatus {
+    pub script_path: String,
+    pub actors: usize,
+    pub busy: usize,
+    /// The number of calls that timed out (each of which replaced its actor)
+    pub timeouts: usize,
+}
+
 pub struct DenoExecutorConfig<C> {
     shims: Vec<(&'static str, &'static [&'static str])>,
     additional_code: Vec<&'static str>,
@@ -89,6 +101,7 @@ impl<C> DenoExecutorConfig<C> {
 pub struct DenoExecutorPool<C, M, R> {
     config: DenoExecutorConfig<C>,
     actor_pool_map: Arc<Mutex<DenoActorPoolMap<C, M, R>>>,
+    timeout_counts: TimeoutCounts,
     return_type: PhantomData<R>,
 }
 
@@ -117,11 +130,14 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Sync + Send
         Self {
             config,
             actor_pool_map: Arc::new(Mutex::new(DenoActorPoolMap::default())),
+            timeout_counts: TimeoutCounts::default(),
             return_type: PhantomData,
         }
     }
 
-    // Execute a method and obtain its result
+    // Execute a method and obtain its result. If the call doesn't complete within `timeout`, its
+    // execution is interrupted and it fails with `DenoError::Timeout`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute(
         &self,
         script_path: &str,
@@ -130,6 +146,7 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Sync + Send
         arguments: Vec<Arg>,
         call_context: C,
         callback_processor: impl CallbackProcessor<M>,
+        timeout: Option<Duration>,
     ) -> Result<Value, DenoError> {
         let (result, _) = self
             .execute_and_get_r(
@@ -139,12 +156,14 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Sync + Send
                 arguments,
                 call_context,
                 callback_processor,
+                timeout,
             )
             .await?;
         Ok(result)
     }
 
     // execute(...), but also return R from Deno's GothamStorage
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_and_get_r(
         &self,
         script_path: &str,
@@ -153,11 +172,90 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Sync + Send
         arguments: Vec<Arg>,
         call_context: C,
         callback_processor: impl CallbackProcessor<M>,
+        timeout: Option<Duration>,
     ) -> Result<(Value, Option<R>), DenoError> {
         let executor = self.get_executor(script_path, script).await?;
-        executor
-            .execute(method_name, arguments, call_context, callback_processor)
-            .await
+        let result = executor
+            .execute(
+                method_name,
+                arguments,
+                call_context,
+                callback_processor,
+                timeout,
+            )
+            .await;
+
+        if let Err(DenoError::Timeout(timeout)) = &result {
+            record_timeout(&self.timeout_counts, script_path, method_name, *timeout);
+        }
+        result
+    }
+
+    // Execute a method that returns an (async) iterable and obtain a stream of its items
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_streaming(
+        &self,
+        script_path: &str,
+        script: DenoScriptDefn,
+        method_name: &str,
+        arguments: Vec<Arg>,
+        call_context: C,
+        callback_processor: impl CallbackProcessor<M>,
+        timeout: Option<Duration>,
+    ) -> Result<DenoItemStream, DenoError> {
+        let executor = self.get_executor(script_path, script).await?;
+        let items = executor
+            .execute_streaming(
+                method_name,
+                arguments,
+                call_context,
+                callback_processor,
+                timeout,
+            )
+            .await;
+
+        let items = match items {
+            Ok(items) => items,
+            Err(DenoError::Timeout(timeout)) => {
+                record_timeout(&self.timeout_counts, script_path, method_name, timeout);
+                return Err(DenoError::Timeout(timeout));
+            }
+            Err(err) => return Err(err),
+        };
+
+        // The call may also time out after producing some items
+        let timeout_counts = self.timeout_counts.clone();
+        let script_path = script_path.to_string();
+        let method_name = method_name.to_string();
+        Ok(Box::pin(items.inspect(move |item| {
+            if let Err(DenoError::Timeout(timeout)) = item {
+                record_timeout(&timeout_counts, &script_path, &method_name, *timeout);
+            }
+        })))
+    }
+
+    /// The actors allocated so far for each script (actors are allocated lazily, so scripts that
+    /// haven't been called yet don't appear)
+    pub async fn status(&self) -> Vec<DenoActorPoolStatus> {
+        let actor_pool_map = self.actor_pool_map.lock().await;
+
+        let mut status: Vec<_> = actor_pool_map
+            .iter()
+            .map(|(script_path, actor_pool)| DenoActorPoolStatus {
+                script_path: script_path.clone(),
+                actors: actor_pool.len(),
+                busy: actor_pool.iter().filter(|actor| actor.is_busy()).count(),
+                timeouts: self
+                    .timeout_counts
+                    .lock()
+                    .expect("Timeout counts lock poisoned")
+                    .get(script_path)
+                    .copied()
+                    .unwrap_or(0),
+            })
+            .collect();
+        status.sort_by(|a, b| a.script_path.cmp(&b.script_path));
+        status
     }
 
     // TODO: look at passing a fn pointer struct as an argument
@@ -171,6 +269,9 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Sync + Send
             let mut actor_pool_map = self.actor_pool_map.lock().await;
             let actor_pool = actor_pool_map.entry(script_path.to_string()).or_default();
 
+            // Actors whose call timed out are replaced (their isolate may have been interrupted)
+            actor_pool.retain(|actor| !actor.is_terminated());
+
             let free_actor = actor_pool.iter().find(|actor| !actor.is_busy());
 
             if let Some(actor) = free_actor {
@@ -208,6 +309,29 @@ impl<C: Sync + Send + Debug + 'static, M: Sync + Send + 'static, R: Sync + Send
     }
 }
 
+/// Count a timed-out call and report it (with a `monotonic_counter.` field for an OpenTelemetry
+/// metrics layer)
+fn record_timeout(
+    timeout_counts: &TimeoutCounts,
+    script_path: &str,
+    method_name: &str,
+    timeout: Duration,
+) {
+    *timeout_counts
+        .lock()
+        .expect("Timeout counts lock poisoned")
+        .entry(script_path.to_string())
+        .or_default() += 1;
+
+    tracing::warn!(
+        monotonic_counter.exograph_deno_timeouts = 1u64,
+        script_path,
+        method_name,
+        timeout_ms = timeout.as_millis() as u64,
+        "Deno execution timed out"
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,10 +376,17 @@ mod tests {
                 vec![Arg::Serde(2.into()), Arg::Serde(3.into())],
                 (),
                 (),
+                None,
             )
             .await;
 
         assert_eq!(res.unwrap(), 10);
+
+        // The actor may still be marked busy briefly after sending the result, so don't check that
+        let status = executor_pool.status().await;
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].script_path, module_path);
+        assert_eq!(status[0].actors, 1);
     }
 
     #[tokio::test]
@@ -303,6 +434,7 @@ mod tests {
                 arguments,
                 (),
                 (),
+                None,
             )
             .await
         }
@@ -330,4 +462,67 @@ mod tests {
 
         assert_eq!(result, total_futures);
     }
+
+    #[tokio::test]
+    async fn test_timeout_replaces_actor() {
+        let module_path = "file://test_js/direct.js";
+        let module_script = include_str!("test_js/direct.js").to_string();
+
+        let executor_pool = DenoExecutorPool::<(), (), ()>::new(
+            vec![],
+            vec![],
+            None,
+            Vec::new,
+            |_, _| {},
+            Arc::new(MapEnvironment::default()),
+        );
+
+        let script = || DenoScriptDefn {
+            modules: vec![(
+                ModuleSpecifier::parse(module_path).unwrap(),
+                ResolvedModule::Module(
+                    module_script.clone(),
+                    ModuleType::JavaScript,
+                    ModuleSpecifier::parse(module_path).unwrap(),
+                    false,
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        // Both a synchronous loop and a promise that never settles time out
+        for method_name in ["spin", "wait"] {
+            let res = executor_pool
+                .execute(
+                    module_path,
+                    script(),
+                    method_name,
+                    vec![],
+                    (),
+                    (),
+                    Some(Duration::from_millis(100)),
+                )
+                .await;
+            assert!(matches!(res, Err(DenoError::Timeout(_))), "{method_name}");
+        }
+
+        // The next call gets a fresh actor
+        let res = executor_pool
+            .execute(
+                module_path,
+                script(),
+                "addAndDouble",
+                vec![Arg::Serde(2.into()), Arg::Serde(3.into())],
+                (),
+                (),
+                Some(Duration::from_secs(10)),
+            )
+            .await;
+        assert_eq!(res.unwrap(), 10);
+
+        let status = executor_pool.status().await;
+        assert_eq!(status[0].actors, 1);
+        assert_eq!(status[0].timeouts, 2);
+    }
 }
@@ -34,7 +34,10 @@ use std::cell::RefCell;
 use std::path::PathBuf;
 use tracing::instrument;
 
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use tokio::sync::mpsc::Sender;
 
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -51,6 +54,39 @@ use crate::error::DenoInternalError;
 use super::embedded_module_loader::EmbeddedModuleLoader;
 use deno_error::JsErrorBox;
 
+const ITERATOR_HELPER: &str = "__exoIterator";
+const ITERATOR_NEXT_HELPER: &str = "__exoIteratorNext";
+const ITERATOR_RETURN_HELPER: &str = "__exoIteratorReturn";
+
+/// Helpers to drive (async) iterables returned by streaming functions (see
+/// `DenoModule::execute_streaming_function`)
+const ITERATION_HELPERS: &str = r#"
+globalThis.__exoIterator = (value) => {
+    if (value != null && typeof value[Symbol.asyncIterator] === "function") {
+        return value[Symbol.asyncIterator]();
+    }
+    if (value != null && typeof value !== "string" && typeof value[Symbol.iterator] === "function") {
+        return value[Symbol.iterator]();
+    }
+    throw new TypeError("A streaming function must return an iterable or an async iterable");
+};
+globalThis.__exoIteratorNext = async (iterator) => {
+    const { done, value } = await iterator.next();
+    return { done: !!done, value: done ? null : (value ?? null) };
+};
+globalThis.__exoIteratorReturn = async (iterator) => {
+    if (typeof iterator.return === "function") {
+        await iterator.return();
+    }
+};
+"#;
+
+#[derive(Deserialize)]
+struct IteratorResult {
+    done: bool,
+    value: Value,
+}
+
 /// Minimal implementation of NodeRequireLoader
 /// Since we use the bundler approach, we don't need to load any files from the file system.
 struct BasicNodeRequireLoader;
@@ -255,6 +291,8 @@ impl DenoModule {
 
         worker.execute_main_module(&main_module).await?;
 
+        worker.execute_script("", deno_core::FastString::from_static(ITERATION_HELPERS))?;
+
         additional_code.iter().for_each(|code| {
             worker
                 .execute_script("", deno_core::FastString::from_static(code))
@@ -322,6 +360,52 @@ impl DenoModule {
         function_name: &str,
         args: Vec<Arg>,
     ) -> Result<Value, DenoError> {
+        let value = self.call_function(function_name, args).await?;
+        self.deserialize(value)
+    }
+
+    /// Execute a function that returns an iterable or an async iterable (such as an async
+    /// generator), sending each item to `item_sender` as soon as it is produced.
+    ///
+    /// If the receiver is dropped (for example, because the client disconnected), iteration stops
+    /// and the iterator is closed (so that `finally` blocks in generators run).
+    #[instrument(
+        name = "deno_module::execute_streaming_function"
+        level = "debug"
+        skip_all
+        )]
+    pub async fn execute_streaming_function(
+        &mut self,
+        function_name: &str,
+        args: Vec<Arg>,
+        item_sender: &Sender<Value>,
+    ) -> Result<(), DenoError> {
+        let iterable = self.call_function(function_name, args).await?;
+        let iterator = self.call_helper(ITERATOR_HELPER, vec![iterable]).await?;
+
+        loop {
+            let next = self
+                .call_helper(ITERATOR_NEXT_HELPER, vec![iterator.clone()])
+                .await?;
+            let IteratorResult { done, value } = self.deserialize(next)?;
+
+            if done {
+                return Ok(());
+            }
+
+            if item_sender.send(value).await.is_err() {
+                self.call_helper(ITERATOR_RETURN_HELPER, vec![iterator])
+                    .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    async fn call_function(
+        &mut self,
+        function_name: &str,
+        args: Vec<Arg>,
+    ) -> Result<v8::Global<v8::Value>, DenoError> {
         let worker = &mut self.worker;
         let mut runtime = &mut worker.js_runtime;
 
@@ -342,25 +426,54 @@ impl DenoModule {
                 .collect()
         };
 
-        let global = {
+        let args = {
             deno_core::scope!(scope, &mut runtime);
-            v8::tc_scope!(scope, scope);
 
-            let args: Vec<_> = args
-                .into_iter()
+            args.into_iter()
                 .map(|v| match v {
                     Arg::Serde(v) => {
-                        Ok(serde_v8::to_v8(scope, v).map_err(DenoInternalError::Serde)?)
+                        let local = serde_v8::to_v8(scope, v).map_err(DenoInternalError::Serde)?;
+                        Ok(v8::Global::new(scope, local))
                     }
                     Arg::Shim(name) => Ok(shim_objects
                         .get(&name)
                         .ok_or(DenoDiagnosticError::MissingShim(name))?
-                        .open(scope)
-                        .to_object(scope)
-                        .unwrap()
-                        .into()),
+                        .clone()),
                 })
-                .collect::<Result<Vec<_>, DenoError>>()?;
+                .collect::<Result<Vec<_>, DenoError>>()?
+        };
+
+        self.call(func_value, args, function_name).await
+    }
+
+    /// Call one of the helpers defined by [`ITERATION_HELPERS`]
+    async fn call_helper(
+        &mut self,
+        helper_name: &'static str,
+        args: Vec<v8::Global<v8::Value>>,
+    ) -> Result<v8::Global<v8::Value>, DenoError> {
+        let func_value = self
+            .worker
+            .js_runtime
+            .execute_script("", deno_core::FastString::from_static(helper_name))?;
+
+        self.call(func_value, args, helper_name).await
+    }
+
+    /// Call a function and resolve its result (if the function returns a promise)
+    async fn call(
+        &mut self,
+        func_value: v8::Global<v8::Value>,
+        args: Vec<v8::Global<v8::Value>>,
+        function_name: &str,
+    ) -> Result<v8::Global<v8::Value>, DenoError> {
+        let mut runtime = &mut self.worker.js_runtime;
+
+        let global = {
+            deno_core::scope!(scope, &mut runtime);
+            v8::tc_scope!(scope, scope);
+
+            let args: Vec<_> = args.iter().map(|arg| v8::Local::new(scope, arg)).collect();
 
             let func_obj = func_value.open(scope).to_object(scope).ok_or_else(|| {
                 DenoDiagnosticError::MissingFunction(
@@ -397,27 +510,36 @@ impl DenoModule {
             v8::Global::new(scope, local)
         };
 
-        {
-            #[allow(deprecated)]
-            // Deno's code also uses the deprecated function in their tests. We will reconsider this when their code remove this function.
-            // See: https://github.com/denoland/deno_core/blob/main/core/benches/ops/async.rs
-            let value = runtime
-                .resolve_value(global)
-                .await
-                .map_err(|err| match *err.0 {
-                    deno_core::error::CoreErrorKind::Js(js_error) => {
-                        error!(%js_error, "Exception executing function");
-
-                        Self::process_js_error(self.explicit_error_class_name, *js_error)
-                    }
-                    _ => DenoError::AnyError(err.into()),
-                })?;
+        #[allow(deprecated)]
+        // Deno's code also uses the deprecated function in their tests. We will reconsider this when their code remove this function.
+        // See: https://github.com/denoland/deno_core/blob/main/core/benches/ops/async.rs
+        runtime
+            .resolve_value(global)
+            .await
+            .map_err(|err| match *err.0 {
+                deno_core::error::CoreErrorKind::Js(js_error) => {
+                    error!(%js_error, "Exception executing function");
 
-            deno_core::scope!(scope, &mut runtime);
-            let res = v8::Local::new(scope, value);
-            let res: Value = serde_v8::from_v8(scope, res).map_err(DenoInternalError::Serde)?;
-            Ok(res)
-        }
+                    Self::process_js_error(self.explicit_error_class_name, *js_error)
+                }
+                _ => DenoError::AnyError(err.into()),
+            })
+    }
+
+    fn deserialize<T: DeserializeOwned>(
+        &mut self,
+        value: v8::Global<v8::Value>,
+    ) -> Result<T, DenoError> {
+        let mut runtime = &mut self.worker.js_runtime;
+        deno_core::scope!(scope, &mut runtime);
+        let res = v8::Local::new(scope, value);
+        Ok(serde_v8::from_v8(scope, res).map_err(DenoInternalError::Serde)?)
+    }
+
+    /// A handle to interrupt the module's JavaScript execution from another thread (see
+    /// [`v8::IsolateHandle::terminate_execution`])
+    pub fn isolate_handle(&mut self) -> v8::IsolateHandle {
+        self.worker.js_runtime.v8_isolate().thread_safe_handle()
     }
 
     /// Put a single instance of a type into Deno's op_state
@@ -746,4 +868,127 @@ mod tests {
             Value::String("Register Async Op: param".into())
         );
     }
+
+    async fn streaming_module() -> DenoModule {
+        DenoModule::new(
+            UserCode::LoadFromFs(
+                Path::new("src")
+                    .join("test_js")
+                    .join("streaming.js")
+                    .to_owned(),
+            ),
+            vec![],
+            vec![],
+            vec![],
+            Some("StreamError"),
+            None,
+            None,
+            Arc::new(MapEnvironment::default()),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Execute a streaming function, returning its result along with the items it produced
+    async fn execute_streaming(
+        deno_module: &mut DenoModule,
+        function_name: &str,
+        args: Vec<Arg>,
+    ) -> (Result<(), DenoError>, Vec<Value>) {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        let result = deno_module
+            .execute_streaming_function(function_name, args, &sender)
+            .await;
+        drop(sender);
+
+        let mut items = vec![];
+        while let Some(item) = receiver.recv().await {
+            items.push(item);
+        }
+        (result, items)
+    }
+
+    #[tokio::test]
+    async fn test_streaming_async_generator() {
+        let mut deno_module = streaming_module().await;
+
+        let (result, items) = execute_streaming(
+            &mut deno_module,
+            "countTo",
+            vec![Arg::Serde(Value::Number(3.into()))],
+        )
+        .await;
+
+        result.unwrap();
+        assert_eq!(
+            items,
+            vec![
+                json!({ "count": 1 }),
+                json!({ "count": 2 }),
+                json!({ "count": 3 })
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streaming_sync_iterable() {
+        let mut deno_module = streaming_module().await;
+
+        let (result, items) = execute_streaming(&mut deno_module, "syncArray", vec![]).await;
+
+        result.unwrap();
+        assert_eq!(items, vec![json!("a"), json!("b"), json!("c")]);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_error_after_items() {
+        let mut deno_module = streaming_module().await;
+
+        let (result, items) = execute_streaming(
+            &mut deno_module,
+            "failAfter",
+            vec![Arg::Serde(Value::Number(2.into()))],
+        )
+        .await;
+
+        // The items produced before the error are still delivered
+        assert_eq!(items, vec![json!({ "count": 1 }), json!({ "count": 2 })]);
+        assert!(
+            matches!(result, Err(DenoError::Explicit(ref message)) if message == "Failed after 2 items"),
+            "Unexpected result {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streaming_not_iterable() {
+        let mut deno_module = streaming_module().await;
+
+        // Strings are iterable in JavaScript, but streaming them character by character would
+        // surprise the user
+        let (result, items) = execute_streaming(&mut deno_module, "notIterable", vec![]).await;
+
+        assert!(items.is_empty());
+        assert!(
+            matches!(result, Err(DenoError::JsError(ref error)) if error.to_string().contains("must return an iterable")),
+            "Unexpected result {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streaming_closes_iterator_when_receiver_is_dropped() {
+        let mut deno_module = streaming_module().await;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        drop(receiver);
+        deno_module
+            .execute_streaming_function("untilClosed", vec![], &sender)
+            .await
+            .unwrap();
+
+        let closed = deno_module
+            .execute_function("wasClosed", vec![])
+            .await
+            .unwrap();
+        assert_eq!(closed, Value::Bool(true));
+    }
 }
@@ -17,6 +17,7 @@ use deno_core::futures::FutureExt;
 use deno_core::resolve_import;
 use deno_core::url::Url;
 
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -26,6 +27,8 @@ use include_dir::Dir;
 
 use crate::deno_executor_pool::ResolvedModule;
 
+const INLINE_SOURCE_MAP_PREFIX: &str = "//# sourceMappingURL=data:application/json;base64,";
+
 /// A module loader that allows loading source code from memory for the given module specifier;
 /// otherwise, loading it from an FsModuleLoader
 /// Based on <https://deno.land/x/deno@v1.15.0/cli/standalone.rs>
@@ -154,4 +157,50 @@ impl ModuleLoader for EmbeddedModuleLoader {
             deno_core::ModuleLoadResponse::Async(module_load_future.boxed_local())
         }
     }
+
+    /// Bundled scripts carry an inline source map (see `deno bundle --sourcemap=inline`), which
+    /// lets deno_core map stack traces back to the original TypeScript files.
+    fn get_source_map(&self, file_name: &str) -> Option<Cow<'_, [u8]>> {
+        let specifier = Url::parse(file_name).ok()?;
+        let borrowed_map = self.source_code_map.borrow();
+
+        let mut resolved = borrowed_map.get(&specifier);
+        while let Some(ResolvedModule::Redirect(to)) = resolved {
+            resolved = borrowed_map.get(to);
+        }
+
+        match resolved {
+            Some(ResolvedModule::Module(script, ..)) => inline_source_map(script).map(Cow::Owned),
+            _ => None,
+        }
+    }
+}
+
+fn inline_source_map(script: &str) -> Option<Vec<u8>> {
+    use base64::Engine;
+
+    let encoded = script
+        .lines()
+        .rev()
+        .find_map(|line| line.trim_end().strip_prefix(INLINE_SOURCE_MAP_PREFIX))?;
+
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_inline_source_map() {
+        let script = "function f() {}\n//# sourceMappingURL=data:application/json;base64,eyJ2ZXJzaW9uIjozfQ==\n";
+        assert_eq!(
+            inline_source_map(script),
+            Some(br#"{"version":3}"#.to_vec())
+        );
+
+        assert_eq!(inline_source_map("function f() {}\n"), None);
+    }
 }
@@ -7,6 +7,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::time::Duration;
+
 use deno_core::{
     error::{AnyError, JsError},
     v8::DataError,
@@ -27,6 +29,10 @@ pub enum DenoError {
     #[error("{0}")]
     AnyError(#[from] AnyError),
 
+    // The call didn't complete within its timeout (and its execution was interrupted)
+    #[error("Execution timed out after {} ms", .0.as_millis())]
+    Timeout(Duration),
+
     // Show it to developers (such as missing "await") so they may possibly fix it.
     #[error("{0}")]
     Diagnostic(#[from] DenoDiagnosticError),
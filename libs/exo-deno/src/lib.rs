@@ -20,7 +20,7 @@ pub mod error;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 pub use deno_error;
-pub use deno_executor_pool::DenoExecutorPool;
+pub use deno_executor_pool::{DenoActorPoolStatus, DenoExecutorPool};
 pub use deno_module::{Arg, DenoModule, UserCode};
 
 mod deno_actor;
@@ -11,6 +11,14 @@ export function addAndDouble(i, j) {
   return (i+j) * 2;
 }
 
+export function spin() {
+  while (true) {}
+}
+
+export async function wait() {
+  await new Promise((resolve) => setTimeout(resolve, 60000));
+}
+
   // TODO: avoid fetching from a server in tests
 export async function getJson(id) {
   const r = await fetch("https://jsonplaceholder.typicode.com/todos/" + id);
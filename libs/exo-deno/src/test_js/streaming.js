@@ -0,0 +1,51 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+class StreamError extends Error {
+  constructor(message) {
+    super(message);
+    this.name = "StreamError";
+  }
+}
+
+let closed = false;
+
+export async function* countTo(n) {
+  for (let i = 1; i <= n; i++) {
+    await new Promise((resolve) => setTimeout(resolve, 1));
+    yield { count: i };
+  }
+}
+
+export function syncArray() {
+  return ["a", "b", "c"];
+}
+
+export async function* failAfter(n) {
+  yield* countTo(n);
+  throw new StreamError(`Failed after ${n} items`);
+}
+
+export function notIterable() {
+  return "abc";
+}
+
+export async function* untilClosed() {
+  try {
+    while (true) {
+      yield "item";
+    }
+  } finally {
+    closed = true;
+  }
+}
+
+export function wasClosed() {
+  return closed;
+}
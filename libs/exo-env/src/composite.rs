@@ -23,6 +23,10 @@ impl Environment for CompositeEnvironment {
     fn non_system_envs(&self) -> Box<dyn Iterator<Item = (String, String)> + '_> {
         Box::new(self.envs.iter().flat_map(|env| env.non_system_envs()))
     }
+
+    fn reload(&self) {
+        self.envs.iter().for_each(|env| env.reload());
+    }
 }
 
 impl CompositeEnvironment {
@@ -9,30 +9,40 @@
 
 use crate::Environment;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 pub struct DotEnvironment {
     file_path: std::path::PathBuf,
-    vars: std::sync::OnceLock<HashMap<String, String>>,
+    /// Loaded on first use (and again after a [`Environment::reload`])
+    vars: RwLock<Option<Arc<HashMap<String, String>>>>,
 }
 
 impl DotEnvironment {
     pub fn new<P: AsRef<std::path::Path>>(file_path: P) -> Self {
         Self {
             file_path: file_path.as_ref().to_path_buf(),
-            vars: std::sync::OnceLock::new(),
+            vars: RwLock::new(None),
         }
     }
 
-    fn load_vars(&self) -> &HashMap<String, String> {
-        self.vars.get_or_init(|| {
-            let mut vars = HashMap::new();
-            if let Ok(iter) = dotenvy::from_filename_iter(&self.file_path) {
-                for (key, value) in iter.flatten() {
-                    vars.insert(key, value);
+    fn load_vars(&self) -> Arc<HashMap<String, String>> {
+        if let Some(vars) = self.vars.read().unwrap().as_ref() {
+            return vars.clone();
+        }
+
+        self.vars
+            .write()
+            .unwrap()
+            .get_or_insert_with(|| {
+                let mut vars = HashMap::new();
+                if let Ok(iter) = dotenvy::from_filename_iter(&self.file_path) {
+                    for (key, value) in iter.flatten() {
+                        vars.insert(key, value);
+                    }
                 }
-            }
-            vars
-        })
+                Arc::new(vars)
+            })
+            .clone()
     }
 }
 
@@ -44,6 +54,38 @@ impl Environment for DotEnvironment {
 
     fn non_system_envs(&self) -> Box<dyn Iterator<Item = (String, String)> + '_> {
         let vars = self.load_vars();
-        Box::new(vars.iter().map(|(k, v)| (k.clone(), v.clone())))
+        Box::new(
+            vars.iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        )
+    }
+
+    fn reload(&self) {
+        *self.vars.write().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_rereads_file() {
+        let file_path = std::env::temp_dir().join(format!(".env.reload-{}", std::process::id()));
+        std::fs::write(&file_path, "EXO_JWT_SECRET=old\n").unwrap();
+
+        let env = DotEnvironment::new(&file_path);
+        assert_eq!(env.get("EXO_JWT_SECRET"), Some("old".to_string()));
+
+        std::fs::write(&file_path, "EXO_JWT_SECRET=new\n").unwrap();
+        // Values are cached until reloaded
+        assert_eq!(env.get("EXO_JWT_SECRET"), Some("old".to_string()));
+
+        env.reload();
+        assert_eq!(env.get("EXO_JWT_SECRET"), Some("new".to_string()));
+
+        std::fs::remove_file(&file_path).unwrap();
     }
 }
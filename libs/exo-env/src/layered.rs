@@ -0,0 +1,233 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{DotEnvironment, Environment, MapEnvironment, Profile, SystemEnvironment};
+
+/// Where a layer of a [`LayeredEnvironment`] gets its values from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvSource {
+    /// The environment of the process
+    Process,
+    /// A `.env*` file
+    File(PathBuf),
+    /// Defaults declared in the exo model (such as `@env("LOG_LEVEL") logLevel: String = "info"`)
+    ModelDefaults,
+}
+
+impl Display for EnvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvSource::Process => write!(f, "process environment"),
+            EnvSource::File(path) => write!(f, "{}", path.display()),
+            EnvSource::ModelDefaults => write!(f, "model defaults"),
+        }
+    }
+}
+
+/// The value of a variable along with where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvValue {
+    pub key: String,
+    pub value: String,
+    pub source: EnvSource,
+    /// Lower precedence sources that also define the variable (and are thus ignored)
+    pub overridden: Vec<EnvSource>,
+}
+
+/// An environment made of layers, where a value in a layer overrides values in the layers after it.
+///
+/// The standard layering (see [`LayeredEnvironment::load`] and
+/// [`LayeredEnvironment::with_defaults`]) is: process environment >
+/// `.env.{profile}.local` > `.env.local` > `.env.{profile}` > `.env` > defaults declared in the
+/// exo model.
+pub struct LayeredEnvironment {
+    profile: Option<Profile>,
+    // In order of precedence (first is highest precedence)
+    layers: Vec<(EnvSource, Arc<dyn Environment>)>,
+}
+
+impl LayeredEnvironment {
+    pub fn new(profile: Option<Profile>, layers: Vec<(EnvSource, Arc<dyn Environment>)>) -> Self {
+        Self { profile, layers }
+    }
+
+    /// Layer the process environment over the env files of the profile (that exist) in the
+    /// current directory
+    pub fn load(profile: Option<Profile>) -> Self {
+        // Join with an empty path to keep the file paths relative (such as `.env.dev`)
+        Self::load_in("", profile)
+    }
+
+    /// Same as [`Self::load`], but with env files in `dir`
+    pub fn load_in(dir: impl AsRef<Path>, profile: Option<Profile>) -> Self {
+        let mut layers: Vec<(EnvSource, Arc<dyn Environment>)> =
+            vec![(EnvSource::Process, Arc::new(SystemEnvironment))];
+
+        for file in Profile::env_files(profile.as_ref()) {
+            let file_path = dir.as_ref().join(file);
+            if file_path.exists() {
+                layers.push((
+                    EnvSource::File(file_path.clone()),
+                    Arc::new(DotEnvironment::new(file_path)),
+                ));
+            }
+        }
+
+        Self::new(profile, layers)
+    }
+
+    /// Add defaults (typically, declared in the exo model) as the lowest precedence layer
+    pub fn with_defaults(mut self, defaults: HashMap<String, String>) -> Self {
+        if !defaults.is_empty() {
+            self.layers.push((
+                EnvSource::ModelDefaults,
+                Arc::new(MapEnvironment::from(defaults)),
+            ));
+        }
+        self
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// The env files in use, in order of precedence
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.layers.iter().filter_map(|(source, _)| match source {
+            EnvSource::File(path) => Some(path.as_path()),
+            _ => None,
+        })
+    }
+
+    /// The effective value of a variable and where it came from
+    pub fn resolve(&self, key: &str) -> Option<EnvValue> {
+        let mut defining_layers = self
+            .layers
+            .iter()
+            .filter_map(|(source, env)| env.get(key).map(|value| (source, value)));
+
+        let (source, value) = defining_layers.next()?;
+
+        Some(EnvValue {
+            key: key.to_string(),
+            value,
+            source: source.clone(),
+            overridden: defining_layers.map(|(source, _)| source.clone()).collect(),
+        })
+    }
+
+    /// The effective values of all variables defined in env files or model defaults (sorted by
+    /// key), including those overridden by the process environment.
+    ///
+    /// Variables defined only in the process environment are not listed (use [`Self::resolve`]
+    /// to look them up).
+    pub fn effective_values(&self) -> Vec<EnvValue> {
+        let keys: BTreeSet<String> = self
+            .layers
+            .iter()
+            .flat_map(|(_, env)| env.non_system_envs().map(|(key, _)| key))
+            .collect();
+
+        keys.into_iter()
+            .filter_map(|key| self.resolve(&key))
+            .collect()
+    }
+}
+
+impl Environment for LayeredEnvironment {
+    fn get(&self, key: &str) -> Option<String> {
+        self.layers.iter().find_map(|(_, env)| env.get(key))
+    }
+
+    fn non_system_envs(&self) -> Box<dyn Iterator<Item = (String, String)> + '_> {
+        Box::new(
+            self.layers
+                .iter()
+                .flat_map(|(_, env)| env.non_system_envs()),
+        )
+    }
+
+    fn reload(&self) {
+        self.layers.iter().for_each(|(_, env)| env.reload());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(source: EnvSource, values: &[(&str, &str)]) -> (EnvSource, Arc<dyn Environment>) {
+        let mut env = MapEnvironment::new();
+        for (key, value) in values {
+            env.set(key, value);
+        }
+        (source, Arc::new(env))
+    }
+
+    #[test]
+    fn higher_layers_win() {
+        let env = LayeredEnvironment::new(
+            Some(Profile::dev()),
+            vec![
+                layer(EnvSource::File(".env.dev".into()), &[("PORT", "9000")]),
+                layer(
+                    EnvSource::File(".env".into()),
+                    &[("PORT", "8000"), ("LOG_LEVEL", "debug")],
+                ),
+            ],
+        )
+        .with_defaults(HashMap::from([
+            ("LOG_LEVEL".to_string(), "info".to_string()),
+            ("REGION".to_string(), "us-east".to_string()),
+        ]));
+
+        assert_eq!(env.get("PORT"), Some("9000".to_string()));
+        assert_eq!(env.get("MISSING"), None);
+
+        assert_eq!(
+            env.effective_values(),
+            vec![
+                EnvValue {
+                    key: "LOG_LEVEL".to_string(),
+                    value: "debug".to_string(),
+                    source: EnvSource::File(".env".into()),
+                    overridden: vec![EnvSource::ModelDefaults],
+                },
+                EnvValue {
+                    key: "PORT".to_string(),
+                    value: "9000".to_string(),
+                    source: EnvSource::File(".env.dev".into()),
+                    overridden: vec![EnvSource::File(".env".into())],
+                },
+                EnvValue {
+                    key: "REGION".to_string(),
+                    value: "us-east".to_string(),
+                    source: EnvSource::ModelDefaults,
+                    overridden: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_files() {
+        assert_eq!(
+            Profile::env_files(Some(&Profile::test())),
+            vec![".env.test.local", ".env.local", ".env.test", ".env"]
+        );
+        assert_eq!(Profile::env_files(None), vec![".env.local", ".env"]);
+    }
+}
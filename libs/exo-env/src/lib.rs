@@ -9,12 +9,16 @@
 
 mod composite;
 mod dot;
+mod layered;
 mod map;
+mod profile;
 mod system;
 
 pub use composite::CompositeEnvironment;
 pub use dot::DotEnvironment;
+pub use layered::{EnvSource, EnvValue, LayeredEnvironment};
 pub use map::MapEnvironment;
+pub use profile::Profile;
 pub use system::SystemEnvironment;
 
 pub trait Environment: Send + Sync {
@@ -22,6 +26,10 @@ pub trait Environment: Send + Sync {
 
     fn non_system_envs(&self) -> Box<dyn Iterator<Item = (String, String)> + '_>;
 
+    /// Re-read the underlying sources (such as env files), so that subsequent lookups see their
+    /// current values. Does nothing for environments whose values can't change.
+    fn reload(&self) {}
+
     fn enabled(&self, key: &str, default_value: bool) -> Result<bool, EnvError> {
         match self.get(key) {
             Some(value) => match value.to_lowercase().as_str() {
@@ -34,6 +34,12 @@ impl Environment for MapEnvironment {
             Box::new(local_iter)
         }
     }
+
+    fn reload(&self) {
+        if let Some(fallback) = &self.fallback {
+            fallback.reload();
+        }
+    }
 }
 
 impl From<HashMap<String, String>> for MapEnvironment {
@@ -0,0 +1,67 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+/// A named set of `.env.{profile}*` files, such as `dev`, `test`, or `production`.
+///
+/// The profile is typically selected through the `EXO_ENV` variable (the `exo dev`, `exo test`,
+/// etc. commands set it to the subcommand name).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile(String);
+
+impl Profile {
+    pub const ENV_KEY: &'static str = "EXO_ENV";
+
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn dev() -> Self {
+        Self::new("dev")
+    }
+
+    pub fn test() -> Self {
+        Self::new("test")
+    }
+
+    pub fn prod() -> Self {
+        Self::new("production")
+    }
+
+    /// The profile selected by `EXO_ENV` in the given environment, if any
+    pub fn from_env(env: &dyn crate::Environment) -> Option<Self> {
+        env.get(Self::ENV_KEY)
+            .filter(|name| !name.is_empty())
+            .map(Self::new)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// Env files for the profile, in order of precedence (first is highest precedence).
+    ///
+    /// Without a profile, only the profile-agnostic files (`.env.local` and `.env`) are used.
+    pub fn env_files(profile: Option<&Profile>) -> Vec<String> {
+        match profile {
+            Some(profile) => vec![
+                format!(".env.{}.local", profile.name()),
+                ".env.local".to_string(),
+                format!(".env.{}", profile.name()),
+                ".env".to_string(),
+            ],
+            None => vec![".env.local".to_string(), ".env".to_string()],
+        }
+    }
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
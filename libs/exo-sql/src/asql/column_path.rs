@@ -13,7 +13,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     AbstractPredicate, ColumnId, Database, TableId,
-    sql::{SQLParamContainer, predicate::ParamEquality, relation::RelationColumnPair},
+    asql::traversal::AbstractTraversal,
+    sql::{
+        SQLParamContainer, physical_column_type::PhysicalColumnType, predicate::ParamEquality,
+        relation::RelationColumnPair,
+    },
 };
 
 /// A link in `ColumnPath` to a column starting at a root table and ending at a leaf column. This
@@ -32,9 +36,22 @@ pub enum ColumnPath {
     Physical(PhysicalColumnPath),
     Param(SQLParamContainer),
     Predicate(Box<AbstractPredicate>), // TODO: Generalize this to be any expression
+    /// The primary keys of rows reachable through a self-referential relation (to be used with `IN`)
+    Traversal(Box<AbstractTraversal>),
+    /// A (non-object) value inside a JSON column
+    JsonField(JsonFieldPath),
     Null,
 }
 
+/// A path to a value inside a JSON column such as `"customers"."address" #>> '{geo,lat}'`, which
+/// will be interpreted as `typ` (so that it can be compared with or ordered as a typed value)
+#[derive(Debug, PartialEq, Clone)]
+pub struct JsonFieldPath {
+    pub column: PhysicalColumnPath,
+    pub path: Vec<String>,
+    pub typ: Box<dyn PhysicalColumnType>,
+}
+
 impl ParamEquality for ColumnPath {
     fn param_eq(&self, other: &Self) -> Option<bool> {
         match (self, other) {
@@ -261,6 +278,10 @@ impl PhysicalColumnPath {
         self.0[0].self_column_ids()[0].table_id
     }
 
+    pub fn links(&self) -> &[ColumnPathLink] {
+        &self.0
+    }
+
     pub fn last_link(&self) -> &ColumnPathLink {
         self.0.last().expect("Unexpected empty column path")
     }
@@ -8,18 +8,27 @@
 // by the Apache License, Version 2.0.
 
 use crate::{
-    Database,
+    ColumnId, Database, SQLParamContainer, TableId,
     database_error::DatabaseError,
     sql::{
-        connect::database_client_manager::DatabaseClientManager, transaction::TransactionStepResult,
+        connect::database_client_manager::DatabaseClientManager,
+        transaction::{ExecutionConfig, TransactionStepResult},
     },
     transform::{pg::Postgres, transformer::OperationTransformer},
 };
 
-use super::{abstract_operation::AbstractOperation, transaction_holder::TransactionHolder};
+use super::{
+    abstract_operation::AbstractOperation,
+    execution_plan::{OperationPlan, PlanNode},
+    transaction_holder::TransactionHolder,
+};
 
 pub struct DatabaseExecutor {
     pub database_client: DatabaseClientManager,
+    /// The client for a read replica (if configured). Reads go there unless the consistency of
+    /// the request requires otherwise (see [`super::read_consistency::ReadConsistency`]).
+    pub replica_client: Option<DatabaseClientManager>,
+    pub config: ExecutionConfig,
 }
 
 impl DatabaseExecutor {
@@ -33,10 +42,73 @@ impl DatabaseExecutor {
         database: &Database,
     ) -> Result<TransactionStepResult, DatabaseError> {
         let database_kind = Postgres {};
+        // Selects that lock rows must run on the primary (in the transaction that writes them)
+        let is_read =
+            matches!(&operation, AbstractOperation::Select(select) if select.lock.is_none());
+        let plan_root = tx_holder
+            .plan_recorder()
+            .is_enabled()
+            .then(|| PlanNode::from_operation(&operation, database));
         let transaction_script = database_kind.to_transaction_script(database, operation);
 
+        if let Some(root) = plan_root {
+            tx_holder.plan_recorder().record(OperationPlan {
+                root,
+                steps: transaction_script.plan_steps(database),
+            });
+        }
+
+        tx_holder
+            .with_tx(
+                database,
+                &self.config,
+                &self.database_client,
+                self.replica_client.as_ref(),
+                is_read,
+                transaction_script,
+            )
+            .await
+    }
+
+    /// Transform an operation into SQL statements without executing it (for example, to inspect or
+    /// benchmark the generated SQL). Statements that depend on the results of earlier statements
+    /// (such as inserts of nested elements) are omitted.
+    pub fn to_sql_statements(operation: AbstractOperation, database: &Database) -> Vec<String> {
+        let database_kind = Postgres {};
+        database_kind
+            .to_transaction_script(database, operation)
+            .concrete_statements(database)
+    }
+
+    /// Insert rows into a table using `COPY` (much faster than `INSERT` for a large number of rows).
+    ///
+    /// Each row supplies values for `column_ids` (in the same order), where `None` stands for `NULL`.
+    /// Returns the number of rows inserted.
+    pub async fn copy_rows(
+        &self,
+        table_id: TableId,
+        column_ids: &[ColumnId],
+        rows: &[Vec<Option<SQLParamContainer>>],
+        tx_holder: &mut TransactionHolder,
+        database: &Database,
+    ) -> Result<u64, DatabaseError> {
+        tx_holder
+            .copy_rows(database, &self.database_client, table_id, column_ids, rows)
+            .await
+    }
+
+    /// Execute a statement that isn't a part of an operation, returning the number of rows affected.
+    ///
+    /// The statement always runs in the request's transaction, so it takes effect only if the rest
+    /// of the request's work is committed.
+    pub async fn execute_statement(
+        &self,
+        statement: &str,
+        params: &[SQLParamContainer],
+        tx_holder: &mut TransactionHolder,
+    ) -> Result<u64, DatabaseError> {
         tx_holder
-            .with_tx(database, &self.database_client, transaction_script)
+            .execute_statement(&self.database_client, statement, params)
             .await
     }
 }
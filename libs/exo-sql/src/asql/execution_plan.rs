@@ -0,0 +1,532 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A description of how an operation executes (the tree of operations, their predicates, and the
+//! resulting transaction steps) for visualization and performance tuning.
+
+use std::sync::{
+    Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use serde::Serialize;
+
+use crate::{
+    ColumnId, Database, TableId,
+    sql::predicate::{CaseSensitivity, NumericComparator},
+};
+
+use super::{
+    abstract_operation::AbstractOperation,
+    column_path::ColumnPath,
+    delete::AbstractDelete,
+    insert::{InsertionElement, InsertionRow},
+    predicate::AbstractPredicate,
+    select::AbstractSelect,
+    selection::{Selection, SelectionElement},
+    update::AbstractUpdate,
+};
+
+/// The plan of an operation: the tree of abstract operations (with their predicates, including
+/// those from access control rules) and the transaction steps they turn into.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationPlan {
+    pub root: PlanNode,
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanNode {
+    pub kind: PlanNodeKind,
+    pub table: String,
+    /// The name of the field (for subselects) through which the parent reaches this node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// The predicate to filter rows (omitted if it selects all rows)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<String>,
+    /// The predicates that must hold before a mutation proceeds
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub prechecks: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanNodeKind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanStep {
+    pub kind: PlanStepKind,
+    /// The SQL of the step (only for steps that don't depend on the results of earlier steps)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sql: Option<String>,
+    /// The index of the step whose results this step uses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanStepKind {
+    /// A statement known upfront
+    Concrete,
+    /// A statement (or a set of them) filled in with the results of an earlier step
+    Template,
+    /// A filter of the rows from an earlier step (for example, to apply access control)
+    Filter,
+    /// A statement computed from the results of earlier steps
+    Dynamic,
+    /// A query that must return a row for the operation to proceed
+    Precheck,
+}
+
+/// Collects the plans of the operations executed for a request (once enabled, so that requests
+/// that don't ask for plans don't pay for computing them).
+#[derive(Debug, Default)]
+pub struct PlanRecorder {
+    enabled: AtomicBool,
+    plans: Mutex<Vec<OperationPlan>>,
+}
+
+impl PlanRecorder {
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record(&self, plan: OperationPlan) {
+        self.plans.lock().unwrap().push(plan);
+    }
+
+    /// The plans recorded so far (in the order of execution)
+    pub fn plans(&self) -> Vec<OperationPlan> {
+        self.plans.lock().unwrap().clone()
+    }
+}
+
+impl PlanNode {
+    pub(crate) fn from_operation(operation: &AbstractOperation, database: &Database) -> Self {
+        match operation {
+            AbstractOperation::Select(select) => Self::from_select(select, None, database),
+            AbstractOperation::Insert(insert) => {
+                let mut node = Self::new(
+                    PlanNodeKind::Insert,
+                    insert.table_id,
+                    None,
+                    &insert.precheck_predicates,
+                    database,
+                );
+                node.add_nested_insertions(&insert.rows, database);
+                node.children
+                    .push(Self::from_select(&insert.selection, None, database));
+                node
+            }
+            AbstractOperation::Update(update) => Self::from_update(update, database),
+            AbstractOperation::Delete(delete) => Self::from_delete(delete, database),
+        }
+    }
+
+    fn new(
+        kind: PlanNodeKind,
+        table_id: TableId,
+        predicate: Option<&AbstractPredicate>,
+        prechecks: &[AbstractPredicate],
+        database: &Database,
+    ) -> Self {
+        Self {
+            kind,
+            table: database.get_table(table_id).name.fully_qualified_name(),
+            field: None,
+            predicate: predicate
+                .filter(|predicate| !predicate.is_true())
+                .map(|predicate| describe_predicate(predicate, database)),
+            prechecks: prechecks
+                .iter()
+                .map(|predicate| describe_predicate(predicate, database))
+                .collect(),
+            children: vec![],
+        }
+    }
+
+    fn from_select(select: &AbstractSelect, field: Option<&str>, database: &Database) -> Self {
+        let mut node = Self::new(
+            PlanNodeKind::Select,
+            select.table_id,
+            Some(&select.predicate),
+            &[],
+            database,
+        );
+        node.field = field.map(|field| field.to_string());
+
+        let elements = match &select.selection {
+            Selection::Seq(elements) | Selection::Json(elements, _) => elements,
+        };
+        for element in elements {
+            node.add_subselects(&element.alias, &element.column, database);
+        }
+        node
+    }
+
+    fn add_subselects(&mut self, alias: &str, element: &SelectionElement, database: &Database) {
+        match element {
+            SelectionElement::SubSelect(_, select)
+            | SelectionElement::TransitiveSubSelect(_, select) => {
+                self.children
+                    .push(Self::from_select(select, Some(alias), database));
+            }
+            SelectionElement::Object(elements) => {
+                for (alias, element) in elements {
+                    self.add_subselects(alias, element, database);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_nested_insertions(&mut self, rows: &[InsertionRow], database: &Database) {
+        for row in rows {
+            for element in &row.elems {
+                if let InsertionElement::NestedInsert(nested) = element {
+                    let table_id = nested.relation_id.deref(database).linked_table_id;
+                    let mut node = Self::new(
+                        PlanNodeKind::Insert,
+                        table_id,
+                        None,
+                        &nested.precheck_predicates,
+                        database,
+                    );
+                    node.add_nested_insertions(&nested.insertions, database);
+                    self.children.push(node);
+                }
+            }
+        }
+    }
+
+    fn from_update(update: &AbstractUpdate, database: &Database) -> Self {
+        let mut node = Self::new(
+            PlanNodeKind::Update,
+            update.table_id,
+            Some(&update.predicate),
+            &update.precheck_predicates,
+            database,
+        );
+
+        for nested in &update.nested_updates {
+            node.children
+                .push(Self::from_update(&nested.update, database));
+        }
+        for nested in &update.nested_inserts {
+            for op in &nested.ops {
+                let mut insert_node = Self::new(
+                    PlanNodeKind::Insert,
+                    op.insert.table_id,
+                    Some(&nested.filter_predicate),
+                    &op.insert.precheck_predicates,
+                    database,
+                );
+                insert_node.add_nested_insertions(&op.insert.rows, database);
+                node.children.push(insert_node);
+            }
+        }
+        for nested in &update.nested_deletes {
+            node.children
+                .push(Self::from_delete(&nested.delete, database));
+        }
+
+        node.children
+            .push(Self::from_select(&update.selection, None, database));
+        node
+    }
+
+    fn from_delete(delete: &AbstractDelete, database: &Database) -> Self {
+        let mut node = Self::new(
+            PlanNodeKind::Delete,
+            delete.table_id,
+            Some(&delete.predicate),
+            &delete.precheck_predicates,
+            database,
+        );
+        node.children
+            .push(Self::from_select(&delete.selection, None, database));
+        node
+    }
+}
+
+/// Describe a predicate in a SQL-like form (such as `concerts.venue_id -> venues.published = ?`).
+/// Parameters are shown as `?`, so the description doesn't include any values from the request.
+fn describe_predicate(predicate: &AbstractPredicate, database: &Database) -> String {
+    let binary = |l: &ColumnPath, op: &str, r: &ColumnPath| {
+        format!(
+            "{} {op} {}",
+            describe_column_path(l, database),
+            describe_column_path(r, database)
+        )
+    };
+
+    match predicate {
+        AbstractPredicate::True => "true".to_string(),
+        AbstractPredicate::False => "false".to_string(),
+        AbstractPredicate::Eq(l, r) => binary(l, "=", r),
+        AbstractPredicate::Neq(l, r) => binary(l, "<>", r),
+        AbstractPredicate::Lt(l, r) => binary(l, "<", r),
+        AbstractPredicate::Lte(l, r) => binary(l, "<=", r),
+        AbstractPredicate::Gt(l, r) => binary(l, ">", r),
+        AbstractPredicate::Gte(l, r) => binary(l, ">=", r),
+        AbstractPredicate::In(l, r) => binary(l, "IN", r),
+        AbstractPredicate::ArrayContains(l, r) => binary(l, "@>", r),
+        AbstractPredicate::StringLike(l, r, CaseSensitivity::Sensitive) => binary(l, "LIKE", r),
+        AbstractPredicate::StringLike(l, r, CaseSensitivity::Insensitive) => binary(l, "ILIKE", r),
+        AbstractPredicate::StringStartsWith(l, r) => binary(l, "STARTS WITH", r),
+        AbstractPredicate::StringEndsWith(l, r) => binary(l, "ENDS WITH", r),
+        AbstractPredicate::JsonContains(l, r) => binary(l, "@>", r),
+        AbstractPredicate::JsonContainedBy(l, r) => binary(l, "<@", r),
+        AbstractPredicate::JsonMatchKey(l, r) => binary(l, "?", r),
+        AbstractPredicate::JsonMatchAnyKey(l, r) => binary(l, "?|", r),
+        AbstractPredicate::JsonMatchAllKeys(l, r) => binary(l, "?&", r),
+        AbstractPredicate::VectorDistance(l, r, function, comparator, target) => {
+            let comparator = match comparator {
+                NumericComparator::Eq => "=",
+                NumericComparator::Neq => "<>",
+                NumericComparator::Lt => "<",
+                NumericComparator::Lte => "<=",
+                NumericComparator::Gt => ">",
+                NumericComparator::Gte => ">=",
+            };
+            format!(
+                "{function:?}({}, {}) {comparator} {}",
+                describe_column_path(l, database),
+                describe_column_path(r, database),
+                describe_column_path(target, database)
+            )
+        }
+        AbstractPredicate::And(l, r) => format!(
+            "({} AND {})",
+            describe_predicate(l, database),
+            describe_predicate(r, database)
+        ),
+        AbstractPredicate::Or(l, r) => format!(
+            "({} OR {})",
+            describe_predicate(l, database),
+            describe_predicate(r, database)
+        ),
+        AbstractPredicate::Not(p) => format!("NOT {}", describe_predicate(p, database)),
+    }
+}
+
+fn describe_column_path(column_path: &ColumnPath, database: &Database) -> String {
+    match column_path {
+        ColumnPath::Physical(path) => path
+            .links()
+            .iter()
+            .map(|link| {
+                // For a relation, the columns that refer to the next table in the path
+                link.self_column_ids()
+                    .iter()
+                    .map(|column_id| describe_column(*column_id, database))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join(" -> "),
+        ColumnPath::JsonField(json_field) => format!(
+            "{} #>> '{{{}}}'",
+            describe_column_path(&ColumnPath::Physical(json_field.column.clone()), database),
+            json_field.path.join(",")
+        ),
+        ColumnPath::Param(_) => "?".to_string(),
+        ColumnPath::Predicate(predicate) => describe_predicate(predicate, database),
+        ColumnPath::Traversal(traversal) => format!(
+            "traversal(start: {}, each: {})",
+            describe_predicate(&traversal.start, database),
+            describe_predicate(&traversal.level_predicate, database)
+        ),
+        ColumnPath::Null => "NULL".to_string(),
+    }
+}
+
+fn describe_column(column_id: ColumnId, database: &Database) -> String {
+    let column = column_id.get_column(database);
+    format!(
+        "{}.{}",
+        database
+            .get_table(column.table_id)
+            .name
+            .fully_qualified_name(),
+        column.name
+    )
+}
+
+/// Render plans in the DOT format (for Graphviz), with the operation tree and the transaction
+/// steps of each plan in a cluster of its own.
+pub fn plans_to_dot(plans: &[OperationPlan]) -> String {
+    let mut dot = String::from("digraph plan {\n  node [shape=box, fontname=\"monospace\"];\n");
+
+    for (plan_index, plan) in plans.iter().enumerate() {
+        dot.push_str(&format!(
+            "  subgraph cluster_{plan_index} {{\n    label=\"operation {}\";\n",
+            plan_index + 1
+        ));
+
+        let mut node_count = 0;
+        add_dot_node(&mut dot, &plan.root, plan_index, &mut node_count, None);
+
+        for (step_index, step) in plan.steps.iter().enumerate() {
+            let mut label = format!("step {}: {:?}", step_index + 1, step.kind);
+            if let Some(sql) = &step.sql {
+                label.push('\n');
+                label.push_str(sql);
+            }
+            dot.push_str(&format!(
+                "    p{plan_index}_s{step_index} [shape=note, label=\"{}\"];\n",
+                escape_dot(&label)
+            ));
+            if step_index > 0 {
+                dot.push_str(&format!(
+                    "    p{plan_index}_s{} -> p{plan_index}_s{step_index} [style=dotted];\n",
+                    step_index - 1
+                ));
+            }
+            if let Some(depends_on) = step.depends_on {
+                dot.push_str(&format!(
+                    "    p{plan_index}_s{depends_on} -> p{plan_index}_s{step_index} [style=dashed, label=\"uses\"];\n"
+                ));
+            }
+        }
+
+        dot.push_str("  }\n");
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn add_dot_node(
+    dot: &mut String,
+    node: &PlanNode,
+    plan_index: usize,
+    node_count: &mut usize,
+    parent: Option<usize>,
+) {
+    let id = *node_count;
+    *node_count += 1;
+
+    let mut label = format!("{:?} {}", node.kind, node.table);
+    if let Some(predicate) = &node.predicate {
+        label.push_str(&format!("\nwhere {predicate}"));
+    }
+    for precheck in &node.prechecks {
+        label.push_str(&format!("\nprecheck {precheck}"));
+    }
+    dot.push_str(&format!(
+        "    p{plan_index}_n{id} [label=\"{}\"];\n",
+        escape_dot(&label)
+    ));
+
+    if let Some(parent) = parent {
+        let edge_label = node
+            .field
+            .as_ref()
+            .map(|field| format!(" [label=\"{}\"]", escape_dot(field)))
+            .unwrap_or_default();
+        dot.push_str(&format!(
+            "    p{plan_index}_n{parent} -> p{plan_index}_n{id}{edge_label};\n"
+        ));
+    }
+
+    for child in &node.children {
+        add_dot_node(dot, child, plan_index, node_count, Some(id));
+    }
+}
+
+fn escape_dot(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\l")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_output() {
+        let plan = OperationPlan {
+            root: PlanNode {
+                kind: PlanNodeKind::Select,
+                table: "concerts".to_string(),
+                field: None,
+                predicate: Some("concerts.published = ?".to_string()),
+                prechecks: vec![],
+                children: vec![PlanNode {
+                    kind: PlanNodeKind::Select,
+                    table: "venues".to_string(),
+                    field: Some("venue".to_string()),
+                    predicate: None,
+                    prechecks: vec![],
+                    children: vec![],
+                }],
+            },
+            steps: vec![PlanStep {
+                kind: PlanStepKind::Concrete,
+                sql: Some(r#"SELECT "concerts"."id" FROM "concerts""#.to_string()),
+                depends_on: None,
+            }],
+        };
+
+        assert_eq!(
+            plans_to_dot(&[plan]),
+            r#"digraph plan {
+  node [shape=box, fontname="monospace"];
+  subgraph cluster_0 {
+    label="operation 1";
+    p0_n0 [label="Select concerts\lwhere concerts.published = ?"];
+    p0_n1 [label="Select venues"];
+    p0_n0 -> p0_n1 [label="venue"];
+    p0_s0 [shape=note, label="step 1: Concrete\lSELECT \"concerts\".\"id\" FROM \"concerts\""];
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn recorder_collects_plans() {
+        let recorder = PlanRecorder::default();
+        assert!(!recorder.is_enabled());
+
+        recorder.enable();
+        assert!(recorder.is_enabled());
+
+        recorder.record(OperationPlan {
+            root: PlanNode {
+                kind: PlanNodeKind::Delete,
+                table: "concerts".to_string(),
+                field: None,
+                predicate: None,
+                prechecks: vec![],
+                children: vec![],
+            },
+            steps: vec![],
+        });
+        assert_eq!(recorder.plans().len(), 1);
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Counters for the database work performed while serving a request.
+///
+/// Shared (through an `Arc`) between the [`TransactionHolder`](super::transaction_holder::TransactionHolder)
+/// that performs the work and whoever reports it (for example, in the `extensions` of a GraphQL response).
+#[derive(Debug, Default)]
+pub struct ExecutionStats {
+    statements: AtomicU64,
+    rows: AtomicU64,
+    duration_micros: AtomicU64,
+}
+
+impl ExecutionStats {
+    pub(crate) fn record(&self, statements: usize, rows: usize, elapsed: Duration) {
+        self.statements
+            .fetch_add(statements as u64, Ordering::Relaxed);
+        self.rows.fetch_add(rows as u64, Ordering::Relaxed);
+        self.duration_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// The number of SQL statements executed
+    pub fn statements(&self) -> u64 {
+        self.statements.load(Ordering::Relaxed)
+    }
+
+    /// The number of rows returned by the statements
+    pub fn rows(&self) -> u64 {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    /// The total time spent executing the statements
+    pub fn duration(&self) -> Duration {
+        Duration::from_micros(self.duration_micros.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates() {
+        let stats = ExecutionStats::default();
+        stats.record(2, 10, Duration::from_millis(3));
+        stats.record(1, 0, Duration::from_millis(2));
+
+        assert_eq!(stats.statements(), 3);
+        assert_eq!(stats.rows(), 10);
+        assert_eq!(stats.duration(), Duration::from_millis(5));
+    }
+}
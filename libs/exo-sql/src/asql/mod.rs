@@ -11,12 +11,17 @@ pub mod abstract_operation;
 pub mod column_path;
 pub mod database_executor;
 pub mod delete;
+pub mod execution_plan;
+pub mod execution_stats;
 pub mod insert;
 pub mod order_by;
 
 pub mod predicate;
+pub mod read_consistency;
 pub mod select;
 pub mod selection;
 pub mod transaction_holder;
+pub mod traversal;
 
 pub mod update;
+pub mod window;
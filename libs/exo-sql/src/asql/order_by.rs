@@ -9,7 +9,7 @@
 
 use crate::{ColumnPath, VectorDistanceFunction, sql::order::Ordering};
 
-use super::column_path::PhysicalColumnPath;
+use super::column_path::{JsonFieldPath, PhysicalColumnPath};
 
 /// Represents an abstract order-by clause
 #[derive(Debug)]
@@ -19,6 +19,8 @@ pub struct AbstractOrderBy(pub Vec<(AbstractOrderByExpr, Ordering)>);
 pub enum AbstractOrderByExpr {
     Column(PhysicalColumnPath),
     VectorDistance(ColumnPath, ColumnPath, VectorDistanceFunction),
+    /// A (non-object) value inside a JSON column
+    JsonField(JsonFieldPath),
 }
 
 impl AbstractOrderBy {
@@ -27,6 +29,7 @@ impl AbstractOrderBy {
             .iter()
             .flat_map(|(expr, _)| match expr {
                 AbstractOrderByExpr::Column(path) => vec![path],
+                AbstractOrderByExpr::JsonField(json_field) => vec![&json_field.column],
                 AbstractOrderByExpr::VectorDistance(lhs, rhs, _) => [lhs, rhs]
                     .iter()
                     .filter_map(|path| match path {
@@ -0,0 +1,213 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Routing of reads between the primary database and a read replica.
+//!
+//! A replica applies the changes made on the primary with some delay, so reading from it right
+//! after a write may miss that write. Within a request, reads that follow a write always go to the
+//! primary. Across requests, a client may pass the position (LSN) of the primary's write-ahead log
+//! reported after its write, and reads go to the replica only once it has replayed up to there.
+
+/// How fresh the reads of a request must be
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Read from the replica until the request writes (the default)
+    #[default]
+    Replica,
+    /// Read everything from the primary
+    Primary,
+    /// Read from the replica only if it has replayed the primary's log up to the given LSN (such
+    /// as `0/16B3748`)
+    AfterLsn(String),
+}
+
+impl ReadConsistency {
+    /// Parse the consistency from the values of the `Exo-Read-Consistency` and
+    /// `Exo-Read-After-Lsn` headers (see [`READ_CONSISTENCY_HEADER`] and [`READ_AFTER_LSN_HEADER`])
+    pub fn from_headers(
+        consistency: Option<&str>,
+        after_lsn: Option<&str>,
+    ) -> Result<Self, ReadConsistencyError> {
+        match consistency.map(|value| value.trim().to_ascii_lowercase()) {
+            Some(value) if value == "primary" => Ok(Self::Primary),
+            Some(value) if value != "replica" => {
+                Err(ReadConsistencyError::InvalidConsistency(value))
+            }
+            _ => match after_lsn.map(str::trim) {
+                Some(lsn) if is_lsn(lsn) => Ok(Self::AfterLsn(lsn.to_string())),
+                Some(lsn) => Err(ReadConsistencyError::InvalidLsn(lsn.to_string())),
+                None => Ok(Self::Replica),
+            },
+        }
+    }
+}
+
+/// The header to choose the consistency of reads ("primary" or "replica")
+pub const READ_CONSISTENCY_HEADER: &str = "exo-read-consistency";
+
+/// The header to pass the LSN the replica must have replayed before serving reads
+pub const READ_AFTER_LSN_HEADER: &str = "exo-read-after-lsn";
+
+/// The response header with the LSN of the primary after committing the writes of a request
+pub const COMMIT_LSN_HEADER: &str = "exo-commit-lsn";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ReadConsistencyError {
+    #[error("Invalid read consistency '{0}' (expected 'primary' or 'replica')")]
+    InvalidConsistency(String),
+    #[error("Invalid LSN '{0}'")]
+    InvalidLsn(String),
+}
+
+/// The database to execute a read on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReadTarget {
+    Primary,
+    Replica,
+}
+
+/// The state of the request that decides where its next read goes
+pub(crate) struct ReadRouting<'a> {
+    pub consistency: &'a ReadConsistency,
+    /// The request has written (so the replica may not have its changes)
+    pub wrote: bool,
+    /// The work runs in a transaction on the primary
+    pub in_transaction: bool,
+    /// Whether the replica has replayed up to the LSN of [`ReadConsistency::AfterLsn`] (`None` if
+    /// not checked yet)
+    pub replica_caught_up: Option<bool>,
+}
+
+impl ReadRouting<'_> {
+    /// Where to execute the next read, or `None` if that depends on whether the replica has caught
+    /// up (which the caller must check first)
+    pub fn target(&self) -> Option<ReadTarget> {
+        if self.wrote || self.in_transaction {
+            return Some(ReadTarget::Primary);
+        }
+
+        match self.consistency {
+            ReadConsistency::Primary => Some(ReadTarget::Primary),
+            ReadConsistency::Replica => Some(ReadTarget::Replica),
+            ReadConsistency::AfterLsn(_) => self.replica_caught_up.map(|caught_up| {
+                if caught_up {
+                    ReadTarget::Replica
+                } else {
+                    ReadTarget::Primary
+                }
+            }),
+        }
+    }
+}
+
+/// Whether the value has the textual form of a Postgres LSN (two hexadecimal numbers separated by
+/// a slash)
+fn is_lsn(value: &str) -> bool {
+    let is_hex = |part: &str| {
+        !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    matches!(value.split_once('/'), Some((high, low)) if is_hex(high) && is_hex(low))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_headers() {
+        assert_eq!(
+            ReadConsistency::from_headers(None, None),
+            Ok(ReadConsistency::Replica)
+        );
+        assert_eq!(
+            ReadConsistency::from_headers(Some("Primary"), Some("0/16B3748")),
+            Ok(ReadConsistency::Primary)
+        );
+        assert_eq!(
+            ReadConsistency::from_headers(Some("replica"), Some(" 0/16B3748 ")),
+            Ok(ReadConsistency::AfterLsn("0/16B3748".to_string()))
+        );
+        assert_eq!(
+            ReadConsistency::from_headers(Some("eventual"), None),
+            Err(ReadConsistencyError::InvalidConsistency(
+                "eventual".to_string()
+            ))
+        );
+        assert_eq!(
+            ReadConsistency::from_headers(None, Some("16B3748")),
+            Err(ReadConsistencyError::InvalidLsn("16B3748".to_string()))
+        );
+        assert_eq!(
+            ReadConsistency::from_headers(None, Some("0/'; DROP")),
+            Err(ReadConsistencyError::InvalidLsn("0/'; DROP".to_string()))
+        );
+    }
+
+    fn routing(consistency: &ReadConsistency) -> ReadRouting<'_> {
+        ReadRouting {
+            consistency,
+            wrote: false,
+            in_transaction: false,
+            replica_caught_up: None,
+        }
+    }
+
+    #[test]
+    fn reads_after_writes_go_to_primary() {
+        for consistency in [
+            ReadConsistency::Replica,
+            ReadConsistency::Primary,
+            ReadConsistency::AfterLsn("0/1".to_string()),
+        ] {
+            let after_write = ReadRouting {
+                wrote: true,
+                ..routing(&consistency)
+            };
+            assert_eq!(after_write.target(), Some(ReadTarget::Primary));
+
+            let in_transaction = ReadRouting {
+                in_transaction: true,
+                ..routing(&consistency)
+            };
+            assert_eq!(in_transaction.target(), Some(ReadTarget::Primary));
+        }
+    }
+
+    #[test]
+    fn reads_before_writes() {
+        assert_eq!(
+            routing(&ReadConsistency::Replica).target(),
+            Some(ReadTarget::Replica)
+        );
+        assert_eq!(
+            routing(&ReadConsistency::Primary).target(),
+            Some(ReadTarget::Primary)
+        );
+
+        let after_lsn = ReadConsistency::AfterLsn("0/1".to_string());
+        assert_eq!(routing(&after_lsn).target(), None);
+        assert_eq!(
+            ReadRouting {
+                replica_caught_up: Some(true),
+                ..routing(&after_lsn)
+            }
+            .target(),
+            Some(ReadTarget::Replica)
+        );
+        assert_eq!(
+            ReadRouting {
+                replica_caught_up: Some(false),
+                ..routing(&after_lsn)
+            }
+            .target(),
+            Some(ReadTarget::Primary)
+        );
+    }
+}
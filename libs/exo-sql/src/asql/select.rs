@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::{Limit, Offset, TableId};
+use crate::{Limit, Offset, RowLock, TableId};
 
 use super::{order_by::AbstractOrderBy, predicate::AbstractPredicate, selection::Selection};
 
@@ -27,4 +27,6 @@ pub struct AbstractSelect {
     pub offset: Option<Offset>,
     /// The limit
     pub limit: Option<Limit>,
+    /// The lock to take on the selected rows of the table (only meaningful inside a transaction)
+    pub lock: Option<RowLock>,
 }
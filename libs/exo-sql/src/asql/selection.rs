@@ -9,9 +9,12 @@
 
 //! Support for selecting columns in a table, including json aggregates
 
-use crate::{ColumnId, RelationId, sql::function::Function};
+use crate::{
+    ColumnId, RelationId, sql::column::MaskStrategy, sql::function::Function,
+    sql::opaque_id::OpaqueIdKey,
+};
 
-use super::select::AbstractSelect;
+use super::{select::AbstractSelect, window::AbstractWindow};
 
 /// A selection element along with its alias
 #[derive(Debug)]
@@ -24,6 +27,11 @@ impl AliasedSelectionElement {
     pub fn new(alias: String, column: SelectionElement) -> Self {
         Self { alias, column }
     }
+
+    /// Split into the alias and the element (useful to nest the element in a [`SelectionElement::Object`])
+    pub fn into_parts(self) -> (String, SelectionElement) {
+        (self.alias, self.column)
+    }
 }
 
 /// The cardinality of a json aggregate
@@ -57,6 +65,13 @@ pub enum SelectionElement {
     Constant(String),
     /// A subselect such as `... (SELECT * FROM table)`
     SubSelect(RelationId, Box<AbstractSelect>),
+    /// A subselect of the rows reachable by following a chain of relations (the select is on the
+    /// table at the end of the chain). For example, to select books of a publisher through authors:
+    /// `... (SELECT ... FROM books WHERE books.author_id IN (SELECT authors.id FROM authors WHERE
+    /// authors.publisher_id = publishers.id))`
+    TransitiveSubSelect(Vec<RelationId>, Box<AbstractSelect>),
+    /// A window function such as `rank() OVER (PARTITION BY game_id ORDER BY points DESC)`
+    Window(Box<AbstractWindow>),
     /// Extract a field from a JSON value
     JsonExtract {
         source: Box<SelectionElement>,
@@ -67,4 +82,25 @@ pub enum SelectionElement {
         source: Box<SelectionElement>,
         key: String,
     },
+    /// An object of the elements (typically, extracted from `source`) if `source` is a JSON
+    /// object, and null otherwise (for example, for a missing optional value)
+    JsonProjection {
+        source: Box<SelectionElement>,
+        elements: Vec<(String, SelectionElement)>,
+    },
+    /// The value of another element, or a fallback literal (coerced to the element's type) if it is null
+    Coalesce {
+        source: Box<SelectionElement>,
+        fallback: String,
+    },
+    /// The value of another element, masked (for readers who may not see the full value)
+    Masked {
+        source: Box<SelectionElement>,
+        strategy: MaskStrategy,
+    },
+    /// The value of another (integer) element, as an opaque id
+    OpaqueId {
+        source: Box<SelectionElement>,
+        key: OpaqueIdKey,
+    },
 }
@@ -9,23 +9,32 @@
 
 use std::{
     ops::DerefMut,
-    sync::{LazyLock, atomic::AtomicBool},
+    sync::{Arc, LazyLock, atomic::AtomicBool},
+    time::Instant,
 };
 
 use tokio::sync::Mutex;
+use tokio_postgres::types::{ToSql, Type};
 
 use crate::{
-    Database,
+    ColumnId, Database, SQLParamContainer, TableId,
     database_error::DatabaseError,
     sql::{
         connect::{
             database_client::{DatabaseClient, TransactionWrapper},
             database_client_manager::DatabaseClientManager,
         },
-        transaction::{TransactionScript, TransactionStepResult},
+        copy::copy_rows,
+        transaction::{ExecutionConfig, TransactionScript, TransactionStepResult},
     },
 };
 
+use super::{
+    execution_plan::PlanRecorder,
+    execution_stats::ExecutionStats,
+    read_consistency::{ReadConsistency, ReadRouting, ReadTarget},
+};
+
 /// Manages the state of a transaction.
 ///
 /// The implementation complexity comes from the requirement that we must defer the creation of the transaction
@@ -36,12 +45,44 @@ use crate::{
 pub struct TransactionHolder {
     state: LazyLock<Mutex<TransactionState>>,
     needs_transaction: AtomicBool,
+    stats: Arc<ExecutionStats>,
+    plan_recorder: Arc<PlanRecorder>,
+    /// Settings yet to be applied to the transaction (see [`Self::set_session_settings`])
+    pending_session_settings: Option<Vec<(String, String)>>,
+    session_settings_set: bool,
+    /// The maximum number of statements to execute (see [`Self::with_statement_budget`])
+    statement_budget: Option<u64>,
+    /// How fresh reads must be, if a read replica is configured (see [`ReadConsistency`])
+    read_consistency: ReadConsistency,
+    /// Whether any work so far has written (so that later reads must see the changes)
+    wrote: bool,
+    /// Whether any work came with a read replica (so that committing records the LSN)
+    replica_configured: bool,
+    /// The LSN of the primary after committing the writes (see [`Self::commit_lsn`])
+    commit_lsn: Option<String>,
 }
 
 struct TransactionState {
     client: Option<DatabaseClient>,
     transaction: Option<TransactionWrapper<'static>>,
     finalized: bool,
+    /// The settings applied to the current transaction (to apply again, if it rolls back to a
+    /// savepoint taken before it started)
+    session_settings: Option<Vec<(String, String)>>,
+    /// The number of savepoints taken so far (to give each a unique name)
+    savepoint_count: usize,
+    /// The client for reads routed to the replica
+    replica_client: Option<DatabaseClient>,
+    /// Whether the replica has replayed up to the LSN required by the read consistency (once
+    /// checked)
+    replica_caught_up: Option<bool>,
+}
+
+/// A point in the transaction to roll back to (see [`TransactionHolder::savepoint`])
+#[derive(Debug)]
+pub struct Savepoint {
+    /// The name of the savepoint, or `None` if the transaction hadn't started when taking it
+    name: Option<String>,
 }
 
 impl Default for TransactionHolder {
@@ -49,6 +90,15 @@ impl Default for TransactionHolder {
         Self {
             state: LazyLock::new(|| Mutex::new(TransactionState::new())),
             needs_transaction: AtomicBool::new(false),
+            stats: Arc::new(ExecutionStats::default()),
+            plan_recorder: Arc::new(PlanRecorder::default()),
+            pending_session_settings: None,
+            session_settings_set: false,
+            statement_budget: None,
+            read_consistency: ReadConsistency::default(),
+            wrote: false,
+            replica_configured: false,
+            commit_lsn: None,
         }
     }
 }
@@ -58,6 +108,53 @@ impl TransactionHolder {
         Self::default()
     }
 
+    /// Create a holder that records the work it executes in the given stats
+    pub fn with_stats(stats: Arc<ExecutionStats>) -> Self {
+        Self {
+            stats,
+            ..Self::default()
+        }
+    }
+
+    /// Record the plans of the operations executed through this holder in the given recorder (if
+    /// it is enabled)
+    pub fn with_plan_recorder(self, plan_recorder: Arc<PlanRecorder>) -> Self {
+        Self {
+            plan_recorder,
+            ..self
+        }
+    }
+
+    /// Fail any work that would take the number of statements recorded in the stats beyond the
+    /// budget (without executing it). With the stats shared by all the work for a request, this
+    /// catches requests that execute a statement per row (for example, through nested selections
+    /// or computed fields) before they overwhelm the database.
+    pub fn with_statement_budget(self, statement_budget: Option<u64>) -> Self {
+        Self {
+            statement_budget,
+            ..self
+        }
+    }
+
+    /// Route the reads to the primary or the replica (if configured) based on the consistency
+    pub fn with_read_consistency(self, read_consistency: ReadConsistency) -> Self {
+        Self {
+            read_consistency,
+            ..self
+        }
+    }
+
+    /// The LSN of the primary after committing the writes of the request (only when using a read
+    /// replica). A later request may pass it to read its writes from the replica (see
+    /// [`ReadConsistency::AfterLsn`]).
+    pub fn commit_lsn(&self) -> Option<&str> {
+        self.commit_lsn.as_deref()
+    }
+
+    pub(crate) fn plan_recorder(&self) -> &PlanRecorder {
+        &self.plan_recorder
+    }
+
     /// Set the flag to indicate that a transaction must be used when executing work.
     ///
     /// Typically, a caller higher-up in the stack calls this method when it determines that a transaction
@@ -68,11 +165,36 @@ impl TransactionHolder {
             .store(true, std::sync::atomic::Ordering::SeqCst);
     }
 
-    /// Execute work within a transaction context
+    /// Set the given settings (name-value pairs such as `app.user_id`) for the transaction, so that
+    /// triggers and row-level security policies can read them (with `current_setting`).
+    ///
+    /// The settings are applied with `set_config(name, value, true)` (the equivalent of `SET
+    /// LOCAL`) before the next work, so they don't outlive the transaction (and leak to the next
+    /// user of the pooled connection). For the same reason, this ensures that the work happens in
+    /// a transaction. Only the first call has an effect.
+    pub fn set_session_settings(&mut self, settings: Vec<(String, String)>) {
+        if self.session_settings_set {
+            return;
+        }
+        self.session_settings_set = true;
+
+        if !settings.is_empty() {
+            self.ensure_transaction();
+            self.pending_session_settings = Some(settings);
+        }
+    }
+
+    /// Execute work within a transaction context.
+    ///
+    /// Reads (`is_read`) may go to the replica (if given) instead, unless they follow a write or
+    /// must run in the transaction (see [`ReadRouting`]).
     pub(super) async fn with_tx(
         &mut self,
         database: &Database,
+        config: &ExecutionConfig,
         client_manager: &DatabaseClientManager,
+        replica_manager: Option<&DatabaseClientManager>,
+        is_read: bool,
         work: TransactionScript<'_>,
     ) -> Result<TransactionStepResult, DatabaseError> {
         let mut state = self.state.lock().await;
@@ -83,24 +205,266 @@ impl TransactionHolder {
             ));
         }
 
+        // Checked against the number of steps, since some steps (such as filters) may execute more
+        // than one statement, which isn't known until the earlier steps have executed
+        self.check_statement_budget(work.step_count())?;
+
+        if let Some(replica_manager) = replica_manager {
+            self.replica_configured = true;
+
+            if is_read
+                && !work.needs_transaction()
+                && self.reads_from_replica(&mut state, replica_manager).await?
+            {
+                let start = Instant::now();
+                let result = match state.ensure_replica_client(replica_manager).await {
+                    Ok(client) => work.execute(database, config, client.deref_mut()).await,
+                    Err(err) => Err(err),
+                };
+                match &result {
+                    Ok((rows, statements)) => {
+                        self.stats.record(*statements, rows.len(), start.elapsed())
+                    }
+                    Err(err) if err.is_connection_failure() => replica_manager.flush_pool(err),
+                    Err(_) => {}
+                }
+                return result.map(|(rows, _)| rows);
+            }
+        }
+
+        if !is_read {
+            self.wrote = true;
+        }
+
         // Ensure we have a client
         state.ensure_client(client_manager).await?;
 
+        if let Some(settings) = self.pending_session_settings.take() {
+            state.apply_session_settings(settings).await?;
+        }
+
         // Execute the work
         let needs_tx = self
             .needs_transaction
             .load(std::sync::atomic::Ordering::SeqCst);
-        state.execute_work(database, work, needs_tx).await
+        let start = Instant::now();
+        let result = state.execute_work(database, config, work, needs_tx).await;
+        match &result {
+            Ok((rows, statements)) => self.stats.record(*statements, rows.len(), start.elapsed()),
+            Err(err) if err.is_connection_failure() => client_manager.flush_pool(err),
+            Err(_) => {}
+        }
+        result.map(|(rows, _)| rows)
+    }
+
+    /// Copy rows into a table (see [`copy_rows`]).
+    ///
+    /// Unlike other work, copying always happens within the transaction, so that a failure (for
+    /// example, in a later batch) rolls back everything copied as a part of the same request.
+    pub(super) async fn copy_rows(
+        &mut self,
+        database: &Database,
+        client_manager: &DatabaseClientManager,
+        table_id: TableId,
+        column_ids: &[ColumnId],
+        rows: &[Vec<Option<SQLParamContainer>>],
+    ) -> Result<u64, DatabaseError> {
+        let mut state = self.state.lock().await;
+
+        if state.finalized {
+            return Err(DatabaseError::Transaction(
+                "Transaction already finalized".to_string(),
+            ));
+        }
+
+        self.check_statement_budget(1)?;
+        self.wrote = true;
+
+        state.ensure_client(client_manager).await?;
+
+        if let Some(settings) = self.pending_session_settings.take() {
+            state.apply_session_settings(settings).await?;
+        }
+
+        let tx = state.ensure_transaction().await?;
+        let start = Instant::now();
+        let result = copy_rows(tx, database, table_id, column_ids, rows).await;
+        match &result {
+            Ok(_) => self.stats.record(1, 0, start.elapsed()),
+            Err(err) if err.is_connection_failure() => client_manager.flush_pool(err),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Execute a statement that isn't a part of an operation (such as an insert into a table not in
+    /// the model), returning the number of rows affected.
+    ///
+    /// Like copying rows, this always happens within the transaction, so that the statement takes
+    /// effect only if the rest of the request's work is committed.
+    pub(super) async fn execute_statement(
+        &mut self,
+        client_manager: &DatabaseClientManager,
+        statement: &str,
+        params: &[SQLParamContainer],
+    ) -> Result<u64, DatabaseError> {
+        let mut state = self.state.lock().await;
+
+        if state.finalized {
+            return Err(DatabaseError::Transaction(
+                "Transaction already finalized".to_string(),
+            ));
+        }
+
+        self.check_statement_budget(1)?;
+        self.wrote = true;
+
+        state.ensure_client(client_manager).await?;
+
+        if let Some(settings) = self.pending_session_settings.take() {
+            state.apply_session_settings(settings).await?;
+        }
+
+        let tx = state.ensure_transaction().await?;
+        let params: Vec<&(dyn ToSql + Sync)> = params
+            .iter()
+            .map(|param| param as &(dyn ToSql + Sync))
+            .collect();
+        let start = Instant::now();
+        let result = tx
+            .execute(statement, &params)
+            .await
+            .map_err(DatabaseError::Delegate);
+        match &result {
+            Ok(_) => self.stats.record(1, 0, start.elapsed()),
+            Err(err) if err.is_connection_failure() => client_manager.flush_pool(err),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Take a savepoint, so that a failure of the work that follows (such as that of an interceptor)
+    /// can be undone with [`Self::rollback_to_savepoint`] without undoing the earlier work (and
+    /// without leaving the transaction in the failed state, which would fail any further work).
+    ///
+    /// If the transaction hasn't started yet, there is nothing to preserve, so we don't start one
+    /// just to take a savepoint. Instead, rolling back to such a savepoint rolls back the whole
+    /// transaction (and the next work starts a new one).
+    pub async fn savepoint(&self) -> Result<Savepoint, DatabaseError> {
+        let mut state = self.state.lock().await;
+
+        if state.finalized || state.transaction.is_none() {
+            return Ok(Savepoint { name: None });
+        }
+
+        state.savepoint_count += 1;
+        let name = format!("exo_savepoint_{}", state.savepoint_count);
+        state
+            .execute_savepoint_command(&format!("SAVEPOINT {name}"))
+            .await?;
+
+        Ok(Savepoint { name: Some(name) })
+    }
+
+    /// Release a savepoint (keeping the work done since taking it)
+    pub async fn release_savepoint(&self, savepoint: Savepoint) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().await;
+
+        match savepoint.name {
+            Some(name) if !state.finalized => {
+                state
+                    .execute_savepoint_command(&format!("RELEASE SAVEPOINT {name}"))
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Undo the work done since taking the savepoint
+    pub async fn rollback_to_savepoint(
+        &mut self,
+        savepoint: Savepoint,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().await;
+
+        if state.finalized {
+            return Ok(());
+        }
+
+        match savepoint.name {
+            Some(name) => {
+                state
+                    .execute_savepoint_command(&format!("ROLLBACK TO SAVEPOINT {name}"))
+                    .await
+            }
+            None => {
+                if let Some(tx) = state.transaction.take() {
+                    tx.rollback().await.map_err(DatabaseError::Delegate)?;
+                }
+                // The next transaction needs the settings, too
+                if let Some(settings) = state.session_settings.take() {
+                    self.pending_session_settings = Some(settings);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether to execute the next read on the replica
+    async fn reads_from_replica(
+        &self,
+        state: &mut TransactionState,
+        replica_manager: &DatabaseClientManager,
+    ) -> Result<bool, DatabaseError> {
+        let mut routing = ReadRouting {
+            consistency: &self.read_consistency,
+            wrote: self.wrote,
+            in_transaction: state.transaction.is_some()
+                || self.pending_session_settings.is_some()
+                || self
+                    .needs_transaction
+                    .load(std::sync::atomic::Ordering::SeqCst),
+            replica_caught_up: state.replica_caught_up,
+        };
+
+        if routing.target().is_none()
+            && let ReadConsistency::AfterLsn(lsn) = &self.read_consistency
+        {
+            let caught_up = state.check_replica_caught_up(replica_manager, lsn).await?;
+            routing.replica_caught_up = Some(caught_up);
+        }
+
+        Ok(routing.target() == Some(ReadTarget::Replica))
+    }
+
+    fn check_statement_budget(&self, statements: usize) -> Result<(), DatabaseError> {
+        match self.statement_budget {
+            Some(budget) if self.stats.statements() + statements as u64 > budget => {
+                tracing::warn!(
+                    budget,
+                    executed = self.stats.statements(),
+                    "Aborting the request, since it exceeded its SQL statement budget"
+                );
+                Err(DatabaseError::StatementBudgetExceeded { budget })
+            }
+            _ => Ok(()),
+        }
     }
 
     /// Finalize the transaction (commit or rollback based on parameter)
     pub async fn finalize(&mut self, commit: bool) -> Result<(), tokio_postgres::Error> {
         let mut state = self.state.lock().await;
         if commit {
-            state.commit().await
+            state.commit().await?;
+            if self.wrote && self.replica_configured {
+                self.commit_lsn = state.current_lsn().await;
+            }
         } else {
-            state.rollback().await
+            state.rollback().await?;
         }
+        // Return the replica connection to the pool
+        state.replica_client = None;
+        Ok(())
     }
 }
 
@@ -110,6 +474,10 @@ impl TransactionState {
             client: None,
             transaction: None,
             finalized: false,
+            session_settings: None,
+            savepoint_count: 0,
+            replica_client: None,
+            replica_caught_up: None,
         }
     }
 
@@ -134,6 +502,69 @@ impl TransactionState {
         Ok(())
     }
 
+    async fn ensure_replica_client(
+        &mut self,
+        replica_manager: &DatabaseClientManager,
+    ) -> Result<&mut DatabaseClient, DatabaseError> {
+        if let Some(ref client) = self.replica_client
+            && client.is_closed()
+        {
+            tracing::warn!("Replica client was closed; dropping and reacquiring");
+            self.replica_client = None;
+        }
+
+        if self.replica_client.is_none() {
+            self.replica_client = Some(replica_manager.get_client().await?);
+        }
+        self.replica_client
+            .as_mut()
+            .ok_or_else(|| DatabaseError::Transaction("No replica client available".to_string()))
+    }
+
+    /// Whether the replica has replayed the primary's log up to the LSN (checked once per request,
+    /// so that all reads see the same state). A replica that isn't in recovery (such as a promoted
+    /// one) has nothing to replay.
+    async fn check_replica_caught_up(
+        &mut self,
+        replica_manager: &DatabaseClientManager,
+        lsn: &str,
+    ) -> Result<bool, DatabaseError> {
+        let client = self.ensure_replica_client(replica_manager).await?;
+        let row = client
+            .query_one(
+                "SELECT coalesce(pg_last_wal_replay_lsn() >= $1::text::pg_lsn, true)",
+                &[&lsn],
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Failed to check the replay position of the replica");
+                DatabaseError::Delegate(e)
+            })?;
+        let caught_up: bool = row.get(0);
+
+        if !caught_up {
+            tracing::debug!(lsn, "Replica hasn't caught up; reading from the primary");
+        }
+        self.replica_caught_up = Some(caught_up);
+        Ok(caught_up)
+    }
+
+    /// The current LSN of the primary (`None` if it couldn't be determined, which only means that
+    /// clients can't use it to read their writes from the replica)
+    async fn current_lsn(&self) -> Option<String> {
+        let client = self.client.as_ref()?;
+        match client
+            .query_one("SELECT pg_current_wal_lsn()::text", &[])
+            .await
+        {
+            Ok(row) => row.get(0),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to determine the LSN after committing");
+                None
+            }
+        }
+    }
+
     async fn ensure_transaction(
         &mut self,
     ) -> Result<&mut TransactionWrapper<'static>, DatabaseError> {
@@ -173,17 +604,56 @@ impl TransactionState {
         }
     }
 
+    async fn apply_session_settings(
+        &mut self,
+        settings: Vec<(String, String)>,
+    ) -> Result<(), DatabaseError> {
+        {
+            let (names, values): (Vec<&str>, Vec<&str>) = settings
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .unzip();
+
+            let tx = self.ensure_transaction().await?;
+            tx.query_typed(
+                "SELECT set_config(name, value, true) FROM unnest($1::text[], $2::text[]) AS settings(name, value)",
+                &[
+                    (&names as &(dyn ToSql + Sync), Type::TEXT_ARRAY),
+                    (&values, Type::TEXT_ARRAY),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Failed to apply the session settings");
+                DatabaseError::Delegate(e)
+            })?;
+        }
+
+        self.session_settings = Some(settings);
+
+        Ok(())
+    }
+
+    async fn execute_savepoint_command(&mut self, command: &str) -> Result<(), DatabaseError> {
+        let tx = self.ensure_transaction().await?;
+        tx.batch_execute(command).await.map_err(|e| {
+            tracing::error!(error = %e, command, "Failed to execute a savepoint command");
+            DatabaseError::Delegate(e)
+        })
+    }
+
     async fn execute_work(
         &mut self,
         database: &Database,
+        config: &ExecutionConfig,
         work: TransactionScript<'_>,
         needs_tx: bool,
-    ) -> Result<TransactionStepResult, DatabaseError> {
+    ) -> Result<(TransactionStepResult, usize), DatabaseError> {
         if work.needs_transaction() || needs_tx {
             let tx = self.ensure_transaction().await?;
-            work.execute(database, tx.deref_mut()).await
+            work.execute(database, config, tx.deref_mut()).await
         } else if let Some(ref mut client) = self.client {
-            work.execute(database, client.deref_mut()).await
+            work.execute(database, config, client.deref_mut()).await
         } else {
             Err(DatabaseError::Transaction(
                 "No database client available".to_string(),
@@ -210,6 +680,8 @@ impl TransactionState {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     // Note: These tests would require a real database connection to be comprehensive
@@ -232,6 +704,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_session_settings() {
+        let mut holder = TransactionHolder::new();
+        holder.set_session_settings(vec![("app.user_id".to_string(), "42".to_string())]);
+        holder.set_session_settings(vec![("app.user_id".to_string(), "7".to_string())]);
+
+        assert_eq!(
+            holder.pending_session_settings,
+            Some(vec![("app.user_id".to_string(), "42".to_string())])
+        );
+        assert!(
+            holder
+                .needs_transaction
+                .load(std::sync::atomic::Ordering::SeqCst)
+        );
+    }
+
+    #[test]
+    fn test_statement_budget() {
+        let holder = TransactionHolder::new().with_statement_budget(Some(3));
+        holder.stats.record(2, 0, Duration::ZERO);
+
+        assert!(holder.check_statement_budget(1).is_ok());
+        assert!(matches!(
+            holder.check_statement_budget(2),
+            Err(DatabaseError::StatementBudgetExceeded { budget: 3 })
+        ));
+        assert!(TransactionHolder::new().check_statement_budget(100).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_savepoint_before_transaction() {
+        let mut holder = TransactionHolder::new();
+
+        // Without a transaction, there is nothing to preserve
+        let savepoint = holder.savepoint().await.unwrap();
+        assert!(savepoint.name.is_none());
+        assert!(holder.rollback_to_savepoint(savepoint).await.is_ok());
+        assert_eq!(holder.state.lock().await.savepoint_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_commit_lsn_only_after_writes() {
+        let mut holder = TransactionHolder::new().with_read_consistency(ReadConsistency::Primary);
+        assert_eq!(holder.read_consistency, ReadConsistency::Primary);
+
+        // Nothing was written, so there is no position to read after
+        holder.replica_configured = true;
+        holder.finalize(true).await.unwrap();
+        assert_eq!(holder.commit_lsn(), None);
+    }
+
     #[tokio::test]
     async fn test_finalized_state_consistency() {
         let mut holder = TransactionHolder::new();
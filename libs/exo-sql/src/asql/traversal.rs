@@ -0,0 +1,48 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AbstractPredicate, sql::relation::RelationColumnPair};
+
+/// The default limit on the number of levels to traverse (guards against cycles in the data)
+pub const DEFAULT_MAX_TRAVERSAL_DEPTH: u32 = 100;
+
+/// The direction to follow a self-referential relation such as `parent`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalDirection {
+    /// Follow the relation in reverse (from a row to the rows that refer to it, i.e. children)
+    Descendants,
+    /// Follow the relation (from a row to the row it refers to, i.e. parent)
+    Ancestors,
+}
+
+/// The primary keys of the rows reachable from the rows matching `start` by repeatedly following a
+/// self-referential relation. Used as the right-hand side of an `IN` predicate such as:
+///
+/// ```text
+/// categories.id IN <traversal>
+/// ```
+///
+/// Rows matching `start` are not included (unless reachable through another path).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbstractTraversal {
+    /// The column pairs of the relation (self column: the referring column such as
+    /// `categories.parent_id`, foreign column: the referred column such as `categories.id`)
+    pub column_pairs: Vec<RelationColumnPair>,
+    pub direction: TraversalDirection,
+    /// The predicate to select the starting rows
+    pub start: AbstractPredicate,
+    /// The predicate that every visited row (including the starting rows) must satisfy. Rows that
+    /// don't satisfy it are not visited, so rows reachable only through them are excluded, too.
+    /// Typically, this is the access-control predicate.
+    pub level_predicate: AbstractPredicate,
+    /// The maximum number of levels to traverse
+    pub max_depth: u32,
+}
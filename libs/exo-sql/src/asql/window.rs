@@ -0,0 +1,49 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AbstractPredicate, ColumnId, TableId, sql::order::Ordering};
+
+/// A ranking window function
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFunction {
+    /// `rank()`: rank with gaps (1, 1, 3, ...)
+    Rank,
+    /// `dense_rank()`: rank without gaps (1, 1, 2, ...)
+    DenseRank,
+    /// `row_number()`: a unique sequential number (1, 2, 3, ...)
+    RowNumber,
+}
+
+impl WindowFunction {
+    pub fn sql_name(&self) -> &'static str {
+        match self {
+            WindowFunction::Rank => "rank",
+            WindowFunction::DenseRank => "dense_rank",
+            WindowFunction::RowNumber => "row_number",
+        }
+    }
+}
+
+/// A window function evaluated for each row of a table, such as the rank of a score within its
+/// game: `rank() OVER (PARTITION BY game_id ORDER BY points DESC)`.
+///
+/// The window is computed over all rows of the table matching `predicate` (and not just the rows
+/// selected by the enclosing query), so filtering or paginating the enclosing query doesn't change
+/// the value for a row.
+#[derive(Debug)]
+pub struct AbstractWindow {
+    pub table_id: TableId,
+    pub function: WindowFunction,
+    pub partition_by: Vec<ColumnId>,
+    pub order_by: Vec<(ColumnId, Ordering)>,
+    /// The rows participating in the window (typically, the access-control predicate)
+    pub predicate: AbstractPredicate,
+}
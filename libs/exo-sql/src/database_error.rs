@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use thiserror::Error;
+use tokio_postgres::error::SqlState;
 
 #[derive(Error, Debug)]
 pub enum DatabaseError {
@@ -45,12 +46,138 @@ pub enum DatabaseError {
 
     #[error("{0}")]
     Generic(String),
+
+    #[error("{0}")]
+    ColumnExtraction(#[from] crate::sql::column_extraction::ColumnExtractionError),
+
+    #[cfg(not(target_family = "wasm"))]
+    #[error("{0}")]
+    CircuitOpen(#[from] crate::sql::connect::circuit_breaker::CircuitOpenError),
+
+    #[error("The request exceeded its budget of {budget} SQL statements")]
+    StatementBudgetExceeded { budget: u64 },
 }
 
 impl DatabaseError {
     pub fn with_context(self, context: String) -> DatabaseError {
         DatabaseError::WithContext(context, Box::new(self))
     }
+
+    /// The underlying error, if the operation failed because the circuit to the database is open
+    #[cfg(not(target_family = "wasm"))]
+    pub fn circuit_open(&self) -> Option<&crate::sql::connect::circuit_breaker::CircuitOpenError> {
+        match self {
+            DatabaseError::CircuitOpen(error) => Some(error),
+            DatabaseError::WithContext(_, error) => error.circuit_open(),
+            _ => None,
+        }
+    }
+
+    /// The budget, if the operation was aborted because the request would have exceeded its
+    /// statement budget
+    pub fn statement_budget_exceeded(&self) -> Option<u64> {
+        match self {
+            DatabaseError::StatementBudgetExceeded { budget } => Some(*budget),
+            DatabaseError::WithContext(_, error) => error.statement_budget_exceeded(),
+            _ => None,
+        }
+    }
+
+    /// Did the operation fail because the connection to the database broke (for example, because
+    /// the database restarted or failed over)? Other connections in the pool are likely broken as
+    /// well.
+    pub fn is_connection_failure(&self) -> bool {
+        match self {
+            DatabaseError::Delegate(error) => {
+                error.is_closed() || connection_failure_sqlstate(error).is_some()
+            }
+            DatabaseError::WithContext(_, error) => error.is_connection_failure(),
+            _ => false,
+        }
+    }
+
+    /// The name of the violated constraint, if the operation failed because of a unique
+    /// constraint (or primary key) violation
+    pub fn unique_violation_constraint(&self) -> Option<&str> {
+        match self {
+            DatabaseError::Delegate(error) => error
+                .as_db_error()
+                .filter(|db_error| *db_error.code() == SqlState::UNIQUE_VIOLATION)
+                .and_then(|db_error| db_error.constraint()),
+            DatabaseError::WithContext(_, error) => error.unique_violation_constraint(),
+            _ => None,
+        }
+    }
+
+    /// The details of the violation, if the operation failed because of a foreign key constraint
+    /// violation
+    pub fn foreign_key_violation(&self) -> Option<ForeignKeyViolation<'_>> {
+        match self {
+            DatabaseError::Delegate(error) => {
+                let db_error = error
+                    .as_db_error()
+                    .filter(|db_error| *db_error.code() == SqlState::FOREIGN_KEY_VIOLATION)?;
+
+                // Postgres reports the violation as, for example:
+                // - `Key (author_id)=(42) is not present in table "authors".` (when inserting/updating the referencing row)
+                // - `Key (id)=(42) is still referenced from table "books".` (when deleting/updating the referenced row)
+                let detail = db_error.detail();
+                let kind = if detail.is_some_and(|detail| detail.contains("is still referenced")) {
+                    ForeignKeyViolationKind::StillReferenced
+                } else {
+                    ForeignKeyViolationKind::MissingReference
+                };
+
+                Some(ForeignKeyViolation {
+                    constraint: db_error.constraint()?,
+                    kind,
+                    key_values: detail.and_then(foreign_key_values),
+                })
+            }
+            DatabaseError::WithContext(_, error) => error.foreign_key_violation(),
+            _ => None,
+        }
+    }
+}
+
+/// The SQLSTATE of the error, if it reports that the server is shutting down, restarting, or
+/// otherwise unable to serve the connection
+pub(crate) fn connection_failure_sqlstate(error: &tokio_postgres::Error) -> Option<&SqlState> {
+    let code = error.as_db_error()?.code();
+
+    [
+        SqlState::ADMIN_SHUTDOWN,
+        SqlState::CRASH_SHUTDOWN,
+        SqlState::CANNOT_CONNECT_NOW,
+        SqlState::CONNECTION_FAILURE,
+        SqlState::CONNECTION_DOES_NOT_EXIST,
+    ]
+    .contains(code)
+    .then_some(code)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignKeyViolationKind {
+    /// The referencing row points to a row that doesn't exist
+    MissingReference,
+    /// The referenced row can't be removed (or its key changed), since other rows point to it
+    StillReferenced,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyViolation<'a> {
+    /// The name of the violated constraint
+    pub constraint: &'a str,
+    pub kind: ForeignKeyViolationKind,
+    /// The (comma-separated) key values as reported by Postgres (for example, `42`)
+    pub key_values: Option<&'a str>,
+}
+
+/// Extract the key values from the detail of a foreign key violation
+fn foreign_key_values(detail: &str) -> Option<&str> {
+    let (_, rest) = detail.split_once(")=(")?;
+    let (values, _) = rest.rsplit_once(") is ")?;
+    Some(values)
 }
 
 pub trait WithContext {
@@ -62,3 +189,51 @@ impl<T> WithContext for Result<T, DatabaseError> {
         self.map_err(|e| e.with_context(context))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foreign_key_values_from_detail() {
+        assert_eq!(
+            foreign_key_values(r#"Key (author_id)=(42) is not present in table "authors"."#),
+            Some("42")
+        );
+        assert_eq!(
+            foreign_key_values(r#"Key (id)=(42) is still referenced from table "books"."#),
+            Some("42")
+        );
+        assert_eq!(
+            foreign_key_values(
+                r#"Key (author_first, author_last)=(Jane, Doe) is not present in table "authors"."#
+            ),
+            Some("Jane, Doe")
+        );
+        assert_eq!(foreign_key_values("unexpected"), None);
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[tokio::test]
+    async fn connection_failures() {
+        use crate::testing::test_support::with_client;
+
+        with_client(|client| async move {
+            let syntax_error = client.simple_query("SELEC 1").await.unwrap_err();
+            assert!(!DatabaseError::Delegate(syntax_error).is_connection_failure());
+
+            // Terminating its own backend breaks the connection (as a database restart would)
+            let terminated = client
+                .simple_query("SELECT pg_terminate_backend(pg_backend_pid())")
+                .await
+                .unwrap_err();
+            let error = DatabaseError::Delegate(terminated);
+            assert!(error.is_connection_failure());
+            assert!(
+                DatabaseError::WithContext("Executing a query".to_string(), Box::new(error))
+                    .is_connection_failure()
+            );
+        })
+        .await;
+    }
+}
@@ -42,40 +42,57 @@ mod transform;
 #[cfg(feature = "test-support")]
 pub mod testing;
 
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+
 pub mod database_error;
 
 /// Public types at the root level of this crate
 pub use asql::{
     abstract_operation::AbstractOperation,
-    column_path::{ColumnPath, ColumnPathLink, PhysicalColumnPath},
+    column_path::{ColumnPath, ColumnPathLink, JsonFieldPath, PhysicalColumnPath},
     database_executor::DatabaseExecutor,
     delete::AbstractDelete,
+    execution_plan::{
+        OperationPlan, PlanNode, PlanNodeKind, PlanRecorder, PlanStep, PlanStepKind, plans_to_dot,
+    },
+    execution_stats::ExecutionStats,
     insert::{AbstractInsert, ColumnValuePair, InsertionElement, InsertionRow, NestedInsertion},
     order_by::{AbstractOrderBy, AbstractOrderByExpr},
     predicate::AbstractPredicate,
+    read_consistency::{
+        COMMIT_LSN_HEADER, READ_AFTER_LSN_HEADER, READ_CONSISTENCY_HEADER, ReadConsistency,
+        ReadConsistencyError,
+    },
     select::AbstractSelect,
     selection::{AliasedSelectionElement, Selection, SelectionCardinality, SelectionElement},
-    transaction_holder::TransactionHolder,
+    transaction_holder::{Savepoint, TransactionHolder},
+    traversal::{AbstractTraversal, DEFAULT_MAX_TRAVERSAL_DEPTH, TraversalDirection},
     update::{
         AbstractUpdate, NestedAbstractDelete, NestedAbstractInsert, NestedAbstractInsertSet,
         NestedAbstractUpdate,
     },
+    window::{AbstractWindow, WindowFunction},
 };
 
 pub use sql::{
     SQLBytes, SQLParam, SQLParamContainer,
     array_util::{self, ArrayEntry},
-    column::Column,
+    column::{Column, MaskStrategy},
+    column_extraction::{ColumnExtractionError, extract_column},
     connect::creation::{Connect, TransactionMode},
     connect::database_client::DatabaseClient,
     connect::database_client_manager::DatabaseClientManager,
     database::{Database, TableId},
-    function::Function,
+    function::{DateTruncUnit, Function, StringFunction},
     limit::Limit,
+    lock::RowLock,
     offset::Offset,
+    opaque_id::OpaqueIdKey,
     order::Ordering,
     physical_column::{
-        ColumnId, ColumnReference, PhysicalColumn, get_mto_relation_for_columns,
+        ColumnId, ColumnReference, ColumnStorage, CompressionMethod, PhysicalColumn,
+        ReferentialAction, StorageStrategy, get_mto_relation_for_columns,
         get_otm_relation_for_columns,
     },
     physical_column_type::{
@@ -84,15 +101,29 @@ pub use sql::{
         PhysicalColumnType, PhysicalColumnTypeExt, StringColumnType, TimeColumnType,
         TimestampColumnType, UuidColumnType, VectorColumnType,
     },
-    physical_table::{PhysicalEnum, PhysicalIndex, PhysicalTable},
+    physical_table::{PhysicalEnum, PhysicalIndex, PhysicalTable, SchemaRename},
     predicate::{CaseSensitivity, NumericComparator, ParamEquality, Predicate},
     relation::{ManyToOne, ManyToOneId, OneToMany, OneToManyId, RelationColumnPair, RelationId},
     schema_object::SchemaObjectName,
+    sql_expression::SqlExpression,
     vector::{DEFAULT_VECTOR_SIZE, VectorDistanceFunction},
 };
 
+pub use sql::statement_log::{
+    DEFAULT_MAX_PARAM_LENGTH, STATEMENT_LOG_TARGET, StatementLogConfig, StatementLogFormat,
+};
+pub use sql::transaction::{DEFAULT_IN_LIST_CHUNK_SIZE, ExecutionConfig};
+
 #[cfg(feature = "pool")]
-pub use sql::connect::database_pool::{PoolConfig, PoolStatus};
+pub use sql::connect::{
+    database_pool::{PoolConfig, PoolStatus},
+    pool_sizing::AdaptivePoolConfig,
+};
+
+#[cfg(not(target_family = "wasm"))]
+pub use sql::connect::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerStats, CircuitOpenError, CircuitState,
+};
 
 #[cfg(feature = "bigdecimal")]
 pub use pg_bigdecimal::BigDecimal;
@@ -16,7 +16,10 @@ use crate::sql::physical_column_type::{
     IntColumnType, JsonColumnType, NumericColumnType, PhysicalColumnType, StringColumnType,
     TimeColumnType, TimestampColumnType, VectorColumnType,
 };
-use crate::{Database, PhysicalColumn, SchemaObjectName};
+use crate::{
+    ColumnStorage, CompressionMethod, Database, PhysicalColumn, ReferentialAction,
+    SchemaObjectName, StorageStrategy,
+};
 
 use super::DebugPrintTo;
 use super::enum_spec::EnumSpec;
@@ -36,6 +39,7 @@ pub struct ColumnSpec {
     pub is_nullable: bool,
     pub unique_constraints: Vec<String>,
     pub default_value: Option<ColumnDefault>,
+    pub storage: ColumnStorage,
 }
 
 impl PartialEq for ColumnSpec {
@@ -47,6 +51,7 @@ impl PartialEq for ColumnSpec {
             && self.is_nullable == other.is_nullable
             && self.unique_constraints == other.unique_constraints
             && self.default_value == other.default_value
+            && self.storage == other.storage
     }
 }
 
@@ -88,6 +93,9 @@ impl DebugPrintTo for ColumnSpec {
         if self.default_value.is_some() {
             attributes.push("DEFAULT");
         }
+        if !self.storage.is_default() {
+            attributes.push("STORAGE");
+        }
 
         let attr_str = if attributes.is_empty() {
             "".to_string()
@@ -413,6 +421,8 @@ pub struct ColumnReferenceSpec {
     pub foreign_pk_column_name: String,
     pub foreign_pk_type: Box<dyn PhysicalColumnType>,
     pub group_name: String,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
 }
 
 impl PartialEq for ColumnReferenceSpec {
@@ -421,6 +431,22 @@ impl PartialEq for ColumnReferenceSpec {
             && self.foreign_pk_column_name == other.foreign_pk_column_name
             && self.foreign_pk_type.equals(other.foreign_pk_type.as_ref())
             && self.group_name == other.group_name
+            && self.on_delete == other.on_delete
+            && self.on_update == other.on_update
+    }
+}
+
+impl ColumnReferenceSpec {
+    /// The `ON DELETE`/`ON UPDATE` clauses for the foreign key constraint (empty for the default actions)
+    pub(super) fn referential_action_clauses(&self) -> String {
+        let mut clauses = String::new();
+        if let Some(action) = self.on_delete.sql() {
+            clauses.push_str(&format!(" ON DELETE {action}"));
+        }
+        if let Some(action) = self.on_update.sql() {
+            clauses.push_str(&format!(" ON UPDATE {action}"));
+        }
+        clauses
     }
 }
 
@@ -435,19 +461,24 @@ impl DebugPrintTo for ColumnReferenceSpec {
         let indent_str = " ".repeat(indent);
         writeln!(
             writer,
-            "{}ColumnReference: FK -> {}.{} (type: {})",
+            "{}ColumnReference: FK -> {}.{} (type: {}){}",
             indent_str,
             self.foreign_table_name.fully_qualified_name(),
             self.foreign_pk_column_name,
-            self.foreign_pk_type.type_string()
+            self.foreign_pk_type.type_string(),
+            self.referential_action_clauses()
         )
     }
 }
 
 const COLUMNS_TYPE_QUERY: &str = "
-  SELECT pg_class.relname as table_name, attname as column_name, format_type(atttypid, atttypmod), attndims, attnotnull FROM pg_attribute 
+  SELECT pg_class.relname as table_name, attname as column_name, format_type(atttypid, atttypmod), attndims, attnotnull,
+      CASE WHEN pg_type.typstorage = 'x' AND attstorage <> pg_type.typstorage THEN attstorage::text END AS storage,
+      to_jsonb(pg_attribute) ->> 'attcompression' AS compression
+    FROM pg_attribute 
     LEFT JOIN pg_class ON pg_attribute.attrelid = pg_class.oid 
     LEFT JOIN pg_namespace ON pg_class.relnamespace = pg_namespace.oid
+    LEFT JOIN pg_type ON pg_attribute.atttypid = pg_type.oid
   WHERE attnum > 0 AND attisdropped = false AND pg_namespace.nspname = $1";
 
 const COLUMNS_DEFAULT_QUERY: &str = r#"
@@ -530,6 +561,7 @@ impl ColumnSpec {
             default_value,
             not_null,
             db_type,
+            storage,
         } = table_attributes
             .get(column_name)
             .ok_or(DatabaseError::Generic(format!(
@@ -549,6 +581,7 @@ impl ColumnSpec {
                 is_nullable: !not_null,
                 unique_constraints,
                 default_value: default_value.clone(),
+                storage: *storage,
             }),
             issues: vec![],
         })
@@ -588,6 +621,21 @@ impl ColumnSpec {
         }
     }
 
+    /// The statement to set the storage parameters declared in the model (after creating the column)
+    pub(super) fn storage_sql(&self, table_name: &SchemaObjectName) -> Option<String> {
+        let actions = self
+            .storage
+            .alter_actions(&ColumnStorage::default(), &self.name);
+
+        (!actions.is_empty()).then(|| {
+            format!(
+                "ALTER TABLE {} {};",
+                table_name.sql_name(),
+                actions.join(", ")
+            )
+        })
+    }
+
     pub fn diff<'a>(
         &'a self,
         new: &'a Self,
@@ -608,10 +656,11 @@ impl ColumnSpec {
         }
 
         // If the column type differs only in reference type, that is taken care by table-level migration
-        if (!type_same && !self.differs_only_in_reference_column(new))
+        let recreate = (!type_same && !self.differs_only_in_reference_column(new))
             || (!reference_specs_same && !self.differs_only_in_reference_column(new))
-            || !is_pk_same
-        {
+            || !is_pk_same;
+
+        if recreate {
             changes.push(SchemaOp::DeleteColumn {
                 table: self_table,
                 column: self,
@@ -656,6 +705,15 @@ impl ColumnSpec {
             }
         }
 
+        // A recreated column gets its storage parameters when created
+        if !recreate && self.storage != new.storage {
+            changes.push(SchemaOp::SetColumnStorage {
+                table: new_table,
+                column: new,
+                old_storage: &self.storage,
+            });
+        }
+
         changes
     }
 
@@ -674,6 +732,8 @@ impl ColumnSpec {
                             foreign_pk_column_name: foreign_column.name.clone(),
                             foreign_pk_type: foreign_column.typ.clone(),
                             group_name: column_reference.group_name.clone(),
+                            on_delete: column_reference.on_delete,
+                            on_update: column_reference.on_update,
                         }
                     })
                     .collect()
@@ -689,6 +749,7 @@ impl ColumnSpec {
             is_nullable: column.is_nullable,
             unique_constraints: column.unique_constraints,
             default_value: column.default_value,
+            storage: column.storage,
             reference_specs,
         }
     }
@@ -741,6 +802,16 @@ impl ColumnSpec {
             let table_name: String = row.get("table_name");
             let column_name: String = row.get("column_name");
             let not_null: bool = row.get("attnotnull");
+            let storage = ColumnStorage {
+                strategy: row
+                    .get::<_, Option<String>>("storage")
+                    .and_then(|code| StorageStrategy::from_code(&code)),
+                // `attcompression` exists only in Postgres 14 and later (hence selected through
+                // `to_jsonb`), and is empty for the default compression
+                compression: row
+                    .get::<_, Option<String>>("compression")
+                    .and_then(|code| CompressionMethod::from_code(&code)),
+            };
 
             let table_name = SchemaObjectName::new_with_schema_name(table_name, schema_name);
 
@@ -786,6 +857,7 @@ impl ColumnSpec {
                     db_type,
                     not_null,
                     default_value: None,
+                    storage,
                 },
             );
         }
@@ -864,6 +936,7 @@ pub struct ColumnAttribute {
     pub default_value: Option<ColumnDefault>,
     pub db_type: Option<Box<dyn PhysicalColumnType>>,
     pub not_null: bool,
+    pub storage: ColumnStorage,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
@@ -13,7 +13,8 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::{
-    SchemaObjectName, database_error::DatabaseError, sql::connect::database_client::DatabaseClient,
+    ReferentialAction, SchemaObjectName, database_error::DatabaseError,
+    sql::connect::database_client::DatabaseClient,
 };
 
 #[derive(Debug)]
@@ -33,6 +34,8 @@ pub(super) struct ForeignKeyConstraint {
     pub(super) constraint_name: String,
     pub(super) foreign_table: SchemaObjectName,
     pub(super) column_pairs: Vec<ForeignKeyConstraintColumnPair>,
+    pub(super) on_delete: ReferentialAction,
+    pub(super) on_update: ReferentialAction,
 }
 
 #[derive(Debug)]
@@ -52,6 +55,10 @@ lazy_static! {
     static ref PRIMARY_KEY_RE: Regex = Regex::new(r"PRIMARY KEY \(([^)]+)\)").unwrap();
     static ref FOREIGN_KEY_RE: Regex =
         Regex::new(r"FOREIGN KEY \(([^)]+)\) REFERENCES ([^\(]+)\(([^)]+)\)").unwrap();
+    static ref ON_DELETE_RE: Regex =
+        Regex::new(r"ON DELETE (NO ACTION|CASCADE|RESTRICT|SET NULL|SET DEFAULT)").unwrap();
+    static ref ON_UPDATE_RE: Regex =
+        Regex::new(r"ON UPDATE (NO ACTION|CASCADE|RESTRICT|SET NULL|SET DEFAULT)").unwrap();
     static ref UNIQUE_RE: Regex = Regex::new(r"UNIQUE \(([^)]+)\)").unwrap();
     static ref LIST_RE: Regex = Regex::new(r"(\w+)").unwrap();
 }
@@ -148,6 +155,8 @@ impl Constraints {
                         )
                         .collect(),
                     foreign_table,
+                    on_delete: Self::parse_referential_action(&ON_DELETE_RE, condef),
+                    on_update: Self::parse_referential_action(&ON_UPDATE_RE, condef),
                 }
             })
             .collect::<Vec<_>>();
@@ -172,6 +181,15 @@ impl Constraints {
         })
     }
 
+    // Postgres omits the clause for the default action (`NO ACTION`). We don't support `SET DEFAULT`,
+    // so treat it as the default action.
+    fn parse_referential_action(action_re: &Regex, condef: &str) -> ReferentialAction {
+        action_re
+            .captures(condef)
+            .and_then(|captures| ReferentialAction::from_sql(&captures[1]))
+            .unwrap_or_default()
+    }
+
     fn parse_column_list(column_list: &str) -> Vec<String> {
         // Basically just split the string on commas and remove the quotes (the regex takes care of the quotes)
         LIST_RE
@@ -10,7 +10,8 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
-    Database, ManyToOne, PhysicalColumn, PhysicalIndex, PhysicalTable, SchemaObjectName, TableId,
+    Database, ManyToOne, PhysicalColumn, PhysicalIndex, PhysicalTable, SchemaObjectName,
+    SchemaRename, TableId,
     database_error::DatabaseError,
     schema::column_spec::ColumnSpec,
     sql::{
@@ -34,8 +35,10 @@ use super::{
 
 const SCHEMAS_QUERY: &str = "SELECT schema_name FROM information_schema.schemata WHERE schema_name != 'information_schema' AND schema_name NOT LIKE 'pg_%' ORDER BY schema_name";
 
-const TABLE_NAMES_QUERY: &str =
-    "SELECT table_name FROM information_schema.tables WHERE table_schema = $1 ORDER BY table_name";
+// Partitions of a partitioned table are managed along with it, so they aren't listed as tables
+const TABLE_NAMES_QUERY: &str = "SELECT table_name FROM information_schema.tables WHERE table_schema = $1 
+  AND NOT EXISTS (SELECT 1 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace WHERE c.relispartition AND c.relname = table_name AND n.nspname = table_schema)
+  ORDER BY table_name";
 
 const ENUM_NAMES_QUERY: &str = "SELECT t.typname AS enum_name FROM pg_type t JOIN pg_catalog.pg_namespace n ON n.oid = t.typnamespace 
   WHERE t.typtype = 'e'  AND n.nspname = $1 ORDER BY enum_name";
@@ -50,6 +53,8 @@ pub struct DatabaseSpec {
     pub tables: Vec<TableSpec>,
     pub enums: Vec<EnumSpec>,
     pub functions: Vec<FunctionSpec>,
+    /// Renames declared in the model (always empty for a spec extracted from a live database)
+    pub renames: Vec<SchemaRename>,
 }
 
 impl DatabaseSpec {
@@ -58,6 +63,7 @@ impl DatabaseSpec {
             tables,
             enums,
             functions,
+            renames: vec![],
         }
     }
 
@@ -153,6 +159,8 @@ impl DatabaseSpec {
                         unique_constraints: column_spec.unique_constraints.to_owned(),
                         default_value: column_spec.default_value.to_owned(),
                         update_sync: false, // There is no good way to know from the database spec if a column should be updated on sync
+                        storage: column_spec.storage,
+                        opaque_id: false, // An API concern, not known to the database
                         column_references: None,
                     }
                 })
@@ -187,6 +195,8 @@ impl DatabaseSpec {
                             ColumnReference {
                                 foreign_column_id,
                                 group_name: ref_spec.group_name.clone(),
+                                on_delete: ref_spec.on_delete,
+                                on_update: ref_spec.on_update,
                             }
                         })
                         .collect()
@@ -294,14 +304,36 @@ impl DatabaseSpec {
 
                 all_function_specs.extend(function_specs);
 
+                let mut columns: Vec<ColumnSpec> = table
+                    .columns
+                    .clone()
+                    .into_iter()
+                    .map(|c| ColumnSpec::from_physical(c, database))
+                    .collect();
+
+                // Postgres requires the primary key and unique constraints of a partitioned
+                // table to include the partition column
+                if let Some(partition) = &table.partition {
+                    let unique_constraints: Vec<String> = columns
+                        .iter()
+                        .flat_map(|c| c.unique_constraints.iter().cloned())
+                        .collect();
+
+                    if let Some(partition_column) =
+                        columns.iter_mut().find(|c| c.name == partition.column_name)
+                    {
+                        partition_column.is_pk = true;
+                        for constraint in unique_constraints {
+                            if !partition_column.unique_constraints.contains(&constraint) {
+                                partition_column.unique_constraints.push(constraint);
+                            }
+                        }
+                    }
+                }
+
                 TableSpec::new(
                     table.name.clone(),
-                    table
-                        .columns
-                        .clone()
-                        .into_iter()
-                        .map(|c| ColumnSpec::from_physical(c, database))
-                        .collect(),
+                    columns,
                     table
                         .indices
                         .clone()
@@ -315,16 +347,23 @@ impl DatabaseSpec {
                     trigger_specs,
                     table.managed,
                 )
+                .with_partition(table.partition.clone())
             })
             .collect();
 
         let enums = database
             .enums()
             .into_iter()
-            .map(|(_, enum_)| EnumSpec::new(enum_.name.clone(), enum_.variants.clone()))
+            .map(|(_, enum_)| {
+                EnumSpec::new(enum_.name.clone(), enum_.variants.clone())
+                    .with_variant_mappings(enum_.variant_mappings.clone())
+            })
             .collect();
 
-        DatabaseSpec::new(tables, enums, all_function_specs)
+        DatabaseSpec {
+            renames: database.renames.clone(),
+            ..DatabaseSpec::new(tables, enums, all_function_specs)
+        }
     }
 
     /// Creates a new schema specification from an SQL database.
@@ -423,11 +462,7 @@ impl DatabaseSpec {
         issues.extend(functions_issues);
 
         Ok(WithIssues {
-            value: DatabaseSpec {
-                tables,
-                enums,
-                functions,
-            },
+            value: DatabaseSpec::new(tables, enums, functions),
             issues,
         })
     }
@@ -480,8 +515,8 @@ impl DatabaseSpec {
 
     pub fn with_table_renamed<'a>(
         &mut self,
-        old_name: &'a SchemaObjectName,
-        new_name: &'a SchemaObjectName,
+        old_name: &SchemaObjectName,
+        new_name: &SchemaObjectName,
     ) -> Vec<SchemaOp<'a>> {
         let mut ops = vec![];
 
@@ -519,6 +554,99 @@ impl DatabaseSpec {
 
         ops
     }
+
+    pub fn with_column_renamed<'a>(
+        &mut self,
+        table_name: &SchemaObjectName,
+        old_name: &str,
+        new_name: &str,
+    ) -> Vec<SchemaOp<'a>> {
+        let mut ops = vec![];
+
+        self.tables.iter_mut().for_each(|table| {
+            if &table.name == table_name {
+                if let Some(column) = table
+                    .columns
+                    .iter_mut()
+                    .find(|column| column.name == old_name)
+                {
+                    column.name = new_name.to_string();
+                    ops.push(SchemaOp::RenameColumn {
+                        table_name: table_name.clone(),
+                        name: old_name.to_string(),
+                        new_name: new_name.to_string(),
+                    });
+                }
+
+                table.indices.iter_mut().for_each(|index| {
+                    if index.columns.remove(old_name) {
+                        index.columns.insert(new_name.to_string());
+                    }
+                });
+            }
+
+            // Foreign keys referring to the renamed column follow it
+            table.columns.iter_mut().for_each(|column| {
+                if let Some(reference_specs) = &mut column.reference_specs {
+                    for reference_spec in reference_specs {
+                        if &reference_spec.foreign_table_name == table_name
+                            && reference_spec.foreign_pk_column_name == old_name
+                        {
+                            reference_spec.foreign_pk_column_name = new_name.to_string();
+                        }
+                    }
+                }
+            });
+        });
+
+        ops
+    }
+
+    /// Apply the renames declared in `renames` to this spec (typically, extracted from a live
+    /// database), returning the operations that perform them.
+    ///
+    /// A rename applies only if the old name exists and the new name doesn't, so a rename that has
+    /// already been migrated is ignored (and the `@renamedFrom` annotation may be removed at any
+    /// point after that).
+    pub fn with_renames_applied<'a>(&mut self, renames: &[SchemaRename]) -> Vec<SchemaOp<'a>> {
+        let mut ops = vec![];
+
+        let has_table = |spec: &Self, name: &SchemaObjectName| {
+            spec.tables.iter().any(|table| &table.name == name)
+        };
+
+        // Rename tables first, so that column renames can refer to the new table names
+        for rename in renames {
+            if let SchemaRename::Table { old_name, new_name } = rename
+                && has_table(self, old_name)
+                && !has_table(self, new_name)
+            {
+                ops.extend(self.with_table_renamed(old_name, new_name));
+            }
+        }
+
+        for rename in renames {
+            if let SchemaRename::Column {
+                table_name,
+                old_name,
+                new_name,
+            } = rename
+            {
+                let has_column = |name: &str| {
+                    self.tables.iter().any(|table| {
+                        &table.name == table_name
+                            && table.columns.iter().any(|column| column.name == name)
+                    })
+                };
+
+                if has_column(old_name) && !has_column(new_name) {
+                    ops.extend(self.with_column_renamed(table_name, old_name, new_name));
+                }
+            }
+        }
+
+        ops
+    }
 }
 
 impl DebugPrintTo for DatabaseSpec {
@@ -599,6 +727,7 @@ mod tests {
                             default_value: Some(ColumnDefault::Autoincrement(
                                 ColumnAutoincrement::Serial,
                             )),
+                            storage: Default::default(),
                         },
                         ColumnSpec {
                             name: "name".into(),
@@ -610,6 +739,7 @@ mod tests {
                             is_nullable: true,
                             unique_constraints: vec![],
                             default_value: None,
+                            storage: Default::default(),
                         },
                         ColumnSpec {
                             name: "email".into(),
@@ -619,6 +749,7 @@ mod tests {
                             is_nullable: true,
                             unique_constraints: vec![],
                             default_value: None,
+                            storage: Default::default(),
                         },
                     ],
                     vec![],
@@ -651,6 +782,7 @@ mod tests {
                         is_nullable: true,
                         unique_constraints: vec![],
                         default_value: None,
+                        storage: Default::default(),
                     }],
                     vec![],
                     vec![],
@@ -687,6 +819,7 @@ mod tests {
                             is_nullable: true,
                             unique_constraints: vec![],
                             default_value: None,
+                            storage: Default::default(),
                         },
                         ColumnSpec {
                             name: "just_precision".into(),
@@ -699,6 +832,7 @@ mod tests {
                             is_nullable: true,
                             unique_constraints: vec![],
                             default_value: None,
+                            storage: Default::default(),
                         },
                         ColumnSpec {
                             name: "no_precision_and_scale".into(),
@@ -711,6 +845,7 @@ mod tests {
                             is_nullable: true,
                             unique_constraints: vec![],
                             default_value: None,
+                            storage: Default::default(),
                         },
                     ],
                     vec![],
@@ -10,11 +10,14 @@
 use crate::SchemaObjectName;
 use crate::database_error::DatabaseError;
 use crate::sql::connect::database_client::DatabaseClient;
+use crate::sql::physical_column_type::{ArrayColumnType, EnumColumnType};
 
 use super::DebugPrintTo;
+use super::column_spec::{ColumnDefault, ColumnSpec};
 use super::issue::WithIssues;
 use super::op::SchemaOp;
 use super::statement::SchemaStatement;
+use super::table_spec::TableSpec;
 
 const ENUM_VARIANTS_QUERY: &str = "SELECT e.enumlabel AS enum_value FROM pg_type t JOIN pg_enum e ON t.oid = e.enumtypid JOIN pg_catalog.pg_namespace n ON n.oid = t.typnamespace
   WHERE n.nspname = $1 AND t.typname = $2 ORDER BY e.enumsortorder;";
@@ -23,11 +26,57 @@ const ENUM_VARIANTS_QUERY: &str = "SELECT e.enumlabel AS enum_value FROM pg_type
 pub struct EnumSpec {
     pub name: SchemaObjectName,
     pub variants: Vec<String>,
+    /// Removed variants along with the variants to rewrite their values to (from `@mapsTo`)
+    pub variant_mappings: Vec<(String, String)>,
+}
+
+/// A column whose values are of an enum (or arrays of it)
+#[derive(Debug)]
+pub struct EnumColumn<'a> {
+    pub table: &'a TableSpec,
+    pub column: &'a ColumnSpec,
+    pub is_array: bool,
+}
+
+impl<'a> EnumColumn<'a> {
+    /// The column, if it uses the enum
+    pub fn of(table: &'a TableSpec, column: &'a ColumnSpec, enum_: &EnumSpec) -> Option<Self> {
+        let typ = column.typ.as_any();
+
+        let (enum_type, is_array) = match typ.downcast_ref::<EnumColumnType>() {
+            Some(enum_type) => (enum_type, false),
+            None => (
+                typ.downcast_ref::<ArrayColumnType>()?
+                    .typ
+                    .as_any()
+                    .downcast_ref::<EnumColumnType>()?,
+                true,
+            ),
+        };
+
+        let same_enum = enum_type.enum_name.schema_name() == enum_.name.schema_name()
+            && enum_type.enum_name.name == enum_.name.name;
+
+        same_enum.then_some(Self {
+            table,
+            column,
+            is_array,
+        })
+    }
 }
 
 impl EnumSpec {
     pub fn new(name: SchemaObjectName, variants: Vec<String>) -> Self {
-        Self { name, variants }
+        Self {
+            name,
+            variants,
+            variant_mappings: vec![],
+        }
+    }
+
+    pub fn with_variant_mappings(mut self, variant_mappings: Vec<(String, String)>) -> Self {
+        self.variant_mappings = variant_mappings;
+        self
     }
 
     pub fn sql_name(&self) -> String {
@@ -50,24 +99,169 @@ impl EnumSpec {
         let issues = Vec::new();
 
         Ok(WithIssues {
-            value: EnumSpec { name, variants },
+            value: EnumSpec::new(name, variants),
             issues,
         })
     }
 
-    pub fn diff<'a>(&'a self, new: &'a Self) -> Vec<SchemaOp<'a>> {
+    /// The changes to turn this enum into the new one, given the columns that use this enum
+    pub fn diff<'a>(&'a self, new: &'a Self, columns: Vec<EnumColumn<'a>>) -> Vec<SchemaOp<'a>> {
         let mut changes = Vec::new();
 
-        let existing_variants = &self.variants;
-        let new_variants = &new.variants;
-
-        if existing_variants != new_variants {
-            changes.push(SchemaOp::CreateEnum { enum_: self });
+        if self.variants != new.variants {
+            changes.push(SchemaOp::AlterEnum {
+                old: self,
+                new,
+                columns,
+            });
         }
 
         changes
     }
 
+    /// The variants of this enum that the new one removes, along with the variants their values
+    /// are rewritten to (`None` if the new enum doesn't map them)
+    pub fn removed_variants<'a>(&'a self, new: &'a Self) -> Vec<(&'a str, Option<&'a str>)> {
+        self.variants
+            .iter()
+            .filter(|variant| !new.variants.contains(variant))
+            .map(|variant| {
+                let mapped_to = new
+                    .variant_mappings
+                    .iter()
+                    .find(|(from, _)| from == variant)
+                    .map(|(_, to)| to.as_str());
+                (variant.as_str(), mapped_to)
+            })
+            .collect()
+    }
+
+    /// Change the variants by recreating the enum (Postgres can't remove or reorder variants, and
+    /// variants added to an existing enum can't be used in the same transaction), converting
+    /// the columns that use it and rewriting the values of the mapped variants.
+    ///
+    /// If a column has values of a removed variant that isn't mapped, the migration fails (instead
+    /// of losing those values).
+    pub(super) fn alteration_sql(&self, new: &Self, columns: &[EnumColumn]) -> SchemaStatement {
+        let removed_variants = self.removed_variants(new);
+        let mappings: Vec<_> = removed_variants
+            .iter()
+            .filter_map(|(from, to)| to.map(|to| (*from, to)))
+            .collect();
+        let unmapped: Vec<_> = removed_variants
+            .iter()
+            .filter(|(_, to)| to.is_none())
+            .map(|(from, _)| *from)
+            .collect();
+
+        let enum_name = self.sql_name();
+        let old_enum_name = SchemaObjectName {
+            name: format!("{}_old", self.name.name),
+            schema: self.name.schema.clone(),
+        };
+
+        let mut lines = vec!["DO $$".to_string(), "BEGIN".to_string()];
+
+        if !unmapped.is_empty() {
+            let unmapped_list = quoted_list(&unmapped);
+            for EnumColumn {
+                table,
+                column,
+                is_array,
+            } in columns
+            {
+                let condition = if *is_array {
+                    format!("\"{}\"::text[] && ARRAY[{unmapped_list}]", column.name)
+                } else {
+                    format!("\"{}\"::text IN ({unmapped_list})", column.name)
+                };
+                lines.push(format!(
+                    "  IF EXISTS (SELECT 1 FROM {} WHERE {condition}) THEN",
+                    table.sql_name()
+                ));
+                lines.push(format!(
+                    "    RAISE EXCEPTION 'Column {}.\"{}\" has values of the removed variants {} of the enum {enum_name}. Map them to other variants with @mapsTo.';",
+                    table.sql_name(),
+                    column.name,
+                    unmapped.join(", "),
+                ));
+                lines.push("  END IF;".to_string());
+            }
+        }
+
+        lines.push(format!(
+            "  ALTER TYPE {enum_name} RENAME TO \"{}\";",
+            old_enum_name.name
+        ));
+        lines.push(format!("  {}", new.creation_sql().statement));
+
+        for EnumColumn {
+            table,
+            column,
+            is_array,
+        } in columns
+        {
+            let table_name = table.sql_name();
+            let column_name = &column.name;
+
+            // Defaults of the old type would prevent changing the column's type
+            let default_value = column.default_value.as_ref().and_then(|default_value| {
+                match default_value {
+                    ColumnDefault::Enum(value) => {
+                        let value = mappings
+                            .iter()
+                            .find(|(from, _)| *from == value.as_str())
+                            .map_or(value.as_str(), |(_, to)| *to);
+                        // A default of an unmapped removed variant is replaced by the model's
+                        // (through the column's migration)
+                        (!unmapped.contains(&value)).then(|| format!("'{value}'"))
+                    }
+                    default_value => default_value.to_sql(),
+                }
+            });
+            if column.default_value.is_some() {
+                lines.push(format!(
+                    "  ALTER TABLE {table_name} ALTER COLUMN \"{column_name}\" DROP DEFAULT;"
+                ));
+            }
+
+            let conversion = if *is_array {
+                mappings
+                    .iter()
+                    .fold(format!("\"{column_name}\"::text[]"), |value, (from, to)| {
+                        format!("array_replace({value}, '{from}', '{to}')")
+                    })
+            } else if mappings.is_empty() {
+                format!("\"{column_name}\"::text")
+            } else {
+                let cases: String = mappings
+                    .iter()
+                    .map(|(from, to)| format!(" WHEN '{from}' THEN '{to}'"))
+                    .collect();
+                format!("CASE \"{column_name}\"::text{cases} ELSE \"{column_name}\"::text END")
+            };
+            let array_suffix = if *is_array { "[]" } else { "" };
+            lines.push(format!(
+                "  ALTER TABLE {table_name} ALTER COLUMN \"{column_name}\" TYPE {enum_name}{array_suffix} USING ({conversion})::{enum_name}{array_suffix};"
+            ));
+
+            if let Some(default_value) = default_value {
+                lines.push(format!(
+                    "  ALTER TABLE {table_name} ALTER COLUMN \"{column_name}\" SET DEFAULT {default_value};"
+                ));
+            }
+        }
+
+        lines.push(format!("  DROP TYPE {};", old_enum_name.sql_name()));
+        lines.push("END $$;".to_string());
+
+        SchemaStatement {
+            statement: lines.join("\n"),
+            pre_statements: vec![],
+            post_statements: vec![],
+        }
+    }
+
     /// Converts the table specification to SQL statements.
     pub(super) fn creation_sql(&self) -> SchemaStatement {
         let table_name = self.sql_name();
@@ -95,6 +289,14 @@ impl EnumSpec {
     }
 }
 
+fn quoted_list(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{value}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl DebugPrintTo for EnumSpec {
     fn debug_print_to<W: std::io::Write>(
         &self,
@@ -0,0 +1,222 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Constraints Postgres places on identifiers (table, column, index, constraint names, etc.).
+
+/// The maximum length (in bytes) of an identifier. Postgres silently truncates longer identifiers
+/// (`NAMEDATALEN - 1`), so a longer name would never match the one we expect in the database.
+pub const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// Length of the hash suffix (including the separating underscore) added to truncated identifiers
+const HASH_SUFFIX_LENGTH: usize = 9;
+
+/// Fit a generated identifier into [`MAX_IDENTIFIER_LENGTH`] bytes.
+///
+/// Identifiers that already fit are returned as is. Longer ones are truncated and suffixed with a
+/// hash of the full name, so the result is deterministic (the same name maps to the same
+/// identifier across builds) and identifiers sharing a long prefix don't collide. For example,
+/// `<long prefix>_venue_id_idx` becomes `<truncated prefix>_1a2b3c4d`.
+pub fn fit_identifier(name: &str) -> String {
+    if name.len() <= MAX_IDENTIFIER_LENGTH {
+        return name.to_string();
+    }
+
+    let mut prefix_length = MAX_IDENTIFIER_LENGTH - HASH_SUFFIX_LENGTH;
+    while !name.is_char_boundary(prefix_length) {
+        prefix_length -= 1;
+    }
+
+    format!("{}_{:08x}", &name[..prefix_length], fnv1a_hash(name))
+}
+
+/// Is the identifier a reserved word in Postgres (thus, usable only when quoted)?
+pub fn is_reserved_word(name: &str) -> bool {
+    RESERVED_WORDS
+        .binary_search(&name.to_ascii_lowercase().as_str())
+        .is_ok()
+}
+
+/// Can the identifier be used unquoted without changing its meaning? That is, it is lowercase
+/// (Postgres folds unquoted identifiers to lowercase), made up of letters, digits, and
+/// underscores, and isn't a reserved word.
+pub fn is_plain_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_');
+
+    valid_start
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !is_reserved_word(name)
+}
+
+/// A 32-bit FNV-1a hash (unlike `std`'s hashers, guaranteed to be stable across Rust versions)
+fn fnv1a_hash(value: &str) -> u32 {
+    value.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Keywords that Postgres reserves (including those that can't be a function or type name), from
+/// https://www.postgresql.org/docs/current/sql-keywords-appendix.html (sorted for binary search)
+const RESERVED_WORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_words_are_sorted() {
+        assert!(RESERVED_WORDS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn fit_identifier_keeps_short_names() {
+        assert_eq!(fit_identifier("concerts"), "concerts");
+
+        let max_length_name = "a".repeat(MAX_IDENTIFIER_LENGTH);
+        assert_eq!(fit_identifier(&max_length_name), max_length_name);
+    }
+
+    #[test]
+    fn fit_identifier_truncates_long_names() {
+        let prefix = "unique_constraint_concert_performance_schedule_with_rehearsal";
+        let first = fit_identifier(&format!("{prefix}_venue_id_idx"));
+        let second = fit_identifier(&format!("{prefix}_artist_id_idx"));
+
+        assert_eq!(first.len(), MAX_IDENTIFIER_LENGTH);
+        assert!(first.starts_with(&prefix[..MAX_IDENTIFIER_LENGTH - HASH_SUFFIX_LENGTH]));
+        // Deterministic
+        assert_eq!(first, fit_identifier(&format!("{prefix}_venue_id_idx")));
+        // Names sharing the truncated prefix don't collide
+        assert_ne!(first, second);
+
+        // Never splits a multi-byte character
+        let multi_byte = fit_identifier(&"é".repeat(40));
+        assert!(multi_byte.len() <= MAX_IDENTIFIER_LENGTH);
+    }
+
+    #[test]
+    fn plain_identifiers() {
+        assert!(is_plain_identifier("main_title"));
+        assert!(is_plain_identifier("_title2"));
+        assert!(!is_plain_identifier("mainTitle"));
+        assert!(!is_plain_identifier("2title"));
+        assert!(!is_plain_identifier("main-title"));
+        assert!(!is_plain_identifier("order"));
+        assert!(!is_plain_identifier(""));
+
+        assert!(is_reserved_word("user"));
+        assert!(is_reserved_word("Limit"));
+        assert!(!is_reserved_word("users"));
+    }
+}
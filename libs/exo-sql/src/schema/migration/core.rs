@@ -15,6 +15,7 @@ use crate::{
     schema::{
         database_spec::DatabaseSpec,
         issue::WithIssues,
+        migration::drift::DriftReport,
         op::SchemaOp,
         spec::{MigrationScope, MigrationScopeMatches, diff},
     },
@@ -93,10 +94,14 @@ impl Migration {
         new_schema_spec: &DatabaseSpec,
         scope: &MigrationScope,
     ) -> Self {
-        let diffs = diff(old_schema_spec, new_schema_spec, scope);
+        let mut old_schema_spec = old_schema_spec.clone();
+        let rename_ops = old_schema_spec.with_renames_applied(&new_schema_spec.renames);
 
-        let diffs = diffs
+        let diffs = diff(&old_schema_spec, new_schema_spec, scope);
+
+        let diffs = rename_ops
             .into_iter()
+            .chain(diffs)
             .map(|diff| (diff, None))
             .collect::<Vec<_>>();
 
@@ -206,6 +211,28 @@ impl Migration {
         }
     }
 
+    /// Compare the model against the live database and describe every incompatibility found.
+    pub async fn drift(
+        client: &DatabaseClient,
+        database: &Database,
+        scope: &MigrationScope,
+    ) -> Result<DriftReport, DatabaseError> {
+        let new_schema = DatabaseSpec::from_database(database);
+
+        let old_schema = Self::extract_schema_from_db(client, &new_schema, scope).await?;
+
+        let diff = diff(&old_schema.value, &new_schema, scope);
+
+        Ok(DriftReport {
+            warnings: old_schema
+                .issues
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect(),
+            ..DriftReport::from_ops(&diff)
+        })
+    }
+
     pub async fn apply(
         &self,
         client: &mut DatabaseClient,
@@ -245,9 +272,14 @@ impl Migration {
         } in self.statements.iter()
         {
             if *is_destructive && !allow_destructive_changes {
-                write!(writer, "-- ")?;
+                // Comment out each line of multi-line statements (such as `DO` blocks)
+                for line in statement.lines() {
+                    writeln!(writer, "-- {line}")?;
+                }
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, "{statement}\n")?;
             }
-            writeln!(writer, "{statement}\n")?;
         }
         Ok(())
     }
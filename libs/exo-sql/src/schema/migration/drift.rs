@@ -0,0 +1,335 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Machine-readable report of the differences between a model and a live database.
+//!
+//! Unlike [`super::Migration`], which describes how to bring the database in line with the model,
+//! a drift report describes *what* is out of line so that it can be logged or consumed by tooling
+//! (for example, to refuse to start a server whose model no longer matches the database).
+
+use serde::Serialize;
+
+use crate::schema::{column_spec::ColumnSpec, op::SchemaOp, table_spec::TableSpec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    MissingSchema,
+    MissingSequence,
+    MissingTable,
+    MissingEnum,
+    EnumVariants,
+    MissingColumn,
+    ExtraColumn,
+    TypeMismatch,
+    ColumnMismatch,
+    Nullability,
+    DefaultValue,
+    MissingIndex,
+    MissingExtension,
+    Constraint,
+    ForeignKey,
+    Function,
+    Trigger,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftIssue {
+    pub kind: DriftKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// What the model requires (for example, the column type)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// What the database has
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DriftReport {
+    pub issues: Vec<DriftIssue>,
+    /// Non-fatal problems encountered while introspecting the database
+    pub warnings: Vec<String>,
+}
+
+impl DriftReport {
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Build a report from the operations that would migrate the database to the model.
+    ///
+    /// Operations that don't affect compatibility (such as extra tables in the database) are
+    /// skipped, consistent with [`super::Migration::verify`].
+    pub fn from_ops(ops: &[SchemaOp]) -> Self {
+        let mut issues = vec![];
+        let mut index = 0;
+
+        while index < ops.len() {
+            let op = &ops[index];
+
+            // A changed column is diffed as a delete followed by a create of the same column
+            if let (
+                SchemaOp::DeleteColumn {
+                    table,
+                    column: actual,
+                },
+                Some(SchemaOp::CreateColumn {
+                    column: expected, ..
+                }),
+            ) = (op, ops.get(index + 1))
+                && actual.name == expected.name
+            {
+                issues.push(column_mismatch(table, actual, expected));
+                index += 2;
+                continue;
+            }
+
+            if let Some(message) = op.error_string() {
+                issues.push(issue_for_op(op, message));
+            }
+            index += 1;
+        }
+
+        Self {
+            issues,
+            warnings: vec![],
+        }
+    }
+}
+
+fn column_mismatch(table: &TableSpec, actual: &ColumnSpec, expected: &ColumnSpec) -> DriftIssue {
+    let type_same = actual.typ.equals(expected.typ.as_ref());
+
+    let (kind, message) = if type_same {
+        (
+            DriftKind::ColumnMismatch,
+            format!(
+                "The column `{}` in the table `{}` differs from the model in its primary key or reference definition.",
+                expected.name,
+                table.sql_name()
+            ),
+        )
+    } else {
+        (
+            DriftKind::TypeMismatch,
+            format!(
+                "The column `{}` in the table `{}` has type `{}` in the database, but the model expects `{}`.",
+                expected.name,
+                table.sql_name(),
+                actual.typ.type_string(),
+                expected.typ.type_string()
+            ),
+        )
+    };
+
+    DriftIssue {
+        kind,
+        table: Some(table.name.fully_qualified_name()),
+        column: Some(expected.name.clone()),
+        expected: Some(expected.typ.type_string()),
+        actual: Some(actual.typ.type_string()),
+        message,
+    }
+}
+
+fn issue_for_op(op: &SchemaOp, message: String) -> DriftIssue {
+    let issue = |kind, table: Option<&TableSpec>, column: Option<&ColumnSpec>| DriftIssue {
+        kind,
+        table: table.map(|table| table.name.fully_qualified_name()),
+        column: column.map(|column| column.name.clone()),
+        expected: None,
+        actual: None,
+        message: message.clone(),
+    };
+
+    match op {
+        SchemaOp::CreateSchema { schema } => DriftIssue {
+            expected: Some(schema.clone()),
+            ..issue(DriftKind::MissingSchema, None, None)
+        },
+        SchemaOp::CreateSequence { sequence } => DriftIssue {
+            expected: Some(sequence.fully_qualified_name()),
+            ..issue(DriftKind::MissingSequence, None, None)
+        },
+        SchemaOp::CreateTable { table } => issue(DriftKind::MissingTable, Some(table), None),
+        SchemaOp::CreateEnum { enum_ } => DriftIssue {
+            expected: Some(enum_.name.fully_qualified_name()),
+            ..issue(DriftKind::MissingEnum, None, None)
+        },
+        SchemaOp::AlterEnum { old, new, .. } => DriftIssue {
+            expected: Some(new.variants.join(", ")),
+            actual: Some(old.variants.join(", ")),
+            ..issue(DriftKind::EnumVariants, None, None)
+        },
+        SchemaOp::CreateColumn { table, column } => DriftIssue {
+            expected: Some(column.typ.type_string()),
+            ..issue(DriftKind::MissingColumn, Some(table), Some(column))
+        },
+        SchemaOp::DeleteColumn { table, column } => DriftIssue {
+            actual: Some(column.typ.type_string()),
+            ..issue(DriftKind::ExtraColumn, Some(table), Some(column))
+        },
+        SchemaOp::SetNotNull { table, column } => DriftIssue {
+            expected: Some("not null".to_string()),
+            actual: Some("nullable".to_string()),
+            ..issue(DriftKind::Nullability, Some(table), Some(column))
+        },
+        SchemaOp::UnsetNotNull { table, column } => DriftIssue {
+            expected: Some("nullable".to_string()),
+            actual: Some("not null".to_string()),
+            ..issue(DriftKind::Nullability, Some(table), Some(column))
+        },
+        SchemaOp::SetColumnDefaultValue {
+            table,
+            column,
+            default_value,
+        } => DriftIssue {
+            expected: Some(default_value.clone()),
+            ..issue(DriftKind::DefaultValue, Some(table), Some(column))
+        },
+        SchemaOp::UnsetColumnDefaultValue { table, column } => {
+            issue(DriftKind::DefaultValue, Some(table), Some(column))
+        }
+        SchemaOp::CreateIndex { table, index } => DriftIssue {
+            expected: Some(index.name.clone()),
+            ..issue(DriftKind::MissingIndex, Some(table), None)
+        },
+        SchemaOp::CreateExtension { extension } => DriftIssue {
+            expected: Some(extension.clone()),
+            ..issue(DriftKind::MissingExtension, None, None)
+        },
+        SchemaOp::CreateUniqueConstraint {
+            table,
+            constraint_name,
+            ..
+        } => DriftIssue {
+            expected: Some(constraint_name.clone()),
+            ..issue(DriftKind::Constraint, Some(table), None)
+        },
+        SchemaOp::RemoveUniqueConstraint { table, constraint } => DriftIssue {
+            actual: Some(constraint.clone()),
+            ..issue(DriftKind::Constraint, Some(table), None)
+        },
+        SchemaOp::CreateForeignKeyReference { table, name, .. } => DriftIssue {
+            expected: Some(name.clone()),
+            ..issue(DriftKind::ForeignKey, Some(table), None)
+        },
+        SchemaOp::DeleteForeignKeyReference { table, name } => DriftIssue {
+            actual: Some(name.clone()),
+            ..issue(DriftKind::ForeignKey, Some(table), None)
+        },
+        SchemaOp::CreateFunction { function } | SchemaOp::CreateOrReplaceFunction { function } => {
+            DriftIssue {
+                expected: Some(function.name.clone()),
+                ..issue(DriftKind::Function, None, None)
+            }
+        }
+        SchemaOp::DeleteFunction { name } => DriftIssue {
+            actual: Some(name.to_string()),
+            ..issue(DriftKind::Function, None, None)
+        },
+        SchemaOp::CreateTrigger {
+            trigger,
+            table_name,
+        } => DriftIssue {
+            table: Some(table_name.fully_qualified_name()),
+            expected: Some(trigger.name.clone()),
+            ..issue(DriftKind::Trigger, None, None)
+        },
+        SchemaOp::DeleteTrigger {
+            trigger,
+            table_name,
+        } => DriftIssue {
+            table: Some(table_name.fully_qualified_name()),
+            actual: Some(trigger.name.clone()),
+            ..issue(DriftKind::Trigger, None, None)
+        },
+        SchemaOp::CreatePartition { table, value } => DriftIssue {
+            expected: Some(value.to_string()),
+            ..issue(DriftKind::MissingTable, Some(table), None)
+        },
+        _ => issue(DriftKind::Other, None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        SchemaObjectName,
+        schema::{
+            index_spec::IndexSpec,
+            test_helper::{int_column, pk_column, string_column},
+        },
+    };
+
+    #[test]
+    fn reports_missing_and_mismatched_columns() {
+        let table = TableSpec::new(
+            SchemaObjectName::new("concerts", None),
+            vec![pk_column("id"), int_column("title")],
+            vec![],
+            vec![],
+            true,
+        );
+        let db_title = int_column("title");
+        let model_title = string_column("title");
+        let model_venue = int_column("venue_id");
+        let index = IndexSpec {
+            name: "concert_title_idx".to_string(),
+            columns: ["title".to_string()].into_iter().collect(),
+            index_kind: Default::default(),
+        };
+
+        let ops = vec![
+            SchemaOp::DeleteColumn {
+                table: &table,
+                column: &db_title,
+            },
+            SchemaOp::CreateColumn {
+                table: &table,
+                column: &model_title,
+            },
+            SchemaOp::CreateColumn {
+                table: &table,
+                column: &model_venue,
+            },
+            SchemaOp::CreateIndex {
+                table: &table,
+                index: &index,
+            },
+            SchemaOp::DeleteTable { table: &table },
+        ];
+
+        let report = DriftReport::from_ops(&ops);
+        let kinds: Vec<_> = report.issues.iter().map(|issue| issue.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                DriftKind::TypeMismatch,
+                DriftKind::MissingColumn,
+                DriftKind::MissingIndex
+            ]
+        );
+
+        let mismatch = &report.issues[0];
+        assert_eq!(mismatch.table.as_deref(), Some("concerts"));
+        assert_eq!(mismatch.column.as_deref(), Some("title"));
+        assert_eq!(mismatch.expected, Some(model_title.typ.type_string()));
+        assert_eq!(mismatch.actual, Some(db_title.typ.type_string()));
+    }
+}
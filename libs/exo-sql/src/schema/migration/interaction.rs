@@ -114,18 +114,28 @@ pub async fn migrate_interactively(
     scope: &MigrationScope,
     interactions: &dyn MigrationInteraction,
 ) -> Result<Migration, InteractionError> {
+    // Renames declared in the model don't need to be asked about
+    let rename_ops = old_db_spec.with_renames_applied(&new_db_spec.renames);
+
     let table_actions = get_table_actions(&old_db_spec, &new_db_spec, scope, interactions).await?;
 
-    apply_table_actions(&mut old_db_spec, new_db_spec, table_actions, scope)
+    apply_table_actions(
+        &mut old_db_spec,
+        new_db_spec,
+        rename_ops,
+        table_actions,
+        scope,
+    )
 }
 
 fn apply_table_actions(
     old_db_spec: &mut DatabaseSpec,
     new_db_spec: DatabaseSpec,
+    rename_ops: Vec<SchemaOp>,
     table_actions: Vec<TableAction>,
     scope: &MigrationScope,
 ) -> Result<Migration, InteractionError> {
-    let mut all_ops: Vec<SchemaOp> = vec![];
+    let mut all_ops: Vec<SchemaOp> = rename_ops;
 
     for table_action in table_actions.iter() {
         if let TableAction::Rename {
@@ -1,8 +1,10 @@
 mod core;
+mod drift;
 mod interaction;
 mod predefined_interaction;
 
 pub use core::{Migration, MigrationError, MigrationStatement, VerificationErrors, wipe_database};
 
+pub use drift::{DriftIssue, DriftKind, DriftReport};
 pub use interaction::{InteractionError, MigrationInteraction, TableAction, migrate_interactively};
 pub use predefined_interaction::PredefinedMigrationInteraction;
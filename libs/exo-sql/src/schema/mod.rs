@@ -11,10 +11,12 @@ pub mod column_spec;
 pub mod database_spec;
 pub mod enum_spec;
 pub mod function_spec;
+pub mod identifier;
 pub mod index_spec;
 pub mod issue;
 pub mod migration;
 pub mod op;
+pub mod partition_spec;
 pub mod spec;
 pub mod table_spec;
 pub mod test_helper;
@@ -10,14 +10,16 @@
 use std::collections::HashSet;
 
 use crate::{
-    SchemaObjectName,
+    ColumnStorage, SchemaObjectName,
     schema::{constraint::sorted_comma_list, index_spec::IndexSpec},
 };
 
 use super::{
     column_spec::{ColumnReferenceSpec, ColumnSpec},
-    enum_spec::EnumSpec,
+    enum_spec::{EnumColumn, EnumSpec},
     function_spec::FunctionSpec,
+    identifier::fit_identifier,
+    partition_spec::list_partition_creation_sql,
     statement::SchemaStatement,
     table_spec::TableSpec,
     trigger_spec::TriggerSpec,
@@ -80,6 +82,12 @@ pub enum SchemaOp<'a> {
     DeleteEnum {
         enum_: &'a EnumSpec,
     },
+    /// Change the variants of an enum, converting the columns that use it
+    AlterEnum {
+        old: &'a EnumSpec,
+        new: &'a EnumSpec,
+        columns: Vec<EnumColumn<'a>>,
+    },
 
     CreateColumn {
         table: &'a TableSpec,
@@ -90,7 +98,7 @@ pub enum SchemaOp<'a> {
         column: &'a ColumnSpec,
     },
     RenameColumn {
-        table: &'a TableSpec,
+        table_name: SchemaObjectName,
         name: String,
         new_name: String,
     },
@@ -111,6 +119,12 @@ pub enum SchemaOp<'a> {
         table: &'a TableSpec,
         column: &'a ColumnSpec,
     },
+    /// Change the storage parameters of a column (`column` has the new ones)
+    SetColumnStorage {
+        table: &'a TableSpec,
+        column: &'a ColumnSpec,
+        old_storage: &'a ColumnStorage,
+    },
 
     CreateExtension {
         extension: String,
@@ -166,6 +180,11 @@ pub enum SchemaOp<'a> {
         trigger: &'a TriggerSpec,
         table_name: &'a SchemaObjectName,
     },
+    /// Create a partition for a value of a list-partitioned table
+    CreatePartition {
+        table: &'a TableSpec,
+        value: &'a String,
+    },
 }
 
 impl SchemaOp<'_> {
@@ -206,9 +225,13 @@ impl SchemaOp<'_> {
 
             SchemaOp::CreateEnum { enum_ } => enum_.creation_sql(),
             SchemaOp::DeleteEnum { enum_ } => enum_.deletion_sql(),
+            SchemaOp::AlterEnum { old, new, columns } => old.alteration_sql(new, columns),
 
             SchemaOp::CreateColumn { table, column } => {
-                let column_stmt = column.to_sql(table.has_single_pk());
+                let mut column_stmt = column.to_sql(table.has_single_pk());
+                column_stmt
+                    .post_statements
+                    .extend(column.storage_sql(&table.name));
 
                 SchemaStatement {
                     statement: format!(
@@ -229,13 +252,13 @@ impl SchemaOp<'_> {
                 ..Default::default()
             },
             SchemaOp::RenameColumn {
-                table,
+                table_name,
                 name,
                 new_name,
             } => SchemaStatement {
                 statement: format!(
                     "ALTER TABLE {} RENAME COLUMN \"{}\" TO \"{}\";",
-                    table.sql_name(),
+                    table_name.sql_name(),
                     name,
                     new_name
                 ),
@@ -272,6 +295,21 @@ impl SchemaOp<'_> {
                 ),
                 ..Default::default()
             },
+            SchemaOp::SetColumnStorage {
+                table,
+                column,
+                old_storage,
+            } => SchemaStatement {
+                statement: format!(
+                    "ALTER TABLE {} {};",
+                    table.sql_name(),
+                    column
+                        .storage
+                        .alter_actions(old_storage, &column.name)
+                        .join(", ")
+                ),
+                ..Default::default()
+            },
 
             SchemaOp::CreateExtension { extension } => SchemaStatement {
                 statement: format!("CREATE EXTENSION IF NOT EXISTS \"{extension}\";"),
@@ -316,11 +354,11 @@ impl SchemaOp<'_> {
                 let (self_columns, foreign_columns): (Vec<&ColumnSpec>, Vec<&ColumnReferenceSpec>) =
                     reference_columns.into_iter().unzip();
 
-                let constraint_name = format!(
+                let constraint_name = fit_identifier(&format!(
                     "{}_{}_fk",
                     table.name.fully_qualified_name_with_sep("_"),
                     name
-                );
+                ));
 
                 let foreign_reference_columns = if foreign_columns.len() == 1 {
                     // If there is only one foreign column, we don't need to specify the columns in the foreign key constraint (assume it's the primary key)
@@ -339,7 +377,7 @@ impl SchemaOp<'_> {
                 };
 
                 let foreign_constraint = format!(
-                    r#"ALTER TABLE {} ADD CONSTRAINT "{constraint_name}" FOREIGN KEY ({}) REFERENCES {}{};"#,
+                    r#"ALTER TABLE {} ADD CONSTRAINT "{constraint_name}" FOREIGN KEY ({}) REFERENCES {}{}{};"#,
                     table.name.sql_name(),
                     self_columns
                         .iter()
@@ -347,7 +385,8 @@ impl SchemaOp<'_> {
                         .collect::<Vec<_>>()
                         .join(", "),
                     foreign_columns[0].foreign_table_name.sql_name(), // Foreign columns all point to the same table, so use any of them
-                    foreign_reference_columns
+                    foreign_reference_columns,
+                    foreign_columns[0].referential_action_clauses() // The actions are declared per relation, so are the same for all columns
                 );
 
                 SchemaStatement {
@@ -412,6 +451,10 @@ impl SchemaOp<'_> {
                 ),
                 ..Default::default()
             },
+            SchemaOp::CreatePartition { table, value } => SchemaStatement {
+                statement: list_partition_creation_sql(&table.name, value),
+                ..Default::default()
+            },
         }
     }
 
@@ -424,6 +467,12 @@ impl SchemaOp<'_> {
             | SchemaOp::DeleteEnum { .. }
             | SchemaOp::DeleteSequence { .. } => true,
 
+            // Values of removed variants must be mapped to other variants to keep them
+            SchemaOp::AlterEnum { old, new, .. } => old
+                .removed_variants(new)
+                .iter()
+                .any(|(_, mapped_to)| mapped_to.is_none()),
+
             // Explicitly matching the other cases here to ensure that we have thought about each case
             SchemaOp::CreateSchema { .. }
             | SchemaOp::RenameSchema { .. }
@@ -443,13 +492,15 @@ impl SchemaOp<'_> {
             | SchemaOp::DeleteForeignKeyReference { .. }
             | SchemaOp::SetColumnDefaultValue { .. }
             | SchemaOp::UnsetColumnDefaultValue { .. }
+            | SchemaOp::SetColumnStorage { .. } // Affects only how (newly written) values are stored
             | SchemaOp::SetNotNull { .. }
             | SchemaOp::UnsetNotNull { .. }
             | SchemaOp::CreateFunction { .. }
             | SchemaOp::DeleteFunction { .. }
             | SchemaOp::CreateOrReplaceFunction { .. }
             | SchemaOp::CreateTrigger { .. }
-            | SchemaOp::DeleteTrigger { .. } => false,
+            | SchemaOp::DeleteTrigger { .. }
+            | SchemaOp::CreatePartition { .. } => false,
         }
     }
 
@@ -480,6 +531,12 @@ impl SchemaOp<'_> {
                 enum_.sql_name()
             )),
             SchemaOp::DeleteEnum { .. } => None, // An extra enum in the database is not a problem
+            SchemaOp::AlterEnum { old, new, .. } => Some(format!(
+                "The enum `{}` has the variants [{}] in the database, but [{}] in the model.",
+                new.sql_name(),
+                old.variants.join(", "),
+                new.variants.join(", ")
+            )),
 
             SchemaOp::CreateColumn { table, column } => Some(format!(
                 "The column `{}` in the table `{}` exists in the model, but does not exist in the database table.",
@@ -522,6 +579,7 @@ impl SchemaOp<'_> {
                 column.name,
                 table.sql_name()
             )),
+            SchemaOp::SetColumnStorage { .. } => None, // Different storage parameters don't affect the data
 
             SchemaOp::CreateExtension { extension } => {
                 Some(format!("The model requires the extension `{extension}`."))
@@ -602,6 +660,11 @@ impl SchemaOp<'_> {
                 "The model requires a function named `{}` with body `{}`",
                 function.name, function.body
             )),
+            SchemaOp::CreatePartition { table, value } => Some(format!(
+                "The model requires a partition for the value `{}` in table `{}`",
+                value,
+                table.sql_name()
+            )),
         }
     }
 }
@@ -0,0 +1,321 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    SchemaObjectName, database_error::DatabaseError, sql::connect::database_client::DatabaseClient,
+};
+
+/// The number of future range partitions (beyond the current one) to create by default
+pub const DEFAULT_PREMAKE: u32 = 3;
+
+const PARTITION_KEY_QUERY: &str = r#"
+SELECT
+   partition.partstrat::text AS strategy,
+   attribute.attname AS column_name
+FROM
+   pg_catalog.pg_partitioned_table partition
+   JOIN pg_catalog.pg_class cls ON cls.oid = partition.partrelid
+   JOIN pg_catalog.pg_namespace schema ON schema.oid = cls.relnamespace
+   JOIN pg_catalog.pg_attribute attribute ON attribute.attrelid = cls.oid AND attribute.attnum = partition.partattrs[0]
+WHERE
+   cls.relname = $1 AND schema.nspname = $2"#;
+
+const PARTITION_BOUNDS_QUERY: &str = r#"
+SELECT
+   pg_get_expr(child.relpartbound, child.oid) AS bound
+FROM
+   pg_catalog.pg_inherits inheritance
+   JOIN pg_catalog.pg_class child ON child.oid = inheritance.inhrelid
+   JOIN pg_catalog.pg_class parent ON parent.oid = inheritance.inhparent
+   JOIN pg_catalog.pg_namespace schema ON schema.oid = parent.relnamespace
+WHERE
+   parent.relname = $1 AND schema.nspname = $2"#;
+
+/// How a table is partitioned (declared through `@partitionBy`).
+///
+/// The table is partitioned by a single column. Rows that don't fall in any partition go to the
+/// default partition (`<table>_default`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PartitionSpec {
+    pub column_name: String,
+    pub strategy: PartitionStrategy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PartitionStrategy {
+    /// A partition for each period (named `<table>_p<period start>`). Partitions for the current
+    /// and the next `premake` periods are created along with the table and then periodically by
+    /// the server.
+    Range {
+        /// `None` if read from the database (Postgres doesn't record the interval)
+        interval: Option<PartitionInterval>,
+        premake: u32,
+    },
+    /// A partition for each value (named `<table>_<value>`)
+    List { values: Vec<String> },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PartitionInterval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl PartitionInterval {
+    fn unit(&self) -> &'static str {
+        match self {
+            PartitionInterval::Day => "day",
+            PartitionInterval::Week => "week",
+            PartitionInterval::Month => "month",
+            PartitionInterval::Year => "year",
+        }
+    }
+
+    /// The `to_char` format for the suffix of partition names
+    fn name_format(&self) -> &'static str {
+        match self {
+            PartitionInterval::Day | PartitionInterval::Week => "YYYYMMDD",
+            PartitionInterval::Month => "YYYYMM",
+            PartitionInterval::Year => "YYYY",
+        }
+    }
+}
+
+impl std::str::FromStr for PartitionInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(PartitionInterval::Day),
+            "week" => Ok(PartitionInterval::Week),
+            "month" => Ok(PartitionInterval::Month),
+            "year" => Ok(PartitionInterval::Year),
+            _ => Err(format!(
+                "Invalid partition interval '{s}'. Expected one of day, week, month, or year"
+            )),
+        }
+    }
+}
+
+impl PartitionSpec {
+    /// The `PARTITION BY` clause for the `CREATE TABLE` statement
+    pub fn partition_clause(&self) -> String {
+        let method = match self.strategy {
+            PartitionStrategy::Range { .. } => "RANGE",
+            PartitionStrategy::List { .. } => "LIST",
+        };
+
+        format!(" PARTITION BY {method} (\"{}\")", self.column_name)
+    }
+
+    /// Does the table partitioned by `self` have the same partition key as `other`? Migrations
+    /// must recreate the table if not.
+    pub fn same_key(&self, other: &PartitionSpec) -> bool {
+        self.column_name == other.column_name
+            && matches!(
+                (&self.strategy, &other.strategy),
+                (
+                    PartitionStrategy::Range { .. },
+                    PartitionStrategy::Range { .. }
+                ) | (
+                    PartitionStrategy::List { .. },
+                    PartitionStrategy::List { .. }
+                )
+            )
+    }
+
+    /// Statements to create the partitions when creating the table
+    pub fn creation_sql(&self, table_name: &SchemaObjectName) -> Vec<String> {
+        let mut statements = vec![format!(
+            "CREATE TABLE {} PARTITION OF {} DEFAULT;",
+            partition_name(table_name, "default").sql_name(),
+            table_name.sql_name()
+        )];
+
+        match &self.strategy {
+            PartitionStrategy::Range { .. } => {
+                statements.extend(self.maintenance_sql(table_name));
+            }
+            PartitionStrategy::List { values } => {
+                statements.extend(
+                    values
+                        .iter()
+                        .map(|value| list_partition_creation_sql(table_name, value)),
+                );
+            }
+        }
+
+        statements
+    }
+
+    /// A statement to create the range partitions for the current and the upcoming periods
+    /// (ignoring the ones that already exist). The statement computes the periods when executed,
+    /// so the same statement can be executed periodically.
+    ///
+    /// Returns `None` for list partitioning (or if the interval is unknown).
+    pub fn maintenance_sql(&self, table_name: &SchemaObjectName) -> Option<String> {
+        let PartitionStrategy::Range {
+            interval: Some(interval),
+            premake,
+        } = &self.strategy
+        else {
+            return None;
+        };
+
+        let unit = interval.unit();
+        let schema = table_name.schema_name();
+        let table = &table_name.name;
+
+        Some(format!(
+            r#"DO $$
+DECLARE
+	period_start timestamp;
+BEGIN
+	FOR i IN 0..{premake} LOOP
+		period_start := date_trunc('{unit}', now()) + i * interval '1 {unit}';
+		EXECUTE format('CREATE TABLE IF NOT EXISTS %I.%I PARTITION OF %I.%I FOR VALUES FROM (%L) TO (%L)', '{schema}', '{table}_p' || to_char(period_start, '{format}'), '{schema}', '{table}', period_start, period_start + interval '1 {unit}');
+	END LOOP;
+END $$;"#,
+            format = interval.name_format(),
+        ))
+    }
+
+    pub(super) async fn from_live_db(
+        client: &DatabaseClient,
+        table_name: &SchemaObjectName,
+    ) -> Result<Option<PartitionSpec>, DatabaseError> {
+        let params: [&(dyn tokio_postgres::types::ToSql + Sync); 2] =
+            [&table_name.name, &table_name.schema_name()];
+
+        let Some(row) = client
+            .query(PARTITION_KEY_QUERY, &params)
+            .await?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        let strategy: String = row.get("strategy");
+        let column_name: String = row.get("column_name");
+
+        let strategy = match strategy.as_str() {
+            "r" => PartitionStrategy::Range {
+                interval: None,
+                premake: DEFAULT_PREMAKE,
+            },
+            "l" => {
+                let values = client
+                    .query(PARTITION_BOUNDS_QUERY, &params)
+                    .await?
+                    .into_iter()
+                    .flat_map(|row| parse_list_bound(&row.get::<_, String>("bound")))
+                    .collect();
+
+                PartitionStrategy::List { values }
+            }
+            _ => {
+                return Err(DatabaseError::Generic(format!(
+                    "Unsupported partitioning strategy for table {}",
+                    table_name.fully_qualified_name()
+                )));
+            }
+        };
+
+        Ok(Some(PartitionSpec {
+            column_name,
+            strategy,
+        }))
+    }
+}
+
+pub(super) fn list_partition_creation_sql(table_name: &SchemaObjectName, value: &str) -> String {
+    format!(
+        "CREATE TABLE {} PARTITION OF {} FOR VALUES IN ('{}');",
+        partition_name(table_name, value).sql_name(),
+        table_name.sql_name(),
+        value.replace('\'', "''")
+    )
+}
+
+fn partition_name(table_name: &SchemaObjectName, suffix: &str) -> SchemaObjectName {
+    let suffix: String = suffix
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    SchemaObjectName {
+        name: format!("{}_{suffix}", table_name.name),
+        schema: table_name.schema.clone(),
+    }
+}
+
+/// Parse the values from a list partition bound such as `FOR VALUES IN ('us', 'eu')`
+fn parse_list_bound(bound: &str) -> Vec<String> {
+    let Some(values) = bound
+        .strip_prefix("FOR VALUES IN (")
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return vec![]; // The default partition
+    };
+
+    values
+        .split(", ")
+        .map(|value| {
+            let value = value.split("::").next().unwrap_or(value); // Such as 'us'::text
+            value.trim_matches('\'').replace("''", "'")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_partitions() {
+        let spec = PartitionSpec {
+            column_name: "region".to_string(),
+            strategy: PartitionStrategy::List {
+                values: vec!["us".to_string(), "eu-west".to_string()],
+            },
+        };
+        let table_name = SchemaObjectName::new("orders", None);
+
+        assert_eq!(spec.partition_clause(), " PARTITION BY LIST (\"region\")");
+        assert_eq!(
+            spec.creation_sql(&table_name),
+            vec![
+                "CREATE TABLE \"orders_default\" PARTITION OF \"orders\" DEFAULT;",
+                "CREATE TABLE \"orders_us\" PARTITION OF \"orders\" FOR VALUES IN ('us');",
+                "CREATE TABLE \"orders_eu_west\" PARTITION OF \"orders\" FOR VALUES IN ('eu-west');",
+            ]
+        );
+        assert_eq!(spec.maintenance_sql(&table_name), None);
+    }
+
+    #[test]
+    fn parses_list_bounds() {
+        assert_eq!(
+            parse_list_bound("FOR VALUES IN ('us', 'o''neil')"),
+            vec!["us".to_string(), "o'neil".to_string()]
+        );
+        assert_eq!(parse_list_bound("DEFAULT"), Vec::<String>::new());
+    }
+}
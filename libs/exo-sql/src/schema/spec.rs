@@ -16,7 +16,7 @@ use wildmatch::WildMatch;
 
 use crate::SchemaObjectName;
 
-use super::{database_spec::DatabaseSpec, op::SchemaOp};
+use super::{database_spec::DatabaseSpec, enum_spec::EnumColumn, op::SchemaOp};
 
 #[derive(Debug, PartialEq)]
 pub enum MigrationScope {
@@ -178,7 +178,19 @@ pub fn diff<'a>(
             .iter()
             .find(|new_enum| old_enum.sql_name() == new_enum.sql_name())
         {
-            Some(new_enum) => changes.extend(old_enum.diff(new_enum)),
+            Some(new_enum) => {
+                let columns = old
+                    .tables
+                    .iter()
+                    .flat_map(|table| {
+                        table
+                            .columns
+                            .iter()
+                            .filter_map(move |column| EnumColumn::of(table, column, old_enum))
+                    })
+                    .collect();
+                changes.extend(old_enum.diff(new_enum, columns))
+            }
 
             // enum does not exist, deletion
             None => changes.push(SchemaOp::DeleteEnum { enum_: old_enum }),
@@ -25,6 +25,7 @@ use super::enum_spec::EnumSpec;
 use super::index_spec::IndexSpec;
 use super::issue::WithIssues;
 use super::op::SchemaOp;
+use super::partition_spec::{PartitionSpec, PartitionStrategy};
 use super::statement::SchemaStatement;
 use super::trigger_spec::TriggerSpec;
 
@@ -42,6 +43,7 @@ pub struct TableSpec {
     pub indices: Vec<IndexSpec>,
     pub triggers: Vec<TriggerSpec>,
     pub managed: bool,
+    pub partition: Option<PartitionSpec>,
 }
 
 impl TableSpec {
@@ -58,9 +60,14 @@ impl TableSpec {
             indices,
             triggers,
             managed,
+            partition: None,
         }
     }
 
+    pub fn with_partition(self, partition: Option<PartitionSpec>) -> Self {
+        Self { partition, ..self }
+    }
+
     pub fn has_single_pk(&self) -> bool {
         self.columns.iter().filter(|c| c.is_pk).count() == 1
     }
@@ -71,6 +78,7 @@ impl TableSpec {
             columns: vec![],
             indices: vec![],
             managed: self.managed,
+            partition: self.partition.clone(),
         }
     }
 
@@ -130,6 +138,8 @@ impl TableSpec {
                         foreign_pk_column_name: foreign_column.clone(),
                         foreign_pk_type: column_spec.typ,
                         group_name: foreign_constraint.constraint_name.clone(),
+                        on_delete: foreign_constraint.on_delete,
+                        on_update: foreign_constraint.on_update,
                     };
 
                     let existing_ref_specs = column_reference_mapping.get_mut(&self_column);
@@ -202,6 +212,8 @@ impl TableSpec {
         } = TriggerSpec::from_live_db(client, &table_name).await?;
         issues.extend(triggers_issues);
 
+        let partition = PartitionSpec::from_live_db(client, &table_name).await?;
+
         Ok(WithIssues {
             value: TableSpec {
                 name: table_name,
@@ -209,6 +221,7 @@ impl TableSpec {
                 indices,
                 triggers,
                 managed: true,
+                partition,
             },
             issues,
         })
@@ -245,6 +258,7 @@ impl TableSpec {
                     is_nullable: !not_null,
                     unique_constraints: vec![],
                     default_value: None,
+                    storage: Default::default(),
                 }
             })
             .collect();
@@ -284,6 +298,19 @@ impl TableSpec {
             return vec![];
         }
 
+        // Postgres can't change how an existing table is partitioned, so recreate the table
+        let same_partition_key = match (&self.partition, &new.partition) {
+            (None, None) => true,
+            (Some(existing), Some(new)) => existing.same_key(new),
+            _ => false,
+        };
+        if !same_partition_key {
+            return vec![
+                SchemaOp::DeleteTable { table: self },
+                SchemaOp::CreateTable { table: new },
+            ];
+        }
+
         let existing_columns = &self.columns;
         let new_columns = &new.columns;
 
@@ -428,35 +455,74 @@ impl TableSpec {
             }
         }
 
+        if let (
+            Some(PartitionSpec {
+                strategy: PartitionStrategy::List { values: existing },
+                ..
+            }),
+            Some(PartitionSpec {
+                strategy: PartitionStrategy::List { values: new_values },
+                ..
+            }),
+        ) = (&self.partition, &new.partition)
+        {
+            // Partitions for values removed from the model are kept (dropping them would delete data)
+            for value in new_values.iter().filter(|value| !existing.contains(value)) {
+                changes.push(SchemaOp::CreatePartition { table: new, value });
+            }
+        }
+
         let self_foreign_key_references = self.foreign_key_references();
         let new_foreign_key_references = new.foreign_key_references();
 
         // No need to remove the foreign key references since deleting the column will take care of it
 
         for (column_group_name, column_map) in new_foreign_key_references.into_iter() {
-            let existing_column_map_by_group_name = self_foreign_key_references
+            let new_column_map = column_map
                 .iter()
-                .find(|(group_name, _)| group_name == &column_group_name);
+                .map(|(column, _)| column.name.clone())
+                .collect::<Vec<_>>();
 
-            if existing_column_map_by_group_name.is_none() {
-                // new foreign key reference
-                let new_column_map = column_map
-                    .iter()
-                    .map(|(column, _)| column.name.clone())
-                    .collect::<Vec<_>>();
-                let column_map_by_columns =
+            let existing = self_foreign_key_references
+                .iter()
+                .find(|(group_name, _)| group_name == &column_group_name)
+                .or_else(|| {
                     self_foreign_key_references.iter().find(|(_, columns)| {
                         columns
                             .iter()
                             .all(|(column, _)| new_column_map.contains(&column.name))
-                    });
-                if column_map_by_columns.is_none() {
+                    })
+                });
+
+            match existing {
+                None => {
+                    // new foreign key reference
                     changes.push(SchemaOp::CreateForeignKeyReference {
                         table: new,
                         name: column_group_name,
                         reference_columns: column_map,
                     });
                 }
+                Some((existing_group_name, existing_column_map)) => {
+                    // Postgres doesn't support altering the referential actions of a constraint, so recreate it
+                    let actions = |column_map: &[(&ColumnSpec, &ColumnReferenceSpec)]| {
+                        column_map
+                            .first()
+                            .map(|(_, reference)| (reference.on_delete, reference.on_update))
+                    };
+
+                    if actions(existing_column_map) != actions(&column_map) {
+                        changes.push(SchemaOp::DeleteForeignKeyReference {
+                            table: self,
+                            name: existing_group_name.clone(),
+                        });
+                        changes.push(SchemaOp::CreateForeignKeyReference {
+                            table: new,
+                            name: column_group_name,
+                            reference_columns: column_map,
+                        });
+                    }
+                }
             }
         }
 
@@ -473,6 +539,7 @@ impl TableSpec {
             .map(|c| {
                 let mut s = c.to_sql(self.has_single_pk());
                 post_statements.append(&mut s.post_statements);
+                post_statements.extend(c.storage_sql(&self.name));
                 s.statement
             })
             .collect::<Vec<_>>()
@@ -528,8 +595,18 @@ impl TableSpec {
             post_statements.push(trigger.creation_sql(&self.name));
         }
 
+        let partition_clause = match &self.partition {
+            Some(partition) => {
+                post_statements.extend(partition.creation_sql(&self.name));
+                partition.partition_clause()
+            }
+            None => "".to_string(),
+        };
+
         SchemaStatement {
-            statement: format!("CREATE TABLE {table_name} (\n\t{column_stmts}{pk_str}\n);",),
+            statement: format!(
+                "CREATE TABLE {table_name} (\n\t{column_stmts}{pk_str}\n){partition_clause};",
+            ),
             pre_statements: vec![],
             post_statements,
         }
@@ -1,7 +1,7 @@
 #![cfg(test)]
 
-use crate::SchemaObjectName;
 use crate::sql::physical_column_type::{IntBits, IntColumnType, JsonColumnType, StringColumnType};
+use crate::{ReferentialAction, SchemaObjectName};
 
 use super::column_spec::{ColumnAutoincrement, ColumnDefault, ColumnReferenceSpec, ColumnSpec};
 
@@ -14,6 +14,7 @@ pub fn pk_column(name: impl Into<String>) -> ColumnSpec {
         is_nullable: false,
         unique_constraints: vec![],
         default_value: Some(ColumnDefault::Autoincrement(ColumnAutoincrement::Serial)),
+        storage: Default::default(),
     }
 }
 
@@ -34,11 +35,14 @@ pub fn pk_reference_column(
             foreign_pk_column_name: "id".to_string(),
             foreign_pk_type: Box::new(IntColumnType { bits: IntBits::_16 }),
             group_name: group_name.into(),
+            on_delete: ReferentialAction::NoAction,
+            on_update: ReferentialAction::NoAction,
         }]),
         is_pk: false,
         is_nullable: false,
         unique_constraints: vec![],
         default_value: None,
+        storage: Default::default(),
     }
 }
 
@@ -51,6 +55,7 @@ pub fn int_column(name: impl Into<String>) -> ColumnSpec {
         is_nullable: false,
         unique_constraints: vec![],
         default_value: None,
+        storage: Default::default(),
     }
 }
 
@@ -63,6 +68,7 @@ pub fn string_column(name: impl Into<String>) -> ColumnSpec {
         is_nullable: false,
         unique_constraints: vec![],
         default_value: None,
+        storage: Default::default(),
     }
 }
 
@@ -75,5 +81,6 @@ pub fn json_column(name: impl Into<String>) -> ColumnSpec {
         is_nullable: false,
         unique_constraints: vec![],
         default_value: None,
+        storage: Default::default(),
     }
 }
@@ -0,0 +1,214 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Snapshots of the database used by a model, to quickly get to a known state (for example, at the
+//! start of a test or when switching between branches during development).
+//!
+//! Snapshots are created and restored using `pg_dump` and `pg_restore` (which must be available on
+//! the `PATH`) and are limited to the schemas (and extensions) used by the model.
+
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use thiserror::Error;
+
+use crate::{
+    Database,
+    schema::{
+        database_spec::DatabaseSpec,
+        spec::{MigrationScope, MigrationScopeMatches},
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Failed to find executable: {0} ({1})")]
+    ExecutableNotFound(&'static str, which::Error),
+
+    #[error("Failed to launch {0}: {1}")]
+    Launch(&'static str, #[source] std::io::Error),
+
+    #[error("{0} failed: {1}")]
+    Failed(&'static str, String),
+}
+
+/// The parts of the database to include in a snapshot
+#[derive(Debug, PartialEq)]
+pub struct SnapshotScope {
+    pub schemas: Vec<String>,
+    pub extensions: Vec<String>,
+}
+
+impl SnapshotScope {
+    pub fn from_database(database: &Database, scope: &MigrationScope) -> Self {
+        let spec = DatabaseSpec::from_database(database);
+        let scope = match scope {
+            MigrationScope::Specified(scope) => scope,
+            MigrationScope::FromNewSpec => &MigrationScopeMatches::from_specs_schemas(&[&spec]),
+        };
+
+        let mut schemas: Vec<String> = spec.required_schemas(scope).into_iter().collect();
+        if spec.needs_public_schema() {
+            schemas.push("public".to_string());
+        }
+        schemas.sort();
+
+        let mut extensions: Vec<String> = spec.required_extensions(scope).into_iter().collect();
+        extensions.sort();
+
+        Self {
+            schemas,
+            extensions,
+        }
+    }
+}
+
+/// Dump the database in the `pg_dump` custom format to `output` (or to stdout, if not specified)
+pub fn dump_snapshot(
+    database_url: &str,
+    scope: &SnapshotScope,
+    output: Option<&Path>,
+) -> Result<(), SnapshotError> {
+    run("pg_dump", dump_args(database_url, scope, output))
+}
+
+/// Restore a snapshot created by [`dump_snapshot`].
+///
+/// The restoration happens in a single transaction, so a failure leaves the database untouched. If
+/// `clean` is set, objects in the snapshot are dropped before being recreated (otherwise, the
+/// database is expected to not have them, as is the case for a freshly created database).
+pub fn restore_snapshot(
+    database_url: &str,
+    input: &Path,
+    clean: bool,
+) -> Result<(), SnapshotError> {
+    run("pg_restore", restore_args(database_url, input, clean))
+}
+
+fn dump_args(database_url: &str, scope: &SnapshotScope, output: Option<&Path>) -> Vec<String> {
+    let mut args = vec![
+        "--format=custom".to_string(),
+        "--no-owner".to_string(),
+        "--no-privileges".to_string(),
+        format!("--dbname={database_url}"),
+    ];
+
+    if let Some(output) = output {
+        args.push(format!("--file={}", output.display()));
+    }
+
+    // Quote the names so that `pg_dump` matches them exactly (instead of as patterns)
+    args.extend(
+        scope
+            .schemas
+            .iter()
+            .map(|schema| format!("--schema=\"{schema}\"")),
+    );
+    args.extend(
+        scope
+            .extensions
+            .iter()
+            .map(|extension| format!("--extension=\"{extension}\"")),
+    );
+
+    args
+}
+
+fn restore_args(database_url: &str, input: &Path, clean: bool) -> Vec<String> {
+    let mut args = vec![
+        "--no-owner".to_string(),
+        "--no-privileges".to_string(),
+        "--single-transaction".to_string(),
+        format!("--dbname={database_url}"),
+    ];
+
+    if clean {
+        args.push("--clean".to_string());
+        args.push("--if-exists".to_string());
+    }
+
+    args.push(input.display().to_string());
+
+    args
+}
+
+fn run(program: &'static str, args: Vec<String>) -> Result<(), SnapshotError> {
+    let executable =
+        which::which(program).map_err(|e| SnapshotError::ExecutableNotFound(program, e))?;
+
+    let output = Command::new(executable)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| SnapshotError::Launch(program, e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SnapshotError::Failed(
+            program,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_args_include_scope() {
+        let scope = SnapshotScope {
+            schemas: vec!["Info".to_string(), "public".to_string()],
+            extensions: vec!["vector".to_string()],
+        };
+
+        assert_eq!(
+            dump_args(
+                "postgres://localhost/concerts",
+                &scope,
+                Some(Path::new("concerts.pgdump"))
+            ),
+            vec![
+                "--format=custom",
+                "--no-owner",
+                "--no-privileges",
+                "--dbname=postgres://localhost/concerts",
+                "--file=concerts.pgdump",
+                "--schema=\"Info\"",
+                "--schema=\"public\"",
+                "--extension=\"vector\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn restore_args_with_clean() {
+        assert_eq!(
+            restore_args(
+                "postgres://localhost/concerts",
+                Path::new("concerts.pgdump"),
+                true
+            ),
+            vec![
+                "--no-owner",
+                "--no-privileges",
+                "--single-transaction",
+                "--dbname=postgres://localhost/concerts",
+                "--clean",
+                "--if-exists",
+                "concerts.pgdump",
+            ]
+        );
+    }
+}
@@ -8,12 +8,14 @@
 // by the Apache License, Version 2.0.
 
 use maybe_owned::MaybeOwned;
+use serde::{Deserialize, Serialize};
 
 use crate::{ColumnId, Database, ParamEquality, Predicate, SchemaObjectName};
 
 use super::{
     ExpressionBuilder, SQLBuilder, SQLParamContainer, function::Function, json_agg::JsonAgg,
-    json_object::JsonObject, select::Select, transaction::TransactionStepId,
+    json_object::JsonObject, opaque_id::OpaqueIdKey, physical_column_type::PhysicalColumnType,
+    select::Select, transaction::TransactionStepId, traversal::Traversal, window::WindowSelect,
 };
 
 /// A column-like concept covering any usage where a database table column could be used. For
@@ -48,6 +50,10 @@ pub enum Column {
     JsonAgg(JsonAgg),
     /// A sub-select query.
     SubSelect(Box<Select>),
+    /// A recursive sub-select query that follows a self-referential relation.
+    Traversal(Box<Traversal>),
+    /// A sub-select query that computes a window function for the current row.
+    Window(Box<WindowSelect>),
     // TODO: Generalize the following to return any type of value, not just strings
     /// A constant string so that we can have a query return a particular value passed in as in
     /// `select 'Concert', id from "concerts"`. Here 'Concert' is the constant string. Needed to
@@ -66,11 +72,74 @@ pub enum Column {
         column: Box<Column>,
         path: Vec<String>,
     },
+    /// Extract a (non-object) value from a JSON column as text and cast it to `typ`, as in
+    /// `("customers"."address" #>> '{geo,lat}')::float8`
+    JsonFieldValue {
+        column: Box<Column>,
+        path: Vec<String>,
+        typ: Box<dyn PhysicalColumnType>,
+    },
     /// Extract a JSON field from each element of a JSON array and re-aggregate
     JsonArrayExtract {
         column: Box<Column>,
         key: String,
     },
+    /// An object if the JSON column holds an object (and null otherwise), as in `CASE WHEN
+    /// jsonb_typeof("todos"."info") = 'object' THEN json_build_object(...) END`
+    JsonProjection {
+        column: Box<Column>,
+        object: JsonObject,
+    },
+    /// A column with a fallback literal for null values, as in `COALESCE("todos"."title", 'Untitled')`
+    Coalesce {
+        column: Box<Column>,
+        fallback: String,
+    },
+    /// A (text) column with its value hidden (except for null values), fully or in part
+    Masked {
+        column: Box<Column>,
+        strategy: MaskStrategy,
+    },
+    /// An integer column exposed as an opaque id (see [`OpaqueIdKey`])
+    OpaqueId {
+        column: Box<Column>,
+        key: OpaqueIdKey,
+    },
+}
+
+/// How to mask a value
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MaskStrategy {
+    /// Replace the value with `****`
+    Full,
+    /// Keep only the last four characters, as in `****1234` (values with up to four characters are
+    /// fully masked)
+    Last4,
+    /// Keep only the first character of the local part of an email address, as in `j***@example.com`
+    Email,
+    /// Replace the value with null
+    Null,
+}
+
+impl MaskStrategy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "full" => Some(MaskStrategy::Full),
+            "last4" => Some(MaskStrategy::Last4),
+            "email" => Some(MaskStrategy::Email),
+            "null" => Some(MaskStrategy::Null),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MaskStrategy::Full => "full",
+            MaskStrategy::Last4 => "last4",
+            MaskStrategy::Email => "email",
+            MaskStrategy::Null => "null",
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -150,6 +219,8 @@ impl ExpressionBuilder for Column {
                 selection_table.build(database, builder);
                 builder.push(')');
             }
+            Column::Traversal(traversal) => traversal.build(database, builder),
+            Column::Window(window) => window.build(database, builder),
             Column::Constant(value) => {
                 builder.push('\'');
                 builder.push_str(value);
@@ -181,6 +252,15 @@ impl ExpressionBuilder for Column {
                 }
                 builder.push(')');
             }
+            Column::JsonFieldValue { column, path, typ } => {
+                builder.push_str("((");
+                column.build(database, builder);
+                builder.push_str(" #>> '{");
+                builder.push_iter(path.iter(), ",", |builder, key| builder.push_str(key));
+                builder.push_str("}')::");
+                builder.push_str(typ.get_pg_type().name());
+                builder.push(')');
+            }
             Column::JsonArrayExtract { column, key } => {
                 builder.push_str("(SELECT COALESCE(json_agg(elem -> '");
                 builder.push_str(key);
@@ -188,6 +268,45 @@ impl ExpressionBuilder for Column {
                 column.build(database, builder);
                 builder.push_str(", '[]'::json)) AS elem)");
             }
+            Column::JsonProjection { column, object } => {
+                builder.push_str("CASE WHEN jsonb_typeof(");
+                column.build(database, builder);
+                builder.push_str(") = 'object' THEN ");
+                object.build(database, builder);
+                builder.push_str(" END");
+            }
+            Column::Coalesce { column, fallback } => {
+                builder.push_str("COALESCE(");
+                column.build(database, builder);
+                builder.push_str(", '");
+                builder.push_str(&fallback.replace('\'', "''"));
+                builder.push_str("')");
+            }
+            Column::Masked { column, strategy } => match strategy {
+                MaskStrategy::Full => {
+                    builder.push_str("CASE WHEN ");
+                    column.build(database, builder);
+                    builder.push_str(" IS NULL THEN NULL ELSE '****' END");
+                }
+                MaskStrategy::Last4 => {
+                    builder.push_str("CASE WHEN ");
+                    column.build(database, builder);
+                    builder.push_str(" IS NULL THEN NULL WHEN length(");
+                    column.build(database, builder);
+                    builder.push_str(") <= 4 THEN '****' ELSE '****' || right(");
+                    column.build(database, builder);
+                    builder.push_str(", 4) END");
+                }
+                MaskStrategy::Email => {
+                    builder.push_str("regexp_replace(");
+                    column.build(database, builder);
+                    builder.push_str(", '^(.)[^@]*', '\\1***')");
+                }
+                MaskStrategy::Null => {
+                    builder.push_str("NULL");
+                }
+            },
+            Column::OpaqueId { column, key } => key.build_encoding(column, database, builder),
         }
     }
 }
@@ -0,0 +1,139 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Extraction of values from result rows with errors that identify the column.
+//!
+//! A failed conversion from `tokio_postgres` only says that a value couldn't be converted. When
+//! the model has drifted from the database (say, a column changed its type), knowing the column,
+//! its Postgres type, and the expected Rust type makes the problem much easier to track down.
+
+use std::fmt;
+
+use thiserror::Error;
+use tokio_postgres::{Row, types::FromSqlOwned};
+
+#[derive(Error, Debug)]
+pub struct ColumnExtractionError {
+    /// Name of the column (or its position, if the row has no such column)
+    pub column: String,
+    /// Name of the table, if the caller knows it
+    pub table: Option<String>,
+    /// OID of the table, if the column is a plain table column
+    pub table_oid: Option<u32>,
+    pub pg_type: String,
+    pub type_oid: u32,
+    pub rust_type: &'static str,
+    #[source]
+    pub source: tokio_postgres::Error,
+}
+
+impl ColumnExtractionError {
+    pub fn with_table(self, table: impl Into<String>) -> Self {
+        Self {
+            table: Some(table.into()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ColumnExtractionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to read column ")?;
+        match (&self.table, self.table_oid) {
+            (Some(table), _) => write!(f, "{table}.{}", self.column)?,
+            (None, Some(table_oid)) => write!(f, "{} (of table OID {table_oid})", self.column)?,
+            (None, None) => write!(f, "{}", self.column)?,
+        }
+        write!(
+            f,
+            " of Postgres type {} (OID {}) as {}: {}",
+            self.pg_type, self.type_oid, self.rust_type, self.source
+        )
+    }
+}
+
+/// Extract the value of the column at the index as `T`
+pub fn extract_column<T: FromSqlOwned>(
+    row: &Row,
+    index: usize,
+) -> Result<T, ColumnExtractionError> {
+    row.try_get(index).map_err(|source| {
+        let column = row.columns().get(index);
+
+        ColumnExtractionError {
+            column: column
+                .map(|column| column.name().to_string())
+                .unwrap_or_else(|| format!("#{index}")),
+            table: None,
+            table_oid: column.and_then(|column| column.table_oid()),
+            pg_type: column
+                .map(|column| column.type_().name().to_string())
+                .unwrap_or_else(|| "<none>".to_string()),
+            type_oid: column.map(|column| column.type_().oid()).unwrap_or(0),
+            rust_type: std::any::type_name::<T>(),
+            source,
+        }
+    })
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use crate::testing::test_support::*;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_column_and_types() {
+        let init_script = r#"
+            CREATE TABLE products (id SERIAL PRIMARY KEY, price TEXT);
+            INSERT INTO products (price) VALUES ('cheap');
+        "#;
+
+        with_init_script(init_script, |client| async move {
+            let row = client
+                .query_one("SELECT price, 'tax'::text AS tax FROM products", &[])
+                .await
+                .unwrap();
+
+            assert_eq!(extract_column::<String>(&row, 0).unwrap(), "cheap");
+
+            let error = extract_column::<i32>(&row, 0).unwrap_err();
+            assert_eq!(error.column, "price");
+            assert!(error.table_oid.is_some());
+            assert_eq!(error.pg_type, "text");
+            assert_eq!(error.rust_type, "i32");
+
+            let message = error.with_table("products").to_string();
+            let expected =
+                "Unable to read column products.price of Postgres type text (OID 25) as i32: ";
+            assert!(message.starts_with(expected), "{message}");
+
+            // A computed column doesn't belong to a table
+            let error = extract_column::<i64>(&row, 1).unwrap_err();
+            assert_eq!(error.table_oid, None);
+            let message = error.to_string();
+            let expected = "Unable to read column tax of Postgres type text (OID 25) as i64: ";
+            assert!(message.starts_with(expected), "{message}");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn reports_missing_column() {
+        with_client(|client| async move {
+            let row = client.query_one("SELECT 1::int4", &[]).await.unwrap();
+
+            let error = extract_column::<i32>(&row, 2).unwrap_err();
+            assert_eq!(error.column, "#2");
+            assert_eq!(error.pg_type, "<none>");
+            assert_eq!(error.type_oid, 0);
+        })
+        .await;
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![cfg(not(target_family = "wasm"))]
+
+//! A circuit breaker to fail fast while a dependency (such as the database) is down.
+//!
+//! After `failure_threshold` consecutive failures, the circuit opens and calls fail right away
+//! (with [`CircuitOpenError`]) for the cool-down period. Then a single trial call goes through:
+//! if it succeeds the circuit closes, otherwise it opens for another cool-down period. This
+//! keeps requests from piling up (each holding a task and waiting for a connection timeout)
+//! during an outage.
+
+use std::{
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use thiserror::Error;
+
+lazy_static! {
+    // All live breakers (to report their stats)
+    static ref BREAKERS: Mutex<Vec<Weak<CircuitBreaker>>> = Mutex::new(vec![]);
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    failure_threshold: u32,
+    cool_down: Duration,
+    state: Mutex<State>,
+    trips: AtomicU64,
+    rejections: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Closed {
+        consecutive_failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A trial call is in progress (if it doesn't finish within the cool-down period, another
+    /// trial is allowed)
+    HalfOpen {
+        trial_started: Instant,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerStats {
+    pub name: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Number of times the circuit has opened
+    pub trips: u64,
+    /// Number of calls rejected while the circuit was open
+    pub rejections: u64,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{name} is temporarily unavailable")]
+pub struct CircuitOpenError {
+    pub name: String,
+    /// Seconds until the next trial call is allowed
+    pub retry_after_secs: u64,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, failure_threshold: u32, cool_down: Duration) -> Arc<Self> {
+        let breaker = Arc::new(Self {
+            name: name.into(),
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+            state: Mutex::new(State::Closed {
+                consecutive_failures: 0,
+            }),
+            trips: AtomicU64::new(0),
+            rejections: AtomicU64::new(0),
+        });
+
+        let mut breakers = BREAKERS.lock().unwrap();
+        breakers.retain(|breaker| breaker.strong_count() > 0);
+        breakers.push(Arc::downgrade(&breaker));
+
+        breaker
+    }
+
+    /// Check if a call may proceed. Callers that get `Ok` must report the outcome of the call
+    /// through [`Self::record`].
+    pub fn check(&self) -> Result<(), CircuitOpenError> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        let retry_at = match *state {
+            State::Closed { .. } => return Ok(()),
+            State::Open { until } => until,
+            State::HalfOpen { trial_started } => trial_started + self.cool_down,
+        };
+
+        if now >= retry_at {
+            *state = State::HalfOpen { trial_started: now };
+            Ok(())
+        } else {
+            self.rejections.fetch_add(1, Ordering::Relaxed);
+            Err(CircuitOpenError {
+                name: self.name.clone(),
+                retry_after_secs: (retry_at - now).as_secs_f64().ceil() as u64,
+            })
+        }
+    }
+
+    pub fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap();
+
+        if success {
+            if !matches!(*state, State::Closed { .. }) {
+                tracing::info!("Circuit for {} closed", self.name);
+            }
+            *state = State::Closed {
+                consecutive_failures: 0,
+            };
+            return;
+        }
+
+        let consecutive_failures = match *state {
+            State::Closed {
+                consecutive_failures,
+            } => consecutive_failures + 1,
+            // A failed trial (or a late failure of a call admitted before the circuit opened)
+            _ => self.failure_threshold,
+        };
+
+        *state = if consecutive_failures >= self.failure_threshold {
+            if matches!(*state, State::Closed { .. }) {
+                let trips = self.trips.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::warn!(
+                    "Circuit for {} opened after {} consecutive failures (trips so far: {}); failing fast for {:?}",
+                    self.name,
+                    consecutive_failures,
+                    trips,
+                    self.cool_down
+                );
+            }
+            State::Open {
+                until: Instant::now() + self.cool_down,
+            }
+        } else {
+            State::Closed {
+                consecutive_failures,
+            }
+        };
+    }
+
+    pub fn stats(&self) -> CircuitBreakerStats {
+        let state = *self.state.lock().unwrap();
+
+        let (state, consecutive_failures) = match state {
+            State::Closed {
+                consecutive_failures,
+            } => (CircuitState::Closed, consecutive_failures),
+            State::Open { .. } => (CircuitState::Open, self.failure_threshold),
+            State::HalfOpen { .. } => (CircuitState::HalfOpen, self.failure_threshold),
+        };
+
+        CircuitBreakerStats {
+            name: self.name.clone(),
+            state,
+            consecutive_failures,
+            trips: self.trips.load(Ordering::Relaxed),
+            rejections: self.rejections.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Stats of all live circuit breakers
+    pub fn all_stats() -> Vec<CircuitBreakerStats> {
+        BREAKERS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|breaker| breaker.stats())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_and_recovers() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::ZERO);
+
+        assert!(breaker.check().is_ok());
+        breaker.record(false);
+        assert_eq!(breaker.stats().state, CircuitState::Closed);
+        breaker.record(false);
+        assert_eq!(breaker.stats().state, CircuitState::Open);
+        assert_eq!(breaker.stats().trips, 1);
+
+        // With no cool-down, the next call is a trial
+        assert!(breaker.check().is_ok());
+        assert_eq!(breaker.stats().state, CircuitState::HalfOpen);
+        breaker.record(true);
+        assert_eq!(breaker.stats().state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn fails_fast_while_open() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::from_secs(60));
+
+        breaker.record(false);
+        let error = breaker.check().unwrap_err();
+        assert_eq!(error.name, "test");
+        assert!(error.retry_after_secs > 0 && error.retry_after_secs <= 60);
+        assert_eq!(breaker.stats().rejections, 1);
+    }
+}
@@ -7,8 +7,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+#[cfg(not(target_family = "wasm"))]
+use std::sync::Arc;
+
 use crate::{Connect, database_error::DatabaseError};
 
+#[cfg(not(target_family = "wasm"))]
+use super::circuit_breaker::CircuitBreaker;
 use super::{creation::DatabaseCreation, database_client::DatabaseClient};
 
 #[cfg(feature = "postgres-url")]
@@ -17,12 +22,29 @@ use super::creation::TransactionMode;
 #[cfg(feature = "pool")]
 use super::database_pool::{DatabasePool, PoolConfig, PoolStatus};
 
-pub enum DatabaseClientManager {
+pub struct DatabaseClientManager {
+    source: ClientSource,
+    /// Fails client acquisition fast while the database is unreachable
+    #[cfg(not(target_family = "wasm"))]
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+}
+
+enum ClientSource {
     #[cfg(feature = "pool")]
     Pooled(DatabasePool),
     Direct(DatabaseCreation),
 }
 
+impl From<ClientSource> for DatabaseClientManager {
+    fn from(source: ClientSource) -> Self {
+        Self {
+            source,
+            #[cfg(not(target_family = "wasm"))]
+            circuit_breaker: None,
+        }
+    }
+}
+
 impl DatabaseClientManager {
     pub async fn from_connect_direct(
         check_connection: bool,
@@ -34,7 +56,7 @@ impl DatabaseClientManager {
             connect: Box::new(connect),
         };
 
-        let res = Ok(Self::Direct(creation));
+        let res = Ok(Self::from(ClientSource::Direct(creation)));
 
         if let Ok(ref res) = res
             && check_connection
@@ -57,9 +79,9 @@ impl DatabaseClientManager {
             connect: Box::new(connect),
         };
 
-        let res = Ok(Self::Pooled(
+        let res = Ok(Self::from(ClientSource::Pooled(
             DatabasePool::create(creation, Some(pool_size)).await?,
-        ));
+        )));
 
         if let Ok(ref res) = res
             && check_connection
@@ -70,20 +92,54 @@ impl DatabaseClientManager {
         res
     }
 
+    /// Guard client acquisition with the circuit breaker
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
     pub async fn get_client(&self) -> Result<DatabaseClient, DatabaseError> {
-        match self {
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            circuit_breaker.check()?;
+            let client = self.source.get_client().await;
+            circuit_breaker.record(client.is_ok());
+            return client;
+        }
+
+        self.source.get_client().await
+    }
+
+    /// Flush the connection pool (if using pooled connections) after a connection failure (see
+    /// [`DatabasePool::flush`])
+    pub fn flush_pool(
+        &self,
+        #[cfg_attr(not(feature = "pool"), allow(unused_variables))] cause: &DatabaseError,
+    ) {
+        match &self.source {
             #[cfg(feature = "pool")]
-            DatabaseClientManager::Pooled(pool) => pool.get_client().await,
-            DatabaseClientManager::Direct(creation) => creation.get_client().await,
+            ClientSource::Pooled(pool) => pool.flush(cause),
+            ClientSource::Direct(_) => {}
         }
     }
 
     /// Get the current status of the connection pool (if using pooled connections)
     #[cfg(feature = "pool")]
     pub fn pool_status(&self) -> Option<PoolStatus> {
+        match &self.source {
+            ClientSource::Pooled(pool) => Some(pool.status()),
+            ClientSource::Direct(_) => None,
+        }
+    }
+}
+
+impl ClientSource {
+    async fn get_client(&self) -> Result<DatabaseClient, DatabaseError> {
         match self {
-            DatabaseClientManager::Pooled(pool) => Some(pool.status()),
-            DatabaseClientManager::Direct(_) => None,
+            #[cfg(feature = "pool")]
+            ClientSource::Pooled(pool) => pool.get_client().await,
+            ClientSource::Direct(creation) => creation.get_client().await,
         }
     }
 }
@@ -124,9 +180,9 @@ impl DatabaseClientManager {
             url: url.to_string(),
             transaction_mode,
         };
-        let res = Ok(Self::Pooled(
+        let res = Ok(Self::from(ClientSource::Pooled(
             DatabasePool::create_with_config(creation, pool_config).await?,
-        ));
+        )));
 
         if let Ok(ref res) = res
             && check_connection
@@ -146,7 +202,7 @@ impl DatabaseClientManager {
             url: url.to_string(),
             transaction_mode,
         };
-        let res = Ok(DatabaseClientManager::Direct(creation));
+        let res = Ok(Self::from(ClientSource::Direct(creation)));
 
         if let Ok(ref res) = res
             && check_connection
@@ -9,7 +9,13 @@
 
 #![cfg(feature = "pool")]
 
-use std::time::Duration;
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 #[cfg(feature = "postgres-url")]
 use deadpool_postgres::ConfigConnectImpl;
@@ -17,12 +23,17 @@ use deadpool_postgres::{
     Connect, Hook, HookError, Manager, ManagerConfig, Pool, RecyclingMethod, Runtime,
 };
 
+use tokio::task::JoinHandle;
 use tokio_postgres::Config;
 
 use crate::TransactionMode;
 use crate::database_error::DatabaseError;
 
-use super::{creation::DatabaseCreation, database_client::DatabaseClient};
+use super::{
+    creation::DatabaseCreation,
+    database_client::DatabaseClient,
+    pool_sizing::{AdaptivePoolConfig, WaitStats, adapt_size},
+};
 
 /// Configuration for the database connection pool.
 #[derive(Debug, Clone, Default)]
@@ -37,6 +48,30 @@ pub struct PoolConfig {
     pub recycle_timeout_secs: Option<u64>,
     /// Maximum lifetime of a connection in seconds before forced recycling (default: 900 = 15min)
     pub max_lifetime_secs: Option<u64>,
+    /// Number of connections to create at startup (and keep around if probing is enabled)
+    pub min_idle: Option<usize>,
+    /// Interval in seconds between validating idle connections (default: no probing)
+    pub probe_interval_secs: Option<u64>,
+    /// Interval in seconds between re-resolving the database host names, to rebuild the
+    /// connections when the addresses change, such as after a DNS-based failover (default: 30s;
+    /// 0 disables re-resolving)
+    pub dns_refresh_secs: Option<u64>,
+    /// Bounds for resizing the pool based on load (default: fixed size)
+    pub adaptive: Option<AdaptivePoolConfig>,
+    /// Idle time in seconds before sending TCP keepalive probes (default: 2 hours), so that
+    /// proxies and NATs that drop idle connections keep them open
+    pub keepalive_idle_secs: Option<u64>,
+    /// Interval in seconds between unanswered TCP keepalive probes (default: the OS setting)
+    pub keepalive_interval_secs: Option<u64>,
+    /// Number of unanswered TCP keepalive probes before the connection is considered dead
+    /// (default: the OS setting)
+    pub keepalive_retries: Option<u32>,
+    /// The `application_name` the connections report to the database (overrides the one in the
+    /// URL)
+    pub application_name: Option<String>,
+    /// Command-line options sent to the server at connection startup, such as
+    /// `-c statement_timeout=5000` (overrides the ones in the URL)
+    pub options: Option<String>,
 }
 
 impl PoolConfig {
@@ -68,8 +103,80 @@ impl PoolConfig {
         self.max_lifetime_secs = Some(secs);
         self
     }
+
+    pub fn with_min_idle(mut self, count: usize) -> Self {
+        self.min_idle = Some(count);
+        self
+    }
+
+    pub fn with_probe_interval(mut self, secs: u64) -> Self {
+        self.probe_interval_secs = Some(secs);
+        self
+    }
+
+    pub fn with_dns_refresh_interval(mut self, secs: u64) -> Self {
+        self.dns_refresh_secs = Some(secs);
+        self
+    }
+
+    pub fn with_adaptive_size(mut self, adaptive: AdaptivePoolConfig) -> Self {
+        self.adaptive = Some(adaptive);
+        self
+    }
+
+    pub fn with_keepalive_idle(mut self, secs: u64) -> Self {
+        self.keepalive_idle_secs = Some(secs);
+        self
+    }
+
+    pub fn with_keepalive_interval(mut self, secs: u64) -> Self {
+        self.keepalive_interval_secs = Some(secs);
+        self
+    }
+
+    pub fn with_keepalive_retries(mut self, retries: u32) -> Self {
+        self.keepalive_retries = Some(retries);
+        self
+    }
+
+    pub fn with_application_name(mut self, application_name: impl Into<String>) -> Self {
+        self.application_name = Some(application_name.into());
+        self
+    }
+
+    pub fn with_options(mut self, options: impl Into<String>) -> Self {
+        self.options = Some(options.into());
+        self
+    }
+
+    /// Apply the connection options to the configuration used to create connections
+    fn apply_connection_options(&self, config: &mut Config) {
+        if let Some(secs) = self.keepalive_idle_secs {
+            config.keepalives(true);
+            config.keepalives_idle(Duration::from_secs(secs.max(1)));
+        }
+        if let Some(secs) = self.keepalive_interval_secs {
+            config.keepalives(true);
+            config.keepalives_interval(Duration::from_secs(secs.max(1)));
+        }
+        if let Some(retries) = self.keepalive_retries {
+            config.keepalives(true);
+            config.keepalives_retries(retries);
+        }
+        if let Some(application_name) = &self.application_name {
+            config.application_name(application_name);
+        }
+        if let Some(options) = &self.options {
+            config.options(options);
+        }
+    }
 }
 
+/// Concurrent requests failing because of the same restart flush the pool only once
+const MIN_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+const DEFAULT_DNS_REFRESH_SECS: u64 = 30;
+
 /// The current status of the connection pool.
 #[derive(Debug, Clone)]
 pub struct PoolStatus {
@@ -81,10 +188,56 @@ pub struct PoolStatus {
     pub available: usize,
     /// Number of tasks waiting for a connection
     pub waiting: usize,
+    /// Number of times the pool was flushed (after a connection failure or a change in the
+    /// addresses of the database host)
+    pub flushes: u64,
+    /// Time since the pool was last flushed
+    pub since_last_flush: Option<Duration>,
+}
+
+#[derive(Default)]
+struct FlushState {
+    /// Connections created before this are discarded instead of being reused
+    last_flush: Mutex<Option<Instant>>,
+    flushes: AtomicU64,
+}
+
+impl FlushState {
+    fn last_flush(&self) -> Option<Instant> {
+        *self.last_flush.lock().unwrap()
+    }
+
+    /// Drop the idle connections, and mark those in use to be dropped once returned. Returns the
+    /// number of dropped idle connections (`None`, if the pool was flushed just now).
+    fn flush(&self, pool: &Pool) -> Option<usize> {
+        let now = Instant::now();
+        {
+            let mut last_flush = self.last_flush.lock().unwrap();
+            if last_flush.is_some_and(|last_flush| now - last_flush < MIN_FLUSH_INTERVAL) {
+                return None;
+            }
+            *last_flush = Some(now);
+        }
+        self.flushes.fetch_add(1, Ordering::Relaxed);
+
+        Some(pool.retain(|_, _| false).removed.len())
+    }
 }
 
 pub struct DatabasePool {
     pool: Pool,
+    wait_stats: Arc<WaitStats>,
+    flush_state: Arc<FlushState>,
+    /// Probing and resizing tasks (stopped when the pool is dropped)
+    background_tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for DatabasePool {
+    fn drop(&mut self) {
+        for task in &self.background_tasks {
+            task.abort();
+        }
+    }
 }
 
 impl DatabasePool {
@@ -118,7 +271,11 @@ impl DatabasePool {
     }
 
     pub async fn get_client(&self) -> Result<DatabaseClient, DatabaseError> {
-        match self.pool.get().await {
+        let start = Instant::now();
+        let client = self.pool.get().await;
+        self.wait_stats.record(start.elapsed());
+
+        match client {
             Ok(client) => Ok(DatabaseClient::Pooled(client)),
             Err(err) => {
                 let status = self.pool.status();
@@ -143,9 +300,31 @@ impl DatabasePool {
             size: status.size,
             available: status.available,
             waiting: status.waiting,
+            flushes: self.flush_state.flushes.load(Ordering::Relaxed),
+            since_last_flush: self
+                .flush_state
+                .last_flush()
+                .map(|last_flush| last_flush.elapsed()),
         }
     }
 
+    /// Discard all connections after a connection failure (such as a database restart or
+    /// failover), since the other connections are most likely broken as well. Idle connections are
+    /// dropped right away, and those in use are dropped when returned to the pool, so that
+    /// subsequent requests get fresh connections instead of finding the broken ones one at a time.
+    pub fn flush(&self, cause: &DatabaseError) {
+        let Some(dropped) = self.flush_state.flush(&self.pool) else {
+            return;
+        };
+
+        tracing::warn!(
+            event = "db_pool_flushed",
+            error = %cause,
+            dropped,
+            "Database connection failure detected; flushed the connection pool"
+        );
+    }
+
     #[cfg(feature = "postgres-url")]
     async fn from_db_url_with_config(
         url: &str,
@@ -217,21 +396,33 @@ impl DatabasePool {
 
     pub async fn from_connect_with_config(
         pool_config: PoolConfig,
-        config: Config,
+        mut config: Config,
         connect: impl Connect + 'static,
     ) -> Result<Self, DatabaseError> {
+        pool_config.apply_connection_options(&mut config);
+
         // Validate connections when checked out so stale connections are not reused.
         let manager_config = ManagerConfig {
             recycling_method: RecyclingMethod::Verified,
         };
 
+        let hosts = tcp_hosts(&config);
         let manager = Manager::from_connect(config, connect, manager_config);
 
         // Build pool with timeouts - requires runtime for timeout support
         let mut builder = Pool::builder(manager).runtime(Runtime::Tokio1);
 
-        // Apply max_size
-        if let Some(max_size) = pool_config.max_size {
+        // Apply max_size (starting within the bounds, if adaptive)
+        let max_size = match &pool_config.adaptive {
+            Some(adaptive) => Some(
+                pool_config
+                    .max_size
+                    .unwrap_or(10)
+                    .clamp(adaptive.min_size, adaptive.max_size.max(adaptive.min_size)),
+            ),
+            None => pool_config.max_size,
+        };
+        if let Some(max_size) = max_size {
             builder = builder.max_size(max_size);
         }
 
@@ -250,7 +441,18 @@ impl DatabasePool {
         // Add pre_recycle hook to enforce max_lifetime
         // This rejects connections that are too old, causing the pool to create fresh ones
         let max_lifetime = Duration::from_secs(max_lifetime_secs);
+        let flush_state = Arc::new(FlushState::default());
+        let hook_flush_state = flush_state.clone();
         builder = builder.pre_recycle(Hook::sync_fn(move |_conn, metrics| {
+            // Connections checked out when the pool was flushed are discarded once returned
+            if hook_flush_state
+                .last_flush()
+                .is_some_and(|last_flush| metrics.created <= last_flush)
+            {
+                return Err(HookError::Message(
+                    "Connection predates the last pool flush".into(),
+                ));
+            }
             if metrics.age() > max_lifetime {
                 tracing::debug!(
                     age_secs = metrics.age().as_secs(),
@@ -266,22 +468,183 @@ impl DatabasePool {
 
         // Log pool configuration for debugging
         tracing::info!(
-            max_size = pool_config.max_size.unwrap_or(10),
+            max_size = max_size.unwrap_or(10),
             wait_timeout_secs = wait_timeout,
             create_timeout_secs = create_timeout,
             recycle_timeout_secs = recycle_timeout,
             max_lifetime_secs = max_lifetime_secs,
+            keepalive_idle_secs = pool_config.keepalive_idle_secs,
+            application_name = pool_config.application_name.as_deref(),
             "Creating database connection pool"
         );
 
         let pool = builder.build().expect("Failed to create DB pool");
 
-        let db = Self { pool };
+        let min_idle = pool_config
+            .min_idle
+            .unwrap_or(0)
+            .min(pool.status().max_size);
+        if min_idle > 0 {
+            prefill(&pool, min_idle).await;
+        }
+
+        let wait_stats = Arc::new(WaitStats::default());
+        let mut background_tasks = vec![];
+
+        if let Some(interval_secs) = pool_config.probe_interval_secs {
+            background_tasks.push(tokio::spawn(probe(
+                pool.clone(),
+                Duration::from_secs(interval_secs.max(1)),
+                min_idle,
+            )));
+        }
+
+        let dns_refresh_secs = pool_config
+            .dns_refresh_secs
+            .unwrap_or(DEFAULT_DNS_REFRESH_SECS);
+        // Addresses given as IPs never change
+        let host_names: Vec<_> = hosts
+            .into_iter()
+            .filter(|(host, _)| host.parse::<std::net::IpAddr>().is_err())
+            .collect();
+        if dns_refresh_secs > 0 && !host_names.is_empty() {
+            background_tasks.push(tokio::spawn(refresh_dns(
+                pool.clone(),
+                flush_state.clone(),
+                Duration::from_secs(dns_refresh_secs),
+                host_names,
+            )));
+        }
+
+        if let Some(adaptive) = pool_config.adaptive {
+            background_tasks.push(tokio::spawn(adapt_size(
+                pool.clone(),
+                adaptive,
+                wait_stats.clone(),
+            )));
+        }
+
+        let db = Self {
+            pool,
+            wait_stats,
+            flush_state,
+            background_tasks,
+        };
 
         Ok(db)
     }
 }
 
+/// Make sure that the pool has at least `count` connections, by checking out that many
+/// connections at once (creating new ones as needed, and validating idle ones) and returning
+/// them to the pool.
+async fn prefill(pool: &Pool, count: usize) {
+    let connections = futures::future::join_all((0..count).map(|_| pool.get())).await;
+    let failures = connections.iter().filter(|conn| conn.is_err()).count();
+
+    if failures > 0 {
+        tracing::warn!(
+            requested = count,
+            failed = failures,
+            "Failed to create some database connections for the pool"
+        );
+    } else {
+        tracing::debug!(count, "Filled database connection pool");
+    }
+}
+
+/// Periodically validate idle connections (replacing broken ones) and keep `min_idle`
+/// connections around.
+async fn probe(pool: Pool, interval: Duration, min_idle: usize) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        // Checking out connections validates them (see `RecyclingMethod::Verified`)
+        let available = pool.status().available;
+        prefill(&pool, available.max(min_idle)).await;
+    }
+}
+
+/// Periodically re-resolve the database host names. If they resolve to different addresses (such
+/// as after a DNS-based failover), flush the pool, so that connections to the stale addresses
+/// (which may still accept connections, for example, as a demoted read-only replica) are replaced
+/// with ones to the new addresses.
+async fn refresh_dns(
+    pool: Pool,
+    flush_state: Arc<FlushState>,
+    interval: Duration,
+    hosts: Vec<(String, u16)>,
+) {
+    let mut resolved = resolve_hosts(&hosts).await;
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let now_resolved = resolve_hosts(&hosts).await;
+        flush_on_address_change(&pool, &flush_state, &mut resolved, now_resolved);
+    }
+}
+
+/// Flush the pool if the host names now resolve to different addresses (and remember them)
+fn flush_on_address_change(
+    pool: &Pool,
+    flush_state: &FlushState,
+    resolved: &mut Vec<std::net::SocketAddr>,
+    now_resolved: Vec<std::net::SocketAddr>,
+) {
+    // Keep the known addresses if resolution fails (the DNS server may be briefly unavailable)
+    if now_resolved.is_empty() || now_resolved == *resolved {
+        return;
+    }
+
+    tracing::warn!(
+        event = "db_pool_flushed",
+        previous = ?resolved,
+        current = ?now_resolved,
+        dropped = flush_state.flush(pool),
+        "Database host resolves to different addresses; flushed the connection pool"
+    );
+    *resolved = now_resolved;
+}
+
+fn tcp_hosts(config: &Config) -> Vec<(String, u16)> {
+    let ports = config.get_ports();
+
+    config
+        .get_hosts()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, host)| match host {
+            tokio_postgres::config::Host::Tcp(host) => {
+                // Either one port for all hosts or a port for each host
+                let port = ports
+                    .get(index)
+                    .or_else(|| ports.first())
+                    .copied()
+                    .unwrap_or(5432);
+                Some((host.clone(), port))
+            }
+            #[allow(unreachable_patterns)]
+            _ => None,
+        })
+        .collect()
+}
+
+async fn resolve_hosts(hosts: &[(String, u16)]) -> Vec<std::net::SocketAddr> {
+    let mut addresses = vec![];
+
+    for (host, port) in hosts {
+        match tokio::net::lookup_host((host.as_str(), *port)).await {
+            Ok(resolved) => addresses.extend(resolved),
+            Err(err) => tracing::warn!("Failed to resolve database host {host}: {err}"),
+        }
+    }
+
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
 struct ConnectBridge(Box<dyn super::creation::Connect>);
 
 impl Connect for ConnectBridge {
@@ -295,3 +658,221 @@ impl Connect for ConnectBridge {
         self.0.connect(pg_config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn applies_connection_options() {
+        let mut config = Config::new();
+        config.application_name("from-url");
+
+        PoolConfig::new()
+            .with_keepalive_idle(60)
+            .with_keepalive_interval(10)
+            .with_keepalive_retries(3)
+            .with_options("-c statement_timeout=5000")
+            .apply_connection_options(&mut config);
+
+        assert!(config.get_keepalives());
+        assert_eq!(config.get_keepalives_idle(), Duration::from_secs(60));
+        assert_eq!(
+            config.get_keepalives_interval(),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(config.get_keepalives_retries(), Some(3));
+        assert_eq!(config.get_options(), Some("-c statement_timeout=5000"));
+        // Not configured, so the one from the URL is kept
+        assert_eq!(config.get_application_name(), Some("from-url"));
+
+        PoolConfig::new()
+            .with_application_name("exograph")
+            .apply_connection_options(&mut config);
+        assert_eq!(config.get_application_name(), Some("exograph"));
+    }
+
+    async fn backend_pid(client: &DatabaseClient) -> i32 {
+        client
+            .query_one("SELECT pg_backend_pid()", &[])
+            .await
+            .unwrap()
+            .get(0)
+    }
+
+    #[tokio::test]
+    async fn flush_discards_connections() {
+        use crate::testing::test_support::with_database_url;
+
+        with_database_url(|url| async move {
+            let pool = DatabasePool::from_db_url_with_config(
+                &url,
+                PoolConfig::new(),
+                TransactionMode::ReadWrite,
+            )
+            .await
+            .unwrap();
+
+            let idle = pool.get_client().await.unwrap();
+            let in_use = pool.get_client().await.unwrap();
+            let in_use_pid = backend_pid(&in_use).await;
+            drop(idle);
+            assert_eq!(pool.status().size, 2);
+
+            let cause = DatabaseError::Generic("connection closed".to_string());
+            pool.flush(&cause);
+
+            // The idle connection is dropped right away
+            let status = pool.status();
+            assert_eq!(status.flushes, 1);
+            assert!(status.since_last_flush.is_some());
+            assert_eq!(status.size, 1);
+
+            // Flushing again right after (for another request failing because of the same
+            // restart) is a no-op
+            pool.flush(&cause);
+            assert_eq!(pool.status().flushes, 1);
+
+            // The connection in use when flushing isn't reused once returned
+            drop(in_use);
+            let client = pool.get_client().await.unwrap();
+            assert_ne!(backend_pid(&client).await, in_use_pid);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn prefills_min_idle_connections() {
+        use crate::testing::test_support::with_database_url;
+
+        with_database_url(|url| async move {
+            let pool = DatabasePool::from_db_url_with_config(
+                &url,
+                PoolConfig::new().with_min_idle(3),
+                TransactionMode::ReadWrite,
+            )
+            .await
+            .unwrap();
+            let status = pool.status();
+            assert_eq!(status.size, 3);
+            assert_eq!(status.available, 3);
+
+            // Never more than the pool may hold
+            let pool = DatabasePool::from_db_url_with_config(
+                &url,
+                PoolConfig::new().with_max_size(2).with_min_idle(5),
+                TransactionMode::ReadWrite,
+            )
+            .await
+            .unwrap();
+            assert_eq!(pool.status().size, 2);
+
+            // Without a minimum, connections are created on demand
+            let pool = DatabasePool::from_db_url_with_config(
+                &url,
+                PoolConfig::new(),
+                TransactionMode::ReadWrite,
+            )
+            .await
+            .unwrap();
+            assert_eq!(pool.status().size, 0);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn probe_restores_min_idle_connections() {
+        use crate::testing::test_support::with_database_url;
+
+        with_database_url(|url| async move {
+            let pool = DatabasePool::from_db_url_with_config(
+                &url,
+                PoolConfig::new().with_min_idle(2).with_probe_interval(1),
+                TransactionMode::ReadWrite,
+            )
+            .await
+            .unwrap();
+            assert_eq!(pool.status().size, 2);
+
+            pool.flush(&DatabaseError::Generic("connection closed".to_string()));
+            assert_eq!(pool.status().size, 0);
+
+            // The next probe creates the connections again (rather than the next requests)
+            tokio::time::sleep(Duration::from_millis(2500)).await;
+            let status = pool.status();
+            assert_eq!(status.size, 2);
+            assert_eq!(status.available, 2);
+        })
+        .await;
+    }
+
+    #[test]
+    fn tcp_hosts_with_ports() {
+        let config = Config::from_str("postgres://db.example.com:6543/app").unwrap();
+        assert_eq!(
+            tcp_hosts(&config),
+            vec![("db.example.com".to_string(), 6543)]
+        );
+
+        let config = Config::from_str("host=primary.example.com,10.0.0.2 dbname=app").unwrap();
+        assert_eq!(
+            tcp_hosts(&config),
+            vec![
+                ("primary.example.com".to_string(), 5432),
+                ("10.0.0.2".to_string(), 5432)
+            ]
+        );
+
+        let config = Config::from_str("host=a.example.com,b.example.com port=5433,5434").unwrap();
+        assert_eq!(
+            tcp_hosts(&config),
+            vec![
+                ("a.example.com".to_string(), 5433),
+                ("b.example.com".to_string(), 5434)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolves_hosts() {
+        let addresses = resolve_hosts(&[
+            ("localhost".to_string(), 5432),
+            ("127.0.0.1".to_string(), 5432),
+            ("unknown.invalid".to_string(), 5432),
+        ])
+        .await;
+
+        // Sorted, without duplicates, and skipping the hosts that don't resolve
+        assert!(addresses.contains(&"127.0.0.1:5432".parse().unwrap()));
+        assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[tokio::test]
+    async fn flushes_on_address_change() {
+        let manager = Manager::new(Config::new(), tokio_postgres::NoTls);
+        let pool = Pool::builder(manager)
+            .runtime(Runtime::Tokio1)
+            .build()
+            .unwrap();
+        let flush_state = FlushState::default();
+
+        let primary: std::net::SocketAddr = "10.0.0.1:5432".parse().unwrap();
+        let replica: std::net::SocketAddr = "10.0.0.2:5432".parse().unwrap();
+        let mut resolved = vec![primary];
+
+        flush_on_address_change(&pool, &flush_state, &mut resolved, vec![primary]);
+        assert_eq!(flush_state.flushes.load(Ordering::Relaxed), 0);
+
+        // A failed resolution keeps the known addresses
+        flush_on_address_change(&pool, &flush_state, &mut resolved, vec![]);
+        assert_eq!(flush_state.flushes.load(Ordering::Relaxed), 0);
+        assert_eq!(resolved, vec![primary]);
+
+        flush_on_address_change(&pool, &flush_state, &mut resolved, vec![replica]);
+        assert_eq!(flush_state.flushes.load(Ordering::Relaxed), 1);
+        assert!(flush_state.last_flush().is_some());
+        assert_eq!(resolved, vec![replica]);
+    }
+}
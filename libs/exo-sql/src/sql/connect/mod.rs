@@ -7,8 +7,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod circuit_breaker;
 pub mod creation;
 pub mod database_client;
 pub mod database_client_manager;
 pub mod database_pool;
+pub mod pool_sizing;
 pub mod ssl_config;
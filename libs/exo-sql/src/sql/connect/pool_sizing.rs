@@ -0,0 +1,176 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![cfg(feature = "pool")]
+
+//! Adaptive sizing of the connection pool.
+//!
+//! Periodically samples how long requests waited for a connection and how many are waiting,
+//! then grows the pool (when requests wait) or shrinks it (after a sustained period of low use)
+//! between the configured bounds.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use deadpool_postgres::Pool;
+
+/// Wait time (for a connection) beyond which the pool grows
+const GROW_WAIT_THRESHOLD: Duration = Duration::from_millis(50);
+/// Number of consecutive low-use intervals before the pool shrinks
+const SHRINK_AFTER_INTERVALS: u32 = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptivePoolConfig {
+    pub min_size: usize,
+    pub max_size: usize,
+    /// Interval between sizing decisions (default: 10s)
+    pub interval_secs: Option<u64>,
+}
+
+/// Time spent waiting for connections (since the last sample)
+#[derive(Debug, Default)]
+pub(super) struct WaitStats {
+    total_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl WaitStats {
+    pub(super) fn record(&self, wait: Duration) {
+        self.total_micros
+            .fetch_add(wait.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Average wait since the last call
+    fn take_average(&self) -> Duration {
+        let total_micros = self.total_micros.swap(0, Ordering::Relaxed);
+        let count = self.count.swap(0, Ordering::Relaxed);
+
+        if count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_micros(total_micros / count)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LoadSample {
+    max_size: usize,
+    in_use: usize,
+    waiting: usize,
+    average_wait: Duration,
+}
+
+pub(super) async fn adapt_size(pool: Pool, config: AdaptivePoolConfig, wait_stats: Arc<WaitStats>) {
+    let interval = Duration::from_secs(config.interval_secs.unwrap_or(10).max(1));
+    let mut low_use_intervals = 0;
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let status = pool.status();
+        let sample = LoadSample {
+            max_size: status.max_size,
+            in_use: status.size.saturating_sub(status.available),
+            waiting: status.waiting,
+            average_wait: wait_stats.take_average(),
+        };
+
+        if let Some(new_size) = next_size(&config, sample, &mut low_use_intervals) {
+            tracing::info!(
+                from = sample.max_size,
+                to = new_size,
+                in_use = sample.in_use,
+                waiting = sample.waiting,
+                average_wait_ms = sample.average_wait.as_millis() as u64,
+                "Resizing database connection pool"
+            );
+            pool.resize(new_size);
+        }
+    }
+}
+
+/// The new size of the pool (if it should change)
+fn next_size(
+    config: &AdaptivePoolConfig,
+    sample: LoadSample,
+    low_use_intervals: &mut u32,
+) -> Option<usize> {
+    let current = sample.max_size;
+    let step = (current / 4).max(1);
+
+    let new_size = if sample.waiting > 0 || sample.average_wait > GROW_WAIT_THRESHOLD {
+        *low_use_intervals = 0;
+        (current + step).min(config.max_size)
+    } else if sample.in_use * 2 < current {
+        *low_use_intervals += 1;
+        if *low_use_intervals < SHRINK_AFTER_INTERVALS {
+            return None;
+        }
+        *low_use_intervals = 0;
+        current.saturating_sub(step).max(config.min_size)
+    } else {
+        *low_use_intervals = 0;
+        current
+    };
+
+    (new_size != current).then_some(new_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(max_size: usize, in_use: usize, waiting: usize, wait_ms: u64) -> LoadSample {
+        LoadSample {
+            max_size,
+            in_use,
+            waiting,
+            average_wait: Duration::from_millis(wait_ms),
+        }
+    }
+
+    #[test]
+    fn grows_and_shrinks_within_bounds() {
+        let config = AdaptivePoolConfig {
+            min_size: 4,
+            max_size: 12,
+            interval_secs: None,
+        };
+        let mut low_use = 0;
+
+        assert_eq!(
+            next_size(&config, sample(8, 8, 3, 0), &mut low_use),
+            Some(10)
+        );
+        assert_eq!(
+            next_size(&config, sample(8, 5, 0, 200), &mut low_use),
+            Some(10)
+        );
+        assert_eq!(next_size(&config, sample(12, 12, 5, 0), &mut low_use), None);
+        assert_eq!(next_size(&config, sample(8, 6, 0, 0), &mut low_use), None);
+
+        for _ in 1..SHRINK_AFTER_INTERVALS {
+            assert_eq!(next_size(&config, sample(8, 1, 0, 0), &mut low_use), None);
+        }
+        assert_eq!(
+            next_size(&config, sample(8, 1, 0, 0), &mut low_use),
+            Some(6)
+        );
+
+        low_use = SHRINK_AFTER_INTERVALS;
+        assert_eq!(next_size(&config, sample(4, 0, 0, 0), &mut low_use), None);
+    }
+}
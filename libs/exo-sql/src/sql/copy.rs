@@ -0,0 +1,70 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Bulk insertion of rows using `COPY ... FROM STDIN` (in the binary format).
+
+use std::pin::pin;
+
+use tokio_postgres::{
+    Transaction,
+    binary_copy::BinaryCopyInWriter,
+    types::{Kind, ToSql, Type},
+};
+
+use crate::{ColumnId, Database, SQLParamContainer, TableId, database_error::DatabaseError};
+
+/// Copy rows into a table, returning the number of rows copied.
+///
+/// Each row supplies values for `column_ids` (in the same order), where `None` stands for `NULL`.
+/// Columns not in `column_ids` get their default values.
+pub(crate) async fn copy_rows(
+    tx: &Transaction<'_>,
+    database: &Database,
+    table_id: TableId,
+    column_ids: &[ColumnId],
+    rows: &[Vec<Option<SQLParamContainer>>],
+) -> Result<u64, DatabaseError> {
+    let table_name = database.get_table(table_id).name.sql_name();
+    let column_names = column_ids
+        .iter()
+        .map(|column_id| format!("\"{}\"", column_id.get_column(database).name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // The binary format needs the exact column types (including the ones of user-defined types
+    // such as enums), so let the database tell us
+    let statement = tx
+        .prepare(&format!("SELECT {column_names} FROM {table_name} LIMIT 0"))
+        .await?;
+    let types: Vec<Type> = statement
+        .columns()
+        .iter()
+        .map(|column| match column.type_().kind() {
+            // Enum values are sent as their labels (whose binary representation is the same as
+            // text), so encode them as such
+            Kind::Enum(_) => Type::TEXT,
+            _ => column.type_().clone(),
+        })
+        .collect();
+
+    let sink = tx
+        .copy_in(format!("COPY {table_name} ({column_names}) FROM STDIN BINARY").as_str())
+        .await?;
+    let mut writer = pin!(BinaryCopyInWriter::new(sink, &types));
+
+    for row in rows {
+        let values: Vec<&(dyn ToSql + Sync)> = row
+            .iter()
+            .map(|value| value as &(dyn ToSql + Sync))
+            .collect();
+        writer.as_mut().write(&values).await?;
+    }
+
+    Ok(writer.as_mut().finish().await?)
+}
@@ -14,7 +14,7 @@ use crate::{ColumnId, ManyToOne, PhysicalColumn, PhysicalTable, SchemaObjectName
 use serde::{Deserialize, Serialize};
 use typed_generational_arena::{Arena, IgnoreGeneration, Index};
 
-use super::physical_table::PhysicalEnum;
+use super::physical_table::{PhysicalEnum, SchemaRename};
 
 pub type SerializableSlab<T> = Arena<T, usize, IgnoreGeneration>;
 pub type TableId = Index<PhysicalTable, usize, IgnoreGeneration>;
@@ -24,6 +24,8 @@ pub struct Database {
     tables: SerializableSlab<PhysicalTable>,
     enums: SerializableSlab<PhysicalEnum>,
     pub relations: Vec<ManyToOne>,
+    /// Renames declared in the model (used only for migrations)
+    pub renames: Vec<SchemaRename>,
 }
 
 impl Database {
@@ -139,6 +141,7 @@ impl Default for Database {
             tables: SerializableSlab::new(),
             enums: SerializableSlab::new(),
             relations: vec![],
+            renames: vec![],
         }
     }
 }
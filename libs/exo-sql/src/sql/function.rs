@@ -1,4 +1,7 @@
-use crate::{ColumnId, Database, PhysicalColumnTypeExt, SQLParamContainer, VectorDistanceFunction};
+use crate::{
+    ColumnId, Database, PhysicalColumnTypeExt, SQLParamContainer, SqlExpression,
+    VectorDistanceFunction,
+};
 
 use super::{ExpressionBuilder, SQLBuilder};
 
@@ -13,6 +16,83 @@ pub enum Function {
         distance_function: VectorDistanceFunction,
         target: SQLParamContainer,
     },
+    /// String functions applied (in order) to a column, such as `substring(lower("concerts"."title") FROM 1 FOR 10)`
+    StringTransform {
+        column_id: ColumnId,
+        transforms: Vec<StringFunction>,
+    },
+    /// Date/time functions applied to a column, such as `to_char(date_trunc('month', "concerts"."start_time", $1), $2)`.
+    /// The timezone (if any) applies to both truncation and formatting.
+    DateTransform {
+        column_id: ColumnId,
+        truncate: Option<DateTruncUnit>,
+        format: Option<SQLParamContainer>,
+        timezone: Option<SQLParamContainer>,
+    },
+    /// An expression declared in the model, such as `("orders"."price" * "orders"."quantity")`
+    Expression(SqlExpression),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DateTruncUnit {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl DateTruncUnit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DateTruncUnit::Year => "year",
+            DateTruncUnit::Quarter => "quarter",
+            DateTruncUnit::Month => "month",
+            DateTruncUnit::Week => "week",
+            DateTruncUnit::Day => "day",
+            DateTruncUnit::Hour => "hour",
+            DateTruncUnit::Minute => "minute",
+            DateTruncUnit::Second => "second",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringFunction {
+    Lower,
+    Upper,
+    Trim,
+    /// `substring(<value> FROM <start> FOR <length>)` (`start` is 1-based, as in Postgres)
+    Substring {
+        start: i64,
+        length: Option<i64>,
+    },
+}
+
+impl StringFunction {
+    fn prefix(&self) -> &'static str {
+        match self {
+            StringFunction::Lower => "lower(",
+            StringFunction::Upper => "upper(",
+            StringFunction::Trim => "trim(",
+            StringFunction::Substring { .. } => "substring(",
+        }
+    }
+
+    fn push_suffix(&self, builder: &mut SQLBuilder) {
+        if let StringFunction::Substring { start, length } = self {
+            builder.push_str(" FROM ");
+            builder.push_str(start.to_string());
+            if let Some(length) = length {
+                builder.push_str(" FOR ");
+                builder.push_str(length.to_string());
+            }
+        }
+        builder.push(')');
+    }
 }
 
 impl ExpressionBuilder for Function {
@@ -49,6 +129,69 @@ impl ExpressionBuilder for Function {
                 builder.push_param(target.param());
                 builder.push_str("::vector");
             }
+            Function::StringTransform {
+                column_id,
+                transforms,
+            } => {
+                // The first transform is the innermost one
+                for transform in transforms.iter().rev() {
+                    builder.push_str(transform.prefix());
+                }
+                column_id.get_column(database).build(database, builder);
+                for transform in transforms.iter() {
+                    transform.push_suffix(builder);
+                }
+            }
+            Function::DateTransform {
+                column_id,
+                truncate,
+                format,
+                timezone,
+            } => {
+                let column = column_id.get_column(database);
+
+                if format.is_some() {
+                    builder.push_str("to_char(");
+                }
+
+                match truncate {
+                    Some(unit) => {
+                        builder.push_str("date_trunc('");
+                        builder.push_str(unit.as_str());
+                        builder.push_str("', ");
+                        column.build(database, builder);
+                        if let Some(timezone) = timezone {
+                            builder.push_str(", ");
+                            builder.push_param(timezone.param());
+                        }
+                        builder.push(')');
+                    }
+                    None => column.build(database, builder),
+                }
+
+                match format {
+                    Some(format) => {
+                        if let Some(timezone) = timezone {
+                            builder.push_str(" AT TIME ZONE ");
+                            builder.push_param(timezone.param());
+                        }
+                        builder.push_str(", ");
+                        builder.push_param(format.param());
+                        builder.push(')');
+                    }
+                    None => {
+                        if truncate.is_some()
+                            && column
+                                .typ
+                                .is::<crate::sql::physical_column_type::DateColumnType>()
+                        {
+                            // `date_trunc` returns a timestamp, so cast it back to keep the value a date
+                            builder.push_str("::date");
+                        }
+                    }
+                }
+            }
+            Function::Expression(expression) => expression.build(database, builder),
         }
     }
 }
@@ -0,0 +1,50 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::Database;
+
+use super::{ExpressionBuilder, SQLBuilder, schema_object::SchemaObjectName};
+
+/// The lock to take on the selected rows (which lasts until the end of the transaction)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowLock {
+    /// `FOR UPDATE`: Block other transactions from updating, deleting, or locking the rows
+    ForUpdate,
+    /// `FOR SHARE`: Block other transactions from updating or deleting the rows (but let them
+    /// take a share lock)
+    ForShare,
+    /// `FOR UPDATE SKIP LOCKED`: Lock the rows like `ForUpdate`, but skip the rows that another
+    /// transaction has already locked (instead of waiting for them). Useful to let multiple
+    /// workers pick up jobs from a queue table.
+    SkipLocked,
+}
+
+/// A locking clause such as `FOR UPDATE OF "concerts"`.
+///
+/// We always name the table to lock, so that the rows of tables joined for predicates or order-by
+/// stay unlocked (locking them would also fail, since they are on the nullable side of a left join).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LockClause {
+    pub lock: RowLock,
+    pub table_name: SchemaObjectName,
+}
+
+impl ExpressionBuilder for LockClause {
+    fn build(&self, _database: &Database, builder: &mut SQLBuilder) {
+        builder.push_str(match self.lock {
+            RowLock::ForUpdate | RowLock::SkipLocked => "FOR UPDATE OF ",
+            RowLock::ForShare => "FOR SHARE OF ",
+        });
+        // Postgres requires an unqualified name here
+        builder.push_identifier(&self.table_name.name);
+        if self.lock == RowLock::SkipLocked {
+            builder.push_str(" SKIP LOCKED");
+        }
+    }
+}
@@ -18,20 +18,25 @@ mod test_util;
 
 pub mod array_util;
 pub mod column;
+pub mod column_extraction;
 pub mod connect;
 pub mod database;
 pub mod function;
+pub mod lock;
+pub mod opaque_id;
 pub mod order;
 pub mod physical_column;
 pub mod physical_column_type;
 pub mod predicate;
 pub mod relation;
 pub mod schema_object;
+pub mod sql_expression;
 pub mod vector;
 pub use sql_bytes::SQLBytes;
 pub use sql_param::SQLParam;
 pub use sql_param_container::SQLParamContainer;
 
+pub(crate) mod copy;
 pub(crate) mod cte;
 pub(crate) mod delete;
 pub(crate) mod group_by;
@@ -44,9 +49,12 @@ pub(crate) mod offset;
 pub(crate) mod physical_table;
 pub(crate) mod select;
 pub(crate) mod sql_operation;
+pub(crate) mod statement_log;
 pub(crate) mod table;
 pub(crate) mod transaction;
+pub(crate) mod traversal;
 pub(crate) mod update;
+pub(crate) mod window;
 
 pub(crate) use expression_builder::ExpressionBuilder;
 pub(crate) use sql_builder::SQLBuilder;
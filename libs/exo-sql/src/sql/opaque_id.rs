@@ -0,0 +1,178 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Opaque ids: integer primary keys exposed as fixed-length strings (such as `"5c1d0e7a"`) that
+//! don't reveal how many rows a table has or in which order they were created.
+//!
+//! The encoding is a keyed permutation of the integers in `[0, 2^31)`: a multiplication by an odd
+//! number (modulo 2^31), an XOR with a mask, and another multiplication, written as eight
+//! hexadecimal digits. Postgres computes it while selecting the column (so the JSON it produces
+//! has the encoded values), and [`OpaqueIdKey::decode`] reverses it for the values in requests.
+//!
+//! The encoding obfuscates ids; it isn't encryption. A client that learns enough pairs of ids and
+//! their encoded values may recover the key.
+
+use crate::Database;
+
+use super::{ExpressionBuilder, SQLBuilder, SQLParamContainer, column::Column};
+
+const MODULUS: u64 = 1 << 31;
+const MODULUS_MASK: u64 = MODULUS - 1;
+
+/// The length of an encoded id (the number of hexadecimal digits needed for a value below 2^31)
+const ENCODED_LENGTH: usize = 8;
+
+/// The key to encode and decode opaque ids (derived from a secret such as the value of
+/// `EXO_OPAQUE_ID_KEY`)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct OpaqueIdKey {
+    multiplier: u64,
+    mask: u64,
+    scrambler: u64,
+}
+
+impl OpaqueIdKey {
+    pub fn from_secret(secret: &str) -> Self {
+        let derive = |seed: u8| fnv1a(seed, secret.as_bytes()) & MODULUS_MASK;
+
+        Self {
+            // Multipliers must be odd to be invertible modulo 2^31
+            multiplier: derive(1) | 1,
+            mask: derive(2),
+            scrambler: derive(3) | 1,
+        }
+    }
+
+    /// The opaque form of an id (`None` for ids outside `[0, 2^31)`)
+    pub fn encode(&self, id: i64) -> Option<String> {
+        let id = u64::try_from(id).ok().filter(|id| *id < MODULUS)?;
+
+        let value = (id * self.multiplier) & MODULUS_MASK;
+        let value = ((value ^ self.mask) * self.scrambler) & MODULUS_MASK;
+
+        Some(format!("{value:08x}"))
+    }
+
+    /// The id for an opaque id (`None` if the value isn't an opaque id)
+    pub fn decode(&self, opaque_id: &str) -> Option<i64> {
+        if opaque_id.len() != ENCODED_LENGTH || !opaque_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u64::from_str_radix(opaque_id, 16)
+            .ok()
+            .filter(|value| *value < MODULUS)?;
+
+        let value = (value * inverse(self.scrambler)) & MODULUS_MASK;
+        let value = ((value ^ self.mask) * inverse(self.multiplier)) & MODULUS_MASK;
+
+        Some(value as i64)
+    }
+
+    /// Build the encoding of the column's value, as in
+    /// `lpad(to_hex(((((("venues"."id")::int8 * $1) % 2147483648) # $2) * $3) % 2147483648), 8, '0')`.
+    ///
+    /// The key is passed as parameters, so that it doesn't appear in logged statements.
+    pub(crate) fn build_encoding(
+        &self,
+        column: &Column,
+        database: &Database,
+        builder: &mut SQLBuilder,
+    ) {
+        let modulus = MODULUS.to_string();
+
+        builder.push_str("lpad(to_hex((((((");
+        column.build(database, builder);
+        builder.push_str(")::int8 * ");
+        builder.push_param(SQLParamContainer::i64(self.multiplier as i64).param());
+        builder.push_str(") % ");
+        builder.push_str(&modulus);
+        builder.push_str(") # ");
+        builder.push_param(SQLParamContainer::i64(self.mask as i64).param());
+        builder.push_str(") * ");
+        builder.push_param(SQLParamContainer::i64(self.scrambler as i64).param());
+        builder.push_str(") % ");
+        builder.push_str(&modulus);
+        builder.push_str("), ");
+        builder.push_str(&ENCODED_LENGTH.to_string());
+        builder.push_str(", '0')");
+    }
+}
+
+/// Don't print the key (which would let a reader of the logs decode ids)
+impl std::fmt::Debug for OpaqueIdKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OpaqueIdKey(..)")
+    }
+}
+
+/// The inverse of an odd number modulo 2^31 (through Newton's iteration, which doubles the
+/// number of correct low bits with each step, starting with three)
+fn inverse(value: u64) -> u64 {
+    let mut inverse = value;
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(value.wrapping_mul(inverse)));
+    }
+    inverse & MODULUS_MASK
+}
+
+/// A stable hash (unlike the std hasher, which may change between releases and would change the
+/// encoded ids along with it)
+fn fnv1a(seed: u8, bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    std::iter::once(seed)
+        .chain(bytes.iter().copied())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let key = OpaqueIdKey::from_secret("secret");
+
+        for id in [0, 1, 2, 3, 42, 1000, 65535, MODULUS as i64 - 1] {
+            let encoded = key.encode(id).unwrap();
+            assert_eq!(encoded.len(), ENCODED_LENGTH);
+            assert_eq!(key.decode(&encoded), Some(id));
+        }
+    }
+
+    #[test]
+    fn hides_sequence() {
+        let key = OpaqueIdKey::from_secret("secret");
+
+        let one = key.encode(1).unwrap();
+        let two = key.encode(2).unwrap();
+        assert_ne!(one, two);
+        assert_ne!(one, "00000001");
+
+        // Another secret, another encoding
+        let other_key = OpaqueIdKey::from_secret("another secret");
+        assert_ne!(other_key.encode(1).unwrap(), one);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let key = OpaqueIdKey::from_secret("secret");
+
+        assert_eq!(key.encode(-1), None);
+        assert_eq!(key.encode(MODULUS as i64), None);
+
+        assert_eq!(key.decode("1"), None);
+        assert_eq!(key.decode("+1234567"), None);
+        assert_eq!(key.decode("zzzzzzzz"), None);
+        assert_eq!(key.decode("ffffffff"), None);
+    }
+}
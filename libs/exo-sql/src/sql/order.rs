@@ -7,11 +7,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use super::vector::VectorDistanceFunction;
+use serde::{Deserialize, Serialize};
+
+use super::{column::Column, vector::VectorDistanceFunction};
 use crate::{ColumnId, Database, SQLParamContainer, sql::vector::VectorDistance};
 
 use super::{ExpressionBuilder, SQLBuilder};
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Copy)]
 pub enum Ordering {
     Asc,
     Desc,
@@ -34,6 +36,9 @@ pub enum OrderByElementExpr {
         VectorDistanceOperand,
         VectorDistanceFunction,
     ),
+    /// An expression such as a value extracted from a JSON column (any table alias is already
+    /// applied to the expression)
+    Expression(Column),
 }
 
 #[derive(Debug, PartialEq)]
@@ -63,6 +68,9 @@ impl ExpressionBuilder for OrderByElement {
                 VectorDistance::new((lhs, self.2.as_ref()), (rhs, self.2.as_ref()), *function)
                     .build(database, builder);
             }
+            OrderByElementExpr::Expression(column) => {
+                column.build(database, builder);
+            }
         }
         builder.push_space();
 
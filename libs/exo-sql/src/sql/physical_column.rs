@@ -36,6 +36,15 @@ pub struct PhysicalColumn {
     pub default_value: Option<ColumnDefault>,
     pub update_sync: bool,
 
+    /// How Postgres stores the column's values (from `@storage`)
+    #[serde(default)]
+    pub storage: ColumnStorage,
+
+    /// Are the column's (integer) values exposed as opaque ids (the primary key of a type with
+    /// `@opaqueId` and the foreign keys that refer to it)? Doesn't affect the schema.
+    #[serde(default)]
+    pub opaque_id: bool,
+
     /// references to other foreign columns. A column can have multiple references if it point to multiple tables.
     pub column_references: Option<Vec<ColumnReference>>,
 }
@@ -51,6 +60,8 @@ impl Clone for PhysicalColumn {
             unique_constraints: self.unique_constraints.clone(),
             default_value: self.default_value.clone(),
             update_sync: self.update_sync,
+            storage: self.storage,
+            opaque_id: self.opaque_id,
             column_references: self.column_references.clone(),
         }
     }
@@ -66,6 +77,8 @@ impl PartialEq for PhysicalColumn {
             && self.unique_constraints == other.unique_constraints
             && self.default_value == other.default_value
             && self.update_sync == other.update_sync
+            && self.storage == other.storage
+            && self.opaque_id == other.opaque_id
             && self.column_references == other.column_references
     }
 }
@@ -185,4 +198,170 @@ impl Ord for ColumnId {
 pub struct ColumnReference {
     pub foreign_column_id: ColumnId,
     pub group_name: String,
+    /// What happens to the referencing rows when the referenced row is deleted
+    #[serde(default)]
+    pub on_delete: ReferentialAction,
+    /// What happens to the referencing rows when the referenced row's key is updated
+    #[serde(default)]
+    pub on_update: ReferentialAction,
+}
+
+/// The referential action of a foreign key constraint (`ON DELETE <action>`/`ON UPDATE <action>`)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ReferentialAction {
+    /// Postgres' default: reject the change if referencing rows exist (checked at the end of the statement)
+    #[default]
+    NoAction,
+    /// Apply the same change to the referencing rows
+    Cascade,
+    /// Reject the change if referencing rows exist (checked immediately)
+    Restrict,
+    /// Set the referencing columns to null
+    SetNull,
+}
+
+impl ReferentialAction {
+    /// The SQL keyword(s) for the action, or `None` for the default action (which we don't emit)
+    pub fn sql(&self) -> Option<&'static str> {
+        match self {
+            ReferentialAction::NoAction => None,
+            ReferentialAction::Cascade => Some("CASCADE"),
+            ReferentialAction::Restrict => Some("RESTRICT"),
+            ReferentialAction::SetNull => Some("SET NULL"),
+        }
+    }
+
+    /// Parse the SQL keyword(s) as reported by Postgres (such as `SET NULL`)
+    pub fn from_sql(action: &str) -> Option<Self> {
+        match action.to_uppercase().as_str() {
+            "NO ACTION" => Some(ReferentialAction::NoAction),
+            "CASCADE" => Some(ReferentialAction::Cascade),
+            "RESTRICT" => Some(ReferentialAction::Restrict),
+            "SET NULL" => Some(ReferentialAction::SetNull),
+            _ => None,
+        }
+    }
+}
+
+/// How Postgres stores (and TOASTs) a column's values (`ALTER COLUMN ... SET STORAGE/COMPRESSION`).
+///
+/// `None` stands for the default for the column type, so only the parameters declared in the
+/// model (with `@storage`) are emitted in DDL and compared during migration.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ColumnStorage {
+    pub strategy: Option<StorageStrategy>,
+    pub compression: Option<CompressionMethod>,
+}
+
+/// The storage strategy of a column (see the "TOAST" chapter of the Postgres documentation)
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StorageStrategy {
+    /// Inline and uncompressed
+    Plain,
+    /// Out-of-line and uncompressed (makes substring operations on large values faster)
+    External,
+    /// Out-of-line and compressed (the default for `TEXT`, `BYTEA`, and `JSONB`)
+    Extended,
+    /// Inline and compressed (moved out-of-line only as a last resort)
+    Main,
+}
+
+/// The compression method for a column's large values
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompressionMethod {
+    Pglz,
+    Lz4,
+}
+
+impl ColumnStorage {
+    pub fn is_default(&self) -> bool {
+        self == &ColumnStorage::default()
+    }
+
+    /// The `ALTER COLUMN` actions to change the column's storage from `old` to `self` (empty, if
+    /// nothing changed)
+    pub fn alter_actions(&self, old: &ColumnStorage, column_name: &str) -> Vec<String> {
+        let mut actions = vec![];
+
+        if self.strategy != old.strategy {
+            // We allow specifying the storage only for types whose default is `EXTENDED`
+            let strategy = self.strategy.unwrap_or(StorageStrategy::Extended);
+            actions.push(format!(
+                "ALTER COLUMN \"{column_name}\" SET STORAGE {}",
+                strategy.sql()
+            ));
+        }
+
+        if self.compression != old.compression {
+            let compression = self
+                .compression
+                .map(|compression| compression.sql())
+                .unwrap_or("DEFAULT");
+            actions.push(format!(
+                "ALTER COLUMN \"{column_name}\" SET COMPRESSION {compression}"
+            ));
+        }
+
+        actions
+    }
+}
+
+impl StorageStrategy {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            StorageStrategy::Plain => "PLAIN",
+            StorageStrategy::External => "EXTERNAL",
+            StorageStrategy::Extended => "EXTENDED",
+            StorageStrategy::Main => "MAIN",
+        }
+    }
+
+    /// Parse the name used in the model (such as `external`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "plain" => Some(StorageStrategy::Plain),
+            "external" => Some(StorageStrategy::External),
+            "extended" => Some(StorageStrategy::Extended),
+            "main" => Some(StorageStrategy::Main),
+            _ => None,
+        }
+    }
+
+    /// Parse the code reported by Postgres in `pg_attribute.attstorage`
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "p" => Some(StorageStrategy::Plain),
+            "e" => Some(StorageStrategy::External),
+            "x" => Some(StorageStrategy::Extended),
+            "m" => Some(StorageStrategy::Main),
+            _ => None,
+        }
+    }
+}
+
+impl CompressionMethod {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            CompressionMethod::Pglz => "pglz",
+            CompressionMethod::Lz4 => "lz4",
+        }
+    }
+
+    /// Parse the name used in the model (such as `lz4`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "pglz" => Some(CompressionMethod::Pglz),
+            "lz4" => Some(CompressionMethod::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Parse the code reported by Postgres in `pg_attribute.attcompression` (empty for the default)
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "p" => Some(CompressionMethod::Pglz),
+            "l" => Some(CompressionMethod::Lz4),
+            _ => None,
+        }
+    }
 }
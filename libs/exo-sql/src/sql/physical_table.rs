@@ -9,7 +9,10 @@
 
 use std::collections::HashSet;
 
-use crate::{Database, schema::index_spec::IndexKind};
+use crate::{
+    Database,
+    schema::{index_spec::IndexKind, partition_spec::PartitionSpec},
+};
 
 use super::{
     ExpressionBuilder, SQLBuilder, column::Column, delete::Delete, insert::Insert,
@@ -31,6 +34,9 @@ pub struct PhysicalTable {
     pub indices: Vec<PhysicalIndex>,
 
     pub managed: bool,
+
+    /// Partitioning of the table (from `@partitionBy`)
+    pub partition: Option<PartitionSpec>,
 }
 
 /// A physical enum in the database such as "Priority" with variants "LOW", "MEDIUM", "HIGH".
@@ -38,6 +44,26 @@ pub struct PhysicalTable {
 pub struct PhysicalEnum {
     pub name: SchemaObjectName,
     pub variants: Vec<String>,
+    /// Removed variants along with the variants to rewrite their values to in migrations (from
+    /// `@mapsTo`)
+    #[serde(default)]
+    pub variant_mappings: Vec<(String, String)>,
+}
+
+/// A rename declared in the model (through `@renamedFrom`), so that migrations rename the
+/// table or column instead of dropping and recreating it.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum SchemaRename {
+    Table {
+        old_name: SchemaObjectName,
+        new_name: SchemaObjectName,
+    },
+    Column {
+        /// The (new) name of the table containing the column
+        table_name: SchemaObjectName,
+        old_name: String,
+        new_name: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -501,6 +501,7 @@ mod tests {
                         is_nullable: false,
                         unique_constraints: vec![],
                         default_value: None,
+                        storage: Default::default(),
                     },
                 ],
                 vec![],
@@ -12,8 +12,8 @@ use std::collections::HashMap;
 use crate::{Database, Limit, Offset};
 
 use super::{
-    ExpressionBuilder, SQLBuilder, column::Column, group_by::GroupBy, order::OrderBy,
-    predicate::ConcretePredicate, table::Table,
+    ExpressionBuilder, SQLBuilder, column::Column, group_by::GroupBy, lock::LockClause,
+    order::OrderBy, predicate::ConcretePredicate, table::Table,
 };
 
 /// A select statement
@@ -33,6 +33,8 @@ pub struct Select {
     pub limit: Option<Limit>,
     /// The group by clause
     pub group_by: Option<GroupBy>,
+    /// The locking clause
+    pub lock: Option<LockClause>,
     /// Whether this is a top-level selection. This is used to put the `::text` cast on a top-level select statement
     /// This way, we can grab the JSON as a string and return it to the user as is. Specifically, we don't want to
     /// decode into a JSON object and then re-encode it as a string.
@@ -92,6 +94,10 @@ impl ExpressionBuilder for Select {
                 builder.push_space();
                 offset.build(database, builder);
             }
+            if let Some(lock) = &self.lock {
+                builder.push_space();
+                lock.build(database, builder);
+            }
         });
     }
 }
@@ -151,6 +157,7 @@ mod tests {
             offset: None,
             group_by: None,
             top_level_selection: true,
+            lock: None,
         };
 
         assert_binding!(
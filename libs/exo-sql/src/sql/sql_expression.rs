@@ -0,0 +1,312 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A restricted SQL expression over the columns of a table, such as `price * quantity` or
+//! `first_name || ' ' || last_name`.
+//!
+//! We parse the expression at build time (so any error surfaces early) and keep only tokens we
+//! know to be safe: column references, numeric and string literals, arithmetic and concatenation
+//! operators, parentheses, and calls to a small set of functions.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ColumnId, Database};
+
+use super::{ExpressionBuilder, SQLBuilder};
+
+/// Functions that an expression may call
+const ALLOWED_FUNCTIONS: [&str; 10] = [
+    "abs", "ceil", "coalesce", "floor", "greatest", "least", "length", "lower", "round", "upper",
+];
+
+/// Keywords that an expression may use in place of a value
+const ALLOWED_KEYWORDS: [&str; 3] = ["null", "true", "false"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SqlExpression {
+    parts: Vec<SqlExpressionPart>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum SqlExpressionPart {
+    Column(ColumnId),
+    /// Validated SQL text such as an operator, a literal, or a function name
+    Text(String),
+}
+
+impl SqlExpression {
+    /// Parse an expression, resolving each identifier (other than function names and keywords)
+    /// to a column using `lookup_column`.
+    pub fn parse(
+        expression: &str,
+        lookup_column: impl Fn(&str) -> Option<ColumnId>,
+    ) -> Result<Self, String> {
+        let chars: Vec<char> = expression.chars().collect();
+        let mut parts = vec![];
+        let mut depth = 0;
+        let mut expect_value = true;
+        let mut index = 0;
+
+        while index < chars.len() {
+            let c = chars[index];
+
+            if c.is_whitespace() {
+                index += 1;
+                continue;
+            }
+
+            if c.is_ascii_alphabetic() || c == '_' {
+                let start = index;
+                while index < chars.len()
+                    && (chars[index].is_ascii_alphanumeric() || chars[index] == '_')
+                {
+                    index += 1;
+                }
+                let identifier: String = chars[start..index].iter().collect();
+
+                if !expect_value {
+                    return Err(format!("Unexpected identifier '{identifier}'"));
+                }
+
+                let next_non_space = chars[index..].iter().find(|c| !c.is_whitespace());
+                if next_non_space == Some(&'(') {
+                    let function_name = identifier.to_lowercase();
+                    if !ALLOWED_FUNCTIONS.contains(&function_name.as_str()) {
+                        return Err(format!("Function '{identifier}' is not supported"));
+                    }
+                    push_text(&mut parts, &function_name);
+                    // The value is the function's result; the opening parenthesis follows
+                    continue;
+                }
+
+                let keyword = identifier.to_lowercase();
+                if ALLOWED_KEYWORDS.contains(&keyword.as_str()) {
+                    push_text(&mut parts, &keyword);
+                } else {
+                    let column_id = lookup_column(&identifier)
+                        .ok_or_else(|| format!("Unknown column '{identifier}'"))?;
+                    parts.push(SqlExpressionPart::Column(column_id));
+                }
+                expect_value = false;
+                continue;
+            }
+
+            if c.is_ascii_digit() {
+                if !expect_value {
+                    return Err("Unexpected number".to_string());
+                }
+                let start = index;
+                while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.')
+                {
+                    index += 1;
+                }
+                let number: String = chars[start..index].iter().collect();
+                if number.matches('.').count() > 1 || number.ends_with('.') {
+                    return Err(format!("Invalid number '{number}'"));
+                }
+                push_text(&mut parts, &number);
+                expect_value = false;
+                continue;
+            }
+
+            match c {
+                '\'' => {
+                    if !expect_value {
+                        return Err("Unexpected string literal".to_string());
+                    }
+                    let mut literal = String::from("'");
+                    index += 1;
+                    loop {
+                        match chars.get(index) {
+                            None => return Err("Unterminated string literal".to_string()),
+                            Some('\'') if chars.get(index + 1) == Some(&'\'') => {
+                                literal.push_str("''");
+                                index += 2;
+                            }
+                            Some('\'') => {
+                                literal.push('\'');
+                                index += 1;
+                                break;
+                            }
+                            Some(c) => {
+                                literal.push(*c);
+                                index += 1;
+                            }
+                        }
+                    }
+                    push_text(&mut parts, &literal);
+                    expect_value = false;
+                }
+                '(' => {
+                    if !expect_value {
+                        return Err("Unexpected '('".to_string());
+                    }
+                    depth += 1;
+                    push_text(&mut parts, "(");
+                    index += 1;
+                }
+                ')' => {
+                    if expect_value || depth == 0 {
+                        return Err("Unexpected ')'".to_string());
+                    }
+                    depth -= 1;
+                    push_text(&mut parts, ")");
+                    index += 1;
+                }
+                ',' => {
+                    if expect_value || depth == 0 {
+                        return Err("Unexpected ','".to_string());
+                    }
+                    push_text(&mut parts, ", ");
+                    expect_value = true;
+                    index += 1;
+                }
+                '|' if chars.get(index + 1) == Some(&'|') => {
+                    if expect_value {
+                        return Err("Unexpected '||'".to_string());
+                    }
+                    push_text(&mut parts, " || ");
+                    expect_value = true;
+                    index += 2;
+                }
+                '+' | '-' | '*' | '/' | '%' => {
+                    if expect_value {
+                        // Only a sign may precede a value (`--` would start a comment)
+                        if c != '-' || parts_end_with(&parts, "-") {
+                            return Err(format!("Unexpected '{c}'"));
+                        }
+                        push_text(&mut parts, "-");
+                    } else {
+                        push_text(&mut parts, &format!(" {c} "));
+                        expect_value = true;
+                    }
+                    index += 1;
+                }
+                _ => return Err(format!("Unexpected character '{c}'")),
+            }
+        }
+
+        if expect_value {
+            return Err("Incomplete expression".to_string());
+        }
+        if depth != 0 {
+            return Err("Unbalanced parentheses".to_string());
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// The columns that the expression refers to
+    pub fn column_ids(&self) -> impl Iterator<Item = ColumnId> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            SqlExpressionPart::Column(column_id) => Some(*column_id),
+            SqlExpressionPart::Text(_) => None,
+        })
+    }
+}
+
+fn push_text(parts: &mut Vec<SqlExpressionPart>, text: &str) {
+    match parts.last_mut() {
+        Some(SqlExpressionPart::Text(last)) => last.push_str(text),
+        _ => parts.push(SqlExpressionPart::Text(text.to_string())),
+    }
+}
+
+fn parts_end_with(parts: &[SqlExpressionPart], suffix: &str) -> bool {
+    matches!(parts.last(), Some(SqlExpressionPart::Text(text)) if text.ends_with(suffix))
+}
+
+impl ExpressionBuilder for SqlExpression {
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        builder.push('(');
+        for part in &self.parts {
+            match part {
+                SqlExpressionPart::Column(column_id) => {
+                    column_id.get_column(database).build(database, builder)
+                }
+                SqlExpressionPart::Text(text) => builder.push_str(text),
+            }
+        }
+        builder.push(')');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::ExpressionBuilder;
+    use crate::transform::test_util::TestSetup;
+
+    use multiplatform_test::multiplatform_test;
+
+    #[multiplatform_test]
+    fn valid_expressions() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_id_column,
+                 concerts_name_column,
+                 ..
+             }| {
+                let lookup = |name: &str| match name {
+                    "id" => Some(concerts_id_column),
+                    "name" => Some(concerts_name_column),
+                    _ => None,
+                };
+
+                let assert_sql = |expression: &str, expected: &str| {
+                    let expression = SqlExpression::parse(expression, lookup).unwrap();
+                    assert_eq!(expression.to_sql(&database).0, expected);
+                };
+
+                assert_sql("id * 2 + -1", r#"("concerts"."id" * 2 + -1)"#);
+                assert_sql(
+                    "upper(name) || ' (' || id || ')'",
+                    r#"(upper("concerts"."name") || ' (' || "concerts"."id" || ')')"#,
+                );
+                assert_sql(
+                    "COALESCE(name, 'It''s unnamed')",
+                    r#"(coalesce("concerts"."name", 'It''s unnamed'))"#,
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn invalid_expressions() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 concerts_id_column, ..
+             }| {
+                let lookup = |name: &str| (name == "id").then_some(concerts_id_column);
+
+                for expression in [
+                    "",
+                    "price * 2",
+                    "id * ",
+                    "id; drop table concerts",
+                    "--id",
+                    "id /* comment */",
+                    "pg_sleep(10)",
+                    "(id * 2",
+                    "id * 2)",
+                    "'unterminated",
+                    "id \"quoted\"",
+                    "id id",
+                    "1.2.3",
+                ] {
+                    assert!(
+                        SqlExpression::parse(expression, lookup).is_err(),
+                        "Expected '{expression}' to be rejected"
+                    );
+                }
+            },
+        );
+    }
+}
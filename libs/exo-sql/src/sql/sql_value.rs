@@ -14,7 +14,7 @@ use tokio_postgres::types::{FromSql, ToSql, Type, to_sql_checked};
 use crate::database_error::DatabaseError;
 
 /// An SQL value to transfer the result of one transaction step to another.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SQLValue {
     value: Vec<u8>,
     pub(crate) type_: Type,
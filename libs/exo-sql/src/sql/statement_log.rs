@@ -0,0 +1,160 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Logging of the executed statements along with their parameters, under the
+//! [`STATEMENT_LOG_TARGET`] target.
+//!
+//! The executor's [`ExecutionConfig`](crate::ExecutionConfig) determines what gets logged (the
+//! Postgres subsystem builds it from `EXO_SQL_LOG`, `EXO_SQL_LOG_MAX_PARAM_LENGTH`, and
+//! `EXO_SQL_EXPLAIN_THRESHOLD`).
+
+use std::time::Duration;
+
+use tokio_postgres::types::{ToSql, Type};
+
+pub const STATEMENT_LOG_TARGET: &str = "exo_sql::statements";
+
+pub const DEFAULT_MAX_PARAM_LENGTH: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementLogFormat {
+    /// The statement as is, followed by a line for each parameter
+    Multiline,
+    /// The statement with the parameters on a single line
+    Pretty,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementLogConfig {
+    /// `None` if statement logging is disabled
+    pub format: Option<StatementLogFormat>,
+    /// Longer parameter values (such as vectors or blobs) are truncated to this many characters
+    pub max_param_length: usize,
+    /// Log the plan of statements that take longer than this (see `log_plan` for the cost)
+    pub explain_threshold: Option<Duration>,
+}
+
+impl Default for StatementLogConfig {
+    fn default() -> Self {
+        Self {
+            format: None,
+            max_param_length: DEFAULT_MAX_PARAM_LENGTH,
+            explain_threshold: None,
+        }
+    }
+}
+
+impl StatementLogConfig {
+    /// Should the plan of a statement that took `elapsed` be logged?
+    pub fn should_explain(&self, elapsed: Duration) -> bool {
+        self.format.is_some()
+            && self
+                .explain_threshold
+                .is_some_and(|threshold| elapsed >= threshold)
+    }
+}
+
+/// Format the statement along with its parameters (and their types)
+pub(crate) fn format_statement(
+    stmt: &str,
+    params: &[(&(dyn ToSql + Sync), Type)],
+    format: StatementLogFormat,
+    max_param_length: usize,
+) -> String {
+    let params = params
+        .iter()
+        .enumerate()
+        .map(|(index, (value, typ))| {
+            format!(
+                "${} = {}::{}",
+                index + 1,
+                truncate(&format!("{value:?}"), max_param_length),
+                typ.name()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    match format {
+        StatementLogFormat::Multiline => {
+            let mut formatted = stmt.to_string();
+            for param in params {
+                formatted.push_str("\n  ");
+                formatted.push_str(&param);
+            }
+            formatted
+        }
+        StatementLogFormat::Pretty => {
+            let stmt = stmt.split_whitespace().collect::<Vec<_>>().join(" ");
+            if params.is_empty() {
+                stmt
+            } else {
+                format!("{stmt} -- {}", params.join(", "))
+            }
+        }
+    }
+}
+
+fn truncate(value: &str, max_length: usize) -> String {
+    let length = value.chars().count();
+    if length <= max_length {
+        return value.to_string();
+    }
+
+    let truncated: String = value.chars().take(max_length).collect();
+    format!("{truncated}… ({length} chars)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiline_statements() {
+        let id = 42i32;
+        let title = "Concert".to_string();
+
+        assert_eq!(
+            format_statement(
+                "SELECT *\nFROM concerts\nWHERE id = $1 AND title = $2",
+                &[
+                    (&id as &(dyn ToSql + Sync), Type::INT4),
+                    (&title, Type::TEXT)
+                ],
+                StatementLogFormat::Multiline,
+                100,
+            ),
+            "SELECT *\nFROM concerts\nWHERE id = $1 AND title = $2\n  $1 = 42::int4\n  $2 = \"Concert\"::text"
+        );
+    }
+
+    #[test]
+    fn pretty_statements() {
+        let embedding = vec![0.5f32; 100];
+
+        assert_eq!(
+            format_statement(
+                "SELECT *\n  FROM documents\n  ORDER BY embedding <-> $1",
+                &[(&embedding as &(dyn ToSql + Sync), Type::FLOAT4_ARRAY)],
+                StatementLogFormat::Pretty,
+                12,
+            ),
+            "SELECT * FROM documents ORDER BY embedding <-> $1 -- $1 = [0.5, 0.5, 0… (500 chars)::_float4"
+        );
+
+        assert_eq!(
+            format_statement(
+                "SELECT 1",
+                &[],
+                StatementLogFormat::Pretty,
+                DEFAULT_MAX_PARAM_LENGTH
+            ),
+            "SELECT 1"
+        );
+    }
+}
@@ -7,27 +7,59 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::fmt::Debug;
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    ops::Range,
+    time::{Duration, Instant},
+};
 
-use tokio_postgres::{GenericClient, Row, error::SqlState};
-use tracing::{error, info, instrument, warn};
+use tokio_postgres::{
+    GenericClient, Row,
+    types::{ToSql, Type},
+};
+use tracing::{debug, error, info, instrument, warn};
 
 use crate::{
-    Column, Database, Predicate, SQLParamContainer, TableId,
-    database_error::DatabaseError,
+    Column, Database, PlanStep, PlanStepKind, Predicate, SQLParamContainer, TableId,
+    database_error::{DatabaseError, connection_failure_sqlstate},
     sql::{SQLBuilder, select::Select, table::Table},
 };
 
 use super::{
     ExpressionBuilder, SQLValue,
     column::ArrayParamWrapper,
+    column_extraction::extract_column,
     predicate::ConcretePredicate,
     sql_operation::{SQLOperation, TemplateSQLOperation},
+    statement_log::{STATEMENT_LOG_TARGET, StatementLogConfig, format_statement},
 };
 
 /// Rows obtained from a SQL operation
 pub type TransactionStepResult = Vec<Row>;
 
+pub const DEFAULT_IN_LIST_CHUNK_SIZE: usize = 10_000;
+
+/// Settings that affect how transaction scripts are executed (configured through the environment
+/// when creating the executor)
+#[derive(Debug, Clone)]
+pub struct ExecutionConfig {
+    /// Maximum number of keys in the array parameter of a filter statement (see
+    /// `TemplateFilterOperation::resolve`)
+    pub in_list_chunk_size: usize,
+    /// What to log about the executed statements
+    pub statement_log: StatementLogConfig,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            in_list_chunk_size: DEFAULT_IN_LIST_CHUNK_SIZE,
+            statement_log: StatementLogConfig::default(),
+        }
+    }
+}
+
 /// Sequence of SQL operations that are executed in a transaction
 #[derive(Default, Debug)]
 pub struct TransactionScript<'a> {
@@ -45,7 +77,7 @@ pub struct TransactionStepId(pub usize);
 impl TransactionContext {
     /// Returns the value of a column in a row from the given step id
     pub fn resolve_value(&self, step_id: TransactionStepId, row: usize, col: usize) -> SQLValue {
-        self.results[step_id.0][row].get::<usize, SQLValue>(col)
+        extract_column(&self.results[step_id.0][row], col).unwrap_or_else(|error| panic!("{error}"))
     }
 
     /// Returns the number of rows in the result of the given step id
@@ -55,7 +87,8 @@ impl TransactionContext {
 }
 
 impl<'a> TransactionScript<'a> {
-    /// Returns the result of the last step
+    /// Returns the result of the last step along with the number of statements executed (which
+    /// may exceed the number of steps, since a step may execute multiple statements)
     #[instrument(
         name = "TransactionScript::execute"
         skip_all
@@ -63,22 +96,28 @@ impl<'a> TransactionScript<'a> {
     pub async fn execute<T: tokio_postgres::GenericClient>(
         self,
         database: &Database,
+        config: &ExecutionConfig,
         tx: &mut T,
-    ) -> Result<TransactionStepResult, DatabaseError> {
+    ) -> Result<(TransactionStepResult, usize), DatabaseError> {
         let mut transaction_context = TransactionContext { results: vec![] };
+        let mut statements = 0;
 
         // Execute each step in the transaction and store the result in the transaction_context
         for step in self.steps.into_iter() {
-            let result = step.execute(database, tx, &transaction_context).await?;
+            let result = step
+                .execute(database, config, tx, &transaction_context, &mut statements)
+                .await?;
             transaction_context.results.push(result)
         }
 
         // Return the result of the last step (usually the "select")
-        transaction_context
+        let result = transaction_context
             .results
             .into_iter()
             .next_back()
-            .ok_or_else(|| DatabaseError::Transaction("".into()))
+            .ok_or_else(|| DatabaseError::Transaction("".into()))?;
+
+        Ok((result, statements))
     }
 
     /// Adds a step to the transaction script and return the step id (which is just the index of the step in the script)
@@ -91,6 +130,62 @@ impl<'a> TransactionScript<'a> {
     pub fn needs_transaction(&self) -> bool {
         self.steps.len() > 1
     }
+
+    pub(crate) fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// The SQL statements of the steps that don't depend on the results of earlier steps
+    pub fn concrete_statements(&self, database: &Database) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter_map(|step| {
+                let mut sql_builder = SQLBuilder::new();
+                match step {
+                    TransactionStep::Concrete(step) => {
+                        step.operation.build(database, &mut sql_builder)
+                    }
+                    TransactionStep::Precheck(select) => select.build(database, &mut sql_builder),
+                    _ => return None,
+                }
+                Some(sql_builder.into_sql().0)
+            })
+            .collect()
+    }
+
+    /// Describe the steps (for an [`OperationPlan`](crate::OperationPlan))
+    pub(crate) fn plan_steps(&self, database: &Database) -> Vec<PlanStep> {
+        self.steps
+            .iter()
+            .map(|step| {
+                let mut sql_builder = SQLBuilder::new();
+                let (kind, depends_on) = match step {
+                    TransactionStep::Concrete(step) => {
+                        step.operation.build(database, &mut sql_builder);
+                        (PlanStepKind::Concrete, None)
+                    }
+                    TransactionStep::Precheck(select) => {
+                        select.build(database, &mut sql_builder);
+                        (PlanStepKind::Precheck, None)
+                    }
+                    TransactionStep::Template(step) => {
+                        (PlanStepKind::Template, Some(step.prev_step_id.0))
+                    }
+                    TransactionStep::Filter(step) => {
+                        (PlanStepKind::Filter, Some(step.prev_step_id.0))
+                    }
+                    TransactionStep::Dynamic(_) => (PlanStepKind::Dynamic, None),
+                };
+                let sql = sql_builder.into_sql().0;
+
+                PlanStep {
+                    kind,
+                    sql: (!sql.is_empty()).then_some(sql),
+                    depends_on,
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -111,13 +206,19 @@ impl TransactionStep<'_> {
     pub async fn execute(
         self,
         database: &Database,
+        config: &ExecutionConfig,
         client: &mut impl GenericClient,
         transaction_context: &TransactionContext,
+        statements: &mut usize,
     ) -> Result<TransactionStepResult, DatabaseError> {
         match self {
-            Self::Concrete(step) => step.execute(database, client).await,
+            Self::Concrete(step) => {
+                *statements += 1;
+                step.execute(database, config, client).await
+            }
             Self::Template(step) => {
                 let concrete = step.resolve(transaction_context);
+                *statements += concrete.len();
 
                 let mut res: Result<TransactionStepResult, DatabaseError> = Ok(vec![]);
 
@@ -126,27 +227,43 @@ impl TransactionStep<'_> {
                 for (index, substep) in concrete.into_iter().enumerate() {
                     if index == substep_count - 1 {
                         // Execute the last step and return the result
-                        res = substep.execute(database, client).await;
+                        res = substep.execute(database, config, client).await;
                     } else {
                         // Execute all but the last step
-                        substep.execute(database, client).await?;
+                        substep.execute(database, config, client).await?;
                     }
                 }
 
                 res
             }
             Self::Filter(step) => {
-                let concrete = step.resolve(transaction_context, database);
-                concrete.execute(database, client).await
+                let key_columns = database.get_pk_column_ids(step.table_id).len();
+                let chunks = step.resolve(transaction_context, database, config.in_list_chunk_size);
+                *statements += chunks.len();
+
+                // With a composite key, chunks may select the same row (see
+                // `TemplateFilterOperation::resolve`), so skip the rows selected by an earlier chunk
+                let mut selected_keys = HashSet::new();
+                let mut res = vec![];
+                for chunk in chunks {
+                    for row in chunk.execute(database, config, client).await? {
+                        if key_columns == 1 || selected_keys.insert(row_key(&row, key_columns)?) {
+                            res.push(row);
+                        }
+                    }
+                }
+                Ok(res)
             }
             Self::Dynamic(step) => {
+                *statements += 1;
                 step.resolve(transaction_context)
-                    .execute(database, client)
+                    .execute(database, config, client)
                     .await
             }
             Self::Precheck(select) => {
+                *statements += 1;
                 let precheck_result =
-                    run_query(SQLOperation::Select(select), database, client).await?;
+                    run_query(SQLOperation::Select(select), database, config, client).await?;
                 if precheck_result.len() != 1 {
                     return Err(DatabaseError::Precheck(format!(
                         "Expected 1 row, got {}",
@@ -181,15 +298,17 @@ impl<'a> ConcreteTransactionStep<'a> {
     pub async fn execute(
         self,
         database: &Database,
+        config: &ExecutionConfig,
         client: &mut impl GenericClient,
     ) -> Result<TransactionStepResult, DatabaseError> {
-        run_query(self.operation, database, client).await
+        run_query(self.operation, database, config, client).await
     }
 }
 
 async fn run_query(
     operation: SQLOperation<'_>,
     database: &Database,
+    config: &ExecutionConfig,
     client: &mut impl GenericClient,
 ) -> Result<TransactionStepResult, DatabaseError> {
     let mut sql_builder = SQLBuilder::new();
@@ -201,21 +320,31 @@ async fn run_query(
         .map(|p| (p.param.as_pg(), p.param_type.clone()))
         .collect();
 
-    info!("Executing SQL operation: {}", stmt);
-    println!("[debug] SQL: {}", stmt);
-    println!(
-        "[debug] Params: {:?}",
-        params.iter().map(|(p, _)| p).collect::<Vec<_>>()
-    );
+    let log_config = &config.statement_log;
+    match log_config.format {
+        Some(format) => info!(
+            target: STATEMENT_LOG_TARGET,
+            "{}",
+            format_statement(&stmt, &params, format, log_config.max_param_length)
+        ),
+        None => info!("Executing SQL operation: {}", stmt),
+    }
 
     let retry_config = RetryConfig::from_env();
     let allow_retry = retry_config.max_retries > 0 && operation_is_read_only(&operation);
 
     let mut attempt: u32 = 0;
     loop {
+        let start = Instant::now();
         let result = client.query_typed(&stmt, &params[..]).await;
         match result {
-            Ok(rows) => return Ok(rows),
+            Ok(rows) => {
+                let elapsed = start.elapsed();
+                if log_config.should_explain(elapsed) {
+                    log_plan(&stmt, &params, elapsed, client).await;
+                }
+                return Ok(rows);
+            }
             Err(err) => {
                 let retryable = allow_retry && is_retryable_db_error(&err);
                 log_query_error(&stmt, &err);
@@ -238,6 +367,62 @@ async fn run_query(
     }
 }
 
+const EXPLAIN_SAVEPOINT: &str = "exo_explain";
+
+/// Log the plan of a slow statement (without executing it again, since `EXPLAIN` without
+/// `ANALYZE` only plans the statement).
+///
+/// The plan must be obtained on the same connection (and in the same transaction) as the statement,
+/// since the statement may depend on the transaction's earlier writes. So this is on the request's
+/// path: it adds up to three round trips (along with planning the statement again) to each slow
+/// statement, while the transaction keeps holding its locks. Thus, the threshold is meant for
+/// diagnosing slow statements rather than to be left enabled.
+///
+/// `EXPLAIN` runs under a savepoint, so that its failure doesn't abort the caller's transaction.
+async fn log_plan(
+    stmt: &str,
+    params: &[(&(dyn ToSql + Sync), Type)],
+    elapsed: Duration,
+    client: &mut impl GenericClient,
+) {
+    // Fails if the statement didn't run in a transaction (in which case, there is nothing to protect)
+    let in_savepoint = client
+        .batch_execute(&format!("SAVEPOINT {EXPLAIN_SAVEPOINT}"))
+        .await
+        .is_ok();
+
+    let result = client.query_typed(&format!("EXPLAIN {stmt}"), params).await;
+
+    if in_savepoint {
+        let restore = if result.is_ok() {
+            format!("RELEASE SAVEPOINT {EXPLAIN_SAVEPOINT}")
+        } else {
+            format!(
+                "ROLLBACK TO SAVEPOINT {EXPLAIN_SAVEPOINT}; RELEASE SAVEPOINT {EXPLAIN_SAVEPOINT}"
+            )
+        };
+        if let Err(err) = client.batch_execute(&restore).await {
+            warn!("Failed to restore the transaction after explaining a slow statement: {err}");
+        }
+    }
+
+    match result {
+        Ok(rows) => {
+            let plan = rows
+                .iter()
+                .filter_map(|row| row.try_get::<_, String>(0).ok())
+                .collect::<Vec<_>>()
+                .join("\n");
+            info!(
+                target: STATEMENT_LOG_TARGET,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "Slow statement plan:\n{plan}"
+            );
+        }
+        Err(err) => debug!("Failed to explain a slow statement: {err}"),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct RetryConfig {
     max_retries: u32,
@@ -282,15 +467,7 @@ fn operation_is_read_only(operation: &SQLOperation<'_>) -> bool {
 }
 
 fn is_retryable_db_error(err: &tokio_postgres::Error) -> bool {
-    if let Some(db_error) = err.as_db_error() {
-        let code = db_error.code();
-        return *code == SqlState::ADMIN_SHUTDOWN
-            || *code == SqlState::CRASH_SHUTDOWN
-            || *code == SqlState::CANNOT_CONNECT_NOW
-            || *code == SqlState::CONNECTION_FAILURE
-            || *code == SqlState::CONNECTION_DOES_NOT_EXIST;
-    }
-    false
+    connection_failure_sqlstate(err).is_some()
 }
 
 fn log_query_error(stmt: &str, err: &tokio_postgres::Error) {
@@ -311,12 +488,7 @@ fn log_query_error(stmt: &str, err: &tokio_postgres::Error) {
             "Postgres error executing query"
         );
 
-        if *code == SqlState::ADMIN_SHUTDOWN
-            || *code == SqlState::CRASH_SHUTDOWN
-            || *code == SqlState::CANNOT_CONNECT_NOW
-            || *code == SqlState::CONNECTION_FAILURE
-            || *code == SqlState::CONNECTION_DOES_NOT_EXIST
-        {
+        if connection_failure_sqlstate(err).is_some() {
             warn!(
                 sqlstate = %code.code(),
                 "Transient Postgres connection error detected (likely restart or disconnect)"
@@ -358,13 +530,39 @@ pub struct TemplateFilterOperation {
 }
 
 impl TemplateFilterOperation {
+    /// Resolve into statements that filter rows by the primary keys obtained from the previous
+    /// step (with `pk = ANY($1)`).
+    ///
+    /// If there are more keys than the chunk size, each statement gets a chunk of the keys, to
+    /// keep the array parameters (and thus the parameter size and the planning cost) bounded.
+    ///
+    /// With a composite primary key, each key column gets its own array (`pk_1 = ANY($1) AND
+    /// pk_2 = ANY($2)`), which matches the combinations of the values across the rows of a chunk.
+    /// Therefore, chunks may overlap, and the caller must skip the rows selected by more than one
+    /// chunk.
     pub fn resolve<'a>(
         self,
         transaction_context: &TransactionContext,
         database: &Database,
-    ) -> ConcreteTransactionStep<'a> {
+        chunk_size: usize,
+    ) -> Vec<ConcreteTransactionStep<'a>> {
         let rows = transaction_context.row_count(self.prev_step_id);
 
+        chunk_ranges(rows, chunk_size)
+            .into_iter()
+            .map(|rows| {
+                self.resolve_rows(rows, self.predicate.clone(), transaction_context, database)
+            })
+            .collect()
+    }
+
+    fn resolve_rows<'a>(
+        &self,
+        rows: Range<usize>,
+        predicate: ConcretePredicate,
+        transaction_context: &TransactionContext,
+        database: &Database,
+    ) -> ConcreteTransactionStep<'a> {
         let pk_column_ids = database.get_pk_column_ids(self.table_id);
         let pk_column_types = database
             .get_table(self.table_id)
@@ -373,32 +571,33 @@ impl TemplateFilterOperation {
             .map(|pk_physical_column| pk_physical_column.typ.get_pg_type())
             .collect::<Vec<_>>();
 
-        let predicate = pk_column_ids.iter().enumerate().fold(
-            self.predicate,
-            |predicate, (index, pk_column_id)| {
-                Predicate::and(
-                    predicate,
-                    Predicate::Eq(
-                        Column::physical(*pk_column_id, None),
-                        Column::ArrayParam {
-                            param: SQLParamContainer::from_sql_values(
-                                (0..rows)
-                                    .map(|row| {
-                                        transaction_context.resolve_value(
-                                            self.prev_step_id,
-                                            row,
-                                            index,
-                                        )
-                                    })
-                                    .collect::<Vec<_>>(),
-                                pk_column_types[index].clone(),
-                            ),
-                            wrapper: ArrayParamWrapper::Any,
-                        },
-                    ),
-                )
-            },
-        );
+        let predicate =
+            pk_column_ids
+                .iter()
+                .enumerate()
+                .fold(predicate, |predicate, (index, pk_column_id)| {
+                    Predicate::and(
+                        predicate,
+                        Predicate::Eq(
+                            Column::physical(*pk_column_id, None),
+                            Column::ArrayParam {
+                                param: SQLParamContainer::from_sql_values(
+                                    rows.clone()
+                                        .map(|row| {
+                                            transaction_context.resolve_value(
+                                                self.prev_step_id,
+                                                row,
+                                                index,
+                                            )
+                                        })
+                                        .collect::<Vec<_>>(),
+                                    pk_column_types[index].clone(),
+                                ),
+                                wrapper: ArrayParamWrapper::Any,
+                            },
+                        ),
+                    )
+                });
 
         ConcreteTransactionStep {
             operation: SQLOperation::Select(Select {
@@ -413,11 +612,31 @@ impl TemplateFilterOperation {
                     .map(|pk_column_id| Column::physical(pk_column_id, None))
                     .collect(),
                 group_by: None,
+                lock: None,
             }),
         }
     }
 }
 
+/// The ranges of rows to filter in each statement
+fn chunk_ranges(rows: usize, chunk_size: usize) -> Vec<Range<usize>> {
+    if rows <= chunk_size || chunk_size == 0 {
+        return vec![0..rows];
+    }
+
+    (0..rows)
+        .step_by(chunk_size)
+        .map(|start| start..(start + chunk_size).min(rows))
+        .collect()
+}
+
+/// The values of the key columns of a row selected by a filter statement
+fn row_key(row: &Row, key_columns: usize) -> Result<Vec<SQLValue>, DatabaseError> {
+    (0..key_columns)
+        .map(|col| Ok(extract_column(row, col)?))
+        .collect()
+}
+
 /// A step that is resolved at runtime (e.g. a select that depends on the result of a previous step)
 pub struct DynamicTransactionStep<'a> {
     pub function: Box<dyn FnOnce(&TransactionContext) -> ConcreteTransactionStep<'a> + Send + 'a>,
@@ -434,3 +653,21 @@ impl std::fmt::Debug for DynamicTransactionStep<'_> {
         f.debug_struct("DynamicTransactionStep").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multiplatform_test::multiplatform_test;
+
+    #[multiplatform_test]
+    fn chunk_boundaries() {
+        assert_eq!(chunk_ranges(0, 3), vec![0..0]);
+        assert_eq!(chunk_ranges(2, 3), vec![0..2]);
+        assert_eq!(chunk_ranges(3, 3), vec![0..3]);
+        assert_eq!(chunk_ranges(4, 3), vec![0..3, 3..4]);
+        assert_eq!(chunk_ranges(6, 3), vec![0..3, 3..6]);
+        assert_eq!(chunk_ranges(7, 3), vec![0..3, 3..6, 6..7]);
+        assert_eq!(chunk_ranges(3, 1), vec![0..1, 1..2, 2..3]);
+        assert_eq!(chunk_ranges(3, 0), vec![0..3]);
+    }
+}
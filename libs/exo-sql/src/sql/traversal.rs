@@ -0,0 +1,212 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::{ColumnId, Database, asql::traversal::TraversalDirection};
+
+use super::{ExpressionBuilder, SQLBuilder, column::Column, predicate::ConcretePredicate};
+
+/// A recursive subquery that selects the primary keys of rows reachable through a
+/// self-referential relation (see `AbstractTraversal`).
+#[derive(Debug, PartialEq)]
+pub struct Traversal {
+    /// (referring column such as `parent_id`, referred column such as `id`)
+    pub column_pairs: Vec<(ColumnId, ColumnId)>,
+    pub direction: TraversalDirection,
+    pub start: ConcretePredicate,
+    pub level_predicate: ConcretePredicate,
+    pub max_depth: u32,
+}
+
+impl ExpressionBuilder for Traversal {
+    /// Build a recursive subquery such as (for descendants):
+    ///
+    /// ```sql
+    /// (WITH RECURSIVE "categories$tree" AS (
+    ///     SELECT "categories"."id", "categories"."parent_id", 0 AS "$depth" FROM "categories" WHERE <start> AND <level_predicate>
+    ///     UNION ALL
+    ///     SELECT "categories"."id", "categories"."parent_id", "categories$tree"."$depth" + 1 FROM "categories"
+    ///         INNER JOIN "categories$tree" ON "categories"."parent_id" = "categories$tree"."id"
+    ///         WHERE "categories$tree"."$depth" < <max_depth> AND (<level_predicate>)
+    /// ) SELECT "categories$tree"."id" FROM "categories$tree" WHERE "categories$tree"."$depth" > 0)
+    /// ```
+    ///
+    /// For ancestors, the join condition is reversed (`"categories"."id" = "categories$tree"."parent_id"`).
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        let Some((first_self_column_id, _)) = self.column_pairs.first() else {
+            builder.push_str("(SELECT NULL WHERE FALSE)");
+            return;
+        };
+
+        let table = database.get_table(first_self_column_id.table_id);
+        let tree_name = format!("{}$tree", table.name.synthetic_name());
+
+        let referred_columns: Vec<_> = self
+            .column_pairs
+            .iter()
+            .map(|(_, referred)| *referred)
+            .collect();
+        // The columns carried through the recursion: the referred columns followed by the referring columns
+        let table_columns: Vec<_> = referred_columns
+            .iter()
+            .copied()
+            .chain(self.column_pairs.iter().map(|(referring, _)| *referring))
+            .collect();
+
+        let push_tree_column = |builder: &mut SQLBuilder, column_name: &str| {
+            builder.push_identifier(&tree_name);
+            builder.push('.');
+            builder.push_identifier(column_name);
+        };
+
+        let push_table_columns = |builder: &mut SQLBuilder| {
+            builder.push_iter(table_columns.iter(), ", ", |builder, column_id| {
+                Column::physical(*column_id, None).build(database, builder)
+            });
+        };
+
+        builder.push_str("(WITH RECURSIVE ");
+        builder.push_identifier(&tree_name);
+        builder.push_str(" AS (SELECT ");
+        push_table_columns(builder);
+        builder.push_str(", 0 AS \"$depth\" FROM ");
+        table.build(database, builder);
+        let start_predicates: Vec<_> = [&self.start, &self.level_predicate]
+            .into_iter()
+            .filter(|predicate| **predicate != ConcretePredicate::True)
+            .collect();
+        if !start_predicates.is_empty() {
+            builder.push_str(" WHERE ");
+            builder.push_iter(
+                start_predicates.into_iter(),
+                " AND ",
+                |builder, predicate| {
+                    builder.push('(');
+                    predicate.build(database, builder);
+                    builder.push(')');
+                },
+            );
+        }
+
+        builder.push_str(" UNION ALL SELECT ");
+        push_table_columns(builder);
+        builder.push_str(", ");
+        push_tree_column(builder, "$depth");
+        builder.push_str(" + 1 FROM ");
+        table.build(database, builder);
+        builder.push_str(" INNER JOIN ");
+        builder.push_identifier(&tree_name);
+        builder.push_str(" ON ");
+        builder.push_iter(
+            self.column_pairs.iter(),
+            " AND ",
+            |builder, (referring, referred)| {
+                // Descendants: the child's referring column matches the parent's referred column
+                // Ancestors: the parent's referred column matches the child's referring column
+                let (table_column, tree_column) = match self.direction {
+                    TraversalDirection::Descendants => (referring, referred),
+                    TraversalDirection::Ancestors => (referred, referring),
+                };
+                Column::physical(*table_column, None).build(database, builder);
+                builder.push_str(" = ");
+                push_tree_column(builder, &tree_column.get_column(database).name);
+            },
+        );
+        builder.push_str(" WHERE ");
+        push_tree_column(builder, "$depth");
+        builder.push_str(&format!(" < {}", self.max_depth));
+        if self.level_predicate != ConcretePredicate::True {
+            builder.push_str(" AND (");
+            self.level_predicate.build(database, builder);
+            builder.push(')');
+        }
+
+        builder.push_str(") SELECT ");
+        builder.push_iter(referred_columns.iter(), ", ", |builder, column_id| {
+            push_tree_column(builder, &column_id.get_column(database).name)
+        });
+        builder.push_str(" FROM ");
+        builder.push_identifier(&tree_name);
+        builder.push_str(" WHERE ");
+        push_tree_column(builder, "$depth");
+        builder.push_str(" > 0)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        SQLParamContainer, SchemaObjectName,
+        schema::{
+            database_spec::DatabaseSpec,
+            table_spec::TableSpec,
+            test_helper::{pk_column, pk_reference_column, string_column},
+        },
+    };
+
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    #[multiplatform_test]
+    fn descendants_and_ancestors() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                SchemaObjectName::new("categories", None),
+                vec![
+                    pk_column("id"),
+                    string_column("name"),
+                    pk_reference_column("parent_id", "categories", None, "parent"),
+                ],
+                vec![],
+                vec![],
+                true,
+            )],
+            vec![],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&SchemaObjectName::new("categories", None))
+            .unwrap();
+        let id_col_id = database.get_column_id(table_id, "id").unwrap();
+        let name_col_id = database.get_column_id(table_id, "name").unwrap();
+        let parent_id_col_id = database.get_column_id(table_id, "parent_id").unwrap();
+
+        let traversal = |direction| Traversal {
+            column_pairs: vec![(parent_id_col_id, id_col_id)],
+            direction,
+            start: ConcretePredicate::Eq(
+                Column::physical(id_col_id, None),
+                Column::Param(SQLParamContainer::i32(1)),
+            ),
+            level_predicate: ConcretePredicate::Neq(
+                Column::physical(name_col_id, None),
+                Column::Param(SQLParamContainer::string("hidden".to_string())),
+            ),
+            max_depth: 10,
+        };
+
+        assert_binding!(
+            traversal(TraversalDirection::Descendants).to_sql(&database),
+            r#"(WITH RECURSIVE "categories$tree" AS (SELECT "categories"."id", "categories"."parent_id", 0 AS "$depth" FROM "categories" WHERE ("categories"."id" = $1) AND ("categories"."name" <> $2) UNION ALL SELECT "categories"."id", "categories"."parent_id", "categories$tree"."$depth" + 1 FROM "categories" INNER JOIN "categories$tree" ON "categories"."parent_id" = "categories$tree"."id" WHERE "categories$tree"."$depth" < 10 AND ("categories"."name" <> $3)) SELECT "categories$tree"."id" FROM "categories$tree" WHERE "categories$tree"."$depth" > 0)"#,
+            1,
+            "hidden".to_string(),
+            "hidden".to_string()
+        );
+
+        assert_binding!(
+            traversal(TraversalDirection::Ancestors).to_sql(&database),
+            r#"(WITH RECURSIVE "categories$tree" AS (SELECT "categories"."id", "categories"."parent_id", 0 AS "$depth" FROM "categories" WHERE ("categories"."id" = $1) AND ("categories"."name" <> $2) UNION ALL SELECT "categories"."id", "categories"."parent_id", "categories$tree"."$depth" + 1 FROM "categories" INNER JOIN "categories$tree" ON "categories"."id" = "categories$tree"."parent_id" WHERE "categories$tree"."$depth" < 10 AND ("categories"."name" <> $3)) SELECT "categories$tree"."id" FROM "categories$tree" WHERE "categories$tree"."$depth" > 0)"#,
+            1,
+            "hidden".to_string(),
+            "hidden".to_string()
+        );
+    }
+}
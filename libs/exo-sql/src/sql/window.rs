@@ -0,0 +1,165 @@
+// Copyright Exograph, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file at the root of this repository.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::{ColumnId, Database, TableId, asql::window::WindowFunction};
+
+use super::{
+    ExpressionBuilder, SQLBuilder,
+    column::Column,
+    order::{OrderBy, OrderByElement, Ordering},
+    predicate::ConcretePredicate,
+};
+
+/// A correlated subquery that computes a window function for the current row (see `AbstractWindow`).
+#[derive(Debug, PartialEq)]
+pub struct WindowSelect {
+    pub table_id: TableId,
+    pub function: WindowFunction,
+    pub partition_by: Vec<ColumnId>,
+    pub order_by: Vec<(ColumnId, Ordering)>,
+    pub predicate: ConcretePredicate,
+    /// The alias of the table in the enclosing query (if aliased)
+    pub table_alias: Option<String>,
+}
+
+impl ExpressionBuilder for WindowSelect {
+    /// Build a subquery such as:
+    ///
+    /// ```sql
+    /// (SELECT "scores$window"."$value" FROM (
+    ///     SELECT "scores"."id", rank() OVER (PARTITION BY "scores"."game_id" ORDER BY "scores"."points" DESC) AS "$value"
+    ///     FROM "scores" WHERE <predicate>
+    /// ) AS "scores$window" WHERE "scores$window"."id" = "scores"."id")
+    /// ```
+    ///
+    /// The window must be computed in a subquery, since it needs to see all rows (not just the ones
+    /// selected by the enclosing query) and window functions may not be nested inside the
+    /// aggregates (such as `json_agg`) used by the enclosing query.
+    fn build(&self, database: &Database, builder: &mut SQLBuilder) {
+        let table = database.get_table(self.table_id);
+        let window_name = format!("{}$window", table.name.synthetic_name());
+        let pk_column_ids = database.get_pk_column_ids(self.table_id);
+
+        let push_window_column = |builder: &mut SQLBuilder, column_name: &str| {
+            builder.push_identifier(&window_name);
+            builder.push('.');
+            builder.push_identifier(column_name);
+        };
+
+        builder.push_str("(SELECT ");
+        push_window_column(builder, "$value");
+        builder.push_str(" FROM (SELECT ");
+        builder.push_iter(pk_column_ids.iter(), ", ", |builder, column_id| {
+            Column::physical(*column_id, None).build(database, builder)
+        });
+        builder.push_str(", ");
+        builder.push_str(self.function.sql_name());
+        builder.push_str("() OVER (");
+        if !self.partition_by.is_empty() {
+            builder.push_str("PARTITION BY ");
+            builder.push_iter(self.partition_by.iter(), ", ", |builder, column_id| {
+                Column::physical(*column_id, None).build(database, builder)
+            });
+            if !self.order_by.is_empty() {
+                builder.push_space();
+            }
+        }
+        if !self.order_by.is_empty() {
+            OrderBy(
+                self.order_by
+                    .iter()
+                    .map(|(column_id, ordering)| OrderByElement::new(*column_id, *ordering, None))
+                    .collect(),
+            )
+            .build(database, builder);
+        }
+        builder.push_str(") AS \"$value\" FROM ");
+        table.build(database, builder);
+        if self.predicate != ConcretePredicate::True {
+            builder.push_str(" WHERE ");
+            self.predicate.build(database, builder);
+        }
+        builder.push_str(") AS ");
+        builder.push_identifier(&window_name);
+        builder.push_str(" WHERE ");
+        builder.push_iter(pk_column_ids.iter(), " AND ", |builder, column_id| {
+            push_window_column(builder, &column_id.get_column(database).name);
+            builder.push_str(" = ");
+            Column::physical(*column_id, self.table_alias.clone()).build(database, builder);
+        });
+        builder.push(')');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        SchemaObjectName,
+        schema::{
+            database_spec::DatabaseSpec,
+            table_spec::TableSpec,
+            test_helper::{int_column, pk_column, string_column},
+        },
+    };
+
+    use multiplatform_test::multiplatform_test;
+
+    use super::*;
+
+    #[multiplatform_test]
+    fn rank_within_partition() {
+        let database = DatabaseSpec::new(
+            vec![TableSpec::new(
+                SchemaObjectName::new("scores", None),
+                vec![pk_column("id"), string_column("game"), int_column("points")],
+                vec![],
+                vec![],
+                true,
+            )],
+            vec![],
+            vec![],
+        )
+        .to_database();
+
+        let table_id = database
+            .get_table_id(&SchemaObjectName::new("scores", None))
+            .unwrap();
+        let game_col_id = database.get_column_id(table_id, "game").unwrap();
+        let points_col_id = database.get_column_id(table_id, "points").unwrap();
+        let id_col_id = database.get_column_id(table_id, "id").unwrap();
+
+        let window = WindowSelect {
+            table_id,
+            function: WindowFunction::Rank,
+            partition_by: vec![game_col_id],
+            order_by: vec![(points_col_id, Ordering::Desc), (id_col_id, Ordering::Asc)],
+            predicate: ConcretePredicate::True,
+            table_alias: None,
+        };
+
+        assert_binding!(
+            window.to_sql(&database),
+            r#"(SELECT "scores$window"."$value" FROM (SELECT "scores"."id", rank() OVER (PARTITION BY "scores"."game" ORDER BY "scores"."points" DESC, "scores"."id" ASC) AS "$value" FROM "scores") AS "scores$window" WHERE "scores$window"."id" = "scores"."id")"#
+        );
+
+        let window = WindowSelect {
+            table_id,
+            function: WindowFunction::RowNumber,
+            partition_by: vec![],
+            order_by: vec![(points_col_id, Ordering::Desc)],
+            predicate: ConcretePredicate::True,
+            table_alias: Some("scores$scores".to_string()),
+        };
+
+        assert_binding!(
+            window.to_sql(&database),
+            r#"(SELECT "scores$window"."$value" FROM (SELECT "scores"."id", row_number() OVER (ORDER BY "scores"."points" DESC) AS "$value" FROM "scores") AS "scores$window" WHERE "scores$window"."id" = "scores$scores"."id")"#
+        );
+    }
+}
@@ -44,7 +44,7 @@ fn cleanup() {
 // Thus we can't use tokio::sync::Mutex here.
 // TODO: Find a better way to handle this.
 #[allow(clippy::await_holding_lock)]
-pub async fn with_client<Fut, T>(f: impl FnOnce(DatabaseClient) -> Fut) -> T
+pub async fn with_database_url<Fut, T>(f: impl FnOnce(String) -> Fut) -> T
 where
     Fut: Future<Output = T>,
 {
@@ -57,15 +57,25 @@ where
 
     let database = database_server.create_database(&database_name).unwrap();
 
-    let client =
-        DatabaseClientManager::from_url_direct(&database.url(), false, TransactionMode::ReadWrite)
-            .await
-            .unwrap()
-            .get_client()
-            .await
-            .unwrap();
+    f(database.url()).await
+}
+
+pub async fn with_client<Fut, T>(f: impl FnOnce(DatabaseClient) -> Fut) -> T
+where
+    Fut: Future<Output = T>,
+{
+    with_database_url(|url| async move {
+        let client =
+            DatabaseClientManager::from_url_direct(&url, false, TransactionMode::ReadWrite)
+                .await
+                .unwrap()
+                .get_client()
+                .await
+                .unwrap();
 
-    f(client).await
+        f(client).await
+    })
+    .await
 }
 
 pub async fn with_init_script<Fut, T>(init_script: &str, f: impl FnOnce(DatabaseClient) -> Fut) -> T
@@ -13,8 +13,10 @@ use crate::{
 };
 
 use crate::{
-    AbstractDelete, Column, Database,
+    AbstractDelete, AbstractPredicate, AbstractSelect, AliasedSelectionElement, Column, ColumnId,
+    Database, ReferentialAction, Selection, SelectionElement, TableId,
     sql::{
+        predicate::ConcretePredicate,
         sql_operation::SQLOperation,
         transaction::{ConcreteTransactionStep, TransactionScript, TransactionStep},
     },
@@ -58,6 +60,12 @@ impl DeleteStrategy for CteStrategy {
             transaction_script,
         );
 
+        for operation in emulated_referential_actions(table_id, &predicate, database, transformer) {
+            let _ = transaction_script.add_step(TransactionStep::Concrete(Box::new(
+                ConcreteTransactionStep::new(operation),
+            )));
+        }
+
         let delete_query = to_delete(
             AbstractDelete {
                 table_id,
@@ -75,6 +83,124 @@ impl DeleteStrategy for CteStrategy {
     }
 }
 
+/// Emulate `ON DELETE CASCADE` and `ON DELETE SET NULL` for references from tables not managed by
+/// Exograph. We don't create the foreign keys for such tables, so they may not exist (or may not
+/// carry the declared action). For managed tables, we rely on the foreign key constraint instead.
+///
+/// For example, to delete venues whose `concerts` table (unmanaged) declares `ON DELETE CASCADE`:
+/// ```sql
+/// DELETE FROM "concerts" WHERE ("concerts"."venue_id") IN (SELECT "venues"."id" FROM "venues" WHERE <the predicate>)
+/// ```
+/// Only the immediate references are emulated (i.e. the emulated deletes don't cascade further).
+fn emulated_referential_actions<'a>(
+    table_id: TableId,
+    predicate: &AbstractPredicate,
+    database: &'a Database,
+    transformer: &Postgres,
+) -> Vec<SQLOperation<'a>> {
+    let mut operations = vec![];
+
+    for (referencing_table_id, referencing_table) in database.tables().iter() {
+        if referencing_table.managed {
+            continue;
+        }
+
+        // (group name, action, (referencing column, referenced column) pairs)
+        let mut references: Vec<(&str, ReferentialAction, Vec<(ColumnId, ColumnId)>)> = vec![];
+
+        for (column_index, column) in referencing_table.columns.iter().enumerate() {
+            let self_column_id = ColumnId {
+                table_id: referencing_table_id,
+                column_index,
+            };
+
+            for reference in column.column_references.iter().flatten() {
+                if reference.foreign_column_id.table_id != table_id {
+                    continue;
+                }
+
+                let column_pair = (self_column_id, reference.foreign_column_id);
+                match references
+                    .iter_mut()
+                    .find(|(group_name, _, _)| *group_name == reference.group_name)
+                {
+                    Some((_, _, column_pairs)) => column_pairs.push(column_pair),
+                    None => references.push((
+                        &reference.group_name,
+                        reference.on_delete,
+                        vec![column_pair],
+                    )),
+                }
+            }
+        }
+
+        for (_, action, column_pairs) in references {
+            if !matches!(
+                action,
+                ReferentialAction::Cascade | ReferentialAction::SetNull
+            ) {
+                continue;
+            }
+
+            let referenced_rows = transformer.compute_select(
+                AbstractSelect {
+                    table_id,
+                    selection: Selection::Seq(
+                        column_pairs
+                            .iter()
+                            .map(|(_, foreign_column_id)| {
+                                AliasedSelectionElement::new(
+                                    foreign_column_id.get_column(database).name.clone(),
+                                    SelectionElement::Physical(*foreign_column_id),
+                                )
+                            })
+                            .collect(),
+                    ),
+                    predicate: predicate.clone(),
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                },
+                &SelectionLevel::TopLevel,
+                true, // allow duplicate rows since this is going to be used as a part of `IN`
+                database,
+            );
+
+            let referencing_predicate = ConcretePredicate::In(
+                Column::ColumnArray(
+                    column_pairs
+                        .iter()
+                        .map(|(self_column_id, _)| Column::physical(*self_column_id, None))
+                        .collect(),
+                ),
+                Column::SubSelect(Box::new(referenced_rows)),
+            );
+
+            let operation = if action == ReferentialAction::Cascade {
+                SQLOperation::Delete(referencing_table.delete(referencing_predicate, vec![]))
+            } else {
+                SQLOperation::Update(
+                    referencing_table.update(
+                        column_pairs
+                            .iter()
+                            .map(|(self_column_id, _)| {
+                                (self_column_id.get_column(database), Column::Null)
+                            })
+                            .collect(),
+                        referencing_predicate.into(),
+                        vec![],
+                    ),
+                )
+            };
+
+            operations.push(operation);
+        }
+    }
+
+    operations
+}
+
 fn to_delete<'a>(
     abstract_delete: AbstractDelete,
     database: &'a Database,
@@ -166,6 +292,7 @@ mod tests {
                         order_by: None,
                         offset: None,
                         limit: None,
+                        lock: None,
                     },
                     predicate: Predicate::True,
                     precheck_predicates: vec![],
@@ -207,6 +334,7 @@ mod tests {
                         order_by: None,
                         offset: None,
                         limit: None,
+                        lock: None,
                     },
                     predicate,
                     precheck_predicates: vec![],
@@ -254,6 +382,7 @@ mod tests {
                         order_by: None,
                         offset: None,
                         limit: None,
+                        lock: None,
                     },
                     predicate,
                     precheck_predicates: vec![],
@@ -269,4 +398,77 @@ mod tests {
             },
         );
     }
+
+    #[multiplatform_test]
+    fn emulated_referential_actions_for_unmanaged_tables() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 mut database,
+                 concerts_table,
+                 concerts_venue_id_column,
+                 venues_table,
+                 venues_name_column,
+                 ..
+             }| {
+                let predicate = AbstractPredicate::Eq(
+                    ColumnPath::Physical(PhysicalColumnPath::leaf(venues_name_column)),
+                    ColumnPath::Param(SQLParamContainer::string("v1".to_string())),
+                );
+
+                // With managed tables, we rely on the foreign key constraint
+                assert!(
+                    emulated_referential_actions(venues_table, &predicate, &database, &Postgres {})
+                        .is_empty()
+                );
+
+                database.get_table_mut(concerts_table).managed = false;
+                let set_action = |database: &mut Database, action: ReferentialAction| {
+                    database
+                        .get_column_mut(concerts_venue_id_column)
+                        .column_references
+                        .as_mut()
+                        .unwrap()[0]
+                        .on_delete = action;
+                };
+
+                set_action(&mut database, ReferentialAction::Cascade);
+                {
+                    let operations = emulated_referential_actions(
+                        venues_table,
+                        &predicate,
+                        &database,
+                        &Postgres {},
+                    );
+                    assert_eq!(operations.len(), 1);
+                    assert_binding!(
+                        operations[0].to_sql(&database),
+                        r#"DELETE FROM "concerts" WHERE "concerts"."venue_id" IN (SELECT "venues"."id" FROM "venues" WHERE "venues"."name" = $1)"#,
+                        "v1".to_string()
+                    );
+                }
+
+                set_action(&mut database, ReferentialAction::SetNull);
+                {
+                    let operations = emulated_referential_actions(
+                        venues_table,
+                        &predicate,
+                        &database,
+                        &Postgres {},
+                    );
+                    assert_eq!(operations.len(), 1);
+                    assert_binding!(
+                        operations[0].to_sql(&database),
+                        r#"UPDATE "concerts" SET "venue_id" = NULL WHERE "concerts"."venue_id" IN (SELECT "venues"."id" FROM "venues" WHERE "venues"."name" = $1)"#,
+                        "v1".to_string()
+                    );
+                }
+
+                set_action(&mut database, ReferentialAction::Restrict);
+                assert!(
+                    emulated_referential_actions(venues_table, &predicate, &database, &Postgres {})
+                        .is_empty()
+                );
+            },
+        );
+    }
 }
@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use crate::{
-    AbstractOrderBy, ColumnPath, Database,
+    AbstractOrderBy, Column, ColumnPath, Database,
     asql::order_by::AbstractOrderByExpr,
     sql::order::{OrderBy, OrderByElement, OrderByElementExpr, VectorDistanceOperand},
     transform::transformer::OrderByTransformer,
@@ -45,6 +45,22 @@ impl OrderByTransformer for Postgres {
                         let column_id = path.leaf_column();
                         OrderByElement::new(column_id, *ordering, table_alias)
                     }
+                    AbstractOrderByExpr::JsonField(json_field) => {
+                        let path = &json_field.column;
+                        let table_alias = match (selection_level.prefix(database), path.alias()) {
+                            (Some(prefix), Some(alias)) => Some(format!("{}${}", prefix, alias)),
+                            (None, Some(alias)) => Some(alias),
+                            _ => None,
+                        };
+
+                        let expr = OrderByElementExpr::Expression(Column::JsonFieldValue {
+                            column: Box::new(Column::physical(path.leaf_column(), table_alias)),
+                            path: json_field.path.clone(),
+                            typ: json_field.typ.clone(),
+                        });
+
+                        OrderByElement(expr, *ordering, None)
+                    }
                     AbstractOrderByExpr::VectorDistance(lhs, rhs, op) => {
                         fn to_column(column_path: &ColumnPath) -> VectorDistanceOperand {
                             match column_path {
@@ -42,11 +42,17 @@ fn compute_precheck_query(
     fn get_lead_table_ids(column_path: &ColumnPath) -> Vec<TableId> {
         match column_path {
             ColumnPath::Physical(physical_path) => vec![physical_path.lead_table_id()],
+            ColumnPath::JsonField(json_field) => vec![json_field.column.lead_table_id()],
             ColumnPath::Predicate(predicate) => predicate
                 .column_paths()
                 .iter()
                 .flat_map(|path| get_lead_table_ids(path))
                 .collect(),
+            ColumnPath::Traversal(traversal) => traversal
+                .column_pairs
+                .first()
+                .map(|pair| vec![pair.self_column_id.table_id])
+                .unwrap_or_default(),
             ColumnPath::Param(_) | ColumnPath::Null => vec![],
         }
     }
@@ -85,5 +91,6 @@ fn compute_precheck_query(
         order_by: None,
         offset: None,
         limit: None,
+        lock: None,
     }))
 }
@@ -10,8 +10,8 @@
 use crate::{
     AbstractPredicate, AbstractSelect, AliasedSelectionElement, Column, ColumnPath, Database,
     NumericComparator, Selection, SelectionElement, VectorDistanceFunction,
-    asql::column_path::{ColumnPathLink, RelationLink},
-    sql::predicate::ConcretePredicate,
+    asql::column_path::{ColumnPathLink, JsonFieldPath, RelationLink},
+    sql::{predicate::ConcretePredicate, traversal::Traversal},
     transform::{pg::selection_level::SelectionLevel, transformer::PredicateTransformer},
 };
 
@@ -192,6 +192,13 @@ fn to_subselect_predicate(
                 to_subselect_predicate(transformer, p1, selection_level, database),
                 to_subselect_predicate(transformer, p2, selection_level, database),
             ),
+            AbstractPredicate::Not(p) => {
+                // The negated predicate may span multiple relations (for example, `NOT
+                // (venue.name = $1 AND title = $2)`), so each relation must still be brought in
+                // through a subselect (a join predicate would refer to tables not in the context)
+                let p = to_subselect_predicate(transformer, p, selection_level, database);
+                ConcretePredicate::Not(Box::new(p))
+            }
             _ => to_join_predicate(predicate, selection_level, database),
         },
     }
@@ -225,6 +232,7 @@ fn form_subselect(
         order_by: None,
         offset: None,
         limit: None,
+        lock: None,
     };
 
     let select = select_transformer.compute_select(
@@ -263,7 +271,34 @@ fn leaf_column(
             Column::physical(links.leaf_column(), alias)
         }
         ColumnPath::Param(l) => Column::Param(l.clone()),
+        ColumnPath::JsonField(JsonFieldPath { column, path, typ }) => Column::JsonFieldValue {
+            column: Box::new(leaf_column(
+                &ColumnPath::Physical(column.clone()),
+                selection_level,
+                database,
+            )),
+            path: path.clone(),
+            typ: typ.clone(),
+        },
         ColumnPath::Null => Column::Null,
+        ColumnPath::Traversal(traversal) => {
+            // The start and level predicates are evaluated against the traversed table inside the
+            // recursive query, so they are always at the top level
+            let to_predicate = |predicate| {
+                to_subselect_predicate(&Postgres {}, predicate, &SelectionLevel::TopLevel, database)
+            };
+            Column::Traversal(Box::new(Traversal {
+                column_pairs: traversal
+                    .column_pairs
+                    .iter()
+                    .map(|pair| (pair.self_column_id, pair.foreign_column_id))
+                    .collect(),
+                direction: traversal.direction,
+                start: to_predicate(&traversal.start),
+                level_predicate: to_predicate(&traversal.level_predicate),
+                max_depth: traversal.max_depth,
+            }))
+        }
         ColumnPath::Predicate(_) => unreachable!(),
     }
 }
@@ -284,7 +319,24 @@ fn attempt_subselect_predicate(
                     _ => None,
                 }
             }
-            ColumnPath::Param(_) | ColumnPath::Null | ColumnPath::Predicate(_) => None,
+            ColumnPath::JsonField(json_field) => {
+                let (head, tail) = json_field.column.split_head();
+                match (head, tail) {
+                    (ColumnPathLink::Relation(link), Some(tail)) => Some((
+                        link,
+                        ColumnPath::JsonField(JsonFieldPath {
+                            column: tail,
+                            path: json_field.path.clone(),
+                            typ: json_field.typ.clone(),
+                        }),
+                    )),
+                    _ => None,
+                }
+            }
+            ColumnPath::Param(_)
+            | ColumnPath::Null
+            | ColumnPath::Predicate(_)
+            | ColumnPath::Traversal(_) => None,
         }
     }
 
@@ -619,6 +671,108 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn test_not_spanning_relations() {
+        TestSetup::with_setup(
+            move |TestSetup {
+                      database,
+                      concerts_name_column,
+                      concerts_venue_id_column,
+                      venues_name_column,
+                      ..
+                  }| {
+                let abstract_predicate = AbstractPredicate::Not(Box::new(AbstractPredicate::and(
+                    AbstractPredicate::Eq(
+                        ColumnPath::Physical(PhysicalColumnPath::from_columns(
+                            vec![concerts_venue_id_column, venues_name_column],
+                            &database,
+                        )),
+                        ColumnPath::Param(SQLParamContainer::string("v1".to_string())),
+                    ),
+                    AbstractPredicate::Eq(
+                        ColumnPath::Physical(PhysicalColumnPath::leaf(concerts_name_column)),
+                        ColumnPath::Param(SQLParamContainer::string("c1".to_string())),
+                    ),
+                )));
+
+                {
+                    let predicate = Postgres {}.to_predicate(
+                        &abstract_predicate,
+                        &SelectionLevel::TopLevel,
+                        true,
+                        &database,
+                    );
+
+                    assert_binding!(
+                        predicate.to_sql(&database),
+                        r#"NOT(("venues"."name" = $1 AND "concerts"."name" = $2))"#,
+                        "v1".to_string(),
+                        "c1".to_string()
+                    );
+                }
+
+                {
+                    let predicate = Postgres {}.to_predicate(
+                        &abstract_predicate,
+                        &SelectionLevel::TopLevel,
+                        false,
+                        &database,
+                    );
+
+                    assert_binding!(
+                        predicate.to_sql(&database),
+                        r#"NOT(("concerts"."venue_id" IN (SELECT "venues"."id" FROM "venues" WHERE "venues"."name" = $1) AND "concerts"."name" = $2))"#,
+                        "v1".to_string(),
+                        "c1".to_string()
+                    );
+                }
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn test_nested_one_to_many_path() {
+        TestSetup::with_setup(
+            move |TestSetup {
+                      database,
+                      concert_artists_concert_id_column,
+                      concert_artists_artist_id_column,
+                      artists_address_id_column,
+                      addresses_city_column,
+                      ..
+                  }| {
+                // concerts(where: {concertArtists: {artist: {address: {city: {eq: "c1"}}}}})
+                let abstract_predicate = AbstractPredicate::Eq(
+                    ColumnPath::Physical(PhysicalColumnPath::from_columns(
+                        vec![
+                            concert_artists_concert_id_column,
+                            concert_artists_artist_id_column,
+                            artists_address_id_column,
+                            addresses_city_column,
+                        ],
+                        &database,
+                    )),
+                    ColumnPath::Param(SQLParamContainer::string("c1".to_string())),
+                );
+
+                // The one-to-many link must be lowered to an `IN` subselect (and not a join), so
+                // that each concert appears only once regardless of the number of its artists
+                let predicate = Postgres {}.to_predicate(
+                    &abstract_predicate,
+                    &SelectionLevel::TopLevel,
+                    false,
+                    &database,
+                );
+
+                assert_binding!(
+                    predicate.to_sql(&database),
+                    r#""concerts"."id" IN (SELECT "concert_artists"."concert_id" FROM "concert_artists" LEFT JOIN "artists" LEFT JOIN "addresses" ON "artists"."address_id" = "addresses"."id" ON "concert_artists"."artist_id" = "artists"."id" WHERE "addresses"."city" = $1)"#,
+                    "c1".to_string()
+                );
+            },
+        );
+    }
+
     fn test_nested_op_predicate<OP>(op: OP, op_combinator: fn(&str, &str) -> String)
     where
         OP: Clone + Fn(ColumnPath, ColumnPath) -> AbstractPredicate,
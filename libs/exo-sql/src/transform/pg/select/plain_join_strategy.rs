@@ -76,6 +76,12 @@ impl SelectionStrategy for PlainJoinStrategy {
     }
 
     fn suitable(&self, selection_context: &SelectionContext) -> bool {
+        if selection_context.abstract_select.lock.is_some() {
+            // Postgres doesn't allow locking rows while aggregating them, so we leave it to the
+            // strategies that lock the rows in a subselect
+            return false;
+        }
+
         if matches!(
             selection_context.abstract_select.selection,
             Selection::Json(..)
@@ -131,6 +137,7 @@ impl SelectionStrategy for PlainJoinStrategy {
             limit: abstract_select.limit.clone(),
             group_by: None,
             top_level_selection: selection_level.is_top_level(),
+            lock: None,
         }
     }
 }
@@ -154,7 +154,8 @@ impl Postgres {
 #[cfg(test)]
 mod tests {
     use crate::{
-        AbstractOrderBy, Limit, Offset, Ordering, RelationId,
+        AbstractOrderBy, DateTruncUnit, Function, Limit, MaskStrategy, Offset, OpaqueIdKey,
+        Ordering, RelationId, RowLock, StringFunction,
         asql::{
             column_path::{ColumnPath, PhysicalColumnPath},
             order_by::AbstractOrderByExpr,
@@ -192,6 +193,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -227,6 +229,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -261,6 +264,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -272,6 +276,219 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn coalesced_json() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_name_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![AliasedSelectionElement::new(
+                            "name".to_string(),
+                            SelectionElement::Coalesce {
+                                source: Box::new(SelectionElement::Physical(concerts_name_column)),
+                                fallback: "Untitled 'concert'".to_string(),
+                            },
+                        )],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('name', COALESCE("concerts"."name", 'Untitled ''concert'''))), '[]'::json)::text FROM "concerts""#
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn string_transform_json() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_name_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![AliasedSelectionElement::new(
+                            "name".to_string(),
+                            SelectionElement::Function(Function::StringTransform {
+                                column_id: concerts_name_column,
+                                transforms: vec![
+                                    StringFunction::Trim,
+                                    StringFunction::Upper,
+                                    StringFunction::Substring {
+                                        start: 1,
+                                        length: Some(10),
+                                    },
+                                ],
+                            }),
+                        )],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('name', substring(upper(trim("concerts"."name")) FROM 1 FOR 10))), '[]'::json)::text FROM "concerts""#
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn date_transform_json() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_name_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![AliasedSelectionElement::new(
+                            "month".to_string(),
+                            SelectionElement::Function(Function::DateTransform {
+                                column_id: concerts_name_column,
+                                truncate: Some(DateTruncUnit::Month),
+                                format: Some(SQLParamContainer::str("YYYY-MM")),
+                                timezone: Some(SQLParamContainer::str("America/New_York")),
+                            }),
+                        )],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('month', to_char(date_trunc('month', "concerts"."name", $1) AT TIME ZONE $2, $3))), '[]'::json)::text FROM "concerts""#,
+                    "America/New_York",
+                    "America/New_York",
+                    "YYYY-MM"
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn masked_json() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_name_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![
+                            AliasedSelectionElement::new(
+                                "name".to_string(),
+                                SelectionElement::Masked {
+                                    source: Box::new(SelectionElement::Physical(
+                                        concerts_name_column,
+                                    )),
+                                    strategy: MaskStrategy::Last4,
+                                },
+                            ),
+                            AliasedSelectionElement::new(
+                                "contact".to_string(),
+                                SelectionElement::Masked {
+                                    source: Box::new(SelectionElement::Physical(
+                                        concerts_name_column,
+                                    )),
+                                    strategy: MaskStrategy::Email,
+                                },
+                            ),
+                        ],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('name', CASE WHEN "concerts"."name" IS NULL THEN NULL WHEN length("concerts"."name") <= 4 THEN '****' ELSE '****' || right("concerts"."name", 4) END, 'contact', regexp_replace("concerts"."name", '^(.)[^@]*', '\1***'))), '[]'::json)::text FROM "concerts""#
+                );
+            },
+        );
+    }
+
+    #[multiplatform_test]
+    fn opaque_id_json() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_id_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![AliasedSelectionElement::new(
+                            "id".to_string(),
+                            SelectionElement::OpaqueId {
+                                source: Box::new(SelectionElement::Physical(concerts_id_column)),
+                                key: OpaqueIdKey::from_secret("secret"),
+                            },
+                        )],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('id', lpad(to_hex(((((("concerts"."id")::int8 * $1) % 2147483648) # $2) * $3) % 2147483648), 8, '0'))), '[]'::json)::text FROM "concerts""#,
+                    1620270001i64,
+                    1077732977i64,
+                    627496259i64
+                );
+            },
+        );
+    }
+
     #[multiplatform_test]
     fn nested_many_to_one_json() {
         // {
@@ -322,6 +539,7 @@ mod tests {
                                         order_by: None,
                                         offset: None,
                                         limit: None,
+                                        lock: None,
                                     }),
                                 ),
                             ),
@@ -332,6 +550,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -387,6 +606,7 @@ mod tests {
                                         order_by: None,
                                         offset: None,
                                         limit: None,
+                                        lock: None,
                                     }),
                                 ),
                             ),
@@ -397,6 +617,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -408,6 +629,91 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn nested_transitive_aggregate() {
+        // concerts { artistsAgg { id { count } } } (through concert_artists)
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 artists_table,
+                 concerts_id_column,
+                 artists_id_column,
+                 concert_artists_concert_id_column,
+                 concert_artists_artist_id_column,
+                 ..
+             }| {
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![
+                            AliasedSelectionElement::new(
+                                "id".to_string(),
+                                SelectionElement::Physical(concerts_id_column),
+                            ),
+                            AliasedSelectionElement::new(
+                                "artistsAgg".to_string(),
+                                SelectionElement::TransitiveSubSelect(
+                                    vec![
+                                        RelationId::OneToMany(
+                                            get_otm_relation_for_columns(
+                                                &[concert_artists_concert_id_column],
+                                                &database,
+                                                None,
+                                            )
+                                            .unwrap(),
+                                        ),
+                                        RelationId::ManyToOne(
+                                            get_mto_relation_for_columns(
+                                                &[concert_artists_artist_id_column],
+                                                &database,
+                                                None,
+                                            )
+                                            .unwrap(),
+                                        ),
+                                    ],
+                                    Box::new(AbstractSelect {
+                                        table_id: artists_table,
+                                        selection: Selection::Json(
+                                            vec![AliasedSelectionElement::new(
+                                                "id".to_string(),
+                                                SelectionElement::Object(vec![(
+                                                    "count".to_string(),
+                                                    SelectionElement::Function(Function::Named {
+                                                        function_name: "count".to_string(),
+                                                        column_id: artists_id_column,
+                                                    }),
+                                                )]),
+                                            )],
+                                            SelectionCardinality::One,
+                                        ),
+                                        predicate: Predicate::True,
+                                        order_by: None,
+                                        offset: None,
+                                        limit: None,
+                                        lock: None,
+                                    }),
+                                ),
+                            ),
+                        ],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate: Predicate::True,
+                    order_by: None,
+                    offset: None,
+                    limit: None,
+                    lock: None,
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('id', "concerts"."id", 'artistsAgg', (SELECT json_build_object('id', json_build_object('count', count("artists"."id"))) FROM "artists" WHERE "artists"."id" IN (SELECT "concert_artists"."artist_id" FROM "concert_artists" WHERE "concert_artists"."concert_id" = "concerts"."id")))), '[]'::json)::text FROM "concerts""#
+                );
+            },
+        );
+    }
+
     #[multiplatform_test]
     fn nested_one_to_many_with_predicate() {
         // venues { concert(where: {venue: {id: {eq: 2}}} )}
@@ -460,6 +766,7 @@ mod tests {
                                         order_by: None,
                                         offset: None,
                                         limit: None,
+                                        lock: None,
                                     }),
                                 ),
                             ),
@@ -470,6 +777,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -518,6 +826,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -555,6 +864,7 @@ mod tests {
                     )])),
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -592,6 +902,7 @@ mod tests {
                     order_by: None,
                     offset: Some(Offset(10)),
                     limit: Some(Limit(20)),
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -606,6 +917,49 @@ mod tests {
         );
     }
 
+    #[multiplatform_test]
+    fn locked_json() {
+        TestSetup::with_setup(
+            |TestSetup {
+                 database,
+                 concerts_table,
+                 concerts_id_column,
+                 concerts_name_column,
+                 ..
+             }| {
+                let concert_name_path =
+                    ColumnPath::Physical(PhysicalColumnPath::leaf(concerts_name_column));
+
+                let literal = ColumnPath::Param(SQLParamContainer::string("c1".to_string()));
+                let predicate = AbstractPredicate::Eq(concert_name_path, literal);
+
+                let aselect = AbstractSelect {
+                    table_id: concerts_table,
+                    selection: Selection::Json(
+                        vec![AliasedSelectionElement::new(
+                            "id".to_string(),
+                            SelectionElement::Physical(concerts_id_column),
+                        )],
+                        SelectionCardinality::Many,
+                    ),
+                    predicate,
+                    order_by: None,
+                    offset: None,
+                    limit: Some(Limit(1)),
+                    lock: Some(RowLock::SkipLocked),
+                };
+
+                let select = Postgres {}.to_select(aselect, &database);
+                assert_binding!(
+                    select.to_sql(&database),
+                    r#"SELECT COALESCE(json_agg(json_build_object('id', "concerts"."id")), '[]'::json)::text FROM (SELECT "concerts".* FROM "concerts" WHERE "concerts"."name" = $1 LIMIT $2 FOR UPDATE OF "concerts" SKIP LOCKED) AS "concerts""#,
+                    "c1".to_string(),
+                    1i64
+                );
+            },
+        );
+    }
+
     #[multiplatform_test]
     fn nested_order_by() {
         TestSetup::with_setup(
@@ -635,6 +989,7 @@ mod tests {
                     )])),
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -688,6 +1043,7 @@ mod tests {
                                         order_by: None,
                                         offset: None,
                                         limit: None,
+                                        lock: None,
                                     }),
                                 ),
                             ),
@@ -698,6 +1054,7 @@ mod tests {
                     order_by: None,
                     offset: None,
                     limit: None,
+                    lock: None,
                 };
 
                 let select = Postgres {}.to_select(aselect, &database);
@@ -8,12 +8,20 @@
 // by the Apache License, Version 2.0.
 
 use crate::{
-    AliasedSelectionElement, Column, Database, Selection, SelectionCardinality, SelectionElement,
+    AbstractWindow, AliasedSelectionElement, Column, Database, RelationColumnPair, RelationId,
+    Selection, SelectionCardinality, SelectionElement, TableId,
     sql::{
         json_agg::JsonAgg,
         json_object::{JsonObject, JsonObjectElement},
+        predicate::ConcretePredicate,
+        select::Select,
+        table::Table,
+        window::WindowSelect,
+    },
+    transform::{
+        pg::{Postgres, selection_level::SelectionLevel},
+        transformer::PredicateTransformer,
     },
-    transform::pg::{Postgres, selection_level::SelectionLevel},
 };
 
 pub enum SelectionSQL {
@@ -116,6 +124,46 @@ impl SelectionElement {
                     database,
                 )))
             }
+            SelectionElement::TransitiveSubSelect(relation_ids, select) => {
+                let linking_predicate =
+                    transitive_linking_predicate(&relation_ids, selection_level, database);
+
+                // The select is on the table at the end of the chain, which doesn't relate directly
+                // to the selection level, so compute it on its own and link it to the parent row
+                let mut select =
+                    transformer.compute_select(*select, &SelectionLevel::TopLevel, false, database);
+                select.top_level_selection = false;
+                select.predicate = ConcretePredicate::and(select.predicate, linking_predicate);
+
+                Column::SubSelect(Box::new(select))
+            }
+            SelectionElement::Window(window) => {
+                let AbstractWindow {
+                    table_id,
+                    function,
+                    partition_by,
+                    order_by,
+                    predicate,
+                } = *window;
+                let table_alias = selection_level.self_referencing_table_alias(table_id, database);
+
+                // The window predicate is evaluated in its own subquery (against the unaliased table)
+                let predicate = transformer.to_predicate(
+                    &predicate,
+                    &SelectionLevel::TopLevel,
+                    false,
+                    database,
+                );
+
+                Column::Window(Box::new(WindowSelect {
+                    table_id,
+                    function,
+                    partition_by,
+                    order_by,
+                    predicate,
+                    table_alias,
+                }))
+            }
             SelectionElement::JsonExtract { source, path } => {
                 let column = source.to_sql(selection_level, transformer, database);
                 Column::JsonExtract {
@@ -130,6 +178,117 @@ impl SelectionElement {
                     key,
                 }
             }
+            SelectionElement::JsonProjection { source, elements } => {
+                let column = source.to_sql(selection_level, transformer, database);
+                let elements = elements
+                    .into_iter()
+                    .map(|(alias, element)| {
+                        JsonObjectElement::new(
+                            alias,
+                            element.to_sql(selection_level, transformer, database),
+                        )
+                    })
+                    .collect();
+                Column::JsonProjection {
+                    column: Box::new(column),
+                    object: JsonObject(elements),
+                }
+            }
+            SelectionElement::Coalesce { source, fallback } => {
+                let column = source.to_sql(selection_level, transformer, database);
+                Column::Coalesce {
+                    column: Box::new(column),
+                    fallback,
+                }
+            }
+            SelectionElement::Masked { source, strategy } => {
+                let column = source.to_sql(selection_level, transformer, database);
+                Column::Masked {
+                    column: Box::new(column),
+                    strategy,
+                }
+            }
+            SelectionElement::OpaqueId { source, key } => {
+                let column = source.to_sql(selection_level, transformer, database);
+                Column::OpaqueId {
+                    column: Box::new(column),
+                    key,
+                }
+            }
         }
     }
 }
+
+/// The predicate for the rows of the table at the end of a chain of relations that are reachable
+/// from the parent row: an equality with the parent row for the first relation, and an `IN`
+/// subselect for each subsequent relation.
+fn transitive_linking_predicate(
+    relation_ids: &[RelationId],
+    selection_level: &SelectionLevel,
+    database: &Database,
+) -> ConcretePredicate {
+    // The table the relation starts at and its column pairs (self column: the column in that
+    // table, foreign column: the column in the linked table)
+    let relation_columns = |relation_id: &RelationId| -> (TableId, Vec<RelationColumnPair>) {
+        match relation_id {
+            RelationId::OneToMany(relation_id) => {
+                let relation = relation_id.deref(database);
+                (relation.self_table_id, relation.column_pairs)
+            }
+            RelationId::ManyToOne(relation_id) => {
+                let relation = relation_id.deref(database);
+                (relation.self_table_id, relation.column_pairs)
+            }
+        }
+    };
+
+    let (first, rest) = relation_ids
+        .split_first()
+        .expect("Transitive subselect without any relation");
+
+    let (parent_table_id, column_pairs) = relation_columns(first);
+    let parent_table_alias =
+        selection_level.self_referencing_table_alias(parent_table_id, database);
+
+    let first_predicate =
+        column_pairs
+            .iter()
+            .fold(ConcretePredicate::True, |predicate, column_pair| {
+                ConcretePredicate::and(
+                    predicate,
+                    ConcretePredicate::Eq(
+                        Column::physical(column_pair.foreign_column_id, None),
+                        Column::physical(column_pair.self_column_id, parent_table_alias.clone()),
+                    ),
+                )
+            });
+
+    rest.iter().fold(first_predicate, |predicate, relation_id| {
+        let (table_id, column_pairs) = relation_columns(relation_id);
+
+        let linked_rows = Select {
+            table: Table::physical(table_id, None),
+            columns: column_pairs
+                .iter()
+                .map(|column_pair| Column::physical(column_pair.self_column_id, None))
+                .collect(),
+            predicate,
+            order_by: None,
+            offset: None,
+            limit: None,
+            group_by: None,
+            top_level_selection: false,
+            lock: None,
+        };
+
+        ConcretePredicate::In(
+            Column::ColumnArray(
+                column_pairs
+                    .iter()
+                    .map(|column_pair| Column::physical(column_pair.foreign_column_id, None))
+                    .collect(),
+            ),
+            Column::SubSelect(Box::new(linked_rows)),
+        )
+    })
+}
@@ -38,6 +38,7 @@ impl<'c> SelectionContext<'c> {
             .iter()
             .flat_map(|p| match p {
                 ColumnPath::Physical(links) => Some(links.clone()),
+                ColumnPath::JsonField(json_field) => Some(json_field.column.clone()),
                 _ => None,
             })
             .collect();
@@ -9,9 +9,10 @@
 
 use crate::{
     AbstractOrderBy, AbstractPredicate, Column, Database, Limit, Offset, PhysicalColumnPath,
-    RelationId, Selection, TableId,
+    RelationId, RowLock, Selection, TableId,
     sql::{
-        predicate::ConcretePredicate, schema_object::SchemaObjectName, select::Select, table::Table,
+        lock::LockClause, predicate::ConcretePredicate, schema_object::SchemaObjectName,
+        select::Select, table::Table,
     },
     transform::{
         join_util,
@@ -44,7 +45,7 @@ pub(crate) trait SelectionStrategy {
 }
 
 /// Compute an inner select that picks up all the columns from the given table, and applies the
-/// given clauses.
+/// given clauses (locking the selected rows of the table, if requested).
 #[allow(clippy::too_many_arguments)]
 pub(super) fn compute_inner_select(
     table: Table,
@@ -53,15 +54,16 @@ pub(super) fn compute_inner_select(
     order_by: &Option<AbstractOrderBy>,
     limit: &Option<Limit>,
     offset: &Option<Offset>,
+    lock: Option<RowLock>,
     selection_level: &SelectionLevel,
     transformer: &impl OrderByTransformer,
     database: &Database,
 ) -> Select {
+    let wildcard_table_name = &database.get_table(wildcard_table).name;
+
     Select {
         table,
-        columns: vec![Column::Star(Some(
-            database.get_table(wildcard_table).name.clone(),
-        ))],
+        columns: vec![Column::Star(Some(wildcard_table_name.clone()))],
         predicate,
         order_by: order_by
             .as_ref()
@@ -70,6 +72,10 @@ pub(super) fn compute_inner_select(
         limit: limit.clone(),
         group_by: None,
         top_level_selection: false,
+        lock: lock.map(|lock| LockClause {
+            lock,
+            table_name: wildcard_table_name.clone(),
+        }),
     }
 }
 
@@ -96,6 +102,7 @@ pub(super) fn nest_subselect(
         limit: None,
         group_by: None,
         top_level_selection: selection_level.is_top_level(),
+        lock: None,
     }
 }
 
@@ -152,6 +152,7 @@ impl SelectionStrategy for SubqueryWithInPredicateStrategy {
             &abstract_select.order_by,
             &abstract_select.limit,
             &abstract_select.offset,
+            abstract_select.lock,
             selection_level,
             transformer,
             database,
@@ -116,6 +116,7 @@ impl UpdateStrategy for MultiStatementStrategy {
                 limit: None,
                 group_by: None,
                 top_level_selection: false,
+                lock: None,
             })
         } else {
             let column_id_values: Vec<(ColumnId, MaybeOwned<'a, Column>)> = abstract_update
@@ -384,6 +385,7 @@ mod tests {
                         order_by: None,
                         offset: None,
                         limit: None,
+                        lock: None,
                     },
                     precheck_predicates: vec![],
                 };
@@ -440,6 +442,7 @@ mod tests {
                             order_by: None,
                             offset: None,
                             limit: None,
+                            lock: None,
                         },
                         nested_updates: vec![],
                         nested_inserts: vec![],
@@ -474,6 +477,7 @@ mod tests {
                         order_by: None,
                         offset: None,
                         limit: None,
+                        lock: None,
                     },
                     precheck_predicates: vec![],
                 };
@@ -13,4 +13,6 @@ export type PlaygroundConfig = {
 
   jwtSourceHeader?: string;
   jwtSourceCookie?: string;
+
+  introspectionToken?: string;
 };
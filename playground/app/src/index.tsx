@@ -18,8 +18,12 @@ import {
 import { PlaygroundConfig } from "./config";
 let playgroundConfig = (window as any).exoConfig as PlaygroundConfig;
 
+// Pass the introspection token (if the server requires one) so that the playground can fetch the schema
 const urlFetcher: Fetcher = createGraphiQLFetcher({
   url: playgroundConfig.graphqlHttpPath,
+  headers: playgroundConfig.introspectionToken
+    ? { _exo_introspection_token: playgroundConfig.introspectionToken }
+    : undefined,
 });
 
 const container = document.getElementById("root");